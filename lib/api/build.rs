@@ -144,6 +144,8 @@ fn configure_validation(builder: Builder) -> Builder {
             ("ChangeAliases.timeout", "range(min = 1)"),
             ("ListCollectionAliasesRequest.collection_name", "length(min = 1, max = 255), custom(function = \"common::validation::validate_collection_name_legacy\")"),
             ("HnswConfigDiff.ef_construct", "range(min = 4)"),
+            ("HnswConfigDiff.target_recall", "range(min = 0.0, max = 1.0)"),
+            ("HnswConfigDiff.m0", "range(min = 1)"),
             ("WalConfigDiff.wal_capacity_mb", "range(min = 1)"),
             ("WalConfigDiff.wal_retain_closed", "range(min = 1)"),
             ("OptimizersConfigDiff.deleted_threshold", "range(min = 0.0, max = 1.0)"),
@@ -161,6 +163,7 @@ fn configure_validation(builder: Builder) -> Builder {
             ("QuantizationConfig.quantization", ""),
             ("QuantizationConfigDiff.quantization", ""),
             ("ScalarQuantization.quantile", "range(min = 0.5, max = 1.0)"),
+            ("BinaryQuantization.oversampling", "range(min = 1.0)"),
             ("UpdateCollectionClusterSetupRequest.timeout", "range(min = 1)"),
             ("UpdateCollectionClusterSetupRequest.operation", ""),
             ("StrictModeConfig.max_query_limit", "range(min = 1)"),
@@ -180,7 +183,6 @@ fn configure_validation(builder: Builder) -> Builder {
             "CollectionClusterInfoRequest",
             "UpdateCollectionClusterSetupRequest",
             "ProductQuantization",
-            "BinaryQuantization",
             "Disabled",
             "QuantizationConfigDiff",
             "quantization_config_diff::Quantization",
@@ -20,7 +20,10 @@ use segment::data_types::{facets as segment_facets, vectors as segment_vectors};
 use segment::index::query_optimization::rescore_formula::parsed_formula::{
     DatetimeExpression, DecayKind, ParsedExpression, ParsedFormula,
 };
-use segment::types::{DateTimePayloadType, FloatPayloadType, default_quantization_ignore_value};
+use segment::index::sparse_index::sparse_index_config::MmapAdvice;
+use segment::types::{
+    DateTimePayloadType, FloatPayloadType, PopulatePolicy, default_quantization_ignore_value,
+};
 use segment::vector_storage::query::{self as segment_query, NaiveFeedbackCoefficients};
 use sparse::common::sparse_vector::validate_sparse_vector_impl;
 use tonic::Status;
@@ -184,6 +187,7 @@ impl From<segment::data_types::index::TokenizerType> for TokenizerType {
             segment::data_types::index::TokenizerType::Whitespace => TokenizerType::Whitespace,
             segment::data_types::index::TokenizerType::Multilingual => TokenizerType::Multilingual,
             segment::data_types::index::TokenizerType::Word => TokenizerType::Word,
+            segment::data_types::index::TokenizerType::Ngram => TokenizerType::Ngram,
         }
     }
 }
@@ -195,12 +199,18 @@ impl From<segment::data_types::index::KeywordIndexParams> for PayloadIndexParams
             is_tenant,
             on_disk,
             enable_hnsw,
+            null_index,
+            trigram_index,
+            composite_with,
         } = params;
         PayloadIndexParams {
             index_params: Some(IndexParams::KeywordIndexParams(KeywordIndexParams {
                 is_tenant,
                 on_disk,
                 enable_hnsw,
+                null_index,
+                trigram_index,
+                composite_with: composite_with.map(|key| key.to_string()),
             })),
         }
     }
@@ -215,6 +225,8 @@ impl From<segment::data_types::index::IntegerIndexParams> for PayloadIndexParams
             on_disk,
             is_principal,
             enable_hnsw,
+            null_index,
+            composite_with,
         } = params;
         PayloadIndexParams {
             index_params: Some(IndexParams::IntegerIndexParams(IntegerIndexParams {
@@ -223,6 +235,8 @@ impl From<segment::data_types::index::IntegerIndexParams> for PayloadIndexParams
                 is_principal,
                 on_disk,
                 enable_hnsw,
+                null_index,
+                composite_with: composite_with.map(|key| key.to_string()),
             })),
         }
     }
@@ -235,12 +249,14 @@ impl From<segment::data_types::index::FloatIndexParams> for PayloadIndexParams {
             on_disk,
             is_principal,
             enable_hnsw,
+            null_index,
         } = params;
         PayloadIndexParams {
             index_params: Some(IndexParams::FloatIndexParams(FloatIndexParams {
                 on_disk,
                 is_principal,
                 enable_hnsw,
+                null_index,
             })),
         }
     }
@@ -252,11 +268,13 @@ impl From<segment::data_types::index::GeoIndexParams> for PayloadIndexParams {
             r#type: _,
             on_disk,
             enable_hnsw,
+            null_index,
         } = params;
         PayloadIndexParams {
             index_params: Some(IndexParams::GeoIndexParams(GeoIndexParams {
                 on_disk,
                 enable_hnsw,
+                null_index,
             })),
         }
     }
@@ -271,11 +289,16 @@ impl From<segment::data_types::index::TextIndexParams> for PayloadIndexParams {
             max_token_len,
             lowercase,
             ascii_folding,
+            unicode_normalization,
+            case_folding,
             phrase_matching,
             on_disk,
+            on_disk_populate_postings,
+            lock_in_ram,
             stopwords,
             stemmer,
             enable_hnsw,
+            null_index,
         } = params;
         let tokenizer = TokenizerType::from(tokenizer);
 
@@ -296,6 +319,11 @@ impl From<segment::data_types::index::TextIndexParams> for PayloadIndexParams {
                 stopwords: stopwords_set,
                 stemmer: stemming_algo,
                 enable_hnsw,
+                null_index,
+                on_disk_populate_postings: on_disk_populate_postings.map(|x| x as u64),
+                lock_in_ram,
+                unicode_normalization,
+                case_folding,
             })),
         }
     }
@@ -307,11 +335,13 @@ impl From<segment::data_types::index::BoolIndexParams> for PayloadIndexParams {
             r#type: _,
             on_disk,
             enable_hnsw,
+            null_index,
         } = params;
         PayloadIndexParams {
             index_params: Some(IndexParams::BoolIndexParams(BoolIndexParams {
                 on_disk,
                 enable_hnsw,
+                null_index,
             })),
         }
     }
@@ -324,12 +354,14 @@ impl From<segment::data_types::index::UuidIndexParams> for PayloadIndexParams {
             is_tenant,
             on_disk,
             enable_hnsw,
+            null_index,
         } = params;
         PayloadIndexParams {
             index_params: Some(IndexParams::UuidIndexParams(UuidIndexParams {
                 is_tenant,
                 on_disk,
                 enable_hnsw,
+                null_index,
             })),
         }
     }
@@ -342,12 +374,14 @@ impl From<segment::data_types::index::DatetimeIndexParams> for PayloadIndexParam
             on_disk,
             is_principal,
             enable_hnsw,
+            null_index,
         } = params;
         PayloadIndexParams {
             index_params: Some(IndexParams::DatetimeIndexParams(DatetimeIndexParams {
                 on_disk,
                 is_principal,
                 enable_hnsw,
+                null_index,
             })),
         }
     }
@@ -359,11 +393,15 @@ impl From<segment::types::PayloadIndexInfo> for PayloadSchemaInfo {
             data_type,
             params,
             points,
+            ram_usage_bytes,
+            disk_usage_bytes,
         } = schema;
         PayloadSchemaInfo {
             data_type: PayloadSchemaType::from(data_type) as i32,
             params: params.map(|p| p.into()),
             points: Some(points as u64),
+            ram_usage_bytes: Some(ram_usage_bytes as u64),
+            disk_usage_bytes: Some(disk_usage_bytes as u64),
         }
     }
 }
@@ -458,6 +496,7 @@ impl TryFrom<TokenizerType> for segment::data_types::index::TokenizerType {
             }
             TokenizerType::Whitespace => Ok(segment::data_types::index::TokenizerType::Whitespace),
             TokenizerType::Word => Ok(segment::data_types::index::TokenizerType::Word),
+            TokenizerType::Ngram => Ok(segment::data_types::index::TokenizerType::Ngram),
         }
     }
 }
@@ -484,12 +523,21 @@ impl TryFrom<KeywordIndexParams> for segment::data_types::index::KeywordIndexPar
             is_tenant,
             on_disk,
             enable_hnsw,
+            null_index,
+            trigram_index,
+            composite_with,
         } = params;
         Ok(segment::data_types::index::KeywordIndexParams {
             r#type: KeywordIndexType::Keyword,
             is_tenant,
             on_disk,
             enable_hnsw,
+            null_index,
+            trigram_index,
+            composite_with: composite_with
+                .as_deref()
+                .map(json::json_path_from_proto)
+                .transpose()?,
         })
     }
 }
@@ -503,6 +551,8 @@ impl TryFrom<IntegerIndexParams> for segment::data_types::index::IntegerIndexPar
             is_principal,
             on_disk,
             enable_hnsw,
+            null_index,
+            composite_with,
         } = params;
         Ok(segment::data_types::index::IntegerIndexParams {
             r#type: IntegerIndexType::Integer,
@@ -511,6 +561,11 @@ impl TryFrom<IntegerIndexParams> for segment::data_types::index::IntegerIndexPar
             is_principal,
             on_disk,
             enable_hnsw,
+            null_index,
+            composite_with: composite_with
+                .as_deref()
+                .map(json::json_path_from_proto)
+                .transpose()?,
         })
     }
 }
@@ -522,12 +577,14 @@ impl TryFrom<FloatIndexParams> for segment::data_types::index::FloatIndexParams
             on_disk,
             is_principal,
             enable_hnsw,
+            null_index,
         } = params;
         Ok(segment::data_types::index::FloatIndexParams {
             r#type: FloatIndexType::Float,
             on_disk,
             is_principal,
             enable_hnsw,
+            null_index,
         })
     }
 }
@@ -538,11 +595,13 @@ impl TryFrom<GeoIndexParams> for segment::data_types::index::GeoIndexParams {
         let GeoIndexParams {
             on_disk,
             enable_hnsw,
+            null_index,
         } = params;
         Ok(segment::data_types::index::GeoIndexParams {
             r#type: GeoIndexType::Geo,
             on_disk,
             enable_hnsw,
+            null_index,
         })
     }
 }
@@ -594,6 +653,11 @@ impl TryFrom<TextIndexParams> for segment::data_types::index::TextIndexParams {
             stopwords,
             stemmer,
             enable_hnsw,
+            null_index,
+            on_disk_populate_postings,
+            lock_in_ram,
+            unicode_normalization,
+            case_folding,
         } = params;
 
         // Convert stopwords if present
@@ -617,13 +681,18 @@ impl TryFrom<TextIndexParams> for segment::data_types::index::TextIndexParams {
                 .unwrap_or_else(|_| Err(Status::invalid_argument("unknown tokenizer type")))?,
             lowercase,
             ascii_folding,
+            unicode_normalization,
+            case_folding,
             min_token_len: min_token_len.map(|x| x as usize),
             max_token_len: max_token_len.map(|x| x as usize),
             phrase_matching,
             on_disk,
+            on_disk_populate_postings: on_disk_populate_postings.map(|x| x as usize),
+            lock_in_ram,
             stopwords: stopwords_converted,
             stemmer,
             enable_hnsw,
+            null_index,
         })
     }
 }
@@ -654,11 +723,13 @@ impl TryFrom<BoolIndexParams> for segment::data_types::index::BoolIndexParams {
         let BoolIndexParams {
             on_disk,
             enable_hnsw,
+            null_index,
         } = params;
         Ok(segment::data_types::index::BoolIndexParams {
             r#type: BoolIndexType::Bool,
             on_disk,
             enable_hnsw,
+            null_index,
         })
     }
 }
@@ -670,12 +741,14 @@ impl TryFrom<DatetimeIndexParams> for segment::data_types::index::DatetimeIndexP
             on_disk,
             is_principal,
             enable_hnsw,
+            null_index,
         } = params;
         Ok(segment::data_types::index::DatetimeIndexParams {
             r#type: DatetimeIndexType::Datetime,
             on_disk,
             is_principal,
             enable_hnsw,
+            null_index,
         })
     }
 }
@@ -687,12 +760,14 @@ impl TryFrom<UuidIndexParams> for segment::data_types::index::UuidIndexParams {
             is_tenant,
             on_disk,
             enable_hnsw,
+            null_index,
         } = params;
         Ok(segment::data_types::index::UuidIndexParams {
             r#type: UuidIndexType::Uuid,
             is_tenant,
             on_disk,
             enable_hnsw,
+            null_index,
         })
     }
 }
@@ -738,6 +813,8 @@ impl TryFrom<PayloadSchemaInfo> for segment::types::PayloadIndexInfo {
             data_type,
             params,
             points,
+            ram_usage_bytes,
+            disk_usage_bytes,
         } = schema;
         let data_type = match PayloadSchemaType::try_from(data_type) {
             Err(_) => {
@@ -773,6 +850,8 @@ impl TryFrom<PayloadSchemaInfo> for segment::types::PayloadIndexInfo {
             data_type,
             params,
             points: points.unwrap_or(0) as usize,
+            ram_usage_bytes: ram_usage_bytes.unwrap_or(0) as usize,
+            disk_usage_bytes: disk_usage_bytes.unwrap_or(0) as usize,
         })
     }
 }
@@ -1304,12 +1383,14 @@ impl From<segment::types::BinaryQuantization> for BinaryQuantization {
             always_ram,
             encoding,
             query_encoding,
+            oversampling,
         } = binary;
         BinaryQuantization {
             always_ram,
             encoding: encoding
                 .map(|encoding| i32::from(BinaryQuantizationEncoding::from(encoding))),
             query_encoding: query_encoding.map(BinaryQuantizationQueryEncoding::from),
+            oversampling: oversampling.map(|OrderedFloat(oversampling)| oversampling),
         }
     }
 }
@@ -1322,6 +1403,7 @@ impl TryFrom<BinaryQuantization> for segment::types::BinaryQuantization {
             always_ram,
             encoding,
             query_encoding,
+            oversampling,
         } = value;
         let encoding = encoding
             .map(BinaryQuantizationEncoding::try_from)
@@ -1337,6 +1419,7 @@ impl TryFrom<BinaryQuantization> for segment::types::BinaryQuantization {
                     .map_err(|_| {
                         Status::invalid_argument("Unknown binary quantization query encoding")
                     })?,
+                oversampling: oversampling.map(OrderedFloat),
             },
         })
     }
@@ -2043,6 +2126,9 @@ impl TryFrom<Match> for segment::types::Match {
                 MatchValue::TextAny(text_any) => {
                     segment::types::Match::TextAny(segment::types::MatchTextAny { text_any })
                 }
+                MatchValue::TextFuzzy(text_fuzzy) => {
+                    segment::types::Match::TextFuzzy(segment::types::MatchTextFuzzy { text_fuzzy })
+                }
             }),
             _ => Err(Status::invalid_argument("Malformed Match condition")),
         }
@@ -2086,6 +2172,9 @@ impl From<segment::types::Match> for Match {
             segment::types::Match::TextAny(segment::types::MatchTextAny { text_any }) => {
                 MatchValue::TextAny(text_any)
             }
+            segment::types::Match::TextFuzzy(segment::types::MatchTextFuzzy { text_fuzzy }) => {
+                MatchValue::TextFuzzy(text_fuzzy)
+            }
         };
         Self {
             match_value: Some(match_value),
@@ -2271,6 +2360,9 @@ impl From<HnswConfigDiff> for segment::types::HnswConfig {
             on_disk,
             payload_m,
             inline_storage,
+            target_recall,
+            m0,
+            lock_in_ram,
         } = hnsw_config;
         Self {
             m: m.unwrap_or_default() as usize,
@@ -2280,6 +2372,9 @@ impl From<HnswConfigDiff> for segment::types::HnswConfig {
             on_disk,
             payload_m: payload_m.map(|x| x as usize),
             inline_storage,
+            target_recall: target_recall.map(OrderedFloat),
+            m0: m0.map(|x| x as usize),
+            lock_in_ram,
         }
     }
 }
@@ -3435,3 +3530,45 @@ impl From<Modifier> for grpc::Modifier {
         }
     }
 }
+
+impl From<grpc::MmapAdvice> for MmapAdvice {
+    fn from(value: grpc::MmapAdvice) -> Self {
+        match value {
+            grpc::MmapAdvice::Normal => MmapAdvice::Normal,
+            grpc::MmapAdvice::Random => MmapAdvice::Random,
+            grpc::MmapAdvice::Sequential => MmapAdvice::Sequential,
+            grpc::MmapAdvice::WillNeed => MmapAdvice::WillNeed,
+        }
+    }
+}
+
+impl From<MmapAdvice> for grpc::MmapAdvice {
+    fn from(value: MmapAdvice) -> Self {
+        match value {
+            MmapAdvice::Normal => grpc::MmapAdvice::Normal,
+            MmapAdvice::Random => grpc::MmapAdvice::Random,
+            MmapAdvice::Sequential => grpc::MmapAdvice::Sequential,
+            MmapAdvice::WillNeed => grpc::MmapAdvice::WillNeed,
+        }
+    }
+}
+
+impl From<grpc::PopulatePolicy> for PopulatePolicy {
+    fn from(value: grpc::PopulatePolicy) -> Self {
+        match value {
+            grpc::PopulatePolicy::Eager => PopulatePolicy::Eager,
+            grpc::PopulatePolicy::Lazy => PopulatePolicy::Lazy,
+            grpc::PopulatePolicy::Prioritized => PopulatePolicy::Prioritized,
+        }
+    }
+}
+
+impl From<PopulatePolicy> for grpc::PopulatePolicy {
+    fn from(value: PopulatePolicy) -> Self {
+        match value {
+            PopulatePolicy::Eager => grpc::PopulatePolicy::Eager,
+            PopulatePolicy::Lazy => grpc::PopulatePolicy::Lazy,
+            PopulatePolicy::Prioritized => grpc::PopulatePolicy::Prioritized,
+        }
+    }
+}
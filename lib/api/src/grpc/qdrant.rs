@@ -272,7 +272,7 @@ pub struct FieldCondition {
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct Match {
-    #[prost(oneof = "r#match::MatchValue", tags = "1, 2, 3, 4, 5, 6, 7, 8, 9, 10")]
+    #[prost(oneof = "r#match::MatchValue", tags = "1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11")]
     pub match_value: ::core::option::Option<r#match::MatchValue>,
 }
 /// Nested message and enum types in `Match`.
@@ -311,6 +311,9 @@ pub mod r#match {
         /// Match any word in the text
         #[prost(string, tag = "10")]
         TextAny(::prost::alloc::string::String),
+        /// Match all words in the text, tolerating typos
+        #[prost(string, tag = "11")]
+        TextFuzzy(::prost::alloc::string::String),
     }
 }
 #[derive(serde::Serialize)]
@@ -454,6 +457,17 @@ pub struct VectorParams {
     /// Configuration for multi-vector search
     #[prost(message, optional, tag = "7")]
     pub multivector_config: ::core::option::Option<MultiVectorConfig>,
+    /// If true - on-disk vectors are read with O_DIRECT in searches, bypassing the page cache.
+    /// Falls back to mmap if the filesystem doesn't support it.
+    #[prost(bool, optional, tag = "8")]
+    pub direct_io: ::core::option::Option<bool>,
+    /// If true - optimized on-disk vectors are kept compressed with zstd, and are decompressed on demand.
+    /// Frequently read vectors are cached decompressed in RAM, up to 64 MiB per vector storage.
+    #[prost(bool, optional, tag = "9")]
+    pub compressed: ::core::option::Option<bool>,
+    /// If true - recently accessed parts of optimized on-disk vectors are locked in RAM, the rest is read from disk.
+    #[prost(bool, optional, tag = "10")]
+    pub tiered: ::core::option::Option<bool>,
 }
 #[derive(validator::Validate)]
 #[derive(serde::Serialize)]
@@ -751,6 +765,20 @@ pub struct HnswConfigDiff {
     /// Requires quantized vectors to be enabled. Multi-vectors are not supported.
     #[prost(bool, optional, tag = "7")]
     pub inline_storage: ::core::option::Option<bool>,
+    /// Target recall of searches without explicit `hnsw_ef`. If set, `ef` of each HNSW index
+    /// is tuned by periodically comparing sampled searches with exact search.
+    #[prost(double, optional, tag = "8")]
+    #[validate(range(min = 0.0, max = 1.0))]
+    pub target_recall: ::core::option::Option<f64>,
+    /// Number of edges per node on level 0 of the index graph, which contains all points.
+    /// Upper levels use `m`. If not set, `2 * m` is used.
+    #[prost(uint64, optional, tag = "9")]
+    #[validate(range(min = 1))]
+    pub m0: ::core::option::Option<u64>,
+    /// Lock the HNSW graph links in RAM with `mlock`, so that they are not evicted from the
+    /// page cache under memory pressure. Requires a large enough `RLIMIT_MEMLOCK`.
+    #[prost(bool, optional, tag = "10")]
+    pub lock_in_ram: ::core::option::Option<bool>,
 }
 #[derive(serde::Serialize)]
 #[allow(clippy::derive_partial_eq_without_eq)]
@@ -766,6 +794,13 @@ pub struct SparseIndexConfig {
     /// Datatype used to store weights in the index.
     #[prost(enumeration = "Datatype", optional, tag = "3")]
     pub datatype: ::core::option::Option<i32>,
+    /// Keep only this many elements with the largest weights in each posting list.
+    /// Search becomes approximate, but index size and latency are bounded.
+    #[prost(uint64, optional, tag = "4")]
+    pub max_posting_length: ::core::option::Option<u64>,
+    /// Access pattern hint for memory mapped index files.
+    #[prost(enumeration = "MmapAdvice", optional, tag = "5")]
+    pub mmap_advice: ::core::option::Option<i32>,
 }
 #[derive(validator::Validate)]
 #[derive(serde::Serialize)]
@@ -784,6 +819,26 @@ pub struct WalConfigDiff {
     #[validate(range(min = 1))]
     pub wal_retain_closed: ::core::option::Option<u64>,
 }
+#[derive(serde::Serialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct MmapAdviceConfig {
+    /// Access pattern hint for vector storages
+    #[prost(enumeration = "MmapAdvice", optional, tag = "1")]
+    pub vectors: ::core::option::Option<i32>,
+    /// Access pattern hint for HNSW graph links
+    #[prost(enumeration = "MmapAdvice", optional, tag = "2")]
+    pub links: ::core::option::Option<i32>,
+    /// Access pattern hint for payload field indexes
+    #[prost(enumeration = "MmapAdvice", optional, tag = "3")]
+    pub payload_index: ::core::option::Option<i32>,
+    /// Access pattern hint for sparse vector indexes
+    #[prost(enumeration = "MmapAdvice", optional, tag = "4")]
+    pub sparse_index: ::core::option::Option<i32>,
+    /// Request transparent huge pages for vector storages and HNSW graph links
+    #[prost(bool, optional, tag = "5")]
+    pub hugepages: ::core::option::Option<bool>,
+}
 #[derive(validator::Validate)]
 #[derive(serde::Serialize)]
 #[allow(clippy::derive_partial_eq_without_eq)]
@@ -862,6 +917,10 @@ pub struct OptimizersConfigDiff {
     /// Default is disabled.
     #[prost(bool, optional, tag = "10")]
     pub prevent_unoptimized: ::core::option::Option<bool>,
+    /// Expected access pattern of memory mapped files per segment component.
+    /// Components without a value use the global advice.
+    #[prost(message, optional, tag = "11")]
+    pub mmap_advice: ::core::option::Option<MmapAdviceConfig>,
 }
 #[derive(validator::Validate)]
 #[derive(serde::Serialize)]
@@ -967,6 +1026,11 @@ pub struct BinaryQuantization {
     /// It can increase the accuracy of search at the cost of performance.
     #[prost(message, optional, tag = "3")]
     pub query_encoding: ::core::option::Option<BinaryQuantizationQueryEncoding>,
+    /// Oversampling of searches, which don't specify it in their quantization
+    /// params. If not set, no oversampling.
+    #[prost(double, optional, tag = "4")]
+    #[validate(range(min = 1.0))]
+    pub oversampling: ::core::option::Option<f64>,
 }
 #[derive(validator::Validate)]
 #[derive(serde::Serialize)]
@@ -1314,6 +1378,9 @@ pub struct CollectionParams {
     /// Define number of milliseconds to wait before attempting to read from another replica.
     #[prost(uint64, optional, tag = "11")]
     pub read_fan_out_delay_ms: ::core::option::Option<u64>,
+    /// When memory mapped files of segments are read into the page cache on load
+    #[prost(enumeration = "PopulatePolicy", optional, tag = "12")]
+    pub populate_policy: ::core::option::Option<i32>,
 }
 #[derive(validator::Validate)]
 #[derive(serde::Serialize)]
@@ -1337,6 +1404,9 @@ pub struct CollectionParamsDiff {
     /// Define number of milliseconds to wait before attempting to read from another replica.
     #[prost(uint64, optional, tag = "5")]
     pub read_fan_out_delay_ms: ::core::option::Option<u64>,
+    /// When memory mapped files of segments are read into the page cache on load
+    #[prost(enumeration = "PopulatePolicy", optional, tag = "6")]
+    pub populate_policy: ::core::option::Option<i32>,
 }
 #[derive(serde::Serialize)]
 #[allow(clippy::derive_partial_eq_without_eq)]
@@ -1379,6 +1449,19 @@ pub struct KeywordIndexParams {
     /// Default: true.
     #[prost(bool, optional, tag = "3")]
     pub enable_hnsw: ::core::option::Option<bool>,
+    /// If true, maintain a null index for this payload field, so that `is_empty` and `is_null`
+    /// conditions are answered from bitmaps instead of reading payloads. Default: true.
+    #[prost(bool, optional, tag = "4")]
+    pub null_index: ::core::option::Option<bool>,
+    /// If true, maintain a trigram index for this payload field, so that candidates of substring
+    /// matches with `text` are pre-filtered by trigrams instead of reading all payloads. Default: false.
+    #[prost(bool, optional, tag = "5")]
+    pub trigram_index: ::core::option::Option<bool>,
+    /// If set, maintain a composite index of this field and the given number or datetime field, so
+    /// that filters matching a value of this field and a range of the other one are served by a
+    /// single lookup instead of intersecting both indexes.
+    #[prost(string, optional, tag = "6")]
+    pub composite_with: ::core::option::Option<::prost::alloc::string::String>,
 }
 #[derive(serde::Serialize)]
 #[allow(clippy::derive_partial_eq_without_eq)]
@@ -1403,6 +1486,15 @@ pub struct IntegerIndexParams {
     /// Default: true.
     #[prost(bool, optional, tag = "5")]
     pub enable_hnsw: ::core::option::Option<bool>,
+    /// If true, maintain a null index for this payload field, so that `is_empty` and `is_null`
+    /// conditions are answered from bitmaps instead of reading payloads. Default: true.
+    #[prost(bool, optional, tag = "6")]
+    pub null_index: ::core::option::Option<bool>,
+    /// If set, maintain a composite index of this field and the given number or datetime field, so
+    /// that filters matching a value of this field and a range of the other one are served by a
+    /// single lookup instead of intersecting both indexes.
+    #[prost(string, optional, tag = "7")]
+    pub composite_with: ::core::option::Option<::prost::alloc::string::String>,
 }
 #[derive(serde::Serialize)]
 #[allow(clippy::derive_partial_eq_without_eq)]
@@ -1420,6 +1512,10 @@ pub struct FloatIndexParams {
     /// Default: true.
     #[prost(bool, optional, tag = "3")]
     pub enable_hnsw: ::core::option::Option<bool>,
+    /// If true, maintain a null index for this payload field, so that `is_empty` and `is_null`
+    /// conditions are answered from bitmaps instead of reading payloads. Default: true.
+    #[prost(bool, optional, tag = "4")]
+    pub null_index: ::core::option::Option<bool>,
 }
 #[derive(serde::Serialize)]
 #[allow(clippy::derive_partial_eq_without_eq)]
@@ -1433,6 +1529,10 @@ pub struct GeoIndexParams {
     /// Default: true.
     #[prost(bool, optional, tag = "2")]
     pub enable_hnsw: ::core::option::Option<bool>,
+    /// If true, maintain a null index for this payload field, so that `is_empty` and `is_null`
+    /// conditions are answered from bitmaps instead of reading payloads. Default: true.
+    #[prost(bool, optional, tag = "3")]
+    pub null_index: ::core::option::Option<bool>,
 }
 #[derive(serde::Serialize)]
 #[allow(clippy::derive_partial_eq_without_eq)]
@@ -1482,6 +1582,26 @@ pub struct TextIndexParams {
     /// Default: true.
     #[prost(bool, optional, tag = "10")]
     pub enable_hnsw: ::core::option::Option<bool>,
+    /// For on-disk index: number of the longest posting lists to load into memory when the index is opened, along with the vocabulary.
+    /// Default: nothing is loaded in advance.
+    #[prost(uint64, optional, tag = "11")]
+    pub on_disk_populate_postings: ::core::option::Option<u64>,
+    /// If true, apply Unicode NFKC normalization to tokens (e.g., full-width "ＡＢＣ" -> "ABC").
+    /// Default: false.
+    #[prost(bool, optional, tag = "12")]
+    pub unicode_normalization: ::core::option::Option<bool>,
+    /// If true, apply full Unicode case folding to tokens instead of lowercasing (e.g., "Straße" -> "strasse").
+    /// Default: false.
+    #[prost(bool, optional, tag = "13")]
+    pub case_folding: ::core::option::Option<bool>,
+    /// If true, lock the vocabulary in RAM with `mlock`, so that it is not evicted from the page cache
+    /// under memory pressure. Requires a large enough `RLIMIT_MEMLOCK`. Default: false.
+    #[prost(bool, optional, tag = "14")]
+    pub lock_in_ram: ::core::option::Option<bool>,
+    /// If true, maintain a null index for this payload field, so that `is_empty` and `is_null`
+    /// conditions are answered from bitmaps instead of reading payloads. Default: true.
+    #[prost(bool, optional, tag = "15")]
+    pub null_index: ::core::option::Option<bool>,
 }
 #[derive(serde::Serialize)]
 #[allow(clippy::derive_partial_eq_without_eq)]
@@ -1521,6 +1641,10 @@ pub struct BoolIndexParams {
     /// Default: true.
     #[prost(bool, optional, tag = "2")]
     pub enable_hnsw: ::core::option::Option<bool>,
+    /// If true, maintain a null index for this payload field, so that `is_empty` and `is_null`
+    /// conditions are answered from bitmaps instead of reading payloads. Default: true.
+    #[prost(bool, optional, tag = "3")]
+    pub null_index: ::core::option::Option<bool>,
 }
 #[derive(serde::Serialize)]
 #[allow(clippy::derive_partial_eq_without_eq)]
@@ -1538,6 +1662,10 @@ pub struct DatetimeIndexParams {
     /// Default: true.
     #[prost(bool, optional, tag = "3")]
     pub enable_hnsw: ::core::option::Option<bool>,
+    /// If true, maintain a null index for this payload field, so that `is_empty` and `is_null`
+    /// conditions are answered from bitmaps instead of reading payloads. Default: true.
+    #[prost(bool, optional, tag = "4")]
+    pub null_index: ::core::option::Option<bool>,
 }
 #[derive(serde::Serialize)]
 #[allow(clippy::derive_partial_eq_without_eq)]
@@ -1554,6 +1682,10 @@ pub struct UuidIndexParams {
     /// Default: true.
     #[prost(bool, optional, tag = "3")]
     pub enable_hnsw: ::core::option::Option<bool>,
+    /// If true, maintain a null index for this payload field, so that `is_empty` and `is_null`
+    /// conditions are answered from bitmaps instead of reading payloads. Default: true.
+    #[prost(bool, optional, tag = "4")]
+    pub null_index: ::core::option::Option<bool>,
 }
 #[derive(validator::Validate)]
 #[derive(serde::Serialize)]
@@ -1612,6 +1744,12 @@ pub struct PayloadSchemaInfo {
     /// Number of points indexed within this field
     #[prost(uint64, optional, tag = "3")]
     pub points: ::core::option::Option<u64>,
+    /// Expected RAM usage of the index in bytes, once it is loaded
+    #[prost(uint64, optional, tag = "4")]
+    pub ram_usage_bytes: ::core::option::Option<u64>,
+    /// Size of the index files on disk in bytes
+    #[prost(uint64, optional, tag = "5")]
+    pub disk_usage_bytes: ::core::option::Option<u64>,
 }
 #[derive(serde::Serialize)]
 #[allow(clippy::derive_partial_eq_without_eq)]
@@ -2123,6 +2261,7 @@ pub enum Datatype {
     Float32 = 1,
     Uint8 = 2,
     Float16 = 3,
+    Bfloat16 = 4,
 }
 impl Datatype {
     /// String value of the enum field names used in the ProtoBuf definition.
@@ -2135,6 +2274,7 @@ impl Datatype {
             Datatype::Float32 => "Float32",
             Datatype::Uint8 => "Uint8",
             Datatype::Float16 => "Float16",
+            Datatype::Bfloat16 => "Bfloat16",
         }
     }
     /// Creates an enum from field names used in the ProtoBuf definition.
@@ -2144,6 +2284,7 @@ impl Datatype {
             "Float32" => Some(Self::Float32),
             "Uint8" => Some(Self::Uint8),
             "Float16" => Some(Self::Float16),
+            "Bfloat16" => Some(Self::Bfloat16),
             _ => None,
         }
     }
@@ -2179,6 +2320,43 @@ impl Modifier {
 #[derive(serde::Serialize)]
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
 #[repr(i32)]
+pub enum MmapAdvice {
+    /// No special treatment
+    Normal = 0,
+    /// Pages are accessed in random order, read-ahead is disabled
+    Random = 1,
+    /// Pages are accessed sequentially, aggressive read-ahead is used
+    Sequential = 2,
+    /// Pages will be accessed soon, they are read ahead in the background
+    WillNeed = 3,
+}
+impl MmapAdvice {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            MmapAdvice::Normal => "MmapAdviceNormal",
+            MmapAdvice::Random => "MmapAdviceRandom",
+            MmapAdvice::Sequential => "MmapAdviceSequential",
+            MmapAdvice::WillNeed => "MmapAdviceWillNeed",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "MmapAdviceNormal" => Some(Self::Normal),
+            "MmapAdviceRandom" => Some(Self::Random),
+            "MmapAdviceSequential" => Some(Self::Sequential),
+            "MmapAdviceWillNeed" => Some(Self::WillNeed),
+            _ => None,
+        }
+    }
+}
+#[derive(serde::Serialize)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
 pub enum MultiVectorComparator {
     MaxSim = 0,
 }
@@ -2449,12 +2627,46 @@ impl ShardingMethod {
 #[derive(serde::Serialize)]
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
 #[repr(i32)]
+pub enum PopulatePolicy {
+    /// Read vectors, HNSW graph links and payload indexes into the page cache on load
+    Eager = 0,
+    /// Don't read anything ahead, pages are loaded from disk by the first searches
+    Lazy = 1,
+    /// Same as eager, but HNSW graph links are populated with latency-critical parts first
+    Prioritized = 2,
+}
+impl PopulatePolicy {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            PopulatePolicy::Eager => "PopulatePolicyEager",
+            PopulatePolicy::Lazy => "PopulatePolicyLazy",
+            PopulatePolicy::Prioritized => "PopulatePolicyPrioritized",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "PopulatePolicyEager" => Some(Self::Eager),
+            "PopulatePolicyLazy" => Some(Self::Lazy),
+            "PopulatePolicyPrioritized" => Some(Self::Prioritized),
+            _ => None,
+        }
+    }
+}
+#[derive(serde::Serialize)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
 pub enum TokenizerType {
     Unknown = 0,
     Prefix = 1,
     Whitespace = 2,
     Word = 3,
     Multilingual = 4,
+    Ngram = 5,
 }
 impl TokenizerType {
     /// String value of the enum field names used in the ProtoBuf definition.
@@ -2468,6 +2680,7 @@ impl TokenizerType {
             TokenizerType::Whitespace => "Whitespace",
             TokenizerType::Word => "Word",
             TokenizerType::Multilingual => "Multilingual",
+            TokenizerType::Ngram => "Ngram",
         }
     }
     /// Creates an enum from field names used in the ProtoBuf definition.
@@ -2478,6 +2691,7 @@ impl TokenizerType {
             "Whitespace" => Some(Self::Whitespace),
             "Word" => Some(Self::Word),
             "Multilingual" => Some(Self::Multilingual),
+            "Ngram" => Some(Self::Ngram),
             _ => None,
         }
     }
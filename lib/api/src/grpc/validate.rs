@@ -507,6 +507,8 @@ impl Validate for super::qdrant::IntegerIndexParams {
             is_principal: _,
             on_disk: _,
             enable_hnsw: _,
+            null_index: _,
+            composite_with: _,
         } = &self;
         validate_integer_index_params(lookup, range)
     }
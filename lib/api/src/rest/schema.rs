@@ -1352,6 +1352,7 @@ pub struct SearchMatrixPairsResponse {
 #[derive(Debug, JsonSchema, Serialize, Deserialize, Validate)]
 pub struct FacetRequestInternal {
     /// Payload key to use for faceting.
+    /// For fields with full-text index, values are tokens of the indexed text.
     pub key: JsonPath,
 
     /// Max number of hits to return. Default is 10.
@@ -64,6 +64,7 @@ fn setup() -> (TempDir, LocalShard, Runtime) {
             flush_interval_sec: 30,
             max_optimization_threads: Some(2),
             prevent_unoptimized: None,
+            mmap_advice: None,
         },
         wal_config,
         hnsw_config: Default::default(),
@@ -82,6 +82,7 @@ fn batch_search_bench(c: &mut Criterion) {
             flush_interval_sec: 30,
             max_optimization_threads: Some(2),
             prevent_unoptimized: None,
+            mmap_advice: None,
         },
         wal_config,
         hnsw_config: Default::default(),
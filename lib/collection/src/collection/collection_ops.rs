@@ -362,7 +362,7 @@ impl Collection {
             for (key, response_schema) in payload_schema {
                 info.payload_schema
                     .entry(key)
-                    .and_modify(|info_schema| info_schema.points += response_schema.points)
+                    .and_modify(|info_schema| info_schema.merge(&response_schema))
                     .or_insert(response_schema);
             }
         }
@@ -91,6 +91,8 @@ pub struct Collection {
     collection_stats_cache: CollectionSizeStatsCache,
     // Background tasks to clean shards
     shard_clean_tasks: ShardCleanTasks,
+    // Taken for read by all updates, and for write to quiesce updates of the collection
+    updates_barrier: Arc<RwLock<()>>,
 }
 
 pub type RequestShardTransfer = Arc<dyn Fn(ShardTransfer) + Send + Sync>;
@@ -198,6 +200,7 @@ impl Collection {
             optimizer_resource_budget,
             collection_stats_cache,
             shard_clean_tasks: Default::default(),
+            updates_barrier: Default::default(),
         })
     }
 
@@ -315,6 +318,7 @@ impl Collection {
             optimizer_resource_budget,
             collection_stats_cache,
             shard_clean_tasks: Default::default(),
+            updates_barrier: Default::default(),
         }
     }
 
@@ -917,7 +921,7 @@ impl Collection {
     }
 }
 
-struct CollectionVersion;
+pub(crate) struct CollectionVersion;
 
 impl StorageVersion for CollectionVersion {
     fn current_raw() -> &'static str {
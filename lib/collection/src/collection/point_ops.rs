@@ -33,11 +33,14 @@ impl Collection {
         wait: bool,
         hw_measurement_acc: HwMeasurementAcc,
     ) -> CollectionResult<Option<UpdateResult>> {
+        let updates_barrier = self.updates_barrier.clone().read_owned().await;
         let shard_holder = self.shards_holder.clone().read_owned().await;
 
         let results = self
             .update_runtime
             .spawn(async move {
+                let _updates_barrier = updates_barrier;
+
                 // `ShardReplicaSet::update_local` is *not* cancel safe, so we *have to* execute *all*
                 // `update_local` requests to completion.
                 //
@@ -97,9 +100,12 @@ impl Collection {
         ordering: WriteOrdering,
         hw_measurement_acc: HwMeasurementAcc,
     ) -> CollectionResult<UpdateResult> {
+        let updates_barrier = self.updates_barrier.clone().read_owned().await;
         let shard_holder = self.shards_holder.clone().read_owned().await;
 
         let result = self.update_runtime.spawn(async move {
+            let _updates_barrier = updates_barrier;
+
             let Some(shard) = shard_holder.get_shard(shard_selection) else {
                 return Ok(None);
             };
@@ -147,12 +153,15 @@ impl Collection {
         shard_keys_selection: Option<ShardKey>,
         hw_measurement_acc: HwMeasurementAcc,
     ) -> CollectionResult<UpdateResult> {
+        let updates_barrier = self.updates_barrier.clone().read_owned().await;
         let shard_holder = self.shards_holder.clone().read_owned().await;
         let start_time = std::time::Instant::now();
 
         let results = self
             .update_runtime
             .spawn(async move {
+                let _updates_barrier = updates_barrier;
+
                 let updates = FuturesUnordered::new();
                 let operations = shard_holder.split_by_shard(operation, &shard_keys_selection)?;
 
@@ -1,20 +1,30 @@
 use std::collections::HashSet;
+use std::io::Write;
 use std::path::Path;
 
 use common::fs::read_json;
+use common::io_throttle::Throttled;
 use common::storage_version::StorageVersion as _;
 use common::tar_ext::BuilderExt;
-use common::tar_unpack::tar_unpack_file;
+use common::tar_unpack::tar_unpack_file_filtered;
 use fs_err::File;
+use futures::TryStreamExt as _;
+use segment::common::BYTES_IN_MB;
 use segment::types::SnapshotFormat;
 use segment::utils::fs::move_all;
 use shard::snapshots::snapshot_data::SnapshotData;
 use shard::snapshots::snapshot_manifest::{RecoveryType, SnapshotManifest};
 use tokio::sync::OwnedRwLockReadGuard;
+use tokio_util::codec::{BytesCodec, FramedRead};
+use tokio_util::io::SyncIoBridge;
 
 use super::Collection;
 use crate::collection::CollectionVersion;
 use crate::collection::payload_index_schema::PAYLOAD_INDEX_CONFIG_FILE;
+use crate::common::snapshot_metadata::{
+    SNAPSHOT_METADATA_FILE, SnapshotCompatibilityPolicy, SnapshotMetadata,
+};
+use crate::common::snapshot_point::SnapshotPointGuard;
 use crate::common::snapshot_stream::SnapshotStream;
 use crate::common::snapshots_manager::SnapshotStorageManager;
 use crate::config::{COLLECTION_CONFIG_FILE, CollectionConfigInternal, ShardingMethod};
@@ -46,6 +56,8 @@ impl Collection {
     /// 2. Archive the temporary directory into a single file.
     /// 3. Move the archive to the final location.
     ///
+    /// With object storage, shard snapshots are archived directly into a multipart upload instead.
+    ///
     /// # Arguments
     ///
     /// * `global_temp_dir`: directory used to host snapshots while they are being created
@@ -56,6 +68,24 @@ impl Collection {
         &self,
         global_temp_dir: &Path,
         this_peer_id: PeerId,
+    ) -> CollectionResult<SnapshotDescription> {
+        self.create_snapshot_at_point(global_temp_dir, this_peer_id, None)
+            .await
+    }
+
+    /// Same as [`Collection::create_snapshot`], but `snapshot_point` is dropped as soon as all
+    /// local shards have taken their snapshot point, see [`SnapshotPoint`].
+    ///
+    /// With a snapshot point, shards are snapshotted concurrently, so that all of them reach it at
+    /// once. WAL is not included, as it would contain updates accepted after the snapshot point.
+    /// All updates accepted before it must be applied to segments already.
+    ///
+    /// [`SnapshotPoint`]: crate::common::snapshot_point::SnapshotPoint
+    pub async fn create_snapshot_at_point(
+        &self,
+        global_temp_dir: &Path,
+        this_peer_id: PeerId,
+        snapshot_point: Option<SnapshotPointGuard>,
     ) -> CollectionResult<SnapshotDescription> {
         let snapshot_name = format!(
             "{}-{this_peer_id}-{}.snapshot",
@@ -67,6 +97,38 @@ impl Collection {
         let snapshot_path = self.snapshots_path.join(&snapshot_name);
         log::info!("Creating collection snapshot {snapshot_name} into {snapshot_path:?}");
 
+        let snapshot_manager = self.get_snapshots_storage_manager()?;
+
+        // Stream snapshot directly into object storage
+        if let Some(cloud_storage) = snapshot_manager.cloud_storage() {
+            let (read_half, write_half) = tokio::io::duplex(BYTES_IN_MB);
+
+            let tar = self.streaming_snapshot_archive(SyncIoBridge::new(write_half))?;
+
+            let snapshot_creator = self
+                .snapshot_creator(
+                    global_temp_dir,
+                    &snapshot_name,
+                    &tar,
+                    SnapshotFormat::Streamable,
+                    snapshot_point,
+                )
+                .await?;
+
+            let archive = async move {
+                snapshot_creator.await?;
+                tar.finish().await?;
+                CollectionResult::Ok(())
+            };
+
+            // If archiving fails, the upload is dropped, which aborts it
+            let (_, snapshot_description) = tokio::try_join!(
+                archive,
+                cloud_storage.store_stream(read_half, &snapshot_path),
+            )?;
+            return Ok(snapshot_description);
+        }
+
         // Dedicated temporary file for archiving this snapshot (deleted on drop)
         let snapshot_temp_arc_file = tempfile::Builder::new()
             .prefix(&format!("{snapshot_name}-arc-"))
@@ -79,79 +141,30 @@ impl Collection {
                 ))
             })?;
 
-        let tar = BuilderExt::new_seekable_owned(File::create(snapshot_temp_arc_file.path())?);
-
-        // Create snapshot of each shard
-        {
-            let snapshot_temp_temp_dir = tempfile::Builder::new()
-                .prefix(&format!("{snapshot_name}-temp-"))
-                .tempdir_in(global_temp_dir)
-                .map_err(|err| {
-                    CollectionError::service_error(format!(
-                        "failed to create temporary snapshot directory {}/{snapshot_name}-temp-XXXX: \
-                         {err}",
-                        global_temp_dir.display(),
-                    ))
-                })?;
-
-            let mut futures = Vec::new();
-            {
-                let shards_holder = self.shards_holder.read().await;
-
-                // Create snapshot of each shard
-                for (shard_id, replica_set) in shards_holder.get_shards() {
-                    let shard_snapshot_path = shard_path(Path::new(""), shard_id);
-
-                    // If node is listener, we can save whatever currently is in the storage
-                    let save_wal = self.shared_storage_config.node_type != NodeType::Listener;
-                    let future = replica_set
-                        .create_snapshot(
-                            snapshot_temp_temp_dir.path(),
-                            tar.descend(&shard_snapshot_path)?,
-                            SnapshotFormat::Regular,
-                            None,
-                            save_wal,
-                        )
-                        .await?;
-                    futures.push(future);
-                }
-            }
-
-            for future in futures {
-                future.await.map_err(|err| {
-                    CollectionError::service_error(format!("failed to create snapshot: {err}"))
-                })?;
-            }
-        }
-
-        // Save collection config and version
-        tar.append_data(
-            CollectionVersion::current_raw().as_bytes().to_vec(),
-            Path::new(common::storage_version::VERSION_FILE),
-        )
-        .await?;
+        let snapshot_temp_arc = File::create(snapshot_temp_arc_file.path())?;
+        let (tar, format) = if self.snapshot_compression_level().is_some() {
+            // Compressed archive is not seekable, so segments are archived as directories
+            let tar = self.streaming_snapshot_archive(snapshot_temp_arc)?;
+            (tar, SnapshotFormat::Streamable)
+        } else {
+            let tar = BuilderExt::new_seekable_owned(Throttled(snapshot_temp_arc));
+            (tar, SnapshotFormat::Regular)
+        };
 
-        tar.append_data(
-            self.collection_config.read().await.to_bytes()?,
-            Path::new(COLLECTION_CONFIG_FILE),
+        self.snapshot_creator(
+            global_temp_dir,
+            &snapshot_name,
+            &tar,
+            format,
+            snapshot_point,
         )
+        .await?
         .await?;
 
-        self.shards_holder
-            .read()
-            .await
-            .save_key_mapping_to_tar(&tar)
-            .await?;
-
-        self.payload_index_schema
-            .save_to_tar(&tar, Path::new(PAYLOAD_INDEX_CONFIG_FILE))
-            .await?;
-
         tar.finish().await.map_err(|err| {
             CollectionError::service_error(format!("failed to create snapshot archive: {err}"))
         })?;
 
-        let snapshot_manager = self.get_snapshots_storage_manager()?;
         snapshot_manager
             .store_file(snapshot_temp_arc_file.path(), snapshot_path.as_path())
             .await
@@ -163,23 +176,237 @@ impl Collection {
             })
     }
 
+    /// Stream a snapshot of the collection, without storing it.
+    ///
+    /// Segments are archived directly into the stream, so no temporary archive is created.
+    ///
+    /// # Cancel safety
+    ///
+    /// This method is cancel safe.
+    pub async fn stream_snapshot(
+        &self,
+        global_temp_dir: &Path,
+        this_peer_id: PeerId,
+    ) -> CollectionResult<SnapshotStream> {
+        let snapshot_name = format!(
+            "{}-{this_peer_id}-{}.snapshot",
+            self.name(),
+            chrono::Utc::now().format("%Y-%m-%d-%H-%M-%S"),
+        );
+        log::info!("Streaming collection snapshot {snapshot_name}");
+
+        let (read_half, write_half) = tokio::io::duplex(4096);
+
+        let tar = self.streaming_snapshot_archive(SyncIoBridge::new(write_half))?;
+
+        let snapshot_creator = self
+            .snapshot_creator(
+                global_temp_dir,
+                &snapshot_name,
+                &tar,
+                SnapshotFormat::Streamable,
+                None,
+            )
+            .await?;
+
+        let future = async move {
+            snapshot_creator.await?;
+            tar.finish().await?;
+            CollectionResult::Ok(())
+        };
+
+        tokio::spawn(async move {
+            if let Err(err) = future.await {
+                log::error!("Failed to stream collection snapshot: {err}");
+            }
+        });
+
+        Ok(SnapshotStream::new_stream(
+            FramedRead::new(read_half, BytesCodec::new()).map_ok(|bytes| bytes.freeze()),
+            Some(snapshot_name),
+        ))
+    }
+
+    fn snapshot_compression_level(&self) -> Option<i32> {
+        self.shared_storage_config
+            .snapshots_config
+            .compression_level
+    }
+
+    /// Streaming builder of a snapshot archive, compressed with zstd if configured.
+    ///
+    /// Written bytes are accounted with the global IO throttle.
+    fn streaming_snapshot_archive(
+        &self,
+        output: impl Write + Send + 'static,
+    ) -> CollectionResult<BuilderExt> {
+        let output = Throttled(output);
+        let tar = match self.snapshot_compression_level() {
+            Some(level) => BuilderExt::new_zstd_owned(output, level)?,
+            None => BuilderExt::new_streaming_owned(output),
+        };
+        Ok(tar)
+    }
+
+    /// Prepare a future, which archives snapshots of all shards, collection config and
+    /// metadata into `tar`.
+    ///
+    /// The future doesn't hold any collection locks while shards are being archived.
+    ///
+    /// See [`Collection::create_snapshot_at_point`] for `snapshot_point`.
+    async fn snapshot_creator(
+        &self,
+        global_temp_dir: &Path,
+        snapshot_name: &str,
+        tar: &BuilderExt,
+        format: SnapshotFormat,
+        snapshot_point: Option<SnapshotPointGuard>,
+    ) -> CollectionResult<impl Future<Output = CollectionResult<()>> + use<>> {
+        // Directory for intermediary files of shard snapshots (deleted on drop)
+        let snapshot_temp_temp_dir = tempfile::Builder::new()
+            .prefix(&format!("{snapshot_name}-temp-"))
+            .tempdir_in(global_temp_dir)
+            .map_err(|err| {
+                CollectionError::service_error(format!(
+                    "failed to create temporary snapshot directory {}/{snapshot_name}-temp-XXXX: \
+                     {err}",
+                    global_temp_dir.display(),
+                ))
+            })?;
+
+        let mut futures = Vec::new();
+        {
+            let shards_holder = self.shards_holder.read().await;
+
+            // Create snapshot of each shard
+            for (shard_id, replica_set) in shards_holder.get_shards() {
+                let shard_snapshot_path = shard_path(Path::new(""), shard_id);
+
+                // If node is listener, we can save whatever currently is in the storage
+                let save_wal = self.shared_storage_config.node_type != NodeType::Listener
+                    && snapshot_point.is_none();
+                let future = replica_set
+                    .create_snapshot(
+                        snapshot_temp_temp_dir.path(),
+                        tar.descend(&shard_snapshot_path)?,
+                        format,
+                        None,
+                        save_wal,
+                        snapshot_point.clone(),
+                    )
+                    .await?;
+                futures.push(future);
+            }
+        }
+
+        let concurrent_shards = snapshot_point.is_some();
+        drop(snapshot_point);
+
+        let tar = tar.clone();
+        let shards_holder = self.shards_holder.clone();
+        let collection_config = self.collection_config.clone();
+        let payload_index_schema = self.payload_index_schema.clone();
+
+        let future = async move {
+            let shard_snapshots = futures.into_iter().map(|shard_future| async move {
+                shard_future.await.map_err(|err| {
+                    CollectionError::service_error(format!("failed to create snapshot: {err}"))
+                })
+            });
+            if concurrent_shards {
+                // All shards have to reach the snapshot point at once
+                futures::future::try_join_all(shard_snapshots).await?;
+            } else {
+                for shard_snapshot in shard_snapshots {
+                    shard_snapshot.await?;
+                }
+            }
+            drop(snapshot_temp_temp_dir);
+
+            // Save collection config and version
+            tar.append_data(
+                CollectionVersion::current_raw().as_bytes().to_vec(),
+                Path::new(common::storage_version::VERSION_FILE),
+            )
+            .await?;
+
+            tar.append_data(
+                SnapshotMetadata::current().to_bytes()?,
+                Path::new(SNAPSHOT_METADATA_FILE),
+            )
+            .await?;
+
+            tar.append_data(
+                collection_config.read().await.to_bytes()?,
+                Path::new(COLLECTION_CONFIG_FILE),
+            )
+            .await?;
+
+            shards_holder
+                .read()
+                .await
+                .save_key_mapping_to_tar(&tar)
+                .await?;
+
+            payload_index_schema
+                .save_to_tar(&tar, Path::new(PAYLOAD_INDEX_CONFIG_FILE))
+                .await?;
+
+            Ok(())
+        };
+
+        Ok(future)
+    }
+
     /// Restore collection from snapshot
     ///
+    /// Snapshot metadata is checked against `compatibility_policy` before the snapshot is unpacked.
+    /// If `shards` is given, only these shards are unpacked and restored.
+    ///
     /// This method performs blocking IO.
     pub fn restore_snapshot(
         snapshot_data: SnapshotData,
         target_dir: &Path,
         this_peer_id: PeerId,
         is_distributed: bool,
+        compatibility_policy: SnapshotCompatibilityPolicy,
+        shards: Option<&HashSet<ShardId>>,
     ) -> CollectionResult<()> {
+        let metadata = match &snapshot_data {
+            SnapshotData::Packed(snapshot_path) => {
+                SnapshotMetadata::load_from_archive(snapshot_path)?
+            }
+            SnapshotData::Unpacked(snapshot_dir) => SnapshotMetadata::load(snapshot_dir.path())?,
+        };
+        SnapshotMetadata::check_compatibility(metadata.as_ref(), compatibility_policy)?;
+
+        // Shard directories are named by shard ID, skip the ones not selected
+        let is_selected = |path: &Path| {
+            let Some(shards) = shards else {
+                return true;
+            };
+            let shard_id = path
+                .components()
+                .next()
+                .and_then(|dir| dir.as_os_str().to_str())
+                .and_then(|dir| dir.parse::<ShardId>().ok());
+            shard_id.is_none_or(|shard_id| shards.contains(&shard_id))
+        };
+
         match snapshot_data {
             SnapshotData::Packed(snapshot_path) => {
-                tar_unpack_file(&snapshot_path, target_dir)?;
+                tar_unpack_file_filtered(&snapshot_path, target_dir, is_selected)?;
                 snapshot_path.close()?;
             }
             SnapshotData::Unpacked(snapshot_dir) => {
                 // already unpacked snapshot, validate files and move to target dir
                 let snapshot_dir_path = snapshot_dir.path();
+                for entry in fs_err::read_dir(snapshot_dir_path)? {
+                    let entry = entry?;
+                    if !is_selected(Path::new(&entry.file_name())) {
+                        fs_err::remove_dir_all(entry.path())?;
+                    }
+                }
                 move_all(snapshot_dir_path, target_dir)?;
             }
         }
@@ -191,18 +418,11 @@ impl Collection {
         let shard_ids_list: Vec<_> = match config.params.sharding_method.unwrap_or_default() {
             ShardingMethod::Auto => (0..configured_shards).collect(),
             ShardingMethod::Custom => {
-                // Load shard mapping from disk
-                let mapping_path = target_dir.join(SHARD_KEY_MAPPING_FILE);
                 debug_assert!(
-                    mapping_path.exists(),
+                    target_dir.join(SHARD_KEY_MAPPING_FILE).exists(),
                     "Shard mapping file must exist once custom sharding is used"
                 );
-                if !mapping_path.exists() {
-                    Vec::new()
-                } else {
-                    let shard_key_mapping: ShardKeyMapping = read_json(&mapping_path)?;
-                    shard_key_mapping.shard_ids()
-                }
+                Self::load_snapshot_shard_key_mapping(target_dir)?.shard_ids()
             }
         };
 
@@ -214,6 +434,9 @@ impl Collection {
         );
 
         for shard_id in shard_ids_list {
+            if shards.is_some_and(|shards| !shards.contains(&shard_id)) {
+                continue;
+            }
             let shard_path = shard_path(target_dir, shard_id);
             let shard_config_opt = ShardConfig::load(&shard_path)?;
             if let Some(shard_config) = shard_config_opt {
@@ -240,6 +463,19 @@ impl Collection {
         Ok(())
     }
 
+    /// Load shard key mapping of the collection snapshot unpacked at `snapshot_dir`
+    ///
+    /// The mapping is empty if the collection does not use custom sharding.
+    pub fn load_snapshot_shard_key_mapping(
+        snapshot_dir: &Path,
+    ) -> CollectionResult<ShardKeyMapping> {
+        let mapping_path = snapshot_dir.join(SHARD_KEY_MAPPING_FILE);
+        if !mapping_path.exists() {
+            return Ok(ShardKeyMapping::default());
+        }
+        Ok(read_json(&mapping_path)?)
+    }
+
     /// # Cancel safety
     ///
     /// This method is *not* cancel safe.
@@ -374,6 +610,29 @@ impl Collection {
             .assert_shard_exists(shard_id)
     }
 
+    /// Block updates of the collection, and wait until all accepted updates are applied to local
+    /// shards.
+    ///
+    /// Used to snapshot several collections at a consistent point. Updates wait until the returned
+    /// guard is dropped.
+    pub async fn quiesce_updates(
+        &self,
+    ) -> CollectionResult<tokio::sync::OwnedRwLockWriteGuard<()>> {
+        let updates_barrier = self.updates_barrier.clone().write_owned().await;
+
+        let mut plungers = Vec::new();
+        for shard in self.shards_holder.read().await.all_shards() {
+            if let Some(plunger) = shard.plunge_local_async().await? {
+                plungers.push(plunger);
+            }
+        }
+        for plunger in plungers {
+            plunger.await?;
+        }
+
+        Ok(updates_barrier)
+    }
+
     pub async fn try_take_partial_snapshot_recovery_lock(
         &self,
         shard_id: ShardId,
@@ -276,4 +276,26 @@ impl Collection {
 
         results.into_iter().sum()
     }
+
+    /// Reclaim space of deleted points for all local shards in the collection.
+    /// Returns amount of reclaimed points.
+    pub async fn defragment(&self) -> CollectionResult<usize> {
+        let shard_holder = self.shards_holder.clone().read_owned().await;
+
+        let results = self
+            .update_runtime
+            .spawn(async move {
+                let local_updates: FuturesUnordered<_> = shard_holder
+                    .all_shards()
+                    .map(|shard| shard.defragment())
+                    .collect();
+
+                let results: Vec<_> = local_updates.collect().await;
+
+                results
+            })
+            .await?;
+
+        results.into_iter().sum()
+    }
 }
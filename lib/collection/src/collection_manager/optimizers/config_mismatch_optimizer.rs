@@ -6,8 +6,12 @@ use itertools::Itertools;
 use parking_lot::Mutex;
 use segment::common::operation_time_statistics::OperationDurationsAggregator;
 use segment::entry::NonAppendableSegmentEntry;
+use segment::index::hnsw_index::graph_links::GraphLinksFormat;
 use segment::index::sparse_index::sparse_index_config::SparseIndexType;
-use segment::types::{HnswConfig, HnswGlobalConfig, Indexes, QuantizationConfig, VectorName};
+use segment::segment::Segment;
+use segment::types::{
+    HnswConfig, HnswGlobalConfig, Indexes, MmapAdviceConfig, QuantizationConfig, VectorName,
+};
 
 use crate::collection_manager::optimizers::segment_optimizer::{
     OptimizationPlanner, OptimizerThresholds, SegmentOptimizer,
@@ -28,6 +32,7 @@ pub struct ConfigMismatchOptimizer {
     hnsw_config: HnswConfig,
     hnsw_global_config: HnswGlobalConfig,
     quantization_config: Option<QuantizationConfig>,
+    mmap_advice: MmapAdviceConfig,
     telemetry_durations_aggregator: Arc<Mutex<OperationDurationsAggregator>>,
 }
 
@@ -49,10 +54,17 @@ impl ConfigMismatchOptimizer {
             hnsw_config,
             hnsw_global_config,
             quantization_config,
+            mmap_advice: MmapAdviceConfig::default(),
             telemetry_durations_aggregator: OperationDurationsAggregator::new(),
         }
     }
 
+    /// Set expected access pattern of memory mapped files for optimized segments.
+    pub fn with_mmap_advice(mut self, mmap_advice: MmapAdviceConfig) -> Self {
+        self.mmap_advice = mmap_advice;
+        self
+    }
+
     /// Check if current configuration requires vectors to be stored on disk
     fn check_if_vectors_on_disk(&self, vector_name: &VectorName) -> Option<bool> {
         self.collection_params
@@ -169,6 +181,16 @@ impl ConfigMismatchOptimizer {
 
         sparse_has_mismatch || dense_has_mismatch
     }
+
+    /// Check if any HNSW graph of the segment has links in the legacy plain format, and the
+    /// rebuild of such graphs is enabled.
+    fn has_plain_links(&self, segment: &Segment) -> bool {
+        common::flags::feature_flags().hnsw_convert_plain_links
+            && segment.vector_data.values().any(|vector_data| {
+                vector_data.vector_index.borrow().graph_links_format()
+                    == Some(GraphLinksFormat::Plain)
+            })
+    }
 }
 
 impl SegmentOptimizer for ConfigMismatchOptimizer {
@@ -204,13 +226,19 @@ impl SegmentOptimizer for ConfigMismatchOptimizer {
         &self.thresholds_config
     }
 
+    fn mmap_advice(&self) -> MmapAdviceConfig {
+        self.mmap_advice
+    }
+
     fn plan_optimizations(&self, planner: &mut OptimizationPlanner) {
         let to_optimize = planner
             .remaining()
             .iter()
             .filter_map(|(&segment_id, segment)| {
                 let segment = segment.read();
-                self.has_config_mismatch(&*segment).then(|| {
+                let requires_rebuild =
+                    self.has_config_mismatch(&*segment) || self.has_plain_links(&segment);
+                requires_rebuild.then(|| {
                     let vector_size = segment
                         .max_available_vectors_size_in_bytes()
                         .unwrap_or_default();
@@ -301,6 +329,9 @@ mod tests {
             on_disk: None,
             payload_m: None,
             inline_storage: None,
+            target_recall: None,
+            m0: None,
+            lock_in_ram: None,
         };
 
         // Optimizers used in test
@@ -440,6 +471,9 @@ mod tests {
             on_disk: None,
             payload_m: None,
             inline_storage: None,
+            target_recall: None,
+            m0: None,
+            lock_in_ram: None,
         };
 
         // Optimizers used in test
@@ -6,7 +6,7 @@ use parking_lot::Mutex;
 use segment::common::operation_time_statistics::OperationDurationsAggregator;
 use segment::entry::NonAppendableSegmentEntry as _;
 use segment::segment::Segment;
-use segment::types::{HnswConfig, HnswGlobalConfig, QuantizationConfig};
+use segment::types::{HnswConfig, HnswGlobalConfig, MmapAdviceConfig, QuantizationConfig};
 
 use crate::collection_manager::holders::segment_holder::SegmentId;
 use crate::collection_manager::optimizers::segment_optimizer::{
@@ -30,6 +30,7 @@ pub struct IndexingOptimizer {
     hnsw_config: HnswConfig,
     hnsw_global_config: HnswGlobalConfig,
     quantization_config: Option<QuantizationConfig>,
+    mmap_advice: MmapAdviceConfig,
     telemetry_durations_aggregator: Arc<Mutex<OperationDurationsAggregator>>,
 }
 
@@ -54,10 +55,17 @@ impl IndexingOptimizer {
             hnsw_config,
             hnsw_global_config,
             quantization_config,
+            mmap_advice: MmapAdviceConfig::default(),
             telemetry_durations_aggregator: OperationDurationsAggregator::new(),
         }
     }
 
+    /// Set expected access pattern of memory mapped files for optimized segments.
+    pub fn with_mmap_advice(mut self, mmap_advice: MmapAdviceConfig) -> Self {
+        self.mmap_advice = mmap_advice;
+        self
+    }
+
     fn is_optimization_required(&self, segment: &Segment) -> bool {
         let segment_config = segment.config();
         let indexing_threshold_bytes = self
@@ -153,6 +161,10 @@ impl SegmentOptimizer for IndexingOptimizer {
         &self.thresholds_config
     }
 
+    fn mmap_advice(&self) -> MmapAdviceConfig {
+        self.mmap_advice
+    }
+
     fn plan_optimizations(&self, planner: &mut OptimizationPlanner) {
         let max_segment_size_bytes = self
             .thresholds_config
@@ -771,6 +783,9 @@ mod tests {
             on_disk: None,
             payload_m: None,
             inline_storage: None,
+            target_recall: None,
+            m0: None,
+            lock_in_ram: None,
         };
 
         {
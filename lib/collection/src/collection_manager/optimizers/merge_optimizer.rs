@@ -5,7 +5,7 @@ use itertools::Itertools;
 use parking_lot::Mutex;
 use segment::common::operation_time_statistics::OperationDurationsAggregator;
 use segment::entry::NonAppendableSegmentEntry as _;
-use segment::types::{HnswConfig, HnswGlobalConfig, QuantizationConfig};
+use segment::types::{HnswConfig, HnswGlobalConfig, MmapAdviceConfig, QuantizationConfig};
 
 use crate::collection_manager::optimizers::segment_optimizer::{
     OptimizationPlanner, OptimizerThresholds, SegmentOptimizer,
@@ -51,6 +51,7 @@ pub struct MergeOptimizer {
     hnsw_config: HnswConfig,
     hnsw_global_config: HnswGlobalConfig,
     quantization_config: Option<QuantizationConfig>,
+    mmap_advice: MmapAdviceConfig,
     telemetry_durations_aggregator: Arc<Mutex<OperationDurationsAggregator>>,
 }
 
@@ -75,9 +76,16 @@ impl MergeOptimizer {
             hnsw_config,
             hnsw_global_config,
             quantization_config,
+            mmap_advice: MmapAdviceConfig::default(),
             telemetry_durations_aggregator: OperationDurationsAggregator::new(),
         }
     }
+
+    /// Set expected access pattern of memory mapped files for optimized segments.
+    pub fn with_mmap_advice(mut self, mmap_advice: MmapAdviceConfig) -> Self {
+        self.mmap_advice = mmap_advice;
+        self
+    }
 }
 
 impl SegmentOptimizer for MergeOptimizer {
@@ -113,6 +121,10 @@ impl SegmentOptimizer for MergeOptimizer {
         &self.thresholds_config
     }
 
+    fn mmap_advice(&self) -> MmapAdviceConfig {
+        self.mmap_advice
+    }
+
     fn plan_optimizations(&self, planner: &mut OptimizationPlanner) {
         let mut candidates = planner
             .remaining()
@@ -20,10 +20,11 @@ use segment::common::operation_time_statistics::{
 use segment::entry::entry_point::NonAppendableSegmentEntry;
 use segment::index::sparse_index::sparse_index_config::SparseIndexType;
 use segment::segment::{Segment, SegmentVersion};
-use segment::segment_constructor::build_segment;
 use segment::segment_constructor::segment_builder::SegmentBuilder;
+use segment::segment_constructor::{SegmentLoadOptions, build_segment};
 use segment::types::{
-    HnswConfig, HnswGlobalConfig, Indexes, QuantizationConfig, SegmentConfig, VectorStorageType,
+    HnswConfig, HnswGlobalConfig, Indexes, MmapAdviceConfig, QuantizationConfig, SegmentConfig,
+    VectorStorageType,
 };
 use shard::proxy_segment::{DeletedPoints, ProxyIndexChanges};
 use shard::segment_holder::locked::LockedSegmentHolder;
@@ -78,9 +79,26 @@ pub trait SegmentOptimizer {
     /// Get thresholds configuration for the current optimizer
     fn threshold_config(&self) -> &OptimizerThresholds;
 
+    /// Get expected access pattern of memory mapped files for optimized segments
+    fn mmap_advice(&self) -> MmapAdviceConfig;
+
     /// Find segments that require optimization and write them into `planner`.
     fn plan_optimizations(&self, planner: &mut OptimizationPlanner);
 
+    /// Optimize `segments` in place, instead of building a new segment out of them.
+    ///
+    /// Called once the segments are checked to be available for optimization, while other
+    /// optimizations are locked out of the segment holder.
+    /// Returns the number of optimized points, or `None` if the segments have to be rebuilt.
+    fn optimize_in_place(
+        &self,
+        _segments: &[LockedSegment],
+        _permit: &ResourcePermit,
+        _stopped: &AtomicBool,
+    ) -> CollectionResult<Option<usize>> {
+        Ok(None)
+    }
+
     /// Wrapper around [`SegmentOptimizer::plan_optimizations`].
     /// Simplified interface and extra checks.
     #[cfg(test)]
@@ -330,6 +348,47 @@ pub trait SegmentOptimizer {
                     }, // Mmap threshold wins
                 }
 
+                // Read on-disk vectors with O_DIRECT, if configured
+                let config_direct_io = collection_params
+                    .vectors
+                    .get_params(vector_name)
+                    .and_then(|config| config.direct_io);
+                if config_direct_io == Some(true) && config.storage_type == VectorStorageType::Mmap {
+                    config.storage_type = VectorStorageType::MmapDirectIo;
+                }
+
+                // Keep recently accessed on-disk vectors in RAM, if configured. Takes precedence over
+                // direct IO, which bypasses the page cache.
+                let config_tiered = collection_params
+                    .vectors
+                    .get_params(vector_name)
+                    .and_then(|config| config.tiered);
+                if config_tiered == Some(true)
+                    && matches!(
+                        config.storage_type,
+                        VectorStorageType::Mmap | VectorStorageType::MmapDirectIo,
+                    )
+                {
+                    config.storage_type = VectorStorageType::TieredMmap;
+                }
+
+                // Keep on-disk vectors compressed, if configured. Takes precedence over direct IO,
+                // compressed vectors are never read from the file directly.
+                let config_compressed = collection_params
+                    .vectors
+                    .get_params(vector_name)
+                    .and_then(|config| config.compressed);
+                if config_compressed == Some(true)
+                    && matches!(
+                        config.storage_type,
+                        VectorStorageType::Mmap
+                            | VectorStorageType::MmapDirectIo
+                            | VectorStorageType::TieredMmap,
+                    )
+                {
+                    config.storage_type = VectorStorageType::MmapCompressed;
+                }
+
                 // If we explicitly configure on_disk, but the segment storage type uses something
                 // that doesn't match, warn about it
                 if let Some(config_on_disk) = config_on_disk
@@ -361,6 +420,13 @@ pub trait SegmentOptimizer {
                     };
 
                     config.index.index_type = index_type;
+                    // Apply the current cap, source segments may be created with another one
+                    config.index.max_posting_length = params
+                        .index
+                        .and_then(|index_params| index_params.max_posting_length);
+                    config.index.mmap_advice = params
+                        .index
+                        .and_then(|index_params| index_params.mmap_advice);
                 }
             });
 
@@ -464,6 +530,11 @@ pub trait SegmentOptimizer {
             segment_builder.set_defragment_keys(defragmentation_keys.into_iter().collect());
         }
 
+        segment_builder.set_load_options(SegmentLoadOptions {
+            populate: self.collection_params().populate_policy,
+            mmap_advice: self.mmap_advice(),
+        });
+
         {
             progress_copy_data.start();
             let segment_guards = segments.iter().map(|segment| segment.read()).collect_vec();
@@ -678,6 +749,14 @@ pub trait SegmentOptimizer {
             return Ok(0);
         }
 
+        if let Some(optimized_points) = self.optimize_in_place(&input_segments, &permit, stopped)? {
+            if optimized_points > 0 {
+                on_successful_start();
+                timer.set_success(true);
+            }
+            return Ok(optimized_points);
+        }
+
         // Check that we have enough disk space for optimization
         self.check_segments_size(&input_segments)?;
 
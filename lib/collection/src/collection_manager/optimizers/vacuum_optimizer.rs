@@ -1,20 +1,41 @@
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::sync::atomic::AtomicBool;
+use std::time::Duration;
 
+use chrono::Utc;
+use common::budget::ResourcePermit;
 use itertools::Itertools;
 use ordered_float::OrderedFloat;
-use parking_lot::Mutex;
+use parking_lot::{Mutex, RwLockUpgradableReadGuard};
+use segment::common::operation_error::check_process_stopped;
 use segment::common::operation_time_statistics::OperationDurationsAggregator;
 use segment::entry::entry_point::NonAppendableSegmentEntry;
 use segment::index::VectorIndex;
 use segment::segment::Segment;
-use segment::types::{HnswConfig, HnswGlobalConfig, QuantizationConfig};
+use segment::types::{HnswConfig, HnswGlobalConfig, MmapAdviceConfig, QuantizationConfig};
 use segment::vector_storage::VectorStorage;
 
+use crate::collection_manager::holders::segment_holder::LockedSegment;
 use crate::collection_manager::optimizers::segment_optimizer::{
     OptimizationPlanner, OptimizerThresholds, SegmentOptimizer,
 };
 use crate::config::CollectionParams;
+use crate::operations::types::CollectionResult;
+
+/// Minimum time since the last vacuum finished, before littered postings or unhealed graphs alone
+/// trigger another one
+///
+/// Postings of write-heavy collections are littered again quickly, this prevents vacuuming their
+/// segments over and over.
+const POSTINGS_VACUUM_COOLDOWN: Duration = Duration::from_secs(60);
+
+/// Fraction of the deleted threshold, above which deleted points still linked from an HNSW graph
+/// get their neighbors re-linked in place
+///
+/// Healing well before the deleted threshold is reached keeps recall from degrading until the
+/// segment is rebuilt.
+const GRAPH_HEALING_THRESHOLD_FACTOR: f64 = 0.5;
 
 /// Optimizer which looks for segments with high amount of soft-deleted points or vectors
 ///
@@ -32,6 +53,7 @@ pub struct VacuumOptimizer {
     hnsw_config: HnswConfig,
     hnsw_global_config: HnswGlobalConfig,
     quantization_config: Option<QuantizationConfig>,
+    mmap_advice: MmapAdviceConfig,
     telemetry_durations_aggregator: Arc<Mutex<OperationDurationsAggregator>>,
 }
 
@@ -58,10 +80,17 @@ impl VacuumOptimizer {
             hnsw_config,
             quantization_config,
             hnsw_global_config,
+            mmap_advice: MmapAdviceConfig::default(),
             telemetry_durations_aggregator: OperationDurationsAggregator::new(),
         }
     }
 
+    /// Set expected access pattern of memory mapped files for optimized segments.
+    pub fn with_mmap_advice(mut self, mmap_advice: MmapAdviceConfig) -> Self {
+        self.mmap_advice = mmap_advice;
+        self
+    }
+
     /// Calculate littered ratio for segment on point level
     ///
     /// Returns `None` if littered ratio did not reach vacuum thresholds.
@@ -116,6 +145,75 @@ impl VacuumOptimizer {
             })
             .max_by_key(|ratio| OrderedFloat(*ratio))
     }
+
+    /// Calculate littered ratio for segment on postings level
+    ///
+    /// Immutable full text and sparse vector indexes keep deleted points in postings, until they
+    /// are compacted or the segment is rebuilt. Immutable map indexes keep updated points in a
+    /// delta, along with their stale values in postings.
+    ///
+    /// Returns `None` if littered ratio did not reach vacuum thresholds.
+    fn littered_postings_ratio(&self, segment: &Segment) -> Option<f64> {
+        let (full_text_deleted_ratio, map_delta_ratio) = {
+            let payload_index = segment.payload_index.borrow();
+            (
+                payload_index.full_text_deleted_ratio(),
+                payload_index.map_delta_ratio(),
+            )
+        };
+        let sparse_deleted_ratio = segment
+            .vector_data
+            .values()
+            .map(|vector_data| vector_data.vector_index.borrow().deleted_ratio())
+            .fold(0.0, f64::max);
+        let deleted_ratio = full_text_deleted_ratio
+            .max(map_delta_ratio)
+            .max(sparse_deleted_ratio);
+
+        // Estimate of deleted points still present in postings
+        let deleted_in_postings = (deleted_ratio * segment.total_point_count() as f64) as usize;
+        let reached_minimum = deleted_in_postings >= self.min_vectors_number;
+        let reached_ratio = deleted_ratio > self.deleted_threshold;
+        (reached_minimum && reached_ratio).then_some(deleted_ratio)
+    }
+
+    /// Calculate ratio of deleted points, which are still linked from an HNSW graph
+    ///
+    /// Such points are skipped during search, but still used for graph traversal, degrading
+    /// recall. Their neighbors can be re-linked in place with [`Segment::heal_graphs`].
+    ///
+    /// Returns `None` if the ratio did not reach the healing threshold.
+    fn unhealed_graph_ratio(&self, segment: &Segment) -> Option<f64> {
+        let healing_threshold = self.deleted_threshold * GRAPH_HEALING_THRESHOLD_FACTOR;
+        segment
+            .vector_data
+            .values()
+            .filter_map(|vector_data| {
+                let vector_index = vector_data.vector_index.borrow();
+                let unhealed_ratio = vector_index.unhealed_deleted_ratio();
+                let unhealed_count =
+                    (unhealed_ratio * vector_index.indexed_vector_count() as f64) as usize;
+
+                let reached_minimum = unhealed_count >= self.min_vectors_number;
+                let reached_ratio = unhealed_ratio > healing_threshold;
+                (reached_minimum && reached_ratio).then_some(unhealed_ratio)
+            })
+            .max_by_key(|ratio| OrderedFloat(*ratio))
+    }
+
+    /// Whether the last vacuum finished, or the optimizer was created, within
+    /// [`POSTINGS_VACUUM_COOLDOWN`]
+    fn is_postings_cooling_down(&self) -> bool {
+        let last_vacuum = self
+            .telemetry_durations_aggregator
+            .lock()
+            .last_response_date();
+        last_vacuum.is_some_and(|last_vacuum| {
+            (Utc::now() - last_vacuum)
+                .to_std()
+                .is_ok_and(|elapsed| elapsed < POSTINGS_VACUUM_COOLDOWN)
+        })
+    }
 }
 
 impl SegmentOptimizer for VacuumOptimizer {
@@ -151,7 +249,12 @@ impl SegmentOptimizer for VacuumOptimizer {
         &self.thresholds_config
     }
 
+    fn mmap_advice(&self) -> MmapAdviceConfig {
+        self.mmap_advice
+    }
+
     fn plan_optimizations(&self, planner: &mut OptimizationPlanner) {
+        let postings_cooling_down = self.is_postings_cooling_down();
         let to_optimize = planner
             .remaining()
             .iter()
@@ -159,7 +262,17 @@ impl SegmentOptimizer for VacuumOptimizer {
                 let segment = segment.read();
                 let littered_ratio_segment = self.littered_ratio_segment(&segment);
                 let littered_ratio_vectors = self.littered_vectors_index_ratio(&segment);
+                let (littered_ratio_postings, unhealed_ratio_graph) = if postings_cooling_down {
+                    (None, None)
+                } else {
+                    (
+                        self.littered_postings_ratio(&segment),
+                        self.unhealed_graph_ratio(&segment),
+                    )
+                };
                 let worst_ratio = std::iter::chain(littered_ratio_segment, littered_ratio_vectors)
+                    .chain(littered_ratio_postings)
+                    .chain(unhealed_ratio_graph)
                     .max_by_key(|ratio| OrderedFloat(*ratio));
                 worst_ratio.map(|ratio| (segment_id, ratio))
             })
@@ -170,6 +283,48 @@ impl SegmentOptimizer for VacuumOptimizer {
         }
     }
 
+    /// Segments, which are only littered on postings or graph level, get their postings compacted
+    /// and HNSW graphs healed in place.
+    /// Falls back to rebuilding the segment if neither can be done.
+    fn optimize_in_place(
+        &self,
+        segments: &[LockedSegment],
+        permit: &ResourcePermit,
+        stopped: &AtomicBool,
+    ) -> CollectionResult<Option<usize>> {
+        let [LockedSegment::Original(segment)] = segments else {
+            return Ok(None);
+        };
+
+        let segment = segment.upgradable_read();
+        if self.littered_ratio_segment(&segment).is_some()
+            || self.littered_vectors_index_ratio(&segment).is_some()
+        {
+            return Ok(None);
+        }
+
+        check_process_stopped(stopped)?;
+
+        let mut segment = RwLockUpgradableReadGuard::upgrade(segment);
+        let compacted = segment.compact_postings(self.deleted_threshold)?;
+
+        check_process_stopped(stopped)?;
+
+        // Permit of this optimizer holds IO resources, same as used for CPUs during indexing
+        let healed = segment.heal_graphs(
+            self.deleted_threshold * GRAPH_HEALING_THRESHOLD_FACTOR,
+            permit.num_io as usize,
+        )?;
+        if compacted == 0 && healed == 0 {
+            return Ok(None);
+        }
+        log::debug!(
+            "Compacted {compacted} postings indexes and healed {healed} graphs of segment {}",
+            segment.segment_uuid(),
+        );
+        Ok(Some(segment.available_point_count()))
+    }
+
     fn get_telemetry_counter(&self) -> &Mutex<OperationDurationsAggregator> {
         &self.telemetry_durations_aggregator
     }
@@ -415,6 +570,9 @@ mod tests {
             on_disk: None,
             payload_m: None,
             inline_storage: None,
+            target_recall: None,
+            m0: None,
+            lock_in_ram: None,
         };
 
         // Optimizers used in test
@@ -6,6 +6,8 @@ pub mod file_utils;
 pub mod is_ready;
 pub mod retrieve_request_trait;
 pub mod sha_256;
+pub mod snapshot_metadata;
+pub mod snapshot_point;
 pub mod snapshot_stream;
 pub mod snapshots_manager;
 pub mod stoppable_task;
@@ -0,0 +1,416 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::Path;
+
+use common::fs::read_json;
+use common::storage_version::StorageVersion;
+use common::tar_unpack::tar_read_file;
+use segment::segment::SegmentVersion;
+use semver::Version;
+use serde::{Deserialize, Serialize};
+
+use crate::collection::{Collection, CollectionVersion};
+use crate::operations::types::{CollectionError, CollectionResult};
+
+/// File in the root of a collection snapshot, describing the node which produced it
+pub const SNAPSHOT_METADATA_FILE: &str = "snapshot_metadata.json";
+
+/// What to do when recovering a snapshot produced on an incompatible node.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SnapshotCompatibilityPolicy {
+    /// Recover the snapshot, older file formats are migrated when the collection is loaded
+    #[default]
+    Migrate,
+    /// Refuse snapshots with older format versions, which would be migrated on load
+    Refuse,
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Endianness {
+    Little,
+    Big,
+}
+
+impl Endianness {
+    pub const fn current() -> Self {
+        if cfg!(target_endian = "big") {
+            Self::Big
+        } else {
+            Self::Little
+        }
+    }
+}
+
+impl fmt::Display for Endianness {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Little => write!(f, "little-endian"),
+            Self::Big => write!(f, "big-endian"),
+        }
+    }
+}
+
+/// Difference between a snapshot and this node.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SnapshotIncompatibility {
+    /// Format can't be read by this node
+    NewerFormat {
+        format: String,
+        version: String,
+        supported: String,
+    },
+    /// Format is too old to be migrated by this node
+    UnsupportedFormat {
+        format: String,
+        version: String,
+        current: String,
+    },
+    /// Format is not known to this node
+    UnknownFormat { format: String, version: String },
+    /// Format is migrated when the collection is loaded
+    OlderFormat {
+        format: String,
+        version: String,
+        current: String,
+    },
+}
+
+impl SnapshotIncompatibility {
+    /// Whether the snapshot can't be recovered, regardless of the policy
+    pub fn is_fatal(&self) -> bool {
+        match self {
+            Self::NewerFormat { .. }
+            | Self::UnsupportedFormat { .. }
+            | Self::UnknownFormat { .. } => true,
+            Self::OlderFormat { .. } => false,
+        }
+    }
+}
+
+impl fmt::Display for SnapshotIncompatibility {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NewerFormat {
+                format,
+                version,
+                supported,
+            } => write!(
+                f,
+                "{format} format {version} is newer than supported {supported}",
+            ),
+            Self::UnsupportedFormat {
+                format,
+                version,
+                current,
+            } => write!(
+                f,
+                "{format} format {version} can't be migrated to {current}"
+            ),
+            Self::UnknownFormat { format, version } => {
+                write!(f, "unknown {format} format {version}")
+            }
+            Self::OlderFormat {
+                format,
+                version,
+                current,
+            } => write!(f, "{format} format {version} will be migrated to {current}"),
+        }
+    }
+}
+
+/// Metadata of the node which produced a snapshot.
+///
+/// Persisted file formats are little-endian on any host, so the snapshot is portable by itself.
+/// Architecture and endianness of the producing node are informational only. The metadata makes it
+/// possible to tell, on recovery, which formats have to be migrated.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SnapshotMetadata {
+    pub qdrant_version: String,
+    pub arch: String,
+    pub endian: Endianness,
+    /// Storage version of collections and segments, by name
+    pub format_versions: BTreeMap<String, String>,
+    /// Newest version of each versioned file format written by the producing node, by format name.
+    ///
+    /// Files of the snapshot may be of older versions, which are read or migrated on load anyway.
+    #[serde(default)]
+    pub storage_formats: BTreeMap<String, u32>,
+}
+
+impl SnapshotMetadata {
+    /// Metadata of this node.
+    pub fn current() -> Self {
+        let format_versions = [
+            ("collection", CollectionVersion::current_raw()),
+            ("segment", SegmentVersion::current_raw()),
+        ]
+        .into_iter()
+        .map(|(format, version)| (format.to_string(), version.to_string()))
+        .collect();
+
+        let storage_formats = SegmentVersion::storage_format_versions()
+            .into_iter()
+            .map(|(format, version)| (format.to_string(), version))
+            .collect();
+
+        Self {
+            qdrant_version: env!("CARGO_PKG_VERSION").to_string(),
+            arch: std::env::consts::ARCH.to_string(),
+            endian: Endianness::current(),
+            format_versions,
+            storage_formats,
+        }
+    }
+
+    pub fn to_bytes(&self) -> CollectionResult<Vec<u8>> {
+        serde_json::to_vec_pretty(self).map_err(|err| {
+            CollectionError::service_error(format!("Failed to serialize snapshot metadata: {err}"))
+        })
+    }
+
+    /// Load metadata from an unpacked snapshot, `None` for snapshots produced before it existed.
+    pub fn load(snapshot_dir: &Path) -> CollectionResult<Option<Self>> {
+        let path = snapshot_dir.join(SNAPSHOT_METADATA_FILE);
+        if !path.exists() {
+            return Ok(None);
+        }
+        Ok(Some(read_json(&path)?))
+    }
+
+    /// Load metadata from a packed snapshot without unpacking it, `None` for snapshots produced
+    /// before it existed.
+    pub fn load_from_archive(snapshot_path: &Path) -> CollectionResult<Option<Self>> {
+        let Some(data) = tar_read_file(snapshot_path, Path::new(SNAPSHOT_METADATA_FILE))? else {
+            return Ok(None);
+        };
+        let metadata = serde_json::from_slice(&data).map_err(|err| {
+            CollectionError::bad_input(format!("Invalid {SNAPSHOT_METADATA_FILE}: {err}"))
+        })?;
+        Ok(Some(metadata))
+    }
+
+    /// Differences between the snapshot and this node, which require migration on recovery.
+    pub fn incompatibilities(&self) -> Vec<SnapshotIncompatibility> {
+        let current = Self::current();
+        let mut incompatibilities = Vec::new();
+
+        for (format, version) in &self.format_versions {
+            let incompatibility = match format.as_str() {
+                "collection" => storage_version_incompatibility::<CollectionVersion>(
+                    format,
+                    version,
+                    |stored| Collection::can_upgrade_storage(stored, &CollectionVersion::current()),
+                ),
+                "segment" => storage_version_incompatibility::<SegmentVersion>(
+                    format,
+                    version,
+                    SegmentVersion::can_upgrade_storage,
+                ),
+                _ => Some(SnapshotIncompatibility::UnknownFormat {
+                    format: format.clone(),
+                    version: version.clone(),
+                }),
+            };
+            incompatibilities.extend(incompatibility);
+        }
+
+        for (format, &version) in &self.storage_formats {
+            let Some(&current_version) = current.storage_formats.get(format) else {
+                incompatibilities.push(SnapshotIncompatibility::UnknownFormat {
+                    format: format.clone(),
+                    version: version.to_string(),
+                });
+                continue;
+            };
+            if version > current_version {
+                incompatibilities.push(SnapshotIncompatibility::NewerFormat {
+                    format: format.clone(),
+                    version: version.to_string(),
+                    supported: current_version.to_string(),
+                });
+            } else if version < current_version {
+                incompatibilities.push(SnapshotIncompatibility::OlderFormat {
+                    format: format.clone(),
+                    version: version.to_string(),
+                    current: current_version.to_string(),
+                });
+            }
+        }
+
+        incompatibilities
+    }
+
+    /// Log a compatibility summary of the snapshot, and check it against `policy`.
+    ///
+    /// Snapshots with formats which can't be read or migrated are refused regardless of the policy.
+    pub fn check_compatibility(
+        metadata: Option<&Self>,
+        policy: SnapshotCompatibilityPolicy,
+    ) -> CollectionResult<()> {
+        let Some(metadata) = metadata else {
+            log::info!("Snapshot has no {SNAPSHOT_METADATA_FILE}, skipping compatibility check");
+            return Ok(());
+        };
+
+        let current = Self::current();
+        log::info!(
+            "Snapshot produced by Qdrant {} on {} ({}), recovering on Qdrant {} on {} ({})",
+            metadata.qdrant_version,
+            metadata.arch,
+            metadata.endian,
+            current.qdrant_version,
+            current.arch,
+            current.endian,
+        );
+
+        let incompatibilities = metadata.incompatibilities();
+        if incompatibilities.is_empty() {
+            return Ok(());
+        }
+        for incompatibility in &incompatibilities {
+            log::warn!("Snapshot compatibility: {incompatibility}");
+        }
+
+        let is_fatal = incompatibilities
+            .iter()
+            .any(SnapshotIncompatibility::is_fatal);
+        if is_fatal || policy == SnapshotCompatibilityPolicy::Refuse {
+            let incompatibilities: Vec<_> =
+                incompatibilities.iter().map(ToString::to_string).collect();
+            return Err(CollectionError::bad_input(format!(
+                "Snapshot is incompatible with this node: {}",
+                incompatibilities.join(", "),
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+/// Compare storage version of a snapshot with the current one, following the same rules as
+/// loading the storage does.
+///
+/// Storage of a newer version is refused, older storage is migrated if `can_upgrade`.
+fn storage_version_incompatibility<V: StorageVersion>(
+    format: &str,
+    version: &str,
+    can_upgrade: impl Fn(&Version) -> bool,
+) -> Option<SnapshotIncompatibility> {
+    let current = V::current();
+    let Ok(stored) = Version::parse(version) else {
+        return Some(SnapshotIncompatibility::UnknownFormat {
+            format: format.to_string(),
+            version: version.to_string(),
+        });
+    };
+
+    if stored > current {
+        Some(SnapshotIncompatibility::NewerFormat {
+            format: format.to_string(),
+            version: version.to_string(),
+            supported: current.to_string(),
+        })
+    } else if stored == current {
+        None
+    } else if can_upgrade(&stored) {
+        Some(SnapshotIncompatibility::OlderFormat {
+            format: format.to_string(),
+            version: version.to_string(),
+            current: current.to_string(),
+        })
+    } else {
+        Some(SnapshotIncompatibility::UnsupportedFormat {
+            format: format.to_string(),
+            version: version.to_string(),
+            current: current.to_string(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snapshot_compatibility() {
+        let current = SnapshotMetadata::current();
+        assert!(current.incompatibilities().is_empty());
+        for policy in [
+            SnapshotCompatibilityPolicy::Migrate,
+            SnapshotCompatibilityPolicy::Refuse,
+        ] {
+            SnapshotMetadata::check_compatibility(Some(&current), policy).unwrap();
+            SnapshotMetadata::check_compatibility(None, policy).unwrap();
+        }
+
+        let mut foreign = current.clone();
+        foreign.arch = "s390x".to_string();
+        foreign.endian = match current.endian {
+            Endianness::Little => Endianness::Big,
+            Endianness::Big => Endianness::Little,
+        };
+        foreign
+            .storage_formats
+            .insert("histogram_borders".to_string(), 0);
+        // Endianness of the producing host doesn't matter, only the older format does
+        assert_eq!(foreign.incompatibilities().len(), 1);
+        SnapshotMetadata::check_compatibility(Some(&foreign), SnapshotCompatibilityPolicy::Migrate)
+            .unwrap();
+        assert!(
+            SnapshotMetadata::check_compatibility(
+                Some(&foreign),
+                SnapshotCompatibilityPolicy::Refuse,
+            )
+            .is_err()
+        );
+
+        // Storage of newer releases is refused, the same way as on load
+        let mut newer_patch = current.clone();
+        let mut version = CollectionVersion::current();
+        version.patch += 1;
+        newer_patch
+            .format_versions
+            .insert("collection".to_string(), version.to_string());
+        assert_eq!(newer_patch.incompatibilities().len(), 1);
+
+        let refused = [
+            ("collection", "999.0.0"),
+            ("segment", "0.1.0"),
+            ("collection", "0.4.0"),
+            ("segment", "not a version"),
+        ];
+        for (format, version) in refused {
+            let mut incompatible = current.clone();
+            incompatible
+                .format_versions
+                .insert(format.to_string(), version.to_string());
+            assert!(
+                SnapshotMetadata::check_compatibility(
+                    Some(&incompatible),
+                    SnapshotCompatibilityPolicy::Migrate,
+                )
+                .is_err(),
+                "{format} {version}",
+            );
+        }
+
+        let mut newer_storage_format = current.clone();
+        *newer_storage_format
+            .storage_formats
+            .get_mut("hnsw_links")
+            .unwrap() += 1;
+        newer_storage_format
+            .storage_formats
+            .insert("unknown".to_string(), 1);
+        assert_eq!(newer_storage_format.incompatibilities().len(), 2);
+        assert!(
+            SnapshotMetadata::check_compatibility(
+                Some(&newer_storage_format),
+                SnapshotCompatibilityPolicy::Migrate,
+            )
+            .is_err()
+        );
+    }
+}
@@ -0,0 +1,32 @@
+use tokio::sync::mpsc;
+
+/// Point at which snapshots of several shards are taken, used to snapshot them consistently.
+///
+/// Each snapshot holds a [`SnapshotPointGuard`] until it has taken its snapshot point, i.e. until
+/// all segments of the shard are proxied and later updates no longer affect the snapshot. The
+/// point is reached once all guards are dropped.
+pub struct SnapshotPoint {
+    receiver: mpsc::Receiver<()>,
+}
+
+/// Held by a snapshot until it has taken its snapshot point, see [`SnapshotPoint`].
+#[derive(Clone)]
+pub struct SnapshotPointGuard {
+    _sender: mpsc::Sender<()>,
+}
+
+impl SnapshotPoint {
+    pub fn new() -> (Self, SnapshotPointGuard) {
+        let (sender, receiver) = mpsc::channel(1);
+        (Self { receiver }, SnapshotPointGuard { _sender: sender })
+    }
+
+    /// Wait until all guards are dropped.
+    ///
+    /// Failed snapshots drop their guards as well, so the point is also reached if any of them
+    /// fails.
+    pub async fn wait(mut self) {
+        // Nothing is ever sent, receiving ends once all senders are dropped
+        let _ = self.receiver.recv().await;
+    }
+}
@@ -6,7 +6,7 @@ use actix_files::NamedFile;
 use actix_web::http::header::ContentDisposition;
 use actix_web::{HttpResponse, Responder};
 use bytes::Bytes;
-use futures::{Stream, TryStreamExt};
+use futures::{Stream, StreamExt, TryStreamExt};
 
 pub struct SnapShotStreamLocalFS {
     pub snapshot_path: PathBuf,
@@ -40,6 +40,23 @@ impl SnapshotStream {
             filename,
         })
     }
+
+    /// Keep `guard` alive until the byte stream is dropped, i.e. until the snapshot is streamed.
+    ///
+    /// Snapshots on the local file system are complete already, so `guard` is dropped right away.
+    pub fn with_guard<G: 'static>(self, guard: G) -> Self {
+        match self {
+            SnapshotStream::LocalFS(_) => self,
+            SnapshotStream::ByteStream(SnapShotStreamCloudStrage { stream, filename }) => {
+                SnapshotStream::ByteStream(SnapShotStreamCloudStrage {
+                    stream: Box::pin(stream.inspect(move |_| {
+                        let _ = &guard;
+                    })),
+                    filename,
+                })
+            }
+        }
+    }
 }
 
 impl Responder for SnapshotStream {
@@ -1,14 +1,18 @@
 use std::path::{Path, PathBuf};
+use std::time::Duration;
 
 use common::tempfile_ext::MaybeTempPath;
 use fs_err as fs;
 use fs_err::tokio as tokio_fs;
-use object_store::ObjectStoreExt;
 use object_store::aws::AmazonS3Builder;
+use object_store::azure::MicrosoftAzureBuilder;
+use object_store::gcp::GoogleCloudStorageBuilder;
+use object_store::{ObjectStoreExt, RetryConfig};
 use serde::Deserialize;
 use tempfile::TempPath;
-use tokio::io::AsyncWriteExt;
+use tokio::io::{AsyncRead, AsyncWriteExt};
 
+use super::snapshot_metadata::SnapshotCompatibilityPolicy;
 use super::snapshot_stream::{SnapShotStreamLocalFS, SnapshotStream};
 use crate::common::file_utils::move_file;
 use crate::common::sha_256::hash_file;
@@ -22,6 +26,18 @@ use crate::operations::types::{CollectionError, CollectionResult};
 pub struct SnapshotsConfig {
     pub snapshots_storage: SnapshotsStorageConfig,
     pub s3_config: Option<S3Config>,
+    pub gcs_config: Option<GcsConfig>,
+    pub azure_config: Option<AzureConfig>,
+    /// Retries of failed requests to object storage
+    #[serde(default)]
+    pub retry: ObjectStoreRetryConfig,
+    /// Compress snapshot archives with zstd at this level, archives are not compressed if not set.
+    /// Archives are decompressed transparently on recovery.
+    #[serde(default)]
+    pub compression_level: Option<i32>,
+    /// What to do when recovering a snapshot with older storage formats
+    #[serde(default)]
+    pub compatibility_policy: SnapshotCompatibilityPolicy,
 }
 
 #[derive(Clone, Debug, Default, Deserialize)]
@@ -30,6 +46,8 @@ pub enum SnapshotsStorageConfig {
     #[default]
     Local,
     S3,
+    Gcs,
+    Azure,
 }
 
 #[derive(Clone, Deserialize, Debug, Default)]
@@ -41,6 +59,51 @@ pub struct S3Config {
     pub endpoint_url: Option<String>,
 }
 
+#[derive(Clone, Deserialize, Debug, Default)]
+pub struct GcsConfig {
+    pub bucket: String,
+    /// Path to the service account JSON file
+    pub service_account_path: Option<String>,
+    /// Service account JSON, as an alternative to `service_account_path`
+    pub service_account_key: Option<String>,
+}
+
+#[derive(Clone, Deserialize, Debug, Default)]
+pub struct AzureConfig {
+    pub container: String,
+    pub account: Option<String>,
+    pub access_key: Option<String>,
+    pub endpoint_url: Option<String>,
+}
+
+#[derive(Clone, Deserialize, Debug)]
+#[serde(default)]
+pub struct ObjectStoreRetryConfig {
+    /// Max number of retries of a failed request
+    pub max_retries: usize,
+    /// Max time to keep retrying a request, in seconds
+    pub retry_timeout_sec: u64,
+}
+
+impl Default for ObjectStoreRetryConfig {
+    fn default() -> Self {
+        Self {
+            max_retries: 10,
+            retry_timeout_sec: 180,
+        }
+    }
+}
+
+impl From<&ObjectStoreRetryConfig> for RetryConfig {
+    fn from(config: &ObjectStoreRetryConfig) -> Self {
+        RetryConfig {
+            max_retries: config.max_retries,
+            retry_timeout: Duration::from_secs(config.retry_timeout_sec),
+            ..RetryConfig::default()
+        }
+    }
+}
+
 pub struct SnapshotStorageCloud {
     client: Box<dyn object_store::ObjectStore>,
 }
@@ -51,9 +114,8 @@ pub enum SnapshotStorageManager {
     LocalFS(SnapshotStorageLocalFS),
     // Assuming that we can have common operations for all cloud storages
     S3(SnapshotStorageCloud),
-    // <TODO> : Implement other cloud storage
-    // GCS(SnapshotStorageCloud),
-    // AZURE(SnapshotStorageCloud),
+    Gcs(SnapshotStorageCloud),
+    Azure(SnapshotStorageCloud),
 }
 
 impl SnapshotStorageManager {
@@ -63,7 +125,8 @@ impl SnapshotStorageManager {
                 Ok(SnapshotStorageManager::LocalFS(SnapshotStorageLocalFS))
             }
             SnapshotsStorageConfig::S3 => {
-                let mut builder = AmazonS3Builder::from_env();
+                let mut builder =
+                    AmazonS3Builder::from_env().with_retry((&snapshots_config.retry).into());
                 if let Some(s3_config) = &snapshots_config.s3_config {
                     builder = builder.with_bucket_name(&s3_config.bucket);
 
@@ -90,6 +153,56 @@ impl SnapshotStorageManager {
 
                 Ok(SnapshotStorageManager::S3(SnapshotStorageCloud { client }))
             }
+            SnapshotsStorageConfig::Gcs => {
+                let mut builder = GoogleCloudStorageBuilder::from_env()
+                    .with_retry((&snapshots_config.retry).into());
+                if let Some(gcs_config) = &snapshots_config.gcs_config {
+                    builder = builder.with_bucket_name(&gcs_config.bucket);
+
+                    if let Some(service_account_path) = &gcs_config.service_account_path {
+                        builder = builder.with_service_account_path(service_account_path);
+                    }
+                    if let Some(service_account_key) = &gcs_config.service_account_key {
+                        builder = builder.with_service_account_key(service_account_key);
+                    }
+                }
+                let client: Box<dyn object_store::ObjectStore> =
+                    Box::new(builder.build().map_err(|e| {
+                        CollectionError::service_error(format!("Failed to create GCS client: {e}"))
+                    })?);
+
+                Ok(SnapshotStorageManager::Gcs(SnapshotStorageCloud { client }))
+            }
+            SnapshotsStorageConfig::Azure => {
+                let mut builder =
+                    MicrosoftAzureBuilder::from_env().with_retry((&snapshots_config.retry).into());
+                if let Some(azure_config) = &snapshots_config.azure_config {
+                    builder = builder.with_container_name(&azure_config.container);
+
+                    if let Some(account) = &azure_config.account {
+                        builder = builder.with_account(account);
+                    }
+                    if let Some(access_key) = &azure_config.access_key {
+                        builder = builder.with_access_key(access_key);
+                    }
+                    if let Some(endpoint_url) = &azure_config.endpoint_url {
+                        builder = builder.with_endpoint(endpoint_url.clone());
+                        if endpoint_url.starts_with("http://") {
+                            builder = builder.with_allow_http(true);
+                        }
+                    }
+                }
+                let client: Box<dyn object_store::ObjectStore> =
+                    Box::new(builder.build().map_err(|e| {
+                        CollectionError::service_error(format!(
+                            "Failed to create Azure client: {e}"
+                        ))
+                    })?);
+
+                Ok(SnapshotStorageManager::Azure(SnapshotStorageCloud {
+                    client,
+                }))
+            }
         }
     }
 
@@ -98,7 +211,9 @@ impl SnapshotStorageManager {
             SnapshotStorageManager::LocalFS(storage_impl) => {
                 storage_impl.delete_snapshot(snapshot_name).await
             }
-            SnapshotStorageManager::S3(storage_impl) => {
+            SnapshotStorageManager::S3(storage_impl)
+            | SnapshotStorageManager::Gcs(storage_impl)
+            | SnapshotStorageManager::Azure(storage_impl) => {
                 storage_impl.delete_snapshot(snapshot_name).await
             }
         }
@@ -112,7 +227,9 @@ impl SnapshotStorageManager {
             SnapshotStorageManager::LocalFS(storage_impl) => {
                 storage_impl.list_snapshots(directory).await
             }
-            SnapshotStorageManager::S3(storage_impl) => {
+            SnapshotStorageManager::S3(storage_impl)
+            | SnapshotStorageManager::Gcs(storage_impl)
+            | SnapshotStorageManager::Azure(storage_impl) => {
                 storage_impl.list_snapshots(directory).await
             }
         }
@@ -133,12 +250,24 @@ impl SnapshotStorageManager {
             SnapshotStorageManager::LocalFS(storage_impl) => {
                 storage_impl.store_file(source_path, target_path).await
             }
-            SnapshotStorageManager::S3(storage_impl) => {
+            SnapshotStorageManager::S3(storage_impl)
+            | SnapshotStorageManager::Gcs(storage_impl)
+            | SnapshotStorageManager::Azure(storage_impl) => {
                 storage_impl.store_file(source_path, target_path).await
             }
         }
     }
 
+    /// Object storage, which can store snapshots without staging them in a local file.
+    pub fn cloud_storage(&self) -> Option<&SnapshotStorageCloud> {
+        match self {
+            SnapshotStorageManager::LocalFS(_) => None,
+            SnapshotStorageManager::S3(storage_impl)
+            | SnapshotStorageManager::Gcs(storage_impl)
+            | SnapshotStorageManager::Azure(storage_impl) => Some(storage_impl),
+        }
+    }
+
     pub async fn get_stored_file(
         &self,
         storage_path: &Path,
@@ -148,7 +277,9 @@ impl SnapshotStorageManager {
             SnapshotStorageManager::LocalFS(storage_impl) => {
                 storage_impl.get_stored_file(storage_path, local_path).await
             }
-            SnapshotStorageManager::S3(storage_impl) => {
+            SnapshotStorageManager::S3(storage_impl)
+            | SnapshotStorageManager::Gcs(storage_impl)
+            | SnapshotStorageManager::Azure(storage_impl) => {
                 storage_impl.get_stored_file(storage_path, local_path).await
             }
         }
@@ -163,7 +294,9 @@ impl SnapshotStorageManager {
             SnapshotStorageManager::LocalFS(_storage_impl) => {
                 SnapshotStorageLocalFS::get_snapshot_path(snapshots_path, snapshot_name)
             }
-            SnapshotStorageManager::S3(_storage_impl) => Ok(
+            SnapshotStorageManager::S3(_storage_impl)
+            | SnapshotStorageManager::Gcs(_storage_impl)
+            | SnapshotStorageManager::Azure(_storage_impl) => Ok(
                 SnapshotStorageCloud::get_snapshot_path(snapshots_path, snapshot_name),
             ),
         }
@@ -178,7 +311,9 @@ impl SnapshotStorageManager {
             SnapshotStorageManager::LocalFS(_storage_impl) => {
                 SnapshotStorageLocalFS::get_full_snapshot_path(snapshots_path, snapshot_name)
             }
-            SnapshotStorageManager::S3(_storage_impl) => Ok(
+            SnapshotStorageManager::S3(_storage_impl)
+            | SnapshotStorageManager::Gcs(_storage_impl)
+            | SnapshotStorageManager::Azure(_storage_impl) => Ok(
                 SnapshotStorageCloud::get_full_snapshot_path(snapshots_path, snapshot_name),
             ),
         }
@@ -193,7 +328,9 @@ impl SnapshotStorageManager {
             SnapshotStorageManager::LocalFS(_storage_impl) => {
                 SnapshotStorageLocalFS::get_snapshot_file(snapshot_path, temp_dir)
             }
-            SnapshotStorageManager::S3(storage_impl) => {
+            SnapshotStorageManager::S3(storage_impl)
+            | SnapshotStorageManager::Gcs(storage_impl)
+            | SnapshotStorageManager::Azure(storage_impl) => {
                 storage_impl
                     .get_snapshot_file(snapshot_path, temp_dir)
                     .await
@@ -209,7 +346,9 @@ impl SnapshotStorageManager {
             SnapshotStorageManager::LocalFS(_storage_impl) => {
                 Ok(SnapshotStorageLocalFS::get_snapshot_stream(snapshot_path))
             }
-            SnapshotStorageManager::S3(storage_impl) => {
+            SnapshotStorageManager::S3(storage_impl)
+            | SnapshotStorageManager::Gcs(storage_impl)
+            | SnapshotStorageManager::Azure(storage_impl) => {
                 storage_impl.get_snapshot_stream(snapshot_path).await
             }
         }
@@ -401,11 +540,27 @@ impl SnapshotStorageCloud {
         source_path: &Path,
         target_path: &Path,
     ) -> CollectionResult<SnapshotDescription> {
+        // Upload checksum first, to avoid making snapshot available without checksum
+        let checksum = hash_file(source_path).await?;
+        snapshot_storage_ops::put_checksum(&self.client, target_path, checksum).await?;
+
         snapshot_storage_ops::multipart_upload(&self.client, source_path, target_path).await?;
         tokio_fs::remove_file(source_path).await?;
         snapshot_storage_ops::get_snapshot_description(&self.client, target_path).await
     }
 
+    /// Store snapshot data from `reader`, without staging it in a local file.
+    ///
+    /// If this future is dropped before it completes, the upload is aborted.
+    pub async fn store_stream(
+        &self,
+        reader: impl AsyncRead + Unpin,
+        target_path: &Path,
+    ) -> CollectionResult<SnapshotDescription> {
+        snapshot_storage_ops::multipart_upload_reader(&self.client, reader, target_path).await?;
+        snapshot_storage_ops::get_snapshot_description(&self.client, target_path).await
+    }
+
     async fn get_stored_file(
         &self,
         storage_path: &Path,
@@ -467,3 +622,45 @@ impl SnapshotStorageCloud {
         Ok(SnapshotStream::new_stream(download.into_stream(), None))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_object_storage_config() {
+        let config: SnapshotsConfig = serde_json::from_value(serde_json::json!({
+            "snapshots_storage": "gcs",
+            "gcs_config": { "bucket": "snapshots" },
+        }))
+        .unwrap();
+        assert!(matches!(
+            config.snapshots_storage,
+            SnapshotsStorageConfig::Gcs
+        ));
+        assert_eq!(config.gcs_config.unwrap().bucket, "snapshots");
+        assert_eq!(config.retry.max_retries, 10);
+        assert_eq!(config.retry.retry_timeout_sec, 180);
+
+        let config: SnapshotsConfig = serde_json::from_value(serde_json::json!({
+            "snapshots_storage": "azure",
+            "azure_config": {
+                "container": "snapshots",
+                "account": "qdrant",
+                "endpoint_url": "http://localhost:10000",
+            },
+            "retry": { "max_retries": 3 },
+        }))
+        .unwrap();
+        assert!(matches!(
+            config.snapshots_storage,
+            SnapshotsStorageConfig::Azure
+        ));
+        let azure_config = config.azure_config.unwrap();
+        assert_eq!(azure_config.container, "snapshots");
+        assert_eq!(azure_config.account.as_deref(), Some("qdrant"));
+        assert_eq!(azure_config.access_key, None);
+        assert_eq!(config.retry.max_retries, 3);
+        assert_eq!(config.retry.retry_timeout_sec, 180);
+    }
+}
@@ -11,9 +11,9 @@ use segment::common::anonymize::Anonymize;
 use segment::data_types::vectors::DEFAULT_VECTOR_NAME;
 use segment::index::sparse_index::sparse_index_config::{SparseIndexConfig, SparseIndexType};
 use segment::types::{
-    Distance, HnswConfig, Indexes, Payload, PayloadStorageType, QuantizationConfig, SegmentConfig,
-    SparseVectorDataConfig, StrictModeConfig, VectorDataConfig, VectorName, VectorNameBuf,
-    VectorStorageDatatype, VectorStorageType,
+    Distance, HnswConfig, Indexes, Payload, PayloadStorageType, PopulatePolicy, QuantizationConfig,
+    SegmentConfig, SparseVectorDataConfig, StrictModeConfig, VectorDataConfig, VectorName,
+    VectorNameBuf, VectorStorageDatatype, VectorStorageType,
 };
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
@@ -136,6 +136,12 @@ pub struct CollectionParams {
     #[serde(default, skip_serializing_if = "Option::is_none")]
     #[validate(nested)]
     pub sparse_vectors: Option<BTreeMap<VectorNameBuf, SparseVectorParams>>,
+    /// When memory mapped files of segments are read into the page cache on load.
+    /// Applied on the next start.
+    ///
+    /// Default is inferred from `on_disk` flags of vectors, HNSW graphs and payload indexes.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub populate_policy: Option<PopulatePolicy>,
 }
 
 impl CollectionParams {
@@ -168,6 +174,7 @@ impl CollectionParams {
             read_fan_out_delay_ms: _, // May be changed,
             on_disk_payload: _, // May be changed
             sparse_vectors,  // Parameters may be changes, but not the structure
+            populate_policy: _, // May be changed
         } = other;
 
         self.vectors.check_compatible(vectors)?;
@@ -335,6 +342,7 @@ impl CollectionParams {
             read_fan_out_delay_ms: None,
             on_disk_payload: default_on_disk_payload(),
             sparse_vectors: None,
+            populate_policy: None,
         }
     }
 
@@ -596,6 +604,10 @@ impl CollectionParams {
                                     .index
                                     .and_then(|index| index.datatype)
                                     .map(VectorStorageDatatype::from),
+                                max_posting_length: params
+                                    .index
+                                    .and_then(|index| index.max_posting_length),
+                                mmap_advice: params.index.and_then(|index| index.mmap_advice),
                             },
                             storage_type: params.storage_type(),
                             modifier: params.modifier,
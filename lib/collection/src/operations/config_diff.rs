@@ -5,9 +5,11 @@
 use std::num::NonZeroU32;
 
 use api::rest::MaxOptimizationThreads;
+use ordered_float::OrderedFloat;
 use schemars::JsonSchema;
 use segment::types::{
-    BinaryQuantization, HnswConfig, ProductQuantization, ScalarQuantization, StrictModeConfig,
+    BinaryQuantization, HnswConfig, MmapAdviceConfig, PopulatePolicy, ProductQuantization,
+    ScalarQuantization, StrictModeConfig,
 };
 use serde::{Deserialize, Serialize};
 use validator::{Validate, ValidationErrors};
@@ -73,6 +75,20 @@ pub struct HnswConfigDiff {
     /// Requires quantized vectors to be enabled. Multi-vectors are not supported.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub inline_storage: Option<bool>,
+    /// Target recall of searches without explicit `hnsw_ef`. If set, `ef` of each HNSW index
+    /// is tuned by periodically comparing sampled searches with exact search.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[validate(range(min = 0.0, max = 1.0))]
+    pub target_recall: Option<OrderedFloat<f64>>,
+    /// Number of edges per node on level 0 of the index graph, which contains all points.
+    /// Upper levels use `m`. If not set, `2 * m` is used.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[validate(range(min = 1))]
+    pub m0: Option<usize>,
+    /// Lock the HNSW graph links in RAM with `mlock`, so that they are not evicted from the
+    /// page cache under memory pressure. Requires a large enough `RLIMIT_MEMLOCK`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lock_in_ram: Option<bool>,
 }
 
 #[derive(Debug, Deserialize, Serialize, JsonSchema, Validate, Clone, PartialEq, Eq, Hash)]
@@ -102,6 +118,9 @@ pub struct CollectionParamsDiff {
     /// Note: those payload values that are involved in filtering and are indexed - remain in RAM.
     #[serde(default)]
     pub on_disk_payload: Option<bool>,
+    /// When memory mapped files of segments are read into the page cache on load.
+    /// Applied on the next start.
+    pub populate_policy: Option<PopulatePolicy>,
 }
 
 #[derive(Debug, Deserialize, Serialize, JsonSchema, Validate, Clone, PartialEq)]
@@ -168,6 +187,12 @@ pub struct OptimizersConfigDiff {
     /// Default is disabled.
     #[serde(default)]
     pub prevent_unoptimized: Option<bool>,
+
+    /// Expected access pattern of memory mapped files per segment component.
+    /// Components without a value use the global advice.
+    /// Applied to segments loaded or created afterwards.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mmap_advice: Option<MmapAdviceConfig>,
 }
 
 impl std::hash::Hash for OptimizersConfigDiff {
@@ -183,6 +208,7 @@ impl std::hash::Hash for OptimizersConfigDiff {
             flush_interval_sec,
             max_optimization_threads,
             prevent_unoptimized,
+            mmap_advice,
         } = self;
 
         deleted_threshold.map(f64::to_le_bytes).hash(state);
@@ -194,6 +220,7 @@ impl std::hash::Hash for OptimizersConfigDiff {
         flush_interval_sec.hash(state);
         max_optimization_threads.hash(state);
         prevent_unoptimized.hash(state);
+        mmap_advice.hash(state);
     }
 }
 
@@ -209,6 +236,9 @@ impl DiffConfig<HnswConfigDiff> for HnswConfig {
             on_disk,
             payload_m,
             inline_storage,
+            target_recall,
+            m0,
+            lock_in_ram,
         } = diff;
 
         HnswConfig {
@@ -219,6 +249,9 @@ impl DiffConfig<HnswConfigDiff> for HnswConfig {
             on_disk: on_disk.or(self.on_disk),
             payload_m: payload_m.or(self.payload_m),
             inline_storage: inline_storage.or(self.inline_storage),
+            target_recall: target_recall.or(self.target_recall),
+            m0: m0.or(self.m0),
+            lock_in_ram: lock_in_ram.or(self.lock_in_ram),
         }
     }
 }
@@ -233,6 +266,9 @@ impl DiffConfig<HnswConfigDiff> for HnswConfigDiff {
             on_disk,
             payload_m,
             inline_storage,
+            target_recall,
+            m0,
+            lock_in_ram,
         } = diff;
 
         HnswConfigDiff {
@@ -243,6 +279,9 @@ impl DiffConfig<HnswConfigDiff> for HnswConfigDiff {
             on_disk: on_disk.or(self.on_disk),
             payload_m: payload_m.or(self.payload_m),
             inline_storage: inline_storage.or(self.inline_storage),
+            target_recall: target_recall.or(self.target_recall),
+            m0: m0.or(self.m0),
+            lock_in_ram: lock_in_ram.or(self.lock_in_ram),
         }
     }
 }
@@ -259,6 +298,7 @@ impl DiffConfig<OptimizersConfigDiff> for OptimizersConfig {
             flush_interval_sec,
             max_optimization_threads,
             prevent_unoptimized,
+            mmap_advice,
         } = diff;
 
         OptimizersConfig {
@@ -273,6 +313,7 @@ impl DiffConfig<OptimizersConfigDiff> for OptimizersConfig {
             max_optimization_threads: max_optimization_threads
                 .map_or(self.max_optimization_threads, From::from),
             prevent_unoptimized: prevent_unoptimized.or(self.prevent_unoptimized),
+            mmap_advice: mmap_advice.or(self.mmap_advice),
         }
     }
 }
@@ -301,6 +342,7 @@ impl DiffConfig<CollectionParamsDiff> for CollectionParams {
             read_fan_out_factor,
             read_fan_out_delay_ms,
             on_disk_payload,
+            populate_policy,
         } = diff;
 
         CollectionParams {
@@ -314,6 +356,7 @@ impl DiffConfig<CollectionParamsDiff> for CollectionParams {
             sharding_method: self.sharding_method,
             sparse_vectors: self.sparse_vectors.clone(),
             vectors: self.vectors.clone(),
+            populate_policy: populate_policy.or(self.populate_policy),
         }
     }
 }
@@ -386,6 +429,9 @@ impl From<HnswConfig> for HnswConfigDiff {
             on_disk,
             payload_m,
             inline_storage,
+            target_recall,
+            m0,
+            lock_in_ram,
         } = config;
 
         HnswConfigDiff {
@@ -396,6 +442,9 @@ impl From<HnswConfig> for HnswConfigDiff {
             on_disk,
             payload_m,
             inline_storage,
+            target_recall,
+            m0,
+            lock_in_ram,
         }
     }
 }
@@ -428,6 +477,7 @@ impl From<CollectionParams> for CollectionParamsDiff {
             sharding_method: _,
             sparse_vectors: _,
             vectors: _,
+            populate_policy,
         } = config;
 
         CollectionParamsDiff {
@@ -436,6 +486,7 @@ impl From<CollectionParams> for CollectionParamsDiff {
             read_fan_out_factor,
             read_fan_out_delay_ms,
             on_disk_payload: Some(on_disk_payload),
+            populate_policy,
         }
     }
 }
@@ -453,6 +504,7 @@ impl From<OptimizersConfig> for OptimizersConfigDiff {
             flush_interval_sec,
             max_optimization_threads,
             prevent_unoptimized,
+            mmap_advice,
         } = config;
 
         Self {
@@ -466,6 +518,7 @@ impl From<OptimizersConfig> for OptimizersConfigDiff {
             flush_interval_sec: Some(flush_interval_sec),
             max_optimization_threads: max_optimization_threads.map(MaxOptimizationThreads::Threads),
             prevent_unoptimized,
+            mmap_advice,
         }
     }
 }
@@ -526,6 +579,7 @@ mod tests {
             read_fan_out_factor: None,
             read_fan_out_delay_ms: None,
             on_disk_payload: None,
+            populate_policy: None,
         };
 
         let new_params = params.update(&diff);
@@ -543,6 +597,26 @@ mod tests {
         assert_eq!(new_config.m, 32)
     }
 
+    #[test]
+    fn test_hnsw_m0_update() {
+        let base_config = HnswConfig::default();
+        assert_eq!(base_config.effective_m0(), base_config.m * 2);
+
+        let update: HnswConfigDiff = serde_json::from_str(r#"{ "m0": 48 }"#).unwrap();
+        let new_config = base_config.update(&update);
+        assert_eq!(new_config.m0, Some(48));
+        assert_eq!(new_config.effective_m0(), 48);
+        assert!(new_config.mismatch_requires_rebuild(&base_config));
+
+        // Explicit default of `m0` doesn't require rebuilding
+        let update: HnswConfigDiff = serde_json::from_str(r#"{ "m0": 32 }"#).unwrap();
+        assert!(
+            !base_config
+                .update(&update)
+                .mismatch_requires_rebuild(&base_config)
+        );
+    }
+
     #[test]
     fn test_optimizer_update() {
         let base_config = OptimizersConfig {
@@ -555,6 +629,7 @@ mod tests {
             flush_interval_sec: 30,
             max_optimization_threads: Some(1),
             prevent_unoptimized: None,
+            mmap_advice: None,
         };
         let update: OptimizersConfigDiff =
             serde_json::from_str(r#"{ "indexing_threshold": 10000 }"#).unwrap();
@@ -580,6 +655,7 @@ mod tests {
             flush_interval_sec: 30,
             max_optimization_threads: Some(1),
             prevent_unoptimized: None,
+            mmap_advice: None,
         };
 
         let update: OptimizersConfigDiff = serde_json::from_str(json_diff).unwrap();
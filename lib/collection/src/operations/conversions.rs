@@ -17,12 +17,14 @@ use api::rest::{
     BaseGroupRequest, LookupLocation, MaxOptimizationThreads, ShardKeyWithFallback, schema as rest,
 };
 use itertools::Itertools;
+use ordered_float::OrderedFloat;
 use segment::common::operation_error::OperationError;
 use segment::data_types::modifier::Modifier;
 use segment::data_types::vectors::{VectorInternal, VectorStructInternal};
+use segment::index::sparse_index::sparse_index_config::MmapAdvice;
 use segment::types::{
-    Distance, Filter, HnswConfig, MultiVectorConfig, QuantizationConfig, StrictModeConfigOutput,
-    WithPayloadInterface,
+    Distance, Filter, HnswConfig, MmapAdviceConfig, MultiVectorConfig, PopulatePolicy,
+    QuantizationConfig, StrictModeConfigOutput, WithPayloadInterface,
 };
 use shard::retrieve::record_internal::RecordInternal;
 use tonic::Status;
@@ -84,6 +86,61 @@ pub fn sharding_method_from_proto(sharding_method: i32) -> Result<ShardingMethod
     }
 }
 
+fn populate_policy_from_proto(populate_policy: i32) -> Result<PopulatePolicy, Status> {
+    api::grpc::qdrant::PopulatePolicy::try_from(populate_policy)
+        .map(PopulatePolicy::from)
+        .map_err(|err| {
+            Status::invalid_argument(format!(
+                "Cannot convert PopulatePolicy: {populate_policy}, error: {err}"
+            ))
+        })
+}
+
+fn mmap_advice_from_proto(advice: i32) -> Result<MmapAdvice, Status> {
+    api::grpc::qdrant::MmapAdvice::try_from(advice)
+        .map(MmapAdvice::from)
+        .map_err(|_| Status::invalid_argument(format!("Cannot convert mmap advice: {advice}")))
+}
+
+fn mmap_advice_config_from_proto(
+    config: api::grpc::qdrant::MmapAdviceConfig,
+) -> Result<MmapAdviceConfig, Status> {
+    let api::grpc::qdrant::MmapAdviceConfig {
+        vectors,
+        links,
+        payload_index,
+        sparse_index,
+        hugepages,
+    } = config;
+    Ok(MmapAdviceConfig {
+        vectors: vectors.map(mmap_advice_from_proto).transpose()?,
+        links: links.map(mmap_advice_from_proto).transpose()?,
+        payload_index: payload_index.map(mmap_advice_from_proto).transpose()?,
+        sparse_index: sparse_index.map(mmap_advice_from_proto).transpose()?,
+        hugepages,
+    })
+}
+
+fn mmap_advice_config_to_proto(config: MmapAdviceConfig) -> api::grpc::qdrant::MmapAdviceConfig {
+    let MmapAdviceConfig {
+        vectors,
+        links,
+        payload_index,
+        sparse_index,
+        hugepages,
+    } = config;
+    let to_proto = |advice: Option<MmapAdvice>| {
+        advice.map(|advice| api::grpc::qdrant::MmapAdvice::from(advice) as i32)
+    };
+    api::grpc::qdrant::MmapAdviceConfig {
+        vectors: to_proto(vectors),
+        links: to_proto(links),
+        payload_index: to_proto(payload_index),
+        sparse_index: to_proto(sparse_index),
+        hugepages,
+    }
+}
+
 pub fn write_ordering_to_proto(ordering: WriteOrdering) -> api::grpc::qdrant::WriteOrdering {
     api::grpc::qdrant::WriteOrdering {
         r#type: match ordering {
@@ -247,6 +304,9 @@ impl From<api::grpc::qdrant::HnswConfigDiff> for HnswConfigDiff {
             on_disk,
             payload_m,
             inline_storage,
+            target_recall,
+            m0,
+            lock_in_ram,
         } = value;
         Self {
             m: m.map(|v| v as usize),
@@ -256,6 +316,9 @@ impl From<api::grpc::qdrant::HnswConfigDiff> for HnswConfigDiff {
             on_disk,
             payload_m: payload_m.map(|v| v as usize),
             inline_storage,
+            target_recall: target_recall.map(OrderedFloat),
+            m0: m0.map(|v| v as usize),
+            lock_in_ram,
         }
     }
 }
@@ -270,6 +333,9 @@ impl From<HnswConfigDiff> for api::grpc::qdrant::HnswConfigDiff {
             on_disk,
             payload_m,
             inline_storage,
+            target_recall,
+            m0,
+            lock_in_ram,
         } = value;
         Self {
             m: m.map(|v| v as u64),
@@ -279,6 +345,9 @@ impl From<HnswConfigDiff> for api::grpc::qdrant::HnswConfigDiff {
             on_disk,
             payload_m: payload_m.map(|v| v as u64),
             inline_storage,
+            target_recall: target_recall.map(|OrderedFloat(v)| v),
+            m0: m0.map(|v| v as u64),
+            lock_in_ram,
         }
     }
 }
@@ -308,6 +377,7 @@ impl TryFrom<api::grpc::qdrant::CollectionParamsDiff> for CollectionParamsDiff {
             read_fan_out_factor,
             on_disk_payload,
             read_fan_out_delay_ms,
+            populate_policy,
         } = value;
         Ok(Self {
             replication_factor: replication_factor
@@ -326,6 +396,9 @@ impl TryFrom<api::grpc::qdrant::CollectionParamsDiff> for CollectionParamsDiff {
             read_fan_out_factor,
             read_fan_out_delay_ms,
             on_disk_payload,
+            populate_policy: populate_policy
+                .map(populate_policy_from_proto)
+                .transpose()?,
         })
     }
 }
@@ -345,6 +418,7 @@ impl TryFrom<api::grpc::qdrant::OptimizersConfigDiff> for OptimizersConfigDiff {
             deprecated_max_optimization_threads,
             max_optimization_threads,
             prevent_unoptimized,
+            mmap_advice,
         } = value;
         Ok(Self {
             deleted_threshold,
@@ -362,6 +436,7 @@ impl TryFrom<api::grpc::qdrant::OptimizersConfigDiff> for OptimizersConfigDiff {
                     .map(TryFrom::try_from)
                     .transpose()?),
             prevent_unoptimized,
+            mmap_advice: mmap_advice.map(mmap_advice_config_from_proto).transpose()?,
         })
     }
 }
@@ -420,6 +495,7 @@ impl From<CollectionInfo> for api::grpc::qdrant::CollectionInfo {
             flush_interval_sec,
             max_optimization_threads,
             prevent_unoptimized,
+            mmap_advice,
         } = optimizer_config;
 
         let HnswConfig {
@@ -430,6 +506,9 @@ impl From<CollectionInfo> for api::grpc::qdrant::CollectionInfo {
             on_disk,
             payload_m,
             inline_storage,
+            target_recall,
+            m0,
+            lock_in_ram,
         } = hnsw_config;
 
         let CollectionParams {
@@ -442,6 +521,7 @@ impl From<CollectionInfo> for api::grpc::qdrant::CollectionInfo {
             read_fan_out_factor,
             sharding_method,
             sparse_vectors,
+            populate_policy,
         } = params;
 
         api::grpc::qdrant::CollectionInfo {
@@ -505,6 +585,8 @@ impl From<CollectionInfo> for api::grpc::qdrant::CollectionInfo {
                         }
                     }),
                     read_fan_out_delay_ms,
+                    populate_policy: populate_policy
+                        .map(|policy| api::grpc::qdrant::PopulatePolicy::from(policy) as i32),
                 }),
                 hnsw_config: Some(api::grpc::qdrant::HnswConfigDiff {
                     m: Some(m as u64),
@@ -514,6 +596,9 @@ impl From<CollectionInfo> for api::grpc::qdrant::CollectionInfo {
                     on_disk,
                     payload_m: payload_m.map(|v| v as u64),
                     inline_storage,
+                    target_recall: target_recall.map(|OrderedFloat(v)| v),
+                    m0: m0.map(|v| v as u64),
+                    lock_in_ram,
                 }),
                 optimizer_config: Some(api::grpc::qdrant::OptimizersConfigDiff {
                     deleted_threshold: Some(deleted_threshold),
@@ -526,6 +611,7 @@ impl From<CollectionInfo> for api::grpc::qdrant::CollectionInfo {
                     deprecated_max_optimization_threads: max_optimization_threads.map(|x| x as u64),
                     max_optimization_threads: Some(From::from(max_optimization_threads)),
                     prevent_unoptimized,
+                    mmap_advice: mmap_advice.map(mmap_advice_config_to_proto),
                 }),
                 wal_config: wal_config.map(|wal_config| {
                     let WalConfig {
@@ -629,6 +715,7 @@ impl TryFrom<api::grpc::qdrant::OptimizersConfigDiff> for OptimizersConfig {
             deprecated_max_optimization_threads,
             max_optimization_threads,
             prevent_unoptimized,
+            mmap_advice,
         } = optimizer_config;
 
         let converted_max_optimization_threads: Option<usize> =
@@ -651,6 +738,7 @@ impl TryFrom<api::grpc::qdrant::OptimizersConfigDiff> for OptimizersConfig {
             flush_interval_sec: flush_interval_sec.unwrap_or_default(),
             max_optimization_threads: converted_max_optimization_threads,
             prevent_unoptimized,
+            mmap_advice: mmap_advice.map(mmap_advice_config_from_proto).transpose()?,
         })
     }
 }
@@ -723,6 +811,9 @@ impl TryFrom<api::grpc::qdrant::VectorParams> for VectorParams {
             on_disk,
             datatype,
             multivector_config,
+            direct_io,
+            compressed,
+            tiered,
         } = vector_params;
         Ok(Self {
             size: NonZeroU64::new(size).ok_or_else(|| {
@@ -738,6 +829,9 @@ impl TryFrom<api::grpc::qdrant::VectorParams> for VectorParams {
             multivector_config: multivector_config
                 .map(MultiVectorConfig::try_from)
                 .transpose()?,
+            direct_io,
+            compressed,
+            tiered,
         })
     }
 }
@@ -750,6 +844,7 @@ fn convert_datatype_from_proto(datatype: Option<i32>) -> Result<Option<Datatype>
                 api::grpc::qdrant::Datatype::Uint8 => Ok(Some(Datatype::Uint8)),
                 api::grpc::qdrant::Datatype::Float32 => Ok(Some(Datatype::Float32)),
                 api::grpc::qdrant::Datatype::Float16 => Ok(Some(Datatype::Float16)),
+                api::grpc::qdrant::Datatype::Bfloat16 => Ok(Some(Datatype::Bfloat16)),
                 api::grpc::qdrant::Datatype::Default => Ok(None),
             }
         } else {
@@ -789,10 +884,27 @@ impl TryFrom<api::grpc::qdrant::SparseVectorParams> for SparseVectorParams {
         Ok(Self {
             index: index
                 .map(|index_config| -> Result<_, Status> {
+                    if index_config.max_posting_length == Some(0) {
+                        return Err(Status::invalid_argument(
+                            "max_posting_length must be greater than 0",
+                        ));
+                    }
                     Ok(SparseIndexParams {
                         full_scan_threshold: index_config.full_scan_threshold.map(|v| v as usize),
                         on_disk: index_config.on_disk,
                         datatype: convert_datatype_from_proto(index_config.datatype)?,
+                        max_posting_length: index_config.max_posting_length.map(|v| v as usize),
+                        mmap_advice: index_config
+                            .mmap_advice
+                            .map(|advice| {
+                                api::grpc::qdrant::MmapAdvice::try_from(advice).map_err(|_| {
+                                    Status::invalid_argument(format!(
+                                        "Cannot convert mmap advice: {advice}"
+                                    ))
+                                })
+                            })
+                            .transpose()?
+                            .map(MmapAdvice::from),
                     })
                 })
                 .transpose()?,
@@ -814,11 +926,16 @@ impl From<SparseVectorParams> for api::grpc::qdrant::SparseVectorParams {
                     full_scan_threshold,
                     on_disk,
                     datatype,
+                    max_posting_length,
+                    mmap_advice,
                 } = index_config;
                 api::grpc::qdrant::SparseIndexConfig {
                     full_scan_threshold: full_scan_threshold.map(|v| v as u64),
                     on_disk,
                     datatype: datatype.map(|dt| api::grpc::qdrant::Datatype::from(dt).into()),
+                    max_posting_length: max_posting_length.map(|v| v as u64),
+                    mmap_advice: mmap_advice
+                        .map(|advice| api::grpc::qdrant::MmapAdvice::from(advice).into()),
                 }
             }),
             modifier: modifier.map(|modifier| api::grpc::qdrant::Modifier::from(modifier) as i32),
@@ -1372,6 +1489,9 @@ impl From<VectorParams> for api::grpc::qdrant::VectorParams {
             on_disk,
             datatype,
             multivector_config,
+            direct_io,
+            compressed,
+            tiered,
         } = value;
         api::grpc::qdrant::VectorParams {
             size: size.get(),
@@ -1387,6 +1507,9 @@ impl From<VectorParams> for api::grpc::qdrant::VectorParams {
             on_disk,
             datatype: datatype.map(|dt| api::grpc::qdrant::Datatype::from(dt).into()),
             multivector_config: multivector_config.map(api::grpc::qdrant::MultiVectorConfig::from),
+            direct_io,
+            compressed,
+            tiered,
         }
     }
 }
@@ -1397,6 +1520,7 @@ impl From<Datatype> for api::grpc::qdrant::Datatype {
             Datatype::Float32 => api::grpc::qdrant::Datatype::Float32,
             Datatype::Uint8 => api::grpc::qdrant::Datatype::Uint8,
             Datatype::Float16 => api::grpc::qdrant::Datatype::Float16,
+            Datatype::Bfloat16 => api::grpc::qdrant::Datatype::Bfloat16,
         }
     }
 }
@@ -1846,6 +1970,7 @@ impl TryFrom<api::grpc::qdrant::CollectionConfig> for CollectionConfig {
                         sharding_method,
                         sparse_vectors_config,
                         read_fan_out_delay_ms,
+                        populate_policy,
                     } = params;
                     CollectionParams {
                         vectors: match vectors_config {
@@ -1903,6 +2028,9 @@ impl TryFrom<api::grpc::qdrant::CollectionConfig> for CollectionConfig {
                             .map(sharding_method_from_proto)
                             .transpose()?,
                         read_fan_out_delay_ms,
+                        populate_policy: populate_policy
+                            .map(populate_policy_from_proto)
+                            .transpose()?,
                     }
                 }
             },
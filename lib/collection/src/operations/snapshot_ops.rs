@@ -11,6 +11,7 @@ use url::Url;
 use validator::Validate;
 
 use crate::operations::types::CollectionResult;
+use crate::shards::shard::ShardId;
 
 /// Defines source of truth for snapshot recovery:
 ///
@@ -82,6 +83,11 @@ pub struct SnapshotRecover {
     /// Optional API key used when fetching the snapshot from a remote URL.
     #[serde(default)]
     pub api_key: Option<String>,
+
+    /// Restore only these shards into the existing collection, other shards are kept as is.
+    /// By default, all shards are restored.
+    #[serde(default)]
+    pub shards: Option<Vec<ShardId>>,
 }
 
 fn snapshot_description_example() -> SnapshotDescription {
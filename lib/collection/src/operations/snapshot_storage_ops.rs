@@ -5,12 +5,14 @@ use common::budget::ResourceBudget;
 use fs_err as fs;
 use fs_err::{File, tokio as tokio_fs};
 use futures::StreamExt;
-use object_store::{ObjectStoreExt, WriteMultipart};
+use object_store::{ObjectStoreExt, PutPayload, WriteMultipart};
 use segment::common::BYTES_IN_MB;
-use tokio::io::AsyncWriteExt;
+use sha2::{Digest as _, Sha256};
+use tokio::io::{AsyncRead, AsyncReadExt as _, AsyncWriteExt};
 
-use super::snapshot_ops::SnapshotDescription;
+use super::snapshot_ops::{SnapshotDescription, get_checksum_path};
 use super::types::{CollectionError, CollectionResult};
+use crate::common::sha_256::{hash_file, hashes_equal};
 
 pub(crate) fn trim_dot_slash(path: &Path) -> CollectionResult<object_store::path::Path> {
     // Get file name by trimming the path.
@@ -54,7 +56,7 @@ pub async fn get_snapshot_description(
     })?)?;
     let size = file_meta.size;
     let last_modified = file_meta.last_modified.naive_local();
-    let checksum = None;
+    let checksum = get_checksum(client, path).await?;
 
     Ok(SnapshotDescription {
         name,
@@ -99,6 +101,47 @@ pub async fn get_appropriate_chunk_size(local_source_path: &Path) -> CollectionR
     Ok(DEFAULT_CHUNK_SIZE)
 }
 
+/// Multipart upload, which is aborted if it is dropped before being finished, e.g. on an error or
+/// when the uploading future is cancelled. Otherwise uploaded parts are left in the storage.
+struct AbortOnDrop(Option<WriteMultipart>);
+
+impl AbortOnDrop {
+    fn new(write: WriteMultipart) -> Self {
+        Self(Some(write))
+    }
+
+    fn write(&mut self) -> &mut WriteMultipart {
+        self.0.as_mut().expect("upload is taken only once finished")
+    }
+
+    async fn finish(mut self) -> object_store::Result<()> {
+        // In-flight parts may still fail, keep the upload to abort it in that case
+        self.write().wait_for_capacity(0).await?;
+
+        // Completing the upload aborts it by itself on failure
+        let write = self.0.take().expect("upload is taken only once finished");
+        write.finish().await?;
+        Ok(())
+    }
+}
+
+impl Drop for AbortOnDrop {
+    fn drop(&mut self) {
+        let Some(write) = self.0.take() else {
+            return;
+        };
+        let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+            log::warn!("Failed to abort multipart upload, no runtime to run it on");
+            return;
+        };
+        runtime.spawn(async move {
+            if let Err(err) = write.abort().await {
+                log::warn!("Failed to abort multipart upload: {err}");
+            }
+        });
+    }
+}
+
 pub async fn multipart_upload(
     client: &dyn object_store::ObjectStore,
     source_path: &Path,
@@ -111,7 +154,7 @@ pub async fn multipart_upload(
         .map_err(|e| CollectionError::service_error(format!("Failed to put multipart: {e}")))?;
 
     let chunk_size: usize = get_appropriate_chunk_size(source_path).await?;
-    let mut write = WriteMultipart::new_with_chunk_size(upload, chunk_size);
+    let mut write = AbortOnDrop::new(WriteMultipart::new_with_chunk_size(upload, chunk_size));
     let file = File::open(source_path)?;
     let mut reader = BufReader::new(file);
     let mut buffer = vec![0u8; chunk_size];
@@ -124,7 +167,8 @@ pub async fn multipart_upload(
     // Note:
     //  1. write.write() is sync but a worker thread is spawned internally.
     //  2. write.finish() will wait for all the worker threads to finish.
-    while let Ok(bytes_read) = reader.read(&mut buffer) {
+    loop {
+        let bytes_read = reader.read(&mut buffer)?;
         if bytes_read == 0 {
             break;
         }
@@ -132,13 +176,14 @@ pub async fn multipart_upload(
 
         // Wait for capacity before writing the buffer
         write
+            .write()
             .wait_for_capacity(max_concurrency)
             .await
             .map_err(|e| {
                 CollectionError::service_error(format!("Failed to wait for capacity: {e}"))
             })?;
 
-        write.write(buffer);
+        write.write().write(buffer);
     }
     write
         .finish() //  2. write.finish() will wait for all the worker threads to finish.
@@ -148,6 +193,71 @@ pub async fn multipart_upload(
     Ok(())
 }
 
+/// Upload snapshot data from `reader`, which size is not known in advance.
+///
+/// SHA-256 checksum of the data is computed while uploading. It is stored once all data is
+/// uploaded, but before the upload is completed, so that the snapshot never becomes available
+/// without its checksum. Returns the checksum.
+pub async fn multipart_upload_reader(
+    client: &dyn object_store::ObjectStore,
+    mut reader: impl AsyncRead + Unpin,
+    target_path: &Path,
+) -> CollectionResult<String> {
+    /// Up to 10,000 parts, so up to 500GB in total
+    const CHUNK_SIZE: usize = 50 * 1024 * 1024;
+
+    let s3_path = trim_dot_slash(target_path)?;
+    let upload = client
+        .put_multipart(&s3_path)
+        .await
+        .map_err(|e| CollectionError::service_error(format!("Failed to put multipart: {e}")))?;
+
+    let mut write = AbortOnDrop::new(WriteMultipart::new_with_chunk_size(upload, CHUNK_SIZE));
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0u8; BYTES_IN_MB];
+
+    let cpu_budget = ResourceBudget::default();
+    // Cap max concurrency to avoid saturating the network on high core count
+    let max_concurrency = std::cmp::min(cpu_budget.available_cpu_budget(), 8);
+
+    loop {
+        let bytes_read = reader.read(&mut buffer).await.map_err(|err| {
+            CollectionError::service_error(format!("Failed to read snapshot data: {err}"))
+        })?;
+        if bytes_read == 0 {
+            break;
+        }
+        let buffer = &buffer[..bytes_read];
+        hasher.update(buffer);
+
+        write
+            .write()
+            .wait_for_capacity(max_concurrency)
+            .await
+            .map_err(|e| {
+                CollectionError::service_error(format!("Failed to wait for capacity: {e}"))
+            })?;
+
+        write.write().write(buffer);
+    }
+
+    let checksum = format!("{:x}", hasher.finalize());
+    put_checksum(client, target_path, checksum.clone()).await?;
+
+    if let Err(err) = write.finish().await {
+        // Snapshot is not available, don't leave its checksum behind
+        let checksum_path = trim_dot_slash(&get_checksum_path(target_path))?;
+        if let Err(err) = client.delete(&checksum_path).await {
+            log::warn!("Failed to delete checksum of failed snapshot upload: {err}");
+        }
+        return Err(CollectionError::service_error(format!(
+            "Failed to finish upload: {err}"
+        )));
+    }
+
+    Ok(checksum)
+}
+
 pub async fn list_snapshot_descriptions(
     client: &dyn object_store::ObjectStore,
     directory: &Path,
@@ -162,6 +272,11 @@ pub async fn list_snapshot_descriptions(
         .transpose()
         .map_err(|e| CollectionError::service_error(format!("Failed to list snapshots: {e}")))?
     {
+        // Skip checksums and other non-snapshot objects
+        if meta.location.extension() != Some("snapshot") {
+            continue;
+        }
+
         snapshots.push(SnapshotDescription {
             name: get_filename(meta.location.as_ref())?,
             creation_time: Some(meta.last_modified.naive_local()),
@@ -189,9 +304,49 @@ pub async fn delete_snapshot(
         .delete(&s3_path)
         .await
         .map_err(|e| CollectionError::service_error(format!("Failed to delete snapshot: {e}")))?;
+
+    // We might not have a checksum for the snapshot, ignore deletion errors in that case
+    let checksum_path = trim_dot_slash(&get_checksum_path(path))?;
+    if let Err(err) = client.delete(&checksum_path).await {
+        log::warn!("Failed to delete checksum of snapshot, ignoring: {err}");
+    }
+
     Ok(true)
 }
 
+/// Store SHA-256 `checksum` of the snapshot at `path` next to it.
+pub async fn put_checksum(
+    client: &dyn object_store::ObjectStore,
+    path: &Path,
+    checksum: String,
+) -> CollectionResult<()> {
+    let checksum_path = trim_dot_slash(&get_checksum_path(path))?;
+    client
+        .put(&checksum_path, PutPayload::from(checksum))
+        .await
+        .map_err(|e| CollectionError::service_error(format!("Failed to put checksum: {e}")))?;
+    Ok(())
+}
+
+/// SHA-256 checksum of the snapshot at `path`, if it was stored.
+pub async fn get_checksum(
+    client: &dyn object_store::ObjectStore,
+    path: &Path,
+) -> CollectionResult<Option<String>> {
+    let checksum_path = trim_dot_slash(&get_checksum_path(path))?;
+    let checksum = match client.get(&checksum_path).await {
+        Ok(result) => result.bytes().await,
+        Err(object_store::Error::NotFound { .. }) => return Ok(None),
+        Err(e) => Err(e),
+    }
+    .map_err(|e| CollectionError::service_error(format!("Failed to get checksum: {e}")))?;
+
+    let checksum = String::from_utf8(checksum.to_vec()).map_err(|e| {
+        CollectionError::object_storage_error(format!("Invalid checksum of snapshot: {e}"))
+    })?;
+    Ok(Some(checksum.trim().to_string()))
+}
+
 pub async fn download_snapshot(
     client: &dyn object_store::ObjectStore,
     path: &Path,
@@ -254,5 +409,77 @@ pub async fn download_snapshot(
             total_size
         )));
     }
+
+    // check checksum, if it was stored with the snapshot
+    if let Some(expected_checksum) = get_checksum(client, path).await? {
+        let checksum = hash_file(target_path).await?;
+        if !hashes_equal(&checksum, &expected_checksum) {
+            // Don't leave corrupted snapshot behind
+            tokio_fs::remove_file(target_path).await?;
+            return Err(CollectionError::service_error(format!(
+                "Downloaded snapshot checksum does not match the stored checksum: \
+                 {checksum} != {expected_checksum}",
+            )));
+        }
+    }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use object_store::memory::InMemory;
+    use tempfile::Builder;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_list_snapshots_skips_other_objects() {
+        let client = InMemory::new();
+        for key in ["dir/a.snapshot", "dir/a.snapshot.checksum", "dir/notes.txt"] {
+            client
+                .put(&key.into(), PutPayload::from_static(b"data"))
+                .await
+                .unwrap();
+        }
+
+        let snapshots = list_snapshot_descriptions(&client, Path::new("./dir"))
+            .await
+            .unwrap();
+        let names: Vec<_> = snapshots.into_iter().map(|s| s.name).collect();
+        assert_eq!(names, ["a.snapshot"]);
+    }
+
+    #[tokio::test]
+    async fn test_checksum_round_trip() {
+        let client = InMemory::new();
+        let dir = Builder::new().prefix("snapshots").tempdir().unwrap();
+        let data = vec![42u8; 3 * BYTES_IN_MB + 5];
+        let path = Path::new("./dir/a.snapshot");
+
+        let checksum = multipart_upload_reader(&client, data.as_slice(), path)
+            .await
+            .unwrap();
+        assert_eq!(checksum, format!("{:x}", Sha256::digest(&data)));
+
+        let description = get_snapshot_description(&client, path).await.unwrap();
+        assert_eq!(description.size, data.len() as u64);
+        assert_eq!(description.checksum, Some(checksum));
+
+        let target_path = dir.path().join("a.snapshot");
+        download_snapshot(&client, path, &target_path)
+            .await
+            .unwrap();
+        assert_eq!(fs::read(&target_path).unwrap(), data);
+
+        // Corrupted snapshot is not left behind
+        put_checksum(&client, path, "0123abc".to_string())
+            .await
+            .unwrap();
+        assert!(
+            download_snapshot(&client, path, &target_path)
+                .await
+                .is_err()
+        );
+        assert!(!target_path.exists());
+    }
+}
@@ -25,6 +25,7 @@ use segment::common::operation_error::{CancelledError, OperationError};
 use segment::data_types::groups::GroupId;
 use segment::data_types::modifier::Modifier;
 use segment::data_types::vectors::{DEFAULT_VECTOR_NAME, DenseVector};
+use segment::index::sparse_index::sparse_index_config::MmapAdvice;
 use segment::types::{
     Distance, Filter, HnswConfig, MultiVectorConfig, Payload, PayloadIndexInfo, PayloadKeyType,
     PointIdType, QuantizationConfig, SearchParams, SeqNumberType, ShardKey,
@@ -1501,6 +1502,7 @@ pub enum Datatype {
     Float32,
     Uint8,
     Float16,
+    Bfloat16,
 }
 
 impl From<Datatype> for VectorStorageDatatype {
@@ -1509,6 +1511,7 @@ impl From<Datatype> for VectorStorageDatatype {
             Datatype::Float32 => VectorStorageDatatype::Float32,
             Datatype::Uint8 => VectorStorageDatatype::Uint8,
             Datatype::Float16 => VectorStorageDatatype::Float16,
+            Datatype::Bfloat16 => VectorStorageDatatype::Bfloat16,
         }
     }
 }
@@ -1552,10 +1555,36 @@ pub struct VectorParams {
     ///   2 bytes.
     /// - For `uint8` datatype - vectors are stored as unsigned 8-bit integers, 1 byte.
     ///   It expects vector elements to be in range `[0, 255]`.
+    /// - For `bfloat16` datatype - vectors are stored as brain floating point numbers, 2 bytes.
+    ///   Less precise than `float16`, but has the same range as `float32`.
     pub datatype: Option<Datatype>,
 
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub multivector_config: Option<MultiVectorConfig>,
+
+    /// If true, on-disk vectors are read with `O_DIRECT` in searches, bypassing the page cache.
+    /// Avoids evicting other data from the page cache during full scans of very large storages.
+    /// Falls back to mmap if the filesystem doesn't support `O_DIRECT`.
+    /// Only has effect together with `on_disk`.
+    /// Default: false
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub direct_io: Option<bool>,
+
+    /// If true, optimized on-disk vectors are kept compressed with zstd, and are decompressed on
+    /// demand. Frequently read vectors are cached decompressed in RAM, up to 64 MiB per vector storage.
+    /// Intended for original vectors which are rarely read, like with quantization and rescoring.
+    /// Only has effect together with `on_disk`.
+    /// Default: false
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compressed: Option<bool>,
+
+    /// If true, recently accessed parts of optimized on-disk vectors are locked in RAM, the rest
+    /// is read from disk. The fraction kept in RAM is configured globally.
+    /// A middle ground between in-memory and on-disk vectors.
+    /// Only has effect together with `on_disk`.
+    /// Default: false
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tiered: Option<bool>,
 }
 
 /// Validate the value is in `[1, 65536]` or `None`.
@@ -1578,6 +1607,7 @@ fn is_hnsw_diff_empty(hnsw_config: &Option<HnswConfigDiff>) -> bool {
 pub struct SparseVectorParams {
     /// Custom params for index. If none - values from collection configuration are used.
     #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[validate(nested)]
     pub index: Option<SparseIndexParams>,
 
     /// Configures addition value modifications for sparse vectors.
@@ -1594,7 +1624,18 @@ impl SparseVectorParams {
 
 /// Configuration for sparse inverted index.
 #[derive(
-    Debug, Hash, Deserialize, Serialize, JsonSchema, Anonymize, Copy, Clone, PartialEq, Eq, Default,
+    Debug,
+    Hash,
+    Deserialize,
+    Serialize,
+    JsonSchema,
+    Validate,
+    Anonymize,
+    Copy,
+    Clone,
+    PartialEq,
+    Eq,
+    Default,
 )]
 #[serde(rename_all = "snake_case")]
 pub struct SparseIndexParams {
@@ -1613,12 +1654,29 @@ pub struct SparseIndexParams {
     /// - For `float32` datatype - vectors are stored as single-precision floating point numbers,
     ///   4 bytes.
     /// - For `float16` datatype - vectors are stored as half-precision floating point numbers,
-    ///   2 bytes.
+    ///   2 bytes. It halves the index size, weights keep about 3 significant digits, so results
+    ///   with very close scores may be ranked differently.
     /// - For `uint8` datatype - vectors are quantized to unsigned 8-bit integers, 1 byte.
     ///   Quantization to fit byte range `[0, 255]` happens during indexing automatically, so the
     ///   actual vector data does not need to conform to this range.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub datatype: Option<Datatype>,
+    /// Keep only this many elements with the largest weights in each posting list of optimized
+    /// segments. Search becomes approximate, as vectors with small weights in a dimension are not
+    /// found through it, but index size and search latency are bounded. Default: no limit
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[validate(range(min = 1))]
+    #[anonymize(false)]
+    pub max_posting_length: Option<usize>,
+    /// Access pattern hint for memory mapped index files of optimized segments.
+    ///
+    /// - `normal` - no special treatment.
+    /// - `random` - disables read-ahead, suits searches skipping through large posting lists.
+    /// - `sequential` - aggressive read-ahead, suits full scans of posting lists.
+    ///
+    /// Default: `normal`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mmap_advice: Option<MmapAdvice>,
 }
 
 impl SparseIndexParams {
@@ -1627,12 +1685,16 @@ impl SparseIndexParams {
             full_scan_threshold,
             on_disk,
             datatype,
+            max_posting_length,
+            mmap_advice,
         } = other;
 
         self.full_scan_threshold
             .replace_if_some(full_scan_threshold);
         self.on_disk.replace_if_some(on_disk);
         self.datatype.replace_if_some(datatype);
+        self.max_posting_length.replace_if_some(max_posting_length);
+        self.mmap_advice.replace_if_some(mmap_advice);
     }
 }
 
@@ -1853,6 +1915,9 @@ impl From<&VectorParams> for VectorParamsBase {
             on_disk: _,
             datatype: _,
             multivector_config: _,
+            direct_io: _,
+            compressed: _,
+            tiered: _,
         } = params;
         Self {
             size: size.get() as _, // TODO!?
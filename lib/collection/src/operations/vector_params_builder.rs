@@ -20,6 +20,9 @@ impl VectorParamsBuilder {
                 on_disk: None,
                 datatype: None,
                 multivector_config: None,
+                direct_io: None,
+                compressed: None,
+                tiered: None,
             },
         }
     }
@@ -49,6 +52,21 @@ impl VectorParamsBuilder {
         self
     }
 
+    pub fn with_direct_io(mut self, direct_io: bool) -> Self {
+        self.vector_params.direct_io = Some(direct_io);
+        self
+    }
+
+    pub fn with_compressed(mut self, compressed: bool) -> Self {
+        self.vector_params.compressed = Some(compressed);
+        self
+    }
+
+    pub fn with_tiered(mut self, tiered: bool) -> Self {
+        self.vector_params.tiered = Some(tiered);
+        self
+    }
+
     pub fn build(self) -> VectorParams {
         self.vector_params
     }
@@ -5,7 +5,7 @@ use fs_err as fs;
 use schemars::JsonSchema;
 use segment::common::anonymize::Anonymize;
 use segment::index::hnsw_index::num_rayon_threads;
-use segment::types::{HnswConfig, HnswGlobalConfig, QuantizationConfig};
+use segment::types::{HnswConfig, HnswGlobalConfig, MmapAdviceConfig, QuantizationConfig};
 use serde::{Deserialize, Serialize};
 use validator::Validate;
 
@@ -90,6 +90,12 @@ pub struct OptimizersConfig {
     /// Default is disabled.
     #[serde(default)]
     pub prevent_unoptimized: Option<bool>,
+
+    /// Expected access pattern of memory mapped files per segment component.
+    /// Components without a value use the global advice.
+    /// Applied to segments loaded or created afterwards.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mmap_advice: Option<MmapAdviceConfig>,
 }
 
 impl OptimizersConfig {
@@ -106,6 +112,7 @@ impl OptimizersConfig {
             flush_interval_sec: 60,
             max_optimization_threads: Some(0),
             prevent_unoptimized: None,
+            mmap_advice: None,
         }
     }
 
@@ -180,47 +187,60 @@ pub fn build_optimizers(
     let segments_path = shard_path.join(SEGMENTS_PATH);
     let temp_segments_path = shard_path.join(TEMP_SEGMENTS_PATH);
     let threshold_config = optimizers_config.optimizer_thresholds(num_indexing_threads);
+    let mmap_advice = optimizers_config.mmap_advice.unwrap_or_default();
 
     Arc::new(vec![
-        Arc::new(MergeOptimizer::new(
-            optimizers_config.get_number_segments(),
-            threshold_config,
-            segments_path.clone(),
-            temp_segments_path.clone(),
-            collection_params.clone(),
-            *hnsw_config,
-            hnsw_global_config.clone(),
-            quantization_config.clone(),
-        )),
-        Arc::new(IndexingOptimizer::new(
-            optimizers_config.get_number_segments(),
-            threshold_config,
-            segments_path.clone(),
-            temp_segments_path.clone(),
-            collection_params.clone(),
-            *hnsw_config,
-            hnsw_global_config.clone(),
-            quantization_config.clone(),
-        )),
-        Arc::new(VacuumOptimizer::new(
-            optimizers_config.deleted_threshold,
-            optimizers_config.vacuum_min_vector_number,
-            threshold_config,
-            segments_path.clone(),
-            temp_segments_path.clone(),
-            collection_params.clone(),
-            *hnsw_config,
-            hnsw_global_config.clone(),
-            quantization_config.clone(),
-        )),
-        Arc::new(ConfigMismatchOptimizer::new(
-            threshold_config,
-            segments_path,
-            temp_segments_path,
-            collection_params.clone(),
-            *hnsw_config,
-            hnsw_global_config.clone(),
-            quantization_config.clone(),
-        )),
+        Arc::new(
+            MergeOptimizer::new(
+                optimizers_config.get_number_segments(),
+                threshold_config,
+                segments_path.clone(),
+                temp_segments_path.clone(),
+                collection_params.clone(),
+                *hnsw_config,
+                hnsw_global_config.clone(),
+                quantization_config.clone(),
+            )
+            .with_mmap_advice(mmap_advice),
+        ),
+        Arc::new(
+            IndexingOptimizer::new(
+                optimizers_config.get_number_segments(),
+                threshold_config,
+                segments_path.clone(),
+                temp_segments_path.clone(),
+                collection_params.clone(),
+                *hnsw_config,
+                hnsw_global_config.clone(),
+                quantization_config.clone(),
+            )
+            .with_mmap_advice(mmap_advice),
+        ),
+        Arc::new(
+            VacuumOptimizer::new(
+                optimizers_config.deleted_threshold,
+                optimizers_config.vacuum_min_vector_number,
+                threshold_config,
+                segments_path.clone(),
+                temp_segments_path.clone(),
+                collection_params.clone(),
+                *hnsw_config,
+                hnsw_global_config.clone(),
+                quantization_config.clone(),
+            )
+            .with_mmap_advice(mmap_advice),
+        ),
+        Arc::new(
+            ConfigMismatchOptimizer::new(
+                threshold_config,
+                segments_path,
+                temp_segments_path,
+                collection_params.clone(),
+                *hnsw_config,
+                hnsw_global_config.clone(),
+                quantization_config.clone(),
+            )
+            .with_mmap_advice(mmap_advice),
+        ),
     ])
 }
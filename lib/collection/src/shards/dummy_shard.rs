@@ -61,6 +61,7 @@ impl DummyShard {
             optimizations: Default::default(),
             async_scorer: None,
             indexed_only_excluded_vectors: None,
+            sparse_postings_by_name: None,
             update_queue: None,
         }
     }
@@ -25,6 +25,7 @@ use tokio::sync::Mutex;
 use super::shard::ShardId;
 use super::update_tracker::UpdateTracker;
 use crate::collection_manager::optimizers::TrackerLog;
+use crate::common::snapshot_point::SnapshotPointGuard;
 use crate::hash_ring::HashRingRouter;
 use crate::operations::point_ops::{
     PointInsertOperationsInternal, PointOperations, PointStructPersisted, PointSyncOperation,
@@ -361,9 +362,10 @@ impl ForwardProxyShard {
         format: SnapshotFormat,
         manifest: Option<SnapshotManifest>,
         save_wal: bool,
+        snapshot_point: Option<SnapshotPointGuard>,
     ) -> CollectionResult<impl Future<Output = CollectionResult<()>> + use<>> {
         self.wrapped_shard
-            .get_snapshot_creator(temp_path, tar, format, manifest, save_wal)
+            .get_snapshot_creator(temp_path, tar, format, manifest, save_wal, snapshot_point)
             .await
     }
 
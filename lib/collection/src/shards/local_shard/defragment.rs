@@ -0,0 +1,40 @@
+use parking_lot::RwLockUpgradableReadGuard;
+
+use crate::operations::types::CollectionResult;
+use crate::shards::local_shard::LocalShard;
+
+impl LocalShard {
+    /// Reclaim space of deleted points in appendable segments in place, without waiting for
+    /// optimizers to rebuild them.
+    /// Returns amount of reclaimed points.
+    pub async fn defragment(&self) -> CollectionResult<usize> {
+        let segments = self.segments.clone();
+
+        let reclaimed = tokio::task::spawn_blocking(move || -> CollectionResult<usize> {
+            // Block updates and hold the segment holder while defragmenting, so optimizers can't
+            // pick up segments whose internal IDs are being changed
+            let _updates_guard = segments.acquire_updates_lock();
+            let segments = segments.read();
+
+            let mut reclaimed = 0;
+            for (segment_id, segment) in segments.iter_original() {
+                let segment = segment.upgradable_read();
+                if !segment.is_defragmentable() {
+                    continue;
+                }
+
+                let mut segment = RwLockUpgradableReadGuard::upgrade(segment);
+                let segment_reclaimed = segment.defragment()?;
+                if segment_reclaimed > 0 {
+                    log::debug!("Reclaimed {segment_reclaimed} points in segment {segment_id}");
+                }
+                reclaimed += segment_reclaimed;
+            }
+
+            Ok(reclaimed)
+        })
+        .await??;
+
+        Ok(reclaimed)
+    }
+}
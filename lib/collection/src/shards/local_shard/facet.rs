@@ -1,4 +1,4 @@
-use std::collections::BTreeSet;
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::sync::Arc;
 use std::time::Duration;
 
@@ -8,7 +8,7 @@ use futures::future;
 use futures::future::try_join_all;
 use itertools::{Itertools, process_results};
 use segment::data_types::facets::{FacetParams, FacetValue, FacetValueHit};
-use segment::types::{Condition, FieldCondition, Filter, Match};
+use segment::types::{Condition, FieldCondition, Filter, Match, PayloadSchemaType};
 use shard::common::stopping_guard::StoppingGuard;
 use tokio::runtime::Handle;
 use tokio::time::error::Elapsed;
@@ -100,6 +100,20 @@ impl LocalShard {
         // To do this we will perform exact counts for each of the values in the field.
         let instant = std::time::Instant::now();
 
+        // Values of full-text fields are tokens, which can't be matched exactly by a filter
+        let is_text_field = self
+            .payload_index_schema
+            .read()
+            .schema
+            .get(&request.key)
+            .is_some_and(|schema| schema.kind() == PayloadSchemaType::Text);
+
+        if is_text_field {
+            return self
+                .exact_text_facet(request, search_runtime_handle, timeout, hw_measurement_acc)
+                .await;
+        }
+
         // Get unique values for the field
         let unique_values = self
             .unique_values(
@@ -144,6 +158,76 @@ impl LocalShard {
         Ok(hits)
     }
 
+    /// Returns tokens of a full-text field with exact counts.
+    ///
+    /// Points of each token are collected from all segments and deduplicated, as the same point
+    /// can be in different segments if it has different versions.
+    async fn exact_text_facet(
+        &self,
+        request: Arc<FacetParams>,
+        handle: &Handle,
+        timeout: Duration,
+        hw_measurement_acc: HwMeasurementAcc,
+    ) -> CollectionResult<Vec<FacetValueHit>> {
+        let stopping_guard = StoppingGuard::new();
+
+        let spawn_read = |segment: LockedSegment, hw_counter: &HardwareCounterCell| {
+            let request = Arc::clone(&request);
+
+            let is_stopped = stopping_guard.get_is_stopped();
+
+            let hw_counter = hw_counter.fork();
+            let task = handle.spawn_blocking(move || {
+                let get_segment = segment.get();
+                let read_segment = get_segment.read();
+
+                read_segment.facet_point_ids(
+                    &request.key,
+                    request.filter.as_ref(),
+                    &is_stopped,
+                    &hw_counter,
+                )
+            });
+            AbortOnDropHandle::new(task)
+        };
+
+        let hw_counter = hw_measurement_acc.get_counter_cell();
+
+        let all_reads = {
+            let segments_lock = self.segments.read();
+
+            tokio::time::timeout(
+                timeout,
+                try_join_all(
+                    segments_lock
+                        .non_appendable_then_appendable_segments()
+                        .map(|segment| spawn_read(segment, &hw_counter)),
+                ),
+            )
+        }
+        .await
+        .map_err(|_: Elapsed| CollectionError::timeout(timeout, "facet"))??;
+
+        let points_per_value = process_results(all_reads, |reads| {
+            reads.fold(HashMap::<_, HashSet<_>>::new(), |mut acc, map| {
+                map.into_iter().for_each(|(value, point_ids)| {
+                    acc.entry(value).or_default().extend(point_ids);
+                });
+                acc
+            })
+        })?;
+
+        let hits = points_per_value
+            .into_iter()
+            .map(|(value, point_ids)| FacetValueHit {
+                value,
+                count: point_ids.len(),
+            })
+            .collect();
+
+        Ok(hits)
+    }
+
     async fn unique_values(
         &self,
         request: Arc<FacetParams>,
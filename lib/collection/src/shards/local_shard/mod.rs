@@ -1,4 +1,5 @@
 pub mod clock_map;
+mod defragment;
 pub mod disk_usage_watcher;
 pub(super) mod facet;
 pub(super) mod formula_rescore;
@@ -43,10 +44,12 @@ use itertools::Itertools;
 use parking_lot::Mutex as ParkingMutex;
 use segment::entry::entry_point::NonAppendableSegmentEntry as _;
 use segment::index::field_index::{CardinalityEstimation, EstimationMerge};
-use segment::segment_constructor::{build_segment, load_segment, normalize_segment_dir};
+use segment::segment_constructor::{
+    SegmentLoadOptions, build_segment, load_segment_with_options, normalize_segment_dir,
+};
 use segment::types::{
     Filter, PayloadIndexInfo, PayloadKeyType, PointIdType, SegmentConfig, SegmentType,
-    SeqNumberType,
+    SeqNumberType, VectorNameBuf,
 };
 use shard::files::{NEWEST_CLOCKS_PATH, OLDEST_CLOCKS_PATH, ShardDataFiles};
 use shard::operations::CollectionUpdateOperations;
@@ -70,6 +73,7 @@ use crate::collection_manager::segments_searcher::SegmentsSearcher;
 use crate::common::file_utils::{move_dir, move_file};
 use crate::config::CollectionConfigInternal;
 use crate::operations::OperationWithClockTag;
+use crate::operations::config_diff::DiffConfig;
 use crate::operations::shared_storage_config::SharedStorageConfig;
 use crate::operations::types::{
     CollectionError, CollectionResult, OptimizationSegmentInfo, OptimizersStatus,
@@ -399,17 +403,51 @@ impl LocalShard {
             })
             .map(|entry| entry.path());
 
+        let load_options = SegmentLoadOptions {
+            populate: collection_config_read.params.populate_policy,
+            mmap_advice: effective_optimizers_config.mmap_advice.unwrap_or_default(),
+        };
+
+        // Changing `lock_in_ram` does not rebuild segments, so it is applied to loaded ones here
+        let lock_in_ram_vectors: Arc<Vec<VectorNameBuf>> = Arc::new(
+            collection_config_read
+                .params
+                .vectors
+                .params_iter()
+                .filter(|(_, vector_params)| {
+                    collection_config_read
+                        .hnsw_config
+                        .update_opt(vector_params.hnsw_config.as_ref())
+                        .lock_in_ram
+                        .unwrap_or_default()
+                })
+                .map(|(vector_name, _)| vector_name.to_owned())
+                .collect(),
+        );
+
         let mut segment_stream = futures::stream::iter(segment_paths)
             .map(|segment_path| {
                 let payload_index_schema = Arc::clone(&payload_index_schema);
+                let lock_in_ram_vectors = Arc::clone(&lock_in_ram_vectors);
                 let handle = tokio::task::spawn_blocking(move || {
                     let Some((segment_path, uuid)) = normalize_segment_dir(&segment_path)? else {
                         return CollectionResult::Ok(None);
                     };
-                    let mut segment = load_segment(&segment_path, uuid, &AtomicBool::new(false))?;
+                    let mut segment = load_segment_with_options(
+                        &segment_path,
+                        uuid,
+                        &load_options,
+                        &AtomicBool::new(false),
+                    )?;
 
                     segment.check_consistency_and_repair()?;
 
+                    for vector_name in lock_in_ram_vectors.iter() {
+                        if segment.config().vector_data.contains_key(vector_name) {
+                            segment.lock_vector_index_in_ram(vector_name)?;
+                        }
+                    }
+
                     if rebuild_payload_index {
                         segment.update_all_field_indices(
                             &payload_index_schema.read().schema.clone(),
@@ -1033,7 +1071,7 @@ impl LocalShard {
                 for (key, val) in segment_info.index_schema {
                     schema
                         .entry(key)
-                        .and_modify(|entry| entry.points += val.points)
+                        .and_modify(|entry| entry.merge(&val))
                         .or_insert(val);
                 }
             }
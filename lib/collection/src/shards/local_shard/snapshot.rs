@@ -24,6 +24,7 @@ use tokio::sync::OwnedMutexGuard;
 use tokio_util::task::AbortOnDropHandle;
 use wal::{Wal, WalOptions};
 
+use crate::common::snapshot_point::SnapshotPointGuard;
 use crate::operations::types::{CollectionError, CollectionResult};
 use crate::shards::local_shard::{LocalShard, LocalShardClocks};
 
@@ -53,6 +54,7 @@ impl LocalShard {
         format: SnapshotFormat,
         manifest: Option<SnapshotManifest>,
         save_wal: bool,
+        snapshot_point: Option<SnapshotPointGuard>,
     ) -> CollectionResult<impl Future<Output = CollectionResult<()>> + use<>> {
         let segments = self.segments.clone();
         let wal = self.wal.wal.clone();
@@ -97,6 +99,7 @@ impl LocalShard {
                     &tar.descend(Path::new(SEGMENTS_PATH))?,
                     format,
                     manifest.as_ref(),
+                    snapshot_point,
                 )?;
 
                 let wal_guard = wal.blocking_lock_owned();
@@ -242,6 +245,10 @@ impl LocalShard {
 /// temporary segment, which will source the configuration from it.
 ///
 /// Shortcuts at the first failing segment snapshot.
+///
+/// `snapshot_point` is dropped as soon as all segments are proxied, see [`SnapshotPoint`].
+///
+/// [`SnapshotPoint`]: crate::common::snapshot_point::SnapshotPoint
 #[expect(clippy::too_many_arguments)]
 pub fn snapshot_all_segments(
     segments: LockedSegmentHolder,
@@ -252,6 +259,7 @@ pub fn snapshot_all_segments(
     tar: &tar_ext::BuilderExt,
     format: SnapshotFormat,
     manifest: Option<&SnapshotManifest>,
+    mut snapshot_point: Option<SnapshotPointGuard>,
 ) -> OperationResult<()> {
     // Snapshotting may take long-running read locks on segments blocking incoming writes, do
     // this through proxied segments to allow writes to continue.
@@ -262,6 +270,9 @@ pub fn snapshot_all_segments(
         segment_config,
         payload_index_schema,
         |segment| {
+            // All segments are proxied before the first one is snapshotted
+            drop(snapshot_point.take());
+
             let read_segment = segment.read();
             let request_segment_manifest = if let Some(manifest) = manifest {
                 let segment_id = read_segment.segment_id()?;
@@ -51,6 +51,7 @@ fn test_snapshot_all() {
         &tar,
         SnapshotFormat::Regular,
         None,
+        None,
     )
     .unwrap();
 
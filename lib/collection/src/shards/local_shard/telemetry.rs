@@ -4,6 +4,7 @@ use std::time::Duration;
 
 use common::types::{DetailsLevel, TelemetryDetail};
 use segment::common::operation_time_statistics::OperationDurationStatistics;
+use segment::telemetry::{SegmentTelemetry, SparsePostingsTelemetry};
 use segment::types::SizeStats;
 use segment::vector_storage::common::get_async_scorer;
 use shard::common::stopping_guard::StoppingGuard;
@@ -63,6 +64,7 @@ impl LocalShard {
         };
 
         let (segments, index_only_excluded_vectors) = segments_data?;
+        let sparse_postings_by_name = sparse_postings_by_name(&segments);
         let total_optimized_points = self.total_optimized_points.load(Ordering::Relaxed);
 
         let optimizations: OperationDurationStatistics = self
@@ -113,6 +115,8 @@ impl LocalShard {
             async_scorer: Some(get_async_scorer()),
             indexed_only_excluded_vectors: (!index_only_excluded_vectors.is_empty())
                 .then_some(index_only_excluded_vectors),
+            sparse_postings_by_name: (!sparse_postings_by_name.is_empty())
+                .then_some(sparse_postings_by_name),
             update_queue: Some(self.local_update_queue_info()),
         })
     }
@@ -178,3 +182,24 @@ impl LocalShard {
         AbortOnDropHandle::new(stats).await?
     }
 }
+
+/// Merge posting lengths of sparse vector indexes of all segments
+fn sparse_postings_by_name(
+    segments: &[SegmentTelemetry],
+) -> HashMap<String, SparsePostingsTelemetry> {
+    let mut sparse_postings_by_name: HashMap<String, SparsePostingsTelemetry> = HashMap::new();
+    for vector_index in segments
+        .iter()
+        .flat_map(|segment| &segment.vector_index_searches)
+    {
+        if let (Some(vector_name), Some(sparse_postings)) =
+            (&vector_index.index_name, &vector_index.sparse_postings)
+        {
+            sparse_postings_by_name
+                .entry(vector_name.clone())
+                .or_default()
+                .merge(sparse_postings);
+        }
+    }
+    sparse_postings_by_name
+}
@@ -28,6 +28,7 @@ use super::transfer::driver::MAX_RETRY_COUNT;
 use super::transfer::transfer_tasks_pool::TransferTaskProgress;
 use super::update_tracker::UpdateTracker;
 use crate::collection_manager::optimizers::TrackerLog;
+use crate::common::snapshot_point::SnapshotPointGuard;
 use crate::operations::OperationWithClockTag;
 use crate::operations::point_ops::WriteOrdering;
 use crate::operations::types::{
@@ -149,10 +150,11 @@ impl QueueProxyShard {
         format: SnapshotFormat,
         manifest: Option<SnapshotManifest>,
         save_wal: bool,
+        snapshot_point: Option<SnapshotPointGuard>,
     ) -> CollectionResult<impl Future<Output = CollectionResult<()>> + use<>> {
         self.inner_unchecked()
             .wrapped_shard
-            .get_snapshot_creator(temp_path, tar, format, manifest, save_wal)
+            .get_snapshot_creator(temp_path, tar, format, manifest, save_wal, snapshot_point)
             .await
     }
 
@@ -1533,6 +1533,27 @@ impl ShardReplicaSet {
         }
         Ok(removed_records_count)
     }
+
+    /// Reclaim space of deleted points in the local shard (if present).
+    /// Returns amount of reclaimed points.
+    pub async fn defragment(&self) -> CollectionResult<usize> {
+        let local = self.local.read().await;
+        let Some(local) = local.as_ref() else {
+            // No local shard to defragment.
+            return Ok(0);
+        };
+
+        let reclaimed_points_count = local.defragment().await?;
+        if reclaimed_points_count > 0 {
+            log::debug!(
+                "Reclaimed {} deleted points in shard {}:{}",
+                reclaimed_points_count,
+                self.collection_id,
+                self.shard_id,
+            );
+        }
+        Ok(reclaimed_points_count)
+    }
 }
 
 /// Represents a change in replica set, due to scaling of `replication_factor`
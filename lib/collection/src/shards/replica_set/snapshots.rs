@@ -10,6 +10,7 @@ use shard::snapshots::snapshot_utils::{SnapshotMergePlan, SnapshotUtils};
 
 use super::{REPLICA_STATE_FILE, ShardReplicaSet};
 use crate::common::file_utils::{move_dir, move_file};
+use crate::common::snapshot_point::SnapshotPointGuard;
 use crate::operations::types::{CollectionError, CollectionResult};
 use crate::shards::dummy_shard::DummyShard;
 use crate::shards::local_shard::LocalShard;
@@ -26,6 +27,7 @@ impl ShardReplicaSet {
         format: SnapshotFormat,
         manifest: Option<SnapshotManifest>,
         save_wal: bool,
+        snapshot_point: Option<SnapshotPointGuard>,
     ) -> CollectionResult<impl Future<Output = CollectionResult<()>> + use<>> {
         // Track concurrent `create_partial_snapshot` requests, so that cluster manager can load-balance them
         let partial_snapshot_create_request_guard = if manifest.is_some() {
@@ -42,7 +44,14 @@ impl ShardReplicaSet {
         let maybe_local_snapshot_future = if let Some(local) = &*local_read {
             Some(
                 local
-                    .get_snapshot_creator(&temp_path, &tar, format, manifest, save_wal)
+                    .get_snapshot_creator(
+                        &temp_path,
+                        &tar,
+                        format,
+                        manifest,
+                        save_wal,
+                        snapshot_point,
+                    )
                     .await?,
             )
         } else {
@@ -901,6 +901,7 @@ mod tests {
         flush_interval_sec: 30,
         max_optimization_threads: Some(2),
         prevent_unoptimized: None,
+        mmap_advice: None,
     };
 
     async fn new_shard_replica_set(collection_dir: &TempDir) -> ShardReplicaSet {
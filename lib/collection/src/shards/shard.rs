@@ -17,6 +17,7 @@ use tokio::sync::oneshot;
 use super::local_shard::clock_map::RecoveryPoint;
 use super::update_tracker::UpdateTracker;
 use crate::collection_manager::optimizers::TrackerLog;
+use crate::common::snapshot_point::SnapshotPointGuard;
 use crate::operations::OperationWithClockTag;
 use crate::operations::operation_effect::{EstimateOperationEffectArea, OperationEffectArea};
 use crate::operations::types::{CollectionError, CollectionResult, OptimizersStatus};
@@ -136,26 +137,55 @@ impl Shard {
         format: SnapshotFormat,
         manifest: Option<SnapshotManifest>,
         save_wal: bool,
+        snapshot_point: Option<SnapshotPointGuard>,
     ) -> CollectionResult<impl Future<Output = CollectionResult<()>> + use<>> {
         let future = match self {
             Shard::Local(local_shard) => Either::Left(Either::Left(
                 local_shard
-                    .get_snapshot_creator(temp_path, tar, format, manifest, save_wal)
+                    .get_snapshot_creator(
+                        temp_path,
+                        tar,
+                        format,
+                        manifest,
+                        save_wal,
+                        snapshot_point,
+                    )
                     .await?,
             )),
             Shard::Proxy(proxy_shard) => Either::Left(Either::Right(
                 proxy_shard
-                    .get_snapshot_creator(temp_path, tar, format, manifest, save_wal)
+                    .get_snapshot_creator(
+                        temp_path,
+                        tar,
+                        format,
+                        manifest,
+                        save_wal,
+                        snapshot_point,
+                    )
                     .await?,
             )),
             Shard::ForwardProxy(proxy_shard) => Either::Right(Either::Left(
                 proxy_shard
-                    .get_snapshot_creator(temp_path, tar, format, manifest, save_wal)
+                    .get_snapshot_creator(
+                        temp_path,
+                        tar,
+                        format,
+                        manifest,
+                        save_wal,
+                        snapshot_point,
+                    )
                     .await?,
             )),
             Shard::QueueProxy(proxy_shard) => Either::Right(Either::Right(
                 proxy_shard
-                    .get_snapshot_creator(temp_path, tar, format, manifest, save_wal)
+                    .get_snapshot_creator(
+                        temp_path,
+                        tar,
+                        format,
+                        manifest,
+                        save_wal,
+                        snapshot_point,
+                    )
                     .await?,
             )),
             Shard::Dummy(dummy_shard) => {
@@ -279,6 +309,22 @@ impl Shard {
         }
     }
 
+    pub async fn defragment(&self) -> CollectionResult<usize> {
+        match self {
+            Self::Local(local_shard) => local_shard.defragment().await,
+            Self::Proxy(proxy_shard) => proxy_shard.wrapped_shard.defragment().await,
+            Self::ForwardProxy(proxy_shard) => proxy_shard.wrapped_shard.defragment().await,
+            Self::QueueProxy(proxy_shard) => {
+                if let Some(local_shard) = proxy_shard.wrapped_shard() {
+                    local_shard.defragment().await
+                } else {
+                    Ok(0)
+                }
+            }
+            Self::Dummy(_) => Ok(0),
+        }
+    }
+
     pub async fn shard_recovery_point(&self) -> CollectionResult<RecoveryPoint> {
         match self {
             Self::Local(local_shard) => Ok(local_shard.recovery_point().await),
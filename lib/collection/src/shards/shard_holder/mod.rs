@@ -12,6 +12,7 @@ use ahash::AHashMap;
 use api::rest::ShardKeyWithFallback;
 use common::budget::ResourceBudget;
 use common::fs::sync_parent_dir_async;
+use common::io_throttle::Throttled;
 use common::save_on_disk::SaveOnDisk;
 use common::tar_ext::BuilderExt;
 use common::tar_unpack::tar_unpack_file;
@@ -1156,7 +1157,7 @@ impl ShardHolder {
         let snapshots_path = snapshots_path.to_path_buf();
         let snapshot_manager = shard.get_snapshots_storage_manager()?;
 
-        let tar = BuilderExt::new_seekable_owned(File::create(temp_file.path())?);
+        let tar = BuilderExt::new_seekable_owned(Throttled(File::create(temp_file.path())?));
 
         let snapshot_creator = shard
             .create_snapshot(
@@ -1165,6 +1166,7 @@ impl ShardHolder {
                 SnapshotFormat::Regular,
                 None,
                 false,
+                None,
             )
             .await?;
 
@@ -1226,7 +1228,7 @@ impl ShardHolder {
 
         let (read_half, write_half) = tokio::io::duplex(4096);
 
-        let tar = BuilderExt::new_streaming_owned(SyncIoBridge::new(write_half));
+        let tar = BuilderExt::new_streaming_owned(Throttled(SyncIoBridge::new(write_half)));
 
         let snapshot_creator = shard
             .create_snapshot(
@@ -1235,6 +1237,7 @@ impl ShardHolder {
                 SnapshotFormat::Streamable,
                 manifest,
                 false,
+                None,
             )
             .await?;
 
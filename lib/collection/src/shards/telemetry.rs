@@ -3,7 +3,7 @@ use std::collections::HashMap;
 use schemars::JsonSchema;
 use segment::common::anonymize::{Anonymize, anonymize_collection_values};
 use segment::common::operation_time_statistics::OperationDurationStatistics;
-use segment::telemetry::SegmentTelemetry;
+use segment::telemetry::{SegmentTelemetry, SparsePostingsTelemetry};
 use segment::types::ShardKey;
 use serde::Serialize;
 
@@ -72,6 +72,11 @@ pub struct LocalShardTelemetry {
     pub async_scorer: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub indexed_only_excluded_vectors: Option<HashMap<String, usize>>,
+    /// Distribution of posting list lengths of sparse vector indexes across all segments,
+    /// grouped by vector name.
+    /// Only collected together with segments telemetry.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sparse_postings_by_name: Option<HashMap<String, SparsePostingsTelemetry>>,
     /// Update queue status
     #[serde(skip_serializing_if = "Option::is_none")]
     pub update_queue: Option<ShardUpdateQueueInfo>,
@@ -498,7 +498,8 @@ mod internal_conversions {
                 optimizations: _, // not included in grpc
                 async_scorer: _,  // not included in grpc
                 indexed_only_excluded_vectors,
-                update_queue: _, // not included in grpc
+                sparse_postings_by_name: _, // not included in grpc
+                update_queue: _,            // not included in grpc
             } = value;
 
             grpc::LocalShardTelemetry {
@@ -668,7 +669,8 @@ mod internal_conversions {
                             .collect()
                     },
                 ),
-                update_queue: None, // Not included in grpc
+                sparse_postings_by_name: None, // Not included in grpc
+                update_queue: None,            // Not included in grpc
             })
         }
     }
@@ -24,6 +24,7 @@ pub const TEST_OPTIMIZERS_CONFIG: OptimizersConfig = OptimizersConfig {
     flush_interval_sec: 30,
     max_optimization_threads: Some(2),
     prevent_unoptimized: None,
+    mmap_advice: None,
 };
 
 pub fn create_collection_config_with_dim(dim: usize) -> CollectionConfigInternal {
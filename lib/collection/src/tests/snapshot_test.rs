@@ -9,6 +9,7 @@ use shard::snapshots::snapshot_data::SnapshotData;
 use tempfile::Builder;
 
 use crate::collection::{Collection, RequestShardTransfer};
+use crate::common::snapshot_metadata::SnapshotCompatibilityPolicy;
 use crate::config::{CollectionConfigInternal, CollectionParams, WalConfig};
 use crate::operations::shared_storage_config::SharedStorageConfig;
 use crate::operations::types::{NodeType, VectorsConfig};
@@ -116,7 +117,15 @@ async fn _test_snapshot_collection(node_type: NodeType) {
 
         // Do not recover in local mode if some shards are remote
         assert!(
-            Collection::restore_snapshot(snapshot_data, recover_dir.path(), 0, false,).is_err(),
+            Collection::restore_snapshot(
+                snapshot_data,
+                recover_dir.path(),
+                0,
+                false,
+                SnapshotCompatibilityPolicy::default(),
+                None,
+            )
+            .is_err(),
         );
     }
 
@@ -126,7 +135,14 @@ async fn _test_snapshot_collection(node_type: NodeType) {
         .unwrap();
     let snapshot_data =
         SnapshotData::new_packed_persistent(snapshots_path.path().join(&snapshot_description.name));
-    if let Err(err) = Collection::restore_snapshot(snapshot_data, recover_dir.path(), 0, true) {
+    if let Err(err) = Collection::restore_snapshot(
+        snapshot_data,
+        recover_dir.path(),
+        0,
+        true,
+        SnapshotCompatibilityPolicy::default(),
+        None,
+    ) {
         panic!("Failed to restore snapshot: {err}")
     }
 
@@ -33,6 +33,7 @@ pub const TEST_OPTIMIZERS_CONFIG: OptimizersConfig = OptimizersConfig {
     flush_interval_sec: 30,
     max_optimization_threads: Some(2),
     prevent_unoptimized: None,
+    mmap_advice: None,
 };
 
 #[cfg(test)]
@@ -2,6 +2,7 @@ use std::sync::Arc;
 
 use api::rest::SearchRequestInternal;
 use collection::collection::Collection;
+use collection::common::snapshot_metadata::SnapshotCompatibilityPolicy;
 use collection::config::{CollectionConfigInternal, CollectionParams, WalConfig};
 use collection::operations::CollectionUpdateOperations;
 use collection::operations::point_ops::{
@@ -132,7 +133,14 @@ async fn _test_snapshot_and_recover_collection(node_type: NodeType) {
     let snapshot_data =
         SnapshotData::new_packed_persistent(snapshots_path.path().join(snapshot_description.name));
 
-    if let Err(err) = Collection::restore_snapshot(snapshot_data, recover_dir.path(), 0, false) {
+    if let Err(err) = Collection::restore_snapshot(
+        snapshot_data,
+        recover_dir.path(),
+        0,
+        false,
+        SnapshotCompatibilityPolicy::default(),
+        None,
+    ) {
         panic!("Failed to restore snapshot: {err}")
     }
 
@@ -70,6 +70,46 @@ pub struct FeatureFlags {
     ///
     /// Enabled by default in Qdrant 1.17.1+
     pub single_file_mmap_vector_storage: bool,
+
+    /// Periodically persist progress of HNSW graph construction, so that an interrupted build
+    /// resumes from the last checkpoint instead of starting from scratch.
+    ///
+    /// Useful on hosts where index building is slow, e.g. emulated ones.
+    pub hnsw_build_checkpoints: bool,
+
+    /// Encode links of newly built HNSW graphs with byte-aligned varints instead of bit-packing.
+    ///
+    /// Compresses slightly worse, but is cheaper to decode on targets where bit-packing decode is
+    /// comparatively expensive, e.g. s390x.
+    pub hnsw_varint_links: bool,
+
+    /// Store base vectors of HNSW graphs with inline vectors as `f16`, if the vector storage
+    /// is `f32`.
+    ///
+    /// Base vectors are only used to rescore candidates during graph traversal, so half
+    /// precision roughly halves their share of the links file at a small accuracy cost. There is
+    /// no `u8` option, as base vectors would be no more accurate than the quantized link vectors.
+    pub hnsw_inline_f16_base_vectors: bool,
+
+    /// Rebuild HNSW indexes with links in the legacy plain format through the optimizer, instead of
+    /// keeping them until the index is rebuilt for another reason.
+    ///
+    /// The target format is the same as for newly built graphs, including inline vectors if
+    /// `inline_storage` is enabled.
+    pub hnsw_convert_plain_links: bool,
+
+    /// Store each link vector of HNSW graphs with inline vectors once, instead of next to every
+    /// link pointing to the point.
+    ///
+    /// Makes the links file several times smaller for high `m`, at the cost of reading link
+    /// vectors from a random location.
+    pub hnsw_dedup_link_vectors: bool,
+
+    /// Front-code string values of newly built mmap keyword payload indexes.
+    ///
+    /// Cuts the disk size of long repetitive keywords, such as URLs or paths. Values are decoded
+    /// into memory when the index is loaded.
+    pub payload_index_prefix_compressed_strings: bool,
 }
 
 impl Default for FeatureFlags {
@@ -86,6 +126,12 @@ impl Default for FeatureFlags {
             migrate_rocksdb_payload_indices: true,
             appendable_quantization: true,
             single_file_mmap_vector_storage: false,
+            hnsw_build_checkpoints: false,
+            hnsw_varint_links: false,
+            hnsw_inline_f16_base_vectors: false,
+            hnsw_convert_plain_links: false,
+            hnsw_dedup_link_vectors: false,
+            payload_index_prefix_compressed_strings: false,
         }
     }
 }
@@ -112,6 +158,12 @@ pub fn init_feature_flags(mut flags: FeatureFlags) {
         migrate_rocksdb_payload_indices,
         appendable_quantization,
         single_file_mmap_vector_storage,
+        hnsw_build_checkpoints,
+        hnsw_varint_links,
+        hnsw_inline_f16_base_vectors,
+        hnsw_convert_plain_links,
+        hnsw_dedup_link_vectors,
+        payload_index_prefix_compressed_strings,
     } = &mut flags;
 
     // If all is set, explicitly set all feature flags
@@ -126,6 +178,12 @@ pub fn init_feature_flags(mut flags: FeatureFlags) {
         *migrate_rocksdb_payload_indices = true;
         *appendable_quantization = true;
         *single_file_mmap_vector_storage = true;
+        *hnsw_build_checkpoints = true;
+        *hnsw_varint_links = true;
+        *hnsw_inline_f16_base_vectors = true;
+        *hnsw_convert_plain_links = true;
+        *hnsw_dedup_link_vectors = true;
+        *payload_index_prefix_compressed_strings = true;
     }
 
     let res = FEATURE_FLAGS.set(flags);
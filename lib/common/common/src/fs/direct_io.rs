@@ -0,0 +1,102 @@
+//! Reading files with `O_DIRECT`, bypassing the page cache.
+
+use std::io;
+use std::path::Path;
+
+use fs_err::File;
+
+/// Alignment of offsets, lengths and buffers of direct reads.
+///
+/// Logical block size of practically all devices is either 512 bytes or 4KiB, this satisfies both.
+pub const DIRECT_IO_ALIGNMENT: usize = 4096;
+
+#[derive(Clone, Copy)]
+#[repr(C, align(4096))]
+struct AlignedBlock([u8; DIRECT_IO_ALIGNMENT]);
+
+/// Heap buffer, which start and length are aligned to [`DIRECT_IO_ALIGNMENT`].
+pub struct AlignedBuffer {
+    blocks: Vec<AlignedBlock>,
+}
+
+impl AlignedBuffer {
+    /// Zeroed buffer of at least `len` bytes, rounded up to [`DIRECT_IO_ALIGNMENT`].
+    pub fn new(len: usize) -> Self {
+        let blocks_count = len.div_ceil(DIRECT_IO_ALIGNMENT);
+        Self {
+            blocks: vec![AlignedBlock([0; DIRECT_IO_ALIGNMENT]); blocks_count],
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.blocks.len() * DIRECT_IO_ALIGNMENT
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.blocks.is_empty()
+    }
+
+    pub fn as_slice(&self) -> &[u8] {
+        // Safety: blocks are plain bytes without padding, laid out contiguously
+        unsafe { std::slice::from_raw_parts(self.blocks.as_ptr().cast(), self.len()) }
+    }
+
+    pub fn as_mut_slice(&mut self) -> &mut [u8] {
+        // Safety: blocks are plain bytes without padding, laid out contiguously
+        unsafe { std::slice::from_raw_parts_mut(self.blocks.as_mut_ptr().cast(), self.len()) }
+    }
+}
+
+/// Open a file for reading with `O_DIRECT`, so that reads bypass the page cache.
+///
+/// Fails with [`io::ErrorKind::Unsupported`] on platforms without `O_DIRECT`. Some filesystems,
+/// like tmpfs, refuse to open files with `O_DIRECT` as well.
+#[cfg(target_os = "linux")]
+pub fn open_direct(path: &Path) -> io::Result<File> {
+    use fs_err::os::unix::fs::OpenOptionsExt as _;
+
+    fs_err::OpenOptions::new()
+        .read(true)
+        .custom_flags(nix::libc::O_DIRECT)
+        .open(path)
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn open_direct(_path: &Path) -> io::Result<File> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "O_DIRECT is not supported on this platform",
+    ))
+}
+
+/// Read `len` bytes of a file opened with [`open_direct`] at `offset` into the start of `buffer`.
+///
+/// `offset` and `len` must be aligned to [`DIRECT_IO_ALIGNMENT`]. Returns the number of bytes
+/// read, which is less than `len` only at the end of the file.
+#[cfg(target_os = "linux")]
+pub fn read_direct(
+    file: &File,
+    buffer: &mut AlignedBuffer,
+    offset: u64,
+    len: usize,
+) -> io::Result<usize> {
+    use std::os::unix::fs::FileExt as _;
+
+    debug_assert_eq!(offset % DIRECT_IO_ALIGNMENT as u64, 0);
+    debug_assert_eq!(len % DIRECT_IO_ALIGNMENT, 0);
+    file.file()
+        .read_at(&mut buffer.as_mut_slice()[..len], offset)
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn read_direct(
+    _file: &File,
+    _buffer: &mut AlignedBuffer,
+    _offset: u64,
+    _len: usize,
+) -> io::Result<usize> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "O_DIRECT is not supported on this platform",
+    ))
+}
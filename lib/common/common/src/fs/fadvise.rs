@@ -33,6 +33,41 @@ pub fn clear_disk_cache(file_path: &Path) -> io::Result<()> {
     }
 }
 
+/// Hint the OS that the given range of the file is going to be read soon, so that it can start
+/// reading it in the background.
+///
+/// Uses `posix_fadvise(POSIX_FADV_WILLNEED)` or `fcntl(F_RDADVISE)` on Apple platforms.
+/// Does nothing on other platforms.
+pub fn advise_will_need(file: &File, offset: u64, len: u64) -> io::Result<()> {
+    #[cfg(posix_fadvise_supported)]
+    posix_fadvise(
+        file,
+        offset as _,
+        len as _,
+        PosixFadviseAdvice::POSIX_FADV_WILLNEED,
+    )?;
+
+    #[cfg(target_vendor = "apple")]
+    {
+        use std::os::fd::AsRawFd as _;
+
+        let advisory = nix::libc::radvisory {
+            ra_offset: offset as _,
+            ra_count: len as _,
+        };
+        // Safety: `advisory` outlives the call, and `F_RDADVISE` only reads it
+        let res = unsafe { nix::libc::fcntl(file.as_raw_fd(), nix::libc::F_RDADVISE, &advisory) };
+        if res == -1 {
+            return Err(io::Error::last_os_error());
+        }
+    }
+
+    #[cfg(not(any(posix_fadvise_supported, target_vendor = "apple")))]
+    let _ = (file, offset, len);
+
+    Ok(())
+}
+
 /// A wrapper around [`File`] intended for one-time sequential read.
 ///
 /// On supported platforms, the file contents is evicted from the OS file cache
@@ -0,0 +1,128 @@
+//! Functions to atomically replace a directory with a new version of it.
+//!
+//! The new version is written into a staging directory next to the original one, see
+//! [`staged_dir_path`], and swapped in by [`replace_dir`] with two renames:
+//!
+//! 1. `<dir>` is renamed to `<dir>.replaced`,
+//! 2. `<dir>.staged` is renamed to `<dir>`.
+//!
+//! If the process crashes in between, `<dir>` does not exist. [`recover_replaced_dir`] completes
+//! the swap in this case, or removes leftovers of an interrupted one otherwise. It must be called
+//! before `<dir>` is opened.
+
+use std::io;
+use std::path::{Path, PathBuf};
+
+use fs_err as fs;
+
+use super::safe_delete::sync_parent_dir;
+use super::sync::bulk_sync_dir;
+
+const STAGED_SUFFIX: &str = ".staged";
+const REPLACED_SUFFIX: &str = ".replaced";
+
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+/// Directory to write the new version of `path` into, before calling [`replace_dir`].
+pub fn staged_dir_path(path: &Path) -> PathBuf {
+    with_suffix(path, STAGED_SUFFIX)
+}
+
+/// Replace directory `path` with its staged version, see [`staged_dir_path`].
+///
+/// Staged directory is synced to disk before the swap, so it is complete if found after a crash.
+pub fn replace_dir(path: &Path) -> io::Result<()> {
+    let staged = staged_dir_path(path);
+    let replaced = with_suffix(path, REPLACED_SUFFIX);
+
+    bulk_sync_dir(&staged)?;
+
+    if replaced.exists() {
+        fs::remove_dir_all(&replaced)?;
+    }
+    fs::rename(path, &replaced)?;
+    fs::rename(&staged, path)?;
+    sync_parent_dir(path)?;
+
+    fs::remove_dir_all(&replaced)
+}
+
+/// Complete or clean up an interrupted [`replace_dir`] of directory `path`.
+pub fn recover_replaced_dir(path: &Path) -> io::Result<()> {
+    let staged = staged_dir_path(path);
+    let replaced = with_suffix(path, REPLACED_SUFFIX);
+
+    if !path.exists() {
+        if staged.exists() {
+            // Staged directory was moved aside only once it was complete, finish the swap
+            fs::rename(&staged, path)?;
+        } else if replaced.exists() {
+            fs::rename(&replaced, path)?;
+        } else {
+            return Ok(());
+        }
+        sync_parent_dir(path)?;
+    }
+
+    if staged.exists() {
+        fs::remove_dir_all(&staged)?;
+    }
+    if replaced.exists() {
+        fs::remove_dir_all(&replaced)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_dir(path: &Path, content: &str) -> io::Result<()> {
+        fs::create_dir_all(path)?;
+        fs::write(path.join("file"), content)
+    }
+
+    fn read(path: &Path) -> io::Result<String> {
+        fs::read_to_string(path.join("file"))
+    }
+
+    #[test]
+    fn test_replace_dir() -> io::Result<()> {
+        let base = tempfile::tempdir()?;
+        let dir = base.path().join("index");
+
+        make_dir(&dir, "old")?;
+        make_dir(&staged_dir_path(&dir), "new")?;
+        replace_dir(&dir)?;
+        assert_eq!(read(&dir)?, "new");
+        assert_eq!(fs::read_dir(base.path())?.count(), 1);
+
+        // Crashed before staging was complete
+        make_dir(&staged_dir_path(&dir), "partial")?;
+        recover_replaced_dir(&dir)?;
+        assert_eq!(read(&dir)?, "new");
+        assert_eq!(fs::read_dir(base.path())?.count(), 1);
+
+        // Crashed between the renames
+        make_dir(&staged_dir_path(&dir), "newer")?;
+        fs::rename(&dir, with_suffix(&dir, REPLACED_SUFFIX))?;
+        recover_replaced_dir(&dir)?;
+        assert_eq!(read(&dir)?, "newer");
+        assert_eq!(fs::read_dir(base.path())?.count(), 1);
+
+        // Crashed before removing the replaced directory
+        make_dir(&with_suffix(&dir, REPLACED_SUFFIX), "older")?;
+        recover_replaced_dir(&dir)?;
+        assert_eq!(read(&dir)?, "newer");
+        assert_eq!(fs::read_dir(base.path())?.count(), 1);
+
+        // Nothing to recover
+        recover_replaced_dir(&base.path().join("nonexistent"))?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,212 @@
+//! Global throttle of background IO.
+//!
+//! Snapshot archives, background rewrites of storage files and the storage scrubber share a single
+//! bytes per second budget, so that backups and migrations don't starve IO of searches on busy
+//! nodes.
+//!
+//! The throttle is disabled until [`configure`] is called with a limit. Throttled operations sleep,
+//! so they must account their IO as it is done, in small portions, see [`Throttled`].
+//!
+//! [`Throttle`] can also be used on its own, to additionally limit a single background task.
+
+use std::io::{self, Read, Seek, Write};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use parking_lot::Mutex;
+
+static THROTTLE: Mutex<Option<Throttle>> = Mutex::new(None);
+
+/// Usage of the throttle, since it was configured
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct IoThrottleStats {
+    /// Configured limit
+    pub bytes_per_sec: u64,
+    /// Rate of throttled IO over the last second
+    pub current_bytes_per_sec: u64,
+    /// Total number of throttled bytes
+    pub bytes_total: u64,
+    /// Total time throttled operations waited for the budget
+    pub waited_total: Duration,
+}
+
+/// Set the limit of background IO, `None` disables the throttle.
+pub fn configure(bytes_per_sec: Option<u64>) {
+    *THROTTLE.lock() =
+        bytes_per_sec.map(|bytes_per_sec| Throttle::new(bytes_per_sec, Instant::now()));
+}
+
+/// Usage of the throttle, `None` if it is disabled.
+pub fn stats() -> Option<IoThrottleStats> {
+    THROTTLE
+        .lock()
+        .as_ref()
+        .map(|throttle| throttle.stats(Instant::now()))
+}
+
+/// Account `bytes` of background IO, sleeping if it exceeds the budget.
+///
+/// Blocks the current thread, so it must not be called within an asynchronous execution context,
+/// nor while holding locks which searches or updates may wait for.
+pub fn consume(bytes: usize) {
+    let wait = match THROTTLE.lock().as_mut() {
+        Some(throttle) => throttle.reserve(bytes as u64, Instant::now()),
+        None => return,
+    };
+    if !wait.is_zero() {
+        thread::sleep(wait);
+    }
+}
+
+/// Reader or writer, which accounts all transferred bytes with [`consume`].
+pub struct Throttled<T>(pub T);
+
+impl<T> Throttled<T> {
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<R: Read> Read for Throttled<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.0.read(buf)?;
+        consume(read);
+        Ok(read)
+    }
+}
+
+impl<W: Write> Write for Throttled<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.0.write(buf)?;
+        consume(written);
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.0.flush()
+    }
+}
+
+impl<S: Seek> Seek for Throttled<S> {
+    fn seek(&mut self, pos: io::SeekFrom) -> io::Result<u64> {
+        self.0.seek(pos)
+    }
+}
+
+/// Token bucket, which allows a burst of at most one second worth of bytes.
+///
+/// Reservations beyond the budget make it negative, so that concurrent operations wait in turn.
+pub struct Throttle {
+    bytes_per_sec: u64,
+    budget: f64,
+    refilled_at: Instant,
+    window_started_at: Instant,
+    window_bytes: u64,
+    last_window_bytes_per_sec: u64,
+    bytes_total: u64,
+    waited_total: Duration,
+}
+
+impl Throttle {
+    pub fn with_rate(bytes_per_sec: u64) -> Self {
+        Self::new(bytes_per_sec, Instant::now())
+    }
+
+    /// Account `bytes` of IO, sleeping if it exceeds the budget, see [`consume`].
+    pub fn consume(&mut self, bytes: usize) {
+        let wait = self.reserve(bytes as u64, Instant::now());
+        if !wait.is_zero() {
+            thread::sleep(wait);
+        }
+    }
+
+    fn new(bytes_per_sec: u64, now: Instant) -> Self {
+        let bytes_per_sec = bytes_per_sec.max(1);
+        Self {
+            bytes_per_sec,
+            budget: bytes_per_sec as f64,
+            refilled_at: now,
+            window_started_at: now,
+            window_bytes: 0,
+            last_window_bytes_per_sec: 0,
+            bytes_total: 0,
+            waited_total: Duration::ZERO,
+        }
+    }
+
+    /// Reserve `bytes` of the budget, returns how long to wait until they fit into it.
+    fn reserve(&mut self, bytes: u64, now: Instant) -> Duration {
+        let rate = self.bytes_per_sec as f64;
+        let elapsed = now.saturating_duration_since(self.refilled_at);
+        self.refilled_at = now;
+        self.budget = (self.budget + elapsed.as_secs_f64() * rate).min(rate) - bytes as f64;
+
+        let window = now.saturating_duration_since(self.window_started_at);
+        if window >= Duration::from_secs(1) {
+            self.last_window_bytes_per_sec =
+                (self.window_bytes as f64 / window.as_secs_f64()) as u64;
+            self.window_started_at = now;
+            self.window_bytes = 0;
+        }
+        self.window_bytes += bytes;
+        self.bytes_total += bytes;
+
+        let wait = if self.budget < 0.0 {
+            Duration::from_secs_f64(-self.budget / rate)
+        } else {
+            Duration::ZERO
+        };
+        self.waited_total += wait;
+        wait
+    }
+
+    fn stats(&self, now: Instant) -> IoThrottleStats {
+        // Current window is only used once complete, so that idle periods are accounted
+        let window = now.saturating_duration_since(self.window_started_at);
+        let current_bytes_per_sec = if window >= Duration::from_secs(1) {
+            (self.window_bytes as f64 / window.as_secs_f64()) as u64
+        } else {
+            self.last_window_bytes_per_sec
+        };
+
+        IoThrottleStats {
+            bytes_per_sec: self.bytes_per_sec,
+            current_bytes_per_sec,
+            bytes_total: self.bytes_total,
+            waited_total: self.waited_total,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_throttle_reserve() {
+        let start = Instant::now();
+        let mut throttle = Throttle::new(1000, start);
+
+        // Burst of one second worth of bytes
+        assert_eq!(throttle.reserve(1000, start), Duration::ZERO);
+        assert_eq!(throttle.reserve(500, start), Duration::from_millis(500));
+        // Concurrent reservation waits for the previous one
+        assert_eq!(throttle.reserve(500, start), Duration::from_secs(1));
+
+        // Budget is refilled over time, but the burst is capped
+        let later = start + Duration::from_secs(10);
+        assert_eq!(throttle.reserve(1000, later), Duration::ZERO);
+        assert_eq!(throttle.reserve(100, later), Duration::from_millis(100));
+
+        let stats = throttle.stats(later);
+        assert_eq!(stats.bytes_per_sec, 1000);
+        assert_eq!(stats.bytes_total, 3100);
+        assert_eq!(stats.waited_total, Duration::from_millis(1600));
+        // 2000 bytes in the window of 10 seconds, closed by the reservation at `later`
+        assert_eq!(stats.current_bytes_per_sec, 200);
+
+        // Idle time lowers the current usage
+        let idle = later + Duration::from_secs(100);
+        assert_eq!(throttle.stats(idle).current_bytes_per_sec, 11);
+    }
+}
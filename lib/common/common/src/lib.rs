@@ -14,6 +14,7 @@ pub mod ext;
 pub mod fixed_length_priority_queue;
 pub mod flags;
 pub mod fs;
+pub mod io_throttle;
 pub mod is_alive_lock;
 pub mod iterator_ext;
 pub mod load_concurrency;
@@ -40,4 +41,5 @@ pub mod toposort;
 pub mod typelevel;
 pub mod types;
 pub mod validation;
+pub mod varint_links;
 pub mod zeros;
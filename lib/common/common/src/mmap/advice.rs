@@ -1,6 +1,7 @@
 //! Platform-independent abstractions over [`memmap2::Mmap::advise`]/[`memmap2::MmapMut::advise`]
 //! and [`memmap2::Advice`].
 
+use std::cell::Cell;
 use std::hint::black_box;
 use std::io;
 use std::num::Wrapping;
@@ -13,6 +14,11 @@ use serde::Deserialize;
 /// See [`set_global`] and [`get_global`].
 static ADVICE: parking_lot::RwLock<Advice> = parking_lot::RwLock::new(Advice::Random);
 
+thread_local! {
+    /// Overrides the global [`Advice`] value in the current thread, see [`with_global_override`].
+    static ADVICE_OVERRIDE: Cell<Option<Advice>> = const { Cell::new(None) };
+}
+
 /// Set global [`Advice`] value.
 ///
 /// When the `segment` crate creates [`memmap2::Mmap`] or [`memmap2::MmapMut`]
@@ -31,8 +37,31 @@ pub fn set_global(advice: Advice) {
 }
 
 /// Get current global [`Advice`] value.
+///
+/// Returns the override of the current thread, if any (see [`with_global_override`]).
 pub fn get_global() -> Advice {
-    *ADVICE.read()
+    ADVICE_OVERRIDE.get().unwrap_or_else(|| *ADVICE.read())
+}
+
+/// Run `f` with the global [`Advice`] value overridden by `advice` in the current thread.
+///
+/// Allows to advise all memmaps of a component, which are created with [`AdviceSetting::Global`],
+/// without passing the [`Advice`] value down to each of them. Does nothing if `advice` is `None`.
+pub fn with_global_override<T>(advice: Option<Advice>, f: impl FnOnce() -> T) -> T {
+    let Some(advice) = advice else {
+        return f();
+    };
+
+    struct RestoreOverride(Option<Advice>);
+
+    impl Drop for RestoreOverride {
+        fn drop(&mut self) {
+            ADVICE_OVERRIDE.set(self.0);
+        }
+    }
+
+    let _restore = RestoreOverride(ADVICE_OVERRIDE.replace(Some(advice)));
+    f()
 }
 
 /// Platform-independent version of [`memmap2::Advice`].
@@ -50,6 +79,9 @@ pub enum Advice {
 
     /// See [`memmap2::Advice::Sequential`].
     Sequential,
+
+    /// See [`memmap2::Advice::WillNeed`].
+    WillNeed,
 }
 
 #[cfg(unix)]
@@ -59,6 +91,7 @@ impl From<Advice> for memmap2::Advice {
             Advice::Normal => memmap2::Advice::Normal,
             Advice::Random => memmap2::Advice::Random,
             Advice::Sequential => memmap2::Advice::Sequential,
+            Advice::WillNeed => memmap2::Advice::WillNeed,
         }
     }
 }
@@ -170,6 +203,14 @@ fn populate_simple(slice: &[u8]) {
     );
 }
 
+/// Populate pages of a memory-mapped region, block until they are populated.
+///
+/// Unlike [`Madviseable::populate`], which populates the whole map, only touches the given part.
+pub fn populate_region(region: &[u8]) {
+    will_need_multiple_pages(region);
+    populate_simple(region);
+}
+
 /// Trigger readahead for a memory-mapped region by calling
 /// `madvise(MADV_WILLNEED)` on it.
 ///
@@ -230,3 +271,127 @@ fn get_page_mask() -> Result<usize, String> {
     }
     Ok(page_size - 1)
 }
+
+/// Lock pages of a memory-mapped region in RAM with `mlock(2)`, so that they are not evicted
+/// from the page cache under memory pressure. Blocks until all pages are loaded.
+///
+/// Pages stay locked until they are unmapped. If the process is not allowed to lock that much
+/// memory, the error explains the current `RLIMIT_MEMLOCK` limit.
+#[cfg(unix)]
+pub fn lock_in_ram(region: &[u8]) -> io::Result<()> {
+    if region.is_empty() {
+        return Ok(());
+    }
+
+    // Safety: mlock doesn't access the memory. Linux rounds the address down to the page
+    // boundary, and fails with ENOMEM if the region is not mapped.
+    let res = unsafe { nix::libc::mlock(region.as_ptr().cast(), region.len()) };
+    if res == 0 {
+        return Ok(());
+    }
+
+    let err = io::Error::last_os_error();
+    match err.raw_os_error() {
+        Some(nix::libc::ENOMEM | nix::libc::EPERM | nix::libc::EAGAIN) => Err(io::Error::new(
+            err.kind(),
+            format!(
+                "Failed to lock {} bytes in RAM: {err}. Locked memory is limited by \
+                 RLIMIT_MEMLOCK, currently {}. Raise it with `ulimit -l`, `--ulimit memlock` \
+                 of Docker or `LimitMEMLOCK` of systemd",
+                region.len(),
+                memlock_limit_description(),
+            ),
+        )),
+        _ => Err(err),
+    }
+}
+
+#[cfg(not(unix))]
+pub fn lock_in_ram(_region: &[u8]) -> io::Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "Locking memory in RAM is not supported on this platform",
+    ))
+}
+
+/// Unlock pages of a memory-mapped region locked with [`lock_in_ram`], so that they can be evicted
+/// from the page cache again.
+///
+/// All pages overlapping with the region are unlocked, including partially covered ones.
+#[cfg(unix)]
+pub fn unlock_in_ram(region: &[u8]) -> io::Result<()> {
+    if region.is_empty() {
+        return Ok(());
+    }
+
+    // Safety: munlock doesn't access the memory
+    let res = unsafe { nix::libc::munlock(region.as_ptr().cast(), region.len()) };
+    if res == 0 {
+        Ok(())
+    } else {
+        Err(io::Error::last_os_error())
+    }
+}
+
+#[cfg(not(unix))]
+pub fn unlock_in_ram(_region: &[u8]) -> io::Result<()> {
+    Ok(())
+}
+
+/// Ask the kernel to back a memory-mapped region with transparent huge pages, reducing TLB
+/// pressure for large read-mostly mappings.
+///
+/// Uses `MADV_HUGEPAGE`, pages are collapsed by `khugepaged` in the background. `MADV_COLLAPSE` is
+/// not used, as it collapses synchronously and faults in every page of the region.
+#[cfg(target_os = "linux")]
+pub fn request_hugepages(region: &[u8]) -> io::Result<()> {
+    let Some(page_mask) = *PAGE_SIZE_MASK else {
+        return Ok(());
+    };
+    if region.is_empty() {
+        return Ok(());
+    }
+
+    // `madvise()` requires the address to be page-aligned.
+    let addr = region.as_ptr().map_addr(|addr| addr & !page_mask);
+    let length = region.len() + (region.as_ptr().addr() & page_mask);
+
+    // Safety: this advice does not change the contents of the region, only its backing pages.
+    let res = unsafe { nix::libc::madvise(addr as *mut _, length, nix::libc::MADV_HUGEPAGE) };
+    if res != 0 {
+        let err = io::Error::last_os_error();
+        return Err(io::Error::new(
+            err.kind(),
+            format!("Failed to call madvise(MADV_HUGEPAGE): {err}"),
+        ));
+    }
+
+    Ok(())
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn request_hugepages(_region: &[u8]) -> io::Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "Transparent huge pages are not supported on this platform",
+    ))
+}
+
+/// Soft limit of `RLIMIT_MEMLOCK` in a human-readable form.
+#[cfg(unix)]
+fn memlock_limit_description() -> String {
+    let mut limit = nix::libc::rlimit {
+        rlim_cur: 0,
+        rlim_max: 0,
+    };
+    // Safety: getrlimit only writes into `limit`
+    let res = unsafe { nix::libc::getrlimit(nix::libc::RLIMIT_MEMLOCK, &mut limit) };
+    if res != 0 {
+        return format!("unknown ({})", io::Error::last_os_error());
+    }
+    if limit.rlim_cur == nix::libc::RLIM_INFINITY {
+        "unlimited".to_string()
+    } else {
+        format!("{} bytes", limit.rlim_cur)
+    }
+}
@@ -69,7 +69,7 @@ impl<T: Sized + 'static> UniversalMmapChunk<T> {
 
 /// Checks if the file name matches the pattern for mmap chunks
 /// Return ID from the file name if it matches, None otherwise
-fn check_mmap_file_name_pattern(file_name: &str) -> Option<usize> {
+pub fn check_mmap_file_name_pattern(file_name: &str) -> Option<usize> {
     file_name
         .strip_prefix(MMAP_CHUNKS_PATTERN_START)
         .and_then(|file_name| file_name.strip_suffix(MMAP_CHUNKS_PATTERN_END))
@@ -0,0 +1,660 @@
+//! Encryption at rest for immutable memory mapped files.
+//!
+//! Files are encrypted with AES-256-GCM in fixed size chunks, each with its own random nonce and
+//! authentication tag. The file header, chunk index and whether the chunk is the last one are
+//! authenticated as well, so chunks can't be reordered, truncated or moved between files without
+//! being detected.
+//!
+//! Files are encrypted while they are written, with [`EncryptingWriter`]: chunks are sealed in
+//! memory, so plaintext never reaches the disk. Encrypted files are never memory mapped directly
+//! either: [`super::open_read_mmap`] detects them and decrypts the whole file into anonymous memory
+//! instead. Encrypted files therefore always consume RAM for their full size.
+//!
+//! Only immutable files written once by a segment build are encrypted:
+//!
+//! - vectors of immutable mmap dense storages, compressed or not
+//! - HNSW graph links
+//! - postings of mmap full-text indexes
+//! - mmap hashmaps of payload indexes with multiple values per key, such as the full-text
+//!   vocabulary and map index postings
+//!
+//! All other files are stored as plaintext. Notably the WAL, payload storage, quantized vectors,
+//! deleted flags, id trackers, appendable and sparse vector storages, and other payload index files.
+//!
+//! Keys are supplied by a [`KeyProvider`], which is registered with [`set_key_provider`]. Use
+//! [`StaticKeyProvider`] for a key from configuration, or implement the trait to fetch the key
+//! from a KMS. New files are only encrypted while a provider is registered, see [`is_enabled`].
+//!
+//! Layout:
+//!
+//! ```text
+//! | magic (8) | chunk size: u32 | reserved: u32 | plaintext length: u64 |
+//! | nonce (12) | ciphertext (chunk size) | tag (16) |
+//! | ... |
+//! | nonce (12) | ciphertext (remainder) | tag (16) |
+//! ```
+
+use std::fmt;
+use std::io::{self, BufReader, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+use std::sync::Arc;
+
+use fs_err::File;
+use memmap2::{Mmap, MmapMut};
+use ring::aead::{AES_256_GCM, Aad, LessSafeKey, NONCE_LEN, Nonce, UnboundKey};
+use ring::rand::{SecureRandom as _, SystemRandom};
+
+use crate::zeros::WriteZerosExt as _;
+
+const MAGIC: &[u8; 8] = b"qdrtenc1";
+const HEADER_SIZE: usize = MAGIC.len() + 2 * size_of::<u32>() + size_of::<u64>();
+/// Part of the header authenticated with each chunk: magic, chunk size and reserved bytes.
+///
+/// The plaintext length is only known once all chunks are written, truncation is detected by the
+/// flag of the last chunk instead.
+const AUTHENTICATED_HEADER_SIZE: usize = MAGIC.len() + 2 * size_of::<u32>();
+const TAG_LEN: usize = 16;
+/// Size of plaintext in a single chunk
+const CHUNK_SIZE: usize = 1024 * 1024;
+
+/// Registered [`KeyProvider`], encryption is disabled if `None`.
+static KEY_PROVIDER: parking_lot::RwLock<Option<Arc<dyn KeyProvider>>> =
+    parking_lot::RwLock::new(None);
+
+/// 256 bit AES key.
+#[derive(Clone)]
+pub struct EncryptionKey([u8; 32]);
+
+impl EncryptionKey {
+    pub fn new(key: [u8; 32]) -> Self {
+        Self(key)
+    }
+
+    /// Parse key from 64 hexadecimal characters.
+    pub fn from_hex(hex: &str) -> io::Result<Self> {
+        let hex = hex.trim();
+        if hex.len() != 64 || !hex.is_ascii() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "encryption key must be 64 hexadecimal characters",
+            ));
+        }
+
+        let mut key = [0; 32];
+        for (byte, pair) in key.iter_mut().zip(hex.as_bytes().chunks_exact(2)) {
+            let pair = std::str::from_utf8(pair).unwrap();
+            *byte = u8::from_str_radix(pair, 16).map_err(|_| {
+                io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "encryption key must be 64 hexadecimal characters",
+                )
+            })?;
+        }
+        Ok(Self(key))
+    }
+
+    fn aead_key(&self) -> LessSafeKey {
+        LessSafeKey::new(UnboundKey::new(&AES_256_GCM, &self.0).unwrap())
+    }
+}
+
+impl fmt::Debug for EncryptionKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("EncryptionKey(<redacted>)")
+    }
+}
+
+/// Source of the key to encrypt and decrypt files with.
+///
+/// This is the hook to integrate an external key management service. The key is requested each
+/// time a file is encrypted or decrypted, so implementations should cache it if fetching is slow.
+pub trait KeyProvider: Send + Sync {
+    fn key(&self) -> io::Result<EncryptionKey>;
+}
+
+/// [`KeyProvider`] with a fixed key, for example from configuration.
+#[derive(Debug)]
+pub struct StaticKeyProvider(EncryptionKey);
+
+impl StaticKeyProvider {
+    pub fn new(key: EncryptionKey) -> Self {
+        Self(key)
+    }
+}
+
+impl KeyProvider for StaticKeyProvider {
+    fn key(&self) -> io::Result<EncryptionKey> {
+        Ok(self.0.clone())
+    }
+}
+
+/// Register [`KeyProvider`] to encrypt new files with, or disable encryption with `None`.
+///
+/// Should be called at startup, before any segment is loaded. Existing encrypted files can only be
+/// read while a provider with the same key is registered.
+pub fn set_key_provider(provider: Option<Arc<dyn KeyProvider>>) {
+    *KEY_PROVIDER.write() = provider;
+}
+
+/// Whether new files are encrypted, see [`set_key_provider`].
+pub fn is_enabled() -> bool {
+    KEY_PROVIDER.read().is_some()
+}
+
+fn current_key() -> io::Result<EncryptionKey> {
+    let provider = KEY_PROVIDER.read().clone();
+    match provider {
+        Some(provider) => provider.key(),
+        None => Err(io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            "file is encrypted, but no encryption key is configured",
+        )),
+    }
+}
+
+/// Check whether the file at `path` is encrypted.
+pub fn is_encrypted_file(path: &Path) -> io::Result<bool> {
+    is_encrypted(&mut File::open(path)?)
+}
+
+/// Check whether `file` is encrypted, reads from the current position.
+pub(super) fn is_encrypted(file: &mut File) -> io::Result<bool> {
+    if file.metadata()?.len() < HEADER_SIZE as u64 {
+        return Ok(false);
+    }
+    let mut magic = [0; MAGIC.len()];
+    file.read_exact(&mut magic)?;
+    Ok(&magic == MAGIC)
+}
+
+/// Writer which encrypts everything written to it, if encryption is enabled.
+///
+/// Plaintext is buffered and sealed one chunk at a time, only ciphertext is written to `inner`.
+/// [`EncryptingWriter::finish`] must be called once everything is written, to seal the last chunk
+/// and complete the header. Without encryption, everything is written to `inner` as is.
+pub struct EncryptingWriter<W: Write + Seek> {
+    inner: W,
+    /// `None` if encryption is disabled
+    state: Option<EncryptingState>,
+}
+
+struct EncryptingState {
+    key: LessSafeKey,
+    rng: SystemRandom,
+    /// Position of the header in the inner writer
+    header_pos: u64,
+    /// Plaintext of the current chunk
+    buffer: Vec<u8>,
+    chunk_index: usize,
+    plaintext_len: u64,
+}
+
+impl<W: Write + Seek> EncryptingWriter<W> {
+    /// Start writing at the current position of `inner`, encrypted with the key of the registered
+    /// provider, if any.
+    pub fn new(inner: W) -> io::Result<Self> {
+        let provider = KEY_PROVIDER.read().clone();
+        match provider {
+            Some(provider) => Self::with_key(inner, &provider.key()?),
+            None => Ok(Self { inner, state: None }),
+        }
+    }
+
+    fn with_key(mut inner: W, key: &EncryptionKey) -> io::Result<Self> {
+        let header_pos = inner.stream_position()?;
+        // Header is completed on finish, once the plaintext length is known
+        inner.write_zeros(HEADER_SIZE)?;
+        Ok(Self {
+            inner,
+            state: Some(EncryptingState {
+                key: key.aead_key(),
+                rng: SystemRandom::new(),
+                header_pos,
+                buffer: Vec::with_capacity(CHUNK_SIZE),
+                chunk_index: 0,
+                plaintext_len: 0,
+            }),
+        })
+    }
+
+    /// Whether written data is encrypted.
+    pub fn is_encrypted(&self) -> bool {
+        self.state.is_some()
+    }
+
+    /// Seal the last chunk and write the header, returns the inner writer.
+    pub fn finish(mut self) -> io::Result<W> {
+        if let Some(mut state) = self.state.take() {
+            state.seal_chunk(&mut self.inner, true)?;
+
+            let end = self.inner.stream_position()?;
+            self.inner.seek(SeekFrom::Start(state.header_pos))?;
+            self.inner.write_all(&header_bytes(state.plaintext_len))?;
+            self.inner.seek(SeekFrom::Start(end))?;
+        }
+        Ok(self.inner)
+    }
+}
+
+impl EncryptingState {
+    fn seal_chunk(&mut self, writer: &mut impl Write, is_last: bool) -> io::Result<()> {
+        let mut nonce = [0; NONCE_LEN];
+        self.rng
+            .fill(&mut nonce)
+            .map_err(|_| io::Error::other("failed to generate nonce"))?;
+        let tag = self
+            .key
+            .seal_in_place_separate_tag(
+                Nonce::assume_unique_for_key(nonce),
+                Aad::from(chunk_aad(&header_bytes(0), self.chunk_index, is_last)),
+                &mut self.buffer,
+            )
+            .map_err(|_| io::Error::other("failed to encrypt chunk"))?;
+
+        writer.write_all(&nonce)?;
+        writer.write_all(&self.buffer)?;
+        writer.write_all(tag.as_ref())?;
+        self.buffer.clear();
+        self.chunk_index += 1;
+        Ok(())
+    }
+}
+
+impl<W: Write + Seek> Write for EncryptingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let Some(state) = &mut self.state else {
+            return self.inner.write(buf);
+        };
+
+        // Only seal a full chunk once more data follows, the last chunk is sealed on finish
+        if state.buffer.len() == CHUNK_SIZE && !buf.is_empty() {
+            state.seal_chunk(&mut self.inner, false)?;
+        }
+        let len = buf.len().min(CHUNK_SIZE - state.buffer.len());
+        state.buffer.extend_from_slice(&buf[..len]);
+        state.plaintext_len += len as u64;
+        Ok(len)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Reader of a file written by [`EncryptingWriter`], decrypts one chunk at a time.
+///
+/// Plaintext files are read as is. Use it to stream temporary files back, instead of decrypting
+/// them into memory as a whole.
+pub struct DecryptingReader {
+    reader: BufReader<File>,
+    /// `None` if the file is not encrypted
+    state: Option<DecryptingState>,
+}
+
+struct DecryptingState {
+    key: LessSafeKey,
+    header: [u8; HEADER_SIZE],
+    plaintext_len: u64,
+    chunk_count: usize,
+    chunk_index: usize,
+    /// Plaintext of the current chunk
+    buffer: Vec<u8>,
+    /// Read position in `buffer`
+    pos: usize,
+}
+
+impl DecryptingReader {
+    /// Open the file at `path`, encrypted files are decrypted with the key of the registered
+    /// provider.
+    pub fn open(path: &Path) -> io::Result<Self> {
+        Self::open_with_key(path, current_key)
+    }
+
+    fn open_with_key(
+        path: &Path,
+        key: impl FnOnce() -> io::Result<EncryptionKey>,
+    ) -> io::Result<Self> {
+        let mut file = File::open(path)?;
+        let is_encrypted = is_encrypted(&mut file)?;
+        file.seek(SeekFrom::Start(0))?;
+        if !is_encrypted {
+            return Ok(Self {
+                reader: BufReader::new(file),
+                state: None,
+            });
+        }
+
+        let mut header = [0; HEADER_SIZE];
+        file.read_exact(&mut header)?;
+        let plaintext_len = validate_header(&file, &header)?;
+        Ok(Self {
+            reader: BufReader::new(file),
+            state: Some(DecryptingState {
+                key: key()?.aead_key(),
+                header,
+                plaintext_len,
+                chunk_count: chunk_count(plaintext_len),
+                chunk_index: 0,
+                buffer: Vec::with_capacity(CHUNK_SIZE + TAG_LEN),
+                pos: 0,
+            }),
+        })
+    }
+}
+
+impl DecryptingState {
+    fn read_chunk(&mut self, reader: &mut BufReader<File>) -> io::Result<()> {
+        let chunk_len = CHUNK_SIZE.min(self.plaintext_len as usize - self.chunk_index * CHUNK_SIZE);
+        let mut nonce = [0; NONCE_LEN];
+        reader.read_exact(&mut nonce)?;
+        self.buffer.resize(chunk_len + TAG_LEN, 0);
+        reader.read_exact(&mut self.buffer)?;
+
+        let is_last = self.chunk_index + 1 == self.chunk_count;
+        let plaintext_len = self
+            .key
+            .open_in_place(
+                Nonce::assume_unique_for_key(nonce),
+                Aad::from(chunk_aad(&self.header, self.chunk_index, is_last)),
+                &mut self.buffer,
+            )
+            .map_err(|_| {
+                invalid_file(
+                    reader.get_ref(),
+                    "authentication failed, wrong key or corrupted data",
+                )
+            })?
+            .len();
+        self.buffer.truncate(plaintext_len);
+        self.pos = 0;
+        self.chunk_index += 1;
+        Ok(())
+    }
+}
+
+impl Read for DecryptingReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let Some(state) = &mut self.state else {
+            return self.reader.read(buf);
+        };
+
+        if state.pos == state.buffer.len() {
+            if state.chunk_index == state.chunk_count {
+                return Ok(0);
+            }
+            state.read_chunk(&mut self.reader)?;
+        }
+        let len = buf.len().min(state.buffer.len() - state.pos);
+        buf[..len].copy_from_slice(&state.buffer[state.pos..state.pos + len]);
+        state.pos += len;
+        Ok(len)
+    }
+}
+
+/// Decrypt `file` into read-only anonymous memory, with the key of the registered provider.
+pub(super) fn decrypt_to_anonymous(file: &File) -> io::Result<Mmap> {
+    decrypt_with_key(file, &current_key()?)
+}
+
+fn decrypt_with_key(file: &File, key: &EncryptionKey) -> io::Result<Mmap> {
+    let plaintext_len = read_header(file)?;
+    let mut decrypted = MmapMut::map_anon(plaintext_len as usize)?;
+    let mut offset = 0;
+    for_each_decrypted_chunk(file, key, |plaintext| {
+        decrypted[offset..offset + plaintext.len()].copy_from_slice(plaintext);
+        offset += plaintext.len();
+    })?;
+    decrypted.make_read_only()
+}
+
+/// Verify authentication tags of all chunks of the encrypted file at `path`, with the key of the
+/// registered provider. Plaintext is discarded.
+///
+/// Calls `on_chunk` with the length of each verified chunk, for example to throttle reads.
+pub fn verify_file(path: &Path, mut on_chunk: impl FnMut(usize)) -> io::Result<()> {
+    let key = current_key()?;
+    let file = File::open(path)?;
+    for_each_decrypted_chunk(&file, &key, |plaintext| on_chunk(plaintext.len()))
+}
+
+/// Read and validate the header of encrypted `file`, returns the plaintext length.
+fn read_header(file: &File) -> io::Result<u64> {
+    let mut header = [0; HEADER_SIZE];
+    let mut reader = file.file();
+    reader.seek(SeekFrom::Start(0))?;
+    reader.read_exact(&mut header)?;
+    validate_header(file, &header)
+}
+
+fn validate_header(file: &File, header: &[u8; HEADER_SIZE]) -> io::Result<u64> {
+    let chunk_size = u32::from_le_bytes(header[8..12].try_into().unwrap()) as usize;
+    let plaintext_len = u64::from_le_bytes(header[16..24].try_into().unwrap());
+    if &header[..MAGIC.len()] != MAGIC {
+        return Err(invalid_file(file, "bad header"));
+    }
+    if chunk_size != CHUNK_SIZE {
+        return Err(invalid_file(file, "unsupported chunk size"));
+    }
+    let expected_len = HEADER_SIZE as u64
+        + plaintext_len
+        + chunk_count(plaintext_len) as u64 * (NONCE_LEN + TAG_LEN) as u64;
+    if file.metadata()?.len() != expected_len {
+        return Err(invalid_file(file, "unexpected file size"));
+    }
+    Ok(plaintext_len)
+}
+
+/// Decrypt all chunks of `file` in order, and call `f` with the plaintext of each.
+fn for_each_decrypted_chunk(
+    file: &File,
+    key: &EncryptionKey,
+    mut f: impl FnMut(&[u8]),
+) -> io::Result<()> {
+    let key = key.aead_key();
+    let mut reader = BufReader::new(file.file());
+    reader.seek(SeekFrom::Start(0))?;
+
+    let mut header = [0; HEADER_SIZE];
+    reader.read_exact(&mut header)?;
+    let plaintext_len = validate_header(file, &header)?;
+
+    let mut buffer = vec![0; CHUNK_SIZE + TAG_LEN];
+    let chunk_count = chunk_count(plaintext_len);
+    for (index, chunk_len) in chunk_lengths(plaintext_len).enumerate() {
+        let mut nonce = [0; NONCE_LEN];
+        reader.read_exact(&mut nonce)?;
+        let chunk = &mut buffer[..chunk_len + TAG_LEN];
+        reader.read_exact(chunk)?;
+
+        let plaintext = key
+            .open_in_place(
+                Nonce::assume_unique_for_key(nonce),
+                Aad::from(chunk_aad(&header, index, index + 1 == chunk_count)),
+                chunk,
+            )
+            .map_err(|_| {
+                invalid_file(file, "authentication failed, wrong key or corrupted data")
+            })?;
+        f(plaintext);
+    }
+    Ok(())
+}
+
+fn invalid_file(file: &File, msg: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("invalid encrypted file {}: {msg}", file.path().display()),
+    )
+}
+
+fn header_bytes(plaintext_len: u64) -> [u8; HEADER_SIZE] {
+    let mut header = [0; HEADER_SIZE];
+    header[..8].copy_from_slice(MAGIC);
+    header[8..12].copy_from_slice(&(CHUNK_SIZE as u32).to_le_bytes());
+    header[16..24].copy_from_slice(&plaintext_len.to_le_bytes());
+    header
+}
+
+/// Authenticated data of a chunk: authenticated part of the file header, chunk index and whether
+/// it is the last chunk
+fn chunk_aad(
+    header: &[u8; HEADER_SIZE],
+    index: usize,
+    is_last: bool,
+) -> [u8; AUTHENTICATED_HEADER_SIZE + 9] {
+    let mut aad = [0; AUTHENTICATED_HEADER_SIZE + 9];
+    aad[..AUTHENTICATED_HEADER_SIZE].copy_from_slice(&header[..AUTHENTICATED_HEADER_SIZE]);
+    aad[AUTHENTICATED_HEADER_SIZE..AUTHENTICATED_HEADER_SIZE + 8]
+        .copy_from_slice(&(index as u64).to_le_bytes());
+    aad[AUTHENTICATED_HEADER_SIZE + 8] = u8::from(is_last);
+    aad
+}
+
+/// Number of chunks, there is always a last chunk, even if empty
+fn chunk_count(plaintext_len: u64) -> usize {
+    (plaintext_len as usize).div_ceil(CHUNK_SIZE).max(1)
+}
+
+fn chunk_lengths(plaintext_len: u64) -> impl Iterator<Item = usize> {
+    let plaintext_len = plaintext_len as usize;
+    (0..chunk_count(plaintext_len as u64))
+        .map(move |index| CHUNK_SIZE.min(plaintext_len - index * CHUNK_SIZE))
+}
+
+#[cfg(test)]
+mod tests {
+    use fs_err as fs;
+    use tempfile::Builder;
+
+    use super::*;
+
+    fn encrypt_to(path: &Path, data: &[u8], key: &EncryptionKey) {
+        let mut writer = EncryptingWriter::with_key(File::create(path).unwrap(), key).unwrap();
+        // Write in pieces not aligned to chunks
+        for piece in data.chunks(1000) {
+            writer.write_all(piece).unwrap();
+        }
+        writer.finish().unwrap().sync_all().unwrap();
+    }
+
+    #[test]
+    fn test_encrypt_decrypt_roundtrip() {
+        let dir = Builder::new().prefix("encryption").tempdir().unwrap();
+        let path = dir.path().join("data.mmap");
+        let key = EncryptionKey::new([7; 32]);
+
+        let data: Vec<u8> = (0..CHUNK_SIZE * 2 + 123).map(|i| (i % 251) as u8).collect();
+        encrypt_to(&path, &data, &key);
+        assert!(is_encrypted_file(&path).unwrap());
+        let encrypted = fs::read(&path).unwrap();
+        assert!(
+            !encrypted
+                .windows(64)
+                .any(|window| window == &data[1000..1064])
+        );
+
+        let decrypted = decrypt_with_key(&File::open(&path).unwrap(), &key).unwrap();
+        assert_eq!(&decrypted[..], &data[..]);
+
+        // Wrong key is rejected
+        let wrong_key = EncryptionKey::new([8; 32]);
+        assert!(decrypt_with_key(&File::open(&path).unwrap(), &wrong_key).is_err());
+
+        // Tampered data is rejected
+        let mut tampered = encrypted;
+        tampered[HEADER_SIZE + NONCE_LEN + CHUNK_SIZE + TAG_LEN + NONCE_LEN + 42] ^= 1;
+        fs::write(&path, &tampered).unwrap();
+        assert!(decrypt_with_key(&File::open(&path).unwrap(), &key).is_err());
+    }
+
+    #[test]
+    fn test_encrypt_empty_and_chunk_aligned() {
+        let dir = Builder::new().prefix("encryption").tempdir().unwrap();
+        let path = dir.path().join("data.mmap");
+        let key = EncryptionKey::new([7; 32]);
+
+        for len in [0, CHUNK_SIZE, CHUNK_SIZE * 2] {
+            let data = vec![42; len];
+            encrypt_to(&path, &data, &key);
+            let decrypted = decrypt_with_key(&File::open(&path).unwrap(), &key).unwrap();
+            assert_eq!(&decrypted[..], &data[..]);
+        }
+    }
+
+    #[test]
+    fn test_decrypting_reader() {
+        let dir = Builder::new().prefix("encryption").tempdir().unwrap();
+        let path = dir.path().join("data.mmap");
+        let key = EncryptionKey::new([7; 32]);
+
+        let data: Vec<u8> = (0..CHUNK_SIZE + 123).map(|i| (i % 251) as u8).collect();
+        encrypt_to(&path, &data, &key);
+        let mut decrypted = Vec::new();
+        DecryptingReader::open_with_key(&path, || Ok(key.clone()))
+            .unwrap()
+            .read_to_end(&mut decrypted)
+            .unwrap();
+        assert_eq!(decrypted, data);
+
+        // Plaintext is read as is
+        fs::write(&path, &data).unwrap();
+        let mut read = Vec::new();
+        DecryptingReader::open(&path)
+            .unwrap()
+            .read_to_end(&mut read)
+            .unwrap();
+        assert_eq!(read, data);
+    }
+
+    #[test]
+    fn test_truncated_file_is_rejected() {
+        let dir = Builder::new().prefix("encryption").tempdir().unwrap();
+        let path = dir.path().join("data.mmap");
+        let key = EncryptionKey::new([7; 32]);
+
+        let data = vec![42; CHUNK_SIZE * 2 + 123];
+        encrypt_to(&path, &data, &key);
+
+        // Drop the last chunk, and fix up the plaintext length in the header
+        let mut encrypted = fs::read(&path).unwrap();
+        encrypted.truncate(HEADER_SIZE + 2 * (NONCE_LEN + CHUNK_SIZE + TAG_LEN));
+        encrypted[..HEADER_SIZE].copy_from_slice(&header_bytes(2 * CHUNK_SIZE as u64));
+        fs::write(&path, &encrypted).unwrap();
+        assert!(decrypt_with_key(&File::open(&path).unwrap(), &key).is_err());
+    }
+
+    #[test]
+    fn test_writer_without_encryption_is_passthrough() {
+        let dir = Builder::new().prefix("encryption").tempdir().unwrap();
+        let path = dir.path().join("data.mmap");
+
+        let mut writer = EncryptingWriter {
+            inner: File::create(&path).unwrap(),
+            state: None,
+        };
+        writer.write_all(b"data").unwrap();
+        assert!(!writer.is_encrypted());
+        drop(writer.finish().unwrap());
+        assert_eq!(fs::read(&path).unwrap(), b"data");
+    }
+
+    #[test]
+    fn test_plaintext_file_is_not_encrypted() {
+        let dir = Builder::new().prefix("encryption").tempdir().unwrap();
+        let path = dir.path().join("data.mmap");
+
+        fs::write(&path, b"data").unwrap();
+        assert!(!is_encrypted_file(&path).unwrap());
+        fs::write(&path, [0; 64]).unwrap();
+        assert!(!is_encrypted_file(&path).unwrap());
+    }
+
+    #[test]
+    fn test_key_from_hex() {
+        let key = EncryptionKey::from_hex(&"0f".repeat(32)).unwrap();
+        assert_eq!(key.0, [0x0f; 32]);
+        assert!(EncryptionKey::from_hex("0f").is_err());
+        assert!(EncryptionKey::from_hex(&"zz".repeat(32)).is_err());
+        assert_eq!(format!("{key:?}"), "EncryptionKey(<redacted>)");
+    }
+}
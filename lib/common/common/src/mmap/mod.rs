@@ -1,5 +1,6 @@
 pub mod advice;
 pub mod chunked;
+pub mod encryption;
 mod mmap_readonly;
 mod mmap_rw;
 mod ops;
@@ -8,6 +8,7 @@ use fs_err::{File, OpenOptions};
 use memmap2::{Mmap, MmapMut};
 
 use super::advice::{AdviceSetting, Madviseable, madvise};
+use super::encryption;
 
 pub const TEMP_FILE_EXTENSION: &str = "tmp";
 
@@ -84,12 +85,19 @@ pub fn create_and_ensure_length(path: &Path, length: usize) -> io::Result<File>
 }
 
 pub fn open_read_mmap(path: &Path, advice: AdviceSetting, populate: bool) -> io::Result<Mmap> {
-    let file = OpenOptions::new()
+    let mut file = OpenOptions::new()
         .read(true)
         .append(true)
         .create(true)
         .open(path)?;
 
+    // Encrypted files are decrypted into anonymous memory, which is always populated
+    if encryption::is_encrypted(&mut file)? {
+        let mmap = encryption::decrypt_to_anonymous(&file)?;
+        madvise(&mmap, advice.resolve())?;
+        return Ok(mmap);
+    }
+
     let mmap = unsafe { Mmap::map(&file)? };
 
     // Populate before advising
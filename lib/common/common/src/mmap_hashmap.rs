@@ -18,7 +18,7 @@ use rand::rngs::StdRng;
 use zerocopy::little_endian::{U32 as LeU32, U64 as LeU64};
 use zerocopy::{FromBytes, Immutable, IntoBytes, KnownLayout};
 
-use crate::mmap::{AdviceSetting, Madviseable, open_read_mmap};
+use crate::mmap::{AdviceSetting, Madviseable, advice, encryption, open_read_mmap};
 use crate::zeros::WriteZerosExt as _;
 
 type ValuesLen = u32;
@@ -241,7 +241,8 @@ impl<K: Key + ?Sized, V: Sized + PersistLe + FromBytes + Immutable + IntoBytes +
             .tempfile_in(path.parent().ok_or(io::ErrorKind::InvalidInput)?)?
             .into_parts();
         let file = File::from_parts::<&Path>(file, temp_path.as_ref());
-        let mut bufw = io::BufWriter::new(file);
+        // Encrypted while written, if enabled
+        let mut bufw = encryption::EncryptingWriter::new(io::BufWriter::new(file))?;
 
         // 1. Header
         let header = HeaderDisk {
@@ -286,11 +287,14 @@ impl<K: Key + ?Sized, V: Sized + PersistLe + FromBytes + Immutable + IntoBytes +
         }
 
         // Explicitly flush write buffer so we can catch IO errors
+        let mut bufw = bufw.finish()?;
         bufw.flush()?;
         let file = bufw.into_inner().unwrap();
 
         file.sync_all()?;
         drop(file);
+        // Unlike this one, single value maps are never encrypted: their format is detected from
+        // the plain file, see `MmapSingleValueHashMap::is_single_value_file`
         temp_path.persist(path)?;
 
         Ok(())
@@ -519,6 +523,365 @@ Use get_stored() + StoredValues::iter_native() instead."
     }
 }
 
+/// Magic prefix of [`MmapSingleValueHashMap`] files.
+///
+/// Distinguishes them from [`MmapHashMap`] files, which start with the key type name.
+const SINGLE_VALUE_MAGIC: [u8; 8] = *b"mhm1val\0";
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, FromBytes, Immutable, IntoBytes, KnownLayout)]
+struct SingleValueHeaderDisk {
+    magic: [u8; 8],
+    key_type: [u8; 8],
+    buckets_pos: u64,
+    buckets_count: u64,
+}
+
+const _: [u8; 32] = [0; size_of::<SingleValueHeaderDisk>()];
+const _: [u8; 8] = [0; align_of::<SingleValueHeaderDisk>()];
+
+impl SingleValueHeaderDisk {
+    fn decode(&self) -> Header {
+        Header {
+            key_type: self.key_type,
+            buckets_pos: u64::from_le(self.buckets_pos),
+            buckets_count: u64::from_le(self.buckets_count),
+        }
+    }
+}
+
+/// How many bytes we need to read from disk to locate an entry of [`MmapSingleValueHashMap`].
+pub const READ_SINGLE_VALUE_ENTRY_OVERHEAD: usize = SIZE_OF_KEY + BUCKET_OFFSET_OVERHEAD;
+
+/// On-disk hash map with exactly one value per key, backed by a memory-mapped file.
+///
+/// Same as [`MmapHashMap`], but entries don't store `values_len`, which saves a length field
+/// (and its padding) per entry.
+///
+/// The layout of the memory-mapped file is as follows:
+///
+/// | header                    | phf | padding       | alignment | buckets | entries   |
+/// |---------------------------|-----|---------------|-----------|---------|-----------|
+/// | [`SingleValueHeaderDisk`] |     | `u8[0..4095]` |  `u8[]`   | `u32[]` | See below |
+///
+/// ## Entry format for the `str` key
+///
+/// | key    | `'\0xff'` | padding | value |
+/// |--------|-----------|---------|-------|
+/// | `u8[]` | `u8`      | `u8[]`  | `V`   |
+///
+/// ## Entry format for the `i64` key
+///
+/// | key   | value |
+/// |-------|-------|
+/// | `i64` | `V`   |
+pub struct MmapSingleValueHashMap<
+    K: Key + ?Sized,
+    V: Sized + PersistLe + FromBytes + Immutable + IntoBytes + KnownLayout,
+> {
+    mmap: Mmap,
+    header: Header,
+    phf: Function,
+    decoded_keys: Option<Vec<K::OwnedKey>>,
+    _phantom_key: PhantomData<K>,
+    _phantom_value: PhantomData<V>,
+}
+
+impl<K: Key + ?Sized, V: Sized + PersistLe + FromBytes + Immutable + IntoBytes + KnownLayout>
+    MmapSingleValueHashMap<K, V>
+{
+    const VALUE_SIZE: usize = size_of::<V>();
+
+    /// Entries are aligned to both key and value alignment.
+    const ENTRY_ALIGN: usize = if K::ALIGN > Self::VALUE_SIZE {
+        K::ALIGN
+    } else {
+        Self::VALUE_SIZE
+    };
+
+    /// Check whether the file at `path` is in [`MmapSingleValueHashMap`] format.
+    pub fn is_single_value_file(path: &Path) -> io::Result<bool> {
+        let mut magic = [0u8; SINGLE_VALUE_MAGIC.len()];
+        let mut file = File::open(path)?;
+        match io::Read::read_exact(&mut file, &mut magic) {
+            Ok(()) => Ok(magic == SINGLE_VALUE_MAGIC),
+            Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => Ok(false),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Save `map` contents to `path`.
+    pub fn create<'a>(path: &Path, map: impl Iterator<Item = (&'a K, V)> + Clone) -> io::Result<()>
+    where
+        K: 'a,
+    {
+        let keys_vec: Vec<_> = map.clone().map(|(k, _)| k).collect();
+        let keys_count = keys_vec.len();
+        let phf = Function::from(keys_vec);
+
+        // == First pass ==
+
+        let mut file_size = 0;
+        // 1. Header
+        file_size += size_of::<SingleValueHeaderDisk>();
+
+        // 2. PHF
+        file_size += phf.write_bytes();
+
+        // 3. Padding
+        let padding_len = file_size.next_multiple_of(PADDING_SIZE) - file_size;
+        file_size += padding_len;
+
+        // 4. Buckets
+        let buckets_size = keys_count * size_of::<BucketOffset>();
+        let bucket_align = buckets_size.next_multiple_of(Self::ENTRY_ALIGN) - buckets_size;
+        file_size += bucket_align;
+        let buckets_pos = file_size;
+
+        // 5. Data
+        let mut buckets = vec![0 as BucketOffset; keys_count];
+        let mut last_bucket = 0usize;
+        for (k, _) in map.clone() {
+            last_bucket = last_bucket.next_multiple_of(Self::ENTRY_ALIGN);
+            buckets[phf.get(k).expect("Key not found in phf") as usize] =
+                last_bucket as BucketOffset;
+            last_bucket += Self::entry_bytes(k);
+        }
+
+        // == Second pass ==
+        let (file, temp_path) = tempfile::Builder::new()
+            .prefix(path.file_name().ok_or(io::ErrorKind::InvalidInput)?)
+            .tempfile_in(path.parent().ok_or(io::ErrorKind::InvalidInput)?)?
+            .into_parts();
+        let file = File::from_parts::<&Path>(file, temp_path.as_ref());
+        let mut bufw = io::BufWriter::new(file);
+
+        // 1. Header
+        let header = SingleValueHeaderDisk {
+            magic: SINGLE_VALUE_MAGIC,
+            key_type: K::NAME,
+            buckets_pos: (buckets_pos as u64).to_le(),
+            buckets_count: (keys_count as u64).to_le(),
+        };
+        bufw.write_all(header.as_bytes())?;
+
+        // 2. PHF
+        phf.write(&mut bufw)?;
+
+        // 3. Padding
+        bufw.write_zeros(padding_len)?;
+
+        // 4. Buckets
+        bufw.write_zeros(bucket_align)?;
+        for bucket in buckets {
+            bufw.write_all(bucket.to_le().as_bytes())?;
+        }
+
+        // 5. Data
+        let mut pos = 0usize;
+        for (key, value) in map {
+            let next_pos = pos.next_multiple_of(Self::ENTRY_ALIGN);
+            if next_pos > pos {
+                bufw.write_zeros(next_pos - pos)?;
+                pos = next_pos;
+            }
+
+            pos += Self::entry_bytes(key);
+
+            key.write(&mut bufw)?;
+            bufw.write_zeros(Self::key_padding_bytes(key))?;
+            bufw.write_all(value.to_le().as_bytes())?;
+        }
+
+        // Explicitly flush write buffer so we can catch IO errors
+        bufw.flush()?;
+        let file = bufw.into_inner().unwrap();
+
+        file.sync_all()?;
+        drop(file);
+        temp_path.persist(path)?;
+
+        Ok(())
+    }
+
+    fn key_size_with_padding(key: &K) -> usize {
+        key.write_bytes().next_multiple_of(Self::VALUE_SIZE)
+    }
+
+    fn key_padding_bytes(key: &K) -> usize {
+        Self::key_size_with_padding(key) - key.write_bytes()
+    }
+
+    /// Return the total size of the entry in bytes, including key with padding and value.
+    fn entry_bytes(key: &K) -> usize {
+        Self::key_size_with_padding(key) + Self::VALUE_SIZE
+    }
+
+    /// Load the hash map from file.
+    pub fn open(path: &Path, populate: bool) -> io::Result<Self> {
+        let mmap = open_read_mmap(path, AdviceSetting::Global, populate)?;
+
+        let (header_disk, _) = SingleValueHeaderDisk::read_from_prefix(mmap.as_ref())
+            .map_err(|_| io::ErrorKind::InvalidData)?;
+
+        if header_disk.magic != SINGLE_VALUE_MAGIC {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "Not a single value mmap hashmap",
+            ));
+        }
+
+        let header = header_disk.decode();
+
+        if header.key_type != K::NAME {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "Key type mismatch",
+            ));
+        }
+
+        let phf = Function::read(&mut Cursor::new(
+            &mmap
+                .get(size_of::<SingleValueHeaderDisk>()..header.buckets_pos as usize)
+                .ok_or(io::ErrorKind::InvalidData)?,
+        ))?;
+
+        let mut result = Self {
+            mmap,
+            header,
+            phf,
+            decoded_keys: None,
+            _phantom_key: PhantomData,
+            _phantom_value: PhantomData,
+        };
+
+        if !K::CAN_READ_REF_FROM_BYTES {
+            let mut decoded_keys = Vec::with_capacity(result.keys_count());
+            for i in 0..result.keys_count() {
+                let entry = result.get_entry(i)?;
+                let key = K::read_owned_from_bytes(entry).ok_or_else(|| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        "Can't decode key from mmap entry",
+                    )
+                })?;
+                decoded_keys.push(key);
+            }
+            result.decoded_keys = Some(decoded_keys);
+        }
+
+        Ok(result)
+    }
+
+    pub fn keys_count(&self) -> usize {
+        self.header.buckets_count as usize
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &K> + '_ {
+        self.iter().map(|(key, _)| key)
+    }
+
+    /// Iterate over all entries, values are converted to native byte order.
+    pub fn iter(&self) -> impl Iterator<Item = (&K, V::Native)> + '_ {
+        (0..self.keys_count()).filter_map(move |i| {
+            let entry = match self.get_entry(i) {
+                Ok(entry) => entry,
+                Err(err) => {
+                    debug_assert!(false, "Error reading entry for key {i}: {err}");
+                    log::error!("Error reading entry for key {i}: {err}");
+                    return None;
+                }
+            };
+
+            let key = match self.decoded_keys.as_ref() {
+                Some(decoded_keys) => decoded_keys.get(i)?.borrow(),
+                None => K::from_bytes(entry)?,
+            };
+
+            match Self::get_value_from_entry(entry, key) {
+                Ok(value) => Some((key, value)),
+                Err(err) => {
+                    debug_assert!(false, "Error reading entry for key {i}: {err}");
+                    log::error!("Error reading entry for key {i}: {err}");
+                    None
+                }
+            }
+        })
+    }
+
+    /// Get the value associated with the `key`, converted to native byte order.
+    pub fn get(&self, key: &K) -> io::Result<Option<V::Native>> {
+        let Some(hash) = self.phf.get(key) else {
+            return Ok(None);
+        };
+
+        let entry = self.get_entry(hash as usize)?;
+
+        if !key.matches(entry) {
+            return Ok(None);
+        }
+
+        Ok(Some(Self::get_value_from_entry(entry, key)?))
+    }
+
+    fn get_value_from_entry(entry: &[u8], key: &K) -> io::Result<V::Native> {
+        let value_from = Self::key_size_with_padding(key);
+
+        let (value, _) = entry
+            .get(value_from..)
+            .and_then(|bytes| V::read_from_prefix(bytes).ok())
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Can't read value from mmap, relative offset: {value_from}"),
+                )
+            })?;
+
+        Ok(PersistLe::from_le(value))
+    }
+
+    fn get_entry(&self, index: usize) -> io::Result<&[u8]> {
+        // Absolute position of the bucket array in the mmap.
+        let bucket_from = self.header.buckets_pos as usize;
+        let bucket_to =
+            bucket_from + self.header.buckets_count as usize * size_of::<BucketOffset>();
+
+        let bucket_val = self
+            .mmap
+            .get(bucket_from..bucket_to)
+            .and_then(|b| <[BucketOffset]>::ref_from_bytes(b).ok())
+            .and_then(|buckets| buckets.get(index).copied())
+            .map(u64::from_le)
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Can't read bucket from mmap, pos: {bucket_from}:{bucket_to}"),
+                )
+            })?;
+
+        let entry_start = bucket_to + bucket_val as usize;
+
+        self.mmap.get(entry_start..).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Can't read entry from mmap, bucket_val {entry_start} is out of bounds"),
+            )
+        })
+    }
+
+    /// Populate all pages in the mmap.
+    /// Block until all pages are populated.
+    pub fn populate(&self) -> io::Result<()> {
+        self.mmap.populate();
+        Ok(())
+    }
+
+    /// Lock all pages of the mmap in RAM, see [`advice::lock_in_ram`].
+    pub fn lock_in_ram(&self) -> io::Result<()> {
+        advice::lock_in_ram(&self.mmap)
+    }
+}
+
 struct KeysIter<
     'a,
     K: Key + ?Sized,
@@ -987,6 +1350,70 @@ mod tests {
         assert!(mmap.get_stored(&100).unwrap().is_none())
     }
 
+    #[test]
+    fn test_mmap_single_value_hash() {
+        test_mmap_single_value_hash_impl(gen_ident, |s| s.as_str(), |s| s.to_owned());
+        test_mmap_single_value_hash_impl(|rng| rng.random::<i64>(), |i| i, |i| *i);
+        test_mmap_single_value_hash_impl(|rng| rng.random::<u128>(), |i| i, |i| *i);
+    }
+
+    fn test_mmap_single_value_hash_impl<K: Key + ?Sized, K1: Ord + Hash>(
+        generator: impl Clone + Fn(&mut StdRng) -> K1,
+        as_ref: impl Fn(&K1) -> &K,
+        from_ref: impl Fn(&K) -> K1,
+    ) {
+        let mut rng = StdRng::seed_from_u64(42);
+        let tmpdir = tempfile::Builder::new().tempdir().unwrap();
+        let path = tmpdir.path().join("map");
+
+        let map: BTreeMap<K1, u32> = gen_map(&mut rng, generator.clone(), 1000)
+            .into_iter()
+            .map(|(k, v)| (k, *v.first().unwrap()))
+            .collect();
+        MmapSingleValueHashMap::<K, u32>::create(&path, map.iter().map(|(k, v)| (as_ref(k), *v)))
+            .unwrap();
+
+        assert!(MmapSingleValueHashMap::<K, u32>::is_single_value_file(&path).unwrap());
+        let mmap = MmapSingleValueHashMap::<K, u32>::open(&path, false).unwrap();
+
+        // Non-existing keys should return None
+        for _ in 0..1000 {
+            let key = repeat_until(|| generator(&mut rng), |key| !map.contains_key(key));
+            assert!(mmap.get(as_ref(&key)).unwrap().is_none());
+        }
+
+        assert_eq!(mmap.keys_count(), map.len());
+        assert_eq!(mmap.keys().count(), map.len());
+
+        for (k, v) in mmap.iter() {
+            assert_eq!(map.get(&from_ref(k)), Some(&v));
+        }
+
+        // Existing keys should return the correct values
+        for (k, v) in &map {
+            assert_eq!(mmap.get(as_ref(k)).unwrap(), Some(*v));
+        }
+    }
+
+    #[test]
+    fn test_mmap_single_value_hash_rejects_multi_value_file() {
+        let tmpdir = tempfile::Builder::new().tempdir().unwrap();
+        let path = tmpdir.path().join("map");
+
+        let map: BTreeMap<String, BTreeSet<u32>> =
+            [("hello".to_string(), [1].into_iter().collect())]
+                .into_iter()
+                .collect();
+        MmapHashMap::<str, u32>::create(
+            &path,
+            map.iter().map(|(k, v)| (k.as_str(), v.iter().copied())),
+        )
+        .unwrap();
+
+        assert!(!MmapSingleValueHashMap::<str, u32>::is_single_value_file(&path).unwrap());
+        assert!(MmapSingleValueHashMap::<str, u32>::open(&path, false).is_err());
+    }
+
     #[test]
     #[cfg(target_endian = "big")]
     fn test_get_raw_returns_raw_stored_values_on_be() {
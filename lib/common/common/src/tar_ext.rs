@@ -88,6 +88,12 @@ impl BuilderExt<OwnedOutput> {
     pub fn new_streaming_owned(output: impl Write + Send + 'static) -> Self {
         Self::new(Box::new(SeekWrapper(output)))
     }
+
+    /// Streaming builder, which compresses the archive with zstd at the given `level`.
+    pub fn new_zstd_owned(output: impl Write + Send + 'static, level: i32) -> io::Result<Self> {
+        let encoder = zstd::Encoder::new(output, level)?;
+        Ok(Self::new(Box::new(SeekWrapper(ZstdOutput(encoder)))))
+    }
 }
 
 impl<'a> BuilderExt<BorrowedOutput<'a>> {
@@ -297,6 +303,24 @@ impl<T: Write> io::Seek for SeekWrapper<T> {
     }
 }
 
+/// Zstd-compressed output.
+///
+/// [`tar::Builder`] never flushes its output, so flush is used by [`BuilderExt::blocking_finish`]
+/// to finish the zstd frame after the archive footer. Unlike [`zstd::stream::AutoFinishEncoder`],
+/// nothing is written on drop, see [`BlowFuseOnDrop`].
+struct ZstdOutput<W: Write>(zstd::Encoder<'static, W>);
+
+impl<W: Write> Write for ZstdOutput<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.0.do_finish()?;
+        self.0.get_mut().flush()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -382,6 +406,26 @@ mod tests {
         assert_eq!(result.unwrap_err().kind(), io::ErrorKind::NotSeekable);
     }
 
+    #[test]
+    fn test_zstd_ok() {
+        let dir = tempfile::tempdir().unwrap();
+        let archive_path = dir.path().join("archive.tar.zst");
+
+        let tar =
+            BuilderExt::new_zstd_owned(fs_err::File::create(&archive_path).unwrap(), 3).unwrap();
+        tar.blocking_append_data(&[0; 64 * 1024], Path::new("foo"))
+            .unwrap();
+        tar.blocking_finish().unwrap();
+        assert!(fs_err::metadata(&archive_path).unwrap().len() < 1024);
+
+        let unpacked = dir.path().join("unpacked");
+        crate::tar_unpack::tar_unpack_file(&archive_path, &unpacked).unwrap();
+        assert_eq!(
+            fs_err::read(unpacked.join("foo")).unwrap(),
+            vec![0; 64 * 1024]
+        );
+    }
+
     #[test]
     fn test_writeseek_ok() {
         let tar = BuilderExt::new_seekable_borrowed(io::Cursor::new(Vec::new()));
@@ -1,22 +1,110 @@
 //! Wrappers around [`tar::Archive::unpack()`] with extra safety checks.
 
-use std::io;
+use std::io::{self, Read as _, Seek as _};
 use std::path::Path;
 
 use fs_err as fs;
 use tar::{Archive, EntryType};
 
+use crate::io_throttle::Throttled;
+
 pub fn tar_unpack_file(path: &Path, dst: &Path) -> Result<(), io::Error> {
+    tar_unpack_file_filtered(path, dst, |_| true)
+}
+
+/// Same as [`tar_unpack_file()`], but only unpacks entries whose path matches `filter`.
+///
+/// Skipped entries are still read, but not written to `dst`.
+pub fn tar_unpack_file_filtered(
+    path: &Path,
+    dst: &Path,
+    filter: impl FnMut(&Path) -> bool,
+) -> Result<(), io::Error> {
     let reader = io::BufReader::new(fs::File::open(path)?);
-    tar_unpack_reader(reader, dst)?;
+    unpack_reader(reader, dst, filter)?;
     Ok(())
 }
 
+/// Read the regular file at `file_path` from the archive at `path`, without unpacking it.
+///
+/// Contents of other entries are skipped by seeking, unless the archive is zstd-compressed and has
+/// to be decompressed up to the file. Returns `None` if the archive has no such file.
+pub fn tar_read_file(path: &Path, file_path: &Path) -> Result<Option<Vec<u8>>, io::Error> {
+    let mut file = fs::File::open(path)?;
+    let mut magic = [0; ZSTD_MAGIC.len()];
+    let is_zstd = file.read_exact(&mut magic).is_ok() && magic == ZSTD_MAGIC;
+    file.rewind()?;
+
+    let reader = io::BufReader::new(Throttled(file));
+    if is_zstd {
+        let mut archive = Archive::new(zstd::Decoder::with_buffer(reader)?);
+        read_archive_file(archive.entries()?, file_path)
+    } else {
+        let mut archive = Archive::new(reader);
+        read_archive_file(archive.entries_with_seek()?, file_path)
+    }
+}
+
+fn read_archive_file<R: io::Read>(
+    entries: tar::Entries<'_, R>,
+    file_path: &Path,
+) -> Result<Option<Vec<u8>>, io::Error> {
+    for entry in entries {
+        let mut entry = entry?;
+        if entry.header().entry_type() == EntryType::Regular && entry.path()? == file_path {
+            let mut data = Vec::new();
+            entry.read_to_end(&mut data)?;
+            return Ok(Some(data));
+        }
+    }
+    Ok(None)
+}
+
+/// Magic number at the start of a zstd frame
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xB5, 0x2F, 0xFD];
+
 /// Same as [`Archive::new()`] followed by [`Archive::unpack()`], but checks
 /// that we don't unpack something beyond regular files and directories.
 ///
+/// Zstd-compressed archives are decompressed transparently. Reads are accounted with the global
+/// IO throttle, so this function must be called from a blocking context.
+///
 /// Accepts a reader and returns the same reader.
 pub fn tar_unpack_reader<R: io::Read>(reader: R, dst: &Path) -> Result<R, io::Error> {
+    unpack_reader(reader, dst, |_| true)
+}
+
+fn unpack_reader<R: io::Read>(
+    reader: R,
+    dst: &Path,
+    filter: impl FnMut(&Path) -> bool,
+) -> Result<R, io::Error> {
+    let mut reader = Throttled(reader);
+
+    // Read the magic number, and put it back in front of the reader
+    let mut magic = Vec::with_capacity(ZSTD_MAGIC.len());
+    (&mut reader)
+        .take(ZSTD_MAGIC.len() as u64)
+        .read_to_end(&mut magic)?;
+    let is_zstd = magic == ZSTD_MAGIC;
+    let reader = io::Cursor::new(magic).chain(reader);
+
+    let reader = if is_zstd {
+        let decoder = unpack_archive(zstd::Decoder::new(reader)?, dst, filter)?;
+        decoder.finish().into_inner()
+    } else {
+        unpack_archive(reader, dst, filter)?
+    };
+
+    let (_magic, reader) = reader.into_inner();
+    Ok(reader.into_inner())
+}
+
+fn unpack_archive<R: io::Read>(
+    reader: R,
+    dst: &Path,
+    mut filter: impl FnMut(&Path) -> bool,
+) -> Result<R, io::Error> {
     let mut archive = Archive::new(reader);
     archive.set_overwrite(false);
 
@@ -34,6 +122,9 @@ pub fn tar_unpack_reader<R: io::Read>(reader: R, dst: &Path) -> Result<R, io::Er
                 )));
             }
         }
+        if !filter(entry.path()?.as_ref()) {
+            continue;
+        }
         entry.unpack_in(dst)?;
     }
 
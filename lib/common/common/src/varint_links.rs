@@ -0,0 +1,125 @@
+/// Byte-aligned alternative to [`crate::bitpacking_links::pack_links`].
+///
+/// The first `sorted_count` links are sorted and delta-encoded, like in
+/// [`crate::bitpacking_links::pack_links`], but all values are written as
+/// LEB128 varints instead of being bit-packed. It compresses worse, but
+/// decoding doesn't need to shift values across word boundaries, which is
+/// cheaper on targets where the bit-packing decode is comparatively expensive.
+///
+/// Parameters:
+/// - `sorted_count` is `m` (or `m0`) for this layer.
+/// - `raw_links` is in/out parameter. Input: links to pack, output: same links,
+///   but re-ordered.
+pub fn pack_links_varint(links: &mut Vec<u8>, raw_links: &mut [u32], sorted_count: usize) {
+    let sorted_count = raw_links.len().min(sorted_count);
+    raw_links[..sorted_count].sort_unstable();
+
+    let mut prev = 0;
+    for &value in &raw_links[..sorted_count] {
+        write_varint(links, value - prev);
+        prev = value;
+    }
+    for &value in &raw_links[sorted_count..] {
+        write_varint(links, value);
+    }
+}
+
+/// Returns an iterator over links packed with [`pack_links_varint`].
+#[inline]
+pub fn iterate_varint_links(links: &[u8], sorted_count: usize) -> VarintLinksIterator<'_> {
+    VarintLinksIterator {
+        data: links,
+        sorted_remaining: sorted_count,
+        current_delta: 0,
+    }
+}
+
+fn write_varint(out: &mut Vec<u8>, mut value: u32) {
+    while value >= 0x80 {
+        out.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+/// Iterator over links packed with [`pack_links_varint`].
+/// Created by [`iterate_varint_links`].
+pub struct VarintLinksIterator<'a> {
+    data: &'a [u8],
+    sorted_remaining: usize,
+    current_delta: u32,
+}
+
+impl Iterator for VarintLinksIterator<'_> {
+    type Item = u32;
+
+    #[inline]
+    fn next(&mut self) -> Option<u32> {
+        let mut value = 0u32;
+        let mut shift = 0u32;
+        loop {
+            let (&byte, rest) = self.data.split_first()?;
+            self.data = rest;
+            value |= u32::from(byte & 0x7F).wrapping_shl(shift);
+            if byte & 0x80 == 0 {
+                break;
+            }
+            shift += 7;
+        }
+
+        if self.sorted_remaining > 0 {
+            self.sorted_remaining -= 1;
+            self.current_delta = self.current_delta.wrapping_add(value);
+            Some(self.current_delta)
+        } else {
+            Some(value)
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        // Each value ends with a byte without the continuation bit
+        let count = self.data.iter().filter(|&&byte| byte & 0x80 == 0).count();
+        (count, Some(count))
+    }
+}
+
+impl ExactSizeIterator for VarintLinksIterator<'_> {}
+
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools as _;
+    use rand::rngs::StdRng;
+    use rand::{Rng as _, SeedableRng as _};
+
+    use super::*;
+    use crate::iterator_ext::{check_exact_size_iterator_len, check_iterator_fold};
+
+    #[test]
+    fn test_random() {
+        let mut rng = StdRng::seed_from_u64(42);
+
+        for _ in 0..1_000 {
+            let sorted_count = rng.random_range(0..100);
+            let total_count = rng.random_range(0..200);
+            let bits = rng.random_range(8..=32);
+
+            let mut raw_links_orig =
+                std::iter::repeat_with(|| rng.random_range(0..1u64 << bits) as u32)
+                    .unique()
+                    .take(total_count)
+                    .collect_vec();
+            let mut raw_links_updated = raw_links_orig.clone();
+
+            let mut links = Vec::new();
+            pack_links_varint(&mut links, &mut raw_links_updated, sorted_count);
+            let unpacked = iterate_varint_links(&links, sorted_count).collect_vec();
+
+            raw_links_orig[..sorted_count.min(total_count)].sort_unstable();
+            assert_eq!(raw_links_orig, unpacked);
+            assert_eq!(raw_links_updated, unpacked);
+
+            check_iterator_fold(|| iterate_varint_links(&links, sorted_count));
+            check_exact_size_iterator_len(iterate_varint_links(&links, sorted_count));
+        }
+    }
+}
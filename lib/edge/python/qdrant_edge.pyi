@@ -28,7 +28,13 @@ ConditionType = Union[
     "Filter",
 ]
 MatchType = Union[
-    "MatchValue", "MatchText", "MatchTextAny", "MatchPhrase", "MatchAny", "MatchExcept"
+    "MatchValue",
+    "MatchText",
+    "MatchTextAny",
+    "MatchTextFuzzy",
+    "MatchPhrase",
+    "MatchAny",
+    "MatchExcept",
 ]
 RangeType = Union["RangeFloat", "RangeDateTime"]
 QuantizationConfigType = Union[
@@ -164,6 +170,25 @@ class EdgeShard:
         """
         ...
 
+    def text_match_positions(
+            self,
+            point_ids: List[PointId],
+            condition: "FieldCondition",
+    ) -> Dict[PointId, List[int]]:
+        """
+        Get positions of tokens matched by a full-text condition, for highlighting.
+
+        Positions are only stored if the full text index of the field has phrase matching enabled.
+
+        Args:
+            point_ids: List of point IDs to get positions for.
+            condition: Field condition with a text or phrase match.
+
+        Returns:
+            Token positions by point ID, for points which match the condition.
+        """
+        ...
+
     def info(self) -> "ShardInfo":
         """
         Get information about the shard.
@@ -346,6 +371,8 @@ class SparseIndexConfig:
             self,
             full_scan_threshold: Optional[int] = None,
             datatype: Optional["VectorStorageDatatype"] = None,
+            max_posting_length: Optional[int] = None,
+            mmap_advice: Optional["MmapAdvice"] = None,
     ) -> None:
         """
         Create a SparseIndexConfig.
@@ -353,6 +380,8 @@ class SparseIndexConfig:
         Args:
             full_scan_threshold: Threshold for full scan vs index search.
             datatype: Storage datatype.
+            max_posting_length: Max length of posting lists in immutable index.
+            mmap_advice: Access pattern hint for memory mapped index files.
         """
         ...
 
@@ -371,6 +400,16 @@ class SparseIndexConfig:
         """Storage datatype."""
         ...
 
+    @property
+    def max_posting_length(self) -> Optional[int]:
+        """Max length of posting lists in immutable index."""
+        ...
+
+    @property
+    def mmap_advice(self) -> Optional["MmapAdvice"]:
+        """Access pattern hint for memory mapped index files."""
+        ...
+
 
 class PlainIndexConfig:
     """Configuration for plain (brute-force) index."""
@@ -391,6 +430,9 @@ class HnswIndexConfig:
             on_disk: Optional[bool] = None,
             payload_m: Optional[int] = None,
             inline_storage: Optional[bool] = None,
+            target_recall: Optional[float] = None,
+            m0: Optional[int] = None,
+            lock_in_ram: Optional[bool] = None,
     ) -> None:
         """
         Create an HnswIndexConfig.
@@ -402,6 +444,9 @@ class HnswIndexConfig:
             on_disk: Whether to store on disk.
             payload_m: Payload index m value.
             inline_storage: Whether to use inline storage.
+            target_recall: Target recall for automatic tuning of search ef.
+            m0: Number of edges per node on level 0, defaults to 2 * m.
+            lock_in_ram: Whether to lock graph links in RAM with mlock.
         """
         ...
 
@@ -435,6 +480,21 @@ class HnswIndexConfig:
         """Inline storage flag."""
         ...
 
+    @property
+    def target_recall(self) -> Optional[float]:
+        """Target recall for automatic tuning of search ef."""
+        ...
+
+    @property
+    def m0(self) -> Optional[int]:
+        """Number of edges per node on level 0."""
+        ...
+
+    @property
+    def lock_in_ram(self) -> Optional[bool]:
+        """Whether graph links are locked in RAM with mlock."""
+        ...
+
 
 class MultiVectorConfig:
     """Configuration for multi-vector storage."""
@@ -530,6 +590,7 @@ class BinaryQuantizationConfig:
             always_ram: Optional[bool] = None,
             encoding: Optional["BinaryQuantizationEncoding"] = None,
             query_encoding: Optional["BinaryQuantizationQueryEncoding"] = None,
+            oversampling: Optional[float] = None,
     ) -> None:
         """
         Create a BinaryQuantizationConfig.
@@ -538,6 +599,7 @@ class BinaryQuantizationConfig:
             always_ram: Whether to keep in RAM.
             encoding: Binary encoding type.
             query_encoding: Query encoding type.
+            oversampling: Oversampling of searches which don't specify it.
         """
         ...
 
@@ -556,6 +618,11 @@ class BinaryQuantizationConfig:
         """Query encoding."""
         ...
 
+    @property
+    def oversampling(self) -> Optional[float]:
+        """Default oversampling."""
+        ...
+
 
 # ============================================================================
 # Enums
@@ -579,6 +646,9 @@ class VectorStorageType(Enum):
     ChunkedMmap = ...
     InRamChunkedMmap = ...
     InRamMmap = ...
+    MmapDirectIo = ...
+    MmapCompressed = ...
+    TieredMmap = ...
 
 
 class VectorStorageDatatype(Enum):
@@ -587,6 +657,7 @@ class VectorStorageDatatype(Enum):
     Float32 = ...
     Float16 = ...
     Uint8 = ...
+    Bfloat16 = ...
 
 
 class PayloadStorageType(Enum):
@@ -643,6 +714,15 @@ class SparseIndexType(Enum):
     Mmap = ...
 
 
+class MmapAdvice(Enum):
+    """Access pattern hints for memory mapped index files."""
+
+    Normal = ...
+    Random = ...
+    Sequential = ...
+    WillNeed = ...
+
+
 class SparseVectorStorageType(Enum):
     """Sparse vector storage types."""
 
@@ -889,6 +969,16 @@ class PayloadIndexInfo:
         """Number of points with this field."""
         ...
 
+    @property
+    def ram_usage_bytes(self) -> int:
+        """Expected RAM usage of the index in bytes, once it is loaded."""
+        ...
+
+    @property
+    def disk_usage_bytes(self) -> int:
+        """Size of the index files on disk in bytes."""
+        ...
+
 
 # ============================================================================
 # Request Classes
@@ -2150,6 +2240,24 @@ class MatchTextAny:
         ...
 
 
+class MatchTextFuzzy:
+    """Match all of the words in text, tolerating typos."""
+
+    def __init__(self, text_fuzzy: str) -> None:
+        """
+        Create a MatchTextFuzzy.
+
+        Args:
+            text_fuzzy: Space-separated words to match, each within 1 or 2 edits depending on its length.
+        """
+        ...
+
+    @property
+    def text_fuzzy(self) -> str:
+        """Text."""
+        ...
+
+
 class MatchPhrase:
     """Match exact phrase."""
 
@@ -2,6 +2,7 @@ use std::fmt;
 
 use bytemuck::TransparentWrapper;
 use derive_more::Into;
+use ordered_float::OrderedFloat;
 use pyo3::IntoPyObjectExt as _;
 use pyo3::prelude::*;
 use segment::types::*;
@@ -269,16 +270,18 @@ pub struct PyBinaryQuantizationConfig(BinaryQuantizationConfig);
 #[pymethods]
 impl PyBinaryQuantizationConfig {
     #[new]
-    #[pyo3(signature = (always_ram = None, encoding = None, query_encoding = None))]
+    #[pyo3(signature = (always_ram = None, encoding = None, query_encoding = None, oversampling = None))]
     pub fn new(
         always_ram: Option<bool>,
         encoding: Option<PyBinaryQuantizationEncoding>,
         query_encoding: Option<PyBinaryQuantizationQueryEncoding>,
+        oversampling: Option<f64>,
     ) -> Self {
         Self(BinaryQuantizationConfig {
             always_ram,
             encoding: encoding.map(BinaryQuantizationEncoding::from),
             query_encoding: query_encoding.map(BinaryQuantizationQueryEncoding::from),
+            oversampling: oversampling.map(OrderedFloat),
         })
     }
 
@@ -299,6 +302,11 @@ impl PyBinaryQuantizationConfig {
             .map(PyBinaryQuantizationQueryEncoding::from)
     }
 
+    #[getter]
+    pub fn oversampling(&self) -> Option<f64> {
+        self.0.oversampling.map(|OrderedFloat(v)| v)
+    }
+
     pub fn __repr__(&self) -> String {
         self.repr()
     }
@@ -311,6 +319,7 @@ impl PyBinaryQuantizationConfig {
             always_ram: _,
             encoding: _,
             query_encoding: _,
+            oversampling: _,
         } = self.0;
     }
 }
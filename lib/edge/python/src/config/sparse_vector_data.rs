@@ -5,7 +5,9 @@ use bytemuck::TransparentWrapper;
 use derive_more::Into;
 use pyo3::prelude::*;
 use segment::data_types::modifier::Modifier;
-use segment::index::sparse_index::sparse_index_config::{SparseIndexConfig, SparseIndexType};
+use segment::index::sparse_index::sparse_index_config::{
+    MmapAdvice, SparseIndexConfig, SparseIndexType,
+};
 use segment::types::*;
 
 use super::vector_data::*;
@@ -95,15 +97,24 @@ pub struct PySparseIndexConfig(SparseIndexConfig);
 #[pymethods]
 impl PySparseIndexConfig {
     #[new]
-    #[pyo3(signature = (full_scan_threshold = None, datatype = None))]
+    #[pyo3(signature = (
+        full_scan_threshold = None,
+        datatype = None,
+        max_posting_length = None,
+        mmap_advice = None,
+    ))]
     pub fn new(
         full_scan_threshold: Option<usize>,
         datatype: Option<PyVectorStorageDatatype>,
+        max_posting_length: Option<usize>,
+        mmap_advice: Option<PyMmapAdvice>,
     ) -> Self {
         Self(SparseIndexConfig {
             index_type: SparseIndexType::MutableRam,
             full_scan_threshold,
             datatype: datatype.map(VectorStorageDatatype::from),
+            max_posting_length,
+            mmap_advice: mmap_advice.map(MmapAdvice::from),
         })
     }
 
@@ -122,6 +133,16 @@ impl PySparseIndexConfig {
         self.0.datatype.map(PyVectorStorageDatatype::from)
     }
 
+    #[getter]
+    pub fn max_posting_length(&self) -> Option<usize> {
+        self.0.max_posting_length
+    }
+
+    #[getter]
+    pub fn mmap_advice(&self) -> Option<PyMmapAdvice> {
+        self.0.mmap_advice.map(PyMmapAdvice::from)
+    }
+
     pub fn __repr__(&self) -> String {
         self.repr()
     }
@@ -134,6 +155,8 @@ impl PySparseIndexConfig {
             full_scan_threshold: _,
             index_type: _,
             datatype: _,
+            max_posting_length: _,
+            mmap_advice: _,
         } = self.0;
     }
 }
@@ -185,6 +208,57 @@ impl From<PySparseIndexType> for SparseIndexType {
     }
 }
 
+#[pyclass(name = "MmapAdvice", from_py_object)]
+#[derive(Copy, Clone, Debug)]
+pub enum PyMmapAdvice {
+    Normal,
+    Random,
+    Sequential,
+    WillNeed,
+}
+
+#[pymethods]
+impl PyMmapAdvice {
+    pub fn __repr__(&self) -> String {
+        self.repr()
+    }
+}
+
+impl Repr for PyMmapAdvice {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let repr = match self {
+            Self::Normal => "Normal",
+            Self::Random => "Random",
+            Self::Sequential => "Sequential",
+            Self::WillNeed => "WillNeed",
+        };
+
+        f.simple_enum::<Self>(repr)
+    }
+}
+
+impl From<MmapAdvice> for PyMmapAdvice {
+    fn from(advice: MmapAdvice) -> Self {
+        match advice {
+            MmapAdvice::Normal => PyMmapAdvice::Normal,
+            MmapAdvice::Random => PyMmapAdvice::Random,
+            MmapAdvice::Sequential => PyMmapAdvice::Sequential,
+            MmapAdvice::WillNeed => PyMmapAdvice::WillNeed,
+        }
+    }
+}
+
+impl From<PyMmapAdvice> for MmapAdvice {
+    fn from(advice: PyMmapAdvice) -> Self {
+        match advice {
+            PyMmapAdvice::Normal => MmapAdvice::Normal,
+            PyMmapAdvice::Random => MmapAdvice::Random,
+            PyMmapAdvice::Sequential => MmapAdvice::Sequential,
+            PyMmapAdvice::WillNeed => MmapAdvice::WillNeed,
+        }
+    }
+}
+
 #[pyclass(name = "SparseVectorStorageType", from_py_object)]
 #[derive(Copy, Clone, Debug)]
 pub enum PySparseVectorStorageType {
@@ -3,6 +3,7 @@ use std::{fmt, mem};
 
 use bytemuck::TransparentWrapper;
 use derive_more::Into;
+use ordered_float::OrderedFloat;
 use pyo3::IntoPyObjectExt as _;
 use pyo3::prelude::*;
 use segment::types::*;
@@ -178,6 +179,9 @@ pub enum PyVectorStorageType {
     ChunkedMmap,
     InRamChunkedMmap,
     InRamMmap,
+    MmapDirectIo,
+    MmapCompressed,
+    TieredMmap,
 }
 
 #[pymethods]
@@ -195,6 +199,9 @@ impl Repr for PyVectorStorageType {
             Self::ChunkedMmap => "ChunkedMmap",
             Self::InRamChunkedMmap => "InRamChunkedMmap",
             Self::InRamMmap => "InRamMmap",
+            Self::MmapDirectIo => "MmapDirectIo",
+            Self::MmapCompressed => "MmapCompressed",
+            Self::TieredMmap => "TieredMmap",
         };
 
         f.simple_enum::<Self>(repr)
@@ -209,6 +216,9 @@ impl From<VectorStorageType> for PyVectorStorageType {
             VectorStorageType::ChunkedMmap => PyVectorStorageType::ChunkedMmap,
             VectorStorageType::InRamChunkedMmap => PyVectorStorageType::InRamChunkedMmap,
             VectorStorageType::InRamMmap => PyVectorStorageType::InRamMmap,
+            VectorStorageType::MmapDirectIo => PyVectorStorageType::MmapDirectIo,
+            VectorStorageType::MmapCompressed => PyVectorStorageType::MmapCompressed,
+            VectorStorageType::TieredMmap => PyVectorStorageType::TieredMmap,
         }
     }
 }
@@ -221,6 +231,9 @@ impl From<PyVectorStorageType> for VectorStorageType {
             PyVectorStorageType::ChunkedMmap => VectorStorageType::ChunkedMmap,
             PyVectorStorageType::InRamChunkedMmap => VectorStorageType::InRamChunkedMmap,
             PyVectorStorageType::InRamMmap => VectorStorageType::InRamMmap,
+            PyVectorStorageType::MmapDirectIo => VectorStorageType::MmapDirectIo,
+            PyVectorStorageType::MmapCompressed => VectorStorageType::MmapCompressed,
+            PyVectorStorageType::TieredMmap => VectorStorageType::TieredMmap,
         }
     }
 }
@@ -303,7 +316,8 @@ pub struct PyHnswIndexConfig(HnswConfig);
 #[pymethods]
 impl PyHnswIndexConfig {
     #[new]
-    #[pyo3(signature = (m, ef_construct, full_scan_threshold, on_disk=None, payload_m=None, inline_storage=None))]
+    #[pyo3(signature = (m, ef_construct, full_scan_threshold, on_disk=None, payload_m=None, inline_storage=None, target_recall=None, m0=None, lock_in_ram=None))]
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         m: usize,
         ef_construct: usize,
@@ -311,6 +325,9 @@ impl PyHnswIndexConfig {
         on_disk: Option<bool>,
         payload_m: Option<usize>,
         inline_storage: Option<bool>,
+        target_recall: Option<f64>,
+        m0: Option<usize>,
+        lock_in_ram: Option<bool>,
     ) -> Self {
         Self(HnswConfig {
             m,
@@ -320,6 +337,9 @@ impl PyHnswIndexConfig {
             on_disk,
             payload_m,
             inline_storage,
+            target_recall: target_recall.map(OrderedFloat),
+            m0,
+            lock_in_ram,
         })
     }
 
@@ -353,6 +373,21 @@ impl PyHnswIndexConfig {
         self.0.inline_storage
     }
 
+    #[getter]
+    pub fn target_recall(&self) -> Option<f64> {
+        self.0.target_recall.map(|OrderedFloat(v)| v)
+    }
+
+    #[getter]
+    pub fn m0(&self) -> Option<usize> {
+        self.0.m0
+    }
+
+    #[getter]
+    pub fn lock_in_ram(&self) -> Option<bool> {
+        self.0.lock_in_ram
+    }
+
     pub fn __repr__(&self) -> String {
         self.repr()
     }
@@ -369,6 +404,9 @@ impl PyHnswIndexConfig {
             on_disk: _,
             payload_m: _,
             inline_storage: _,
+            target_recall: _,
+            m0: _,
+            lock_in_ram: _,
         } = self.0;
     }
 }
@@ -450,6 +488,7 @@ pub enum PyVectorStorageDatatype {
     Float32,
     Float16,
     Uint8,
+    Bfloat16,
 }
 
 #[pymethods]
@@ -465,6 +504,7 @@ impl Repr for PyVectorStorageDatatype {
             Self::Float32 => "Float32",
             Self::Float16 => "Float16",
             Self::Uint8 => "Uint8",
+            Self::Bfloat16 => "Bfloat16",
         };
 
         f.simple_enum::<Self>(repr)
@@ -477,6 +517,7 @@ impl From<VectorStorageDatatype> for PyVectorStorageDatatype {
             VectorStorageDatatype::Float32 => PyVectorStorageDatatype::Float32,
             VectorStorageDatatype::Float16 => PyVectorStorageDatatype::Float16,
             VectorStorageDatatype::Uint8 => PyVectorStorageDatatype::Uint8,
+            VectorStorageDatatype::Bfloat16 => PyVectorStorageDatatype::Bfloat16,
         }
     }
 }
@@ -487,6 +528,7 @@ impl From<PyVectorStorageDatatype> for VectorStorageDatatype {
             PyVectorStorageDatatype::Float32 => VectorStorageDatatype::Float32,
             PyVectorStorageDatatype::Float16 => VectorStorageDatatype::Float16,
             PyVectorStorageDatatype::Uint8 => VectorStorageDatatype::Uint8,
+            PyVectorStorageDatatype::Bfloat16 => VectorStorageDatatype::Bfloat16,
         }
     }
 }
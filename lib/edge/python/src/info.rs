@@ -91,6 +91,16 @@ impl PyPayloadIndexInfo {
     pub fn points(&self) -> usize {
         self.0.points
     }
+
+    #[getter]
+    pub fn ram_usage_bytes(&self) -> usize {
+        self.0.ram_usage_bytes
+    }
+
+    #[getter]
+    pub fn disk_usage_bytes(&self) -> usize {
+        self.0.disk_usage_bytes
+    }
 }
 
 impl PyPayloadIndexInfo {
@@ -100,6 +110,8 @@ impl PyPayloadIndexInfo {
             data_type: _,
             params: _,
             points: _,
+            ram_usage_bytes: _,
+            disk_usage_bytes: _,
         } = self.0;
     }
 }
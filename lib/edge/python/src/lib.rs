@@ -11,6 +11,7 @@ pub mod types;
 pub mod update;
 pub mod utils;
 
+use std::collections::HashMap;
 use std::path::PathBuf;
 
 use bytemuck::TransparentWrapperAlloc as _;
@@ -41,7 +42,7 @@ mod qdrant_edge {
     };
     #[pymodule_export]
     use super::config::sparse_vector_data::{
-        PyModifier, PySparseIndexConfig, PySparseIndexType, PySparseVectorDataConfig,
+        PyMmapAdvice, PyModifier, PySparseIndexConfig, PySparseIndexType, PySparseVectorDataConfig,
         PySparseVectorStorageType,
     };
     #[pymodule_export]
@@ -69,8 +70,8 @@ mod qdrant_edge {
     use super::types::filter::{
         PyFieldCondition, PyFilter, PyGeoBoundingBox, PyGeoPoint, PyGeoPolygon, PyGeoRadius,
         PyHasIdCondition, PyHasVectorCondition, PyIsEmptyCondition, PyIsNullCondition, PyMatchAny,
-        PyMatchExcept, PyMatchPhrase, PyMatchText, PyMatchTextAny, PyMatchValue, PyMinShould,
-        PyNestedCondition, PyRangeDateTime, PyRangeFloat, PyValuesCount,
+        PyMatchExcept, PyMatchPhrase, PyMatchText, PyMatchTextAny, PyMatchTextFuzzy, PyMatchValue,
+        PyMinShould, PyNestedCondition, PyRangeDateTime, PyRangeFloat, PyValuesCount,
     };
     #[pymodule_export]
     use super::types::formula::{PyDecayKind, PyExpressionInterface, PyFormula};
@@ -157,6 +158,21 @@ impl PyEdgeShard {
         Ok(points)
     }
 
+    pub fn text_match_positions(
+        &self,
+        point_ids: Vec<PyPointId>,
+        condition: PyFieldCondition,
+    ) -> Result<HashMap<PyPointId, Vec<u32>>> {
+        let point_ids = PyPointId::peel_vec(point_ids);
+        let positions = self
+            .get_shard()?
+            .text_match_positions(&point_ids, &condition.0)?
+            .into_iter()
+            .map(|(point_id, positions)| (PyPointId(point_id), positions))
+            .collect();
+        Ok(positions)
+    }
+
     pub fn info(&self) -> Result<PyShardInfo> {
         let info = self.get_shard()?.info();
         let info = PyShardInfo(info);
@@ -23,6 +23,7 @@ impl FromPyObject<'_, '_> for PyMatch {
             Value(PyMatchValue),
             Text(PyMatchText),
             TextAny(PyMatchTextAny),
+            TextFuzzy(PyMatchTextFuzzy),
             Phrase(PyMatchPhrase),
             Any(PyMatchAny),
             Except(PyMatchExcept),
@@ -33,6 +34,7 @@ impl FromPyObject<'_, '_> for PyMatch {
                 Match::Value(_) => {}
                 Match::Text(_) => {}
                 Match::TextAny(_) => {}
+                Match::TextFuzzy(_) => {}
                 Match::Phrase(_) => {}
                 Match::Any(_) => {}
                 Match::Except(_) => {}
@@ -43,6 +45,7 @@ impl FromPyObject<'_, '_> for PyMatch {
             Helper::Value(value) => Match::Value(MatchValue::from(value)),
             Helper::Text(text) => Match::Text(MatchText::from(text)),
             Helper::TextAny(text_any) => Match::TextAny(MatchTextAny::from(text_any)),
+            Helper::TextFuzzy(text_fuzzy) => Match::TextFuzzy(MatchTextFuzzy::from(text_fuzzy)),
             Helper::Phrase(phrase) => Match::Phrase(MatchPhrase::from(phrase)),
             Helper::Any(any) => Match::Any(MatchAny::from(any)),
             Helper::Except(except) => Match::Except(MatchExcept::from(except)),
@@ -62,6 +65,7 @@ impl<'py> IntoPyObject<'py> for PyMatch {
             Match::Value(value) => PyMatchValue(value).into_bound_py_any(py),
             Match::Text(text) => PyMatchText(text).into_bound_py_any(py),
             Match::TextAny(text_any) => PyMatchTextAny(text_any).into_bound_py_any(py),
+            Match::TextFuzzy(text_fuzzy) => PyMatchTextFuzzy(text_fuzzy).into_bound_py_any(py),
             Match::Phrase(phrase) => PyMatchPhrase(phrase).into_bound_py_any(py),
             Match::Any(any) => PyMatchAny(any).into_bound_py_any(py),
             Match::Except(except) => PyMatchExcept(except).into_bound_py_any(py),
@@ -75,6 +79,7 @@ impl Repr for PyMatch {
             Match::Value(value) => PyMatchValue::wrap_ref(value).fmt(f),
             Match::Text(text) => PyMatchText::wrap_ref(text).fmt(f),
             Match::TextAny(text_any) => PyMatchTextAny::wrap_ref(text_any).fmt(f),
+            Match::TextFuzzy(text_fuzzy) => PyMatchTextFuzzy::wrap_ref(text_fuzzy).fmt(f),
             Match::Phrase(phrase) => PyMatchPhrase::wrap_ref(phrase).fmt(f),
             Match::Any(any) => PyMatchAny::wrap_ref(any).fmt(f),
             Match::Except(except) => PyMatchExcept::wrap_ref(except).fmt(f),
@@ -237,6 +242,36 @@ impl PyMatchTextAny {
     }
 }
 
+#[pyclass(name = "MatchTextFuzzy", from_py_object)]
+#[derive(Clone, Debug, Into, TransparentWrapper)]
+#[repr(transparent)]
+pub struct PyMatchTextFuzzy(pub MatchTextFuzzy);
+
+#[pyclass_repr]
+#[pymethods]
+impl PyMatchTextFuzzy {
+    #[new]
+    pub fn new(text_fuzzy: String) -> Self {
+        Self(MatchTextFuzzy { text_fuzzy })
+    }
+
+    #[getter]
+    pub fn text_fuzzy(&self) -> &str {
+        &self.0.text_fuzzy
+    }
+
+    pub fn __repr__(&self) -> String {
+        self.repr()
+    }
+}
+
+impl PyMatchTextFuzzy {
+    fn _getters(self) {
+        // Every field should have a getter method
+        let MatchTextFuzzy { text_fuzzy: _ } = self.0;
+    }
+}
+
 #[pyclass(name = "MatchPhrase", from_py_object)]
 #[derive(Clone, Debug, Into, TransparentWrapper)]
 #[repr(transparent)]
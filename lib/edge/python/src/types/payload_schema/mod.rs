@@ -11,6 +11,7 @@ use segment::types::{PayloadSchemaParams, PayloadSchemaType};
 
 pub use self::text_index::*;
 use crate::repr::*;
+use crate::types::PyJsonPath;
 
 #[pyclass(name = "PayloadSchemaType", from_py_object)]
 #[derive(Copy, Clone, Debug)]
@@ -192,6 +193,21 @@ impl PyKeywordIndexParams {
     pub fn enable_hnsw(&self) -> Option<bool> {
         self.0.enable_hnsw
     }
+
+    #[getter]
+    pub fn null_index(&self) -> Option<bool> {
+        self.0.null_index
+    }
+
+    #[getter]
+    pub fn trigram_index(&self) -> Option<bool> {
+        self.0.trigram_index
+    }
+
+    #[getter]
+    pub fn composite_with(&self) -> Option<&PyJsonPath> {
+        self.0.composite_with.as_ref().map(PyJsonPath::wrap_ref)
+    }
 }
 
 impl PyKeywordIndexParams {
@@ -202,6 +218,9 @@ impl PyKeywordIndexParams {
             is_tenant: _,
             on_disk: _,
             enable_hnsw: _,
+            null_index: _,
+            trigram_index: _,
+            composite_with: _,
         } = self.0;
     }
 }
@@ -238,6 +257,16 @@ impl PyIntegerIndexParams {
     pub fn enable_hnsw(&self) -> Option<bool> {
         self.0.enable_hnsw
     }
+
+    #[getter]
+    pub fn null_index(&self) -> Option<bool> {
+        self.0.null_index
+    }
+
+    #[getter]
+    pub fn composite_with(&self) -> Option<&PyJsonPath> {
+        self.0.composite_with.as_ref().map(PyJsonPath::wrap_ref)
+    }
 }
 
 impl PyIntegerIndexParams {
@@ -250,6 +279,8 @@ impl PyIntegerIndexParams {
             is_principal: _,
             on_disk: _,
             enable_hnsw: _,
+            null_index: _,
+            composite_with: _,
         } = self.0;
     }
 }
@@ -276,6 +307,11 @@ impl PyFloatIndexParams {
     pub fn enable_hnsw(&self) -> Option<bool> {
         self.0.enable_hnsw
     }
+
+    #[getter]
+    pub fn null_index(&self) -> Option<bool> {
+        self.0.null_index
+    }
 }
 
 impl PyFloatIndexParams {
@@ -286,6 +322,7 @@ impl PyFloatIndexParams {
             is_principal: _,
             on_disk: _,
             enable_hnsw: _,
+            null_index: _,
         } = self.0;
     }
 }
@@ -307,6 +344,11 @@ impl PyGeoIndexParams {
     pub fn enable_hnsw(&self) -> Option<bool> {
         self.0.enable_hnsw
     }
+
+    #[getter]
+    pub fn null_index(&self) -> Option<bool> {
+        self.0.null_index
+    }
 }
 
 impl PyGeoIndexParams {
@@ -316,6 +358,7 @@ impl PyGeoIndexParams {
             r#type: _, // not relevant for Qdrant Edge
             on_disk: _,
             enable_hnsw: _,
+            null_index: _,
         } = self.0;
     }
 }
@@ -337,6 +380,11 @@ impl PyBoolIndexParams {
     pub fn enable_hnsw(&self) -> Option<bool> {
         self.0.enable_hnsw
     }
+
+    #[getter]
+    pub fn null_index(&self) -> Option<bool> {
+        self.0.null_index
+    }
 }
 
 impl PyBoolIndexParams {
@@ -346,6 +394,7 @@ impl PyBoolIndexParams {
             r#type: _, // not relevant for Qdrant Edge
             on_disk: _,
             enable_hnsw: _,
+            null_index: _,
         } = self.0;
     }
 }
@@ -372,6 +421,11 @@ impl PyDatetimeIndexParams {
     pub fn enable_hnsw(&self) -> Option<bool> {
         self.0.enable_hnsw
     }
+
+    #[getter]
+    pub fn null_index(&self) -> Option<bool> {
+        self.0.null_index
+    }
 }
 
 impl PyDatetimeIndexParams {
@@ -382,6 +436,7 @@ impl PyDatetimeIndexParams {
             is_principal: _,
             on_disk: _,
             enable_hnsw: _,
+            null_index: _,
         } = self.0;
     }
 }
@@ -408,6 +463,11 @@ impl PyUuidIndexParams {
     pub fn enable_hnsw(&self) -> Option<bool> {
         self.0.enable_hnsw
     }
+
+    #[getter]
+    pub fn null_index(&self) -> Option<bool> {
+        self.0.null_index
+    }
 }
 
 impl PyUuidIndexParams {
@@ -418,6 +478,7 @@ impl PyUuidIndexParams {
             is_tenant: _,
             on_disk: _,
             enable_hnsw: _,
+            null_index: _,
         } = self.0;
     }
 }
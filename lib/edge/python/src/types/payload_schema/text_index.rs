@@ -42,6 +42,16 @@ impl PyTextIndexParams {
         self.0.ascii_folding
     }
 
+    #[getter]
+    pub fn unicode_normalization(&self) -> Option<bool> {
+        self.0.unicode_normalization
+    }
+
+    #[getter]
+    pub fn case_folding(&self) -> Option<bool> {
+        self.0.case_folding
+    }
+
     #[getter]
     pub fn phrase_matching(&self) -> Option<bool> {
         self.0.phrase_matching
@@ -57,6 +67,16 @@ impl PyTextIndexParams {
         self.0.on_disk
     }
 
+    #[getter]
+    pub fn on_disk_populate_postings(&self) -> Option<usize> {
+        self.0.on_disk_populate_postings
+    }
+
+    #[getter]
+    pub fn lock_in_ram(&self) -> Option<bool> {
+        self.0.lock_in_ram
+    }
+
     #[getter]
     pub fn stemmer(&self) -> Option<&PyStemmingAlgorithm> {
         self.0.stemmer.as_ref().map(PyStemmingAlgorithm::wrap_ref)
@@ -66,6 +86,11 @@ impl PyTextIndexParams {
     pub fn enable_hnsw(&self) -> Option<bool> {
         self.0.enable_hnsw
     }
+
+    #[getter]
+    pub fn null_index(&self) -> Option<bool> {
+        self.0.null_index
+    }
 }
 
 impl PyTextIndexParams {
@@ -78,11 +103,16 @@ impl PyTextIndexParams {
             max_token_len: _,
             lowercase: _,
             ascii_folding: _,
+            unicode_normalization: _,
+            case_folding: _,
             phrase_matching: _,
             stopwords: _,
             on_disk: _,
+            on_disk_populate_postings: _,
+            lock_in_ram: _,
             stemmer: _,
             enable_hnsw: _,
+            null_index: _,
         } = self.0;
     }
 }
@@ -94,6 +124,7 @@ pub enum PyTokenizerType {
     Whitespace,
     Word,
     Multilingual,
+    Ngram,
 }
 
 impl Repr for PyTokenizerType {
@@ -103,6 +134,7 @@ impl Repr for PyTokenizerType {
             Self::Whitespace => "Whitespace",
             Self::Word => "Word",
             Self::Multilingual => "Multilingual",
+            Self::Ngram => "Ngram",
         };
 
         f.simple_enum::<Self>(repr)
@@ -116,6 +148,7 @@ impl From<TokenizerType> for PyTokenizerType {
             TokenizerType::Whitespace => PyTokenizerType::Whitespace,
             TokenizerType::Word => PyTokenizerType::Word,
             TokenizerType::Multilingual => PyTokenizerType::Multilingual,
+            TokenizerType::Ngram => PyTokenizerType::Ngram,
         }
     }
 }
@@ -127,6 +160,7 @@ impl From<PyTokenizerType> for TokenizerType {
             PyTokenizerType::Whitespace => TokenizerType::Whitespace,
             PyTokenizerType::Word => TokenizerType::Word,
             PyTokenizerType::Multilingual => TokenizerType::Multilingual,
+            PyTokenizerType::Ngram => TokenizerType::Ngram,
         }
     }
 }
@@ -38,7 +38,7 @@ impl EdgeShard {
             for (payload_key, payload_index) in segment_info.index_schema {
                 payload_schema
                     .entry(payload_key)
-                    .and_modify(|total: &mut PayloadIndexInfo| total.points += payload_index.points)
+                    .and_modify(|total: &mut PayloadIndexInfo| total.merge(&payload_index))
                     .or_insert(payload_index);
             }
         }
@@ -1,10 +1,14 @@
+use std::collections::HashMap;
 use std::sync::atomic::AtomicBool;
 
 use common::counter::hardware_accumulator::HwMeasurementAcc;
 use segment::common::operation_error::OperationResult;
-use segment::types::{ExtendedPointId, WithPayload, WithPayloadInterface, WithVector};
+use segment::types::{
+    ExtendedPointId, FieldCondition, WithPayload, WithPayloadInterface, WithVector,
+};
 use shard::retrieve::record_internal::RecordInternal;
 use shard::retrieve::retrieve_blocking::retrieve_blocking;
+use shard::retrieve::text_match_positions_blocking::text_match_positions_blocking;
 
 use crate::{DEFAULT_EDGE_TIMEOUT, EdgeShard};
 
@@ -36,4 +40,26 @@ impl EdgeShard {
 
         Ok(points)
     }
+
+    /// Positions of tokens matched by a full-text `condition` in the documents of the given
+    /// points, for highlighting.
+    ///
+    /// Positions are only stored if the full text index of the field has `phrase_matching`
+    /// enabled. Points which don't match the condition are not included.
+    pub fn text_match_positions(
+        &self,
+        point_ids: &[ExtendedPointId],
+        condition: &FieldCondition,
+    ) -> OperationResult<HashMap<ExtendedPointId, Vec<u32>>> {
+        let positions = text_match_positions_blocking(
+            self.segments.clone(),
+            point_ids,
+            condition,
+            DEFAULT_EDGE_TIMEOUT,
+            &AtomicBool::new(false),
+            HwMeasurementAcc::disposable_edge(),
+        )?;
+
+        Ok(positions.into_iter().collect())
+    }
 }
@@ -58,6 +58,16 @@ impl<'a, V: PostingValue> PostingIterator<'a, V> {
 
         greater_or_equal
     }
+
+    /// Moves the iterator forward to the given offset, without visiting the elements in between.
+    ///
+    /// Offsets behind the current position are ignored.
+    pub fn skip_to(&mut self, offset: usize) {
+        if offset > self.offset {
+            self.offset = offset.min(self.visitor.len());
+            self.current_elem = None;
+        }
+    }
 }
 
 impl<V: PostingValue> Iterator for PostingIterator<'_, V> {
@@ -120,6 +120,14 @@ impl<V: PostingValue> PostingList<V> {
     pub fn is_empty(&self) -> bool {
         self.len() == 0
     }
+
+    /// Size of the heap memory allocated by the posting list, in bytes
+    pub fn heap_size(&self) -> usize {
+        self.id_data.capacity()
+            + self.chunks.capacity() * size_of::<PostingChunk<SizedTypeFor<V>>>()
+            + self.remainders.capacity() * size_of::<RemainderPosting<SizedTypeFor<V>>>()
+            + self.var_size_data.capacity()
+    }
 }
 
 impl<V: PostingValue> FromIterator<(PointOffsetType, V)> for PostingList<V> {
@@ -160,6 +160,14 @@ where
     // There is no such id
     assert!(!visitor.contains(postings_count));
 
+    // skipping to an offset lands on the element at that offset
+    for offset in (0..model.len()).step_by(CHUNK_LEN / 3) {
+        let mut skipping_iter = posting_list.iter();
+        skipping_iter.skip_to(offset);
+        let (expected_id, _) = &model[offset];
+        assert_eq!(skipping_iter.next().map(|elem| elem.id), Some(*expected_id));
+    }
+
     // intersect against all sequential ids in the posting range, model is a hashmap in this case
     let model = model.into_iter().collect::<HashMap<_, _>>();
     let mut intersection_iter = posting_list.iter();
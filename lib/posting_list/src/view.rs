@@ -21,6 +21,15 @@ pub struct PostingListView<'a, V: PostingValue> {
     pub(crate) _phantom: PhantomData<V>,
 }
 
+// Implemented manually, derive would require `V: Clone`
+impl<V: PostingValue> Clone for PostingListView<'_, V> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<V: PostingValue> Copy for PostingListView<'_, V> {}
+
 pub struct PostingListComponents<'a, S> {
     pub id_data: &'a [u8],
     pub chunks: &'a [PostingChunk<S>],
@@ -1,3 +1,4 @@
+use std::hash::Hasher;
 #[cfg(feature = "testing")]
 use std::io::{Read, Write};
 #[cfg(feature = "testing")]
@@ -15,6 +16,7 @@ use common::types::PointOffsetType;
 use fs_err as fs;
 #[cfg(feature = "testing")]
 use fs_err::File;
+use seahash::SeaHasher;
 
 pub trait EncodedStorage {
     fn get_vector_data(&self, index: PointOffsetType) -> &[u8];
@@ -35,6 +37,37 @@ pub trait EncodedStorage {
     fn files(&self) -> Vec<PathBuf>;
 
     fn immutable_files(&self) -> Vec<PathBuf>;
+
+    /// Checksum of all encoded vectors, the same on every platform.
+    fn checksum(&self) -> u64 {
+        let mut hasher = SeaHasher::new();
+        for index in 0..self.vectors_count() {
+            hasher.write(self.get_vector_data(index as PointOffsetType));
+        }
+        hasher.finish()
+    }
+}
+
+/// Check the encoded vectors of `storage` against the `expected` checksum from metadata.
+///
+/// Metadata written before checksums were introduced has none, and is not checked.
+pub(crate) fn verify_checksum(
+    storage: &impl EncodedStorage,
+    expected: Option<u64>,
+) -> std::io::Result<()> {
+    let Some(expected) = expected else {
+        return Ok(());
+    };
+    let checksum = storage.checksum();
+    if checksum != expected {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            format!(
+                "Encoded vectors checksum mismatch, expected {expected:016x}, found {checksum:016x}",
+            ),
+        ));
+    }
+    Ok(())
 }
 
 pub trait EncodedStorageBuilder {
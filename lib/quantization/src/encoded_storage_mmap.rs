@@ -0,0 +1,310 @@
+//! Immutable encoded vectors in a single memory mapped file.
+//!
+//! Layout:
+//!
+//! ```text
+//! | "qenc" | version: u32 | quantized vector size: u64 | vectors count: u64 | zeros up to 64 bytes |
+//! | vector 0 | vector 1 | ... |
+//! ```
+//!
+//! The header is padded to 64 bytes, so that encoded vectors stay aligned for SIMD scoring.
+//! Files written before the header was introduced hold the vectors only, and are still readable.
+
+use std::io::Read;
+use std::num::NonZeroUsize;
+use std::path::{Path, PathBuf};
+
+use common::counter::hardware_counter::HardwareCounterCell;
+use common::fs::clear_disk_cache;
+use common::mmap::{Madviseable, MmapFlusher, advice};
+use common::types::PointOffsetType;
+use fs_err as fs;
+use fs_err::{File, OpenOptions};
+use memmap2::{Mmap, MmapMut};
+
+use crate::{EncodedStorage, EncodedStorageBuilder};
+
+const HEADER_MAGIC: &[u8; 4] = b"qenc";
+const HEADER_SIZE: usize = 64;
+const FORMAT_VERSION: u32 = 1;
+
+#[derive(Debug)]
+pub struct EncodedStorageMmap {
+    mmap: Mmap,
+    /// Offset of the first vector in the file, zero for files without header
+    data_offset: usize,
+    quantized_vector_size: NonZeroUsize,
+    path: PathBuf,
+}
+
+pub struct EncodedStorageMmapBuilder {
+    mmap: MmapMut,
+    cursor_pos: usize,
+    quantized_vector_size: NonZeroUsize,
+    path: PathBuf,
+}
+
+impl EncodedStorageMmap {
+    pub fn from_file(path: &Path, quantized_vector_size: usize) -> std::io::Result<Self> {
+        let quantized_vector_size = non_zero_vector_size(quantized_vector_size)?;
+        let file = OpenOptions::new().read(true).open(path)?;
+        let mmap = unsafe { Mmap::map(&file)? };
+        advice::madvise(&mmap, advice::get_global())?;
+
+        let data_offset = validate_layout(&mmap, mmap.len(), quantized_vector_size)?;
+        Ok(Self {
+            mmap,
+            data_offset,
+            quantized_vector_size,
+            path: path.to_path_buf(),
+        })
+    }
+
+    /// Load all vectors into the page cache.
+    pub fn populate(&self) {
+        self.mmap.populate();
+    }
+
+    /// Drop the file from the page cache.
+    pub fn clear_cache(&self) -> std::io::Result<()> {
+        clear_disk_cache(&self.path)
+    }
+}
+
+/// Offset of the first vector in the encoded vectors file at `path`.
+///
+/// For storages which read the file written by [`EncodedStorageMmapBuilder`] without mapping it.
+pub fn data_offset(path: &Path, quantized_vector_size: usize) -> std::io::Result<usize> {
+    let quantized_vector_size = non_zero_vector_size(quantized_vector_size)?;
+    let file_len = fs::metadata(path)?.len() as usize;
+    let mut header = Vec::with_capacity(HEADER_SIZE);
+    File::open(path)?
+        .take(HEADER_SIZE as u64)
+        .read_to_end(&mut header)?;
+    validate_layout(&header, file_len, quantized_vector_size)
+}
+
+impl EncodedStorage for EncodedStorageMmap {
+    fn get_vector_data(&self, index: PointOffsetType) -> &[u8] {
+        let start = self.data_offset + self.quantized_vector_size.get() * index as usize;
+        let end = start + self.quantized_vector_size.get();
+        self.mmap.get(start..end).unwrap_or(&[])
+    }
+
+    fn upsert_vector(
+        &mut self,
+        _id: PointOffsetType,
+        _vector: &[u8],
+        _hw_counter: &HardwareCounterCell,
+    ) -> std::io::Result<()> {
+        Err(std::io::Error::new(
+            std::io::ErrorKind::Unsupported,
+            "Cannot upsert vector in mmap storage",
+        ))
+    }
+
+    fn is_on_disk(&self) -> bool {
+        true
+    }
+
+    fn vectors_count(&self) -> usize {
+        (self.mmap.len() - self.data_offset) / self.quantized_vector_size.get()
+    }
+
+    fn flusher(&self) -> MmapFlusher {
+        // Mmap storage does not need a flusher, as it is non-appendable and already backed by a file.
+        Box::new(|| Ok(()))
+    }
+
+    fn files(&self) -> Vec<PathBuf> {
+        vec![self.path.clone()]
+    }
+
+    fn immutable_files(&self) -> Vec<PathBuf> {
+        vec![self.path.clone()]
+    }
+}
+
+impl EncodedStorageMmapBuilder {
+    pub fn new(
+        path: &Path,
+        vectors_count: usize,
+        quantized_vector_size: usize,
+    ) -> std::io::Result<Self> {
+        let quantized_vector_size = non_zero_vector_size(quantized_vector_size)?;
+        let encoded_storage_size = HEADER_SIZE + quantized_vector_size.get() * vectors_count;
+        path.parent()
+            .ok_or_else(|| {
+                std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    "Path must have a parent directory",
+                )
+            })
+            .and_then(fs::create_dir_all)?;
+
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            // Don't truncate because we explicitly set the length later
+            .truncate(false)
+            .open(path)?;
+        file.set_len(encoded_storage_size as u64)?;
+
+        let mut mmap = unsafe { MmapMut::map_mut(&file) }?;
+        advice::madvise(&mmap, advice::get_global())?;
+
+        mmap[..HEADER_SIZE].fill(0);
+        mmap[0..4].copy_from_slice(HEADER_MAGIC);
+        mmap[4..8].copy_from_slice(&FORMAT_VERSION.to_le_bytes());
+        mmap[8..16].copy_from_slice(&(quantized_vector_size.get() as u64).to_le_bytes());
+        mmap[16..24].copy_from_slice(&(vectors_count as u64).to_le_bytes());
+
+        Ok(Self {
+            mmap,
+            cursor_pos: HEADER_SIZE,
+            quantized_vector_size,
+            path: path.to_path_buf(),
+        })
+    }
+}
+
+impl EncodedStorageBuilder for EncodedStorageMmapBuilder {
+    type Storage = EncodedStorageMmap;
+
+    fn build(self) -> std::io::Result<EncodedStorageMmap> {
+        if self.cursor_pos != self.mmap.len() {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!(
+                    "Encoded vectors fill {} of {} allocated bytes",
+                    self.cursor_pos,
+                    self.mmap.len(),
+                ),
+            ));
+        }
+        self.mmap.flush()?;
+        let mmap = self.mmap.make_read_only()?;
+        Ok(EncodedStorageMmap {
+            mmap,
+            data_offset: HEADER_SIZE,
+            quantized_vector_size: self.quantized_vector_size,
+            path: self.path,
+        })
+    }
+
+    fn push_vector_data(&mut self, other: &[u8]) -> std::io::Result<()> {
+        debug_assert_eq!(
+            self.quantized_vector_size.get(),
+            other.len(),
+            "Pushed vector size does not match expected quantized vector size"
+        );
+        let end = self.cursor_pos + other.len();
+        let Some(target) = self.mmap.get_mut(self.cursor_pos..end) else {
+            return Err(std::io::Error::other(format!(
+                "Overflow allocated quantization storage mmap file (cursor_pos {} + len {} > total {})",
+                self.cursor_pos,
+                other.len(),
+                self.mmap.len(),
+            )));
+        };
+        target.copy_from_slice(other);
+        self.cursor_pos = end;
+        Ok(())
+    }
+}
+
+fn non_zero_vector_size(quantized_vector_size: usize) -> std::io::Result<NonZeroUsize> {
+    NonZeroUsize::new(quantized_vector_size).ok_or_else(|| {
+        std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            "`quantized_vector_size` must be non-zero",
+        )
+    })
+}
+
+/// Validate the header at the start of a file of `file_len` bytes, and get the offset of vectors.
+fn validate_layout(
+    start: &[u8],
+    file_len: usize,
+    quantized_vector_size: NonZeroUsize,
+) -> std::io::Result<usize> {
+    let invalid = |message: String| std::io::Error::new(std::io::ErrorKind::InvalidData, message);
+
+    if !start.starts_with(HEADER_MAGIC) {
+        // Legacy file without header
+        if !file_len.is_multiple_of(quantized_vector_size.get()) {
+            return Err(invalid(format!(
+                "Encoded file size ({file_len}) is not a multiple of quantized_vector_size ({quantized_vector_size})",
+            )));
+        }
+        return Ok(0);
+    }
+
+    if start.len() < HEADER_SIZE {
+        return Err(invalid(format!(
+            "Encoded file is too small for its header ({file_len} bytes)",
+        )));
+    }
+    let read_u64 = |pos: usize| u64::from_le_bytes(start[pos..pos + 8].try_into().unwrap());
+
+    let version = u32::from_le_bytes(start[4..8].try_into().unwrap());
+    if version != FORMAT_VERSION {
+        return Err(invalid(format!(
+            "Unsupported encoded file format version {version}",
+        )));
+    }
+    let stored_vector_size = read_u64(8);
+    if stored_vector_size != quantized_vector_size.get() as u64 {
+        return Err(invalid(format!(
+            "Encoded file has vectors of {stored_vector_size} bytes, expected {quantized_vector_size}",
+        )));
+    }
+    let vectors_count = read_u64(16) as usize;
+    let expected_len = HEADER_SIZE + vectors_count * quantized_vector_size.get();
+    if file_len != expected_len {
+        return Err(invalid(format!(
+            "Encoded file size ({file_len}) doesn't match {vectors_count} vectors ({expected_len})",
+        )));
+    }
+    Ok(HEADER_SIZE)
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::Builder;
+
+    use super::*;
+
+    #[test]
+    fn test_encoded_storage_mmap() {
+        let dir = Builder::new().prefix("encoded_storage").tempdir().unwrap();
+        let path = dir.path().join("quantized.data");
+
+        let mut builder = EncodedStorageMmapBuilder::new(&path, 3, 4).unwrap();
+        for i in 0..3u8 {
+            builder.push_vector_data(&[i; 4]).unwrap();
+        }
+        builder.build().unwrap();
+
+        let storage = EncodedStorageMmap::from_file(&path, 4).unwrap();
+        assert_eq!(storage.vectors_count(), 3);
+        assert_eq!(storage.get_vector_data(2), &[2; 4]);
+        assert_eq!(data_offset(&path, 4).unwrap(), HEADER_SIZE);
+
+        storage.populate();
+        storage.clear_cache().unwrap();
+
+        // Wrong vector size is an error instead of misaligned vectors
+        let err = EncodedStorageMmap::from_file(&path, 2).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+
+        // Files without header are still readable
+        let legacy_path = dir.path().join("legacy.data");
+        fs::write(&legacy_path, [[0u8; 4], [1; 4]].concat()).unwrap();
+        let storage = EncodedStorageMmap::from_file(&legacy_path, 4).unwrap();
+        assert_eq!(storage.vectors_count(), 2);
+        assert_eq!(storage.get_vector_data(1), &[1; 4]);
+        assert_eq!(data_offset(&legacy_path, 4).unwrap(), 0);
+    }
+}
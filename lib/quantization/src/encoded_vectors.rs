@@ -4,10 +4,33 @@ use common::counter::hardware_counter::HardwareCounterCell;
 use common::mmap::MmapFlusher;
 use common::typelevel::TBool;
 use common::types::PointOffsetType;
+use half::f16;
 use serde::{Deserialize, Serialize};
 
 use crate::EncodingError;
 
+/// Element type of vectors accepted by encoding.
+///
+/// Storages holding `f16` vectors can be encoded directly, without expanding them into `f32`
+/// buffers first. Values are converted one at a time while encoding.
+pub trait EncodeElement: Copy + Send + Sync + 'static {
+    fn to_f32(self) -> f32;
+}
+
+impl EncodeElement for f32 {
+    #[inline]
+    fn to_f32(self) -> f32 {
+        self
+    }
+}
+
+impl EncodeElement for f16 {
+    #[inline]
+    fn to_f32(self) -> f32 {
+        f16::to_f32(self)
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
 pub enum DistanceType {
     Dot,
@@ -91,8 +114,8 @@ impl DistanceType {
     }
 }
 
-pub(crate) fn validate_vector_parameters<'a>(
-    data: impl Iterator<Item = impl AsRef<[f32]> + 'a> + Clone,
+pub(crate) fn validate_vector_parameters<'a, T: EncodeElement>(
+    data: impl Iterator<Item = impl AsRef<[T]> + 'a> + Clone,
     vector_parameters: &VectorParameters,
 ) -> Result<(), EncodingError> {
     let mut count = 0;
@@ -14,6 +14,7 @@ use fs_err as fs;
 use serde::{Deserialize, Serialize};
 use strum::EnumIter;
 
+use crate::encoded_storage::verify_checksum;
 use crate::encoded_vectors::validate_vector_parameters;
 use crate::vector_stats::{VectorElementStats, VectorStats};
 use crate::{
@@ -23,7 +24,8 @@ use crate::{
 
 // v1 and earlier: encoded words persisted in native-endian (non-portable on BE).
 // v2+: encoded words persisted in canonical little-endian bytes.
-const METADATA_FORMAT_VERSION: u32 = 2;
+// v3+: metadata records word width, byte order and count of the encoded vectors, validated on load.
+const METADATA_FORMAT_VERSION: u32 = 3;
 
 pub const fn metadata_format_version() -> u32 {
     METADATA_FORMAT_VERSION
@@ -51,6 +53,14 @@ impl StorageWordOrder {
     }
 }
 
+/// Byte order of words in the data file, recorded since metadata v3
+#[derive(Clone, Copy, Eq, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum DataByteOrder {
+    LittleEndian,
+    BigEndian,
+}
+
 pub struct EncodedVectorsBin<TBitsStoreType: BitsStoreType, TStorage: EncodedStorage> {
     encoded_vectors: TStorage,
     metadata: Metadata,
@@ -143,17 +153,104 @@ struct Metadata {
     format_version: u32,
     vector_parameters: VectorParameters,
     #[serde(default)]
-    #[serde(skip_serializing_if = "Encoding::is_one")]
     encoding: Encoding,
     #[serde(default)]
-    #[serde(skip_serializing_if = "QueryEncoding::is_same_as_storage")]
     query_encoding: QueryEncoding,
+    /// Number of bits in words of encoded vectors, required since v3
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    word_bits: Option<u32>,
+    /// Byte order of words in the data file, required since v3
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    byte_order: Option<DataByteOrder>,
+    /// Number of encoded vectors when the metadata was saved, required since v3
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    vectors_count: Option<usize>,
+    /// Checksum of encoded vectors, see [`EncodedStorage::checksum`]
+    ///
+    /// Removed on the first upsert, as vectors of mutable storages change.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    checksum: Option<u64>,
 
     #[serde(default)]
     #[serde(skip_serializing_if = "Option::is_none")]
     vector_stats: Option<VectorStats>,
 }
 
+impl Metadata {
+    /// Validate the metadata against the loaded data, and get the word order of the data file.
+    ///
+    /// Mismatches would silently produce wrong scores, so they are reported as errors instead.
+    fn validate<TBitsStoreType: BitsStoreType>(
+        &self,
+        vectors_count: usize,
+    ) -> std::io::Result<StorageWordOrder> {
+        let invalid = |message: String| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("Invalid binary quantization metadata: {message}"),
+            )
+        };
+
+        if self.format_version > METADATA_FORMAT_VERSION {
+            return Err(invalid(format!(
+                "unsupported format version {}",
+                self.format_version,
+            )));
+        }
+
+        let needs_stats = !self.encoding.is_one() || !self.query_encoding.is_same_as_storage();
+        if needs_stats && self.vector_stats.is_none() {
+            return Err(invalid(format!(
+                "vector stats are required by encoding {:?} with query encoding {:?}",
+                self.encoding, self.query_encoding,
+            )));
+        }
+
+        if self.format_version < 3 {
+            return Ok(StorageWordOrder::from_metadata_format_version(
+                self.format_version,
+            ));
+        }
+
+        let (Some(word_bits), Some(byte_order), Some(expected_count)) =
+            (self.word_bits, self.byte_order, self.vectors_count)
+        else {
+            return Err(invalid(format!(
+                "word bits, byte order and vectors count are required by format version {}",
+                self.format_version,
+            )));
+        };
+
+        let expected_word_bits = TBitsStoreType::WORD_BITS;
+        if word_bits != expected_word_bits {
+            return Err(invalid(format!(
+                "vectors are stored in {word_bits}-bit words, expected {expected_word_bits}-bit words",
+            )));
+        }
+
+        // Vectors are appended to mutable storages after the metadata is saved
+        if vectors_count < expected_count {
+            return Err(invalid(format!(
+                "data file has {vectors_count} vectors, expected at least {expected_count}",
+            )));
+        }
+
+        match byte_order {
+            DataByteOrder::LittleEndian => Ok(StorageWordOrder::CanonicalLe),
+            DataByteOrder::BigEndian if cfg!(target_endian = "big") => {
+                Ok(StorageWordOrder::LegacyNative)
+            }
+            DataByteOrder::BigEndian => Err(invalid(
+                "big-endian data file can't be read on a little-endian platform".to_string(),
+            )),
+        }
+    }
+}
+
 pub trait BitsStoreType:
     Default
     + Copy
@@ -168,6 +265,9 @@ pub trait BitsStoreType:
     + bytemuck::Pod
     + std::fmt::Debug
 {
+    /// Number of bits in a word
+    const WORD_BITS: u32 = u8::BITS * std::mem::size_of::<Self>() as u32;
+
     /// Xor vectors and return the number of bits set to 1
     ///
     /// Assume that `v1` and `v2` are aligned to `BITS_STORE_TYPE_SIZE` with both with zeros
@@ -528,6 +628,10 @@ impl<TBitsStoreType: BitsStoreType, TStorage: EncodedStorage>
             vector_parameters: vector_parameters.clone(),
             encoding,
             query_encoding,
+            word_bits: Some(TBitsStoreType::WORD_BITS),
+            byte_order: Some(DataByteOrder::LittleEndian),
+            vectors_count: Some(encoded_vectors.vectors_count()),
+            checksum: Some(encoded_vectors.checksum()),
             vector_stats,
         };
         if let Some(meta_path) = meta_path {
@@ -562,17 +666,8 @@ impl<TBitsStoreType: BitsStoreType, TStorage: EncodedStorage>
     pub fn load(encoded_vectors: TStorage, meta_path: &Path) -> std::io::Result<Self> {
         let contents = fs::read_to_string(meta_path)?;
         let metadata: Metadata = serde_json::from_str(&contents)?;
-        if metadata.format_version > METADATA_FORMAT_VERSION {
-            return Err(std::io::Error::new(
-                std::io::ErrorKind::InvalidData,
-                format!(
-                    "Unsupported binary quantization metadata format version {}",
-                    metadata.format_version
-                ),
-            ));
-        }
         let storage_word_order =
-            StorageWordOrder::from_metadata_format_version(metadata.format_version);
+            metadata.validate::<TBitsStoreType>(encoded_vectors.vectors_count())?;
         let result = Self {
             metadata,
             metadata_path: Some(meta_path.to_path_buf()),
@@ -583,6 +678,11 @@ impl<TBitsStoreType: BitsStoreType, TStorage: EncodedStorage>
         Ok(result)
     }
 
+    /// Check encoded vectors against the checksum in metadata.
+    pub fn verify(&self) -> std::io::Result<()> {
+        verify_checksum(&self.encoded_vectors, self.metadata.checksum)
+    }
+
     fn encode_vector(
         vector: &[f32],
         vector_stats: &Option<VectorStats>,
@@ -990,6 +1090,13 @@ impl<TBitsStoreType: BitsStoreType, TStorage: EncodedStorage> EncodedVectors
         vector: &[f32],
         hw_counter: &HardwareCounterCell,
     ) -> std::io::Result<()> {
+        // Persist removal of the checksum before vectors stop matching it
+        if self.metadata.checksum.take().is_some()
+            && let Some(meta_path) = &self.metadata_path
+        {
+            atomic_save_json(meta_path, &self.metadata)?;
+        }
+
         let mut encoded_vector =
             Self::encode_vector(vector, &self.metadata.vector_stats, self.metadata.encoding);
         Self::canonicalize_words_in_place(
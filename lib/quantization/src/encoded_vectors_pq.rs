@@ -18,7 +18,7 @@ use fs_err as fs;
 use parking_lot::Mutex;
 use serde::{Deserialize, Serialize};
 
-use crate::encoded_storage::{EncodedStorage, EncodedStorageBuilder};
+use crate::encoded_storage::{EncodedStorage, EncodedStorageBuilder, verify_checksum};
 use crate::encoded_vectors::{EncodedVectors, VectorParameters, validate_vector_parameters};
 use crate::kmeans::kmeans;
 use crate::{ConditionalVariable, EncodingError};
@@ -46,6 +46,10 @@ pub struct Metadata {
     pub centroids: Vec<Vec<f32>>,
     pub vector_division: Vec<Range<usize>>,
     pub vector_parameters: VectorParameters,
+    /// Checksum of encoded vectors, see [`EncodedStorage::checksum`]
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub checksum: Option<u64>,
 }
 
 impl<TStorage: EncodedStorage> EncodedVectorsPQ<TStorage> {
@@ -107,6 +111,7 @@ impl<TStorage: EncodedStorage> EncodedVectorsPQ<TStorage> {
             centroids,
             vector_division,
             vector_parameters: vector_parameters.clone(),
+            checksum: Some(encoded_vectors.checksum()),
         };
         if let Some(meta_path) = meta_path {
             meta_path
@@ -150,6 +155,11 @@ impl<TStorage: EncodedStorage> EncodedVectorsPQ<TStorage> {
         Ok(result)
     }
 
+    /// Check encoded vectors against the checksum in metadata.
+    pub fn verify(&self) -> std::io::Result<()> {
+        verify_checksum(&self.encoded_vectors, self.metadata.checksum)
+    }
+
     pub fn get_quantized_vector_size(
         vector_parameters: &VectorParameters,
         chunk_size: usize,
@@ -1,6 +1,6 @@
 use std::alloc::Layout;
 use std::path::{Path, PathBuf};
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 
 use common::counter::hardware_counter::HardwareCounterCell;
 use common::fs::atomic_save_json;
@@ -8,26 +8,43 @@ use common::mmap::MmapFlusher;
 use common::typelevel::True;
 use common::types::PointOffsetType;
 use fs_err as fs;
+use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
 use serde::{Deserialize, Serialize};
 
 use crate::EncodingError;
-use crate::encoded_storage::{EncodedStorage, EncodedStorageBuilder};
+use crate::encoded_storage::{EncodedStorage, EncodedStorageBuilder, verify_checksum};
 use crate::encoded_vectors::{
-    DistanceType, EncodedVectors, VectorParameters, validate_vector_parameters,
+    DistanceType, EncodeElement, EncodedVectors, VectorParameters, validate_vector_parameters,
+};
+use crate::quantile::{
+    find_interval_per_coordinate, find_min_max_from_iter, find_quantile_interval,
 };
-use crate::quantile::{find_min_max_from_iter, find_quantile_interval};
 
 pub const ALIGNMENT: usize = 16;
 // Each encoded vector stores an additional f32 at the beginning. Define it's size here.
 const ADDITIONAL_CONSTANT_SIZE: usize = std::mem::size_of::<f32>();
+/// Number of vectors encoded together, between checks of the stop flag and progress updates
+const ENCODE_CHUNK_SIZE: usize = 4 * 1024;
 // v1 and earlier: per-vector constant persisted in native-endian (non-portable on BE).
 // v2+: per-vector constant persisted in canonical little-endian.
-const METADATA_FORMAT_VERSION: u32 = 2;
+// v3+: optional per-dimension quantization parameters.
+const METADATA_FORMAT_VERSION: u32 = 3;
+const DIMENSIONS_MIN_FORMAT_VERSION: u32 = 3;
+/// Version written for metadata without per-dimension parameters, readable by older versions
+const COMMON_METADATA_FORMAT_VERSION: u32 = 2;
 
 pub const fn metadata_format_version() -> u32 {
     METADATA_FORMAT_VERSION
 }
 
+/// Lowest format version which can store the given per-dimension parameters.
+fn written_format_version(dimensions: Option<&DimensionParametersTable>) -> u32 {
+    match dimensions {
+        Some(_) => DIMENSIONS_MIN_FORMAT_VERSION,
+        None => COMMON_METADATA_FORMAT_VERSION,
+    }
+}
+
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 enum VectorOffsetEncoding {
     LegacyNative,
@@ -48,6 +65,10 @@ impl VectorOffsetEncoding {
 #[derive(Clone, PartialEq, Debug)]
 pub enum ScalarQuantizationMethod {
     Int8,
+    /// Int8 with quantization interval computed and stored for each dimension.
+    ///
+    /// More accurate for vectors with non-uniform ranges of dimensions, but scored without SIMD.
+    Int8PerDimension,
     // Future methods can be added here
 }
 
@@ -82,6 +103,12 @@ impl Metadata {
         }
     }
 
+    fn dimensions(&self) -> Option<&DimensionParametersTable> {
+        match self {
+            Metadata::Int8(meta) => meta.dimensions.as_ref(),
+        }
+    }
+
     pub fn postprocess_score(&self, score: f32, query_offset: f32, vector_offset: f32) -> f32 {
         match self {
             Metadata::Int8(metadata) => {
@@ -113,6 +140,147 @@ struct MetadataInt8 {
     offset: f32,
     multiplier: f32,
     vector_parameters: VectorParameters,
+    /// Quantization intervals of each dimension, used instead of global `alpha` and `offset`
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    dimensions: Option<DimensionParametersTable>,
+    /// Checksum of encoded vectors, see [`EncodedStorage::checksum`]
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    checksum: Option<u64>,
+}
+
+/// Quantization interval of a single dimension.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+struct DimensionParameters {
+    alpha: f32,
+    offset: f32,
+}
+
+impl DimensionParameters {
+    fn from_min_max(min: f32, max: f32) -> Self {
+        Self {
+            alpha: (max - min).max(0.0) / 127.0,
+            offset: min,
+        }
+    }
+
+    #[inline]
+    fn encode_value(&self, value: f32) -> u8 {
+        if self.alpha == 0.0 {
+            return 0;
+        }
+        let i = (value - self.offset) / self.alpha;
+        i.clamp(0.0, 127.0).round() as u8
+    }
+
+    #[inline]
+    fn decode_value(&self, value: u8) -> f32 {
+        self.offset + self.alpha * f32::from(value)
+    }
+}
+
+/// Quantization intervals of all dimensions.
+///
+/// Persisted in metadata as a hex string of little-endian `alpha` and `offset` of each dimension,
+/// so that the table is bit-exact and independent of the platform.
+#[derive(Clone, Debug, PartialEq)]
+struct DimensionParametersTable(Vec<DimensionParameters>);
+
+impl DimensionParametersTable {
+    const ENTRY_SIZE: usize = 2 * size_of::<f32>();
+
+    fn to_le_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(self.0.len() * Self::ENTRY_SIZE);
+        for dimension in &self.0 {
+            bytes.extend_from_slice(&dimension.alpha.to_le_bytes());
+            bytes.extend_from_slice(&dimension.offset.to_le_bytes());
+        }
+        bytes
+    }
+
+    fn from_le_bytes(bytes: &[u8]) -> Result<Self, String> {
+        if !bytes.len().is_multiple_of(Self::ENTRY_SIZE) {
+            return Err(format!(
+                "Dimension parameters size ({}) is not a multiple of {}",
+                bytes.len(),
+                Self::ENTRY_SIZE,
+            ));
+        }
+        let read_f32 = |bytes: &[u8]| f32::from_le_bytes(bytes.try_into().unwrap());
+        let dimensions = bytes
+            .chunks_exact(Self::ENTRY_SIZE)
+            .map(|entry| DimensionParameters {
+                alpha: read_f32(&entry[..4]),
+                offset: read_f32(&entry[4..]),
+            })
+            .collect();
+        Ok(Self(dimensions))
+    }
+
+    fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Encode `vector` into `encoded`, one value per dimension.
+    fn encode_vector<T: EncodeElement>(&self, vector: &[T], encoded: &mut Vec<u8>) {
+        encoded.extend(
+            self.0
+                .iter()
+                .zip(vector)
+                .map(|(dimension, value)| dimension.encode_value(value.to_f32())),
+        );
+    }
+
+    /// Score encoded `query` and `vector` with decoded values.
+    ///
+    /// Intervals differ between dimensions, so integer products of encoded values can't be summed
+    /// up as in the global mode.
+    fn score(&self, vector_parameters: &VectorParameters, query: &[u8], vector: &[u8]) -> f32 {
+        let values = self
+            .0
+            .iter()
+            .zip(query.iter().zip(vector))
+            .map(|(dimension, (&q, &v))| (dimension.decode_value(q), dimension.decode_value(v)));
+        let score = match vector_parameters.distance_type {
+            DistanceType::Dot => values.map(|(q, v)| q * v).sum::<f32>(),
+            DistanceType::L1 => values.map(|(q, v)| (q - v).abs()).sum::<f32>(),
+            DistanceType::L2 => values.map(|(q, v)| (q - v) * (q - v)).sum::<f32>(),
+        };
+        if vector_parameters.invert {
+            -score
+        } else {
+            score
+        }
+    }
+}
+
+impl Serialize for DimensionParametersTable {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let hex: String = self
+            .to_le_bytes()
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect();
+        serializer.serialize_str(&hex)
+    }
+}
+
+impl<'de> Deserialize<'de> for DimensionParametersTable {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde::de::Error;
+
+        let hex = String::deserialize(deserializer)?;
+        if !hex.len().is_multiple_of(2) {
+            return Err(D::Error::custom("Odd length of dimension parameters"));
+        }
+        let bytes = (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(hex.get(i..i + 2).unwrap_or_default(), 16))
+            .collect::<Result<Vec<u8>, _>>()
+            .map_err(D::Error::custom)?;
+        Self::from_le_bytes(&bytes).map_err(D::Error::custom)
+    }
 }
 
 impl MetadataInt8 {
@@ -138,6 +306,58 @@ impl MetadataInt8 {
         self.postprocess_score(score, query_offset, vector_2_offset)
     }
 
+    /// Encode `vector`, prefixed with its constant for scoring.
+    fn encode_vector<T: EncodeElement>(&self, vector: &[T]) -> Vec<u8> {
+        let vector_parameters = &self.vector_parameters;
+        let mut encoded_vector = Vec::with_capacity(self.actual_dim + ADDITIONAL_CONSTANT_SIZE);
+        // Persist the per-vector constant in canonical little-endian so segments can be moved
+        // across endianness.
+        encoded_vector.extend_from_slice(&f32::default().to_le_bytes());
+        if let Some(dimensions) = &self.dimensions {
+            // Per-dimension scoring doesn't use the constant, and skips the alignment padding
+            dimensions.encode_vector(vector, &mut encoded_vector);
+            encoded_vector.resize(self.actual_dim + ADDITIONAL_CONSTANT_SIZE, 0);
+            return encoded_vector;
+        }
+        for value in vector {
+            let encoded = self.encode_value(value.to_f32());
+            encoded_vector.push(encoded);
+        }
+        if !vector_parameters.dim.is_multiple_of(ALIGNMENT) {
+            for _ in 0..(ALIGNMENT - vector_parameters.dim % ALIGNMENT) {
+                let placeholder = match vector_parameters.distance_type {
+                    DistanceType::Dot => 0.0,
+                    DistanceType::L1 | DistanceType::L2 => self.offset,
+                };
+                let encoded = self.encode_value(placeholder);
+                encoded_vector.push(encoded);
+            }
+        }
+        let vector_offset = match vector_parameters.distance_type {
+            DistanceType::Dot => {
+                let elements_sum = encoded_vector.iter().map(|&x| f32::from(x)).sum::<f32>();
+                elements_sum * self.alpha * self.offset
+            }
+            DistanceType::L1 => 0.0,
+            DistanceType::L2 => {
+                let elements_sqr_sum = encoded_vector
+                    .iter()
+                    .map(|&x| f32::from(x) * f32::from(x))
+                    .sum::<f32>();
+                elements_sqr_sum * self.alpha * self.alpha
+            }
+        };
+        let vector_offset = if vector_parameters.invert {
+            -vector_offset
+        } else {
+            vector_offset
+        };
+        // apply `a^2` shift
+        let vector_offset = self.get_shift() + vector_offset;
+        encoded_vector[0..ADDITIONAL_CONSTANT_SIZE].copy_from_slice(&vector_offset.to_le_bytes());
+        encoded_vector
+    }
+
     fn get_shift(&self) -> f32 {
         // Dotprod after shifting produces a number which is not related to vector and query
         // (x - a)(y - a) = xy - ax - ay + a^2
@@ -163,27 +383,70 @@ impl<TStorage: EncodedStorage> EncodedVectorsU8<TStorage> {
     }
 
     #[allow(clippy::too_many_arguments)]
-    pub fn encode<'a>(
-        orig_data: impl Iterator<Item = impl AsRef<[f32]> + 'a> + Clone,
+    pub fn encode<'a, T: EncodeElement>(
+        orig_data: impl Iterator<Item = impl AsRef<[T]> + Sync + 'a> + Clone,
+        storage_builder: impl EncodedStorageBuilder<Storage = TStorage>,
+        vector_parameters: &VectorParameters,
+        count: usize,
+        quantile: Option<f32>,
+        method: ScalarQuantizationMethod,
+        meta_path: Option<&Path>,
+        stopped: &AtomicBool,
+    ) -> Result<Self, EncodingError> {
+        Self::encode_parallel(
+            orig_data,
+            storage_builder,
+            vector_parameters,
+            count,
+            quantile,
+            method,
+            meta_path,
+            1,
+            None,
+            stopped,
+        )
+    }
+
+    /// Encode vectors in chunks of [`ENCODE_CHUNK_SIZE`], encoding each chunk on up to
+    /// `max_threads` threads.
+    ///
+    /// Encoded vectors are stored in the order of `orig_data`. The stop flag is checked for each
+    /// vector, and `progress` is increased by the number of vectors of each stored chunk.
+    ///
+    /// Input vectors may hold any [`EncodeElement`], e.g. `f16` vectors are encoded as they are,
+    /// converting each value on the fly.
+    #[allow(clippy::too_many_arguments)]
+    pub fn encode_parallel<'a, T: EncodeElement>(
+        orig_data: impl Iterator<Item = impl AsRef<[T]> + Sync + 'a> + Clone,
         mut storage_builder: impl EncodedStorageBuilder<Storage = TStorage>,
         vector_parameters: &VectorParameters,
         count: usize,
         quantile: Option<f32>,
         method: ScalarQuantizationMethod,
         meta_path: Option<&Path>,
+        max_threads: usize,
+        progress: Option<&AtomicU64>,
         stopped: &AtomicBool,
     ) -> Result<Self, EncodingError> {
-        assert_eq!(method, ScalarQuantizationMethod::Int8);
         let actual_dim = Self::get_actual_dim(vector_parameters);
 
         if count == 0 {
+            let dimensions = match method {
+                ScalarQuantizationMethod::Int8 => None,
+                ScalarQuantizationMethod::Int8PerDimension => Some(DimensionParametersTable(vec![
+                        DimensionParameters::default();
+                        vector_parameters.dim
+                    ])),
+            };
             let metadata = Metadata::Int8(MetadataInt8 {
-                format_version: METADATA_FORMAT_VERSION,
+                format_version: written_format_version(dimensions.as_ref()),
                 actual_dim,
                 alpha: 0.0,
                 offset: 0.0,
                 multiplier: 0.0,
                 vector_parameters: vector_parameters.clone(),
+                dimensions,
+                checksum: None,
             });
             if let Some(meta_path) = meta_path {
                 meta_path
@@ -249,73 +512,96 @@ impl<TStorage: EncodedStorage> EncodedVectorsU8<TStorage> {
             multiplier
         };
 
+        let dimensions = match method {
+            ScalarQuantizationMethod::Int8 => None,
+            ScalarQuantizationMethod::Int8PerDimension => {
+                let intervals = find_interval_per_coordinate(
+                    orig_data.clone(),
+                    vector_parameters.dim,
+                    count,
+                    quantile.unwrap_or(1.0),
+                    max_threads,
+                    stopped,
+                )?;
+                Some(DimensionParametersTable(
+                    intervals
+                        .into_iter()
+                        .map(|(min, max)| DimensionParameters::from_min_max(min, max))
+                        .collect(),
+                ))
+            }
+        };
+
         let metadata = MetadataInt8 {
-            format_version: METADATA_FORMAT_VERSION,
+            format_version: written_format_version(dimensions.as_ref()),
             actual_dim,
             alpha,
             offset,
             multiplier,
             vector_parameters: vector_parameters.clone(),
+            dimensions,
+            checksum: None,
         };
 
-        for vector in orig_data {
+        let pool = (max_threads > 1)
+            .then(|| {
+                rayon::ThreadPoolBuilder::new()
+                    .thread_name(|idx| format!("sq-encoding-{idx}"))
+                    .num_threads(max_threads)
+                    .build()
+            })
+            .transpose()
+            .map_err(|e| {
+                EncodingError::EncodingError(format!(
+                    "Failed scalar encoding while thread pool init: {e}"
+                ))
+            })?;
+
+        let mut orig_data = orig_data;
+        loop {
             if stopped.load(Ordering::Relaxed) {
                 return Err(EncodingError::Stopped);
             }
-
-            let mut encoded_vector = Vec::with_capacity(actual_dim + ADDITIONAL_CONSTANT_SIZE);
-            // Persist the per-vector constant in canonical little-endian so segments can be moved
-            // across endianness.
-            encoded_vector.extend_from_slice(&f32::default().to_le_bytes());
-            for &value in vector.as_ref() {
-                let encoded = metadata.encode_value(value);
-                encoded_vector.push(encoded);
+            let chunk: Vec<_> = orig_data.by_ref().take(ENCODE_CHUNK_SIZE).collect();
+            if chunk.is_empty() {
+                break;
             }
-            if !vector_parameters.dim.is_multiple_of(ALIGNMENT) {
-                for _ in 0..(ALIGNMENT - vector_parameters.dim % ALIGNMENT) {
-                    let placeholder = match vector_parameters.distance_type {
-                        DistanceType::Dot => 0.0,
-                        DistanceType::L1 | DistanceType::L2 => offset,
-                    };
-                    let encoded = metadata.encode_value(placeholder);
-                    encoded_vector.push(encoded);
-                }
-            }
-            let vector_offset = match vector_parameters.distance_type {
-                DistanceType::Dot => {
-                    let elements_sum = encoded_vector.iter().map(|&x| f32::from(x)).sum::<f32>();
-                    elements_sum * alpha * offset
-                }
-                DistanceType::L1 => 0.0,
-                DistanceType::L2 => {
-                    let elements_sqr_sum = encoded_vector
-                        .iter()
-                        .map(|&x| f32::from(x) * f32::from(x))
-                        .sum::<f32>();
-                    elements_sqr_sum * alpha * alpha
-                }
+
+            // Collecting keeps the order of vectors, regardless of the order they are encoded in
+            let encode = |vector: &[T]| {
+                (!stopped.load(Ordering::Relaxed)).then(|| metadata.encode_vector(vector))
             };
-            let vector_offset = if vector_parameters.invert {
-                -vector_offset
-            } else {
-                vector_offset
+            let encoded_chunk: Option<Vec<Vec<u8>>> = match &pool {
+                Some(pool) => pool.install(|| {
+                    chunk
+                        .par_iter()
+                        .map(|vector| encode(vector.as_ref()))
+                        .collect()
+                }),
+                None => chunk.iter().map(|vector| encode(vector.as_ref())).collect(),
             };
-            // apply `a^2` shift
-            let vector_offset = metadata.get_shift() + vector_offset;
-            encoded_vector[0..ADDITIONAL_CONSTANT_SIZE]
-                .copy_from_slice(&vector_offset.to_le_bytes());
-            storage_builder
-                .push_vector_data(&encoded_vector)
-                .map_err(|e| {
-                    EncodingError::EncodingError(format!("Failed to push encoded vector: {e}",))
-                })?;
+            let encoded_chunk = encoded_chunk.ok_or(EncodingError::Stopped)?;
+
+            for encoded_vector in &encoded_chunk {
+                storage_builder
+                    .push_vector_data(encoded_vector)
+                    .map_err(|e| {
+                        EncodingError::EncodingError(format!("Failed to push encoded vector: {e}",))
+                    })?;
+            }
+            if let Some(progress) = progress {
+                progress.fetch_add(chunk.len() as u64, Ordering::Relaxed);
+            }
         }
 
         let encoded_vectors = storage_builder
             .build()
             .map_err(|e| EncodingError::EncodingError(format!("Failed to build storage: {e}",)))?;
 
-        let metadata = Metadata::Int8(metadata);
+        let metadata = Metadata::Int8(MetadataInt8 {
+            checksum: Some(encoded_vectors.checksum()),
+            ..metadata
+        });
         if let Some(meta_path) = meta_path {
             meta_path
                 .parent()
@@ -358,6 +644,20 @@ impl<TStorage: EncodedStorage> EncodedVectorsU8<TStorage> {
                         ),
                     ));
                 }
+                if let Some(dimensions) = &meta.dimensions
+                    && (meta.format_version < DIMENSIONS_MIN_FORMAT_VERSION
+                        || dimensions.len() != meta.vector_parameters.dim)
+                {
+                    return Err(std::io::Error::new(
+                        std::io::ErrorKind::InvalidData,
+                        format!(
+                            "Invalid scalar quantization dimension parameters: {} dimensions in metadata of version {}, expected {}",
+                            dimensions.len(),
+                            meta.format_version,
+                            meta.vector_parameters.dim,
+                        ),
+                    ));
+                }
             }
         }
         // Determine on-disk encoding rules from metadata (post validation).
@@ -375,7 +675,21 @@ impl<TStorage: EncodedStorage> EncodedVectorsU8<TStorage> {
         Ok(result)
     }
 
+    /// Check encoded vectors against the checksum in metadata.
+    pub fn verify(&self) -> std::io::Result<()> {
+        match &self.metadata {
+            Metadata::Int8(meta) => verify_checksum(&self.encoded_vectors, meta.checksum),
+        }
+    }
+
     pub fn score_point_simple(&self, query: &EncodedQueryU8, bytes: &[u8]) -> f32 {
+        if let Some(dimensions) = self.metadata.dimensions() {
+            return dimensions.score(
+                self.metadata.vector_parameters(),
+                &query.encoded_query,
+                &bytes[ADDITIONAL_CONSTANT_SIZE..],
+            );
+        }
         match &self.metadata {
             Metadata::Int8(metadata) => {
                 let (vector_offset, v_ptr) = self.parse_vec_data(bytes);
@@ -395,6 +709,11 @@ impl<TStorage: EncodedStorage> EncodedVectorsU8<TStorage> {
     }
 
     pub fn score_point_simple_internal(&self, i: PointOffsetType, j: PointOffsetType) -> f32 {
+        if let Some(dimensions) = self.metadata.dimensions() {
+            let (_, query) = self.get_quantized_vector_offset_and_code(i);
+            let (_, vector) = self.get_quantized_vector_offset_and_code(j);
+            return dimensions.score(self.metadata.vector_parameters(), query, vector);
+        }
         match &self.metadata {
             Metadata::Int8(metadata) => {
                 let (query_offset, q_ptr) = self.get_vec_ptr(i);
@@ -537,8 +856,8 @@ impl<TStorage: EncodedStorage> EncodedVectorsU8<TStorage> {
         }
     }
 
-    fn find_alpha_offset_size_dim<'a>(
-        orig_data: impl Iterator<Item = impl AsRef<[f32]> + 'a> + Clone,
+    fn find_alpha_offset_size_dim<'a, T: EncodeElement>(
+        orig_data: impl Iterator<Item = impl AsRef<[T]> + 'a> + Clone,
     ) -> (f32, f32) {
         let (min, max) = find_min_max_from_iter(orig_data);
         Self::alpha_offset_from_min_max(min, max)
@@ -633,6 +952,15 @@ impl<TStorage: EncodedStorage> EncodedVectorsU8<TStorage> {
     }
 
     fn encode_int8_query(metadata: &MetadataInt8, query: &[f32]) -> EncodedQueryU8 {
+        if let Some(dimensions) = &metadata.dimensions {
+            let mut encoded_query = Vec::with_capacity(metadata.actual_dim);
+            dimensions.encode_vector(query, &mut encoded_query);
+            encoded_query.resize(metadata.actual_dim, 0);
+            return EncodedQueryU8 {
+                offset: 0.0,
+                encoded_query,
+            };
+        }
         let dim = query.len();
         let mut query: Vec<_> = query.iter().map(|&v| metadata.encode_value(v)).collect();
         if !dim.is_multiple_of(ALIGNMENT) {
@@ -708,6 +1036,11 @@ impl<TStorage: EncodedStorage> EncodedVectors for EncodedVectorsU8<TStorage> {
             .vector_io_read()
             .incr_delta(self.metadata.vector_parameters().dim * 2);
 
+        // Per-dimension parameters are not supported by SIMD scoring
+        if self.metadata.dimensions().is_some() {
+            return self.score_point_simple_internal(i, j);
+        }
+
         #[cfg(target_arch = "x86_64")]
         if is_x86_feature_detected!("avx2") && is_x86_feature_detected!("fma") {
             return self.score_point_avx_internal(i, j);
@@ -801,6 +1134,11 @@ impl<TStorage: EncodedStorage> EncodedVectors for EncodedVectorsU8<TStorage> {
 
         debug_assert!(bytes.len() >= ADDITIONAL_CONSTANT_SIZE + self.metadata.actual_dim());
 
+        // Per-dimension parameters are not supported by SIMD scoring
+        if self.metadata.dimensions().is_some() {
+            return self.score_point_simple(query, bytes);
+        }
+
         #[cfg(target_arch = "x86_64")]
         if is_x86_feature_detected!("avx2") && is_x86_feature_detected!("fma") {
             return self.score_point_avx(query, bytes);
@@ -947,4 +1285,31 @@ mod endian_tests {
             });
         }
     }
+
+    #[test]
+    fn dimension_parameters_are_persisted_as_le() {
+        let table = DimensionParametersTable(vec![
+            DimensionParameters {
+                alpha: 1.0,
+                offset: -2.5,
+            },
+            DimensionParameters {
+                alpha: 0.1,
+                offset: f32::MIN_POSITIVE,
+            },
+        ]);
+
+        let bytes = table.to_le_bytes();
+        assert_eq!(&bytes[..8], &[0, 0, 0x80, 0x3f, 0, 0, 0x20, 0xc0]);
+        assert_eq!(
+            DimensionParametersTable::from_le_bytes(&bytes),
+            Ok(table.clone())
+        );
+        assert!(DimensionParametersTable::from_le_bytes(&bytes[1..]).is_err());
+
+        let json = serde_json::to_string(&table).unwrap();
+        assert!(json.starts_with("\"0000803f000020c0"));
+        let restored: DimensionParametersTable = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored, table);
+    }
 }
@@ -1,4 +1,5 @@
 pub mod encoded_storage;
+pub mod encoded_storage_mmap;
 pub mod encoded_vectors;
 pub mod encoded_vectors_binary;
 pub mod encoded_vectors_pq;
@@ -6,13 +7,15 @@ pub mod encoded_vectors_u8;
 pub mod kmeans;
 pub mod p_square;
 pub mod quantile;
+pub mod rescore;
 pub mod vector_stats;
 
 use std::fmt::Display;
 use std::sync::{Arc, Condvar, Mutex};
 
 pub use encoded_storage::{EncodedStorage, EncodedStorageBuilder};
-pub use encoded_vectors::{DistanceType, EncodedVectors, VectorParameters};
+pub use encoded_storage_mmap::{EncodedStorageMmap, EncodedStorageMmapBuilder};
+pub use encoded_vectors::{DistanceType, EncodeElement, EncodedVectors, VectorParameters};
 pub use encoded_vectors_pq::{EncodedQueryPQ, EncodedVectorsPQ};
 pub use encoded_vectors_u8::{EncodedQueryU8, EncodedVectorsU8};
 
@@ -4,16 +4,19 @@ use permutation_iterator::Permutor;
 use rayon::iter::{IntoParallelIterator, ParallelIterator};
 
 use crate::EncodingError;
+use crate::encoded_vectors::EncodeElement;
 use crate::p_square::P2Quantile;
 
 pub const SAMPLE_SIZE: usize = 5_000;
+/// Number of vectors sampled by streaming estimations, which don't keep the sampled vectors
+pub const STREAMING_SAMPLE_SIZE: usize = 100_000;
 pub const P2_MARKERS: usize = 7;
 
-pub(crate) fn find_min_max_from_iter<'a>(
-    iter: impl Iterator<Item = impl AsRef<[f32]> + 'a> + Clone,
+pub(crate) fn find_min_max_from_iter<'a, T: EncodeElement>(
+    iter: impl Iterator<Item = impl AsRef<[T]> + 'a> + Clone,
 ) -> (f32, f32) {
     iter.fold((f32::MAX, f32::MIN), |(mut min, mut max), vector| {
-        for &value in vector.as_ref() {
+        for value in vector.as_ref().iter().map(|value| value.to_f32()) {
             if value < min {
                 min = value;
             }
@@ -25,8 +28,8 @@ pub(crate) fn find_min_max_from_iter<'a>(
     })
 }
 
-pub(crate) fn find_quantile_interval<'a>(
-    vector_data: impl Iterator<Item = impl AsRef<[f32]> + 'a> + Clone,
+pub(crate) fn find_quantile_interval<'a, T: EncodeElement>(
+    vector_data: impl Iterator<Item = impl AsRef<[T]> + 'a> + Clone,
     dim: usize,
     count: usize,
     quantile: f32,
@@ -36,44 +39,32 @@ pub(crate) fn find_quantile_interval<'a>(
         return Ok(None);
     }
 
-    let selected_vectors = take_random_vectors(vector_data, count, SAMPLE_SIZE, stopped)?;
-    let selected_vectors_count = selected_vectors.len();
-    let mut data_slice: Vec<f32> = Vec::with_capacity(selected_vectors_count * dim);
-    for vector in selected_vectors.iter() {
-        if stopped.load(Ordering::Relaxed) {
-            return Err(EncodingError::Stopped);
-        }
-
-        data_slice.extend_from_slice(vector.as_ref());
-    }
-
-    let data_slice_len = data_slice.len();
-    if data_slice_len < 4 {
+    let sample_size = std::cmp::min(count, STREAMING_SAMPLE_SIZE);
+    let values_count = sample_size * dim;
+    if values_count < 4 {
         return Ok(None);
     }
 
-    let cut_index = std::cmp::min(
-        (data_slice_len - 1) / 2,
-        (selected_vectors_count as f32 * (1.0 - quantile) / 2.0) as usize,
-    );
-    let cut_index = std::cmp::max(cut_index, 1);
-    let comparator = |a: &f32, b: &f32| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal);
-    let (selected_values, _, _) =
-        data_slice.select_nth_unstable_by(data_slice_len - cut_index, comparator);
-    let (_, _, selected_values) = selected_values.select_nth_unstable_by(cut_index, comparator);
-
-    if selected_values.len() < 2 {
-        return Ok(None);
-    }
+    // Each tail cuts `(1 - quantile) / 2` of the sampled vectors worth of values, at least one
+    let tail_values = (sample_size as f64 * (1.0 - f64::from(quantile)) / 2.0).max(1.0);
+    let tail = (tail_values / values_count as f64).min(0.5);
+
+    // Estimate both bounds in one pass, without buffering the sampled values
+    let mut min = P2Quantile::<P2_MARKERS>::new(tail)?;
+    let mut max = P2Quantile::<P2_MARKERS>::new(1.0 - tail)?;
+    for_each_random_vector(vector_data, count, sample_size, stopped, |vector| {
+        for value in vector {
+            min.push(f64::from(value.to_f32()));
+            max.push(f64::from(value.to_f32()));
+        }
+    })?;
 
-    let selected_values = [selected_values];
-    Ok(Some(find_min_max_from_iter(
-        selected_values.iter().map(|v| &v[..]),
-    )))
+    let (min, max) = (min.estimate() as f32, max.estimate() as f32);
+    Ok((min < max).then_some((min, max)))
 }
 
-pub fn find_interval_per_coordinate<'a>(
-    vector_data: impl Iterator<Item = impl AsRef<[f32]> + Send + Sync + 'a> + Clone,
+pub fn find_interval_per_coordinate<'a, T: EncodeElement>(
+    vector_data: impl Iterator<Item = impl AsRef<[T]> + Sync + 'a> + Clone,
     dim: usize,
     count: usize,
     quantile: f32,
@@ -91,8 +82,8 @@ pub fn find_interval_per_coordinate<'a>(
     find_interval_per_coordinate_p2(vector_data, dim, count, quantile, num_threads, stopped)
 }
 
-fn find_min_max_interval_per_coordinate<'a>(
-    vector_data: impl Iterator<Item = impl AsRef<[f32]> + 'a> + Clone,
+fn find_min_max_interval_per_coordinate<'a, T: EncodeElement>(
+    vector_data: impl Iterator<Item = impl AsRef<[T]> + 'a> + Clone,
     dim: usize,
     count: usize,
     stopped: &AtomicBool,
@@ -106,7 +97,8 @@ fn find_min_max_interval_per_coordinate<'a>(
             return Err(EncodingError::Stopped);
         }
 
-        for ((min, max), &value) in result.iter_mut().zip(vector.as_ref().iter()) {
+        for ((min, max), value) in result.iter_mut().zip(vector.as_ref().iter()) {
+            let value = value.to_f32();
             *min = min.min(value);
             *max = max.max(value);
         }
@@ -121,8 +113,8 @@ fn find_min_max_interval_per_coordinate<'a>(
     Ok(result)
 }
 
-fn find_interval_per_coordinate_p2<'a>(
-    vector_data: impl Iterator<Item = impl AsRef<[f32]> + Send + Sync + 'a> + Clone,
+fn find_interval_per_coordinate_p2<'a, T: EncodeElement>(
+    vector_data: impl Iterator<Item = impl AsRef<[T]> + Sync + 'a> + Clone,
     dim: usize,
     count: usize,
     quantile: f32,
@@ -160,7 +152,7 @@ fn find_interval_per_coordinate_p2<'a>(
                     }
 
                     let vector = vector.as_ref();
-                    let value = f64::from(vector[d]);
+                    let value = f64::from(vector[d].to_f32());
                     min.push(value);
                     max.push(value);
                 }
@@ -171,17 +163,53 @@ fn find_interval_per_coordinate_p2<'a>(
     })
 }
 
-// Take random vectors from the input iterator using `Permutor`.
-fn take_random_vectors<'a>(
-    vector_data: impl Iterator<Item = impl AsRef<[f32]> + 'a>,
-    count: usize,
-    sample_size: usize,
-    stopped: &AtomicBool,
-) -> Result<Vec<impl AsRef<[f32]> + 'a>, EncodingError> {
+// Sorted indices of `sample_size` random vectors out of `count`, using `Permutor`.
+fn random_sample_indices(count: usize, sample_size: usize) -> Vec<usize> {
     let slice_size = std::cmp::min(count, sample_size);
     let permutor = Permutor::new(count as u64);
     let mut selected_vectors: Vec<usize> = permutor.map(|i| i as usize).take(slice_size).collect();
     selected_vectors.sort_unstable();
+    selected_vectors
+}
+
+// Call `f` for random vectors from the input iterator, without keeping them.
+fn for_each_random_vector<'a, T: EncodeElement>(
+    vector_data: impl Iterator<Item = impl AsRef<[T]> + 'a>,
+    count: usize,
+    sample_size: usize,
+    stopped: &AtomicBool,
+    mut f: impl FnMut(&[T]),
+) -> Result<(), EncodingError> {
+    let selected_vectors = random_sample_indices(count, sample_size);
+    let Some(&last_selected) = selected_vectors.last() else {
+        return Ok(());
+    };
+
+    let mut selected = selected_vectors.iter().peekable();
+    for (vector_index, vector_data) in vector_data.enumerate() {
+        if stopped.load(Ordering::Relaxed) {
+            return Err(EncodingError::Stopped);
+        }
+
+        if selected.next_if_eq(&&vector_index).is_some() {
+            f(vector_data.as_ref());
+        }
+        if vector_index == last_selected {
+            break;
+        }
+    }
+    Ok(())
+}
+
+// Take random vectors from the input iterator using `Permutor`.
+fn take_random_vectors<'a, T: EncodeElement>(
+    vector_data: impl Iterator<Item = impl AsRef<[T]> + 'a>,
+    count: usize,
+    sample_size: usize,
+    stopped: &AtomicBool,
+) -> Result<Vec<impl AsRef<[T]> + 'a>, EncodingError> {
+    let selected_vectors = random_sample_indices(count, sample_size);
+    let slice_size = selected_vectors.len();
 
     let mut data_slice = Vec::with_capacity(slice_size);
     let mut selected_index: usize = 0;
@@ -251,4 +279,32 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn test_streaming_quantile_interval_cuts_outliers() {
+        const COUNT: usize = 20_000;
+        const DIM: usize = 8;
+
+        let mut rng = StdRng::seed_from_u64(42);
+        let mut data: Vec<Vec<f32>> = (0..COUNT)
+            .map(|_| (0..DIM).map(|_| rng.random::<f32>()).collect())
+            .collect();
+        for vector in data.iter_mut().step_by(1_000) {
+            vector[0] = 1000.0;
+            vector[1] = -1000.0;
+        }
+
+        let (min, max) =
+            find_quantile_interval(data.iter(), DIM, COUNT, 0.99, &AtomicBool::new(false))
+                .unwrap()
+                .unwrap();
+        assert!((-0.1..0.1).contains(&min), "min {min}");
+        assert!((0.9..1.1).contains(&max), "max {max}");
+
+        assert!(
+            find_quantile_interval(data.iter(), DIM, COUNT, 1.0, &AtomicBool::new(false))
+                .unwrap()
+                .is_none(),
+        );
+    }
 }
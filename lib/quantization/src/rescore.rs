@@ -0,0 +1,116 @@
+//! Two-phase search over quantized vectors.
+//!
+//! Quantized vectors are fast to score but approximate. A search first takes more candidates than
+//! requested with quantized scores (oversampling), and then rescores the candidates with the
+//! original vectors to select the final top.
+
+use common::types::{PointOffsetType, ScoredPointOffset};
+use serde::{Deserialize, Serialize};
+
+/// Defaults of the two-phase search, persisted with quantized vectors.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct RescoreDefaults {
+    /// Oversampling used if a search doesn't request one
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub oversampling: Option<f64>,
+}
+
+impl RescoreDefaults {
+    pub fn is_empty(&self) -> bool {
+        self.oversampling.is_none()
+    }
+}
+
+/// Parameters of a single two-phase search.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rescorer {
+    oversampling: Option<f64>,
+    rescore: bool,
+}
+
+impl Rescorer {
+    /// Rescorer with `oversampling` of candidates, which are rescored with original vectors if
+    /// `rescore` is set.
+    ///
+    /// Oversampling of 1.0 or less takes exactly the requested number of candidates.
+    pub fn new(oversampling: Option<f64>, rescore: bool) -> Self {
+        Self {
+            oversampling: oversampling.filter(|oversampling| *oversampling > 1.0),
+            rescore,
+        }
+    }
+
+    /// Rescorer which takes exactly the requested number of candidates, and doesn't rescore them.
+    pub fn disabled() -> Self {
+        Self::new(None, false)
+    }
+
+    pub fn oversampling(&self) -> Option<f64> {
+        self.oversampling
+    }
+
+    pub fn is_rescoring(&self) -> bool {
+        self.rescore
+    }
+
+    /// Number of candidates to take from the quantized search for a result of `top` points.
+    pub fn candidates_limit(&self, top: usize) -> usize {
+        match self.oversampling {
+            Some(oversampling) => ((oversampling * top as f64) as usize).max(top),
+            None => top,
+        }
+    }
+
+    /// Select the best `top` of the quantized `candidates`.
+    ///
+    /// If rescoring, the candidates are scored again with `score_original`, which scores the
+    /// given points with original vectors.
+    pub fn rescore<E>(
+        &self,
+        mut candidates: Vec<ScoredPointOffset>,
+        top: usize,
+        score_original: impl FnOnce(&[PointOffsetType]) -> Result<Vec<ScoredPointOffset>, E>,
+    ) -> Result<Vec<ScoredPointOffset>, E> {
+        if self.rescore {
+            let ids: Vec<PointOffsetType> = candidates.iter().map(|x| x.idx).collect();
+            candidates = score_original(&ids)?;
+            candidates.sort_unstable();
+            candidates.reverse();
+        }
+        candidates.truncate(top);
+        Ok(candidates)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::Infallible;
+
+    use super::*;
+
+    fn scored(idx: PointOffsetType, score: f32) -> ScoredPointOffset {
+        ScoredPointOffset { idx, score }
+    }
+
+    #[test]
+    fn test_rescorer() {
+        let candidates = vec![scored(1, 0.9), scored(2, 0.8), scored(3, 0.7)];
+        let original = |ids: &[PointOffsetType]| -> Result<_, Infallible> {
+            Ok(ids
+                .iter()
+                .map(|&idx| scored(idx, idx as f32))
+                .collect::<Vec<_>>())
+        };
+
+        let rescorer = Rescorer::new(Some(3.0), true);
+        assert_eq!(rescorer.candidates_limit(2), 6);
+        let result = rescorer.rescore(candidates.clone(), 2, original).unwrap();
+        assert_eq!(result, vec![scored(3, 3.0), scored(2, 2.0)]);
+
+        let rescorer = Rescorer::new(Some(0.5), false);
+        assert_eq!(rescorer.candidates_limit(2), 2);
+        let result = rescorer.rescore(candidates, 2, original).unwrap();
+        assert_eq!(result, vec![scored(1, 0.9), scored(2, 0.8)]);
+    }
+}
@@ -13,6 +13,10 @@ pub mod test_binary;
 #[cfg(test)]
 pub mod test_binary_encodings;
 #[cfg(test)]
+pub mod test_binary_metadata;
+#[cfg(test)]
+pub mod test_checksum;
+#[cfg(test)]
 pub mod test_neon;
 #[cfg(test)]
 pub mod test_pq;
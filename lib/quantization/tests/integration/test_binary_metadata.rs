@@ -0,0 +1,93 @@
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicBool;
+
+    use fs_err as fs;
+    use quantization::encoded_storage::{TestEncodedStorage, TestEncodedStorageBuilder};
+    use quantization::encoded_vectors::{DistanceType, VectorParameters};
+    use quantization::encoded_vectors_binary::{EncodedVectorsBin, Encoding, QueryEncoding};
+    use tempfile::Builder;
+
+    const DIM: usize = 8;
+    const COUNT: usize = 4;
+
+    fn error_message(result: std::io::Result<impl Sized>) -> String {
+        match result {
+            Ok(_) => panic!("expected invalid metadata"),
+            Err(err) => {
+                assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+                err.to_string()
+            }
+        }
+    }
+
+    #[test]
+    fn test_binary_metadata_v3_is_validated_on_load() {
+        let dir = Builder::new().prefix("bq_meta_v3").tempdir().unwrap();
+        let data_path = dir.path().join("data.bin");
+        let meta_path = dir.path().join("meta.json");
+
+        let data: Vec<Vec<f32>> = (0..COUNT)
+            .map(|i| (0..DIM).map(|j| (i + j) as f32 - 4.0).collect())
+            .collect();
+        let u128_size =
+            EncodedVectorsBin::<u128, TestEncodedStorage>::get_quantized_vector_size_from_params(
+                DIM,
+                Encoding::OneBit,
+            );
+        EncodedVectorsBin::<u128, _>::encode(
+            data.iter(),
+            TestEncodedStorageBuilder::new(Some(&data_path), u128_size),
+            &VectorParameters {
+                dim: DIM,
+                deprecated_count: None,
+                distance_type: DistanceType::Dot,
+                invert: false,
+            },
+            Encoding::OneBit,
+            QueryEncoding::SameAsStorage,
+            Some(&meta_path),
+            &AtomicBool::new(false),
+        )
+        .unwrap();
+
+        let meta: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&meta_path).unwrap()).unwrap();
+        assert_eq!(meta["format_version"], 3);
+        assert_eq!(meta["word_bits"], 128);
+        assert_eq!(meta["byte_order"], "little_endian");
+        assert_eq!(meta["vectors_count"], COUNT);
+
+        let storage = TestEncodedStorage::from_file(&data_path, u128_size).unwrap();
+        EncodedVectorsBin::<u128, _>::load(storage, &meta_path).unwrap();
+
+        // Loading with another word width
+        let u8_size =
+            EncodedVectorsBin::<u8, TestEncodedStorage>::get_quantized_vector_size_from_params(
+                DIM,
+                Encoding::OneBit,
+            );
+        let storage = TestEncodedStorage::from_file(&data_path, u8_size).unwrap();
+        let message = error_message(EncodedVectorsBin::<u8, _>::load(storage, &meta_path));
+        assert!(message.contains("128-bit words"), "{message}");
+
+        // Truncated data file
+        let truncated_path = dir.path().join("truncated.bin");
+        let bytes = fs::read(&data_path).unwrap();
+        fs::write(&truncated_path, &bytes[..u128_size * 2]).unwrap();
+        let storage = TestEncodedStorage::from_file(&truncated_path, u128_size).unwrap();
+        let message = error_message(EncodedVectorsBin::<u128, _>::load(storage, &meta_path));
+        assert!(message.contains("has 2 vectors"), "{message}");
+
+        // Required fields missing
+        let mut incomplete = meta.clone();
+        incomplete.as_object_mut().unwrap().remove("word_bits");
+        fs::write(&meta_path, serde_json::to_vec(&incomplete).unwrap()).unwrap();
+        let storage = TestEncodedStorage::from_file(&data_path, u128_size).unwrap();
+        let message = error_message(EncodedVectorsBin::<u128, _>::load(storage, &meta_path));
+        assert!(
+            message.contains("required by format version 3"),
+            "{message}"
+        );
+    }
+}
@@ -0,0 +1,112 @@
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicBool;
+
+    use common::counter::hardware_counter::HardwareCounterCell;
+    use fs_err as fs;
+    use quantization::EncodedVectors;
+    use quantization::encoded_storage::{TestEncodedStorage, TestEncodedStorageBuilder};
+    use quantization::encoded_vectors::{DistanceType, VectorParameters};
+    use quantization::encoded_vectors_binary::{EncodedVectorsBin, Encoding, QueryEncoding};
+    use quantization::encoded_vectors_u8::{EncodedVectorsU8, ScalarQuantizationMethod};
+    use tempfile::Builder;
+
+    const DIM: usize = 8;
+    const COUNT: usize = 200;
+
+    fn vector_parameters() -> VectorParameters {
+        VectorParameters {
+            dim: DIM,
+            deprecated_count: None,
+            distance_type: DistanceType::Dot,
+            invert: false,
+        }
+    }
+
+    fn vector_data() -> Vec<Vec<f32>> {
+        (0..COUNT)
+            .map(|i| {
+                (0..DIM)
+                    .map(|j| ((i * DIM + j) % 13) as f32 - 6.0)
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_scalar_checksum() {
+        let dir = Builder::new().prefix("sq_checksum").tempdir().unwrap();
+        let data_path = dir.path().join("data.bin");
+        let meta_path = dir.path().join("meta.json");
+
+        let quantized_vector_size =
+            EncodedVectorsU8::<TestEncodedStorage>::get_quantized_vector_size(&vector_parameters());
+        let encoded = EncodedVectorsU8::encode(
+            vector_data().iter(),
+            TestEncodedStorageBuilder::new(Some(&data_path), quantized_vector_size),
+            &vector_parameters(),
+            COUNT,
+            None,
+            ScalarQuantizationMethod::Int8,
+            Some(&meta_path),
+            &AtomicBool::new(false),
+        )
+        .unwrap();
+        encoded.verify().unwrap();
+
+        let storage = TestEncodedStorage::from_file(&data_path, quantized_vector_size).unwrap();
+        EncodedVectorsU8::load(storage, &meta_path)
+            .unwrap()
+            .verify()
+            .unwrap();
+
+        // Flip a single bit of the last vector
+        let mut bytes = fs::read(&data_path).unwrap();
+        *bytes.last_mut().unwrap() ^= 1;
+        fs::write(&data_path, bytes).unwrap();
+
+        let storage = TestEncodedStorage::from_file(&data_path, quantized_vector_size).unwrap();
+        let err = EncodedVectorsU8::load(storage, &meta_path)
+            .unwrap()
+            .verify()
+            .unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+        assert!(err.to_string().contains("checksum mismatch"), "{err}");
+    }
+
+    #[test]
+    fn test_binary_checksum_is_removed_on_upsert() {
+        let dir = Builder::new().prefix("bq_checksum").tempdir().unwrap();
+        let data_path = dir.path().join("data.bin");
+        let meta_path = dir.path().join("meta.json");
+
+        let quantized_vector_size =
+            EncodedVectorsBin::<u128, TestEncodedStorage>::get_quantized_vector_size_from_params(
+                DIM,
+                Encoding::OneBit,
+            );
+        let mut encoded = EncodedVectorsBin::<u128, _>::encode(
+            vector_data().iter(),
+            TestEncodedStorageBuilder::new(Some(&data_path), quantized_vector_size),
+            &vector_parameters(),
+            Encoding::OneBit,
+            QueryEncoding::SameAsStorage,
+            Some(&meta_path),
+            &AtomicBool::new(false),
+        )
+        .unwrap();
+        encoded.verify().unwrap();
+
+        let meta: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&meta_path).unwrap()).unwrap();
+        assert!(meta["checksum"].is_u64());
+
+        let hw_counter = HardwareCounterCell::new();
+        encoded.upsert_vector(0, &[-1.0; DIM], &hw_counter).unwrap();
+        encoded.verify().unwrap();
+
+        let meta: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&meta_path).unwrap()).unwrap();
+        assert!(meta.get("checksum").is_none());
+    }
+}
@@ -1,8 +1,10 @@
 #[cfg(test)]
 mod tests {
-    use std::sync::atomic::AtomicBool;
+    use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 
     use common::counter::hardware_counter::HardwareCounterCell;
+    use half::f16;
+    use quantization::EncodingError;
     use quantization::encoded_storage::{TestEncodedStorage, TestEncodedStorageBuilder};
     use quantization::encoded_vectors::{DistanceType, EncodedVectors, VectorParameters};
     use quantization::encoded_vectors_u8::{EncodedVectorsU8, ScalarQuantizationMethod};
@@ -13,6 +15,7 @@ mod tests {
 
     #[rstest]
     #[case(ScalarQuantizationMethod::Int8)]
+    #[case(ScalarQuantizationMethod::Int8PerDimension)]
     fn test_dot_simple(#[case] method: ScalarQuantizationMethod) {
         let vectors_count = 129;
         let vector_dim = 65;
@@ -57,6 +60,7 @@ mod tests {
 
     #[rstest]
     #[case(ScalarQuantizationMethod::Int8)]
+    #[case(ScalarQuantizationMethod::Int8PerDimension)]
     fn test_l2_simple(#[case] method: ScalarQuantizationMethod) {
         let vectors_count = 129;
         let vector_dim = 65;
@@ -101,6 +105,7 @@ mod tests {
 
     #[rstest]
     #[case(ScalarQuantizationMethod::Int8)]
+    #[case(ScalarQuantizationMethod::Int8PerDimension)]
     fn test_l1_simple(#[case] method: ScalarQuantizationMethod) {
         let vectors_count = 129;
         let vector_dim = 65;
@@ -149,6 +154,7 @@ mod tests {
 
     #[rstest]
     #[case(ScalarQuantizationMethod::Int8)]
+    #[case(ScalarQuantizationMethod::Int8PerDimension)]
     fn test_dot_inverted_simple(#[case] method: ScalarQuantizationMethod) {
         let vectors_count = 129;
         let vector_dim = 65;
@@ -193,6 +199,7 @@ mod tests {
 
     #[rstest]
     #[case(ScalarQuantizationMethod::Int8)]
+    #[case(ScalarQuantizationMethod::Int8PerDimension)]
     fn test_l2_inverted_simple(#[case] method: ScalarQuantizationMethod) {
         let vectors_count = 129;
         let vector_dim = 65;
@@ -237,6 +244,7 @@ mod tests {
 
     #[rstest]
     #[case(ScalarQuantizationMethod::Int8)]
+    #[case(ScalarQuantizationMethod::Int8PerDimension)]
     fn test_l1_inverted_simple(#[case] method: ScalarQuantizationMethod) {
         let vectors_count = 129;
         let vector_dim = 65;
@@ -285,6 +293,7 @@ mod tests {
 
     #[rstest]
     #[case(ScalarQuantizationMethod::Int8)]
+    #[case(ScalarQuantizationMethod::Int8PerDimension)]
     fn test_dot_internal_simple(#[case] method: ScalarQuantizationMethod) {
         let vectors_count: usize = 129;
         let vector_dim = 65;
@@ -327,6 +336,7 @@ mod tests {
 
     #[rstest]
     #[case(ScalarQuantizationMethod::Int8)]
+    #[case(ScalarQuantizationMethod::Int8PerDimension)]
     fn test_dot_inverted_internal_simple(#[case] method: ScalarQuantizationMethod) {
         let vectors_count: usize = 129;
         let vector_dim = 65;
@@ -369,6 +379,7 @@ mod tests {
 
     #[rstest]
     #[case(ScalarQuantizationMethod::Int8)]
+    #[case(ScalarQuantizationMethod::Int8PerDimension)]
     fn test_u8_large_quantile(#[case] method: ScalarQuantizationMethod) {
         let vectors_count = 129;
         let vector_dim = 65;
@@ -414,6 +425,8 @@ mod tests {
     #[rstest]
     #[case(ScalarQuantizationMethod::Int8, false)]
     #[case(ScalarQuantizationMethod::Int8, true)]
+    #[case(ScalarQuantizationMethod::Int8PerDimension, false)]
+    #[case(ScalarQuantizationMethod::Int8PerDimension, true)]
     fn test_sq_u8_encode_internal(#[case] method: ScalarQuantizationMethod, #[case] invert: bool) {
         let vectors_count = 129;
         let vector_dim = 70;
@@ -469,4 +482,237 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_parallel_encoding_keeps_order() {
+        let vectors_count = 10_000;
+        let vector_dim = 17;
+
+        let mut rng = rand::rngs::StdRng::seed_from_u64(42);
+        let vector_data: Vec<Vec<f32>> = (0..vectors_count)
+            .map(|_| (0..vector_dim).map(|_| rng.random()).collect())
+            .collect();
+
+        let vector_parameters = VectorParameters {
+            dim: vector_dim,
+            deprecated_count: None,
+            distance_type: DistanceType::Dot,
+            invert: false,
+        };
+        let quantized_vector_size =
+            EncodedVectorsU8::<TestEncodedStorage>::get_quantized_vector_size(&vector_parameters);
+        let sequential = EncodedVectorsU8::encode(
+            vector_data.iter(),
+            TestEncodedStorageBuilder::new(None, quantized_vector_size),
+            &vector_parameters,
+            vectors_count,
+            None,
+            ScalarQuantizationMethod::Int8,
+            None,
+            &AtomicBool::new(false),
+        )
+        .unwrap();
+
+        let progress = AtomicU64::new(0);
+        let parallel = EncodedVectorsU8::encode_parallel(
+            vector_data.iter(),
+            TestEncodedStorageBuilder::new(None, quantized_vector_size),
+            &vector_parameters,
+            vectors_count,
+            None,
+            ScalarQuantizationMethod::Int8,
+            None,
+            4,
+            Some(&progress),
+            &AtomicBool::new(false),
+        )
+        .unwrap();
+        assert_eq!(progress.load(Ordering::Relaxed), vectors_count as u64);
+
+        for index in 0..vectors_count as u32 {
+            assert_eq!(
+                sequential.get_quantized_vector(index),
+                parallel.get_quantized_vector(index),
+            );
+        }
+
+        let stopped = AtomicBool::new(true);
+        let result = EncodedVectorsU8::encode_parallel(
+            vector_data.iter(),
+            TestEncodedStorageBuilder::new(None, quantized_vector_size),
+            &vector_parameters,
+            vectors_count,
+            None,
+            ScalarQuantizationMethod::Int8,
+            None,
+            4,
+            None,
+            &stopped,
+        );
+        assert!(matches!(result, Err(EncodingError::Stopped)));
+    }
+
+    #[test]
+    fn test_per_dimension_non_uniform_ranges() {
+        let vectors_count = 256;
+        let vector_dim = 32;
+        // Ranges of dimensions are narrow, but far apart from each other
+        let mut rng = rand::rngs::StdRng::seed_from_u64(42);
+        let mut random_vector = || -> Vec<f32> {
+            (0..vector_dim)
+                .map(|d| 100.0 * d as f32 + rng.random::<f32>())
+                .collect()
+        };
+        let vector_data: Vec<Vec<f32>> = (0..vectors_count).map(|_| random_vector()).collect();
+        let query = random_vector();
+
+        let vector_parameters = VectorParameters {
+            dim: vector_dim,
+            deprecated_count: None,
+            distance_type: DistanceType::L2,
+            invert: false,
+        };
+        let quantized_vector_size =
+            EncodedVectorsU8::<TestEncodedStorage>::get_quantized_vector_size(&vector_parameters);
+        let total_error = |method: ScalarQuantizationMethod| {
+            let encoded = EncodedVectorsU8::encode(
+                vector_data.iter(),
+                TestEncodedStorageBuilder::new(None, quantized_vector_size),
+                &vector_parameters,
+                vectors_count,
+                None,
+                method,
+                None,
+                &AtomicBool::new(false),
+            )
+            .unwrap();
+            let query_u8 = encoded.encode_query(&query);
+            let hw_counter = HardwareCounterCell::new();
+            vector_data
+                .iter()
+                .enumerate()
+                .map(|(index, vector)| {
+                    // Compare to the first vector, as global scores are shifted by a constant
+                    let score = encoded.score_point(&query_u8, index as u32, &hw_counter)
+                        - encoded.score_point(&query_u8, 0, &hw_counter);
+                    let original_score =
+                        l2_similarity(&query, vector) - l2_similarity(&query, &vector_data[0]);
+                    (score - original_score).abs()
+                })
+                .sum::<f32>()
+        };
+
+        let global_error = total_error(ScalarQuantizationMethod::Int8);
+        let per_dimension_error = total_error(ScalarQuantizationMethod::Int8PerDimension);
+        assert!(per_dimension_error < global_error);
+    }
+
+    #[rstest]
+    #[case(ScalarQuantizationMethod::Int8)]
+    #[case(ScalarQuantizationMethod::Int8PerDimension)]
+    fn test_f16_input_encoding(#[case] method: ScalarQuantizationMethod) {
+        let vectors_count = 300;
+        let vector_dim = 33;
+
+        let mut rng = rand::rngs::StdRng::seed_from_u64(42);
+        let half_data: Vec<Vec<f16>> = (0..vectors_count)
+            .map(|_| {
+                (0..vector_dim)
+                    .map(|_| f16::from_f32(rng.random::<f32>() - 0.5))
+                    .collect()
+            })
+            .collect();
+        let float_data: Vec<Vec<f32>> = half_data
+            .iter()
+            .map(|vector| vector.iter().map(|value| value.to_f32()).collect())
+            .collect();
+
+        let vector_parameters = VectorParameters {
+            dim: vector_dim,
+            deprecated_count: None,
+            distance_type: DistanceType::Dot,
+            invert: false,
+        };
+        let quantized_vector_size =
+            EncodedVectorsU8::<TestEncodedStorage>::get_quantized_vector_size(&vector_parameters);
+        let half_encoded = EncodedVectorsU8::encode(
+            half_data.iter(),
+            TestEncodedStorageBuilder::new(None, quantized_vector_size),
+            &vector_parameters,
+            vectors_count,
+            Some(0.99),
+            method.clone(),
+            None,
+            &AtomicBool::new(false),
+        )
+        .unwrap();
+        let float_encoded = EncodedVectorsU8::encode(
+            float_data.iter(),
+            TestEncodedStorageBuilder::new(None, quantized_vector_size),
+            &vector_parameters,
+            vectors_count,
+            Some(0.99),
+            method,
+            None,
+            &AtomicBool::new(false),
+        )
+        .unwrap();
+
+        // Encoding `f16` values directly is the same as encoding them expanded into `f32`
+        for index in 0..vectors_count as u32 {
+            assert_eq!(
+                half_encoded.get_quantized_vector(index),
+                float_encoded.get_quantized_vector(index),
+            );
+        }
+    }
+
+    #[rstest]
+    #[case(ScalarQuantizationMethod::Int8, 2)]
+    #[case(ScalarQuantizationMethod::Int8PerDimension, 3)]
+    fn test_u8_metadata_format_version(
+        #[case] method: ScalarQuantizationMethod,
+        #[case] expected_version: u32,
+    ) {
+        let vectors_count = 16;
+        let vector_dim = 8;
+        let dir = tempfile::Builder::new()
+            .prefix("sq_meta")
+            .tempdir()
+            .unwrap();
+        let data_path = dir.path().join("data.bin");
+        let meta_path = dir.path().join("meta.json");
+
+        let mut rng = rand::rngs::StdRng::seed_from_u64(42);
+        let vector_data: Vec<Vec<f32>> = (0..vectors_count)
+            .map(|_| (0..vector_dim).map(|_| rng.random()).collect())
+            .collect();
+        let vector_parameters = VectorParameters {
+            dim: vector_dim,
+            deprecated_count: None,
+            distance_type: DistanceType::Dot,
+            invert: false,
+        };
+        let quantized_vector_size =
+            EncodedVectorsU8::<TestEncodedStorage>::get_quantized_vector_size(&vector_parameters);
+        EncodedVectorsU8::encode(
+            vector_data.iter(),
+            TestEncodedStorageBuilder::new(Some(&data_path), quantized_vector_size),
+            &vector_parameters,
+            vectors_count,
+            None,
+            method,
+            Some(&meta_path),
+            &AtomicBool::new(false),
+        )
+        .unwrap();
+
+        // Only metadata with per-dimension parameters needs the new format version
+        let meta: serde_json::Value =
+            serde_json::from_str(&fs_err::read_to_string(&meta_path).unwrap()).unwrap();
+        assert_eq!(meta["format_version"], expected_version);
+
+        let storage = TestEncodedStorage::from_file(&data_path, quantized_vector_size).unwrap();
+        EncodedVectorsU8::load(storage, &meta_path).unwrap();
+    }
 }
@@ -0,0 +1,188 @@
+//! Scoring of vectors read from little-endian storage files.
+//!
+//! Benchmarks the actual storage and scorer code: dense mmap vectors, compressed mmap sparse
+//! postings and binary quantized vectors. On big-endian targets, the same code decodes the
+//! little-endian files, either on every read or once into the decoded cache. Compare results of
+//! little- and big-endian targets to track the cost of decoding.
+
+#[cfg(not(target_os = "windows"))]
+mod prof;
+
+use std::borrow::Cow;
+use std::sync::atomic::AtomicBool;
+
+use common::counter::hardware_counter::HardwareCounterCell;
+use common::mmap::AdviceSetting;
+use common::types::{PointOffsetType, ScoreType};
+use criterion::{Criterion, criterion_group, criterion_main};
+use quantization::encoded_storage::{TestEncodedStorage, TestEncodedStorageBuilder};
+use quantization::encoded_vectors::{DistanceType, EncodedVectors as _, VectorParameters};
+use quantization::encoded_vectors_binary::{EncodedVectorsBin, Encoding, QueryEncoding};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use segment::data_types::named_vectors::CowVector;
+use segment::data_types::vectors::{DenseVector, QueryVector};
+use segment::types::Distance;
+use segment::vector_storage::dense::memmap_dense_vector_storage::open_memmap_vector_storage;
+use segment::vector_storage::{DEFAULT_STOPPED, VectorStorage as _, new_raw_scorer};
+use sparse::common::scores_memory_pool::ScoresMemoryPool;
+use sparse::common::sparse_vector_fixture::random_sparse_vector;
+use sparse::index::inverted_index::InvertedIndex as _;
+use sparse::index::inverted_index::inverted_index_compressed_mmap::InvertedIndexCompressedMmap;
+use sparse::index::inverted_index::inverted_index_ram_builder::InvertedIndexBuilder;
+use sparse::index::search_context::SearchContext;
+use tempfile::Builder;
+
+const COUNT: usize = 10_000;
+const DENSE_DIM: usize = 1024;
+/// Number of points scored at once, like a batch of candidates during search
+const BATCH_SIZE: usize = 64;
+const MAX_SPARSE_DIM: usize = 30_000;
+const TOP: usize = 10;
+
+fn random_vector(rng: &mut StdRng, dim: usize) -> DenseVector {
+    (0..dim).map(|_| rng.random()).collect()
+}
+
+fn random_batch(rng: &mut StdRng) -> Vec<PointOffsetType> {
+    (0..BATCH_SIZE)
+        .map(|_| rng.random_range(0..COUNT as PointOffsetType))
+        .collect()
+}
+
+fn dense_scoring_bench(c: &mut Criterion) {
+    let mut group = c.benchmark_group("endian-scoring-dense");
+    let mut rng = StdRng::seed_from_u64(42);
+
+    let dir = Builder::new().prefix("storage_dir").tempdir().unwrap();
+    let mut storage = open_memmap_vector_storage(
+        dir.path(),
+        DENSE_DIM,
+        Distance::Dot,
+        false,
+        AdviceSetting::Global,
+        false,
+    )
+    .unwrap();
+    let mut vectors =
+        (0..COUNT).map(|_| (CowVector::from(random_vector(&mut rng, DENSE_DIM)), false));
+    storage.update_from(&mut vectors, &DEFAULT_STOPPED).unwrap();
+    drop(storage);
+
+    // Reopen, so that vectors are read from the file
+    let storage = open_memmap_vector_storage(
+        dir.path(),
+        DENSE_DIM,
+        Distance::Dot,
+        false,
+        AdviceSetting::Global,
+        false,
+    )
+    .unwrap();
+    assert_eq!(storage.available_vector_count(), COUNT);
+
+    let query = QueryVector::from(random_vector(&mut rng, DENSE_DIM));
+    let scorer = new_raw_scorer(query, &storage, HardwareCounterCell::new()).unwrap();
+
+    group.bench_function("mmap-score-points", |b| {
+        let mut scores = vec![0.0 as ScoreType; BATCH_SIZE];
+        b.iter_batched(
+            || random_batch(&mut rng),
+            |points| scorer.score_points(&points, &mut scores),
+            criterion::BatchSize::SmallInput,
+        )
+    });
+}
+
+fn sparse_scoring_bench(c: &mut Criterion) {
+    let mut group = c.benchmark_group("endian-scoring-sparse");
+    let mut rng = StdRng::seed_from_u64(42);
+
+    let mut builder = InvertedIndexBuilder::new();
+    for id in 0..COUNT as PointOffsetType {
+        builder.add(
+            id,
+            random_sparse_vector(&mut rng, MAX_SPARSE_DIM).into_remapped(),
+        );
+    }
+    let dir = Builder::new().prefix("index_dir").tempdir().unwrap();
+    let index =
+        InvertedIndexCompressedMmap::<f32>::from_ram_index(Cow::Owned(builder.build()), &dir)
+            .unwrap();
+
+    let pool = ScoresMemoryPool::new();
+    let stopped = AtomicBool::new(false);
+    let hardware_counter = HardwareCounterCell::new();
+
+    group.bench_function("mmap-search", |b| {
+        b.iter_batched(
+            || random_sparse_vector(&mut rng, MAX_SPARSE_DIM).into_remapped(),
+            |query| {
+                SearchContext::new(query, TOP, &index, pool.get(), &stopped, &hardware_counter)
+                    .search(&|_| true)
+            },
+            criterion::BatchSize::SmallInput,
+        )
+    });
+}
+
+fn quantized_scoring_bench(c: &mut Criterion) {
+    let mut group = c.benchmark_group("endian-scoring-binary-quantized");
+    let mut rng = StdRng::seed_from_u64(42);
+
+    let vectors: Vec<DenseVector> = (0..COUNT)
+        .map(|_| random_vector(&mut rng, DENSE_DIM))
+        .collect();
+    let vector_size =
+        EncodedVectorsBin::<u128, TestEncodedStorage>::get_quantized_vector_size_from_params(
+            DENSE_DIM,
+            Encoding::OneBit,
+        );
+    let encoded = EncodedVectorsBin::<u128, _>::encode(
+        vectors.iter(),
+        TestEncodedStorageBuilder::new(None, vector_size),
+        &VectorParameters {
+            dim: DENSE_DIM,
+            deprecated_count: None,
+            distance_type: DistanceType::Dot,
+            invert: false,
+        },
+        Encoding::OneBit,
+        QueryEncoding::SameAsStorage,
+        None,
+        &AtomicBool::new(false),
+    )
+    .unwrap();
+
+    let query = encoded.encode_query(&random_vector(&mut rng, DENSE_DIM));
+    let hardware_counter = HardwareCounterCell::new();
+
+    group.bench_function("score-points", |b| {
+        b.iter_batched(
+            || random_batch(&mut rng),
+            |points| {
+                points
+                    .iter()
+                    .map(|&point| encoded.score_point(&query, point, &hardware_counter))
+                    .sum::<ScoreType>()
+            },
+            criterion::BatchSize::SmallInput,
+        )
+    });
+}
+
+#[cfg(not(target_os = "windows"))]
+criterion_group! {
+    name = benches;
+    config = Criterion::default().with_profiler(prof::FlamegraphProfiler::new(100));
+    targets = dense_scoring_bench, sparse_scoring_bench, quantized_scoring_bench
+}
+
+#[cfg(target_os = "windows")]
+criterion_group! {
+    name = benches;
+    config = Criterion::default();
+    targets = dense_scoring_bench, sparse_scoring_bench, quantized_scoring_bench
+}
+
+criterion_main!(benches);
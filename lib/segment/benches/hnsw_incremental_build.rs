@@ -373,6 +373,9 @@ fn build_hnsw_index<R: Rng + ?Sized>(
         on_disk: Some(false),
         payload_m: None,
         inline_storage: None,
+        target_recall: None,
+        m0: None,
+        lock_in_ram: None,
     };
 
     let open_args = HnswIndexOpenArgs {
@@ -391,7 +394,7 @@ fn build_hnsw_index<R: Rng + ?Sized>(
     pub const HNSW_INDEX_CONFIG_FILE: &str = "hnsw_config.json";
     if path.join(HNSW_INDEX_CONFIG_FILE).exists() {
         log::info!("Loading cached HNSW index from {path:?}");
-        return HNSWIndex::open(open_args).unwrap();
+        return HNSWIndex::open(open_args, None).unwrap();
     }
 
     let permit_cpu_count = num_rayon_threads(open_args.hnsw_config.max_indexing_threads);
@@ -408,6 +411,7 @@ fn build_hnsw_index<R: Rng + ?Sized>(
             hnsw_global_config: &HnswGlobalConfig::default(),
             feature_flags: feature_flags(),
             progress: ProgressTracker::new_for_test(),
+            build_checkpoint_dir: None,
         },
     )
     .unwrap()
@@ -110,6 +110,9 @@ fn make_segment_index<R: Rng + ?Sized>(rng: &mut R, distance: Distance) -> HNSWI
         on_disk: None,
         payload_m: None,
         inline_storage: None,
+        target_recall: None,
+        m0: None,
+        lock_in_ram: None,
     };
     let permit_cpu_count = num_rayon_threads(hnsw_config.max_indexing_threads);
     let permit = Arc::new(ResourcePermit::dummy(permit_cpu_count as u32));
@@ -133,6 +136,7 @@ fn make_segment_index<R: Rng + ?Sized>(rng: &mut R, distance: Distance) -> HNSWI
             hnsw_global_config: &HnswGlobalConfig::default(),
             feature_flags: FeatureFlags::default(),
             progress: ProgressTracker::new_for_test(),
+            build_checkpoint_dir: None,
         },
     )
     .unwrap();
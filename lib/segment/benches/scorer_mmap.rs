@@ -37,7 +37,8 @@ fn init_mmap_vector_storage(
 ) -> (VectorStorageEnum, Arc<AtomicRefCell<IdTrackerSS>>) {
     let id_tracker = Arc::new(AtomicRefCell::new(FixtureIdTracker::new(num)));
     let mut storage =
-        open_memmap_vector_storage(path, dim, dist, AdviceSetting::Global, populate).unwrap();
+        open_memmap_vector_storage(path, dim, dist, false, AdviceSetting::Global, populate)
+            .unwrap();
     let mut vectors = (0..num).map(|_id| {
         let vector = random_vector(dim);
         (CowVector::from(vector), false)
@@ -49,7 +50,8 @@ fn init_mmap_vector_storage(
     assert_eq!(storage.available_vector_count(), num);
     drop(storage);
     let storage =
-        open_memmap_vector_storage(path, dim, dist, AdviceSetting::Global, populate).unwrap();
+        open_memmap_vector_storage(path, dim, dist, false, AdviceSetting::Global, populate)
+            .unwrap();
     assert_eq!(storage.available_vector_count(), num);
     (storage, id_tracker)
 }
@@ -87,6 +87,7 @@ fn sparse_vector_index_build_benchmark(c: &mut Criterion) {
             let sparse_vector_index: SparseVectorIndex<InvertedIndexRam> =
                 SparseVectorIndex::open(SparseVectorIndexOpenArgs {
                     config: index_config,
+                    modifier: None,
                     id_tracker: id_tracker.clone(),
                     vector_storage: vector_storage.clone(),
                     payload_index: wrapped_payload_index.clone(),
@@ -103,6 +104,7 @@ fn sparse_vector_index_build_benchmark(c: &mut Criterion) {
     let sparse_vector_index: SparseVectorIndex<InvertedIndexRam> =
         SparseVectorIndex::open(SparseVectorIndexOpenArgs {
             config: index_config,
+            modifier: None,
             id_tracker,
             vector_storage,
             payload_index: wrapped_payload_index,
@@ -107,6 +107,7 @@ fn sparse_vector_index_search_benchmark_impl(
     let sparse_vector_index_mmap: SparseVectorIndex<InvertedIndexCompressedMmap<f32>> =
         SparseVectorIndex::open(SparseVectorIndexOpenArgs {
             config: sparse_index_config,
+            modifier: None,
             id_tracker: sparse_vector_index.id_tracker().clone(),
             vector_storage: sparse_vector_index.vector_storage().clone(),
             payload_index: sparse_vector_index.payload_index().clone(),
@@ -240,6 +240,11 @@ impl OperationDurationsAggregator {
         }))
     }
 
+    /// Time the last operation finished at, or the aggregator was created at.
+    pub fn last_response_date(&self) -> Option<DateTime<Utc>> {
+        self.last_response_date
+    }
+
     pub fn add_operation_result(&mut self, success: bool, duration: Duration) {
         if success {
             self.total_value += duration.as_micros() as u64;
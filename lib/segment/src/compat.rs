@@ -167,6 +167,9 @@ mod tests {
                             on_disk: None,
                             payload_m: Some(10),
                             inline_storage: None,
+                            target_recall: None,
+                            m0: None,
+                            lock_in_ram: None,
                         }),
                         quantization_config: None,
                         on_disk: None,
@@ -199,6 +202,9 @@ mod tests {
                 on_disk: None,
                 payload_m: None,
                 inline_storage: None,
+                target_recall: None,
+                m0: None,
+                lock_in_ram: None,
             }),
             storage_type: StorageTypeV5::InMemory,
             payload_storage_type: None,
@@ -275,6 +281,9 @@ mod tests {
                 on_disk: None,
                 payload_m: None,
                 inline_storage: None,
+                target_recall: None,
+                m0: None,
+                lock_in_ram: None,
             }),
             storage_type: StorageTypeV5::InMemory,
             payload_storage_type: None,
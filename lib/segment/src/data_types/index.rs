@@ -6,6 +6,8 @@ use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use validator::{Validate, ValidationError, ValidationErrors};
 
+use crate::json_path::JsonPath;
+
 // Keyword
 
 #[derive(Default, Debug, Deserialize, Serialize, JsonSchema, Clone, Copy, PartialEq, Hash, Eq)]
@@ -34,6 +36,24 @@ pub struct KeywordIndexParams {
     /// Default: true.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub enable_hnsw: Option<bool>,
+
+    /// Maintain a null index for this payload field, so that `is_empty` and `is_null` conditions
+    /// are answered from bitmaps instead of reading payloads.
+    /// Default: true.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub null_index: Option<bool>,
+
+    /// Maintain a trigram index for this payload field, so that candidates of substring matches
+    /// with `text` are pre-filtered by trigrams instead of reading all payloads.
+    /// Default: false.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trigram_index: Option<bool>,
+
+    /// Maintain a composite index of this field and the given number or datetime field, so that
+    /// filters matching a value of this field and a range of the other one are served by a single
+    /// lookup instead of intersecting both indexes.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub composite_with: Option<JsonPath>,
 }
 
 // Integer
@@ -74,6 +94,18 @@ pub struct IntegerIndexParams {
     /// Default: true.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub enable_hnsw: Option<bool>,
+
+    /// Maintain a null index for this payload field, so that `is_empty` and `is_null` conditions
+    /// are answered from bitmaps instead of reading payloads.
+    /// Default: true.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub null_index: Option<bool>,
+
+    /// Maintain a composite index of this field and the given number or datetime field, so that
+    /// filters matching a value of this field and a range of the other one are served by a single
+    /// lookup instead of intersecting both indexes.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub composite_with: Option<JsonPath>,
 }
 
 impl Validate for IntegerIndexParams {
@@ -85,6 +117,8 @@ impl Validate for IntegerIndexParams {
             is_principal: _,
             on_disk: _,
             enable_hnsw: _,
+            null_index: _,
+            composite_with: _,
         } = &self;
         validate_integer_index_params(lookup, range)
     }
@@ -132,6 +166,12 @@ pub struct UuidIndexParams {
     /// Default: true.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub enable_hnsw: Option<bool>,
+
+    /// Maintain a null index for this payload field, so that `is_empty` and `is_null` conditions
+    /// are answered from bitmaps instead of reading payloads.
+    /// Default: true.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub null_index: Option<bool>,
 }
 
 // Float
@@ -162,6 +202,12 @@ pub struct FloatIndexParams {
     /// Default: true.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub enable_hnsw: Option<bool>,
+
+    /// Maintain a null index for this payload field, so that `is_empty` and `is_null` conditions
+    /// are answered from bitmaps instead of reading payloads.
+    /// Default: true.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub null_index: Option<bool>,
 }
 
 // Geo
@@ -188,6 +234,12 @@ pub struct GeoIndexParams {
     /// Default: true.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub enable_hnsw: Option<bool>,
+
+    /// Maintain a null index for this payload field, so that `is_empty` and `is_null` conditions
+    /// are answered from bitmaps instead of reading payloads.
+    /// Default: true.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub null_index: Option<bool>,
 }
 
 // Text
@@ -207,6 +259,7 @@ pub enum TokenizerType {
     #[default]
     Word,
     Multilingual,
+    Ngram,
 }
 
 #[derive(Debug, Default, Deserialize, Serialize, JsonSchema, Clone, PartialEq, Hash, Eq)]
@@ -223,6 +276,7 @@ pub struct TextIndexParams {
     pub min_token_len: Option<usize>,
 
     /// Maximum characters to be tokenized.
+    /// For `ngram` tokenizer - number of characters in n-grams. Default: 2.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub max_token_len: Option<usize>,
 
@@ -234,6 +288,17 @@ pub struct TextIndexParams {
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub ascii_folding: Option<bool>,
 
+    /// If true, apply Unicode NFKC normalization to tokens, so that compatibility variants of the
+    /// same characters match (e.g., full-width "ＡＢＣ" -> "ABC", "ﬁ" -> "fi"). Default: false.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unicode_normalization: Option<bool>,
+
+    /// If true, apply full Unicode case folding to tokens instead of lowercasing, so that
+    /// case-insensitive matching works consistently across scripts (e.g., "Straße" -> "strasse").
+    /// Default: false.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub case_folding: Option<bool>,
+
     /// If true, support phrase matching. Default: false.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub phrase_matching: Option<bool>,
@@ -246,6 +311,17 @@ pub struct TextIndexParams {
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub on_disk: Option<bool>,
 
+    /// For on-disk index: number of the longest posting lists to load into memory when the index
+    /// is opened, along with the vocabulary. Default: nothing is loaded in advance.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub on_disk_populate_postings: Option<usize>,
+
+    /// If true, lock the vocabulary in RAM with `mlock`, so that it is not evicted from the page
+    /// cache under memory pressure. Requires a large enough `RLIMIT_MEMLOCK`, otherwise the
+    /// vocabulary is left unlocked and a warning is logged. Default: false.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lock_in_ram: Option<bool>,
+
     /// Algorithm for stemming. Default: disabled.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub stemmer: Option<StemmingAlgorithm>,
@@ -255,6 +331,12 @@ pub struct TextIndexParams {
     /// Default: true.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub enable_hnsw: Option<bool>,
+
+    /// Maintain a null index for this payload field, so that `is_empty` and `is_null` conditions
+    /// are answered from bitmaps instead of reading payloads.
+    /// Default: true.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub null_index: Option<bool>,
 }
 
 #[derive(Default, Debug, Deserialize, Serialize, JsonSchema, Clone, Copy, PartialEq, Hash, Eq)]
@@ -512,6 +594,12 @@ pub struct BoolIndexParams {
     /// Default: true.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub enable_hnsw: Option<bool>,
+
+    /// Maintain a null index for this payload field, so that `is_empty` and `is_null` conditions
+    /// are answered from bitmaps instead of reading payloads.
+    /// Default: true.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub null_index: Option<bool>,
 }
 
 // Datetime
@@ -542,6 +630,12 @@ pub struct DatetimeIndexParams {
     /// Default: true.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub enable_hnsw: Option<bool>,
+
+    /// Maintain a null index for this payload field, so that `is_empty` and `is_null` conditions
+    /// are answered from bitmaps instead of reading payloads.
+    /// Default: true.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub null_index: Option<bool>,
 }
 
 #[cfg(test)]
@@ -8,6 +8,9 @@ pub struct SegmentManifest {
     pub segment_id: String,
     pub segment_version: SeqNumberType,
     pub file_versions: HashMap<PathBuf, FileVersion>,
+    /// SHA-256 of immutable files, which are unchanged if hashes match regardless of versions
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub file_hashes: HashMap<PathBuf, String>,
 }
 
 impl SegmentManifest {
@@ -16,6 +19,7 @@ impl SegmentManifest {
             segment_id: segment_id.into(),
             segment_version: 0,
             file_versions: HashMap::new(),
+            file_hashes: HashMap::new(),
         }
     }
 
@@ -25,6 +29,10 @@ impl SegmentManifest {
             .map(|version| version.or_segment_version(self.segment_version))
     }
 
+    pub fn file_hash(&self, file: &Path) -> Option<&str> {
+        self.file_hashes.get(file).map(String::as_str)
+    }
+
     pub fn file_versions(&self) -> impl Iterator<Item = (&Path, SeqNumberType)> {
         self.file_versions.iter().map(|(file, version)| {
             let file = file.as_path();
@@ -7,7 +7,8 @@ use super::primitive::PrimitiveVectorElement;
 use super::tiny_map;
 use super::vectors::{
     DenseVector, MultiDenseVectorInternal, TypedMultiDenseVector, TypedMultiDenseVectorRef,
-    VectorElementType, VectorElementTypeByte, VectorElementTypeHalf, VectorInternal, VectorRef,
+    VectorElementType, VectorElementTypeBf16, VectorElementTypeByte, VectorElementTypeHalf,
+    VectorInternal, VectorRef,
 };
 use crate::common::operation_error::OperationError;
 use crate::types::{VectorDataConfig, VectorName, VectorNameBuf, VectorStorageDatatype};
@@ -359,6 +360,9 @@ impl<'a> NamedVectors<'a> {
             Some(VectorStorageDatatype::Float16) => config
                 .distance
                 .preprocess_vector::<VectorElementTypeHalf>(dense_vector),
+            Some(VectorStorageDatatype::Bfloat16) => config
+                .distance
+                .preprocess_vector::<VectorElementTypeBf16>(dense_vector),
         }
     }
 }
@@ -1,13 +1,15 @@
 use std::borrow::Cow;
 
-use half::f16;
+use half::{bf16, f16};
 use itertools::Itertools;
 use serde::{Deserialize, Serialize};
 use zerocopy::{FromBytes, Immutable, IntoBytes, KnownLayout};
 
 use super::named_vectors::CowMultiVector;
 use super::vectors::TypedMultiDenseVector;
-use crate::data_types::vectors::{VectorElementType, VectorElementTypeByte, VectorElementTypeHalf};
+use crate::data_types::vectors::{
+    VectorElementType, VectorElementTypeBf16, VectorElementTypeByte, VectorElementTypeHalf,
+};
 use crate::types::{Distance, QuantizationConfig, VectorStorageDatatype};
 
 pub trait PrimitiveVectorElement
@@ -26,6 +28,13 @@ where
         vector: &'a [Self],
     ) -> Cow<'a, [f32]>;
 
+    /// Vector as `f16` values, which scalar quantization encodes without expanding into `f32`.
+    ///
+    /// `None` for types which have to go through [`Self::quantization_preprocess`].
+    fn quantization_half(_vector: &[Self]) -> Option<&[f16]> {
+        None
+    }
+
     fn datatype() -> VectorStorageDatatype;
 
     fn from_float_multivector(
@@ -88,6 +97,10 @@ impl PrimitiveVectorElement for VectorElementTypeHalf {
         Cow::Owned(vector.iter().map(|&x| f16::to_f32(x)).collect_vec())
     }
 
+    fn quantization_half(vector: &[Self]) -> Option<&[f16]> {
+        Some(vector)
+    }
+
     fn from_float_multivector(
         multivector: CowMultiVector<VectorElementType>,
     ) -> CowMultiVector<Self> {
@@ -121,6 +134,56 @@ impl PrimitiveVectorElement for VectorElementTypeHalf {
     }
 }
 
+impl PrimitiveVectorElement for VectorElementTypeBf16 {
+    fn slice_from_float_cow(vector: Cow<[VectorElementType]>) -> Cow<[Self]> {
+        Cow::Owned(vector.iter().map(|&x| bf16::from_f32(x)).collect())
+    }
+
+    fn slice_to_float_cow(vector: Cow<[Self]>) -> Cow<[VectorElementType]> {
+        Cow::Owned(vector.iter().map(|&x| bf16::to_f32(x)).collect_vec())
+    }
+
+    fn quantization_preprocess<'a>(
+        _quantization_config: &QuantizationConfig,
+        _distance: Distance,
+        vector: &'a [Self],
+    ) -> Cow<'a, [f32]> {
+        Cow::Owned(vector.iter().map(|&x| bf16::to_f32(x)).collect_vec())
+    }
+
+    fn from_float_multivector(
+        multivector: CowMultiVector<VectorElementType>,
+    ) -> CowMultiVector<Self> {
+        CowMultiVector::Owned(TypedMultiDenseVector::new(
+            multivector
+                .as_vec_ref()
+                .flattened_vectors
+                .iter()
+                .map(|&x| bf16::from_f32(x))
+                .collect_vec(),
+            multivector.as_vec_ref().dim,
+        ))
+    }
+
+    fn into_float_multivector(
+        multivector: CowMultiVector<Self>,
+    ) -> CowMultiVector<VectorElementType> {
+        CowMultiVector::Owned(TypedMultiDenseVector::new(
+            multivector
+                .as_vec_ref()
+                .flattened_vectors
+                .iter()
+                .map(|&x| bf16::to_f32(x))
+                .collect_vec(),
+            multivector.as_vec_ref().dim,
+        ))
+    }
+
+    fn datatype() -> VectorStorageDatatype {
+        VectorStorageDatatype::Bfloat16
+    }
+}
+
 impl PrimitiveVectorElement for VectorElementTypeByte {
     fn slice_from_float_cow(vector: Cow<[VectorElementType]>) -> Cow<[Self]> {
         Cow::Owned(vector.iter().map(|&x| x as u8).collect())
@@ -22,9 +22,9 @@ use crate::index::field_index::{CardinalityEstimation, FieldIndex};
 use crate::json_path::JsonPath;
 use crate::telemetry::SegmentTelemetry;
 use crate::types::{
-    ExtendedPointId, Filter, Payload, PayloadFieldSchema, PayloadKeyType, PayloadKeyTypeRef,
-    PointIdType, ScoredPoint, SearchParams, SegmentConfig, SegmentInfo, SegmentType, SeqNumberType,
-    VectorName, VectorNameBuf, WithPayload, WithVector,
+    ExtendedPointId, FieldCondition, Filter, Payload, PayloadFieldSchema, PayloadKeyType,
+    PayloadKeyTypeRef, PointIdType, ScoredPoint, SearchParams, SegmentConfig, SegmentInfo,
+    SegmentType, SeqNumberType, VectorName, VectorNameBuf, WithPayload, WithVector,
 };
 
 /// Define all operations which can be performed with non-appendable Segment or Segment-like entity.
@@ -102,6 +102,17 @@ pub trait NonAppendableSegmentEntry: SnapshotEntry {
         hw_counter: &HardwareCounterCell,
     ) -> OperationResult<Payload>;
 
+    /// Positions of tokens matched by a full-text condition in the document of the point.
+    ///
+    /// Intended for highlighting. Returns `None` if the point doesn't match, or if there is no
+    /// full text index storing positions for the field.
+    fn text_match_positions(
+        &self,
+        point_id: PointIdType,
+        condition: &FieldCondition,
+        hw_counter: &HardwareCounterCell,
+    ) -> OperationResult<Option<Vec<u32>>>;
+
     /// Iterator over all points in segment in ascending order.
     fn iter_points(&self) -> Box<dyn Iterator<Item = PointIdType> + '_>;
 
@@ -154,6 +165,15 @@ pub trait NonAppendableSegmentEntry: SnapshotEntry {
         hw_counter: &HardwareCounterCell,
     ) -> OperationResult<BTreeSet<FacetValue>>;
 
+    /// Return ids of points for each value of the given key, which satisfy the filter.
+    fn facet_point_ids(
+        &self,
+        key: &JsonPath,
+        filter: Option<&Filter>,
+        is_stopped: &AtomicBool,
+        hw_counter: &HardwareCounterCell,
+    ) -> OperationResult<HashMap<FacetValue, Vec<PointIdType>>>;
+
     /// Return the largest counts for the given facet request.
     fn facet(
         &self,
@@ -228,11 +248,24 @@ pub trait NonAppendableSegmentEntry: SnapshotEntry {
     /// the appendable state of the wrapped segment.
     fn is_appendable(&self) -> bool;
 
+    /// Whether payload of existing points can be updated in place
+    ///
+    /// Non-appendable segments may accept payload updates, if all of their payload indexes do.
+    fn accepts_payload_updates(&self) -> bool {
+        self.is_appendable()
+    }
+
     /// Returns a function, which when called, will flush all pending changes to disk.
     /// If there are currently no changes to flush, returns None.
     /// If `force` is true, will return a flusher even if there are no changes to flush.
     fn flusher(&self, force: bool) -> Option<Flusher>;
 
+    /// Tune parameters of indexes, which adapt to searches, like default HNSW `ef`.
+    ///
+    /// May run exact searches for a while, so it is called in the background after flushing,
+    /// not on the search, update or flush path. Tuned parameters are persisted by the next flush.
+    fn tune_indexes(&self) {}
+
     /// Immediately flush all changes to disk and return persisted version.
     /// Blocks the current thread.
     fn flush(&self, force: bool) -> OperationResult<SeqNumberType> {
@@ -320,6 +353,30 @@ pub trait NonAppendableSegmentEntry: SnapshotEntry {
         self.apply_field_index(op_num, key.to_owned(), schema, indexes)
     }
 
+    /// Build index of a payload field, which is already indexed with a different schema, next to
+    /// the current index, to be swapped in by [`Self::apply_field_index_rebuild`]. Returns false
+    /// if the field is not indexed with a different schema, or can't be rebuilt in place, in
+    /// which case the index has to be deleted and created again.
+    fn stage_field_index_rebuild(
+        &self,
+        _op_num: SeqNumberType,
+        _key: PayloadKeyTypeRef,
+        _field_schema: &PayloadFieldSchema,
+        _hw_counter: &HardwareCounterCell,
+    ) -> OperationResult<bool> {
+        Ok(false)
+    }
+
+    /// Swap in the index staged by [`Self::stage_field_index_rebuild`]. Returns whether it was
+    /// actually applied or not.
+    fn apply_field_index_rebuild(
+        &mut self,
+        _op_num: SeqNumberType,
+        _key: PayloadKeyTypeRef,
+    ) -> OperationResult<bool> {
+        Ok(false)
+    }
+
     fn delete_point(
         &mut self,
         op_num: SeqNumberType,
@@ -6,7 +6,9 @@ use common::types::PointOffsetType;
 use rand::Rng;
 
 use crate::data_types::vectors::{DenseVector, QueryVector, VectorElementType, VectorRef};
-use crate::index::hnsw_index::graph_links::StorageGraphLinksVectors;
+use crate::index::hnsw_index::graph_links::{
+    GraphLinksBaseVectorEncoding, StorageGraphLinksVectors,
+};
 use crate::index::hnsw_index::point_scorer::FilteredScorer;
 use crate::types::{Distance, ScalarQuantizationConfig};
 use crate::vector_storage::dense::volatile_dense_vector_storage::new_volatile_dense_vector_storage;
@@ -60,6 +62,7 @@ impl TestRawScorerProducer {
                 // the directory immediately.
                 tempfile::tempdir().unwrap().path(),
                 1,
+                None,
                 &AtomicBool::new(false),
             )
             .unwrap()
@@ -81,7 +84,11 @@ impl TestRawScorerProducer {
     }
 
     pub fn graph_links_vectors(&self) -> Option<StorageGraphLinksVectors<'_>> {
-        StorageGraphLinksVectors::try_new(&self.storage, self.quantized_vectors.as_ref())
+        StorageGraphLinksVectors::try_new(
+            &self.storage,
+            self.quantized_vectors.as_ref(),
+            GraphLinksBaseVectorEncoding::Storage,
+        )
     }
 
     pub fn scorer(&self, query: impl Into<QueryVector>) -> FilteredScorer<'_> {
@@ -77,6 +77,7 @@ pub fn fixture_sparse_index_from_iter<I: InvertedIndex>(
     let sparse_vector_index: SparseVectorIndex<I> =
         SparseVectorIndex::open(SparseVectorIndexOpenArgs {
             config: sparse_index_config,
+            modifier: None,
             id_tracker,
             vector_storage: vector_storage.clone(),
             payload_index: wrapped_payload_index,
@@ -10,7 +10,7 @@ use rand::{Rng, SeedableRng};
 use super::in_memory_id_tracker::InMemoryIdTracker;
 use super::mutable_id_tracker::MutableIdTracker;
 use crate::common::Flusher;
-use crate::common::operation_error::OperationResult;
+use crate::common::operation_error::{OperationError, OperationResult};
 use crate::id_tracker::immutable_id_tracker::ImmutableIdTracker;
 #[cfg(feature = "rocksdb")]
 use crate::id_tracker::simple_id_tracker::SimpleIdTracker;
@@ -206,6 +206,30 @@ pub trait IdTracker: fmt::Debug {
         Ok(to_return)
     }
 
+    /// Whether this tracker supports [`IdTracker::compact`]
+    fn supports_compaction(&self) -> bool {
+        false
+    }
+
+    /// Move points to new internal IDs and drop all internal IDs from `len` onwards
+    ///
+    /// Each move relocates the mapping and version of a point from the first to the second
+    /// internal ID, which must not be used by another point. All points must be below `len`
+    /// afterwards.
+    ///
+    /// The new state is persisted immediately, superseding all pending changes. The caller must
+    /// flush other segment components beforehand.
+    fn compact(
+        &mut self,
+        _moves: &[(PointOffsetType, PointOffsetType)],
+        _len: usize,
+    ) -> OperationResult<()> {
+        Err(OperationError::service_error(format!(
+            "Compaction is not supported by {}",
+            self.name(),
+        )))
+    }
+
     fn files(&self) -> Vec<PathBuf>;
 
     fn immutable_files(&self) -> Vec<PathBuf> {
@@ -465,6 +489,30 @@ impl IdTracker for IdTrackerEnum {
         }
     }
 
+    fn supports_compaction(&self) -> bool {
+        match self {
+            IdTrackerEnum::MutableIdTracker(id_tracker) => id_tracker.supports_compaction(),
+            IdTrackerEnum::ImmutableIdTracker(id_tracker) => id_tracker.supports_compaction(),
+            IdTrackerEnum::InMemoryIdTracker(id_tracker) => id_tracker.supports_compaction(),
+            #[cfg(feature = "rocksdb")]
+            IdTrackerEnum::RocksDbIdTracker(id_tracker) => id_tracker.supports_compaction(),
+        }
+    }
+
+    fn compact(
+        &mut self,
+        moves: &[(PointOffsetType, PointOffsetType)],
+        len: usize,
+    ) -> OperationResult<()> {
+        match self {
+            IdTrackerEnum::MutableIdTracker(id_tracker) => id_tracker.compact(moves, len),
+            IdTrackerEnum::ImmutableIdTracker(id_tracker) => id_tracker.compact(moves, len),
+            IdTrackerEnum::InMemoryIdTracker(id_tracker) => id_tracker.compact(moves, len),
+            #[cfg(feature = "rocksdb")]
+            IdTrackerEnum::RocksDbIdTracker(id_tracker) => id_tracker.compact(moves, len),
+        }
+    }
+
     fn files(&self) -> Vec<PathBuf> {
         match self {
             IdTrackerEnum::MutableIdTracker(id_tracker) => id_tracker.files(),
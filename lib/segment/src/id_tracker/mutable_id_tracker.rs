@@ -7,7 +7,7 @@ use std::sync::atomic::AtomicU64;
 
 use bitvec::prelude::{BitSlice, BitVec};
 use byteorder::{ReadBytesExt, WriteBytesExt};
-use common::fs::OneshotFile;
+use common::fs::{OneshotFile, atomic_save};
 use common::is_alive_lock::IsAliveLock;
 use common::types::PointOffsetType;
 use fs_err::File;
@@ -410,6 +410,57 @@ impl IdTracker for MutableIdTracker {
         "mutable id tracker"
     }
 
+    fn supports_compaction(&self) -> bool {
+        true
+    }
+
+    fn compact(
+        &mut self,
+        moves: &[(PointOffsetType, PointOffsetType)],
+        len: usize,
+    ) -> OperationResult<()> {
+        // Wait for ongoing flushes and disarm flushers created before, their pending changes refer
+        // to old internal IDs and are superseded by the files we rewrite below
+        self.is_alive_lock.blocking_mark_dead();
+        self.is_alive_lock = IsAliveLock::new();
+        self.pending_mappings = Default::default();
+        self.pending_versions = Default::default();
+
+        let mut moved_versions = BTreeMap::new();
+        for &(from, to) in moves {
+            let external_id = self.mappings.external_id(from).ok_or_else(|| {
+                OperationError::service_error(format!("No point to move at internal id {from}"))
+            })?;
+            debug_assert!(self.mappings.is_deleted_point(to));
+            self.mappings.set_link(external_id, to);
+
+            let version = self
+                .internal_to_version
+                .get_mut(from as usize)
+                .map_or(DELETED_POINT_VERSION, |version| {
+                    std::mem::replace(version, DELETED_POINT_VERSION)
+                });
+            if to as usize >= self.internal_to_version.len() {
+                self.internal_to_version
+                    .resize(to as usize + 1, DELETED_POINT_VERSION);
+            }
+            self.internal_to_version[to as usize] = version;
+            moved_versions.insert(to, version);
+        }
+        self.mappings.truncate(len);
+        self.internal_to_version.truncate(len);
+
+        // Persist versions at new positions first, so that both old and new mappings are valid
+        // if we crash before all files are rewritten
+        let versions_path = versions_path(&self.segment_path);
+        store_version_changes(&versions_path, &moved_versions)?;
+        let mappings_len = rewrite_mappings(&mappings_path(&self.segment_path), &self.mappings)?;
+        rewrite_versions(&versions_path, &self.internal_to_version)?;
+        self.mappings_expected_len = Arc::new(AtomicU64::new(mappings_len));
+
+        Ok(())
+    }
+
     #[inline]
     fn files(&self) -> Vec<PathBuf> {
         Self::segment_files(&self.segment_path)
@@ -503,6 +554,34 @@ fn store_mapping_changes(
     Ok(())
 }
 
+/// Atomically replace the mappings file with insertions of all current point mappings
+///
+/// Returns the new length of the file.
+fn rewrite_mappings(mappings_path: &Path, mappings: &PointMappings) -> OperationResult<u64> {
+    let changes: Vec<_> = mappings
+        .iter_internal()
+        .filter_map(|internal_id| {
+            let external_id = mappings.external_id(internal_id)?;
+            Some(MappingChange::Insert(external_id, internal_id))
+        })
+        .collect();
+
+    atomic_save(mappings_path, |writer| {
+        write_mapping_changes(writer, &changes)
+    })
+    .map_err(|err| {
+        OperationError::service_error(format!(
+            "Failed to rewrite ID tracker point mappings ({}): {err}",
+            mappings_path.display(),
+        ))
+    })?;
+
+    Ok(changes
+        .iter()
+        .map(|change| change.change_type().operation_size() as u64)
+        .sum())
+}
+
 /// Serializes pending point mapping changes into the given writer
 ///
 /// ## File format
@@ -856,6 +935,22 @@ fn store_version_changes(
     Ok(())
 }
 
+/// Atomically replace the versions file with the given versions
+fn rewrite_versions(versions_path: &Path, versions: &[SeqNumberType]) -> OperationResult<()> {
+    atomic_save(versions_path, |writer| {
+        for &version in versions {
+            writer.write_u64::<FileEndianess>(version)?;
+        }
+        Ok(())
+    })
+    .map_err(|err: OperationError| {
+        OperationError::service_error(format!(
+            "Failed to rewrite ID tracker point versions ({}): {err}",
+            versions_path.display(),
+        ))
+    })
+}
+
 /// Serializes pending point version changes into the given writer
 fn write_version_changes<W>(
     mut writer: W,
@@ -1174,6 +1269,50 @@ pub(super) mod tests {
             );
     }
 
+    #[test]
+    fn test_compact_persists_reload() {
+        let segment_dir = Builder::new().prefix("segment_dir").tempdir().unwrap();
+
+        {
+            let mut id_tracker = MutableIdTracker::open(segment_dir.path()).unwrap();
+            for (internal_id, external_id) in [100, 101, 102, 103, 104, 105].into_iter().enumerate()
+            {
+                let internal_id = internal_id as PointOffsetType;
+                id_tracker
+                    .set_link(external_id.into(), internal_id)
+                    .unwrap();
+                id_tracker
+                    .set_internal_version(internal_id, external_id)
+                    .unwrap();
+            }
+            id_tracker.drop(101.into()).unwrap();
+            id_tracker.drop(103.into()).unwrap();
+            id_tracker.drop(105.into()).unwrap();
+            id_tracker.mapping_flusher()().unwrap();
+            id_tracker.versions_flusher()().unwrap();
+
+            // Fill hole at 1 with the live point at 4, leaving 3 points
+            id_tracker.compact(&[(4, 1)], 3).unwrap();
+
+            // Flushers must not bring back stale changes
+            id_tracker.drop(102.into()).unwrap();
+            id_tracker.mapping_flusher()().unwrap();
+            id_tracker.versions_flusher()().unwrap();
+        }
+
+        let id_tracker = MutableIdTracker::open(segment_dir.path()).unwrap();
+        assert_eq!(id_tracker.total_point_count(), 3);
+        assert_eq!(id_tracker.available_point_count(), 2);
+        assert_eq!(id_tracker.internal_id(100.into()), Some(0));
+        assert_eq!(id_tracker.internal_id(104.into()), Some(1));
+        assert_eq!(id_tracker.internal_id(101.into()), None);
+        assert_eq!(id_tracker.internal_id(102.into()), None);
+        assert_eq!(id_tracker.internal_id(103.into()), None);
+        assert_eq!(id_tracker.internal_version(0), Some(100));
+        assert_eq!(id_tracker.internal_version(1), Some(104));
+        assert_eq!(id_tracker.internal_version(2), Some(DELETED_POINT_VERSION));
+    }
+
     /// Tests de/serializing of only single ID mappings.
     #[test]
     fn test_point_mappings_de_serialization_single() {
@@ -268,6 +268,19 @@ impl PointMappings {
         self.internal_to_external.len()
     }
 
+    /// Drop all internal IDs from `len` onwards, they must not be linked to any point
+    pub(crate) fn truncate(&mut self, len: usize) {
+        debug_assert!(
+            self.external_to_internal_num
+                .values()
+                .chain(self.external_to_internal_uuid.values())
+                .all(|&internal_id| (internal_id as usize) < len),
+            "cannot truncate point mappings with linked points beyond {len}",
+        );
+        self.internal_to_external.truncate(len);
+        self.deleted.truncate(len);
+    }
+
     /// Generate a random [`PointMappings`].
     #[cfg(test)]
     pub fn random(rand: &mut StdRng, total_size: u32) -> Self {
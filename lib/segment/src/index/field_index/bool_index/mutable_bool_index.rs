@@ -162,6 +162,7 @@ impl MutableBoolIndex {
             points_count: self.indexed_count,
             points_values_count: (self.trues_count + self.falses_count),
             histogram_bucket_size: None,
+            resources: None,
             index_type: "mmap_bool",
         }
     }
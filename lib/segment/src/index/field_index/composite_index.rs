@@ -0,0 +1,638 @@
+use std::collections::HashMap;
+use std::io::Write;
+use std::ops::Bound;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use common::counter::hardware_counter::HardwareCounterCell;
+use common::fs::{atomic_save, clear_disk_cache};
+use common::mmap::{AdviceSetting, Madviseable, open_read_mmap};
+use common::types::PointOffsetType;
+use fs_err as fs;
+use memmap2::Mmap;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::common::Flusher;
+use crate::common::flags::dynamic_mmap_flags::DynamicMmapFlags;
+use crate::common::operation_error::{OperationError, OperationResult};
+use crate::index::field_index::{CardinalityEstimation, PrimaryCondition};
+use crate::types::{
+    DateTimePayloadType, FieldCondition, Match, MatchValue, Payload, PayloadContainer,
+    PayloadKeyType, RangeInterface, ValueVariants,
+};
+
+const COMPOSITE_PATH: &str = "composite.bin";
+const STALE_DIR: &str = "stale";
+
+const COMPOSITE_MAGIC: &[u8; 4] = b"cpix";
+const COMPOSITE_VERSION: u32 = 1;
+/// Magic, version, number of keys and entries, and size of keys
+const COMPOSITE_HEADER_SIZE: usize = 32;
+/// Key index, point offset and value of the second field
+const ENTRY_SIZE: usize = 2 * size_of::<u32>() + size_of::<u64>();
+
+/// Tags of the first field keys, so that keywords and integers with the same bytes don't collide
+const KEY_TAG_KEYWORD: u8 = 0;
+const KEY_TAG_INTEGER: u8 = 1;
+
+/// Ordered pair of payload fields, indexed together by [`CompositeIndex`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub struct CompositeIndexConfig {
+    /// Field matched by exact value, keyword or integer
+    pub first: PayloadKeyType,
+    /// Field matched by range, number or datetime
+    pub second: PayloadKeyType,
+}
+
+impl CompositeIndexConfig {
+    pub fn dir(&self, path: &Path) -> PathBuf {
+        path.join(format!(
+            "{}-{}-composite",
+            self.first.filename(),
+            self.second.filename(),
+        ))
+    }
+}
+
+/// Match of the first field and range of the second field of a composite index.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompositeCondition {
+    pub first: FieldCondition,
+    pub second: FieldCondition,
+}
+
+impl CompositeCondition {
+    /// Find a match of `config.first` and a range of `config.second` among `conditions`.
+    pub fn find<'a>(
+        config: &CompositeIndexConfig,
+        conditions: impl Iterator<Item = &'a FieldCondition> + Clone,
+    ) -> Option<Self> {
+        let first = conditions
+            .clone()
+            .find(|condition| condition.key == config.first && first_key(condition).is_some())?;
+        let second = conditions.into_iter().find(|condition| {
+            condition.key == config.second && second_bounds(condition).is_some()
+        })?;
+        Some(Self {
+            first: first.clone(),
+            second: second.clone(),
+        })
+    }
+
+    fn config(&self) -> CompositeIndexConfig {
+        CompositeIndexConfig {
+            first: self.first.key.clone(),
+            second: self.second.key.clone(),
+        }
+    }
+}
+
+/// Index of an ordered pair of payload fields, persisted in a single little-endian file:
+///
+/// ```text
+/// header:  [magic: "cpix"][version: u32][keys_count: u64][entries_count: u64][keys_size: u64]
+/// offsets: [offset: u64] * (keys_count + 1)
+/// keys:    keys_size bytes of sorted keys of the first field, padded to 8 bytes
+/// entries: [key_index: u32][point_id: u32][value: u64] * entries_count
+/// ```
+///
+/// Entries are sorted by the key of the first field and the value of the second one, so points
+/// with a value of the first field and a range of the second field are a single run of entries.
+/// Values of the second field are stored as order-preserving encoding of `f64`.
+///
+/// The file is immutable, points with changed payload are only marked as stale. Stale points are
+/// always returned as candidates, and have to be checked against the original conditions.
+pub struct CompositeIndex {
+    config: CompositeIndexConfig,
+    path: PathBuf,
+    mmap: Mmap,
+    keys_count: usize,
+    entries_count: usize,
+    keys_size: usize,
+    /// Points, which payload has changed after the index was built
+    stale: DynamicMmapFlags,
+}
+
+impl std::fmt::Debug for CompositeIndex {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CompositeIndex")
+            .field("config", &self.config)
+            .field("path", &self.path)
+            .field("keys_count", &self.keys_count)
+            .field("entries_count", &self.entries_count)
+            .finish_non_exhaustive()
+    }
+}
+
+impl CompositeIndex {
+    /// Build the index in `dir` from payloads of points.
+    pub fn build<'a>(
+        dir: &Path,
+        config: CompositeIndexConfig,
+        payloads: impl IntoIterator<Item = (PointOffsetType, &'a Payload)>,
+    ) -> OperationResult<Self> {
+        let mut builder = CompositeIndexBuilder::new(config);
+        for (point_id, payload) in payloads {
+            builder.add_point(point_id, payload);
+        }
+        builder.finalize(dir)
+    }
+
+    pub fn open(dir: &Path, config: CompositeIndexConfig, populate: bool) -> OperationResult<Self> {
+        let path = dir.join(COMPOSITE_PATH);
+        let mmap = open_read_mmap(&path, AdviceSetting::Global, populate)?;
+        if mmap.len() < COMPOSITE_HEADER_SIZE || &mmap[0..4] != COMPOSITE_MAGIC {
+            return Err(OperationError::service_error(format!(
+                "Corrupted {COMPOSITE_PATH}: bad header",
+            )));
+        }
+
+        let version = read_u32(&mmap, 4);
+        if version != COMPOSITE_VERSION {
+            return Err(OperationError::service_error(format!(
+                "Unsupported {COMPOSITE_PATH} version: {version}",
+            )));
+        }
+
+        let keys_count = read_u64(&mmap, 8) as usize;
+        let entries_count = read_u64(&mmap, 16) as usize;
+        let keys_size = read_u64(&mmap, 24) as usize;
+
+        let expected = (keys_count + 1)
+            .checked_mul(size_of::<u64>())
+            .and_then(|size| size.checked_add(keys_size.next_multiple_of(size_of::<u64>())))
+            .and_then(|size| size.checked_add(entries_count.checked_mul(ENTRY_SIZE)?))
+            .and_then(|size| size.checked_add(COMPOSITE_HEADER_SIZE));
+        if expected != Some(mmap.len()) {
+            return Err(OperationError::service_error(format!(
+                "Corrupted {COMPOSITE_PATH}: {keys_count} keys and {entries_count} entries don't fit {} bytes",
+                mmap.len(),
+            )));
+        }
+
+        let stale = DynamicMmapFlags::open(&dir.join(STALE_DIR), populate)?;
+
+        Ok(Self {
+            config,
+            path,
+            mmap,
+            keys_count,
+            entries_count,
+            keys_size,
+            stale,
+        })
+    }
+
+    pub fn config(&self) -> &CompositeIndexConfig {
+        &self.config
+    }
+
+    /// Whether the index can serve `condition`.
+    pub fn serves(&self, condition: &CompositeCondition) -> bool {
+        condition.config() == self.config
+    }
+
+    /// Mark the point as stale, if its payload has changed after the index was built.
+    pub fn mark_stale(
+        &mut self,
+        point_id: PointOffsetType,
+        hw_counter: &HardwareCounterCell,
+    ) -> OperationResult<()> {
+        self.stale.set_with_resize(
+            point_id,
+            true,
+            hw_counter.ref_payload_index_io_write_counter(),
+        )?;
+        Ok(())
+    }
+
+    /// Candidate points for the condition, a point with multiple values may be repeated.
+    ///
+    /// Candidates include stale points, so they have to be checked against the condition.
+    pub fn filter<'a>(
+        &'a self,
+        condition: &CompositeCondition,
+        hw_counter: &HardwareCounterCell,
+    ) -> Option<Box<dyn Iterator<Item = PointOffsetType> + 'a>> {
+        let entries = self.entries_range(condition)?;
+        hw_counter
+            .payload_index_io_read_counter()
+            .incr_delta(entries.len() * ENTRY_SIZE);
+
+        let matched = entries
+            .map(|index| self.entry(index).1)
+            .filter(|point_id| !self.stale.get(*point_id));
+        Some(Box::new(matched.chain(self.stale.iter_trues())))
+    }
+
+    pub fn estimate_cardinality(
+        &self,
+        condition: &CompositeCondition,
+    ) -> Option<CardinalityEstimation> {
+        let matched = self.entries_range(condition)?.len();
+        let stale = self.stale.count_flags();
+        Some(
+            CardinalityEstimation {
+                primary_clauses: vec![],
+                min: 0,
+                exp: matched,
+                max: matched + stale,
+            }
+            .with_primary_clause(PrimaryCondition::Composite(Box::new(condition.clone()))),
+        )
+    }
+
+    /// Range of entries with the key of the first field and the value of the second field
+    /// within the condition.
+    fn entries_range(&self, condition: &CompositeCondition) -> Option<std::ops::Range<usize>> {
+        if !self.serves(condition) {
+            return None;
+        }
+        let key = first_key(&condition.first)?;
+        let (lower, upper) = second_bounds(&condition.second)?;
+
+        let Some(key_index) = self.find_key(&key) else {
+            return Some(0..0);
+        };
+
+        let start = self.partition_point(|(index, value)| {
+            index < key_index
+                || index == key_index
+                    && match lower {
+                        Bound::Included(lower) => value < lower,
+                        Bound::Excluded(lower) => value <= lower,
+                        Bound::Unbounded => false,
+                    }
+        });
+        let end = self.partition_point(|(index, value)| {
+            index < key_index
+                || index == key_index
+                    && match upper {
+                        Bound::Included(upper) => value <= upper,
+                        Bound::Excluded(upper) => value < upper,
+                        Bound::Unbounded => true,
+                    }
+        });
+        Some(start..end.max(start))
+    }
+
+    fn find_key(&self, key: &[u8]) -> Option<u32> {
+        let (mut low, mut high) = (0, self.keys_count);
+        while low < high {
+            let middle = low + (high - low) / 2;
+            match self.key(middle).cmp(key) {
+                std::cmp::Ordering::Less => low = middle + 1,
+                std::cmp::Ordering::Greater => high = middle,
+                std::cmp::Ordering::Equal => return Some(middle as u32),
+            }
+        }
+        None
+    }
+
+    /// Index of the first entry, for which `pred` of its key index and value is false.
+    fn partition_point(&self, pred: impl Fn((u32, u64)) -> bool) -> usize {
+        let (mut low, mut high) = (0, self.entries_count);
+        while low < high {
+            let middle = low + (high - low) / 2;
+            let (key_index, _, value) = self.entry(middle);
+            if pred((key_index, value)) {
+                low = middle + 1;
+            } else {
+                high = middle;
+            }
+        }
+        low
+    }
+
+    fn key(&self, index: usize) -> &[u8] {
+        let keys_start = COMPOSITE_HEADER_SIZE + (self.keys_count + 1) * size_of::<u64>();
+        let start = read_u64(&self.mmap, COMPOSITE_HEADER_SIZE + index * size_of::<u64>());
+        let end = read_u64(
+            &self.mmap,
+            COMPOSITE_HEADER_SIZE + (index + 1) * size_of::<u64>(),
+        );
+        &self.mmap[keys_start + start as usize..keys_start + end as usize]
+    }
+
+    fn entry(&self, index: usize) -> (u32, PointOffsetType, u64) {
+        let offset = COMPOSITE_HEADER_SIZE
+            + (self.keys_count + 1) * size_of::<u64>()
+            + self.keys_size.next_multiple_of(size_of::<u64>())
+            + index * ENTRY_SIZE;
+        (
+            read_u32(&self.mmap, offset),
+            read_u32(&self.mmap, offset + 4),
+            read_u64(&self.mmap, offset + 8),
+        )
+    }
+
+    pub fn flusher(&self) -> Flusher {
+        self.stale.flusher()
+    }
+
+    pub fn files(&self) -> Vec<PathBuf> {
+        let mut files = vec![self.path.clone()];
+        files.extend(self.stale.files());
+        files
+    }
+
+    pub fn wipe(self) -> OperationResult<()> {
+        let dir = self.path.parent().map(Path::to_path_buf);
+        drop(self);
+        if let Some(dir) = dir {
+            fs::remove_dir_all(dir)?;
+        }
+        Ok(())
+    }
+
+    /// Populate all pages in the mmap.
+    /// Block until all pages are populated.
+    pub fn populate(&self) -> OperationResult<()> {
+        self.mmap.populate();
+        self.stale.populate()
+    }
+
+    /// Drop disk cache.
+    pub fn clear_cache(&self) -> OperationResult<()> {
+        clear_disk_cache(&self.path)?;
+        self.stale.clear_cache()
+    }
+}
+
+/// Collects values of both fields of points, to be written by [`CompositeIndexBuilder::finalize`].
+pub struct CompositeIndexBuilder {
+    config: CompositeIndexConfig,
+    entries: HashMap<Vec<u8>, Vec<(u64, PointOffsetType)>>,
+}
+
+impl CompositeIndexBuilder {
+    pub fn new(config: CompositeIndexConfig) -> Self {
+        Self {
+            config,
+            entries: HashMap::new(),
+        }
+    }
+
+    /// Add an entry for each pair of values of the fields in the payload.
+    pub fn add_point(&mut self, point_id: PointOffsetType, payload: &Payload) {
+        let seconds: Vec<u64> = flatten(payload.get_value(&self.config.second).iter().copied())
+            .filter_map(second_value)
+            .collect();
+        if seconds.is_empty() {
+            return;
+        }
+        for key in
+            flatten(payload.get_value(&self.config.first).iter().copied()).filter_map(first_value)
+        {
+            let entries = self.entries.entry(key).or_default();
+            entries.extend(seconds.iter().map(|&second| (second, point_id)));
+        }
+    }
+
+    pub fn finalize(self, dir: &Path) -> OperationResult<CompositeIndex> {
+        let mut keys: Vec<_> = self.entries.into_iter().collect();
+        keys.sort_unstable_by(|(a, _), (b, _)| a.cmp(b));
+        for (_, entries) in &mut keys {
+            entries.sort_unstable();
+            entries.dedup();
+        }
+
+        let keys_size: usize = keys.iter().map(|(key, _)| key.len()).sum();
+        let entries_count: usize = keys.iter().map(|(_, entries)| entries.len()).sum();
+
+        fs::create_dir_all(dir)?;
+        // Stale points of a previous build don't apply to the new one
+        let stale_dir = dir.join(STALE_DIR);
+        if stale_dir.exists() {
+            fs::remove_dir_all(&stale_dir)?;
+        }
+
+        atomic_save::<OperationError, _>(&dir.join(COMPOSITE_PATH), |writer| {
+            writer.write_all(COMPOSITE_MAGIC)?;
+            writer.write_all(&COMPOSITE_VERSION.to_le_bytes())?;
+            writer.write_all(&(keys.len() as u64).to_le_bytes())?;
+            writer.write_all(&(entries_count as u64).to_le_bytes())?;
+            writer.write_all(&(keys_size as u64).to_le_bytes())?;
+
+            let mut offset = 0u64;
+            writer.write_all(&offset.to_le_bytes())?;
+            for (key, _) in &keys {
+                offset += key.len() as u64;
+                writer.write_all(&offset.to_le_bytes())?;
+            }
+            for (key, _) in &keys {
+                writer.write_all(key)?;
+            }
+            let padding = keys_size.next_multiple_of(size_of::<u64>()) - keys_size;
+            writer.write_all(&[0; size_of::<u64>()][..padding])?;
+
+            for (key_index, (_, entries)) in keys.iter().enumerate() {
+                for &(value, point_id) in entries {
+                    writer.write_all(&(key_index as u32).to_le_bytes())?;
+                    writer.write_all(&point_id.to_le_bytes())?;
+                    writer.write_all(&value.to_le_bytes())?;
+                }
+            }
+            Ok(())
+        })?;
+
+        CompositeIndex::open(dir, self.config, false)
+    }
+}
+
+fn flatten<'a>(values: impl Iterator<Item = &'a Value>) -> impl Iterator<Item = &'a Value> {
+    values.flat_map(|value| match value {
+        Value::Array(values) => values.iter().collect(),
+        value => vec![value],
+    })
+}
+
+fn keyword_key(keyword: &str) -> Vec<u8> {
+    let mut key = Vec::with_capacity(keyword.len() + 1);
+    key.push(KEY_TAG_KEYWORD);
+    key.extend_from_slice(keyword.as_bytes());
+    key
+}
+
+fn integer_key(integer: i64) -> Vec<u8> {
+    let mut key = Vec::with_capacity(size_of::<i64>() + 1);
+    key.push(KEY_TAG_INTEGER);
+    key.extend_from_slice(&integer.to_le_bytes());
+    key
+}
+
+fn first_value(value: &Value) -> Option<Vec<u8>> {
+    match value {
+        Value::String(keyword) => Some(keyword_key(keyword)),
+        Value::Number(number) => number.as_i64().map(integer_key),
+        _ => None,
+    }
+}
+
+fn second_value(value: &Value) -> Option<u64> {
+    let value = match value {
+        Value::Number(number) => number.as_f64()?,
+        Value::String(datetime) => DateTimePayloadType::from_str(datetime).ok()?.timestamp() as f64,
+        _ => return None,
+    };
+    (!value.is_nan()).then(|| ordered_key(value))
+}
+
+/// Key of the first field matched by the condition.
+fn first_key(condition: &FieldCondition) -> Option<Vec<u8>> {
+    match condition.r#match.as_ref()? {
+        Match::Value(MatchValue { value }) => match value {
+            ValueVariants::String(keyword) => Some(keyword_key(keyword)),
+            ValueVariants::Integer(integer) => Some(integer_key(*integer)),
+            ValueVariants::Bool(_) => None,
+        },
+        _ => None,
+    }
+}
+
+/// Bounds of encoded values of the second field within the range of the condition.
+///
+/// If both inclusive and exclusive bounds are set, the inclusive one is used. It is a superset of
+/// the range, as the candidates are checked against the condition anyway.
+fn second_bounds(condition: &FieldCondition) -> Option<(Bound<u64>, Bound<u64>)> {
+    let range = match condition.range.as_ref()? {
+        RangeInterface::Float(range) => range.map(|value| value.0),
+        RangeInterface::DateTime(range) => range.map(|value| value.timestamp() as f64),
+    };
+    let bound = |inclusive: Option<f64>, exclusive: Option<f64>| match (inclusive, exclusive) {
+        (Some(value), _) => Bound::Included(ordered_key(value)),
+        (None, Some(value)) => Bound::Excluded(ordered_key(value)),
+        (None, None) => Bound::Unbounded,
+    };
+    Some((bound(range.gte, range.gt), bound(range.lte, range.lt)))
+}
+
+/// Encode `f64` into `u64` with the same order.
+fn ordered_key(value: f64) -> u64 {
+    // Negative zero is equal to zero
+    let bits = if value == 0.0 { 0 } else { value.to_bits() };
+    if bits >> 63 == 1 {
+        !bits
+    } else {
+        bits | 1 << 63
+    }
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(
+        bytes[offset..offset + 4]
+            .try_into()
+            .expect("slice length mismatch"),
+    )
+}
+
+fn read_u64(bytes: &[u8], offset: usize) -> u64 {
+    u64::from_le_bytes(
+        bytes[offset..offset + 8]
+            .try_into()
+            .expect("slice length mismatch"),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use ordered_float::OrderedFloat;
+    use serde_json::json;
+    use tempfile::Builder;
+
+    use super::*;
+    use crate::json_path::JsonPath;
+    use crate::types::Range;
+
+    fn condition(tenant: &str, range: Range<OrderedFloat<f64>>) -> CompositeCondition {
+        CompositeCondition {
+            first: FieldCondition::new_match(JsonPath::new("tenant"), tenant.to_owned().into()),
+            second: FieldCondition::new_range(JsonPath::new("timestamp"), range),
+        }
+    }
+
+    fn filter(index: &CompositeIndex, condition: &CompositeCondition) -> Vec<PointOffsetType> {
+        let hw_counter = HardwareCounterCell::new();
+        let mut points: Vec<_> = index.filter(condition, &hw_counter).unwrap().collect();
+        points.sort_unstable();
+        points.dedup();
+        points
+    }
+
+    #[test]
+    fn test_composite_index() {
+        let dir = Builder::new().prefix("composite_index").tempdir().unwrap();
+        let config = CompositeIndexConfig {
+            first: JsonPath::new("tenant"),
+            second: JsonPath::new("timestamp"),
+        };
+        let payloads: Vec<Payload> = [
+            json!({"tenant": "a", "timestamp": 10}),
+            json!({"tenant": "a", "timestamp": [20, -5.5]}),
+            json!({"tenant": ["a", "b"], "timestamp": 30}),
+            json!({"tenant": "b", "timestamp": 10}),
+            json!({"tenant": "a"}),
+            json!({"tenant": "a", "timestamp": "2024-01-01T00:00:00Z"}),
+        ]
+        .into_iter()
+        .map(|value| serde_json::from_value(value).unwrap())
+        .collect();
+
+        let mut index = CompositeIndex::build(
+            dir.path(),
+            config.clone(),
+            payloads
+                .iter()
+                .enumerate()
+                .map(|(id, p)| (id as PointOffsetType, p)),
+        )
+        .unwrap();
+
+        let range = |gte: Option<f64>, lt: Option<f64>| Range {
+            gte: gte.map(OrderedFloat),
+            lt: lt.map(OrderedFloat),
+            gt: None,
+            lte: None,
+        };
+        assert_eq!(
+            filter(&index, &condition("a", range(Some(10.0), Some(30.0)))),
+            vec![0, 1]
+        );
+        assert_eq!(
+            filter(&index, &condition("a", range(None, Some(0.0)))),
+            vec![1]
+        );
+        assert_eq!(
+            filter(&index, &condition("b", range(None, None))),
+            vec![2, 3]
+        );
+        assert_eq!(
+            filter(&index, &condition("a", range(Some(1e15), None))),
+            vec![5]
+        );
+        assert!(filter(&index, &condition("c", range(None, None))).is_empty());
+
+        let estimation = index
+            .estimate_cardinality(&condition("a", range(Some(10.0), None)))
+            .unwrap();
+        assert_eq!(estimation.exp, 4);
+        assert_eq!(estimation.primary_clauses.len(), 1);
+
+        // Stale points are candidates regardless of their indexed values
+        let hw_counter = HardwareCounterCell::new();
+        index.mark_stale(3, &hw_counter).unwrap();
+        index.flusher()().unwrap();
+        drop(index);
+
+        let index = CompositeIndex::open(dir.path(), config, false).unwrap();
+        assert_eq!(
+            filter(&index, &condition("a", range(Some(10.0), Some(30.0)))),
+            vec![0, 1, 3]
+        );
+        assert_eq!(
+            filter(&index, &condition("b", range(None, None))),
+            vec![2, 3]
+        );
+    }
+}
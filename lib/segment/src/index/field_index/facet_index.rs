@@ -2,6 +2,7 @@ use common::counter::hardware_counter::HardwareCounterCell;
 use common::types::PointOffsetType;
 
 use super::bool_index::BoolIndex;
+use super::full_text_index::text_index::FullTextIndex;
 use super::map_index::{IdIter, MapIndex};
 use crate::data_types::facets::{FacetHit, FacetValueRef};
 use crate::types::{IntPayloadType, UuidIntType};
@@ -31,9 +32,25 @@ pub enum FacetIndexEnum<'a> {
     Int(&'a MapIndex<IntPayloadType>),
     Uuid(&'a MapIndex<UuidIntType>),
     Bool(&'a BoolIndex),
+    /// Values are tokens of the indexed text
+    FullText(&'a FullTextIndex),
 }
 
 impl<'a> FacetIndexEnum<'a> {
+    /// Whether values of a single point can be retrieved cheaply.
+    ///
+    /// Full-text index doesn't store tokens per point, so faceting over it should go through
+    /// [`FacetIndexEnum::iter_values_map`] instead of [`FacetIndexEnum::get_point_values`].
+    pub fn has_point_values(&self) -> bool {
+        match self {
+            FacetIndexEnum::Keyword(_)
+            | FacetIndexEnum::Int(_)
+            | FacetIndexEnum::Uuid(_)
+            | FacetIndexEnum::Bool(_) => true,
+            FacetIndexEnum::FullText(_) => false,
+        }
+    }
+
     pub fn get_point_values(
         &self,
         point_id: PointOffsetType,
@@ -45,6 +62,9 @@ impl<'a> FacetIndexEnum<'a> {
             FacetIndexEnum::Int(index) => Box::new(FacetIndex::get_point_values(*index, point_id)),
             FacetIndexEnum::Uuid(index) => Box::new(FacetIndex::get_point_values(*index, point_id)),
             FacetIndexEnum::Bool(index) => Box::new(FacetIndex::get_point_values(*index, point_id)),
+            FacetIndexEnum::FullText(index) => {
+                Box::new(FacetIndex::get_point_values(*index, point_id))
+            }
         }
     }
 
@@ -54,6 +74,7 @@ impl<'a> FacetIndexEnum<'a> {
             FacetIndexEnum::Int(index) => Box::new(FacetIndex::iter_values(*index)),
             FacetIndexEnum::Uuid(index) => Box::new(FacetIndex::iter_values(*index)),
             FacetIndexEnum::Bool(index) => Box::new(FacetIndex::iter_values(*index)),
+            FacetIndexEnum::FullText(index) => Box::new(FacetIndex::iter_values(*index)),
         }
     }
 
@@ -72,6 +93,9 @@ impl<'a> FacetIndexEnum<'a> {
             FacetIndexEnum::Bool(index) => {
                 Box::new(FacetIndex::iter_values_map(*index, hw_counter))
             }
+            FacetIndexEnum::FullText(index) => {
+                Box::new(FacetIndex::iter_values_map(*index, hw_counter))
+            }
         }
     }
 
@@ -83,6 +107,7 @@ impl<'a> FacetIndexEnum<'a> {
             FacetIndexEnum::Int(index) => Box::new(FacetIndex::iter_counts_per_value(*index)),
             FacetIndexEnum::Uuid(index) => Box::new(FacetIndex::iter_counts_per_value(*index)),
             FacetIndexEnum::Bool(index) => Box::new(FacetIndex::iter_counts_per_value(*index)),
+            FacetIndexEnum::FullText(index) => Box::new(FacetIndex::iter_counts_per_value(*index)),
         }
     }
 }
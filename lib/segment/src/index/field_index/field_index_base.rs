@@ -26,11 +26,11 @@ use crate::index::field_index::geo_index::GeoMapIndex;
 use crate::index::field_index::null_index::MutableNullIndex;
 use crate::index::field_index::null_index::mutable_null_index::MutableNullIndexBuilder;
 use crate::index::field_index::numeric_index::NumericIndexInner;
-use crate::index::field_index::{CardinalityEstimation, PayloadBlockCondition};
+use crate::index::field_index::{CardinalityEstimation, IndexResources, PayloadBlockCondition};
 use crate::index::payload_config::{
     FullPayloadIndexType, IndexMutability, PayloadIndexType, StorageType,
 };
-use crate::telemetry::PayloadIndexTelemetry;
+use crate::telemetry::{PayloadIndexResourcesTelemetry, PayloadIndexTelemetry};
 use crate::types::{
     DateTimePayloadType, FieldCondition, FloatPayloadType, IntPayloadType, Match, MatchPhrase,
     MatchText, PayloadKeyType, RangeInterface, UuidIntType, UuidPayloadType,
@@ -50,6 +50,13 @@ pub trait PayloadFieldIndex {
 
     fn immutable_files(&self) -> Vec<PathBuf>;
 
+    /// Estimate RAM and disk usage of the index.
+    ///
+    /// By default, all index files are expected to be loaded into RAM.
+    fn estimate_resources(&self) -> OperationResult<IndexResources> {
+        IndexResources::from_files(self.files())
+    }
+
     /// Get iterator over points fitting given `condition`
     /// Return `None` if condition does not match the index type
     fn filter<'a>(
@@ -245,6 +252,10 @@ impl FieldIndex {
         self.get_payload_field_index().immutable_files()
     }
 
+    pub fn estimate_resources(&self) -> OperationResult<IndexResources> {
+        self.get_payload_field_index().estimate_resources()
+    }
+
     pub fn filter<'a>(
         &'a self,
         condition: &'a FieldCondition,
@@ -331,6 +342,20 @@ impl FieldIndex {
     }
 
     pub fn get_telemetry_data(&self) -> PayloadIndexTelemetry {
+        let telemetry = self.get_index_telemetry_data();
+        match self.estimate_resources() {
+            Ok(resources) => telemetry.set_resources(PayloadIndexResourcesTelemetry {
+                disk_bytes: resources.disk_bytes,
+                populated: !self.is_on_disk(),
+            }),
+            Err(err) => {
+                log::warn!("Failed to estimate resources of payload index: {err}");
+                telemetry
+            }
+        }
+    }
+
+    fn get_index_telemetry_data(&self) -> PayloadIndexTelemetry {
         match self {
             FieldIndex::IntIndex(index) => index.get_telemetry_data(),
             FieldIndex::DatetimeIndex(index) => index.get_telemetry_data(),
@@ -400,12 +425,12 @@ impl FieldIndex {
             FieldIndex::IntMapIndex(index) => Some(FacetIndexEnum::Int(index)),
             FieldIndex::UuidMapIndex(index) => Some(FacetIndexEnum::Uuid(index)),
             FieldIndex::BoolIndex(index) => Some(FacetIndexEnum::Bool(index)),
+            FieldIndex::FullTextIndex(index) => Some(FacetIndexEnum::FullText(index)),
             FieldIndex::UuidIndex(_)
             | FieldIndex::IntIndex(_)
             | FieldIndex::DatetimeIndex(_)
             | FieldIndex::FloatIndex(_)
             | FieldIndex::GeoIndex(_)
-            | FieldIndex::FullTextIndex(_)
             | FieldIndex::NullIndex(_) => None,
         }
     }
@@ -443,6 +468,32 @@ impl FieldIndex {
         }
     }
 
+    /// Whether values of points can be updated in place.
+    ///
+    /// Immutable map indexes with mmap storage keep updated points in a delta, see
+    /// [`MapIndex::accepts_updates`].
+    pub fn accepts_updates(&self) -> bool {
+        match self {
+            FieldIndex::IntIndex(index) => index.get_mutability_type() == IndexMutability::Mutable,
+            FieldIndex::DatetimeIndex(index) => {
+                index.get_mutability_type() == IndexMutability::Mutable
+            }
+            FieldIndex::IntMapIndex(index) => index.accepts_updates(),
+            FieldIndex::KeywordIndex(index) => index.accepts_updates(),
+            FieldIndex::FloatIndex(index) => {
+                index.get_mutability_type() == IndexMutability::Mutable
+            }
+            FieldIndex::GeoIndex(index) => index.get_mutability_type() == IndexMutability::Mutable,
+            FieldIndex::BoolIndex(index) => index.get_mutability_type() == IndexMutability::Mutable,
+            FieldIndex::FullTextIndex(index) => {
+                index.get_mutability_type() == IndexMutability::Mutable
+            }
+            FieldIndex::UuidIndex(index) => index.get_mutability_type() == IndexMutability::Mutable,
+            FieldIndex::UuidMapIndex(index) => index.accepts_updates(),
+            FieldIndex::NullIndex(index) => index.get_mutability_type() == IndexMutability::Mutable,
+        }
+    }
+
     /// Populate all pages in the mmap.
     /// Block until all pages are populated.
     pub fn populate(&self) -> OperationResult<()> {
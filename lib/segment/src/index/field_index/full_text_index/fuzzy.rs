@@ -0,0 +1,170 @@
+use super::inverted_index::TokenId;
+
+/// Maximal number of edits allowed for a query token, depending on its length.
+///
+/// Short tokens are matched exactly, as a single edit would already match most of the vocabulary.
+pub fn auto_max_edits(token: &str) -> u32 {
+    match token.chars().count() {
+        0..=2 => 0,
+        3..=5 => 1,
+        _ => 2,
+    }
+}
+
+/// Check whether Levenshtein distance between `a` and `b` is at most `max_edits`
+pub fn is_within_edits(a: &str, b: &str, max_edits: u32) -> bool {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<u32> = (0..=b.len() as u32).collect();
+    for c in a.chars() {
+        row = next_row(&row, c, &b);
+        if row.iter().all(|&distance| distance > max_edits) {
+            return false;
+        }
+    }
+    row[b.len()] <= max_edits
+}
+
+/// Vocabulary sorted lexicographically, used to find tokens similar to a query token.
+///
+/// Neighbouring tokens in sorted order share prefixes, which allows to reuse the state of the
+/// Levenshtein automaton between them and to skip whole ranges of tokens at once.
+#[derive(Debug, Default)]
+pub struct SortedVocab {
+    tokens: Vec<(Box<str>, TokenId)>,
+}
+
+impl SortedVocab {
+    pub fn new<'a>(vocab: impl IntoIterator<Item = (&'a str, TokenId)>) -> Self {
+        let mut tokens: Vec<(Box<str>, TokenId)> = vocab
+            .into_iter()
+            .map(|(token, token_id)| (Box::from(token), token_id))
+            .collect();
+        tokens.sort_unstable_by(|(a, _), (b, _)| a.cmp(b));
+        Self { tokens }
+    }
+
+    /// Find all tokens within `max_edits` Levenshtein distance from `query`.
+    ///
+    /// Simulates the Levenshtein automaton of `query` over the sorted vocabulary: automaton state
+    /// (a row of the edit distance matrix) is computed once per distinct prefix, and all tokens
+    /// sharing a prefix from which the automaton can't reach an accepting state are skipped.
+    pub fn fuzzy_matches(&self, query: &str, max_edits: u32) -> Vec<TokenId> {
+        let query: Vec<char> = query.chars().collect();
+
+        // `rows[i]` is the automaton state after consuming first `i` chars of the current token
+        let mut rows: Vec<Vec<u32>> = vec![(0..=query.len() as u32).collect()];
+        let mut token_chars: Vec<char> = Vec::new();
+        let mut next_chars: Vec<char> = Vec::new();
+        // Length of the prefix of `token_chars`, after which no continuation can match
+        let mut dead_prefix_len: Option<usize> = None;
+
+        let mut matches = Vec::new();
+
+        for (token, token_id) in &self.tokens {
+            next_chars.clear();
+            next_chars.extend(token.chars());
+            let common_prefix_len = token_chars
+                .iter()
+                .zip(&next_chars)
+                .take_while(|(a, b)| a == b)
+                .count();
+            std::mem::swap(&mut token_chars, &mut next_chars);
+
+            if dead_prefix_len.is_some_and(|dead_len| common_prefix_len >= dead_len) {
+                continue;
+            }
+            dead_prefix_len = None;
+
+            rows.truncate(common_prefix_len + 1);
+            for i in rows.len() - 1..token_chars.len() {
+                let row = next_row(&rows[i], token_chars[i], &query);
+                if row.iter().all(|&distance| distance > max_edits) {
+                    dead_prefix_len = Some(i + 1);
+                    break;
+                }
+                rows.push(row);
+            }
+
+            if dead_prefix_len.is_none() && rows[token_chars.len()][query.len()] <= max_edits {
+                matches.push(*token_id);
+            }
+        }
+
+        matches
+    }
+}
+
+/// Transition of the Levenshtein automaton of `query` by char `c`
+fn next_row(prev_row: &[u32], c: char, query: &[char]) -> Vec<u32> {
+    let mut row = Vec::with_capacity(prev_row.len());
+    row.push(prev_row[0] + 1);
+    for (j, query_char) in query.iter().enumerate() {
+        let substitution = prev_row[j] + u32::from(*query_char != c);
+        let deletion = prev_row[j + 1] + 1;
+        let insertion = row[j] + 1;
+        row.push(substitution.min(deletion).min(insertion));
+    }
+    row
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fuzzy_matches() {
+        let words = [
+            "apple",
+            "apply",
+            "ample",
+            "maple",
+            "applesauce",
+            "banana",
+            "bandana",
+            "app",
+            "a",
+            "",
+            "grüße",
+            "grusse",
+            "aple",
+        ];
+        let vocab = SortedVocab::new(words.iter().enumerate().map(|(i, w)| (*w, i as TokenId)));
+
+        for query in ["apple", "banana", "grüse", "ap", "", "xyz", "aplpe"] {
+            for max_edits in 0..=2 {
+                let mut found: Vec<_> = vocab
+                    .fuzzy_matches(query, max_edits)
+                    .into_iter()
+                    .map(|id| words[id as usize])
+                    .collect();
+                found.sort_unstable();
+
+                let mut expected: Vec<_> = words
+                    .iter()
+                    .copied()
+                    .filter(|word| is_within_edits(word, query, max_edits))
+                    .collect();
+                expected.sort_unstable();
+
+                assert_eq!(found, expected, "query: {query:?}, max_edits: {max_edits}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_auto_max_edits() {
+        assert_eq!(auto_max_edits("ab"), 0);
+        assert_eq!(auto_max_edits("grüß"), 1);
+        assert_eq!(auto_max_edits("apple"), 1);
+        assert_eq!(auto_max_edits("bananas"), 2);
+    }
+
+    #[test]
+    fn test_is_within_edits() {
+        assert!(is_within_edits("kitten", "sitting", 3));
+        assert!(!is_within_edits("kitten", "sitting", 2));
+        assert!(is_within_edits("grüße", "grüse", 1));
+        assert!(is_within_edits("", "ab", 2));
+        assert!(!is_within_edits("abc", "", 2));
+    }
+}
@@ -1,7 +1,9 @@
 use std::path::PathBuf;
+use std::sync::OnceLock;
 
 use common::types::PointOffsetType;
 
+use super::fuzzy::SortedVocab;
 use super::inverted_index::InvertedIndex;
 use super::inverted_index::immutable_inverted_index::ImmutableInvertedIndex;
 #[cfg(feature = "rocksdb")]
@@ -15,6 +17,7 @@ use crate::common::operation_error::{OperationError, OperationResult};
 use crate::common::rocksdb_buffered_delete_wrapper::DatabaseColumnScheduledDeleteWrapper;
 #[cfg(feature = "rocksdb")]
 use crate::data_types::index::TextIndexParams;
+use crate::index::field_index::IndexResources;
 #[cfg(feature = "rocksdb")]
 use crate::index::field_index::full_text_index::mutable_text_index::{self, MutableFullTextIndex};
 use crate::index::field_index::full_text_index::tokenizers::Tokenizer;
@@ -25,6 +28,8 @@ pub struct ImmutableFullTextIndex {
     pub(super) tokenizer: Tokenizer,
     // Backing storage, source of state, persists deletions
     pub(super) storage: Storage,
+    /// Sorted vocabulary for fuzzy matching, built on first use
+    pub(super) sorted_vocab: OnceLock<SortedVocab>,
 }
 
 pub(super) enum Storage {
@@ -61,6 +66,7 @@ impl ImmutableFullTextIndex {
             inverted_index: ImmutableInvertedIndex::from(mutable),
             tokenizer,
             storage: Storage::RocksDb(db_wrapper),
+            sorted_vocab: OnceLock::new(),
         }))
     }
 
@@ -81,6 +87,7 @@ impl ImmutableFullTextIndex {
             inverted_index,
             storage: Storage::Mmap(Box::new(index)),
             tokenizer,
+            sorted_vocab: OnceLock::new(),
         }
     }
 
@@ -125,6 +132,52 @@ impl ImmutableFullTextIndex {
         }
     }
 
+    /// Fraction of points in postings which are deleted.
+    ///
+    /// Only tracked for mmap backed index.
+    pub fn deleted_ratio(&self) -> f64 {
+        match &self.storage {
+            #[cfg(feature = "rocksdb")]
+            Storage::RocksDb(_) => 0.0,
+            Storage::Mmap(index) => index.deleted_ratio(),
+        }
+    }
+
+    /// Drop deleted points from postings if their ratio exceeds `deleted_threshold`.
+    ///
+    /// Only mmap backed index is compacted, both in memory and on disk.
+    /// Returns whether compaction happened.
+    pub fn compact_if_needed(&mut self, deleted_threshold: f64) -> OperationResult<bool> {
+        match &mut self.storage {
+            #[cfg(feature = "rocksdb")]
+            Storage::RocksDb(_) => Ok(false),
+            Storage::Mmap(index) => {
+                if index.deleted_ratio() <= deleted_threshold {
+                    return Ok(false);
+                }
+                index.compact()?;
+                self.inverted_index = ImmutableInvertedIndex::from(&index.inverted_index);
+                // Token ids are reassigned by compaction
+                self.sorted_vocab = OnceLock::new();
+                if let Err(err) = index.clear_cache() {
+                    log::warn!("Failed to clear mmap cache of ram mmap full text index: {err}");
+                }
+                Ok(true)
+            }
+        }
+    }
+
+    pub(super) fn sorted_vocab(&self) -> &SortedVocab {
+        self.sorted_vocab.get_or_init(|| {
+            SortedVocab::new(
+                self.inverted_index
+                    .vocab
+                    .iter()
+                    .map(|(token, token_id)| (token.as_str(), *token_id)),
+            )
+        })
+    }
+
     pub fn files(&self) -> Vec<PathBuf> {
         match self.storage {
             #[cfg(feature = "rocksdb")]
@@ -141,6 +194,15 @@ impl ImmutableFullTextIndex {
         }
     }
 
+    /// Inverted index is kept in memory, backing storage is only read on load
+    pub fn estimate_resources(&self) -> OperationResult<IndexResources> {
+        let mut resources = self.inverted_index.estimate_resources()?;
+        for file in self.files() {
+            resources.add_file(file, false)?;
+        }
+        Ok(resources)
+    }
+
     pub fn flusher(&self) -> Flusher {
         match self.storage {
             #[cfg(feature = "rocksdb")]
@@ -156,6 +218,7 @@ impl ImmutableFullTextIndex {
             config: _,
             tokenizer,
             storage,
+            sorted_vocab: _,
         } = mutable;
 
         let mutable_text_index::Storage::RocksDb(db) = storage else {
@@ -168,6 +231,7 @@ impl ImmutableFullTextIndex {
             inverted_index: ImmutableInvertedIndex::from(inverted_index),
             tokenizer,
             storage: Storage::RocksDb(db),
+            sorted_vocab: OnceLock::new(),
         }
     }
 
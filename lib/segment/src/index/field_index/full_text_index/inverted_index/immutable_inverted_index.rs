@@ -5,22 +5,31 @@ use ahash::AHashMap;
 use common::counter::hardware_counter::HardwareCounterCell;
 use common::types::PointOffsetType;
 use itertools::Either;
-use posting_list::{PostingBuilder, PostingList, PostingListView, PostingValue};
+use posting_list::{PostingBuilder, PostingList, PostingListView, PostingValue, SizedTypeFor};
 
 use super::immutable_postings_enum::ImmutablePostings;
 use super::mmap_inverted_index::MmapInvertedIndex;
 use super::mmap_inverted_index::mmap_postings_enum::MmapPostingsEnum;
 use super::mutable_inverted_index::MutableInvertedIndex;
-use super::positions::Positions;
+use super::positions::{PartialDocument, Positions};
 use super::postings_iterator::{
-    intersect_compressed_postings_iterator, merge_compressed_postings_iterator,
+    SkippablePostingView, intersect_compressed_postings, merge_compressed_postings_iterator,
+    partial_document_from_postings,
 };
-use super::{Document, InvertedIndex, ParsedQuery, TokenId, TokenSet};
+use super::{Document, InvertedIndex, ParsedQuery, TokenId, TokenSet, vocab_heap_size};
 use crate::common::operation_error::{OperationError, OperationResult};
+use crate::index::field_index::IndexResources;
 use crate::index::field_index::full_text_index::inverted_index::postings_iterator::{
     check_compressed_postings_phrase, intersect_compressed_postings_phrase_iterator,
 };
 
+/// Postings of a merged index, with new ids of its tokens and points, `None` for dropped ones.
+type MergeSource<'a, V> = (
+    &'a [PostingList<V>],
+    &'a [Option<TokenId>],
+    &'a [Option<PointOffsetType>],
+);
+
 #[cfg_attr(test, derive(Clone))]
 #[derive(Debug)]
 pub struct ImmutableInvertedIndex {
@@ -43,15 +52,21 @@ impl ImmutableInvertedIndex {
                 .is_some_and(|x| *x > 0)
         };
 
-        fn intersection<'a, V: PostingValue>(
+        fn intersection<'a, V: PostingValue + Sync>(
             postings: &'a [PostingList<V>],
             tokens: TokenSet,
-            filter: impl Fn(PointOffsetType) -> bool + 'a,
-        ) -> impl Iterator<Item = PointOffsetType> + 'a {
+            filter: impl Fn(PointOffsetType) -> bool + Sync + 'a,
+        ) -> impl Iterator<Item = PointOffsetType> + 'a
+        where
+            SizedTypeFor<V>: Sync,
+        {
             let postings_opt: Option<Vec<_>> = tokens
                 .tokens()
                 .iter()
-                .map(|&token_id| postings.get(token_id as usize).map(PostingList::view))
+                .map(|&token_id| {
+                    let posting = postings.get(token_id as usize)?;
+                    Some(SkippablePostingView::from(posting.view()))
+                })
                 .collect();
 
             // All tokens must have postings
@@ -64,7 +79,7 @@ impl ImmutableInvertedIndex {
                 return Either::Left(std::iter::empty());
             };
 
-            Either::Right(intersect_compressed_postings_iterator(postings, filter))
+            Either::Right(intersect_compressed_postings(postings, filter))
         }
 
         match &self.postings {
@@ -222,6 +237,233 @@ impl ImmutableInvertedIndex {
     }
 }
 
+impl ImmutableInvertedIndex {
+    /// Drop deleted points from postings, along with tokens which are left without postings.
+    ///
+    /// Point ids are preserved, deleted points keep zero tokens count.
+    pub(in crate::index::field_index::full_text_index) fn compact(self) -> Self {
+        let Self {
+            postings,
+            vocab,
+            point_to_tokens_count,
+            points_count,
+        } = self;
+
+        let is_active = |idx: PointOffsetType| {
+            point_to_tokens_count
+                .get(idx as usize)
+                .is_some_and(|count| *count > 0)
+        };
+
+        fn compact_postings<V: PostingValue>(
+            postings: Vec<PostingList<V>>,
+            is_active: impl Fn(PointOffsetType) -> bool,
+        ) -> (Vec<PostingList<V>>, AHashMap<TokenId, TokenId>) {
+            let mut orig_to_new_token = AHashMap::new();
+            let mut compacted = Vec::with_capacity(postings.len());
+            for (orig_token, posting) in postings.into_iter().enumerate() {
+                let posting: PostingList<V> = posting
+                    .iter()
+                    .filter(|elem| is_active(elem.id))
+                    .map(|elem| (elem.id, elem.value))
+                    .collect();
+                if posting.is_empty() {
+                    continue;
+                }
+                orig_to_new_token.insert(orig_token as TokenId, compacted.len() as TokenId);
+                compacted.push(posting);
+            }
+            (compacted, orig_to_new_token)
+        }
+
+        let (postings, orig_to_new_token) = match postings {
+            ImmutablePostings::Ids(postings) => {
+                let (postings, mapping) = compact_postings(postings, is_active);
+                (ImmutablePostings::Ids(postings), mapping)
+            }
+            ImmutablePostings::WithPositions(postings) => {
+                let (postings, mapping) = compact_postings(postings, is_active);
+                (ImmutablePostings::WithPositions(postings), mapping)
+            }
+        };
+
+        let mut vocab: HashMap<String, TokenId> = vocab
+            .into_iter()
+            .filter_map(|(key, orig_token)| {
+                orig_to_new_token
+                    .get(&orig_token)
+                    .map(|new_token| (key, *new_token))
+            })
+            .collect();
+        vocab.shrink_to_fit();
+
+        Self {
+            postings,
+            vocab,
+            point_to_tokens_count,
+            points_count,
+        }
+    }
+
+    /// Merge several indexes into one, without re-tokenizing documents.
+    ///
+    /// Each source comes with a mapping of its point ids into point ids of the merged index.
+    /// Deleted points and points mapped to `None` are dropped. Vocabularies are united, and postings
+    /// of the same token are concatenated with remapped point ids.
+    pub(in crate::index::field_index::full_text_index) fn merge(
+        sources: &[(&Self, &[Option<PointOffsetType>])],
+    ) -> OperationResult<Self> {
+        // Union of vocabularies, along with translation of each source's tokens into merged ones
+        let mut vocab: HashMap<String, TokenId> = HashMap::new();
+        let sources_tokens: Vec<Vec<Option<TokenId>>> = sources
+            .iter()
+            .map(|(index, _)| {
+                let mut orig_to_new_token = vec![None; index.postings.len()];
+                for (token, &orig_token) in &index.vocab {
+                    let new_token = match vocab.get(token) {
+                        Some(&new_token) => new_token,
+                        None => {
+                            let new_token = vocab.len() as TokenId;
+                            vocab.insert(token.clone(), new_token);
+                            new_token
+                        }
+                    };
+                    if let Some(slot) = orig_to_new_token.get_mut(orig_token as usize) {
+                        *slot = Some(new_token);
+                    }
+                }
+                orig_to_new_token
+            })
+            .collect();
+
+        // Only keep mappings of points, which are not deleted in their source
+        let sources_ids: Vec<Vec<Option<PointOffsetType>>> = sources
+            .iter()
+            .map(|(index, new_ids)| {
+                new_ids
+                    .iter()
+                    .enumerate()
+                    .map(|(orig_id, new_id)| {
+                        new_id.filter(|_| {
+                            index
+                                .point_to_tokens_count
+                                .get(orig_id)
+                                .is_some_and(|count| *count > 0)
+                        })
+                    })
+                    .collect()
+            })
+            .collect();
+
+        let mut point_to_tokens_count = Vec::new();
+        for ((index, _), new_ids) in sources.iter().zip(&sources_ids) {
+            for (orig_id, new_id) in new_ids.iter().enumerate() {
+                let Some(new_id) = new_id else {
+                    continue;
+                };
+                let new_id = *new_id as usize;
+                if point_to_tokens_count.len() <= new_id {
+                    point_to_tokens_count.resize(new_id + 1, 0);
+                }
+                debug_assert_eq!(
+                    point_to_tokens_count[new_id], 0,
+                    "point {new_id} is mapped from several sources",
+                );
+                point_to_tokens_count[new_id] = index.point_to_tokens_count[orig_id];
+            }
+        }
+        let points_count = point_to_tokens_count
+            .iter()
+            .filter(|count| **count > 0)
+            .count();
+
+        fn merge_postings<V: PostingValue>(
+            sources: Vec<MergeSource<'_, V>>,
+            tokens_count: usize,
+        ) -> (Vec<PostingList<V>>, AHashMap<TokenId, TokenId>) {
+            let mut builders: Vec<PostingBuilder<V>> =
+                (0..tokens_count).map(|_| PostingBuilder::new()).collect();
+            for (postings, orig_to_new_token, new_ids) in sources {
+                for (posting, new_token) in postings.iter().zip(orig_to_new_token) {
+                    let Some(new_token) = new_token else {
+                        continue;
+                    };
+                    let builder = &mut builders[*new_token as usize];
+                    for elem in posting.iter() {
+                        if let Some(Some(new_id)) = new_ids.get(elem.id as usize) {
+                            builder.add(*new_id, elem.value);
+                        }
+                    }
+                }
+            }
+
+            // Tokens of dropped points only are left without postings
+            let mut merged_to_new_token = AHashMap::new();
+            let mut merged = Vec::with_capacity(tokens_count);
+            for (merged_token, builder) in builders.into_iter().enumerate() {
+                let posting = builder.build();
+                if posting.is_empty() {
+                    continue;
+                }
+                merged_to_new_token.insert(merged_token as TokenId, merged.len() as TokenId);
+                merged.push(posting);
+            }
+            (merged, merged_to_new_token)
+        }
+
+        let with_positions = sources.first().is_some_and(|(index, _)| {
+            matches!(index.postings, ImmutablePostings::WithPositions(_))
+        });
+        let mixed_postings_error = || {
+            OperationError::service_error(
+                "Can't merge text indexes with and without positional information",
+            )
+        };
+
+        let sources_postings = sources.iter().zip(&sources_tokens).zip(&sources_ids);
+        let (postings, merged_to_new_token) = if with_positions {
+            let sources = sources_postings
+                .map(|(((index, _), tokens), ids)| match &index.postings {
+                    ImmutablePostings::WithPositions(postings) => {
+                        Ok((postings.as_slice(), tokens.as_slice(), ids.as_slice()))
+                    }
+                    ImmutablePostings::Ids(_) => Err(mixed_postings_error()),
+                })
+                .collect::<OperationResult<Vec<_>>>()?;
+            let (postings, mapping) = merge_postings(sources, vocab.len());
+            (ImmutablePostings::WithPositions(postings), mapping)
+        } else {
+            let sources = sources_postings
+                .map(|(((index, _), tokens), ids)| match &index.postings {
+                    ImmutablePostings::Ids(postings) => {
+                        Ok((postings.as_slice(), tokens.as_slice(), ids.as_slice()))
+                    }
+                    ImmutablePostings::WithPositions(_) => Err(mixed_postings_error()),
+                })
+                .collect::<OperationResult<Vec<_>>>()?;
+            let (postings, mapping) = merge_postings(sources, vocab.len());
+            (ImmutablePostings::Ids(postings), mapping)
+        };
+
+        let mut vocab: HashMap<String, TokenId> = vocab
+            .into_iter()
+            .filter_map(|(key, merged_token)| {
+                merged_to_new_token
+                    .get(&merged_token)
+                    .map(|new_token| (key, *new_token))
+            })
+            .collect();
+        vocab.shrink_to_fit();
+
+        Ok(Self {
+            postings,
+            vocab,
+            point_to_tokens_count,
+            points_count,
+        })
+    }
+}
+
 impl InvertedIndex for ImmutableInvertedIndex {
     fn get_vocab_mut(&mut self) -> &mut HashMap<String, TokenId> {
         &mut self.vocab
@@ -307,9 +549,33 @@ impl InvertedIndex for ImmutableInvertedIndex {
         self.points_count
     }
 
+    fn estimate_resources(&self) -> OperationResult<IndexResources> {
+        Ok(IndexResources::in_memory(
+            self.postings.heap_size()
+                + vocab_heap_size(&self.vocab)
+                + self.point_to_tokens_count.capacity() * size_of::<usize>(),
+        ))
+    }
+
     fn get_token_id(&self, token: &str, _: &HardwareCounterCell) -> Option<TokenId> {
         self.vocab.get(token).copied()
     }
+
+    fn partial_document(
+        &self,
+        tokens: &TokenSet,
+        point_id: PointOffsetType,
+    ) -> Option<PartialDocument> {
+        match &self.postings {
+            ImmutablePostings::WithPositions(postings) => Some(partial_document_from_postings(
+                tokens,
+                point_id,
+                |token_id| postings.get(*token_id as usize).map(PostingList::view),
+            )),
+            // no positional information
+            ImmutablePostings::Ids(_postings) => None,
+        }
+    }
 }
 
 impl From<MutableInvertedIndex> for ImmutableInvertedIndex {
@@ -20,6 +20,20 @@ impl ImmutablePostings {
         }
     }
 
+    /// Size of the heap memory used by postings, in bytes
+    pub fn heap_size(&self) -> usize {
+        match self {
+            ImmutablePostings::Ids(postings) => {
+                postings.capacity() * size_of::<PostingList<()>>()
+                    + postings.iter().map(PostingList::heap_size).sum::<usize>()
+            }
+            ImmutablePostings::WithPositions(postings) => {
+                postings.capacity() * size_of::<PostingList<Positions>>()
+                    + postings.iter().map(PostingList::heap_size).sum::<usize>()
+            }
+        }
+    }
+
     pub fn posting_len(&self, token: TokenId) -> Option<usize> {
         match self {
             ImmutablePostings::Ids(postings) => {
@@ -2,24 +2,39 @@ use std::fmt::Debug;
 use std::io;
 use std::io::Write;
 use std::marker::PhantomData;
+use std::ops::Range;
 use std::path::{Path, PathBuf};
 
+use common::mmap::advice::populate_region;
+use common::mmap::encryption::{DecryptingReader, EncryptingWriter};
 use common::mmap::{Advice, AdviceSetting, Madviseable, open_read_mmap};
 use common::types::PointOffsetType;
 use common::zeros::WriteZerosExt;
 use fs_err::File;
 use memmap2::Mmap;
 use posting_list::{
-    PostingChunk, PostingList, PostingListComponents, PostingListView, PostingValue,
+    CHUNK_LEN, PostingChunk, PostingList, PostingListComponents, PostingListView, PostingValue,
     RemainderPosting, SizedTypeFor, ValueHandler,
 };
+use tempfile::TempPath;
+use zerocopy::little_endian::{U32, U64};
 use zerocopy::{FromBytes, Immutable, IntoBytes, KnownLayout, Unaligned};
 
 use crate::index::field_index::full_text_index::inverted_index::TokenId;
 use crate::index::field_index::full_text_index::inverted_index::positions::Positions;
+use crate::index::field_index::full_text_index::inverted_index::postings_iterator::{
+    SKIP_BLOCK_LEN, SkippablePostingView,
+};
 
 const ALIGNMENT: usize = 4;
 
+/// Version of the file format, which is written by [`MmapPostings::create`].
+///
+/// - `0`: initial format
+/// - `1`: posting lists are prefixed with skip lists
+/// - `2`: fields of [`PostingsHeader`] are little-endian, previously they were native-endian
+const POSTINGS_FORMAT_VERSION: u32 = 2;
+
 /// Trait marker to enrich [`posting_list::PostingValue`] for handling mmap files with the posting list.
 pub(in crate::index::field_index::full_text_index) trait MmapPostingValue:
     PostingValue<
@@ -38,8 +53,10 @@ impl MmapPostingValue for Positions {}
 #[repr(C)]
 struct PostingsHeader {
     /// Number of posting lists. One posting list per term
-    pub posting_count: usize,
-    _reserved: [u8; 32],
+    pub posting_count: U64,
+    /// Format version, files written before versioning have `0` here
+    pub version: U32,
+    _reserved: [u8; 28],
 }
 
 /// This data structure should contain all the necessary information to
@@ -66,8 +83,20 @@ pub(in crate::index::field_index::full_text_index) struct PostingListHeader {
 }
 
 impl PostingListHeader {
+    /// Number of entries in the skip list, one per complete block of [`SKIP_BLOCK_LEN`] elements
+    fn skips_count(&self) -> usize {
+        self.chunks_count as usize * CHUNK_LEN / SKIP_BLOCK_LEN
+    }
+
+    /// Number of elements in the posting list
+    fn posting_len(&self) -> usize {
+        self.chunks_count as usize * CHUNK_LEN + self.remainder_count as usize
+    }
+
+    /// Size of the posting list data in the current format version
     fn posting_size<V: PostingValue>(&self) -> usize {
-        self.ids_data_bytes_count as usize
+        self.skips_count() * size_of::<U32>()
+            + self.ids_data_bytes_count as usize
             + self.var_size_data_bytes_count as usize
             + self.alignment_bytes_count as usize
             + self.remainder_count as usize * size_of::<RemainderPosting<SizedTypeFor<V>>>()
@@ -91,7 +120,7 @@ pub struct MmapPostings<V: MmapPostingValue> {
 
 impl<V: MmapPostingValue> MmapPostings<V> {
     fn get_header(&self, token_id: TokenId) -> Option<&PostingListHeader> {
-        if self.header.posting_count <= token_id as usize {
+        if self.header.posting_count.get() <= u64::from(token_id) {
             return None;
         }
 
@@ -109,17 +138,27 @@ impl<V: MmapPostingValue> MmapPostings<V> {
     ///
     /// ```ignore
     /// last_doc_id: &'a PointOffsetType,
+    /// block_max_ids: &'a [U32], // since format version 1
     /// chunks_index: &'a [PostingChunk<()>],
     /// data: &'a [u8],
     /// var_size_data: &'a [u8], // might be empty in case of only ids
     /// _alignment: &'a [u8], // 0-3 extra bytes to align the data
     /// remainder_postings: &'a [PointOffsetType],
     /// ```
-    fn get_view<'a>(&'a self, header: &'a PostingListHeader) -> Option<PostingListView<'a, V>> {
+    fn get_view<'a>(
+        &'a self,
+        header: &'a PostingListHeader,
+    ) -> Option<SkippablePostingView<'a, V>> {
         let bytes = self.mmap.get(header.offset as usize..)?;
 
         let (last_doc_id, bytes) = PointOffsetType::read_from_prefix(bytes).ok()?;
 
+        let (block_max_ids, bytes) = if self.header.version.get() >= 1 {
+            <[U32]>::ref_from_prefix_with_elems(bytes, header.skips_count()).ok()?
+        } else {
+            (&[][..], bytes)
+        };
+
         let (chunks, bytes) = <[PostingChunk<SizedTypeFor<V>>]>::ref_from_prefix_with_elems(
             bytes,
             header.chunks_count as usize,
@@ -140,16 +179,26 @@ impl<V: MmapPostingValue> MmapPostings<V> {
             )
             .ok()?;
 
-        Some(PostingListView::from_components(
+        let view = PostingListView::from_components(
             id_data,
             chunks,
             var_size_data,
             remainder_postings,
             Some(last_doc_id),
-        ))
+        );
+
+        Some(SkippablePostingView {
+            view,
+            block_max_ids,
+        })
     }
 
     pub fn get<'a>(&'a self, token_id: TokenId) -> Option<PostingListView<'a, V>> {
+        self.get_skippable(token_id).map(|posting| posting.view)
+    }
+
+    /// Same as [`Self::get`], but with the skip list of the posting, if the file has one
+    pub fn get_skippable<'a>(&'a self, token_id: TokenId) -> Option<SkippablePostingView<'a, V>> {
         let header = self.get_header(token_id)?;
         self.get_view(header)
     }
@@ -163,11 +212,13 @@ impl<V: MmapPostingValue> MmapPostings<V> {
             .tempfile_in(path.parent().ok_or(io::ErrorKind::InvalidInput)?)?
             .into_parts();
         let file = File::from_parts::<&Path>(file, temp_path.as_ref());
-        let mut bufw = io::BufWriter::new(&file);
+        // Encrypted while written, if enabled
+        let mut bufw = EncryptingWriter::new(io::BufWriter::new(&file))?;
 
         let postings_header = PostingsHeader {
-            posting_count: compressed_postings.len(),
-            _reserved: [0; 32],
+            posting_count: U64::new(compressed_postings.len() as u64),
+            version: U32::new(POSTINGS_FORMAT_VERSION),
+            _reserved: [0; 28],
         };
 
         // Write the header to the buffer
@@ -178,29 +229,8 @@ impl<V: MmapPostingValue> MmapPostings<V> {
         let mut posting_offset = size_of::<PostingsHeader>() + postings_lists_headers_size;
 
         for compressed_posting in compressed_postings {
-            let view = compressed_posting.view();
-            let PostingListComponents {
-                id_data,
-                chunks,
-                var_size_data,
-                remainders,
-                last_id: _, // not used for the header
-            } = view.components();
-
-            let id_data_len = id_data.len();
-            let var_size_data_len = var_size_data.len();
-            let data_len = id_data_len + var_size_data_len;
-            let alignment_len = data_len.next_multiple_of(ALIGNMENT) - data_len;
-
-            let posting_list_header = PostingListHeader {
-                offset: posting_offset as u64,
-                chunks_count: chunks.len() as u32,
-                ids_data_bytes_count: id_data_len as u32,
-                var_size_data_bytes_count: var_size_data_len as u32,
-                alignment_bytes_count: alignment_len as u8,
-                remainder_count: remainders.len() as u8,
-                _reserved: [0; 2],
-            };
+            let posting_list_header =
+                Self::posting_list_header(&compressed_posting.view(), posting_offset);
 
             // Write the posting list header to the buffer
             bufw.write_all(posting_list_header.as_bytes())?;
@@ -209,56 +239,116 @@ impl<V: MmapPostingValue> MmapPostings<V> {
         }
 
         for compressed_posting in compressed_postings {
-            let view = compressed_posting.view();
-            let PostingListComponents {
-                id_data,
-                chunks,
-                var_size_data, // not used with just ids postings
-                remainders,
-                last_id,
-            } = view.components();
-
-            bufw.write_all(
-                last_id
-                    .expect("posting must have at least one element")
-                    .as_bytes(),
-            )?;
-
-            for chunk in chunks {
-                bufw.write_all(chunk.as_bytes())?;
-            }
+            Self::write_posting_data(&mut bufw, &compressed_posting.view())?;
+        }
 
-            // write all unaligned data together
-            bufw.write_all(id_data)?;
+        // Explicitly flush write buffer so we can catch IO errors
+        bufw.finish()?.flush()?;
 
-            // write var_size_data if it exists
-            if !var_size_data.is_empty() {
-                bufw.write_all(var_size_data)?;
-            }
+        file.sync_all()?;
+        drop(file);
+        temp_path.persist(&path)?;
 
-            // write alignment padding
-            // Example:
-            // For data size = 5, alignment = 3 as (5 + 3 = 8)
-            // alignment = 8 - 5 = 3
-            let data_len = id_data.len() + var_size_data.len();
-            bufw.write_zeros(data_len.next_multiple_of(ALIGNMENT) - data_len)?;
+        Ok(())
+    }
 
-            for element in remainders {
-                bufw.write_all(element.as_bytes())?;
-            }
+    /// Header of the posting list, whose data is written at `offset` of the file
+    fn posting_list_header(view: &PostingListView<V>, offset: usize) -> PostingListHeader {
+        let PostingListComponents {
+            id_data,
+            chunks,
+            var_size_data,
+            remainders,
+            last_id: _, // not used for the header
+        } = view.components();
+
+        let id_data_len = id_data.len();
+        let var_size_data_len = var_size_data.len();
+        let data_len = id_data_len + var_size_data_len;
+        let alignment_len = data_len.next_multiple_of(ALIGNMENT) - data_len;
+
+        PostingListHeader {
+            offset: offset as u64,
+            chunks_count: chunks.len() as u32,
+            ids_data_bytes_count: id_data_len as u32,
+            var_size_data_bytes_count: var_size_data_len as u32,
+            alignment_bytes_count: alignment_len as u8,
+            remainder_count: remainders.len() as u8,
+            _reserved: [0; 2],
         }
+    }
 
-        // Explicitly flush write buffer so we can catch IO errors
-        bufw.flush()?;
-        drop(bufw);
+    /// Write posting list data in the layout expected by [`Self::get_view`]
+    fn write_posting_data(writer: &mut impl Write, view: &PostingListView<V>) -> io::Result<()> {
+        let PostingListComponents {
+            id_data,
+            chunks,
+            var_size_data, // not used with just ids postings
+            remainders,
+            last_id,
+        } = view.components();
+
+        writer.write_all(
+            last_id
+                .expect("posting must have at least one element")
+                .as_bytes(),
+        )?;
+
+        for block_max_id in Self::block_max_ids(view) {
+            writer.write_all(U32::new(block_max_id).as_bytes())?;
+        }
 
-        file.sync_all()?;
-        drop(file);
-        temp_path.persist(path)?;
+        for chunk in chunks {
+            writer.write_all(chunk.as_bytes())?;
+        }
+
+        // write all unaligned data together
+        writer.write_all(id_data)?;
+
+        // write var_size_data if it exists
+        if !var_size_data.is_empty() {
+            writer.write_all(var_size_data)?;
+        }
+
+        // write alignment padding
+        // Example:
+        // For data size = 5, alignment = 3 as (5 + 3 = 8)
+        // alignment = 8 - 5 = 3
+        let data_len = id_data.len() + var_size_data.len();
+        writer.write_zeros(data_len.next_multiple_of(ALIGNMENT) - data_len)?;
+
+        for element in remainders {
+            writer.write_all(element.as_bytes())?;
+        }
 
         Ok(())
     }
 
+    /// Max id of each complete block of [`SKIP_BLOCK_LEN`] elements
+    fn block_max_ids<'a>(
+        view: &'a PostingListView<V>,
+    ) -> impl Iterator<Item = PointOffsetType> + 'a {
+        let PostingListComponents {
+            id_data,
+            chunks,
+            var_size_data,
+            remainders,
+            last_id,
+        } = view.components();
+
+        PostingListView::<V>::from_components(
+            id_data,
+            chunks,
+            var_size_data,
+            remainders,
+            last_id.map(U32::get),
+        )
+        .into_iter()
+        .skip(SKIP_BLOCK_LEN - 1)
+        .step_by(SKIP_BLOCK_LEN)
+        .map(|elem| elem.id)
+    }
+
     pub fn open(path: impl Into<PathBuf>, populate: bool) -> io::Result<Self> {
         let path = path.into();
         let mmap = open_read_mmap(&path, AdviceSetting::Advice(Advice::Normal), populate)?;
@@ -270,6 +360,17 @@ impl<V: MmapPostingValue> MmapPostings<V> {
             )
         })?;
 
+        if header.version.get() > POSTINGS_FORMAT_VERSION {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "Unsupported postings format version {} in {}",
+                    header.version,
+                    path.display(),
+                ),
+            ));
+        }
+
         Ok(Self {
             _path: path,
             mmap,
@@ -284,10 +385,197 @@ impl<V: MmapPostingValue> MmapPostings<V> {
         self.mmap.populate();
     }
 
+    /// Populate pages of the posting list headers and of `count` longest posting lists.
+    /// Block until all pages are populated.
+    pub fn populate_longest(&self, count: usize) {
+        let headers_end = size_of::<PostingsHeader>()
+            + self.header.posting_count.get() as usize * size_of::<PostingListHeader>();
+        populate_region(&self.mmap[..headers_end.min(self.mmap.len())]);
+
+        let mut lengths: Vec<(usize, TokenId)> = (0..self.header.posting_count.get() as TokenId)
+            .filter_map(|token_id| Some((self.get_header(token_id)?.posting_len(), token_id)))
+            .collect();
+        if count < lengths.len() {
+            lengths.select_nth_unstable_by(count, |a, b| b.cmp(a));
+            lengths.truncate(count);
+        }
+
+        for (_, token_id) in lengths {
+            if let Some(range) = self.posting_range(token_id) {
+                populate_region(&self.mmap[range]);
+            }
+        }
+    }
+
+    /// Range of the posting list data in the file
+    fn posting_range(&self, token_id: TokenId) -> Option<Range<usize>> {
+        let start = self.get_header(token_id)?.offset as usize;
+        // Posting lists are written in the order of tokens, next one starts where this one ends
+        let end = self
+            .get_header(token_id + 1)
+            .map_or(self.mmap.len(), |header| header.offset as usize);
+        (start <= end && end <= self.mmap.len()).then_some(start..end)
+    }
+
     /// Iterate over posting lists, returning a view for each
     pub fn iter_postings<'a>(&'a self) -> impl Iterator<Item = PostingListView<'a, V>> {
-        (0..self.header.posting_count as u32)
+        (0..self.header.posting_count.get() as u32)
             // we are iterating over existing posting lists, all of them should return `Some`
             .filter_map(|posting_idx| self.get(posting_idx))
     }
 }
+
+/// Writes [`MmapPostings`] file from posting lists, which are produced one at a time.
+///
+/// Posting lists data is written into a temporary file next to the target, while their headers are
+/// collected in memory. The final file is assembled by [`MmapPostingsWriter::finish`]. Both files
+/// are encrypted while written, if enabled.
+pub struct MmapPostingsWriter<V: MmapPostingValue> {
+    path: PathBuf,
+    data: EncryptingWriter<io::BufWriter<File>>,
+    data_path: TempPath,
+    /// Headers with offsets relative to the start of the data
+    headers: Vec<PostingListHeader>,
+    data_len: usize,
+    _value_type: PhantomData<V>,
+}
+
+impl<V: MmapPostingValue> MmapPostingsWriter<V> {
+    pub fn new(path: PathBuf) -> io::Result<Self> {
+        let (file, data_path) = tempfile::Builder::new()
+            .prefix(path.file_name().ok_or(io::ErrorKind::InvalidInput)?)
+            .suffix(".data")
+            .tempfile_in(path.parent().ok_or(io::ErrorKind::InvalidInput)?)?
+            .into_parts();
+        let file = File::from_parts::<&Path>(file, data_path.as_ref());
+
+        Ok(Self {
+            path,
+            data: EncryptingWriter::new(io::BufWriter::new(file))?,
+            data_path,
+            headers: Vec::new(),
+            data_len: 0,
+            _value_type: PhantomData,
+        })
+    }
+
+    /// Append posting list of the next token
+    pub fn push(&mut self, posting: &PostingList<V>) -> io::Result<()> {
+        let view = posting.view();
+        let header = MmapPostings::<V>::posting_list_header(&view, self.data_len);
+        MmapPostings::<V>::write_posting_data(&mut self.data, &view)?;
+        self.data_len += header.posting_size::<V>();
+        self.headers.push(header);
+        Ok(())
+    }
+
+    pub fn finish(self) -> io::Result<()> {
+        let Self {
+            path,
+            data,
+            data_path,
+            headers,
+            data_len: _,
+            _value_type,
+        } = self;
+
+        let data = data
+            .finish()?
+            .into_inner()
+            .map_err(io::IntoInnerError::into_error)?;
+        drop(data);
+
+        let (file, temp_path) = tempfile::Builder::new()
+            .prefix(path.file_name().ok_or(io::ErrorKind::InvalidInput)?)
+            .tempfile_in(path.parent().ok_or(io::ErrorKind::InvalidInput)?)?
+            .into_parts();
+        let file = File::from_parts::<&Path>(file, temp_path.as_ref());
+        let mut bufw = EncryptingWriter::new(io::BufWriter::new(&file))?;
+
+        let postings_header = PostingsHeader {
+            posting_count: U64::new(headers.len() as u64),
+            version: U32::new(POSTINGS_FORMAT_VERSION),
+            _reserved: [0; 28],
+        };
+        bufw.write_all(postings_header.as_bytes())?;
+
+        let data_offset =
+            size_of::<PostingsHeader>() + headers.len() * size_of::<PostingListHeader>();
+        for mut header in headers {
+            header.offset += data_offset as u64;
+            bufw.write_all(header.as_bytes())?;
+        }
+
+        io::copy(&mut DecryptingReader::open(&data_path)?, &mut bufw)?;
+
+        // Explicitly flush write buffer so we can catch IO errors
+        bufw.finish()?.flush()?;
+
+        file.sync_all()?;
+        drop(file);
+        temp_path.persist(path)?;
+        data_path.close()?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use posting_list::IdsPostingList;
+
+    use super::*;
+
+    #[test]
+    fn test_posting_ranges() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("postings.dat");
+
+        let postings: Vec<IdsPostingList> = [1, 10_000, 300, 5_000, 129]
+            .into_iter()
+            .map(|len| (0..len).map(|id| (id, ())).collect())
+            .collect();
+        MmapPostings::create(path.clone(), &postings).unwrap();
+        let mmap_postings = MmapPostings::<()>::open(&path, false).unwrap();
+
+        // Ranges are adjacent and cover all data of posting lists
+        let ranges = (0..postings.len() as TokenId)
+            .map(|token_id| mmap_postings.posting_range(token_id).unwrap())
+            .collect::<Vec<_>>();
+        for (range, next_range) in ranges.iter().zip(&ranges[1..]) {
+            assert_eq!(range.end, next_range.start);
+        }
+        assert_eq!(ranges.last().unwrap().end, mmap_postings.mmap.len());
+        for (token_id, range) in ranges.iter().enumerate() {
+            let header = mmap_postings.get_header(token_id as TokenId).unwrap();
+            assert_eq!(range.len(), header.posting_size::<()>());
+        }
+        assert!(
+            mmap_postings
+                .posting_range(postings.len() as TokenId)
+                .is_none()
+        );
+
+        mmap_postings.populate_longest(2);
+        mmap_postings.populate_longest(postings.len() + 1);
+    }
+
+    #[test]
+    fn test_postings_header_is_little_endian() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("postings.dat");
+
+        let postings: Vec<IdsPostingList> = [3, 200]
+            .into_iter()
+            .map(|len| (0..len).map(|id| (id, ())).collect())
+            .collect();
+        MmapPostings::create(path.clone(), &postings).unwrap();
+
+        let bytes = fs_err::read(&path).unwrap();
+        assert_eq!(bytes[..8], 2u64.to_le_bytes());
+        assert_eq!(bytes[8..12], POSTINGS_FORMAT_VERSION.to_le_bytes());
+
+        let mmap_postings = MmapPostings::<()>::open(&path, false).unwrap();
+        assert_eq!(mmap_postings.iter_postings().count(), 2);
+    }
+}
@@ -18,6 +18,13 @@ impl MmapPostingsEnum {
         }
     }
 
+    pub fn populate_longest(&self, count: usize) {
+        match self {
+            MmapPostingsEnum::Ids(postings) => postings.populate_longest(count),
+            MmapPostingsEnum::WithPositions(postings) => postings.populate_longest(count),
+        }
+    }
+
     pub fn posting_len(&self, token_id: TokenId) -> Option<usize> {
         match self {
             MmapPostingsEnum::Ids(postings) => postings.get(token_id).map(|view| view.len()),
@@ -1,31 +1,35 @@
 use std::collections::HashMap;
 use std::io::Write as _;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use bitvec::vec::BitVec;
 use common::counter::hardware_counter::HardwareCounterCell;
-use common::fs::{atomic_save, clear_disk_cache};
+use common::fs::{atomic_save, atomic_save_json, clear_disk_cache, read_json};
 use common::mmap;
 use common::mmap::{
     Advice, AdviceSetting, Madviseable as _, MmapBitSlice, MmapSlice, create_and_ensure_length,
     open_write_mmap,
 };
-use common::mmap_hashmap::{MmapHashMap, READ_ENTRY_OVERHEAD};
+use common::mmap_hashmap::{MmapHashMap, MmapSingleValueHashMap, READ_SINGLE_VALUE_ENTRY_OVERHEAD};
 use common::types::PointOffsetType;
 use itertools::Either;
 use mmap_postings::{MmapPostingValue, MmapPostings};
+use posting_list::SizedTypeFor;
+use serde::{Deserialize, Serialize};
 
 use super::immutable_inverted_index::ImmutableInvertedIndex;
 use super::immutable_postings_enum::ImmutablePostings;
 use super::mmap_inverted_index::mmap_postings_enum::MmapPostingsEnum;
-use super::positions::Positions;
+use super::positions::{PartialDocument, Positions};
 use super::postings_iterator::{
-    intersect_compressed_postings_iterator, merge_compressed_postings_iterator,
+    intersect_compressed_postings, merge_compressed_postings_iterator,
+    partial_document_from_postings,
 };
 use super::{InvertedIndex, ParsedQuery, TokenId, TokenSet};
 use crate::common::Flusher;
 use crate::common::mmap_bitslice_buffered_update_wrapper::MmapBitSliceBufferedUpdateWrapper;
 use crate::common::operation_error::{OperationError, OperationResult};
+use crate::index::field_index::IndexResources;
 use crate::index::field_index::full_text_index::inverted_index::Document;
 use crate::index::field_index::full_text_index::inverted_index::postings_iterator::{
     check_compressed_postings_phrase, intersect_compressed_postings_phrase_iterator,
@@ -33,11 +37,13 @@ use crate::index::field_index::full_text_index::inverted_index::postings_iterato
 
 pub(super) mod mmap_postings;
 pub mod mmap_postings_enum;
+pub mod streaming_builder;
 
 const POSTINGS_FILE: &str = "postings.dat";
 const VOCAB_FILE: &str = "vocab.dat";
 const POINT_TO_TOKENS_COUNT_FILE: &str = "point_to_tokens_count.dat";
 const DELETED_POINTS_FILE: &str = "deleted_points.dat";
+const STATS_FILE: &str = "stats.json";
 
 const POINT_TO_TOKENS_COUNT_MAGIC: &[u8; 4] = b"pttc";
 const POINT_TO_TOKENS_COUNT_VERSION: u32 = 1;
@@ -344,17 +350,26 @@ impl PointToTokensCount {
     }
 }
 
+/// Statistics of the index at the time it was built.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+struct InvertedIndexStats {
+    /// Number of points present in postings
+    indexed_points_count: usize,
+}
+
 pub struct MmapInvertedIndex {
     pub(in crate::index::field_index::full_text_index) path: PathBuf,
     pub(in crate::index::field_index::full_text_index) storage: Storage,
     /// Number of points which are not deleted
     pub(in crate::index::field_index::full_text_index) active_points_count: usize,
+    /// Number of points present in postings, including ones deleted afterwards
+    indexed_points_count: usize,
     is_on_disk: bool,
 }
 
 pub(in crate::index::field_index::full_text_index) struct Storage {
     pub(in crate::index::field_index::full_text_index) postings: MmapPostingsEnum,
-    pub(in crate::index::field_index::full_text_index) vocab: MmapHashMap<str, TokenId>,
+    pub(in crate::index::field_index::full_text_index) vocab: MmapSingleValueHashMap<str, TokenId>,
     pub(in crate::index::field_index::full_text_index) point_to_tokens_count: PointToTokensCount,
     pub(in crate::index::field_index::full_text_index) deleted_points:
         MmapBitSliceBufferedUpdateWrapper,
@@ -366,15 +381,12 @@ impl MmapInvertedIndex {
             postings,
             vocab,
             point_to_tokens_count,
-            points_count: _,
+            points_count,
         } = inverted_index;
 
         debug_assert_eq!(vocab.len(), postings.len());
 
         let postings_path = path.join(POSTINGS_FILE);
-        let vocab_path = path.join(VOCAB_FILE);
-        let point_to_tokens_count_path = path.join(POINT_TO_TOKENS_COUNT_FILE);
-        let deleted_points_path = path.join(DELETED_POINTS_FILE);
 
         match postings {
             ImmutablePostings::Ids(postings) => MmapPostings::create(postings_path, postings)?,
@@ -383,11 +395,23 @@ impl MmapInvertedIndex {
             }
         }
 
-        // Currently MmapHashMap maps str -> [u32], but we only need to map str -> u32.
-        // TODO: Consider making another mmap structure for this case.
-        MmapHashMap::<str, TokenId>::create(
+        Self::create_metadata(&path, vocab, point_to_tokens_count, *points_count)
+    }
+
+    /// Write all files of the index, except for postings
+    fn create_metadata(
+        path: &Path,
+        vocab: &HashMap<String, TokenId>,
+        point_to_tokens_count: &[usize],
+        points_count: usize,
+    ) -> OperationResult<()> {
+        let vocab_path = path.join(VOCAB_FILE);
+        let point_to_tokens_count_path = path.join(POINT_TO_TOKENS_COUNT_FILE);
+        let deleted_points_path = path.join(DELETED_POINTS_FILE);
+
+        MmapSingleValueHashMap::<str, TokenId>::create(
             &vocab_path,
-            vocab.iter().map(|(k, v)| (k.as_str(), std::iter::once(*v))),
+            vocab.iter().map(|(k, v)| (k.as_str(), *v)),
         )?;
 
         // Save point_to_tokens_count, separated into a bitslice for None values and a slice for actual values
@@ -403,6 +427,11 @@ impl MmapInvertedIndex {
         let point_to_tokens_count_iter = point_to_tokens_count.iter().copied();
         PointToTokensCount::create(&point_to_tokens_count_path, point_to_tokens_count_iter)?;
 
+        let stats = InvertedIndexStats {
+            indexed_points_count: points_count,
+        };
+        atomic_save_json(&path.join(STATS_FILE), &stats)?;
+
         Ok(())
     }
 
@@ -428,7 +457,10 @@ impl MmapInvertedIndex {
                 populate,
             )?),
         };
-        let vocab = MmapHashMap::<str, TokenId>::open(&vocab_path, false)?;
+        if !MmapSingleValueHashMap::<str, TokenId>::is_single_value_file(&vocab_path)? {
+            Self::migrate_legacy_vocab(&vocab_path)?;
+        }
+        let vocab = MmapSingleValueHashMap::<str, TokenId>::open(&vocab_path, false)?;
 
         let point_to_tokens_count = PointToTokensCount::open(&point_to_tokens_count_path, populate)?;
 
@@ -439,6 +471,14 @@ impl MmapInvertedIndex {
         let deleted_points = MmapBitSliceBufferedUpdateWrapper::new(deleted);
         let points_count = point_to_tokens_count.len() - num_deleted_points;
 
+        // Indexes built before stats were persisted may have deleted points in postings
+        let stats_path = path.join(STATS_FILE);
+        let indexed_points_count = if stats_path.is_file() {
+            read_json::<InvertedIndexStats>(&stats_path)?.indexed_points_count
+        } else {
+            point_to_tokens_count.len()
+        };
+
         Ok(Some(Self {
             path,
             storage: Storage {
@@ -448,16 +488,50 @@ impl MmapInvertedIndex {
                 deleted_points,
             },
             active_points_count: points_count,
+            indexed_points_count,
             is_on_disk: !populate,
         }))
     }
 
-    pub(super) fn iter_vocab(&self) -> impl Iterator<Item = (&str, TokenId)> + '_ {
-        // unwrap safety: we know that each token points to a token id.
-        self.storage
-            .vocab
+    /// Rewrite vocabulary stored in legacy str -> [u32] format into single value format.
+    fn migrate_legacy_vocab(vocab_path: &Path) -> OperationResult<()> {
+        let legacy = MmapHashMap::<str, TokenId>::open(vocab_path, false)?;
+        let vocab: Vec<(Box<str>, TokenId)> = legacy
             .iter_stored()
-            .filter_map(|(k, v)| v.iter_native().next().map(|token_id| (k, token_id)))
+            .filter_map(|(token, ids)| ids.iter_native().next().map(|id| (token.into(), id)))
+            .collect();
+        // Release the mmap before replacing the file
+        drop(legacy);
+
+        MmapSingleValueHashMap::<str, TokenId>::create(
+            vocab_path,
+            vocab.iter().map(|(token, id)| (token.as_ref(), *id)),
+        )?;
+        Ok(())
+    }
+
+    pub(in crate::index::field_index::full_text_index) fn iter_vocab(
+        &self,
+    ) -> impl Iterator<Item = (&str, TokenId)> + '_ {
+        self.storage.vocab.iter()
+    }
+
+    /// Fraction of points in postings which are deleted.
+    ///
+    /// Deleted points are only filtered out at query time, use this to decide when postings
+    /// should be rebuilt without them.
+    pub fn deleted_ratio(&self) -> f64 {
+        if self.indexed_points_count == 0 {
+            return 0.0;
+        }
+        let deleted_count = self
+            .indexed_points_count
+            .saturating_sub(self.active_points_count);
+        deleted_count as f64 / self.indexed_points_count as f64
+    }
+
+    pub fn has_positions(&self) -> bool {
+        matches!(self.storage.postings, MmapPostingsEnum::WithPositions(_))
     }
 
     /// Returns whether the point id is valid and active.
@@ -478,15 +552,18 @@ impl MmapInvertedIndex {
         // in case of mmap immutable index, deleted points are still in the postings
         let filter = move |idx| self.is_active(idx);
 
-        fn intersection<'a, V: MmapPostingValue>(
+        fn intersection<'a, V: MmapPostingValue + Sync>(
             postings: &'a MmapPostings<V>,
             tokens: TokenSet,
-            filter: impl Fn(u32) -> bool + 'a,
-        ) -> Box<dyn Iterator<Item = PointOffsetType> + 'a> {
+            filter: impl Fn(u32) -> bool + Sync + 'a,
+        ) -> Box<dyn Iterator<Item = PointOffsetType> + 'a>
+        where
+            SizedTypeFor<V>: Sync,
+        {
             let postings_opt: Option<Vec<_>> = tokens
                 .tokens()
                 .iter()
-                .map(|&token_id| postings.get(token_id))
+                .map(|&token_id| postings.get_skippable(token_id))
                 .collect();
 
             let Some(posting_readers) = postings_opt else {
@@ -499,10 +576,7 @@ impl MmapInvertedIndex {
                 return Box::new(std::iter::empty());
             }
 
-            Box::new(intersect_compressed_postings_iterator(
-                posting_readers,
-                filter,
-            ))
+            Box::new(intersect_compressed_postings(posting_readers, filter))
         }
 
         match &self.storage.postings {
@@ -648,20 +722,29 @@ impl MmapInvertedIndex {
     }
 
     pub fn files(&self) -> Vec<PathBuf> {
-        vec![
+        let mut files = vec![
             self.path.join(POSTINGS_FILE),
             self.path.join(VOCAB_FILE),
             self.path.join(POINT_TO_TOKENS_COUNT_FILE),
             self.path.join(DELETED_POINTS_FILE),
-        ]
+        ];
+        files.extend(self.stats_file());
+        files
     }
 
     pub fn immutable_files(&self) -> Vec<PathBuf> {
-        vec![
+        let mut files = vec![
             self.path.join(POSTINGS_FILE),
             self.path.join(VOCAB_FILE),
             self.path.join(POINT_TO_TOKENS_COUNT_FILE),
-        ]
+        ];
+        files.extend(self.stats_file());
+        files
+    }
+
+    fn stats_file(&self) -> Option<PathBuf> {
+        let path = self.path.join(STATS_FILE);
+        path.is_file().then_some(path)
     }
 
     pub fn flusher(&self) -> Flusher {
@@ -681,6 +764,21 @@ impl MmapInvertedIndex {
         Ok(())
     }
 
+    /// Populate vocabulary, tokens count of points and only `postings_count` longest postings,
+    /// which are the most likely to be read by queries.
+    pub fn populate_selective(&self, postings_count: usize) -> OperationResult<()> {
+        self.storage.postings.populate_longest(postings_count);
+        self.storage.vocab.populate()?;
+        self.storage.point_to_tokens_count.populate()?;
+        Ok(())
+    }
+
+    /// Lock vocabulary in RAM, so that looking up tokens of a query never reads from disk.
+    pub fn lock_vocab_in_ram(&self) -> OperationResult<()> {
+        self.storage.vocab.lock_in_ram()?;
+        Ok(())
+    }
+
     /// Drop disk cache.
     pub fn clear_cache(&self) -> OperationResult<()> {
         let files = self.files();
@@ -817,20 +915,35 @@ impl InvertedIndex for MmapInvertedIndex {
         self.active_points_count
     }
 
+    /// All files are mapped into memory, and expected to be resident once populated
+    fn estimate_resources(&self) -> OperationResult<IndexResources> {
+        IndexResources::from_files(self.files())
+    }
+
     fn get_token_id(&self, token: &str, hw_counter: &HardwareCounterCell) -> Option<TokenId> {
         if self.is_on_disk {
             hw_counter.payload_index_io_read_counter().incr_delta(
-                READ_ENTRY_OVERHEAD + size_of::<TokenId>(), // Avoid check overhead and assume token is always read
+                READ_SINGLE_VALUE_ENTRY_OVERHEAD + size_of::<TokenId>(), // Avoid check overhead and assume token is always read
             );
         }
 
-        self.storage
-            .vocab
-            .get_stored(token)
-            .ok()
-            .flatten()
-            .and_then(|v| v.as_stored().first().copied())
-            .map(TokenId::from_le)
+        self.storage.vocab.get(token).ok().flatten()
+    }
+
+    fn partial_document(
+        &self,
+        tokens: &TokenSet,
+        point_id: PointOffsetType,
+    ) -> Option<PartialDocument> {
+        match &self.storage.postings {
+            MmapPostingsEnum::WithPositions(postings) => Some(partial_document_from_postings(
+                tokens,
+                point_id,
+                |token_id| postings.get(*token_id),
+            )),
+            // no positional information
+            MmapPostingsEnum::Ids(_postings) => None,
+        }
     }
 }
 
@@ -838,7 +951,141 @@ impl InvertedIndex for MmapInvertedIndex {
 mod tests {
     use std::io::Write as _;
 
-    use super::{LegacyEndian, PointToTokensCount, POINT_TO_TOKENS_COUNT_HEADER_SIZE};
+    use common::mmap_hashmap::{MmapHashMap, MmapSingleValueHashMap};
+    use common::types::PointOffsetType;
+    use posting_list::IdsPostingList;
+
+    use super::mmap_postings::MmapPostings;
+    use super::{
+        LegacyEndian, MmapInvertedIndex, POINT_TO_TOKENS_COUNT_HEADER_SIZE, PointToTokensCount,
+        TokenId,
+    };
+    use crate::index::field_index::full_text_index::inverted_index::postings_iterator::{
+        SKIP_BLOCK_LEN, SkippablePostingView, intersect_compressed_postings_iterator,
+        intersect_compressed_postings_parallel,
+    };
+
+    #[test]
+    fn test_skip_lists_intersection() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("postings.dat");
+
+        let ids: [Vec<PointOffsetType>; 3] = [
+            (0..100_000).step_by(3).collect(),
+            (0..100_000).step_by(7).collect(),
+            // Short posting without complete blocks
+            (50_000..50_500).collect(),
+        ];
+        let postings: Vec<IdsPostingList> = ids
+            .iter()
+            .map(|ids| ids.iter().map(|id| (*id, ())).collect())
+            .collect();
+        MmapPostings::create(path.clone(), &postings).unwrap();
+        let mmap_postings = MmapPostings::<()>::open(&path, false).unwrap();
+
+        for (token_id, ids) in ids.iter().enumerate() {
+            let posting = mmap_postings.get_skippable(token_id as TokenId).unwrap();
+            let expected_block_max_ids = ids
+                .chunks_exact(SKIP_BLOCK_LEN)
+                .map(|block| *block.last().unwrap())
+                .collect::<Vec<_>>();
+            let block_max_ids = posting
+                .block_max_ids
+                .iter()
+                .map(|id| id.get())
+                .collect::<Vec<_>>();
+            assert_eq!(block_max_ids, expected_block_max_ids);
+        }
+
+        for tokens in [[0, 1], [1, 0], [0, 2], [2, 1]] {
+            let expected = ids[tokens[0]]
+                .iter()
+                .copied()
+                .filter(|id| ids[tokens[1]].binary_search(id).is_ok())
+                .collect::<Vec<_>>();
+
+            let skippable = tokens
+                .iter()
+                .map(|&token_id| mmap_postings.get_skippable(token_id as TokenId).unwrap())
+                .collect();
+            let intersection =
+                intersect_compressed_postings_iterator(skippable, |_| true).collect::<Vec<_>>();
+            assert_eq!(intersection, expected);
+        }
+    }
+
+    #[test]
+    fn test_parallel_intersection() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("postings.dat");
+
+        let ids: [Vec<PointOffsetType>; 3] = [
+            (0..200_000).step_by(2).collect(),
+            (0..200_000).step_by(3).collect(),
+            (0..200_000).step_by(5).collect(),
+        ];
+        let postings: Vec<IdsPostingList> = ids
+            .iter()
+            .map(|ids| ids.iter().map(|id| (*id, ())).collect())
+            .collect();
+        MmapPostings::create(path.clone(), &postings).unwrap();
+        let mmap_postings = MmapPostings::<()>::open(&path, false).unwrap();
+
+        let is_active = |id: PointOffsetType| !id.is_multiple_of(11);
+
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(4)
+            .build()
+            .unwrap();
+
+        for tokens in [vec![0, 1], vec![2, 1, 0]] {
+            // With skip lists
+            let skippable: Vec<_> = tokens
+                .iter()
+                .map(|&token_id| mmap_postings.get_skippable(token_id as TokenId).unwrap())
+                .collect();
+            // Without skip lists
+            let in_memory: Vec<_> = tokens
+                .iter()
+                .map(|&token_id| SkippablePostingView::from(postings[token_id].view()))
+                .collect();
+
+            let expected = intersect_compressed_postings_iterator(skippable.clone(), is_active)
+                .collect::<Vec<_>>();
+            assert!(!expected.is_empty());
+
+            for views in [skippable, in_memory] {
+                let parallel =
+                    pool.install(|| intersect_compressed_postings_parallel(views, &is_active));
+                assert_eq!(parallel, expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_legacy_vocab_migrates_to_single_value_map() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("vocab.dat");
+
+        let vocab: Vec<(&str, TokenId)> = vec![("hello", 0), ("world", 1), ("ünïcode", 2)];
+        MmapHashMap::<str, TokenId>::create(
+            &path,
+            vocab
+                .iter()
+                .map(|(token, id)| (*token, std::iter::once(*id))),
+        )
+        .unwrap();
+        assert!(!MmapSingleValueHashMap::<str, TokenId>::is_single_value_file(&path).unwrap());
+
+        MmapInvertedIndex::migrate_legacy_vocab(&path).unwrap();
+
+        assert!(MmapSingleValueHashMap::<str, TokenId>::is_single_value_file(&path).unwrap());
+        let migrated = MmapSingleValueHashMap::<str, TokenId>::open(&path, false).unwrap();
+        assert_eq!(migrated.keys_count(), vocab.len());
+        for (token, id) in vocab {
+            assert_eq!(migrated.get(token).unwrap(), Some(id));
+        }
+    }
 
     #[test]
     fn test_point_to_tokens_count_endian_migrates_legacy_le_and_be() {
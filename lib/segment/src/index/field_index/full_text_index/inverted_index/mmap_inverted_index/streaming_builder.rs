@@ -0,0 +1,333 @@
+use std::collections::HashMap;
+use std::io::{self, BufWriter, Read, Write};
+use std::path::PathBuf;
+
+use common::mmap::encryption::{DecryptingReader, EncryptingWriter};
+use common::types::PointOffsetType;
+use fs_err as fs;
+use fs_err::File;
+use itertools::Itertools;
+use posting_list::{PostingBuilder, PostingList};
+use tempfile::TempDir;
+use zerocopy::IntoBytes;
+
+use super::mmap_postings::{MmapPostingValue, MmapPostingsWriter};
+use super::{MmapInvertedIndex, POSTINGS_FILE};
+use crate::common::operation_error::OperationResult;
+use crate::index::field_index::full_text_index::inverted_index::TokenId;
+use crate::index::field_index::full_text_index::inverted_index::positions::Positions;
+
+/// Default memory limit for postings buffered by [`StreamingInvertedIndexBuilder`]
+pub const DEFAULT_BUFFER_BYTES: usize = 128 * 1024 * 1024;
+
+/// Marks points, which don't have entries in any run
+const NO_RUN: u32 = u32::MAX;
+
+type RunEntries = Box<dyn Iterator<Item = io::Result<PostingEntry>>>;
+
+/// Occurrence of a token in a point document
+struct PostingEntry {
+    token: TokenId,
+    point_id: PointOffsetType,
+    /// Positions of the token in the document, empty if phrase matching is disabled
+    positions: Vec<u32>,
+}
+
+impl PostingEntry {
+    fn memory_size(&self) -> usize {
+        size_of::<Self>() + self.positions.len() * size_of::<u32>()
+    }
+
+    fn write(&self, writer: &mut impl Write) -> io::Result<()> {
+        writer.write_all(self.token.as_bytes())?;
+        writer.write_all(self.point_id.as_bytes())?;
+        writer.write_all((self.positions.len() as u32).as_bytes())?;
+        writer.write_all(self.positions.as_bytes())
+    }
+
+    /// Read next entry, `None` at the end of the run
+    fn read(reader: &mut impl Read) -> io::Result<Option<Self>> {
+        let mut header = [0u32; 3];
+        match reader.read_exact(header.as_mut_bytes()) {
+            Ok(()) => {}
+            Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+            Err(err) => return Err(err),
+        }
+        let [token, point_id, positions_count] = header;
+
+        let mut positions = vec![0u32; positions_count as usize];
+        reader.read_exact(positions.as_mut_bytes())?;
+
+        Ok(Some(Self {
+            token,
+            point_id,
+            positions,
+        }))
+    }
+}
+
+/// Builds [`MmapInvertedIndex`] files without holding all postings in memory.
+///
+/// Token occurrences are buffered, and once the buffer exceeds the memory limit, it is sorted and
+/// spilled into a run file in a temporary directory. Runs are merged in a single pass when
+/// postings are written. Vocabulary and tokens count of each point are kept in memory.
+pub struct StreamingInvertedIndexBuilder {
+    path: PathBuf,
+    with_positions: bool,
+    vocab: HashMap<String, TokenId>,
+    point_to_tokens_count: Vec<usize>,
+    points_count: usize,
+    /// Run holding the current entries of each point. Entries of re-indexed points in older runs
+    /// are skipped on merge.
+    point_run: Vec<u32>,
+    buffer: Vec<PostingEntry>,
+    buffer_bytes: usize,
+    max_buffer_bytes: usize,
+    runs_dir: Option<TempDir>,
+    runs: Vec<PathBuf>,
+}
+
+impl StreamingInvertedIndexBuilder {
+    pub fn new(path: PathBuf, with_positions: bool, max_buffer_bytes: usize) -> Self {
+        Self {
+            path,
+            with_positions,
+            vocab: HashMap::new(),
+            point_to_tokens_count: Vec::new(),
+            points_count: 0,
+            point_run: Vec::new(),
+            buffer: Vec::new(),
+            buffer_bytes: 0,
+            max_buffer_bytes,
+            runs_dir: None,
+            runs: Vec::new(),
+        }
+    }
+
+    /// Translate string tokens into token ids, registering unseen tokens in the vocabulary.
+    ///
+    /// The order of the tokens is preserved.
+    pub fn register_tokens(&mut self, str_tokens: &[impl AsRef<str>]) -> Vec<TokenId> {
+        str_tokens
+            .iter()
+            .map(|token| match self.vocab.get(token.as_ref()) {
+                Some(&token_id) => token_id,
+                None => {
+                    let token_id = self.vocab.len() as TokenId;
+                    self.vocab.insert(token.as_ref().to_string(), token_id);
+                    token_id
+                }
+            })
+            .collect()
+    }
+
+    /// Index document of the point, given as ordered token ids
+    pub fn add_document(
+        &mut self,
+        point_id: PointOffsetType,
+        document: &[TokenId],
+    ) -> OperationResult<()> {
+        self.remove(point_id);
+
+        let mut token_positions: HashMap<TokenId, Vec<u32>> = HashMap::new();
+        for (position, token) in (0u32..).zip(document) {
+            let positions = token_positions.entry(*token).or_default();
+            if self.with_positions {
+                positions.push(position);
+            }
+        }
+
+        let idx = point_id as usize;
+        if self.point_to_tokens_count.len() <= idx {
+            self.point_to_tokens_count.resize(idx + 1, 0);
+            self.point_run.resize(idx + 1, NO_RUN);
+        }
+        self.point_to_tokens_count[idx] = token_positions.len();
+        self.point_run[idx] = self.runs.len() as u32;
+        self.points_count += 1;
+
+        for (token, positions) in token_positions {
+            let entry = PostingEntry {
+                token,
+                point_id,
+                positions,
+            };
+            self.buffer_bytes += entry.memory_size();
+            self.buffer.push(entry);
+        }
+
+        if self.buffer_bytes > self.max_buffer_bytes {
+            self.spill()?;
+        }
+
+        Ok(())
+    }
+
+    pub fn remove(&mut self, point_id: PointOffsetType) {
+        let idx = point_id as usize;
+        let Some(run) = self.point_run.get(idx).copied() else {
+            return;
+        };
+        if run == NO_RUN {
+            return;
+        }
+
+        // Entries in spilled runs are skipped on merge, but buffered ones must be dropped, as the
+        // point may be indexed again into the same buffer
+        if run == self.runs.len() as u32 {
+            self.buffer.retain(|entry| entry.point_id != point_id);
+            self.buffer_bytes = self.buffer.iter().map(PostingEntry::memory_size).sum();
+        }
+
+        self.point_run[idx] = NO_RUN;
+        self.point_to_tokens_count[idx] = 0;
+        self.points_count -= 1;
+    }
+
+    /// Sort buffered entries and write them into a new run
+    fn spill(&mut self) -> OperationResult<()> {
+        let runs_dir = match &self.runs_dir {
+            Some(runs_dir) => runs_dir,
+            None => {
+                fs::create_dir_all(&self.path)?;
+                let runs_dir = tempfile::Builder::new()
+                    .prefix(".postings_runs")
+                    .tempdir_in(&self.path)?;
+                self.runs_dir.insert(runs_dir)
+            }
+        };
+
+        let run_path = runs_dir.path().join(format!("run_{}", self.runs.len()));
+        // Runs hold postings, encrypt them like the postings file
+        let mut writer = EncryptingWriter::new(BufWriter::new(File::create(&run_path)?))?;
+
+        self.buffer
+            .sort_unstable_by_key(|entry| (entry.token, entry.point_id));
+        for entry in self.buffer.drain(..) {
+            entry.write(&mut writer)?;
+        }
+        writer.finish()?.flush()?;
+
+        self.buffer_bytes = 0;
+        self.runs.push(run_path);
+        Ok(())
+    }
+
+    /// Merge runs and write all index files into the index directory
+    pub fn finalize(self) -> OperationResult<()> {
+        let Self {
+            path,
+            with_positions,
+            vocab,
+            point_to_tokens_count,
+            points_count,
+            point_run,
+            mut buffer,
+            buffer_bytes: _,
+            max_buffer_bytes: _,
+            runs_dir,
+            runs,
+        } = self;
+
+        fs::create_dir_all(&path)?;
+
+        // Spilled runs, followed by the buffer as the last run
+        let mut sources = runs
+            .iter()
+            .map(|run_path| -> io::Result<RunEntries> {
+                let mut reader = DecryptingReader::open(run_path)?;
+                let entries =
+                    std::iter::from_fn(move || PostingEntry::read(&mut reader).transpose());
+                Ok(Box::new(entries))
+            })
+            .collect::<io::Result<Vec<_>>>()?;
+        buffer.sort_unstable_by_key(|entry| (entry.token, entry.point_id));
+        sources.push(Box::new(buffer.into_iter().map(Ok)));
+
+        let entries = sources
+            .into_iter()
+            .enumerate()
+            .map(|(run, entries)| entries.map(move |entry| entry.map(|entry| (run as u32, entry))))
+            .kmerge_by(|a, b| match (a, b) {
+                (Ok((_, a)), Ok((_, b))) => (a.token, a.point_id) < (b.token, b.point_id),
+                // surface errors as early as possible
+                (Err(_), _) => true,
+                (Ok(_), Err(_)) => false,
+            })
+            // Drop stale entries of removed or re-indexed points
+            .filter_ok(|(run, entry)| point_run[entry.point_id as usize] == *run)
+            .map_ok(|(_, entry)| entry);
+
+        let postings_path = path.join(POSTINGS_FILE);
+        let orig_to_new_token = if with_positions {
+            write_postings(postings_path, entries, |positions| {
+                let mut value = Positions::default();
+                for position in positions {
+                    value.push(position);
+                }
+                value
+            })?
+        } else {
+            write_postings(postings_path, entries, |_| ())?
+        };
+        drop(runs_dir);
+
+        // Keep only tokens that have non-empty postings
+        let mut vocab: HashMap<String, TokenId> = vocab
+            .into_iter()
+            .filter_map(|(key, orig_token)| {
+                orig_to_new_token
+                    .get(&orig_token)
+                    .map(|new_token| (key, *new_token))
+            })
+            .collect();
+        vocab.shrink_to_fit();
+
+        MmapInvertedIndex::create_metadata(&path, &vocab, &point_to_tokens_count, points_count)
+    }
+}
+
+/// Write postings of entries sorted by token and point id.
+///
+/// Returns mapping of original token ids to ids of written postings, tokens without entries are
+/// skipped.
+fn write_postings<V: MmapPostingValue>(
+    path: PathBuf,
+    entries: impl Iterator<Item = io::Result<PostingEntry>>,
+    to_value: impl Fn(Vec<u32>) -> V,
+) -> OperationResult<HashMap<TokenId, TokenId>> {
+    let mut writer = MmapPostingsWriter::<V>::new(path)?;
+    let mut orig_to_new_token = HashMap::new();
+
+    let mut write_posting = |token: TokenId, builder: PostingBuilder<V>| -> io::Result<()> {
+        let posting: PostingList<V> = builder.build();
+        orig_to_new_token.insert(token, orig_to_new_token.len() as TokenId);
+        writer.push(&posting)
+    };
+
+    let mut current: Option<(TokenId, PostingBuilder<V>)> = None;
+    for entry in entries {
+        let PostingEntry {
+            token,
+            point_id,
+            positions,
+        } = entry?;
+
+        let builder = match &mut current {
+            Some((current_token, builder)) if *current_token == token => builder,
+            _ => {
+                if let Some((prev_token, builder)) = current.take() {
+                    write_posting(prev_token, builder)?;
+                }
+                &mut current.insert((token, PostingBuilder::new())).1
+            }
+        };
+        builder.add(point_id, to_value(positions));
+    }
+    if let Some((token, builder)) = current {
+        write_posting(token, builder)?;
+    }
+
+    writer.finish()?;
+    Ok(orig_to_new_token)
+}
@@ -3,7 +3,7 @@ pub mod immutable_postings_enum;
 pub(super) mod mmap_inverted_index;
 pub(super) mod mutable_inverted_index;
 pub(super) mod mutable_inverted_index_builder;
-mod positions;
+pub(super) mod positions;
 mod posting_list;
 mod postings_iterator;
 
@@ -14,14 +14,23 @@ use ahash::AHashSet;
 use common::counter::hardware_counter::HardwareCounterCell;
 use common::types::PointOffsetType;
 use itertools::Itertools;
+use positions::PartialDocument;
 
 use crate::common::operation_error::OperationResult;
-use crate::index::field_index::{CardinalityEstimation, PayloadBlockCondition, PrimaryCondition};
+use crate::index::field_index::{
+    CardinalityEstimation, IndexResources, PayloadBlockCondition, PrimaryCondition,
+};
 use crate::index::query_estimator::expected_should_estimation;
 use crate::types::{FieldCondition, Match, PayloadKeyType};
 
 pub type TokenId = u32;
 
+/// Approximate size of the heap memory used by a vocabulary, in bytes
+fn vocab_heap_size(vocab: &HashMap<String, TokenId>) -> usize {
+    vocab.capacity() * size_of::<(String, TokenId)>()
+        + vocab.keys().map(String::capacity).sum::<usize>()
+}
+
 /// Contains the set of tokens that are in a document.
 ///
 /// Internally, it keeps them unique and sorted, so that we can binary-search over them
@@ -382,7 +391,44 @@ pub trait InvertedIndex {
 
     fn points_count(&self) -> usize;
 
+    /// Estimate RAM and disk usage of the index, see [`IndexResources`]
+    fn estimate_resources(&self) -> OperationResult<IndexResources>;
+
     fn get_token_id(&self, token: &str, hw_counter: &HardwareCounterCell) -> Option<TokenId>;
+
+    /// Reconstruct positions of the given tokens in the document of the point.
+    ///
+    /// Returns `None` if the index doesn't store positions.
+    fn partial_document(
+        &self,
+        tokens: &TokenSet,
+        point_id: PointOffsetType,
+    ) -> Option<PartialDocument>;
+
+    /// Positions of document tokens which make the point match the query, in ascending order.
+    ///
+    /// Intended for highlighting. For phrase queries, only tokens of the phrase occurrences are
+    /// reported. Returns `None` if the point doesn't match the query, or if the index doesn't
+    /// store positions.
+    fn match_positions(
+        &self,
+        parsed_query: &ParsedQuery,
+        point_id: PointOffsetType,
+    ) -> Option<Vec<u32>> {
+        if !self.check_match(parsed_query, point_id) {
+            return None;
+        }
+
+        let positions = match parsed_query {
+            ParsedQuery::AllTokens(tokens) | ParsedQuery::AnyTokens(tokens) => {
+                self.partial_document(tokens, point_id)?.positions()
+            }
+            ParsedQuery::Phrase(phrase) => self
+                .partial_document(&phrase.to_token_set(), point_id)?
+                .phrase_positions(phrase),
+        };
+        Some(positions)
+    }
 }
 
 #[cfg(test)]
@@ -396,6 +442,7 @@ mod tests {
     use super::{Document, InvertedIndex, ParsedQuery, TokenId, TokenSet};
     use crate::index::field_index::full_text_index::inverted_index::immutable_inverted_index::ImmutableInvertedIndex;
     use crate::index::field_index::full_text_index::inverted_index::mmap_inverted_index::MmapInvertedIndex;
+    use crate::index::field_index::full_text_index::inverted_index::mmap_inverted_index::streaming_builder::StreamingInvertedIndexBuilder;
     use crate::index::field_index::full_text_index::inverted_index::mutable_inverted_index::MutableInvertedIndex;
 
     fn generate_word() -> String {
@@ -580,6 +627,35 @@ mod tests {
         assert_eq!(immutable.points_count, imm_mmap.points_count);
     }
 
+    #[rstest]
+    fn test_estimate_resources(#[values(false, true)] phrase_matching: bool) {
+        let mutable = mutable_inverted_index(2000, 200, phrase_matching);
+        let mutable_resources = mutable.estimate_resources().unwrap();
+        assert!(mutable_resources.ram_bytes > 0);
+        assert_eq!(mutable_resources.disk_bytes, 0);
+        assert!(mutable_resources.files.is_empty());
+
+        let immutable = ImmutableInvertedIndex::from(mutable);
+        let immutable_resources = immutable.estimate_resources().unwrap();
+        assert!(immutable_resources.ram_bytes > 0);
+        assert_eq!(immutable_resources.disk_bytes, 0);
+
+        let mmap_dir = tempfile::tempdir().unwrap();
+        MmapInvertedIndex::create(mmap_dir.path().into(), &immutable).unwrap();
+        let mmap = MmapInvertedIndex::open(mmap_dir.path().into(), false, phrase_matching)
+            .unwrap()
+            .unwrap();
+
+        let mmap_resources = mmap.estimate_resources().unwrap();
+        assert_eq!(mmap_resources.files.len(), mmap.files().len());
+        for (path, size) in &mmap_resources.files {
+            assert_eq!(fs_err::metadata(path).unwrap().len() as usize, *size);
+        }
+        let files_size: usize = mmap_resources.files.iter().map(|(_, size)| size).sum();
+        assert_eq!(mmap_resources.disk_bytes, files_size);
+        assert_eq!(mmap_resources.ram_bytes, files_size);
+    }
+
     #[rstest]
     fn test_mmap_index_congruence(#[values(false, true)] phrase_matching: bool) {
         let indexed_count = 10000;
@@ -671,6 +747,246 @@ mod tests {
         );
     }
 
+    #[rstest]
+    fn test_compact_drops_deleted_points(#[values(false, true)] phrase_matching: bool) {
+        let indexed_count = 2000;
+        let deleted_count = 500;
+
+        let hw_counter = HardwareCounterCell::new();
+        let mmap_dir = tempfile::tempdir().unwrap();
+
+        let mut_index = mutable_inverted_index(indexed_count, 0, phrase_matching);
+        let immutable = ImmutableInvertedIndex::from(mut_index);
+        MmapInvertedIndex::create(mmap_dir.path().into(), &immutable).unwrap();
+        let mut mmap_index =
+            MmapInvertedIndex::open(mmap_dir.path().into(), false, phrase_matching)
+                .unwrap()
+                .unwrap();
+        assert_eq!(mmap_index.deleted_ratio(), 0.0);
+
+        let mut points_to_delete = (0..indexed_count).collect::<Vec<_>>();
+        points_to_delete.shuffle(&mut rand::rng());
+        let points_to_delete = &points_to_delete[..deleted_count as usize];
+        for point_id in points_to_delete {
+            mmap_index.remove(*point_id);
+        }
+        assert_eq!(
+            mmap_index.deleted_ratio(),
+            f64::from(deleted_count) / f64::from(indexed_count),
+        );
+
+        let compacted = ImmutableInvertedIndex::from(&mmap_index).compact();
+
+        // Deleted points are not in postings anymore, and all postings are non-empty
+        for token_id in 0..compacted.postings.len() as TokenId {
+            let ids = compacted
+                .postings
+                .iter_ids(token_id)
+                .unwrap()
+                .collect::<Vec<_>>();
+            assert!(!ids.is_empty());
+            assert!(ids.iter().all(|id| !points_to_delete.contains(id)));
+        }
+        assert_eq!(compacted.vocab.len(), compacted.postings.len());
+        assert_eq!(compacted.points_count, mmap_index.active_points_count);
+
+        let compacted_dir = tempfile::tempdir().unwrap();
+        MmapInvertedIndex::create(compacted_dir.path().into(), &compacted).unwrap();
+        let compacted_mmap =
+            MmapInvertedIndex::open(compacted_dir.path().into(), false, phrase_matching)
+                .unwrap()
+                .unwrap();
+        assert_eq!(compacted_mmap.deleted_ratio(), 0.0);
+
+        // Queries return the same results as before compaction.
+        // Tokens which only occurred in deleted points are not in the compacted vocabulary.
+        for query in (0..100).map(|_| generate_query()) {
+            let before = ParsedQuery::AnyTokens(
+                query
+                    .iter()
+                    .filter_map(|token| mmap_index.get_token_id(token, &hw_counter))
+                    .collect(),
+            );
+            let after = ParsedQuery::AnyTokens(
+                query
+                    .iter()
+                    .filter_map(|token| compacted_mmap.get_token_id(token, &hw_counter))
+                    .collect(),
+            );
+            let before = mmap_index.filter(before, &hw_counter).collect::<Vec<_>>();
+            let after = compacted_mmap
+                .filter(after, &hw_counter)
+                .collect::<Vec<_>>();
+            assert_eq!(before, after);
+        }
+    }
+
+    #[rstest]
+    fn test_merge_immutable_indexes(#[values(false, true)] phrase_matching: bool) {
+        let hw_counter = HardwareCounterCell::new();
+
+        let first =
+            ImmutableInvertedIndex::from(mutable_inverted_index(1000, 200, phrase_matching));
+        let second =
+            ImmutableInvertedIndex::from(mutable_inverted_index(800, 100, phrase_matching));
+
+        // Interleave points of both indexes, drop the tail of the first one
+        let first_ids = (0..1000)
+            .map(|id| (id < 900).then_some(id * 2))
+            .collect::<Vec<_>>();
+        let second_ids = (0..800).map(|id| Some(id * 2 + 1)).collect::<Vec<_>>();
+
+        let merged = ImmutableInvertedIndex::merge(&[
+            (&first, first_ids.as_slice()),
+            (&second, second_ids.as_slice()),
+        ])
+        .unwrap();
+
+        let expected_points_count =
+            (0..900).filter(|id| !first.values_is_empty(*id)).count() + second.points_count();
+        assert_eq!(merged.points_count(), expected_points_count);
+        assert_eq!(merged.vocab.len(), merged.postings.len());
+        for token_id in 0..merged.postings.len() as TokenId {
+            assert!(merged.postings.posting_len(token_id).unwrap() > 0);
+        }
+        for (id, new_id) in first_ids.iter().enumerate() {
+            if let Some(new_id) = new_id {
+                assert_eq!(merged.values_count(*new_id), first.values_count(id as _));
+            }
+        }
+
+        // Tokens unknown to an index can't match any of its points
+        let parse_query = |index: &ImmutableInvertedIndex, query: &[String]| {
+            ParsedQuery::AnyTokens(
+                query
+                    .iter()
+                    .filter_map(|token| index.get_token_id(token, &hw_counter))
+                    .collect(),
+            )
+        };
+
+        for query in (0..100).map(|_| generate_query()) {
+            let mut expected = Vec::new();
+            for (index, new_ids) in [(&first, &first_ids), (&second, &second_ids)] {
+                expected.extend(
+                    index
+                        .filter(parse_query(index, &query), &hw_counter)
+                        .filter_map(|id| new_ids[id as usize]),
+                );
+            }
+            expected.sort_unstable();
+
+            let merged_filtered = merged
+                .filter(parse_query(&merged, &query), &hw_counter)
+                .collect::<Vec<_>>();
+            assert_eq!(merged_filtered, expected);
+        }
+    }
+
+    #[rstest]
+    fn test_streaming_build_congruence(#[values(false, true)] phrase_matching: bool) {
+        let indexed_count = 1000;
+
+        let hw_counter = HardwareCounterCell::new();
+        let expected_dir = tempfile::tempdir().unwrap();
+        let streaming_dir = tempfile::tempdir().unwrap();
+
+        let mut mutable = MutableInvertedIndex::new(phrase_matching);
+        // Small buffer forces spilling postings into many runs
+        let mut streaming =
+            StreamingInvertedIndexBuilder::new(streaming_dir.path().into(), phrase_matching, 4096);
+
+        // Index all points, then re-index some of them with new documents
+        let mut documents = vec![Vec::new(); indexed_count as usize];
+        let reindexed = (0..100).map(|_| rand::rng().random_range(0..indexed_count));
+        for idx in (0..indexed_count).chain(reindexed) {
+            let doc_len = rand::rng().random_range(10..=30);
+            let tokens: Vec<String> = (0..doc_len).map(|_| generate_word()).collect();
+
+            mutable.remove(idx);
+            let token_ids = mutable.register_tokens(&tokens);
+            if phrase_matching {
+                mutable
+                    .index_document(idx, Document(token_ids.clone()), &hw_counter)
+                    .unwrap();
+            }
+            mutable
+                .index_tokens(idx, TokenSet::from_iter(token_ids), &hw_counter)
+                .unwrap();
+
+            let token_ids = streaming.register_tokens(&tokens);
+            streaming.add_document(idx, &token_ids).unwrap();
+
+            documents[idx as usize] = tokens;
+        }
+
+        for _ in 0..200 {
+            let idx = rand::rng().random_range(0..indexed_count);
+            mutable.remove(idx);
+            streaming.remove(idx);
+        }
+
+        MmapInvertedIndex::create(
+            expected_dir.path().into(),
+            &ImmutableInvertedIndex::from(mutable),
+        )
+        .unwrap();
+        streaming.finalize().unwrap();
+
+        let open = |path: &std::path::Path| {
+            let mmap = MmapInvertedIndex::open(path.into(), false, phrase_matching)
+                .unwrap()
+                .unwrap();
+            ImmutableInvertedIndex::from(&mmap)
+        };
+        let expected = open(expected_dir.path());
+        let streamed = open(streaming_dir.path());
+
+        assert_eq!(streamed.points_count(), expected.points_count());
+        assert_eq!(streamed.vocab.len(), expected.vocab.len());
+        for (token, expected_token_id) in &expected.vocab {
+            let streamed_token_id = streamed.vocab[token];
+            assert!(
+                streamed
+                    .postings
+                    .iter_ids(streamed_token_id)
+                    .unwrap()
+                    .eq(expected.postings.iter_ids(*expected_token_id).unwrap())
+            );
+        }
+        for idx in 0..indexed_count {
+            assert_eq!(streamed.values_count(idx), expected.values_count(idx));
+        }
+
+        // Phrases taken from indexed documents
+        for _ in 0..100 {
+            let document = &documents[rand::rng().random_range(0..documents.len())];
+            let phrase = &document[3..6];
+
+            let parse_phrase = |index: &ImmutableInvertedIndex| {
+                phrase
+                    .iter()
+                    .map(|token| index.get_token_id(token, &hw_counter))
+                    .collect::<Option<Vec<_>>>()
+                    .map(|tokens| ParsedQuery::Phrase(Document(tokens)))
+            };
+            let (Some(expected_query), Some(streamed_query)) =
+                (parse_phrase(&expected), parse_phrase(&streamed))
+            else {
+                // Tokens of removed documents, vocabularies are the same
+                continue;
+            };
+
+            let expected_filtered = expected
+                .filter(expected_query, &hw_counter)
+                .collect::<Vec<_>>();
+            let streamed_filtered = streamed
+                .filter(streamed_query, &hw_counter)
+                .collect::<Vec<_>>();
+            assert_eq!(streamed_filtered, expected_filtered);
+        }
+    }
+
     fn check_query_congruence(
         mut_parsed_queries: &[Option<ParsedQuery>],
         mmap_parsed_queries: &[Option<ParsedQuery>],
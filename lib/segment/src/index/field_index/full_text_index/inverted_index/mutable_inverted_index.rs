@@ -4,10 +4,12 @@ use common::counter::hardware_counter::HardwareCounterCell;
 use common::types::PointOffsetType;
 use itertools::Either;
 
+use super::positions::PartialDocument;
 use super::posting_list::PostingList;
 use super::postings_iterator::{intersect_postings_iterator, merge_postings_iterator};
-use super::{Document, InvertedIndex, ParsedQuery, TokenId, TokenSet};
+use super::{Document, InvertedIndex, ParsedQuery, TokenId, TokenSet, vocab_heap_size};
 use crate::common::operation_error::OperationResult;
+use crate::index::field_index::IndexResources;
 
 #[cfg_attr(test, derive(Clone))]
 pub struct MutableInvertedIndex {
@@ -288,7 +290,46 @@ impl InvertedIndex for MutableInvertedIndex {
         self.points_count
     }
 
+    fn estimate_resources(&self) -> OperationResult<IndexResources> {
+        let postings_size = self.postings.capacity() * size_of::<PostingList>()
+            + self
+                .postings
+                .iter()
+                .map(PostingList::heap_size)
+                .sum::<usize>();
+
+        let point_to_tokens_size = self.point_to_tokens.capacity() * size_of::<Option<TokenSet>>()
+            + self
+                .point_to_tokens
+                .iter()
+                .flatten()
+                .map(|tokens| tokens.len() * size_of::<TokenId>())
+                .sum::<usize>();
+
+        let point_to_doc_size = self.point_to_doc.as_ref().map_or(0, |point_to_doc| {
+            point_to_doc.capacity() * size_of::<Option<Document>>()
+                + point_to_doc
+                    .iter()
+                    .flatten()
+                    .map(|doc| doc.len() * size_of::<TokenId>())
+                    .sum::<usize>()
+        });
+
+        Ok(IndexResources::in_memory(
+            postings_size + vocab_heap_size(&self.vocab) + point_to_tokens_size + point_to_doc_size,
+        ))
+    }
+
     fn get_token_id(&self, token: &str, _hw_counter: &HardwareCounterCell) -> Option<TokenId> {
         self.vocab.get(token).copied()
     }
+
+    fn partial_document(
+        &self,
+        tokens: &TokenSet,
+        point_id: PointOffsetType,
+    ) -> Option<PartialDocument> {
+        let doc = self.get_document(point_id)?;
+        Some(PartialDocument::from_document(doc, tokens))
+    }
 }
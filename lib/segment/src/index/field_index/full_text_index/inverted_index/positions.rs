@@ -1,7 +1,7 @@
 use posting_list::{PostingValue, UnsizedHandler, UnsizedValue};
 use zerocopy::{FromBytes, IntoBytes};
 
-use crate::index::field_index::full_text_index::inverted_index::{Document, TokenId};
+use crate::index::field_index::full_text_index::inverted_index::{Document, TokenId, TokenSet};
 
 /// Represents a list of positions of a token in a document.
 #[derive(Default, Clone, Debug)]
@@ -61,6 +61,21 @@ pub struct TokenPosition {
 pub struct PartialDocument(Vec<TokenPosition>);
 
 impl PartialDocument {
+    /// Reconstruct partial document from a full document, keeping only the given tokens
+    pub fn from_document(document: &Document, tokens: &TokenSet) -> Self {
+        let tokens_positions = document
+            .tokens()
+            .iter()
+            .enumerate()
+            .filter(|(_, token_id)| tokens.contains(token_id))
+            .map(|(position, token_id)| TokenPosition {
+                token_id: *token_id,
+                position: position as u32,
+            })
+            .collect();
+        Self(tokens_positions)
+    }
+
     pub fn new(mut tokens_positions: Vec<TokenPosition>) -> Self {
         tokens_positions.sort_by_key(|tok_pos| tok_pos.position);
 
@@ -92,6 +107,43 @@ impl PartialDocument {
         }
     }
 
+    /// Positions of all tokens, in ascending order
+    pub fn positions(&self) -> Vec<u32> {
+        self.0.iter().map(|tok_pos| tok_pos.position).collect()
+    }
+
+    /// Positions of tokens in every occurrence of the given phrase, in ascending order.
+    ///
+    /// Returns empty vector if the phrase is empty or not found.
+    pub fn phrase_positions(&self, phrase: &Document) -> Vec<u32> {
+        match phrase.tokens() {
+            [] => Vec::new(),
+
+            [token] => self
+                .0
+                .iter()
+                .filter(|tok_pos| tok_pos.token_id == *token)
+                .map(|tok_pos| tok_pos.position)
+                .collect(),
+
+            phrase => {
+                let mut positions: Vec<u32> = self
+                    .sequential_position_windows(phrase.len())
+                    .filter(|window| {
+                        window
+                            .iter()
+                            .zip(phrase)
+                            .all(|(doc_token, query_token)| doc_token.token_id == *query_token)
+                    })
+                    .flat_map(|window| window.iter().map(|tok_pos| tok_pos.position))
+                    .collect();
+                // Occurrences may overlap, e.g. "a a" in "a a a"
+                positions.dedup();
+                positions
+            }
+        }
+    }
+
     /// Returns an iterator over windows which have sequential sequence of tokens.
     ///
     /// Will only return a window if:
@@ -101,13 +153,20 @@ impl PartialDocument {
         &self,
         window_size: usize,
     ) -> impl Iterator<Item = impl Iterator<Item = TokenId>> {
+        self.sequential_position_windows(window_size)
+            .map(|window| window.iter().map(|tok_pos| tok_pos.token_id))
+    }
+
+    fn sequential_position_windows(
+        &self,
+        window_size: usize,
+    ) -> impl Iterator<Item = &[TokenPosition]> {
         debug_assert!(window_size >= 2, "Window size must be at least 2");
-        self.0.windows(window_size).filter_map(|window| {
+        self.0.windows(window_size).filter(|window| {
             // make sure the positions are sequential
             window
                 .windows(2)
                 .all(|pair| pair[0].position + 1 == pair[1].position)
-                .then_some(window.iter().map(|tok_pos| tok_pos.token_id))
         })
     }
 }
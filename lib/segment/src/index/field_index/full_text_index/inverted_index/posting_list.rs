@@ -30,6 +30,11 @@ impl PostingList {
         self.list.contains(val)
     }
 
+    /// Approximate size of the heap memory used by the posting list, in bytes
+    pub fn heap_size(&self) -> usize {
+        self.list.serialized_size()
+    }
+
     #[inline]
     pub fn iter(&self) -> impl Iterator<Item = PointOffsetType> + '_ {
         self.list.iter()
@@ -1,12 +1,16 @@
 use common::types::PointOffsetType;
 use itertools::{Either, Itertools};
-use posting_list::{PostingIterator, PostingListView, PostingValue};
+use posting_list::{
+    CHUNK_LEN, PostingElement, PostingIterator, PostingListView, PostingValue, SizedTypeFor,
+};
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
+use zerocopy::little_endian::U32;
 
 use super::posting_list::PostingList;
 use crate::index::field_index::full_text_index::inverted_index::positions::{
     PartialDocument, Positions, TokenPosition,
 };
-use crate::index::field_index::full_text_index::inverted_index::{Document, TokenId};
+use crate::index::field_index::full_text_index::inverted_index::{Document, TokenId, TokenSet};
 
 pub fn intersect_postings_iterator<'a>(
     mut postings: Vec<&'a PostingList>,
@@ -34,40 +38,198 @@ pub fn merge_postings_iterator<'a>(
         .dedup()
 }
 
-pub fn intersect_compressed_postings_iterator<'a, V: PostingValue + 'a>(
-    mut postings: Vec<PostingListView<'a, V>>,
-    is_active: impl Fn(PointOffsetType) -> bool + 'a,
-) -> impl Iterator<Item = PointOffsetType> + 'a {
+/// Number of posting elements covered by a single entry of the skip list
+pub const SKIP_BLOCK_LEN: usize = 8 * CHUNK_LEN;
+
+/// Posting list view, optionally accompanied by a skip list.
+///
+/// Skip list holds the max id of each complete block of [`SKIP_BLOCK_LEN`] elements, so that
+/// intersection can jump over blocks without touching their chunks.
+pub struct SkippablePostingView<'a, V: PostingValue> {
+    pub view: PostingListView<'a, V>,
+    pub block_max_ids: &'a [U32],
+}
+
+// Implemented manually, derive would require `V: Clone`
+impl<V: PostingValue> Clone for SkippablePostingView<'_, V> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<V: PostingValue> Copy for SkippablePostingView<'_, V> {}
+
+impl<'a, V: PostingValue> From<PostingListView<'a, V>> for SkippablePostingView<'a, V> {
+    fn from(view: PostingListView<'a, V>) -> Self {
+        Self {
+            view,
+            block_max_ids: &[],
+        }
+    }
+}
+
+struct SkippingPostingIterator<'a, V: PostingValue> {
+    iter: PostingIterator<'a, V>,
+    block_max_ids: &'a [U32],
+    /// First block, which may contain ids greater or equal to the last target
+    block: usize,
+}
+
+impl<'a, V: PostingValue> SkippingPostingIterator<'a, V> {
+    fn new(posting: SkippablePostingView<'a, V>) -> Self {
+        Self {
+            iter: posting.view.into_iter(),
+            block_max_ids: posting.block_max_ids,
+            block: 0,
+        }
+    }
+
+    /// Same as [`PostingIterator::advance_until_greater_or_equal`], but skips whole blocks,
+    /// whose max id is smaller than the target.
+    fn advance_until_greater_or_equal(
+        &mut self,
+        target_id: PointOffsetType,
+    ) -> Option<PostingElement<V>> {
+        let skipped_blocks = self.block_max_ids[self.block..]
+            .partition_point(|block_max_id| block_max_id.get() < target_id);
+        if skipped_blocks > 0 {
+            self.block += skipped_blocks;
+            self.iter.skip_to(self.block * SKIP_BLOCK_LEN);
+        }
+
+        self.iter.advance_until_greater_or_equal(target_id)
+    }
+}
+
+/// Minimal estimated cost of intersection, which is worth to be split between threads.
+///
+/// Cost is the number of lookups into other postings: length of the smallest posting multiplied
+/// by the number of other postings.
+pub const PARALLEL_INTERSECTION_MIN_COST: usize = 1 << 20;
+
+/// Minimal number of elements of the smallest posting, which are intersected by a single thread
+const PARALLEL_INTERSECTION_MIN_PARTITION_LEN: usize = 16 * CHUNK_LEN;
+
+/// Remove the smallest posting from the list and return it
+fn take_smallest_posting<'a, V: PostingValue>(
+    postings: &mut Vec<SkippablePostingView<'a, V>>,
+) -> SkippablePostingView<'a, V> {
     let smallest_posting_idx = postings
         .iter()
         .enumerate()
-        .min_by_key(|(_idx, posting)| posting.len())
+        .min_by_key(|(_idx, posting)| posting.view.len())
         .map(|(idx, _posting)| idx)
         .unwrap();
-    let smallest_posting = postings.remove(smallest_posting_idx);
-    let smallest_posting_iterator = smallest_posting.into_iter();
+    postings.remove(smallest_posting_idx)
+}
+
+/// Check that all postings contain the id, ids must be checked in ascending order
+fn all_contain<V: PostingValue>(
+    posting_iterators: &mut [SkippingPostingIterator<'_, V>],
+    id: PointOffsetType,
+) -> bool {
+    posting_iterators.iter_mut().all(|posting_iterator| {
+        // Custom "contains" check, which leverages the fact that smallest posting is sorted,
+        // so the next id that must be in all postings is strictly greater than the previous one.
+        //
+        // This means that the other iterators can remember the last id they returned to avoid extra work
+        posting_iterator
+            // potential optimization: Make posting iterator of just ids, without values (a.k.a. positions).
+            //                         We are discarding them here, thus unnecessarily reading them from the tails of the posting lists.
+            .advance_until_greater_or_equal(id)
+            .is_some_and(|elem| elem.id == id)
+    })
+}
+
+/// Intersect postings, in parallel if the estimated cost of the intersection is at least
+/// [`PARALLEL_INTERSECTION_MIN_COST`].
+///
+/// Parallel intersection is eager, so that it is only worth it if the whole result is consumed.
+pub fn intersect_compressed_postings<'a, V>(
+    postings: Vec<SkippablePostingView<'a, V>>,
+    is_active: impl Fn(PointOffsetType) -> bool + Sync + 'a,
+) -> impl Iterator<Item = PointOffsetType> + 'a
+where
+    V: PostingValue + Sync + 'a,
+    SizedTypeFor<V>: Sync,
+{
+    let smallest_len = postings.iter().map(|posting| posting.view.len()).min();
+    let cost = smallest_len.unwrap_or(0) * postings.len().saturating_sub(1);
+
+    if cost >= PARALLEL_INTERSECTION_MIN_COST && rayon::current_num_threads() > 1 {
+        Either::Left(intersect_compressed_postings_parallel(postings, &is_active).into_iter())
+    } else {
+        Either::Right(intersect_compressed_postings_iterator(postings, is_active))
+    }
+}
+
+pub fn intersect_compressed_postings_iterator<'a, V: PostingValue + 'a>(
+    mut postings: Vec<SkippablePostingView<'a, V>>,
+    is_active: impl Fn(PointOffsetType) -> bool + 'a,
+) -> impl Iterator<Item = PointOffsetType> + 'a {
+    let smallest_posting = take_smallest_posting(&mut postings);
+    let smallest_posting_iterator = smallest_posting.view.into_iter();
 
     let mut posting_iterators = postings
         .into_iter()
-        .map(PostingListView::into_iter)
+        .map(SkippingPostingIterator::new)
         .collect::<Vec<_>>();
 
     smallest_posting_iterator
         .map(|elem| elem.id)
-        .filter(move |id| {
-            is_active(*id)
-                && posting_iterators.iter_mut().all(|posting_iterator| {
-                    // Custom "contains" check, which leverages the fact that smallest posting is sorted,
-                    // so the next id that must be in all postings is strictly greater than the previous one.
-                    //
-                    // This means that the other iterators can remember the last id they returned to avoid extra work
-                    posting_iterator
-                        // potential optimization: Make posting iterator of just ids, without values (a.k.a. positions).
-                        //                         We are discarding them here, thus unnecessarily reading them from the tails of the posting lists.
-                        .advance_until_greater_or_equal(*id)
-                        .is_some_and(|elem| elem.id == *id)
-                })
+        .filter(move |id| is_active(*id) && all_contain(&mut posting_iterators, *id))
+}
+
+/// Intersect postings on the rayon thread pool.
+///
+/// The smallest posting is split into partitions, each of them is intersected with the other
+/// postings by a separate thread. Results of partitions are concatenated, so ids are sorted.
+pub fn intersect_compressed_postings_parallel<V>(
+    mut postings: Vec<SkippablePostingView<'_, V>>,
+    is_active: &(impl Fn(PointOffsetType) -> bool + Sync),
+) -> Vec<PointOffsetType>
+where
+    V: PostingValue + Sync,
+    SizedTypeFor<V>: Sync,
+{
+    let smallest_posting = take_smallest_posting(&mut postings);
+    let smallest_len = smallest_posting.view.len();
+    if smallest_len == 0 {
+        return Vec::new();
+    }
+
+    let partitions_count = rayon::current_num_threads()
+        .min(smallest_len.div_ceil(PARALLEL_INTERSECTION_MIN_PARTITION_LEN))
+        .max(1);
+    // Align partitions to chunks, so that each chunk is decompressed only once
+    let partition_len = smallest_len
+        .div_ceil(partitions_count)
+        .next_multiple_of(CHUNK_LEN);
+
+    let partition_starts = (0..smallest_len).step_by(partition_len).collect::<Vec<_>>();
+
+    let partitions: Vec<Vec<PointOffsetType>> = partition_starts
+        .into_par_iter()
+        .map(|start| {
+            let mut smallest_posting_iterator = smallest_posting.view.into_iter();
+            smallest_posting_iterator.skip_to(start);
+
+            // Other postings are iterated from the beginning, first lookup skips to the partition
+            let mut posting_iterators = postings
+                .iter()
+                .copied()
+                .map(SkippingPostingIterator::new)
+                .collect::<Vec<_>>();
+
+            smallest_posting_iterator
+                .take(partition_len)
+                .map(|elem| elem.id)
+                .filter(|id| is_active(*id) && all_contain(&mut posting_iterators, *id))
+                .collect()
         })
+        .collect();
+
+    partitions.concat()
 }
 
 pub fn merge_compressed_postings_iterator<'a, V: PostingValue + 'a>(
@@ -193,6 +355,29 @@ pub fn check_compressed_postings_phrase<'a>(
     phrase_in_all_postings(point_id, phrase, Vec::new(), &mut posting_iterators)
 }
 
+/// Reconstructs a partial document of the point from the posting lists (which contain positions)
+///
+/// Only the given tokens are looked up, tokens missing from the document are skipped.
+pub fn partial_document_from_postings<'a>(
+    tokens: &TokenSet,
+    point_id: PointOffsetType,
+    token_to_posting: impl Fn(&TokenId) -> Option<PostingListView<'a, Positions>>,
+) -> PartialDocument {
+    let tokens_positions = tokens
+        .tokens()
+        .iter()
+        .filter_map(|token_id| {
+            let elem = token_to_posting(token_id)?
+                .into_iter()
+                .advance_until_greater_or_equal(point_id)?;
+            (elem.id == point_id).then(|| elem.value.to_token_positions(*token_id))
+        })
+        .flatten()
+        .collect();
+
+    PartialDocument::new(tokens_positions)
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -231,9 +416,9 @@ mod tests {
         let p2_compressed: IdsPostingList = p2.iter().map(|id| (id, ())).collect();
         let p3_compressed: IdsPostingList = p3.iter().map(|id| (id, ())).collect();
         let compressed_posting_reades = vec![
-            p1_compressed.view(),
-            p2_compressed.view(),
-            p3_compressed.view(),
+            p1_compressed.view().into(),
+            p2_compressed.view().into(),
+            p3_compressed.view().into(),
         ];
         let merged = intersect_compressed_postings_iterator(compressed_posting_reades, |_| true);
 
@@ -1,15 +1,20 @@
 use std::path::PathBuf;
+use std::sync::OnceLock;
 
 use common::counter::hardware_counter::HardwareCounterCell;
 use common::types::PointOffsetType;
 use fs_err as fs;
 use serde_json::Value;
 
+use super::fuzzy::SortedVocab;
 use super::inverted_index::immutable_inverted_index::ImmutableInvertedIndex;
 use super::inverted_index::mmap_inverted_index::MmapInvertedIndex;
-use super::inverted_index::mutable_inverted_index::MutableInvertedIndex;
-use super::inverted_index::{Document, InvertedIndex, TokenSet};
-use super::text_index::FullTextIndex;
+use super::inverted_index::mmap_inverted_index::streaming_builder::{
+    DEFAULT_BUFFER_BYTES, StreamingInvertedIndexBuilder,
+};
+use super::inverted_index::{InvertedIndex, TokenId};
+use super::text_index::{FullTextIndex, MergedTextIndex};
+use super::tokenizer_config::StoredTokenizerConfig;
 use super::tokenizers::Tokenizer;
 use crate::common::Flusher;
 use crate::common::operation_error::{OperationError, OperationResult};
@@ -17,11 +22,13 @@ use crate::data_types::index::TextIndexParams;
 use crate::index::field_index::full_text_index::immutable_text_index::{
     ImmutableFullTextIndex, Storage,
 };
-use crate::index::field_index::{FieldIndexBuilderTrait, ValueIndexer};
+use crate::index::field_index::{FieldIndexBuilderTrait, IndexResources, ValueIndexer};
 
 pub struct MmapFullTextIndex {
     pub(super) inverted_index: MmapInvertedIndex,
     pub(super) tokenizer: Tokenizer,
+    /// Sorted vocabulary for fuzzy matching, built on first use
+    pub(super) sorted_vocab: OnceLock<SortedVocab>,
 }
 
 impl MmapFullTextIndex {
@@ -35,19 +42,73 @@ impl MmapFullTextIndex {
         let has_positions = config.phrase_matching == Some(true);
         let tokenizer = Tokenizer::new_from_text_index_params(&config);
 
-        let inverted_index = MmapInvertedIndex::open(path, populate, has_positions)?;
+        // Complete or roll back a compaction, which was interrupted while swapping directories
+        common::fs::recover_replaced_dir(&path)?;
+
+        // Index built with a different tokenization pipeline can't be queried correctly,
+        // report it as missing so that it gets rebuilt
+        let expected_config = StoredTokenizerConfig::from_params(&config);
+        if let Some(stored_config) = StoredTokenizerConfig::load(&path)?
+            && !stored_config.is_compatible_with(&expected_config)
+        {
+            log::warn!(
+                "Full text index at {} was built with a different tokenizer config ({stored_config:?}), expected {expected_config:?}",
+                path.display(),
+            );
+            return Ok(None);
+        }
+
+        let inverted_index = MmapInvertedIndex::open(path.clone(), populate, has_positions)?;
+
+        if is_on_disk
+            && let Some(postings_count) = config.on_disk_populate_postings
+            && let Some(inverted_index) = &inverted_index
+        {
+            inverted_index.populate_selective(postings_count)?;
+        }
+
+        if config.lock_in_ram == Some(true)
+            && let Some(inverted_index) = &inverted_index
+            && let Err(err) = inverted_index.lock_vocab_in_ram()
+        {
+            log::warn!(
+                "Vocabulary of full text index at {} is not locked in RAM: {err}",
+                path.display(),
+            );
+        }
+
         Ok(inverted_index.map(|inverted_index| Self {
             inverted_index,
             tokenizer,
+            sorted_vocab: OnceLock::new(),
         }))
     }
 
     pub fn files(&self) -> Vec<PathBuf> {
-        self.inverted_index.files()
+        let mut files = self.inverted_index.files();
+        files.extend(self.tokenizer_config_file());
+        files
     }
 
     pub fn immutable_files(&self) -> Vec<PathBuf> {
-        self.inverted_index.immutable_files()
+        let mut files = self.inverted_index.immutable_files();
+        files.extend(self.tokenizer_config_file());
+        files
+    }
+
+    pub fn estimate_resources(&self) -> OperationResult<IndexResources> {
+        let mut resources = self.inverted_index.estimate_resources()?;
+        if let Some(file) = self.tokenizer_config_file() {
+            // Only read when the index is opened
+            resources.add_file(file, false)?;
+        }
+        Ok(resources)
+    }
+
+    /// Indexes built by older versions don't have the tokenizer config persisted
+    fn tokenizer_config_file(&self) -> Option<PathBuf> {
+        let path = StoredTokenizerConfig::path(self.path());
+        path.is_file().then_some(path)
     }
 
     fn path(&self) -> &PathBuf {
@@ -74,6 +135,54 @@ impl MmapFullTextIndex {
         self.inverted_index.flusher()
     }
 
+    /// Fraction of points in postings which are deleted, see [`MmapInvertedIndex::deleted_ratio`]
+    pub fn deleted_ratio(&self) -> f64 {
+        self.inverted_index.deleted_ratio()
+    }
+
+    /// Rebuild postings without deleted points.
+    ///
+    /// New files are written into a staging directory next to the index, which then atomically
+    /// replaces the index directory, see [`common::fs::replace_dir`]. Point offsets are preserved.
+    pub fn compact(&mut self) -> OperationResult<()> {
+        let compacted = ImmutableInvertedIndex::from(&self.inverted_index).compact();
+
+        let path = self.path().clone();
+        let staged_path = common::fs::staged_dir_path(&path);
+        // Leftover of a compaction, which was interrupted before the swap
+        if staged_path.exists() {
+            fs::remove_dir_all(&staged_path)?;
+        }
+        fs::create_dir_all(&staged_path)?;
+
+        MmapInvertedIndex::create(staged_path.clone(), &compacted)?;
+        drop(compacted);
+        if let Some(config_file) = self.tokenizer_config_file() {
+            fs::copy(config_file, StoredTokenizerConfig::path(&staged_path))?;
+        }
+
+        // Pending deletions are already dropped from the compacted postings
+        common::fs::replace_dir(&path)?;
+
+        let populate = !self.is_on_disk();
+        let has_positions = self.inverted_index.has_positions();
+        self.inverted_index = MmapInvertedIndex::open(path.clone(), populate, has_positions)?
+            .ok_or_else(|| {
+                OperationError::service_error(format!(
+                    "Failed to reopen compacted full text index at {}",
+                    path.display(),
+                ))
+            })?;
+        // Token ids are reassigned by compaction
+        self.sorted_vocab = OnceLock::new();
+        Ok(())
+    }
+
+    pub(super) fn sorted_vocab(&self) -> &SortedVocab {
+        self.sorted_vocab
+            .get_or_init(|| SortedVocab::new(self.inverted_index.iter_vocab()))
+    }
+
     pub fn is_on_disk(&self) -> bool {
         self.inverted_index.is_on_disk()
     }
@@ -94,10 +203,13 @@ impl MmapFullTextIndex {
 
 pub struct FullTextMmapIndexBuilder {
     path: PathBuf,
-    mutable_index: MutableInvertedIndex,
+    /// Accumulates postings on disk, so that memory usage doesn't grow with the index size
+    streaming_index: StreamingInvertedIndexBuilder,
     config: TextIndexParams,
     is_on_disk: bool,
     tokenizer: Tokenizer,
+    /// Index merged from source segments, replaces indexing of payloads
+    merged: Option<ImmutableInvertedIndex>,
 }
 
 impl FullTextMmapIndexBuilder {
@@ -105,13 +217,25 @@ impl FullTextMmapIndexBuilder {
         let with_positions = config.phrase_matching.unwrap_or_default();
         let tokenizer = Tokenizer::new_from_text_index_params(&config);
         Self {
+            streaming_index: StreamingInvertedIndexBuilder::new(
+                path.clone(),
+                with_positions,
+                DEFAULT_BUFFER_BYTES,
+            ),
             path,
-            mutable_index: MutableInvertedIndex::new(with_positions),
             config,
             is_on_disk,
             tokenizer,
+            merged: None,
         }
     }
+
+    /// Use inverted index merged from source segments instead of tokenizing payloads.
+    ///
+    /// Points added to the builder afterwards are ignored.
+    pub fn set_merged(&mut self, merged: MergedTextIndex) {
+        self.merged = Some(merged.inverted_index);
+    }
 }
 
 impl ValueIndexer for FullTextMmapIndexBuilder {
@@ -142,22 +266,17 @@ impl ValueIndexer for FullTextMmapIndexBuilder {
             });
         }
 
-        let tokens = self.mutable_index.register_tokens(&str_tokens);
+        let tokens = self.streaming_index.register_tokens(&str_tokens);
 
-        if self.mutable_index.point_to_doc.is_some() {
-            let document = Document::new(tokens.clone());
-            self.mutable_index
-                .index_document(id, document, hw_counter)?;
-        }
+        hw_counter
+            .payload_index_io_write_counter()
+            .incr_delta(tokens.len() * size_of::<TokenId>());
 
-        let token_set = TokenSet::from_iter(tokens);
-        self.mutable_index.index_tokens(id, token_set, hw_counter)?;
-
-        Ok(())
+        self.streaming_index.add_document(id, &tokens)
     }
 
     fn remove_point(&mut self, id: PointOffsetType) -> OperationResult<()> {
-        self.mutable_index.remove(id);
+        self.streaming_index.remove(id);
 
         Ok(())
     }
@@ -176,23 +295,29 @@ impl FieldIndexBuilderTrait for FullTextMmapIndexBuilder {
         payload: &[&Value],
         hw_counter: &HardwareCounterCell,
     ) -> OperationResult<()> {
+        if self.merged.is_some() {
+            return Ok(());
+        }
         ValueIndexer::add_point(self, id, payload, hw_counter)
     }
 
     fn finalize(self) -> OperationResult<Self::FieldIndexType> {
         let Self {
             path,
-            mutable_index,
+            streaming_index,
             config,
             is_on_disk,
             tokenizer,
+            merged,
         } = self;
 
-        let immutable = ImmutableInvertedIndex::from(mutable_index);
-
         fs::create_dir_all(path.as_path())?;
 
-        MmapInvertedIndex::create(path.clone(), &immutable)?;
+        match &merged {
+            Some(merged) => MmapInvertedIndex::create(path.clone(), merged)?,
+            None => streaming_index.finalize()?,
+        }
+        StoredTokenizerConfig::from_params(&config).save(&path)?;
 
         let populate = !is_on_disk;
         let has_positions = config.phrase_matching.unwrap_or_default();
@@ -206,15 +331,21 @@ impl FieldIndexBuilderTrait for FullTextMmapIndexBuilder {
         let mmap_index = MmapFullTextIndex {
             inverted_index,
             tokenizer: tokenizer.clone(),
+            sorted_vocab: OnceLock::new(),
         };
 
         let text_index = if is_on_disk {
             FullTextIndex::Mmap(Box::new(mmap_index))
         } else {
+            let immutable = match merged {
+                Some(merged) => merged,
+                None => ImmutableInvertedIndex::from(&mmap_index.inverted_index),
+            };
             FullTextIndex::Immutable(ImmutableFullTextIndex {
                 inverted_index: immutable,
                 tokenizer,
                 storage: Storage::Mmap(Box::new(mmap_index)),
+                sorted_vocab: OnceLock::new(),
             })
         };
 
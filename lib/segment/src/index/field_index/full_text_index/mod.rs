@@ -1,9 +1,11 @@
+pub mod fuzzy;
 mod immutable_text_index;
 mod inverted_index;
 pub mod mmap_text_index;
 mod mutable_text_index;
 pub mod stop_words;
 pub mod text_index;
+mod tokenizer_config;
 pub mod tokenizers;
 
 #[cfg(test)]
@@ -1,5 +1,6 @@
 use std::borrow::Cow;
 use std::path::PathBuf;
+use std::sync::OnceLock;
 
 use common::counter::hardware_counter::HardwareCounterCell;
 use common::types::PointOffsetType;
@@ -7,6 +8,7 @@ use gridstore::Gridstore;
 use gridstore::config::StorageOptions;
 use itertools::Itertools;
 
+use super::fuzzy::SortedVocab;
 use super::inverted_index::mutable_inverted_index::MutableInvertedIndex;
 use super::inverted_index::mutable_inverted_index_builder::MutableInvertedIndexBuilder;
 use super::inverted_index::{Document, InvertedIndex, TokenSet};
@@ -17,7 +19,7 @@ use crate::common::operation_error::{OperationError, OperationResult};
 #[cfg(feature = "rocksdb")]
 use crate::common::rocksdb_buffered_delete_wrapper::DatabaseColumnScheduledDeleteWrapper;
 use crate::data_types::index::TextIndexParams;
-use crate::index::field_index::ValueIndexer;
+use crate::index::field_index::{IndexResources, ValueIndexer};
 use crate::index::payload_config::StorageType;
 
 const GRIDSTORE_OPTIONS: StorageOptions = StorageOptions {
@@ -32,6 +34,8 @@ pub struct MutableFullTextIndex {
     pub(super) config: TextIndexParams,
     pub(super) storage: Storage,
     pub(super) tokenizer: Tokenizer,
+    /// Sorted vocabulary for fuzzy matching, built on first use and reset on new tokens
+    pub(super) sorted_vocab: OnceLock<SortedVocab>,
 }
 
 pub(super) enum Storage {
@@ -73,6 +77,7 @@ impl MutableFullTextIndex {
             config,
             storage: Storage::RocksDb(db_wrapper),
             tokenizer,
+            sorted_vocab: OnceLock::new(),
         }))
     }
 
@@ -131,6 +136,7 @@ impl MutableFullTextIndex {
             config,
             storage: Storage::Gridstore(store),
             tokenizer,
+            sorted_vocab: OnceLock::new(),
         }))
     }
 
@@ -185,6 +191,15 @@ impl MutableFullTextIndex {
         }
     }
 
+    /// Inverted index is kept in memory, backing storage is only read on load
+    pub(super) fn estimate_resources(&self) -> OperationResult<IndexResources> {
+        let mut resources = self.inverted_index.estimate_resources()?;
+        for file in self.files() {
+            resources.add_file(file, false)?;
+        }
+        Ok(resources)
+    }
+
     #[inline]
     pub(super) fn flusher(&self) -> Flusher {
         match &self.storage {
@@ -221,7 +236,11 @@ impl MutableFullTextIndex {
             });
         }
 
+        let vocab_len = self.inverted_index.vocab.len();
         let tokens = self.inverted_index.register_tokens(&str_tokens);
+        if self.inverted_index.vocab.len() != vocab_len {
+            self.sorted_vocab = OnceLock::new();
+        }
 
         let phrase_matching = self.config.phrase_matching.unwrap_or_default();
         if phrase_matching {
@@ -269,6 +288,17 @@ impl MutableFullTextIndex {
         Ok(())
     }
 
+    pub(super) fn sorted_vocab(&self) -> &SortedVocab {
+        self.sorted_vocab.get_or_init(|| {
+            SortedVocab::new(
+                self.inverted_index
+                    .vocab
+                    .iter()
+                    .map(|(token, token_id)| (token.as_str(), *token_id)),
+            )
+        })
+    }
+
     #[allow(clippy::unnecessary_wraps)]
     pub fn remove_point(&mut self, id: PointOffsetType) -> OperationResult<()> {
         // Update persisted storage
@@ -393,10 +423,15 @@ mod tests {
             lowercase: None,
             phrase_matching: None,
             on_disk: None,
+            on_disk_populate_postings: None,
+            lock_in_ram: None,
             stopwords: None,
             stemmer: None,
             ascii_folding: None,
+            unicode_normalization: None,
+            case_folding: None,
             enable_hnsw: None,
+            null_index: None,
         };
 
         {
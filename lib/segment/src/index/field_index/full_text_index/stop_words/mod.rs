@@ -1,4 +1,6 @@
 use ahash::AHashSet;
+use itertools::Itertools as _;
+use sha2::{Digest as _, Sha256};
 
 use crate::data_types::index::{Language, StopwordsInterface};
 
@@ -105,6 +107,27 @@ impl StopwordsFilter {
         self.stopwords.contains(token)
     }
 
+    pub fn is_empty(&self) -> bool {
+        self.stopwords.is_empty()
+    }
+
+    /// Hash of the effective stopwords set, stable across platforms and insertion order.
+    ///
+    /// Returns `None` if there are no stopwords.
+    pub fn fingerprint(&self) -> Option<String> {
+        if self.stopwords.is_empty() {
+            return None;
+        }
+
+        let mut hasher = Sha256::new();
+        for word in self.stopwords.iter().sorted_unstable() {
+            hasher.update(word.as_bytes());
+            // Separator, can't be a part of a stopword
+            hasher.update([0u8]);
+        }
+        Some(data_encoding::HEXLOWER.encode(&hasher.finalize()))
+    }
+
     fn add_stopword(&mut self, word: &str, lowercase: bool) {
         if lowercase {
             self.stopwords.insert(word.to_lowercase());
@@ -202,6 +225,26 @@ mod tests {
         assert!(!filter.is_stopword("programming"));
     }
 
+    #[test]
+    fn test_stopwords_fingerprint() {
+        assert_eq!(StopwordsFilter::new(&None, true).fingerprint(), None);
+
+        let custom = StopwordsFilter::new(
+            &Some(StopwordsInterface::new_custom(&["hello", "world"])),
+            true,
+        );
+        let custom_reordered = StopwordsFilter::new(
+            &Some(StopwordsInterface::new_custom(&["World", "hello"])),
+            true,
+        );
+        let english =
+            StopwordsFilter::new(&Some(StopwordsInterface::Language(Language::English)), true);
+
+        assert!(custom.fingerprint().is_some());
+        assert_eq!(custom.fingerprint(), custom_reordered.fingerprint());
+        assert_ne!(custom.fingerprint(), english.fingerprint());
+    }
+
     #[test]
     fn test_case_sensitivity() {
         let option = Some(StopwordsInterface::new_custom(&["Hello", "World"]));
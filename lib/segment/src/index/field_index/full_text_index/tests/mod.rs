@@ -2,11 +2,16 @@ mod test_congruence;
 
 use common::counter::hardware_counter::HardwareCounterCell;
 use common::types::PointOffsetType;
+use fs_err as fs;
 use tempfile::Builder;
 
+use crate::data_types::facets::{FacetValue, FacetValueRef};
 use crate::data_types::index::{TextIndexParams, TextIndexType, TokenizerType};
+use crate::index::field_index::facet_index::FacetIndex;
 use crate::index::field_index::full_text_index::text_index::FullTextIndex;
-use crate::index::field_index::{FieldIndexBuilderTrait as _, ValueIndexer};
+use crate::index::field_index::{FieldIndexBuilderTrait as _, PayloadFieldIndex, ValueIndexer};
+use crate::json_path::JsonPath;
+use crate::types::{FieldCondition, Match, MatchTextFuzzy};
 
 fn movie_titles() -> Vec<String> {
     vec![
@@ -163,9 +168,14 @@ fn test_prefix_search() {
         phrase_matching: None,
         stopwords: None,
         on_disk: None,
+        on_disk_populate_postings: None,
+        lock_in_ram: None,
         stemmer: None,
         ascii_folding: None,
+        unicode_normalization: None,
+        case_folding: None,
         enable_hnsw: None,
+        null_index: None,
     };
 
     let mut index =
@@ -212,11 +222,16 @@ fn test_phrase_matching() {
         max_token_len: None,
         lowercase: Some(true),
         on_disk: None,
+        on_disk_populate_postings: None,
+        lock_in_ram: None,
         phrase_matching: Some(true), // Enable phrase matching
         stopwords: None,
         stemmer: None,
         ascii_folding: None,
+        unicode_normalization: None,
+        case_folding: None,
         enable_hnsw: None,
+        null_index: None,
     };
 
     let mut mutable_index =
@@ -322,11 +337,16 @@ fn test_ascii_folding_in_full_text_index_word() {
         max_token_len: None,
         lowercase: None,
         on_disk: None,
+        on_disk_populate_postings: None,
+        lock_in_ram: None,
         phrase_matching: None,
         stopwords: None,
         stemmer: None,
         ascii_folding: Some(true),
+        unicode_normalization: None,
+        case_folding: None,
         enable_hnsw: None,
+        null_index: None,
     };
     let config_disabled = TextIndexParams {
         ascii_folding: Some(false),
@@ -398,3 +418,344 @@ fn test_ascii_folding_in_full_text_index_word() {
         .collect();
     assert!(results_acento2.contains(&0));
 }
+
+#[test]
+fn test_facet_over_vocabulary() {
+    let hw_counter = HardwareCounterCell::default();
+
+    let temp_dir = Builder::new().prefix("test_dir").tempdir().unwrap();
+    let config = TextIndexParams {
+        lowercase: Some(true),
+        ..Default::default()
+    };
+
+    let mut mutable_index =
+        FullTextIndex::builder_gridstore(temp_dir.path().to_path_buf(), config.clone())
+            .make_empty()
+            .unwrap();
+
+    let mut mmap_builder =
+        FullTextIndex::builder_mmap(temp_dir.path().to_path_buf(), config.clone(), true);
+    mmap_builder.init().unwrap();
+
+    let documents = vec![
+        (0, "the quick brown fox jumps over the lazy dog".to_string()),
+        (1, "brown fox quick the jumps over lazy dog".to_string()),
+        (2, "quick brown fox runs fast".to_string()),
+        (3, "the lazy dog sleeps peacefully".to_string()),
+    ];
+
+    for (point_id, text) in documents {
+        mutable_index
+            .add_many(point_id, vec![text.clone()], &hw_counter)
+            .unwrap();
+        mmap_builder
+            .add_many(point_id, vec![text], &hw_counter)
+            .unwrap();
+    }
+
+    let mmap_index = mmap_builder.finalize().unwrap();
+
+    for mut index in [mutable_index, mmap_index] {
+        let count_of = |index: &FullTextIndex, token: &str| {
+            index
+                .iter_counts_per_value()
+                .find(|hit| hit.value == FacetValueRef::Keyword(token))
+                .map(|hit| hit.count)
+        };
+        assert_eq!(count_of(&index, "dog"), Some(3));
+        assert_eq!(count_of(&index, "fast"), Some(1));
+        assert_eq!(count_of(&index, "cat"), None);
+
+        let mut point_tokens: Vec<_> = index
+            .get_point_values(2)
+            .map(|value| value.to_owned())
+            .collect();
+        point_tokens.sort();
+        assert_eq!(
+            point_tokens,
+            ["brown", "fast", "fox", "quick", "runs"]
+                .map(|token| FacetValue::Keyword(token.into())),
+        );
+
+        index.remove_point(0).unwrap();
+
+        // Deleted points are not in the values map
+        let dog_points: Vec<_> = index
+            .iter_values_map(&hw_counter)
+            .find(|(value, _)| *value == FacetValueRef::Keyword("dog"))
+            .map(|(_, points)| points.collect())
+            .unwrap();
+        assert_eq!(dog_points, vec![1, 3]);
+        assert_eq!(index.get_point_values(0).count(), 0);
+
+        // Deleted points are not counted, tokens without points are skipped
+        assert_eq!(count_of(&index, "dog"), Some(2));
+        index.remove_point(2).unwrap();
+        assert_eq!(count_of(&index, "fast"), None);
+        assert_eq!(count_of(&index, "fox"), Some(1));
+    }
+}
+
+#[test]
+fn test_match_positions() {
+    let hw_counter = HardwareCounterCell::default();
+
+    let temp_dir = Builder::new().prefix("test_dir").tempdir().unwrap();
+    let config = TextIndexParams {
+        lowercase: Some(true),
+        phrase_matching: Some(true),
+        ..Default::default()
+    };
+
+    let mut mutable_index =
+        FullTextIndex::builder_gridstore(temp_dir.path().to_path_buf(), config.clone())
+            .make_empty()
+            .unwrap();
+
+    let mut mmap_builder =
+        FullTextIndex::builder_mmap(temp_dir.path().to_path_buf(), config.clone(), true);
+    mmap_builder.init().unwrap();
+
+    let documents = vec![
+        (0, "the quick brown fox jumps over the lazy dog".to_string()),
+        (1, "brown fox brown fox brown".to_string()),
+    ];
+
+    for (point_id, text) in documents {
+        mutable_index
+            .add_many(point_id, vec![text.clone()], &hw_counter)
+            .unwrap();
+        mmap_builder
+            .add_many(point_id, vec![text], &hw_counter)
+            .unwrap();
+    }
+
+    let mmap_index = mmap_builder.finalize().unwrap();
+
+    for index in [mutable_index, mmap_index] {
+        let text = Match::new_text("dog the");
+        assert_eq!(
+            index.text_match_positions(&text, 0, &hw_counter),
+            Some(vec![0, 6, 8]),
+        );
+        // Point doesn't match
+        assert_eq!(index.text_match_positions(&text, 1, &hw_counter), None);
+
+        // Only tokens of phrase occurrences are reported, overlapping ones once
+        let phrase = Match::new_phrase("brown fox brown");
+        assert_eq!(
+            index.text_match_positions(&phrase, 1, &hw_counter),
+            Some(vec![0, 1, 2, 3, 4]),
+        );
+        let phrase = Match::new_phrase("quick brown");
+        assert_eq!(
+            index.text_match_positions(&phrase, 0, &hw_counter),
+            Some(vec![1, 2]),
+        );
+        assert_eq!(index.text_match_positions(&phrase, 1, &hw_counter), None);
+    }
+}
+
+#[test]
+fn test_fuzzy_match() {
+    let hw_counter = HardwareCounterCell::default();
+
+    let temp_dir = Builder::new().prefix("test_dir").tempdir().unwrap();
+    let config = TextIndexParams {
+        lowercase: Some(true),
+        ..Default::default()
+    };
+
+    let mut mutable_index =
+        FullTextIndex::builder_gridstore(temp_dir.path().join("mutable"), config.clone())
+            .make_empty()
+            .unwrap();
+
+    let mut mmap_builder =
+        FullTextIndex::builder_mmap(temp_dir.path().join("mmap"), config.clone(), true);
+    mmap_builder.init().unwrap();
+
+    let mut ram_builder =
+        FullTextIndex::builder_mmap(temp_dir.path().join("ram"), config.clone(), false);
+    ram_builder.init().unwrap();
+
+    let documents = vec![
+        (0, "the quick brown fox".to_string()),
+        (1, "a quick brown dog".to_string()),
+        (2, "lazy dogs sleep".to_string()),
+        (3, "quantum physics".to_string()),
+    ];
+
+    for (point_id, text) in documents {
+        mutable_index
+            .add_many(point_id, vec![text.clone()], &hw_counter)
+            .unwrap();
+        mmap_builder
+            .add_many(point_id, vec![text.clone()], &hw_counter)
+            .unwrap();
+        ram_builder
+            .add_many(point_id, vec![text], &hw_counter)
+            .unwrap();
+    }
+
+    // Sorted vocabulary of mutable index picks up tokens added after it was built
+    assert!(mutable_index.parse_fuzzy_query("physicz").is_some());
+    assert!(mutable_index.parse_fuzzy_query("chemistrz").is_none());
+    mutable_index
+        .add_many(4, vec!["chemistry".to_string()], &hw_counter)
+        .unwrap();
+    assert!(mutable_index.parse_fuzzy_query("chemistrz").is_some());
+    mutable_index.remove_point(4).unwrap();
+
+    let mmap_index = mmap_builder.finalize().unwrap();
+    let ram_index = ram_builder.finalize().unwrap();
+
+    let expectations: [(&str, &[PointOffsetType]); 5] = [
+        // Every query token must match within allowed edits
+        ("Quik browm", &[0, 1]),
+        ("quik fox", &[0]),
+        // Expands into multiple vocabulary tokens
+        ("dogz", &[1, 2]),
+        // Short tokens are matched exactly
+        ("fx", &[]),
+        ("xyzzy", &[]),
+    ];
+
+    for index in [mutable_index, mmap_index, ram_index] {
+        for (text, expected) in expectations {
+            let condition = FieldCondition::new_match(
+                JsonPath::new("text"),
+                Match::TextFuzzy(MatchTextFuzzy {
+                    text_fuzzy: text.to_string(),
+                }),
+            );
+
+            let mut found: Vec<_> = index.filter(&condition, &hw_counter).unwrap().collect();
+            found.sort_unstable();
+            assert_eq!(found, expected, "query: {text}");
+
+            let cardinality = index.estimate_cardinality(&condition, &hw_counter).unwrap();
+            assert!(cardinality.min <= found.len() && found.len() <= cardinality.max);
+
+            let query = index.parse_fuzzy_query(text);
+            for point_id in 0..4 {
+                let is_match = query
+                    .as_ref()
+                    .is_some_and(|query| index.check_fuzzy_match(query, point_id));
+                assert_eq!(is_match, expected.contains(&point_id), "query: {text}");
+            }
+        }
+    }
+}
+
+#[test]
+fn test_ngram_phrase_matching() {
+    let hw_counter = HardwareCounterCell::default();
+
+    let temp_dir = Builder::new().prefix("test_dir").tempdir().unwrap();
+    let config = TextIndexParams {
+        tokenizer: TokenizerType::Ngram,
+        phrase_matching: Some(true),
+        ..Default::default()
+    };
+
+    let mut mutable_index =
+        FullTextIndex::builder_gridstore(temp_dir.path().join("mutable"), config.clone())
+            .make_empty()
+            .unwrap();
+
+    let mut mmap_builder =
+        FullTextIndex::builder_mmap(temp_dir.path().join("mmap"), config.clone(), true);
+    mmap_builder.init().unwrap();
+
+    let documents = vec![
+        (0, "東京都庁は新宿にある".to_string()),
+        (1, "新宿は東京都にある".to_string()),
+        (2, "京都の庁舎".to_string()),
+    ];
+
+    for (point_id, text) in documents {
+        mutable_index
+            .add_many(point_id, vec![text.clone()], &hw_counter)
+            .unwrap();
+        mmap_builder
+            .add_many(point_id, vec![text], &hw_counter)
+            .unwrap();
+    }
+
+    let mmap_index = mmap_builder.finalize().unwrap();
+
+    for index in [mutable_index, mmap_index] {
+        let find = |r#match: Match| {
+            let condition = FieldCondition::new_match(JsonPath::new("text"), r#match);
+            let mut found: Vec<_> = index.filter(&condition, &hw_counter).unwrap().collect();
+            found.sort_unstable();
+            found
+        };
+
+        // All n-grams of the text, in any order
+        assert_eq!(find(Match::new_text("東京都")), vec![0, 1]);
+        assert_eq!(find(Match::new_text("京都")), vec![0, 1, 2]);
+
+        // N-grams in sequence
+        assert_eq!(find(Match::new_phrase("東京都庁")), vec![0]);
+        assert_eq!(find(Match::new_phrase("新宿にある")), vec![0]);
+        assert_eq!(find(Match::new_phrase("都にある")), vec![1]);
+        assert_eq!(find(Match::new_phrase("京都の")), vec![2]);
+    }
+}
+
+#[test]
+fn test_compaction_replaces_index_dir() {
+    let hw_counter = HardwareCounterCell::default();
+
+    let temp_dir = Builder::new().prefix("test_dir").tempdir().unwrap();
+    let config = TextIndexParams {
+        lowercase: Some(true),
+        ..Default::default()
+    };
+
+    let filter_the = |index: &FullTextIndex| -> Vec<_> {
+        let query = index.parse_text_query("the", &hw_counter).unwrap();
+        index.filter_query(query, &hw_counter).collect()
+    };
+
+    for is_on_disk in [false, true] {
+        let index_dir = temp_dir.path().join(format!("title-text-{is_on_disk}"));
+        let mut mmap_builder =
+            FullTextIndex::builder_mmap(index_dir.clone(), config.clone(), is_on_disk);
+        mmap_builder.init().unwrap();
+        for (point_id, title) in movie_titles().into_iter().enumerate() {
+            mmap_builder
+                .add_many(point_id as PointOffsetType, vec![title], &hw_counter)
+                .unwrap();
+        }
+        let mut index = mmap_builder.finalize().unwrap();
+
+        for point_id in (0..movie_titles().len() as PointOffsetType).step_by(2) {
+            index.remove_point(point_id).unwrap();
+        }
+        let expected = filter_the(&index);
+
+        assert!(!index.compact_if_needed(0.6).unwrap());
+        assert!(index.compact_if_needed(0.1).unwrap());
+        assert_eq!(index.deleted_ratio(), 0.0);
+        assert_eq!(filter_the(&index), expected);
+        drop(index);
+
+        // Leftover of an interrupted compaction is removed on load
+        let staged_dir = common::fs::staged_dir_path(&index_dir);
+        fs::create_dir_all(&staged_dir).unwrap();
+
+        let index = FullTextIndex::new_mmap(index_dir, config.clone(), is_on_disk)
+            .unwrap()
+            .unwrap();
+        assert!(!staged_dir.exists());
+        assert_eq!(index.deleted_ratio(), 0.0);
+        assert_eq!(filter_the(&index), expected);
+    }
+
+    // Only the index directories are left
+    assert_eq!(fs::read_dir(temp_dir.path()).unwrap().count(), 2);
+}
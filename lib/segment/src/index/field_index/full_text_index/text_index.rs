@@ -13,7 +13,9 @@ use rocksdb::DB;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
+use super::fuzzy::{SortedVocab, auto_max_edits};
 use super::immutable_text_index::ImmutableFullTextIndex;
+use super::inverted_index::immutable_inverted_index::ImmutableInvertedIndex;
 use super::inverted_index::{InvertedIndex, ParsedQuery, TokenId, TokenSet};
 use super::mmap_text_index::{FullTextMmapIndexBuilder, MmapFullTextIndex};
 use super::mutable_text_index::MutableFullTextIndex;
@@ -24,16 +26,29 @@ use crate::common::operation_error::{OperationError, OperationResult};
 use crate::common::rocksdb_buffered_delete_wrapper::DatabaseColumnScheduledDeleteWrapper;
 #[cfg(feature = "rocksdb")]
 use crate::common::rocksdb_wrapper::DatabaseColumnWrapper;
+use crate::data_types::facets::{FacetHit, FacetValueRef};
 use crate::data_types::index::TextIndexParams;
+use crate::index::field_index::facet_index::FacetIndex;
 use crate::index::field_index::full_text_index::inverted_index::Document;
+use crate::index::field_index::map_index::IdIter;
 use crate::index::field_index::{
-    CardinalityEstimation, FieldIndexBuilderTrait, PayloadBlockCondition, PayloadFieldIndex,
-    ValueIndexer,
+    CardinalityEstimation, FieldIndexBuilderTrait, IndexResources, PayloadBlockCondition,
+    PayloadFieldIndex, ValueIndexer,
 };
 use crate::index::payload_config::{IndexMutability, StorageType};
 use crate::telemetry::PayloadIndexTelemetry;
-use crate::types::{FieldCondition, Match, MatchPhrase, MatchText, PayloadKeyType};
+use crate::types::{FieldCondition, Match, MatchPhrase, MatchText, MatchTextFuzzy, PayloadKeyType};
+
+/// Parsed [`Match::TextFuzzy`] query.
+///
+/// Each query token is expanded into similar tokens of the vocabulary, point matches if it
+/// contains any of the expansions of every query token.
+pub struct FuzzyQuery {
+    /// [`ParsedQuery::AnyTokens`] of expansions, one per query token
+    expansions: Vec<ParsedQuery>,
+}
 
+#[allow(clippy::large_enum_variant)]
 pub enum FullTextIndex {
     Mutable(MutableFullTextIndex),
     Immutable(ImmutableFullTextIndex),
@@ -168,6 +183,29 @@ impl FullTextIndex {
         }
     }
 
+    /// Iterate over tokens of the vocabulary with number of points containing them.
+    ///
+    /// Immutable index keeps deleted points in postings until compacted, so counts of it are
+    /// approximate.
+    pub fn vocab_with_postings_len(&self) -> Box<dyn Iterator<Item = (&str, usize)> + '_> {
+        match self {
+            Self::Mutable(index) => Box::new(index.inverted_index.vocab_with_postings_len_iter()),
+            Self::Immutable(index) => Box::new(index.inverted_index.vocab_with_postings_len_iter()),
+            Self::Mmap(index) => Box::new(index.inverted_index.vocab_with_postings_len_iter()),
+        }
+    }
+
+    /// Run `f` over the sorted vocabulary of the index.
+    ///
+    /// The vocabulary is sorted on first use, mutable index sorts it again after new tokens are added.
+    fn with_sorted_vocab<R>(&self, f: impl FnOnce(&SortedVocab) -> R) -> R {
+        match self {
+            Self::Mutable(index) => f(index.sorted_vocab()),
+            Self::Immutable(index) => f(index.sorted_vocab()),
+            Self::Mmap(index) => f(index.sorted_vocab()),
+        }
+    }
+
     fn get_tokenizer(&self) -> &Tokenizer {
         match self {
             Self::Mutable(index) => &index.tokenizer,
@@ -215,6 +253,94 @@ impl FullTextIndex {
         }
     }
 
+    pub fn check_fuzzy_match(&self, query: &FuzzyQuery, point_id: PointOffsetType) -> bool {
+        query
+            .expansions
+            .iter()
+            .all(|expansion| self.check_match(expansion, point_id))
+    }
+
+    fn filter_fuzzy_query<'a>(
+        &'a self,
+        query: FuzzyQuery,
+        condition: &FieldCondition,
+        hw_counter: &'a HardwareCounterCell,
+    ) -> Box<dyn Iterator<Item = PointOffsetType> + 'a> {
+        let FuzzyQuery { mut expansions } = query;
+
+        // Iterate over points of the most selective expansion, check the others
+        let Some((most_selective, _)) =
+            expansions.iter().enumerate().min_by_key(|(_, expansion)| {
+                self.estimate_query_cardinality(expansion, condition, hw_counter)
+                    .exp
+            })
+        else {
+            return Box::new(std::iter::empty());
+        };
+        let iterated = expansions.swap_remove(most_selective);
+
+        Box::new(
+            self.filter_query(iterated, hw_counter)
+                .filter(move |&point_id| {
+                    expansions
+                        .iter()
+                        .all(|expansion| self.check_match(expansion, point_id))
+                }),
+        )
+    }
+
+    fn estimate_fuzzy_query_cardinality(
+        &self,
+        query: &FuzzyQuery,
+        condition: &FieldCondition,
+        hw_counter: &HardwareCounterCell,
+    ) -> CardinalityEstimation {
+        let Some(mut estimation) = query
+            .expansions
+            .iter()
+            .map(|expansion| self.estimate_query_cardinality(expansion, condition, hw_counter))
+            .min_by_key(|estimation| estimation.exp)
+        else {
+            return CardinalityEstimation::exact(0);
+        };
+        // Other expansions may exclude any of the points
+        if query.expansions.len() > 1 {
+            estimation.min = 0;
+        }
+        estimation
+    }
+
+    /// Positions of document tokens which make the point match the query, see
+    /// [`InvertedIndex::match_positions`]
+    pub fn match_positions(
+        &self,
+        query: &ParsedQuery,
+        point_id: PointOffsetType,
+    ) -> Option<Vec<u32>> {
+        match self {
+            Self::Mutable(index) => index.inverted_index.match_positions(query, point_id),
+            Self::Immutable(index) => index.inverted_index.match_positions(query, point_id),
+            Self::Mmap(index) => index.inverted_index.match_positions(query, point_id),
+        }
+    }
+
+    /// Positions of tokens matched by a full-text `match` condition, for highlighting.
+    ///
+    /// Positions are only stored if the index has `phrase_matching` enabled.
+    pub fn text_match_positions(
+        &self,
+        r#match: &Match,
+        point_id: PointOffsetType,
+        hw_counter: &HardwareCounterCell,
+    ) -> Option<Vec<u32>> {
+        let parsed_query = match r#match {
+            Match::Text(MatchText { text }) => self.parse_text_query(text, hw_counter),
+            Match::Phrase(MatchPhrase { phrase }) => self.parse_phrase_query(phrase, hw_counter),
+            _ => None,
+        }?;
+        self.match_positions(&parsed_query, point_id)
+    }
+
     pub fn values_count(&self, point_id: PointOffsetType) -> usize {
         match self {
             Self::Mutable(index) => index.inverted_index.values_count(point_id),
@@ -275,6 +401,7 @@ impl FullTextIndex {
             points_values_count: self.points_count(),
             points_count: self.points_count(),
             histogram_bucket_size: None,
+            resources: None,
         }
     }
 
@@ -321,6 +448,35 @@ impl FullTextIndex {
         Some(ParsedQuery::AnyTokens(tokens))
     }
 
+    /// Tries to parse a fuzzy query. If any token has no similar tokens in the vocabulary,
+    /// returns `None`
+    ///
+    /// Allowed number of edits depends on the token length, see [`auto_max_edits`]
+    pub fn parse_fuzzy_query(&self, text: &str) -> Option<FuzzyQuery> {
+        let mut query_tokens = AHashSet::new();
+        self.get_tokenizer().tokenize_query(text, |token| {
+            query_tokens.insert(token);
+        });
+        if query_tokens.is_empty() {
+            return None;
+        }
+
+        let expansions = self.with_sorted_vocab(|vocab| {
+            query_tokens
+                .iter()
+                .map(|token| {
+                    let similar_tokens: TokenSet = vocab
+                        .fuzzy_matches(token, auto_max_edits(token))
+                        .into_iter()
+                        .collect();
+                    (!similar_tokens.is_empty()).then_some(ParsedQuery::AnyTokens(similar_tokens))
+                })
+                .collect::<Option<Vec<_>>>()
+        })?;
+
+        Some(FuzzyQuery { expansions })
+    }
+
     pub fn parse_tokenset(&self, text: &str, hw_counter: &HardwareCounterCell) -> TokenSet {
         let mut tokenset = AHashSet::new();
         self.get_tokenizer().tokenize_doc(text, |token| {
@@ -438,6 +594,32 @@ impl FullTextIndex {
         }
     }
 
+    /// Fraction of points in postings which are deleted, but not yet dropped from them
+    pub fn deleted_ratio(&self) -> f64 {
+        match self {
+            // Mutable index removes points from postings directly
+            FullTextIndex::Mutable(_) => 0.0,
+            FullTextIndex::Immutable(index) => index.deleted_ratio(),
+            FullTextIndex::Mmap(index) => index.deleted_ratio(),
+        }
+    }
+
+    /// Drop deleted points from postings of immutable index if their ratio exceeds
+    /// `deleted_threshold`. Returns whether compaction happened.
+    pub fn compact_if_needed(&mut self, deleted_threshold: f64) -> OperationResult<bool> {
+        match self {
+            FullTextIndex::Mutable(_) => Ok(false),
+            FullTextIndex::Immutable(index) => index.compact_if_needed(deleted_threshold),
+            FullTextIndex::Mmap(index) => {
+                if index.deleted_ratio() <= deleted_threshold {
+                    return Ok(false);
+                }
+                index.compact()?;
+                Ok(true)
+            }
+        }
+    }
+
     pub fn get_mutability_type(&self) -> IndexMutability {
         match self {
             FullTextIndex::Mutable(_) => IndexMutability::Mutable,
@@ -457,6 +639,64 @@ impl FullTextIndex {
     }
 }
 
+/// Inverted index merged from full-text indexes of several segments.
+///
+/// Used by segment builder to construct the full-text index of the new segment without
+/// re-tokenizing all documents, see [`FullTextMmapIndexBuilder::set_merged`].
+pub struct MergedTextIndex {
+    pub(super) inverted_index: ImmutableInvertedIndex,
+}
+
+impl MergedTextIndex {
+    /// Merge full-text indexes, `new_ids` of each source maps its point ids into point ids of the
+    /// merged index. Points mapped to `None` are dropped.
+    ///
+    /// Points of `previous` merged index, if any, keep their ids.
+    ///
+    /// Returns `None` if any of the sources is mutable, such index has to be rebuilt from payloads.
+    pub fn merge(
+        previous: Option<&MergedTextIndex>,
+        sources: &[(&FullTextIndex, &[Option<PointOffsetType>])],
+    ) -> OperationResult<Option<Self>> {
+        // Mmap indexes are loaded into memory to be merged
+        let mut loaded = Vec::new();
+        for (index, _) in sources {
+            match index {
+                FullTextIndex::Mutable(_) => return Ok(None),
+                FullTextIndex::Immutable(_) => {}
+                FullTextIndex::Mmap(index) => {
+                    loaded.push(ImmutableInvertedIndex::from(&index.inverted_index));
+                }
+            }
+        }
+
+        let previous_ids: Vec<Option<PointOffsetType>> = previous
+            .map(|previous| {
+                (0..previous.inverted_index.point_to_tokens_count.len() as PointOffsetType)
+                    .map(Some)
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let mut loaded = loaded.iter();
+        let inputs = previous
+            .map(|previous| (&previous.inverted_index, previous_ids.as_slice()))
+            .into_iter()
+            .chain(sources.iter().map(|(index, new_ids)| {
+                let inverted_index = match index {
+                    FullTextIndex::Immutable(index) => &index.inverted_index,
+                    FullTextIndex::Mmap(_) => loaded.next().expect("mmap index is loaded"),
+                    FullTextIndex::Mutable(_) => unreachable!("mutable indexes are not merged"),
+                };
+                (inverted_index, *new_ids)
+            }))
+            .collect::<Vec<_>>();
+
+        let inverted_index = ImmutableInvertedIndex::merge(&inputs)?;
+        Ok(Some(Self { inverted_index }))
+    }
+}
+
 #[cfg(feature = "rocksdb")]
 pub struct FullTextIndexRocksDbBuilder {
     mutable_index: MutableFullTextIndex,
@@ -553,6 +793,71 @@ impl ValueIndexer for FullTextIndex {
     }
 }
 
+impl FacetIndex for FullTextIndex {
+    /// Tokens are not stored per point, so this checks every token of the vocabulary.
+    /// Prefer [`FacetIndex::iter_values_map`] when possible.
+    fn get_point_values(
+        &self,
+        point_id: PointOffsetType,
+    ) -> impl Iterator<Item = FacetValueRef<'_>> + '_ {
+        let hw_counter = HardwareCounterCell::disposable();
+        let is_empty = self.values_is_empty(point_id);
+        self.vocab_with_postings_len()
+            .filter(move |_| !is_empty)
+            .filter(move |(token, _)| {
+                self.get_token(token, &hw_counter).is_some_and(|token_id| {
+                    let query = ParsedQuery::AnyTokens(TokenSet::from_iter([token_id]));
+                    self.check_match(&query, point_id)
+                })
+            })
+            .map(|(token, _)| FacetValueRef::Keyword(token))
+    }
+
+    fn iter_values(&self) -> impl Iterator<Item = FacetValueRef<'_>> + '_ {
+        self.vocab_with_postings_len()
+            .filter(|(_, posting_len)| *posting_len > 0)
+            .map(|(token, _)| FacetValueRef::Keyword(token))
+    }
+
+    fn iter_values_map<'a>(
+        &'a self,
+        hw_counter: &'a HardwareCounterCell,
+    ) -> impl Iterator<Item = (FacetValueRef<'a>, IdIter<'a>)> + 'a {
+        self.vocab_with_postings_len()
+            .filter(|(_, posting_len)| *posting_len > 0)
+            .filter_map(move |(token, _)| {
+                let token_id = self.get_token(token, hw_counter)?;
+                let query = ParsedQuery::AnyTokens(TokenSet::from_iter([token_id]));
+                Some((
+                    FacetValueRef::Keyword(token),
+                    self.filter_query(query, hw_counter),
+                ))
+            })
+    }
+
+    fn iter_counts_per_value(&self) -> impl Iterator<Item = FacetHit<FacetValueRef<'_>>> + '_ {
+        let hw_counter = HardwareCounterCell::disposable();
+        self.vocab_with_postings_len()
+            .filter(|(_, posting_len)| *posting_len > 0)
+            .filter_map(move |(token, posting_len)| {
+                let count = match self {
+                    // Removed points are dropped from the postings
+                    FullTextIndex::Mutable(_) => posting_len,
+                    // Removed points stay in the postings, so only count the remaining ones
+                    FullTextIndex::Immutable(_) | FullTextIndex::Mmap(_) => {
+                        let token_id = self.get_token(token, &hw_counter)?;
+                        let query = ParsedQuery::AnyTokens(TokenSet::from_iter([token_id]));
+                        self.filter_query(query, &hw_counter).count()
+                    }
+                };
+                (count > 0).then_some(FacetHit {
+                    value: FacetValueRef::Keyword(token),
+                    count,
+                })
+            })
+    }
+}
+
 impl PayloadFieldIndex for FullTextIndex {
     fn count_indexed_points(&self) -> usize {
         self.points_count()
@@ -590,6 +895,14 @@ impl PayloadFieldIndex for FullTextIndex {
         }
     }
 
+    fn estimate_resources(&self) -> OperationResult<IndexResources> {
+        match self {
+            Self::Mutable(index) => index.estimate_resources(),
+            Self::Immutable(index) => index.estimate_resources(),
+            Self::Mmap(index) => index.estimate_resources(),
+        }
+    }
+
     fn filter<'a>(
         &'a self,
         condition: &'a FieldCondition,
@@ -600,6 +913,12 @@ impl PayloadFieldIndex for FullTextIndex {
             Some(Match::Phrase(MatchPhrase { phrase })) => {
                 self.parse_phrase_query(phrase, hw_counter)
             }
+            Some(Match::TextFuzzy(MatchTextFuzzy { text_fuzzy })) => {
+                let Some(fuzzy_query) = self.parse_fuzzy_query(text_fuzzy) else {
+                    return Some(Box::new(std::iter::empty()));
+                };
+                return Some(self.filter_fuzzy_query(fuzzy_query, condition, hw_counter));
+            }
             _ => return None,
         };
 
@@ -620,6 +939,16 @@ impl PayloadFieldIndex for FullTextIndex {
             Some(Match::Phrase(MatchPhrase { phrase })) => {
                 self.parse_phrase_query(phrase, hw_counter)
             }
+            Some(Match::TextFuzzy(MatchTextFuzzy { text_fuzzy })) => {
+                let Some(fuzzy_query) = self.parse_fuzzy_query(text_fuzzy) else {
+                    return Some(CardinalityEstimation::exact(0));
+                };
+                return Some(self.estimate_fuzzy_query_cardinality(
+                    &fuzzy_query,
+                    condition,
+                    hw_counter,
+                ));
+            }
             _ => return None,
         };
 
@@ -0,0 +1,206 @@
+use std::path::{Path, PathBuf};
+
+use common::fs::{atomic_save_json, read_json};
+use serde::{Deserialize, Serialize};
+
+use crate::common::operation_error::OperationResult;
+use crate::data_types::index::{StemmingAlgorithm, TextIndexParams, TokenizerType};
+use crate::index::field_index::full_text_index::stop_words::StopwordsFilter;
+
+const TOKENIZER_CONFIG_FILE: &str = "tokenizer_config.json";
+
+/// Version of the persisted tokenizer config.
+///
+/// Bump whenever the tokenization pipeline changes in a way that produces different tokens for
+/// the same configuration, so that indexes built by older versions get rebuilt.
+const TOKENIZER_CONFIG_VERSION: u32 = 1;
+
+/// Tokenization pipeline an index was built with.
+///
+/// Tokens stored in the vocabulary are the output of this pipeline, so querying an index with a
+/// different pipeline (e.g. stemming enabled at query time but not at index time) silently
+/// produces wrong results. Persisting it next to the index allows detecting such mismatch on load.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StoredTokenizerConfig {
+    version: u32,
+    tokenizer: TokenizerType,
+    lowercase: bool,
+    ascii_folding: bool,
+    /// Absent in configs persisted before the option was introduced
+    #[serde(default)]
+    unicode_normalization: bool,
+    #[serde(default)]
+    case_folding: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    min_token_len: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    max_token_len: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    stemmer: Option<StemmingAlgorithm>,
+    /// Hash of the effective stopwords set, see [`StopwordsFilter::fingerprint`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    stopwords_hash: Option<String>,
+}
+
+impl StoredTokenizerConfig {
+    pub fn from_params(params: &TextIndexParams) -> Self {
+        let TextIndexParams {
+            r#type: _,
+            tokenizer,
+            min_token_len,
+            max_token_len,
+            lowercase,
+            ascii_folding,
+            unicode_normalization,
+            case_folding,
+            phrase_matching: _,
+            stopwords,
+            on_disk: _,
+            on_disk_populate_postings: _,
+            lock_in_ram: _,
+            stemmer,
+            enable_hnsw: _,
+            null_index: _,
+        } = params;
+
+        let lowercase = lowercase.unwrap_or(true);
+        let case_folding = case_folding.unwrap_or(false);
+        let stopwords_hash =
+            StopwordsFilter::new(stopwords, lowercase || case_folding).fingerprint();
+
+        Self {
+            version: TOKENIZER_CONFIG_VERSION,
+            tokenizer: *tokenizer,
+            lowercase,
+            ascii_folding: ascii_folding.unwrap_or(false),
+            unicode_normalization: unicode_normalization.unwrap_or(false),
+            case_folding,
+            min_token_len: *min_token_len,
+            max_token_len: *max_token_len,
+            stemmer: stemmer.clone(),
+            stopwords_hash,
+        }
+    }
+
+    pub fn path(dir: &Path) -> PathBuf {
+        dir.join(TOKENIZER_CONFIG_FILE)
+    }
+
+    pub fn save(&self, dir: &Path) -> OperationResult<()> {
+        atomic_save_json(&Self::path(dir), self)?;
+        Ok(())
+    }
+
+    /// Load persisted config from the index directory.
+    ///
+    /// Returns `None` for indexes built before the config was persisted.
+    pub fn load(dir: &Path) -> OperationResult<Option<Self>> {
+        let path = Self::path(dir);
+        if !path.is_file() {
+            return Ok(None);
+        }
+        Ok(Some(read_json(&path)?))
+    }
+
+    /// Check whether an index built with `self` can be queried with `expected` pipeline.
+    pub fn is_compatible_with(&self, expected: &Self) -> bool {
+        self == expected
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_types::index::{
+        Language, Snowball, SnowballLanguage, SnowballParams, StopwordsInterface,
+    };
+
+    fn english_stemmer() -> StemmingAlgorithm {
+        StemmingAlgorithm::Snowball(SnowballParams {
+            r#type: Snowball::Snowball,
+            language: SnowballLanguage::English,
+        })
+    }
+
+    #[test]
+    fn test_tokenizer_config_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+
+        assert_eq!(StoredTokenizerConfig::load(dir.path()).unwrap(), None);
+
+        let params = TextIndexParams {
+            stemmer: Some(english_stemmer()),
+            ..Default::default()
+        };
+        let config = StoredTokenizerConfig::from_params(&params);
+        config.save(dir.path()).unwrap();
+
+        let loaded = StoredTokenizerConfig::load(dir.path()).unwrap().unwrap();
+        assert_eq!(loaded, config);
+        assert!(loaded.is_compatible_with(&config));
+    }
+
+    #[test]
+    fn test_tokenizer_config_detects_stemmer_mismatch() {
+        let with_stemmer = StoredTokenizerConfig::from_params(&TextIndexParams {
+            stemmer: Some(english_stemmer()),
+            ..Default::default()
+        });
+        let without_stemmer = StoredTokenizerConfig::from_params(&TextIndexParams::default());
+
+        assert!(!with_stemmer.is_compatible_with(&without_stemmer));
+
+        // Storage-only options don't affect tokenization
+        let on_disk = StoredTokenizerConfig::from_params(&TextIndexParams {
+            on_disk: Some(true),
+            ..Default::default()
+        });
+        assert!(on_disk.is_compatible_with(&without_stemmer));
+    }
+
+    #[test]
+    fn test_tokenizer_config_detects_stopwords_mismatch() {
+        let english = StoredTokenizerConfig::from_params(&TextIndexParams {
+            stopwords: Some(StopwordsInterface::Language(Language::English)),
+            ..Default::default()
+        });
+        let custom = StoredTokenizerConfig::from_params(&TextIndexParams {
+            stopwords: Some(StopwordsInterface::new_custom(&["the", "a"])),
+            ..Default::default()
+        });
+        let custom_reordered = StoredTokenizerConfig::from_params(&TextIndexParams {
+            stopwords: Some(StopwordsInterface::new_custom(&["a", "the"])),
+            ..Default::default()
+        });
+        let none = StoredTokenizerConfig::from_params(&TextIndexParams::default());
+
+        assert!(!english.is_compatible_with(&custom));
+        assert!(!english.is_compatible_with(&none));
+        assert!(custom.is_compatible_with(&custom_reordered));
+    }
+
+    #[test]
+    fn test_tokenizer_config_detects_normalization_mismatch() {
+        let default = StoredTokenizerConfig::from_params(&TextIndexParams::default());
+        let normalized = StoredTokenizerConfig::from_params(&TextIndexParams {
+            unicode_normalization: Some(true),
+            ..Default::default()
+        });
+        let folded = StoredTokenizerConfig::from_params(&TextIndexParams {
+            case_folding: Some(true),
+            ..Default::default()
+        });
+
+        assert!(!normalized.is_compatible_with(&default));
+        assert!(!folded.is_compatible_with(&default));
+        assert!(!folded.is_compatible_with(&normalized));
+
+        // Configs persisted before the options were introduced are compatible with defaults
+        let mut legacy = serde_json::to_value(&default).unwrap();
+        let legacy_object = legacy.as_object_mut().unwrap();
+        legacy_object.remove("unicode_normalization");
+        legacy_object.remove("case_folding");
+        let legacy: StoredTokenizerConfig = serde_json::from_value(legacy).unwrap();
+        assert!(legacy.is_compatible_with(&default));
+    }
+}
@@ -0,0 +1,59 @@
+// Full case folding, as defined by Unicode `CaseFolding.txt` (statuses C and F).
+
+use std::borrow::Cow;
+
+use unicase::UniCase;
+
+pub fn fold_case_cow<'a>(input: Cow<'a, str>) -> Cow<'a, str> {
+    // Cheap check if already folded
+    if input
+        .bytes()
+        .all(|b| b.is_ascii() && !b.is_ascii_uppercase())
+    {
+        return input;
+    }
+
+    Cow::Owned(fold_case(input.as_ref()))
+}
+
+pub fn fold_case(input: &str) -> String {
+    UniCase::new(input).to_folded_case()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fold_case() {
+        assert_eq!(fold_case("Straße STRASSE"), "strasse strasse");
+        assert_eq!(
+            fold_case("\u{03A3}\u{038A}\u{03A3}\u{03A5}\u{03A6}\u{039F}\u{03A3}"), // ΣΊΣΥΦΟΣ
+            fold_case("\u{03C3}\u{03AF}\u{03C3}\u{03C5}\u{03C6}\u{03BF}\u{03C2}"), // σίσυφος
+        );
+        assert_eq!(fold_case("\u{FB01}le"), "file");
+        assert_eq!(fold_case("\u{0130}"), "i\u{0307}"); // İ
+        // ᾈᾳ
+        assert_eq!(
+            fold_case("\u{1F88}\u{1FB3}"),
+            "\u{1F00}\u{03B9}\u{03B1}\u{03B9}"
+        );
+        // Cherokee small letters fold to capital ones, capital ones are kept
+        assert_eq!(
+            fold_case("\u{AB70}\u{13F8} \u{13A0}"),
+            "\u{13A0}\u{13F0} \u{13A0}"
+        );
+    }
+
+    #[test]
+    fn test_fold_case_cow_borrows_folded() {
+        assert!(matches!(
+            fold_case_cow(Cow::Borrowed("hello world")),
+            Cow::Borrowed(_)
+        ));
+        assert!(matches!(
+            fold_case_cow(Cow::Borrowed("Hello")),
+            Cow::Owned(_)
+        ));
+    }
+}
@@ -60,12 +60,9 @@ impl JapaneseTokenizer {
                 continue;
             }
 
-            let surface = if tokens_processor.lowercase {
-                Cow::Owned(surface.to_lowercase())
-            } else {
-                Cow::Owned(surface.to_string())
-            };
-            cb(surface);
+            let surface = tokens_processor.normalize_if_enabled(Cow::Borrowed(surface));
+            let surface = tokens_processor.lowercase_if_enabled(surface);
+            cb(Cow::Owned(surface.into_owned()));
         }
     }
 }
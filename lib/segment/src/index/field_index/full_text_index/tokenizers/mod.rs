@@ -1,12 +1,15 @@
 use std::borrow::Cow;
 use std::sync::Arc;
 mod ascii_folding;
+mod case_folding;
 mod japanese;
 mod multilingual;
+mod ngram;
 mod stemmer;
 pub mod tokens_processor;
 
 use multilingual::MultilingualTokenizer;
+use ngram::NgramTokenizer;
 pub use stemmer::Stemmer;
 pub use tokens_processor::TokensProcessor;
 
@@ -104,13 +107,13 @@ impl PrefixTokenizer {
         text.split(|c| !char::is_alphanumeric(c))
             .filter(|token| !token.is_empty())
             .for_each(|word| {
+                let word_cow = tokens_processor.normalize_if_enabled(Cow::Borrowed(word));
+
                 // Apply ASCII folding if enabled
-                let mut word_cow = tokens_processor.fold_if_enabled(Cow::Borrowed(word));
+                let word_cow = tokens_processor.fold_if_enabled(word_cow);
 
-                // Handle lowercase
-                if tokens_processor.lowercase {
-                    word_cow = Cow::Owned(word_cow.to_lowercase());
-                }
+                // Handle lowercase or case folding
+                let word_cow = tokens_processor.lowercase_if_enabled(word_cow);
 
                 let word_cow = tokens_processor.stem_if_enabled(word_cow);
 
@@ -168,18 +171,25 @@ impl Tokenizer {
             max_token_len,
             lowercase,
             ascii_folding,
+            unicode_normalization,
+            case_folding,
             on_disk: _,
+            on_disk_populate_postings: _,
+            lock_in_ram: _,
             phrase_matching: _,
             stopwords,
             stemmer,
             enable_hnsw: _,
+            null_index: _,
         } = params;
 
         let lowercase = lowercase.unwrap_or(true);
         let ascii_folding = ascii_folding.unwrap_or(false);
-        let stopwords_filter = Arc::new(StopwordsFilter::new(stopwords, lowercase));
+        let case_folding = case_folding.unwrap_or(false);
+        // Stopwords are compared with processed tokens, so they must be lowercase if tokens are
+        let stopwords_filter = Arc::new(StopwordsFilter::new(stopwords, lowercase || case_folding));
 
-        let tokens_processor = TokensProcessor::new(
+        let mut tokens_processor = TokensProcessor::new(
             lowercase,
             ascii_folding,
             stopwords_filter,
@@ -187,6 +197,8 @@ impl Tokenizer {
             *min_token_len,
             *max_token_len,
         );
+        tokens_processor.unicode_normalization = unicode_normalization.unwrap_or(false);
+        tokens_processor.case_folding = case_folding;
 
         Self::new(*tokenizer, tokens_processor)
     }
@@ -210,6 +222,9 @@ impl Tokenizer {
             TokenizerType::Prefix => {
                 PrefixTokenizer::tokenize(text, &self.tokens_processor, callback)
             }
+            TokenizerType::Ngram => {
+                NgramTokenizer::tokenize(text, &self.tokens_processor, callback)
+            }
         }
     }
 
@@ -225,6 +240,9 @@ impl Tokenizer {
             TokenizerType::Prefix => {
                 PrefixTokenizer::tokenize_query(text, &self.tokens_processor, callback)
             }
+            TokenizerType::Ngram => {
+                NgramTokenizer::tokenize(text, &self.tokens_processor, callback)
+            }
         }
     }
 }
@@ -418,11 +436,16 @@ mod tests {
             max_token_len: Some(4),
             lowercase: Some(true),
             ascii_folding: None,
+            unicode_normalization: None,
+            case_folding: None,
             on_disk: None,
+            on_disk_populate_postings: None,
+            lock_in_ram: None,
             phrase_matching: None,
             stopwords: None,
             stemmer: None,
             enable_hnsw: None,
+            null_index: None,
         };
 
         let tokenizer = Tokenizer::new_from_text_index_params(&params);
@@ -451,11 +474,16 @@ mod tests {
             max_token_len: None,
             lowercase: Some(true),
             ascii_folding: None,
+            unicode_normalization: None,
+            case_folding: None,
             on_disk: None,
+            on_disk_populate_postings: None,
+            lock_in_ram: None,
             phrase_matching: None,
             stopwords: Some(StopwordsInterface::Language(Language::English)),
             stemmer: None,
             enable_hnsw: None,
+            null_index: None,
         };
 
         let tokenizer = Tokenizer::new_from_text_index_params(&params);
@@ -495,11 +523,16 @@ mod tests {
                 max_token_len: None,
                 lowercase: Some(true),
                 ascii_folding: None,
+                unicode_normalization: None,
+                case_folding: None,
                 on_disk: None,
+                on_disk_populate_postings: None,
+                lock_in_ram: None,
                 phrase_matching: None,
                 stopwords: Some(StopwordsInterface::Language(Language::English)),
                 stemmer: None,
                 enable_hnsw: None,
+                null_index: None,
             };
 
             let tokenizer = Tokenizer::new_from_text_index_params(&params);
@@ -529,7 +562,11 @@ mod tests {
             max_token_len: None,
             lowercase: Some(true),
             ascii_folding: None,
+            unicode_normalization: None,
+            case_folding: None,
             on_disk: None,
+            on_disk_populate_postings: None,
+            lock_in_ram: None,
             phrase_matching: None,
             stopwords: Some(StopwordsInterface::new_set(
                 &[Language::English],
@@ -537,6 +574,7 @@ mod tests {
             )),
             stemmer: None,
             enable_hnsw: None,
+            null_index: None,
         };
 
         let tokenizer = Tokenizer::new_from_text_index_params(&params);
@@ -569,11 +607,16 @@ mod tests {
             max_token_len: None,
             lowercase: Some(true),
             ascii_folding: None,
+            unicode_normalization: None,
+            case_folding: None,
             on_disk: None,
+            on_disk_populate_postings: None,
+            lock_in_ram: None,
             phrase_matching: None,
             stopwords: Some(StopwordsInterface::new_custom(&["as", "the", "a"])),
             stemmer: None,
             enable_hnsw: None,
+            null_index: None,
         };
 
         let tokenizer = Tokenizer::new_from_text_index_params(&params);
@@ -609,11 +652,16 @@ mod tests {
             max_token_len: None,
             lowercase: Some(true),
             ascii_folding: None,
+            unicode_normalization: None,
+            case_folding: None,
             on_disk: None,
+            on_disk_populate_postings: None,
+            lock_in_ram: None,
             phrase_matching: None,
             stopwords: Some(StopwordsInterface::Language(Language::English)),
             stemmer: None,
             enable_hnsw: None,
+            null_index: None,
         };
 
         let tokenizer = Tokenizer::new_from_text_index_params(&params);
@@ -646,7 +694,11 @@ mod tests {
             max_token_len: None,
             lowercase: Some(true),
             ascii_folding: None,
+            unicode_normalization: None,
+            case_folding: None,
             on_disk: None,
+            on_disk_populate_postings: None,
+            lock_in_ram: None,
             phrase_matching: None,
             stopwords: Some(StopwordsInterface::new_set(
                 &[Language::English, Language::Spanish],
@@ -654,6 +706,7 @@ mod tests {
             )),
             stemmer: None,
             enable_hnsw: None,
+            null_index: None,
         };
 
         let tokenizer = Tokenizer::new_from_text_index_params(&params);
@@ -692,11 +745,16 @@ mod tests {
             max_token_len: None,
             lowercase: Some(false), // Case sensitivity is enabled
             ascii_folding: None,
+            unicode_normalization: None,
+            case_folding: None,
             on_disk: None,
+            on_disk_populate_postings: None,
+            lock_in_ram: None,
             phrase_matching: None,
             stopwords: Some(StopwordsInterface::new_custom(&["the", "The", "LAZY"])),
             stemmer: None,
             enable_hnsw: None,
+            null_index: None,
         };
 
         let tokenizer = Tokenizer::new_from_text_index_params(&params);
@@ -741,11 +799,16 @@ mod tests {
             max_token_len: None,
             lowercase: Some(true),
             ascii_folding: Some(false),
+            unicode_normalization: None,
+            case_folding: None,
             on_disk: None,
+            on_disk_populate_postings: None,
+            lock_in_ram: None,
             phrase_matching: None,
             stopwords: None,
             stemmer: None,
             enable_hnsw: None,
+            null_index: None,
         };
         let tokenizer_disabled = Tokenizer::new_from_text_index_params(&params_disabled);
         let mut tokens_disabled = Vec::new();
@@ -760,11 +823,16 @@ mod tests {
             max_token_len: None,
             lowercase: Some(true),
             ascii_folding: Some(true),
+            unicode_normalization: None,
+            case_folding: None,
             on_disk: None,
+            on_disk_populate_postings: None,
+            lock_in_ram: None,
             phrase_matching: None,
             stopwords: None,
             stemmer: None,
             enable_hnsw: None,
+            null_index: None,
         };
         let tokenizer_enabled = Tokenizer::new_from_text_index_params(&params_enabled);
         let mut tokens_enabled = Vec::new();
@@ -772,6 +840,39 @@ mod tests {
         assert_eq!(tokens_enabled, expected_enabled);
     }
 
+    #[test]
+    fn test_unicode_normalization_and_case_folding() {
+        let text = "ＱＤＲＡＮＴ Straße ﬁle";
+
+        let tokenize = |unicode_normalization, case_folding| {
+            let params = TextIndexParams {
+                unicode_normalization: Some(unicode_normalization),
+                case_folding: Some(case_folding),
+                ..Default::default()
+            };
+            let tokenizer = Tokenizer::new_from_text_index_params(&params);
+            let mut tokens = Vec::new();
+            tokenizer.tokenize_doc(text, |token| tokens.push(token.into_owned()));
+            tokens
+        };
+
+        assert_eq!(tokenize(false, false), ["ｑｄｒａｎｔ", "straße", "ﬁle"]);
+        assert_eq!(tokenize(true, false), ["qdrant", "straße", "file"]);
+        assert_eq!(tokenize(false, true), ["ｑｄｒａｎｔ", "strasse", "file"]);
+        assert_eq!(tokenize(true, true), ["qdrant", "strasse", "file"]);
+
+        // Query is processed the same way as documents
+        let params = TextIndexParams {
+            unicode_normalization: Some(true),
+            case_folding: Some(true),
+            ..Default::default()
+        };
+        let tokenizer = Tokenizer::new_from_text_index_params(&params);
+        let mut tokens = Vec::new();
+        tokenizer.tokenize_query("STRASSE Ｑdrant", |token| tokens.push(token.into_owned()));
+        assert_eq!(tokens, ["strasse", "qdrant"]);
+    }
+
     #[test]
     fn test_ascii_folding_prefix_tokenizer() {
         let text = "ação";
@@ -0,0 +1,152 @@
+use std::borrow::Cow;
+
+use super::TokensProcessor;
+
+/// Size of n-grams, if `max_token_len` is not configured
+const DEFAULT_NGRAM_SIZE: usize = 2;
+
+/// Splits text into words and emits overlapping character n-grams of each word.
+///
+/// N-grams are emitted in order of their offsets, so consecutive n-grams get consecutive positions
+/// in the document, and phrase matching keeps working: phrase `"東京都"` is tokenized into
+/// `["東京", "京都"]`, which occur in sequence in any document containing it.
+///
+/// Words shorter than n-gram size are emitted as is.
+pub struct NgramTokenizer;
+
+impl NgramTokenizer {
+    pub fn tokenize<'a, C: FnMut(Cow<'a, str>)>(
+        text: &'a str,
+        tokens_processor: &TokensProcessor,
+        mut callback: C,
+    ) {
+        let ngram_size = tokens_processor
+            .max_token_len
+            .unwrap_or(DEFAULT_NGRAM_SIZE)
+            .max(1);
+
+        for word in text.split(|c| !char::is_alphanumeric(c)) {
+            for segment in segment_word(word) {
+                for ngram in char_ngrams(segment, ngram_size) {
+                    // `max_token_len` is the n-gram size, shorter words are still indexed
+                    let Some(token_cow) = tokens_processor.process_token(ngram, false) else {
+                        continue;
+                    };
+
+                    callback(token_cow);
+                }
+            }
+        }
+    }
+}
+
+/// Word segmentation hook, splits a word into parts which are n-grammed independently.
+///
+/// CJK scripts don't separate words with whitespace, so a "word" is often a whole sentence, and
+/// n-grams are the segmentation. Mixed-script words (e.g. `"Qdrantのコード"`) are split at the
+/// script boundary, so that n-grams don't span both scripts.
+fn segment_word(word: &str) -> impl Iterator<Item = &str> {
+    let mut rest = word;
+    std::iter::from_fn(move || {
+        let first = rest.chars().next()?;
+        let first_is_cjk = is_cjk(first);
+        let end = rest
+            .char_indices()
+            .find(|(_, c)| is_cjk(*c) != first_is_cjk)
+            .map_or(rest.len(), |(i, _)| i);
+        let (segment, tail) = rest.split_at(end);
+        rest = tail;
+        Some(segment)
+    })
+}
+
+/// Overlapping n-grams of `n` chars, text shorter than `n` chars is a single n-gram
+fn char_ngrams(text: &str, n: usize) -> impl Iterator<Item = &str> {
+    let boundaries: Vec<usize> = text
+        .char_indices()
+        .map(|(i, _)| i)
+        .chain(std::iter::once(text.len()))
+        .collect();
+    let chars_count = boundaries.len() - 1;
+    let ngrams_count = chars_count.saturating_sub(n) + 1;
+
+    (0..ngrams_count)
+        .map(move |start| &text[boundaries[start]..boundaries[(start + n).min(chars_count)]])
+}
+
+/// Whether char belongs to a CJK script: Han ideographs, Hiragana, Katakana, Hangul or Bopomofo
+fn is_cjk(c: char) -> bool {
+    matches!(
+        c,
+        '\u{1100}'..='\u{11FF}' // Hangul Jamo
+            | '\u{2E80}'..='\u{2FDF}' // CJK and Kangxi radicals
+            | '\u{3040}'..='\u{30FF}' // Hiragana, Katakana
+            | '\u{3100}'..='\u{318F}' // Bopomofo, Hangul compatibility Jamo
+            | '\u{31F0}'..='\u{31FF}' // Katakana phonetic extensions
+            | '\u{3400}'..='\u{4DBF}' // CJK unified ideographs extension A
+            | '\u{4E00}'..='\u{9FFF}' // CJK unified ideographs
+            | '\u{AC00}'..='\u{D7AF}' // Hangul syllables
+            | '\u{F900}'..='\u{FAFF}' // CJK compatibility ideographs
+            | '\u{FF66}'..='\u{FF9F}' // Halfwidth Katakana
+            | '\u{20000}'..='\u{3134F}' // CJK unified ideographs extensions B-G
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tokenize(text: &str, tokens_processor: &TokensProcessor) -> Vec<String> {
+        let mut tokens = Vec::new();
+        NgramTokenizer::tokenize(text, tokens_processor, |token| {
+            tokens.push(token.into_owned())
+        });
+        tokens
+    }
+
+    #[test]
+    fn test_ngram_tokenizer_cjk() {
+        let tokens_processor = TokensProcessor::default();
+
+        assert_eq!(
+            tokenize("東京都に住む", &tokens_processor),
+            ["東京", "京都", "都に", "に住", "住む"],
+        );
+        // Punctuation separates words, single char words are kept
+        assert_eq!(
+            tokenize("今天是星期一。好", &tokens_processor),
+            ["今天", "天是", "是星", "星期", "期一", "好"],
+        );
+        // N-grams don't span script boundaries
+        assert_eq!(
+            tokenize("Qdrantのコード", &tokens_processor),
+            ["Qd", "dr", "ra", "an", "nt", "のコ", "コー", "ード"],
+        );
+    }
+
+    #[test]
+    fn test_ngram_tokenizer_size() {
+        let tokens_processor =
+            TokensProcessor::new(true, false, Default::default(), None, Some(2), Some(3));
+
+        assert_eq!(
+            tokenize("Hello, a мир!", &tokens_processor),
+            ["hel", "ell", "llo", "мир"],
+        );
+    }
+
+    #[test]
+    fn test_ngram_tokenizer_phrase_positions() {
+        let tokens_processor = TokensProcessor::default();
+
+        let document = tokenize("東京都庁は新宿にある", &tokens_processor);
+        let phrase = tokenize("都庁は新宿", &tokens_processor);
+
+        // Phrase n-grams occur in the document as a contiguous sequence
+        assert!(
+            document
+                .windows(phrase.len())
+                .any(|window| window == phrase.as_slice())
+        );
+    }
+}
@@ -1,6 +1,8 @@
 use std::borrow::Cow;
 use std::sync::Arc;
 
+use unicode_normalization::{IsNormalized, UnicodeNormalization, is_nfkc_quick};
+
 use super::stemmer::Stemmer;
 use crate::index::field_index::full_text_index::stop_words::StopwordsFilter;
 
@@ -9,6 +11,10 @@ use crate::index::field_index::full_text_index::stop_words::StopwordsFilter;
 pub struct TokensProcessor {
     pub lowercase: bool,
     pub ascii_folding: bool,
+    /// Apply NFKC normalization to tokens
+    pub unicode_normalization: bool,
+    /// Apply full case folding instead of lowercasing
+    pub case_folding: bool,
     stopwords_filter: Arc<StopwordsFilter>, // TDOO(rocksdb): Remove once rocksdb has been removed!
     stemmer: Option<Stemmer>,
     pub min_token_len: Option<usize>,
@@ -27,6 +33,8 @@ impl TokensProcessor {
         Self {
             lowercase,
             ascii_folding,
+            unicode_normalization: false,
+            case_folding: false,
             stopwords_filter,
             stemmer,
             min_token_len,
@@ -58,6 +66,26 @@ impl TokensProcessor {
         }
     }
 
+    /// Applies NFKC normalization if enabled, e.g. full-width `"ＡＢＣ"` becomes `"ABC"`.
+    pub fn normalize_if_enabled<'a>(&self, input: Cow<'a, str>) -> Cow<'a, str> {
+        if self.unicode_normalization && is_nfkc_quick(input.chars()) != IsNormalized::Yes {
+            Cow::Owned(input.nfkc().collect())
+        } else {
+            input
+        }
+    }
+
+    /// Applies case folding or lowercasing, if enabled. Case folding takes precedence.
+    pub fn lowercase_if_enabled<'a>(&self, input: Cow<'a, str>) -> Cow<'a, str> {
+        if self.case_folding {
+            super::case_folding::fold_case_cow(input)
+        } else if self.lowercase {
+            Cow::Owned(input.to_lowercase())
+        } else {
+            input
+        }
+    }
+
     pub fn is_stopword(&self, token: &str) -> bool {
         self.stopwords_filter.is_stopword(token)
     }
@@ -68,27 +96,30 @@ impl TokensProcessor {
         check_max_len: bool,
     ) -> Option<Cow<'a, str>> {
         let Self {
-            lowercase,
+            lowercase: _,
             stopwords_filter,
             stemmer,
             min_token_len,
             max_token_len,
             ascii_folding,
+            unicode_normalization: _,
+            case_folding: _,
         } = self;
 
         if token_cow.is_empty() {
             return None;
         }
 
+        // Handle Unicode normalization, before any other transformation
+        token_cow = self.normalize_if_enabled(token_cow);
+
         // Handle ASCII folding (normalize accents)
         if *ascii_folding {
             token_cow = super::ascii_folding::fold_to_ascii_cow(token_cow);
         }
 
-        // Handle lowercase
-        if *lowercase {
-            token_cow = Cow::Owned(token_cow.to_lowercase());
-        }
+        // Handle lowercase or case folding
+        token_cow = self.lowercase_if_enabled(token_cow);
 
         // Handle stopwords
         if stopwords_filter.is_stopword(&token_cow) {
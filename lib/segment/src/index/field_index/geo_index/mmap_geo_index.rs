@@ -11,13 +11,14 @@ use fs_err as fs;
 use memmap2::MmapMut;
 use serde::{Deserialize, Serialize};
 
+use super::mmap_rtree::{GeoRect, MmapGeoRTree};
 use super::mutable_geo_index::InMemoryGeoMapIndex;
 use crate::common::Flusher;
 use crate::common::mmap_bitslice_buffered_update_wrapper::MmapBitSliceBufferedUpdateWrapper;
 use crate::common::operation_error::{OperationError, OperationResult};
 use crate::index::field_index::geo_hash::GeoHash;
 use crate::index::field_index::mmap_point_to_values::MmapPointToValues;
-use crate::types::GeoPoint;
+use crate::types::{GeoPoint, GeoPolygon};
 
 const DELETED_PATH: &str = "deleted.bin";
 const COUNTS_PER_HASH: &str = "counts_per_hash.bin";
@@ -77,6 +78,9 @@ pub(super) struct Storage {
     pub(super) point_to_values: MmapPointToValues<GeoPoint>,
     /// Deleted flags for each PointOffsetType
     pub(super) deleted: MmapBitSliceBufferedUpdateWrapper,
+    /// R-tree over point values, for polygon filters.
+    /// Missing in indexes built before it was introduced.
+    pub(super) rtree: Option<MmapGeoRTree>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -109,6 +113,19 @@ impl MmapGeoMapIndex {
                 .map(|(idx, values)| (idx as PointOffsetType, values.iter().cloned())),
         )?;
 
+        MmapGeoRTree::build(
+            path,
+            dynamic_index
+                .point_to_values
+                .iter()
+                .enumerate()
+                .flat_map(|(idx, values)| {
+                    values
+                        .iter()
+                        .map(move |value| (idx as PointOffsetType, *value))
+                }),
+        )?;
+
         {
             let points_map_file = create_and_ensure_length(
                 &points_map_path,
@@ -235,6 +252,7 @@ impl MmapGeoMapIndex {
             )?)?
         };
         let point_to_values = MmapPointToValues::open(path, true)?;
+        let rtree = MmapGeoRTree::open(path, populate)?;
 
         let deleted = open_write_mmap(&deleted_path, AdviceSetting::Global, populate)?;
         let deleted = MmapBitSlice::from(deleted, 0);
@@ -248,6 +266,7 @@ impl MmapGeoMapIndex {
                 points_map_ids,
                 point_to_values,
                 deleted: MmapBitSliceBufferedUpdateWrapper::new(deleted),
+                rtree,
             },
             deleted_count,
             points_values_count: stats.points_values_count,
@@ -360,6 +379,9 @@ impl MmapGeoMapIndex {
             self.path.join(STATS_PATH),
         ];
         files.extend(self.storage.point_to_values.files());
+        if let Some(rtree) = &self.storage.rtree {
+            files.extend(rtree.files());
+        }
         files
     }
 
@@ -371,6 +393,9 @@ impl MmapGeoMapIndex {
             self.path.join(STATS_PATH),
         ];
         files.extend(self.storage.point_to_values.immutable_files());
+        if let Some(rtree) = &self.storage.rtree {
+            files.extend(rtree.files());
+        }
         files
     }
 
@@ -415,6 +440,25 @@ impl MmapGeoMapIndex {
             .flatten()
     }
 
+    /// Returns point IDs with a value within the bounding rectangle of the polygon, found with
+    /// the R-tree. Values still have to be checked against the polygon itself.
+    ///
+    /// Returns `None` if the index has no R-tree.
+    pub fn polygon_candidates(
+        &self,
+        polygon: &GeoPolygon,
+        hw_counter: &HardwareCounterCell,
+    ) -> Option<impl Iterator<Item = PointOffsetType> + '_> {
+        let rtree = self.storage.rtree.as_ref()?;
+        let hw_counter = self.make_conditioned_counter(hw_counter);
+        let candidates = rtree.query(&GeoRect::of_polygon(polygon), &hw_counter);
+        Some(
+            candidates
+                .into_iter()
+                .filter(|idx| !self.storage.deleted.get(*idx as usize).unwrap_or(true)),
+        )
+    }
+
     pub fn points_count(&self) -> usize {
         self.storage
             .point_to_values
@@ -448,6 +492,9 @@ impl MmapGeoMapIndex {
         self.storage.points_map.populate()?;
         self.storage.points_map_ids.populate()?;
         self.storage.point_to_values.populate();
+        if let Some(rtree) = &self.storage.rtree {
+            rtree.populate();
+        }
         Ok(())
     }
 
@@ -464,6 +511,9 @@ impl MmapGeoMapIndex {
         clear_disk_cache(&points_map_ids_path)?;
 
         self.storage.point_to_values.clear_cache()?;
+        if let Some(rtree) = &self.storage.rtree {
+            rtree.clear_cache()?;
+        }
 
         Ok(())
     }
@@ -0,0 +1,408 @@
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use common::counter::conditioned_counter::ConditionedCounter;
+use common::fs::{atomic_save, clear_disk_cache};
+use common::mmap::{AdviceSetting, Madviseable, open_read_mmap};
+use common::types::PointOffsetType;
+use memmap2::Mmap;
+
+use crate::common::operation_error::{OperationError, OperationResult};
+use crate::types::{GeoPoint, GeoPolygon};
+
+const RTREE_PATH: &str = "rtree.bin";
+
+const RTREE_MAGIC: &[u8; 4] = b"grtr";
+const RTREE_VERSION: u32 = 1;
+/// Magic, version, number of entries, nodes and leaf nodes
+const RTREE_HEADER_SIZE: usize = 32;
+
+/// Max number of children of a node
+const NODE_CAPACITY: usize = 16;
+/// Bounding rectangle and range of children
+const NODE_SIZE: usize = 4 * size_of::<f64>() + 2 * size_of::<u32>();
+/// Longitude, latitude and point offset
+const ENTRY_SIZE: usize = 2 * size_of::<f64>() + size_of::<PointOffsetType>();
+
+/// Rectangle in longitude/latitude coordinates, bounds are inclusive.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct GeoRect {
+    pub min_lon: f64,
+    pub min_lat: f64,
+    pub max_lon: f64,
+    pub max_lat: f64,
+}
+
+impl GeoRect {
+    const EMPTY: Self = Self {
+        min_lon: f64::INFINITY,
+        min_lat: f64::INFINITY,
+        max_lon: f64::NEG_INFINITY,
+        max_lat: f64::NEG_INFINITY,
+    };
+
+    /// Bounding rectangle of the polygon, its interiors are within the exterior.
+    pub fn of_polygon(polygon: &GeoPolygon) -> Self {
+        polygon
+            .exterior
+            .points
+            .iter()
+            .fold(Self::EMPTY, |rect, point| rect.with_point(point))
+    }
+
+    fn with_point(self, point: &GeoPoint) -> Self {
+        Self {
+            min_lon: self.min_lon.min(point.lon.0),
+            min_lat: self.min_lat.min(point.lat.0),
+            max_lon: self.max_lon.max(point.lon.0),
+            max_lat: self.max_lat.max(point.lat.0),
+        }
+    }
+
+    fn with_rect(self, other: &Self) -> Self {
+        Self {
+            min_lon: self.min_lon.min(other.min_lon),
+            min_lat: self.min_lat.min(other.min_lat),
+            max_lon: self.max_lon.max(other.max_lon),
+            max_lat: self.max_lat.max(other.max_lat),
+        }
+    }
+
+    fn intersects(&self, other: &Self) -> bool {
+        self.min_lon <= other.max_lon
+            && other.min_lon <= self.max_lon
+            && self.min_lat <= other.max_lat
+            && other.min_lat <= self.max_lat
+    }
+
+    fn contains(&self, lon: f64, lat: f64) -> bool {
+        (self.min_lon..=self.max_lon).contains(&lon) && (self.min_lat..=self.max_lat).contains(&lat)
+    }
+
+    fn center(&self) -> (f64, f64) {
+        (
+            (self.min_lon + self.max_lon) / 2.0,
+            (self.min_lat + self.max_lat) / 2.0,
+        )
+    }
+}
+
+struct Node {
+    rect: GeoRect,
+    /// Range of children, entries for leaf nodes and nodes otherwise
+    children_start: u32,
+    children_end: u32,
+}
+
+/// Static R-tree over geo point values, persisted in a single little-endian file.
+///
+/// The tree is bulk loaded with Sort-Tile-Recursive packing. Leaf nodes are stored first, then
+/// each upper level, so the root is the last node:
+///
+/// ```text
+/// header | leaf nodes | level 1 nodes | ... | root | entries
+/// ```
+///
+/// Entries hold the point values with their point offsets, so a point with multiple values may
+/// be found multiple times.
+pub struct MmapGeoRTree {
+    path: PathBuf,
+    mmap: Mmap,
+    entries_count: usize,
+    nodes_count: usize,
+    leaves_count: usize,
+}
+
+impl MmapGeoRTree {
+    pub fn build(
+        path: &Path,
+        values: impl IntoIterator<Item = (PointOffsetType, GeoPoint)>,
+    ) -> OperationResult<()> {
+        let mut entries: Vec<_> = values.into_iter().collect();
+
+        sort_tile_recursive(&mut entries, |(_, point)| (point.lon.0, point.lat.0));
+        let mut nodes: Vec<Node> = entries
+            .chunks(NODE_CAPACITY)
+            .enumerate()
+            .map(|(i, chunk)| Node {
+                rect: chunk
+                    .iter()
+                    .fold(GeoRect::EMPTY, |rect, (_, point)| rect.with_point(point)),
+                children_start: (i * NODE_CAPACITY) as u32,
+                children_end: (i * NODE_CAPACITY + chunk.len()) as u32,
+            })
+            .collect();
+        let leaves_count = nodes.len();
+
+        // Pack each level into parent nodes, until a single root is left
+        let mut level = 0..nodes.len();
+        while level.len() > 1 {
+            sort_tile_recursive(&mut nodes[level.clone()], |node| node.rect.center());
+            let parents: Vec<Node> = nodes[level.clone()]
+                .chunks(NODE_CAPACITY)
+                .enumerate()
+                .map(|(i, chunk)| Node {
+                    rect: chunk
+                        .iter()
+                        .fold(GeoRect::EMPTY, |rect, node| rect.with_rect(&node.rect)),
+                    children_start: (level.start + i * NODE_CAPACITY) as u32,
+                    children_end: (level.start + i * NODE_CAPACITY + chunk.len()) as u32,
+                })
+                .collect();
+            let parents_start = nodes.len();
+            nodes.extend(parents);
+            level = parents_start..nodes.len();
+        }
+
+        atomic_save::<OperationError, _>(&path.join(RTREE_PATH), |writer| {
+            writer.write_all(RTREE_MAGIC)?;
+            writer.write_all(&RTREE_VERSION.to_le_bytes())?;
+            writer.write_all(&(entries.len() as u64).to_le_bytes())?;
+            writer.write_all(&(nodes.len() as u32).to_le_bytes())?;
+            writer.write_all(&(leaves_count as u32).to_le_bytes())?;
+            writer.write_all(&[0; 8])?;
+
+            for node in &nodes {
+                writer.write_all(&node.rect.min_lon.to_le_bytes())?;
+                writer.write_all(&node.rect.min_lat.to_le_bytes())?;
+                writer.write_all(&node.rect.max_lon.to_le_bytes())?;
+                writer.write_all(&node.rect.max_lat.to_le_bytes())?;
+                writer.write_all(&node.children_start.to_le_bytes())?;
+                writer.write_all(&node.children_end.to_le_bytes())?;
+            }
+
+            for (idx, point) in &entries {
+                writer.write_all(&point.lon.0.to_le_bytes())?;
+                writer.write_all(&point.lat.0.to_le_bytes())?;
+                writer.write_all(&idx.to_le_bytes())?;
+            }
+            Ok(())
+        })
+    }
+
+    /// Open the R-tree of the index at `path`.
+    ///
+    /// Returns `None` if the index was built without an R-tree.
+    pub fn open(path: &Path, populate: bool) -> OperationResult<Option<Self>> {
+        let path = path.join(RTREE_PATH);
+        if !path.is_file() {
+            return Ok(None);
+        }
+
+        let mmap = open_read_mmap(&path, AdviceSetting::Global, populate)?;
+        if mmap.len() < RTREE_HEADER_SIZE {
+            return Err(OperationError::service_error(format!(
+                "Corrupted {RTREE_PATH}: file too small ({})",
+                mmap.len()
+            )));
+        }
+
+        if &mmap[0..4] != RTREE_MAGIC {
+            return Err(OperationError::service_error(format!(
+                "Corrupted {RTREE_PATH}: bad magic {:?}",
+                &mmap[0..4],
+            )));
+        }
+
+        let version = read_u32(&mmap, 4);
+        if version != RTREE_VERSION {
+            return Err(OperationError::service_error(format!(
+                "Unsupported {RTREE_PATH} version: {version}",
+            )));
+        }
+
+        let entries_count =
+            u64::from_le_bytes(mmap[8..16].try_into().expect("slice length mismatch")) as usize;
+        let nodes_count = read_u32(&mmap, 16) as usize;
+        let leaves_count = read_u32(&mmap, 20) as usize;
+
+        let expected = entries_count
+            .checked_mul(ENTRY_SIZE)
+            .and_then(|size| size.checked_add(nodes_count.checked_mul(NODE_SIZE)?))
+            .and_then(|size| size.checked_add(RTREE_HEADER_SIZE));
+        if expected != Some(mmap.len()) || leaves_count > nodes_count {
+            return Err(OperationError::service_error(format!(
+                "Corrupted {RTREE_PATH}: {entries_count} entries and {nodes_count} nodes don't fit {} bytes",
+                mmap.len()
+            )));
+        }
+
+        Ok(Some(Self {
+            path,
+            mmap,
+            entries_count,
+            nodes_count,
+            leaves_count,
+        }))
+    }
+
+    /// Offsets of points with a value within `rect`, offsets may be repeated.
+    pub fn query(&self, rect: &GeoRect, hw_counter: &ConditionedCounter) -> Vec<PointOffsetType> {
+        let mut result = Vec::new();
+        let Some(root) = self.nodes_count.checked_sub(1) else {
+            return result;
+        };
+
+        let io_counter = hw_counter.payload_index_io_read_counter();
+        let mut stack = vec![root];
+        while let Some(node_index) = stack.pop() {
+            io_counter.incr_delta(NODE_SIZE);
+            let node = self.node(node_index);
+            if !node.rect.intersects(rect) {
+                continue;
+            }
+
+            let children = node.children_start as usize..node.children_end as usize;
+            if node_index < self.leaves_count {
+                let entries = children.start..children.end.min(self.entries_count);
+                io_counter.incr_delta(entries.len() * ENTRY_SIZE);
+                for entry_index in entries {
+                    let (idx, lon, lat) = self.entry(entry_index);
+                    if rect.contains(lon, lat) {
+                        result.push(idx);
+                    }
+                }
+            } else {
+                // Children are always on lower levels, stored before the node
+                stack.extend(children.start..children.end.min(node_index));
+            }
+        }
+        result
+    }
+
+    fn node(&self, index: usize) -> Node {
+        let offset = RTREE_HEADER_SIZE + index * NODE_SIZE;
+        Node {
+            rect: GeoRect {
+                min_lon: read_f64(&self.mmap, offset),
+                min_lat: read_f64(&self.mmap, offset + 8),
+                max_lon: read_f64(&self.mmap, offset + 16),
+                max_lat: read_f64(&self.mmap, offset + 24),
+            },
+            children_start: read_u32(&self.mmap, offset + 32),
+            children_end: read_u32(&self.mmap, offset + 36),
+        }
+    }
+
+    fn entry(&self, index: usize) -> (PointOffsetType, f64, f64) {
+        let offset = RTREE_HEADER_SIZE + self.nodes_count * NODE_SIZE + index * ENTRY_SIZE;
+        let lon = read_f64(&self.mmap, offset);
+        let lat = read_f64(&self.mmap, offset + 8);
+        let idx = read_u32(&self.mmap, offset + 16);
+        (idx, lon, lat)
+    }
+
+    pub fn files(&self) -> Vec<PathBuf> {
+        vec![self.path.clone()]
+    }
+
+    /// Populate all pages in the mmap.
+    /// Block until all pages are populated.
+    pub fn populate(&self) {
+        self.mmap.populate();
+    }
+
+    /// Drop disk cache.
+    pub fn clear_cache(&self) -> OperationResult<()> {
+        clear_disk_cache(&self.path)?;
+        Ok(())
+    }
+}
+
+/// Order `items` for packing into nodes of [`NODE_CAPACITY`] consecutive items: sort by
+/// longitude, cut into vertical slices of whole nodes, and sort each slice by latitude.
+fn sort_tile_recursive<T>(items: &mut [T], center: impl Fn(&T) -> (f64, f64)) {
+    let nodes_count = items.len().div_ceil(NODE_CAPACITY);
+    let slices_count = (nodes_count as f64).sqrt().ceil() as usize;
+    let slice_size = (slices_count * NODE_CAPACITY).max(1);
+
+    items.sort_unstable_by(|a, b| center(a).0.total_cmp(&center(b).0));
+    for slice in items.chunks_mut(slice_size) {
+        slice.sort_unstable_by(|a, b| center(a).1.total_cmp(&center(b).1));
+    }
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(
+        bytes[offset..offset + 4]
+            .try_into()
+            .expect("slice length mismatch"),
+    )
+}
+
+fn read_f64(bytes: &[u8], offset: usize) -> f64 {
+    f64::from_le_bytes(
+        bytes[offset..offset + 8]
+            .try_into()
+            .expect("slice length mismatch"),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use common::counter::hardware_counter::HardwareCounterCell;
+    use rand::prelude::StdRng;
+    use rand::{Rng, SeedableRng};
+    use tempfile::Builder;
+
+    use super::*;
+    use crate::types::test_utils::build_polygon;
+
+    #[test]
+    fn test_rtree_query_matches_scan() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let values: Vec<(PointOffsetType, GeoPoint)> = (0..5_000)
+            .map(|i| {
+                let point = GeoPoint::new_unchecked(
+                    rng.random_range(-180.0..180.0),
+                    rng.random_range(-90.0..90.0),
+                );
+                (i / 2, point)
+            })
+            .collect();
+
+        let dir = Builder::new().prefix("rtree").tempdir().unwrap();
+        MmapGeoRTree::build(dir.path(), values.iter().copied()).unwrap();
+        let rtree = MmapGeoRTree::open(dir.path(), false).unwrap().unwrap();
+
+        let polygon = build_polygon(vec![
+            (-20.0, -10.0),
+            (35.0, -10.0),
+            (35.0, 45.0),
+            (-20.0, 45.0),
+            (-20.0, -10.0),
+        ]);
+        let rect = GeoRect::of_polygon(&polygon);
+        let hw_counter = HardwareCounterCell::new();
+        let hw_counter = ConditionedCounter::new(true, &hw_counter);
+
+        let mut found = rtree.query(&rect, &hw_counter);
+        found.sort_unstable();
+        let mut expected: Vec<_> = values
+            .iter()
+            .filter(|(_, point)| rect.contains(point.lon.0, point.lat.0))
+            .map(|(idx, _)| *idx)
+            .collect();
+        expected.sort_unstable();
+        assert!(!expected.is_empty());
+        assert_eq!(found, expected);
+    }
+
+    #[test]
+    fn test_empty_rtree() {
+        let dir = Builder::new().prefix("rtree").tempdir().unwrap();
+        assert!(MmapGeoRTree::open(dir.path(), false).unwrap().is_none());
+
+        MmapGeoRTree::build(dir.path(), []).unwrap();
+        let rtree = MmapGeoRTree::open(dir.path(), false).unwrap().unwrap();
+        let rect = GeoRect {
+            min_lon: -180.0,
+            min_lat: -90.0,
+            max_lon: 180.0,
+            max_lat: 90.0,
+        };
+        let hw_counter = HardwareCounterCell::new();
+        let hw_counter = ConditionedCounter::new(true, &hw_counter);
+        assert!(rtree.query(&rect, &hw_counter).is_empty());
+    }
+}
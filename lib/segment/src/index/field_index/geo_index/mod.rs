@@ -39,6 +39,7 @@ use crate::types::{FieldCondition, GeoPoint, PayloadKeyType};
 
 pub mod immutable_geo_index;
 pub mod mmap_geo_index;
+pub mod mmap_rtree;
 pub mod mutable_geo_index;
 
 /// Max number of sub-regions computed for an input geo query
@@ -324,6 +325,7 @@ impl GeoMapIndex {
             points_count: self.points_count(),
             points_values_count: self.points_values_count(),
             histogram_bucket_size: None,
+            resources: None,
             index_type: match self {
                 GeoMapIndex::Mutable(_) => "mutable_geo",
                 GeoMapIndex::Immutable(_) => "immutable_geo",
@@ -728,8 +730,18 @@ impl PayloadFieldIndex for GeoMapIndex {
         }
 
         if let Some(geo_polygon) = &condition.geo_polygon {
-            let geo_hashes = polygon_hashes(geo_polygon, GEO_QUERY_MAX_REGION).ok()?;
             let geo_condition_copy = geo_polygon.convert();
+            if let GeoMapIndex::Mmap(index) = self
+                && let Some(candidates) = index.polygon_candidates(geo_polygon, hw_counter)
+            {
+                return Some(Box::new(candidates.unique().filter(move |point| {
+                    index.check_values_any(*point, hw_counter, |geo_point| {
+                        geo_condition_copy.check_point(geo_point)
+                    })
+                })));
+            }
+
+            let geo_hashes = polygon_hashes(geo_polygon, GEO_QUERY_MAX_REGION).ok()?;
             return Some(Box::new(self.iterator(geo_hashes).filter(move |point| {
                 self.check_values_any(*point, hw_counter, |geo_point| {
                     geo_condition_copy.check_point(geo_point)
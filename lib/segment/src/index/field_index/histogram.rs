@@ -1,22 +1,29 @@
 use std::collections::BTreeMap;
 use std::collections::Bound::{Excluded, Included, Unbounded};
+use std::io::Write;
 use std::ops::Bound;
 use std::path::{Path, PathBuf};
 
-use common::fs::{atomic_save_bin, atomic_save_json, read_bin, read_json};
+use common::fs::{atomic_save, atomic_save_json, read_bin, read_json};
 use common::types::PointOffsetType;
+use fs_err as fs;
 use itertools::Itertools;
 use num_traits::Num;
 use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 
-use crate::common::operation_error::OperationResult;
+use crate::common::operation_error::{OperationError, OperationResult};
 use crate::index::field_index::utils::check_boundaries;
 
 const MIN_BUCKET_SIZE: usize = 10;
 const CONFIG_PATH: &str = "histogram_config.json";
 const BORDERS_PATH: &str = "histogram_borders.bin";
 
+const BORDERS_MAGIC: &[u8; 4] = b"hbrd";
+pub(crate) const BORDERS_VERSION: u32 = 1;
+/// Magic, version and number of borders
+const BORDERS_HEADER_SIZE: usize = 16;
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct Counts {
     pub left: usize,
@@ -55,6 +62,15 @@ pub trait Numericable: Num + PartialEq + PartialOrd + Copy {
     fn to_f64(self) -> f64;
     fn from_f64(x: f64) -> Self;
     fn from_u128(x: u128) -> Self;
+    /// Size of the value in persisted histogram borders
+    const LE_SIZE: usize;
+    fn extend_le_bytes(self, bytes: &mut Vec<u8>);
+    /// Read the value from exactly [`Self::LE_SIZE`] little-endian bytes
+    fn from_le_slice(bytes: &[u8]) -> Self;
+    /// Big-endian bytes of the value, ordered the same way as the values when compared as bytes
+    type OrderedBytes: Copy + Ord + std::fmt::Debug + 'static;
+    fn to_ordered_bytes(self) -> Self::OrderedBytes;
+    fn from_ordered_bytes(bytes: Self::OrderedBytes) -> Self;
     fn min(self, b: Self) -> Self {
         if self < b { self } else { b }
     }
@@ -82,6 +98,21 @@ impl Numericable for i64 {
     fn from_u128(x: u128) -> Self {
         x as i64
     }
+    const LE_SIZE: usize = size_of::<i64>();
+    fn extend_le_bytes(self, bytes: &mut Vec<u8>) {
+        bytes.extend_from_slice(&self.to_le_bytes());
+    }
+    fn from_le_slice(bytes: &[u8]) -> Self {
+        i64::from_le_bytes(bytes.try_into().expect("slice length mismatch"))
+    }
+    type OrderedBytes = [u8; size_of::<i64>()];
+    fn to_ordered_bytes(self) -> Self::OrderedBytes {
+        // Flip the sign bit, so negative values go first
+        (self ^ i64::MIN).to_be_bytes()
+    }
+    fn from_ordered_bytes(bytes: Self::OrderedBytes) -> Self {
+        i64::from_be_bytes(bytes) ^ i64::MIN
+    }
     fn abs_diff(self, b: Self) -> Self {
         i64::abs_diff(self, b) as i64
     }
@@ -103,6 +134,35 @@ impl Numericable for f64 {
     fn from_u128(x: u128) -> Self {
         x as Self
     }
+    const LE_SIZE: usize = size_of::<f64>();
+    fn extend_le_bytes(self, bytes: &mut Vec<u8>) {
+        bytes.extend_from_slice(&self.to_le_bytes());
+    }
+    fn from_le_slice(bytes: &[u8]) -> Self {
+        f64::from_le_bytes(bytes.try_into().expect("slice length mismatch"))
+    }
+    type OrderedBytes = [u8; size_of::<f64>()];
+    fn to_ordered_bytes(self) -> Self::OrderedBytes {
+        // Negative zero is equal to zero
+        let bits = if self == 0.0 { 0 } else { self.to_bits() };
+        // Invert all bits of negative values to reverse their order, set the sign bit of positive
+        // values so they go after negative ones
+        let bits = if bits >> 63 == 1 {
+            !bits
+        } else {
+            bits | 1 << 63
+        };
+        bits.to_be_bytes()
+    }
+    fn from_ordered_bytes(bytes: Self::OrderedBytes) -> Self {
+        let bits = u64::from_be_bytes(bytes);
+        let bits = if bits >> 63 == 1 {
+            bits & !(1 << 63)
+        } else {
+            !bits
+        };
+        f64::from_bits(bits)
+    }
 }
 
 impl Numericable for u128 {
@@ -126,6 +186,26 @@ impl Numericable for u128 {
         x
     }
 
+    const LE_SIZE: usize = size_of::<u128>();
+
+    fn extend_le_bytes(self, bytes: &mut Vec<u8>) {
+        bytes.extend_from_slice(&self.to_le_bytes());
+    }
+
+    fn from_le_slice(bytes: &[u8]) -> Self {
+        u128::from_le_bytes(bytes.try_into().expect("slice length mismatch"))
+    }
+
+    type OrderedBytes = [u8; size_of::<u128>()];
+
+    fn to_ordered_bytes(self) -> Self::OrderedBytes {
+        self.to_be_bytes()
+    }
+
+    fn from_ordered_bytes(bytes: Self::OrderedBytes) -> Self {
+        u128::from_be_bytes(bytes)
+    }
+
     fn abs_diff(self, b: Self) -> Self {
         u128::abs_diff(self, b)
     }
@@ -163,13 +243,23 @@ impl<T: Numericable + Serialize + DeserializeOwned> Histogram<T> {
         let borders_path = path.join(BORDERS_PATH);
 
         let histogram_config: HistogramConfig = read_json(&config_path)?;
-        let histogram_buckets: Vec<(Point<T>, Counts)> = read_bin(&borders_path)?;
+        let bytes = fs::read(&borders_path)?;
+        let borders = if bytes.starts_with(BORDERS_MAGIC) {
+            Self::borders_from_le_bytes(&bytes)?
+        } else {
+            // Legacy borders are plain bincode. Bincode writes little-endian integers on every
+            // target, so they are read as they are and rewritten with the versioned header.
+            let legacy_borders: Vec<(Point<T>, Counts)> = read_bin(&borders_path)?;
+            let borders: BTreeMap<_, _> = legacy_borders.into_iter().collect();
+            Self::save_borders(&borders_path, &borders)?;
+            borders
+        };
 
         Ok(Self {
             max_bucket_size: histogram_config.max_bucket_size,
             precision: histogram_config.precision,
             total_count: histogram_config.total_count,
-            borders: histogram_buckets.into_iter().collect(),
+            borders,
         })
     }
 
@@ -186,15 +276,89 @@ impl<T: Numericable + Serialize + DeserializeOwned> Histogram<T> {
             },
         )?;
 
-        let borders: Vec<(Point<T>, Counts)> = self
-            .borders
-            .iter()
-            .map(|(k, v)| (k.clone(), v.clone()))
-            .collect();
-        atomic_save_bin(&borders_path, &borders)?;
+        Self::save_borders(&borders_path, &self.borders)?;
         Ok(())
     }
 
+    /// Size of a persisted border: value, point offset, left and right counts
+    const fn border_size() -> usize {
+        T::LE_SIZE + size_of::<u32>() + 2 * size_of::<u64>()
+    }
+
+    fn save_borders(path: &Path, borders: &BTreeMap<Point<T>, Counts>) -> OperationResult<()> {
+        let mut bytes =
+            Vec::with_capacity(BORDERS_HEADER_SIZE + borders.len() * Self::border_size());
+        bytes.extend_from_slice(BORDERS_MAGIC);
+        bytes.extend_from_slice(&BORDERS_VERSION.to_le_bytes());
+        bytes.extend_from_slice(&(borders.len() as u64).to_le_bytes());
+        for (point, counts) in borders {
+            point.val.extend_le_bytes(&mut bytes);
+            bytes.extend_from_slice(&point.idx.to_le_bytes());
+            bytes.extend_from_slice(&(counts.left as u64).to_le_bytes());
+            bytes.extend_from_slice(&(counts.right as u64).to_le_bytes());
+        }
+        atomic_save::<OperationError, _>(path, |writer| Ok(writer.write_all(&bytes)?))
+    }
+
+    fn borders_from_le_bytes(bytes: &[u8]) -> OperationResult<BTreeMap<Point<T>, Counts>> {
+        if bytes.len() < BORDERS_HEADER_SIZE {
+            return Err(OperationError::service_error(format!(
+                "Corrupted {BORDERS_PATH}: file too small ({})",
+                bytes.len()
+            )));
+        }
+
+        let version = u32::from_le_bytes(bytes[4..8].try_into().expect("slice length mismatch"));
+        if version != BORDERS_VERSION {
+            return Err(OperationError::service_error(format!(
+                "Unsupported {BORDERS_PATH} version: {version}",
+            )));
+        }
+
+        let len = u64::from_le_bytes(bytes[8..16].try_into().expect("slice length mismatch"));
+        let expected = usize::try_from(len)
+            .ok()
+            .and_then(|len| len.checked_mul(Self::border_size()))
+            .and_then(|size| size.checked_add(BORDERS_HEADER_SIZE));
+        if expected != Some(bytes.len()) {
+            return Err(OperationError::service_error(format!(
+                "Corrupted {BORDERS_PATH}: {len} borders don't fit {} bytes",
+                bytes.len()
+            )));
+        }
+
+        let value_end = T::LE_SIZE;
+        let idx_end = value_end + size_of::<u32>();
+        let left_end = idx_end + size_of::<u64>();
+        let borders = bytes[BORDERS_HEADER_SIZE..]
+            .chunks_exact(Self::border_size())
+            .map(|border| {
+                let point = Point {
+                    val: T::from_le_slice(&border[..value_end]),
+                    idx: PointOffsetType::from_le_bytes(
+                        border[value_end..idx_end]
+                            .try_into()
+                            .expect("slice length mismatch"),
+                    ),
+                };
+                let counts = Counts {
+                    left: u64::from_le_bytes(
+                        border[idx_end..left_end]
+                            .try_into()
+                            .expect("slice length mismatch"),
+                    ) as usize,
+                    right: u64::from_le_bytes(
+                        border[left_end..]
+                            .try_into()
+                            .expect("slice length mismatch"),
+                    ) as usize,
+                };
+                (point, counts)
+            })
+            .collect();
+        Ok(borders)
+    }
+
     pub fn files(path: &Path) -> Vec<PathBuf> {
         vec![path.join(CONFIG_PATH), path.join(BORDERS_PATH)]
     }
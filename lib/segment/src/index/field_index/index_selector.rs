@@ -23,6 +23,7 @@ use crate::index::field_index::full_text_index::text_index::FullTextIndex;
 use crate::index::field_index::geo_index::GeoMapIndex;
 use crate::index::field_index::null_index::MutableNullIndex;
 use crate::index::field_index::numeric_index::NumericIndex;
+use crate::index::field_index::trigram_index::{TrigramIndex, TrigramIndexBuilder};
 use crate::index::payload_config::{FullPayloadIndexType, PayloadIndexType};
 use crate::json_path::JsonPath;
 use crate::types::{PayloadFieldSchema, PayloadSchemaParams};
@@ -465,6 +466,15 @@ impl IndexSelector<'_> {
         )?))
     }
 
+    pub fn trigram_builder(dir: &Path, field: &JsonPath) -> TrigramIndexBuilder {
+        TrigramIndexBuilder::new(&trigram_dir(dir, field))
+    }
+
+    pub fn open_trigram_index(dir: &Path, field: &JsonPath) -> OperationResult<TrigramIndex> {
+        // trigram index is always on disk
+        TrigramIndex::open(&trigram_dir(dir, field), false)
+    }
+
     pub fn new_null_index(
         dir: &Path,
         field: &JsonPath,
@@ -606,3 +616,7 @@ fn bool_dir(dir: &Path, field: &JsonPath) -> PathBuf {
 fn null_dir(dir: &Path, field: &JsonPath) -> PathBuf {
     dir.join(format!("{}-null", &field.filename()))
 }
+
+fn trigram_dir(dir: &Path, field: &JsonPath) -> PathBuf {
+    dir.join(format!("{}-trigram", &field.filename()))
+}
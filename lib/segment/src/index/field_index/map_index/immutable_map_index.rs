@@ -7,9 +7,11 @@ use std::path::PathBuf;
 use std::sync::Arc;
 
 use bitvec::vec::BitVec;
+use common::counter::hardware_counter::HardwareCounterCell;
 use common::mmap_hashmap::Key;
 use common::types::PointOffsetType;
 use gridstore::Blob;
+use itertools::Either;
 #[cfg(feature = "rocksdb")]
 use parking_lot::RwLock;
 #[cfg(feature = "rocksdb")]
@@ -17,9 +19,12 @@ use rocksdb::DB;
 
 #[cfg(feature = "rocksdb")]
 use super::MapIndex;
+use super::map_index_delta::MapIndexDelta;
 use super::mmap_map_index::MmapMapIndex;
 use super::{IdIter, MapIndexKey};
 use crate::common::Flusher;
+#[cfg(feature = "rocksdb")]
+use crate::common::operation_error::OperationError;
 use crate::common::operation_error::OperationResult;
 #[cfg(feature = "rocksdb")]
 use crate::common::rocksdb_buffered_delete_wrapper::DatabaseColumnScheduledDeleteWrapper;
@@ -39,6 +44,8 @@ pub struct ImmutableMapIndex<N: MapIndexKey + Key + ?Sized> {
     /// Amount of point which have at least one indexed payload value
     indexed_points: usize,
     values_count: usize,
+    /// Values of points updated after the index was loaded, also persisted by mmap storage
+    delta: MapIndexDelta<N>,
     // Backing storage, source of state, persists deletions
     storage: Storage<N>,
 }
@@ -94,6 +101,10 @@ where
         // flatten values-to-points map
         for (value, points) in map {
             let points = points.into_iter().collect::<Vec<_>>();
+            // All points of the value may have been moved into the delta
+            if points.is_empty() {
+                continue;
+            }
             let container_len = value_to_points_container.len() as u32;
             let range = container_len..container_len + points.len() as u32;
             value_to_points.insert(
@@ -133,99 +144,129 @@ where
             point_to_values: ImmutablePointToValues::new(point_to_values),
             indexed_points,
             values_count,
+            delta: MapIndexDelta::default(),
             storage: Storage::RocksDb(db_wrapper),
         }))
     }
 
     /// Open and load immutable numeric index from mmap storage
     pub(super) fn open_mmap(index: MmapMapIndex<N>) -> Self {
-        // Construct intermediate values to points map from backing storage
-        let mapping = || {
-            index.storage.value_to_points.iter_stored().map(|(value, ids)| {
-                (
-                    value,
-                    ids.iter_native().filter(|idx| {
-                        let is_deleted = index.storage.deleted.get(*idx as usize).unwrap_or(false);
-                        !is_deleted
-                    }),
-                )
-            })
+        let mut immutable = Self {
+            value_to_points: HashMap::new(),
+            value_to_points_container: Vec::new(),
+            deleted_value_to_points_container: BitVec::new(),
+            point_to_values: ImmutablePointToValues::new(Vec::new()),
+            indexed_points: 0,
+            values_count: 0,
+            delta: MapIndexDelta::default(),
+            storage: Storage::Mmap(Box::new(index)),
         };
+        immutable.load_mmap();
+        immutable
+    }
 
-        let mut indexed_points = 0;
-        let mut values_count = 0;
-        let mut value_to_points = HashMap::new();
-
-        // Create points to values mapping
-        let mut point_to_values: Vec<Vec<N::Owned>> = vec![];
-        for (value, ids) in mapping() {
-            for idx in ids {
-                if point_to_values.len() <= idx as usize {
-                    point_to_values.resize_with(idx as usize + 1, Vec::new)
+    /// Load the index into memory from mmap storage, if used.
+    fn load_mmap(&mut self) {
+        let (
+            value_to_points,
+            value_to_points_container,
+            point_to_values,
+            indexed_points,
+            values_count,
+        ) = match &self.storage {
+            #[cfg(feature = "rocksdb")]
+            Storage::RocksDb(_) => return,
+            Storage::Mmap(index) => {
+                // Construct intermediate values to points map from backing storage
+                let hw_counter = HardwareCounterCell::disposable(); // Loading is not measured
+                let mapping = || index.iter_values_map(&hw_counter);
+
+                let mut indexed_points = 0;
+                let mut values_count = 0;
+                let mut value_to_points = HashMap::new();
+
+                // Create points to values mapping
+                let mut point_to_values: Vec<Vec<N::Owned>> = vec![];
+                for (value, ids) in mapping() {
+                    for idx in ids {
+                        if point_to_values.len() <= idx as usize {
+                            point_to_values.resize_with(idx as usize + 1, Vec::new)
+                        }
+                        let point_values = &mut point_to_values[idx as usize];
+
+                        if point_values.is_empty() {
+                            indexed_points += 1;
+                        }
+                        values_count += 1;
+
+                        point_values.push(value.to_owned());
+                    }
                 }
-                let point_values = &mut point_to_values[idx as usize];
-
-                if point_values.is_empty() {
-                    indexed_points += 1;
+                let point_to_values = ImmutablePointToValues::new(point_to_values);
+
+                // Create flattened values-to-points mapping
+                let mut value_to_points_container = Vec::with_capacity(values_count);
+                for (value, points) in mapping() {
+                    let points = points.into_iter().collect::<Vec<_>>();
+                    // All points of the value may have been moved into the delta
+                    if points.is_empty() {
+                        continue;
+                    }
+                    let container_len = value_to_points_container.len() as u32;
+                    let range = container_len..container_len + points.len() as u32;
+                    value_to_points.insert(
+                        value.to_owned(),
+                        ContainerSegment {
+                            count: range.len() as u32,
+                            range,
+                        },
+                    );
+                    value_to_points_container.extend(points);
+                }
+                value_to_points.shrink_to_fit();
+
+                // Sort IDs in each slice of points
+                // This is very important because we binary search
+                for value in value_to_points.keys() {
+                    if let Some((slice, _offset)) = Self::get_mut_point_ids_slice(
+                        &value_to_points,
+                        &mut value_to_points_container,
+                        value.borrow(),
+                    ) {
+                        slice.sort_unstable();
+                    } else {
+                        debug_assert!(
+                            false,
+                            "value {} not found in value_to_points",
+                            value.borrow(),
+                        );
+                    }
                 }
-                values_count += 1;
 
-                point_values.push(value.to_owned());
-            }
-        }
-        let point_to_values = ImmutablePointToValues::new(point_to_values);
+                debug_assert_eq!(indexed_points, index.get_indexed_points());
 
-        // Create flattened values-to-points mapping
-        let mut value_to_points_container = Vec::with_capacity(values_count);
-        for (value, points) in mapping() {
-            let points = points.into_iter().collect::<Vec<_>>();
-            let container_len = value_to_points_container.len() as u32;
-            let range = container_len..container_len + points.len() as u32;
-            value_to_points.insert(
-                value.to_owned(),
-                ContainerSegment {
-                    count: range.len() as u32,
-                    range,
-                },
-            );
-            value_to_points_container.extend(points);
-        }
-        value_to_points.shrink_to_fit();
+                // Index is now loaded into memory, clear cache of backing mmap storage
+                if let Err(err) = index.clear_cache() {
+                    log::warn!("Failed to clear mmap cache of ram mmap map index: {err}");
+                }
 
-        // Sort IDs in each slice of points
-        // This is very important because we binary search
-        for value in value_to_points.keys() {
-            if let Some((slice, _offset)) = Self::get_mut_point_ids_slice(
-                &value_to_points,
-                &mut value_to_points_container,
-                value.borrow(),
-            ) {
-                slice.sort_unstable();
-            } else {
-                debug_assert!(
-                    false,
-                    "value {} not found in value_to_points",
-                    value.borrow(),
-                );
+                (
+                    value_to_points,
+                    value_to_points_container,
+                    point_to_values,
+                    indexed_points,
+                    values_count,
+                )
             }
-        }
-
-        debug_assert_eq!(indexed_points, index.get_indexed_points());
-
-        // Index is now loaded into memory, clear cache of backing mmap storage
-        if let Err(err) = index.clear_cache() {
-            log::warn!("Failed to clear mmap cache of ram mmap map index: {err}");
-        }
+        };
 
-        Self {
-            value_to_points,
-            value_to_points_container,
-            deleted_value_to_points_container: BitVec::new(),
-            point_to_values,
-            indexed_points,
-            values_count,
-            storage: Storage::Mmap(Box::new(index)),
-        }
+        self.value_to_points = value_to_points;
+        self.value_to_points_container = value_to_points_container;
+        self.deleted_value_to_points_container = BitVec::new();
+        self.point_to_values = point_to_values;
+        self.indexed_points = indexed_points;
+        self.values_count = values_count;
+        self.delta = MapIndexDelta::default();
     }
 
     /// Return mutable slice of a container which holds point_ids for given value.
@@ -321,7 +362,45 @@ where
         }
     }
 
+    /// Set values of the point, which are kept in the delta until the index is rebuilt.
+    ///
+    /// Only supported with mmap storage, which persists the values.
+    pub fn add_many_to_map<Q>(
+        &mut self,
+        idx: PointOffsetType,
+        values: Vec<Q>,
+        hw_counter: &HardwareCounterCell,
+    ) -> OperationResult<()>
+    where
+        Q: Into<N::Owned>,
+    {
+        self.remove_point(idx)?;
+        if values.is_empty() {
+            return Ok(());
+        }
+
+        let values: Vec<N::Owned> = values.into_iter().map(Into::into).collect();
+        match &mut self.storage {
+            #[cfg(feature = "rocksdb")]
+            Storage::RocksDb(_) => {
+                return Err(OperationError::service_error(
+                    "Can't add values to immutable map index",
+                ));
+            }
+            Storage::Mmap(index) => index.add_many_to_map(idx, values.clone(), hw_counter)?,
+        }
+        self.indexed_points += 1;
+        self.values_count += values.len();
+        self.delta.add_point(idx, values);
+        Ok(())
+    }
+
     pub fn remove_point(&mut self, idx: PointOffsetType) -> OperationResult<()> {
+        if let Some(removed_values) = self.delta.remove_point(idx) {
+            self.indexed_points -= 1;
+            self.values_count = self.values_count.saturating_sub(removed_values.len());
+        }
+
         if let Some(removed_values) = self.point_to_values.get_values(idx) {
             let mut removed_values_count = 0;
             for value in removed_values {
@@ -334,15 +413,10 @@ where
                 );
 
                 // Update persisted storage
-                match self.storage {
-                    #[cfg(feature = "rocksdb")]
-                    Storage::RocksDb(ref db_wrapper) => {
-                        let key = MapIndex::encode_db_record(value.borrow(), idx);
-                        db_wrapper.remove(key)?;
-                    }
-                    Storage::Mmap(ref mut index) => {
-                        index.remove_point(idx);
-                    }
+                #[cfg(feature = "rocksdb")]
+                if let Storage::RocksDb(ref db_wrapper) = self.storage {
+                    let key = MapIndex::encode_db_record(value.borrow(), idx);
+                    db_wrapper.remove(key)?;
                 }
                 removed_values_count += 1;
             }
@@ -353,6 +427,37 @@ where
             self.values_count = self.values_count.saturating_sub(removed_values_count);
         }
         self.point_to_values.remove_point(idx);
+
+        match &mut self.storage {
+            #[cfg(feature = "rocksdb")]
+            Storage::RocksDb(_) => {}
+            Storage::Mmap(index) => index.remove_point(idx)?,
+        }
+        Ok(())
+    }
+
+    /// Whether values of points can be updated, only mmap storage persists them.
+    pub fn accepts_updates(&self) -> bool {
+        matches!(self.storage, Storage::Mmap(_))
+    }
+
+    /// Share of points updated on top of mmap storage, see [`MmapMapIndex::delta_ratio`].
+    pub fn delta_ratio(&self) -> f64 {
+        match &self.storage {
+            #[cfg(feature = "rocksdb")]
+            Storage::RocksDb(_) => 0.0,
+            Storage::Mmap(index) => index.delta_ratio(),
+        }
+    }
+
+    /// Rebuild mmap storage without the delta, see [`MmapMapIndex::compact`], and reload it.
+    pub fn compact(&mut self) -> OperationResult<()> {
+        match &mut self.storage {
+            #[cfg(feature = "rocksdb")]
+            Storage::RocksDb(_) => return Ok(()),
+            Storage::Mmap(index) => index.compact()?,
+        }
+        self.load_mmap();
         Ok(())
     }
 
@@ -414,6 +519,10 @@ where
     }
 
     pub fn check_values_any(&self, idx: PointOffsetType, check_fn: impl Fn(&N) -> bool) -> bool {
+        if let Some(values) = self.delta.get_values(idx) {
+            return values.iter().any(|value| check_fn(value.borrow()));
+        }
+
         let mut hw_count_val = 0;
 
         self.point_to_values.check_values_any(idx, |v| {
@@ -424,10 +533,18 @@ where
     }
 
     pub fn get_values(&self, idx: PointOffsetType) -> Option<impl Iterator<Item = &N> + '_> {
-        Some(self.point_to_values.get_values(idx)?.map(|v| v.borrow()))
+        if let Some(values) = self.delta.get_values(idx) {
+            return Some(Either::Left(values.iter().map(|v| v.borrow())));
+        }
+        Some(Either::Right(
+            self.point_to_values.get_values(idx)?.map(|v| v.borrow()),
+        ))
     }
 
     pub fn values_count(&self, idx: PointOffsetType) -> Option<usize> {
+        if let Some(values) = self.delta.get_values(idx) {
+            return Some(values.len());
+        }
         self.point_to_values.get_values_count(idx)
     }
 
@@ -440,31 +557,50 @@ where
     }
 
     pub fn get_unique_values_count(&self) -> usize {
-        self.value_to_points.len()
+        self.value_to_points.len() + self.iter_delta_only_values().count()
     }
 
     pub fn get_count_for_value(&self, value: &N) -> Option<usize> {
-        self.value_to_points
-            .get(value)
-            .map(|entry| entry.count as usize)
+        let delta_count = self.delta.get_count_for_value(value);
+        match self.value_to_points.get(value) {
+            Some(entry) => Some(entry.count as usize + delta_count),
+            None => (delta_count > 0).then_some(delta_count),
+        }
+    }
+
+    /// Values of updated points, which are not in the loaded index.
+    fn iter_delta_only_values(&self) -> impl Iterator<Item = &N> + '_ {
+        self.delta
+            .iter_values_map()
+            .map(|(value, _)| value)
+            .filter(|value| !self.value_to_points.contains_key(*value))
     }
 
     pub fn iter_counts_per_value(&self) -> impl Iterator<Item = (&N, usize)> + '_ {
-        self.value_to_points
-            .iter()
-            .map(|(k, entry)| (k.borrow(), entry.count as usize))
+        let loaded = self.value_to_points.iter().map(|(k, entry)| {
+            let k = k.borrow();
+            (k, entry.count as usize + self.delta.get_count_for_value(k))
+        });
+        let delta_only = self
+            .iter_delta_only_values()
+            .map(|value| (value, self.delta.get_count_for_value(value)));
+        loaded.chain(delta_only)
     }
 
     pub fn iter_values_map(&self) -> impl Iterator<Item = (&N, IdIter<'_>)> {
-        self.value_to_points.keys().map(move |k| {
-            (
-                k.borrow(),
-                Box::new(self.get_iterator(k.borrow())) as IdIter,
-            )
-        })
+        self.iter_values().map(move |k| (k, self.get_iterator(k)))
     }
 
     pub fn get_iterator(&self, value: &N) -> IdIter<'_> {
+        let loaded = self.get_loaded_iterator(value);
+        if self.delta.contains_value(value) {
+            Box::new(loaded.chain(self.delta.get_iterator(value)))
+        } else {
+            loaded
+        }
+    }
+
+    fn get_loaded_iterator(&self, value: &N) -> IdIter<'_> {
         if let Some(entry) = self.value_to_points.get(value) {
             let range = entry.range.start as usize..entry.range.end as usize;
 
@@ -488,7 +624,12 @@ where
     }
 
     pub fn iter_values(&self) -> Box<dyn Iterator<Item = &N> + '_> {
-        Box::new(self.value_to_points.keys().map(|v| v.borrow()))
+        Box::new(
+            self.value_to_points
+                .keys()
+                .map(|v| v.borrow())
+                .chain(self.iter_delta_only_values()),
+        )
     }
 
     pub fn storage_type(&self) -> StorageType {
@@ -0,0 +1,104 @@
+use std::borrow::Borrow;
+
+use ahash::HashMap;
+use common::types::PointOffsetType;
+use roaring::RoaringBitmap;
+
+use super::MapIndexKey;
+
+/// Values of points updated on top of an immutable map index.
+///
+/// Points in the delta are removed from the underlying index, so each point is served either by
+/// the index or by the delta.
+pub(super) struct MapIndexDelta<N: MapIndexKey + ?Sized> {
+    point_to_values: HashMap<PointOffsetType, Vec<N::Owned>>,
+    value_to_points: HashMap<N::Owned, RoaringBitmap>,
+    values_count: usize,
+}
+
+impl<N: MapIndexKey + ?Sized> Default for MapIndexDelta<N> {
+    fn default() -> Self {
+        Self {
+            point_to_values: HashMap::default(),
+            value_to_points: HashMap::default(),
+            values_count: 0,
+        }
+    }
+}
+
+impl<N: MapIndexKey + ?Sized> MapIndexDelta<N> {
+    /// Set values of the point, replacing the previous ones.
+    pub fn add_point(&mut self, idx: PointOffsetType, values: Vec<N::Owned>) {
+        self.remove_point(idx);
+        if values.is_empty() {
+            return;
+        }
+        for value in &values {
+            self.value_to_points
+                .entry(value.clone())
+                .or_default()
+                .insert(idx);
+        }
+        self.values_count += values.len();
+        self.point_to_values.insert(idx, values);
+    }
+
+    /// Remove the point, returns its values if it was in the delta.
+    pub fn remove_point(&mut self, idx: PointOffsetType) -> Option<Vec<N::Owned>> {
+        let values = self.point_to_values.remove(&idx)?;
+        for value in &values {
+            let value: &N = value.borrow();
+            if let Some(points) = self.value_to_points.get_mut(value) {
+                points.remove(idx);
+                if points.is_empty() {
+                    self.value_to_points.remove(value);
+                }
+            }
+        }
+        self.values_count -= values.len();
+        Some(values)
+    }
+
+    pub fn get_values(&self, idx: PointOffsetType) -> Option<&[N::Owned]> {
+        self.point_to_values.get(&idx).map(Vec::as_slice)
+    }
+
+    pub fn points_count(&self) -> usize {
+        self.point_to_values.len()
+    }
+
+    pub fn values_count(&self) -> usize {
+        self.values_count
+    }
+
+    pub fn get_count_for_value(&self, value: &N) -> usize {
+        self.value_to_points
+            .get(value)
+            .map_or(0, |points| points.len() as usize)
+    }
+
+    pub fn get_iterator(&self, value: &N) -> impl Iterator<Item = PointOffsetType> + '_ {
+        self.value_to_points
+            .get(value)
+            .into_iter()
+            .flat_map(|points| points.iter())
+    }
+
+    /// Points with any of the `values`.
+    pub fn union<K: Borrow<N>>(&self, values: impl IntoIterator<Item = K>) -> RoaringBitmap {
+        values
+            .into_iter()
+            .filter_map(|value| self.value_to_points.get(value.borrow()))
+            .fold(RoaringBitmap::new(), |union, points| union | points)
+    }
+
+    pub fn contains_value(&self, value: &N) -> bool {
+        self.value_to_points.contains_key(value)
+    }
+
+    pub fn iter_values_map(&self) -> impl Iterator<Item = (&N, &RoaringBitmap)> + '_ {
+        self.value_to_points
+            .iter()
+            .map(|(value, points)| (value.borrow(), points))
+    }
+}
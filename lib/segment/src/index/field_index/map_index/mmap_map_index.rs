@@ -13,19 +13,30 @@ use common::mmap::{AdviceSetting, MmapBitSlice, create_and_ensure_length};
 use common::mmap_hashmap::{Key, MmapHashMap, READ_ENTRY_OVERHEAD};
 use common::types::PointOffsetType;
 use fs_err as fs;
+use indexmap::IndexSet;
 use itertools::Itertools;
 use memmap2::MmapMut;
+use roaring::RoaringBitmap;
 use serde::{Deserialize, Serialize};
 
+use super::map_index_delta::MapIndexDelta;
+use super::roaring_postings::{ROARING_POSTINGS_MAX_VALUES, RoaringPostings};
 use super::{IdIter, MapIndexKey};
 use crate::common::Flusher;
 use crate::common::mmap_bitslice_buffered_update_wrapper::MmapBitSliceBufferedUpdateWrapper;
 use crate::common::operation_error::{OperationError, OperationResult};
-use crate::index::field_index::mmap_point_to_values::MmapPointToValues;
+use crate::index::field_index::mmap_point_to_values::{
+    MmapPointToValues, MmapPointToValuesBuilder, PointToValuesCompression,
+};
 
 const DELETED_PATH: &str = "deleted.bin";
 const HASHMAP_PATH: &str = "values_to_points.bin";
+/// Hashmap of values to positions of their roaring postings
+const POSITIONS_HASHMAP_PATH: &str = "values_to_positions.bin";
+const ROARING_POSTINGS_PATH: &str = "postings.roaring";
 const CONFIG_PATH: &str = "mmap_field_index_config.json";
+/// Postings collected while building the index, removed once the index is built
+const POSTINGS_SPILL_PATH: &str = "postings.spill";
 
 pub struct MmapMapIndex<N: MapIndexKey + Key + ?Sized> {
     path: PathBuf,
@@ -35,11 +46,18 @@ pub struct MmapMapIndex<N: MapIndexKey + Key + ?Sized> {
     // pub(super) deleted: MmapBitSliceBufferedUpdateWrapper,
     deleted_count: usize,
     total_key_value_pairs: usize,
+    /// Number of deleted points per value, if postings hold each point at most once.
+    /// Allows counting points per value without reading postings.
+    deleted_per_value: Option<HashMap<N::Owned, usize>>,
+    /// Values of points updated after the index was built, persisted in the point-to-values delta
+    delta: MapIndexDelta<N>,
     is_on_disk: bool,
 }
 
 pub(super) struct Storage<N: MapIndexKey + Key + ?Sized> {
+    /// Postings of each value, or a single position of its posting in `roaring_postings`
     pub(super) value_to_points: MmapHashMap<N, PointOffsetType>,
+    roaring_postings: Option<RoaringPostings>,
     point_to_values: MmapPointToValues<N>,
     pub(super) deleted: MmapBitSliceBufferedUpdateWrapper,
 }
@@ -47,11 +65,19 @@ pub(super) struct Storage<N: MapIndexKey + Key + ?Sized> {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct MmapMapIndexConfig {
     total_key_value_pairs: usize,
+    /// Whether postings hold each point at most once, not set for indexes built before
+    #[serde(default)]
+    unique_postings: bool,
+    /// Whether postings are stored as roaring bitmaps, for low-cardinality values
+    #[serde(default)]
+    roaring_postings: bool,
 }
 
 impl<N: MapIndexKey + Key + ?Sized> MmapMapIndex<N> {
     /// Open and load mmap map index from the given path
     pub fn open(path: &Path, is_on_disk: bool) -> OperationResult<Option<Self>> {
+        common::fs::recover_replaced_dir(path)?;
+
         let hashmap_path = path.join(HASHMAP_PATH);
         let deleted_path = path.join(DELETED_PATH);
         let config_path = path.join(CONFIG_PATH);
@@ -61,34 +87,89 @@ impl<N: MapIndexKey + Key + ?Sized> MmapMapIndex<N> {
             return Ok(None);
         }
 
-        let config: MmapMapIndexConfig = read_json(&config_path)?;
+        let mut config: MmapMapIndexConfig = read_json(&config_path)?;
 
         let do_populate = !is_on_disk;
 
-        let hashmap = MmapHashMap::open(&hashmap_path, do_populate)?;
+        if !config.roaring_postings {
+            let hashmap = MmapHashMap::<N, PointOffsetType>::open(&hashmap_path, false)?;
+            if hashmap.keys_count() <= ROARING_POSTINGS_MAX_VALUES {
+                Self::convert_to_roaring_postings(path, hashmap)?;
+                config.roaring_postings = true;
+                // Roaring postings hold each point at most once
+                config.unique_postings = true;
+                atomic_save_json(&config_path, &config)?;
+            }
+        }
+
+        let (hashmap, roaring_postings) = if config.roaring_postings {
+            // Leftover of an interrupted conversion
+            if hashmap_path.is_file() {
+                fs::remove_file(&hashmap_path)?;
+            }
+            let hashmap = MmapHashMap::open(&path.join(POSITIONS_HASHMAP_PATH), do_populate)?;
+            let roaring_postings = RoaringPostings::open(&path.join(ROARING_POSTINGS_PATH))?;
+            (hashmap, Some(roaring_postings))
+        } else {
+            (MmapHashMap::open(&hashmap_path, do_populate)?, None)
+        };
         let point_to_values = MmapPointToValues::open(path, do_populate)?;
 
         let deleted = mmap::open_write_mmap(&deleted_path, AdviceSetting::Global, do_populate)?;
-        let deleted = MmapBitSlice::from(deleted, 0);
+        let mut deleted = MmapBitSlice::from(deleted, 0);
+
+        // Updated points are served by the delta. Points whose deletion didn't reach the disk
+        // before the delta did are deleted again.
+        let mut delta = MapIndexDelta::default();
+        for idx in point_to_values.delta_points() {
+            if let Some(values) = point_to_values.get_values(idx) {
+                let values = values
+                    .map(|value| N::to_owned(N::from_referenced(&value)))
+                    .collect();
+                delta.add_point(idx, values);
+            }
+            if deleted.get(idx as usize).is_some_and(|deleted| !*deleted) {
+                deleted.set(idx as usize, true);
+            }
+        }
         let deleted_count = deleted.count_ones();
 
+        let deleted_per_value = config.unique_postings.then(|| {
+            let mut deleted_per_value = HashMap::default();
+            for idx in deleted.iter_ones() {
+                count_point_values(
+                    &point_to_values,
+                    idx as PointOffsetType,
+                    &mut deleted_per_value,
+                );
+            }
+            deleted_per_value
+        });
+
         Ok(Some(Self {
             path: path.to_path_buf(),
             storage: Storage {
                 value_to_points: hashmap,
+                roaring_postings,
                 point_to_values,
                 deleted: MmapBitSliceBufferedUpdateWrapper::new(deleted),
             },
             deleted_count,
             total_key_value_pairs: config.total_key_value_pairs,
+            deleted_per_value,
+            delta,
             is_on_disk,
         }))
     }
 
+    /// Build the index from the values of points and the unique `values` among them.
+    ///
+    /// Postings are collected from the written point-to-values through a spill file, so that
+    /// only the unique values are kept in memory.
     pub fn build(
         path: &Path,
-        point_to_values: Vec<Vec<N::Owned>>,
-        values_to_points: HashMap<N::Owned, Vec<PointOffsetType>>,
+        point_to_values: MmapPointToValuesBuilder<N>,
+        values: IndexSet<N::Owned>,
         is_on_disk: bool,
     ) -> OperationResult<Self> {
         fs::create_dir_all(path)?;
@@ -96,31 +177,53 @@ impl<N: MapIndexKey + Key + ?Sized> MmapMapIndex<N> {
         let hashmap_path = path.join(HASHMAP_PATH);
         let deleted_path = path.join(DELETED_PATH);
         let config_path = path.join(CONFIG_PATH);
+        let spill_path = path.join(POSTINGS_SPILL_PATH);
+
+        let compression = if common::flags::feature_flags().payload_index_prefix_compressed_strings
+        {
+            PointToValuesCompression::Prefix
+        } else {
+            PointToValuesCompression::None
+        };
+        let point_to_values = point_to_values.finalize(compression)?;
+
+        let (spill, offsets, total_key_value_pairs) =
+            Self::spill_postings(&spill_path, &point_to_values, &values)?;
+        let spill_bytes = spill.as_deref().unwrap_or_default();
+        let posting = |index: usize| {
+            let id_size = size_of::<PointOffsetType>();
+            spill_bytes[offsets[index] * id_size..offsets[index + 1] * id_size]
+                .chunks_exact(id_size)
+                .map(|id| PointOffsetType::from_le_bytes(id.try_into().unwrap()))
+        };
+        // Values of points added again may be left without postings
+        let postings = values
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| offsets[index + 1] > offsets[*index])
+            .map(|(index, value)| (Borrow::<N>::borrow(value), posting(index)));
+
+        let roaring_postings = postings.clone().count() <= ROARING_POSTINGS_MAX_VALUES;
+        if roaring_postings {
+            let postings: Vec<(&N, RoaringBitmap)> = postings
+                .map(|(value, ids)| (value, ids.collect()))
+                .collect();
+            Self::create_roaring_postings(path, &postings)?;
+        } else {
+            MmapHashMap::create(&hashmap_path, postings)?;
+        }
+        drop(spill);
+        fs::remove_file(&spill_path)?;
 
         atomic_save_json(
             &config_path,
             &MmapMapIndexConfig {
-                total_key_value_pairs: point_to_values.iter().map(|v| v.len()).sum(),
+                total_key_value_pairs,
+                unique_postings: true,
+                roaring_postings,
             },
         )?;
 
-        MmapHashMap::create(
-            &hashmap_path,
-            values_to_points
-                .iter()
-                .map(|(value, ids)| (value.borrow(), ids.iter().copied())),
-        )?;
-
-        MmapPointToValues::<N>::from_iter(
-            path,
-            point_to_values.iter().enumerate().map(|(idx, values)| {
-                (
-                    idx as PointOffsetType,
-                    values.iter().map(|value| N::as_referenced(value.borrow())),
-                )
-            }),
-        )?;
-
         {
             let deleted_flags_count = point_to_values.len();
             let deleted_file = create_and_ensure_length(
@@ -132,20 +235,116 @@ impl<N: MapIndexKey + Key + ?Sized> MmapMapIndex<N> {
             let mut deleted_mmap = unsafe { MmapMut::map_mut(&deleted_file)? };
             deleted_mmap.fill(0);
             let mut deleted_bitflags = MmapBitSlice::from(deleted_mmap, 0);
-            for (idx, values) in point_to_values.iter().enumerate() {
-                if values.is_empty() {
+            for idx in 0..deleted_flags_count {
+                if point_to_values.get_values_count(idx as PointOffsetType) == Some(0) {
                     deleted_bitflags.set(idx, true);
                 }
             }
         }
+        drop(point_to_values);
 
         Self::open(path, is_on_disk)?.ok_or_else(|| {
             OperationError::service_error("Failed to open MmapMapIndex after building it")
         })
     }
 
+    /// Write postings of `values` into the spill file, sorted by point.
+    ///
+    /// Postings are counted in a first pass over `point_to_values`, and written in a second one.
+    /// Returns the spill file, if there are any postings, the offset of each posting followed by
+    /// the total number of postings, and the total number of values of points.
+    fn spill_postings(
+        spill_path: &Path,
+        point_to_values: &MmapPointToValues<N>,
+        values: &IndexSet<N::Owned>,
+    ) -> OperationResult<(Option<MmapMut>, Vec<usize>, usize)> {
+        let points_count = point_to_values.len() as PointOffsetType;
+        let mut indices = Vec::new();
+
+        let mut offsets = vec![0; values.len() + 1];
+        let mut total_key_value_pairs = 0;
+        for point_id in 0..points_count {
+            total_key_value_pairs += point_to_values.get_values_count(point_id).unwrap_or(0);
+            point_value_indices(point_to_values, values, point_id, &mut indices)?;
+            for &index in &indices {
+                offsets[index + 1] += 1;
+            }
+        }
+        for index in 0..values.len() {
+            offsets[index + 1] += offsets[index];
+        }
+
+        let postings_count = offsets[values.len()];
+        create_and_ensure_length(spill_path, postings_count * size_of::<PointOffsetType>())?;
+        if postings_count == 0 {
+            return Ok((None, offsets, total_key_value_pairs));
+        }
+        let mut spill = mmap::open_write_mmap(spill_path, AdviceSetting::Global, false)?;
+        let mut positions = offsets[..values.len()].to_vec();
+        for point_id in 0..points_count {
+            point_value_indices(point_to_values, values, point_id, &mut indices)?;
+            for &index in &indices {
+                let position = positions[index] * size_of::<PointOffsetType>();
+                spill[position..position + size_of::<PointOffsetType>()]
+                    .copy_from_slice(&point_id.to_le_bytes());
+                positions[index] += 1;
+            }
+        }
+
+        Ok((Some(spill), offsets, total_key_value_pairs))
+    }
+
+    /// Save `postings` as roaring bitmaps, with a hashmap of values to their positions.
+    fn create_roaring_postings(
+        path: &Path,
+        postings: &[(&N, RoaringBitmap)],
+    ) -> OperationResult<()> {
+        RoaringPostings::create(
+            &path.join(ROARING_POSTINGS_PATH),
+            postings.iter().map(|(_, bitmap)| bitmap),
+        )?;
+        MmapHashMap::create(
+            &path.join(POSITIONS_HASHMAP_PATH),
+            postings
+                .iter()
+                .enumerate()
+                .map(|(position, (value, _))| (*value, iter::once(position as PointOffsetType))),
+        )?;
+        Ok(())
+    }
+
+    /// Convert postings of an index built before into roaring bitmaps.
+    ///
+    /// The original hashmap is removed only after the config is updated, so an interrupted
+    /// conversion starts over.
+    fn convert_to_roaring_postings(
+        path: &Path,
+        hashmap: MmapHashMap<N, PointOffsetType>,
+    ) -> OperationResult<()> {
+        let postings: Vec<(&N, RoaringBitmap)> = hashmap
+            .iter_stored()
+            .map(|(value, ids)| (value, ids.iter_native().collect()))
+            .collect();
+        Self::create_roaring_postings(path, &postings)?;
+        Ok(())
+    }
+
+    fn hashmap_path(&self) -> PathBuf {
+        if self.storage.roaring_postings.is_some() {
+            self.path.join(POSITIONS_HASHMAP_PATH)
+        } else {
+            self.path.join(HASHMAP_PATH)
+        }
+    }
+
     pub fn flusher(&self) -> Flusher {
-        self.storage.deleted.flusher()
+        // Updated values are persisted before deletions of their previous versions
+        let point_to_values_flusher = self.storage.point_to_values.flusher();
+        let deleted_flusher = self.storage.deleted.flusher();
+        Box::new(move || {
+            point_to_values_flusher()?;
+            deleted_flusher()
+        })
     }
 
     pub fn wipe(self) -> OperationResult<()> {
@@ -162,28 +361,80 @@ impl<N: MapIndexKey + Key + ?Sized> MmapMapIndex<N> {
 
     pub fn files(&self) -> Vec<PathBuf> {
         let mut files = vec![
-            self.path.join(HASHMAP_PATH),
+            self.hashmap_path(),
             self.path.join(DELETED_PATH),
             self.path.join(CONFIG_PATH),
         ];
+        if self.storage.roaring_postings.is_some() {
+            files.push(self.path.join(ROARING_POSTINGS_PATH));
+        }
         files.extend(self.storage.point_to_values.files());
         files
     }
 
     pub fn immutable_files(&self) -> Vec<PathBuf> {
-        let mut files = vec![self.path.join(HASHMAP_PATH), self.path.join(CONFIG_PATH)];
+        let mut files = vec![self.hashmap_path(), self.path.join(CONFIG_PATH)];
+        if self.storage.roaring_postings.is_some() {
+            files.push(self.path.join(ROARING_POSTINGS_PATH));
+        }
         files.extend(self.storage.point_to_values.immutable_files());
         files
     }
 
-    pub fn remove_point(&mut self, idx: PointOffsetType) {
+    /// Set values of the point, which are kept in the delta until the index is rebuilt.
+    pub fn add_many_to_map<Q>(
+        &mut self,
+        idx: PointOffsetType,
+        values: Vec<Q>,
+        hw_counter: &HardwareCounterCell,
+    ) -> OperationResult<()>
+    where
+        Q: Into<N::Owned>,
+    {
+        self.remove_point(idx)?;
+        if values.is_empty() {
+            return Ok(());
+        }
+
+        let values: Vec<N::Owned> = values.into_iter().map(Into::into).collect();
+        let mut hw_cell_wb = hw_counter
+            .payload_index_io_write_counter()
+            .write_back_counter();
+        for value in &values {
+            hw_cell_wb.incr_delta(N::mmapped_size(N::as_referenced(value.borrow())));
+        }
+
+        self.storage.point_to_values.set_values(
+            idx,
+            values.iter().map(|value| N::as_referenced(value.borrow())),
+        )?;
+        self.delta.add_point(idx, values);
+        Ok(())
+    }
+
+    pub fn remove_point(&mut self, idx: PointOffsetType) -> OperationResult<()> {
+        if self.delta.remove_point(idx).is_some() {
+            // Otherwise the values would be restored from the delta file on load
+            self.storage
+                .point_to_values
+                .set_values(idx, iter::empty())?;
+        }
+
         let idx = idx as usize;
         if let Some(deleted) = self.storage.deleted.get(idx)
             && !deleted
         {
             self.storage.deleted.set(idx, true);
             self.deleted_count += 1;
+            if let Some(deleted_per_value) = &mut self.deleted_per_value {
+                count_point_values(
+                    &self.storage.point_to_values,
+                    idx as PointOffsetType,
+                    deleted_per_value,
+                );
+            }
         }
+        Ok(())
     }
 
     pub fn check_values_any(
@@ -192,6 +443,10 @@ impl<N: MapIndexKey + Key + ?Sized> MmapMapIndex<N> {
         hw_counter: &HardwareCounterCell,
         check_fn: impl Fn(&N) -> bool,
     ) -> bool {
+        if let Some(values) = self.delta.get_values(idx) {
+            return values.iter().any(|value| check_fn(value.borrow()));
+        }
+
         let hw_counter = self.make_conditioned_counter(hw_counter);
 
         // Measure self.deleted access.
@@ -216,6 +471,12 @@ impl<N: MapIndexKey + Key + ?Sized> MmapMapIndex<N> {
         &self,
         idx: PointOffsetType,
     ) -> Option<Box<dyn Iterator<Item = N::Referenced<'_>> + '_>> {
+        if let Some(values) = self.delta.get_values(idx) {
+            return Some(Box::new(
+                values.iter().map(|value| N::as_referenced(value.borrow())),
+            ));
+        }
+
         self.storage
             .deleted
             .get(idx as usize)
@@ -227,6 +488,10 @@ impl<N: MapIndexKey + Key + ?Sized> MmapMapIndex<N> {
     }
 
     pub fn values_count(&self, idx: PointOffsetType) -> Option<usize> {
+        if let Some(values) = self.delta.get_values(idx) {
+            return Some(values.len());
+        }
+
         self.storage
             .deleted
             .get(idx as usize)
@@ -237,24 +502,37 @@ impl<N: MapIndexKey + Key + ?Sized> MmapMapIndex<N> {
     pub fn get_indexed_points(&self) -> usize {
         self.storage
             .point_to_values
-            .len()
+            .file_len()
             .saturating_sub(self.deleted_count)
+            + self.delta.points_count()
     }
 
     /// Returns the number of key-value pairs in the index.
     /// Note that is doesn't count deleted pairs.
     pub fn get_values_count(&self) -> usize {
-        self.total_key_value_pairs
+        self.total_key_value_pairs + self.delta.values_count()
     }
 
     pub fn get_unique_values_count(&self) -> usize {
-        self.storage.value_to_points.keys_count()
+        self.storage.value_to_points.keys_count() + self.iter_delta_only_values().count()
     }
 
     pub fn get_count_for_value(
         &self,
         value: &N,
         hw_counter: &HardwareCounterCell,
+    ) -> Option<usize> {
+        let delta_count = self.delta.get_count_for_value(value);
+        match self.get_stored_count_for_value(value, hw_counter) {
+            Some(count) => Some(count + delta_count),
+            None => (delta_count > 0).then_some(delta_count),
+        }
+    }
+
+    fn get_stored_count_for_value(
+        &self,
+        value: &N,
+        hw_counter: &HardwareCounterCell,
     ) -> Option<usize> {
         let hw_counter = self.make_conditioned_counter(hw_counter);
 
@@ -265,7 +543,13 @@ impl<N: MapIndexKey + Key + ?Sized> MmapMapIndex<N> {
             .incr_delta(READ_ENTRY_OVERHEAD);
 
         match self.storage.value_to_points.get_stored(value) {
-            Ok(Some(points)) => Some(points.len()),
+            Ok(Some(points)) => match &self.storage.roaring_postings {
+                Some(roaring_postings) => {
+                    let posting = roaring_postings.get(points.iter_native().next()?)?;
+                    Some(posting.len() as usize)
+                }
+                None => Some(points.len()),
+            },
             Ok(None) => None,
             Err(err) => {
                 debug_assert!(
@@ -279,20 +563,36 @@ impl<N: MapIndexKey + Key + ?Sized> MmapMapIndex<N> {
     }
 
     pub fn get_iterator(&self, value: &N, hw_counter: &HardwareCounterCell) -> IdIter<'_> {
+        let stored = self.get_stored_iterator(value, hw_counter);
+        if self.delta.contains_value(value) {
+            Box::new(stored.chain(self.delta.get_iterator(value)))
+        } else {
+            stored
+        }
+    }
+
+    fn get_stored_iterator(&self, value: &N, hw_counter: &HardwareCounterCell) -> IdIter<'_> {
         let hw_counter = self.make_conditioned_counter(hw_counter);
 
         match self.storage.value_to_points.get_stored(value) {
+            Ok(Some(slice)) if self.storage.roaring_postings.is_some() => {
+                // Roaring postings are in memory, only the hashmap lookup is read
+                hw_counter
+                    .payload_index_io_read_counter()
+                    .incr_delta(READ_ENTRY_OVERHEAD);
+
+                match self.roaring_posting(slice.iter_native().next()) {
+                    Some(posting) => Box::new(posting.iter().filter(|idx| !self.is_deleted(*idx))),
+                    None => Box::new(iter::empty()),
+                }
+            }
             Ok(Some(slice)) => {
                 // We're iterating over the whole (mmapped) slice
                 hw_counter
                     .payload_index_io_read_counter()
                     .incr_delta(std::mem::size_of_val(slice.as_stored()) + READ_ENTRY_OVERHEAD);
 
-                Box::new(
-                    slice
-                        .iter_native()
-                        .filter(|idx| !self.storage.deleted.get(*idx as usize).unwrap_or(false)),
-                )
+                Box::new(slice.iter_native().filter(|idx| !self.is_deleted(*idx)))
             }
             Ok(None) => {
                 hw_counter
@@ -312,19 +612,97 @@ impl<N: MapIndexKey + Key + ?Sized> MmapMapIndex<N> {
         }
     }
 
+    /// Points with any of the `values`.
+    ///
+    /// Roaring postings are merged into a single bitmap, other postings are deduplicated while
+    /// iterating.
+    pub fn get_union_iterator<'a, K: Borrow<N> + 'a>(
+        &'a self,
+        values: impl IntoIterator<Item = K> + 'a,
+        hw_counter: &'a HardwareCounterCell,
+    ) -> IdIter<'a> {
+        let Some(roaring_postings) = &self.storage.roaring_postings else {
+            return Box::new(
+                values
+                    .into_iter()
+                    .flat_map(move |value| self.get_iterator(value.borrow(), hw_counter))
+                    .unique(),
+            );
+        };
+
+        let values: Vec<K> = values.into_iter().collect();
+        let delta_union = self.delta.union(values.iter().map(|value| value.borrow()));
+
+        let hw_counter = self.make_conditioned_counter(hw_counter);
+        let positions = values.iter().filter_map(|value| {
+            hw_counter
+                .payload_index_io_read_counter()
+                .incr_delta(READ_ENTRY_OVERHEAD);
+            self.storage
+                .value_to_points
+                .get_stored(value.borrow())
+                .ok()
+                .flatten()?
+                .iter_native()
+                .next()
+        });
+        let union = roaring_postings.union(positions);
+
+        Box::new(
+            union
+                .into_iter()
+                .filter(|idx| !self.is_deleted(*idx))
+                .chain(delta_union),
+        )
+    }
+
+    /// Whether point `idx` is deleted, points beyond the deleted bitmap are not in the index.
+    fn is_deleted(&self, idx: PointOffsetType) -> bool {
+        self.storage.deleted.get(idx as usize).unwrap_or(true)
+    }
+
+    /// Roaring posting at `position`, if postings are stored as roaring bitmaps.
+    fn roaring_posting(&self, position: Option<PointOffsetType>) -> Option<&RoaringBitmap> {
+        self.storage.roaring_postings.as_ref()?.get(position?)
+    }
+
+    /// Values of updated points, which are not stored in the index.
+    fn iter_delta_only_values(&self) -> impl Iterator<Item = (&N, &RoaringBitmap)> + '_ {
+        self.delta.iter_values_map().filter(|(value, _)| {
+            !matches!(self.storage.value_to_points.get_stored(value), Ok(Some(_)))
+        })
+    }
+
     pub fn iter_values(&self) -> impl Iterator<Item = &N> + '_ {
-        self.storage.value_to_points.keys()
+        self.storage
+            .value_to_points
+            .keys()
+            .chain(self.iter_delta_only_values().map(|(value, _)| value))
     }
 
     pub fn iter_counts_per_value(&self) -> impl Iterator<Item = (&N, usize)> + '_ {
-        self.storage.value_to_points.iter_stored().map(|(k, v)| {
-            let count = v
-                .iter_native()
-                .filter(|idx| !self.storage.deleted.get(*idx as usize).unwrap_or(true))
-                .unique()
-                .count();
-            (k, count)
-        })
+        let stored = self.storage.value_to_points.iter_stored().map(|(k, v)| {
+            let posting_len = match self.roaring_posting(v.iter_native().next()) {
+                Some(posting) => posting.len() as usize,
+                None => v.len(),
+            };
+            let count = match &self.deleted_per_value {
+                // Only the length of the posting is read
+                Some(deleted_per_value) => {
+                    posting_len.saturating_sub(deleted_per_value.get(k).copied().unwrap_or(0))
+                }
+                None => v
+                    .iter_native()
+                    .filter(|idx| !self.is_deleted(*idx))
+                    .unique()
+                    .count(),
+            };
+            (k, count + self.delta.get_count_for_value(k))
+        });
+        let delta_only = self
+            .iter_delta_only_values()
+            .map(|(value, points)| (value, points.len() as usize));
+        stored.chain(delta_only)
     }
 
     pub fn iter_values_map<'a>(
@@ -333,24 +711,91 @@ impl<N: MapIndexKey + Key + ?Sized> MmapMapIndex<N> {
     ) -> impl Iterator<Item = (&'a N, IdIter<'a>)> + 'a {
         let hw_counter = self.make_conditioned_counter(hw_counter);
 
-        self.storage.value_to_points.iter_stored().map(move |(k, v)| {
-            hw_counter
-                .payload_index_io_read_counter()
-                .incr_delta(k.write_bytes());
-
-            (
-                k,
-                Box::new(
-                    v.iter_native()
-                        .filter(|idx| !self.storage.deleted.get(*idx as usize).unwrap_or(true))
-                        .measure_hw_with_acc(
-                            hw_counter.new_accumulator(),
-                            size_of::<PointOffsetType>(),
-                            |i| i.payload_index_io_read_counter(),
-                        ),
-                ) as IdIter,
-            )
-        })
+        let stored = self
+            .storage
+            .value_to_points
+            .iter_stored()
+            .map(move |(k, v)| {
+                hw_counter
+                    .payload_index_io_read_counter()
+                    .incr_delta(k.write_bytes());
+
+                let ids: IdIter = match &self.storage.roaring_postings {
+                    Some(_) => match self.roaring_posting(v.iter_native().next()) {
+                        Some(posting) => Box::new(posting.iter()),
+                        None => Box::new(iter::empty()),
+                    },
+                    None => Box::new(v.iter_native()),
+                };
+
+                (
+                    k,
+                    Box::new(
+                        ids.filter(|idx| !self.is_deleted(*idx))
+                            .measure_hw_with_acc(
+                                hw_counter.new_accumulator(),
+                                size_of::<PointOffsetType>(),
+                                |i| i.payload_index_io_read_counter(),
+                            )
+                            .chain(self.delta.get_iterator(k)),
+                    ) as IdIter,
+                )
+            });
+        let delta_only = self
+            .iter_delta_only_values()
+            .map(|(value, points)| (value, Box::new(points.iter()) as IdIter));
+        stored.chain(delta_only)
+    }
+
+    /// Share of points served by the delta, relative to the points stored in the index.
+    pub fn delta_ratio(&self) -> f64 {
+        let point_to_values = &self.storage.point_to_values;
+        point_to_values.delta_points_count() as f64 / point_to_values.file_len().max(1) as f64
+    }
+
+    /// Rebuild the index from the current values of points, dropping the delta.
+    ///
+    /// New files are written into a staging directory next to the index, which then atomically
+    /// replaces the index directory, see [`common::fs::replace_dir`]. Point offsets are preserved.
+    pub fn compact(&mut self) -> OperationResult<()> {
+        let path = self.path.clone();
+        let staged_path = common::fs::staged_dir_path(&path);
+        // Leftover of a compaction, which was interrupted before the swap
+        if staged_path.exists() {
+            fs::remove_dir_all(&staged_path)?;
+        }
+
+        let mut point_to_values = MmapPointToValuesBuilder::new(&staged_path);
+        let mut values = IndexSet::new();
+        for idx in 0..self.storage.point_to_values.len() as PointOffsetType {
+            let Some(point_values) = self.get_values(idx) else {
+                continue;
+            };
+            let point_values: Vec<_> = point_values.collect();
+            if point_values.is_empty() {
+                continue;
+            }
+            for value in &point_values {
+                values.insert(N::to_owned(N::from_referenced(value)));
+            }
+            point_to_values.add_point(idx, point_values)?;
+        }
+        drop(Self::build(
+            &staged_path,
+            point_to_values,
+            values,
+            self.is_on_disk,
+        )?);
+
+        common::fs::replace_dir(&path)?;
+
+        *self = Self::open(&path, self.is_on_disk)?.ok_or_else(|| {
+            OperationError::service_error(format!(
+                "Failed to reopen compacted map index at {}",
+                path.display(),
+            ))
+        })?;
+        Ok(())
     }
 
     fn make_conditioned_counter<'a>(
@@ -374,7 +819,7 @@ impl<N: MapIndexKey + Key + ?Sized> MmapMapIndex<N> {
 
     /// Drop disk cache.
     pub fn clear_cache(&self) -> OperationResult<()> {
-        let value_to_points_path = self.path.join(HASHMAP_PATH);
+        let value_to_points_path = self.hashmap_path();
         let deleted_path = self.path.join(DELETED_PATH);
 
         clear_disk_cache(&value_to_points_path)?;
@@ -384,3 +829,39 @@ impl<N: MapIndexKey + Key + ?Sized> MmapMapIndex<N> {
         Ok(())
     }
 }
+
+/// Indices of the distinct values of the point in `values`.
+fn point_value_indices<N: MapIndexKey + Key + ?Sized>(
+    point_to_values: &MmapPointToValues<N>,
+    values: &IndexSet<N::Owned>,
+    point_id: PointOffsetType,
+    indices: &mut Vec<usize>,
+) -> OperationResult<()> {
+    indices.clear();
+    for value in point_to_values.get_values(point_id).into_iter().flatten() {
+        let index = values
+            .get_index_of(N::from_referenced(&value))
+            .ok_or_else(|| OperationError::service_error("Value of a point is not indexed"))?;
+        indices.push(index);
+    }
+    indices.sort_unstable();
+    indices.dedup();
+    Ok(())
+}
+
+/// Count each distinct value of the point in the file once.
+fn count_point_values<N: MapIndexKey + ?Sized>(
+    point_to_values: &MmapPointToValues<N>,
+    idx: PointOffsetType,
+    counts: &mut HashMap<N::Owned, usize>,
+) {
+    let Some(values) = point_to_values.get_file_values(idx) else {
+        return;
+    };
+    for value in values
+        .map(|value| N::to_owned(N::from_referenced(&value)))
+        .unique()
+    {
+        *counts.entry(value).or_default() += 1;
+    }
+}
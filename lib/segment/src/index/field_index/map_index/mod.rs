@@ -1,5 +1,4 @@
 use std::borrow::Borrow;
-use std::collections::hash_map::Entry;
 use std::fmt::{Debug, Display};
 use std::hash::{BuildHasher, Hash};
 use std::iter;
@@ -8,7 +7,6 @@ use std::str::FromStr;
 #[cfg(feature = "rocksdb")]
 use std::sync::Arc;
 
-use ahash::HashMap;
 use common::counter::hardware_counter::HardwareCounterCell;
 use common::mmap_hashmap::Key;
 use common::types::PointOffsetType;
@@ -28,7 +26,7 @@ use self::immutable_map_index::ImmutableMapIndex;
 use self::mutable_map_index::MutableMapIndex;
 use super::FieldIndexBuilderTrait;
 use super::facet_index::FacetIndex;
-use super::mmap_point_to_values::MmapValue;
+use super::mmap_point_to_values::{MmapPointToValuesBuilder, MmapValue};
 use crate::common::Flusher;
 use crate::common::operation_error::{OperationError, OperationResult};
 use crate::data_types::facets::{FacetHit, FacetValueRef};
@@ -46,8 +44,10 @@ use crate::types::{
 };
 
 pub mod immutable_map_index;
+mod map_index_delta;
 pub mod mmap_map_index;
 pub mod mutable_map_index;
+mod roaring_postings;
 
 /// Block size in Gridstore for keyword map index.
 /// Keyword(s) are stored as cbor vector.
@@ -163,8 +163,8 @@ where
     pub fn builder_mmap(path: &Path, is_on_disk: bool) -> MapIndexMmapBuilder<N> {
         MapIndexMmapBuilder {
             path: path.to_owned(),
-            point_to_values: Default::default(),
-            values_to_points: Default::default(),
+            point_to_values: MmapPointToValuesBuilder::new(path),
+            values: Default::default(),
             is_on_disk,
         }
     }
@@ -304,6 +304,7 @@ where
             points_count: self.get_indexed_points(),
             points_values_count: self.get_values_count(),
             histogram_bucket_size: None,
+            resources: None,
             index_type: match self {
                 MapIndex::Mutable(_) => "mutable_map",
                 MapIndex::Immutable(_) => "immutable_map",
@@ -349,10 +350,7 @@ where
         match self {
             MapIndex::Mutable(index) => index.remove_point(id),
             MapIndex::Immutable(index) => index.remove_point(id),
-            MapIndex::Mmap(index) => {
-                index.remove_point(id);
-                Ok(())
-            }
+            MapIndex::Mmap(index) => index.remove_point(id),
         }
     }
 
@@ -494,14 +492,30 @@ where
         A: BuildHasher,
         K: Borrow<N> + Hash + Eq,
     {
-        Box::new(
+        self.get_union_iterator(
             self.iter_values()
-                .filter(|key| !excluded.contains((*key).borrow()))
-                .flat_map(move |key| self.get_iterator(key.borrow(), hw_counter))
-                .unique(),
+                .filter(|key| !excluded.contains((*key).borrow())),
+            hw_counter,
         )
     }
 
+    /// Points with any of the `values`.
+    fn get_union_iterator<'a, K: Borrow<N> + 'a>(
+        &'a self,
+        values: impl IntoIterator<Item = K> + 'a,
+        hw_counter: &'a HardwareCounterCell,
+    ) -> IdIter<'a> {
+        match self {
+            MapIndex::Mmap(index) => index.get_union_iterator(values, hw_counter),
+            _ => Box::new(
+                values
+                    .into_iter()
+                    .flat_map(move |value| self.get_iterator(value.borrow(), hw_counter))
+                    .unique(),
+            ),
+        }
+    }
+
     pub fn is_on_disk(&self) -> bool {
         match self {
             MapIndex::Mutable(_) => false,
@@ -542,6 +556,40 @@ where
         Ok(())
     }
 
+    /// Whether values of points can be updated in place.
+    ///
+    /// Immutable indexes accept updates only with mmap storage, which keeps them in a delta.
+    pub fn accepts_updates(&self) -> bool {
+        match self {
+            MapIndex::Mutable(_) => true,
+            MapIndex::Immutable(index) => index.accepts_updates(),
+            MapIndex::Mmap(_) => true,
+        }
+    }
+
+    /// Share of points updated on top of the immutable index, see [`Self::compact_if_needed`].
+    pub fn delta_ratio(&self) -> f64 {
+        match self {
+            MapIndex::Mutable(_) => 0.0,
+            MapIndex::Immutable(index) => index.delta_ratio(),
+            MapIndex::Mmap(index) => index.delta_ratio(),
+        }
+    }
+
+    /// Rebuild the immutable index with updated values of points, if the share of updated points
+    /// exceeds `threshold`. Returns whether the index was rebuilt.
+    pub fn compact_if_needed(&mut self, threshold: f64) -> OperationResult<bool> {
+        if self.delta_ratio() <= threshold {
+            return Ok(false);
+        }
+        match self {
+            MapIndex::Mutable(_) => return Ok(false),
+            MapIndex::Immutable(index) => index.compact()?,
+            MapIndex::Mmap(index) => index.compact()?,
+        }
+        Ok(true)
+    }
+
     pub fn get_mutability_type(&self) -> IndexMutability {
         match self {
             Self::Mutable(_) => IndexMutability::Mutable,
@@ -596,8 +644,9 @@ where
 
 pub struct MapIndexMmapBuilder<N: MapIndexKey + ?Sized> {
     path: PathBuf,
-    point_to_values: Vec<Vec<N::Owned>>,
-    values_to_points: HashMap<N::Owned, Vec<PointOffsetType>>,
+    point_to_values: MmapPointToValuesBuilder<N>,
+    /// Unique values of points, postings are collected from `point_to_values` on finalize
+    values: IndexSet<N::Owned>,
     is_on_disk: bool,
 }
 
@@ -626,26 +675,26 @@ where
         }
         let flatten_values: Vec<N::Owned> = flatten_values.into_iter().map(Into::into).collect();
 
-        if self.point_to_values.len() <= id as usize {
-            self.point_to_values.resize_with(id as usize + 1, Vec::new);
-        }
-
-        self.point_to_values[id as usize].extend(flatten_values.clone());
+        self.point_to_values.add_point(
+            id,
+            flatten_values
+                .iter()
+                .map(|value| N::as_referenced(value.borrow())),
+        )?;
 
         let mut hw_cell_wb = hw_counter
             .payload_index_io_write_counter()
             .write_back_counter();
 
         for value in flatten_values {
-            let entry = self.values_to_points.entry(value);
+            let (index, is_new) = self.values.insert_full(value);
 
-            if let Entry::Vacant(e) = &entry {
-                let size = N::mmapped_size(N::as_referenced(e.key().borrow()));
+            if is_new {
+                let size = N::mmapped_size(N::as_referenced(self.values[index].borrow()));
                 hw_cell_wb.incr_delta(size);
             }
 
             hw_cell_wb.incr_delta(size_of_val(&id));
-            entry.or_default().push(id);
         }
 
         Ok(())
@@ -655,7 +704,7 @@ where
         Ok(MapIndex::Mmap(Box::new(MmapMapIndex::build(
             &self.path,
             self.point_to_values,
-            self.values_to_points,
+            self.values,
             self.is_on_disk,
         )?)))
     }
@@ -759,11 +808,9 @@ impl PayloadFieldIndex for MapIndex<str> {
                 ValueVariants::Bool(_) => None,
             },
             Some(Match::Any(MatchAny { any: any_variant })) => match any_variant {
-                AnyVariants::Strings(keywords) => Some(Box::new(
-                    keywords
-                        .iter()
-                        .flat_map(move |keyword| self.get_iterator(keyword.as_str(), hw_counter))
-                        .unique(),
+                AnyVariants::Strings(keywords) => Some(self.get_union_iterator(
+                    keywords.iter().map(|keyword| keyword.as_str()),
+                    hw_counter,
                 )),
                 AnyVariants::Integers(integers) => {
                     if integers.is_empty() {
@@ -916,12 +963,7 @@ impl PayloadFieldIndex for MapIndex<UuidIntType> {
 
                     let uuids = uuids.ok()?;
 
-                    Some(Box::new(
-                        uuids
-                            .into_iter()
-                            .flat_map(move |uuid| self.get_iterator(&uuid, hw_counter))
-                            .unique(),
-                    ))
+                    Some(self.get_union_iterator(uuids, hw_counter))
                 }
                 AnyVariants::Integers(integers) => {
                     if integers.is_empty() {
@@ -939,12 +981,13 @@ impl PayloadFieldIndex for MapIndex<UuidIntType> {
                         .collect();
 
                     let excluded_uuids = uuids.ok()?;
-                    let exclude_iter = self
-                        .iter_values()
-                        .filter(move |key| !excluded_uuids.contains(*key))
-                        .flat_map(move |key| self.get_iterator(key, hw_counter))
-                        .unique();
-                    Some(Box::new(exclude_iter))
+                    Some(
+                        self.get_union_iterator(
+                            self.iter_values()
+                                .filter(move |key| !excluded_uuids.contains(*key)),
+                            hw_counter,
+                        ),
+                    )
                 }
                 AnyVariants::Integers(other) => {
                     if other.is_empty() {
@@ -1103,12 +1146,9 @@ impl PayloadFieldIndex for MapIndex<IntPayloadType> {
                         None
                     }
                 }
-                AnyVariants::Integers(integers) => Some(Box::new(
-                    integers
-                        .iter()
-                        .flat_map(move |integer| self.get_iterator(integer, hw_counter))
-                        .unique(),
-                )),
+                AnyVariants::Integers(integers) => {
+                    Some(self.get_union_iterator(integers, hw_counter))
+                }
             },
             Some(Match::Except(MatchExcept { except })) => match except {
                 AnyVariants::Strings(other) => {
@@ -1256,12 +1296,8 @@ impl ValueIndexer for MapIndex<str> {
     ) -> OperationResult<()> {
         match self {
             MapIndex::Mutable(index) => index.add_many_to_map(id, values, hw_counter),
-            MapIndex::Immutable(_) => Err(OperationError::service_error(
-                "Can't add values to immutable map index",
-            )),
-            MapIndex::Mmap(_) => Err(OperationError::service_error(
-                "Can't add values to mmap map index",
-            )),
+            MapIndex::Immutable(index) => index.add_many_to_map(id, values, hw_counter),
+            MapIndex::Mmap(index) => index.add_many_to_map(id, values, hw_counter),
         }
     }
 
@@ -1288,12 +1324,8 @@ impl ValueIndexer for MapIndex<IntPayloadType> {
     ) -> OperationResult<()> {
         match self {
             MapIndex::Mutable(index) => index.add_many_to_map(id, values, hw_counter),
-            MapIndex::Immutable(_) => Err(OperationError::service_error(
-                "Can't add values to immutable map index",
-            )),
-            MapIndex::Mmap(_) => Err(OperationError::service_error(
-                "Can't add values to mmap map index",
-            )),
+            MapIndex::Immutable(index) => index.add_many_to_map(id, values, hw_counter),
+            MapIndex::Mmap(index) => index.add_many_to_map(id, values, hw_counter),
         }
     }
 
@@ -1317,12 +1349,8 @@ impl ValueIndexer for MapIndex<UuidIntType> {
     ) -> OperationResult<()> {
         match self {
             MapIndex::Mutable(index) => index.add_many_to_map(id, values, hw_counter),
-            MapIndex::Immutable(_) => Err(OperationError::service_error(
-                "Can't add values to immutable map index",
-            )),
-            MapIndex::Mmap(_) => Err(OperationError::service_error(
-                "Can't add values to mmap map index",
-            )),
+            MapIndex::Immutable(index) => index.add_many_to_map(id, values, hw_counter),
+            MapIndex::Mmap(index) => index.add_many_to_map(id, values, hw_counter),
         }
     }
 
@@ -1347,6 +1375,7 @@ mod tests {
     use super::*;
     #[cfg(feature = "rocksdb")]
     use crate::common::rocksdb_wrapper::open_db_with_existing_cf;
+    use crate::json_path::JsonPath;
 
     #[cfg(feature = "rocksdb")]
     const FIELD_NAME: &str = "test";
@@ -1527,6 +1556,27 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_mmap_builder_replaces_values_of_point_added_again() {
+        let temp_dir = Builder::new().prefix("store_dir").tempdir().unwrap();
+        let mut builder = MapIndex::<IntPayloadType>::builder_mmap(temp_dir.path(), false);
+        builder.init().unwrap();
+
+        let hw_counter = HardwareCounterCell::new();
+        for (idx, values) in [(0, vec![1, 2]), (1, vec![2]), (0, vec![3, 3])] {
+            let values: Vec<Value> = values.into_iter().map(Value::from).collect();
+            let values: Vec<_> = values.iter().collect();
+            builder.add_point(idx, &values, &hw_counter).unwrap();
+        }
+        let index = builder.finalize().unwrap();
+
+        let points = |value: IntPayloadType| index.get_iterator(&value, &hw_counter).collect_vec();
+        assert_eq!(points(1), Vec::<PointOffsetType>::new());
+        assert_eq!(points(2), vec![1]);
+        assert_eq!(points(3), vec![0]);
+        assert_eq!(index.get_unique_values_count(), 2);
+    }
+
     #[rstest]
     #[cfg_attr(feature = "rocksdb", case(IndexType::Mutable))]
     #[case(IndexType::MutableGridstore)]
@@ -1601,6 +1651,160 @@ mod tests {
         );
     }
 
+    #[rstest]
+    #[cfg_attr(feature = "rocksdb", case(IndexType::Mutable))]
+    #[case(IndexType::MutableGridstore)]
+    #[cfg_attr(feature = "rocksdb", case(IndexType::Immutable))]
+    #[case(IndexType::Mmap)]
+    #[case(IndexType::RamMmap)]
+    fn test_counts_per_value(#[case] index_type: IndexType) {
+        let data = vec![
+            vec![EcoString::from("AA"), EcoString::from("AA")],
+            vec![EcoString::from("AA"), EcoString::from("BB")],
+            vec![EcoString::from("BB")],
+            vec![EcoString::from("CC"), EcoString::from("AA")],
+        ];
+
+        let temp_dir = Builder::new().prefix("store_dir").tempdir().unwrap();
+        save_map_index::<str>(&data, temp_dir.path(), index_type, |v| v.to_string().into());
+        let mut index = load_map_index::<str>(&data, temp_dir.path(), index_type);
+
+        let counts = |index: &MapIndex<str>| {
+            let mut counts: Vec<_> = index
+                .iter_counts_per_value()
+                .filter(|(_, count)| *count > 0)
+                .map(|(value, count)| (value.to_string(), count))
+                .collect();
+            counts.sort();
+            counts
+        };
+
+        let expected = |pairs: &[(&str, usize)]| {
+            pairs
+                .iter()
+                .map(|(value, count)| (value.to_string(), *count))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(counts(&index), expected(&[("AA", 3), ("BB", 2), ("CC", 1)]));
+
+        index.remove_point(1).unwrap();
+        assert_eq!(counts(&index), expected(&[("AA", 2), ("BB", 1), ("CC", 1)]));
+
+        // Deletions must be taken into account after reopening as well
+        index.flusher()().unwrap();
+        drop(index);
+        // Only check values of the first point, the removed one has none
+        let index = load_map_index::<str>(&data[..1], temp_dir.path(), index_type);
+        assert_eq!(counts(&index), expected(&[("AA", 2), ("BB", 1), ("CC", 1)]));
+    }
+
+    #[rstest]
+    #[case(IndexType::MutableGridstore)]
+    #[case(IndexType::Mmap)]
+    #[case(IndexType::RamMmap)]
+    fn test_update_points(#[case] index_type: IndexType) {
+        let data = vec![
+            vec![EcoString::from("AA"), EcoString::from("BB")],
+            vec![EcoString::from("BB")],
+            vec![EcoString::from("CC")],
+        ];
+
+        let temp_dir = Builder::new().prefix("store_dir").tempdir().unwrap();
+        save_map_index::<str>(&data, temp_dir.path(), index_type, |v| v.to_string().into());
+        let mut index = load_map_index::<str>(&data, temp_dir.path(), index_type);
+        assert!(index.accepts_updates());
+
+        let hw_counter = HardwareCounterCell::new();
+        index
+            .add_point(0, &[&Value::from("DD")], &hw_counter)
+            .unwrap();
+        index
+            .add_point(2, &[&Value::from(vec!["CC", "DD"])], &hw_counter)
+            .unwrap();
+        index
+            .add_point(5, &[&Value::from("EE")], &hw_counter)
+            .unwrap();
+        // Update a point, then remove it
+        index
+            .add_point(1, &[&Value::from("FF")], &hw_counter)
+            .unwrap();
+        index.remove_point(1).unwrap();
+
+        let updated = [
+            vec![EcoString::from("DD")],
+            vec![],
+            vec![EcoString::from("CC"), EcoString::from("DD")],
+            vec![],
+            vec![],
+            vec![EcoString::from("EE")],
+        ];
+        let check = |index: &MapIndex<str>| {
+            for (idx, values) in updated.iter().enumerate() {
+                let index_values: HashSet<String> = index
+                    .get_values(idx as PointOffsetType)
+                    .into_iter()
+                    .flatten()
+                    .map(|v| v.to_string())
+                    .collect();
+                let values: HashSet<String> = values.iter().map(|v| v.to_string()).collect();
+                assert_eq!(index_values, values, "values of point {idx}");
+            }
+
+            let points = |value: &str| {
+                let mut points = index.get_iterator(value, &hw_counter).collect_vec();
+                points.sort_unstable();
+                points
+            };
+            assert_eq!(points("AA"), Vec::<PointOffsetType>::new());
+            assert_eq!(points("BB"), Vec::<PointOffsetType>::new());
+            assert_eq!(points("CC"), vec![2]);
+            assert_eq!(points("DD"), vec![0, 2]);
+            assert_eq!(points("EE"), vec![5]);
+            assert_eq!(points("FF"), Vec::<PointOffsetType>::new());
+
+            let mut union = index
+                .get_union_iterator(["BB", "DD", "EE"], &hw_counter)
+                .collect_vec();
+            union.sort_unstable();
+            assert_eq!(union, vec![0, 2, 5]);
+
+            let mut counts: Vec<_> = index
+                .iter_counts_per_value()
+                .filter(|(_, count)| *count > 0)
+                .map(|(value, count)| (value.to_string(), count))
+                .collect();
+            counts.sort();
+            assert_eq!(
+                counts,
+                vec![
+                    ("CC".to_string(), 1),
+                    ("DD".to_string(), 2),
+                    ("EE".to_string(), 1),
+                ],
+            );
+            assert_eq!(index.get_indexed_points(), 3);
+            assert!(index.check_values_any(5, &hw_counter, |v| v == "EE"));
+            assert!(!index.check_values_any(0, &hw_counter, |v| v == "AA"));
+        };
+        check(&index);
+
+        // Updates are persisted
+        index.flusher()().unwrap();
+        drop(index);
+        let mut index = load_map_index::<str>(&[], temp_dir.path(), index_type);
+        check(&index);
+
+        // Updates are folded into the rebuilt index
+        let is_immutable = index.get_mutability_type() == IndexMutability::Immutable;
+        assert_eq!(index.compact_if_needed(0.0).unwrap(), is_immutable);
+        assert_eq!(index.delta_ratio(), 0.0);
+        check(&index);
+        drop(index);
+        let index = load_map_index::<str>(&[], temp_dir.path(), index_type);
+        check(&index);
+    }
+
     #[rstest]
     #[cfg_attr(feature = "rocksdb", case(IndexType::Mutable))]
     #[case(IndexType::MutableGridstore)]
@@ -1623,4 +1827,43 @@ mod tests {
                 .equals_min_exp_max(&CardinalityEstimation::exact(0))
         );
     }
+
+    #[rstest]
+    #[cfg_attr(feature = "rocksdb", case(IndexType::Mutable))]
+    #[case(IndexType::MutableGridstore)]
+    #[cfg_attr(feature = "rocksdb", case(IndexType::Immutable))]
+    #[case(IndexType::Mmap)]
+    #[case(IndexType::RamMmap)]
+    fn test_match_any_and_except(#[case] index_type: IndexType) {
+        let data = vec![
+            vec![EcoString::from("AA"), EcoString::from("BB")],
+            vec![EcoString::from("BB")],
+            vec![EcoString::from("CC")],
+            vec![EcoString::from("AA"), EcoString::from("CC")],
+            vec![EcoString::from("DD")],
+        ];
+
+        let temp_dir = Builder::new().prefix("store_dir").tempdir().unwrap();
+        save_map_index::<str>(&data, temp_dir.path(), index_type, |v| v.to_string().into());
+        let mut index = load_map_index::<str>(&data, temp_dir.path(), index_type);
+
+        let hw_counter = HardwareCounterCell::new();
+        let filter = |index: &MapIndex<str>, r#match: Match| {
+            let condition = FieldCondition::new_match(JsonPath::new("test"), r#match);
+            index
+                .filter(&condition, &hw_counter)
+                .unwrap()
+                .sorted()
+                .collect_vec()
+        };
+        let any = || Match::from(vec!["AA".to_string(), "CC".to_string()]);
+        let except = || Match::Except(MatchExcept::from(vec!["AA".to_string(), "DD".to_string()]));
+
+        assert_eq!(filter(&index, any()), vec![0, 2, 3]);
+        assert_eq!(filter(&index, except()), vec![0, 1, 2, 3]);
+
+        index.remove_point(3).unwrap();
+        assert_eq!(filter(&index, any()), vec![0, 2]);
+        assert_eq!(filter(&index, except()), vec![0, 1, 2]);
+    }
 }
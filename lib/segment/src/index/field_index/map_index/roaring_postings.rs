@@ -0,0 +1,197 @@
+use std::io::Write;
+use std::path::Path;
+
+use common::fs::atomic_save;
+use common::mmap::{AdviceSetting, open_read_mmap};
+use common::types::PointOffsetType;
+use roaring::RoaringBitmap;
+
+use crate::common::operation_error::{OperationError, OperationResult};
+
+const ROARING_MAGIC: &[u8; 4] = b"rpst";
+const ROARING_VERSION: u32 = 1;
+/// Magic, version and number of postings
+const ROARING_HEADER_SIZE: usize = 16;
+
+/// Max number of unique values to store postings as roaring bitmaps.
+///
+/// Each value of a low-cardinality field is expected to be shared by many points, so its posting
+/// compresses well, and filters combining several values become bitmap operations.
+pub const ROARING_POSTINGS_MAX_VALUES: usize = 256;
+
+/// Postings of a low-cardinality field, as roaring bitmaps addressed by position.
+///
+/// Persisted in a single little-endian file:
+///
+/// ```text
+/// header:  [magic: "rpst"][version: u32][postings_count: u64]
+/// offsets: [offset: u64] * (postings_count + 1)
+/// data:    [bitmap: roaring portable format] * postings_count
+/// ```
+///
+/// Offsets are relative to the start of the data. The roaring portable format is little-endian
+/// on any host. Bitmaps are decoded into memory on open.
+#[derive(Debug)]
+pub struct RoaringPostings {
+    bitmaps: Vec<RoaringBitmap>,
+}
+
+impl RoaringPostings {
+    /// Save `postings` to `path`, position of a posting is its index in the iterator.
+    pub fn create<'a>(
+        path: &Path,
+        postings: impl ExactSizeIterator<Item = &'a RoaringBitmap> + Clone,
+    ) -> OperationResult<()> {
+        atomic_save::<OperationError, _>(path, |writer| {
+            writer.write_all(ROARING_MAGIC)?;
+            writer.write_all(&ROARING_VERSION.to_le_bytes())?;
+            writer.write_all(&(postings.len() as u64).to_le_bytes())?;
+
+            let mut offset = 0u64;
+            writer.write_all(&offset.to_le_bytes())?;
+            for bitmap in postings.clone() {
+                offset += bitmap.serialized_size() as u64;
+                writer.write_all(&offset.to_le_bytes())?;
+            }
+            for bitmap in postings {
+                bitmap.serialize_into(&mut *writer)?;
+            }
+            Ok(())
+        })
+    }
+
+    pub fn open(path: &Path) -> OperationResult<Self> {
+        let mmap = open_read_mmap(path, AdviceSetting::Global, false)?;
+        if mmap.len() < ROARING_HEADER_SIZE || &mmap[0..4] != ROARING_MAGIC {
+            return Err(OperationError::service_error(format!(
+                "Corrupted {}: bad header",
+                path.display(),
+            )));
+        }
+
+        let version = read_u32(&mmap, 4);
+        if version != ROARING_VERSION {
+            return Err(OperationError::service_error(format!(
+                "Unsupported {} version: {version}",
+                path.display(),
+            )));
+        }
+
+        let postings_count = read_u64(&mmap, 8) as usize;
+        let data_start = postings_count
+            .checked_add(1)
+            .and_then(|count| count.checked_mul(size_of::<u64>()))
+            .and_then(|size| size.checked_add(ROARING_HEADER_SIZE))
+            .filter(|&data_start| data_start <= mmap.len())
+            .ok_or_else(|| {
+                OperationError::service_error(format!(
+                    "Corrupted {}: {postings_count} postings don't fit {} bytes",
+                    path.display(),
+                    mmap.len(),
+                ))
+            })?;
+
+        let data = &mmap[data_start..];
+        let offset = |position: usize| {
+            read_u64(&mmap, ROARING_HEADER_SIZE + position * size_of::<u64>()) as usize
+        };
+        let bitmaps = (0..postings_count)
+            .map(|position| {
+                let bytes = data
+                    .get(offset(position)..offset(position + 1))
+                    .ok_or_else(|| {
+                        OperationError::service_error(format!(
+                            "Corrupted {}: posting {position} is out of bounds",
+                            path.display(),
+                        ))
+                    })?;
+                RoaringBitmap::deserialize_from(bytes).map_err(|err| {
+                    OperationError::service_error(format!(
+                        "Corrupted {}: failed to decode posting {position}: {err}",
+                        path.display(),
+                    ))
+                })
+            })
+            .collect::<OperationResult<_>>()?;
+
+        Ok(Self { bitmaps })
+    }
+
+    pub fn get(&self, position: PointOffsetType) -> Option<&RoaringBitmap> {
+        self.bitmaps.get(position as usize)
+    }
+
+    /// Points in any of the postings at `positions`.
+    pub fn union(&self, positions: impl IntoIterator<Item = PointOffsetType>) -> RoaringBitmap {
+        let mut union = RoaringBitmap::new();
+        for bitmap in positions
+            .into_iter()
+            .filter_map(|position| self.get(position))
+        {
+            union |= bitmap;
+        }
+        union
+    }
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(
+        bytes[offset..offset + 4]
+            .try_into()
+            .expect("slice length mismatch"),
+    )
+}
+
+fn read_u64(bytes: &[u8], offset: usize) -> u64 {
+    u64::from_le_bytes(
+        bytes[offset..offset + 8]
+            .try_into()
+            .expect("slice length mismatch"),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::Builder;
+
+    use super::*;
+
+    #[test]
+    fn test_roaring_postings_roundtrip() {
+        let dir = Builder::new().prefix("roaring_postings").tempdir().unwrap();
+        let path = dir.path().join("postings.roaring");
+
+        let postings = [
+            [1, 5, 100_000].into_iter().collect(),
+            RoaringBitmap::new(),
+            (0..10_000).collect(),
+        ];
+        RoaringPostings::create(&path, postings.iter()).unwrap();
+
+        let loaded = RoaringPostings::open(&path).unwrap();
+        for (position, bitmap) in postings.iter().enumerate() {
+            assert_eq!(loaded.get(position as PointOffsetType), Some(bitmap));
+        }
+        assert_eq!(loaded.get(postings.len() as PointOffsetType), None);
+
+        let union = loaded.union([0, 2, 7]);
+        assert_eq!(union.len(), 10_001);
+        assert!(union.contains(100_000));
+    }
+
+    #[test]
+    fn test_roaring_postings_corrupted() {
+        let dir = Builder::new().prefix("roaring_postings").tempdir().unwrap();
+        let path = dir.path().join("postings.roaring");
+
+        RoaringPostings::create(
+            &path,
+            [[1, 2, 3].into_iter().collect::<RoaringBitmap>()].iter(),
+        )
+        .unwrap();
+        let bytes = fs_err::read(&path).unwrap();
+        fs_err::write(&path, &bytes[..bytes.len() - 1]).unwrap();
+
+        assert!(RoaringPostings::open(&path).is_err());
+    }
+}
@@ -1,18 +1,29 @@
 use std::cmp::max;
+use std::io::{BufWriter, Seek as _, SeekFrom, Write as _};
 use std::path::{Path, PathBuf};
+use std::sync::{Arc, OnceLock};
 
+use ahash::AHashMap;
 use common::counter::conditioned_counter::ConditionedCounter;
 use common::fs::clear_disk_cache;
-use common::mmap::{AdviceSetting, Madviseable, create_and_ensure_length, open_write_mmap};
+use common::mmap::{
+    Advice, AdviceSetting, Madviseable, create_and_ensure_length, open_read_mmap, open_write_mmap,
+};
 use common::types::PointOffsetType;
+use fs_err as fs;
+use fs_err::{File, OpenOptions};
 use memmap2::Mmap;
 use ordered_float::OrderedFloat;
+use parking_lot::Mutex;
 use zerocopy::{FromBytes, Immutable, IntoBytes, KnownLayout};
 
+use crate::common::Flusher;
 use crate::common::operation_error::{OperationError, OperationResult};
 use crate::types::{FloatPayloadType, GeoPoint, IntPayloadType, UuidIntType};
 
 const POINT_TO_VALUES_PATH: &str = "point_to_values.bin";
+const POINT_TO_VALUES_SPILL_PATH: &str = "point_to_values.bin.spill";
+const POINT_TO_VALUES_DELTA_PATH: &str = "point_to_values_delta.bin";
 const NOT_ENOUGH_BYTES_ERROR_MESSAGE: &str = "Not enough bytes to operate with memmapped file `point_to_values.bin`. Is the storage corrupted?";
 const NOT_ENOUGHT_BYTES_ERROR_MESSAGE: &str = NOT_ENOUGH_BYTES_ERROR_MESSAGE;
 const PADDING_SIZE: usize = 4096;
@@ -35,6 +46,19 @@ pub trait MmapValue {
     fn from_referenced<'a>(value: &'a Self::Referenced<'_>) -> &'a Self;
 
     fn as_referenced(&self) -> Self::Referenced<'_>;
+
+    /// Whether values can be stored with [`PointToValuesCompression::Prefix`].
+    const PREFIX_COMPRESSIBLE: bool = false;
+
+    /// Bytes of the value to front-code, if the value is prefix compressible.
+    fn as_prefix_bytes<'a>(_value: &'a Self::Referenced<'_>) -> Option<&'a [u8]> {
+        None
+    }
+
+    /// Value from the bytes restored from front coding, if the value is prefix compressible.
+    fn from_prefix_bytes(_bytes: &[u8]) -> Option<Self::Referenced<'_>> {
+        None
+    }
 }
 
 #[cfg(target_endian = "little")]
@@ -292,17 +316,66 @@ impl MmapValue for str {
     fn as_referenced(&self) -> Self::Referenced<'_> {
         self
     }
+
+    const PREFIX_COMPRESSIBLE: bool = true;
+
+    fn as_prefix_bytes<'a>(value: &'a Self::Referenced<'_>) -> Option<&'a [u8]> {
+        Some(value.as_bytes())
+    }
+
+    fn from_prefix_bytes(bytes: &[u8]) -> Option<&str> {
+        std::str::from_utf8(bytes).ok()
+    }
+}
+
+/// Compression of values in `point_to_values.bin`.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum PointToValuesCompression {
+    #[default]
+    None,
+    /// Front coding of string values: each value stores the length of the prefix it shares with
+    /// the previous value in the file, and the remaining suffix.
+    ///
+    /// Front coding starts over every few values, so that blocks of values can be decoded
+    /// independently. Blocks are decoded into memory on first access.
+    Prefix,
+}
+
+impl PointToValuesCompression {
+    fn to_disk(self) -> u32 {
+        match self {
+            PointToValuesCompression::None => 0,
+            PointToValuesCompression::Prefix => 1,
+        }
+    }
+
+    fn from_disk(value: u32) -> OperationResult<Self> {
+        match value {
+            0 => Ok(PointToValuesCompression::None),
+            1 => Ok(PointToValuesCompression::Prefix),
+            _ => Err(OperationError::service_error(format!(
+                "Unsupported point-to-values compression {value}",
+            ))),
+        }
+    }
 }
 
 /// Flattened memmapped points-to-values map
 /// It's an analogue of `Vec<Vec<N>>` but in memmapped file.
-/// This structure doesn't support adding new values, only removing.
+/// The memmapped file is immutable, updated values are appended to a delta file and kept in memory,
+/// until the owner rebuilds the file from the current values.
 /// It's used in mmap field indices like `MmapMapIndex`, `MmapNumericIndex`, etc to store points-to-values map.
 /// This structure is not generic to avoid boxing lifetimes for `&str` values.
 pub struct MmapPointToValues<T: MmapValue + ?Sized> {
     file_name: PathBuf,
     mmap: Mmap,
     header: Header,
+    /// Blocks of a compressed file
+    prefix_blocks: Option<PrefixBlocks>,
+    delta: PointToValuesDelta,
+    delta_file_name: PathBuf,
+    /// Writer of the delta file, opened on the first update
+    delta_writer: Arc<Mutex<Option<BufWriter<File>>>>,
     phantom: std::marker::PhantomData<T>,
 }
 
@@ -335,6 +408,42 @@ struct Header {
     points_count: u64,
 }
 
+/// Format of the file, stored right after [`HeaderDisk`] within the padding.
+/// Files written before the format was versioned have zeroes there.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, FromBytes, Immutable, IntoBytes, KnownLayout)]
+struct FormatDisk {
+    version: u32,
+    compression: u32,
+    /// Offset of the table of block offsets of a compressed file, right after the values
+    blocks_start: u64,
+    blocks_count: u64,
+}
+
+const FORMAT_OFFSET: usize = size_of::<HeaderDisk>();
+
+/// Version of files with [`FormatDisk`], files without it have version 0
+const FORMAT_VERSION: u32 = 1;
+
+/// Number of values after which front coding starts over with the next point.
+/// Values of a point are always in a single block.
+const PREFIX_BLOCK_VALUES: usize = 16;
+
+/// Front-coded blocks of a compressed file, decoded on first access.
+///
+/// The table of blocks is `[block start offset: u64 LE]` for each block, in file order.
+struct PrefixBlocks {
+    blocks_start: usize,
+    blocks: Vec<OnceLock<DecodedBlock>>,
+}
+
+/// Values of a block in the uncompressed layout
+struct DecodedBlock {
+    bytes: Vec<u8>,
+    /// Offset of each value in the file and in `bytes`, in file order
+    offsets: Vec<(u64, u64)>,
+}
+
 impl HeaderDisk {
     fn decode_le(&self) -> Header {
         Header {
@@ -366,25 +475,384 @@ impl MmapRangeDisk {
         }
     }
 }
+
+/// Magic bytes at the beginning of the delta file.
+const DELTA_MAGIC: [u8; 4] = *b"ptvd";
+
+/// Version of the delta file format.
+const DELTA_VERSION: u32 = 1;
+
+/// `magic` + `version`
+const DELTA_HEADER_SIZE: usize = DELTA_MAGIC.len() + size_of::<u32>();
+
+/// `point_id` + `values count` + `values size`
+const DELTA_RECORD_HEADER_SIZE: usize = 3 * size_of::<u32>();
+
+/// Alignment of values of each point in memory, so that they can be referenced in place
+const DELTA_VALUES_ALIGN: usize = size_of::<u128>();
+
+/*
+Append-only log of values, which changed after the memmapped file was built.
+
+header:  [magic: "ptvd"][version: u32 LE]
+record:  [point_id: u32 LE][count: u32 LE][size: u32 LE][values: size bytes]
+
+Values are encoded the same way as in the uncompressed memmapped file. Each record replaces values
+of the point, the last record wins. Records of points, which are not present in the memmapped file,
+add new points. A torn record at the end of the file (e.g. after a crash during append) is ignored.
+*/
+
+/// Values of points, updated on top of the memmapped file.
+#[derive(Debug, Default)]
+struct PointToValuesDelta {
+    /// Encoded values of updated points, values of each point start at an aligned offset
+    values: Vec<u128>,
+    /// Length of `values` in bytes
+    values_size: usize,
+    /// Ranges of values of updated points within `values`
+    ranges: AHashMap<PointOffsetType, MmapRange>,
+    /// Number of points, including the ones not present in the memmapped file
+    points_count: usize,
+}
+
+impl PointToValuesDelta {
+    /// Load delta from the file, empty if there is no file.
+    fn load(path: &Path) -> OperationResult<Self> {
+        let mut delta = Self::default();
+        if !path.exists() {
+            return Ok(delta);
+        }
+        let data = fs::read(path)?;
+        let valid_len = delta.parse(&data)?;
+        if valid_len < data.len() {
+            log::warn!(
+                "Ignoring {} trailing bytes of incomplete record in point-to-values delta {}",
+                data.len() - valid_len,
+                path.display(),
+            );
+        }
+        Ok(delta)
+    }
+
+    /// Apply records from bytes, returns the length of complete records.
+    fn parse(&mut self, data: &[u8]) -> OperationResult<usize> {
+        if data.len() < DELTA_HEADER_SIZE || data[..DELTA_MAGIC.len()] != DELTA_MAGIC {
+            return Err(OperationError::service_error(
+                "Invalid point-to-values delta file header",
+            ));
+        }
+        let version = read_u32(data, DELTA_MAGIC.len());
+        if version != DELTA_VERSION {
+            return Err(OperationError::service_error(format!(
+                "Unsupported point-to-values delta version {version}, expected {DELTA_VERSION}",
+            )));
+        }
+
+        let mut pos = DELTA_HEADER_SIZE;
+        while pos + DELTA_RECORD_HEADER_SIZE <= data.len() {
+            let point_id = read_u32(data, pos);
+            let count = read_u32(data, pos + size_of::<u32>()) as usize;
+            let size = read_u32(data, pos + 2 * size_of::<u32>()) as usize;
+            let values_start = pos + DELTA_RECORD_HEADER_SIZE;
+            let values_end = values_start + size;
+            if values_end > data.len() {
+                break;
+            }
+            self.push(point_id, count, &data[values_start..values_end]);
+            pos = values_end;
+        }
+        Ok(pos)
+    }
+
+    fn bytes(&self) -> &[u8] {
+        &self.values.as_bytes()[..self.values_size]
+    }
+
+    /// Replace values of the point with `count` encoded values.
+    fn push(&mut self, point_id: PointOffsetType, count: usize, encoded: &[u8]) {
+        let start = self.values_size.next_multiple_of(DELTA_VALUES_ALIGN);
+        let end = start + encoded.len();
+        self.values.resize(end.div_ceil(DELTA_VALUES_ALIGN), 0);
+        self.values.as_mut_bytes()[start..end].copy_from_slice(encoded);
+        self.values_size = end;
+
+        let range = MmapRange {
+            start: start as u64,
+            count: count as u64,
+        };
+        self.ranges.insert(point_id, range);
+        self.points_count = max(self.points_count, point_id as usize + 1);
+    }
+}
+
+/// Open the delta file for appending, creating it if it doesn't exist.
+///
+/// An incomplete record at the end of the file is truncated.
+fn open_delta_writer(path: &Path) -> OperationResult<BufWriter<File>> {
+    let mut file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(path)?;
+
+    if file.metadata()?.len() == 0 {
+        let mut header = Vec::with_capacity(DELTA_HEADER_SIZE);
+        header.extend_from_slice(&DELTA_MAGIC);
+        header.extend_from_slice(&DELTA_VERSION.to_le_bytes());
+        file.write_all(&header)?;
+    } else {
+        let data = fs::read(path)?;
+        let valid_len = PointToValuesDelta::default().parse(&data)?;
+        if valid_len < data.len() {
+            file.set_len(valid_len as u64)?;
+        }
+    }
+    file.seek(SeekFrom::End(0))?;
+
+    Ok(BufWriter::new(file))
+}
+
+fn read_u32(data: &[u8], pos: usize) -> u32 {
+    u32::from_le_bytes(data[pos..pos + size_of::<u32>()].try_into().unwrap())
+}
+
+fn prefix_bytes<'a, T: MmapValue + ?Sized>(
+    value: &'a T::Referenced<'_>,
+) -> OperationResult<&'a [u8]> {
+    T::as_prefix_bytes(value).ok_or_else(|| {
+        OperationError::service_error("Value doesn't support point-to-values prefix compression")
+    })
+}
+
+/// Front-codes each value relative to the previous one.
+///
+/// Encoded value: `[shared prefix length: varint][suffix length: varint][suffix]`
+#[derive(Default)]
+struct PrefixEncoder {
+    previous: Vec<u8>,
+    encoded: Vec<u8>,
+    /// Values encoded in the current block, if any block is started
+    block_values: Option<usize>,
+}
+
+impl PrefixEncoder {
+    /// Start values of the next point, returns whether they start a new block.
+    fn start_point(&mut self) -> bool {
+        if self
+            .block_values
+            .is_some_and(|block_values| block_values < PREFIX_BLOCK_VALUES)
+        {
+            return false;
+        }
+        self.previous.clear();
+        self.block_values = Some(0);
+        true
+    }
+
+    fn encode(&mut self, value: &[u8]) -> &[u8] {
+        let shared = self
+            .previous
+            .iter()
+            .zip(value)
+            .take_while(|(previous, current)| previous == current)
+            .count();
+        self.encoded.clear();
+        write_varint(&mut self.encoded, shared as u64);
+        write_varint(&mut self.encoded, (value.len() - shared) as u64);
+        self.encoded.extend_from_slice(&value[shared..]);
+
+        self.previous.clear();
+        self.previous.extend_from_slice(value);
+        if let Some(block_values) = self.block_values.as_mut() {
+            *block_values += 1;
+        }
+        &self.encoded
+    }
+}
+
+/// Decodes a value front-coded relative to `value`, which is replaced with the decoded one.
+/// Returns the size of the encoded value.
+fn prefix_decode(bytes: &[u8], value: &mut Vec<u8>) -> Option<usize> {
+    let (shared, shared_size) = read_varint(bytes)?;
+    let (suffix_len, suffix_len_size) = read_varint(bytes.get(shared_size..)?)?;
+    let suffix_start = shared_size + suffix_len_size;
+    let suffix_end = suffix_start.checked_add(usize::try_from(suffix_len).ok()?)?;
+    let suffix = bytes.get(suffix_start..suffix_end)?;
+
+    let shared = usize::try_from(shared).ok()?;
+    if shared > value.len() {
+        return None;
+    }
+    value.truncate(shared);
+    value.extend_from_slice(suffix);
+    Some(suffix_end)
+}
+
+fn write_varint(out: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        out.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+/// Returns the value and the number of bytes it takes.
+fn read_varint(bytes: &[u8]) -> Option<(u64, usize)> {
+    let mut value = 0u64;
+    for (i, &byte) in bytes.iter().enumerate().take(10) {
+        value |= u64::from(byte & 0x7F) << (7 * i);
+        if byte & 0x80 == 0 {
+            return Some((value, i + 1));
+        }
+    }
+    None
+}
+
+impl PrefixBlocks {
+    /// Range of values in the decoded block containing the values of `range` in the file.
+    fn get_range<T: MmapValue + ?Sized>(
+        &self,
+        mmap: &[u8],
+        range: MmapRange,
+    ) -> Option<(&[u8], MmapRange)> {
+        let table = mmap.get(self.blocks_start..)?;
+        let (table, _) =
+            <[[u8; size_of::<u64>()]]>::ref_from_prefix_with_elems(table, self.blocks.len())
+                .ok()?;
+        let block_idx = table
+            .partition_point(|start| u64::from_le_bytes(*start) <= range.start)
+            .checked_sub(1)?;
+        let block_start = u64::from_le_bytes(table[block_idx]) as usize;
+        let block_end = table
+            .get(block_idx + 1)
+            .map_or(self.blocks_start, |end| u64::from_le_bytes(*end) as usize);
+
+        let block = &self.blocks[block_idx];
+        let block = match block.get() {
+            Some(block) => block,
+            None => {
+                // Concurrent readers may decode the same block, only one of them is kept
+                let decoded = Self::decode::<T>(mmap.get(block_start..block_end)?, block_start)?;
+                let _ = block.set(decoded);
+                block.get()?
+            }
+        };
+
+        let value_idx = block
+            .offsets
+            .binary_search_by_key(&range.start, |&(offset, _)| offset)
+            .ok()?;
+        let (_, start) = block.offsets[value_idx];
+        Some((
+            block.bytes.as_slice(),
+            MmapRange {
+                start,
+                count: range.count,
+            },
+        ))
+    }
+
+    /// Decode front-coded values of a block at `offset` of the file.
+    fn decode<T: MmapValue + ?Sized>(block: &[u8], offset: usize) -> Option<DecodedBlock> {
+        let mut decoded = DecodedBlock {
+            bytes: Vec::new(),
+            offsets: Vec::new(),
+        };
+        let mut value_bytes = Vec::new();
+        let mut position = 0;
+        while position < block.len() {
+            decoded
+                .offsets
+                .push(((offset + position) as u64, decoded.bytes.len() as u64));
+            position += prefix_decode(&block[position..], &mut value_bytes)?;
+            let value = T::from_prefix_bytes(&value_bytes)?;
+            let value_offset = decoded.bytes.len();
+            decoded
+                .bytes
+                .resize(value_offset + T::mmapped_size(value.clone()), 0);
+            T::write_to_mmap(value, &mut decoded.bytes[value_offset..])?;
+        }
+        Some(decoded)
+    }
+}
+
 impl<T: MmapValue + ?Sized> MmapPointToValues<T> {
     pub fn from_iter<'a>(
         path: &Path,
         iter: impl Iterator<Item = (PointOffsetType, impl Iterator<Item = T::Referenced<'a>>)> + Clone,
     ) -> OperationResult<Self> {
+        Self::from_iter_with_compression(path, iter, PointToValuesCompression::None)
+    }
+
+    /// Build the file with the given compression of values.
+    ///
+    /// Values which don't support the compression are stored uncompressed.
+    pub fn from_iter_with_compression<'a>(
+        path: &Path,
+        iter: impl Iterator<Item = (PointOffsetType, impl Iterator<Item = T::Referenced<'a>>)> + Clone,
+        compression: PointToValuesCompression,
+    ) -> OperationResult<Self> {
+        let compression = if T::PREFIX_COMPRESSIBLE {
+            compression
+        } else {
+            PointToValuesCompression::None
+        };
+        let file_name = path.join(POINT_TO_VALUES_PATH);
+        let (mmap, header) = Self::write_file(&file_name, iter, compression)?;
+        let prefix_blocks = Self::read_format(&mmap)?;
+
+        // Values of a freshly built file are not overridden by a stale delta
+        let delta_file_name = path.join(POINT_TO_VALUES_DELTA_PATH);
+        if delta_file_name.exists() {
+            fs::remove_file(&delta_file_name)?;
+        }
+
+        Ok(Self {
+            file_name,
+            mmap,
+            header,
+            prefix_blocks,
+            delta: PointToValuesDelta::default(),
+            delta_file_name,
+            delta_writer: Arc::new(Mutex::new(None)),
+            phantom: std::marker::PhantomData,
+        })
+    }
+
+    fn write_file<'a>(
+        file_name: &Path,
+        iter: impl Iterator<Item = (PointOffsetType, impl Iterator<Item = T::Referenced<'a>>)> + Clone,
+        compression: PointToValuesCompression,
+    ) -> OperationResult<(Mmap, Header)> {
+        let mut prefix_encoder = match compression {
+            PointToValuesCompression::None => None,
+            PointToValuesCompression::Prefix => Some(PrefixEncoder::default()),
+        };
+
         // calculate file size
         let mut points_count: usize = 0;
         let mut values_size = 0;
+        let mut blocks_count = 0;
         for (point_id, values) in iter.clone() {
             points_count = max(points_count, (point_id + 1) as usize);
-            values_size += values.map(|v| T::mmapped_size(v)).sum::<usize>();
+            values_size += match prefix_encoder.as_mut() {
+                None => values.map(|v| T::mmapped_size(v)).sum::<usize>(),
+                Some(encoder) => {
+                    blocks_count += usize::from(encoder.start_point());
+                    values
+                        .map(|v| Ok(encoder.encode(prefix_bytes::<T>(&v)?).len()))
+                        .sum::<OperationResult<usize>>()?
+                }
+            };
         }
         let ranges_size = points_count * std::mem::size_of::<MmapRangeDisk>();
-        let file_size = PADDING_SIZE + ranges_size + values_size;
+        let blocks_start = PADDING_SIZE + ranges_size + values_size;
+        let file_size = blocks_start + blocks_count * size_of::<u64>();
 
         // create new file and mmap
-        let file_name = path.join(POINT_TO_VALUES_PATH);
-        create_and_ensure_length(&file_name, file_size)?;
-        let mut mmap = open_write_mmap(&file_name, AdviceSetting::Global, false)?;
+        create_and_ensure_length(file_name, file_size)?;
+        let mut mmap = open_write_mmap(file_name, AdviceSetting::Global, false)?;
 
         // fill mmap file data
         let header = Header {
@@ -398,20 +866,52 @@ impl<T: MmapValue + ?Sized> MmapPointToValues<T> {
         header_disk
             .write_to_prefix(mmap.as_mut())
             .map_err(|_| OperationError::service_error(NOT_ENOUGH_BYTES_ERROR_MESSAGE))?;
+        let format_disk = FormatDisk {
+            version: FORMAT_VERSION.to_le(),
+            compression: compression.to_disk().to_le(),
+            blocks_start: (blocks_start as u64).to_le(),
+            blocks_count: (blocks_count as u64).to_le(),
+        };
+        mmap.get_mut(FORMAT_OFFSET..)
+            .and_then(|bytes| format_disk.write_to_prefix(bytes).ok())
+            .ok_or_else(|| OperationError::service_error(NOT_ENOUGH_BYTES_ERROR_MESSAGE))?;
 
-        // counter for values offset
+        // front coding starts over for the values written
+        let mut prefix_encoder = prefix_encoder.map(|_| PrefixEncoder::default());
+        let mut block_offset = blocks_start;
         let mut point_values_offset = header.ranges_start as usize + ranges_size;
         for (point_id, values) in iter {
             let start = point_values_offset;
+            if prefix_encoder
+                .as_mut()
+                .is_some_and(|encoder| encoder.start_point())
+            {
+                mmap.get_mut(block_offset..block_offset + size_of::<u64>())
+                    .ok_or_else(|| OperationError::service_error(NOT_ENOUGH_BYTES_ERROR_MESSAGE))?
+                    .copy_from_slice(&(start as u64).to_le_bytes());
+                block_offset += size_of::<u64>();
+            }
             let mut values_count = 0;
             for value in values {
                 values_count += 1;
                 let bytes = mmap
                     .get_mut(point_values_offset..)
                     .ok_or_else(|| OperationError::service_error(NOT_ENOUGH_BYTES_ERROR_MESSAGE))?;
-                T::write_to_mmap(value.clone(), bytes)
-                    .ok_or_else(|| OperationError::service_error(NOT_ENOUGH_BYTES_ERROR_MESSAGE))?;
-                point_values_offset += T::mmapped_size(value);
+                if let Some(encoder) = prefix_encoder.as_mut() {
+                    let encoded = encoder.encode(prefix_bytes::<T>(&value)?);
+                    bytes
+                        .get_mut(..encoded.len())
+                        .ok_or_else(|| {
+                            OperationError::service_error(NOT_ENOUGH_BYTES_ERROR_MESSAGE)
+                        })?
+                        .copy_from_slice(encoded);
+                    point_values_offset += encoded.len();
+                } else {
+                    T::write_to_mmap(value.clone(), bytes).ok_or_else(|| {
+                        OperationError::service_error(NOT_ENOUGH_BYTES_ERROR_MESSAGE)
+                    })?;
+                    point_values_offset += T::mmapped_size(value);
+                }
             }
 
             let range = MmapRange {
@@ -431,12 +931,52 @@ impl<T: MmapValue + ?Sized> MmapPointToValues<T> {
         }
 
         mmap.flush()?;
-        Ok(Self {
-            file_name,
-            mmap: mmap.make_read_only()?,
-            header,
-            phantom: std::marker::PhantomData,
-        })
+        Ok((mmap.make_read_only()?, header))
+    }
+
+    /// Read the format of the file, following the header.
+    ///
+    /// Returns blocks of the file, if it is compressed.
+    fn read_format(mmap: &[u8]) -> OperationResult<Option<PrefixBlocks>> {
+        let (format_disk, _) = mmap
+            .get(FORMAT_OFFSET..)
+            .and_then(|bytes| FormatDisk::read_from_prefix(bytes).ok())
+            .ok_or_else(|| OperationError::InconsistentStorage {
+                description: NOT_ENOUGH_BYTES_ERROR_MESSAGE.to_owned(),
+            })?;
+        let compression = match u32::from_le(format_disk.version) {
+            0 => PointToValuesCompression::None,
+            FORMAT_VERSION => {
+                PointToValuesCompression::from_disk(u32::from_le(format_disk.compression))?
+            }
+            version => {
+                return Err(OperationError::service_error(format!(
+                    "Unsupported point-to-values format version {version}, expected {FORMAT_VERSION}",
+                )));
+            }
+        };
+
+        let prefix_blocks = match compression {
+            PointToValuesCompression::None => None,
+            PointToValuesCompression::Prefix => {
+                let blocks_start = u64::from_le(format_disk.blocks_start) as usize;
+                let blocks_count = u64::from_le(format_disk.blocks_count) as usize;
+                let blocks_end = blocks_count
+                    .checked_mul(size_of::<u64>())
+                    .and_then(|size| blocks_start.checked_add(size));
+                if blocks_end.is_none_or(|blocks_end| blocks_end > mmap.len()) {
+                    return Err(OperationError::InconsistentStorage {
+                        description: NOT_ENOUGH_BYTES_ERROR_MESSAGE.to_owned(),
+                    });
+                }
+                Some(PrefixBlocks {
+                    blocks_start,
+                    blocks: (0..blocks_count).map(|_| OnceLock::new()).collect(),
+                })
+            }
+        };
+
+        Ok(prefix_blocks)
     }
 
     pub fn open(path: &Path, populate: bool) -> OperationResult<Self> {
@@ -483,23 +1023,93 @@ impl<T: MmapValue + ?Sized> MmapPointToValues<T> {
             }
         };
 
+        let prefix_blocks = Self::read_format(&mmap)?;
+
+        let delta_file_name = path.join(POINT_TO_VALUES_DELTA_PATH);
+        let delta = PointToValuesDelta::load(&delta_file_name)?;
+
         Ok(Self {
             file_name,
             mmap: mmap.make_read_only()?,
             header,
+            prefix_blocks,
+            delta,
+            delta_file_name,
+            delta_writer: Arc::new(Mutex::new(None)),
             phantom: std::marker::PhantomData,
         })
     }
 
     pub fn files(&self) -> Vec<PathBuf> {
-        vec![self.file_name.clone()]
+        let mut files = vec![self.file_name.clone()];
+        if self.delta_file_name.exists() {
+            files.push(self.delta_file_name.clone());
+        }
+        files
     }
 
     pub fn immutable_files(&self) -> Vec<PathBuf> {
-        // `MmapPointToValues` is immutable
+        // The memmapped file is only replaced by a rebuild, the delta file is appended to
         vec![self.file_name.clone()]
     }
 
+    /// Replace values of the point.
+    ///
+    /// Values are appended to the delta file, which is persisted by [`Self::flusher`].
+    pub fn set_values<'a>(
+        &mut self,
+        point_id: PointOffsetType,
+        values: impl IntoIterator<Item = T::Referenced<'a>>,
+    ) -> OperationResult<()> {
+        let mut encoded = Vec::new();
+        let mut count = 0u32;
+        for value in values {
+            let offset = encoded.len();
+            encoded.resize(offset + T::mmapped_size(value.clone()), 0);
+            T::write_to_mmap(value, &mut encoded[offset..])
+                .ok_or_else(|| OperationError::service_error(NOT_ENOUGH_BYTES_ERROR_MESSAGE))?;
+            count += 1;
+        }
+        let size = u32::try_from(encoded.len()).map_err(|_| {
+            OperationError::service_error("Too large values of point in point-to-values delta")
+        })?;
+
+        let mut writer = self.delta_writer.lock();
+        if writer.is_none() {
+            *writer = Some(open_delta_writer(&self.delta_file_name)?);
+        }
+        let writer = writer.as_mut().unwrap();
+        writer.write_all(&point_id.to_le_bytes())?;
+        writer.write_all(&count.to_le_bytes())?;
+        writer.write_all(&size.to_le_bytes())?;
+        writer.write_all(&encoded)?;
+
+        self.delta.push(point_id, count as usize, &encoded);
+        Ok(())
+    }
+
+    /// Write appended delta records to disk.
+    pub fn flusher(&self) -> Flusher {
+        let delta_writer = Arc::clone(&self.delta_writer);
+        Box::new(move || {
+            if let Some(writer) = delta_writer.lock().as_mut() {
+                writer.flush()?;
+                writer.get_ref().sync_data()?;
+            }
+            Ok(())
+        })
+    }
+
+    /// Points with values in the delta, in arbitrary order.
+    pub fn delta_points(&self) -> impl Iterator<Item = PointOffsetType> + '_ {
+        self.delta.ranges.keys().copied()
+    }
+
+    /// Number of points with values in the delta.
+    pub fn delta_points_count(&self) -> usize {
+        self.delta.ranges.len()
+    }
+
     pub fn check_values_any(
         &self,
         point_id: PointOffsetType,
@@ -512,10 +1122,10 @@ impl<T: MmapValue + ?Sized> MmapPointToValues<T> {
         hw_cell.incr_delta(MMAP_PTV_ACCESS_OVERHEAD);
 
         self.get_range(point_id)
-            .map(|range| {
+            .map(|(bytes, range)| {
                 let mut value_offset = range.start as usize;
                 for _ in 0..range.count {
-                    let bytes = self.mmap.get(value_offset..).unwrap();
+                    let bytes = bytes.get(value_offset..).unwrap();
                     let value = T::read_from_mmap(bytes).unwrap();
                     let mmap_size = T::mmapped_size(value.clone());
                     hw_cell.incr_delta(mmap_size);
@@ -533,13 +1143,22 @@ impl<T: MmapValue + ?Sized> MmapPointToValues<T> {
         &'a self,
         point_id: PointOffsetType,
     ) -> Option<impl Iterator<Item = T::Referenced<'a>> + 'a> {
-        // first, get range of values for point
-        let range = self.get_range(point_id)?;
+        let (bytes, range) = self.get_range(point_id)?;
+        Some(Self::read_values(bytes, range))
+    }
 
-        // second, define iteration step for values
+    /// Values of the point in the memmapped file, ignoring the delta.
+    pub fn get_file_values<'a>(
+        &'a self,
+        point_id: PointOffsetType,
+    ) -> Option<impl Iterator<Item = T::Referenced<'a>> + 'a> {
+        let (bytes, range) = self.get_file_range(point_id)?;
+        Some(Self::read_values(bytes, range))
+    }
+
+    fn read_values(bytes: &[u8], range: MmapRange) -> impl Iterator<Item = T::Referenced<'_>> {
         // iteration step gets remainder range from memmapped file and returns left range
-        let bytes: &[u8] = self.mmap.as_ref();
-        let read_value = move |range: MmapRange| -> Option<(T::Referenced<'a>, MmapRange)> {
+        let read_value = move |range: MmapRange| -> Option<(T::Referenced<'_>, MmapRange)> {
             if range.count > 0 {
                 let bytes = bytes.get(range.start as usize..)?;
                 T::read_from_mmap(bytes).map(|value| {
@@ -554,35 +1173,51 @@ impl<T: MmapValue + ?Sized> MmapPointToValues<T> {
             }
         };
 
-        // finally, return iterator
-        Some(
-            std::iter::successors(read_value(range), move |range| read_value(range.1))
-                .map(|(value, _)| value),
-        )
+        std::iter::successors(read_value(range), move |range| read_value(range.1))
+            .map(|(value, _)| value)
     }
 
     pub fn get_values_count(&self, point_id: PointOffsetType) -> Option<usize> {
-        self.get_range(point_id).map(|range| range.count as usize)
+        self.get_range(point_id)
+            .map(|(_, range)| range.count as usize)
     }
 
     pub fn len(&self) -> usize {
+        max(self.header.points_count as usize, self.delta.points_count)
+    }
+
+    /// Number of points in the memmapped file, ignoring the delta.
+    pub fn file_len(&self) -> usize {
         self.header.points_count as usize
     }
 
     #[allow(dead_code)]
     pub fn is_empty(&self) -> bool {
-        self.header.points_count == 0
+        self.len() == 0
     }
 
-    fn get_range(&self, point_id: PointOffsetType) -> Option<MmapRange> {
-        if point_id < self.header.points_count as PointOffsetType {
-            let range_offset = (self.header.ranges_start as usize)
-                + (point_id as usize) * std::mem::size_of::<MmapRangeDisk>();
-            let (range_disk, _) =
-                MmapRangeDisk::read_from_prefix(self.mmap.get(range_offset..)?).ok()?;
-            Some(range_disk.decode_le())
-        } else {
-            None
+    /// Range of values of the point, along with the bytes it refers to.
+    fn get_range(&self, point_id: PointOffsetType) -> Option<(&[u8], MmapRange)> {
+        match self.delta.ranges.get(&point_id) {
+            Some(range) => Some((self.delta.bytes(), *range)),
+            None => self.get_file_range(point_id),
+        }
+    }
+
+    fn get_file_range(&self, point_id: PointOffsetType) -> Option<(&[u8], MmapRange)> {
+        if point_id >= self.header.points_count as PointOffsetType {
+            return None;
+        }
+        let range_offset = (self.header.ranges_start as usize)
+            + (point_id as usize) * std::mem::size_of::<MmapRangeDisk>();
+        let (range_disk, _) =
+            MmapRangeDisk::read_from_prefix(self.mmap.get(range_offset..)?).ok()?;
+        let range = range_disk.decode_le();
+
+        match &self.prefix_blocks {
+            None => Some((self.mmap.as_ref(), range)),
+            Some(_) if range.count == 0 => Some((&[], range)),
+            Some(prefix_blocks) => prefix_blocks.get_range::<T>(&self.mmap, range),
         }
     }
 
@@ -610,6 +1245,154 @@ impl<T: MmapValue + ?Sized> MmapPointToValues<T> {
     }
 }
 
+/*
+Spill file of values added to the builder, in the order they were added.
+
+record:  [point_id: u32 LE][count: u32 LE][size: u32 LE][reserved: u32][values: size bytes][padding]
+
+Values are encoded the same way as in the memmapped file, and padded, so that values of each
+record start at an aligned offset.
+*/
+
+/// Size of the header of a spill record.
+const SPILL_RECORD_HEADER_SIZE: usize = 4 * size_of::<u32>();
+
+/// Alignment of values in the spill file, enough for any stored value type.
+const SPILL_VALUES_ALIGN: usize = size_of::<u128>();
+
+/// Builder of [`MmapPointToValues`], which doesn't keep the values in memory.
+///
+/// Values of points are appended to a spill file next to the resulting file. On
+/// [`Self::finalize`], the spill file is memmapped and read twice: first to calculate the size of
+/// the resulting file, then to write it. The spill file is removed afterwards.
+///
+/// Values of a point added more than once are replaced by the last ones.
+pub struct MmapPointToValuesBuilder<T: MmapValue + ?Sized> {
+    path: PathBuf,
+    /// Writer of the spill file, opened on the first added point
+    spill: Option<BufWriter<File>>,
+    /// Buffer for values of the point being added
+    encoded: Vec<u8>,
+    values_count: usize,
+    phantom: std::marker::PhantomData<T>,
+}
+
+impl<T: MmapValue + ?Sized> MmapPointToValuesBuilder<T> {
+    pub fn new(path: &Path) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            spill: None,
+            encoded: Vec::new(),
+            values_count: 0,
+            phantom: std::marker::PhantomData,
+        }
+    }
+
+    pub fn add_point<'a>(
+        &mut self,
+        point_id: PointOffsetType,
+        values: impl IntoIterator<Item = T::Referenced<'a>>,
+    ) -> OperationResult<()> {
+        self.encoded.clear();
+        let mut count = 0u32;
+        for value in values {
+            let offset = self.encoded.len();
+            self.encoded
+                .resize(offset + T::mmapped_size(value.clone()), 0);
+            T::write_to_mmap(value, &mut self.encoded[offset..])
+                .ok_or_else(|| OperationError::service_error(NOT_ENOUGH_BYTES_ERROR_MESSAGE))?;
+            count += 1;
+        }
+        let size = u32::try_from(self.encoded.len()).map_err(|_| {
+            OperationError::service_error("Too large values of point in point-to-values builder")
+        })?;
+        self.encoded
+            .resize(self.encoded.len().next_multiple_of(SPILL_VALUES_ALIGN), 0);
+
+        if self.spill.is_none() {
+            fs::create_dir_all(&self.path)?;
+            let file = File::create(self.path.join(POINT_TO_VALUES_SPILL_PATH))?;
+            self.spill = Some(BufWriter::new(file));
+        }
+        let spill = self.spill.as_mut().unwrap();
+        spill.write_all(&point_id.to_le_bytes())?;
+        spill.write_all(&count.to_le_bytes())?;
+        spill.write_all(&size.to_le_bytes())?;
+        spill.write_all(&0u32.to_le_bytes())?;
+        spill.write_all(&self.encoded)?;
+
+        self.values_count += count as usize;
+        Ok(())
+    }
+
+    /// Total number of added values.
+    pub fn values_count(&self) -> usize {
+        self.values_count
+    }
+
+    /// Write the memmapped file from the spilled values, and remove the spill file.
+    pub fn finalize(
+        self,
+        compression: PointToValuesCompression,
+    ) -> OperationResult<MmapPointToValues<T>> {
+        fs::create_dir_all(&self.path)?;
+        let Some(spill) = self.spill else {
+            return MmapPointToValues::from_iter_with_compression(
+                &self.path,
+                std::iter::empty::<(PointOffsetType, std::iter::Empty<T::Referenced<'_>>)>(),
+                compression,
+            );
+        };
+        spill.into_inner().map_err(|err| err.into_error())?;
+
+        let spill_file_name = self.path.join(POINT_TO_VALUES_SPILL_PATH);
+        let spill_mmap = open_read_mmap(
+            &spill_file_name,
+            AdviceSetting::from(Advice::Sequential),
+            false,
+        )?;
+        let point_to_values = MmapPointToValues::from_iter_with_compression(
+            &self.path,
+            spill_records::<T>(&spill_mmap),
+            compression,
+        )?;
+        drop(spill_mmap);
+        fs::remove_file(&spill_file_name)?;
+        Ok(point_to_values)
+    }
+}
+
+/// Iterate over records of the spill file.
+fn spill_records<T: MmapValue + ?Sized>(
+    data: &[u8],
+) -> impl Iterator<
+    Item = (
+        PointOffsetType,
+        impl Iterator<Item = T::Referenced<'_>> + Clone,
+    ),
+> + Clone {
+    let mut pos = 0;
+    std::iter::from_fn(move || {
+        if pos + SPILL_RECORD_HEADER_SIZE > data.len() {
+            return None;
+        }
+        let point_id = read_u32(data, pos);
+        let count = read_u32(data, pos + size_of::<u32>()) as usize;
+        let size = read_u32(data, pos + 2 * size_of::<u32>()) as usize;
+        let values_start = pos + SPILL_RECORD_HEADER_SIZE;
+        let mut values = data.get(values_start..values_start + size)?;
+        pos = values_start + size.next_multiple_of(SPILL_VALUES_ALIGN);
+
+        let values = std::iter::from_fn(move || {
+            let value = T::read_from_mmap(values)?;
+            values = values.get(T::mmapped_size(value.clone())..)?;
+            Some(value)
+        })
+        .take(count);
+        Some((point_id, values))
+    })
+}
+
 fn migrate_legacy_be_in_place<T: MmapValue + ?Sized>(
     mmap: &mut [u8],
     header_be: Header,
@@ -715,7 +1498,9 @@ fn migrate_legacy_be_in_place<T: MmapValue + ?Sized>(
 
 #[cfg(test)]
 mod tests {
+    use common::counter::hardware_counter::HardwareCounterCell;
     use itertools::Itertools;
+    use rstest::rstest;
     use tempfile::Builder;
 
     use super::*;
@@ -956,6 +1741,333 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_mmap_point_to_values_prefix_compressed() {
+        let values: Vec<Vec<String>> = (0..100)
+            .map(|i| {
+                (0..i % 4)
+                    .map(|j| format!("https://qdrant.tech/documentation/concepts/{i}/{j}"))
+                    .collect()
+            })
+            .collect();
+        // Values are written in reverse order of points
+        let iter = values
+            .iter()
+            .enumerate()
+            .rev()
+            .map(|(id, values)| (id as PointOffsetType, values.iter().map(|s| s.as_str())));
+
+        let plain_dir = Builder::new()
+            .prefix("mmap_point_to_values_plain")
+            .tempdir()
+            .unwrap();
+        MmapPointToValues::<str>::from_iter(plain_dir.path(), iter.clone()).unwrap();
+
+        let dir = Builder::new()
+            .prefix("mmap_point_to_values_prefix")
+            .tempdir()
+            .unwrap();
+        MmapPointToValues::<str>::from_iter_with_compression(
+            dir.path(),
+            iter,
+            PointToValuesCompression::Prefix,
+        )
+        .unwrap();
+
+        let file_size = |dir: &Path| fs::metadata(dir.join(POINT_TO_VALUES_PATH)).unwrap().len();
+        assert!(file_size(dir.path()) < file_size(plain_dir.path()));
+
+        let check = |point_to_values: &MmapPointToValues<str>| {
+            for (idx, values) in values.iter().enumerate() {
+                let got = point_to_values
+                    .get_values(idx as PointOffsetType)
+                    .unwrap()
+                    .map(|s: &str| s.to_owned())
+                    .collect_vec();
+                assert_eq!(&got, values);
+            }
+        };
+        let point_to_values = MmapPointToValues::<str>::open(dir.path(), false).unwrap();
+        assert!(point_to_values.prefix_blocks.is_some());
+
+        // Blocks are decoded on first access only
+        let decoded_blocks = |point_to_values: &MmapPointToValues<str>| {
+            let prefix_blocks = point_to_values.prefix_blocks.as_ref().unwrap();
+            let decoded = prefix_blocks
+                .blocks
+                .iter()
+                .filter(|block| block.get().is_some())
+                .count();
+            (decoded, prefix_blocks.blocks.len())
+        };
+        assert_eq!(decoded_blocks(&point_to_values).0, 0);
+        let got = point_to_values.get_values(42).unwrap().collect_vec();
+        assert_eq!(got, values[42]);
+        assert_eq!(decoded_blocks(&point_to_values).0, 1);
+
+        check(&point_to_values);
+        let (decoded, blocks) = decoded_blocks(&point_to_values);
+        assert!(blocks > 1);
+        assert_eq!(decoded, blocks);
+    }
+
+    #[test]
+    fn test_mmap_point_to_values_int_ignores_prefix_compression() {
+        let dir = Builder::new()
+            .prefix("mmap_point_to_values_int_prefix")
+            .tempdir()
+            .unwrap();
+        let values: Vec<Vec<IntPayloadType>> = vec![vec![10, 11], vec![12]];
+        MmapPointToValues::<IntPayloadType>::from_iter_with_compression(
+            dir.path(),
+            values.iter().enumerate().map(|(id, values)| {
+                (
+                    id as PointOffsetType,
+                    values.iter().map(|v| v.as_referenced()),
+                )
+            }),
+            PointToValuesCompression::Prefix,
+        )
+        .unwrap();
+
+        let point_to_values = MmapPointToValues::<IntPayloadType>::open(dir.path(), false).unwrap();
+        assert!(point_to_values.prefix_blocks.is_none());
+        let got = point_to_values
+            .get_values(0)
+            .unwrap()
+            .map(|v| *IntPayloadType::from_referenced(&v))
+            .collect_vec();
+        assert_eq!(got, vec![10, 11]);
+    }
+
+    #[rstest]
+    #[case(PointToValuesCompression::None)]
+    #[case(PointToValuesCompression::Prefix)]
+    fn test_mmap_point_to_values_builder(#[case] compression: PointToValuesCompression) {
+        let dir = Builder::new()
+            .prefix("mmap_point_to_values_builder")
+            .tempdir()
+            .unwrap();
+        let values: Vec<Vec<String>> = (0..50)
+            .map(|i| (0..i % 3).map(|j| format!("value-{i}-{j}")).collect())
+            .collect();
+
+        let mut builder = MmapPointToValuesBuilder::<str>::new(dir.path());
+        // Points are added in reverse order, a replaced point keeps the last values
+        builder.add_point(7, ["stale"]).unwrap();
+        for (id, values) in values.iter().enumerate().rev() {
+            builder
+                .add_point(id as PointOffsetType, values.iter().map(|s| s.as_str()))
+                .unwrap();
+        }
+        assert_eq!(
+            builder.values_count(),
+            1 + values.iter().map(Vec::len).sum::<usize>()
+        );
+        let point_to_values = builder.finalize(compression).unwrap();
+        assert!(!dir.path().join(POINT_TO_VALUES_SPILL_PATH).exists());
+        drop(point_to_values);
+
+        let point_to_values = MmapPointToValues::<str>::open(dir.path(), false).unwrap();
+        assert_eq!(point_to_values.len(), values.len());
+        for (idx, values) in values.iter().enumerate() {
+            let got = point_to_values
+                .get_values(idx as PointOffsetType)
+                .unwrap()
+                .map(|s: &str| s.to_owned())
+                .collect_vec();
+            assert_eq!(&got, values);
+        }
+    }
+
+    #[test]
+    fn test_mmap_point_to_values_builder_aligned() {
+        let dir = Builder::new()
+            .prefix("mmap_point_to_values_builder_aligned")
+            .tempdir()
+            .unwrap();
+        // Odd number of values, so that values of the next record have to be aligned
+        let values: Vec<Vec<UuidIntType>> = vec![vec![1, u128::MAX, 3], vec![], vec![4]];
+
+        let mut builder = MmapPointToValuesBuilder::<UuidIntType>::new(dir.path());
+        for (id, values) in values.iter().enumerate() {
+            builder
+                .add_point(
+                    id as PointOffsetType,
+                    values.iter().map(|v| v.as_referenced()),
+                )
+                .unwrap();
+        }
+        let point_to_values = builder.finalize(PointToValuesCompression::None).unwrap();
+
+        for (idx, values) in values.iter().enumerate() {
+            let got = point_to_values
+                .get_values(idx as PointOffsetType)
+                .unwrap()
+                .map(|v| *UuidIntType::from_referenced(&v))
+                .collect_vec();
+            assert_eq!(&got, values);
+        }
+    }
+
+    #[test]
+    fn test_mmap_point_to_values_builder_empty() {
+        let dir = Builder::new()
+            .prefix("mmap_point_to_values_builder_empty")
+            .tempdir()
+            .unwrap();
+        let path = dir.path().join("index");
+        let builder = MmapPointToValuesBuilder::<IntPayloadType>::new(&path);
+        let point_to_values = builder.finalize(PointToValuesCompression::None).unwrap();
+        assert!(point_to_values.is_empty());
+    }
+
+    #[rstest]
+    #[case(PointToValuesCompression::None)]
+    #[case(PointToValuesCompression::Prefix)]
+    fn test_mmap_point_to_values_delta(#[case] compression: PointToValuesCompression) {
+        let dir = Builder::new()
+            .prefix("mmap_point_to_values_delta")
+            .tempdir()
+            .unwrap();
+        let get_values = |point_to_values: &MmapPointToValues<str>, idx: PointOffsetType| {
+            point_to_values
+                .get_values(idx)
+                .map(|iter| iter.map(|s: &str| s.to_owned()).collect_vec())
+        };
+
+        let mut point_to_values = MmapPointToValues::<str>::from_iter_with_compression(
+            dir.path(),
+            [vec!["fox", "box"], vec!["red"], vec![]]
+                .into_iter()
+                .enumerate()
+                .map(|(id, values)| (id as PointOffsetType, values.into_iter())),
+            compression,
+        )
+        .unwrap();
+
+        point_to_values.set_values(1, ["blue", "yellow"]).unwrap();
+        point_to_values.set_values(0, []).unwrap();
+        point_to_values.set_values(4, ["tree"]).unwrap();
+        point_to_values.set_values(1, ["green"]).unwrap();
+        point_to_values.flusher()().unwrap();
+
+        let expected: [&[&str]; 5] = [&[], &["green"], &[], &[], &["tree"]];
+        let check = |point_to_values: &MmapPointToValues<str>| {
+            assert_eq!(point_to_values.len(), 5);
+            assert_eq!(point_to_values.file_len(), 3);
+            for (idx, expected) in expected.iter().enumerate() {
+                // Points missing in both the file and the delta have no values
+                let values = get_values(point_to_values, idx as PointOffsetType);
+                assert_eq!(values.unwrap_or_default(), *expected);
+            }
+            assert_eq!(get_values(point_to_values, 5), None);
+            // Values of the file are still available
+            let file_values = point_to_values
+                .get_file_values(1)
+                .unwrap()
+                .map(|s: &str| s.to_owned())
+                .collect_vec();
+            assert_eq!(file_values, vec!["red"]);
+            assert_eq!(
+                point_to_values.delta_points().sorted().collect_vec(),
+                vec![0, 1, 4],
+            );
+        };
+        check(&point_to_values);
+        drop(point_to_values);
+
+        // Delta is replayed on open, ignoring a torn record at the end
+        let delta_path = dir.path().join(POINT_TO_VALUES_DELTA_PATH);
+        let mut delta_file = OpenOptions::new().append(true).open(&delta_path).unwrap();
+        delta_file.write_all(&[1, 0, 0]).unwrap();
+        drop(delta_file);
+
+        let mut point_to_values = MmapPointToValues::<str>::open(dir.path(), false).unwrap();
+        check(&point_to_values);
+        point_to_values.set_values(2, ["pink"]).unwrap();
+        point_to_values.flusher()().unwrap();
+        assert_eq!(get_values(&point_to_values, 2).unwrap(), vec!["pink"]);
+        assert_eq!(point_to_values.files().len(), 2);
+        drop(point_to_values);
+
+        let point_to_values = MmapPointToValues::<str>::open(dir.path(), false).unwrap();
+        assert_eq!(get_values(&point_to_values, 2).unwrap(), vec!["pink"]);
+        assert_eq!(point_to_values.delta_points_count(), 4);
+
+        // Rebuilding the file from current values drops the delta
+        let current = point_to_values
+            .iter()
+            .map(|(idx, values)| {
+                let values = values
+                    .into_iter()
+                    .flatten()
+                    .map(str::to_owned)
+                    .collect_vec();
+                (idx, values)
+            })
+            .collect_vec();
+        drop(point_to_values);
+        let point_to_values = MmapPointToValues::<str>::from_iter_with_compression(
+            dir.path(),
+            current
+                .iter()
+                .map(|(idx, values)| (*idx, values.iter().map(String::as_str))),
+            compression,
+        )
+        .unwrap();
+        assert!(!delta_path.exists());
+        assert_eq!(point_to_values.delta_points_count(), 0);
+        assert_eq!(get_values(&point_to_values, 1).unwrap(), vec!["green"]);
+        assert_eq!(get_values(&point_to_values, 4).unwrap(), vec!["tree"]);
+    }
+
+    #[test]
+    fn test_mmap_point_to_values_int_delta() {
+        let dir = Builder::new()
+            .prefix("mmap_point_to_values_int_delta")
+            .tempdir()
+            .unwrap();
+        let values: Vec<Vec<IntPayloadType>> = vec![vec![1, 2], vec![3]];
+        let mut point_to_values = MmapPointToValues::<IntPayloadType>::from_iter(
+            dir.path(),
+            values.iter().enumerate().map(|(id, values)| {
+                (
+                    id as PointOffsetType,
+                    values.iter().map(|v| v.as_referenced()),
+                )
+            }),
+        )
+        .unwrap();
+
+        // Odd number of values, so that the next point has to be aligned
+        point_to_values
+            .set_values(0, [5i64, 6, 7].iter().map(|v| v.as_referenced()))
+            .unwrap();
+        point_to_values
+            .set_values(2, [i64::MIN].iter().map(|v| v.as_referenced()))
+            .unwrap();
+
+        let hw_counter = HardwareCounterCell::new();
+        let hw_counter = ConditionedCounter::always(&hw_counter);
+        let check = |idx, value| {
+            point_to_values.check_values_any(
+                idx,
+                |v| *IntPayloadType::from_referenced(&v) == value,
+                &hw_counter,
+            )
+        };
+        assert!(check(2, i64::MIN));
+        assert!(!check(0, 1));
+        assert_eq!(point_to_values.get_values_count(0), Some(3));
+        let got = point_to_values
+            .get_values(0)
+            .unwrap()
+            .map(|v| *IntPayloadType::from_referenced(&v))
+            .collect_vec();
+        assert_eq!(got, vec![5, 6, 7]);
+    }
+
     #[test]
     fn test_mmap_point_to_values_geo() {
         let values: Vec<Vec<GeoPoint>> = vec![
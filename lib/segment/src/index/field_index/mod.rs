@@ -1,17 +1,22 @@
 use std::ops::Deref;
+use std::path::PathBuf;
 
 use ahash::AHashSet;
 use common::types::PointOffsetType;
+use fs_err as fs;
 
+use self::composite_index::CompositeCondition;
+use crate::common::operation_error::OperationResult;
 use crate::types::{Condition, FieldCondition, PointIdType, VectorNameBuf};
 
 pub mod bool_index;
+pub mod composite_index;
 pub(super) mod facet_index;
 mod field_index_base;
 pub mod full_text_index;
 pub mod geo_hash;
 pub mod geo_index;
-mod histogram;
+pub(crate) mod histogram;
 mod immutable_point_to_values;
 pub mod index_selector;
 pub mod map_index;
@@ -21,6 +26,7 @@ pub mod numeric_index;
 mod stat_tools;
 #[cfg(test)]
 mod tests;
+pub mod trigram_index;
 mod utils;
 
 pub use field_index_base::*;
@@ -41,6 +47,9 @@ pub enum PrimaryCondition {
     Condition(Box<FieldCondition>),
     Ids(ResolvedHasId),
     HasVector(VectorNameBuf),
+    Composite(Box<CompositeCondition>),
+    /// Substring match, served by a trigram index
+    Trigram(Box<FieldCondition>),
 }
 
 impl From<FieldCondition> for PrimaryCondition {
@@ -119,10 +128,65 @@ impl CardinalityEstimation {
                     }
                     _ => false,
                 },
+                // Candidates of composite and trigram indexes always have to be checked
+                PrimaryCondition::Composite(_) | PrimaryCondition::Trigram(_) => false,
             })
     }
 }
 
+/// Resources required to serve a field index
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IndexResources {
+    /// Expected RAM usage, once the index is loaded and its files are populated into page cache
+    pub ram_bytes: usize,
+    /// Total size of index files on disk
+    pub disk_bytes: usize,
+    /// Size of each index file in bytes
+    pub files: Vec<(PathBuf, usize)>,
+}
+
+impl IndexResources {
+    /// Resources of an index, which is served directly from its files, e.g. through mmap
+    pub fn from_files(files: impl IntoIterator<Item = PathBuf>) -> OperationResult<Self> {
+        let mut resources = Self::default();
+        for path in files {
+            resources.add_file(path, true)?;
+        }
+        Ok(resources)
+    }
+
+    /// Resources of an index, which is kept in memory and doesn't have files of its own
+    pub fn in_memory(ram_bytes: usize) -> Self {
+        Self {
+            ram_bytes,
+            disk_bytes: 0,
+            files: vec![],
+        }
+    }
+
+    /// Account a file of the index, `loaded` files are expected to be fully present in RAM
+    pub fn add_file(&mut self, path: PathBuf, loaded: bool) -> OperationResult<()> {
+        let size = fs::metadata(&path)?.len() as usize;
+        if loaded {
+            self.ram_bytes += size;
+        }
+        self.disk_bytes += size;
+        self.files.push((path, size));
+        Ok(())
+    }
+
+    pub fn merge(&mut self, other: Self) {
+        let Self {
+            ram_bytes,
+            disk_bytes,
+            files,
+        } = other;
+        self.ram_bytes += ram_bytes;
+        self.disk_bytes += disk_bytes;
+        self.files.extend(files);
+    }
+}
+
 pub trait EstimationMerge: Iterator<Item = CardinalityEstimation> {
     fn merge_independent(self) -> CardinalityEstimation
     where
@@ -186,6 +186,7 @@ impl MutableNullIndex {
             points_count,
             points_values_count: points_count,
             histogram_bucket_size: None,
+            resources: None,
             index_type: "mutable_null_index",
         }
     }
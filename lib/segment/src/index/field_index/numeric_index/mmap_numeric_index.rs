@@ -4,7 +4,7 @@ use std::path::{Path, PathBuf};
 use common::counter::conditioned_counter::ConditionedCounter;
 use common::counter::hardware_counter::HardwareCounterCell;
 use common::counter::iterator_hw_measurement::HwMeasurementIteratorExt;
-use common::fs::{atomic_save_json, clear_disk_cache, read_json};
+use common::fs::{atomic_save_json, clear_disk_cache, read_json, sync_parent_dir};
 use common::mmap;
 use common::mmap::{AdviceSetting, MmapBitSlice, MmapSlice, create_and_ensure_length};
 use common::types::PointOffsetType;
@@ -20,10 +20,70 @@ use crate::common::operation_error::{OperationError, OperationResult};
 use crate::index::field_index::histogram::{Histogram, Numericable, Point};
 use crate::index::field_index::mmap_point_to_values::{MmapPointToValues, MmapValue};
 
-const PAIRS_PATH: &str = "data.bin";
+const PAIRS_PATH: &str = "pairs.bin";
+/// Pairs of the legacy layout, see [`PAIRS_FORMAT_VERSION`]
+const LEGACY_PAIRS_PATH: &str = "data.bin";
 const DELETED_PATH: &str = "deleted.bin";
 const CONFIG_PATH: &str = "mmap_field_index_config.json";
 
+/// Version of the pairs layout:
+///
+/// - 0: [`Point`] in native byte order, stored in [`LEGACY_PAIRS_PATH`]
+/// - 1: [`OrderedPair`], stored in [`PAIRS_PATH`]
+///
+/// The layout is keyed on the file name, so that an interrupted migration is simply repeated. The
+/// legacy file is removed once the migrated pairs are durable. Indexes with a version newer than
+/// this one are refused.
+pub(crate) const PAIRS_FORMAT_VERSION: u32 = 1;
+
+/// Pair of value and point offset, encoded so that pairs sort by plain byte comparison.
+///
+/// The order matches the order of [`Point`], so range bounds are found by binary search over raw
+/// bytes of the mmap without decoding the keys, the same way on any host byte order.
+///
+/// ```text
+/// 0                        N              N + 4
+/// ┌────────────────────────┬──────────────┐
+/// │ ordered value bytes    │ point_offset │
+/// │ (big-endian, see below)│ (big-endian) │
+/// └────────────────────────┴──────────────┘
+/// ```
+///
+/// Value bytes are produced by [`Numericable::to_ordered_bytes`]:
+///
+/// - `i64`: value with the sign bit flipped
+/// - `f64`: bits of negative values inverted, sign bit set for positive values, negative zero
+///   stored as zero
+/// - `u128`: value as is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[repr(C)]
+struct OrderedPair<K> {
+    val: K,
+    idx: [u8; size_of::<PointOffsetType>()],
+}
+
+impl<K> OrderedPair<K> {
+    fn encode<T: Numericable<OrderedBytes = K>>(point: &Point<T>) -> Self {
+        Self {
+            val: point.val.to_ordered_bytes(),
+            idx: point.idx.to_be_bytes(),
+        }
+    }
+
+    fn idx(&self) -> PointOffsetType {
+        PointOffsetType::from_be_bytes(self.idx)
+    }
+
+    fn decode<T: Numericable<OrderedBytes = K>>(&self) -> Point<T>
+    where
+        K: Copy,
+    {
+        Point::new(T::from_ordered_bytes(self.val), self.idx())
+    }
+}
+
+type EncodedPair<T> = OrderedPair<<T as Numericable>::OrderedBytes>;
+
 pub struct MmapNumericIndex<T: Encodable + Numericable + Default + MmapValue + 'static> {
     path: PathBuf,
     pub(super) storage: Storage<T>,
@@ -35,18 +95,28 @@ pub struct MmapNumericIndex<T: Encodable + Numericable + Default + MmapValue + '
 
 pub(super) struct Storage<T: Encodable + Numericable + Default + MmapValue + 'static> {
     deleted: MmapBitSliceBufferedUpdateWrapper,
-    // sorted pairs (id + value), sorted by value (by id if values are equal)
-    pairs: MmapSlice<Point<T>>,
+    // sorted pairs (value + id), sorted by value (by id if values are equal)
+    pairs: MmapSlice<EncodedPair<T>>,
     pub(super) point_to_values: MmapPointToValues<T>,
 }
 
+impl<T: Encodable + Numericable + Default + MmapValue> Storage<T> {
+    fn pairs(&self) -> &[EncodedPair<T>] {
+        &self.pairs
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct MmapNumericIndexConfig {
     max_values_per_point: usize,
+    /// Layout version of the pairs file, missing in legacy configs of native-endian pairs.
+    /// Written before the pairs file, so it is never older than the pairs it describes.
+    #[serde(default)]
+    pairs_format_version: u32,
 }
 
 pub(super) struct NumericIndexPairsIterator<'a, T: Encodable + Numericable> {
-    pairs: &'a [Point<T>],
+    pairs: &'a [EncodedPair<T>],
     deleted: &'a MmapBitSliceBufferedUpdateWrapper,
     start_index: usize,
     end_index: usize,
@@ -57,13 +127,13 @@ impl<T: Encodable + Numericable> Iterator for NumericIndexPairsIterator<'_, T> {
 
     fn next(&mut self) -> Option<Self::Item> {
         while self.start_index < self.end_index {
-            let key = self.pairs[self.start_index].clone();
-            let deleted = self.deleted.get(key.idx as usize).unwrap_or(true);
+            let key = &self.pairs[self.start_index];
+            let deleted = self.deleted.get(key.idx() as usize).unwrap_or(true);
             self.start_index += 1;
             if deleted {
                 continue;
             }
-            return Some(key);
+            return Some(key.decode());
         }
         None
     }
@@ -72,13 +142,13 @@ impl<T: Encodable + Numericable> Iterator for NumericIndexPairsIterator<'_, T> {
 impl<T: Encodable + Numericable> DoubleEndedIterator for NumericIndexPairsIterator<'_, T> {
     fn next_back(&mut self) -> Option<Self::Item> {
         while self.start_index < self.end_index {
-            let key = self.pairs[self.end_index - 1].clone();
-            let deleted = self.deleted.get(key.idx as usize).unwrap_or(true);
+            let key = &self.pairs[self.end_index - 1];
+            let deleted = self.deleted.get(key.idx() as usize).unwrap_or(true);
             self.end_index -= 1;
             if deleted {
                 continue;
             }
-            return Some(key);
+            return Some(key.decode());
         }
         None
     }
@@ -92,6 +162,34 @@ impl<T: Encodable + Numericable + Default + MmapValue> MmapNumericIndex<T> {
     ) -> OperationResult<Self> {
         fs::create_dir_all(path)?;
 
+        let point_to_values = MmapPointToValues::<T>::from_iter(
+            path,
+            in_memory_index
+                .point_to_values
+                .iter()
+                .enumerate()
+                .map(|(idx, values)| {
+                    (
+                        idx as PointOffsetType,
+                        values.iter().map(|v| T::as_referenced(v)),
+                    )
+                }),
+        )?;
+
+        Self::build_with_point_to_values(in_memory_index, point_to_values, path, is_on_disk)
+    }
+
+    /// Build the index with the point-to-values already written to the `path`.
+    ///
+    /// Only the pairs and the histogram of `in_memory_index` are used.
+    pub fn build_with_point_to_values(
+        in_memory_index: InMemoryNumericIndex<T>,
+        point_to_values: MmapPointToValues<T>,
+        path: &Path,
+        is_on_disk: bool,
+    ) -> OperationResult<Self> {
+        fs::create_dir_all(path)?;
+
         let pairs_path = path.join(PAIRS_PATH);
         let deleted_path = path.join(DELETED_PATH);
         let config_path = path.join(CONFIG_PATH);
@@ -100,40 +198,21 @@ impl<T: Encodable + Numericable + Default + MmapValue> MmapNumericIndex<T> {
             &config_path,
             &MmapNumericIndexConfig {
                 max_values_per_point: in_memory_index.max_values_per_point,
+                pairs_format_version: PAIRS_FORMAT_VERSION,
             },
         )?;
 
         in_memory_index.histogram.save(path)?;
 
-        MmapPointToValues::<T>::from_iter(
-            path,
-            in_memory_index
-                .point_to_values
-                .iter()
-                .enumerate()
-                .map(|(idx, values)| {
-                    (
-                        idx as PointOffsetType,
-                        values.iter().map(|v| T::as_referenced(v)),
-                    )
-                }),
+        Self::write_pairs(
+            &pairs_path,
+            in_memory_index.map.len(),
+            in_memory_index.map.iter().cloned(),
         )?;
 
-        {
-            let pairs_file = create_and_ensure_length(
-                &pairs_path,
-                in_memory_index.map.len() * std::mem::size_of::<Point<T>>(),
-            )?;
-            let pairs_mmap = unsafe { MmapMut::map_mut(&pairs_file)? };
-            let mut pairs = unsafe { MmapSlice::<Point<T>>::try_from(pairs_mmap)? };
-            for (src, dst) in in_memory_index.map.iter().zip(pairs.iter_mut()) {
-                *dst = src.clone();
-            }
-        }
-
         {
             const BITS_IN_BYTE: usize = 8;
-            let deleted_flags_count = in_memory_index.point_to_values.len();
+            let deleted_flags_count = point_to_values.len();
             let deleted_file = create_and_ensure_length(
                 &deleted_path,
                 BITS_IN_BYTE
@@ -143,12 +222,13 @@ impl<T: Encodable + Numericable + Default + MmapValue> MmapNumericIndex<T> {
             let mut deleted_mmap = unsafe { MmapMut::map_mut(&deleted_file)? };
             deleted_mmap.fill(0);
             let mut deleted_bitflags = MmapBitSlice::from(deleted_mmap, 0);
-            for (idx, values) in in_memory_index.point_to_values.iter().enumerate() {
-                if values.is_empty() {
+            for idx in 0..deleted_flags_count {
+                if point_to_values.get_values_count(idx as PointOffsetType) == Some(0) {
                     deleted_bitflags.set(idx, true);
                 }
             }
         }
+        drop(point_to_values);
 
         Self::open(path, is_on_disk)?.ok_or_else(|| {
             OperationError::service_error("Failed to open MmapNumericIndex after building it")
@@ -168,6 +248,23 @@ impl<T: Encodable + Numericable + Default + MmapValue> MmapNumericIndex<T> {
 
         let histogram = Histogram::<T>::load(path)?;
         let config: MmapNumericIndexConfig = read_json(&config_path)?;
+        if config.pairs_format_version > PAIRS_FORMAT_VERSION {
+            return Err(OperationError::service_error(format!(
+                "Mmap numeric index at {} has pairs format version {}, \
+                 but at most {PAIRS_FORMAT_VERSION} is supported",
+                path.display(),
+                config.pairs_format_version,
+            )));
+        }
+        let legacy_pairs_path = path.join(LEGACY_PAIRS_PATH);
+        if !pairs_path.is_file() {
+            Self::migrate_legacy_pairs(&legacy_pairs_path, &pairs_path)?;
+        }
+        // Also after a migration interrupted right before the removal
+        if legacy_pairs_path.is_file() {
+            fs::remove_file(&legacy_pairs_path)?;
+            sync_parent_dir(&legacy_pairs_path)?;
+        }
         let deleted = mmap::open_write_mmap(&deleted_path, AdviceSetting::Global, false)?;
         let deleted = MmapBitSlice::from(deleted, 0);
         let deleted_count = deleted.count_ones();
@@ -195,6 +292,43 @@ impl<T: Encodable + Numericable + Default + MmapValue> MmapNumericIndex<T> {
         }))
     }
 
+    /// Write sorted `pairs` into a new file in the current layout.
+    fn write_pairs(
+        pairs_path: &Path,
+        count: usize,
+        pairs: impl Iterator<Item = Point<T>>,
+    ) -> OperationResult<()> {
+        let pairs_file = create_and_ensure_length(pairs_path, count * size_of::<EncodedPair<T>>())?;
+        let pairs_mmap = unsafe { MmapMut::map_mut(&pairs_file)? };
+        let mut encoded = unsafe { MmapSlice::<EncodedPair<T>>::try_from(pairs_mmap)? };
+        for (src, dst) in pairs.zip(encoded.iter_mut()) {
+            *dst = OrderedPair::encode(&src);
+        }
+        encoded.flusher()()?;
+        Ok(())
+    }
+
+    /// Write legacy native-endian pairs into a new file in the current layout.
+    ///
+    /// The new file only appears once it is complete and durable, the legacy file is left as is
+    /// until then.
+    fn migrate_legacy_pairs(legacy_path: &Path, pairs_path: &Path) -> OperationResult<()> {
+        let legacy = unsafe {
+            MmapSlice::<Point<T>>::try_from(mmap::open_write_mmap(
+                legacy_path,
+                AdviceSetting::Global,
+                false,
+            )?)?
+        };
+        let tmp_path = pairs_path.with_extension("bin.tmp");
+        Self::write_pairs(&tmp_path, legacy.len(), legacy.iter().cloned())?;
+        drop(legacy);
+        fs::File::open(&tmp_path)?.sync_all()?;
+        fs::rename(&tmp_path, pairs_path)?;
+        sync_parent_dir(pairs_path)?;
+        Ok(())
+    }
+
     pub fn wipe(self) -> OperationResult<()> {
         let files = self.files();
         let path = self.path.clone();
@@ -272,7 +406,7 @@ impl<T: Encodable + Numericable + Default + MmapValue> MmapNumericIndex<T> {
     /// Returns the number of key-value pairs in the index.
     /// Note that is doesn't count deleted pairs.
     pub(super) fn total_unique_values_count(&self) -> usize {
-        self.storage.pairs.len()
+        self.storage.pairs().len()
     }
 
     pub(super) fn values_range<'a>(
@@ -285,7 +419,7 @@ impl<T: Encodable + Numericable + Default + MmapValue> MmapNumericIndex<T> {
 
         self.values_range_iterator(start_bound, end_bound)
             .map(|Point { idx, .. }| idx)
-            .measure_hw_with_condition_cell(hw_counter, size_of::<Point<T>>(), |i| {
+            .measure_hw_with_condition_cell(hw_counter, size_of::<EncodedPair<T>>(), |i| {
                 i.payload_index_io_read_counter()
             })
     }
@@ -334,43 +468,48 @@ impl<T: Encodable + Numericable + Default + MmapValue> MmapNumericIndex<T> {
         start_bound: Bound<Point<T>>,
         end_bound: Bound<Point<T>>,
     ) -> NumericIndexPairsIterator<'_, T> {
+        // Encode bounds once, the search itself only compares bytes
+        let start_bound = start_bound.map(|bound| OrderedPair::encode(&bound));
+        let end_bound = end_bound.map(|bound| OrderedPair::encode(&bound));
+
         let start_index = match start_bound {
             Bound::Included(bound) => self
                 .storage
-                .pairs
+                .pairs()
                 .binary_search(&bound)
                 .unwrap_or_else(|idx| idx),
-            Bound::Excluded(bound) => match self.storage.pairs.binary_search(&bound) {
+            Bound::Excluded(bound) => match self.storage.pairs().binary_search(&bound) {
                 Ok(idx) => idx + 1,
                 Err(idx) => idx,
             },
             Bound::Unbounded => 0,
         };
 
-        if start_index >= self.storage.pairs.len() {
+        if start_index >= self.storage.pairs().len() {
             return NumericIndexPairsIterator {
-                pairs: &self.storage.pairs,
+                pairs: self.storage.pairs(),
                 deleted: &self.storage.deleted,
-                start_index: self.storage.pairs.len(),
-                end_index: self.storage.pairs.len(),
+                start_index: self.storage.pairs().len(),
+                end_index: self.storage.pairs().len(),
             };
         }
 
         let end_index = match end_bound {
-            Bound::Included(bound) => match self.storage.pairs[start_index..].binary_search(&bound)
-            {
-                Ok(idx) => idx + 1 + start_index,
-                Err(idx) => idx + start_index,
-            },
+            Bound::Included(bound) => {
+                match self.storage.pairs()[start_index..].binary_search(&bound) {
+                    Ok(idx) => idx + 1 + start_index,
+                    Err(idx) => idx + start_index,
+                }
+            }
             Bound::Excluded(bound) => {
-                let end_bound = self.storage.pairs[start_index..].binary_search(&bound);
+                let end_bound = self.storage.pairs()[start_index..].binary_search(&bound);
                 end_bound.unwrap_or_else(|idx| idx) + start_index
             }
-            Bound::Unbounded => self.storage.pairs.len(),
+            Bound::Unbounded => self.storage.pairs().len(),
         };
 
         NumericIndexPairsIterator {
-            pairs: &self.storage.pairs,
+            pairs: self.storage.pairs(),
             deleted: &self.storage.deleted,
             start_index,
             end_index,
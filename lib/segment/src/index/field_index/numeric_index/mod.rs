@@ -34,7 +34,7 @@ use uuid::Uuid;
 use self::immutable_numeric_index::ImmutableNumericIndex;
 use super::FieldIndexBuilderTrait;
 use super::histogram::Point;
-use super::mmap_point_to_values::MmapValue;
+use super::mmap_point_to_values::{MmapPointToValuesBuilder, MmapValue, PointToValuesCompression};
 use super::utils::{check_boundaries, value_to_integer};
 use crate::common::Flusher;
 use crate::common::operation_error::{OperationError, OperationResult};
@@ -131,13 +131,11 @@ impl Encodable for DateTimePayloadType {
 
     fn decode_key(key: &[u8]) -> (PointOffsetType, Self) {
         let (id, timestamp) = decode_i64_key_ascending(key);
-        let datetime =
-            DateTime::from_timestamp(timestamp / 1000, (timestamp % 1000) as u32 * 1_000_000)
-                .unwrap_or_else(|| {
-                    log::warn!("Failed to decode timestamp {timestamp}, fallback to UNIX_EPOCH");
-                    DateTime::UNIX_EPOCH
-                });
-        (id, datetime.into())
+        let datetime = DateTimePayloadType::from_timestamp(timestamp).unwrap_or_else(|| {
+            log::warn!("Failed to decode timestamp {timestamp}, fallback to UNIX_EPOCH");
+            DateTime::UNIX_EPOCH.into()
+        });
+        (id, datetime)
     }
 
     fn cmp_encoded(&self, other: &Self) -> std::cmp::Ordering {
@@ -393,6 +391,7 @@ where
             points_count: self.get_points_count(),
             points_values_count: self.get_histogram().get_total_count(),
             histogram_bucket_size: Some(self.get_histogram().current_bucket_size()),
+            resources: None,
             index_type: match self {
                 NumericIndexInner::Mutable(_) => "mutable_numeric",
                 NumericIndexInner::Immutable(_) => "immutable_numeric",
@@ -596,7 +595,7 @@ where
     {
         NumericIndexMmapBuilder {
             path: path.to_owned(),
-            in_memory_index: InMemoryNumericIndex::default(),
+            point_to_values: MmapPointToValuesBuilder::new(path),
             is_on_disk,
             _phantom: PhantomData,
         }
@@ -755,7 +754,8 @@ where
     Vec<T>: Blob,
 {
     path: PathBuf,
-    in_memory_index: InMemoryNumericIndex<T>,
+    /// Values of points, pairs and histogram are collected from them on finalize
+    point_to_values: MmapPointToValuesBuilder<T>,
     is_on_disk: bool,
     _phantom: PhantomData<P>,
 }
@@ -778,13 +778,12 @@ where
         payload: &[&Value],
         hw_counter: &HardwareCounterCell,
     ) -> OperationResult<()> {
-        self.in_memory_index.remove_point(id);
         let mut flatten_values: Vec<_> = vec![];
         for value in payload.iter() {
             let payload_values = <NumericIndex<T, P> as ValueIndexer>::get_values(value);
             flatten_values.extend(payload_values);
         }
-        let flatten_values = flatten_values
+        let flatten_values: Vec<T> = flatten_values
             .into_iter()
             .map(NumericIndex::into_inner_value)
             .collect();
@@ -793,12 +792,38 @@ where
             .payload_index_io_write_counter()
             .incr_delta(size_of_val(&flatten_values));
 
-        self.in_memory_index.add_many_to_list(id, flatten_values);
+        // Replaces previous values of the point, if it is added again
+        self.point_to_values
+            .add_point(id, flatten_values.iter().map(|v| T::as_referenced(v)))?;
         Ok(())
     }
 
     fn finalize(self) -> OperationResult<Self::FieldIndexType> {
-        let inner = MmapNumericIndex::build(self.in_memory_index, &self.path, self.is_on_disk)?;
+        let point_to_values = self
+            .point_to_values
+            .finalize(PointToValuesCompression::None)?;
+
+        // Collected from the final values of points, so that values of a point added again
+        // are not paired twice
+        let mut in_memory_index = InMemoryNumericIndex::default();
+        let mut values = Vec::new();
+        for (id, point_values) in point_to_values.iter() {
+            values.clear();
+            values.extend(
+                point_values
+                    .into_iter()
+                    .flatten()
+                    .map(|value| *T::from_referenced(&value)),
+            );
+            in_memory_index.add_many_to_map(id, &values);
+        }
+
+        let inner = MmapNumericIndex::build_with_point_to_values(
+            in_memory_index,
+            point_to_values,
+            &self.path,
+            self.is_on_disk,
+        )?;
         Ok(NumericIndex {
             inner: NumericIndexInner::Mmap(inner),
             _phantom: PhantomData,
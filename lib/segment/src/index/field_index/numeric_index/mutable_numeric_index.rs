@@ -172,7 +172,15 @@ impl<T: Encodable + Numericable + Default> InMemoryNumericIndex<T> {
         if self.point_to_values.len() <= idx as usize {
             self.point_to_values.resize_with(idx as usize + 1, Vec::new)
         }
-        for value in &values {
+        self.add_many_to_map(idx, &values);
+        self.point_to_values[idx as usize] = values;
+    }
+
+    /// Add values of the point to the pairs and the histogram only, without keeping point-to-values.
+    ///
+    /// Values added this way can't be removed with [`Self::remove_point`].
+    pub fn add_many_to_map(&mut self, idx: PointOffsetType, values: &[T]) {
+        for value in values {
             let key = Point::new(*value, idx);
             Self::add_to_map(&mut self.map, &mut self.histogram, key);
         }
@@ -180,7 +188,6 @@ impl<T: Encodable + Numericable + Default> InMemoryNumericIndex<T> {
             self.points_count += 1;
             self.max_values_per_point = self.max_values_per_point.max(values.len());
         }
-        self.point_to_values[idx as usize] = values;
     }
 
     pub fn remove_point(&mut self, idx: PointOffsetType) {
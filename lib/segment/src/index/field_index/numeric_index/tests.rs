@@ -24,6 +24,7 @@ enum IndexType {
     RamMmap,
 }
 
+#[allow(clippy::large_enum_variant)]
 enum IndexBuilder {
     #[cfg(feature = "rocksdb")]
     Mutable(NumericIndexBuilder<FloatPayloadType, FloatPayloadType>),
@@ -638,3 +639,184 @@ fn test_empty_cardinality(#[case] index_type: IndexType) {
         HwMeasurementAcc::new(),
     );
 }
+
+#[test]
+fn test_datetime_mmap_index_timezone_range() {
+    let temp_dir = Builder::new()
+        .prefix("test_numeric_index")
+        .tempdir()
+        .unwrap();
+    let mut builder =
+        NumericIndex::<IntPayloadType, DateTimePayloadType>::builder_mmap(temp_dir.path(), false);
+    builder.init().unwrap();
+
+    let hw_counter = HardwareCounterCell::new();
+    let datetimes = [
+        "2024-01-01T00:00:00Z",
+        "2024-01-01T03:00:00+02:00",
+        "2024-01-01T01:30:00Z",
+        "2023-12-31T22:00:00-03:00",
+        "2024-01-01 04:00:00",
+    ];
+    for (idx, datetime) in datetimes.iter().enumerate() {
+        let value = Value::from(*datetime);
+        builder
+            .add_point(idx as PointOffsetType, &[&value], &hw_counter)
+            .unwrap();
+    }
+    drop(builder.finalize().unwrap());
+
+    // Pairs are stored as order-preserving UTC microseconds, smallest first
+    let pairs = fs_err::read(temp_dir.path().join("pairs.bin")).unwrap();
+    let first = DateTimePayloadType::from_str("2024-01-01T00:00:00Z").unwrap();
+    assert_eq!(pairs[..8], (first.timestamp() ^ i64::MIN).to_be_bytes());
+
+    let index = NumericIndexInner::<IntPayloadType>::new_mmap(temp_dir.path(), false)
+        .unwrap()
+        .unwrap();
+
+    // Bounds in different timezones are compared as UTC instants
+    let range: RangeInterface = serde_json::from_value(serde_json::json!({
+        "gte": "2024-01-01T02:00:00+01:00",
+        "lt": "2024-01-01T01:00:00-02:00",
+    }))
+    .unwrap();
+    let RangeInterface::DateTime(range) = range else {
+        panic!("Expected datetime range");
+    };
+    let condition = FieldCondition::new_datetime_range(JsonPath::new("unused"), range);
+    let points = index
+        .filter(&condition, &hw_counter)
+        .unwrap()
+        .sorted()
+        .collect_vec();
+    assert_eq!(points, vec![1, 2, 3]);
+}
+
+#[test]
+fn test_mmap_index_legacy_pairs() {
+    let temp_dir = Builder::new()
+        .prefix("test_numeric_index")
+        .tempdir()
+        .unwrap();
+    let mut builder =
+        NumericIndex::<IntPayloadType, IntPayloadType>::builder_mmap(temp_dir.path(), false);
+    builder.init().unwrap();
+
+    let hw_counter = HardwareCounterCell::new();
+    for idx in 0..10 {
+        let value = Value::from(IntPayloadType::from(idx) * 10);
+        builder
+            .add_point(idx as PointOffsetType, &[&value], &hw_counter)
+            .unwrap();
+    }
+    drop(builder.finalize().unwrap());
+
+    // Replace pairs with the legacy native-endian layout
+    let legacy_pairs: Vec<u8> = (0..10)
+        .flat_map(|idx: PointOffsetType| {
+            let mut pair = Vec::with_capacity(size_of::<Point<IntPayloadType>>());
+            pair.extend((IntPayloadType::from(idx) * 10).to_ne_bytes());
+            pair.extend(idx.to_ne_bytes());
+            pair.resize(size_of::<Point<IntPayloadType>>(), 0);
+            pair
+        })
+        .collect();
+    let legacy_path = temp_dir.path().join("data.bin");
+    fs_err::write(&legacy_path, &legacy_pairs).unwrap();
+    fs_err::remove_file(temp_dir.path().join("pairs.bin")).unwrap();
+    let config_path = temp_dir.path().join("mmap_field_index_config.json");
+    let mut config: serde_json::Value = common::fs::read_json(&config_path).unwrap();
+    config["pairs_format_version"] = 0.into();
+    common::fs::atomic_save_json(&config_path, &config).unwrap();
+
+    let index = NumericIndexInner::<IntPayloadType>::new_mmap(temp_dir.path(), true)
+        .unwrap()
+        .unwrap();
+    let range = Range {
+        lt: None,
+        gt: None,
+        gte: Some(OrderedFloat(30.0)),
+        lte: Some(OrderedFloat(50.0)),
+    };
+    let condition = FieldCondition::new_range(JsonPath::new("unused"), range);
+    let points = index.filter(&condition, &hw_counter).unwrap().collect_vec();
+    assert_eq!(points, vec![3, 4, 5]);
+    drop(index);
+
+    // Legacy pairs are removed after the migration
+    assert!(!legacy_path.exists());
+    assert!(temp_dir.path().join("pairs.bin").is_file());
+
+    // Layouts of newer versions are refused
+    config["pairs_format_version"] = 2.into();
+    common::fs::atomic_save_json(&config_path, &config).unwrap();
+    assert!(NumericIndexInner::<IntPayloadType>::new_mmap(temp_dir.path(), true).is_err());
+}
+
+#[test]
+fn test_mmap_builder_replaces_values_of_point_added_again() {
+    let temp_dir = Builder::new()
+        .prefix("test_numeric_index")
+        .tempdir()
+        .unwrap();
+    let mut builder =
+        NumericIndex::<IntPayloadType, IntPayloadType>::builder_mmap(temp_dir.path(), false);
+    builder.init().unwrap();
+
+    let hw_counter = HardwareCounterCell::new();
+    for (idx, value) in [(0, 10), (1, 20), (0, 30)] {
+        let value = Value::from(value);
+        builder.add_point(idx, &[&value], &hw_counter).unwrap();
+    }
+    let index = builder.finalize().unwrap();
+
+    let range = Range {
+        lt: None,
+        gt: None,
+        gte: Some(OrderedFloat(0.0)),
+        lte: Some(OrderedFloat(100.0)),
+    };
+    let condition = FieldCondition::new_range(JsonPath::new("unused"), range);
+    let points = index
+        .inner()
+        .filter(&condition, &hw_counter)
+        .unwrap()
+        .sorted()
+        .collect_vec();
+    assert_eq!(points, vec![0, 1]);
+    assert_eq!(
+        index.get_values(0).unwrap().collect_vec(),
+        vec![30 as IntPayloadType],
+    );
+}
+
+#[test]
+fn test_ordered_bytes_order() {
+    let floats = [
+        f64::NEG_INFINITY,
+        -1e300,
+        -1.5,
+        -f64::MIN_POSITIVE,
+        0.0,
+        f64::MIN_POSITIVE,
+        1.5,
+        1e300,
+        f64::INFINITY,
+    ];
+    for (a, b) in floats.iter().tuple_windows() {
+        assert!(a.to_ordered_bytes() < b.to_ordered_bytes(), "{a} < {b}");
+    }
+    for value in floats {
+        assert_eq!(f64::from_ordered_bytes(value.to_ordered_bytes()), value);
+    }
+    assert_eq!((-0.0f64).to_ordered_bytes(), 0.0f64.to_ordered_bytes());
+
+    let ints = [i64::MIN, -1000, -1, 0, 1, 1000, i64::MAX];
+    for (a, b) in ints.iter().tuple_windows() {
+        assert!(a.to_ordered_bytes() < b.to_ordered_bytes(), "{a} < {b}");
+    }
+    for value in ints {
+        assert_eq!(i64::from_ordered_bytes(value.to_ordered_bytes()), value);
+    }
+}
@@ -2,6 +2,8 @@ use std::cell::Cell;
 use std::collections::BTreeSet;
 use std::collections::Bound::{Excluded, Included, Unbounded};
 
+use common::fs::atomic_save_bin;
+use fs_err as fs;
 use itertools::Itertools;
 use rand::prelude::StdRng;
 use rand::{Rng, SeedableRng};
@@ -9,7 +11,7 @@ use rand_distr::StandardNormal;
 use serde::Serialize;
 use serde::de::DeserializeOwned;
 
-use crate::index::field_index::histogram::{Histogram, Numericable, Point};
+use crate::index::field_index::histogram::{Counts, Histogram, Numericable, Point};
 use crate::index::field_index::tests::histogram_test_utils::print_results;
 
 pub fn count_range<T: PartialOrd>(points_index: &BTreeSet<Point<T>>, a: T, b: T) -> usize {
@@ -278,4 +280,46 @@ fn test_save_load_histogram() {
 
     let loaded_histogram = Histogram::<f64>::load(dir.path()).unwrap();
     assert_eq!(histogram, loaded_histogram);
+
+    let bytes = fs::read(dir.path().join("histogram_borders.bin")).unwrap();
+    assert!(bytes.starts_with(b"hbrd"), "missing borders magic");
+}
+
+#[test]
+fn test_load_legacy_histogram_borders() {
+    let mut rnd = StdRng::seed_from_u64(42);
+    let points = (0..10_000)
+        .map(|i| Point {
+            val: rnd.random_range(-1000..1000),
+            idx: i,
+        })
+        .collect_vec();
+    let (histogram, _) = build_histogram::<i64>(100, 0.01, points);
+
+    let dir = tempfile::Builder::new()
+        .prefix("histogram_dir")
+        .tempdir()
+        .unwrap();
+    histogram.save(dir.path()).unwrap();
+
+    // Overwrite borders with the legacy bincode encoding
+    let borders_path = dir.path().join("histogram_borders.bin");
+    let legacy_borders: Vec<(Point<i64>, Counts)> = histogram
+        .borders()
+        .iter()
+        .map(|(point, counts)| (point.clone(), counts.clone()))
+        .collect();
+    atomic_save_bin(&borders_path, &legacy_borders).unwrap();
+
+    let loaded_histogram = Histogram::<i64>::load(dir.path()).unwrap();
+    assert_eq!(histogram, loaded_histogram);
+
+    // Legacy borders are migrated on load
+    let bytes = fs::read(&borders_path).unwrap();
+    assert!(
+        bytes.starts_with(b"hbrd"),
+        "legacy borders were not migrated"
+    );
+    let reloaded_histogram = Histogram::<i64>::load(dir.path()).unwrap();
+    assert_eq!(histogram, reloaded_histogram);
 }
@@ -0,0 +1,445 @@
+use std::collections::HashMap;
+use std::io::Write;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+use ahash::AHashSet;
+use common::counter::hardware_counter::HardwareCounterCell;
+use common::fs::{atomic_save, clear_disk_cache};
+use common::mmap::{AdviceSetting, Madviseable, open_read_mmap};
+use common::types::PointOffsetType;
+use fs_err as fs;
+use memmap2::Mmap;
+use serde_json::Value;
+
+use crate::common::Flusher;
+use crate::common::flags::dynamic_mmap_flags::DynamicMmapFlags;
+use crate::common::operation_error::{OperationError, OperationResult};
+use crate::index::field_index::{CardinalityEstimation, PrimaryCondition};
+use crate::types::{FieldCondition, Match, MatchPhrase, MatchText};
+
+const TRIGRAM_PATH: &str = "trigram.bin";
+const STALE_DIR: &str = "stale";
+
+const TRIGRAM_MAGIC: &[u8; 4] = b"tgix";
+const TRIGRAM_VERSION: u32 = 1;
+/// Magic, version, number of trigrams and number of postings
+const TRIGRAM_HEADER_SIZE: usize = 24;
+
+/// Length of a trigram in bytes
+const TRIGRAM_LEN: usize = 3;
+
+/// Index of byte trigrams of string values, to pre-filter candidates of substring matches.
+///
+/// Persisted in a single little-endian file:
+///
+/// ```text
+/// header:   [magic: "tgix"][version: u32][trigrams_count: u64][postings_count: u64]
+/// offsets:  [offset: u64] * (trigrams_count + 1)
+/// trigrams: [trigram: u32] * trigrams_count
+/// postings: [point_id: u32] * postings_count
+/// ```
+///
+/// Trigrams are sorted, and so are the points in the posting of each trigram. A point contains a
+/// substring only if it is in the postings of all trigrams of the substring, so the intersection of
+/// these postings is a superset of the matching points.
+///
+/// The file is immutable, points with changed payload are only marked as stale. Stale points are
+/// always returned as candidates, and have to be checked against the original condition.
+pub struct TrigramIndex {
+    path: PathBuf,
+    mmap: Mmap,
+    trigrams_count: usize,
+    postings_count: usize,
+    /// Points, which payload has changed after the index was built
+    stale: DynamicMmapFlags,
+}
+
+impl std::fmt::Debug for TrigramIndex {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TrigramIndex")
+            .field("path", &self.path)
+            .field("trigrams_count", &self.trigrams_count)
+            .field("postings_count", &self.postings_count)
+            .finish_non_exhaustive()
+    }
+}
+
+impl TrigramIndex {
+    pub fn open(dir: &Path, populate: bool) -> OperationResult<Self> {
+        let path = dir.join(TRIGRAM_PATH);
+        let mmap = open_read_mmap(&path, AdviceSetting::Global, populate)?;
+        if mmap.len() < TRIGRAM_HEADER_SIZE || &mmap[0..4] != TRIGRAM_MAGIC {
+            return Err(OperationError::service_error(format!(
+                "Corrupted {TRIGRAM_PATH}: bad header",
+            )));
+        }
+
+        let version = read_u32(&mmap, 4);
+        if version != TRIGRAM_VERSION {
+            return Err(OperationError::service_error(format!(
+                "Unsupported {TRIGRAM_PATH} version: {version}",
+            )));
+        }
+
+        let trigrams_count = read_u64(&mmap, 8) as usize;
+        let postings_count = read_u64(&mmap, 16) as usize;
+
+        let expected = (trigrams_count + 1)
+            .checked_mul(size_of::<u64>())
+            .and_then(|size| size.checked_add(trigrams_count.checked_mul(size_of::<u32>())?))
+            .and_then(|size| size.checked_add(postings_count.checked_mul(size_of::<u32>())?))
+            .and_then(|size| size.checked_add(TRIGRAM_HEADER_SIZE));
+        if expected != Some(mmap.len()) {
+            return Err(OperationError::service_error(format!(
+                "Corrupted {TRIGRAM_PATH}: {trigrams_count} trigrams and {postings_count} postings don't fit {} bytes",
+                mmap.len(),
+            )));
+        }
+
+        let stale = DynamicMmapFlags::open(&dir.join(STALE_DIR), populate)?;
+
+        Ok(Self {
+            path,
+            mmap,
+            trigrams_count,
+            postings_count,
+            stale,
+        })
+    }
+
+    /// Mark the point as stale, if its payload has changed after the index was built.
+    pub fn mark_stale(
+        &mut self,
+        point_id: PointOffsetType,
+        hw_counter: &HardwareCounterCell,
+    ) -> OperationResult<()> {
+        self.stale.set_with_resize(
+            point_id,
+            true,
+            hw_counter.ref_payload_index_io_write_counter(),
+        )?;
+        Ok(())
+    }
+
+    /// Candidate points for a substring match, `None` if the condition can't be served.
+    ///
+    /// Candidates include stale points, so they have to be checked against the condition.
+    pub fn filter<'a>(
+        &'a self,
+        condition: &FieldCondition,
+        hw_counter: &HardwareCounterCell,
+    ) -> Option<Box<dyn Iterator<Item = PointOffsetType> + 'a>> {
+        let mut postings = self.query_postings(condition)?;
+        postings.sort_unstable_by_key(|posting| posting.len());
+        let (shortest, others) = postings.split_first()?;
+
+        // Shortest posting is read entirely, others are only probed
+        hw_counter
+            .payload_index_io_read_counter()
+            .incr_delta(shortest.len() * size_of::<PointOffsetType>());
+
+        let others = others.to_vec();
+        let matched = shortest
+            .clone()
+            .map(|index| self.point(index))
+            .filter(move |&point_id| {
+                others
+                    .iter()
+                    .all(|posting| self.posting_contains(posting.clone(), point_id))
+            })
+            .filter(|point_id| !self.stale.get(*point_id));
+        Some(Box::new(matched.chain(self.stale.iter_trues())))
+    }
+
+    pub fn estimate_cardinality(
+        &self,
+        condition: &FieldCondition,
+    ) -> Option<CardinalityEstimation> {
+        let postings = self.query_postings(condition)?;
+        let smallest = postings.iter().map(|posting| posting.len()).min()?;
+        let stale = self.stale.count_flags();
+        Some(
+            CardinalityEstimation {
+                primary_clauses: vec![],
+                min: 0,
+                exp: smallest,
+                max: smallest + stale,
+            }
+            .with_primary_clause(PrimaryCondition::Trigram(Box::new(condition.clone()))),
+        )
+    }
+
+    /// Postings of all trigrams of the substring of the condition.
+    ///
+    /// A trigram, which is not in the index, has an empty posting.
+    fn query_postings(&self, condition: &FieldCondition) -> Option<Vec<Range<usize>>> {
+        let (Match::Text(MatchText { text }) | Match::Phrase(MatchPhrase { phrase: text })) =
+            condition.r#match.as_ref()?
+        else {
+            return None;
+        };
+        if text.len() < TRIGRAM_LEN {
+            return None;
+        }
+
+        let mut query: Vec<u32> = trigrams(text).collect();
+        query.sort_unstable();
+        query.dedup();
+        Some(
+            query
+                .into_iter()
+                .map(|trigram| self.posting(trigram).unwrap_or(0..0))
+                .collect(),
+        )
+    }
+
+    fn posting(&self, trigram: u32) -> Option<Range<usize>> {
+        let (mut low, mut high) = (0, self.trigrams_count);
+        while low < high {
+            let middle = low + (high - low) / 2;
+            match self.trigram(middle).cmp(&trigram) {
+                std::cmp::Ordering::Less => low = middle + 1,
+                std::cmp::Ordering::Greater => high = middle,
+                std::cmp::Ordering::Equal => {
+                    let start =
+                        read_u64(&self.mmap, TRIGRAM_HEADER_SIZE + middle * size_of::<u64>());
+                    let end = read_u64(
+                        &self.mmap,
+                        TRIGRAM_HEADER_SIZE + (middle + 1) * size_of::<u64>(),
+                    );
+                    return Some(start as usize..end as usize);
+                }
+            }
+        }
+        None
+    }
+
+    fn posting_contains(&self, posting: Range<usize>, point_id: PointOffsetType) -> bool {
+        let (mut low, mut high) = (posting.start, posting.end);
+        while low < high {
+            let middle = low + (high - low) / 2;
+            match self.point(middle).cmp(&point_id) {
+                std::cmp::Ordering::Less => low = middle + 1,
+                std::cmp::Ordering::Greater => high = middle,
+                std::cmp::Ordering::Equal => return true,
+            }
+        }
+        false
+    }
+
+    fn trigrams_start(&self) -> usize {
+        TRIGRAM_HEADER_SIZE + (self.trigrams_count + 1) * size_of::<u64>()
+    }
+
+    fn trigram(&self, index: usize) -> u32 {
+        read_u32(&self.mmap, self.trigrams_start() + index * size_of::<u32>())
+    }
+
+    fn point(&self, index: usize) -> PointOffsetType {
+        let postings_start = self.trigrams_start() + self.trigrams_count * size_of::<u32>();
+        read_u32(
+            &self.mmap,
+            postings_start + index * size_of::<PointOffsetType>(),
+        )
+    }
+
+    pub fn flusher(&self) -> Flusher {
+        self.stale.flusher()
+    }
+
+    pub fn files(&self) -> Vec<PathBuf> {
+        let mut files = vec![self.path.clone()];
+        files.extend(self.stale.files());
+        files
+    }
+
+    pub fn wipe(self) -> OperationResult<()> {
+        let dir = self.path.parent().map(Path::to_path_buf);
+        drop(self);
+        if let Some(dir) = dir {
+            fs::remove_dir_all(dir)?;
+        }
+        Ok(())
+    }
+
+    /// Populate all pages in the mmap.
+    /// Block until all pages are populated.
+    pub fn populate(&self) -> OperationResult<()> {
+        self.mmap.populate();
+        self.stale.populate()
+    }
+
+    /// Drop disk cache.
+    pub fn clear_cache(&self) -> OperationResult<()> {
+        clear_disk_cache(&self.path)?;
+        self.stale.clear_cache()
+    }
+}
+
+/// Collects trigrams of string values of points, to be written by [`TrigramIndexBuilder::finalize`].
+pub struct TrigramIndexBuilder {
+    dir: PathBuf,
+    postings: HashMap<u32, Vec<PointOffsetType>>,
+}
+
+impl TrigramIndexBuilder {
+    pub fn new(dir: &Path) -> Self {
+        Self {
+            dir: dir.to_path_buf(),
+            postings: HashMap::new(),
+        }
+    }
+
+    pub fn add_point(&mut self, point_id: PointOffsetType, values: &[&Value]) {
+        let mut point_trigrams = AHashSet::new();
+        for value in values {
+            match value {
+                Value::String(text) => point_trigrams.extend(trigrams(text)),
+                Value::Array(values) => {
+                    for value in values {
+                        if let Value::String(text) = value {
+                            point_trigrams.extend(trigrams(text));
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+        for trigram in point_trigrams {
+            self.postings.entry(trigram).or_default().push(point_id);
+        }
+    }
+
+    /// Write the index, to be opened with [`TrigramIndex::open`].
+    pub fn finalize(self) -> OperationResult<()> {
+        let mut postings: Vec<_> = self.postings.into_iter().collect();
+        postings.sort_unstable_by_key(|(trigram, _)| *trigram);
+        for (_, points) in &mut postings {
+            points.sort_unstable();
+        }
+        let postings_count: usize = postings.iter().map(|(_, points)| points.len()).sum();
+
+        fs::create_dir_all(&self.dir)?;
+        // Stale points of a previous build don't apply to the new one
+        let stale_dir = self.dir.join(STALE_DIR);
+        if stale_dir.exists() {
+            fs::remove_dir_all(&stale_dir)?;
+        }
+
+        atomic_save::<OperationError, _>(&self.dir.join(TRIGRAM_PATH), |writer| {
+            writer.write_all(TRIGRAM_MAGIC)?;
+            writer.write_all(&TRIGRAM_VERSION.to_le_bytes())?;
+            writer.write_all(&(postings.len() as u64).to_le_bytes())?;
+            writer.write_all(&(postings_count as u64).to_le_bytes())?;
+
+            let mut offset = 0u64;
+            writer.write_all(&offset.to_le_bytes())?;
+            for (_, points) in &postings {
+                offset += points.len() as u64;
+                writer.write_all(&offset.to_le_bytes())?;
+            }
+            for (trigram, _) in &postings {
+                writer.write_all(&trigram.to_le_bytes())?;
+            }
+            for (_, points) in &postings {
+                for point_id in points {
+                    writer.write_all(&point_id.to_le_bytes())?;
+                }
+            }
+            Ok(())
+        })?;
+
+        Ok(())
+    }
+}
+
+/// Byte trigrams of the text, packed into `u32`.
+fn trigrams(text: &str) -> impl Iterator<Item = u32> + '_ {
+    text.as_bytes()
+        .windows(TRIGRAM_LEN)
+        .map(|window| u32::from(window[0]) << 16 | u32::from(window[1]) << 8 | u32::from(window[2]))
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(
+        bytes[offset..offset + 4]
+            .try_into()
+            .expect("slice length mismatch"),
+    )
+}
+
+fn read_u64(bytes: &[u8], offset: usize) -> u64 {
+    u64::from_le_bytes(
+        bytes[offset..offset + 8]
+            .try_into()
+            .expect("slice length mismatch"),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use tempfile::Builder;
+
+    use super::*;
+    use crate::json_path::JsonPath;
+
+    fn condition(text: &str) -> FieldCondition {
+        FieldCondition::new_match(JsonPath::new("name"), Match::new_text(text))
+    }
+
+    fn filter(index: &TrigramIndex, text: &str) -> Vec<PointOffsetType> {
+        let hw_counter = HardwareCounterCell::new();
+        let mut points: Vec<_> = index
+            .filter(&condition(text), &hw_counter)
+            .unwrap()
+            .collect();
+        points.sort_unstable();
+        points.dedup();
+        points
+    }
+
+    #[test]
+    fn test_trigram_index() {
+        let dir = Builder::new().prefix("trigram_index").tempdir().unwrap();
+        let values = [
+            json!("hello world"),
+            json!(["yellow", "submarine"]),
+            json!("Hello"),
+            json!(42),
+            json!("llo"),
+        ];
+
+        let mut builder = TrigramIndexBuilder::new(dir.path());
+        for (point_id, value) in values.iter().enumerate() {
+            builder.add_point(point_id as PointOffsetType, &[value]);
+        }
+        builder.finalize().unwrap();
+        let mut index = TrigramIndex::open(dir.path(), false).unwrap();
+
+        assert_eq!(filter(&index, "llo"), vec![0, 1, 2, 4]);
+        assert_eq!(filter(&index, "ello"), vec![0, 1, 2]);
+        assert_eq!(filter(&index, "hello"), vec![0]);
+        assert_eq!(filter(&index, "marine"), vec![1]);
+        assert!(filter(&index, "goodbye").is_empty());
+
+        // Too short to be served by trigrams
+        let hw_counter = HardwareCounterCell::new();
+        assert!(index.filter(&condition("ll"), &hw_counter).is_none());
+        assert!(index.estimate_cardinality(&condition("ll")).is_none());
+
+        let estimation = index.estimate_cardinality(&condition("ello")).unwrap();
+        assert_eq!(estimation.exp, 3);
+        assert_eq!(estimation.primary_clauses.len(), 1);
+
+        // Stale points are candidates regardless of their indexed values
+        index.mark_stale(3, &hw_counter).unwrap();
+        index.flusher()().unwrap();
+        drop(index);
+
+        let index = TrigramIndex::open(dir.path(), false).unwrap();
+        assert_eq!(filter(&index, "hello"), vec![0, 3]);
+        assert_eq!(filter(&index, "goodbye"), vec![3]);
+    }
+}
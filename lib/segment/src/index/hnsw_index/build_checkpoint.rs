@@ -0,0 +1,166 @@
+use std::hash::Hasher as _;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use bitvec::slice::BitSlice;
+use common::ext::BitSliceExt as _;
+use common::fs::{atomic_save_bin, read_bin};
+use common::stable_hash::StableHash as _;
+use common::types::PointOffsetType;
+use fs_err as fs;
+use seahash::SeaHasher;
+use serde::{Deserialize, Serialize};
+
+use super::HnswM;
+use crate::common::operation_error::OperationResult;
+use crate::id_tracker::IdTrackerSS;
+
+const BUILD_CHECKPOINT_VERSION: u32 = 1;
+
+/// Checkpoints not updated for this long belong to builds, which are not going to be resumed.
+const BUILD_CHECKPOINT_MAX_AGE: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+/// Progress of the main HNSW graph construction.
+///
+/// Saved periodically during long builds, so that an interrupted build resumes linking from
+/// the last checkpoint instead of starting from scratch.
+///
+/// Indexes are built in temporary segments, which are removed on restart. So checkpoints are
+/// kept in a separate directory, one file per build, named by [`BuildCheckpoint::build_key`].
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BuildCheckpoint {
+    version: u32,
+    m: usize,
+    m0: usize,
+    ef_construct: usize,
+    /// Level of each point in the vector storage
+    pub levels: Vec<u8>,
+    /// Links of points linked so far, by level
+    pub linked: Vec<(PointOffsetType, Vec<Vec<PointOffsetType>>)>,
+}
+
+impl BuildCheckpoint {
+    pub fn new(
+        hnsw_m: HnswM,
+        ef_construct: usize,
+        levels: Vec<u8>,
+        linked: Vec<(PointOffsetType, Vec<Vec<PointOffsetType>>)>,
+    ) -> Self {
+        Self {
+            version: BUILD_CHECKPOINT_VERSION,
+            m: hnsw_m.m,
+            m0: hnsw_m.m0,
+            ef_construct,
+            levels,
+            linked,
+        }
+    }
+
+    pub fn get_path(dir: &Path, build_key: u64) -> PathBuf {
+        dir.join(format!("{build_key:016x}.bin"))
+    }
+
+    /// Identify the build of the index at `index_path` by its points.
+    ///
+    /// Offsets of points are only the same if the segment is built from the same versions of
+    /// the same points, so the checkpoint of another build is never loaded.
+    pub fn build_key(index_path: &Path, id_tracker: &IdTrackerSS, num_points: usize) -> u64 {
+        let mut hasher = SeaHasher::new();
+        // Index directory is named after the vector
+        let index_name = index_path.file_name().unwrap_or_default();
+        hasher.write(index_name.as_encoded_bytes());
+        for offset in 0..num_points as PointOffsetType {
+            if let Some(external_id) = id_tracker.external_id(offset) {
+                external_id.stable_hash(&mut |bytes| hasher.write(bytes));
+            }
+            let version = id_tracker.internal_version(offset).unwrap_or(0);
+            hasher.write(&version.to_le_bytes());
+        }
+        hasher.finish()
+    }
+
+    /// Load checkpoint of a build with the same parameters.
+    ///
+    /// Returns `None` if there is no checkpoint, or it can't be used to resume the build.
+    pub fn load(
+        path: &Path,
+        hnsw_m: HnswM,
+        ef_construct: usize,
+        num_points: usize,
+        deleted: &BitSlice,
+    ) -> Option<Self> {
+        if !path.exists() {
+            return None;
+        }
+
+        let checkpoint: Self = match read_bin(path) {
+            Ok(checkpoint) => checkpoint,
+            Err(err) => {
+                log::warn!(
+                    "Ignoring unreadable HNSW build checkpoint {}: {err}",
+                    path.display(),
+                );
+                return None;
+            }
+        };
+
+        let compatible = checkpoint.version == BUILD_CHECKPOINT_VERSION
+            && checkpoint.m == hnsw_m.m
+            && checkpoint.m0 == hnsw_m.m0
+            && checkpoint.ef_construct == ef_construct
+            && checkpoint.levels.len() == num_points
+            // Linked points must not be deleted since the checkpoint
+            && checkpoint
+                .linked
+                .iter()
+                .all(|(point_id, links)| {
+                    (*point_id as usize) < num_points
+                        && !deleted.get_bit(*point_id as usize).unwrap_or(false)
+                        && links.len() == usize::from(checkpoint.levels[*point_id as usize]) + 1
+                });
+        if !compatible {
+            log::warn!(
+                "Ignoring HNSW build checkpoint {} of a different build",
+                path.display(),
+            );
+            return None;
+        }
+
+        Some(checkpoint)
+    }
+
+    pub fn save(&self, path: &Path) -> OperationResult<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        atomic_save_bin(path, self)?;
+        Ok(())
+    }
+
+    /// Remove the checkpoint once the build is finished
+    pub fn remove(path: &Path) -> OperationResult<()> {
+        if path.exists() {
+            fs::remove_file(path)?;
+        }
+        Ok(())
+    }
+
+    /// Remove checkpoints in `dir`, which were not updated for a long time
+    pub fn remove_stale(dir: &Path) -> OperationResult<()> {
+        if !dir.exists() {
+            return Ok(());
+        }
+        for entry in fs::read_dir(dir)? {
+            let entry = entry?;
+            let age = entry.metadata()?.modified()?.elapsed().unwrap_or_default();
+            if age >= BUILD_CHECKPOINT_MAX_AGE {
+                log::debug!(
+                    "Removing stale HNSW build checkpoint {}",
+                    entry.path().display()
+                );
+                fs::remove_file(entry.path())?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -32,8 +32,10 @@ pub struct HnswGraphConfig {
 }
 
 impl HnswGraphConfig {
+    /// `m0` defaults to `2 * m` if not set.
     pub fn new(
         m: usize,
+        m0: Option<usize>,
         ef_construct: usize,
         full_scan_threshold: usize,
         max_indexing_threads: usize,
@@ -42,7 +44,7 @@ impl HnswGraphConfig {
     ) -> Self {
         HnswGraphConfig {
             m,
-            m0: m * 2,
+            m0: m0.unwrap_or(m * 2),
             ef_construct,
             ef: ef_construct,
             full_scan_threshold,
@@ -0,0 +1,295 @@
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use common::fs::{atomic_save_json, read_json};
+use common::types::{PointOffsetType, ScoredPointOffset};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+
+use crate::common::Flusher;
+use crate::common::operation_error::OperationResult;
+use crate::data_types::vectors::QueryVector;
+
+pub const EF_TUNER_FILE: &str = "ef_tuner.json";
+
+/// Every `SAMPLE_INTERVAL`-th search with default `ef` is compared with exact search.
+const SAMPLE_INTERVAL: usize = 128;
+
+/// Number of compared searches, after which `ef` is adjusted.
+const SAMPLES_PER_ROUND: usize = 16;
+
+/// Searches sampled after this many are still pending are dropped.
+const MAX_PENDING_SAMPLES: usize = SAMPLES_PER_ROUND;
+
+const MIN_EF: usize = 8;
+const MAX_EF: usize = 4096;
+
+/// Persisted state of [`EfTuner`].
+#[derive(Debug, Serialize, Deserialize)]
+struct EfTunerState {
+    target_recall: f64,
+    ef: usize,
+}
+
+#[derive(Debug, Default)]
+struct TuningRound {
+    samples: usize,
+    /// Number of exact search results, which were also found by HNSW search
+    found: usize,
+    /// Number of exact search results
+    expected: usize,
+}
+
+/// Copy of a sampled search, to be compared with exact search outside of the search path.
+#[derive(Debug)]
+struct PendingSample {
+    query: QueryVector,
+    top: usize,
+    found: Vec<PointOffsetType>,
+}
+
+/// Tunes default `ef` of HNSW search toward the target recall.
+///
+/// A fraction of searches with default `ef` is copied aside, and later repeated with exact search
+/// by [`EfTuner::evaluate_pending`]. Once enough of them are compared, `ef` is increased if the
+/// measured recall is below the target, or decreased if the number of missed results is less
+/// than half of the allowed.
+///
+/// Tuned `ef` is persisted by the segment flusher, see [`EfTuner::flusher`].
+#[derive(Debug)]
+pub struct EfTuner {
+    path: PathBuf,
+    target_recall: f64,
+    ef: AtomicUsize,
+    /// `ef`, which is stored on disk
+    persisted_ef: Arc<AtomicUsize>,
+    searches: AtomicUsize,
+    pending: Mutex<Vec<PendingSample>>,
+    round: Mutex<TuningRound>,
+}
+
+impl EfTuner {
+    /// Load `ef` tuned for the same target recall, or start from `initial_ef`.
+    pub fn open(dir: &Path, target_recall: f64, initial_ef: usize) -> Self {
+        let path = Self::get_path(dir);
+        let persisted_ef = if path.exists() {
+            match read_json::<EfTunerState>(&path) {
+                Ok(state) if state.target_recall == target_recall => Some(state.ef),
+                Ok(_) => None,
+                Err(err) => {
+                    log::warn!("Ignoring unreadable tuned ef {}: {err}", path.display());
+                    None
+                }
+            }
+        } else {
+            None
+        };
+        let ef = persisted_ef.unwrap_or(initial_ef);
+
+        Self {
+            path,
+            target_recall,
+            ef: AtomicUsize::new(ef),
+            // Zero is never a valid `ef`, so the initial value is persisted on the next flush
+            persisted_ef: Arc::new(AtomicUsize::new(persisted_ef.unwrap_or(0))),
+            searches: AtomicUsize::new(0),
+            pending: Mutex::new(Vec::new()),
+            round: Mutex::new(TuningRound::default()),
+        }
+    }
+
+    pub fn get_path(dir: &Path) -> PathBuf {
+        dir.join(EF_TUNER_FILE)
+    }
+
+    /// Current tuned `ef`
+    pub fn ef(&self) -> usize {
+        self.ef.load(Ordering::Relaxed)
+    }
+
+    /// Copy a fraction of searches with default `ef`, to compare them with exact search later.
+    ///
+    /// Only cheap bookkeeping is done here, so that searches are never slowed down by tuning.
+    pub fn sample(&self, query: &QueryVector, top: usize, result: &[ScoredPointOffset]) {
+        if !self
+            .searches
+            .fetch_add(1, Ordering::Relaxed)
+            .is_multiple_of(SAMPLE_INTERVAL)
+        {
+            return;
+        }
+        let mut pending = self.pending.lock();
+        if pending.len() >= MAX_PENDING_SAMPLES {
+            return;
+        }
+        pending.push(PendingSample {
+            query: query.clone(),
+            top,
+            found: result.iter().map(|scored| scored.idx).collect(),
+        });
+    }
+
+    /// Compare pending sampled searches with `exact_search`, and adjust `ef` with the results.
+    ///
+    /// Failed exact searches are skipped.
+    pub fn evaluate_pending(
+        &self,
+        exact_search: impl Fn(&QueryVector, usize) -> OperationResult<Vec<ScoredPointOffset>>,
+    ) {
+        let pending = std::mem::take(&mut *self.pending.lock());
+        for PendingSample { query, top, found } in pending {
+            let exact_result = match exact_search(&query, top) {
+                Ok(exact_result) => exact_result,
+                Err(err) => {
+                    log::debug!("Skipping sampled search for ef tuning: {err}");
+                    continue;
+                }
+            };
+            let found_exact = exact_result
+                .iter()
+                .filter(|exact| found.contains(&exact.idx))
+                .count();
+            self.record(found_exact, exact_result.len());
+        }
+    }
+
+    /// Record a sampled search, which found `found` of `expected` results of exact search.
+    ///
+    /// Adjusts `ef` once enough searches are sampled.
+    fn record(&self, found: usize, expected: usize) {
+        let mut round = self.round.lock();
+        round.samples += 1;
+        round.found += found;
+        round.expected += expected;
+        if round.samples < SAMPLES_PER_ROUND {
+            return;
+        }
+        let TuningRound {
+            samples: _,
+            found,
+            expected,
+        } = std::mem::take(&mut *round);
+
+        if expected == 0 {
+            return;
+        }
+
+        let recall = found as f64 / expected as f64;
+        let allowed_misses = 1.0 - self.target_recall;
+        let ef = self.ef();
+        let new_ef = if recall < self.target_recall {
+            (ef + ef / 2).clamp(MIN_EF, MAX_EF)
+        } else if 1.0 - recall <= allowed_misses / 2.0 {
+            (ef - ef / 10).clamp(MIN_EF, MAX_EF)
+        } else {
+            ef
+        };
+
+        if new_ef != ef {
+            log::debug!(
+                "Tuned HNSW ef {ef} -> {new_ef}, recall {recall:.3}, target {}",
+                self.target_recall,
+            );
+            self.ef.store(new_ef, Ordering::Relaxed);
+        }
+    }
+
+    /// Persist tuned `ef`, if it changed since it was last persisted.
+    ///
+    /// Tuned `ef` is only an optimization, so failing to save it is logged and doesn't fail
+    /// the flush.
+    pub fn flusher(&self) -> Option<Flusher> {
+        let ef = self.ef();
+        if self.persisted_ef.load(Ordering::Relaxed) == ef {
+            return None;
+        }
+
+        let path = self.path.clone();
+        let target_recall = self.target_recall;
+        let persisted_ef = self.persisted_ef.clone();
+        Some(Box::new(move || {
+            match atomic_save_json(&path, &EfTunerState { target_recall, ef }) {
+                Ok(()) => persisted_ef.store(ef, Ordering::Relaxed),
+                Err(err) => log::warn!("Failed to save tuned ef {}: {err}", path.display()),
+            }
+            Ok(())
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::Builder;
+
+    use super::*;
+
+    fn flush(tuner: &EfTuner) {
+        if let Some(flusher) = tuner.flusher() {
+            flusher().unwrap();
+        }
+    }
+
+    #[test]
+    fn test_ef_tuner() {
+        let dir = Builder::new().prefix("ef_tuner").tempdir().unwrap();
+
+        let tuner = EfTuner::open(dir.path(), 0.9, 100);
+        assert_eq!(tuner.ef(), 100);
+
+        // Low recall increases ef
+        for _ in 0..SAMPLES_PER_ROUND {
+            tuner.record(8, 10);
+        }
+        assert_eq!(tuner.ef(), 150);
+
+        // Recall close to the target keeps ef
+        for _ in 0..SAMPLES_PER_ROUND {
+            tuner.record(93, 100);
+        }
+        assert_eq!(tuner.ef(), 150);
+
+        // Tuned ef is persisted on flush only
+        assert_eq!(EfTuner::open(dir.path(), 0.9, 100).ef(), 100);
+        flush(&tuner);
+        assert!(tuner.flusher().is_none());
+        assert_eq!(EfTuner::open(dir.path(), 0.9, 100).ef(), 150);
+
+        // High recall decreases ef
+        for _ in 0..SAMPLES_PER_ROUND {
+            tuner.record(10, 10);
+        }
+        assert_eq!(tuner.ef(), 135);
+        flush(&tuner);
+        assert_eq!(EfTuner::open(dir.path(), 0.9, 100).ef(), 135);
+
+        // Ef tuned for another target is not used
+        assert_eq!(EfTuner::open(dir.path(), 0.99, 100).ef(), 100);
+    }
+
+    #[test]
+    fn test_ef_tuner_pending_samples() {
+        let dir = Builder::new().prefix("ef_tuner").tempdir().unwrap();
+        let tuner = EfTuner::open(dir.path(), 0.9, 100);
+
+        let query = QueryVector::Nearest(vec![1.0, 0.0].into());
+        let result: Vec<_> = (0..8)
+            .map(|idx| ScoredPointOffset { idx, score: 1.0 })
+            .collect();
+
+        // Only every `SAMPLE_INTERVAL`-th search is copied, and at most `MAX_PENDING_SAMPLES`
+        for _ in 0..SAMPLE_INTERVAL * (MAX_PENDING_SAMPLES + 4) {
+            tuner.sample(&query, 10, &result);
+        }
+        assert_eq!(tuner.pending.lock().len(), MAX_PENDING_SAMPLES);
+
+        // Exact search finds 2 more points, which HNSW search missed
+        tuner.evaluate_pending(|_, top| {
+            Ok((0..top as PointOffsetType)
+                .map(|idx| ScoredPointOffset { idx, score: 1.0 })
+                .collect())
+        });
+        assert!(tuner.pending.lock().is_empty());
+        assert_eq!(tuner.ef(), 150);
+    }
+}
@@ -19,7 +19,9 @@ use zerocopy::IntoBytes;
 use super::shader_builder::ShaderBuilderParameters;
 use crate::common::operation_error::{OperationError, OperationResult, check_process_stopped};
 use crate::data_types::primitive::PrimitiveVectorElement;
-use crate::data_types::vectors::{VectorElementType, VectorElementTypeByte, VectorElementTypeHalf};
+use crate::data_types::vectors::{
+    VectorElementType, VectorElementTypeBf16, VectorElementTypeByte, VectorElementTypeHalf,
+};
 use crate::index::hnsw_index::gpu::GPU_TIMEOUT;
 use crate::types::{Distance, VectorStorageDatatype};
 use crate::vector_storage::quantized::quantized_vectors::{
@@ -95,6 +97,9 @@ impl ShaderBuilderParameters for GpuVectorStorage {
             VectorStorageDatatype::Uint8 => {
                 defines.insert("VECTOR_STORAGE_ELEMENT_UINT8".to_owned(), None);
             }
+            VectorStorageDatatype::Bfloat16 => {
+                unreachable!("bf16 vectors are uploaded to GPU as f32")
+            }
         }
 
         match self.distance {
@@ -421,6 +426,9 @@ impl GpuVectorStorage {
             VectorStorageEnum::DenseMemmapHalf(vector_storage) => {
                 Self::new_dense_f16(device, vector_storage.as_ref(), stopped)
             }
+            VectorStorageEnum::DenseMemmapBf16(vector_storage) => {
+                Self::new_dense_bf16(device, vector_storage.as_ref(), stopped)
+            }
             VectorStorageEnum::DenseAppendableMemmap(vector_storage) => Self::new_dense_f32(
                 device,
                 vector_storage.as_ref(),
@@ -433,6 +441,9 @@ impl GpuVectorStorage {
             VectorStorageEnum::DenseAppendableMemmapHalf(vector_storage) => {
                 Self::new_dense_f16(device, vector_storage.as_ref(), stopped)
             }
+            VectorStorageEnum::DenseAppendableMemmapBf16(vector_storage) => {
+                Self::new_dense_bf16(device, vector_storage.as_ref(), stopped)
+            }
             #[cfg(feature = "rocksdb")]
             VectorStorageEnum::SparseSimple(_) => Err(OperationError::from(
                 gpu::GpuError::NotSupported("Sparse vectors are not supported on GPU".to_string()),
@@ -484,6 +495,9 @@ impl GpuVectorStorage {
             VectorStorageEnum::MultiDenseAppendableMemmapHalf(vector_storage) => {
                 Self::new_multi_f16(device, vector_storage.as_ref(), stopped)
             }
+            VectorStorageEnum::MultiDenseAppendableMemmapBf16(vector_storage) => {
+                Self::new_multi_bf16(device, vector_storage.as_ref(), stopped)
+            }
         }
     }
 
@@ -501,9 +515,9 @@ impl GpuVectorStorage {
                 vector_storage.total_vector_count(),
                 vector_storage.vector_dim(),
                 (0..vector_storage.total_vector_count()).map(|id| {
-                    VectorElementTypeHalf::slice_from_float_cow(Cow::Borrowed(
+                    VectorElementTypeHalf::slice_from_float_cow(
                         vector_storage.get_dense::<Random>(id as PointOffsetType),
-                    ))
+                    )
                 }),
                 None,
                 None,
@@ -529,9 +543,9 @@ impl GpuVectorStorage {
                 vector_storage.total_vector_count(),
                 vector_storage.vector_dim(),
                 (0..vector_storage.total_vector_count()).map(|id| {
-                    VectorElementTypeHalf::slice_to_float_cow(Cow::Borrowed(
+                    VectorElementTypeHalf::slice_to_float_cow(
                         vector_storage.get_dense::<Random>(id as PointOffsetType),
-                    ))
+                    )
                 }),
                 None,
                 None,
@@ -540,6 +554,29 @@ impl GpuVectorStorage {
         }
     }
 
+    /// GPU has no bf16 arithmetic, vectors are uploaded as f32
+    fn new_dense_bf16<TVectorStorage: DenseVectorStorage<VectorElementTypeBf16>>(
+        device: Arc<gpu::Device>,
+        vector_storage: &TVectorStorage,
+        stopped: &AtomicBool,
+    ) -> OperationResult<Self> {
+        Self::new_typed::<VectorElementType>(
+            device,
+            vector_storage.distance(),
+            vector_storage.total_vector_count(),
+            vector_storage.total_vector_count(),
+            vector_storage.vector_dim(),
+            (0..vector_storage.total_vector_count()).map(|id| {
+                VectorElementTypeBf16::slice_to_float_cow(
+                    vector_storage.get_dense::<Random>(id as PointOffsetType),
+                )
+            }),
+            None,
+            None,
+            stopped,
+        )
+    }
+
     fn new_dense<TElement: PrimitiveVectorElement, TVectorStorage: DenseVectorStorage<TElement>>(
         device: Arc<gpu::Device>,
         vector_storage: &TVectorStorage,
@@ -552,7 +589,7 @@ impl GpuVectorStorage {
             vector_storage.total_vector_count(),
             vector_storage.vector_dim(),
             (0..vector_storage.total_vector_count())
-                .map(|id| Cow::Borrowed(vector_storage.get_dense::<Random>(id as PointOffsetType))),
+                .map(|id| vector_storage.get_dense::<Random>(id as PointOffsetType)),
             None,
             None,
             stopped,
@@ -620,6 +657,33 @@ impl GpuVectorStorage {
         }
     }
 
+    /// GPU has no bf16 arithmetic, vectors are uploaded as f32
+    fn new_multi_bf16<TVectorStorage: MultiVectorStorage<VectorElementTypeBf16>>(
+        device: Arc<gpu::Device>,
+        vector_storage: &TVectorStorage,
+        stopped: &AtomicBool,
+    ) -> OperationResult<Self> {
+        Self::new_typed::<VectorElementType>(
+            device.clone(),
+            vector_storage.distance(),
+            (0..vector_storage.total_vector_count())
+                .map(|id| {
+                    vector_storage
+                        .get_multi::<Random>(id as PointOffsetType)
+                        .vectors_count()
+                })
+                .sum(),
+            vector_storage.total_vector_count(),
+            vector_storage.vector_dim(),
+            vector_storage
+                .iterate_inner_vectors()
+                .map(|vector| VectorElementTypeBf16::slice_to_float_cow(Cow::Borrowed(vector))),
+            None,
+            Some(GpuMultivectors::new_multidense(device, vector_storage)?),
+            stopped,
+        )
+    }
+
     fn new_multi<TElement: PrimitiveVectorElement, TVectorStorage: MultiVectorStorage<TElement>>(
         device: Arc<gpu::Device>,
         vector_storage: &TVectorStorage,
@@ -224,6 +224,7 @@ fn test_gpu_vector_storage_bq(
             always_ram: Some(true),
             encoding: Some(encoding),
             query_encoding: None,
+            oversampling: None,
         },
     });
 
@@ -739,6 +740,7 @@ fn test_gpu_vector_storage_impl(
             QuantizedVectorsStorageType::Immutable,
             dir.path(),
             1,
+            None,
             &DEFAULT_STOPPED,
         )
         .unwrap()
@@ -0,0 +1,122 @@
+//! Extension point for offloading HNSW graph construction.
+//!
+//! Linking a new point into the graph consists of two steps, which dominate the build time:
+//! - candidate generation: search for the nearest neighbors of the new point on a level,
+//! - pruning: selection of the links out of the candidates with the "not closer than base"
+//!   heuristic.
+//!
+//! Both steps are performed by a [`GraphBuildBackend`]. [`CpuGraphBuildBackend`] is the default
+//! one, GPU or other accelerator builders may provide their own implementation and plug it into
+//! [`GraphLayersBuilder::set_backend`], keeping the rest of the builder intact.
+
+use std::sync::atomic::AtomicBool;
+
+use common::fixed_length_priority_queue::FixedLengthPriorityQueue;
+use common::types::{PointOffsetType, ScoreType, ScoredPointOffset};
+
+use super::graph_layers::GraphLayersBase as _;
+use super::graph_layers_builder::GraphLayersBuilder;
+use super::links_container::select_with_heuristic;
+use super::point_scorer::FilteredScorer;
+
+/// Maximal number of points passed to a single call of the batched [`BuildScorer`] methods.
+///
+/// Large enough to amortize a round trip to an accelerator, small enough to keep the buffers
+/// on the stack of the caller.
+pub const BUILD_SCORE_BATCH_SIZE: usize = 256;
+
+/// Batched scoring of points, used by [`GraphBuildBackend`] implementations.
+///
+/// Callers pass at most [`BUILD_SCORE_BATCH_SIZE`] points per call.
+pub trait BuildScorer {
+    /// Score the point being inserted against `point_ids`, scores are written into `scores`.
+    fn score_batch(&self, point_ids: &[PointOffsetType], scores: &mut [ScoreType]);
+
+    /// Score `point_id` against each of `others`, scores are written into `scores`.
+    fn score_internal_batch(
+        &self,
+        point_id: PointOffsetType,
+        others: &[PointOffsetType],
+        scores: &mut [ScoreType],
+    );
+}
+
+impl BuildScorer for FilteredScorer<'_> {
+    fn score_batch(&self, point_ids: &[PointOffsetType], scores: &mut [ScoreType]) {
+        debug_assert!(point_ids.len() <= BUILD_SCORE_BATCH_SIZE);
+        self.raw_scorer().score_points(point_ids, scores);
+    }
+
+    fn score_internal_batch(
+        &self,
+        point_id: PointOffsetType,
+        others: &[PointOffsetType],
+        scores: &mut [ScoreType],
+    ) {
+        debug_assert!(others.len() <= BUILD_SCORE_BATCH_SIZE);
+        for (&other, score) in others.iter().zip(scores) {
+            *score = self.score_internal(point_id, other);
+        }
+    }
+}
+
+/// Candidate generation and pruning of HNSW graph construction.
+pub trait GraphBuildBackend: Send + Sync {
+    /// Find up to `ef` candidate neighbors of the point being inserted on `level`,
+    /// starting from `level_entry`.
+    fn search_candidates(
+        &self,
+        graph: &GraphLayersBuilder,
+        level: usize,
+        ef: usize,
+        level_entry: ScoredPointOffset,
+        scorer: &mut FilteredScorer,
+    ) -> FixedLengthPriorityQueue<ScoredPointOffset>;
+
+    /// Select up to `level_m` links of the point being inserted out of `candidates`, sorted by
+    /// descending score.
+    ///
+    /// Selected links are appended to `selected`, which is empty. It is the links list of the
+    /// point, so no allocation is needed on the hot path.
+    fn select_links(
+        &self,
+        candidates: &[ScoredPointOffset],
+        level_m: usize,
+        scorer: &FilteredScorer,
+        selected: &mut Vec<PointOffsetType>,
+    );
+}
+
+/// Default [`GraphBuildBackend`]: greedy search on the graph and heuristic pruning on CPU.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct CpuGraphBuildBackend;
+
+impl GraphBuildBackend for CpuGraphBuildBackend {
+    fn search_candidates(
+        &self,
+        graph: &GraphLayersBuilder,
+        level: usize,
+        ef: usize,
+        level_entry: ScoredPointOffset,
+        scorer: &mut FilteredScorer,
+    ) -> FixedLengthPriorityQueue<ScoredPointOffset> {
+        graph
+            .search_on_level(level_entry, level, ef, scorer, &AtomicBool::new(false))
+            .unwrap()
+    }
+
+    fn select_links(
+        &self,
+        candidates: &[ScoredPointOffset],
+        level_m: usize,
+        scorer: &FilteredScorer,
+        selected: &mut Vec<PointOffsetType>,
+    ) {
+        select_with_heuristic(
+            candidates.iter().copied(),
+            level_m,
+            |a, b| scorer.score_internal(a, b),
+            selected,
+        );
+    }
+}
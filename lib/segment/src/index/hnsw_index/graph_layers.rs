@@ -29,26 +29,31 @@ use std::cmp::max;
 use std::ops::ControlFlow;
 use std::path::{Path, PathBuf};
 use std::sync::atomic::AtomicBool;
+use std::thread::JoinHandle;
 
 use common::fixed_length_priority_queue::FixedLengthPriorityQueue;
-use common::fs::{atomic_save, read_bin};
+use common::fs::read_bin;
+use common::mmap::{Advice, encryption};
+use common::progress_tracker::ProgressTracker;
 use common::types::{PointOffsetType, ScoredPointOffset};
-use fs_err as fs;
 use itertools::Itertools;
 use serde::{Deserialize, Serialize};
 
 use super::HnswM;
 use super::entry_points::{EntryPoint, EntryPoints};
-use super::graph_links::{GraphLinks, GraphLinksFormat};
+use super::graph_links::{
+    GraphConnectivityReport, GraphLinks, GraphLinksDelta, GraphLinksFormat, LinksPrefetchPool,
+    LinksPrefetcher,
+};
 use crate::common::operation_error::{
     CancellableResult, OperationError, OperationResult, check_process_stopped,
 };
 use crate::common::utils::rev_range;
-use crate::index::hnsw_index::graph_links::{GraphLinksFormatParam, serialize_graph_links};
 use crate::index::hnsw_index::point_scorer::{FilteredBytesScorer, FilteredScorer, ScorerFilters};
 use crate::index::hnsw_index::search_context::SearchContext;
 use crate::index::visited_pool::{VisitedListHandle, VisitedPool};
 use crate::vector_storage::RawScorer;
+use crate::vector_storage::common::get_async_scorer;
 use crate::vector_storage::query_scorer::QueryScorerBytes;
 
 pub type LinkContainer = Vec<PointOffsetType>;
@@ -59,6 +64,7 @@ pub const HNSW_GRAPH_FILE: &str = "graph.bin";
 pub const HNSW_LINKS_FILE: &str = "links.bin";
 pub const COMPRESSED_HNSW_LINKS_FILE: &str = "links_compressed.bin";
 pub const COMPRESSED_WITH_VECTORS_HNSW_LINKS_FILE: &str = "links_comp_vec.bin";
+pub const HNSW_LINKS_DELTA_FILE: &str = "links_delta.bin";
 
 /// Contents of the `graph.bin` file.
 #[derive(Deserialize, Serialize, Debug)]
@@ -75,6 +81,8 @@ pub struct GraphLayers {
     pub(super) links: GraphLinks,
     pub(super) entry_points: EntryPoints,
     pub(super) visited_pool: VisitedPool,
+    /// Set if links are read from disk and io_uring is available
+    pub(super) links_prefetch: Option<LinksPrefetchPool>,
 }
 
 #[derive(Debug, Clone, Copy, Eq, PartialEq)]
@@ -102,6 +110,11 @@ pub trait GraphLayersBase {
     /// Get M based on current level
     fn get_m(&self, level: usize) -> usize;
 
+    /// Prefetcher of links read from disk, `None` if links are not prefetched.
+    fn get_links_prefetcher(&self) -> Option<LinksPrefetcher<'_>> {
+        None
+    }
+
     /// Beam search for closest points within a single graph layer.
     ///
     /// See [module docs](self) for comparison with other search functions.
@@ -121,6 +134,7 @@ pub trait GraphLayersBase {
 
         let limit = self.get_m(level);
         let mut points_ids: Vec<PointOffsetType> = Vec::with_capacity(2 * limit);
+        let mut links_prefetcher = self.get_links_prefetcher();
 
         while let Some(candidate) = search_context.candidates.pop() {
             check_process_stopped(is_stopped)?;
@@ -136,6 +150,14 @@ pub trait GraphLayersBase {
                 }
             });
 
+            // Links of the best remaining candidate are likely to be read next, fetch them
+            // from disk while scoring
+            if let (Some(prefetcher), Some(next)) =
+                (links_prefetcher.as_mut(), search_context.candidates.peek())
+            {
+                prefetcher.prefetch(next.idx, level);
+            }
+
             points_scorer
                 .score_points(&mut points_ids, limit)
                 .for_each(|score_point| {
@@ -351,6 +373,7 @@ pub trait GraphLayersWithVectors: GraphLayersBase {
 
         let limit = self.get_m(level);
         let mut points: Vec<(PointOffsetType, &[u8])> = Vec::with_capacity(2 * limit);
+        let mut links_prefetcher = self.get_links_prefetcher();
 
         while let Some(candidate) = links_search_context.candidates.pop() {
             check_process_stopped(is_stopped)?;
@@ -376,6 +399,14 @@ pub trait GraphLayersWithVectors: GraphLayersBase {
                 score: base_scorer.score_bytes(base_vector),
             });
 
+            // See `search_on_level`
+            if let (Some(prefetcher), Some(next)) = (
+                links_prefetcher.as_mut(),
+                links_search_context.candidates.peek(),
+            ) {
+                prefetcher.prefetch(next.idx, level);
+            }
+
             links_scorer
                 .score_points(&mut points, limit)
                 .for_each(|score_point| {
@@ -478,6 +509,12 @@ impl GraphLayersBase for GraphLayers {
     fn get_m(&self, level: usize) -> usize {
         self.hnsw_m.level_m(level)
     }
+
+    fn get_links_prefetcher(&self) -> Option<LinksPrefetcher<'_>> {
+        self.links_prefetch
+            .as_ref()
+            .and_then(|pool| pool.get(&self.links))
+    }
 }
 
 impl GraphLayersWithVectors for GraphLayers {
@@ -609,35 +646,86 @@ impl GraphLayers {
         }
     }
 
+    pub fn get_links_delta_path(path: &Path) -> PathBuf {
+        path.join(HNSW_LINKS_DELTA_FILE)
+    }
+
     pub fn files(&self, path: &Path) -> Vec<PathBuf> {
-        vec![
+        let mut files = vec![
             GraphLayers::get_path(path),
             GraphLayers::get_links_path(path, self.links.format()),
-        ]
+        ];
+        let delta_path = GraphLayers::get_links_delta_path(path);
+        if delta_path.exists() {
+            files.push(delta_path);
+        }
+        files
     }
 
     pub fn num_points(&self) -> usize {
         self.links.num_points()
     }
+
+    /// Apply links appended to the delta file, see [`GraphLinksDelta`].
+    pub fn reload_links_delta(&mut self, dir: &Path) -> OperationResult<()> {
+        let delta = GraphLinksDelta::load(&Self::get_links_delta_path(dir))?.unwrap_or_default();
+        self.links.set_delta(delta)
+    }
+
+    /// Check that points, for which `is_expected` returns `true`, are reachable from the entry
+    /// point used by searches, see [`GraphLinks::verify_connectivity`].
+    pub fn verify_connectivity(
+        &self,
+        is_expected: impl Fn(PointOffsetType) -> bool,
+    ) -> GraphConnectivityReport {
+        let entry_point = self
+            .entry_points
+            .get_entry_point(&is_expected)
+            .map(|entry_point| entry_point.point_id);
+        self.links.verify_connectivity(entry_point, is_expected)
+    }
 }
 
 impl GraphLayers {
-    pub fn load(dir: &Path, on_disk: bool, compress: bool) -> OperationResult<Self> {
+    /// Load the graph from `dir`.
+    ///
+    /// Links are read into the page cache on load if `populate` is set.
+    pub fn load(dir: &Path, on_disk: bool, populate: bool) -> OperationResult<Self> {
         let graph_data: GraphLayerData = read_bin(&GraphLayers::get_path(dir))?;
 
-        if compress {
-            Self::convert_to_compressed(dir, HnswM::new(graph_data.m, graph_data.m0))?;
-        }
+        let links = Self::load_links(dir, populate)?;
+
+        // Like vectors, links on disk are prefetched if async IO is enabled, unless they are
+        // encrypted and read from RAM
+        let links_path = Self::get_links_path(dir, links.format());
+        let links_prefetch =
+            if on_disk && get_async_scorer() && !encryption::is_encrypted_file(&links_path)? {
+                match LinksPrefetchPool::open(&links_path) {
+                    Ok(pool) => Some(pool),
+                    Err(err) => {
+                        log::debug!("HNSW links prefetch is disabled: {err}");
+                        None
+                    }
+                }
+            } else {
+                None
+            };
 
         Ok(Self {
             hnsw_m: HnswM::new(graph_data.m, graph_data.m0),
-            links: Self::load_links(dir, on_disk)?,
+            links,
             entry_points: graph_data.entry_points.into_owned(),
             visited_pool: VisitedPool::new(),
+            links_prefetch,
         })
     }
 
-    fn load_links(dir: &Path, on_disk: bool) -> OperationResult<GraphLinks> {
+    /// Validate the links file in `dir` like [`Self::load`], without loading the graph.
+    pub fn validate_links(dir: &Path) -> OperationResult<()> {
+        Self::load_links(dir, false).map(|_| ())
+    }
+
+    fn load_links(dir: &Path, populate: bool) -> OperationResult<GraphLinks> {
         for format in [
             GraphLinksFormat::CompressedWithVectors,
             GraphLinksFormat::Compressed,
@@ -645,70 +733,65 @@ impl GraphLayers {
         ] {
             let path = GraphLayers::get_links_path(dir, format);
             if path.exists() {
-                return GraphLinks::load_from_file(&path, on_disk, format);
+                let mut links = GraphLinks::load_from_file(&path, !populate, format)?;
+                if let Some(delta) = GraphLinksDelta::load(&Self::get_links_delta_path(dir))? {
+                    links.set_delta(delta)?;
+                }
+                return Ok(links);
             }
         }
         Err(OperationError::service_error("No links file found"))
     }
 
-    /// Convert the "plain" format into the "compressed" format.
-    /// Note: conversion into the "compressed with vectors" format is not
-    /// supported at the moment, though it is possible to implement.
-    /// As far as [`super::hnsw::LINK_COMPRESSION_CONVERT_EXISTING`] is false,
-    /// this code is not used in production.
-    fn convert_to_compressed(dir: &Path, hnsw_m: HnswM) -> OperationResult<()> {
-        let plain_path = Self::get_links_path(dir, GraphLinksFormat::Plain);
-        let compressed_path = Self::get_links_path(dir, GraphLinksFormat::Compressed);
-        let compressed_with_vectors_path =
-            Self::get_links_path(dir, GraphLinksFormat::CompressedWithVectors);
-
-        if compressed_path.exists() || compressed_with_vectors_path.exists() {
-            return Ok(());
-        }
-
-        let start = std::time::Instant::now();
-
-        let links = GraphLinks::load_from_file(&plain_path, true, GraphLinksFormat::Plain)?;
-        let original_size = fs::metadata(&plain_path)?.len();
-        atomic_save(&compressed_path, |writer| {
-            let edges = links.to_edges();
-            serialize_graph_links(edges, GraphLinksFormatParam::Compressed, hnsw_m, writer)
-        })?;
-        let new_size = fs::metadata(&compressed_path)?.len();
-
-        // Remove the original file
-        fs::remove_file(&plain_path)?;
-
-        log::debug!(
-            "Compressed HNSW graph links in {:.1?}: {:.1}MB -> {:.1}MB ({:.1}%)",
-            start.elapsed(),
-            original_size as f64 / 1024.0 / 1024.0,
-            new_size as f64 / 1024.0 / 1024.0,
-            new_size as f64 / original_size as f64 * 100.0,
-        );
-
-        Ok(())
-    }
-
     #[cfg(feature = "testing")]
     pub fn compress_ram(&mut self) {
         assert_eq!(self.links.format(), GraphLinksFormat::Plain);
-        let dummy =
-            GraphLinks::new_from_edges(Vec::new(), GraphLinksFormatParam::Plain, HnswM::new2(0))
-                .unwrap();
+        let dummy = GraphLinks::new_from_edges(
+            Vec::new(),
+            super::graph_links::GraphLinksFormatParam::Plain,
+            HnswM::new2(0),
+        )
+        .unwrap();
         let links = std::mem::replace(&mut self.links, dummy);
         self.links = GraphLinks::new_from_edges(
             links.to_edges(),
-            GraphLinksFormatParam::Compressed,
+            super::graph_links::GraphLinksFormatParam::Compressed,
             self.hnsw_m,
         )
         .unwrap();
     }
 
+    pub fn links_format(&self) -> GraphLinksFormat {
+        self.links.format()
+    }
+
     pub fn populate(&self) -> OperationResult<()> {
         self.links.populate()?;
         Ok(())
     }
+
+    /// See [`GraphLinks::lock_in_ram`].
+    pub fn lock_in_ram(&self) -> OperationResult<()> {
+        self.links.lock_in_ram()
+    }
+
+    /// See [`GraphLinks::advise`].
+    pub fn advise_links(&self, advice: Advice) -> OperationResult<()> {
+        self.links.advise(advice)
+    }
+
+    /// See [`GraphLinks::request_hugepages`].
+    pub fn request_links_hugepages(&self) -> OperationResult<()> {
+        self.links.request_hugepages()
+    }
+
+    /// See [`GraphLinks::populate_prioritized`].
+    pub fn populate_prioritized(
+        &self,
+        progress: ProgressTracker,
+    ) -> OperationResult<Option<JoinHandle<()>>> {
+        self.links.populate_prioritized(progress)
+    }
 }
 
 #[cfg(test)]
@@ -782,6 +865,7 @@ mod tests {
             links: GraphLinks::new_from_edges(graph_links.clone(), format_param, hnsw_m).unwrap(),
             entry_points: EntryPoints::new(entry_points_num),
             visited_pool: VisitedPool::new(),
+            links_prefetch: None,
         };
 
         let linking_idx: PointOffsetType = 7;
@@ -813,12 +897,10 @@ mod tests {
     }
 
     #[rstest]
-    #[case::uncompressed((GraphLinksFormat::Plain, false))]
-    #[case::converted((GraphLinksFormat::Plain, true))]
-    #[case::compressed((GraphLinksFormat::Compressed, false))]
-    #[case::recompressed((GraphLinksFormat::Compressed, true))]
-    #[case::compressed_with_vectors((GraphLinksFormat::CompressedWithVectors, false))]
-    fn test_save_and_load(#[case] (initial_format, compress): (GraphLinksFormat, bool)) {
+    #[case::uncompressed(GraphLinksFormat::Plain)]
+    #[case::compressed(GraphLinksFormat::Compressed)]
+    #[case::compressed_with_vectors(GraphLinksFormat::CompressedWithVectors)]
+    fn test_save_and_load(#[case] initial_format: GraphLinksFormat) {
         let distance = Distance::Cosine;
         let num_vectors = 100;
         let dim = 8;
@@ -851,12 +933,8 @@ mod tests {
         let res1 = search_in_graph(&query, top, &vector_holder, &graph1);
         drop(graph1);
 
-        let graph2 = GraphLayers::load(dir.path(), false, compress).unwrap();
-        if compress {
-            assert_eq!(graph2.links.format(), GraphLinksFormat::Compressed);
-        } else {
-            assert_eq!(graph2.links.format(), initial_format);
-        }
+        let graph2 = GraphLayers::load(dir.path(), false, true).unwrap();
+        assert_eq!(graph2.links.format(), initial_format);
         let res2 = search_in_graph(&query, top, &vector_holder, &graph2);
 
         assert_eq!(res1, res2)
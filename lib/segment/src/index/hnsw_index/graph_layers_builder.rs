@@ -3,18 +3,22 @@ use std::cmp::{max, min};
 use std::io::Write;
 use std::ops::ControlFlow;
 use std::path::Path;
+use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, AtomicUsize};
 
 use bitvec::prelude::BitVec;
 use common::ext::BitSliceExt;
 use common::fixed_length_priority_queue::FixedLengthPriorityQueue;
 use common::fs::{atomic_save, atomic_save_bin};
+use common::mmap::encryption::{self, EncryptingWriter};
 use common::types::{PointOffsetType, ScoredPointOffset};
 use parking_lot::{Mutex, MutexGuard, RwLock};
 use rand::Rng;
 use rand::distr::Uniform;
 
 use super::HnswM;
+use super::build_checkpoint::BuildCheckpoint;
+use super::graph_build_backend::{CpuGraphBuildBackend, GraphBuildBackend};
 use super::graph_layers::GraphLayerData;
 use super::graph_links::{GraphLinks, GraphLinksFormatParam};
 use super::links_container::{ItemsBuffer, LinksContainer};
@@ -48,6 +52,9 @@ pub struct GraphLayersBuilder {
 
     // List of bool flags, which defines if the point is already indexed or not
     ready_list: BitVec<AtomicUsize>,
+
+    /// Candidate generation and pruning, see [`GraphBuildBackend`]
+    backend: Arc<dyn GraphBuildBackend>,
 }
 
 impl GraphLayersBase for GraphLayersBuilder {
@@ -212,7 +219,8 @@ impl GraphLayersBuilder {
 
         let edges = Self::links_layers_to_edges(self.links_layers);
         let links;
-        if on_disk {
+        // Encrypted links are decrypted into RAM on load, so they can as well be built in RAM
+        if on_disk && !encryption::is_enabled() {
             // Save memory by serializing directly to disk, then re-loading as mmap.
             atomic_save(&links_path, |writer| {
                 serialize_graph_links(edges, format_param, self.hnsw_m, writer)
@@ -220,8 +228,14 @@ impl GraphLayersBuilder {
             links = GraphLinks::load_from_file(&links_path, true, format_param.as_format())?;
         } else {
             // Since we'll keep it in the RAM anyway, we can afford to build in the RAM too.
+            // Links are encrypted while written, if enabled.
             links = GraphLinks::new_from_edges(edges, format_param, self.hnsw_m)?;
-            atomic_save(&links_path, |writer| writer.write_all(links.as_bytes()))?;
+            atomic_save(&links_path, |writer| -> std::io::Result<()> {
+                let mut writer = EncryptingWriter::new(writer)?;
+                writer.write_all(links.as_bytes())?;
+                writer.finish()?;
+                Ok(())
+            })?;
         }
 
         let entry_points = self.entry_points.into_inner();
@@ -239,6 +253,7 @@ impl GraphLayersBuilder {
             links,
             entry_points,
             visited_pool: self.visited_pool,
+            links_prefetch: None,
         })
     }
 
@@ -250,6 +265,7 @@ impl GraphLayersBuilder {
             links: GraphLinks::new_from_edges(edges, format_param, self.hnsw_m).unwrap(),
             entry_points: self.entry_points.into_inner(),
             visited_pool: self.visited_pool,
+            links_prefetch: None,
         }
     }
 
@@ -312,9 +328,15 @@ impl GraphLayersBuilder {
             entry_points: Mutex::new(EntryPoints::new(entry_points_num)),
             visited_pool: VisitedPool::new(),
             ready_list,
+            backend: Arc::new(CpuGraphBuildBackend),
         }
     }
 
+    /// Replace the default [`CpuGraphBuildBackend`], e.g. to offload the build to an accelerator.
+    pub fn set_backend(&mut self, backend: Arc<dyn GraphBuildBackend>) {
+        self.backend = backend;
+    }
+
     pub fn new(
         num_vectors: usize, // Initial number of points in index
         hnsw_m: HnswM,
@@ -384,6 +406,41 @@ impl GraphLayersBuilder {
         picked_level.round() as usize
     }
 
+    pub(super) fn is_ready(&self, point_id: PointOffsetType) -> bool {
+        self.ready_list[point_id as usize]
+    }
+
+    /// Snapshot levels of all points and links of points linked so far.
+    ///
+    /// Must not be called concurrently with linking of new points.
+    pub(super) fn build_checkpoint(&self) -> BuildCheckpoint {
+        let levels = self
+            .links_layers
+            .iter()
+            .map(|layers| layers.len().saturating_sub(1) as u8)
+            .collect();
+        let linked = self
+            .ready_list
+            .iter_ones()
+            .map(|point_id| {
+                let links = self.links_layers[point_id]
+                    .iter()
+                    .map(|links| links.read().links().to_vec())
+                    .collect();
+                (point_id as PointOffsetType, links)
+            })
+            .collect();
+        BuildCheckpoint::new(self.hnsw_m, self.ef_construct, levels, linked)
+    }
+
+    /// Add points linked before the checkpoint was made.
+    /// Levels of all points are expected to be restored with [`Self::set_levels`].
+    pub(super) fn restore_build_checkpoint(&self, checkpoint: BuildCheckpoint) {
+        for (point_id, links_by_level) in checkpoint.linked {
+            self.add_new_point(point_id, links_by_level);
+        }
+    }
+
     pub(crate) fn get_point_level(&self, point_id: PointOffsetType) -> usize {
         self.links_layers[point_id as usize].len() - 1
     }
@@ -495,15 +552,13 @@ impl GraphLayersBuilder {
         points_scorer: &mut FilteredScorer,
         mut level_entry: ScoredPointOffset,
     ) -> ScoredPointOffset {
-        let nearest = self
-            .search_on_level(
-                level_entry,
-                curr_level,
-                self.ef_construct,
-                points_scorer,
-                &AtomicBool::new(false),
-            )
-            .unwrap();
+        let nearest = self.backend.search_candidates(
+            self,
+            curr_level,
+            self.ef_construct,
+            level_entry,
+            points_scorer,
+        );
 
         if let Some(the_nearest) = nearest.iter_unsorted().max() {
             level_entry = *the_nearest;
@@ -529,9 +584,12 @@ impl GraphLayersBuilder {
         let scorer = |a, b| points_scorer.score_internal(a, b);
 
         let selected_nearest = {
-            let iter = nearest.into_iter_sorted();
+            let candidates = nearest.into_sorted_vec();
             let mut existing_links = self.links_layers[point_id as usize][curr_level].write();
-            existing_links.fill_from_sorted_with_heuristic(iter, level_m, scorer);
+            existing_links.fill_with_heuristic(|selected| {
+                self.backend
+                    .select_links(&candidates, level_m, points_scorer, selected);
+            });
             existing_links.links().to_vec()
         };
 
@@ -592,6 +650,8 @@ impl GraphLayersBuilder {
 
 #[cfg(test)]
 mod tests {
+    use std::sync::atomic::Ordering;
+
     use common::fixed_length_priority_queue::FixedLengthPriorityQueue;
     use itertools::Itertools;
     use rand::SeedableRng;
@@ -600,6 +660,7 @@ mod tests {
 
     use super::*;
     use crate::fixtures::index_fixtures::{TestRawScorerProducer, random_vector};
+    use crate::index::hnsw_index::graph_build_backend::{BUILD_SCORE_BATCH_SIZE, BuildScorer as _};
     use crate::index::hnsw_index::graph_links::{GraphLinksFormat, normalize_links};
     use crate::index::hnsw_index::tests::create_graph_layer_fixture;
     use crate::types::Distance;
@@ -942,4 +1003,177 @@ mod tests {
         let avg_connectivity = total_edges as f64 / NUM_VECTORS as f64;
         eprintln!("avg_connectivity = {avg_connectivity:#?}");
     }
+
+    #[test]
+    fn test_build_checkpoint_resume() {
+        let num_vectors = 500;
+        let linked_count = 300;
+        let ef_construct = 16;
+        let mut rng = StdRng::seed_from_u64(42);
+
+        let vector_holder =
+            TestRawScorerProducer::new(8, Distance::Cosine, num_vectors, false, &mut rng);
+
+        let mut builder =
+            GraphLayersBuilder::new(num_vectors, HnswM::new2(M), ef_construct, 10, true);
+        for idx in 0..num_vectors as PointOffsetType {
+            let level = builder.get_random_layer(&mut rng);
+            builder.set_levels(idx, level);
+        }
+        for idx in 0..linked_count as PointOffsetType {
+            builder.link_new_point(idx, vector_holder.internal_scorer(idx));
+        }
+
+        let dir = tempfile::Builder::new()
+            .prefix("build_checkpoint")
+            .tempdir()
+            .unwrap();
+        let path = BuildCheckpoint::get_path(dir.path(), 0);
+        builder.build_checkpoint().save(&path).unwrap();
+
+        // Checkpoint of a build with other parameters is ignored
+        let deleted = BitVec::repeat(false, num_vectors);
+        let other = BuildCheckpoint::load(
+            &path,
+            HnswM::new2(M * 2),
+            ef_construct,
+            num_vectors,
+            &deleted,
+        );
+        assert!(other.is_none());
+
+        let checkpoint =
+            BuildCheckpoint::load(&path, HnswM::new2(M), ef_construct, num_vectors, &deleted)
+                .unwrap();
+        assert_eq!(checkpoint.linked.len(), linked_count);
+
+        let mut resumed =
+            GraphLayersBuilder::new(num_vectors, HnswM::new2(M), ef_construct, 10, true);
+        for (idx, level) in checkpoint.levels.iter().enumerate() {
+            resumed.set_levels(idx as PointOffsetType, usize::from(*level));
+        }
+        resumed.restore_build_checkpoint(checkpoint);
+
+        for idx in 0..num_vectors {
+            assert_eq!(resumed.is_ready(idx as PointOffsetType), idx < linked_count);
+            assert_eq!(
+                resumed.links_layers[idx].len(),
+                builder.links_layers[idx].len()
+            );
+            for (resumed_links, links) in resumed.links_layers[idx]
+                .iter()
+                .zip(&builder.links_layers[idx])
+            {
+                assert_eq!(resumed_links.read().links(), links.read().links());
+            }
+        }
+
+        for idx in linked_count as PointOffsetType..num_vectors as PointOffsetType {
+            resumed.link_new_point(idx, vector_holder.internal_scorer(idx));
+        }
+        let entry = resumed
+            .entry_points
+            .lock()
+            .get_entry_point(|_| true)
+            .unwrap();
+        assert_eq!(
+            entry.level + 1,
+            resumed.links_layers.iter().map(Vec::len).max().unwrap()
+        );
+
+        BuildCheckpoint::remove(&path).unwrap();
+        assert!(!path.exists());
+    }
+
+    /// Backend, which scores all linked points instead of searching the graph
+    #[derive(Default)]
+    struct ExactBuildBackend {
+        batches: AtomicUsize,
+    }
+
+    impl GraphBuildBackend for ExactBuildBackend {
+        fn search_candidates(
+            &self,
+            graph: &GraphLayersBuilder,
+            level: usize,
+            ef: usize,
+            _level_entry: ScoredPointOffset,
+            scorer: &mut FilteredScorer,
+        ) -> FixedLengthPriorityQueue<ScoredPointOffset> {
+            let candidates = (0..graph.num_points() as PointOffsetType)
+                .filter(|&idx| {
+                    graph.is_ready(idx)
+                        && graph.get_point_level(idx) >= level
+                        && scorer.filters().check_vector(idx)
+                })
+                .collect_vec();
+
+            let mut nearest = FixedLengthPriorityQueue::new(ef);
+            let mut scores = [0.0; BUILD_SCORE_BATCH_SIZE];
+            for batch in candidates.chunks(BUILD_SCORE_BATCH_SIZE) {
+                self.batches.fetch_add(1, Ordering::Relaxed);
+                scorer.score_batch(batch, &mut scores[..batch.len()]);
+                for (&idx, &score) in batch.iter().zip(&scores) {
+                    nearest.push(ScoredPointOffset { idx, score });
+                }
+            }
+            nearest
+        }
+
+        fn select_links(
+            &self,
+            candidates: &[ScoredPointOffset],
+            level_m: usize,
+            scorer: &FilteredScorer,
+            selected: &mut Vec<PointOffsetType>,
+        ) {
+            CpuGraphBuildBackend.select_links(candidates, level_m, scorer, selected);
+        }
+    }
+
+    #[test]
+    fn test_custom_build_backend() {
+        let num_vectors = 1000;
+        let dim = 8;
+        let mut rng = StdRng::seed_from_u64(42);
+
+        let vector_holder =
+            TestRawScorerProducer::new(dim, Distance::Cosine, num_vectors, false, &mut rng);
+        let backend = Arc::new(ExactBuildBackend::default());
+        let mut graph_layers_builder =
+            GraphLayersBuilder::new(num_vectors, HnswM::new2(M), 16, 10, true);
+        graph_layers_builder.set_backend(backend.clone());
+
+        for idx in 0..(num_vectors as PointOffsetType) {
+            let level = graph_layers_builder.get_random_layer(&mut rng);
+            graph_layers_builder.set_levels(idx, level);
+            graph_layers_builder.link_new_point(idx, vector_holder.internal_scorer(idx));
+        }
+        assert!(backend.batches.load(Ordering::Relaxed) > 0);
+
+        let top = 5;
+        let query = random_vector(&mut rng, dim);
+        let scorer = vector_holder.scorer(query.clone());
+        let mut reference_top = FixedLengthPriorityQueue::new(top);
+        for idx in 0..num_vectors as PointOffsetType {
+            let score = scorer.score_point(idx);
+            reference_top.push(ScoredPointOffset { idx, score });
+        }
+
+        let graph = graph_layers_builder.into_graph_layers_ram(
+            GraphLinksFormat::Plain
+                .with_param_for_tests(vector_holder.graph_links_vectors().as_ref()),
+        );
+        let graph_search = graph
+            .search(
+                top,
+                16,
+                SearchAlgorithm::Hnsw,
+                vector_holder.scorer(query),
+                None,
+                &DEFAULT_STOPPED,
+            )
+            .unwrap();
+        assert_eq!(reference_top.into_sorted_vec(), graph_search);
+    }
 }
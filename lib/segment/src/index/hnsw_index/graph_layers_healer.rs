@@ -9,6 +9,7 @@ use crate::common::operation_error::OperationResult;
 use crate::index::hnsw_index::HnswM;
 use crate::index::hnsw_index::graph_layers::GraphLayers;
 use crate::index::hnsw_index::graph_layers_builder::{GraphLayersBuilder, LockedLayersContainer};
+use crate::index::hnsw_index::graph_links::GraphLinksDeltaWriter;
 use crate::index::hnsw_index::links_container::{ItemsBuffer, LinksContainer};
 use crate::index::visited_pool::VisitedPool;
 use crate::vector_storage::quantized::quantized_vectors::QuantizedVectors;
@@ -55,6 +56,11 @@ impl<'a> GraphLayersHealer<'a> {
         }
     }
 
+    /// Number of links lists, which point to deleted points.
+    pub fn to_heal_count(&self) -> usize {
+        self.to_heal.len()
+    }
+
     fn point_deleted(&self, point: PointOffsetType) -> bool {
         self.old_to_new[point as usize].is_none()
     }
@@ -253,4 +259,40 @@ impl<'a> GraphLayersHealer<'a> {
             builder.add_new_point(new_offset, links_by_level);
         }
     }
+
+    /// Append healed links into the links delta of the same graph.
+    ///
+    /// Unlike [`Self::save_into_builder`], point offsets are not changed, so
+    /// `old_to_new` is expected to map non-deleted points onto themselves.
+    /// Only changed links are written.
+    ///
+    /// Returns the number of written links lists.
+    pub fn save_into_delta(
+        self,
+        graph_layers: &GraphLayers,
+        writer: &mut GraphLinksDeltaWriter,
+    ) -> OperationResult<usize> {
+        let mut written = 0;
+        for (offset, layers) in self.links_layers.into_iter().enumerate() {
+            if self.old_to_new[offset].is_none() {
+                continue;
+            }
+            let offset = offset as PointOffsetType;
+
+            for (level, layer) in layers.into_iter().enumerate() {
+                let links = layer.into_inner().into_vec();
+                let level_m = graph_layers.hnsw_m.level_m(level);
+                let unchanged = graph_layers
+                    .links
+                    .links(offset, level)
+                    .take(level_m)
+                    .eq(links.iter().copied());
+                if !unchanged {
+                    writer.append(offset, level, &links)?;
+                    written += 1;
+                }
+            }
+        }
+        Ok(written)
+    }
 }
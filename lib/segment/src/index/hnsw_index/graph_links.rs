@@ -1,21 +1,58 @@
 use std::alloc::Layout;
+use std::borrow::Cow;
+use std::hash::Hasher as _;
 use std::io::Cursor;
+use std::ops::Range;
 use std::path::Path;
 use std::sync::Arc;
+use std::sync::atomic::Ordering;
+use std::thread::{self, JoinHandle};
 
+use common::mmap::advice::{self, populate_region};
 use common::mmap::{Advice, AdviceSetting, Madviseable, open_read_mmap};
+use common::progress_tracker::ProgressTracker;
 use common::types::PointOffsetType;
+use half::f16;
 use memmap2::Mmap;
+use seahash::SeaHasher;
+use zerocopy::FromBytes as _;
 
 use crate::common::operation_error::{OperationError, OperationResult};
 use crate::index::hnsw_index::HnswM;
+use crate::telemetry::{HnswLevelLinksTelemetry, HnswLinksTelemetry};
+use crate::types::VectorStorageDatatype;
 use crate::vector_storage::quantized::quantized_vectors::QuantizedVectors;
-use crate::vector_storage::{Sequential, VectorStorageEnum};
+use crate::vector_storage::{Sequential, VectorStorage as _, VectorStorageEnum};
 
+mod connectivity;
+mod delta;
+mod export;
 mod header;
+mod prefetch;
 mod serializer;
+#[cfg(all(
+    target_os = "linux",
+    any(target_arch = "x86_64", target_arch = "aarch64")
+))]
+mod uring_prefetch;
+#[cfg(not(all(
+    target_os = "linux",
+    any(target_arch = "x86_64", target_arch = "aarch64")
+)))]
+mod uring_prefetch_mock;
 mod view;
 
+/// Number of quantized vectors hashed by [`quantization_hash`].
+const QUANTIZATION_HASH_SAMPLE_SIZE: usize = 64;
+
+/// Level 0 links are populated by [`GraphLinks::populate_prioritized`] in chunks of this size,
+/// so the progress is updated regularly.
+const POPULATE_CHUNK_SIZE: usize = 16 * 1024 * 1024;
+
+pub use connectivity::GraphConnectivityReport;
+pub use delta::{GraphLinksDelta, GraphLinksDeltaWriter};
+pub use export::GraphExportFormat;
+pub use prefetch::{LinksPrefetchPool, LinksPrefetcher};
 pub use serializer::serialize_graph_links;
 pub use view::LinksIterator;
 use view::{CompressionInfo, GraphLinksView, LinksWithVectorsIterator};
@@ -59,13 +96,19 @@ pub enum GraphLinksFormat {
     CompressedWithVectors,
 }
 
+/// Newest links format, header versions differ in the lowest byte only.
+pub(crate) const LINKS_FORMAT_VERSION: u32 =
+    (header::HEADER_VERSION_COMPRESSED_WITH_DEDUP_VECTORS & 0xFF) as u32;
+
 /// Current persisted-format versions and legacy decode fallback counters for graph links.
 #[derive(Debug, Clone, Copy, Default)]
 pub struct GraphLinksCompatibilityTelemetry {
     pub plain_version: u64,
     pub compressed_version: u64,
     pub compressed_legacy_version: u64,
+    pub compressed_varint_version: u64,
     pub compressed_with_vectors_version: u64,
+    pub compressed_with_dedup_vectors_version: u64,
     pub compressed_with_vectors_legacy_version: u64,
     pub fallback_decode: GraphLinksFallbackDecodeTelemetry,
 }
@@ -83,7 +126,9 @@ pub fn graph_links_compatibility_telemetry() -> GraphLinksCompatibilityTelemetry
         plain_version: header::HEADER_VERSION_PLAIN,
         compressed_version: header::HEADER_VERSION_COMPRESSED,
         compressed_legacy_version: header::HEADER_VERSION_COMPRESSED_LEGACY,
+        compressed_varint_version: header::HEADER_VERSION_COMPRESSED_VARINT,
         compressed_with_vectors_version: header::HEADER_VERSION_COMPRESSED_WITH_VECTORS,
+        compressed_with_dedup_vectors_version: header::HEADER_VERSION_COMPRESSED_WITH_DEDUP_VECTORS,
         compressed_with_vectors_legacy_version:
             header::HEADER_VERSION_COMPRESSED_WITH_VECTORS_LEGACY,
         fallback_decode: fallback,
@@ -96,21 +141,62 @@ pub fn graph_links_compatibility_telemetry() -> GraphLinksCompatibilityTelemetry
 pub enum GraphLinksFormatParam<'a> {
     Plain,
     Compressed,
+    /// Same as `Compressed`, but links are packed with a byte-aligned varint
+    /// codec, which is cheaper to decode, but compresses worse.
+    CompressedVarint,
     CompressedWithVectors(&'a dyn GraphLinksVectors),
+    /// Same as `CompressedWithVectors`, but each link vector is stored once in a table
+    /// indexed by point id, instead of next to every link pointing to the point.
+    CompressedWithDedupVectors(&'a dyn GraphLinksVectors),
 }
 
 /// This trait lets the [`serialize_graph_links`] to access vector values.
 pub trait GraphLinksVectors {
     /// Base vectors will be included once per point on level 0.
-    /// The layout of each vector must correspond to [`VectorLayout::base`].
-    fn get_base_vector(&self, point_id: PointOffsetType) -> OperationResult<&[u8]>;
+    /// The layout of each vector must correspond to [`VectorLayout::base`],
+    /// and the encoding to [`GraphLinksVectorsLayout::base_encoding`].
+    fn get_base_vector(&self, point_id: PointOffsetType) -> OperationResult<Cow<'_, [u8]>>;
 
-    /// Link vectors will be included for each link per point.
-    /// The layout of each vector must correspond to [`VectorLayout::link`].
+    /// Link vectors will be included for each link per point, or once per point if
+    /// deduplicated. The layout of each vector must correspond to [`VectorLayout::link`].
     fn get_link_vector(&self, point_id: PointOffsetType) -> OperationResult<&[u8]>;
 
     /// Get the layout of base and link vectors.
     fn vectors_layout(&self) -> GraphLinksVectorsLayout;
+
+    /// Quantization of link vectors, stored with the links if present.
+    fn quantization_metadata(&self) -> Option<GraphLinksQuantizationMetadata> {
+        None
+    }
+}
+
+/// Quantization of link vectors in [`GraphLinksFormat::CompressedWithVectors`].
+///
+/// Lets a graph detect that it is loaded next to quantized vectors encoded differently from the
+/// embedded link vectors, e.g. after a partial restore.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct GraphLinksQuantizationMetadata {
+    /// Oversampling of searches rescored with base vectors, the graph is built for.
+    pub rescore_oversampling: f64,
+    /// See [`quantization_hash`].
+    pub quantization_hash: u64,
+}
+
+/// Hash of the encoding of quantized vectors: their layout and the encoded vectors of the first
+/// few points. Changes if the quantization is rebuilt with a different config or data.
+pub fn quantization_hash(
+    quantized_vectors: &QuantizedVectors,
+    vectors_count: usize,
+) -> OperationResult<u64> {
+    let layout = quantized_vectors.get_quantized_vector_layout()?;
+    // Explicit little-endian bytes, so the hash is the same on all platforms
+    let mut hasher = SeaHasher::new();
+    hasher.write(&(layout.size() as u64).to_le_bytes());
+    hasher.write(&(layout.align() as u64).to_le_bytes());
+    for point_id in 0..vectors_count.min(QUANTIZATION_HASH_SAMPLE_SIZE) {
+        hasher.write(quantized_vectors.get_quantized_vector(point_id as PointOffsetType));
+    }
+    Ok(hasher.finish())
 }
 
 /// Layout of base and link vectors, returned by [`GraphLinksVectors::vectors_layout`].
@@ -118,6 +204,23 @@ pub trait GraphLinksVectors {
 pub struct GraphLinksVectorsLayout {
     pub base: Layout,
     pub link: Layout,
+    pub base_encoding: GraphLinksBaseVectorEncoding,
+}
+
+/// Encoding of base vectors in [`GraphLinksFormat::CompressedWithVectors`].
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+pub enum GraphLinksBaseVectorEncoding {
+    /// Bytes of the vector storage, as is.
+    #[default]
+    Storage,
+    /// `f32` vectors of the storage converted to `f16`, little-endian.
+    ///
+    /// Base vectors are only used to rescore candidates during traversal, so
+    /// half precision is enough, while the links file gets much smaller.
+    Float16,
+    // There is no `u8` encoding: link vectors are already quantized, and base
+    // vectors quantized as coarsely would make rescoring during traversal
+    // pointless. Vectors of `u8` storages are stored as is.
 }
 
 /// A [`GraphLinksVectors`] implementation that uses real storage.
@@ -125,36 +228,85 @@ pub struct StorageGraphLinksVectors<'a> {
     vector_storage: &'a VectorStorageEnum,   // base vectors
     quantized_vectors: &'a QuantizedVectors, // link vectors
     vectors_layout: GraphLinksVectorsLayout,
+    quantization_metadata: GraphLinksQuantizationMetadata,
 }
 
 impl<'a> StorageGraphLinksVectors<'a> {
+    /// `base_encoding` other than [`GraphLinksBaseVectorEncoding::Storage`] is
+    /// only applied to `f32` storages, otherwise it is silently ignored.
     pub fn try_new(
         vector_storage: &'a VectorStorageEnum,
         quantized_vectors: Option<&'a QuantizedVectors>,
+        base_encoding: GraphLinksBaseVectorEncoding,
     ) -> Option<Self> {
         let quantized_vectors = quantized_vectors?;
+        let storage_layout = vector_storage.get_vector_layout().ok()?;
+        let (base, base_encoding) = match base_encoding {
+            GraphLinksBaseVectorEncoding::Float16
+                if vector_storage.datatype() == VectorStorageDatatype::Float32 =>
+            {
+                let dim = storage_layout.size() / size_of::<f32>();
+                let layout = Layout::array::<f16>(dim).ok()?;
+                (layout, GraphLinksBaseVectorEncoding::Float16)
+            }
+            GraphLinksBaseVectorEncoding::Storage | GraphLinksBaseVectorEncoding::Float16 => {
+                (storage_layout, GraphLinksBaseVectorEncoding::Storage)
+            }
+        };
+        let quantization_hash =
+            quantization_hash(quantized_vectors, vector_storage.total_vector_count()).ok()?;
         Some(Self {
             vector_storage,
             quantized_vectors,
             vectors_layout: GraphLinksVectorsLayout {
-                base: vector_storage.get_vector_layout().ok()?,
+                base,
                 link: quantized_vectors.get_quantized_vector_layout().ok()?,
+                base_encoding,
+            },
+            quantization_metadata: GraphLinksQuantizationMetadata {
+                rescore_oversampling: quantized_vectors.default_oversampling().unwrap_or(1.0),
+                quantization_hash,
             },
         })
     }
+
+    /// Record oversampling, which searches using the graph should apply by default.
+    ///
+    /// Defaults to the configured oversampling of the quantized vectors.
+    pub fn with_rescore_oversampling(mut self, rescore_oversampling: f64) -> Self {
+        self.quantization_metadata.rescore_oversampling = rescore_oversampling;
+        self
+    }
 }
 
 impl<'a> GraphLinksVectors for StorageGraphLinksVectors<'a> {
     /// Note: uses [`Sequential`] because [`serializer::serialize_graph_links`]
     /// traverses base vectors in a sequential order.
-    fn get_base_vector(&self, point_id: PointOffsetType) -> OperationResult<&[u8]> {
-        self.vector_storage
+    fn get_base_vector(&self, point_id: PointOffsetType) -> OperationResult<Cow<'_, [u8]>> {
+        let bytes = self
+            .vector_storage
             .get_vector_bytes_opt::<Sequential>(point_id)
             .ok_or_else(|| {
                 OperationError::service_error(format!(
                     "Point {point_id} not found in vector storage"
                 ))
-            })
+            })?;
+        match self.vectors_layout.base_encoding {
+            GraphLinksBaseVectorEncoding::Storage => Ok(bytes),
+            GraphLinksBaseVectorEncoding::Float16 => {
+                let vector = <[f32]>::ref_from_bytes(&bytes).map_err(|_| {
+                    OperationError::service_error(format!(
+                        "Point {point_id} is not a float32 vector"
+                    ))
+                })?;
+                Ok(Cow::Owned(
+                    vector
+                        .iter()
+                        .flat_map(|&value| f16::from_f32(value).to_le_bytes())
+                        .collect(),
+                ))
+            }
+        }
     }
 
     /// Note: unlike base vectors, link vectors are written in a random order.
@@ -165,6 +317,10 @@ impl<'a> GraphLinksVectors for StorageGraphLinksVectors<'a> {
     fn vectors_layout(&self) -> GraphLinksVectorsLayout {
         self.vectors_layout
     }
+
+    fn quantization_metadata(&self) -> Option<GraphLinksQuantizationMetadata> {
+        Some(self.quantization_metadata)
+    }
 }
 
 impl GraphLinksFormat {
@@ -218,8 +374,11 @@ impl<'a> GraphLinksFormatParam<'a> {
     pub fn as_format(&self) -> GraphLinksFormat {
         match self {
             GraphLinksFormatParam::Plain => GraphLinksFormat::Plain,
-            GraphLinksFormatParam::Compressed => GraphLinksFormat::Compressed,
-            GraphLinksFormatParam::CompressedWithVectors(_) => {
+            GraphLinksFormatParam::Compressed | GraphLinksFormatParam::CompressedVarint => {
+                GraphLinksFormat::Compressed
+            }
+            GraphLinksFormatParam::CompressedWithVectors(_)
+            | GraphLinksFormatParam::CompressedWithDedupVectors(_) => {
                 GraphLinksFormat::CompressedWithVectors
             }
         }
@@ -297,7 +456,17 @@ impl GraphLinks {
     }
 
     pub fn num_points(&self) -> usize {
-        self.view().reindex.len()
+        self.view().num_points()
+    }
+
+    /// Whether the file was decoded with the legacy big-endian fallback.
+    pub fn is_legacy_fallback(&self) -> bool {
+        self.view().is_legacy_fallback
+    }
+
+    /// Apply links updated after the graph was serialized, see [`GraphLinksDelta`].
+    pub fn set_delta(&mut self, delta: GraphLinksDelta) -> OperationResult<()> {
+        self.with_dependent_mut(|_, view| view.set_delta(delta))
     }
 
     pub fn for_each_link(
@@ -325,14 +494,51 @@ impl GraphLinks {
         point_id: PointOffsetType,
         level: usize,
     ) -> (&[u8], LinksWithVectorsIterator<'_>) {
-        let (base_vector, links, vectors) = self.view().links_with_vectors(point_id, level);
-        (base_vector, links.zip(vectors))
+        self.view().links_with_vectors(point_id, level)
     }
 
     pub fn point_level(&self, point_id: PointOffsetType) -> usize {
         self.view().point_level(point_id)
     }
 
+    /// Encoding of base vectors, `None` if the format has no vectors.
+    pub fn base_vector_encoding(&self) -> Option<GraphLinksBaseVectorEncoding> {
+        match self.view().compression {
+            CompressionInfo::Uncompressed { .. } | CompressionInfo::Compressed { .. } => None,
+            CompressionInfo::CompressedWithVectors {
+                base_vector_encoding,
+                ..
+            } => Some(base_vector_encoding),
+        }
+    }
+
+    /// Quantization of link vectors, `None` if the format has no vectors or the file was written
+    /// before it was stored.
+    pub fn quantization_metadata(&self) -> Option<GraphLinksQuantizationMetadata> {
+        match self.view().compression {
+            CompressionInfo::Uncompressed { .. } | CompressionInfo::Compressed { .. } => None,
+            CompressionInfo::CompressedWithVectors {
+                quantization_metadata,
+                ..
+            } => quantization_metadata,
+        }
+    }
+
+    /// Collect per-level statistics of links, visits all links of the graph.
+    pub fn collect_telemetry(&self) -> HnswLinksTelemetry {
+        let mut levels: Vec<HnswLevelLinksTelemetry> = Vec::new();
+        for point_id in 0..self.num_points() as PointOffsetType {
+            let point_level = self.point_level(point_id);
+            if levels.len() <= point_level {
+                levels.resize_with(point_level + 1, Default::default);
+            }
+            for (level, level_telemetry) in levels.iter_mut().enumerate().take(point_level + 1) {
+                level_telemetry.add_point(self.links(point_id, level).len());
+            }
+        }
+        HnswLinksTelemetry { levels }
+    }
+
     /// Convert the graph links to a vector of edges, suitable for passing into
     /// [`serialize_graph_links`] or using in tests.
     pub fn to_edges(&self) -> Vec<Vec<Vec<PointOffsetType>>> {
@@ -363,6 +569,106 @@ impl GraphLinks {
         };
         Ok(())
     }
+
+    /// Lock the links file in RAM, so that it is not evicted from the page cache.
+    /// This is a blocking operation, which loads the whole file first.
+    ///
+    /// Fails if the process is not allowed to lock that much memory, see
+    /// [`advice::lock_in_ram`].
+    pub fn lock_in_ram(&self) -> OperationResult<()> {
+        match self.borrow_owner() {
+            GraphLinksEnum::Mmap(mmap) => advice::lock_in_ram(&mmap[..])?,
+            GraphLinksEnum::Ram(_) => {}
+        };
+        Ok(())
+    }
+
+    /// Advise the OS how the links file is going to be accessed, if applicable.
+    pub fn advise(&self, advice: Advice) -> OperationResult<()> {
+        match self.borrow_owner() {
+            GraphLinksEnum::Mmap(mmap) => mmap.madvise(advice)?,
+            GraphLinksEnum::Ram(_) => {}
+        };
+        Ok(())
+    }
+
+    /// Request transparent huge pages for the links file, if applicable.
+    pub fn request_hugepages(&self) -> OperationResult<()> {
+        match self.borrow_owner() {
+            GraphLinksEnum::Mmap(mmap) => advice::request_hugepages(&mmap[..])?,
+            GraphLinksEnum::Ram(_) => {}
+        };
+        Ok(())
+    }
+
+    /// Populate the disk cache with data, if applicable, latency-critical parts first.
+    ///
+    /// Blocks until everything except level 0 links is populated: the header, level offsets,
+    /// reindex, offsets and links of the upper levels. These are small, but needed by every
+    /// search. Level 0 links, which make up most of the file, are then populated in a background
+    /// thread. Its progress is reported to `progress` in bytes, the task is finished once
+    /// everything is populated.
+    ///
+    /// Returns the handle of the background thread, `None` if there is nothing left to populate.
+    pub fn populate_prioritized(
+        &self,
+        progress: ProgressTracker,
+    ) -> OperationResult<Option<JoinHandle<()>>> {
+        let GraphLinksEnum::Mmap(mmap) = self.borrow_owner() else {
+            return Ok(None);
+        };
+
+        let Some(level0) = self.level0_range() else {
+            // Links are decoded into RAM, the file is only populated for consistency
+            mmap.populate();
+            return Ok(None);
+        };
+
+        populate_region(&mmap[..level0.start]);
+        populate_region(&mmap[level0.end..]);
+
+        let mmap = Arc::clone(mmap);
+        let handle = thread::Builder::new()
+            .name("graph-links-populate".to_string())
+            .spawn(move || {
+                let populated = progress.track_progress(Some(level0.len() as u64));
+                for chunk in mmap[level0].chunks(POPULATE_CHUNK_SIZE) {
+                    populate_region(chunk);
+                    populated.fetch_add(chunk.len() as u64, Ordering::Relaxed);
+                }
+                // Dropping `progress` marks the task as finished
+                drop(progress);
+            })?;
+        Ok(Some(handle))
+    }
+
+    /// Byte range of level 0 links within [`Self::as_bytes`].
+    ///
+    /// `None` if links are not borrowed from the serialized data, i.e. decoded with the legacy
+    /// big-endian fallback.
+    fn level0_range(&self) -> Option<Range<usize>> {
+        self.range_in_bytes(self.view().level0_neighbors_bytes())
+    }
+
+    /// Byte range of serialized links of the point within [`Self::as_bytes`], used to prefetch
+    /// them from disk.
+    ///
+    /// `None` if the links are taken from the delta, or are not borrowed from the serialized
+    /// data.
+    pub fn links_range(&self, point_id: PointOffsetType, level: usize) -> Option<Range<usize>> {
+        self.range_in_bytes(self.view().serialized_links_bytes(point_id, level)?)
+    }
+
+    fn range_in_bytes(&self, part: &[u8]) -> Option<Range<usize>> {
+        let bytes = self.as_bytes().as_ptr_range();
+        let part = part.as_ptr_range();
+        if part.start < bytes.start || part.end > bytes.end {
+            return None;
+        }
+        let start = part.start.addr() - bytes.start.addr();
+        let end = part.end.addr() - bytes.start.addr();
+        Some(start..end)
+    }
 }
 
 /// Sort the first `m` values in `links` and return them. Used to compare stored
@@ -380,12 +686,16 @@ mod tests {
     use std::mem::size_of;
 
     use common::fs::atomic_save;
-    use rand::Rng;
+    use common::progress_tracker::new_progress_tracker;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng as _};
     use rstest::rstest;
     use tempfile::Builder;
 
     use super::*;
+    use crate::fixtures::index_fixtures::TestRawScorerProducer;
     use crate::index::hnsw_index::HnswM;
+    use crate::types::{Distance, PointIdType};
 
     struct TestGraphLinksVectors {
         base_vectors: Vec<Vec<u8>>,
@@ -408,14 +718,15 @@ mod tests {
                 vectors_layout: GraphLinksVectorsLayout {
                     base: Layout::from_size_align(base_len, base_align).unwrap(),
                     link: Layout::from_size_align(link_len, link_align).unwrap(),
+                    base_encoding: GraphLinksBaseVectorEncoding::Storage,
                 },
             }
         }
     }
 
     impl GraphLinksVectors for TestGraphLinksVectors {
-        fn get_base_vector(&self, point_id: PointOffsetType) -> OperationResult<&[u8]> {
-            Ok(&self.base_vectors[point_id as usize])
+        fn get_base_vector(&self, point_id: PointOffsetType) -> OperationResult<Cow<'_, [u8]>> {
+            Ok(Cow::Borrowed(&self.base_vectors[point_id as usize]))
         }
 
         fn get_link_vector(&self, point_id: PointOffsetType) -> OperationResult<&[u8]> {
@@ -459,7 +770,7 @@ mod tests {
             let links: Vec<_> = if let Some(vectors) = vectors {
                 let (base_vector, iter) = right.links_with_vectors(point_id, level);
                 if level == 0 {
-                    assert_eq!(base_vector, vectors.get_base_vector(point_id).unwrap());
+                    assert_eq!(base_vector, &*vectors.get_base_vector(point_id).unwrap());
                 } else {
                     assert!(base_vector.is_empty());
                 }
@@ -525,6 +836,112 @@ mod tests {
         check_links(links, &cmp_links, &vectors);
     }
 
+    #[rstest]
+    #[case::align_1_16(1, 16)]
+    #[case::align_4_1(4, 1)]
+    #[case::align_16_4(16, 4)]
+    fn test_save_load_dedup_link_vectors(#[case] base_align: usize, #[case] link_align: usize) {
+        let points_count = 1000;
+        let max_levels_count = 10;
+        let hnsw_m = HnswM::new2(8);
+
+        let path = Builder::new().prefix("graph_dir").tempdir().unwrap();
+        let links = random_links(points_count, max_levels_count, &hnsw_m);
+        let vectors = Some(TestGraphLinksVectors::new(
+            points_count,
+            base_align,
+            link_align,
+        ));
+        let vectors_ref = vectors.as_ref().unwrap();
+
+        let mut files_len = Vec::new();
+        for format_param in [
+            GraphLinksFormatParam::CompressedWithVectors(vectors_ref),
+            GraphLinksFormatParam::CompressedWithDedupVectors(vectors_ref),
+        ] {
+            let links_file = path.path().join("links.bin");
+            atomic_save(&links_file, |writer| {
+                serialize_graph_links(links.clone(), format_param, hnsw_m, writer)
+            })
+            .unwrap();
+
+            let cmp_links = GraphLinks::load_from_file(
+                &links_file,
+                true,
+                GraphLinksFormat::CompressedWithVectors,
+            )
+            .unwrap();
+            check_links(links.clone(), &cmp_links, &vectors);
+            files_len.push(cmp_links.as_bytes().len());
+        }
+
+        // Each link vector is stored once instead of once per incoming link
+        assert!(files_len[1] < files_len[0], "{files_len:?}");
+    }
+
+    /// Test that links appended to [`GraphLinksDelta`] are merged with the
+    /// serialized ones, and an incomplete record at the end is ignored.
+    #[rstest]
+    #[case::plain(GraphLinksFormat::Plain)]
+    #[case::compressed(GraphLinksFormat::Compressed)]
+    fn test_links_delta(#[case] format: GraphLinksFormat) {
+        let points_count = 100;
+        let new_points_count = 20;
+        let max_levels_count = 5;
+        let hnsw_m = HnswM::new2(8);
+
+        let path = Builder::new().prefix("graph_dir").tempdir().unwrap();
+        let links_file = path.path().join("links.bin");
+        let delta_file = path.path().join("links_delta.bin");
+        let mut links = random_links(points_count, max_levels_count, &hnsw_m);
+
+        let format_param = format.with_param_for_tests::<TestGraphLinksVectors>(None);
+        atomic_save(&links_file, |writer| {
+            serialize_graph_links(links.clone(), format_param, hnsw_m, writer)
+        })
+        .unwrap();
+
+        let mut writer = GraphLinksDeltaWriter::open(&delta_file).unwrap();
+        // Existing point gets a link to a new point
+        links[0][0].push(points_count as PointOffsetType);
+        writer.append(0, 0, &links[0][0]).unwrap();
+        // New points, linked to existing ones
+        let new_links = random_links(new_points_count, max_levels_count, &hnsw_m);
+        for (point_id, levels) in (points_count as PointOffsetType..).zip(&new_links) {
+            for (level, level_links) in levels.iter().enumerate() {
+                writer.append(point_id, level, level_links).unwrap();
+            }
+        }
+        writer.flush().unwrap();
+        links.extend(new_links);
+
+        // Simulate a torn write of the next record
+        let mut file = fs_err::OpenOptions::new()
+            .append(true)
+            .open(&delta_file)
+            .unwrap();
+        std::io::Write::write_all(&mut file, &[1, 0, 0, 0, 0, 0]).unwrap();
+        drop(file);
+
+        let delta = GraphLinksDelta::load(&delta_file).unwrap().unwrap();
+        let mut cmp_links = GraphLinks::load_from_file(&links_file, true, format).unwrap();
+        cmp_links.set_delta(delta).unwrap();
+        assert_eq!(cmp_links.num_points(), points_count + new_points_count);
+        check_links(links.clone(), &cmp_links, &None);
+
+        // Reopening truncates the torn record, so that new records can be read back
+        let mut writer = GraphLinksDeltaWriter::open(&delta_file).unwrap();
+        links[1][0] = vec![2, 3];
+        writer.append(1, 0, &links[1][0]).unwrap();
+        writer.flush().unwrap();
+        drop(writer);
+
+        let delta = GraphLinksDelta::load(&delta_file).unwrap().unwrap();
+        let mut cmp_links = GraphLinks::load_from_file(&links_file, true, format).unwrap();
+        cmp_links.set_delta(delta).unwrap();
+        check_links(links, &cmp_links, &None);
+    }
+
     #[test]
     fn test_plain_serialization_has_little_endian_versioned_header() {
         let hnsw_m = HnswM::new2(8);
@@ -565,6 +982,425 @@ mod tests {
         assert_eq!(version, super::header::HEADER_VERSION_COMPRESSED);
     }
 
+    #[rstest]
+    #[case::plain(GraphLinksFormat::Plain)]
+    #[case::compressed(GraphLinksFormat::Compressed)]
+    fn test_collect_telemetry(#[case] format: GraphLinksFormat) {
+        let hnsw_m = HnswM::new2(8);
+        let links = vec![
+            vec![vec![1, 2, 3], vec![2]],
+            vec![vec![0]],
+            vec![vec![0, 1], vec![0]],
+            vec![vec![]],
+        ];
+        let graph_links = GraphLinks::new_from_edges(
+            links,
+            format.with_param_for_tests::<TestGraphLinksVectors>(None),
+            hnsw_m,
+        )
+        .unwrap();
+
+        let telemetry = graph_links.collect_telemetry();
+        assert_eq!(
+            telemetry.levels,
+            vec![
+                HnswLevelLinksTelemetry {
+                    points_count: 4,
+                    links_count: 6,
+                    avg_out_degree: 1.5,
+                    degree_histogram: vec![1, 1, 1, 1],
+                },
+                HnswLevelLinksTelemetry {
+                    points_count: 2,
+                    links_count: 2,
+                    avg_out_degree: 1.0,
+                    degree_histogram: vec![0, 2],
+                },
+            ],
+        );
+    }
+
+    #[test]
+    fn test_verify_connectivity() {
+        let hnsw_m = HnswM::new2(8);
+        let format_param = GraphLinksFormatParam::Plain;
+
+        let links = vec![vec![vec![1], vec![]], vec![vec![0, 2]], vec![vec![1]]];
+        let graph_links = GraphLinks::new_from_edges(links, format_param, hnsw_m).unwrap();
+        let report = graph_links.verify_connectivity(Some(0), |_| true);
+        assert!(report.is_ok(), "{report}");
+        assert_eq!(report.reachable_points, 3);
+
+        let links = vec![
+            vec![vec![1], vec![3]], // 3 is not present on level 1
+            vec![vec![0]],
+            vec![vec![5]], // 5 is not present in the graph
+            vec![vec![0]],
+        ];
+        let graph_links = GraphLinks::new_from_edges(links, format_param, hnsw_m).unwrap();
+
+        let report = graph_links.verify_connectivity(Some(0), |_| true);
+        assert!(!report.is_ok());
+        assert!(!report.invalid_entry_point);
+        assert_eq!(report.expected_points, 4);
+        assert_eq!(report.reachable_points, 2);
+        assert_eq!(report.orphaned_points, vec![2, 3]);
+        assert_eq!(report.out_of_range_links, vec![(0, 1, 3), (2, 0, 5)]);
+
+        // Deleted points are not expected to be reachable
+        let report = graph_links.verify_connectivity(Some(0), |point_id| point_id < 2);
+        assert_eq!(report.orphaned_count, 0);
+
+        let report = graph_links.verify_connectivity(None, |_| true);
+        assert!(report.invalid_entry_point);
+        assert_eq!(report.orphaned_count, 4);
+    }
+
+    #[test]
+    fn test_export() {
+        let hnsw_m = HnswM::new2(8);
+        let links = vec![
+            vec![vec![1, 2], vec![2]],
+            vec![vec![0]],
+            vec![vec![0], vec![0]],
+        ];
+        let graph_links =
+            GraphLinks::new_from_edges(links, GraphLinksFormatParam::Plain, hnsw_m).unwrap();
+        // Point 1 is deleted
+        let external_id =
+            |offset| (offset != 1).then(|| PointIdType::NumId(100 + u64::from(offset)));
+
+        let mut jsonl = Vec::new();
+        graph_links
+            .export(GraphExportFormat::Jsonl, external_id, &mut jsonl)
+            .unwrap();
+        let jsonl = String::from_utf8(jsonl).unwrap();
+        assert_eq!(
+            jsonl.lines().collect::<Vec<_>>(),
+            vec![
+                r#"{"offset":0,"id":100,"level":1,"links":[[1,2],[2]]}"#,
+                r#"{"offset":1,"id":null,"level":0,"links":[[0]]}"#,
+                r#"{"offset":2,"id":102,"level":1,"links":[[0],[0]]}"#,
+            ],
+        );
+
+        let mut graphml = Vec::new();
+        graph_links
+            .export(GraphExportFormat::GraphMl, external_id, &mut graphml)
+            .unwrap();
+        let graphml = String::from_utf8(graphml).unwrap();
+        assert_eq!(graphml.matches("<node ").count(), 3);
+        assert_eq!(graphml.matches("<edge ").count(), 6);
+        assert!(graphml.contains(r#"<data key="id">102</data>"#));
+        assert!(
+            graphml.contains(
+                r#"<edge source="n0" target="n2"><data key="link_level">1</data></edge>"#
+            )
+        );
+
+        assert_eq!(
+            "graphml".parse::<GraphExportFormat>().unwrap(),
+            GraphExportFormat::GraphMl,
+        );
+    }
+
+    #[rstest]
+    #[case::plain(GraphLinksFormat::Plain)]
+    #[case::compressed(GraphLinksFormat::Compressed)]
+    fn test_links_prefetch(#[case] format: GraphLinksFormat) {
+        let points_count = 200;
+        let hnsw_m = HnswM::new2(8);
+
+        let path = Builder::new().prefix("graph_dir").tempdir().unwrap();
+        let links_file = path.path().join("links.bin");
+        let links = random_links(points_count, 4, &hnsw_m);
+
+        let format_param = format.with_param_for_tests::<TestGraphLinksVectors>(None);
+        atomic_save(&links_file, |writer| {
+            serialize_graph_links(links.clone(), format_param, hnsw_m, writer)
+        })
+        .unwrap();
+        let graph_links = GraphLinks::load_from_file(&links_file, true, format).unwrap();
+
+        // Plain links are decoded into RAM on big-endian targets, and are not prefetched
+        let is_decoded = cfg!(target_endian = "big") && format == GraphLinksFormat::Plain;
+        for (point_id, levels) in links.iter().enumerate() {
+            for (level, level_links) in levels.iter().enumerate() {
+                let range = graph_links.links_range(point_id as PointOffsetType, level);
+                if is_decoded {
+                    assert!(range.is_none());
+                    continue;
+                }
+                let range = range.unwrap();
+                assert!(range.end <= graph_links.as_bytes().len());
+                if format == GraphLinksFormat::Plain {
+                    assert_eq!(
+                        range.len(),
+                        level_links.len() * size_of::<PointOffsetType>()
+                    );
+                }
+            }
+        }
+
+        // io_uring is not available on every platform and might be forbidden in the sandbox
+        let Ok(pool) = LinksPrefetchPool::open(&links_file) else {
+            return;
+        };
+        let mut prefetcher = pool.get(&graph_links).unwrap();
+        for (point_id, levels) in links.iter().enumerate() {
+            for level in 0..levels.len() {
+                prefetcher.prefetch(point_id as PointOffsetType, level);
+            }
+        }
+        drop(prefetcher);
+        // Prefetcher is reused by the next search
+        assert!(pool.get(&graph_links).is_some());
+
+        check_links(links, &graph_links, &None);
+    }
+
+    #[rstest]
+    #[case::plain(GraphLinksFormat::Plain)]
+    #[case::compressed(GraphLinksFormat::Compressed)]
+    #[case::compressed_with_vectors(GraphLinksFormat::CompressedWithVectors)]
+    fn test_populate_prioritized(#[case] format: GraphLinksFormat) {
+        let points_count = 1000;
+        let hnsw_m = HnswM::new2(8);
+
+        let path = Builder::new().prefix("graph_dir").tempdir().unwrap();
+        let links_file = path.path().join("links.bin");
+        let links = random_links(points_count, 5, &hnsw_m);
+
+        let vectors = format
+            .is_with_vectors()
+            .then(|| TestGraphLinksVectors::new(points_count, 4, 4));
+        let format_param = format.with_param_for_tests(vectors.as_ref());
+        atomic_save(&links_file, |writer| {
+            serialize_graph_links(links.clone(), format_param, hnsw_m, writer)
+        })
+        .unwrap();
+
+        let graph_links = GraphLinks::load_from_file(&links_file, true, format).unwrap();
+        let Some(level0) = graph_links.level0_range() else {
+            // Plain links are decoded into RAM on big-endian targets, the file is populated at once
+            assert!(cfg!(target_endian = "big") && format == GraphLinksFormat::Plain);
+            let handle = graph_links
+                .populate_prioritized(ProgressTracker::new_for_test())
+                .unwrap();
+            assert!(handle.is_none());
+            check_links(links, &graph_links, &vectors);
+            return;
+        };
+        assert!(level0.end <= graph_links.as_bytes().len());
+        if format == GraphLinksFormat::Plain {
+            let level0_links: usize = links.iter().map(|levels| levels[0].len()).sum();
+            assert_eq!(level0.len(), level0_links * size_of::<PointOffsetType>());
+        }
+
+        let (progress_view, progress) = new_progress_tracker();
+        let handle = graph_links
+            .populate_prioritized(progress.running_subtask("level0"))
+            .unwrap()
+            .unwrap();
+        handle.join().unwrap();
+
+        let snapshot = progress_view.snapshot("populate");
+        let level0_progress = &snapshot.children[0];
+        assert_eq!(level0_progress.total, Some(level0.len() as u64));
+        assert_eq!(level0_progress.done, Some(level0.len() as u64));
+        assert!(level0_progress.finished_at.is_some());
+
+        check_links(links, &graph_links, &vectors);
+
+        // Nothing to populate in background for links in RAM
+        let graph_links = GraphLinks::new_from_edges(
+            vec![vec![vec![1]], vec![vec![0]]],
+            GraphLinksFormatParam::Plain,
+            hnsw_m,
+        )
+        .unwrap();
+        let handle = graph_links
+            .populate_prioritized(ProgressTracker::new_for_test())
+            .unwrap();
+        assert!(handle.is_none());
+    }
+
+    #[test]
+    fn test_lock_in_ram() {
+        let points_count = 100;
+        let hnsw_m = HnswM::new2(8);
+
+        let path = Builder::new().prefix("graph_dir").tempdir().unwrap();
+        let links_file = path.path().join("links.bin");
+        let links = random_links(points_count, 3, &hnsw_m);
+        atomic_save(&links_file, |writer| {
+            serialize_graph_links(
+                links.clone(),
+                GraphLinksFormatParam::Compressed,
+                hnsw_m,
+                writer,
+            )
+        })
+        .unwrap();
+
+        // A few KiB are within the default `RLIMIT_MEMLOCK`
+        let graph_links =
+            GraphLinks::load_from_file(&links_file, true, GraphLinksFormat::Compressed).unwrap();
+        graph_links.lock_in_ram().unwrap();
+        check_links(links, &graph_links, &None);
+    }
+
+    #[test]
+    fn test_save_load_float16_base_vectors() {
+        let points_count = 100;
+        let max_levels_count = 5;
+        let dim = 8;
+        let hnsw_m = HnswM::new2(8);
+
+        let mut rng = StdRng::seed_from_u64(42);
+        let vector_holder =
+            TestRawScorerProducer::new(dim, Distance::Dot, points_count, true, &mut rng);
+        let vectors = StorageGraphLinksVectors::try_new(
+            vector_holder.storage(),
+            vector_holder.quantized_vectors(),
+            GraphLinksBaseVectorEncoding::Float16,
+        )
+        .unwrap();
+        assert_eq!(vectors.vectors_layout().base.size(), dim * size_of::<f16>());
+
+        let links = random_links(points_count, max_levels_count, &hnsw_m);
+        let graph_links = GraphLinks::new_from_edges(
+            links.clone(),
+            GraphLinksFormatParam::CompressedWithVectors(&vectors),
+            hnsw_m,
+        )
+        .unwrap();
+        assert_eq!(
+            graph_links.base_vector_encoding(),
+            Some(GraphLinksBaseVectorEncoding::Float16),
+        );
+        check_links(links, &graph_links, &None);
+
+        for point_id in 0..points_count as PointOffsetType {
+            let (base_vector, _) = graph_links.links_with_vectors(point_id, 0);
+            let stored = vector_holder
+                .storage()
+                .get_vector_bytes_opt::<Sequential>(point_id)
+                .unwrap();
+            let stored = <[f32]>::ref_from_bytes(&stored).unwrap();
+            let decoded = base_vector
+                .chunks_exact(size_of::<f16>())
+                .map(|chunk| f16::from_le_bytes([chunk[0], chunk[1]]).to_f32());
+            for (stored, decoded) in stored.iter().zip(decoded) {
+                assert!((stored - decoded).abs() < 1e-2);
+            }
+        }
+    }
+
+    #[test]
+    fn test_quantization_metadata() {
+        let points_count = 100;
+        let dim = 8;
+        let hnsw_m = HnswM::new2(8);
+        let links = random_links(points_count, 3, &hnsw_m);
+
+        let mut rng = StdRng::seed_from_u64(42);
+        let vector_holder =
+            TestRawScorerProducer::new(dim, Distance::Dot, points_count, true, &mut rng);
+        let vectors = StorageGraphLinksVectors::try_new(
+            vector_holder.storage(),
+            vector_holder.quantized_vectors(),
+            GraphLinksBaseVectorEncoding::Storage,
+        )
+        .unwrap();
+        // Scalar quantization has no oversampling configured
+        assert_eq!(
+            vectors
+                .quantization_metadata()
+                .unwrap()
+                .rescore_oversampling,
+            1.0,
+        );
+        let vectors = vectors.with_rescore_oversampling(2.5);
+
+        let path = Builder::new().prefix("graph_dir").tempdir().unwrap();
+        let links_file = path.path().join("links.bin");
+        for format_param in [
+            GraphLinksFormatParam::CompressedWithVectors(&vectors),
+            GraphLinksFormatParam::CompressedWithDedupVectors(&vectors),
+        ] {
+            atomic_save(&links_file, |writer| {
+                serialize_graph_links(links.clone(), format_param, hnsw_m, writer)
+            })
+            .unwrap();
+            let graph_links = GraphLinks::load_from_file(
+                &links_file,
+                true,
+                GraphLinksFormat::CompressedWithVectors,
+            )
+            .unwrap();
+            check_links(links.clone(), &graph_links, &None);
+
+            let metadata = graph_links.quantization_metadata().unwrap();
+            assert_eq!(Some(metadata), vectors.quantization_metadata());
+            assert_eq!(metadata.rescore_oversampling, 2.5);
+        }
+
+        // Quantized vectors of other data don't match
+        let other_holder =
+            TestRawScorerProducer::new(dim, Distance::Dot, points_count, true, &mut rng);
+        let other_hash =
+            quantization_hash(other_holder.quantized_vectors().unwrap(), points_count).unwrap();
+        assert_ne!(
+            other_hash,
+            vectors.quantization_metadata().unwrap().quantization_hash,
+        );
+
+        // Files written without metadata
+        let vectors = TestGraphLinksVectors::new(points_count, 8, 8);
+        let graph_links = GraphLinks::new_from_edges(
+            links,
+            GraphLinksFormatParam::CompressedWithVectors(&vectors),
+            hnsw_m,
+        )
+        .unwrap();
+        assert_eq!(graph_links.quantization_metadata(), None);
+    }
+
+    #[test]
+    fn test_save_load_compressed_varint() {
+        let points_count = 1000;
+        let max_levels_count = 10;
+        let hnsw_m = HnswM::new2(8);
+
+        let path = Builder::new().prefix("graph_dir").tempdir().unwrap();
+        let links_file = path.path().join("links.bin");
+        let links = random_links(points_count, max_levels_count, &hnsw_m);
+
+        atomic_save(&links_file, |writer| {
+            serialize_graph_links(
+                links.clone(),
+                GraphLinksFormatParam::CompressedVarint,
+                hnsw_m,
+                writer,
+            )
+        })
+        .unwrap();
+
+        let bytes = fs_err::read(&links_file).unwrap();
+        let version_offset = size_of::<u64>();
+        let version = u64::from_le_bytes(
+            bytes[version_offset..version_offset + size_of::<u64>()]
+                .try_into()
+                .unwrap(),
+        );
+        assert_eq!(version, super::header::HEADER_VERSION_COMPRESSED_VARINT);
+
+        let cmp_links =
+            GraphLinks::load_from_file(&links_file, true, GraphLinksFormat::Compressed).unwrap();
+        check_links(links, &cmp_links, &None);
+    }
+
     #[test]
     fn test_compressed_with_vectors_serialization_has_little_endian_versioned_header() {
         let hnsw_m = HnswM::new2(8);
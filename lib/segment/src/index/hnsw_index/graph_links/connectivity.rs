@@ -0,0 +1,131 @@
+use std::fmt;
+
+use bitvec::prelude::BitVec;
+use common::types::PointOffsetType;
+
+use super::GraphLinks;
+
+/// Maximal number of problematic points and links listed in [`GraphConnectivityReport`].
+const MAX_REPORTED_ITEMS: usize = 16;
+
+/// Result of [`GraphLinks::verify_connectivity`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GraphConnectivityReport {
+    /// Number of points, which are expected to be reachable from the entry point
+    pub expected_points: usize,
+    /// Number of expected points, which are reachable from the entry point on level 0
+    pub reachable_points: usize,
+    /// There are expected points, but the entry point is missing or is not in the graph
+    pub invalid_entry_point: bool,
+    /// Total number of expected points, which are not reachable from the entry point
+    pub orphaned_count: usize,
+    /// First few orphaned points
+    pub orphaned_points: Vec<PointOffsetType>,
+    /// Total number of links to points, which are not present on the level of the link
+    pub out_of_range_count: usize,
+    /// First few out of range links, as `(point_id, level, link)`
+    pub out_of_range_links: Vec<(PointOffsetType, usize, PointOffsetType)>,
+}
+
+impl GraphConnectivityReport {
+    pub fn is_ok(&self) -> bool {
+        !self.invalid_entry_point && self.orphaned_count == 0 && self.out_of_range_count == 0
+    }
+}
+
+impl fmt::Display for GraphConnectivityReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} of {} points reachable",
+            self.reachable_points, self.expected_points,
+        )?;
+        if self.invalid_entry_point {
+            write!(f, ", invalid entry point")?;
+        }
+        if self.orphaned_count > 0 {
+            write!(
+                f,
+                ", {} orphaned points (e.g. {:?})",
+                self.orphaned_count, self.orphaned_points,
+            )?;
+        }
+        if self.out_of_range_count > 0 {
+            write!(
+                f,
+                ", {} out of range links (e.g. {:?})",
+                self.out_of_range_count, self.out_of_range_links,
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl GraphLinks {
+    /// Check the structure of the graph, visits all links of the graph.
+    ///
+    /// - All links must point to points present on the level of the link.
+    /// - All points for which `is_expected` returns `true` must be reachable from
+    ///   `entry_point` on level 0.
+    pub fn verify_connectivity(
+        &self,
+        entry_point: Option<PointOffsetType>,
+        is_expected: impl Fn(PointOffsetType) -> bool,
+    ) -> GraphConnectivityReport {
+        let num_points = self.num_points();
+        let mut report = GraphConnectivityReport::default();
+
+        let is_valid_link = |link: PointOffsetType, level: usize| {
+            (link as usize) < num_points && self.point_level(link) >= level
+        };
+
+        for point_id in 0..num_points as PointOffsetType {
+            for level in 0..=self.point_level(point_id) {
+                self.for_each_link(point_id, level, |link| {
+                    if !is_valid_link(link, level) {
+                        report.out_of_range_count += 1;
+                        if report.out_of_range_links.len() < MAX_REPORTED_ITEMS {
+                            report.out_of_range_links.push((point_id, level, link));
+                        }
+                    }
+                });
+            }
+        }
+
+        // Traverse level 0 from the entry point
+        let mut reachable: BitVec = BitVec::repeat(false, num_points);
+        let mut queue = Vec::new();
+        if let Some(entry_point) = entry_point.filter(|&ep| (ep as usize) < num_points) {
+            reachable.set(entry_point as usize, true);
+            queue.push(entry_point);
+        }
+        while let Some(point_id) = queue.pop() {
+            self.for_each_link(point_id, 0, |link| {
+                if is_valid_link(link, 0) && !reachable[link as usize] {
+                    reachable.set(link as usize, true);
+                    queue.push(link);
+                }
+            });
+        }
+
+        for point_id in 0..num_points as PointOffsetType {
+            if !is_expected(point_id) {
+                continue;
+            }
+            report.expected_points += 1;
+            if reachable[point_id as usize] {
+                report.reachable_points += 1;
+            } else {
+                report.orphaned_count += 1;
+                if report.orphaned_points.len() < MAX_REPORTED_ITEMS {
+                    report.orphaned_points.push(point_id);
+                }
+            }
+        }
+
+        report.invalid_entry_point =
+            report.expected_points > 0 && entry_point.is_none_or(|ep| !is_valid_link(ep, 0));
+
+        report
+    }
+}
@@ -0,0 +1,193 @@
+use std::io::{BufWriter, Seek as _, SeekFrom, Write as _};
+use std::path::Path;
+
+use ahash::AHashMap;
+use common::types::PointOffsetType;
+use fs_err as fs;
+use fs_err::{File, OpenOptions};
+
+use crate::common::operation_error::{OperationError, OperationResult};
+
+/// Magic bytes at the beginning of the delta file.
+const DELTA_MAGIC: [u8; 4] = *b"hlnd";
+
+/// Version of the delta file format.
+const DELTA_VERSION: u32 = 1;
+
+/// `magic` + `version`
+const DELTA_HEADER_SIZE: usize = DELTA_MAGIC.len() + size_of::<u32>();
+
+/// `point_id` + `level` + `links count`
+const RECORD_HEADER_SIZE: usize = 3 * size_of::<u32>();
+
+/*
+Append-only log of links, which changed after the graph was serialized.
+
+header:  [magic: "hlnd"][version: u32 LE]
+record:  [point_id: u32 LE][level: u32 LE][count: u32 LE][links: count * u32 LE]
+
+Each record replaces links of the point on the given level, the last record wins.
+Records of points, which are not present in the base graph, add new points.
+A torn record at the end of the file (e.g. after a crash during append) is ignored.
+*/
+
+/// Links of the graph, updated on top of serialized [`super::GraphLinks`].
+#[derive(Debug, Default, Clone)]
+pub struct GraphLinksDelta {
+    links: AHashMap<(PointOffsetType, usize), Vec<PointOffsetType>>,
+    /// Highest level with updated links for each point
+    levels: AHashMap<PointOffsetType, usize>,
+    /// Number of points, including the ones not present in the base graph
+    num_points: usize,
+}
+
+impl GraphLinksDelta {
+    /// Load delta from the file, `None` if there is no file.
+    pub fn load(path: &Path) -> OperationResult<Option<Self>> {
+        if !path.exists() {
+            return Ok(None);
+        }
+        let data = fs::read(path)?;
+        let (delta, valid_len) = Self::parse(&data)?;
+        if valid_len < data.len() {
+            log::warn!(
+                "Ignoring {} trailing bytes of incomplete record in GraphLinks delta {}",
+                data.len() - valid_len,
+                path.display(),
+            );
+        }
+        Ok(Some(delta))
+    }
+
+    /// Parse delta from bytes, returns the delta and the length of complete records.
+    fn parse(data: &[u8]) -> OperationResult<(Self, usize)> {
+        if data.len() < DELTA_HEADER_SIZE || data[..DELTA_MAGIC.len()] != DELTA_MAGIC {
+            return Err(OperationError::service_error(
+                "Invalid GraphLinks delta file header",
+            ));
+        }
+        let version = read_u32(data, DELTA_MAGIC.len());
+        if version != DELTA_VERSION {
+            return Err(OperationError::service_error(format!(
+                "Unsupported GraphLinks delta version {version}, expected {DELTA_VERSION}",
+            )));
+        }
+
+        let mut delta = Self::default();
+        let mut pos = DELTA_HEADER_SIZE;
+        while pos + RECORD_HEADER_SIZE <= data.len() {
+            let point_id = read_u32(data, pos);
+            let level = read_u32(data, pos + size_of::<u32>()) as usize;
+            let count = read_u32(data, pos + 2 * size_of::<u32>()) as usize;
+            let links_start = pos + RECORD_HEADER_SIZE;
+            let links_end = links_start + count * size_of::<u32>();
+            if links_end > data.len() {
+                break;
+            }
+            let links = data[links_start..links_end]
+                .chunks_exact(size_of::<u32>())
+                .map(|chunk| u32::from_le_bytes(chunk.try_into().unwrap()))
+                .collect();
+            delta.set_links(point_id, level, links);
+            pos = links_end;
+        }
+        Ok((delta, pos))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.links.is_empty()
+    }
+
+    pub fn num_points(&self) -> usize {
+        self.num_points
+    }
+
+    pub fn set_links(
+        &mut self,
+        point_id: PointOffsetType,
+        level: usize,
+        links: Vec<PointOffsetType>,
+    ) {
+        self.links.insert((point_id, level), links);
+        let point_level = self.levels.entry(point_id).or_default();
+        *point_level = (*point_level).max(level);
+        self.num_points = self.num_points.max(point_id as usize + 1);
+    }
+
+    /// Updated links of the point, `None` if they are not changed.
+    pub fn links(&self, point_id: PointOffsetType, level: usize) -> Option<&[PointOffsetType]> {
+        self.links.get(&(point_id, level)).map(Vec::as_slice)
+    }
+
+    /// Highest level with updated links of the point.
+    pub fn point_level(&self, point_id: PointOffsetType) -> Option<usize> {
+        self.levels.get(&point_id).copied()
+    }
+}
+
+/// Appends records to the [`GraphLinksDelta`] file.
+pub struct GraphLinksDeltaWriter {
+    writer: BufWriter<File>,
+}
+
+impl GraphLinksDeltaWriter {
+    /// Open the delta file for appending, creating it if it doesn't exist.
+    ///
+    /// An incomplete record at the end of the file is truncated.
+    pub fn open(path: &Path) -> OperationResult<Self> {
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)?;
+
+        if file.metadata()?.len() == 0 {
+            let mut header = Vec::with_capacity(DELTA_HEADER_SIZE);
+            header.extend_from_slice(&DELTA_MAGIC);
+            header.extend_from_slice(&DELTA_VERSION.to_le_bytes());
+            file.write_all(&header)?;
+        } else {
+            let data = fs::read(path)?;
+            let (_, valid_len) = GraphLinksDelta::parse(&data)?;
+            if valid_len < data.len() {
+                file.set_len(valid_len as u64)?;
+            }
+        }
+        file.seek(SeekFrom::End(0))?;
+
+        Ok(Self {
+            writer: BufWriter::new(file),
+        })
+    }
+
+    pub fn append(
+        &mut self,
+        point_id: PointOffsetType,
+        level: usize,
+        links: &[PointOffsetType],
+    ) -> OperationResult<()> {
+        let level = u32::try_from(level)
+            .map_err(|_| OperationError::service_error("Too high level in GraphLinks delta"))?;
+        let count = u32::try_from(links.len())
+            .map_err(|_| OperationError::service_error("Too many links in GraphLinks delta"))?;
+        self.writer.write_all(&point_id.to_le_bytes())?;
+        self.writer.write_all(&level.to_le_bytes())?;
+        self.writer.write_all(&count.to_le_bytes())?;
+        for link in links {
+            self.writer.write_all(&link.to_le_bytes())?;
+        }
+        Ok(())
+    }
+
+    /// Write appended records to disk.
+    pub fn flush(&mut self) -> OperationResult<()> {
+        self.writer.flush()?;
+        self.writer.get_ref().sync_data()?;
+        Ok(())
+    }
+}
+
+fn read_u32(data: &[u8], pos: usize) -> u32 {
+    u32::from_le_bytes(data[pos..pos + size_of::<u32>()].try_into().unwrap())
+}
@@ -0,0 +1,123 @@
+use std::io::Write;
+
+use common::types::PointOffsetType;
+use serde::Serialize;
+use strum::EnumString;
+
+use super::GraphLinks;
+use crate::common::operation_error::OperationResult;
+use crate::types::PointIdType;
+
+/// Portable adjacency formats of [`GraphLinks::export`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumString)]
+#[strum(serialize_all = "lowercase")]
+pub enum GraphExportFormat {
+    /// One JSON object per point: internal offset, point id, level and links of each level
+    Jsonl,
+    /// Directed graph in GraphML, with the level of each link as an edge attribute
+    GraphMl,
+}
+
+/// Line of [`GraphExportFormat::Jsonl`].
+#[derive(Serialize)]
+struct JsonlPoint<'a> {
+    offset: PointOffsetType,
+    /// `None` if the point is deleted
+    id: Option<PointIdType>,
+    level: usize,
+    /// Links of each level, starting from level 0, as internal offsets
+    links: &'a [Vec<PointOffsetType>],
+}
+
+impl GraphLinks {
+    /// Write all links of the graph into `writer`, for analysis outside of Qdrant.
+    ///
+    /// Links refer to internal offsets, `external_id` maps them to point ids.
+    pub fn export(
+        &self,
+        format: GraphExportFormat,
+        external_id: impl Fn(PointOffsetType) -> Option<PointIdType>,
+        mut writer: impl Write,
+    ) -> OperationResult<()> {
+        match format {
+            GraphExportFormat::Jsonl => self.export_jsonl(external_id, &mut writer)?,
+            GraphExportFormat::GraphMl => self.export_graphml(external_id, &mut writer)?,
+        }
+        writer.flush()?;
+        Ok(())
+    }
+
+    fn export_jsonl(
+        &self,
+        external_id: impl Fn(PointOffsetType) -> Option<PointIdType>,
+        writer: &mut impl Write,
+    ) -> OperationResult<()> {
+        for point_id in 0..self.num_points() as PointOffsetType {
+            let level = self.point_level(point_id);
+            let links: Vec<Vec<_>> = (0..=level)
+                .map(|level| self.links(point_id, level).collect())
+                .collect();
+            let line = JsonlPoint {
+                offset: point_id,
+                id: external_id(point_id),
+                level,
+                links: &links,
+            };
+            serde_json::to_writer(&mut *writer, &line)?;
+            writeln!(writer)?;
+        }
+        Ok(())
+    }
+
+    fn export_graphml(
+        &self,
+        external_id: impl Fn(PointOffsetType) -> Option<PointIdType>,
+        writer: &mut impl Write,
+    ) -> OperationResult<()> {
+        writeln!(writer, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+        writeln!(
+            writer,
+            r#"<graphml xmlns="http://graphml.graphdrawing.org/xmlns">"#,
+        )?;
+        writeln!(
+            writer,
+            r#"  <key id="id" for="node" attr.name="id" attr.type="string"/>"#,
+        )?;
+        writeln!(
+            writer,
+            r#"  <key id="level" for="node" attr.name="level" attr.type="int"/>"#,
+        )?;
+        writeln!(
+            writer,
+            r#"  <key id="link_level" for="edge" attr.name="level" attr.type="int"/>"#,
+        )?;
+        writeln!(writer, r#"  <graph id="hnsw" edgedefault="directed">"#)?;
+
+        let num_points = self.num_points() as PointOffsetType;
+        for point_id in 0..num_points {
+            writeln!(writer, r#"    <node id="n{point_id}">"#)?;
+            // Point ids are numbers or UUIDs, so they don't need escaping
+            if let Some(id) = external_id(point_id) {
+                writeln!(writer, r#"      <data key="id">{id}</data>"#)?;
+            }
+            let level = self.point_level(point_id);
+            writeln!(writer, r#"      <data key="level">{level}</data>"#)?;
+            writeln!(writer, r"    </node>")?;
+        }
+
+        for point_id in 0..num_points {
+            for level in 0..=self.point_level(point_id) {
+                for link in self.links(point_id, level) {
+                    writeln!(
+                        writer,
+                        r#"    <edge source="n{point_id}" target="n{link}"><data key="link_level">{level}</data></edge>"#,
+                    )?;
+                }
+            }
+        }
+
+        writeln!(writer, "  </graph>")?;
+        writeln!(writer, "</graphml>")?;
+        Ok(())
+    }
+}
@@ -1,9 +1,10 @@
 use std::alloc::Layout;
 
 use common::bitpacking_ordered;
-use zerocopy::little_endian::U64 as LittleU64;
+use zerocopy::little_endian::{F64 as LittleF64, U64 as LittleU64};
 use zerocopy::{FromBytes, Immutable, IntoBytes, KnownLayout};
 
+use super::GraphLinksBaseVectorEncoding;
 use crate::common::operation_error::{OperationError, OperationResult};
 
 /// File header for the plain format.
@@ -26,7 +27,7 @@ pub(super) struct HeaderPlain {
 #[repr(C, align(8))]
 pub(super) struct HeaderCompressed {
     pub(super) point_count: LittleU64,
-    /// Should be [`HEADER_VERSION_COMPRESSED`].
+    /// Should be [`HEADER_VERSION_COMPRESSED`] or [`HEADER_VERSION_COMPRESSED_VARINT`].
     pub(super) version: LittleU64,
     pub(super) levels_count: LittleU64,
     pub(super) total_neighbors_bytes: LittleU64,
@@ -41,7 +42,8 @@ pub(super) struct HeaderCompressed {
 #[repr(C, align(8))]
 pub(super) struct HeaderCompressedWithVectors {
     pub(super) point_count: LittleU64,
-    /// Should be [`HEADER_VERSION_COMPRESSED_WITH_VECTORS`].
+    /// Should be [`HEADER_VERSION_COMPRESSED_WITH_VECTORS`] or
+    /// [`HEADER_VERSION_COMPRESSED_WITH_DEDUP_VECTORS`].
     pub(super) version: LittleU64,
     pub(super) levels_count: LittleU64,
     pub(super) total_neighbors_bytes: LittleU64,
@@ -50,7 +52,25 @@ pub(super) struct HeaderCompressedWithVectors {
     pub(super) m0: LittleU64,
     pub(super) base_vector_layout: PackedVectorLayout,
     pub(super) link_vector_layout: PackedVectorLayout,
-    pub(super) zero_padding: [u8; 3], // for 8-byte alignment
+    /// See [`pack_base_vector_encoding`]. Zero in files written before it was added.
+    pub(super) base_vector_encoding: u8,
+    /// Combination of `HEADER_FLAG_*` bits. Zero in files written before it was added.
+    pub(super) flags: u8,
+    pub(super) zero_padding: [u8; 1], // for 8-byte alignment
+}
+
+/// [`HeaderQuantizationMetadata`] is stored at the end of the file.
+pub(super) const HEADER_FLAG_QUANTIZATION_METADATA: u8 = 1;
+
+/// Quantization of link vectors in the compressed format with embedded vectors.
+///
+/// Stored at the end of the file, after the offsets, so that versions which don't know about it
+/// can still read the file. Present if [`HEADER_FLAG_QUANTIZATION_METADATA`] is set.
+#[derive(FromBytes, Immutable, IntoBytes, KnownLayout)]
+#[repr(C)]
+pub(super) struct HeaderQuantizationMetadata {
+    pub(super) rescore_oversampling: LittleF64,
+    pub(super) quantization_hash: LittleU64,
 }
 
 pub(super) const HEADER_VERSION_PLAIN: u64 = 0xFFFF_FFFF_FFFF_FF00;
@@ -58,6 +78,34 @@ pub(super) const HEADER_VERSION_COMPRESSED_LEGACY: u64 = 0xFFFF_FFFF_FFFF_FF01;
 pub(super) const HEADER_VERSION_COMPRESSED_WITH_VECTORS_LEGACY: u64 = 0xFFFF_FFFF_FFFF_FF02;
 pub(super) const HEADER_VERSION_COMPRESSED: u64 = 0xFFFF_FFFF_FFFF_FF03;
 pub(super) const HEADER_VERSION_COMPRESSED_WITH_VECTORS: u64 = 0xFFFF_FFFF_FFFF_FF04;
+/// Same header as [`HEADER_VERSION_COMPRESSED`], links are packed with
+/// [`common::varint_links::pack_links_varint`].
+pub(super) const HEADER_VERSION_COMPRESSED_VARINT: u64 = 0xFFFF_FFFF_FFFF_FF05;
+/// Same header as [`HEADER_VERSION_COMPRESSED_WITH_VECTORS`], link vectors are stored once per
+/// point in a table after the neighbors, instead of next to each link.
+pub(super) const HEADER_VERSION_COMPRESSED_WITH_DEDUP_VECTORS: u64 = 0xFFFF_FFFF_FFFF_FF06;
+
+const BASE_VECTOR_ENCODING_STORAGE: u8 = 0;
+const BASE_VECTOR_ENCODING_FLOAT16: u8 = 1;
+
+pub(super) fn pack_base_vector_encoding(encoding: GraphLinksBaseVectorEncoding) -> u8 {
+    match encoding {
+        GraphLinksBaseVectorEncoding::Storage => BASE_VECTOR_ENCODING_STORAGE,
+        GraphLinksBaseVectorEncoding::Float16 => BASE_VECTOR_ENCODING_FLOAT16,
+    }
+}
+
+pub(super) fn unpack_base_vector_encoding(
+    encoding: u8,
+) -> OperationResult<GraphLinksBaseVectorEncoding> {
+    match encoding {
+        BASE_VECTOR_ENCODING_STORAGE => Ok(GraphLinksBaseVectorEncoding::Storage),
+        BASE_VECTOR_ENCODING_FLOAT16 => Ok(GraphLinksBaseVectorEncoding::Float16),
+        encoding => Err(OperationError::service_error(format!(
+            "Unsupported base vector encoding in GraphLinks file: {encoding}"
+        ))),
+    }
+}
 
 /// Packed representation of [`Layout`].
 #[derive(Copy, Clone, FromBytes, Immutable, IntoBytes, KnownLayout)]
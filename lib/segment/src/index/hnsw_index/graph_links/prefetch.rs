@@ -0,0 +1,109 @@
+use std::fmt;
+use std::path::Path;
+
+use common::types::PointOffsetType;
+use fs_err::File;
+use parking_lot::Mutex;
+
+use super::GraphLinks;
+#[cfg(all(
+    target_os = "linux",
+    any(target_arch = "x86_64", target_arch = "aarch64")
+))]
+use super::uring_prefetch::UringPrefetcher;
+#[cfg(not(all(
+    target_os = "linux",
+    any(target_arch = "x86_64", target_arch = "aarch64")
+)))]
+use super::uring_prefetch_mock::UringPrefetcher;
+use crate::common::operation_error::OperationResult;
+
+/// Maximal number of idle prefetchers kept in [`LinksPrefetchPool`].
+const POOL_KEEP_LIMIT: usize = 16;
+
+/// Pool of io_uring prefetchers for links of a graph, which is read from disk.
+///
+/// Searches take a [`LinksPrefetcher`] to start reading links of the next hop while scoring the
+/// current one, so that reading them from the memory map doesn't block on a page fault.
+pub struct LinksPrefetchPool {
+    file: File,
+    pool: Mutex<Vec<UringPrefetcher>>,
+}
+
+impl fmt::Debug for LinksPrefetchPool {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LinksPrefetchPool")
+            .field("file", &self.file)
+            .finish_non_exhaustive()
+    }
+}
+
+impl LinksPrefetchPool {
+    /// Fails if io_uring is not available.
+    pub fn open(links_path: &Path) -> OperationResult<Self> {
+        let prefetcher = UringPrefetcher::new()?;
+        Ok(Self {
+            file: File::open(links_path)?,
+            pool: Mutex::new(vec![prefetcher]),
+        })
+    }
+
+    /// `links` must be loaded from the file of this pool.
+    pub fn get<'a>(&'a self, links: &'a GraphLinks) -> Option<LinksPrefetcher<'a>> {
+        let prefetcher = match self.pool.lock().pop() {
+            Some(prefetcher) => prefetcher,
+            None => match UringPrefetcher::new() {
+                Ok(prefetcher) => prefetcher,
+                Err(err) => {
+                    log::debug!("Failed to create links prefetcher, ignoring: {err}");
+                    return None;
+                }
+            },
+        };
+        Some(LinksPrefetcher {
+            pool: self,
+            links,
+            prefetcher: Some(prefetcher),
+            last: None,
+        })
+    }
+}
+
+/// Prefetcher of links, taken from [`LinksPrefetchPool`] for a single search.
+pub struct LinksPrefetcher<'a> {
+    pool: &'a LinksPrefetchPool,
+    links: &'a GraphLinks,
+    prefetcher: Option<UringPrefetcher>,
+    /// Last prefetched point and level, to skip repeated requests
+    last: Option<(PointOffsetType, usize)>,
+}
+
+impl LinksPrefetcher<'_> {
+    /// Start reading links of the point on the level from disk, without waiting.
+    pub fn prefetch(&mut self, point_id: PointOffsetType, level: usize) {
+        if self.last.replace((point_id, level)) == Some((point_id, level)) {
+            return;
+        }
+        let (Some(prefetcher), Some(range)) = (
+            self.prefetcher.as_mut(),
+            self.links.links_range(point_id, level),
+        ) else {
+            return;
+        };
+        if let Err(err) = prefetcher.prefetch(&self.pool.file, range.start as u64, range.len()) {
+            log::debug!("Failed to prefetch links, ignoring: {err}");
+        }
+    }
+}
+
+impl Drop for LinksPrefetcher<'_> {
+    fn drop(&mut self) {
+        let Some(prefetcher) = self.prefetcher.take() else {
+            return;
+        };
+        let mut pool = self.pool.pool.lock();
+        if pool.len() < POOL_KEEP_LIMIT {
+            pool.push(prefetcher);
+        }
+    }
+}
@@ -3,24 +3,28 @@ use std::cmp::Reverse;
 use std::io::{Seek, Write};
 
 use common::bitpacking::packed_bits;
-use common::bitpacking_links::{pack_links, MIN_BITS_PER_VALUE};
+use common::bitpacking_links::{MIN_BITS_PER_VALUE, pack_links};
 use common::bitpacking_ordered;
 use common::types::PointOffsetType;
+use common::varint_links::pack_links_varint;
 use common::zeros::WriteZerosExt;
 use integer_encoding::{VarInt, VarIntWriter};
 use itertools::Either;
-use zerocopy::little_endian::U64 as LittleU64;
 use zerocopy::IntoBytes as AsBytes;
+use zerocopy::little_endian::{F64 as LittleF64, U64 as LittleU64};
 
+use super::GraphLinksFormatParam;
 use super::header::{
-    HeaderCompressed, HeaderPlain, HEADER_VERSION_COMPRESSED, HEADER_VERSION_PLAIN,
+    HEADER_VERSION_COMPRESSED, HEADER_VERSION_COMPRESSED_VARINT, HEADER_VERSION_PLAIN,
+    HeaderCompressed, HeaderPlain,
 };
-use super::GraphLinksFormatParam;
 use crate::common::operation_error::{OperationError, OperationResult};
+use crate::index::hnsw_index::HnswM;
 use crate::index::hnsw_index::graph_links::header::{
-    HeaderCompressedWithVectors, PackedVectorLayout, HEADER_VERSION_COMPRESSED_WITH_VECTORS,
+    HEADER_FLAG_QUANTIZATION_METADATA, HEADER_VERSION_COMPRESSED_WITH_DEDUP_VECTORS,
+    HEADER_VERSION_COMPRESSED_WITH_VECTORS, HeaderCompressedWithVectors,
+    HeaderQuantizationMetadata, PackedVectorLayout, pack_base_vector_encoding,
 };
-use crate::index::hnsw_index::HnswM;
 
 pub fn serialize_graph_links<W: Write + Seek>(
     mut edges: Vec<Vec<Vec<PointOffsetType>>>,
@@ -31,10 +35,11 @@ pub fn serialize_graph_links<W: Write + Seek>(
     let bits_per_unsorted =
         packed_bits(u32::try_from(edges.len().saturating_sub(1)).unwrap()).max(MIN_BITS_PER_VALUE);
 
-    let vectors_layout = match format_param {
-        GraphLinksFormatParam::Plain => None,
-        GraphLinksFormatParam::Compressed => None,
-        GraphLinksFormatParam::CompressedWithVectors(v) => {
+    let (vectors_layout, quantization_metadata) = match format_param {
+        GraphLinksFormatParam::Plain => (None, None),
+        GraphLinksFormatParam::Compressed | GraphLinksFormatParam::CompressedVarint => (None, None),
+        GraphLinksFormatParam::CompressedWithVectors(v)
+        | GraphLinksFormatParam::CompressedWithDedupVectors(v) => {
             let vectors_layout = v.vectors_layout();
             if vectors_layout.base.size() % vectors_layout.base.align() != 0 {
                 return Err(OperationError::service_error(
@@ -46,7 +51,7 @@ pub fn serialize_graph_links<W: Write + Seek>(
                     "Link vector size must be a multiple of its alignment",
                 ));
             }
-            Some(vectors_layout)
+            (Some(vectors_layout), v.quantization_metadata())
         }
     };
 
@@ -64,8 +69,13 @@ pub fn serialize_graph_links<W: Write + Seek>(
     // 1. Write header (placeholder, will be rewritten later)
     writer.write_zeros(match &format_param {
         GraphLinksFormatParam::Plain => size_of::<HeaderPlain>(),
-        GraphLinksFormatParam::Compressed => size_of::<HeaderCompressed>(),
-        GraphLinksFormatParam::CompressedWithVectors(_) => size_of::<HeaderCompressedWithVectors>(),
+        GraphLinksFormatParam::Compressed | GraphLinksFormatParam::CompressedVarint => {
+            size_of::<HeaderCompressed>()
+        }
+        GraphLinksFormatParam::CompressedWithVectors(_)
+        | GraphLinksFormatParam::CompressedWithDedupVectors(_) => {
+            size_of::<HeaderCompressedWithVectors>()
+        }
     })?;
 
     // 2. Write level offsets
@@ -123,9 +133,19 @@ pub fn serialize_graph_links<W: Write + Seek>(
                     writer.write_all(&links_buf)?;
                     offset += links_buf.len();
                 }
-                GraphLinksFormatParam::CompressedWithVectors(vectors) => {
+                GraphLinksFormatParam::CompressedVarint => {
+                    pack_links_varint(&mut links_buf, &mut raw_links, level_m);
+                    writer.write_all(&links_buf)?;
+                    offset += links_buf.len();
+                }
+                GraphLinksFormatParam::CompressedWithVectors(vectors)
+                | GraphLinksFormatParam::CompressedWithDedupVectors(vectors) => {
                     // Unwrap safety: `vectors_layout` is `Some` for `CompressedWithVectors`.
                     let vectors_layout = vectors_layout.as_ref().unwrap();
+                    let inline_link_vectors = matches!(
+                        format_param,
+                        GraphLinksFormatParam::CompressedWithVectors(_)
+                    );
 
                     // 1. Base vector (`B` in the doc, only on level 0).
                     if level == 0 {
@@ -133,7 +153,7 @@ pub fn serialize_graph_links<W: Write + Seek>(
                         if vector.len() != vectors_layout.base.size() {
                             return Err(OperationError::service_error("Vector size mismatch"));
                         }
-                        writer.write_all(vector)?;
+                        writer.write_all(&vector)?;
                         offset += vector.len();
                     }
 
@@ -146,20 +166,23 @@ pub fn serialize_graph_links<W: Write + Seek>(
                     writer.write_all(&links_buf)?;
                     offset += links_buf.len();
 
-                    // 4. Padding to align link vectors (`_` in the doc).
-                    let padding = offset.next_multiple_of(vectors_layout.link.align()) - offset;
-                    writer.write_zeros(padding)?;
-                    offset += padding;
+                    // Deduplicated link vectors are written after all neighbors instead.
+                    if inline_link_vectors {
+                        // 4. Padding to align link vectors (`_` in the doc).
+                        let padding = offset.next_multiple_of(vectors_layout.link.align()) - offset;
+                        writer.write_zeros(padding)?;
+                        offset += padding;
 
-                    // 5. Link vectors (`L` in the doc).
-                    // Write them in the same order as `raw_links`.
-                    for i in raw_links {
-                        let vector = vectors.get_link_vector(i)?;
-                        if vector.len() != vectors_layout.link.size() {
-                            return Err(OperationError::service_error("Vector size mismatch"));
+                        // 5. Link vectors (`L` in the doc).
+                        // Write them in the same order as `raw_links`.
+                        for i in raw_links {
+                            let vector = vectors.get_link_vector(i)?;
+                            if vector.len() != vectors_layout.link.size() {
+                                return Err(OperationError::service_error("Vector size mismatch"));
+                            }
+                            writer.write_all(vector)?;
+                            offset += vector.len();
                         }
-                        writer.write_all(vector)?;
-                        offset += vector.len();
                     }
 
                     // 6. Padding to align the next base vector (`_` in the doc).
@@ -177,6 +200,21 @@ pub fn serialize_graph_links<W: Write + Seek>(
     }
     drop(back_index);
 
+    // 6. Write deduplicated link vectors, one per point (if applicable)
+    if let GraphLinksFormatParam::CompressedWithDedupVectors(vectors) = format_param {
+        // Unwrap safety: `vectors_layout` is `Some` for `CompressedWithDedupVectors`.
+        let vectors_layout = vectors_layout.as_ref().unwrap();
+        let pos = writer.stream_position()? as usize;
+        writer.write_zeros(pos.next_multiple_of(vectors_layout.link.align()) - pos)?;
+        for point_id in 0..edges.len() as PointOffsetType {
+            let vector = vectors.get_link_vector(point_id)?;
+            if vector.len() != vectors_layout.link.size() {
+                return Err(OperationError::service_error("Vector size mismatch"));
+            }
+            writer.write_all(vector)?;
+        }
+    }
+
     // 7. Write offsets (and get some info for the header)
     let (offsets_padding, offsets_parameters) = match &format_param {
         GraphLinksFormatParam::Plain => {
@@ -186,13 +224,25 @@ pub fn serialize_graph_links<W: Write + Seek>(
             write_u64_slice_le(writer, &offsets)?;
             (Some(offsets_padding), None)
         }
-        GraphLinksFormatParam::Compressed | GraphLinksFormatParam::CompressedWithVectors(_) => {
+        GraphLinksFormatParam::Compressed
+        | GraphLinksFormatParam::CompressedVarint
+        | GraphLinksFormatParam::CompressedWithVectors(_)
+        | GraphLinksFormatParam::CompressedWithDedupVectors(_) => {
             let (compressed_offsets, offsets_parameters) = bitpacking_ordered::compress(&offsets);
             writer.write_all(&compressed_offsets)?;
             (None, Some(offsets_parameters))
         }
     };
 
+    // 7.1 Write quantization metadata (if applicable)
+    if let Some(metadata) = &quantization_metadata {
+        let metadata = HeaderQuantizationMetadata {
+            rescore_oversampling: LittleF64::new(metadata.rescore_oversampling),
+            quantization_hash: LittleU64::new(metadata.quantization_hash),
+        };
+        writer.write_all(metadata.as_bytes())?;
+    }
+
     // 8. Write header (not a placeholder anymore)
     writer.seek(std::io::SeekFrom::Start(0))?;
     match format_param {
@@ -208,9 +258,13 @@ pub fn serialize_graph_links<W: Write + Seek>(
             };
             writer.write_all(header.as_bytes())?;
         }
-        GraphLinksFormatParam::Compressed => {
+        GraphLinksFormatParam::Compressed | GraphLinksFormatParam::CompressedVarint => {
+            let version = match format_param {
+                GraphLinksFormatParam::CompressedVarint => HEADER_VERSION_COMPRESSED_VARINT,
+                _ => HEADER_VERSION_COMPRESSED,
+            };
             let header = HeaderCompressed {
-                version: LittleU64::from(HEADER_VERSION_COMPRESSED),
+                version: LittleU64::from(version),
                 point_count: LittleU64::new(edges.len() as u64),
                 total_neighbors_bytes: LittleU64::new(offset as u64),
                 offsets_parameters: offsets_parameters.unwrap(),
@@ -221,10 +275,17 @@ pub fn serialize_graph_links<W: Write + Seek>(
             };
             writer.write_all(header.as_bytes())?;
         }
-        GraphLinksFormatParam::CompressedWithVectors(_) => {
+        GraphLinksFormatParam::CompressedWithVectors(_)
+        | GraphLinksFormatParam::CompressedWithDedupVectors(_) => {
             let vectors_layout = vectors_layout.as_ref().unwrap();
+            let version = match format_param {
+                GraphLinksFormatParam::CompressedWithDedupVectors(_) => {
+                    HEADER_VERSION_COMPRESSED_WITH_DEDUP_VECTORS
+                }
+                _ => HEADER_VERSION_COMPRESSED_WITH_VECTORS,
+            };
             let header = HeaderCompressedWithVectors {
-                version: LittleU64::from(HEADER_VERSION_COMPRESSED_WITH_VECTORS),
+                version: LittleU64::from(version),
                 point_count: LittleU64::new(edges.len() as u64),
                 total_neighbors_bytes: LittleU64::new(offset as u64),
                 offsets_parameters: offsets_parameters.unwrap(),
@@ -233,7 +294,13 @@ pub fn serialize_graph_links<W: Write + Seek>(
                 m0: LittleU64::new(hnsw_m.m0 as u64),
                 base_vector_layout: pack_layout(&vectors_layout.base),
                 link_vector_layout: pack_layout(&vectors_layout.link),
-                zero_padding: [0; 3],
+                base_vector_encoding: pack_base_vector_encoding(vectors_layout.base_encoding),
+                flags: if quantization_metadata.is_some() {
+                    HEADER_FLAG_QUANTIZATION_METADATA
+                } else {
+                    0
+                },
+                zero_padding: [0; 1],
             };
             writer.write_all(header.as_bytes())?;
         }
@@ -0,0 +1,87 @@
+use std::os::fd::AsRawFd;
+
+use fs_err::File;
+use io_uring::{IoUring, opcode, types};
+
+use crate::common::operation_error::{OperationError, OperationResult};
+
+/// Maximal number of reads in flight per prefetcher.
+const PREFETCH_QUEUE_DEPTH: usize = 8;
+
+/// Size of each read buffer, links of a point are only prefetched up to this size.
+const PREFETCH_BUFFER_SIZE: usize = 16 * 1024;
+
+/// Reads parts of a file into the page cache with io_uring, without waiting for completion.
+///
+/// The data read into the buffers is discarded. It is the page cache, which makes subsequent
+/// reads of the same pages through a memory map cheap.
+pub struct UringPrefetcher {
+    io_uring: IoUring,
+    buffers: Vec<Vec<u8>>,
+    free_buffer_ids: Vec<usize>,
+}
+
+impl UringPrefetcher {
+    pub fn new() -> OperationResult<Self> {
+        let io_uring = IoUring::new(PREFETCH_QUEUE_DEPTH as _)?;
+        Ok(Self {
+            io_uring,
+            buffers: vec![vec![0; PREFETCH_BUFFER_SIZE]; PREFETCH_QUEUE_DEPTH],
+            free_buffer_ids: (0..PREFETCH_QUEUE_DEPTH).collect(),
+        })
+    }
+
+    /// Start reading `len` bytes of `file` at `offset`.
+    ///
+    /// The request is dropped if all buffers are in flight.
+    pub fn prefetch(&mut self, file: &File, offset: u64, len: usize) -> OperationResult<()> {
+        self.reap_completed();
+
+        let Some(buffer_id) = self.free_buffer_ids.pop() else {
+            return Ok(());
+        };
+        let buffer = &mut self.buffers[buffer_id];
+
+        let read_e = opcode::Read::new(
+            types::Fd(file.as_raw_fd()),
+            buffer.as_mut_ptr(),
+            len.min(buffer.len()) as _,
+        )
+        .offset(offset)
+        .build()
+        .user_data(buffer_id as _);
+
+        // Safety: the buffer is not used until the read is completed, see `reap_completed`
+        unsafe {
+            self.io_uring.submission().push(&read_e).map_err(|err| {
+                OperationError::service_error(format!("Failed using io-uring: {err}"))
+            })?;
+        }
+        self.io_uring.submit()?;
+
+        Ok(())
+    }
+
+    /// Release buffers of completed reads.
+    fn reap_completed(&mut self) {
+        // Failed reads are ignored, the data is read through the memory map anyway
+        for entry in self.io_uring.completion() {
+            self.free_buffer_ids.push(entry.user_data() as usize);
+        }
+    }
+}
+
+impl Drop for UringPrefetcher {
+    fn drop(&mut self) {
+        // Buffers must outlive the reads in flight
+        while self.free_buffer_ids.len() < self.buffers.len() {
+            let in_flight = self.buffers.len() - self.free_buffer_ids.len();
+            if let Err(err) = self.io_uring.submit_and_wait(in_flight) {
+                log::warn!("Failed to wait for io_uring prefetch reads, leaking buffers: {err}");
+                std::mem::forget(std::mem::take(&mut self.buffers));
+                return;
+            }
+            self.reap_completed();
+        }
+    }
+}
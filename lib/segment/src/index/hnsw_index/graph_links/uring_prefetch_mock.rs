@@ -0,0 +1,22 @@
+#![allow(dead_code)] // The mock is unused on Linux, and so produces dead code warnings
+
+use fs_err::File;
+
+use crate::common::operation_error::{OperationError, OperationResult};
+
+// This is a mock implementation of the uring_prefetch module for those platforms that don't support io_uring.
+#[derive(Debug)]
+pub struct UringPrefetcher;
+
+impl UringPrefetcher {
+    pub fn new() -> OperationResult<Self> {
+        Err(OperationError::service_error(
+            "io_uring is not supported on this platform",
+        ))
+    }
+
+    #[allow(clippy::unnecessary_wraps)] // Mock has to follow the same signature as real `UringPrefetcher`
+    pub fn prefetch(&mut self, _file: &File, _offset: u64, _len: usize) -> OperationResult<()> {
+        Ok(())
+    }
+}
@@ -1,6 +1,6 @@
 use std::alloc::Layout;
 use std::borrow::Cow;
-use std::iter::{Copied, Zip};
+use std::iter::Copied;
 use std::mem::size_of;
 use std::num::NonZero;
 use std::sync::atomic::{AtomicU64, Ordering};
@@ -11,16 +11,23 @@ use common::bitpacking_links::{
 };
 use common::bitpacking_ordered;
 use common::types::PointOffsetType;
+use common::varint_links::{VarintLinksIterator, iterate_varint_links};
 use integer_encoding::VarInt as _;
 use itertools::{Either, Itertools as _};
-use zerocopy::{FromBytes, Immutable};
+use zerocopy::{FromBytes, Immutable, IntoBytes as _};
 
+use super::delta::GraphLinksDelta;
 use super::header::{
-    HEADER_VERSION_COMPRESSED, HEADER_VERSION_COMPRESSED_LEGACY,
+    HEADER_FLAG_QUANTIZATION_METADATA, HEADER_VERSION_COMPRESSED, HEADER_VERSION_COMPRESSED_LEGACY,
+    HEADER_VERSION_COMPRESSED_VARINT, HEADER_VERSION_COMPRESSED_WITH_DEDUP_VECTORS,
     HEADER_VERSION_COMPRESSED_WITH_VECTORS, HEADER_VERSION_COMPRESSED_WITH_VECTORS_LEGACY,
-    HEADER_VERSION_PLAIN, HeaderCompressed, HeaderPlain,
+    HEADER_VERSION_PLAIN, HeaderCompressed, HeaderPlain, HeaderQuantizationMetadata,
+    unpack_base_vector_encoding,
+};
+use super::{
+    GraphLinksBaseVectorEncoding, GraphLinksFallbackDecodeTelemetry, GraphLinksFormat,
+    GraphLinksQuantizationMetadata,
 };
-use super::{GraphLinksFallbackDecodeTelemetry, GraphLinksFormat};
 use crate::common::operation_error::{OperationError, OperationResult};
 use crate::index::hnsw_index::HnswM;
 use crate::index::hnsw_index::graph_links::header::HeaderCompressedWithVectors;
@@ -51,16 +58,65 @@ pub(super) struct GraphLinksView<'a> {
     /// - [`super::serialize_graph_links`] always writes `0` as the first element.
     /// - Additional element is added during deserialization.
     pub(super) level_offsets: Vec<u64>,
+    /// Links updated after the graph was serialized, take precedence over the serialized ones.
+    pub(super) delta: GraphLinksDelta,
+    /// Decoded with the legacy big-endian fallback.
+    pub(super) is_legacy_fallback: bool,
 }
 
 /// An iterator type returned by [`GraphLinksView::links`].
-pub type LinksIterator<'a> = Either<Copied<std::slice::Iter<'a, u32>>, PackedLinksIterator<'a>>;
+pub type LinksIterator<'a> = Either<
+    Copied<std::slice::Iter<'a, u32>>,
+    Either<PackedLinksIterator<'a>, VarintLinksIterator<'a>>,
+>;
 
 /// An iterator type returned by [`super::GraphLinks::links_with_vectors`].
 /// Iterates over pairs of ([`PointOffsetType`], `&[u8]`). The second element is
 /// quantized vector bytes.
-pub type LinksWithVectorsIterator<'a> =
-    Zip<PackedLinksIterator<'a>, std::slice::ChunksExact<'a, u8>>;
+pub struct LinksWithVectorsIterator<'a> {
+    links: PackedLinksIterator<'a>,
+    link_vectors: LinkVectors<'a>,
+}
+
+/// Source of link vectors in [`LinksWithVectorsIterator`].
+enum LinkVectors<'a> {
+    /// Stored next to the links, in the same order.
+    Inline(std::slice::ChunksExact<'a, u8>),
+    /// Table of link vectors of all points, indexed by point id.
+    Deduplicated { table: &'a [u8], size: usize },
+}
+
+impl<'a> LinksWithVectorsIterator<'a> {
+    fn into_links(self) -> PackedLinksIterator<'a> {
+        self.links
+    }
+}
+
+impl<'a> Iterator for LinksWithVectorsIterator<'a> {
+    type Item = (PointOffsetType, &'a [u8]);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let link = self.links.next()?;
+        let vector = match &mut self.link_vectors {
+            LinkVectors::Inline(chunks) => chunks.next()?,
+            LinkVectors::Deduplicated { table, size } => {
+                let start = link as usize * *size;
+                &table[start..start + *size]
+            }
+        };
+        Some((link, vector))
+    }
+}
+
+/// Encoding of links in [`CompressionInfo::Compressed`].
+#[derive(Debug, Clone, Copy)]
+pub(super) enum LinksCodec {
+    /// [`common::bitpacking_links::pack_links`]
+    BitPacking,
+    /// [`common::varint_links::pack_links_varint`]
+    Varint,
+}
 
 #[derive(Debug)]
 pub(super) enum CompressionInfo<'a> {
@@ -92,6 +148,7 @@ pub(super) enum CompressionInfo<'a> {
         offsets: bitpacking_ordered::Reader<'a>,
         hnsw_m: HnswM,
         bits_per_unsorted: u8,
+        codec: LinksCodec,
     },
     CompressedWithVectors {
         /// Compressed links with vectors.
@@ -105,7 +162,8 @@ pub(super) enum CompressionInfo<'a> {
         /// [neighbors for node 0][neighbors for node 1][neighbors for node 2]...
         /// ```
         /// Where:
-        /// 1. `B` is a base vector (i.e. `Vec<u8>` of fixed size).
+        /// 1. `B` is a base vector (i.e. `Vec<u8>` of fixed size), encoded as
+        ///    specified by `base_vector_encoding`.
         ///    Only present on level 0, omitted on higher levels.
         /// 2. `#` is a varint-encoded length.
         ///    This value == number of links == number of link vectors.
@@ -114,14 +172,27 @@ pub(super) enum CompressionInfo<'a> {
         /// 5. `L` are encoded link vectors, one per link (i.e. `Vec<Vec<u8>>`).
         /// 6. `_` is a padding to make the next base vector aligned.
         ///    Only present on level 0, omitted on higher levels.
+        ///
+        /// If link vectors are deduplicated, 4. and 5. are omitted, see `link_vectors`.
         neighbors: &'a [u8],
         offsets: bitpacking_ordered::Reader<'a>,
         hnsw_m: HnswM,
         bits_per_unsorted: u8,
         base_vector_layout: Layout,
+        base_vector_encoding: GraphLinksBaseVectorEncoding,
         /// `NonZero` to avoid handling unlikely corner cases.
         link_vector_size: NonZero<usize>,
         link_vector_alignment: u8,
+        /// Deduplicated link vectors, one per point, indexed by point id.
+        ///
+        /// ```text
+        /// [LLLLLL][LLLLLL][LLLLLL]...
+        /// [point0][point1][point2]...
+        /// ```
+        /// `None` if link vectors are stored next to the links.
+        link_vectors: Option<&'a [u8]>,
+        /// `None` in files written before it was stored.
+        quantization_metadata: Option<GraphLinksQuantizationMetadata>,
     },
 }
 
@@ -152,8 +223,9 @@ impl GraphLinksView<'_> {
         for endian in endians_to_try {
             let header = decode_plain_header(header_bytes, endian)?;
             match Self::load_plain_with_endian(bytes, header, endian) {
-                Ok(view) => {
+                Ok(mut view) => {
                     if matches!(endian, PlainEndian::Big) {
+                        view.is_legacy_fallback = true;
                         let prev =
                             LEGACY_PLAIN_BIG_ENDIAN_FALLBACK_LOADS.fetch_add(1, Ordering::Relaxed);
                         if prev == 0 {
@@ -194,10 +266,18 @@ impl GraphLinksView<'_> {
         }
 
         let (level_offsets_raw, bytes) = decode_u64_slice(bytes, header.levels_count, endian)?;
-        let (reindex, bytes) = decode_u32_slice(bytes, header.point_count, endian)?;
-        let (neighbors, bytes) = decode_u32_slice(bytes, header.total_neighbors_count, endian)?;
+        // Links in the host byte order are borrowed, so that they can be prefetched from disk
+        let (reindex, bytes) = get_point_offsets_with_endian(bytes, header.point_count, endian)?;
+        let (neighbors, bytes) =
+            get_point_offsets_with_endian(bytes, header.total_neighbors_count, endian)?;
         let (_padding, bytes) = split_prefix(bytes, header.offsets_padding_bytes as usize)?;
-        let (offsets, _bytes) = decode_u64_slice(bytes, header.total_offset_count, endian)?;
+        let (offsets, _bytes) = if endian_matches_host(endian) {
+            let (offsets, bytes) = get_slice::<u64>(bytes, header.total_offset_count)?;
+            (Cow::Borrowed(offsets), bytes)
+        } else {
+            let (offsets, bytes) = decode_u64_slice(bytes, header.total_offset_count, endian)?;
+            (Cow::Owned(offsets), bytes)
+        };
 
         validate_plain_layout(&header, &level_offsets_raw, &reindex, &offsets)?;
 
@@ -206,12 +286,11 @@ impl GraphLinksView<'_> {
         level_offsets.push(header.total_offset_count - 1);
 
         Ok(GraphLinksView {
-            reindex: Cow::Owned(reindex),
-            compression: CompressionInfo::Uncompressed {
-                neighbors: Cow::Owned(neighbors),
-                offsets: Cow::Owned(offsets),
-            },
+            reindex,
+            compression: CompressionInfo::Uncompressed { neighbors, offsets },
             level_offsets,
+            delta: GraphLinksDelta::default(),
+            is_legacy_fallback: false,
         })
     }
 
@@ -219,7 +298,7 @@ impl GraphLinksView<'_> {
         let (header, data) =
             HeaderCompressed::ref_from_prefix(data).map_err(|_| error_unsufficent_size())?;
         let endians_to_try: &[PlainEndian] = match header.version.get() {
-            HEADER_VERSION_COMPRESSED => &[PlainEndian::Little],
+            HEADER_VERSION_COMPRESSED | HEADER_VERSION_COMPRESSED_VARINT => &[PlainEndian::Little],
             HEADER_VERSION_COMPRESSED_LEGACY => &[PlainEndian::Little, PlainEndian::Big],
             version => {
                 return Err(OperationError::service_error(format!(
@@ -231,8 +310,9 @@ impl GraphLinksView<'_> {
         let mut first_error = None;
         for endian in endians_to_try {
             match Self::load_compressed_with_endian(data, &header, *endian) {
-                Ok(view) => {
+                Ok(mut view) => {
                     if matches!(endian, PlainEndian::Big) {
+                        view.is_legacy_fallback = true;
                         let prev = LEGACY_COMPRESSED_BIG_ENDIAN_FALLBACK_LOADS
                             .fetch_add(1, Ordering::Relaxed);
                         if prev == 0 {
@@ -259,10 +339,23 @@ impl GraphLinksView<'_> {
     fn load_compressed_with_vectors(data: &[u8]) -> OperationResult<GraphLinksView<'_>> {
         let total_len = data.len();
 
+        let quantization_metadata = match HeaderCompressedWithVectors::ref_from_prefix(data) {
+            Ok((header, _)) if header.flags & HEADER_FLAG_QUANTIZATION_METADATA != 0 => {
+                let (_, metadata) = HeaderQuantizationMetadata::ref_from_suffix(data)
+                    .map_err(|_| error_unsufficent_size())?;
+                Some(GraphLinksQuantizationMetadata {
+                    rescore_oversampling: metadata.rescore_oversampling.get(),
+                    quantization_hash: metadata.quantization_hash.get(),
+                })
+            }
+            _ => None,
+        };
+
         let (header, data) = HeaderCompressedWithVectors::ref_from_prefix(data)
             .map_err(|_| error_unsufficent_size())?;
         let endians_to_try: &[PlainEndian] = match header.version.get() {
-            HEADER_VERSION_COMPRESSED_WITH_VECTORS => &[PlainEndian::Little],
+            HEADER_VERSION_COMPRESSED_WITH_VECTORS
+            | HEADER_VERSION_COMPRESSED_WITH_DEDUP_VECTORS => &[PlainEndian::Little],
             HEADER_VERSION_COMPRESSED_WITH_VECTORS_LEGACY => {
                 &[PlainEndian::Little, PlainEndian::Big]
             }
@@ -275,6 +368,7 @@ impl GraphLinksView<'_> {
 
         let base_vector_layout = header.base_vector_layout.try_into_layout()?;
         let link_vector_layout = header.link_vector_layout.try_into_layout()?;
+        let base_vector_encoding = unpack_base_vector_encoding(header.base_vector_encoding)?;
 
         let mut first_error = None;
         for endian in endians_to_try {
@@ -283,11 +377,20 @@ impl GraphLinksView<'_> {
                 total_len,
                 &header,
                 base_vector_layout,
+                base_vector_encoding,
                 link_vector_layout,
                 *endian,
             ) {
-                Ok(view) => {
+                Ok(mut view) => {
+                    if let CompressionInfo::CompressedWithVectors {
+                        quantization_metadata: view_quantization_metadata,
+                        ..
+                    } = &mut view.compression
+                    {
+                        *view_quantization_metadata = quantization_metadata;
+                    }
                     if matches!(endian, PlainEndian::Big) {
+                        view.is_legacy_fallback = true;
                         let prev = LEGACY_COMPRESSED_WITH_VECTORS_BIG_ENDIAN_FALLBACK_LOADS
                             .fetch_add(1, Ordering::Relaxed);
                         if prev == 0 {
@@ -342,7 +445,7 @@ impl GraphLinksView<'_> {
             // Canonical format: offsets are written little-endian; validate fully in debug/tests.
             // In release builds, prefer constant-time checks to avoid O(n) startup cost on large
             // indices.
-            HEADER_VERSION_COMPRESSED => cfg!(debug_assertions),
+            HEADER_VERSION_COMPRESSED | HEADER_VERSION_COMPRESSED_VARINT => cfg!(debug_assertions),
             // Legacy format: offsets may come from older writers; keep full validation.
             HEADER_VERSION_COMPRESSED_LEGACY => true,
             version => {
@@ -370,8 +473,14 @@ impl GraphLinksView<'_> {
                         OperationError::service_error("Too many points in GraphLinks file")
                     })?,
                 )),
+                codec: match header.version.get() {
+                    HEADER_VERSION_COMPRESSED_VARINT => LinksCodec::Varint,
+                    _ => LinksCodec::BitPacking,
+                },
             },
             level_offsets,
+            delta: GraphLinksDelta::default(),
+            is_legacy_fallback: false,
         })
     }
 
@@ -380,6 +489,7 @@ impl GraphLinksView<'_> {
         total_len: usize,
         header: &HeaderCompressedWithVectors,
         base_vector_layout: Layout,
+        base_vector_encoding: GraphLinksBaseVectorEncoding,
         link_vector_layout: Layout,
         endian: PlainEndian,
     ) -> OperationResult<GraphLinksView<'a>> {
@@ -404,12 +514,27 @@ impl GraphLinksView<'_> {
             (pos.next_multiple_of(alignment) - pos) as u64
         })?;
         let (neighbors, data) = get_slice::<u8>(data, header.total_neighbors_bytes.get())?;
+        let (link_vectors, data) =
+            if header.version.get() == HEADER_VERSION_COMPRESSED_WITH_DEDUP_VECTORS {
+                let (_, data) = get_slice::<u8>(data, {
+                    let pos = total_len - data.len();
+                    (pos.next_multiple_of(link_vector_layout.align()) - pos) as u64
+                })?;
+                let table_len = point_count
+                    .checked_mul(link_vector_layout.size() as u64)
+                    .ok_or_else(error_unsufficent_size)?;
+                let (link_vectors, data) = get_slice::<u8>(data, table_len)?;
+                (Some(link_vectors), data)
+            } else {
+                (None, data)
+            };
         let (offsets, _bytes) = bitpacking_ordered::Reader::new(header.offsets_parameters, data)
             .map_err(|e| {
                 OperationError::service_error(format!("Can't create decompressor: {e}"))
             })?;
         let full_offsets_validation = match header.version.get() {
-            HEADER_VERSION_COMPRESSED_WITH_VECTORS => cfg!(debug_assertions),
+            HEADER_VERSION_COMPRESSED_WITH_VECTORS
+            | HEADER_VERSION_COMPRESSED_WITH_DEDUP_VECTORS => cfg!(debug_assertions),
             HEADER_VERSION_COMPRESSED_WITH_VECTORS_LEGACY => true,
             version => {
                 return Err(OperationError::service_error(format!(
@@ -437,15 +562,110 @@ impl GraphLinksView<'_> {
                     })?,
                 )),
                 base_vector_layout,
+                base_vector_encoding,
                 link_vector_size: NonZero::try_from(link_vector_layout.size()).map_err(|_| {
                     OperationError::service_error("Zero link vector size in GraphLinks file")
                 })?,
                 link_vector_alignment: link_vector_layout.align() as u8,
+                link_vectors,
+                // Set by the caller, as it is not affected by the endianness
+                quantization_metadata: None,
             },
             level_offsets,
+            delta: GraphLinksDelta::default(),
+            is_legacy_fallback: false,
         })
     }
 
+    /// Apply links updated after the graph was serialized.
+    ///
+    /// Not supported for formats with vectors, as the delta doesn't store them.
+    pub(super) fn set_delta(&mut self, delta: GraphLinksDelta) -> OperationResult<()> {
+        if matches!(
+            self.compression,
+            CompressionInfo::CompressedWithVectors { .. }
+        ) {
+            return Err(OperationError::service_error(
+                "GraphLinks delta is not supported for format with vectors",
+            ));
+        }
+        self.delta = delta;
+        Ok(())
+    }
+
+    pub(super) fn num_points(&self) -> usize {
+        self.reindex.len().max(self.delta.num_points())
+    }
+
+    /// Serialized links of level 0, including base and link vectors, if any.
+    ///
+    /// Links of level 0 are stored before the links of the upper levels, see
+    /// [`super::serialize_graph_links`].
+    pub(super) fn level0_neighbors_bytes(&self) -> &[u8] {
+        // `level_offsets[1]` is either the start of level 1, or the additional element
+        let end_idx = self.level_offsets[1] as usize;
+        match &self.compression {
+            CompressionInfo::Uncompressed { neighbors, offsets } => {
+                neighbors[offsets[0] as usize..offsets[end_idx] as usize].as_bytes()
+            }
+            CompressionInfo::Compressed {
+                neighbors, offsets, ..
+            }
+            | CompressionInfo::CompressedWithVectors {
+                neighbors, offsets, ..
+            } => {
+                let start = offsets.get(0).unwrap() as usize;
+                let end = offsets.get(end_idx).unwrap() as usize;
+                &neighbors[start..end]
+            }
+        }
+    }
+
+    /// Serialized links of the point, including link vectors, if any.
+    ///
+    /// `None` if the links of the point are taken from the delta.
+    pub(super) fn serialized_links_bytes(
+        &self,
+        point_id: PointOffsetType,
+        level: usize,
+    ) -> Option<&[u8]> {
+        if self.delta_links(point_id, level).is_some() {
+            return None;
+        }
+        let idx = self.offset_idx(point_id, level);
+        let bytes = match &self.compression {
+            CompressionInfo::Uncompressed { neighbors, offsets } => {
+                neighbors[offsets[idx] as usize..offsets[idx + 1] as usize].as_bytes()
+            }
+            CompressionInfo::Compressed {
+                neighbors, offsets, ..
+            }
+            | CompressionInfo::CompressedWithVectors {
+                neighbors, offsets, ..
+            } => {
+                let start = offsets.get(idx).unwrap() as usize;
+                let end = offsets.get(idx + 1).unwrap() as usize;
+                &neighbors[start..end]
+            }
+        };
+        Some(bytes)
+    }
+
+    /// Links of the point from the delta, `None` if the serialized links apply.
+    #[inline]
+    fn delta_links(&self, point_id: PointOffsetType, level: usize) -> Option<&[PointOffsetType]> {
+        if self.delta.is_empty() {
+            return None;
+        }
+        if let Some(links) = self.delta.links(point_id, level) {
+            return Some(links);
+        }
+        // Levels, which the point doesn't have in the serialized graph
+        let in_base = (point_id as usize) < self.reindex.len()
+            && (level == 0 || level <= self.base_point_level(point_id));
+        if in_base { None } else { Some(&[]) }
+    }
+
     /// Note: it is safe to use `idx + 1` on the result of this function,
     /// because `level_offsets` always contains an additional element.
     #[inline]
@@ -459,6 +679,9 @@ impl GraphLinksView<'_> {
 
     /// Returns `true` if [`Self::links`] would return an empty iterator.
     pub(super) fn links_empty(&self, point_id: PointOffsetType, level: usize) -> bool {
+        if let Some(links) = self.delta_links(point_id, level) {
+            return links.is_empty();
+        }
         let idx = self.offset_idx(point_id, level);
         match &self.compression {
             CompressionInfo::Uncompressed { offsets, .. } => offsets[idx] == offsets[idx + 1],
@@ -473,6 +696,9 @@ impl GraphLinksView<'_> {
     }
 
     pub(super) fn links(&self, point_id: PointOffsetType, level: usize) -> LinksIterator<'_> {
+        if let Some(links) = self.delta_links(point_id, level) {
+            return Either::Left(links.iter().copied());
+        }
         let idx = self.offset_idx(point_id, level);
         match &self.compression {
             CompressionInfo::Uncompressed { neighbors, offsets } => {
@@ -484,29 +710,35 @@ impl GraphLinksView<'_> {
                 offsets,
                 hnsw_m,
                 bits_per_unsorted,
+                codec,
             } => {
                 let neighbors_range =
                     offsets.get(idx).unwrap() as usize..offsets.get(idx + 1).unwrap() as usize;
-                Either::Right(iterate_packed_links(
-                    &neighbors[neighbors_range],
-                    *bits_per_unsorted,
-                    hnsw_m.level_m(level),
-                ))
+                let links = &neighbors[neighbors_range];
+                match codec {
+                    LinksCodec::BitPacking => Either::Right(Either::Left(iterate_packed_links(
+                        links,
+                        *bits_per_unsorted,
+                        hnsw_m.level_m(level),
+                    ))),
+                    LinksCodec::Varint => Either::Right(Either::Right(iterate_varint_links(
+                        links,
+                        hnsw_m.level_m(level),
+                    ))),
+                }
             }
             CompressionInfo::CompressedWithVectors { .. } => {
                 // Not intended to be used outside of tests.
-                Either::Right(self.links_with_vectors(point_id, level).1)
+                Either::Right(Either::Left(
+                    self.links_with_vectors(point_id, level).1.into_links(),
+                ))
             }
         }
     }
 
-    /// Returns a tuple of three elements:
+    /// Returns a tuple of two elements:
     /// - Base vector (only on level 0, empty slice on higher levels).
-    /// - Links iterator.
-    /// - Link vectors iterator.
-    ///
-    /// Both iterators have same length and can be combined into
-    /// [`LinksWithVectorsIterator`].
+    /// - Iterator over links with their vectors.
     ///
     /// # Panics
     ///
@@ -515,11 +747,7 @@ impl GraphLinksView<'_> {
         &self,
         point_id: PointOffsetType,
         level: usize,
-    ) -> (
-        &[u8],
-        PackedLinksIterator<'_>,
-        std::slice::ChunksExact<'_, u8>,
-    ) {
+    ) -> (&[u8], LinksWithVectorsIterator<'_>) {
         let idx = self.offset_idx(point_id, level);
         match &self.compression {
             CompressionInfo::Uncompressed { .. } => unimplemented!(),
@@ -530,8 +758,11 @@ impl GraphLinksView<'_> {
                 hnsw_m,
                 bits_per_unsorted,
                 base_vector_layout,
+                base_vector_encoding: _,
                 link_vector_size,
                 link_vector_alignment,
+                link_vectors,
+                quantization_metadata: _,
             } => {
                 let start = offsets.get(idx).unwrap() as usize;
                 let end = offsets.get(idx + 1).unwrap() as usize;
@@ -572,24 +803,49 @@ impl GraphLinksView<'_> {
                 );
                 pos += links_size;
 
-                // 4. Padding to align link vectors (`_` in the doc).
-                pos = pos.next_multiple_of(*link_vector_alignment as usize);
-
-                // 5. Link vectors (`L` in the doc).
-                let link_vector_bytes = (neighbors_count as usize) * link_vector_size.get();
-                let link_vectors = &neighbors[pos..pos + link_vector_bytes];
-                debug_assert!(link_vectors.as_ptr().addr() % *link_vector_alignment as usize == 0);
+                let link_vectors = match link_vectors {
+                    Some(table) => LinkVectors::Deduplicated {
+                        table,
+                        size: link_vector_size.get(),
+                    },
+                    None => {
+                        // 4. Padding to align link vectors (`_` in the doc).
+                        pos = pos.next_multiple_of(*link_vector_alignment as usize);
+
+                        // 5. Link vectors (`L` in the doc).
+                        let link_vector_bytes = (neighbors_count as usize) * link_vector_size.get();
+                        let link_vectors = &neighbors[pos..pos + link_vector_bytes];
+                        debug_assert!(
+                            link_vectors.as_ptr().addr() % *link_vector_alignment as usize == 0
+                        );
+                        LinkVectors::Inline(link_vectors.chunks_exact(link_vector_size.get()))
+                    }
+                };
 
                 (
                     base_vector,
-                    links,
-                    link_vectors.chunks_exact(link_vector_size.get()),
+                    LinksWithVectorsIterator {
+                        links,
+                        link_vectors,
+                    },
                 )
             }
         }
     }
 
     pub(super) fn point_level(&self, point_id: PointOffsetType) -> usize {
+        if self.delta.is_empty() {
+            return self.base_point_level(point_id);
+        }
+        let base_level =
+            ((point_id as usize) < self.reindex.len()).then(|| self.base_point_level(point_id));
+        match (base_level, self.delta.point_level(point_id)) {
+            (Some(base_level), Some(delta_level)) => base_level.max(delta_level),
+            (base_level, delta_level) => base_level.or(delta_level).unwrap_or(0),
+        }
+    }
+
+    fn base_point_level(&self, point_id: PointOffsetType) -> usize {
         let reindexed_point_id = u64::from(self.reindex[point_id as usize]);
         for (level, (&a, &b)) in self
             .level_offsets
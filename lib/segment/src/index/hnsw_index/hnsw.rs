@@ -1,8 +1,10 @@
+use std::io::Write;
 use std::ops::Deref as _;
 use std::path::{Path, PathBuf};
-use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, OnceLock};
 use std::thread;
+use std::time::{Duration, Instant};
 
 use atomic_refcell::{AtomicRef, AtomicRefCell};
 use bitvec::prelude::BitSlice;
@@ -14,11 +16,13 @@ use common::cpu::linux_low_thread_priority;
 use common::ext::BitSliceExt as _;
 use common::flags::FeatureFlags;
 use common::fs::clear_disk_cache;
-use common::progress_tracker::ProgressTracker;
+use common::mmap::Advice;
+use common::progress_tracker::{ProgressTracker, new_progress_tracker};
 use common::types::{PointOffsetType, ScoredPointOffset, TelemetryDetail};
 use fs_err as fs;
 use itertools::EitherOrBoth;
 use log::{debug, trace};
+use ordered_float::OrderedFloat;
 use parking_lot::Mutex;
 use rand::Rng;
 use rayon::ThreadPool;
@@ -30,17 +34,19 @@ use super::gpu::gpu_insert_context::GpuInsertContext;
 #[cfg(feature = "gpu")]
 use super::gpu::gpu_vector_storage::GpuVectorStorage;
 use super::point_scorer::BatchFilteredSearcher;
-use crate::common::BYTES_IN_KB;
 use crate::common::operation_error::{OperationError, OperationResult, check_process_stopped};
 use crate::common::operation_time_statistics::{
     OperationDurationsAggregator, ScopeDurationMeasurer,
 };
+use crate::common::{BYTES_IN_KB, Flusher};
 use crate::data_types::query_context::VectorQueryContext;
 use crate::data_types::vectors::{QueryVector, VectorInternal, VectorRef};
 use crate::id_tracker::IdTrackerSS;
 use crate::index::hnsw_index::HnswM;
+use crate::index::hnsw_index::build_checkpoint::BuildCheckpoint;
 use crate::index::hnsw_index::build_condition_checker::BuildConditionChecker;
 use crate::index::hnsw_index::config::HnswGraphConfig;
+use crate::index::hnsw_index::ef_tuner::EfTuner;
 #[cfg(feature = "gpu")]
 use crate::index::hnsw_index::gpu::gpu_graph_builder::GPU_MAX_VISITED_FLAGS_FACTOR;
 #[cfg(feature = "gpu")]
@@ -50,7 +56,10 @@ use crate::index::hnsw_index::graph_layers::{
 };
 use crate::index::hnsw_index::graph_layers_builder::GraphLayersBuilder;
 use crate::index::hnsw_index::graph_layers_healer::GraphLayersHealer;
-use crate::index::hnsw_index::graph_links::{GraphLinksFormatParam, StorageGraphLinksVectors};
+use crate::index::hnsw_index::graph_links::{
+    GraphConnectivityReport, GraphExportFormat, GraphLinksBaseVectorEncoding,
+    GraphLinksDeltaWriter, GraphLinksFormatParam, StorageGraphLinksVectors, quantization_hash,
+};
 use crate::index::hnsw_index::point_scorer::FilteredScorer;
 use crate::index::query_estimator::adjust_to_available_vectors;
 use crate::index::sample_estimation::sample_check_cardinality;
@@ -63,15 +72,16 @@ use crate::index::{PayloadIndex, VectorIndex, VectorIndexEnum};
 use crate::json_path::JsonPath;
 use crate::payload_storage::FilterContext;
 use crate::segment_constructor::VectorIndexBuildArgs;
-use crate::telemetry::VectorIndexSearchesTelemetry;
+use crate::telemetry::{HnswLinksTelemetry, VectorIndexSearchesTelemetry};
 use crate::types::Condition::Field;
 use crate::types::{
     ACORN_MAX_SELECTIVITY_DEFAULT, FieldCondition, Filter, HnswConfig, HnswGlobalConfig,
-    QuantizationSearchParams, SearchParams,
+    PopulatePolicy, QuantizationSearchParams, SearchParams,
 };
 use crate::vector_storage::quantized::quantized_vectors::QuantizedVectors;
 use crate::vector_storage::query::DiscoveryQuery;
-use crate::vector_storage::{VectorStorage, VectorStorageEnum, new_raw_scorer};
+use crate::vector_storage::query_scorer::{Float16QueryScorerBytes, QueryScorerBytes};
+use crate::vector_storage::{DEFAULT_STOPPED, VectorStorage, VectorStorageEnum, new_raw_scorer};
 
 const HNSW_USE_HEURISTIC: bool = true;
 const FINISH_MAIN_GRAPH_LOG_MESSAGE: &str = "Finish main graph in time";
@@ -83,7 +93,26 @@ pub const SINGLE_THREADED_HNSW_BUILD_THRESHOLD: usize = 32;
 #[cfg(not(debug_assertions))]
 pub const SINGLE_THREADED_HNSW_BUILD_THRESHOLD: usize = 256;
 
-const LINK_COMPRESSION_CONVERT_EXISTING: bool = false;
+/// Number of points linked in parallel between checks for a build checkpoint.
+const BUILD_CHECKPOINT_CHUNK_SIZE: usize = 10_000;
+/// Minimal interval between build checkpoints, see [`BuildCheckpoint`].
+const BUILD_CHECKPOINT_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Filters with cardinality below `full_scan_threshold`, but not lower than
+/// `full_scan_threshold / ACORN_MODERATE_CARDINALITY_DIVISOR`, are searched with ACORN
+/// instead of plain search, if ACORN is enabled in search params.
+const ACORN_MODERATE_CARDINALITY_DIVISOR: usize = 4;
+
+/// How the graph is traversed by a filtered search.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FilteredSearchStrategy {
+    /// Regular HNSW search, switched to ACORN if selectivity of the filter is below
+    /// [`crate::types::AcornSearchParams::max_selectivity`].
+    Auto,
+    /// Always expand through filtered-out neighbors without scoring them.
+    /// Used for filters of moderate cardinality, which would otherwise fall back to plain search.
+    Acorn,
+}
 
 #[derive(Debug)]
 pub struct HNSWIndex {
@@ -95,7 +124,18 @@ pub struct HNSWIndex {
     path: PathBuf,
     graph: GraphLayers,
     searches_telemetry: HNSWSearchesTelemetry,
+    /// Structure of graph links, collected on the first telemetry request, as it visits all links
+    links_telemetry: OnceLock<HnswLinksTelemetry>,
+    /// Tuner of default `ef`, if target recall is configured
+    ef_tuner: Option<EfTuner>,
     is_on_disk: bool,
+    /// Link vectors stored in the graph are encoded like the quantized vectors, see
+    /// [`Self::check_inline_vectors`]
+    inline_vectors_compatible: bool,
+    /// Number of deleted vectors, whose neighbors are re-linked by
+    /// [`Self::heal_deleted_points`]. Not persisted, healing after reload only patches links of
+    /// points deleted since.
+    healed_deleted_count: usize,
 }
 
 #[derive(Debug)]
@@ -105,6 +145,7 @@ struct HNSWSearchesTelemetry {
     unfiltered_hnsw: Arc<Mutex<OperationDurationsAggregator>>,
     small_cardinality: Arc<Mutex<OperationDurationsAggregator>>,
     large_cardinality: Arc<Mutex<OperationDurationsAggregator>>,
+    acorn: Arc<Mutex<OperationDurationsAggregator>>,
     exact_filtered: Arc<Mutex<OperationDurationsAggregator>>,
     exact_unfiltered: Arc<Mutex<OperationDurationsAggregator>>,
 }
@@ -117,6 +158,7 @@ impl HNSWSearchesTelemetry {
             unfiltered_hnsw: OperationDurationsAggregator::new(),
             small_cardinality: OperationDurationsAggregator::new(),
             large_cardinality: OperationDurationsAggregator::new(),
+            acorn: OperationDurationsAggregator::new(),
             exact_filtered: OperationDurationsAggregator::new(),
             exact_unfiltered: OperationDurationsAggregator::new(),
         }
@@ -133,7 +175,13 @@ pub struct HnswIndexOpenArgs<'a> {
 }
 
 impl HNSWIndex {
-    pub fn open(args: HnswIndexOpenArgs<'_>) -> OperationResult<Self> {
+    /// Open the index from disk.
+    ///
+    /// Graph links are populated according to `populate`, by default only if they are not on disk.
+    pub fn open(
+        args: HnswIndexOpenArgs<'_>,
+        populate: Option<PopulatePolicy>,
+    ) -> OperationResult<Self> {
         let HnswIndexOpenArgs {
             path,
             id_tracker,
@@ -162,6 +210,7 @@ impl HNSWIndex {
 
             HnswGraphConfig::new(
                 hnsw_config.m,
+                hnsw_config.m0,
                 hnsw_config.ef_construct,
                 full_scan_threshold,
                 hnsw_config.max_indexing_threads,
@@ -170,13 +219,38 @@ impl HNSWIndex {
             )
         };
 
-        let do_convert = LINK_COMPRESSION_CONVERT_EXISTING;
-
         let is_on_disk = hnsw_config.on_disk.unwrap_or(false);
+        let populate_prioritized = populate == Some(PopulatePolicy::Prioritized);
+        let populate_links =
+            !populate_prioritized && PopulatePolicy::populate_on_load(populate, !is_on_disk);
+
+        let graph = GraphLayers::load(path, is_on_disk, populate_links)?;
+        if (graph.hnsw_m.m, graph.hnsw_m.m0) != (config.m, config.m0) {
+            return Err(OperationError::service_error(format!(
+                "HNSW graph at {} is built with m={}, m0={}, but its config has m={}, m0={}",
+                path.display(),
+                graph.hnsw_m.m,
+                graph.hnsw_m.m0,
+                config.m,
+                config.m0,
+            )));
+        }
+        if populate_prioritized {
+            // Level 0 links keep being populated in the background, the thread is detached
+            let (_, progress) = new_progress_tracker();
+            graph.populate_prioritized(progress)?;
+        }
+        let ef_tuner = hnsw_config
+            .target_recall
+            .map(|OrderedFloat(target_recall)| EfTuner::open(path, target_recall, config.ef));
+        let inline_vectors_compatible = Self::check_inline_vectors(
+            path,
+            &graph,
+            &vector_storage.borrow(),
+            quantized_vectors.borrow().as_ref(),
+        );
 
-        let graph = GraphLayers::load(path, is_on_disk, do_convert)?;
-
-        Ok(HNSWIndex {
+        let index = HNSWIndex {
             id_tracker,
             vector_storage,
             quantized_vectors,
@@ -185,16 +259,41 @@ impl HNSWIndex {
             path: path.to_owned(),
             graph,
             searches_telemetry: HNSWSearchesTelemetry::new(),
+            links_telemetry: OnceLock::new(),
+            ef_tuner,
             is_on_disk,
-        })
+            inline_vectors_compatible,
+            healed_deleted_count: 0,
+        };
+
+        if hnsw_config.lock_in_ram.unwrap_or_default() {
+            index.lock_links_in_ram();
+        }
+
+        // Links decoded with a guessed byte order may be garbage, even though the file is valid
+        if index.graph.links.is_legacy_fallback() {
+            let report = index.verify_connectivity();
+            if report.is_ok() {
+                debug!(
+                    "Verified HNSW graph at {} decoded with legacy fallback: {report}",
+                    path.display(),
+                );
+            } else {
+                log::warn!(
+                    "HNSW graph at {} decoded with legacy fallback is inconsistent: {report}",
+                    path.display(),
+                );
+            }
+        }
+
+        Ok(index)
     }
 
     pub fn is_on_disk(&self) -> bool {
         self.is_on_disk
     }
 
-    #[cfg(test)]
-    pub(super) fn graph(&self) -> &GraphLayers {
+    pub fn graph(&self) -> &GraphLayers {
         &self.graph
     }
 
@@ -202,6 +301,129 @@ impl HNSWIndex {
         self.quantized_vectors.clone()
     }
 
+    /// Check that all links of the graph point to existing points, and all non-deleted points
+    /// are reachable from the entry point, see [`GraphConnectivityReport`].
+    pub fn verify_connectivity(&self) -> GraphConnectivityReport {
+        let vector_storage = self.vector_storage.borrow();
+        let id_tracker = self.id_tracker.borrow();
+        let deleted_vectors = vector_storage.deleted_vector_bitslice();
+        let deleted_points = id_tracker.deleted_point_bitslice();
+
+        // Without the main graph, points are only linked within payload blocks
+        let has_main_graph = self.config.m > 0;
+
+        self.graph.verify_connectivity(|offset| {
+            has_main_graph
+                && !deleted_vectors.get_bit(offset as usize).unwrap_or(false)
+                && !deleted_points.get_bit(offset as usize).unwrap_or(false)
+        })
+    }
+
+    /// Dump links of the graph with point ids into `writer`, see [`GraphExportFormat`].
+    ///
+    /// Deleted points are exported without an id, as they may still be linked.
+    pub fn export_graph(
+        &self,
+        format: GraphExportFormat,
+        writer: impl Write,
+    ) -> OperationResult<()> {
+        let id_tracker = self.id_tracker.borrow();
+        self.graph
+            .links
+            .export(format, |offset| id_tracker.external_id(offset), writer)
+    }
+
+    /// Number of graph points, whose point or vector is deleted since the graph was built
+    fn deleted_vector_count(&self) -> usize {
+        let vector_storage = self.vector_storage.borrow();
+        let id_tracker = self.id_tracker.borrow();
+        let deleted_vectors = vector_storage.deleted_vector_bitslice();
+        let deleted_points = id_tracker.deleted_point_bitslice();
+        (0..self.graph.num_points())
+            .filter(|&offset| {
+                deleted_vectors.get_bit(offset).unwrap_or(false)
+                    || deleted_points.get_bit(offset).unwrap_or(false)
+            })
+            .count()
+    }
+
+    /// Fraction of graph points, which are deleted, but still linked from their neighbors.
+    ///
+    /// Always `0` for graphs with vectors in links, which can't be healed in place.
+    pub fn unhealed_deleted_ratio(&self) -> f64 {
+        let num_points = self.graph.num_points();
+        if num_points == 0 || self.graph.links.format().is_with_vectors() {
+            return 0.0;
+        }
+        let unhealed_count = self
+            .deleted_vector_count()
+            .saturating_sub(self.healed_deleted_count);
+        unhealed_count as f64 / num_points as f64
+    }
+
+    /// Re-link neighbors of deleted points, so that search quality doesn't degrade until the
+    /// index is rebuilt by the optimizer.
+    ///
+    /// Patched links are appended to the links delta file, the serialized graph is not rewritten.
+    /// Not supported for links with vectors, as the delta doesn't store them.
+    ///
+    /// Returns the number of patched links lists.
+    pub fn heal_deleted_points(&mut self, pool: &ThreadPool) -> OperationResult<usize> {
+        if self.graph.links.format().is_with_vectors() {
+            debug!(
+                "Skipping healing of HNSW graph with vectors in links at {}",
+                self.path.display(),
+            );
+            return Ok(0);
+        }
+
+        let deleted_vector_count = self.deleted_vector_count();
+        let patched = {
+            let vector_storage = self.vector_storage.borrow();
+            let id_tracker = self.id_tracker.borrow();
+            let deleted_vectors = vector_storage.deleted_vector_bitslice();
+            let deleted_points = id_tracker.deleted_point_bitslice();
+
+            // Offsets are preserved, deleted points are mapped to `None`
+            let old_to_new = (0..self.graph.num_points() as PointOffsetType)
+                .map(|offset| {
+                    let deleted = deleted_vectors.get_bit(offset as usize).unwrap_or(false)
+                        || deleted_points.get_bit(offset as usize).unwrap_or(false);
+                    (!deleted).then_some(offset)
+                })
+                .collect::<Vec<_>>();
+
+            let mut healer =
+                GraphLayersHealer::new(&self.graph, &old_to_new, self.config.ef_construct);
+            if healer.to_heal_count() == 0 {
+                0
+            } else {
+                let timer = Instant::now();
+                let quantized_vectors = self.quantized_vectors.borrow();
+                healer.heal(pool, &vector_storage, quantized_vectors.as_ref())?;
+
+                let mut writer =
+                    GraphLinksDeltaWriter::open(&GraphLayers::get_links_delta_path(&self.path))?;
+                let patched = healer.save_into_delta(&self.graph, &mut writer)?;
+                writer.flush()?;
+
+                debug!(
+                    "Healed HNSW graph at {}: patched {patched} links lists in {:?}",
+                    self.path.display(),
+                    timer.elapsed(),
+                );
+                patched
+            }
+        };
+
+        if patched > 0 {
+            self.graph.reload_links_delta(&self.path)?;
+            self.links_telemetry = OnceLock::new();
+        }
+        self.healed_deleted_count = deleted_vector_count;
+        Ok(patched)
+    }
+
     pub fn build<R: Rng + ?Sized>(
         open_args: HnswIndexOpenArgs<'_>,
         build_args: VectorIndexBuildArgs<'_, R>,
@@ -214,7 +436,7 @@ impl HNSWIndex {
                 open_args.path
             );
             debug_assert!(false);
-            return Self::open(open_args);
+            return Self::open(open_args, None);
         }
 
         let HnswIndexOpenArgs {
@@ -234,6 +456,7 @@ impl HNSWIndex {
             hnsw_global_config,
             feature_flags,
             progress,
+            build_checkpoint_dir,
         } = build_args;
 
         fs::create_dir_all(path)?;
@@ -258,6 +481,7 @@ impl HNSWIndex {
 
         let mut config = HnswGraphConfig::new(
             hnsw_config.m,
+            hnsw_config.m0,
             hnsw_config.ef_construct,
             full_scan_threshold,
             hnsw_config.max_indexing_threads,
@@ -374,16 +598,52 @@ impl HNSWIndex {
             })
             .build()?;
 
-        let old_index = old_index.map(|old_index| old_index.reuse(total_vector_count));
+        // Resume linking of an interrupted build
+        let checkpoint_path = build_checkpoint_dir
+            .filter(|_| feature_flags.hnsw_build_checkpoints && build_main_graph)
+            .map(|dir| -> OperationResult<_> {
+                BuildCheckpoint::remove_stale(dir)?;
+                let build_key =
+                    BuildCheckpoint::build_key(path, id_tracker_ref.deref(), total_vector_count);
+                Ok(BuildCheckpoint::get_path(dir, build_key))
+            })
+            .transpose()?;
+        let checkpoint = if let Some(checkpoint_path) = &checkpoint_path {
+            BuildCheckpoint::load(
+                checkpoint_path,
+                HnswM::new(config.m, config.m0),
+                config.ef_construct,
+                total_vector_count,
+                deleted_bitslice,
+            )
+        } else {
+            None
+        };
+
+        let old_index = match &checkpoint {
+            Some(checkpoint) => {
+                debug!(
+                    "Resuming HNSW build from checkpoint with {} linked points",
+                    checkpoint.linked.len(),
+                );
+                None
+            }
+            None => old_index.map(|old_index| old_index.reuse(total_vector_count)),
+        };
 
         let mut indexed_vectors = 0;
         for vector_id in id_tracker_ref.iter_internal_excluding(deleted_bitslice) {
             check_process_stopped(stopped)?;
             indexed_vectors += 1;
 
-            let level = old_index
+            let level = checkpoint
                 .as_ref()
-                .and_then(|old_index| old_index.point_level(vector_id))
+                .map(|checkpoint| usize::from(checkpoint.levels[vector_id as usize]))
+                .or_else(|| {
+                    old_index
+                        .as_ref()
+                        .and_then(|old_index| old_index.point_level(vector_id))
+                })
                 .unwrap_or_else(|| graph_layers_builder.get_random_layer(rng));
             graph_layers_builder.set_levels(vector_id, level);
         }
@@ -426,7 +686,15 @@ impl HNSWIndex {
             let mut first_few_ids = Vec::with_capacity(SINGLE_THREADED_HNSW_BUILD_THRESHOLD);
 
             let mut ids_iter = id_tracker_ref.iter_internal_excluding(deleted_bitslice);
-            if let Some(old_index) = old_index {
+            if let Some(checkpoint) = checkpoint {
+                let restored = checkpoint.linked.len();
+                graph_layers_builder.restore_build_checkpoint(checkpoint);
+                if restored == 0 {
+                    first_few_ids
+                        .extend(ids_iter.by_ref().take(SINGLE_THREADED_HNSW_BUILD_THRESHOLD));
+                }
+                ids.extend(ids_iter.filter(|&vector_id| !graph_layers_builder.is_ready(vector_id)));
+            } else if let Some(old_index) = old_index {
                 progress_migrate.start();
 
                 let timer = std::time::Instant::now();
@@ -491,7 +759,25 @@ impl HNSWIndex {
             }
 
             if !ids.is_empty() {
-                pool.install(|| ids.into_par_iter().try_for_each(insert_point))?;
+                if let Some(checkpoint_path) = &checkpoint_path {
+                    let mut last_checkpoint = Instant::now();
+                    for chunk in ids.chunks(BUILD_CHECKPOINT_CHUNK_SIZE) {
+                        let result =
+                            pool.install(|| chunk.par_iter().copied().try_for_each(insert_point));
+                        // Linking of all points is finished at this moment, so the checkpoint is
+                        // consistent even if the build was stopped
+                        if result.is_err() || last_checkpoint.elapsed() >= BUILD_CHECKPOINT_INTERVAL
+                        {
+                            graph_layers_builder
+                                .build_checkpoint()
+                                .save(checkpoint_path)?;
+                            last_checkpoint = Instant::now();
+                        }
+                        result?;
+                    }
+                } else {
+                    pool.install(|| ids.into_par_iter().try_for_each(insert_point))?;
+                }
             }
 
             drop(progress_main_graph);
@@ -676,21 +962,14 @@ impl HNSWIndex {
         // as it will be discarded anyway
         let is_on_disk = true;
 
-        let graph_links_vectors = hnsw_config
-            .inline_storage
-            .unwrap_or_default()
-            .then(|| {
-                // NOTE: the configuration is silently ignored if try_new fails.
-                StorageGraphLinksVectors::try_new(
-                    &vector_storage_ref,
-                    quantized_vectors_ref.as_ref(),
-                )
-            })
-            .flatten();
-        let format_param = match graph_links_vectors.as_ref() {
-            Some(v) => GraphLinksFormatParam::CompressedWithVectors(v),
-            None => GraphLinksFormatParam::Compressed,
-        };
+        let graph_links_vectors = Self::graph_links_vectors(
+            &hnsw_config,
+            &feature_flags,
+            &vector_storage_ref,
+            quantized_vectors_ref.as_ref(),
+        );
+        let format_param =
+            Self::graph_links_format_param(&feature_flags, graph_links_vectors.as_ref());
 
         let graph: GraphLayers =
             graph_layers_builder.into_graph_layers(path, format_param, is_on_disk)?;
@@ -709,12 +988,19 @@ impl HNSWIndex {
         debug!("finish additional payload field indexing");
 
         config.save(&HnswGraphConfig::get_config_path(path))?;
+        if let Some(checkpoint_path) = &checkpoint_path {
+            BuildCheckpoint::remove(checkpoint_path)?;
+        }
 
         drop(id_tracker_ref);
         drop(vector_storage_ref);
         drop(quantized_vectors_ref);
         drop(payload_index_ref);
 
+        let ef_tuner = hnsw_config
+            .target_recall
+            .map(|OrderedFloat(target_recall)| EfTuner::open(path, target_recall, config.ef));
+
         Ok(HNSWIndex {
             id_tracker,
             vector_storage,
@@ -724,10 +1010,86 @@ impl HNSWIndex {
             path: path.to_owned(),
             graph,
             searches_telemetry: HNSWSearchesTelemetry::new(),
+            links_telemetry: OnceLock::new(),
+            ef_tuner,
             is_on_disk,
+            inline_vectors_compatible: true,
+            healed_deleted_count: 0,
         })
     }
 
+    /// Whether link vectors stored in the graph are encoded like `quantized_vectors`, which
+    /// may not be the case if they are restored separately.
+    ///
+    /// Graphs written without quantization metadata are assumed to match.
+    fn check_inline_vectors(
+        path: &Path,
+        graph: &GraphLayers,
+        vector_storage: &VectorStorageEnum,
+        quantized_vectors: Option<&QuantizedVectors>,
+    ) -> bool {
+        let (Some(metadata), Some(quantized_vectors)) =
+            (graph.links.quantization_metadata(), quantized_vectors)
+        else {
+            return true;
+        };
+        match quantization_hash(quantized_vectors, vector_storage.total_vector_count()) {
+            Ok(hash) if hash == metadata.quantization_hash => true,
+            Ok(_) => {
+                log::warn!(
+                    "Link vectors of HNSW graph at {} don't match quantized vectors, searching without them",
+                    path.display(),
+                );
+                false
+            }
+            Err(err) => {
+                log::warn!(
+                    "Failed to check link vectors of HNSW graph at {}, searching without them: {err}",
+                    path.display(),
+                );
+                false
+            }
+        }
+    }
+
+    /// Vectors to be stored within the links, if `inline_storage` is enabled.
+    ///
+    /// NOTE: the configuration is silently ignored if the vectors can't be stored.
+    fn graph_links_vectors<'a>(
+        hnsw_config: &HnswConfig,
+        feature_flags: &FeatureFlags,
+        vector_storage: &'a VectorStorageEnum,
+        quantized_vectors: Option<&'a QuantizedVectors>,
+    ) -> Option<StorageGraphLinksVectors<'a>> {
+        if !hnsw_config.inline_storage.unwrap_or_default() {
+            return None;
+        }
+        StorageGraphLinksVectors::try_new(
+            vector_storage,
+            quantized_vectors,
+            if feature_flags.hnsw_inline_f16_base_vectors {
+                GraphLinksBaseVectorEncoding::Float16
+            } else {
+                GraphLinksBaseVectorEncoding::Storage
+            },
+        )
+    }
+
+    /// Format of the links of newly built graphs.
+    fn graph_links_format_param<'a>(
+        feature_flags: &FeatureFlags,
+        graph_links_vectors: Option<&'a StorageGraphLinksVectors<'a>>,
+    ) -> GraphLinksFormatParam<'a> {
+        match graph_links_vectors {
+            Some(v) if feature_flags.hnsw_dedup_link_vectors => {
+                GraphLinksFormatParam::CompressedWithDedupVectors(v)
+            }
+            Some(v) => GraphLinksFormatParam::CompressedWithVectors(v),
+            None if feature_flags.hnsw_varint_links => GraphLinksFormatParam::CompressedVarint,
+            None => GraphLinksFormatParam::Compressed,
+        }
+    }
+
     /// Get list of points for indexing, associated with payload block filtering condition
     fn condition_points(
         condition: FieldCondition,
@@ -1015,11 +1377,12 @@ impl HNSWIndex {
         top: usize,
         params: Option<&SearchParams>,
         custom_entry_points: Option<&[PointOffsetType]>,
+        strategy: FilteredSearchStrategy,
         vector_query_context: &VectorQueryContext,
     ) -> OperationResult<Vec<ScoredPointOffset>> {
         let ef = params
             .and_then(|params| params.hnsw_ef)
-            .unwrap_or(self.config.ef);
+            .unwrap_or_else(|| self.default_ef());
         let acorn_enabled = params
             .and_then(|params| params.acorn)
             .is_some_and(|acorn| acorn.enable);
@@ -1043,7 +1406,9 @@ impl HNSWIndex {
         let oversampled_top = get_oversampled_top(quantized_vectors.as_ref(), params, top);
 
         let mut algorithm = SearchAlgorithm::Hnsw;
-        if acorn_enabled
+        if strategy == FilteredSearchStrategy::Acorn {
+            algorithm = SearchAlgorithm::Acorn;
+        } else if acorn_enabled
             && self.config.m0 != 0
             && let Some(filter) = filter
         {
@@ -1077,6 +1442,7 @@ impl HNSWIndex {
                 SearchAlgorithm::Acorn => return Ok(None),
             }
             if !self.graph.has_inline_vectors()
+                || !self.inline_vectors_compatible
                 || !is_quantized_search(quantized_vectors.as_ref(), params)
             {
                 return Ok(None);
@@ -1107,8 +1473,31 @@ impl HNSWIndex {
             let Some(base_scorer_bytes) = base_scorer.scorer_bytes() else {
                 return Ok(None);
             };
+            let is_f16_base = self.graph.links.base_vector_encoding()
+                == Some(GraphLinksBaseVectorEncoding::Float16);
+            let base_scorer_f16;
+            let base_scorer_bytes: &dyn QueryScorerBytes = if is_f16_base {
+                base_scorer_f16 = Float16QueryScorerBytes::new(base_scorer_bytes);
+                &base_scorer_f16
+            } else {
+                base_scorer_bytes
+            };
+
+            // Oversampling the graph is built for applies, unless requested explicitly
+            let oversampled_top = match self.graph.links.quantization_metadata() {
+                Some(metadata)
+                    if params
+                        .and_then(|p| p.quantization)
+                        .and_then(|q| q.oversampling)
+                        .is_none()
+                        && metadata.rescore_oversampling > 1.0 =>
+                {
+                    (metadata.rescore_oversampling * top as f64) as usize
+                }
+                _ => oversampled_top,
+            };
 
-            Ok(Some(self.graph.search_with_vectors(
+            let mut search_result = self.graph.search_with_vectors(
                 top,
                 std::cmp::max(ef, oversampled_top),
                 &link_scorer_filtered,
@@ -1116,7 +1505,17 @@ impl HNSWIndex {
                 base_scorer_bytes,
                 custom_entry_points,
                 &vector_query_context.is_stopped(),
-            )?))
+            )?;
+
+            // Scores of `f16` base vectors are approximate, report the exact ones
+            if is_f16_base {
+                for scored_point in &mut search_result {
+                    scored_point.score = base_scorer.score_point(scored_point.idx);
+                }
+                search_result.sort_unstable_by(|a, b| b.cmp(a));
+            }
+
+            Ok(Some(search_result))
         };
 
         let regular_search = || -> OperationResult<Vec<ScoredPointOffset>> {
@@ -1161,12 +1560,83 @@ impl HNSWIndex {
         }
     }
 
+    /// `ef` of searches without explicit `hnsw_ef` in params
+    fn default_ef(&self) -> usize {
+        self.ef_tuner.as_ref().map_or(self.config.ef, EfTuner::ef)
+    }
+
+    /// Copy a sample of searches with default `ef`, to tune `ef` toward the target recall,
+    /// see [`EfTuner`].
+    fn sample_recall(
+        &self,
+        vectors: &[&QueryVector],
+        top: usize,
+        params: Option<&SearchParams>,
+        results: &[Vec<ScoredPointOffset>],
+    ) {
+        let Some(ef_tuner) = &self.ef_tuner else {
+            return;
+        };
+        if params.is_some_and(|params| params.hnsw_ef.is_some()) {
+            return;
+        }
+        for (&vector, result) in vectors.iter().zip(results) {
+            if matches!(vector, QueryVector::Nearest(_)) {
+                ef_tuner.sample(vector, top, result);
+            }
+        }
+    }
+
+    /// Compare sampled searches with exact search, and adjust tuned `ef`.
+    ///
+    /// Exact search is expensive, so it runs in the background after the segment is flushed,
+    /// see [`crate::entry::NonAppendableSegmentEntry::tune_indexes`], and neither searches,
+    /// telemetry nor flushes pay for it.
+    pub fn tune_ef(&self) {
+        let Some(ef_tuner) = &self.ef_tuner else {
+            return;
+        };
+        let id_tracker = self.id_tracker.borrow();
+        let vector_storage = self.vector_storage.borrow();
+        ef_tuner.evaluate_pending(|vector, top| {
+            let exact_searcher = BatchFilteredSearcher::new(
+                &[vector],
+                &vector_storage,
+                None,
+                None,
+                top,
+                id_tracker.deleted_point_bitslice(),
+                HardwareCounterCell::disposable(),
+            )?;
+            let mut exact_result =
+                exact_searcher.peek_top_iter(id_tracker.iter_internal(), &DEFAULT_STOPPED)?;
+            Ok(exact_result.pop().unwrap_or_default())
+        });
+    }
+
+    /// Persist tuned `ef`, if it changed, see [`Self::tune_ef`].
+    pub fn flusher(&self) -> Option<Flusher> {
+        self.ef_tuner.as_ref().and_then(EfTuner::flusher)
+    }
+
+    /// Whether a filter, too restrictive for regular graph search, is still large enough to
+    /// be searched with ACORN instead of plain search.
+    fn is_acorn_cardinality(&self, params: Option<&SearchParams>, cardinality: usize) -> bool {
+        let acorn_enabled = params
+            .and_then(|params| params.acorn)
+            .is_some_and(|acorn| acorn.enable);
+        acorn_enabled
+            && self.config.m0 != 0
+            && cardinality >= self.config.full_scan_threshold / ACORN_MODERATE_CARDINALITY_DIVISOR
+    }
+
     fn search_vectors_with_graph(
         &self,
         vectors: &[&QueryVector],
         filter: Option<&Filter>,
         top: usize,
         params: Option<&SearchParams>,
+        strategy: FilteredSearchStrategy,
         vector_query_context: &VectorQueryContext,
     ) -> OperationResult<Vec<Vec<ScoredPointOffset>>> {
         vectors
@@ -1177,11 +1647,18 @@ impl HNSWIndex {
                     filter,
                     top,
                     params,
+                    strategy,
+                    vector_query_context,
+                ),
+                other => self.search_with_graph(
+                    other,
+                    filter,
+                    top,
+                    params,
+                    None,
+                    strategy,
                     vector_query_context,
                 ),
-                other => {
-                    self.search_with_graph(other, filter, top, params, None, vector_query_context)
-                }
             })
             .collect()
     }
@@ -1291,6 +1768,7 @@ impl HNSWIndex {
         filter: Option<&Filter>,
         top: usize,
         params: Option<&SearchParams>,
+        strategy: FilteredSearchStrategy,
         vector_query_context: &VectorQueryContext,
     ) -> OperationResult<Vec<ScoredPointOffset>> {
         // Stage 1: Find best entry points using Context search
@@ -1305,6 +1783,7 @@ impl HNSWIndex {
                 DISCOVERY_ENTRY_POINT_COUNT,
                 params,
                 None,
+                strategy,
                 vector_query_context,
             )
             .map(|search_result| search_result.iter().map(|x| x.idx).collect())?;
@@ -1318,6 +1797,7 @@ impl HNSWIndex {
             top,
             params,
             Some(&custom_entry_points),
+            strategy,
             vector_query_context,
         )
     }
@@ -1370,6 +1850,40 @@ impl HNSWIndex {
         self.graph.populate()
     }
 
+    /// Read underlying data from disk into disk cache, latency-critical parts of the graph first.
+    ///
+    /// Returns once the graph can be searched without hitting the disk for anything but level 0
+    /// links, which keep being read in the background, see
+    /// [`GraphLayers::populate_prioritized`].
+    pub fn populate_prioritized(
+        &self,
+        progress: ProgressTracker,
+    ) -> OperationResult<Option<thread::JoinHandle<()>>> {
+        self.graph.populate_prioritized(progress)
+    }
+
+    /// Advise the OS how graph links on disk are going to be accessed.
+    pub fn advise_links(&self, advice: Advice) -> OperationResult<()> {
+        self.graph.advise_links(advice)
+    }
+
+    /// Request transparent huge pages for graph links on disk.
+    pub fn request_links_hugepages(&self) -> OperationResult<()> {
+        self.graph.request_links_hugepages()
+    }
+
+    /// Lock graph links in RAM, see [`GraphLayers::lock_in_ram`].
+    ///
+    /// Links remain usable if they can't be locked, so a failure is only logged.
+    pub fn lock_links_in_ram(&self) {
+        if let Err(err) = self.graph.lock_in_ram() {
+            log::warn!(
+                "HNSW graph links at {} are not locked in RAM: {err}",
+                self.path.display(),
+            );
+        }
+    }
+
     /// Drop disk cache.
     pub fn clear_cache(&self) -> OperationResult<()> {
         for file in self.graph.files(&self.path) {
@@ -1437,7 +1951,16 @@ impl VectorIndex for HNSWIndex {
                 } else {
                     let _timer =
                         ScopeDurationMeasurer::new(&self.searches_telemetry.unfiltered_hnsw);
-                    self.search_vectors_with_graph(vectors, None, top, params, query_context)
+                    let results = self.search_vectors_with_graph(
+                        vectors,
+                        None,
+                        top,
+                        params,
+                        FilteredSearchStrategy::Auto,
+                        query_context,
+                    )?;
+                    self.sample_recall(vectors, top, params, &results);
+                    Ok(results)
                 }
             }
             Some(query_filter) => {
@@ -1480,6 +2003,19 @@ impl VectorIndex for HNSWIndex {
                 );
 
                 if query_cardinality.max < self.config.full_scan_threshold {
+                    if self.is_acorn_cardinality(params, query_cardinality.exp) {
+                        // if cardinality is moderate - traverse the graph with ACORN
+                        let _timer = ScopeDurationMeasurer::new(&self.searches_telemetry.acorn);
+                        return self.search_vectors_with_graph(
+                            vectors,
+                            filter,
+                            top,
+                            params,
+                            FilteredSearchStrategy::Acorn,
+                            query_context,
+                        );
+                    }
+
                     // if cardinality is small - use plain index
                     let _timer =
                         ScopeDurationMeasurer::new(&self.searches_telemetry.small_cardinality);
@@ -1501,6 +2037,7 @@ impl VectorIndex for HNSWIndex {
                         filter,
                         top,
                         params,
+                        FilteredSearchStrategy::Auto,
                         query_context,
                     );
                 }
@@ -1518,7 +2055,25 @@ impl VectorIndex for HNSWIndex {
                     // if cardinality is high enough - use HNSW index
                     let _timer =
                         ScopeDurationMeasurer::new(&self.searches_telemetry.large_cardinality);
-                    self.search_vectors_with_graph(vectors, filter, top, params, query_context)
+                    self.search_vectors_with_graph(
+                        vectors,
+                        filter,
+                        top,
+                        params,
+                        FilteredSearchStrategy::Auto,
+                        query_context,
+                    )
+                } else if self.is_acorn_cardinality(params, query_cardinality.exp) {
+                    // if cardinality is moderate - traverse the graph with ACORN
+                    let _timer = ScopeDurationMeasurer::new(&self.searches_telemetry.acorn);
+                    self.search_vectors_with_graph(
+                        vectors,
+                        filter,
+                        top,
+                        params,
+                        FilteredSearchStrategy::Acorn,
+                        query_context,
+                    )
                 } else {
                     // if cardinality is small - use plain index
                     let _timer =
@@ -1538,10 +2093,17 @@ impl VectorIndex for HNSWIndex {
             unfiltered_hnsw: tm.unfiltered_hnsw.lock().get_statistics(detail),
             filtered_small_cardinality: tm.small_cardinality.lock().get_statistics(detail),
             filtered_large_cardinality: tm.large_cardinality.lock().get_statistics(detail),
+            filtered_acorn: tm.acorn.lock().get_statistics(detail),
             filtered_exact: tm.exact_filtered.lock().get_statistics(detail),
             filtered_sparse: Default::default(),
             unfiltered_exact: tm.exact_unfiltered.lock().get_statistics(detail),
             unfiltered_sparse: Default::default(),
+            sparse_postings: None,
+            hnsw_links: Some(
+                self.links_telemetry
+                    .get_or_init(|| self.graph.links.collect_telemetry())
+                    .clone(),
+            ),
         }
     }
 
@@ -1551,11 +2113,20 @@ impl VectorIndex for HNSWIndex {
         if config_path.exists() {
             files.push(config_path);
         }
+        let ef_tuner_path = EfTuner::get_path(&self.path);
+        if ef_tuner_path.exists() {
+            files.push(ef_tuner_path);
+        }
         files
     }
 
     fn immutable_files(&self) -> Vec<PathBuf> {
-        self.files() // All HNSW index files are immutable 😎
+        // All HNSW index files are immutable 😎, except for the tuned ef and the links delta
+        let ef_tuner_path = EfTuner::get_path(&self.path);
+        let links_delta_path = GraphLayers::get_links_delta_path(&self.path);
+        let mut files = self.files();
+        files.retain(|file| *file != ef_tuner_path && *file != links_delta_path);
+        files
     }
 
     fn indexed_vector_count(&self) -> usize {
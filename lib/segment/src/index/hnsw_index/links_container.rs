@@ -48,25 +48,19 @@ impl LinksContainer {
         &mut self,
         candidates: impl Iterator<Item = ScoredPointOffset>,
         level_m: usize,
-        mut score: impl FnMut(PointOffsetType, PointOffsetType) -> ScoreType,
+        score: impl FnMut(PointOffsetType, PointOffsetType) -> ScoreType,
     ) {
         self.links.clear();
-        if level_m == 0 {
-            // Unlikely.
-            self.processed_by_heuristic = 0;
-            return;
-        }
-        'outer: for candidate in candidates {
-            for &existing in &self.links {
-                if score(candidate.idx, existing) > candidate.score {
-                    continue 'outer;
-                }
-            }
-            self.links.push(candidate.idx);
-            if self.links.len() >= level_m {
-                break;
-            }
-        }
+        select_with_heuristic(candidates, level_m, score, &mut self.links);
+        self.processed_by_heuristic = self.links.len() as u32;
+    }
+
+    /// Put points selected by `select`, e.g. with [`select_with_heuristic`], into the container.
+    ///
+    /// `select` appends the points to the given empty vector, the links of the container.
+    pub fn fill_with_heuristic(&mut self, select: impl FnOnce(&mut Vec<PointOffsetType>)) {
+        self.links.clear();
+        select(&mut self.links);
         self.processed_by_heuristic = self.links.len() as u32;
     }
 
@@ -222,6 +216,33 @@ impl LinksContainer {
     }
 }
 
+/// Select up to `level_m` of `candidates`, sorted by descending score, with the
+/// "not closer than base" heuristic. Selected points are appended to `selected`, which must be
+/// empty.
+pub fn select_with_heuristic(
+    candidates: impl Iterator<Item = ScoredPointOffset>,
+    level_m: usize,
+    mut score: impl FnMut(PointOffsetType, PointOffsetType) -> ScoreType,
+    selected: &mut Vec<PointOffsetType>,
+) {
+    debug_assert!(selected.is_empty());
+    if level_m == 0 {
+        // Unlikely.
+        return;
+    }
+    'outer: for candidate in candidates {
+        for &existing in selected.iter() {
+            if score(candidate.idx, existing) > candidate.score {
+                continue 'outer;
+            }
+        }
+        selected.push(candidate.idx);
+        if selected.len() >= level_m {
+            break;
+        }
+    }
+}
+
 /// Internal buffer to avoid allocations.
 #[derive(Default)]
 pub struct ItemsBuffer(Vec<Item>);
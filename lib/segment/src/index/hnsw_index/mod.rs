@@ -1,9 +1,12 @@
 use common::defaults::thread_count_for_hnsw;
 
 mod build_cache;
+mod build_checkpoint;
 pub mod build_condition_checker;
 mod config;
+mod ef_tuner;
 mod entry_points;
+pub mod graph_build_backend;
 pub mod graph_layers;
 pub mod graph_layers_builder;
 mod graph_layers_healer;
@@ -61,6 +61,9 @@ fn test_graph_connectivity() {
         on_disk: Some(false),
         payload_m: None,
         inline_storage: None,
+        target_recall: None,
+        m0: None,
+        lock_in_ram: None,
     };
 
     let permit_cpu_count = num_rayon_threads(hnsw_config.max_indexing_threads);
@@ -86,6 +89,7 @@ fn test_graph_connectivity() {
             hnsw_global_config: &HnswGlobalConfig::default(),
             feature_flags: FeatureFlags::default(),
             progress: ProgressTracker::new_for_test(),
+            build_checkpoint_dir: None,
         },
     )
     .unwrap();
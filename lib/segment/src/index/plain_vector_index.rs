@@ -171,10 +171,13 @@ impl VectorIndex for PlainVectorIndex {
             unfiltered_hnsw: OperationDurationStatistics::default(),
             filtered_small_cardinality: OperationDurationStatistics::default(),
             filtered_large_cardinality: OperationDurationStatistics::default(),
+            filtered_acorn: OperationDurationStatistics::default(),
             filtered_exact: OperationDurationStatistics::default(),
             filtered_sparse: Default::default(),
             unfiltered_exact: OperationDurationStatistics::default(),
             unfiltered_sparse: OperationDurationStatistics::default(),
+            sparse_postings: None,
+            hnsw_links: None,
         }
     }
 
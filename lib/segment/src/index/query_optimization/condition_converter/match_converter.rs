@@ -7,8 +7,8 @@ use crate::index::field_index::FieldIndex;
 use crate::index::query_optimization::optimized_filter::ConditionCheckerFn;
 use crate::payload_storage::condition_checker::INDEXSET_ITER_THRESHOLD;
 use crate::types::{
-    AnyVariants, Match, MatchAny, MatchExcept, MatchPhrase, MatchText, MatchTextAny, MatchValue,
-    ValueVariants,
+    AnyVariants, Match, MatchAny, MatchExcept, MatchPhrase, MatchText, MatchTextAny,
+    MatchTextFuzzy, MatchValue, ValueVariants,
 };
 
 pub fn get_match_checkers(
@@ -27,6 +27,9 @@ pub fn get_match_checkers(
         Match::Phrase(MatchPhrase { phrase }) => {
             get_match_text_checker(phrase, TextQueryType::Phrase, index, hw_acc)
         }
+        Match::TextFuzzy(MatchTextFuzzy { text_fuzzy }) => {
+            get_match_text_fuzzy_checker(text_fuzzy, index)
+        }
         Match::Any(MatchAny { any }) => get_match_any_checker(any, index, hw_acc),
         Match::Except(MatchExcept { except }) => get_match_except_checker(except, index, hw_acc),
     }
@@ -296,3 +299,30 @@ fn get_match_text_checker(
         | FieldIndex::NullIndex(_) => None,
     }
 }
+
+fn get_match_text_fuzzy_checker(
+    text: String,
+    index: &FieldIndex,
+) -> Option<ConditionCheckerFn<'_>> {
+    match index {
+        FieldIndex::FullTextIndex(full_text_index) => {
+            let Some(fuzzy_query) = full_text_index.parse_fuzzy_query(&text) else {
+                return Some(Box::new(|_| false));
+            };
+
+            Some(Box::new(move |point_id: PointOffsetType| {
+                full_text_index.check_fuzzy_match(&fuzzy_query, point_id)
+            }))
+        }
+        FieldIndex::BoolIndex(_)
+        | FieldIndex::DatetimeIndex(_)
+        | FieldIndex::FloatIndex(_)
+        | FieldIndex::GeoIndex(_)
+        | FieldIndex::IntIndex(_)
+        | FieldIndex::IntMapIndex(_)
+        | FieldIndex::KeywordIndex(_)
+        | FieldIndex::UuidIndex(_)
+        | FieldIndex::UuidMapIndex(_)
+        | FieldIndex::NullIndex(_) => None,
+    }
+}
@@ -0,0 +1,80 @@
+use std::path::{Path, PathBuf};
+
+use ahash::AHashMap;
+use common::counter::hardware_counter::HardwareCounterCell;
+use common::fs::{atomic_save_json, read_json};
+use serde::{Deserialize, Serialize};
+use sparse::common::sparse_vector::SparseVector;
+use sparse::common::types::DimId;
+use sparse::index::inverted_index::InvertedIndex;
+
+use super::indices_tracker::IndicesTracker;
+use crate::common::operation_error::OperationResult;
+
+const DOCUMENT_FREQUENCIES_FILE_NAME: &str = "document_frequencies.json";
+
+/// Number of indexed vectors containing each dimension, materialized for the `idf` modifier.
+///
+/// IDF factors depend on statistics of all segments of the collection, so their per-segment
+/// inputs are persisted instead, and summed up at query time. Reading them doesn't touch posting
+/// lists of the inverted index.
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct DocumentFrequencies {
+    pub map: AHashMap<DimId, usize>,
+}
+
+impl DocumentFrequencies {
+    /// Compute frequencies from posting lists of an immutable index
+    pub fn from_index(
+        inverted_index: &impl InvertedIndex,
+        indices_tracker: &IndicesTracker,
+    ) -> Self {
+        let hw_counter = HardwareCounterCell::disposable();
+        let map = indices_tracker
+            .map
+            .iter()
+            .filter_map(|(&dim_id, dim_offset)| {
+                let posting_list_len = inverted_index.posting_list_len(dim_offset, &hw_counter)?;
+                Some((dim_id, posting_list_len))
+            })
+            .collect();
+        Self { map }
+    }
+
+    /// Load persisted frequencies, `None` if the index was built without them
+    pub fn load(path: &Path) -> OperationResult<Option<Self>> {
+        let path = Self::file_path(path);
+        if !path.exists() {
+            return Ok(None);
+        }
+        Ok(Some(read_json(&path)?))
+    }
+
+    pub fn save(&self, path: &Path) -> OperationResult<()> {
+        let path = Self::file_path(path);
+        Ok(atomic_save_json(&path, self)?)
+    }
+
+    pub fn file_path(path: &Path) -> PathBuf {
+        path.join(DOCUMENT_FREQUENCIES_FILE_NAME)
+    }
+
+    pub fn get(&self, dim_id: DimId) -> Option<usize> {
+        self.map.get(&dim_id).copied()
+    }
+
+    /// Account for an update of an indexed vector, `vector` is empty if it is removed
+    pub fn update(&mut self, old_vector: Option<&SparseVector>, vector: &SparseVector) {
+        for dim_id in old_vector
+            .into_iter()
+            .flat_map(|old_vector| &old_vector.indices)
+        {
+            if let Some(frequency) = self.map.get_mut(dim_id) {
+                *frequency = frequency.saturating_sub(1);
+            }
+        }
+        for &dim_id in &vector.indices {
+            *self.map.entry(dim_id).or_default() += 1;
+        }
+    }
+}
@@ -1,35 +1,126 @@
+use std::io::{self, Write as _};
 use std::path::{Path, PathBuf};
 
 use ahash::AHashMap;
-use common::fs::{atomic_save_json, read_json};
+use common::fs::{atomic_save, read_json};
+use fs_err as fs;
 use serde::{Deserialize, Serialize};
 use sparse::common::sparse_vector::{RemappedSparseVector, SparseVector};
 use sparse::common::types::{DimId, DimOffset};
 
 use crate::common::operation_error::OperationResult;
 
-const INDICES_TRACKER_FILE_NAME: &str = "indices_tracker.json";
+const INDICES_TRACKER_FILE_NAME: &str = "indices_tracker.dat";
 
+/// Unversioned JSON file, written by older versions
+const LEGACY_INDICES_TRACKER_FILE_NAME: &str = "indices_tracker.json";
+
+const INDICES_TRACKER_MAGIC: [u8; 4] = *b"sdim";
+
+const INDICES_TRACKER_VERSION: u32 = 1;
+
+/// Magic, version and number of entries
+const HEADER_SIZE: usize = 4 + size_of::<u32>() + size_of::<u64>();
+
+/// Dimension id and its offset
+const ENTRY_SIZE: usize = size_of::<DimId>() + size_of::<DimOffset>();
+
+/// Mapping of sparse vector dimension ids into dense offsets of the inverted index.
+///
+/// Persisted as a header of magic bytes, format version, and number of entries, followed by
+/// `(dim_id, offset)` pairs ordered by offset. All numbers are little-endian.
 #[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
 pub struct IndicesTracker {
     pub map: AHashMap<DimId, DimOffset>,
 }
 
 impl IndicesTracker {
+    /// Load the mapping, migrating the legacy JSON file if needed
     pub fn open(path: &Path) -> std::io::Result<Self> {
-        let path = Self::file_path(path);
-        Ok(read_json(&path)?)
+        let file_path = Self::file_path(path);
+        let legacy_file_path = path.join(LEGACY_INDICES_TRACKER_FILE_NAME);
+        if !file_path.exists() && legacy_file_path.exists() {
+            let indices_tracker: Self = read_json(&legacy_file_path)?;
+            indices_tracker.write(&file_path)?;
+            fs::remove_file(&legacy_file_path)?;
+            log::debug!("Migrated sparse indices tracker at {path:?} to versioned format");
+            return Ok(indices_tracker);
+        }
+
+        Self::from_bytes(&fs::read(&file_path)?)
     }
 
     pub fn save(&self, path: &Path) -> OperationResult<()> {
-        let path = Self::file_path(path);
-        Ok(atomic_save_json(&path, self)?)
+        Ok(self.write(&Self::file_path(path))?)
+    }
+
+    fn write(&self, file_path: &Path) -> io::Result<()> {
+        atomic_save(file_path, |writer| writer.write_all(&self.to_bytes()))
     }
 
     pub fn file_path(path: &Path) -> PathBuf {
         path.join(INDICES_TRACKER_FILE_NAME)
     }
 
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut entries: Vec<_> = self
+            .map
+            .iter()
+            .map(|(&dim_id, &offset)| (dim_id, offset))
+            .collect();
+        entries.sort_unstable_by_key(|&(_, offset)| offset);
+
+        let mut bytes = Vec::with_capacity(HEADER_SIZE + entries.len() * ENTRY_SIZE);
+        bytes.extend_from_slice(&INDICES_TRACKER_MAGIC);
+        bytes.extend_from_slice(&INDICES_TRACKER_VERSION.to_le_bytes());
+        bytes.extend_from_slice(&(entries.len() as u64).to_le_bytes());
+        for (dim_id, offset) in entries {
+            bytes.extend_from_slice(&dim_id.to_le_bytes());
+            bytes.extend_from_slice(&offset.to_le_bytes());
+        }
+        bytes
+    }
+
+    fn from_bytes(bytes: &[u8]) -> io::Result<Self> {
+        let invalid_data = |msg: String| io::Error::new(io::ErrorKind::InvalidData, msg);
+
+        let (header, entries) = bytes.split_at_checked(HEADER_SIZE).ok_or_else(|| {
+            invalid_data("sparse indices tracker header is truncated".to_string())
+        })?;
+        let (magic, header) = header.split_at(INDICES_TRACKER_MAGIC.len());
+        if magic != INDICES_TRACKER_MAGIC {
+            return Err(invalid_data(format!(
+                "sparse indices tracker has invalid magic {magic:?}",
+            )));
+        }
+        let (version, count) = header.split_at(size_of::<u32>());
+        let version = u32::from_le_bytes(version.try_into().unwrap());
+        if version != INDICES_TRACKER_VERSION {
+            return Err(invalid_data(format!(
+                "unsupported sparse indices tracker version {version}, expected {INDICES_TRACKER_VERSION}",
+            )));
+        }
+        let count = u64::from_le_bytes(count.try_into().unwrap());
+        if entries.len() as u64 != count.saturating_mul(ENTRY_SIZE as u64) {
+            return Err(invalid_data(format!(
+                "sparse indices tracker size {} doesn't match {count} entries",
+                bytes.len(),
+            )));
+        }
+
+        let map = entries
+            .chunks_exact(ENTRY_SIZE)
+            .map(|entry| {
+                let (dim_id, offset) = entry.split_at(size_of::<DimId>());
+                (
+                    DimId::from_le_bytes(dim_id.try_into().unwrap()),
+                    DimOffset::from_le_bytes(offset.try_into().unwrap()),
+                )
+            })
+            .collect();
+        Ok(Self { map })
+    }
+
     pub fn register_indices(&mut self, vector: &SparseVector) {
         for index in &vector.indices {
             if !self.map.contains_key(index) {
@@ -63,3 +154,52 @@ impl IndicesTracker {
         remapped_vector
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use common::fs::atomic_save_json;
+    use tempfile::Builder;
+
+    use super::*;
+
+    fn indices_tracker() -> IndicesTracker {
+        let mut indices_tracker = IndicesTracker::default();
+        indices_tracker
+            .register_indices(&SparseVector::new(vec![7, 3, 1000], vec![1.0; 3]).unwrap());
+        indices_tracker.register_indices(&SparseVector::new(vec![3, 42], vec![1.0; 2]).unwrap());
+        indices_tracker
+    }
+
+    #[test]
+    fn test_save_and_open() {
+        let dir = Builder::new().prefix("indices_tracker").tempdir().unwrap();
+        let indices_tracker = indices_tracker();
+        indices_tracker.save(dir.path()).unwrap();
+
+        let bytes = fs::read(IndicesTracker::file_path(dir.path())).unwrap();
+        assert_eq!(bytes.len(), HEADER_SIZE + 4 * ENTRY_SIZE);
+        assert_eq!(&bytes[..4], &INDICES_TRACKER_MAGIC);
+
+        assert_eq!(IndicesTracker::open(dir.path()).unwrap(), indices_tracker);
+
+        // Truncated or foreign files are rejected
+        let path = IndicesTracker::file_path(dir.path());
+        fs::write(&path, &bytes[..bytes.len() - 1]).unwrap();
+        assert!(IndicesTracker::open(dir.path()).is_err());
+        fs::write(&path, b"{\"map\":{}}").unwrap();
+        assert!(IndicesTracker::open(dir.path()).is_err());
+    }
+
+    #[test]
+    fn test_migrate_legacy_file() {
+        let dir = Builder::new().prefix("indices_tracker").tempdir().unwrap();
+        let indices_tracker = indices_tracker();
+        let legacy_path = dir.path().join(LEGACY_INDICES_TRACKER_FILE_NAME);
+        atomic_save_json(&legacy_path, &indices_tracker).unwrap();
+
+        assert_eq!(IndicesTracker::open(dir.path()).unwrap(), indices_tracker);
+        assert!(!legacy_path.exists());
+        assert!(IndicesTracker::file_path(dir.path()).exists());
+        assert_eq!(IndicesTracker::open(dir.path()).unwrap(), indices_tracker);
+    }
+}
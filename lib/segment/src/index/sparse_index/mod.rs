@@ -1,4 +1,7 @@
+pub mod document_frequencies;
 pub mod indices_tracker;
 pub mod sparse_index_config;
 pub mod sparse_search_telemetry;
 pub mod sparse_vector_index;
+
+pub use sparse::index::inverted_index::inverted_index_compressed_mmap::verify_index_file;
@@ -1,6 +1,7 @@
 use std::path::{Path, PathBuf};
 
 use common::fs::{atomic_save_json, read_json};
+use common::mmap::Advice;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
@@ -42,6 +43,36 @@ impl SparseIndexType {
     }
 }
 
+/// Expected access pattern of memory mapped files
+#[derive(
+    Default, Hash, Debug, Deserialize, Serialize, JsonSchema, Anonymize, Eq, PartialEq, Copy, Clone,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum MmapAdvice {
+    /// No special treatment
+    #[default]
+    Normal,
+    /// Pages are accessed in random order, read-ahead is disabled.
+    /// Suits searches, which skip through posting lists.
+    Random,
+    /// Pages are accessed sequentially, aggressive read-ahead is used.
+    /// Suits full scans of posting lists.
+    Sequential,
+    /// Pages will be accessed soon, they are read ahead in the background.
+    WillNeed,
+}
+
+impl From<MmapAdvice> for Advice {
+    fn from(advice: MmapAdvice) -> Self {
+        match advice {
+            MmapAdvice::Normal => Advice::Normal,
+            MmapAdvice::Random => Advice::Random,
+            MmapAdvice::Sequential => Advice::Sequential,
+            MmapAdvice::WillNeed => Advice::WillNeed,
+        }
+    }
+}
+
 /// Configuration for sparse inverted index.
 #[derive(
     Debug, Deserialize, Serialize, JsonSchema, Anonymize, Copy, Clone, PartialEq, Eq, Default,
@@ -59,6 +90,16 @@ pub struct SparseIndexConfig {
     #[serde(default)]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub datatype: Option<VectorStorageDatatype>,
+    /// Keep only this many elements with the largest weights in each posting list of immutable
+    /// indexes. Search becomes approximate, but index size and latency are bounded.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[anonymize(false)]
+    pub max_posting_length: Option<usize>,
+    /// Access pattern hint for memory mapped index files. Default: `normal`
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mmap_advice: Option<MmapAdvice>,
 }
 
 impl SparseIndexConfig {
@@ -71,6 +112,8 @@ impl SparseIndexConfig {
             full_scan_threshold,
             index_type,
             datatype,
+            max_posting_length: None,
+            mmap_advice: None,
         }
     }
 
@@ -1,10 +1,13 @@
 use std::sync::Arc;
 
+use common::counter::hardware_counter::HardwareCounterCell;
 use common::types::TelemetryDetail;
 use parking_lot::Mutex;
+use sparse::index::inverted_index::InvertedIndex;
 
+use super::indices_tracker::IndicesTracker;
 use crate::common::operation_time_statistics::OperationDurationsAggregator;
-use crate::telemetry::VectorIndexSearchesTelemetry;
+use crate::telemetry::{SparsePostingsTelemetry, VectorIndexSearchesTelemetry};
 
 #[derive(Debug)]
 pub struct SparseSearchesTelemetry {
@@ -34,10 +37,13 @@ impl SparseSearchesTelemetry {
             unfiltered_hnsw: Default::default(),
             filtered_small_cardinality: self.small_cardinality.lock().get_statistics(detail),
             filtered_large_cardinality: Default::default(),
+            filtered_acorn: Default::default(),
             filtered_exact: Default::default(),
             filtered_sparse: self.filtered_sparse.lock().get_statistics(detail),
             unfiltered_sparse: self.unfiltered_sparse.lock().get_statistics(detail),
             unfiltered_exact: Default::default(),
+            sparse_postings: None,
+            hnsw_links: None,
         }
     }
 }
@@ -47,3 +53,20 @@ impl Default for SparseSearchesTelemetry {
         Self::new()
     }
 }
+
+/// Collect lengths of all posting lists of the index.
+///
+/// Lengths are known from posting headers, so posting elements are not read.
+pub fn collect_postings_telemetry(
+    inverted_index: &impl InvertedIndex,
+    indices_tracker: &IndicesTracker,
+) -> SparsePostingsTelemetry {
+    let hw_counter = HardwareCounterCell::disposable();
+    let mut telemetry = SparsePostingsTelemetry::default();
+    for (&dim_id, dim_offset) in &indices_tracker.map {
+        if let Some(length) = inverted_index.posting_list_len(dim_offset, &hw_counter) {
+            telemetry.add_posting(dim_id, length);
+        }
+    }
+    telemetry
+}
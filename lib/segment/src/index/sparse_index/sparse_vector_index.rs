@@ -1,8 +1,8 @@
 use std::borrow::Cow;
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
-use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, OnceLock};
 
 use atomic_refcell::AtomicRefCell;
 use common::counter::hardware_counter::HardwareCounterCell;
@@ -18,10 +18,13 @@ use sparse::index::inverted_index::inverted_index_ram_builder::InvertedIndexBuil
 use sparse::index::inverted_index::{INDEX_FILE_NAME, InvertedIndex, OLD_INDEX_FILE_NAME};
 use sparse::index::search_context::SearchContext;
 
+use super::document_frequencies::DocumentFrequencies;
 use super::indices_tracker::IndicesTracker;
 use super::sparse_index_config::SparseIndexType;
+use crate::common::Flusher;
 use crate::common::operation_error::{OperationError, OperationResult, check_process_stopped};
 use crate::common::operation_time_statistics::ScopeDurationMeasurer;
+use crate::data_types::modifier::Modifier;
 use crate::data_types::named_vectors::CowVector;
 use crate::data_types::query_context::VectorQueryContext;
 use crate::data_types::vectors::{QueryVector, VectorInternal, VectorRef};
@@ -30,10 +33,12 @@ use crate::index::field_index::CardinalityEstimation;
 use crate::index::hnsw_index::point_scorer::BatchFilteredSearcher;
 use crate::index::query_estimator::adjust_to_available_vectors;
 use crate::index::sparse_index::sparse_index_config::SparseIndexConfig;
-use crate::index::sparse_index::sparse_search_telemetry::SparseSearchesTelemetry;
+use crate::index::sparse_index::sparse_search_telemetry::{
+    SparseSearchesTelemetry, collect_postings_telemetry,
+};
 use crate::index::struct_payload_index::StructPayloadIndex;
 use crate::index::{PayloadIndex, VectorIndex};
-use crate::telemetry::VectorIndexSearchesTelemetry;
+use crate::telemetry::{SparsePostingsTelemetry, VectorIndexSearchesTelemetry};
 use crate::types::{DEFAULT_SPARSE_FULL_SCAN_THRESHOLD, Filter, SearchParams};
 use crate::vector_storage::query::TransformInto;
 use crate::vector_storage::{Random, VectorStorage, VectorStorageEnum, check_deleted_condition};
@@ -57,7 +62,21 @@ pub struct SparseVectorIndex<TInvertedIndex: InvertedIndex> {
     inverted_index: TInvertedIndex,
     searches_telemetry: SparseSearchesTelemetry,
     indices_tracker: IndicesTracker,
+    /// Materialized IDF statistics of immutable index, if `idf` modifier is enabled
+    document_frequencies: Option<DocumentFrequencies>,
+    /// Posting lengths of immutable index, collected on load
+    postings_telemetry: Option<SparsePostingsTelemetry>,
     scores_memory_pool: ScoresMemoryPool,
+    /// Deleted vectors in the index when they were first counted, see [`Self::deleted_ratio`]
+    deleted_baseline: OnceLock<DeletedBaseline>,
+}
+
+/// Deleted vectors still present in postings, counted once by walking all points
+#[derive(Debug, Clone, Copy)]
+struct DeletedBaseline {
+    deleted_in_index: usize,
+    deleted_point_count: usize,
+    deleted_vector_count: usize,
 }
 
 /// Getters for internals, used for testing.
@@ -86,6 +105,7 @@ impl<TInvertedIndex: InvertedIndex> SparseVectorIndex<TInvertedIndex> {
 
 pub struct SparseVectorIndexOpenArgs<'a, F: FnMut()> {
     pub config: SparseIndexConfig,
+    pub modifier: Option<Modifier>,
     pub id_tracker: Arc<AtomicRefCell<IdTrackerSS>>,
     pub vector_storage: Arc<AtomicRefCell<VectorStorageEnum>>,
     pub payload_index: Arc<AtomicRefCell<StructPayloadIndex>>,
@@ -99,6 +119,7 @@ impl<TInvertedIndex: InvertedIndex> SparseVectorIndex<TInvertedIndex> {
     pub fn open<F: FnMut()>(args: SparseVectorIndexOpenArgs<F>) -> OperationResult<Self> {
         let SparseVectorIndexOpenArgs {
             config,
+            modifier,
             id_tracker,
             vector_storage,
             payload_index,
@@ -108,6 +129,8 @@ impl<TInvertedIndex: InvertedIndex> SparseVectorIndex<TInvertedIndex> {
         } = args;
 
         let config_path = SparseIndexConfig::get_config_path(path);
+        // Advice requested by the caller, applies if the stored config does not specify one
+        let requested_mmap_advice = config.mmap_advice;
 
         let (config, inverted_index, indices_tracker) = if !config.index_type.is_persisted() {
             // RAM mutable case - build inverted index from scratch and use provided config
@@ -116,11 +139,15 @@ impl<TInvertedIndex: InvertedIndex> SparseVectorIndex<TInvertedIndex> {
                 &id_tracker,
                 &vector_storage,
                 path,
+                None,
                 stopped,
                 tick_progress,
             )?;
             (config, inverted_index, indices_tracker)
         } else {
+            // Complete or roll back a compaction, which was interrupted while swapping directories
+            common::fs::recover_replaced_dir(path)?;
+
             Self::try_load(path).or_else(|e| {
                 if fs::exists(path).unwrap_or(true) {
                     log::warn!("Failed to load {path:?}, rebuilding: {e}");
@@ -135,6 +162,7 @@ impl<TInvertedIndex: InvertedIndex> SparseVectorIndex<TInvertedIndex> {
                     &id_tracker,
                     &vector_storage,
                     path,
+                    config.max_posting_length,
                     stopped,
                     tick_progress,
                 )?;
@@ -154,6 +182,32 @@ impl<TInvertedIndex: InvertedIndex> SparseVectorIndex<TInvertedIndex> {
             })?
         };
 
+        if let Some(mmap_advice) = config.mmap_advice.or(requested_mmap_advice) {
+            inverted_index.advise(mmap_advice.into())?;
+        }
+
+        // Posting lists of immutable index don't change, so IDF statistics can be computed once
+        let document_frequencies =
+            if config.index_type.is_persisted() && modifier == Some(Modifier::Idf) {
+                let document_frequencies = match DocumentFrequencies::load(path)? {
+                    Some(document_frequencies) => document_frequencies,
+                    None => {
+                        let document_frequencies =
+                            DocumentFrequencies::from_index(&inverted_index, &indices_tracker);
+                        document_frequencies.save(path)?;
+                        document_frequencies
+                    }
+                };
+                Some(document_frequencies)
+            } else {
+                None
+            };
+
+        let postings_telemetry = config
+            .index_type
+            .is_persisted()
+            .then(|| collect_postings_telemetry(&inverted_index, &indices_tracker));
+
         let searches_telemetry = SparseSearchesTelemetry::new();
         let path = path.to_path_buf();
         let scores_memory_pool = ScoresMemoryPool::new();
@@ -166,7 +220,10 @@ impl<TInvertedIndex: InvertedIndex> SparseVectorIndex<TInvertedIndex> {
             inverted_index,
             searches_telemetry,
             indices_tracker,
+            document_frequencies,
+            postings_telemetry,
             scores_memory_pool,
+            deleted_baseline: OnceLock::new(),
         })
     }
 
@@ -208,6 +265,7 @@ impl<TInvertedIndex: InvertedIndex> SparseVectorIndex<TInvertedIndex> {
         id_tracker: &AtomicRefCell<IdTrackerSS>,
         vector_storage: &AtomicRefCell<VectorStorageEnum>,
         path: &Path,
+        max_posting_length: Option<usize>,
         stopped: &AtomicBool,
         mut tick_progress: impl FnMut(),
     ) -> OperationResult<(TInvertedIndex, IndicesTracker)> {
@@ -215,7 +273,8 @@ impl<TInvertedIndex: InvertedIndex> SparseVectorIndex<TInvertedIndex> {
         let borrowed_id_tracker = id_tracker.borrow();
         let deleted_bitslice = borrowed_vector_storage.deleted_vector_bitslice();
 
-        let mut ram_index_builder = InvertedIndexBuilder::new();
+        let mut ram_index_builder =
+            InvertedIndexBuilder::new().with_max_posting_length(max_posting_length);
         let mut indices_tracker = IndicesTracker::default();
         for id in borrowed_id_tracker.iter_internal_excluding(deleted_bitslice) {
             check_process_stopped(stopped)?;
@@ -255,6 +314,140 @@ impl<TInvertedIndex: InvertedIndex> SparseVectorIndex<TInvertedIndex> {
         &self.inverted_index
     }
 
+    /// Fraction of indexed vectors, which are deleted or outdated by updates, but still present
+    /// in postings.
+    ///
+    /// Mutable index removes vectors from postings directly.
+    pub fn deleted_ratio(&self) -> f64 {
+        if !self.config.index_type.is_persisted() {
+            return 0.0;
+        }
+
+        let indexed_vector_count = self.inverted_index.vector_count();
+        let outdated_vector_count = self.inverted_index.outdated_vector_count();
+        if indexed_vector_count + outdated_vector_count == 0 {
+            return 0.0;
+        }
+
+        let id_tracker = self.id_tracker.borrow();
+        let vector_storage = self.vector_storage.borrow();
+        let deleted_point_count = id_tracker.deleted_point_count();
+        let deleted_vector_count = vector_storage.deleted_vector_count();
+
+        let baseline = self.deleted_baseline.get_or_init(|| {
+            // Same selection of points as on index build, but includes empty vectors,
+            // so the count is never overestimated
+            let available_vector_count = id_tracker
+                .iter_internal_excluding(vector_storage.deleted_vector_bitslice())
+                .count();
+            DeletedBaseline {
+                deleted_in_index: indexed_vector_count.saturating_sub(available_vector_count),
+                deleted_point_count,
+                deleted_vector_count,
+            }
+        });
+
+        // Points are never added to an immutable index, so later deletions are indexed ones.
+        // Points and their vectors may both be deleted, take the larger count to not count twice.
+        let deleted_since_baseline = deleted_point_count
+            .saturating_sub(baseline.deleted_point_count)
+            .max(deleted_vector_count.saturating_sub(baseline.deleted_vector_count));
+        let deleted_in_index =
+            (baseline.deleted_in_index + deleted_since_baseline).min(indexed_vector_count);
+
+        (deleted_in_index + outdated_vector_count) as f64
+            / (indexed_vector_count + outdated_vector_count) as f64
+    }
+
+    /// Flusher of updates applied to a persisted index, see [`InvertedIndex::flusher`].
+    ///
+    /// Indices tracker and document frequencies are saved first, as updates refer to them.
+    pub fn flusher(&self) -> Option<Flusher> {
+        let inverted_index_flusher = self.inverted_index.flusher()?;
+        let path = self.path.clone();
+        let indices_tracker = self.indices_tracker.clone();
+        let document_frequencies = self.document_frequencies.clone();
+        Some(Box::new(move || {
+            indices_tracker.save(&path)?;
+            if let Some(document_frequencies) = &document_frequencies {
+                document_frequencies.save(&path)?;
+            }
+            inverted_index_flusher()?;
+            Ok(())
+        }))
+    }
+
+    /// Rebuild immutable index without deleted vectors, if their ratio exceeds
+    /// `deleted_threshold`. Returns whether compaction happened.
+    ///
+    /// The index directory is written into a staging directory next to it, which then atomically
+    /// replaces the index directory, see [`common::fs::replace_dir`].
+    pub fn compact_if_needed(&mut self, deleted_threshold: f64) -> OperationResult<bool> {
+        if self.deleted_ratio() <= deleted_threshold {
+            return Ok(false);
+        }
+
+        let staged_path = common::fs::staged_dir_path(&self.path);
+        // Leftover of a compaction, which was interrupted before the swap
+        if staged_path.exists() {
+            fs::remove_dir_all(&staged_path)?;
+        }
+        fs::create_dir_all(&staged_path)?;
+
+        // Config, indices tracker and version are not changed by compaction
+        let index_files = TInvertedIndex::files(&self.path);
+        for entry in fs::read_dir(&self.path)? {
+            let path = entry?.path();
+            if path.is_file()
+                && !index_files.contains(&path)
+                && let Some(file_name) = path.file_name()
+            {
+                fs::copy(&path, staged_path.join(file_name))?;
+            }
+        }
+        // Indices of updates, which are merged into the compacted index, may be not flushed yet
+        if self.inverted_index.accepts_updates() {
+            self.indices_tracker.save(&staged_path)?;
+        }
+
+        let compacted = {
+            let id_tracker = self.id_tracker.borrow();
+            let vector_storage = self.vector_storage.borrow();
+            self.inverted_index.compact(&staged_path, |id| {
+                id_tracker.is_deleted_point(id) || vector_storage.is_deleted_vector(id)
+            })?
+        };
+        let Some(compacted) = compacted else {
+            fs::remove_dir_all(&staged_path)?;
+            return Ok(false);
+        };
+
+        let document_frequencies = self
+            .document_frequencies
+            .as_ref()
+            .map(|_| DocumentFrequencies::from_index(&compacted, &self.indices_tracker));
+        if let Some(document_frequencies) = &document_frequencies {
+            document_frequencies.save(&staged_path)?;
+        }
+        // Reopened from the index directory once it is swapped in
+        drop(compacted);
+
+        common::fs::replace_dir(&self.path)?;
+
+        self.inverted_index = TInvertedIndex::open(&self.path)?;
+        if let Some(mmap_advice) = self.config.mmap_advice {
+            self.inverted_index.advise(mmap_advice.into())?;
+        }
+        self.document_frequencies = document_frequencies;
+        self.postings_telemetry = Some(collect_postings_telemetry(
+            &self.inverted_index,
+            &self.indices_tracker,
+        ));
+        self.deleted_baseline = OnceLock::new();
+
+        Ok(true)
+    }
+
     /// Returns the maximum number of results that can be returned by the index for a given sparse vector
     /// Warning: the cost of this function grows with the number of dimensions in the query vector
     #[cfg(feature = "testing")]
@@ -540,6 +733,13 @@ impl<TInvertedIndex: InvertedIndex> SparseVectorIndex<TInvertedIndex> {
         idf: &mut HashMap<DimId, usize>,
         hw_counter: &HardwareCounterCell,
     ) {
+        if let Some(document_frequencies) = &self.document_frequencies {
+            for (dim_id, count) in idf.iter_mut() {
+                *count += document_frequencies.get(*dim_id).unwrap_or(0);
+            }
+            return;
+        }
+
         for (dim_id, count) in idf.iter_mut() {
             if let Some(remapped_dim_id) = self.indices_tracker.remap_index(*dim_id)
                 && let Some(posting_list_len) = self
@@ -591,7 +791,13 @@ impl<TInvertedIndex: InvertedIndex> VectorIndex for SparseVectorIndex<TInvertedI
     }
 
     fn get_telemetry_data(&self, detail: TelemetryDetail) -> VectorIndexSearchesTelemetry {
-        self.searches_telemetry.get_telemetry_data(detail)
+        let mut telemetry = self.searches_telemetry.get_telemetry_data(detail);
+        // Postings of mutable index change with updates, so they are collected on request
+        telemetry.sparse_postings = Some(match &self.postings_telemetry {
+            Some(postings_telemetry) => postings_telemetry.clone(),
+            None => collect_postings_telemetry(&self.inverted_index, &self.indices_tracker),
+        });
+        telemetry
     }
 
     fn files(&self) -> Vec<PathBuf> {
@@ -602,6 +808,7 @@ impl<TInvertedIndex: InvertedIndex> VectorIndex for SparseVectorIndex<TInvertedI
 
         let mut all_files = vec![
             IndicesTracker::file_path(&self.path),
+            DocumentFrequencies::file_path(&self.path),
             self.path.join(VERSION_FILE),
         ];
         all_files.retain(|f| f.exists());
@@ -617,9 +824,12 @@ impl<TInvertedIndex: InvertedIndex> VectorIndex for SparseVectorIndex<TInvertedI
             return vec![];
         }
 
-        let mut immutable_files = vec![
-            self.path.join(VERSION_FILE), // TODO: Is version file immutable?
-        ];
+        let mut immutable_files = vec![self.path.join(VERSION_FILE)]; // TODO: Is version file immutable?
+        // Updates register new indices and change document frequencies
+        if !self.inverted_index.accepts_updates() {
+            immutable_files.push(IndicesTracker::file_path(&self.path));
+            immutable_files.push(DocumentFrequencies::file_path(&self.path));
+        }
         immutable_files.retain(|f| f.exists());
 
         immutable_files.push(config_file);
@@ -666,7 +876,9 @@ impl<TInvertedIndex: InvertedIndex> VectorIndex for SparseVectorIndex<TInvertedI
             (old_vector, new_vector)
         };
 
-        if self.config.index_type != SparseIndexType::MutableRam {
+        if self.config.index_type != SparseIndexType::MutableRam
+            && !self.inverted_index.accepts_updates()
+        {
             return Err(OperationError::service_error(
                 "Cannot update vector in non-appendable index",
             ));
@@ -676,6 +888,14 @@ impl<TInvertedIndex: InvertedIndex> VectorIndex for SparseVectorIndex<TInvertedI
         let old_vector: Option<SparseVector> =
             old_vector.map(SparseVector::try_from).transpose()?;
 
+        // Statistics of persisted index are not recalculated on the fly
+        if self.config.index_type.is_persisted() {
+            if let Some(document_frequencies) = &mut self.document_frequencies {
+                document_frequencies.update(old_vector.as_ref(), &vector);
+            }
+            self.postings_telemetry = None;
+        }
+
         // do not upsert empty vectors into the index
         if !vector.is_empty() {
             self.indices_tracker.register_indices(&vector);
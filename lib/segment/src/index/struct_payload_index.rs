@@ -7,25 +7,34 @@ use atomic_refcell::AtomicRefCell;
 use common::counter::hardware_counter::HardwareCounterCell;
 use common::counter::iterator_hw_measurement::HwMeasurementIteratorExt;
 use common::either_variant::EitherVariant;
+use common::fs::{atomic_save_json, read_json};
 use common::iterator_ext::IteratorExt;
 use common::types::PointOffsetType;
 use fs_err as fs;
 use schemars::_serde_json::Value;
 
+use super::field_index::composite_index::{
+    CompositeCondition, CompositeIndex, CompositeIndexBuilder, CompositeIndexConfig,
+};
 use super::field_index::facet_index::FacetIndexEnum;
+use super::field_index::full_text_index::text_index::MergedTextIndex;
 #[cfg(feature = "rocksdb")]
 use super::field_index::index_selector::IndexSelectorRocksDb;
 use super::field_index::index_selector::{
     IndexSelector, IndexSelectorGridstore, IndexSelectorMmap,
 };
+use super::field_index::trigram_index::TrigramIndex;
 use super::field_index::{FieldIndexBuilderTrait as _, ResolvedHasId};
-use super::payload_config::{FullPayloadIndexType, PayloadFieldSchemaWithIndexType};
+use super::payload_config::{
+    FullPayloadIndexType, PayloadFieldSchemaWithIndexType, PayloadIndices,
+};
 use crate::common::Flusher;
 use crate::common::operation_error::{OperationError, OperationResult};
 use crate::common::utils::IndexesMap;
 use crate::id_tracker::IdTrackerSS;
 use crate::index::field_index::{
-    CardinalityEstimation, FieldIndex, PayloadBlockCondition, PrimaryCondition,
+    CardinalityEstimation, FieldIndex, FieldIndexBuilder, IndexResources, PayloadBlockCondition,
+    PrimaryCondition,
 };
 use crate::index::payload_config::{self, PayloadConfig};
 use crate::index::query_estimator::estimate_filter;
@@ -43,6 +52,11 @@ use crate::types::{
 };
 use crate::vector_storage::{VectorStorage, VectorStorageEnum};
 
+/// Suffix of staging directories of field indexes being rebuilt
+const REBUILD_DIR_SUFFIX: &str = "-rebuild";
+/// Schema of a staged rebuild, saved once its indexes are built
+const REBUILD_SCHEMA_FILE: &str = "schema.json";
+
 #[derive(Debug)]
 #[allow(clippy::enum_variant_names)]
 enum StorageType {
@@ -87,6 +101,10 @@ pub struct StructPayloadIndex {
     pub(super) vector_storages: HashMap<VectorNameBuf, Arc<AtomicRefCell<VectorStorageEnum>>>,
     /// Indexes, associated with fields
     pub field_indexes: IndexesMap,
+    /// Composite indexes of fields, which have them enabled in the schema, by the first field
+    composite_indexes: HashMap<PayloadKeyType, CompositeIndex>,
+    /// Trigram indexes of fields, which have them enabled in the schema
+    trigram_indexes: HashMap<PayloadKeyType, TrigramIndex>,
     config: PayloadConfig,
     /// Root of index persistence dir
     path: PathBuf,
@@ -107,17 +125,25 @@ impl StructPayloadIndex {
         hw_counter: &HardwareCounterCell,
     ) -> Option<CardinalityEstimation> {
         let full_path = JsonPath::extend_or_new(nested_path, &condition.key);
-        self.field_indexes.get(&full_path).and_then(|indexes| {
-            // rewrite condition with fullpath to enable cardinality estimation
-            let full_path_condition = FieldCondition {
-                key: full_path,
-                ..condition.clone()
-            };
+        // rewrite condition with fullpath to enable cardinality estimation
+        let full_path_condition = FieldCondition {
+            key: full_path.clone(),
+            ..condition.clone()
+        };
 
-            indexes
-                .iter()
-                .find_map(|index| index.estimate_cardinality(&full_path_condition, hw_counter))
-        })
+        self.field_indexes
+            .get(&full_path)
+            .and_then(|indexes| {
+                indexes
+                    .iter()
+                    .find_map(|index| index.estimate_cardinality(&full_path_condition, hw_counter))
+            })
+            // Substring matches, which field indexes can't serve, are pre-filtered by trigrams
+            .or_else(|| {
+                self.trigram_indexes
+                    .get(&full_path)?
+                    .estimate_cardinality(&full_path_condition)
+            })
     }
 
     fn query_field<'a>(
@@ -137,6 +163,15 @@ impl StructPayloadIndex {
                 Some(Box::new(ids.resolved_point_offsets.iter().copied()))
             }
             PrimaryCondition::HasVector(_) => None,
+            PrimaryCondition::Composite(composite_condition) => self
+                .composite_indexes
+                .get(&composite_condition.first.key)
+                .filter(|index| index.serves(composite_condition))?
+                .filter(composite_condition, hw_counter),
+            PrimaryCondition::Trigram(field_condition) => self
+                .trigram_indexes
+                .get(&field_condition.key)?
+                .filter(field_condition, hw_counter),
         }
     }
 
@@ -160,6 +195,8 @@ impl StructPayloadIndex {
                 self.load_from_db(field, payload_schema, create_if_missing)?;
             field_indexes.insert(field.clone(), field_index);
             is_dirty |= dirty;
+            self.load_trigram_index(field, &payload_schema.schema)?;
+            self.load_composite_index(field, &payload_schema.schema)?;
         }
 
         // Put updated payload schemas back into the config
@@ -173,6 +210,85 @@ impl StructPayloadIndex {
         Ok(())
     }
 
+    /// Open trigram index of the field, if it is enabled in the schema. Rebuild it, if it can't be
+    /// opened.
+    fn load_trigram_index(
+        &mut self,
+        field: PayloadKeyTypeRef,
+        payload_schema: &PayloadFieldSchema,
+    ) -> OperationResult<()> {
+        if !payload_schema.trigram_index() {
+            if let Some(index) = self.trigram_indexes.remove(field) {
+                index.wipe()?;
+            }
+            return Ok(());
+        }
+
+        let index = match IndexSelector::open_trigram_index(&self.path, field) {
+            Ok(index) => index,
+            Err(err) => {
+                log::debug!("Rebuilding trigram index for field `{field}`: {err}");
+                let hw_counter = HardwareCounterCell::disposable(); // Internal operation
+                let mut builder = IndexSelector::trigram_builder(&self.path, field);
+                self.payload.borrow().iter(
+                    |point_id, point_payload| {
+                        builder.add_point(point_id, &point_payload.get_value(field));
+                        Ok(true)
+                    },
+                    &hw_counter,
+                )?;
+                builder.finalize()?;
+                IndexSelector::open_trigram_index(&self.path, field)?
+            }
+        };
+        self.trigram_indexes.insert(field.to_owned(), index);
+        Ok(())
+    }
+
+    /// Open composite index of the field, if it is enabled in the schema. Build it, if it can't be
+    /// opened.
+    fn load_composite_index(
+        &mut self,
+        field: PayloadKeyTypeRef,
+        payload_schema: &PayloadFieldSchema,
+    ) -> OperationResult<()> {
+        let config = payload_schema
+            .composite_with()
+            .map(|second| CompositeIndexConfig {
+                first: field.to_owned(),
+                second: second.clone(),
+            });
+
+        // Keep the current index, unless it is disabled or over a different second field
+        if let Some(index) = self.composite_indexes.remove(field) {
+            if Some(index.config()) == config.as_ref() {
+                self.composite_indexes.insert(field.to_owned(), index);
+                return Ok(());
+            }
+            index.wipe()?;
+        }
+        let Some(config) = config else {
+            return Ok(());
+        };
+
+        let index = match CompositeIndex::open(&config.dir(&self.path), config.clone(), false) {
+            Ok(index) => index,
+            Err(err) => {
+                log::debug!(
+                    "Building composite index for fields `{}` and `{}`: {err}",
+                    config.first,
+                    config.second,
+                );
+                self.build_composite_index(
+                    config,
+                    &HardwareCounterCell::disposable(), // Internal operation
+                )?
+            }
+        };
+        self.composite_indexes.insert(field.to_owned(), index);
+        Ok(())
+    }
+
     #[cfg_attr(not(feature = "rocksdb"), allow(clippy::needless_pass_by_ref_mut))]
     fn load_from_db(
         &mut self,
@@ -200,13 +316,15 @@ impl StructPayloadIndex {
                     "index selector is not expected to provide null index",
                 );
 
-                // Special null index complements every index.
-                if let Some(null_index) = IndexSelector::new_null_index(
-                    &self.path,
-                    field,
-                    total_point_count,
-                    create_if_missing,
-                )? {
+                // Special null index complements every index, unless disabled in the schema.
+                if payload_schema.schema.null_index()
+                    && let Some(null_index) = IndexSelector::new_null_index(
+                        &self.path,
+                        field,
+                        total_point_count,
+                        create_if_missing,
+                    )?
+                {
                     indexes.push(null_index);
                 }
 
@@ -390,6 +508,8 @@ impl StructPayloadIndex {
             id_tracker,
             vector_storages,
             field_indexes: Default::default(),
+            composite_indexes: HashMap::new(),
+            trigram_indexes: HashMap::new(),
             config,
             path: path.to_owned(),
             visited_pool: Default::default(),
@@ -403,6 +523,7 @@ impl StructPayloadIndex {
             index.save_config()?;
         }
 
+        index.recover_rebuilds()?;
         index.load_all_fields(create)?;
 
         // If we have a RocksDB instance, but no index using it, completely delete it here
@@ -451,37 +572,318 @@ impl StructPayloadIndex {
         field: PayloadKeyTypeRef,
         payload_schema: &PayloadFieldSchema,
         hw_counter: &HardwareCounterCell,
+    ) -> OperationResult<Vec<FieldIndex>> {
+        self.build_field_indexes_with_merged_text(field, payload_schema, None, hw_counter)
+    }
+
+    /// Build indexes of the field, taking full-text index from `merged_text` index of source
+    /// segments instead of tokenizing payloads, if it is built with mmap storage.
+    pub fn build_field_indexes_with_merged_text(
+        &self,
+        field: PayloadKeyTypeRef,
+        payload_schema: &PayloadFieldSchema,
+        merged_text: Option<MergedTextIndex>,
+        hw_counter: &HardwareCounterCell,
+    ) -> OperationResult<Vec<FieldIndex>> {
+        self.build_field_indexes_in(&self.path, field, payload_schema, merged_text, hw_counter)
+    }
+
+    /// Build indexes of the field with files in `dir`.
+    fn build_field_indexes_in(
+        &self,
+        dir: &Path,
+        field: PayloadKeyTypeRef,
+        payload_schema: &PayloadFieldSchema,
+        mut merged_text: Option<MergedTextIndex>,
+        hw_counter: &HardwareCounterCell,
     ) -> OperationResult<Vec<FieldIndex>> {
         let payload_storage = self.payload.borrow();
         let mut builders = self
-            .selector(payload_schema)
+            .selector_in(dir, payload_schema)
             .index_builder(field, payload_schema)?;
 
-        // Special null index complements every index.
-        let null_index = IndexSelector::null_builder(&self.path, field)?;
-        builders.push(null_index);
+        for builder in &mut builders {
+            if let FieldIndexBuilder::FullTextMmapIndex(builder) = builder
+                && let Some(merged_text) = merged_text.take()
+            {
+                builder.set_merged(merged_text);
+            }
+        }
+
+        // Special null index complements every index, unless disabled in the schema.
+        if payload_schema.null_index() {
+            let null_index = IndexSelector::null_builder(dir, field)?;
+            builders.push(null_index);
+        }
 
         for index in &mut builders {
             index.init()?;
         }
 
+        // Trigram index is written next to the field indexes, and opened when they are applied
+        let mut trigram_builder = payload_schema
+            .trigram_index()
+            .then(|| IndexSelector::trigram_builder(dir, field));
+
         payload_storage.iter(
             |point_id, point_payload| {
                 let field_value = &point_payload.get_value(field);
                 for builder in builders.iter_mut() {
                     builder.add_point(point_id, field_value, hw_counter)?;
                 }
+                if let Some(trigram_builder) = &mut trigram_builder {
+                    trigram_builder.add_point(point_id, field_value);
+                }
                 Ok(true)
             },
             hw_counter,
         )?;
 
+        if let Some(trigram_builder) = trigram_builder {
+            trigram_builder.finalize()?;
+        }
+
         builders
             .into_iter()
             .map(|builder| builder.finalize())
             .collect()
     }
 
+    fn rebuild_dir(&self, field: PayloadKeyTypeRef) -> PathBuf {
+        self.path
+            .join(format!("{}{REBUILD_DIR_SUFFIX}", field.filename()))
+    }
+
+    /// Build indexes of an already indexed field with a different `payload_schema`, e.g. after
+    /// changing tokenizer or index params, to be swapped in by [`Self::apply_staged_rebuild`].
+    /// Returns false if the field is not indexed with a different schema, or its indexes can't be
+    /// rebuilt in place.
+    ///
+    /// New indexes are built from stored payloads in a staging directory, while the current ones
+    /// are left intact and keep serving reads. The staged build is complete once the new schema
+    /// is saved next to it.
+    pub fn stage_rebuild(
+        &self,
+        field: PayloadKeyTypeRef,
+        payload_schema: &PayloadFieldSchema,
+        hw_counter: &HardwareCounterCell,
+    ) -> OperationResult<bool> {
+        match self.config.indices.get(field) {
+            Some(current) if current.schema != *payload_schema => {}
+            _ => return Ok(false),
+        }
+
+        // RocksDB indexes have no files of their own, they are deleted and created again
+        #[cfg(feature = "rocksdb")]
+        if matches!(self.selector(payload_schema), IndexSelector::RocksDb(_)) {
+            return Ok(false);
+        }
+
+        let rebuild_dir = self.rebuild_dir(field);
+        // Leftover of a rebuild, which was staged but not applied
+        if rebuild_dir.exists() {
+            fs::remove_dir_all(&rebuild_dir)?;
+        }
+        fs::create_dir_all(&rebuild_dir)?;
+
+        let indexes =
+            self.build_field_indexes_in(&rebuild_dir, field, payload_schema, None, hw_counter)?;
+        let index_types = indexes.iter().map(|i| i.get_full_index_type()).collect();
+        // Close new indexes before their files are moved
+        drop(indexes);
+
+        let mut staged = PayloadIndices::default();
+        staged.insert(
+            field.to_owned(),
+            PayloadFieldSchemaWithIndexType::new(payload_schema.clone(), index_types),
+        );
+        atomic_save_json(&rebuild_dir.join(REBUILD_SCHEMA_FILE), &staged)?;
+
+        Ok(true)
+    }
+
+    /// Swap indexes staged by [`Self::stage_rebuild`] in place of the current indexes of the
+    /// field. Returns false if no rebuild of the field is staged.
+    ///
+    /// Interrupted swap is completed on load, see [`Self::finish_rebuild`].
+    pub fn apply_staged_rebuild(&mut self, field: PayloadKeyTypeRef) -> OperationResult<bool> {
+        let rebuild_dir = self.rebuild_dir(field);
+        let Some(staged) = Self::read_staged_rebuild(&rebuild_dir)? else {
+            return Ok(false);
+        };
+
+        if let Some(indexes) = self.field_indexes.remove(field) {
+            for index in indexes {
+                index.wipe()?;
+            }
+        }
+        if let Some(index) = self.trigram_indexes.remove(field) {
+            index.wipe()?;
+        }
+        if let Some(index) = self.composite_indexes.remove(field) {
+            index.wipe()?;
+        }
+
+        self.finish_rebuild(&rebuild_dir, staged)?;
+
+        let mut payload_schema = self.config.indices.remove(field).ok_or_else(|| {
+            OperationError::service_error(format!("Rebuilt field `{field}` is not in the config"))
+        })?;
+        let (indexes, _) = self.load_from_db(field, &mut payload_schema, false)?;
+        self.field_indexes.insert(field.to_owned(), indexes);
+        self.load_trigram_index(field, &payload_schema.schema)?;
+        self.load_composite_index(field, &payload_schema.schema)?;
+        self.config.indices.insert(field.to_owned(), payload_schema);
+
+        Ok(true)
+    }
+
+    /// Schema of a complete staged rebuild in `rebuild_dir`, if any.
+    fn read_staged_rebuild(rebuild_dir: &Path) -> OperationResult<Option<PayloadIndices>> {
+        let schema_path = rebuild_dir.join(REBUILD_SCHEMA_FILE);
+        if !schema_path.exists() {
+            return Ok(None);
+        }
+        Ok(Some(read_json(&schema_path)?))
+    }
+
+    /// Move files of a complete staged rebuild in place and save its schema in the config.
+    ///
+    /// Files are moved over the ones with the same names, so this can be repeated if it gets
+    /// interrupted. The staging directory, with the schema, is removed last.
+    fn finish_rebuild(
+        &mut self,
+        rebuild_dir: &Path,
+        staged: PayloadIndices,
+    ) -> OperationResult<()> {
+        for entry in fs::read_dir(rebuild_dir)? {
+            let entry = entry?;
+            if entry.file_name() == REBUILD_SCHEMA_FILE {
+                continue;
+            }
+            let target = self.path.join(entry.file_name());
+            if target.is_dir() {
+                fs::remove_dir_all(&target)?;
+            } else if target.exists() {
+                fs::remove_file(&target)?;
+            }
+            fs::rename(entry.path(), target)?;
+        }
+
+        for (field, payload_schema) in staged.iter() {
+            self.config
+                .indices
+                .insert(field.clone(), payload_schema.clone());
+        }
+        self.save_config()?;
+
+        fs::remove_dir_all(rebuild_dir)?;
+        Ok(())
+    }
+
+    /// Complete rebuilds, which were staged but interrupted while being applied, and remove
+    /// incomplete staged builds. Current indexes are intact in the latter case.
+    fn recover_rebuilds(&mut self) -> OperationResult<()> {
+        for entry in fs::read_dir(&self.path)? {
+            let path = entry?.path();
+            let is_rebuild_dir = path.is_dir()
+                && path
+                    .file_name()
+                    .is_some_and(|name| name.to_string_lossy().ends_with(REBUILD_DIR_SUFFIX));
+            if !is_rebuild_dir {
+                continue;
+            }
+
+            match Self::read_staged_rebuild(&path)? {
+                Some(staged) => {
+                    log::debug!("Completing interrupted payload index rebuild in {path:?}");
+                    self.finish_rebuild(&path, staged)?;
+                }
+                None => fs::remove_dir_all(&path)?,
+            }
+        }
+        Ok(())
+    }
+
+    fn build_composite_index(
+        &self,
+        config: CompositeIndexConfig,
+        hw_counter: &HardwareCounterCell,
+    ) -> OperationResult<CompositeIndex> {
+        let dir = config.dir(&self.path);
+        let mut builder = CompositeIndexBuilder::new(config);
+
+        self.payload.borrow().iter(
+            |point_id, point_payload| {
+                builder.add_point(point_id, point_payload);
+                Ok(true)
+            },
+            hw_counter,
+        )?;
+
+        builder.finalize(&dir)
+    }
+
+    /// Mark the point as stale in composite and trigram indexes over any field, for which
+    /// `is_affected` is true.
+    fn mark_stale(
+        &mut self,
+        point_id: PointOffsetType,
+        is_affected: impl Fn(&JsonPath) -> bool,
+        hw_counter: &HardwareCounterCell,
+    ) -> OperationResult<()> {
+        for index in self.composite_indexes.values_mut() {
+            let config = index.config();
+            if is_affected(&config.first) || is_affected(&config.second) {
+                index.mark_stale(point_id, hw_counter)?;
+            }
+        }
+        for (field, index) in &mut self.trigram_indexes {
+            if is_affected(field) {
+                index.mark_stale(point_id, hw_counter)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Estimation of a composite index over a match and a range in `must` of the filter, if it
+    /// yields fewer candidates than any single condition.
+    fn estimate_composite(
+        &self,
+        query: &Filter,
+        hw_counter: &HardwareCounterCell,
+    ) -> Option<CardinalityEstimation> {
+        if self.composite_indexes.is_empty() {
+            return None;
+        }
+        let must = query.must.as_deref()?;
+        let field_conditions = must.iter().filter_map(|condition| match condition {
+            Condition::Field(field_condition) => Some(field_condition),
+            _ => None,
+        });
+
+        let composite = self
+            .composite_indexes
+            .values()
+            .filter_map(|index| {
+                let condition = CompositeCondition::find(index.config(), field_conditions.clone())?;
+                index.estimate_cardinality(&condition)
+            })
+            .min_by_key(|estimation| estimation.max)?;
+
+        let best_single = must
+            .iter()
+            .map(|condition| self.condition_cardinality(condition, None, hw_counter))
+            .filter(|estimation| !estimation.primary_clauses.is_empty())
+            .map(|estimation| estimation.exp)
+            .min();
+
+        match best_single {
+            Some(best_single) if best_single <= composite.max => None,
+            _ => Some(composite),
+        }
+    }
+
     /// Number of available points
     ///
     /// - excludes soft deleted points
@@ -601,6 +1003,15 @@ impl StructPayloadIndex {
         Ok(())
     }
 
+    /// Estimate resources used by all indexes of the field
+    pub fn estimate_resources(&self, field: PayloadKeyTypeRef) -> OperationResult<IndexResources> {
+        let mut resources = IndexResources::default();
+        for index in self.field_indexes.get(field).into_iter().flatten() {
+            resources.merge(index.estimate_resources()?);
+        }
+        Ok(resources)
+    }
+
     pub fn config(&self) -> &PayloadConfig {
         &self.config
     }
@@ -688,6 +1099,15 @@ impl StructPayloadIndex {
 
     /// Select which type of PayloadIndex to use for the field
     fn selector(&self, payload_schema: &PayloadFieldSchema) -> IndexSelector<'_> {
+        self.selector_in(&self.path, payload_schema)
+    }
+
+    /// Selector of new indexes with files in `dir`.
+    fn selector_in<'a>(
+        &'a self,
+        dir: &'a Path,
+        payload_schema: &PayloadFieldSchema,
+    ) -> IndexSelector<'a> {
         let is_on_disk = payload_schema.is_on_disk();
 
         match &self.storage_type {
@@ -697,7 +1117,7 @@ impl StructPayloadIndex {
                 is_appendable: true,
             }),
             StorageType::GridstoreAppendable => {
-                IndexSelector::Gridstore(IndexSelectorGridstore { dir: &self.path })
+                IndexSelector::Gridstore(IndexSelectorGridstore { dir })
             }
             #[cfg(feature = "rocksdb")]
             StorageType::RocksDbNonAppendable(db) => {
@@ -709,15 +1129,11 @@ impl StructPayloadIndex {
                     });
                 }
 
-                IndexSelector::Mmap(IndexSelectorMmap {
-                    dir: &self.path,
-                    is_on_disk,
-                })
+                IndexSelector::Mmap(IndexSelectorMmap { dir, is_on_disk })
+            }
+            StorageType::GridstoreNonAppendable => {
+                IndexSelector::Mmap(IndexSelectorMmap { dir, is_on_disk })
             }
-            StorageType::GridstoreNonAppendable => IndexSelector::Mmap(IndexSelectorMmap {
-                dir: &self.path,
-                is_on_disk,
-            }),
         }
     }
 
@@ -789,6 +1205,12 @@ impl StructPayloadIndex {
                 index.populate()?;
             }
         }
+        for index in self.composite_indexes.values() {
+            index.populate()?;
+        }
+        for index in self.trigram_indexes.values() {
+            index.populate()?;
+        }
         Ok(())
     }
 
@@ -798,6 +1220,12 @@ impl StructPayloadIndex {
                 index.clear_cache()?;
             }
         }
+        for index in self.composite_indexes.values() {
+            index.clear_cache()?;
+        }
+        for index in self.trigram_indexes.values() {
+            index.clear_cache()?;
+        }
         Ok(())
     }
 
@@ -811,6 +1239,100 @@ impl StructPayloadIndex {
         }
         Ok(())
     }
+
+    /// Positions of tokens matched by a full-text condition in the document of the point.
+    ///
+    /// Returns `None` if the point doesn't match, or if there is no full text index storing
+    /// positions for the field.
+    pub fn text_match_positions(
+        &self,
+        condition: &FieldCondition,
+        point_id: PointOffsetType,
+        hw_counter: &HardwareCounterCell,
+    ) -> Option<Vec<u32>> {
+        let r#match = condition.r#match.as_ref()?;
+        self.field_indexes
+            .get(&condition.key)?
+            .iter()
+            .find_map(|index| match index {
+                FieldIndex::FullTextIndex(index) => {
+                    index.text_match_positions(r#match, point_id, hw_counter)
+                }
+                _ => None,
+            })
+    }
+
+    /// Highest ratio of deleted points still present in postings among full text indexes
+    pub fn full_text_deleted_ratio(&self) -> f64 {
+        self.field_indexes
+            .values()
+            .flatten()
+            .filter_map(|index| match index {
+                FieldIndex::FullTextIndex(index) => Some(index.deleted_ratio()),
+                _ => None,
+            })
+            .fold(0.0, f64::max)
+    }
+
+    /// Rebuild immutable full text indexes without deleted points, if ratio of deleted points
+    /// exceeds `deleted_threshold`. Returns number of compacted indexes.
+    pub fn compact_full_text_indexes(&mut self, deleted_threshold: f64) -> OperationResult<usize> {
+        let mut compacted = 0;
+        for (field, field_indexes) in self.field_indexes.iter_mut() {
+            for index in field_indexes {
+                if let FieldIndex::FullTextIndex(index) = index
+                    && index.compact_if_needed(deleted_threshold)?
+                {
+                    log::debug!("Compacted full text index of field {field}");
+                    compacted += 1;
+                }
+            }
+        }
+        Ok(compacted)
+    }
+
+    /// Whether all field indexes accept updates of point values in place
+    pub fn accepts_updates(&self) -> bool {
+        self.field_indexes
+            .values()
+            .flatten()
+            .all(FieldIndex::accepts_updates)
+    }
+
+    /// Highest ratio of points updated on top of immutable map indexes
+    pub fn map_delta_ratio(&self) -> f64 {
+        self.field_indexes
+            .values()
+            .flatten()
+            .filter_map(|index| match index {
+                FieldIndex::IntMapIndex(index) => Some(index.delta_ratio()),
+                FieldIndex::KeywordIndex(index) => Some(index.delta_ratio()),
+                FieldIndex::UuidMapIndex(index) => Some(index.delta_ratio()),
+                _ => None,
+            })
+            .fold(0.0, f64::max)
+    }
+
+    /// Rebuild immutable map indexes with updated values of points, if ratio of updated points
+    /// exceeds `delta_threshold`. Returns number of compacted indexes.
+    pub fn compact_map_indexes(&mut self, delta_threshold: f64) -> OperationResult<usize> {
+        let mut compacted = 0;
+        for (field, field_indexes) in self.field_indexes.iter_mut() {
+            for index in field_indexes {
+                let is_compacted = match index {
+                    FieldIndex::IntMapIndex(index) => index.compact_if_needed(delta_threshold)?,
+                    FieldIndex::KeywordIndex(index) => index.compact_if_needed(delta_threshold)?,
+                    FieldIndex::UuidMapIndex(index) => index.compact_if_needed(delta_threshold)?,
+                    _ => false,
+                };
+                if is_compacted {
+                    log::debug!("Compacted map index of field {field}");
+                    compacted += 1;
+                }
+            }
+        }
+        Ok(compacted)
+    }
 }
 
 impl PayloadIndex for StructPayloadIndex {
@@ -848,6 +1370,8 @@ impl PayloadIndex for StructPayloadIndex {
             .map(|i| i.get_full_index_type())
             .collect();
         self.field_indexes.insert(field.clone(), field_index);
+        self.load_trigram_index(&field, &payload_schema)?;
+        self.load_composite_index(&field, &payload_schema)?;
 
         self.config.indices.insert(
             field,
@@ -900,6 +1424,12 @@ impl PayloadIndex for StructPayloadIndex {
                 index.wipe()?;
             }
         }
+        if let Some(index) = self.trigram_indexes.remove(field) {
+            index.wipe()?;
+        }
+        if let Some(index) = self.composite_indexes.remove(field) {
+            index.wipe()?;
+        }
 
         self.save_config()?;
 
@@ -932,7 +1462,18 @@ impl PayloadIndex for StructPayloadIndex {
         let available_points = self.available_point_count();
         let estimator =
             |condition: &Condition| self.condition_cardinality(condition, None, hw_counter);
-        estimate_filter(&estimator, query, available_points)
+        let estimation = estimate_filter(&estimator, query, available_points);
+
+        // Composite index replaces intersection of postings of its fields
+        match self.estimate_composite(query, hw_counter) {
+            Some(composite) => CardinalityEstimation {
+                primary_clauses: composite.primary_clauses,
+                min: estimation.min,
+                exp: estimation.exp.min(composite.max),
+                max: estimation.max.min(composite.max),
+            },
+            None => estimation,
+        }
     }
 
     fn estimate_nested_cardinality(
@@ -1014,6 +1555,8 @@ impl PayloadIndex for StructPayloadIndex {
             .borrow_mut()
             .overwrite(point_id, payload, hw_counter)?;
 
+        self.mark_stale(point_id, |_| true, hw_counter)?;
+
         for (field, field_index) in &mut self.field_indexes {
             let field_value = payload.get_value(field);
             if !field_value.is_empty() {
@@ -1046,6 +1589,12 @@ impl PayloadIndex for StructPayloadIndex {
                 .set(point_id, payload, hw_counter)?;
         };
 
+        self.mark_stale(
+            point_id,
+            |field| field.is_affected_by_value_set(&payload.0, key.as_ref()),
+            hw_counter,
+        )?;
+
         let updated_payload = self.get_payload(point_id, hw_counter)?;
         for (field, field_index) in &mut self.field_indexes {
             if !field.is_affected_by_value_set(&payload.0, key.as_ref()) {
@@ -1087,6 +1636,11 @@ impl PayloadIndex for StructPayloadIndex {
         key: PayloadKeyTypeRef,
         hw_counter: &HardwareCounterCell,
     ) -> OperationResult<Vec<Value>> {
+        self.mark_stale(
+            point_id,
+            |field| field.is_affected_by_value_remove(key),
+            hw_counter,
+        )?;
         if let Some(indexes) = self.field_indexes.get_mut(key) {
             for index in indexes {
                 index.remove_point(point_id)?;
@@ -1101,6 +1655,7 @@ impl PayloadIndex for StructPayloadIndex {
         hw_counter: &HardwareCounterCell,
     ) -> OperationResult<Option<Payload>> {
         self.clear_index_for_point(point_id)?;
+        self.mark_stale(point_id, |_| true, hw_counter)?;
         self.payload.borrow_mut().clear(point_id, hw_counter)
     }
 
@@ -1114,6 +1669,12 @@ impl PayloadIndex for StructPayloadIndex {
                 flushers.push(index.flusher());
             }
         }
+        for index in self.composite_indexes.values() {
+            flushers.push(index.flusher());
+        }
+        for index in self.trigram_indexes.values() {
+            flushers.push(index.flusher());
+        }
         flushers.push(self.payload.borrow().flusher());
 
         Box::new(move || {
@@ -1150,6 +1711,16 @@ impl PayloadIndex for StructPayloadIndex {
             .values()
             .flat_map(|indexes| indexes.iter().flat_map(|index| index.files().into_iter()))
             .collect::<Vec<PathBuf>>();
+        files.extend(
+            self.composite_indexes
+                .values()
+                .flat_map(|index| index.files()),
+        );
+        files.extend(
+            self.trigram_indexes
+                .values()
+                .flat_map(|index| index.files()),
+        );
         files.push(self.config_path());
         files
     }
@@ -1178,12 +1749,16 @@ mod tests {
     use uuid::Uuid;
 
     use super::*;
+    use crate::data_types::index::KeywordIndexParams;
     use crate::data_types::vectors::only_default_vector;
     use crate::entry::{NonAppendableSegmentEntry, SegmentEntry};
     use crate::index::payload_config::{IndexMutability, PayloadIndexType};
+    use crate::segment::Segment;
     use crate::segment_constructor::load_segment;
     use crate::segment_constructor::simple_segment_constructor::build_simple_segment;
-    use crate::types::{Distance, PayloadSchemaType};
+    use crate::types::{
+        Distance, Match, PayloadField, PayloadSchemaParams, PayloadSchemaType, ValueVariants,
+    };
 
     #[test]
     fn test_load_payload_index() {
@@ -1255,4 +1830,368 @@ mod tests {
         let schema = payload_config.indices.get(&key).unwrap();
         check_index_types(&schema.types);
     }
+
+    #[test]
+    fn test_disabled_null_index() {
+        let dir = Builder::new().prefix("payload_dir").tempdir().unwrap();
+        let hw_counter = HardwareCounterCell::new();
+        let key = JsonPath::from_str("name").unwrap();
+
+        let mut segment = build_simple_segment(dir.path(), 2, Distance::Dot).unwrap();
+        for (point_id, data) in [r#"{"name": "John Doe"}"#, r#"{"name": null}"#, r"{}"]
+            .into_iter()
+            .enumerate()
+        {
+            let point_id = point_id as u64;
+            segment
+                .upsert_point(
+                    0,
+                    point_id.into(),
+                    only_default_vector(&[1.0, 1.0]),
+                    &hw_counter,
+                )
+                .unwrap();
+            let payload: Payload = serde_json::from_str(data).unwrap();
+            segment
+                .set_full_payload(0, point_id.into(), &payload, &hw_counter)
+                .unwrap();
+        }
+
+        let schema =
+            PayloadFieldSchema::FieldParams(PayloadSchemaParams::Keyword(KeywordIndexParams {
+                null_index: Some(false),
+                ..Default::default()
+            }));
+        segment
+            .create_field_index(0, &key, Some(&schema), &hw_counter)
+            .unwrap();
+
+        let payload_config_path = segment.segment_path.join("payload_index/config.json");
+        let payload_config = PayloadConfig::load(&payload_config_path).unwrap();
+        let types = &payload_config.indices.get(&key).unwrap().types;
+        assert_eq!(types.len(), 1);
+        assert_eq!(types[0].index_type, PayloadIndexType::KeywordIndex);
+
+        // Conditions on emptiness fall back to reading payloads
+        let is_stopped = AtomicBool::new(false);
+        let is_empty = Filter::new_must(Condition::IsEmpty(IsEmptyCondition {
+            is_empty: PayloadField { key: key.clone() },
+        }));
+        let mut empty =
+            segment.read_filtered(None, None, Some(&is_empty), &is_stopped, &hw_counter);
+        empty.sort();
+        assert_eq!(empty, vec![1.into(), 2.into()]);
+
+        let is_null = Filter::new_must(Condition::IsNull(IsNullCondition {
+            is_null: PayloadField { key },
+        }));
+        let null = segment.read_filtered(None, None, Some(&is_null), &is_stopped, &hw_counter);
+        assert_eq!(null, vec![1.into()]);
+    }
+
+    #[test]
+    fn test_composite_index_query() {
+        let dir = Builder::new().prefix("payload_dir").tempdir().unwrap();
+        let hw_counter = HardwareCounterCell::new();
+
+        let mut segment = build_simple_segment(dir.path(), 2, Distance::Dot).unwrap();
+        for point_id in 0..20u64 {
+            let tenant = if point_id % 2 == 0 { "a" } else { "b" };
+            let payload: Payload =
+                serde_json::from_value(serde_json::json!({"tenant": tenant, "ts": point_id}))
+                    .unwrap();
+            segment
+                .upsert_point(
+                    0,
+                    point_id.into(),
+                    only_default_vector(&[1.0, 1.0]),
+                    &hw_counter,
+                )
+                .unwrap();
+            segment
+                .set_full_payload(0, point_id.into(), &payload, &hw_counter)
+                .unwrap();
+        }
+
+        let schema =
+            PayloadFieldSchema::FieldParams(PayloadSchemaParams::Keyword(KeywordIndexParams {
+                composite_with: Some(JsonPath::new("ts")),
+                ..Default::default()
+            }));
+        segment
+            .create_field_index(0, &JsonPath::new("tenant"), Some(&schema), &hw_counter)
+            .unwrap();
+
+        let filter = Filter::new_must(Condition::Field(FieldCondition::new_match(
+            JsonPath::new("tenant"),
+            "a".to_owned().into(),
+        )))
+        .merge_owned(Filter::new_must(Condition::Field(
+            FieldCondition::new_range(
+                JsonPath::new("ts"),
+                crate::types::Range {
+                    gte: Some(ordered_float::OrderedFloat(4.0)),
+                    lt: Some(ordered_float::OrderedFloat(12.0)),
+                    ..Default::default()
+                },
+            ),
+        )));
+
+        let estimation = segment
+            .payload_index
+            .borrow()
+            .estimate_cardinality(&filter, &hw_counter);
+        assert!(matches!(
+            estimation.primary_clauses.as_slice(),
+            [PrimaryCondition::Composite(_)],
+        ));
+
+        let is_stopped = AtomicBool::new(false);
+        let mut found = segment.read_filtered(None, None, Some(&filter), &is_stopped, &hw_counter);
+        found.sort();
+        assert_eq!(found, vec![4.into(), 6.into(), 8.into(), 10.into()]);
+
+        // Changed payload is not in the index anymore, but must still be found
+        let payload: Payload =
+            serde_json::from_value(serde_json::json!({"tenant": "a", "ts": 5})).unwrap();
+        segment
+            .set_full_payload(0, 1.into(), &payload, &hw_counter)
+            .unwrap();
+        segment
+            .delete_payload(0, 4.into(), &JsonPath::new("ts"), &hw_counter)
+            .unwrap();
+
+        let mut found = segment.read_filtered(None, None, Some(&filter), &is_stopped, &hw_counter);
+        found.sort();
+        assert_eq!(found, vec![1.into(), 6.into(), 8.into(), 10.into()]);
+
+        // Stale points survive reopening
+        segment.flush(true).unwrap();
+        let segment_path = segment.segment_path.clone();
+        drop(segment);
+        let segment = load_segment(&segment_path, Uuid::nil(), &AtomicBool::new(false)).unwrap();
+        assert!(
+            segment
+                .payload_index
+                .borrow()
+                .composite_indexes
+                .contains_key(&JsonPath::new("tenant")),
+        );
+        let mut found = segment.read_filtered(None, None, Some(&filter), &is_stopped, &hw_counter);
+        found.sort();
+        assert_eq!(found, vec![1.into(), 6.into(), 8.into(), 10.into()]);
+    }
+
+    #[test]
+    fn test_trigram_index_query() {
+        let dir = Builder::new().prefix("payload_dir").tempdir().unwrap();
+        let hw_counter = HardwareCounterCell::new();
+        let key = JsonPath::new("name");
+
+        let mut segment = build_simple_segment(dir.path(), 2, Distance::Dot).unwrap();
+        let names = ["hello world", "yellow", "Hello", "mellow yellow", "help"];
+        for (point_id, name) in names.into_iter().enumerate() {
+            let point_id = point_id as u64;
+            let payload: Payload =
+                serde_json::from_value(serde_json::json!({ "name": name })).unwrap();
+            segment
+                .upsert_point(
+                    0,
+                    point_id.into(),
+                    only_default_vector(&[1.0, 1.0]),
+                    &hw_counter,
+                )
+                .unwrap();
+            segment
+                .set_full_payload(0, point_id.into(), &payload, &hw_counter)
+                .unwrap();
+        }
+
+        let schema =
+            PayloadFieldSchema::FieldParams(PayloadSchemaParams::Keyword(KeywordIndexParams {
+                trigram_index: Some(true),
+                ..Default::default()
+            }));
+        segment
+            .create_field_index(0, &key, Some(&schema), &hw_counter)
+            .unwrap();
+
+        let filter = Filter::new_must(Condition::Field(FieldCondition::new_match(
+            key.clone(),
+            Match::new_text("ellow"),
+        )));
+        let estimation = segment
+            .payload_index
+            .borrow()
+            .estimate_cardinality(&filter, &hw_counter);
+        assert!(matches!(
+            estimation.primary_clauses.as_slice(),
+            [PrimaryCondition::Trigram(_)],
+        ));
+
+        let is_stopped = AtomicBool::new(false);
+        let mut found = segment.read_filtered(None, None, Some(&filter), &is_stopped, &hw_counter);
+        found.sort();
+        assert_eq!(found, vec![1.into(), 3.into()]);
+
+        // Changed payload is not in the index anymore, but must still be found
+        let payload: Payload =
+            serde_json::from_value(serde_json::json!({ "name": "fellows" })).unwrap();
+        segment
+            .set_full_payload(0, 4.into(), &payload, &hw_counter)
+            .unwrap();
+        segment
+            .delete_payload(0, 1.into(), &key, &hw_counter)
+            .unwrap();
+
+        let mut found = segment.read_filtered(None, None, Some(&filter), &is_stopped, &hw_counter);
+        found.sort();
+        assert_eq!(found, vec![3.into(), 4.into()]);
+
+        // Stale points survive reopening
+        segment.flush(true).unwrap();
+        let segment_path = segment.segment_path.clone();
+        drop(segment);
+        let segment = load_segment(&segment_path, Uuid::nil(), &AtomicBool::new(false)).unwrap();
+        let mut found = segment.read_filtered(None, None, Some(&filter), &is_stopped, &hw_counter);
+        found.sort();
+        assert_eq!(found, vec![3.into(), 4.into()]);
+    }
+
+    #[test]
+    fn test_rebuild_field_index() {
+        let dir = Builder::new().prefix("payload_dir").tempdir().unwrap();
+        let hw_counter = HardwareCounterCell::new();
+        let key = JsonPath::new("name");
+
+        let mut segment = build_simple_segment(dir.path(), 2, Distance::Dot).unwrap();
+        let names = ["hello world", "yellow", "Hello", "mellow yellow", "help"];
+        for (point_id, name) in names.into_iter().enumerate() {
+            let point_id = point_id as u64;
+            let payload: Payload =
+                serde_json::from_value(serde_json::json!({ "name": name })).unwrap();
+            segment
+                .upsert_point(
+                    0,
+                    point_id.into(),
+                    only_default_vector(&[1.0, 1.0]),
+                    &hw_counter,
+                )
+                .unwrap();
+            segment
+                .set_full_payload(0, point_id.into(), &payload, &hw_counter)
+                .unwrap();
+        }
+
+        let keyword_schema = |trigram_index| {
+            PayloadFieldSchema::FieldParams(PayloadSchemaParams::Keyword(KeywordIndexParams {
+                trigram_index: Some(trigram_index),
+                ..Default::default()
+            }))
+        };
+
+        // Field is not indexed yet
+        assert!(
+            !segment
+                .stage_field_index_rebuild(1, &key, &keyword_schema(true), &hw_counter)
+                .unwrap()
+        );
+
+        segment
+            .create_field_index(1, &key, Some(&keyword_schema(false)), &hw_counter)
+            .unwrap();
+
+        // Field is indexed with the same schema
+        assert!(
+            !segment
+                .stage_field_index_rebuild(2, &key, &keyword_schema(false), &hw_counter)
+                .unwrap()
+        );
+
+        let filter = Filter::new_must(Condition::Field(FieldCondition::new_match(
+            key.clone(),
+            Match::new_text("ellow"),
+        )));
+        let primary_clauses = |segment: &Segment| {
+            segment
+                .payload_index
+                .borrow()
+                .estimate_cardinality(&filter, &hw_counter)
+                .primary_clauses
+        };
+        assert!(primary_clauses(&segment).is_empty());
+
+        // Current index is intact until the staged one is applied
+        assert!(
+            segment
+                .stage_field_index_rebuild(2, &key, &keyword_schema(true), &hw_counter)
+                .unwrap()
+        );
+        assert_eq!(
+            segment.get_indexed_fields().get(&key),
+            Some(&keyword_schema(false)),
+        );
+        assert!(primary_clauses(&segment).is_empty());
+
+        assert!(segment.apply_field_index_rebuild(2, &key).unwrap());
+        assert_eq!(
+            segment.get_indexed_fields().get(&key),
+            Some(&keyword_schema(true)),
+        );
+        assert!(matches!(
+            primary_clauses(&segment).as_slice(),
+            [PrimaryCondition::Trigram(_)],
+        ));
+
+        let is_stopped = AtomicBool::new(false);
+        let keyword_filter = Filter::new_must(Condition::Field(FieldCondition::new_match(
+            key.clone(),
+            Match::new_value(ValueVariants::String("yellow".to_string())),
+        )));
+        let found =
+            segment.read_filtered(None, None, Some(&keyword_filter), &is_stopped, &hw_counter);
+        assert_eq!(found, vec![1.into()]);
+
+        // Rebuilt index is loaded after reopening, and no staging files are left
+        segment.flush(true).unwrap();
+        let segment_path = segment.segment_path.clone();
+        drop(segment);
+        let segment = load_segment(&segment_path, Uuid::nil(), &AtomicBool::new(false)).unwrap();
+        assert!(matches!(
+            primary_clauses(&segment).as_slice(),
+            [PrimaryCondition::Trigram(_)],
+        ));
+        let mut found = segment.read_filtered(None, None, Some(&filter), &is_stopped, &hw_counter);
+        found.sort();
+        assert_eq!(found, vec![1.into(), 3.into()]);
+
+        // Staged rebuild, which was not applied, is completed on load
+        assert!(
+            segment
+                .stage_field_index_rebuild(3, &key, &keyword_schema(false), &hw_counter)
+                .unwrap()
+        );
+        drop(segment);
+        let segment = load_segment(&segment_path, Uuid::nil(), &AtomicBool::new(false)).unwrap();
+        assert_eq!(
+            segment.get_indexed_fields().get(&key),
+            Some(&keyword_schema(false)),
+        );
+        assert!(primary_clauses(&segment).is_empty());
+        let found =
+            segment.read_filtered(None, None, Some(&keyword_filter), &is_stopped, &hw_counter);
+        assert_eq!(found, vec![1.into()]);
+
+        let leftovers = walkdir::WalkDir::new(&segment_path)
+            .into_iter()
+            .filter_map(Result::ok)
+            .filter(|entry| {
+                entry
+                    .file_name()
+                    .to_string_lossy()
+                    .ends_with(REBUILD_DIR_SUFFIX)
+            })
+            .count();
+        assert_eq!(leftovers, 0);
+    }
 }
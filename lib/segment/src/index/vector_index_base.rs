@@ -4,6 +4,7 @@ use std::path::PathBuf;
 use common::counter::hardware_counter::HardwareCounterCell;
 use common::types::{PointOffsetType, ScoredPointOffset, TelemetryDetail};
 use half::f16;
+use rayon::ThreadPool;
 use sparse::common::types::{DimId, QuantizedU8};
 use sparse::index::inverted_index::InvertedIndex;
 use sparse::index::inverted_index::inverted_index_compressed_immutable_ram::InvertedIndexCompressedImmutableRam;
@@ -12,9 +13,11 @@ use sparse::index::inverted_index::inverted_index_immutable_ram::InvertedIndexIm
 use sparse::index::inverted_index::inverted_index_mmap::InvertedIndexMmap;
 use sparse::index::inverted_index::inverted_index_ram::InvertedIndexRam;
 
+use super::hnsw_index::graph_links::GraphLinksFormat;
 use super::hnsw_index::hnsw::HNSWIndex;
 use super::plain_vector_index::PlainVectorIndex;
 use super::sparse_index::sparse_vector_index::SparseVectorIndex;
+use crate::common::Flusher;
 use crate::common::operation_error::OperationResult;
 use crate::data_types::query_context::VectorQueryContext;
 use crate::data_types::vectors::{QueryVector, VectorRef};
@@ -133,6 +136,43 @@ impl VectorIndexEnum {
         Ok(())
     }
 
+    /// Flusher of index state, which is not persisted with point updates, like tuned HNSW `ef`
+    /// or updates of immutable sparse index.
+    pub fn flusher(&self) -> Option<Flusher> {
+        match self {
+            Self::Hnsw(index) => index.flusher(),
+            Self::SparseCompressedMmapF32(index) => index.flusher(),
+            Self::SparseCompressedMmapF16(index) => index.flusher(),
+            Self::SparseCompressedMmapU8(index) => index.flusher(),
+            Self::Plain(_)
+            | Self::SparseRam(_)
+            | Self::SparseImmutableRam(_)
+            | Self::SparseMmap(_)
+            | Self::SparseCompressedImmutableRamF32(_)
+            | Self::SparseCompressedImmutableRamF16(_)
+            | Self::SparseCompressedImmutableRamU8(_) => None,
+        }
+    }
+
+    /// Tune index parameters, which adapt to searches, like default HNSW `ef`.
+    ///
+    /// May run exact searches, so it must not be called on the search or update path.
+    pub fn tune(&self) {
+        match self {
+            Self::Hnsw(index) => index.tune_ef(),
+            Self::Plain(_)
+            | Self::SparseRam(_)
+            | Self::SparseImmutableRam(_)
+            | Self::SparseMmap(_)
+            | Self::SparseCompressedImmutableRamF32(_)
+            | Self::SparseCompressedImmutableRamF16(_)
+            | Self::SparseCompressedImmutableRamU8(_)
+            | Self::SparseCompressedMmapF32(_)
+            | Self::SparseCompressedMmapF16(_)
+            | Self::SparseCompressedMmapU8(_) => {}
+        }
+    }
+
     pub fn clear_cache(&self) -> OperationResult<()> {
         match self {
             Self::Plain(_) => {}
@@ -190,6 +230,108 @@ impl VectorIndexEnum {
             Self::SparseCompressedMmapU8(index) => index.inverted_index().vector_count(),
         }
     }
+
+    /// Format of HNSW graph links, `None` for other indexes.
+    pub fn graph_links_format(&self) -> Option<GraphLinksFormat> {
+        match self {
+            Self::Hnsw(index) => Some(index.graph().links_format()),
+            Self::Plain(_)
+            | Self::SparseRam(_)
+            | Self::SparseImmutableRam(_)
+            | Self::SparseMmap(_)
+            | Self::SparseCompressedImmutableRamF32(_)
+            | Self::SparseCompressedImmutableRamF16(_)
+            | Self::SparseCompressedImmutableRamU8(_)
+            | Self::SparseCompressedMmapF32(_)
+            | Self::SparseCompressedMmapF16(_)
+            | Self::SparseCompressedMmapU8(_) => None,
+        }
+    }
+
+    /// Fraction of indexed vectors, which are deleted but still present in the index.
+    ///
+    /// Only sparse indexes are taken into account.
+    pub fn deleted_ratio(&self) -> f64 {
+        match self {
+            Self::Plain(_) | Self::Hnsw(_) => 0.0,
+            Self::SparseRam(index) => index.deleted_ratio(),
+            // Legacy uncompressed formats can't be compacted
+            Self::SparseImmutableRam(_) | Self::SparseMmap(_) => 0.0,
+            Self::SparseCompressedImmutableRamF32(index) => index.deleted_ratio(),
+            Self::SparseCompressedImmutableRamF16(index) => index.deleted_ratio(),
+            Self::SparseCompressedImmutableRamU8(index) => index.deleted_ratio(),
+            Self::SparseCompressedMmapF32(index) => index.deleted_ratio(),
+            Self::SparseCompressedMmapF16(index) => index.deleted_ratio(),
+            Self::SparseCompressedMmapU8(index) => index.deleted_ratio(),
+        }
+    }
+
+    /// Drop deleted vectors from immutable sparse index if their ratio exceeds
+    /// `deleted_threshold`. Returns whether compaction happened.
+    pub fn compact_if_needed(&mut self, deleted_threshold: f64) -> OperationResult<bool> {
+        match self {
+            Self::Plain(_) | Self::Hnsw(_) => Ok(false),
+            Self::SparseRam(index) => index.compact_if_needed(deleted_threshold),
+            Self::SparseImmutableRam(_) | Self::SparseMmap(_) => Ok(false),
+            Self::SparseCompressedImmutableRamF32(index) => {
+                index.compact_if_needed(deleted_threshold)
+            }
+            Self::SparseCompressedImmutableRamF16(index) => {
+                index.compact_if_needed(deleted_threshold)
+            }
+            Self::SparseCompressedImmutableRamU8(index) => {
+                index.compact_if_needed(deleted_threshold)
+            }
+            Self::SparseCompressedMmapF32(index) => index.compact_if_needed(deleted_threshold),
+            Self::SparseCompressedMmapF16(index) => index.compact_if_needed(deleted_threshold),
+            Self::SparseCompressedMmapU8(index) => index.compact_if_needed(deleted_threshold),
+        }
+    }
+
+    /// Fraction of indexed vectors, which are deleted, but still linked from the HNSW graph.
+    pub fn unhealed_deleted_ratio(&self) -> f64 {
+        match self {
+            Self::Hnsw(index) => index.unhealed_deleted_ratio(),
+            Self::Plain(_)
+            | Self::SparseRam(_)
+            | Self::SparseImmutableRam(_)
+            | Self::SparseMmap(_)
+            | Self::SparseCompressedImmutableRamF32(_)
+            | Self::SparseCompressedImmutableRamF16(_)
+            | Self::SparseCompressedImmutableRamU8(_)
+            | Self::SparseCompressedMmapF32(_)
+            | Self::SparseCompressedMmapF16(_)
+            | Self::SparseCompressedMmapU8(_) => 0.0,
+        }
+    }
+
+    /// Re-link neighbors of deleted points in the HNSW graph, if their ratio exceeds
+    /// `deleted_threshold`. Returns whether healing happened.
+    pub fn heal_if_needed(
+        &mut self,
+        deleted_threshold: f64,
+        pool: &ThreadPool,
+    ) -> OperationResult<bool> {
+        match self {
+            Self::Hnsw(index) => {
+                if index.unhealed_deleted_ratio() <= deleted_threshold {
+                    return Ok(false);
+                }
+                index.heal_deleted_points(pool)?;
+                Ok(true)
+            }
+            Self::Plain(_)
+            | Self::SparseRam(_)
+            | Self::SparseImmutableRam(_)
+            | Self::SparseMmap(_)
+            | Self::SparseCompressedImmutableRamF32(_)
+            | Self::SparseCompressedImmutableRamF16(_)
+            | Self::SparseCompressedImmutableRamU8(_)
+            | Self::SparseCompressedMmapF32(_)
+            | Self::SparseCompressedMmapF16(_)
+            | Self::SparseCompressedMmapU8(_) => Ok(false),
+        }
+    }
 }
 
 impl VectorIndex for VectorIndexEnum {
@@ -5,10 +5,11 @@ use std::str::FromStr;
 use ordered_float::OrderedFloat;
 use serde_json::Value;
 
+use crate::index::field_index::full_text_index::fuzzy::{auto_max_edits, is_within_edits};
 use crate::types::{
     AnyVariants, DateTimePayloadType, FieldCondition, FloatPayloadType, GeoBoundingBox, GeoPoint,
     GeoPolygon, GeoRadius, Match, MatchAny, MatchExcept, MatchPhrase, MatchText, MatchTextAny,
-    MatchValue, Range, RangeInterface, ValueVariants, ValuesCount,
+    MatchTextFuzzy, MatchValue, Range, RangeInterface, ValueVariants, ValuesCount,
 };
 
 /// Threshold representing the point to which iterating through an IndexSet is more efficient than using hashing.
@@ -172,6 +173,19 @@ impl ValueChecker for Match {
                     .any(|token| stored.contains(token)),
                 _ => false,
             },
+            Match::TextFuzzy(MatchTextFuzzy { text_fuzzy }) => match payload {
+                Value::String(stored) => {
+                    let stored = stored.to_lowercase();
+                    text_fuzzy.split_whitespace().all(|token| {
+                        let token = token.to_lowercase();
+                        let max_edits = auto_max_edits(&token);
+                        stored
+                            .split_whitespace()
+                            .any(|word| is_within_edits(word, &token, max_edits))
+                    })
+                }
+                _ => false,
+            },
             Match::Any(MatchAny { any }) => match (payload, any) {
                 (Value::String(stored), AnyVariants::Strings(list)) => {
                     if list.len() < INDEXSET_ITER_THRESHOLD {
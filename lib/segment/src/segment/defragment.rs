@@ -0,0 +1,162 @@
+use common::counter::hardware_counter::HardwareCounterCell;
+use common::types::PointOffsetType;
+
+use super::Segment;
+use crate::common::operation_error::{OperationError, OperationResult};
+use crate::entry::entry_point::NonAppendableSegmentEntry;
+use crate::index::{PayloadIndex, VectorIndexEnum};
+use crate::vector_storage::VectorStorage;
+
+impl Segment {
+    /// Whether deleted points of this segment can be reclaimed in place with
+    /// [`Segment::defragment`]
+    pub fn is_defragmentable(&self) -> bool {
+        self.appendable_flag
+            && self.error_status.is_none()
+            && self.id_tracker.borrow().supports_compaction()
+            && self.vector_data.values().all(|vector_data| {
+                matches!(
+                    &*vector_data.vector_index.borrow(),
+                    VectorIndexEnum::Plain(_)
+                ) && vector_data.vector_storage.borrow().is_defragmentable()
+                    && vector_data.quantized_vectors.borrow().is_none()
+            })
+    }
+
+    /// Reclaim space of deleted points without rebuilding the segment
+    ///
+    /// Live points from the tail are moved into slots of deleted points, after which all storages
+    /// are truncated to the number of live points. Points are only ever written into deleted
+    /// slots, so the persisted segment stays valid until the ID tracker switches to the new
+    /// internal IDs at once.
+    ///
+    /// Returns the number of reclaimed internal IDs.
+    pub fn defragment(&mut self) -> OperationResult<usize> {
+        if !self.is_defragmentable() {
+            return Err(OperationError::service_error(
+                "Segment does not support in-place defragmentation",
+            ));
+        }
+
+        let (total, len, moves) = {
+            let id_tracker = self.id_tracker.borrow();
+            let total = id_tracker.total_point_count();
+            let len = id_tracker.available_point_count();
+
+            let holes = (0..len as PointOffsetType).filter(|&id| id_tracker.is_deleted_point(id));
+            let sources = id_tracker
+                .iter_internal()
+                .filter(|&id| id as usize >= len)
+                .collect::<Vec<_>>();
+            let moves: Vec<_> = sources.into_iter().zip(holes).collect();
+            (total, len, moves)
+        };
+
+        let has_vector_tail = self
+            .vector_data
+            .values()
+            .any(|vector_data| vector_data.vector_storage.borrow().total_vector_count() > len);
+        if moves.is_empty() && total == len && !has_vector_tail {
+            return Ok(0);
+        }
+
+        // Persist all pending changes, so that nothing refers to old internal IDs afterwards
+        self.flush(true)?;
+
+        let hw_counter = HardwareCounterCell::disposable(); // Internal operation
+
+        for vector_data in self.vector_data.values() {
+            let mut vector_storage = vector_data.vector_storage.borrow_mut();
+            vector_storage.move_vectors(&moves)?;
+            vector_storage.flusher()()?;
+        }
+
+        {
+            let mut payload_index = self.payload_index.borrow_mut();
+            for &(from, to) in &moves {
+                let payload = payload_index.get_payload(from, &hw_counter)?;
+                if !payload.is_empty() {
+                    payload_index.overwrite_payload(to, &payload, &hw_counter)?;
+                }
+            }
+            payload_index.flusher()()?;
+        }
+
+        self.id_tracker.borrow_mut().compact(&moves, len)?;
+
+        {
+            let mut payload_index = self.payload_index.borrow_mut();
+            for &(from, _) in &moves {
+                payload_index.clear_payload(from, &hw_counter)?;
+            }
+            payload_index.flusher()()?;
+        }
+
+        for vector_data in self.vector_data.values() {
+            let mut vector_storage = vector_data.vector_storage.borrow_mut();
+            vector_storage.truncate(len)?;
+            vector_storage.flusher()()?;
+        }
+
+        Ok(total.saturating_sub(len))
+    }
+
+    /// Drop deleted points from postings of immutable indexes, whose ratio of deleted points
+    /// exceeds `deleted_threshold`, without rebuilding the segment
+    ///
+    /// Immutable map indexes, whose ratio of updated points exceeds the threshold, are rebuilt
+    /// with the updated values.
+    ///
+    /// Returns the number of compacted indexes.
+    pub fn compact_postings(&mut self, deleted_threshold: f64) -> OperationResult<usize> {
+        let mut compacted = {
+            let mut payload_index = self.payload_index.borrow_mut();
+            payload_index.compact_full_text_indexes(deleted_threshold)?
+                + payload_index.compact_map_indexes(deleted_threshold)?
+        };
+        for (vector_name, vector_data) in &self.vector_data {
+            if vector_data
+                .vector_index
+                .borrow_mut()
+                .compact_if_needed(deleted_threshold)?
+            {
+                log::debug!("Compacted sparse index of vector {vector_name}");
+                compacted += 1;
+            }
+        }
+        Ok(compacted)
+    }
+
+    /// Re-link neighbors of deleted points in HNSW graphs, whose ratio of deleted but still
+    /// linked points exceeds `deleted_threshold`, without rebuilding the segment
+    ///
+    /// Returns the number of healed graphs.
+    pub fn heal_graphs(
+        &mut self,
+        deleted_threshold: f64,
+        num_threads: usize,
+    ) -> OperationResult<usize> {
+        let mut pool = None;
+        let mut healed = 0;
+        for (vector_name, vector_data) in &self.vector_data {
+            let mut vector_index = vector_data.vector_index.borrow_mut();
+            if vector_index.unhealed_deleted_ratio() <= deleted_threshold {
+                continue;
+            }
+            let pool = match &mut pool {
+                Some(pool) => pool,
+                None => pool.insert(
+                    rayon::ThreadPoolBuilder::new()
+                        .thread_name(|idx| format!("hnsw-heal-{idx}"))
+                        .num_threads(num_threads.max(1))
+                        .build()?,
+                ),
+            };
+            if vector_index.heal_if_needed(deleted_threshold, pool)? {
+                log::debug!("Healed HNSW graph of vector {vector_name}");
+                healed += 1;
+            }
+        }
+        Ok(healed)
+    }
+}
@@ -30,9 +30,10 @@ use crate::json_path::JsonPath;
 use crate::payload_storage::PayloadStorage;
 use crate::telemetry::SegmentTelemetry;
 use crate::types::{
-    ExtendedPointId, Filter, Payload, PayloadFieldSchema, PayloadIndexInfo, PayloadKeyType,
-    PayloadKeyTypeRef, PointIdType, ScoredPoint, SearchParams, SegmentConfig, SegmentInfo,
-    SegmentType, SeqNumberType, VectorDataInfo, VectorName, VectorNameBuf, WithPayload, WithVector,
+    ExtendedPointId, FieldCondition, Filter, Payload, PayloadFieldSchema, PayloadIndexInfo,
+    PayloadKeyType, PayloadKeyTypeRef, PointIdType, ScoredPoint, SearchParams, SegmentConfig,
+    SegmentInfo, SegmentType, SeqNumberType, VectorDataInfo, VectorName, VectorNameBuf,
+    WithPayload, WithVector,
 };
 use crate::vector_storage::VectorStorage;
 
@@ -166,6 +167,19 @@ impl NonAppendableSegmentEntry for Segment {
         self.payload_by_offset(internal_id, hw_counter)
     }
 
+    fn text_match_positions(
+        &self,
+        point_id: PointIdType,
+        condition: &FieldCondition,
+        hw_counter: &HardwareCounterCell,
+    ) -> OperationResult<Option<Vec<u32>>> {
+        let internal_id = self.lookup_internal_id(point_id)?;
+        Ok(self
+            .payload_index
+            .borrow()
+            .text_match_positions(condition, internal_id, hw_counter))
+    }
+
     fn retrieve(
         &self,
         point_ids: &[PointIdType],
@@ -396,6 +410,16 @@ impl NonAppendableSegmentEntry for Segment {
         self.facet_values(key, filter, is_stopped, hw_counter)
     }
 
+    fn facet_point_ids(
+        &self,
+        key: &JsonPath,
+        filter: Option<&Filter>,
+        is_stopped: &AtomicBool,
+        hw_counter: &HardwareCounterCell,
+    ) -> OperationResult<HashMap<FacetValue, Vec<PointIdType>>> {
+        self.facet_value_point_ids(key, filter, is_stopped, hw_counter)
+    }
+
     fn facet(
         &self,
         request: &FacetParams,
@@ -494,7 +518,16 @@ impl NonAppendableSegmentEntry for Segment {
             .into_iter()
             .map(|(key, index_schema)| {
                 let points_count = payload_index.indexed_points(&key);
-                let index_info = PayloadIndexInfo::new(index_schema, points_count);
+                let mut index_info = PayloadIndexInfo::new(index_schema, points_count);
+                match payload_index.estimate_resources(&key) {
+                    Ok(resources) => {
+                        index_info.ram_usage_bytes = resources.ram_bytes;
+                        index_info.disk_usage_bytes = resources.disk_bytes;
+                    }
+                    Err(err) => {
+                        log::warn!("Failed to estimate resources of payload index {key}: {err}");
+                    }
+                }
                 (key, index_info)
             })
             .collect();
@@ -513,7 +546,24 @@ impl NonAppendableSegmentEntry for Segment {
         self.appendable_flag
     }
 
+    fn accepts_payload_updates(&self) -> bool {
+        self.appendable_flag || self.payload_index.borrow().accepts_updates()
+    }
+
+    fn tune_indexes(&self) {
+        for vector_data in self.vector_data.values() {
+            vector_data.vector_index.borrow().tune();
+        }
+    }
+
     fn flusher(&self, force: bool) -> Option<Flusher> {
+        // Index state, which is not bound to the segment version
+        let vector_index_flushers: Vec<_> = self
+            .vector_data
+            .values()
+            .filter_map(|v| v.vector_index.borrow().flusher())
+            .collect();
+
         let current_persisted_version: Option<SeqNumberType> = *self.persisted_version.lock();
 
         match (self.version, current_persisted_version) {
@@ -524,8 +574,15 @@ impl NonAppendableSegmentEntry for Segment {
             (Some(version), Some(persisted_version)) => {
                 if !force && version == persisted_version {
                     log::trace!("not flushing because version == persisted_version");
-                    // Segment is already flushed
-                    return None;
+                    // Segment is already flushed, only index state may be left to persist
+                    if vector_index_flushers.is_empty() {
+                        return None;
+                    }
+                    return Some(Box::new(move || {
+                        vector_index_flushers
+                            .into_iter()
+                            .try_for_each(|vector_index_flusher| vector_index_flusher())
+                    }));
                 }
             }
             (_, _) => {}
@@ -629,6 +686,9 @@ impl NonAppendableSegmentEntry for Segment {
                         )),
                     })?;
                 }
+                for vector_index_flusher in vector_index_flushers {
+                    vector_index_flusher()?;
+                }
                 payload_index_flusher().map_err(|err| match err {
                     OperationError::Cancelled { .. } => err,
                     _ => OperationError::service_error(format!(
@@ -710,6 +770,7 @@ impl NonAppendableSegmentEntry for Segment {
         self.handle_segment_version_and_failure(op_num, |segment| {
             segment.payload_index.borrow_mut().drop_index(key)?;
             segment.version_tracker.set_payload_index_schema(key, None);
+            segment.forget_immutable_file_hashes(key)?;
             Ok(true)
         })
     }
@@ -728,6 +789,7 @@ impl NonAppendableSegmentEntry for Segment {
 
             if is_incompatible {
                 segment.version_tracker.set_payload_index_schema(key, None);
+                segment.forget_immutable_file_hashes(key)?;
             }
 
             Ok(true)
@@ -783,11 +845,51 @@ impl NonAppendableSegmentEntry for Segment {
             segment
                 .version_tracker
                 .set_payload_index_schema(&key, Some(op_num));
+            segment.forget_immutable_file_hashes(&key)?;
 
             Ok(true)
         })
     }
 
+    fn stage_field_index_rebuild(
+        &self,
+        op_num: SeqNumberType,
+        key: PayloadKeyTypeRef,
+        field_schema: &PayloadFieldSchema,
+        hw_counter: &HardwareCounterCell,
+    ) -> OperationResult<bool> {
+        // Check version without updating it
+        if self.version.unwrap_or(0) > op_num {
+            return Ok(false);
+        }
+
+        self.payload_index
+            .borrow()
+            .stage_rebuild(key, field_schema, hw_counter)
+    }
+
+    fn apply_field_index_rebuild(
+        &mut self,
+        op_num: SeqNumberType,
+        key: PayloadKeyTypeRef,
+    ) -> OperationResult<bool> {
+        self.handle_segment_version_and_failure(op_num, |segment| {
+            let is_rebuilt = segment
+                .payload_index
+                .borrow_mut()
+                .apply_staged_rebuild(key)?;
+
+            if is_rebuilt {
+                segment
+                    .version_tracker
+                    .set_payload_index_schema(key, Some(op_num));
+                segment.forget_immutable_file_hashes(key)?;
+            }
+
+            Ok(is_rebuilt)
+        })
+    }
+
     fn delete_point(
         &mut self,
         op_num: SeqNumberType,
@@ -12,7 +12,7 @@ use crate::entry::entry_point::NonAppendableSegmentEntry;
 use crate::index::PayloadIndex;
 use crate::json_path::JsonPath;
 use crate::payload_storage::FilterContext;
-use crate::types::Filter;
+use crate::types::{Filter, PointIdType};
 
 impl Segment {
     pub(super) fn approximate_facet(
@@ -44,7 +44,10 @@ impl Segment {
             // For now this is defined from some rudimentary benchmarking two scenarios:
             // - a collection with few keys
             // - a collection with almost a unique key per point
-            let use_iterative_approach = percentage_filtered < 0.3;
+            //
+            // Indexes which can't cheaply provide values of a point always go over the values.
+            let use_iterative_approach =
+                percentage_filtered < 0.3 && facet_index.has_point_values();
 
             let iter = if use_iterative_approach {
                 // go over the filtered points and aggregate the values
@@ -114,6 +117,38 @@ impl Segment {
         Ok(hits)
     }
 
+    pub(super) fn facet_value_point_ids(
+        &self,
+        key: &JsonPath,
+        filter: Option<&Filter>,
+        is_stopped: &AtomicBool,
+        hw_counter: &HardwareCounterCell,
+    ) -> OperationResult<HashMap<FacetValue, Vec<PointIdType>>> {
+        let payload_index = self.payload_index.borrow();
+        let id_tracker = self.id_tracker.borrow();
+
+        let facet_index = payload_index.get_facet_index(key)?;
+        let context =
+            filter.map(|filter| payload_index.struct_filtered_context(filter, hw_counter));
+
+        let point_ids = facet_index
+            .iter_values_map(hw_counter)
+            .stop_if(is_stopped)
+            .filter_map(|(value, iter)| {
+                let point_ids = iter
+                    .unique()
+                    .filter(|&point_id| !id_tracker.is_deleted_point(point_id))
+                    .filter(|&point_id| context.as_ref().is_none_or(|ctx| ctx.check(point_id)))
+                    .filter_map(|point_id| id_tracker.external_id(point_id))
+                    .collect_vec();
+
+                (!point_ids.is_empty()).then(|| (value.to_owned(), point_ids))
+            })
+            .collect();
+
+        Ok(point_ids)
+    }
+
     pub(super) fn facet_values(
         &self,
         key: &JsonPath,
@@ -125,7 +160,21 @@ impl Segment {
 
         let facet_index = payload_index.get_facet_index(key)?;
 
-        let values = if let Some(filter) = filter {
+        let values = if let Some(filter) = filter
+            && !facet_index.has_point_values()
+        {
+            // go over the values and check if any of their points passes the filter
+            let context = payload_index.struct_filtered_context(filter, hw_counter);
+
+            facet_index
+                .iter_values_map(hw_counter)
+                .stop_if(is_stopped)
+                .filter_map(|(value, mut iter)| {
+                    iter.any(|point_id| context.check(point_id))
+                        .then(|| value.to_owned())
+                })
+                .collect()
+        } else if let Some(filter) = filter {
             let id_tracker = self.id_tracker.borrow();
             let filter_cardinality = payload_index.estimate_cardinality(filter, hw_counter);
 
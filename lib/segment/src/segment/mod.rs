@@ -1,3 +1,4 @@
+mod defragment;
 mod entry;
 mod facet;
 mod formula_rescore;
@@ -14,7 +15,7 @@ pub mod snapshot;
 mod tests;
 mod vectors;
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fmt;
 use std::path::PathBuf;
 use std::sync::Arc;
@@ -25,16 +26,22 @@ use common::storage_version::StorageVersion;
 use parking_lot::Mutex;
 #[cfg(feature = "rocksdb")]
 use rocksdb::DB;
+use semver::Version;
 use uuid::Uuid;
 
 use self::version_tracker::VersionTracker;
 use crate::common::operation_error::SegmentFailedState;
 use crate::id_tracker::IdTrackerSS;
 use crate::index::VectorIndexEnum;
+use crate::index::field_index::histogram;
+use crate::index::field_index::numeric_index::mmap_numeric_index;
+use crate::index::hnsw_index::graph_links;
 use crate::index::struct_payload_index::StructPayloadIndex;
 use crate::payload_storage::payload_storage_enum::PayloadStorageEnum;
 use crate::types::{SegmentConfig, SegmentType, SeqNumberType, VectorNameBuf};
 use crate::vector_storage::VectorStorageEnum;
+use crate::vector_storage::chunked_mmap_vectors;
+use crate::vector_storage::dense::mmap_dense_vectors;
 use crate::vector_storage::quantized::quantized_vectors::QuantizedVectors;
 
 pub const SEGMENT_STATE_FILE: &str = "segment.json";
@@ -56,6 +63,37 @@ impl StorageVersion for SegmentVersion {
     }
 }
 
+impl SegmentVersion {
+    /// Check if segments of the older `stored` version can be migrated to this version.
+    pub fn can_upgrade_storage(stored: &Version) -> bool {
+        !(stored.major == 0 && stored.minor < 3)
+    }
+
+    /// Versions of file formats within segments, which are versioned on their own.
+    ///
+    /// Each one is the newest version written by this build, files of older versions are still
+    /// read or migrated on load.
+    pub fn storage_format_versions() -> BTreeMap<&'static str, u32> {
+        BTreeMap::from([
+            (
+                "dense_vectors_u8",
+                mmap_dense_vectors::BYTE_VECTORS_FORMAT_VERSION,
+            ),
+            ("dense_deleted", mmap_dense_vectors::DELETED_FORMAT_VERSION),
+            (
+                "chunked_vectors_status",
+                chunked_mmap_vectors::STATUS_VERSION,
+            ),
+            (
+                "numeric_index_pairs",
+                mmap_numeric_index::PAIRS_FORMAT_VERSION,
+            ),
+            ("histogram_borders", histogram::BORDERS_VERSION),
+            ("hnsw_links", graph_links::LINKS_FORMAT_VERSION),
+        ])
+    }
+}
+
 /// Segment - an object which manages an independent group of points.
 ///
 /// - Provides storage, indexing and managing operations for points (vectors + payload)
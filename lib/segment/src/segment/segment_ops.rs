@@ -17,9 +17,9 @@ use crate::common::{check_named_vectors, check_vector_name};
 use crate::data_types::named_vectors::NamedVectors;
 use crate::data_types::vectors::VectorInternal;
 use crate::entry::entry_point::NonAppendableSegmentEntry;
-use crate::index::{PayloadIndex, VectorIndex};
+use crate::index::{PayloadIndex, VectorIndex, VectorIndexEnum};
 use crate::types::{
-    Payload, PayloadFieldSchema, PayloadKeyType, PointIdType, SegmentState, SeqNumberType,
+    Indexes, Payload, PayloadFieldSchema, PayloadKeyType, PointIdType, SegmentState, SeqNumberType,
     SnapshotFormat, VectorName,
 };
 use crate::utils;
@@ -646,6 +646,32 @@ impl Segment {
         self.id_tracker.borrow().total_point_count()
     }
 
+    /// Lock HNSW graph links of the vector in RAM, unless they are locked on load already.
+    ///
+    /// Enabling `lock_in_ram` does not rebuild existing segments, so their config may not have it.
+    pub fn lock_vector_index_in_ram(&self, vector_name: &VectorName) -> OperationResult<()> {
+        let vector_config = self
+            .segment_config
+            .vector_data
+            .get(vector_name)
+            .ok_or_else(|| OperationError::vector_name_not_exists(vector_name))?;
+        let locked_on_load = match &vector_config.index {
+            Indexes::Plain {} => return Ok(()),
+            Indexes::Hnsw(hnsw_config) => hnsw_config.lock_in_ram.unwrap_or_default(),
+        };
+        if locked_on_load {
+            return Ok(());
+        }
+        let vector_data = self
+            .vector_data
+            .get(vector_name)
+            .ok_or_else(|| OperationError::vector_name_not_exists(vector_name))?;
+        if let VectorIndexEnum::Hnsw(index) = &*vector_data.vector_index.borrow() {
+            index.lock_links_in_ram();
+        }
+        Ok(())
+    }
+
     /// Fixes inconsistencies in the ID tracker, if any.
     /// Returns list of IDs without mappings which should be removed from segment
     pub fn fix_id_tracker_inconsistencies(&mut self) -> OperationResult<Vec<PointOffsetType>> {
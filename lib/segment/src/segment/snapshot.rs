@@ -1,12 +1,14 @@
 use std::collections::{HashMap, HashSet};
-use std::io::{Seek, Write};
+use std::io::{self, BufReader, Seek, Write};
 use std::ops::Deref as _;
 use std::path::{Path, PathBuf};
 use std::{fmt, thread};
 
+use common::fs::{atomic_save_json, read_json};
 use common::storage_version::VERSION_FILE;
 use common::tar_ext;
 use fs_err as fs;
+use sha2::{Digest as _, Sha256};
 use uuid::Uuid;
 
 use crate::common::operation_error::{OperationError, OperationResult};
@@ -16,13 +18,16 @@ use crate::entry::snapshot_entry::SnapshotEntry;
 use crate::index::{PayloadIndex, VectorIndex};
 use crate::payload_storage::PayloadStorage;
 use crate::segment::{SEGMENT_STATE_FILE, SNAPSHOT_FILES_PATH, SNAPSHOT_PATH, Segment};
-use crate::types::SnapshotFormat;
+use crate::types::{PayloadKeyTypeRef, SnapshotFormat};
 use crate::utils::path::strip_prefix;
 use crate::vector_storage::VectorStorage;
 
 /// File name, used to store segment manifest inside snapshots
 pub const SEGMENT_MANIFEST_FILE_NAME: &str = "segment_manifest.json";
 
+/// File name, used to persist hashes of immutable files in the segment directory
+pub const IMMUTABLE_FILE_HASHES_FILE: &str = "immutable_file_hashes.json";
+
 impl SnapshotEntry for Segment {
     fn segment_id(&self) -> OperationResult<String> {
         let id = self
@@ -193,13 +198,90 @@ impl Segment {
             let _ = file_versions.insert(path.to_path_buf(), version);
         }
 
+        let file_hashes = self.immutable_file_hashes();
+
         Ok(SegmentManifest {
             segment_id,
             segment_version,
             file_versions,
+            file_hashes,
+        })
+    }
+
+    /// SHA-256 of immutable files, by path relative to the segment directory.
+    ///
+    /// Hashes are persisted once the segment is built, see
+    /// [`Segment::save_immutable_file_hashes`]. Files without a persisted hash, e.g. of segments
+    /// built by older versions, are compared by version only.
+    fn immutable_file_hashes(&self) -> HashMap<PathBuf, String> {
+        let path = self.segment_path.join(IMMUTABLE_FILE_HASHES_FILE);
+        if !path.exists() {
+            return HashMap::new();
+        }
+
+        read_json(&path).unwrap_or_else(|err| {
+            log::warn!(
+                "Failed to read immutable file hashes {}, ignoring them: {err}",
+                path.display(),
+            );
+            HashMap::new()
         })
     }
 
+    /// Hash all immutable files, and persist the hashes in the segment directory.
+    ///
+    /// Reads all immutable files, so it must only be called once they are final, i.e. when the
+    /// segment is built.
+    pub fn save_immutable_file_hashes(&self) -> OperationResult<()> {
+        let mut file_hashes = HashMap::new();
+        for file in self.immutable_files() {
+            if !file.is_file() {
+                continue;
+            }
+            let path = strip_prefix(&file, &self.segment_path)?.to_path_buf();
+            file_hashes.insert(path, hash_file(&file)?);
+        }
+
+        atomic_save_json(
+            &self.segment_path.join(IMMUTABLE_FILE_HASHES_FILE),
+            &file_hashes,
+        )?;
+        Ok(())
+    }
+
+    /// Forget persisted hashes of files, which are replaced or removed along with the payload
+    /// index of `key`.
+    ///
+    /// Files of the new index are not hashed, they are compared by version until the segment is
+    /// rebuilt.
+    pub(super) fn forget_immutable_file_hashes(
+        &self,
+        key: PayloadKeyTypeRef,
+    ) -> OperationResult<()> {
+        let path = self.segment_path.join(IMMUTABLE_FILE_HASHES_FILE);
+        if !path.exists() {
+            return Ok(());
+        }
+
+        let mut kept_files = HashSet::new();
+        for file in self.immutable_files() {
+            kept_files.insert(strip_prefix(&file, &self.segment_path)?.to_path_buf());
+        }
+        for (field, file) in self.payload_index.borrow().immutable_files() {
+            if field == *key {
+                kept_files.remove(strip_prefix(&file, &self.segment_path)?);
+            }
+        }
+
+        let mut file_hashes = self.immutable_file_hashes();
+        let hashes_count = file_hashes.len();
+        file_hashes.retain(|path, _| kept_files.contains(path));
+        if file_hashes.len() != hashes_count {
+            atomic_save_json(&path, &file_hashes)?;
+        }
+        Ok(())
+    }
+
     fn files(&self) -> Vec<PathBuf> {
         let mut files = Vec::new();
 
@@ -346,9 +428,23 @@ pub fn snapshot_files(
     tar.blocking_append_file(&version_file_path, Path::new(VERSION_FILE))
         .map_err(|err| failed_to_add("segment version file", &version_file_path, err))?;
 
+    // Hashes are only valid along with the immutable files, so they are always included
+    let file_hashes_path = segment.segment_path.join(IMMUTABLE_FILE_HASHES_FILE);
+    if file_hashes_path.exists() {
+        tar.blocking_append_file(&file_hashes_path, Path::new(IMMUTABLE_FILE_HASHES_FILE))
+            .map_err(|err| failed_to_add("immutable file hashes", &file_hashes_path, err))?;
+    }
+
     Ok(())
 }
 
+fn hash_file(path: &Path) -> OperationResult<String> {
+    let mut reader = BufReader::new(fs::File::open(path)?);
+    let mut hasher = Sha256::new();
+    io::copy(&mut reader, &mut hasher)?;
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
 fn failed_to_add(what: &str, path: &Path, err: impl fmt::Display) -> OperationError {
     OperationError::service_error(format!(
         "failed to add {what} {} into snapshot: {err}",
@@ -371,7 +467,17 @@ fn updated_files(old: &SegmentManifest, current: &SegmentManifest) -> HashSet<Pa
             continue;
         };
 
-        // 2. if `old` manifest contains this file and file/segment in `current` manifest is *newer*:
+        // 2. if both manifests contain a hash of this file, it's unchanged only if hashes match,
+        //    regardless of versions
+        if let (Some(old_hash), Some(current_hash)) = (old.file_hash(path), current.file_hash(path))
+        {
+            if old_hash != current_hash {
+                updated.insert(path.to_path_buf());
+            }
+            continue;
+        }
+
+        // 3. if `old` manifest contains this file and file/segment in `current` manifest is *newer*:
         //    - if file is `Unversioned` in both manifests, compare segment versions
         //    - if file is versioned in *one* of the manifests only, compare *file* version against
         //      other *segment* version
@@ -381,7 +487,7 @@ fn updated_files(old: &SegmentManifest, current: &SegmentManifest) -> HashSet<Pa
             continue;
         }
 
-        // 3. if `old` manifest contains this file and file/segment versions in both `old` and `current` manifests are 0
+        // 4. if `old` manifest contains this file and file/segment versions in both `old` and `current` manifests are 0
         //    - we can't distinguish between new empty (no operations applied yet) segment (version 0)
         //    - and segment with operation 0 applied (also version 0)
         //    - so if both files/segments are at version 0, we always include the file into snapshot
@@ -392,3 +498,60 @@ fn updated_files(old: &SegmentManifest, current: &SegmentManifest) -> HashSet<Pa
 
     updated
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::SeqNumberType;
+
+    fn manifest(
+        segment_version: SeqNumberType,
+        files: &[(&str, SeqNumberType, Option<&str>)],
+    ) -> SegmentManifest {
+        let mut manifest = SegmentManifest::empty("segment");
+        manifest.segment_version = segment_version;
+        for &(file, version, hash) in files {
+            manifest
+                .file_versions
+                .insert(PathBuf::from(file), FileVersion::from(version));
+            if let Some(hash) = hash {
+                manifest
+                    .file_hashes
+                    .insert(PathBuf::from(file), hash.to_string());
+            }
+        }
+        manifest
+    }
+
+    #[test]
+    fn test_updated_files_by_hash() {
+        let old = manifest(
+            10,
+            &[
+                ("vectors.dat", 5, Some("aaa")),
+                ("graph.bin", 5, Some("bbb")),
+                ("payload.dat", 10, None),
+            ],
+        );
+        let current = manifest(
+            20,
+            &[
+                ("vectors.dat", 15, Some("aaa")),
+                ("graph.bin", 5, Some("ccc")),
+                ("payload.dat", 10, None),
+                ("new.dat", 20, Some("ddd")),
+            ],
+        );
+
+        let updated = updated_files(&old, &current);
+
+        // Newer version, but same content
+        assert!(!updated.contains(Path::new("vectors.dat")));
+        // Same version, but different content
+        assert!(updated.contains(Path::new("graph.bin")));
+        // Not hashed, same version
+        assert!(!updated.contains(Path::new("payload.dat")));
+        // Not in the old manifest
+        assert!(updated.contains(Path::new("new.dat")));
+    }
+}
@@ -13,14 +13,18 @@ use crate::common::operation_error::OperationError::PointIdError;
 use crate::common::{check_named_vectors, check_vector, check_vector_name};
 use crate::data_types::named_vectors::NamedVectors;
 use crate::data_types::query_context::QueryContext;
-use crate::data_types::vectors::{DEFAULT_VECTOR_NAME, only_default_vector};
+use crate::data_types::vectors::{DEFAULT_VECTOR_NAME, VectorInternal, only_default_vector};
 use crate::entry::SnapshotEntry as _;
 use crate::entry::entry_point::{NonAppendableSegmentEntry as _, SegmentEntry as _};
-use crate::segment_constructor::load_segment;
+use crate::index::sparse_index::sparse_index_config::MmapAdvice;
 use crate::segment_constructor::simple_segment_constructor::{
     VECTOR1_NAME, VECTOR2_NAME, build_multivec_segment, build_simple_segment,
 };
-use crate::types::{Distance, Filter, Payload, SnapshotFormat, WithPayload, WithVector};
+use crate::segment_constructor::{SegmentLoadOptions, load_segment, load_segment_with_options};
+use crate::types::{
+    Distance, Filter, MmapAdviceConfig, Payload, PopulatePolicy, SnapshotFormat, WithPayload,
+    WithVector,
+};
 
 #[test]
 fn test_search_batch_equivalence_single() {
@@ -716,3 +720,141 @@ fn test_handle_point_version() {
         .unwrap();
     assert!(applied);
 }
+
+#[test]
+fn test_defragment() {
+    let dir = Builder::new().prefix("segment_dir").tempdir().unwrap();
+    let dim = 4;
+
+    let hw_counter = HardwareCounterCell::new();
+
+    let mut segment = build_simple_segment(dir.path(), dim, Distance::Dot).unwrap();
+    assert!(segment.is_defragmentable());
+
+    let point_vector = |id: u64| vec![id as f32, 1.0, 0.0, 1.0];
+    let point_payload =
+        |id: u64| -> Payload { serde_json::from_value(serde_json::json!({ "id": id })).unwrap() };
+    for id in 0..10u64 {
+        segment
+            .upsert_point(
+                id,
+                id.into(),
+                only_default_vector(&point_vector(id)),
+                &hw_counter,
+            )
+            .unwrap();
+        segment
+            .set_full_payload(id, id.into(), &point_payload(id), &hw_counter)
+            .unwrap();
+    }
+    for id in [1u64, 4, 5, 8] {
+        segment
+            .delete_point(20 + id, id.into(), &hw_counter)
+            .unwrap();
+    }
+
+    let check_points = |segment: &Segment| {
+        assert_eq!(segment.total_point_count(), 6);
+        assert_eq!(segment.available_point_count(), 6);
+        assert_eq!(segment.deleted_point_count(), 0);
+        for id in [0u64, 2, 3, 6, 7, 9] {
+            let vector = segment
+                .vector(DEFAULT_VECTOR_NAME, id.into(), &hw_counter)
+                .unwrap()
+                .unwrap();
+            assert_eq!(vector, VectorInternal::from(point_vector(id)));
+            let payload = segment.payload(id.into(), &hw_counter).unwrap();
+            assert_eq!(payload, point_payload(id));
+            assert_eq!(segment.point_version(id.into()), Some(id));
+        }
+        for id in [1u64, 4, 5, 8] {
+            assert!(!segment.has_point(id.into()));
+        }
+    };
+
+    let reclaimed = segment.defragment().unwrap();
+    assert_eq!(reclaimed, 4);
+    check_points(&segment);
+
+    // Nothing left to reclaim
+    assert_eq!(segment.defragment().unwrap(), 0);
+
+    // Appending after defragmentation must reuse the reclaimed space
+    segment
+        .upsert_point(
+            30,
+            10.into(),
+            only_default_vector(&point_vector(10)),
+            &hw_counter,
+        )
+        .unwrap();
+    segment.delete_point(31, 10.into(), &hw_counter).unwrap();
+    segment.flush(true).unwrap();
+    let segment_path = segment.segment_path.clone();
+    drop(segment);
+
+    let mut segment = load_segment(&segment_path, Uuid::nil(), &AtomicBool::new(false)).unwrap();
+    assert_eq!(segment.total_point_count(), 7);
+    assert_eq!(segment.deleted_point_count(), 1);
+    assert_eq!(segment.defragment().unwrap(), 1);
+    check_points(&segment);
+}
+
+#[rstest]
+#[case::inferred(None, None)]
+#[case::eager(Some(PopulatePolicy::Eager), None)]
+#[case::lazy(Some(PopulatePolicy::Lazy), None)]
+#[case::prioritized(Some(PopulatePolicy::Prioritized), None)]
+#[case::sequential(None, Some(MmapAdvice::Sequential))]
+#[case::eager_will_need(Some(PopulatePolicy::Eager), Some(MmapAdvice::WillNeed))]
+fn test_load_segment_with_options(
+    #[case] populate: Option<PopulatePolicy>,
+    #[case] advice: Option<MmapAdvice>,
+) {
+    let dir = Builder::new().prefix("segment_dir").tempdir().unwrap();
+    let dim = 4;
+
+    let hw_counter = HardwareCounterCell::new();
+
+    let mut segment = build_simple_segment(dir.path(), dim, Distance::Dot).unwrap();
+    let point_vector = |id: u64| vec![id as f32, 1.0, 0.0, 1.0];
+    for id in 0..10u64 {
+        segment
+            .upsert_point(
+                id,
+                id.into(),
+                only_default_vector(&point_vector(id)),
+                &hw_counter,
+            )
+            .unwrap();
+    }
+    segment.flush(true).unwrap();
+    let segment_path = segment.segment_path.clone();
+    drop(segment);
+
+    let options = SegmentLoadOptions {
+        populate,
+        mmap_advice: MmapAdviceConfig {
+            vectors: advice,
+            links: advice,
+            payload_index: advice,
+            sparse_index: advice,
+            hugepages: advice.is_some().then_some(true),
+        },
+    };
+    let segment = load_segment_with_options(
+        &segment_path,
+        Uuid::nil(),
+        &options,
+        &AtomicBool::new(false),
+    )
+    .unwrap();
+    assert_eq!(segment.available_point_count(), 10);
+    for id in 0..10u64 {
+        let vector = segment
+            .vector(DEFAULT_VECTOR_NAME, id.into(), &hw_counter)
+            .unwrap()
+            .unwrap();
+        assert_eq!(vector, VectorInternal::from(point_vector(id)));
+    }
+}
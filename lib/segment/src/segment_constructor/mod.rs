@@ -5,5 +5,6 @@ pub mod segment_builder;
 mod segment_constructor_base;
 #[cfg(any(test, feature = "testing"))]
 pub mod simple_segment_constructor;
+pub mod validation_report;
 
 pub use segment_constructor_base::*;
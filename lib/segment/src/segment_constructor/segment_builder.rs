@@ -25,9 +25,9 @@ use uuid::Uuid;
 #[cfg(feature = "rocksdb")]
 use super::rocksdb_builder::RocksDbBuilder;
 use super::{
-    create_mutable_id_tracker, create_payload_storage, create_sparse_vector_index,
-    create_sparse_vector_storage, get_payload_index_path, get_vector_index_path,
-    get_vector_storage_path, open_vector_storage,
+    SegmentLoadOptions, create_mutable_id_tracker, create_payload_storage,
+    create_sparse_vector_index, create_sparse_vector_storage, get_payload_index_path,
+    get_vector_index_path, get_vector_storage_path, open_vector_storage,
 };
 use crate::common::error_logging::LogError;
 use crate::common::operation_error::{OperationError, OperationResult, check_process_stopped};
@@ -37,6 +37,7 @@ use crate::id_tracker::immutable_id_tracker::ImmutableIdTracker;
 use crate::id_tracker::in_memory_id_tracker::InMemoryIdTracker;
 use crate::id_tracker::{IdTracker, IdTrackerEnum, for_each_unique_point};
 use crate::index::field_index::FieldIndex;
+use crate::index::field_index::full_text_index::text_index::MergedTextIndex;
 use crate::index::sparse_index::sparse_vector_index::SparseVectorIndexOpenArgs;
 use crate::index::struct_payload_index::StructPayloadIndex;
 use crate::index::{PayloadIndex, VectorIndexEnum};
@@ -45,17 +46,23 @@ use crate::payload_storage::payload_storage_enum::PayloadStorageEnum;
 use crate::segment::{Segment, SegmentVersion};
 use crate::segment_constructor::batched_reader::{BatchedVectorReader, PointData};
 use crate::segment_constructor::{
-    VectorIndexBuildArgs, VectorIndexOpenArgs, build_vector_index, load_segment,
+    VectorIndexBuildArgs, VectorIndexOpenArgs, build_vector_index, load_segment_with_options,
 };
 use crate::types::{
     CompactExtendedPointId, ExtendedPointId, HnswGlobalConfig, PayloadFieldSchema, PayloadKeyType,
-    SegmentConfig, SegmentState, SeqNumberType, VectorNameBuf,
+    PayloadSchemaType, QuantizationConfig, SegmentConfig, SegmentState, SeqNumberType,
+    VectorNameBuf, VectorStorageType,
 };
 use crate::vector_storage::quantized::quantized_vectors::{
     QuantizedVectors, QuantizedVectorsStorageType,
 };
 use crate::vector_storage::{VectorStorage, VectorStorageEnum};
 
+/// Directory in the segments path to keep HNSW build checkpoints in.
+///
+/// Hidden entries of the segments path are not loaded as segments.
+pub const HNSW_BUILD_CHECKPOINTS_PATH: &str = ".hnsw_build_checkpoints";
+
 /// Structure for constructing segment out of several other segments
 pub struct SegmentBuilder {
     version: SeqNumberType,
@@ -69,8 +76,14 @@ pub struct SegmentBuilder {
     temp_dir: TempDir,
     indexed_fields: HashMap<PayloadKeyType, PayloadFieldSchema>,
 
+    // Full-text indexes merged from source segments, along with schema they were built with
+    merged_text_indexes: HashMap<PayloadKeyType, (PayloadFieldSchema, MergedTextIndex)>,
+
     // Payload key to defragment data to
     defragment_keys: Vec<PayloadKeyType>,
+
+    // Options to load the built segment with
+    load_options: SegmentLoadOptions,
 }
 
 struct VectorData {
@@ -115,6 +128,7 @@ impl SegmentBuilder {
                 &vector_storage_path,
                 #[cfg(feature = "rocksdb")]
                 vector_name,
+                &SegmentLoadOptions::default(),
             )?;
 
             vector_data.insert(
@@ -158,7 +172,9 @@ impl SegmentBuilder {
             hnsw_global_config: hnsw_global_config.clone(),
             temp_dir,
             indexed_fields: Default::default(),
+            merged_text_indexes: Default::default(),
             defragment_keys: vec![],
+            load_options: SegmentLoadOptions::default(),
         })
     }
 
@@ -166,6 +182,10 @@ impl SegmentBuilder {
         self.defragment_keys = keys;
     }
 
+    pub fn set_load_options(&mut self, load_options: SegmentLoadOptions) {
+        self.load_options = load_options;
+    }
+
     pub fn remove_indexed_field(&mut self, field: &PayloadKeyType) {
         self.indexed_fields.remove(field);
     }
@@ -457,6 +477,13 @@ impl SegmentBuilder {
             }
         }
 
+        let source_payload_indexes = payloads.iter().map(|i| i.deref()).collect_vec();
+        self.merge_text_indexes(
+            &source_payload_indexes,
+            &points_to_insert,
+            internal_range_start,
+        )?;
+
         for payload in payloads {
             for (field, payload_schema) in payload.indexed_fields() {
                 self.indexed_fields.insert(field, payload_schema);
@@ -466,6 +493,102 @@ impl SegmentBuilder {
         Ok(true)
     }
 
+    /// Merge full-text indexes of source segments, so that [`SegmentBuilder::build`] doesn't have
+    /// to tokenize all documents again.
+    ///
+    /// Only fields indexed with the same schema in all source segments are merged. If a field can't
+    /// be merged in any of the [`SegmentBuilder::update`] calls, its index is built from payloads.
+    fn merge_text_indexes(
+        &mut self,
+        payload_indexes: &[&StructPayloadIndex],
+        points: &[PointData],
+        first_new_id: PointOffsetType,
+    ) -> OperationResult<()> {
+        // Map point ids of each source segment into ids in the new segment
+        let mut new_ids: Vec<Vec<Option<PointOffsetType>>> =
+            vec![Vec::new(); payload_indexes.len()];
+        for (new_id, point) in (first_new_id..).zip(points) {
+            let segment_new_ids = &mut new_ids[point.segment_index.get() as usize];
+            let old_id = point.internal_id as usize;
+            if segment_new_ids.len() <= old_id {
+                segment_new_ids.resize(old_id + 1, None);
+            }
+            segment_new_ids[old_id] = Some(new_id);
+        }
+
+        let segments_fields = payload_indexes
+            .iter()
+            .map(|payload_index| payload_index.indexed_fields())
+            .collect_vec();
+
+        let text_fields: Vec<_> = self
+            .merged_text_indexes
+            .keys()
+            .cloned()
+            .chain(segments_fields.iter().flat_map(|fields| {
+                fields
+                    .iter()
+                    .filter(|(_, schema)| schema.kind() == PayloadSchemaType::Text)
+                    .map(|(field, _)| field.clone())
+            }))
+            .unique()
+            .collect();
+
+        for field in text_fields {
+            let previous = self.merged_text_indexes.remove(&field);
+
+            // Points added by previous updates must be in the merged index already
+            if previous.is_none() && first_new_id > 0 {
+                continue;
+            }
+
+            let Some(schema) = segments_fields
+                .first()
+                .and_then(|fields| fields.get(&field))
+            else {
+                continue;
+            };
+            if segments_fields
+                .iter()
+                .any(|fields| fields.get(&field) != Some(schema))
+                || previous
+                    .as_ref()
+                    .is_some_and(|(previous_schema, _)| previous_schema != schema)
+            {
+                continue;
+            }
+
+            let sources: Option<Vec<_>> = payload_indexes
+                .iter()
+                .zip(&new_ids)
+                .map(|(payload_index, new_ids)| {
+                    let text_index =
+                        payload_index
+                            .field_indexes
+                            .get(&field)?
+                            .iter()
+                            .find_map(|index| match index {
+                                FieldIndex::FullTextIndex(text_index) => Some(text_index),
+                                _ => None,
+                            })?;
+                    Some((text_index, new_ids.as_slice()))
+                })
+                .collect();
+            let Some(sources) = sources else {
+                continue;
+            };
+
+            let merged =
+                MergedTextIndex::merge(previous.as_ref().map(|(_, merged)| merged), &sources)?;
+            if let Some(merged) = merged {
+                self.merged_text_indexes
+                    .insert(field, (schema.clone(), merged));
+            }
+        }
+
+        Ok(())
+    }
+
     /// Test wrapper for [`SegmentBuilder::build`].
     #[cfg(feature = "testing")]
     pub fn build_for_test(self, segments_path: &Path) -> Segment {
@@ -494,6 +617,7 @@ impl SegmentBuilder {
         hw_counter: &HardwareCounterCell,
         progress_segment: ProgressTracker,
     ) -> Result<Segment, OperationError> {
+        let load_options = self.load_options;
         let temp_dir = {
             let SegmentBuilder {
                 version,
@@ -504,7 +628,9 @@ impl SegmentBuilder {
                 hnsw_global_config,
                 temp_dir,
                 indexed_fields,
+                mut merged_text_indexes,
                 defragment_keys: _,
+                load_options: _,
             } = self;
 
             let progress_quantization = progress_segment.subtask("quantization");
@@ -600,7 +726,19 @@ impl SegmentBuilder {
             )?;
             for (field, payload_schema, progress) in indexed_fields {
                 progress.start();
-                payload_index.set_indexed(&field, payload_schema, hw_counter)?;
+                match merged_text_indexes.remove(&field) {
+                    // Schema may have been changed after the indexes were merged
+                    Some((merged_schema, merged_text)) if merged_schema == payload_schema => {
+                        let field_index = payload_index.build_field_indexes_with_merged_text(
+                            &field,
+                            &payload_schema,
+                            Some(merged_text),
+                            hw_counter,
+                        )?;
+                        payload_index.apply_index(field, payload_schema, field_index)?;
+                    }
+                    _ => payload_index.set_indexed(&field, payload_schema, hw_counter)?,
+                }
                 check_process_stopped(stopped)?;
             }
             drop(progress_payload_index);
@@ -623,6 +761,8 @@ impl SegmentBuilder {
             // Arc permit to share it with each vector store
             let permit = Arc::new(permit);
 
+            let build_checkpoint_dir = segments_path.join(HNSW_BUILD_CHECKPOINTS_PATH);
+
             progress_vector_index.start();
             for (vector_name, vector_config) in &segment_config.vector_data {
                 let vector_storage = vector_storages_arc.remove(vector_name).unwrap();
@@ -647,9 +787,15 @@ impl SegmentBuilder {
                         hnsw_global_config: &hnsw_global_config,
                         feature_flags: feature_flags(),
                         progress: progress_vector_index.running_subtask(vector_name),
+                        build_checkpoint_dir: Some(&build_checkpoint_dir),
                     },
                 )?;
 
+                // Vectors are only compressed once the index is built, the file is not changed after
+                if vector_config.storage_type == VectorStorageType::MmapCompressed {
+                    vector_storage.borrow().compress_files()?;
+                }
+
                 if vector_storage.borrow().is_on_disk() {
                     // If vector storage is expected to be on-disk, we need to clear cache
                     // to avoid cache pollution
@@ -674,6 +820,7 @@ impl SegmentBuilder {
 
                 let index = create_sparse_vector_index(SparseVectorIndexOpenArgs {
                     config: sparse_vector_config.index,
+                    modifier: sparse_vector_config.modifier,
                     id_tracker: id_tracker_arc.clone(),
                     vector_storage: vector_storage_arc.clone(),
                     payload_index: payload_index_arc.clone(),
@@ -731,7 +878,13 @@ impl SegmentBuilder {
         let destination_path = segments_path.join(segment_uuid.to_string());
         fs::rename(temp_dir.keep(), &destination_path)
             .describe("Moving segment data after optimization")?;
-        load_segment(&destination_path, segment_uuid, stopped)
+        let segment =
+            load_segment_with_options(&destination_path, segment_uuid, &load_options, stopped)?;
+
+        // Immutable files are final now, hash them once for partial snapshots
+        segment.save_immutable_file_hashes()?;
+
+        Ok(segment)
     }
 
     fn update_quantization(
@@ -768,6 +921,16 @@ impl SegmentBuilder {
                 }
 
                 let progress_vector = progress.running_subtask(vector_name);
+                // Only scalar quantization reports encoded vectors. Inner vectors of multivectors
+                // are encoded, their number is not known upfront.
+                let encoded_vectors = matches!(quantization_config, QuantizationConfig::Scalar(_))
+                    .then(|| {
+                        let total_vectors = vector_config
+                            .multivector_config
+                            .is_none()
+                            .then(|| vector_info.vector_storage.total_vector_count() as u64);
+                        progress_vector.track_progress(total_vectors)
+                    });
 
                 let segment_path = temp_path;
                 let quantized_storage_type = if is_appendable {
@@ -784,6 +947,7 @@ impl SegmentBuilder {
                     quantized_storage_type,
                     &vector_storage_path,
                     max_threads,
+                    encoded_vectors.as_deref(),
                     stopped,
                 )?;
 
@@ -9,7 +9,7 @@ use common::budget::ResourcePermit;
 use common::flags::FeatureFlags;
 use common::fs::{safe_delete_with_suffix, sync_parent_dir};
 use common::is_alive_lock::IsAliveLock;
-use common::mmap::{Advice, AdviceSetting};
+use common::mmap::{Advice, AdviceSetting, advice};
 use common::progress_tracker::ProgressTracker;
 use common::storage_version::StorageVersion;
 use fs_err as fs;
@@ -26,6 +26,7 @@ use uuid::Uuid;
 
 #[cfg(feature = "rocksdb")]
 use super::rocksdb_builder::RocksDbBuilder;
+use super::validation_report::{record_failed_segment, validate_segment};
 use crate::common::operation_error::{OperationError, OperationResult, check_process_stopped};
 use crate::data_types::vectors::DEFAULT_VECTOR_NAME;
 use crate::id_tracker::immutable_id_tracker::ImmutableIdTracker;
@@ -52,12 +53,14 @@ use crate::segment::{SEGMENT_STATE_FILE, Segment, SegmentVersion, VectorData};
 #[cfg(feature = "rocksdb")]
 use crate::types::MultiVectorConfig;
 use crate::types::{
-    Distance, HnswGlobalConfig, Indexes, PayloadStorageType, SegmentConfig, SegmentState,
-    SegmentType, SeqNumberType, SparseVectorStorageType, VectorDataConfig, VectorName,
-    VectorStorageDatatype, VectorStorageType,
+    Distance, HnswGlobalConfig, Indexes, MmapAdviceConfig, PayloadStorageType, PopulatePolicy,
+    SegmentConfig, SegmentState, SegmentType, SeqNumberType, SparseVectorStorageType,
+    VectorDataConfig, VectorName, VectorStorageDatatype, VectorStorageType,
 };
+use crate::vector_storage::common::get_hot_tier_ratio;
 use crate::vector_storage::dense::memmap_dense_vector_storage::{
-    open_memmap_vector_storage, open_memmap_vector_storage_byte, open_memmap_vector_storage_half,
+    open_memmap_vector_storage, open_memmap_vector_storage_bf16, open_memmap_vector_storage_byte,
+    open_memmap_vector_storage_half,
 };
 #[cfg(feature = "rocksdb")]
 use crate::vector_storage::dense::simple_dense_vector_storage::open_simple_dense_vector_storage;
@@ -100,6 +103,7 @@ pub fn get_vector_index_path(segment_path: &Path, vector_name: &VectorName) -> P
 fn open_mmap_vector_storage(
     vector_storage_path: &Path,
     vector_config: &VectorDataConfig,
+    direct_io: bool,
     madvise: AdviceSetting,
     populate: bool,
 ) -> OperationResult<VectorStorageEnum> {
@@ -121,6 +125,7 @@ fn open_mmap_vector_storage(
                 vector_storage_path,
                 vector_config.size,
                 vector_config.distance,
+                direct_io,
                 madvise,
                 populate,
             ),
@@ -128,6 +133,7 @@ fn open_mmap_vector_storage(
                 vector_storage_path,
                 vector_config.size,
                 vector_config.distance,
+                direct_io,
                 madvise,
                 populate,
             ),
@@ -135,6 +141,15 @@ fn open_mmap_vector_storage(
                 vector_storage_path,
                 vector_config.size,
                 vector_config.distance,
+                direct_io,
+                madvise,
+                populate,
+            ),
+            VectorStorageDatatype::Bfloat16 => open_memmap_vector_storage_bf16(
+                vector_storage_path,
+                vector_config.size,
+                vector_config.distance,
+                direct_io,
                 madvise,
                 populate,
             ),
@@ -171,13 +186,33 @@ fn open_chunked_mmap_vector_storage(
     }
 }
 
+/// Options for opening components of an existing segment.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SegmentLoadOptions {
+    /// How to populate memory mapped files, inferred from `on_disk` flags if not set.
+    pub populate: Option<PopulatePolicy>,
+    /// Access pattern advice per component, global advice is used where not set.
+    pub mmap_advice: MmapAdviceConfig,
+}
+
 pub(crate) fn open_vector_storage(
     #[cfg(feature = "rocksdb")] db_builder: &mut RocksDbBuilder,
     vector_config: &VectorDataConfig,
     #[cfg(feature = "rocksdb")] stopped: &AtomicBool,
     vector_storage_path: &Path,
     #[cfg(feature = "rocksdb")] vector_name: &VectorName,
+    options: &SegmentLoadOptions,
 ) -> OperationResult<VectorStorageEnum> {
+    let SegmentLoadOptions {
+        populate,
+        mmap_advice,
+    } = *options;
+    let madvise = |default: AdviceSetting| {
+        mmap_advice
+            .vectors
+            .map_or(default, |advice| AdviceSetting::from(Advice::from(advice)))
+    };
+
     match vector_config.storage_type {
         // In memory - RocksDB disabled
         #[cfg(not(feature = "rocksdb"))]
@@ -218,27 +253,54 @@ pub(crate) fn open_vector_storage(
         VectorStorageType::Mmap => open_mmap_vector_storage(
             vector_storage_path,
             vector_config,
-            AdviceSetting::Global,
             false,
+            madvise(AdviceSetting::Global),
+            PopulatePolicy::populate_on_load(populate, false),
         ),
-        VectorStorageType::InRamMmap => open_mmap_vector_storage(
+        // Mmap on disk, read with direct IO, not appendable
+        VectorStorageType::MmapDirectIo => open_mmap_vector_storage(
             vector_storage_path,
             vector_config,
-            AdviceSetting::from(Advice::Normal),
             true,
+            madvise(AdviceSetting::Global),
+            PopulatePolicy::populate_on_load(populate, false),
+        ),
+        // Mmap on disk with recently accessed vectors locked in RAM, not appendable. The hot tier
+        // is enabled once the segment is loaded.
+        VectorStorageType::TieredMmap => open_mmap_vector_storage(
+            vector_storage_path,
+            vector_config,
+            false,
+            madvise(AdviceSetting::Global),
+            PopulatePolicy::populate_on_load(populate, false),
+        ),
+        // Compressed mmap on disk, not appendable. Compression is detected from the file.
+        VectorStorageType::MmapCompressed => open_mmap_vector_storage(
+            vector_storage_path,
+            vector_config,
+            false,
+            madvise(AdviceSetting::Global),
+            PopulatePolicy::populate_on_load(populate, false),
+        ),
+        VectorStorageType::InRamMmap => open_mmap_vector_storage(
+            vector_storage_path,
+            vector_config,
+            false,
+            madvise(AdviceSetting::from(Advice::Normal)),
+            PopulatePolicy::populate_on_load(populate, true),
         ),
         // Chunked mmap on disk, appendable
         VectorStorageType::ChunkedMmap => open_chunked_mmap_vector_storage(
             vector_storage_path,
             vector_config,
-            AdviceSetting::Global,
-            false,
+            madvise(AdviceSetting::Global),
+            PopulatePolicy::populate_on_load(populate, false),
         ),
         VectorStorageType::InRamChunkedMmap => open_chunked_mmap_vector_storage(
             vector_storage_path,
             vector_config,
-            AdviceSetting::from(Advice::Normal),
-            true,
+            madvise(AdviceSetting::from(Advice::Normal)),
+            PopulatePolicy::populate_on_load(populate, true),
         ),
     }
 }
@@ -308,11 +370,15 @@ pub struct VectorIndexBuildArgs<'a, R: Rng + ?Sized> {
     pub hnsw_global_config: &'a HnswGlobalConfig,
     pub feature_flags: FeatureFlags,
     pub progress: ProgressTracker,
+    /// Directory for HNSW build checkpoints, which is kept across restarts.
+    /// Checkpoints are not saved if not set.
+    pub build_checkpoint_dir: Option<&'a Path>,
 }
 
 pub(crate) fn open_vector_index(
     vector_config: &VectorDataConfig,
     open_args: VectorIndexOpenArgs,
+    options: &SegmentLoadOptions,
 ) -> OperationResult<VectorIndexEnum> {
     let VectorIndexOpenArgs {
         path,
@@ -328,14 +394,28 @@ pub(crate) fn open_vector_index(
             quantized_vectors,
             payload_index,
         )),
-        Indexes::Hnsw(hnsw_config) => VectorIndexEnum::Hnsw(HNSWIndex::open(HnswIndexOpenArgs {
-            path,
-            id_tracker,
-            vector_storage,
-            quantized_vectors,
-            payload_index,
-            hnsw_config: *hnsw_config,
-        })?),
+        Indexes::Hnsw(hnsw_config) => {
+            let index = HNSWIndex::open(
+                HnswIndexOpenArgs {
+                    path,
+                    id_tracker,
+                    vector_storage,
+                    quantized_vectors,
+                    payload_index,
+                    hnsw_config: *hnsw_config,
+                },
+                options.populate,
+            )?;
+            if let Some(advice) = options.mmap_advice.links {
+                index.advise_links(advice.into())?;
+            }
+            if options.mmap_advice.hugepages == Some(true)
+                && let Err(err) = index.request_links_hugepages()
+            {
+                log::warn!("Failed to request huge pages for {}: {err}", path.display());
+            }
+            VectorIndexEnum::Hnsw(index)
+        }
     })
 }
 
@@ -392,6 +472,9 @@ pub(crate) fn create_sparse_vector_index(
                 description: format!("{a:?} datatype is not supported"),
             })?
         }
+        (_, a @ VectorStorageDatatype::Bfloat16, _) => Err(OperationError::ValidationError {
+            description: format!("{a:?} datatype is not supported for sparse vectors"),
+        })?,
 
         (SparseIndexType::MutableRam, _, _) => {
             VectorIndexEnum::SparseRam(SparseVectorIndex::open(args)?)
@@ -455,12 +538,14 @@ pub(crate) fn create_sparse_vector_storage(
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn create_segment(
     initial_version: Option<SeqNumberType>,
     version: Option<SeqNumberType>,
     segment_path: &Path,
     uuid: Uuid,
     config: &SegmentConfig,
+    options: &SegmentLoadOptions,
     stopped: &AtomicBool,
     create: bool,
 ) -> OperationResult<Segment> {
@@ -500,8 +585,24 @@ fn create_segment(
             &vector_storage_path,
             #[cfg(feature = "rocksdb")]
             vector_name,
+            options,
         )?);
 
+        if vector_config.storage_type == VectorStorageType::TieredMmap {
+            vector_storage
+                .borrow_mut()
+                .enable_hot_tier(get_hot_tier_ratio());
+        }
+
+        if options.mmap_advice.hugepages == Some(true)
+            && let Err(err) = vector_storage.borrow().request_hugepages()
+        {
+            log::warn!(
+                "Failed to request huge pages for {}: {err}",
+                vector_storage_path.display(),
+            );
+        }
+
         vector_storages.insert(vector_name.to_owned(), vector_storage);
     }
 
@@ -524,14 +625,26 @@ fn create_segment(
     }
 
     let payload_index_path = get_payload_index_path(segment_path);
-    let payload_index: Arc<AtomicRefCell<StructPayloadIndex>> = sp(StructPayloadIndex::open(
-        payload_storage.clone(),
-        id_tracker.clone(),
-        vector_storages.clone(),
-        &payload_index_path,
-        appendable_flag,
-        create,
-    )?);
+    // Field indexes open their memory mapped files with the global advice
+    let payload_index_advice = options.mmap_advice.payload_index.map(Advice::from);
+    let payload_index: Arc<AtomicRefCell<StructPayloadIndex>> =
+        sp(advice::with_global_override(payload_index_advice, || {
+            StructPayloadIndex::open(
+                payload_storage.clone(),
+                id_tracker.clone(),
+                vector_storages.clone(),
+                &payload_index_path,
+                appendable_flag,
+                create,
+            )
+        })?);
+
+    if matches!(
+        options.populate,
+        Some(PopulatePolicy::Eager | PopulatePolicy::Prioritized),
+    ) {
+        payload_index.borrow().populate()?;
+    }
 
     let mut vector_data = HashMap::new();
     for (vector_name, vector_config) in &config.vector_data {
@@ -572,6 +685,7 @@ fn create_segment(
                 payload_index: payload_index.clone(),
                 quantized_vectors: quantized_vectors.clone(),
             },
+            options,
         )?);
 
         check_process_stopped(stopped)?;
@@ -601,8 +715,14 @@ fn create_segment(
             );
         }
 
+        let mut sparse_index_config = sparse_vector_config.index;
+        sparse_index_config.mmap_advice = sparse_index_config
+            .mmap_advice
+            .or(options.mmap_advice.sparse_index);
+
         let vector_index = sp(create_sparse_vector_index(SparseVectorIndexOpenArgs {
-            config: sparse_vector_config.index,
+            config: sparse_index_config,
+            modifier: sparse_vector_config.modifier,
             id_tracker: id_tracker.clone(),
             vector_storage: vector_storage.clone(),
             payload_index: payload_index.clone(),
@@ -772,6 +892,17 @@ pub fn normalize_segment_dir(path: &Path) -> OperationResult<Option<(PathBuf, Uu
 /// In production use [`normalize_segment_dir`] to obtain correct path and UUID.
 /// In tests it is acceptable to pass an arbitrary UUID, e.g., [`Uuid::nil()`].
 pub fn load_segment(path: &Path, uuid: Uuid, stopped: &AtomicBool) -> OperationResult<Segment> {
+    load_segment_with_options(path, uuid, &SegmentLoadOptions::default(), stopped)
+}
+
+/// Same as [`load_segment`], but memory mapped files are populated and advised according to
+/// `options`, instead of being inferred from `on_disk` flags of the segment config.
+pub fn load_segment_with_options(
+    path: &Path,
+    uuid: Uuid,
+    options: &SegmentLoadOptions,
+    stopped: &AtomicBool,
+) -> OperationResult<Segment> {
     let stored_version = SegmentVersion::load(path)?.ok_or_else(|| {
         OperationError::service_error(format!(
             "Segment version file not found in segment: {}",
@@ -791,7 +922,7 @@ pub fn load_segment(path: &Path, uuid: Uuid, stopped: &AtomicBool) -> OperationR
             )));
         }
 
-        if stored_version.major == 0 && stored_version.minor < 3 {
+        if !SegmentVersion::can_upgrade_storage(&stored_version) {
             return Err(OperationError::service_error(format!(
                 "Segment version({stored_version}) is not compatible with current version({app_version})"
             )));
@@ -818,9 +949,21 @@ pub fn load_segment(path: &Path, uuid: Uuid, stopped: &AtomicBool) -> OperationR
         path,
         uuid,
         &segment_state.config,
+        options,
         stopped,
         false,
-    )?;
+    )
+    .map_err(|err| {
+        // Report all invalid files, not only the one which failed to open
+        let report = validate_segment(path, &segment_state.config);
+        if report.is_valid() {
+            return err;
+        }
+        log::error!("Failed to open segment {}: {report}", path.display());
+        let err = OperationError::service_error(format!("{err}, {report}"));
+        record_failed_segment(report);
+        err
+    })?;
 
     #[cfg(feature = "rocksdb")]
     {
@@ -859,7 +1002,16 @@ pub fn build_segment(
     let stopped = AtomicBool::new(false);
 
     fs::create_dir_all(&segment_path)?;
-    let segment = create_segment(None, None, &segment_path, uuid, config, &stopped, true)?;
+    let segment = create_segment(
+        None,
+        None,
+        &segment_path,
+        uuid,
+        config,
+        &SegmentLoadOptions::default(),
+        &stopped,
+        true,
+    )?;
     segment.save_current_state()?;
 
     // Version is the last file to save, as it will be used to check if segment was built correctly.
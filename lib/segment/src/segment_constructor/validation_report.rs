@@ -0,0 +1,271 @@
+//! Open-time validation of segment files.
+//!
+//! Opening a segment stops at the first invalid file. When it fails, the files of all components
+//! are validated independently with [`validate_segment`], so that the error and the
+//! [`ValidationReport`] describe every problem at once. Reports of segments which failed to open
+//! are kept in memory, and can be retrieved with [`failed_segment_reports`].
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use half::f16;
+use parking_lot::Mutex;
+use schemars::JsonSchema;
+use serde::Serialize;
+use sparse::common::types::QuantizedU8;
+use sparse::index::inverted_index::inverted_index_compressed_mmap::InvertedIndexCompressedMmap;
+
+use super::{get_vector_index_path, get_vector_storage_path};
+use crate::common::anonymize::Anonymize;
+use crate::common::operation_error::OperationError;
+use crate::data_types::vectors::{
+    VectorElementType, VectorElementTypeBf16, VectorElementTypeByte, VectorElementTypeHalf,
+};
+use crate::index::hnsw_index::graph_layers::GraphLayers;
+use crate::index::sparse_index::sparse_index_config::SparseIndexType;
+use crate::types::{
+    Indexes, SegmentConfig, SparseVectorDataConfig, VectorDataConfig, VectorStorageDatatype,
+    VectorStorageType,
+};
+use crate::vector_storage::dense::memmap_dense_vector_storage::validate_memmap_vector_storage;
+use crate::vector_storage::quantized::quantized_vectors::QuantizedVectors;
+
+/// Maximum number of kept reports of segments which failed to open
+const MAX_FAILED_REPORTS: usize = 64;
+
+static FAILED_REPORTS: Mutex<Vec<ValidationReport>> = Mutex::new(Vec::new());
+
+/// Problems found in the files of a segment.
+#[derive(Serialize, Clone, Debug, Default, PartialEq, JsonSchema, Anonymize)]
+#[anonymize(false)]
+pub struct ValidationReport {
+    /// Path of the segment
+    pub segment: String,
+    /// Problems found, empty if all validated files are valid
+    pub issues: Vec<ValidationIssue>,
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq, JsonSchema, Anonymize)]
+#[anonymize(false)]
+pub struct ValidationIssue {
+    /// Component of the segment, like the vector storage or the index of a named vector
+    pub component: String,
+    /// Path of the invalid file or directory
+    pub path: String,
+    /// Description of the problem
+    pub message: String,
+}
+
+impl ValidationReport {
+    pub fn is_valid(&self) -> bool {
+        self.issues.is_empty()
+    }
+
+    fn add_issue(&mut self, component: impl Into<String>, path: &Path, err: impl fmt::Display) {
+        self.issues.push(ValidationIssue {
+            component: component.into(),
+            path: path.display().to_string(),
+            message: err.to_string(),
+        });
+    }
+}
+
+impl fmt::Display for ValidationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "segment {} has {} invalid file(s)",
+            self.segment,
+            self.issues.len(),
+        )?;
+        for issue in &self.issues {
+            write!(f, "; {}: {}", issue.component, issue.message)?;
+        }
+        Ok(())
+    }
+}
+
+/// Validate files of all components of the segment at `path`, without opening it.
+///
+/// Only checks which don't need other components are done: headers and sizes of vector files,
+/// checksums of quantized vectors, bounds and checksums of sparse postings and layout of HNSW
+/// links.
+pub fn validate_segment(path: &Path, config: &SegmentConfig) -> ValidationReport {
+    let mut report = ValidationReport {
+        segment: path.display().to_string(),
+        issues: Vec::new(),
+    };
+
+    for (vector_name, vector_config) in &config.vector_data {
+        let storage_path = get_vector_storage_path(path, vector_name);
+        for (file, err) in validate_dense_vector_storage(&storage_path, vector_config) {
+            report.add_issue(format!("vector storage '{vector_name}'"), &file, err);
+        }
+        let is_multivector = vector_config.multivector_config.is_some();
+        if let Err(err) = QuantizedVectors::verify_files(&storage_path, is_multivector) {
+            report.add_issue(
+                format!("quantized vectors '{vector_name}'"),
+                &storage_path,
+                err,
+            );
+        }
+
+        let index_path = get_vector_index_path(path, vector_name);
+        if let Indexes::Hnsw(_) = vector_config.index
+            && let Err(err) = GraphLayers::validate_links(&index_path)
+        {
+            report.add_issue(format!("HNSW index '{vector_name}'"), &index_path, err);
+        }
+    }
+
+    for (vector_name, sparse_config) in &config.sparse_vector_data {
+        let index_path = get_vector_index_path(path, vector_name);
+        if let Err(err) = validate_sparse_index(&index_path, sparse_config) {
+            report.add_issue(format!("sparse index '{vector_name}'"), &index_path, err);
+        }
+    }
+
+    report
+}
+
+/// Keep the report of a segment which failed to open, replacing an older one of the same segment.
+pub fn record_failed_segment(report: ValidationReport) {
+    let mut reports = FAILED_REPORTS.lock();
+    reports.retain(|old| old.segment != report.segment);
+    if reports.len() >= MAX_FAILED_REPORTS {
+        reports.remove(0);
+    }
+    reports.push(report);
+}
+
+/// Reports of segments which failed to open since startup.
+pub fn failed_segment_reports() -> Vec<ValidationReport> {
+    FAILED_REPORTS.lock().clone()
+}
+
+fn validate_dense_vector_storage(
+    storage_path: &Path,
+    vector_config: &VectorDataConfig,
+) -> Vec<(PathBuf, OperationError)> {
+    let is_single_file_mmap = match vector_config.storage_type {
+        VectorStorageType::Mmap
+        | VectorStorageType::InRamMmap
+        | VectorStorageType::MmapDirectIo
+        | VectorStorageType::MmapCompressed
+        | VectorStorageType::TieredMmap => true,
+        VectorStorageType::Memory
+        | VectorStorageType::ChunkedMmap
+        | VectorStorageType::InRamChunkedMmap => false,
+    };
+    // Multivectors are always stored in appendable storages
+    if !is_single_file_mmap || vector_config.multivector_config.is_some() {
+        return Vec::new();
+    }
+
+    let dim = vector_config.size;
+    match vector_config.datatype.unwrap_or_default() {
+        VectorStorageDatatype::Float32 => {
+            validate_memmap_vector_storage::<VectorElementType>(storage_path, dim)
+        }
+        VectorStorageDatatype::Float16 => {
+            validate_memmap_vector_storage::<VectorElementTypeHalf>(storage_path, dim)
+        }
+        VectorStorageDatatype::Uint8 => {
+            validate_memmap_vector_storage::<VectorElementTypeByte>(storage_path, dim)
+        }
+        VectorStorageDatatype::Bfloat16 => {
+            validate_memmap_vector_storage::<VectorElementTypeBf16>(storage_path, dim)
+        }
+    }
+}
+
+fn validate_sparse_index(
+    index_path: &Path,
+    sparse_config: &SparseVectorDataConfig,
+) -> std::io::Result<()> {
+    match sparse_config.index.index_type {
+        // Built in RAM on open, nothing to validate
+        SparseIndexType::MutableRam => return Ok(()),
+        SparseIndexType::ImmutableRam | SparseIndexType::Mmap => {}
+    }
+
+    match sparse_config.index.datatype.unwrap_or_default() {
+        VectorStorageDatatype::Float32 => {
+            InvertedIndexCompressedMmap::<f32>::load(index_path).and_then(|index| index.verify())
+        }
+        VectorStorageDatatype::Float16 => {
+            InvertedIndexCompressedMmap::<f16>::load(index_path).and_then(|index| index.verify())
+        }
+        VectorStorageDatatype::Uint8 => {
+            InvertedIndexCompressedMmap::<QuantizedU8>::load(index_path)
+                .and_then(|index| index.verify())
+        }
+        // Not supported for sparse vectors, rejected on open
+        VectorStorageDatatype::Bfloat16 => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use fs_err as fs;
+    use tempfile::Builder;
+
+    use super::*;
+    use crate::types::{Distance, PayloadStorageType};
+
+    #[test]
+    fn test_validate_segment_collects_all_issues() {
+        let dir = Builder::new().prefix("segment").tempdir().unwrap();
+        let vector_config = VectorDataConfig {
+            size: 2,
+            distance: Distance::Dot,
+            storage_type: VectorStorageType::Mmap,
+            index: Indexes::Plain {},
+            quantization_config: None,
+            multivector_config: None,
+            datatype: None,
+        };
+        let config = SegmentConfig {
+            vector_data: [
+                ("a".into(), vector_config.clone()),
+                ("b".into(), vector_config),
+            ]
+            .into_iter()
+            .collect(),
+            sparse_vector_data: Default::default(),
+            payload_storage_type: PayloadStorageType::Mmap,
+        };
+
+        // Vectors of "a" are truncated, deleted flags of "b" have a bad header
+        let storage_a = get_vector_storage_path(dir.path(), "a");
+        fs::create_dir_all(&storage_a).unwrap();
+        fs::write(storage_a.join("matrix.dat"), b"data\x01\x02\x03").unwrap();
+        fs::write(
+            storage_a.join("deleted.dat"),
+            [b"drp2".as_slice(), &[0; 28]].concat(),
+        )
+        .unwrap();
+        let storage_b = get_vector_storage_path(dir.path(), "b");
+        fs::create_dir_all(&storage_b).unwrap();
+        fs::write(
+            storage_b.join("matrix.dat"),
+            [b"data".as_slice(), &[0; 16]].concat(),
+        )
+        .unwrap();
+        fs::write(
+            storage_b.join("deleted.dat"),
+            [b"xxxx".as_slice(), &[0; 28]].concat(),
+        )
+        .unwrap();
+
+        let report = validate_segment(dir.path(), &config);
+        let mut components: Vec<_> = report
+            .issues
+            .iter()
+            .map(|issue| issue.component.as_str())
+            .collect();
+        components.sort_unstable();
+        assert_eq!(components, ["vector storage 'a'", "vector storage 'b'"]);
+        assert!(report.to_string().contains("2 invalid file(s)"));
+    }
+}
@@ -0,0 +1,9 @@
+//! Metrics for bfloat16 vectors.
+//!
+//! There is no widespread hardware support for bf16 arithmetic, so elements are widened to f32,
+//! which is exact for bf16, and accumulated in f32.
+
+pub mod simple_cosine;
+pub mod simple_dot;
+pub mod simple_euclid;
+pub mod simple_manhattan;
@@ -0,0 +1,22 @@
+use common::types::ScoreType;
+
+use super::simple_dot::dot_similarity_bf16;
+use crate::data_types::vectors::{DenseVector, VectorElementType, VectorElementTypeBf16};
+use crate::spaces::metric::Metric;
+use crate::spaces::simple::CosineMetric;
+use crate::types::Distance;
+
+impl Metric<VectorElementTypeBf16> for CosineMetric {
+    fn distance() -> Distance {
+        Distance::Cosine
+    }
+
+    fn similarity(v1: &[VectorElementTypeBf16], v2: &[VectorElementTypeBf16]) -> ScoreType {
+        dot_similarity_bf16(v1, v2)
+    }
+
+    fn preprocess(vector: DenseVector) -> DenseVector {
+        // Vectors are normalized before conversion to bf16
+        <CosineMetric as Metric<VectorElementType>>::preprocess(vector)
+    }
+}
@@ -0,0 +1,31 @@
+use common::types::ScoreType;
+use half::bf16;
+
+use crate::data_types::vectors::{DenseVector, VectorElementTypeBf16};
+use crate::spaces::metric::Metric;
+use crate::spaces::simple::DotProductMetric;
+use crate::types::Distance;
+
+impl Metric<VectorElementTypeBf16> for DotProductMetric {
+    fn distance() -> Distance {
+        Distance::Dot
+    }
+
+    fn similarity(v1: &[VectorElementTypeBf16], v2: &[VectorElementTypeBf16]) -> ScoreType {
+        dot_similarity_bf16(v1, v2)
+    }
+
+    fn preprocess(vector: DenseVector) -> DenseVector {
+        vector
+    }
+}
+
+pub fn dot_similarity_bf16(
+    v1: &[VectorElementTypeBf16],
+    v2: &[VectorElementTypeBf16],
+) -> ScoreType {
+    v1.iter()
+        .zip(v2)
+        .map(|(a, b)| bf16::to_f32(*a) * bf16::to_f32(*b))
+        .sum::<f32>()
+}
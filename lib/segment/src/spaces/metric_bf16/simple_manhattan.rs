@@ -0,0 +1,31 @@
+use common::types::ScoreType;
+use half::bf16;
+
+use crate::data_types::vectors::{DenseVector, VectorElementTypeBf16};
+use crate::spaces::metric::Metric;
+use crate::spaces::simple::ManhattanMetric;
+use crate::types::Distance;
+
+impl Metric<VectorElementTypeBf16> for ManhattanMetric {
+    fn distance() -> Distance {
+        Distance::Manhattan
+    }
+
+    fn similarity(v1: &[VectorElementTypeBf16], v2: &[VectorElementTypeBf16]) -> ScoreType {
+        manhattan_similarity_bf16(v1, v2)
+    }
+
+    fn preprocess(vector: DenseVector) -> DenseVector {
+        vector
+    }
+}
+
+pub fn manhattan_similarity_bf16(
+    v1: &[VectorElementTypeBf16],
+    v2: &[VectorElementTypeBf16],
+) -> ScoreType {
+    -v1.iter()
+        .zip(v2)
+        .map(|(a, b)| (bf16::to_f32(*a) - bf16::to_f32(*b)).abs())
+        .sum::<f32>()
+}
@@ -1,5 +1,6 @@
 use schemars::JsonSchema;
 use serde::Serialize;
+use sparse::common::types::DimId;
 
 use crate::common::anonymize::Anonymize;
 use crate::common::operation_time_statistics::OperationDurationStatistics;
@@ -30,6 +31,10 @@ pub struct PayloadIndexTelemetry {
     #[serde(skip_serializing_if = "Option::is_none")]
     #[anonymize(false)]
     pub histogram_bucket_size: Option<usize>,
+
+    /// Disk and RAM usage of the index.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resources: Option<PayloadIndexResourcesTelemetry>,
 }
 
 impl PayloadIndexTelemetry {
@@ -37,6 +42,21 @@ impl PayloadIndexTelemetry {
         self.field_name = Some(name);
         self
     }
+
+    pub fn set_resources(mut self, resources: PayloadIndexResourcesTelemetry) -> Self {
+        self.resources = Some(resources);
+        self
+    }
+}
+
+#[derive(Serialize, Clone, Debug, JsonSchema, Anonymize)]
+pub struct PayloadIndexResourcesTelemetry {
+    /// Total size of index files on disk, in bytes.
+    pub disk_bytes: usize,
+
+    /// Whether index files are populated into RAM, instead of being read from disk on demand.
+    #[anonymize(false)]
+    pub populated: bool,
 }
 
 #[derive(Serialize, Clone, Debug, JsonSchema, Anonymize, Default)]
@@ -63,6 +83,9 @@ pub struct VectorIndexSearchesTelemetry {
     #[serde(skip_serializing_if = "OperationDurationStatistics::is_empty")]
     pub filtered_large_cardinality: OperationDurationStatistics,
 
+    #[serde(skip_serializing_if = "OperationDurationStatistics::is_empty")]
+    pub filtered_acorn: OperationDurationStatistics,
+
     #[serde(skip_serializing_if = "OperationDurationStatistics::is_empty")]
     pub filtered_exact: OperationDurationStatistics,
 
@@ -71,6 +94,110 @@ pub struct VectorIndexSearchesTelemetry {
 
     #[serde(skip_serializing_if = "OperationDurationStatistics::is_empty")]
     pub unfiltered_exact: OperationDurationStatistics,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sparse_postings: Option<SparsePostingsTelemetry>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hnsw_links: Option<HnswLinksTelemetry>,
+}
+
+/// Distribution of posting list lengths of a sparse vector index.
+///
+/// Search time is dominated by the longest postings of query dimensions, so a few "hot"
+/// dimensions present in most of the vectors make search slow.
+#[derive(Serialize, Clone, Debug, Default, PartialEq, JsonSchema, Anonymize)]
+pub struct SparsePostingsTelemetry {
+    /// Number of non-empty posting lists
+    pub postings_count: usize,
+
+    /// Length of the longest posting list
+    pub max_posting_length: usize,
+
+    /// Largest dimension id with a non-empty posting list
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[anonymize(false)]
+    pub max_dim_id: Option<DimId>,
+
+    /// Number of posting lists by length, bucket `i` counts lengths in range `[2^i, 2^(i+1))`
+    pub length_histogram: Vec<usize>,
+}
+
+impl SparsePostingsTelemetry {
+    pub fn add_posting(&mut self, dim_id: DimId, length: usize) {
+        if length == 0 {
+            return;
+        }
+
+        self.postings_count += 1;
+        self.max_posting_length = self.max_posting_length.max(length);
+        self.max_dim_id = self.max_dim_id.max(Some(dim_id));
+
+        let bucket = length.ilog2() as usize;
+        if self.length_histogram.len() <= bucket {
+            self.length_histogram.resize(bucket + 1, 0);
+        }
+        self.length_histogram[bucket] += 1;
+    }
+
+    /// Combine statistics of postings of multiple indexes, e.g. of different segments
+    pub fn merge(&mut self, other: &Self) {
+        let Self {
+            postings_count,
+            max_posting_length,
+            max_dim_id,
+            length_histogram,
+        } = other;
+
+        self.postings_count += postings_count;
+        self.max_posting_length = self.max_posting_length.max(*max_posting_length);
+        self.max_dim_id = self.max_dim_id.max(*max_dim_id);
+
+        if self.length_histogram.len() < length_histogram.len() {
+            self.length_histogram.resize(length_histogram.len(), 0);
+        }
+        for (total, count) in self.length_histogram.iter_mut().zip(length_histogram) {
+            *total += count;
+        }
+    }
+}
+
+/// Structure of links of an HNSW graph, collected when first requested.
+///
+/// Badly built graphs, e.g. after an interrupted build or a migration between architectures,
+/// show up as levels with unexpectedly low degree or many points without links.
+#[derive(Serialize, Clone, Debug, Default, PartialEq, JsonSchema, Anonymize)]
+pub struct HnswLinksTelemetry {
+    /// Statistics of each level of the graph, starting from level 0
+    pub levels: Vec<HnswLevelLinksTelemetry>,
+}
+
+#[derive(Serialize, Clone, Debug, Default, PartialEq, JsonSchema, Anonymize)]
+pub struct HnswLevelLinksTelemetry {
+    /// Number of points present on the level
+    pub points_count: usize,
+
+    /// Total number of links of the points on the level
+    pub links_count: usize,
+
+    /// Average number of links per point
+    #[anonymize(false)]
+    pub avg_out_degree: f32,
+
+    /// Number of points by their number of links, index `i` counts points with `i` links
+    pub degree_histogram: Vec<usize>,
+}
+
+impl HnswLevelLinksTelemetry {
+    pub fn add_point(&mut self, degree: usize) {
+        self.points_count += 1;
+        self.links_count += degree;
+        if self.degree_histogram.len() <= degree {
+            self.degree_histogram.resize(degree + 1, 0);
+        }
+        self.degree_histogram[degree] += 1;
+        self.avg_out_degree = self.links_count as f32 / self.points_count as f32;
+    }
 }
 
 #[derive(Serialize, Clone, Debug, JsonSchema, Anonymize)]
@@ -39,7 +39,7 @@ use crate::data_types::primitive::PrimitiveVectorElement;
 use crate::data_types::tiny_map::TinyMap;
 use crate::data_types::vectors::{DenseVector, VectorStructInternal};
 use crate::index::field_index::CardinalityEstimation;
-use crate::index::sparse_index::sparse_index_config::SparseIndexConfig;
+use crate::index::sparse_index::sparse_index_config::{MmapAdvice, SparseIndexConfig};
 use crate::json_path::JsonPath;
 use crate::spaces::metric::{Metric, MetricPostProcessing};
 use crate::spaces::simple::{CosineMetric, DotProductMetric, EuclidMetric, ManhattanMetric};
@@ -86,6 +86,9 @@ impl<'de> Deserialize<'de> for DateTimePayloadType {
     /// Returns a clear user-facing error when the format is invalid.
     /// Example accepted value: `2014-01-01T00:00:00Z`.
     ///
+    /// Values qualified with a timezone offset, e.g. `2014-01-01T02:00:00+02:00`, are converted
+    /// to UTC. Values without a timezone are taken as UTC.
+    ///
     /// Binary formats (CBOR/MessagePack/WAL) also serialize as RFC3339 strings, so we reuse
     /// the same parsing path everywhere.
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
@@ -434,6 +437,10 @@ pub struct PayloadIndexInfo {
     pub params: Option<PayloadSchemaParams>,
     /// Number of points indexed with this index
     pub points: usize,
+    /// Expected RAM usage of the index in bytes, once it is loaded and its files are populated
+    pub ram_usage_bytes: usize,
+    /// Size of the index files on disk in bytes
+    pub disk_usage_bytes: usize,
 }
 
 impl PayloadIndexInfo {
@@ -443,14 +450,25 @@ impl PayloadIndexInfo {
                 data_type,
                 params: None,
                 points: points_count,
+                ram_usage_bytes: 0,
+                disk_usage_bytes: 0,
             },
             PayloadFieldSchema::FieldParams(schema_params) => PayloadIndexInfo {
                 data_type: schema_params.kind(),
                 params: Some(schema_params),
                 points: points_count,
+                ram_usage_bytes: 0,
+                disk_usage_bytes: 0,
             },
         }
     }
+
+    /// Accumulate info of the same index in another segment or shard
+    pub fn merge(&mut self, other: &PayloadIndexInfo) {
+        self.points += other.points;
+        self.ram_usage_bytes += other.ram_usage_bytes;
+        self.disk_usage_bytes += other.disk_usage_bytes;
+    }
 }
 
 #[derive(Debug, Serialize, JsonSchema, Anonymize, Clone, PartialEq, Eq)]
@@ -681,9 +699,31 @@ pub struct HnswConfig {
     /// Requires quantized vectors to be enabled. Multi-vectors are not supported.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub inline_storage: Option<bool>,
+    /// Target recall of searches without explicit `hnsw_ef`. If set, `ef` of each HNSW index
+    /// is tuned by periodically comparing sampled searches with exact search, and the tuned
+    /// value is persisted with the index. Default: not set, `ef` is equal to `ef_construct`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[validate(range(min = 0.0, max = 1.0))]
+    pub target_recall: Option<OrderedFloat<f64>>,
+    /// Number of edges per node on level 0 of the index graph, which contains all points.
+    /// Upper levels use `m`. Default: not set, `2 * m` is used.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[validate(range(min = 1))]
+    pub m0: Option<usize>,
+    /// Lock the HNSW graph links in RAM with `mlock`, once they are loaded, so that they are not
+    /// evicted from the page cache under memory pressure. Requires a large enough
+    /// `RLIMIT_MEMLOCK`, otherwise links are left unlocked and a warning is logged.
+    /// Default: false.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lock_in_ram: Option<bool>,
 }
 
 impl HnswConfig {
+    /// Number of edges per node on level 0, see [`Self::m0`].
+    pub fn effective_m0(&self) -> usize {
+        self.m0.unwrap_or(self.m * 2)
+    }
+
     /// Detect configuration mismatch against `other` that requires rebuilding
     ///
     /// Returns true only if both conditions are met:
@@ -701,9 +741,13 @@ impl HnswConfig {
             payload_m,
             on_disk,
             inline_storage,
+            target_recall,
+            m0: _,
+            lock_in_ram: _,
         } = *self;
 
         m != other.m
+            || self.effective_m0() != other.effective_m0()
             || ef_construct != other.ef_construct
             || full_scan_threshold != other.full_scan_threshold
             || payload_m != other.payload_m
@@ -712,6 +756,8 @@ impl HnswConfig {
             // to flip this flag
             || on_disk != other.on_disk
             || inline_storage != other.inline_storage
+            // Target recall is only read when the index is built, along with the tuned `ef`
+            || target_recall != other.target_recall
     }
 }
 
@@ -850,6 +896,13 @@ pub struct BinaryQuantizationConfig {
     #[serde(default)]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub query_encoding: Option<BinaryQuantizationQueryEncoding>,
+
+    /// Oversampling of searches, which don't specify it in their quantization params.
+    /// Default: not set, no oversampling.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[validate(range(min = 1.0))]
+    pub oversampling: Option<OrderedFloat<f64>>,
 }
 
 #[derive(Clone, Debug, Eq, PartialEq, Hash, Deserialize, Serialize, JsonSchema, Validate)]
@@ -1318,6 +1371,9 @@ impl Default for HnswConfig {
             on_disk: Some(false),
             payload_m: None,
             inline_storage: None,
+            target_recall: None,
+            m0: None,
+            lock_in_ram: None,
         }
     }
 }
@@ -1365,6 +1421,65 @@ impl PayloadStorageType {
     }
 }
 
+/// When memory mapped files of a segment are read into the page cache on load
+///
+/// If not set, it is inferred from `on_disk` flags of vectors, HNSW graphs and payload indexes.
+#[derive(
+    Anonymize, Debug, Deserialize, Serialize, JsonSchema, Copy, Clone, PartialEq, Eq, Hash,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum PopulatePolicy {
+    /// Read vectors, HNSW graph links and payload indexes into the page cache on load
+    Eager,
+    /// Don't read anything ahead, pages are loaded from disk by the first searches
+    ///
+    /// Payload indexes which are kept in RAM are still read on load.
+    Lazy,
+    /// Same as `Eager`, but HNSW graph links are populated with latency-critical parts first,
+    /// while level 0 links are populated in the background
+    Prioritized,
+}
+
+impl PopulatePolicy {
+    /// Whether to populate a file on load, given the choice `inferred` from its `on_disk` flag
+    pub fn populate_on_load(policy: Option<Self>, inferred: bool) -> bool {
+        match policy {
+            None => inferred,
+            Some(PopulatePolicy::Eager | PopulatePolicy::Prioritized) => true,
+            Some(PopulatePolicy::Lazy) => false,
+        }
+    }
+}
+
+/// Access pattern hints for memory mapped files of segment components
+#[derive(
+    Anonymize, Debug, Default, Deserialize, Serialize, JsonSchema, Copy, Clone, PartialEq, Eq, Hash,
+)]
+#[serde(rename_all = "snake_case")]
+pub struct MmapAdviceConfig {
+    /// Dense and multi-dense vector storages.
+    /// Default: global `mmap_advice` for vectors on disk, `normal` for vectors in RAM.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vectors: Option<MmapAdvice>,
+    /// HNSW graph links.
+    /// Default: `random`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub links: Option<MmapAdvice>,
+    /// Payload indexes.
+    /// Default: global `mmap_advice`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub payload_index: Option<MmapAdvice>,
+    /// Sparse indexes, unless set in the config of the sparse index.
+    /// Default: `normal`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sparse_index: Option<MmapAdvice>,
+    /// Request transparent huge pages for immutable vector storages and HNSW graph links on disk.
+    /// Reduces TLB pressure for large segments, requires transparent huge pages enabled in the kernel.
+    /// Default: false.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hugepages: Option<bool>,
+}
+
 #[derive(Clone, Debug, PartialEq, Deserialize, Serialize, JsonSchema, Anonymize)]
 #[serde(rename_all = "snake_case")]
 pub struct SegmentConfig {
@@ -1510,6 +1625,21 @@ pub enum VectorStorageType {
     /// Storage in a single mmap file, not appendable
     /// Pre-fetched into RAM on load
     InRamMmap,
+    /// Same as `Mmap`, but searches read vectors with `O_DIRECT`, bypassing the page cache
+    ///
+    /// Designed for storages much larger than RAM, so that full scans don't evict other data
+    /// from the page cache. Falls back to `Mmap` reads if the filesystem doesn't support `O_DIRECT`.
+    MmapDirectIo,
+    /// Same as `Mmap`, but vectors are compressed with zstd on disk, not appendable
+    ///
+    /// Designed for original vectors which are rarely read, like when quantized vectors are used
+    /// for search. Vectors are decompressed on demand, frequently read blocks are kept in RAM.
+    MmapCompressed,
+    /// Same as `Mmap`, but recently accessed vectors are locked in RAM, not appendable
+    ///
+    /// A middle ground between `InRamMmap` and `Mmap`: the fraction of the storage kept in RAM
+    /// is configured with `storage.performance.hot_tier_ratio`, it follows the access pattern.
+    TieredMmap,
 }
 
 #[cfg(any(test, feature = "testing"))]
@@ -1533,6 +1663,8 @@ pub enum VectorStorageDatatype {
     Float16,
     // Unsigned 8-bit integer
     Uint8,
+    // Brain floating point, truncated single-precision with the same exponent range
+    Bfloat16,
 }
 
 #[derive(
@@ -1569,7 +1701,11 @@ impl VectorStorageType {
     pub fn is_on_disk(&self) -> bool {
         match self {
             Self::Memory | Self::InRamChunkedMmap | Self::InRamMmap => false,
-            Self::Mmap | Self::ChunkedMmap => true,
+            Self::Mmap
+            | Self::ChunkedMmap
+            | Self::MmapDirectIo
+            | Self::MmapCompressed
+            | Self::TieredMmap => true,
         }
     }
 }
@@ -1611,6 +1747,9 @@ impl VectorDataConfig {
             VectorStorageType::ChunkedMmap => true,
             VectorStorageType::InRamChunkedMmap => true,
             VectorStorageType::InRamMmap => false,
+            VectorStorageType::MmapDirectIo => false,
+            VectorStorageType::MmapCompressed => false,
+            VectorStorageType::TieredMmap => false,
         };
         is_index_appendable && is_storage_appendable
     }
@@ -2133,6 +2272,45 @@ impl PayloadSchemaParams {
             PayloadSchemaParams::Bool(params) => params.enable_hnsw.unwrap_or(true),
         }
     }
+
+    pub fn null_index(&self) -> bool {
+        match self {
+            PayloadSchemaParams::Keyword(params) => params.null_index.unwrap_or(true),
+            PayloadSchemaParams::Integer(params) => params.null_index.unwrap_or(true),
+            PayloadSchemaParams::Float(params) => params.null_index.unwrap_or(true),
+            PayloadSchemaParams::Datetime(params) => params.null_index.unwrap_or(true),
+            PayloadSchemaParams::Uuid(params) => params.null_index.unwrap_or(true),
+            PayloadSchemaParams::Text(params) => params.null_index.unwrap_or(true),
+            PayloadSchemaParams::Geo(params) => params.null_index.unwrap_or(true),
+            PayloadSchemaParams::Bool(params) => params.null_index.unwrap_or(true),
+        }
+    }
+
+    pub fn trigram_index(&self) -> bool {
+        match self {
+            PayloadSchemaParams::Keyword(params) => params.trigram_index.unwrap_or(false),
+            PayloadSchemaParams::Integer(_)
+            | PayloadSchemaParams::Float(_)
+            | PayloadSchemaParams::Datetime(_)
+            | PayloadSchemaParams::Uuid(_)
+            | PayloadSchemaParams::Text(_)
+            | PayloadSchemaParams::Geo(_)
+            | PayloadSchemaParams::Bool(_) => false,
+        }
+    }
+
+    pub fn composite_with(&self) -> Option<&JsonPath> {
+        match self {
+            PayloadSchemaParams::Keyword(params) => params.composite_with.as_ref(),
+            PayloadSchemaParams::Integer(params) => params.composite_with.as_ref(),
+            PayloadSchemaParams::Float(_)
+            | PayloadSchemaParams::Datetime(_)
+            | PayloadSchemaParams::Uuid(_)
+            | PayloadSchemaParams::Text(_)
+            | PayloadSchemaParams::Geo(_)
+            | PayloadSchemaParams::Bool(_) => None,
+        }
+    }
 }
 
 impl Validate for PayloadSchemaParams {
@@ -2291,6 +2469,27 @@ impl PayloadFieldSchema {
             PayloadFieldSchema::FieldParams(p) => p.enable_hnsw(),
         }
     }
+
+    pub fn null_index(&self) -> bool {
+        match self {
+            PayloadFieldSchema::FieldType(_) => true,
+            PayloadFieldSchema::FieldParams(p) => p.null_index(),
+        }
+    }
+
+    pub fn trigram_index(&self) -> bool {
+        match self {
+            PayloadFieldSchema::FieldType(_) => false,
+            PayloadFieldSchema::FieldParams(p) => p.trigram_index(),
+        }
+    }
+
+    pub fn composite_with(&self) -> Option<&JsonPath> {
+        match self {
+            PayloadFieldSchema::FieldType(_) => None,
+            PayloadFieldSchema::FieldParams(p) => p.composite_with(),
+        }
+    }
 }
 
 impl From<PayloadSchemaType> for PayloadFieldSchema {
@@ -2307,6 +2506,8 @@ impl TryFrom<PayloadIndexInfo> for PayloadFieldSchema {
             data_type,
             params,
             points: _,
+            ram_usage_bytes: _,
+            disk_usage_bytes: _,
         } = index_info;
 
         match params {
@@ -2431,6 +2632,14 @@ pub struct MatchTextAny {
     pub text_any: String,
 }
 
+/// Full-text match of all tokens of the string, tolerating typos.
+/// Each token matches indexed tokens within 1 or 2 edits, depending on its length.
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub struct MatchTextFuzzy {
+    pub text_fuzzy: String,
+}
+
 impl<S: Into<String>> From<S> for MatchText {
     fn from(text: S) -> Self {
         MatchText { text: text.into() }
@@ -2473,6 +2682,7 @@ pub enum MatchInterface {
     Value(MatchValue),
     Text(MatchText),
     TextAny(MatchTextAny),
+    TextFuzzy(MatchTextFuzzy),
     Phrase(MatchPhrase),
     Any(MatchAny),
     Except(MatchExcept),
@@ -2485,6 +2695,7 @@ pub enum Match {
     Value(MatchValue),
     Text(MatchText),
     TextAny(MatchTextAny),
+    TextFuzzy(MatchTextFuzzy),
     Phrase(MatchPhrase),
     Any(MatchAny),
     Except(MatchExcept),
@@ -2528,6 +2739,9 @@ impl From<MatchInterface> for Match {
             MatchInterface::TextAny(text_any) => Self::TextAny(MatchTextAny {
                 text_any: text_any.text_any,
             }),
+            MatchInterface::TextFuzzy(text_fuzzy) => Self::TextFuzzy(MatchTextFuzzy {
+                text_fuzzy: text_fuzzy.text_fuzzy,
+            }),
             MatchInterface::Any(any) => Self::Any(MatchAny { any: any.any }),
             MatchInterface::Except(except) => Self::Except(MatchExcept {
                 except: except.except,
@@ -3140,6 +3354,7 @@ impl FieldCondition {
             Match::Text(_) => 0,
             Match::Phrase(_) => 0,
             Match::TextAny(_) => 0,
+            Match::TextFuzzy(_) => 0,
         }
     }
 }
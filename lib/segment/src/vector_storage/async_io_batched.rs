@@ -0,0 +1,120 @@
+use std::io;
+
+use common::fs::advise_will_need;
+use common::types::PointOffsetType;
+use fs_err::File;
+use zerocopy::IntoBytes as _;
+
+use crate::common::operation_error::OperationResult;
+use crate::data_types::primitive::PrimitiveVectorElement;
+
+/// Number of points, which reads are issued to the OS at once.
+const DISK_PARALLELISM: usize = 16;
+
+/// Portable implementation of the async_io module for those platforms that don't support io_uring.
+///
+/// Points are processed in batches. Vectors of the whole batch are first hinted to the OS with
+/// `fadvise(WILLNEED)`, so that the disk can fetch them in parallel, and then read with `pread`.
+/// Vectors of consecutive points are read with a single call.
+#[derive(Debug)]
+pub struct UringReader<T: PrimitiveVectorElement> {
+    file: File,
+    /// Buffer for vectors of consecutive points, typed to keep the alignment of `T`
+    buffer: Vec<T>,
+    raw_size: usize,
+    header_size: usize,
+}
+
+#[allow(clippy::unnecessary_wraps)] // `new` have to follow the same signature as io_uring based `UringReader`
+impl<T: PrimitiveVectorElement> UringReader<T> {
+    pub fn new(file: File, raw_size: usize, header_size: usize) -> OperationResult<Self> {
+        Ok(Self {
+            file,
+            buffer: Vec::new(),
+            raw_size,
+            header_size,
+        })
+    }
+
+    /// Takes in iterator of point offsets, reads it, and yields a callback with the read data.
+    pub fn read_stream(
+        &mut self,
+        points: impl IntoIterator<Item = PointOffsetType>,
+        mut callback: impl FnMut(usize, PointOffsetType, &[T]),
+    ) -> OperationResult<()> {
+        let mut batch = Vec::with_capacity(DISK_PARALLELISM);
+
+        for item in points.into_iter().enumerate() {
+            batch.push(item);
+            if batch.len() == DISK_PARALLELISM {
+                self.read_batch(&batch, &mut callback)?;
+                batch.clear();
+            }
+        }
+
+        if !batch.is_empty() {
+            self.read_batch(&batch, &mut callback)?;
+        }
+
+        Ok(())
+    }
+
+    fn read_batch(
+        &mut self,
+        batch: &[(usize, PointOffsetType)],
+        callback: &mut impl FnMut(usize, PointOffsetType, &[T]),
+    ) -> OperationResult<()> {
+        let runs = || batch.chunk_by(|(_, prev), (_, next)| prev.checked_add(1) == Some(*next));
+
+        for run in runs() {
+            let (offset, len) = self.run_range(run);
+            // It is only a hint, reads below are correct regardless of it
+            let _ = advise_will_need(&self.file, offset, len as u64);
+        }
+
+        let dim = self.raw_size / size_of::<T>();
+        for run in runs() {
+            let (offset, len) = self.run_range(run);
+            self.buffer.resize(len / size_of::<T>(), T::default());
+            read_exact_at(&self.file, self.buffer.as_mut_bytes(), offset)?;
+
+            for (&(idx, point), vector) in run.iter().zip(self.buffer.chunks_exact(dim)) {
+                callback(idx, point, vector);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Offset and length in the file of vectors of consecutive points
+    fn run_range(&self, run: &[(usize, PointOffsetType)]) -> (u64, usize) {
+        let (_, first_point) = run[0];
+        let offset = self.header_size + self.raw_size * first_point as usize;
+        (offset as u64, self.raw_size * run.len())
+    }
+}
+
+#[cfg(unix)]
+fn read_exact_at(file: &File, buf: &mut [u8], offset: u64) -> io::Result<()> {
+    use std::os::unix::fs::FileExt as _;
+
+    file.file().read_exact_at(buf, offset)
+}
+
+#[cfg(windows)]
+fn read_exact_at(file: &File, mut buf: &mut [u8], mut offset: u64) -> io::Result<()> {
+    use std::os::windows::fs::FileExt as _;
+
+    while !buf.is_empty() {
+        match file.file().seek_read(buf, offset) {
+            Ok(0) => return Err(io::ErrorKind::UnexpectedEof.into()),
+            Ok(read) => {
+                buf = &mut buf[read..];
+                offset += read as u64;
+            }
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+            Err(err) => return Err(err),
+        }
+    }
+    Ok(())
+}
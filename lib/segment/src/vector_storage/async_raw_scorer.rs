@@ -7,35 +7,48 @@ use super::query::{
 use super::query_scorer::custom_query_scorer::CustomQueryScorer;
 use super::query_scorer::{QueryScorerBytes, QueryScorerBytesImpl};
 use crate::common::operation_error::{OperationError, OperationResult};
-use crate::data_types::vectors::{DenseVector, QueryVector, VectorElementType, VectorInternal};
+use crate::data_types::primitive::PrimitiveVectorElement;
+use crate::data_types::vectors::{DenseVector, QueryVector, VectorInternal};
 use crate::spaces::metric::Metric;
 use crate::spaces::simple::{CosineMetric, DotProductMetric, EuclidMetric, ManhattanMetric};
 use crate::types::Distance;
 use crate::vector_storage::dense::memmap_dense_vector_storage::MemmapDenseVectorStorage;
 use crate::vector_storage::dense::mmap_dense_vectors::MmapDenseVectors;
+use crate::vector_storage::mmap_endian::MmapEndianConvertible;
 use crate::vector_storage::query::NaiveFeedbackQuery;
 use crate::vector_storage::query_scorer::QueryScorer;
 use crate::vector_storage::query_scorer::metric_query_scorer::MetricQueryScorer;
 use crate::vector_storage::{RawScorer, VectorStorage as _};
 
-pub fn new<'a>(
+pub fn new<'a, TElement: PrimitiveVectorElement + MmapEndianConvertible>(
     query: QueryVector,
-    storage: &'a MemmapDenseVectorStorage<VectorElementType>,
+    storage: &'a MemmapDenseVectorStorage<TElement>,
     hardware_counter: HardwareCounterCell,
-) -> OperationResult<Box<dyn RawScorer + 'a>> {
+) -> OperationResult<Box<dyn RawScorer + 'a>>
+where
+    CosineMetric: Metric<TElement>,
+    EuclidMetric: Metric<TElement>,
+    DotProductMetric: Metric<TElement>,
+    ManhattanMetric: Metric<TElement>,
+{
     AsyncRawScorerBuilder::new(query, storage, hardware_counter).build()
 }
 
-pub struct AsyncRawScorerImpl<'a, TQueryScorer: QueryScorer<TVector = [VectorElementType]>> {
+pub struct AsyncRawScorerImpl<'a, TElement, TQueryScorer>
+where
+    TElement: PrimitiveVectorElement + MmapEndianConvertible,
+    TQueryScorer: QueryScorer<TVector = [TElement]>,
+{
     query_scorer: TQueryScorer,
-    storage: &'a MmapDenseVectors<VectorElementType>,
+    storage: &'a MmapDenseVectors<TElement>,
 }
 
-impl<'a, TQueryScorer> AsyncRawScorerImpl<'a, TQueryScorer>
+impl<'a, TElement, TQueryScorer> AsyncRawScorerImpl<'a, TElement, TQueryScorer>
 where
-    TQueryScorer: QueryScorer<TVector = [VectorElementType]>,
+    TElement: PrimitiveVectorElement + MmapEndianConvertible,
+    TQueryScorer: QueryScorer<TVector = [TElement]>,
 {
-    fn new(query_scorer: TQueryScorer, storage: &'a MmapDenseVectors<VectorElementType>) -> Self {
+    fn new(query_scorer: TQueryScorer, storage: &'a MmapDenseVectors<TElement>) -> Self {
         Self {
             query_scorer,
             storage,
@@ -43,9 +56,10 @@ where
     }
 }
 
-impl<TQueryScorer> RawScorer for AsyncRawScorerImpl<'_, TQueryScorer>
+impl<TElement, TQueryScorer> RawScorer for AsyncRawScorerImpl<'_, TElement, TQueryScorer>
 where
-    TQueryScorer: QueryScorer<TVector = [VectorElementType]>,
+    TElement: PrimitiveVectorElement + MmapEndianConvertible,
+    TQueryScorer: QueryScorer<TVector = [TElement]>,
 {
     fn score_points(&self, points: &[PointOffsetType], scores: &mut [ScoreType]) {
         assert_eq!(points.len(), scores.len());
@@ -75,17 +89,24 @@ where
     }
 }
 
-struct AsyncRawScorerBuilder<'a> {
+struct AsyncRawScorerBuilder<'a, TElement: PrimitiveVectorElement + MmapEndianConvertible> {
     query: QueryVector,
-    storage: &'a MemmapDenseVectorStorage<VectorElementType>,
+    storage: &'a MemmapDenseVectorStorage<TElement>,
     distance: Distance,
     hardware_counter: HardwareCounterCell,
 }
 
-impl<'a> AsyncRawScorerBuilder<'a> {
+impl<'a, TElement> AsyncRawScorerBuilder<'a, TElement>
+where
+    TElement: PrimitiveVectorElement + MmapEndianConvertible,
+    CosineMetric: Metric<TElement>,
+    EuclidMetric: Metric<TElement>,
+    DotProductMetric: Metric<TElement>,
+    ManhattanMetric: Metric<TElement>,
+{
     pub fn new(
         query: QueryVector,
-        storage: &'a MemmapDenseVectorStorage<VectorElementType>,
+        storage: &'a MemmapDenseVectorStorage<TElement>,
         hardware_counter: HardwareCounterCell,
     ) -> Self {
         Self {
@@ -105,7 +126,7 @@ impl<'a> AsyncRawScorerBuilder<'a> {
         }
     }
 
-    fn _build_with_metric<TMetric: Metric<VectorElementType> + 'a>(
+    fn _build_with_metric<TMetric: Metric<TElement> + 'a>(
         self,
     ) -> OperationResult<Box<dyn RawScorer + 'a>> {
         let Self {
@@ -187,12 +208,13 @@ impl<'a> AsyncRawScorerBuilder<'a> {
     }
 }
 
-fn async_raw_scorer_from_query_scorer<'a, TQueryScorer>(
+fn async_raw_scorer_from_query_scorer<'a, TElement, TQueryScorer>(
     query_scorer: TQueryScorer,
-    storage: &'a MemmapDenseVectorStorage<VectorElementType>,
+    storage: &'a MemmapDenseVectorStorage<TElement>,
 ) -> Box<dyn RawScorer + 'a>
 where
-    TQueryScorer: QueryScorer<TVector = [VectorElementType]> + 'a,
+    TElement: PrimitiveVectorElement + MmapEndianConvertible,
+    TQueryScorer: QueryScorer<TVector = [TElement]> + 'a,
 {
     Box::new(AsyncRawScorerImpl::new(
         query_scorer,
@@ -1,12 +1,15 @@
 use std::cmp::max;
-use std::io::BufReader;
+use std::collections::BTreeSet;
+use std::hash::Hasher as _;
+use std::io::{self, BufReader, Read as _, Write as _};
 use std::mem::MaybeUninit;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
 use common::counter::hardware_counter::HardwareCounterCell;
-use common::fs::{atomic_save_json, clear_disk_cache};
+use common::fs::{atomic_save, atomic_save_json, clear_disk_cache};
 use common::maybe_uninit::maybe_uninit_fill_from;
-use common::mmap::chunked::{chunk_name, create_chunk, read_mmaps};
+use common::mmap::chunked::{check_mmap_file_name_pattern, chunk_name, create_chunk, read_mmaps};
 use common::mmap::{
     Advice, AdviceSetting, MmapFlusher, MmapType, UniversalMmapChunk, create_and_ensure_length,
     open_write_mmap,
@@ -14,6 +17,8 @@ use common::mmap::{
 use fs_err as fs;
 use fs_err::File;
 use num_traits::AsPrimitive;
+use parking_lot::Mutex;
+use seahash::SeaHasher;
 use serde::{Deserialize, Serialize};
 
 use crate::common::Flusher;
@@ -26,12 +31,18 @@ use crate::vector_storage::{AccessPattern, VectorOffset, VectorOffsetType};
 const CONFIG_FILE_NAME: &str = "config.json";
 const STATUS_FILE_NAME: &str = "status.dat";
 const STATUS_MAGIC: [u8; 4] = *b"cmv1";
-const STATUS_VERSION: u32 = 1;
+pub(crate) const STATUS_VERSION: u32 = 1;
 const STATUS_FILE_SIZE: usize = 16;
 const STATUS_MAGIC_END: usize = 4;
 const STATUS_VERSION_OFFSET: usize = STATUS_MAGIC_END;
 const STATUS_LEN_OFFSET: usize = STATUS_VERSION_OFFSET + 4;
 const LEGACY_STATUS_FILE_SIZE: usize = std::mem::size_of::<usize>();
+const CHECKSUMS_FILE_NAME: &str = "checksums.dat";
+const CHECKSUMS_MAGIC: [u8; 4] = *b"cmc1";
+const CHECKSUMS_HEADER_SIZE: usize = 8;
+/// Flag whether the chunk has a checksum, and the checksum
+const CHECKSUM_ENTRY_SIZE: usize = 1 + size_of::<u64>();
+const CHECKSUM_READ_SIZE: usize = 1024 * 1024;
 
 #[derive(Debug)]
 struct StatusFile {
@@ -180,6 +191,232 @@ fn decode_legacy_status_len(legacy_raw: &[u8], max_vectors: usize) -> OperationR
     )))
 }
 
+/// Checksums of chunk files, catching corruption of chunks at rest
+///
+/// On flush, checksums of all chunks modified since the previous flush are computed from the
+/// flushed chunk files. Before a chunk with a persisted checksum is modified, its checksum is
+/// dropped from the persisted table, so that persisted checksums always describe the chunk files
+/// on disk, even after a crash. Chunk files are verified by the storage scrubber, see
+/// [`verify_chunk_file`].
+#[derive(Debug)]
+struct ChunkChecksums {
+    path: PathBuf,
+    directory: PathBuf,
+    state: Arc<Mutex<ChunkChecksumsState>>,
+}
+
+#[derive(Debug, Default)]
+struct ChunkChecksumsState {
+    /// Persisted checksum of each chunk, `None` for chunks modified since it was computed
+    persisted: Vec<Option<u64>>,
+    /// Chunks modified since the last flush
+    dirty: BTreeSet<usize>,
+}
+
+impl ChunkChecksums {
+    fn open(directory: &Path, chunks_count: usize) -> OperationResult<Self> {
+        let path = directory.join(CHECKSUMS_FILE_NAME);
+        let mut persisted = if path.exists() {
+            decode_checksums(&fs::read(&path)?)?
+        } else {
+            Vec::new()
+        };
+        persisted.resize(chunks_count, None);
+
+        // Chunks without a checksum, for example written by an older version, are checksummed
+        // on next flush
+        let dirty = persisted
+            .iter()
+            .enumerate()
+            .filter(|(_, checksum)| checksum.is_none())
+            .map(|(chunk_idx, _)| chunk_idx)
+            .collect();
+
+        Ok(Self {
+            path,
+            directory: directory.to_owned(),
+            state: Arc::new(Mutex::new(ChunkChecksumsState { persisted, dirty })),
+        })
+    }
+
+    /// Mark the chunk as modified. Must be called before the chunk is written to.
+    fn mark_dirty(&self, chunk_idx: usize) -> OperationResult<()> {
+        let mut state = self.state.lock();
+        if !state.dirty.insert(chunk_idx) {
+            return Ok(());
+        }
+        match state.persisted.get_mut(chunk_idx) {
+            Some(checksum) => {
+                if checksum.take().is_some() {
+                    store_checksums(&self.path, &state.persisted)?;
+                }
+            }
+            // New chunk, without a checksum until it is flushed
+            None => state.persisted.resize(chunk_idx + 1, None),
+        }
+        Ok(())
+    }
+
+    fn truncate(&self, chunks_count: usize) -> OperationResult<()> {
+        let mut state = self.state.lock();
+        state.dirty.retain(|&chunk_idx| chunk_idx < chunks_count);
+        if state.persisted.len() > chunks_count {
+            state.persisted.truncate(chunks_count);
+            store_checksums(&self.path, &state.persisted)?;
+        }
+        Ok(())
+    }
+
+    /// Returns a function computing checksums of modified chunks from their files, and persisting
+    /// them. It must be called once the chunks are flushed.
+    fn flusher(&self, chunks_count: usize) -> Flusher {
+        let dirty = std::mem::take(&mut self.state.lock().dirty);
+        if dirty.is_empty() {
+            return Box::new(|| Ok(()));
+        }
+
+        let path = self.path.clone();
+        let directory = self.directory.clone();
+        let state = Arc::downgrade(&self.state);
+        Box::new(move || {
+            let Some(state) = state.upgrade() else {
+                return Ok(());
+            };
+
+            let store = || {
+                let mut checksums = Vec::with_capacity(dirty.len());
+                for &chunk_idx in dirty.range(..chunks_count) {
+                    let chunk_path = chunk_name(&directory, chunk_idx);
+                    checksums.push((chunk_idx, chunk_file_checksum(&chunk_path, |_| {})?));
+                }
+
+                let mut state = state.lock();
+                for (chunk_idx, checksum) in checksums {
+                    // Chunk modified again while computing its checksum is checksummed on next
+                    // flush
+                    if state.dirty.contains(&chunk_idx) {
+                        continue;
+                    }
+                    if let Some(persisted) = state.persisted.get_mut(chunk_idx) {
+                        *persisted = Some(checksum);
+                    }
+                }
+                store_checksums(&path, &state.persisted)
+            };
+
+            store().inspect_err(|_| {
+                // Retry on next flush
+                state.lock().dirty.extend(dirty.iter().copied());
+            })
+        })
+    }
+}
+
+/// Verify a chunk file of chunked mmap vectors against its persisted checksum, without opening
+/// the storage.
+///
+/// `on_read` is called with the number of bytes read from the chunk file. Returns `None` for files
+/// which are not chunks of a storage with checksums, and for chunks modified since their checksum
+/// was computed. Used to scrub storage files.
+pub fn verify_chunk_file(path: &Path, on_read: impl FnMut(usize)) -> io::Result<Option<bool>> {
+    let chunk_idx = path
+        .file_name()
+        .and_then(|file_name| file_name.to_str())
+        .and_then(check_mmap_file_name_pattern);
+    let (Some(chunk_idx), Some(directory)) = (chunk_idx, path.parent()) else {
+        return Ok(None);
+    };
+    let checksums_path = directory.join(CHECKSUMS_FILE_NAME);
+    let persisted_checksum = || -> io::Result<Option<u64>> {
+        if !checksums_path.exists() {
+            return Ok(None);
+        }
+        let checksums = decode_checksums(&fs::read(&checksums_path)?)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err.to_string()))?;
+        Ok(checksums.get(chunk_idx).copied().flatten())
+    };
+
+    let Some(expected) = persisted_checksum()? else {
+        return Ok(None);
+    };
+    if chunk_file_checksum(path, on_read)? == expected {
+        return Ok(Some(true));
+    }
+    // Checksum is dropped before the chunk is modified, so it changes if the chunk was modified
+    // while reading
+    if persisted_checksum()? != Some(expected) {
+        return Ok(None);
+    }
+    Ok(Some(false))
+}
+
+/// Checksum of the bytes of a chunk file, as stored on disk
+///
+/// `on_read` is called with the number of bytes of each read.
+fn chunk_file_checksum(path: &Path, mut on_read: impl FnMut(usize)) -> io::Result<u64> {
+    let mut file = File::open(path)?;
+    let mut hasher = SeaHasher::new();
+    let mut buffer = Vec::with_capacity(CHECKSUM_READ_SIZE);
+    loop {
+        buffer.clear();
+        let read = (&mut file)
+            .take(CHECKSUM_READ_SIZE as u64)
+            .read_to_end(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        hasher.write(&buffer);
+        on_read(read);
+    }
+    Ok(hasher.finish())
+}
+
+fn store_checksums(path: &Path, checksums: &[Option<u64>]) -> OperationResult<()> {
+    let count = u32::try_from(checksums.len()).map_err(|_| {
+        OperationError::service_error(format!(
+            "Chunked mmap checksums count {} does not fit into u32",
+            checksums.len(),
+        ))
+    })?;
+    atomic_save(path, |writer| {
+        writer.write_all(&CHECKSUMS_MAGIC)?;
+        writer.write_all(&count.to_le_bytes())?;
+        for checksum in checksums {
+            writer.write_all(&[u8::from(checksum.is_some())])?;
+            writer.write_all(&checksum.unwrap_or_default().to_le_bytes())?;
+        }
+        Ok(())
+    })
+}
+
+fn decode_checksums(raw: &[u8]) -> OperationResult<Vec<Option<u64>>> {
+    if raw.len() < CHECKSUMS_HEADER_SIZE || raw[..CHECKSUMS_MAGIC.len()] != CHECKSUMS_MAGIC {
+        return Err(OperationError::service_error(
+            "Invalid chunked mmap checksums header".to_string(),
+        ));
+    }
+
+    let mut count_raw = [0u8; 4];
+    count_raw.copy_from_slice(&raw[CHECKSUMS_MAGIC.len()..CHECKSUMS_HEADER_SIZE]);
+    let count = u32::from_le_bytes(count_raw) as usize;
+
+    let table = &raw[CHECKSUMS_HEADER_SIZE..];
+    if table.len() != count * CHECKSUM_ENTRY_SIZE {
+        return Err(OperationError::service_error(format!(
+            "Invalid chunked mmap checksums size {}, expected {count} checksums",
+            table.len(),
+        )));
+    }
+
+    Ok(table
+        .chunks_exact(CHECKSUM_ENTRY_SIZE)
+        .map(|entry| {
+            let checksum = u64::from_le_bytes(entry[1..].try_into().expect("slice size checked"));
+            (entry[0] != 0).then_some(checksum)
+        })
+        .collect())
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 struct ChunkedMmapConfig {
     chunk_size_bytes: usize,
@@ -195,6 +432,7 @@ pub struct ChunkedMmapVectors<T: Sized + 'static> {
     status: StatusFile,
     chunks: Vec<UniversalMmapChunk<T>>,
     decoded_chunks: Option<Vec<Vec<T>>>,
+    checksums: ChunkChecksums,
     directory: PathBuf,
 }
 
@@ -281,6 +519,7 @@ impl<T: Sized + Copy + MmapEndianConvertible + 'static> ChunkedMmapVectors<T> {
                 )
             })?;
         let status = StatusFile::open(directory, max_vectors)?;
+        let checksums = ChunkChecksums::open(directory, chunks.len())?;
         let decoded_chunks = if cfg!(target_endian = "big") {
             Some(
                 chunks
@@ -296,6 +535,7 @@ impl<T: Sized + Copy + MmapEndianConvertible + 'static> ChunkedMmapVectors<T> {
             config,
             chunks,
             decoded_chunks,
+            checksums,
             directory: directory.to_owned(),
         };
         Ok(vectors)
@@ -352,6 +592,11 @@ impl<T: Sized + Copy + MmapEndianConvertible + 'static> ChunkedMmapVectors<T> {
         self.config.dim
     }
 
+    #[inline]
+    pub fn directory(&self) -> &Path {
+        &self.directory
+    }
+
     fn add_chunk(&mut self) -> OperationResult<()> {
         let chunk = create_chunk(
             &self.directory,
@@ -364,6 +609,7 @@ impl<T: Sized + Copy + MmapEndianConvertible + 'static> ChunkedMmapVectors<T> {
             None
         };
 
+        self.checksums.mark_dirty(self.chunks.len())?;
         self.chunks.push(chunk);
         if let (Some(decoded_chunks), Some(decoded_chunk)) =
             (&mut self.decoded_chunks, decoded_chunk)
@@ -412,6 +658,7 @@ impl<T: Sized + Copy + MmapEndianConvertible + 'static> ChunkedMmapVectors<T> {
             self.add_chunk()?;
         }
 
+        self.checksums.mark_dirty(chunk_idx)?;
         let chunk = &mut self.chunks[chunk_idx];
         let write_range = chunk_offset..chunk_offset + vectors.len();
         Self::encode_chunk_values(vectors, &mut chunk.as_mut_slice()[write_range.clone()]);
@@ -431,6 +678,34 @@ impl<T: Sized + Copy + MmapEndianConvertible + 'static> ChunkedMmapVectors<T> {
         Ok(())
     }
 
+    /// Drop all vectors from `len` onwards
+    ///
+    /// Chunks which are no longer used are removed from disk. Dropped vectors in the last
+    /// remaining chunk are left as-is, they are overwritten by subsequent inserts.
+    pub fn truncate(&mut self, len: usize) -> OperationResult<()> {
+        if len >= self.status.len() {
+            return Ok(());
+        }
+
+        // Persist new length first, so that it never exceeds the capacity of remaining chunks
+        self.status.set_len(len)?;
+        self.status.flusher()()?;
+
+        let chunks_count = len.div_ceil(self.config.chunk_size_vectors);
+        let old_chunks_count = self.chunks.len();
+        self.chunks.truncate(chunks_count);
+        if let Some(decoded_chunks) = &mut self.decoded_chunks {
+            decoded_chunks.truncate(chunks_count);
+        }
+        self.checksums.truncate(chunks_count)?;
+
+        // Remove last chunks first, so that remaining chunk files stay contiguous if interrupted
+        for chunk_idx in (chunks_count..old_chunks_count).rev() {
+            fs::remove_file(chunk_name(&self.directory, chunk_idx))?;
+        }
+        Ok(())
+    }
+
     // returns how many vectors can be inserted starting from key
     pub fn get_remaining_chunk_keys(&self, start_key: VectorOffsetType) -> usize {
         let start_key = start_key.as_();
@@ -505,10 +780,12 @@ impl<T: Sized + Copy + MmapEndianConvertible + 'static> ChunkedMmapVectors<T> {
         Box::new({
             let status_flusher = self.status.flusher();
             let chunks_flushers: Vec<_> = self.chunks.iter().map(|chunk| chunk.flusher()).collect();
+            let checksums_flusher = self.checksums.flusher(self.chunks.len());
             move || {
                 for flusher in chunks_flushers {
                     flusher()?;
                 }
+                checksums_flusher()?;
                 status_flusher()?;
                 Ok(())
             }
@@ -519,6 +796,9 @@ impl<T: Sized + Copy + MmapEndianConvertible + 'static> ChunkedMmapVectors<T> {
         let mut files = Vec::new();
         files.push(Self::config_file(&self.directory));
         files.push(Self::status_file(&self.directory));
+        if self.checksums.path.exists() {
+            files.push(self.checksums.path.clone());
+        }
         for chunk_idx in 0..self.chunks.len() {
             files.push(chunk_name(&self.directory, chunk_idx));
         }
@@ -571,6 +851,7 @@ mod tests {
     use super::*;
     use crate::data_types::vectors::VectorElementType;
     use crate::fixtures::index_fixtures::random_vector;
+    use crate::vector_storage::Random;
 
     #[test]
     fn test_chunked_mmap() {
@@ -639,6 +920,55 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_chunked_mmap_truncate() {
+        let dir = Builder::new().prefix("storage_dir").tempdir().unwrap();
+        let dim = 500;
+        let num_vectors = 1000;
+        let truncated_len = 300;
+        let mut rng = StdRng::seed_from_u64(42);
+
+        let hw_counter = HardwareCounterCell::new();
+
+        let vectors: Vec<_> = (0..num_vectors)
+            .map(|_| random_vector(&mut rng, dim))
+            .collect();
+
+        {
+            let mut chunked_mmap: ChunkedMmapVectors<VectorElementType> =
+                ChunkedMmapVectors::open(dir.path(), dim, AdviceSetting::Global, Some(true))
+                    .unwrap();
+            for vec in &vectors {
+                chunked_mmap.push(vec, &hw_counter).unwrap();
+            }
+            let chunks_count = chunked_mmap.chunks.len();
+
+            chunked_mmap.truncate(truncated_len).unwrap();
+            assert_eq!(chunked_mmap.len(), truncated_len);
+            assert!(chunked_mmap.chunks.len() < chunks_count);
+            assert_eq!(
+                chunked_mmap.chunks.len(),
+                truncated_len.div_ceil(chunked_mmap.config.chunk_size_vectors),
+            );
+            assert!(!chunk_name(dir.path(), chunks_count - 1).exists());
+
+            // Appending after truncation must not touch retained vectors
+            chunked_mmap.push(&vectors[0], &hw_counter).unwrap();
+            chunked_mmap.flusher()().unwrap();
+        }
+
+        let chunked_mmap: ChunkedMmapVectors<VectorElementType> =
+            ChunkedMmapVectors::open(dir.path(), dim, AdviceSetting::Global, Some(true)).unwrap();
+        assert_eq!(chunked_mmap.len(), truncated_len + 1);
+        for (key, vec) in vectors.iter().take(truncated_len).enumerate() {
+            assert_eq!(chunked_mmap.get::<Random>(key).unwrap(), vec.as_slice());
+        }
+        assert_eq!(
+            chunked_mmap.get::<Random>(truncated_len).unwrap(),
+            vectors[0].as_slice(),
+        );
+    }
+
     #[test]
     fn test_chunked_mmap_migrates_legacy_status_file() {
         let dir = Builder::new().prefix("storage_dir").tempdir().unwrap();
@@ -756,6 +1086,115 @@ mod tests {
         );
     }
 
+    /// Verify chunk files like the storage scrubber
+    fn verify_chunk(dir: &Path, chunk_idx: usize) -> Option<bool> {
+        verify_chunk_file(&chunk_name(dir, chunk_idx), |_| {}).unwrap()
+    }
+
+    #[test]
+    fn test_chunked_mmap_checksums() {
+        let dir = Builder::new().prefix("storage_dir").tempdir().unwrap();
+        let dim = 500;
+        let num_vectors = 1000;
+        let mut rng = StdRng::seed_from_u64(42);
+
+        let hw_counter = HardwareCounterCell::new();
+
+        let chunks_count = {
+            let mut chunked_mmap: ChunkedMmapVectors<VectorElementType> =
+                ChunkedMmapVectors::open(dir.path(), dim, AdviceSetting::Global, Some(true))
+                    .unwrap();
+            for _ in 0..num_vectors {
+                chunked_mmap
+                    .push(&random_vector(&mut rng, dim), &hw_counter)
+                    .unwrap();
+            }
+            chunked_mmap.flusher()().unwrap();
+            chunked_mmap.chunks.len()
+        };
+        assert!(chunks_count > 2, "must have multiple chunks to test");
+        for chunk_idx in 0..chunks_count {
+            assert_eq!(verify_chunk(dir.path(), chunk_idx), Some(true));
+        }
+
+        {
+            let mut chunked_mmap: ChunkedMmapVectors<VectorElementType> =
+                ChunkedMmapVectors::open(dir.path(), dim, AdviceSetting::Global, Some(true))
+                    .unwrap();
+
+            // Checksum is dropped before the chunk is modified, so that updates not covered by
+            // a flush before a crash are not reported as corruption
+            chunked_mmap
+                .insert(0, &random_vector(&mut rng, dim), &hw_counter)
+                .unwrap();
+            assert_eq!(verify_chunk(dir.path(), 0), None);
+            assert_eq!(verify_chunk(dir.path(), 1), Some(true));
+
+            // Modified chunks are checksummed again on flush
+            chunked_mmap.flusher()().unwrap();
+            assert_eq!(verify_chunk(dir.path(), 0), Some(true));
+        }
+
+        // Corruption at rest is detected
+        let chunk_path = chunk_name(dir.path(), chunks_count - 1);
+        let mut bytes = fs::read(&chunk_path).unwrap();
+        bytes[0] ^= 0xFF;
+        fs::write(&chunk_path, bytes).unwrap();
+        assert_eq!(verify_chunk(dir.path(), chunks_count - 1), Some(false));
+        assert_eq!(verify_chunk(dir.path(), 0), Some(true));
+
+        // Other files are not verified
+        let status_path = ChunkedMmapVectors::<VectorElementType>::status_file(dir.path());
+        assert_eq!(verify_chunk_file(&status_path, |_| {}).unwrap(), None);
+    }
+
+    #[test]
+    fn test_chunked_mmap_checksums_missing() {
+        let dir = Builder::new().prefix("storage_dir").tempdir().unwrap();
+        let dim = 32;
+        let vector_count = 17;
+        let hw_counter = HardwareCounterCell::new();
+
+        {
+            let mut storage: ChunkedMmapVectors<VectorElementType> =
+                ChunkedMmapVectors::open(dir.path(), dim, AdviceSetting::Global, Some(true))
+                    .unwrap();
+            for i in 0..vector_count {
+                let vector = vec![i as VectorElementType; dim];
+                storage.push(&vector, &hw_counter).unwrap();
+            }
+            storage.flusher()().unwrap();
+        }
+
+        // Simulate storage written by a version without checksums
+        let checksums_path = dir.path().join(CHECKSUMS_FILE_NAME);
+        let status_path = ChunkedMmapVectors::<VectorElementType>::status_file(dir.path());
+        fs::remove_file(&checksums_path).unwrap();
+        let status = fs::read(&status_path).unwrap();
+
+        {
+            let storage: ChunkedMmapVectors<VectorElementType> =
+                ChunkedMmapVectors::open(dir.path(), dim, AdviceSetting::Global, Some(true))
+                    .unwrap();
+            // Nothing is written on open
+            assert!(!checksums_path.exists());
+            assert!(!storage.files().contains(&checksums_path));
+            assert_eq!(storage.len(), vector_count);
+            assert_eq!(verify_chunk(dir.path(), 0), None);
+            storage.flusher()().unwrap();
+        }
+
+        // Status stays readable by versions without checksums
+        assert_eq!(fs::read(&status_path).unwrap(), status);
+        assert_eq!(
+            decode_checksums(&fs::read(&checksums_path).unwrap())
+                .unwrap()
+                .len(),
+            1,
+        );
+        assert_eq!(verify_chunk(dir.path(), 0), Some(true));
+    }
+
     #[test]
     fn test_chunked_mmap_rejects_unsupported_status_version() {
         let dir = Builder::new().prefix("storage_dir").tempdir().unwrap();
@@ -1,4 +1,4 @@
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 
 #[cfg(feature = "rocksdb")]
 use serde::{Deserialize, Serialize};
@@ -13,6 +13,20 @@ pub fn get_async_scorer() -> bool {
     ASYNC_SCORER.load(Ordering::Relaxed)
 }
 
+/// Default fraction of tiered vector storages kept in RAM
+pub const DEFAULT_HOT_TIER_RATIO: f64 = 0.1;
+
+/// Bits of the `f64` fraction of tiered vector storages kept in RAM
+static HOT_TIER_RATIO: AtomicU64 = AtomicU64::new(DEFAULT_HOT_TIER_RATIO.to_bits());
+
+pub fn set_hot_tier_ratio(hot_tier_ratio: f64) {
+    HOT_TIER_RATIO.store(hot_tier_ratio.to_bits(), Ordering::Relaxed);
+}
+
+pub fn get_hot_tier_ratio() -> f64 {
+    f64::from_bits(HOT_TIER_RATIO.load(Ordering::Relaxed))
+}
+
 /// Storage type for RocksDB based storage
 #[derive(Debug, Deserialize, Serialize, Clone)]
 #[cfg(feature = "rocksdb")]
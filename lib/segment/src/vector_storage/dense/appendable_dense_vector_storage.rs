@@ -5,6 +5,7 @@ use std::sync::atomic::AtomicBool;
 
 use bitvec::prelude::BitSlice;
 use common::counter::hardware_counter::HardwareCounterCell;
+use common::io_throttle;
 use common::mmap::AdviceSetting;
 use common::types::PointOffsetType;
 use fs_err as fs;
@@ -12,7 +13,7 @@ use fs_err as fs;
 use crate::common::Flusher;
 use crate::common::flags::bitvec_flags::BitvecFlags;
 use crate::common::flags::dynamic_mmap_flags::DynamicMmapFlags;
-use crate::common::operation_error::{OperationResult, check_process_stopped};
+use crate::common::operation_error::{OperationError, OperationResult, check_process_stopped};
 use crate::data_types::named_vectors::CowVector;
 use crate::data_types::primitive::PrimitiveVectorElement;
 use crate::data_types::vectors::{VectorElementType, VectorRef};
@@ -20,7 +21,7 @@ use crate::types::{Distance, VectorStorageDatatype};
 use crate::vector_storage::chunked_mmap_vectors::ChunkedMmapVectors;
 use crate::vector_storage::mmap_endian::MmapEndianConvertible;
 use crate::vector_storage::{
-    AccessPattern, DenseVectorStorage, VectorOffsetType, VectorStorage, VectorStorageEnum,
+    AccessPattern, DenseVectorStorage, Random, VectorOffsetType, VectorStorage, VectorStorageEnum,
 };
 
 const VECTORS_DIR_PATH: &str = "vectors";
@@ -74,6 +75,47 @@ impl<T: PrimitiveVectorElement + MmapEndianConvertible> AppendableMmapDenseVecto
         self.vectors.clear_cache()?;
         Ok(())
     }
+
+    /// Copy vectors along with their deleted flags, from the first to the second offset of each
+    /// move. Source vectors are left as-is.
+    ///
+    /// Copied bytes are accounted with the global IO throttle.
+    pub fn move_vectors(
+        &mut self,
+        moves: &[(PointOffsetType, PointOffsetType)],
+    ) -> OperationResult<()> {
+        let hw_counter = HardwareCounterCell::disposable(); // Internal operation
+        for &(from, to) in moves {
+            let vector = self
+                .vectors
+                .get::<Random>(from as VectorOffsetType)
+                .ok_or_else(|| OperationError::service_error(format!("Vector {from} not found")))?
+                .to_vec();
+            io_throttle::consume(size_of_val(vector.as_slice()));
+            self.vectors
+                .insert(to as VectorOffsetType, &vector, &hw_counter)?;
+            let deleted = self.deleted.get(from);
+            self.set_deleted(to, deleted);
+        }
+        Ok(())
+    }
+
+    /// Drop all vectors from `len` onwards, removing chunks which are no longer used
+    pub fn truncate(&mut self, len: usize) -> OperationResult<()> {
+        self.vectors.truncate(len)?;
+
+        // Reset flags of dropped vectors, so they don't apply to vectors appended later
+        let dropped_deleted: Vec<_> = self
+            .deleted
+            .iter_trues()
+            .filter(|&key| key as usize >= len)
+            .collect();
+        for key in dropped_deleted {
+            self.deleted.set(key, false);
+        }
+        self.deleted_count = self.deleted.count_trues();
+        Ok(())
+    }
 }
 
 impl<T: PrimitiveVectorElement + MmapEndianConvertible> DenseVectorStorage<T>
@@ -83,10 +125,12 @@ impl<T: PrimitiveVectorElement + MmapEndianConvertible> DenseVectorStorage<T>
         self.vectors.dim()
     }
 
-    fn get_dense<P: AccessPattern>(&self, key: PointOffsetType) -> &[T] {
-        self.vectors
-            .get::<P>(key as VectorOffsetType)
-            .expect("mmap vector not found")
+    fn get_dense<P: AccessPattern>(&self, key: PointOffsetType) -> Cow<'_, [T]> {
+        Cow::Borrowed(
+            self.vectors
+                .get::<P>(key as VectorOffsetType)
+                .expect("mmap vector not found"),
+        )
     }
 
     fn for_each_in_dense_batch<F: FnMut(usize, &[T])>(&self, keys: &[PointOffsetType], f: F) {
@@ -241,6 +285,21 @@ pub fn open_appendable_memmap_vector_storage_half(
     )))
 }
 
+pub fn open_appendable_memmap_vector_storage_bf16(
+    path: &Path,
+    dim: usize,
+    distance: Distance,
+    madvise: AdviceSetting,
+    populate: bool,
+) -> OperationResult<VectorStorageEnum> {
+    let storage =
+        open_appendable_memmap_vector_storage_impl(path, dim, distance, madvise, populate)?;
+
+    Ok(VectorStorageEnum::DenseAppendableMemmapBf16(Box::new(
+        storage,
+    )))
+}
+
 pub fn open_appendable_memmap_vector_storage_impl<
     T: PrimitiveVectorElement + MmapEndianConvertible,
 >(
@@ -0,0 +1,409 @@
+//! zstd-compressed cold tier for immutable dense vectors.
+//!
+//! Vectors are compressed in blocks of [`BLOCK_VECTORS`] vectors, and are decompressed on
+//! demand. Blocks which are read often are kept decompressed in a cache of bounded size, blocks
+//! which are rarely read (for example for rescoring or to return them with points) are
+//! decompressed for each read.
+//!
+//! Layout:
+//!
+//! ```text
+//! | "zvec" | dim: u32 | block vectors: u32 | reserved: u32 | num vectors: u64 |
+//! | block offsets: u64 * (num blocks + 1) |
+//! | zstd frame of block 0 | zstd frame of block 1 | ... |
+//! ```
+//!
+//! Vector values are stored as little-endian, like in the uncompressed file.
+
+use std::collections::HashMap;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU32, Ordering};
+
+use common::mmap::encryption::EncryptingWriter;
+use common::types::PointOffsetType;
+use fs_err as fs;
+use fs_err::File;
+use memmap2::Mmap;
+use parking_lot::Mutex;
+use zerocopy::IntoBytes as _;
+
+use crate::common::operation_error::{OperationError, OperationResult};
+use crate::data_types::primitive::PrimitiveVectorElement;
+use crate::vector_storage::mmap_endian::MmapEndianConvertible;
+
+pub const COMPRESSED_VECTORS_HEADER: &[u8; 4] = b"zvec";
+const HEADER_SIZE: usize = 24;
+/// Number of vectors compressed together
+const BLOCK_VECTORS: usize = 256;
+const ZSTD_LEVEL: i32 = 3;
+/// Number of on-demand reads of a block, after which it is kept decompressed
+const PROMOTE_AFTER_READS: u32 = 8;
+/// Size of decompressed blocks kept in RAM, per vectors file
+const CACHE_BYTES: usize = 64 * 1024 * 1024;
+
+/// Dense vectors, compressed with zstd in blocks.
+#[derive(Debug)]
+pub struct CompressedDenseVectors<T: PrimitiveVectorElement + MmapEndianConvertible> {
+    dim: usize,
+    num_vectors: usize,
+    block_vectors: usize,
+    /// Memory mapped compressed file
+    mmap: Arc<Mmap>,
+    /// Offsets of the compressed blocks in the file, with the end of the last block
+    block_offsets: Vec<u64>,
+    /// Blocks which are kept decompressed, in native byte order
+    cache: Mutex<HashMap<usize, Arc<[T]>>>,
+    /// Maximal number of blocks in the cache, at least one
+    cache_capacity: usize,
+    /// Number of reads of each block, halved when the block is evicted from the cache
+    block_reads: Vec<AtomicU32>,
+}
+
+impl<T: PrimitiveVectorElement + MmapEndianConvertible> CompressedDenseVectors<T> {
+    pub fn is_compressed(data: &[u8]) -> bool {
+        data.starts_with(COMPRESSED_VECTORS_HEADER)
+    }
+
+    /// Compress the uncompressed vectors `payload`, and replace the file at `path` with it.
+    ///
+    /// The payload holds the little-endian values of all vectors, without header.
+    pub fn compress(path: &Path, payload: &[u8], dim: usize) -> OperationResult<()> {
+        let vector_bytes = dim * size_of::<T>();
+        if vector_bytes == 0 || !payload.len().is_multiple_of(vector_bytes) {
+            return Err(OperationError::service_error(format!(
+                "Can't compress vectors of {} bytes with dimension {dim}",
+                payload.len(),
+            )));
+        }
+        let num_vectors = payload.len() / vector_bytes;
+        let block_bytes = BLOCK_VECTORS * vector_bytes;
+
+        let compressed_blocks = payload
+            .chunks(block_bytes)
+            .map(|block| zstd::bulk::compress(block, ZSTD_LEVEL))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut header = [0; HEADER_SIZE];
+        header[..4].copy_from_slice(COMPRESSED_VECTORS_HEADER);
+        header[4..8].copy_from_slice(&(dim as u32).to_le_bytes());
+        header[8..12].copy_from_slice(&(BLOCK_VECTORS as u32).to_le_bytes());
+        header[16..24].copy_from_slice(&(num_vectors as u64).to_le_bytes());
+
+        let tmp_path = path.with_extension("compressing");
+        // Encrypted while written, if enabled
+        let mut writer = EncryptingWriter::new(BufWriter::new(File::create(&tmp_path)?))?;
+        writer.write_all(&header)?;
+        let mut offset = (HEADER_SIZE + (compressed_blocks.len() + 1) * size_of::<u64>()) as u64;
+        for block in &compressed_blocks {
+            writer.write_all(&offset.to_le_bytes())?;
+            offset += block.len() as u64;
+        }
+        writer.write_all(&offset.to_le_bytes())?;
+        for block in &compressed_blocks {
+            writer.write_all(block)?;
+        }
+
+        let file = writer
+            .finish()?
+            .into_inner()
+            .map_err(std::io::IntoInnerError::into_error)?;
+        file.sync_all()?;
+        drop(file);
+        fs::rename(&tmp_path, path)?;
+        Ok(())
+    }
+
+    /// Open compressed vectors from the memory mapped file at `path`.
+    pub fn open(path: &Path, mmap: Arc<Mmap>, dim: usize) -> OperationResult<Self> {
+        Self::open_with_cache_size(path, mmap, dim, CACHE_BYTES)
+    }
+
+    fn open_with_cache_size(
+        path: &Path,
+        mmap: Arc<Mmap>,
+        dim: usize,
+        cache_bytes: usize,
+    ) -> OperationResult<Self> {
+        let invalid = |msg: &str| {
+            OperationError::service_error(format!(
+                "Invalid compressed vectors file {}: {msg}",
+                path.display(),
+            ))
+        };
+
+        if mmap.len() < HEADER_SIZE || !Self::is_compressed(&mmap) {
+            return Err(invalid("bad header"));
+        }
+        let read_u32 = |pos: usize| u32::from_le_bytes(mmap[pos..pos + 4].try_into().unwrap());
+        let read_u64 = |pos: usize| u64::from_le_bytes(mmap[pos..pos + 8].try_into().unwrap());
+
+        if read_u32(4) as usize != dim {
+            return Err(invalid("dimension mismatch"));
+        }
+        let block_vectors = read_u32(8) as usize;
+        let num_vectors = read_u64(16) as usize;
+        if block_vectors == 0 {
+            return Err(invalid("empty blocks"));
+        }
+
+        let num_blocks = num_vectors.div_ceil(block_vectors);
+        let offsets_end = HEADER_SIZE + (num_blocks + 1) * size_of::<u64>();
+        if mmap.len() < offsets_end {
+            return Err(invalid("truncated block offsets"));
+        }
+        let block_offsets: Vec<u64> = (0..=num_blocks)
+            .map(|block| read_u64(HEADER_SIZE + block * size_of::<u64>()))
+            .collect();
+        let offsets_valid = block_offsets.first() == Some(&(offsets_end as u64))
+            && block_offsets.is_sorted()
+            && block_offsets.last() == Some(&(mmap.len() as u64));
+        if !offsets_valid {
+            return Err(invalid("bad block offsets"));
+        }
+
+        let block_bytes = block_vectors * dim * size_of::<T>();
+        Ok(Self {
+            dim,
+            num_vectors,
+            block_vectors,
+            mmap,
+            block_offsets,
+            cache: Mutex::new(HashMap::new()),
+            cache_capacity: (cache_bytes / block_bytes.max(1)).max(1),
+            block_reads: (0..num_blocks).map(|_| AtomicU32::new(0)).collect(),
+        })
+    }
+
+    pub fn num_vectors(&self) -> usize {
+        self.num_vectors
+    }
+
+    /// Get vector, `None` if there is no vector with this key.
+    ///
+    /// Blocks are only kept decompressed once they are read often, see [`Self::cache_block`].
+    pub fn get(&self, key: PointOffsetType) -> OperationResult<Option<Vec<T>>> {
+        let Some((block, index)) = self.locate(key) else {
+            return Ok(None);
+        };
+        let reads = self.block_reads[block]
+            .fetch_add(1, Ordering::Relaxed)
+            .saturating_add(1);
+
+        let cached = self.cache.lock().get(&block).cloned();
+        let vectors = match cached {
+            Some(vectors) => vectors,
+            None => {
+                let vectors = self.decompress_block(block)?;
+                if reads >= PROMOTE_AFTER_READS {
+                    self.cache_block(block, reads, vectors.clone());
+                }
+                vectors
+            }
+        };
+        Ok(Some(self.vector_in_block(&vectors, index).to_vec()))
+    }
+
+    /// Keep a decompressed block in the cache.
+    ///
+    /// If the cache is full, the least read block is evicted, unless it was read more often than
+    /// the new one. Reads of the evicted block are halved, so that blocks which were read often a
+    /// long time ago don't stay in the cache forever.
+    fn cache_block(&self, block: usize, reads: u32, vectors: Arc<[T]>) {
+        let mut cache = self.cache.lock();
+        if cache.contains_key(&block) {
+            return;
+        }
+        if cache.len() >= self.cache_capacity {
+            let victim = cache
+                .keys()
+                .map(|&cached| (self.block_reads[cached].load(Ordering::Relaxed), cached))
+                .min();
+            let Some((victim_reads, victim)) = victim else {
+                return;
+            };
+            if victim_reads > reads {
+                return;
+            }
+            cache.remove(&victim);
+            self.block_reads[victim].store(victim_reads / 2, Ordering::Relaxed);
+        }
+        cache.insert(block, vectors);
+    }
+
+    /// Number of bytes of blocks kept decompressed in RAM.
+    pub fn decompressed_bytes(&self) -> usize {
+        self.cache
+            .lock()
+            .values()
+            .map(|vectors| vectors.len() * size_of::<T>())
+            .sum()
+    }
+
+    fn locate(&self, key: PointOffsetType) -> Option<(usize, usize)> {
+        let key = key as usize;
+        (key < self.num_vectors).then_some((key / self.block_vectors, key % self.block_vectors))
+    }
+
+    fn vector_in_block<'a>(&self, vectors: &'a [T], index: usize) -> &'a [T] {
+        &vectors[index * self.dim..(index + 1) * self.dim]
+    }
+
+    fn decompress_block(&self, block: usize) -> OperationResult<Arc<[T]>> {
+        let start = self.block_offsets[block] as usize;
+        let end = self.block_offsets[block + 1] as usize;
+        let block_len = self
+            .block_vectors
+            .min(self.num_vectors - block * self.block_vectors);
+
+        let mut vectors = vec![T::default(); block_len * self.dim];
+        let decompressed =
+            zstd::bulk::decompress_to_buffer(&self.mmap[start..end], vectors.as_mut_bytes())
+                .map_err(|err| {
+                    OperationError::service_error(format!(
+                        "Failed to decompress block {block} of vectors: {err}",
+                    ))
+                })?;
+        if decompressed != vectors.as_bytes().len() {
+            return Err(OperationError::service_error(format!(
+                "Decompressed block {block} of vectors has {decompressed} bytes, expected {}",
+                vectors.as_bytes().len(),
+            )));
+        }
+
+        if cfg!(target_endian = "big") {
+            for value in vectors.iter_mut() {
+                *value = T::from_le_storage(*value);
+            }
+        }
+        Ok(vectors.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use common::mmap::{self, AdviceSetting};
+    use tempfile::Builder;
+
+    use super::*;
+    use crate::data_types::vectors::VectorElementType;
+
+    #[test]
+    fn test_compressed_vectors_roundtrip() {
+        let dir = Builder::new().prefix("compressed").tempdir().unwrap();
+        let path = dir.path().join("matrix.dat");
+
+        let dim = 4;
+        let num_vectors = BLOCK_VECTORS * 2 + 17;
+        let vectors: Vec<VectorElementType> =
+            (0..num_vectors * dim).map(|i| (i % 97) as f32).collect();
+        let payload: Vec<u8> = vectors.iter().flat_map(|v| v.to_le_bytes()).collect();
+
+        CompressedDenseVectors::<VectorElementType>::compress(&path, &payload, dim).unwrap();
+        assert!(fs::metadata(&path).unwrap().len() < payload.len() as u64);
+
+        let mmap = mmap::open_read_mmap(&path, AdviceSetting::Global, false).unwrap();
+        let compressed =
+            CompressedDenseVectors::<VectorElementType>::open(&path, Arc::new(mmap), dim).unwrap();
+        assert_eq!(compressed.num_vectors(), num_vectors);
+
+        // Rare reads don't keep blocks decompressed
+        let last = num_vectors as PointOffsetType - 1;
+        let vector = compressed.get(last).unwrap().unwrap();
+        assert_eq!(&vector[..], &vectors[(num_vectors - 1) * dim..]);
+        assert_eq!(compressed.decompressed_bytes(), 0);
+
+        // Frequently read blocks are promoted
+        for _ in 0..PROMOTE_AFTER_READS {
+            compressed.get(last).unwrap().unwrap();
+        }
+        assert_eq!(compressed.decompressed_bytes(), 17 * dim * size_of::<f32>());
+
+        for key in [0, 1, BLOCK_VECTORS, BLOCK_VECTORS * 2 + 3] {
+            assert_eq!(
+                compressed.get(key as PointOffsetType).unwrap().unwrap(),
+                &vectors[key * dim..(key + 1) * dim],
+            );
+        }
+        assert!(
+            compressed
+                .get(num_vectors as PointOffsetType)
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_compressed_vectors_cache_eviction() {
+        let dir = Builder::new().prefix("compressed").tempdir().unwrap();
+        let path = dir.path().join("matrix.dat");
+
+        let dim = 4;
+        let num_vectors = BLOCK_VECTORS * 3;
+        let vectors: Vec<VectorElementType> = (0..num_vectors * dim).map(|i| i as f32).collect();
+        let payload: Vec<u8> = vectors.iter().flat_map(|v| v.to_le_bytes()).collect();
+        CompressedDenseVectors::<VectorElementType>::compress(&path, &payload, dim).unwrap();
+
+        // Room for a single block
+        let block_bytes = BLOCK_VECTORS * dim * size_of::<f32>();
+        let mmap = mmap::open_read_mmap(&path, AdviceSetting::Global, false).unwrap();
+        let compressed = CompressedDenseVectors::<VectorElementType>::open_with_cache_size(
+            &path,
+            Arc::new(mmap),
+            dim,
+            block_bytes,
+        )
+        .unwrap();
+        let read_block = |block: usize, times: u32| {
+            let key = (block * BLOCK_VECTORS) as PointOffsetType;
+            for _ in 0..times {
+                let vector = compressed.get(key).unwrap().unwrap();
+                assert_eq!(
+                    vector,
+                    &vectors[key as usize * dim..(key as usize + 1) * dim]
+                );
+            }
+        };
+
+        read_block(0, PROMOTE_AFTER_READS * 2);
+        assert!(compressed.cache.lock().contains_key(&0));
+
+        // Less frequently read block doesn't evict the cached one
+        read_block(1, PROMOTE_AFTER_READS);
+        assert!(compressed.cache.lock().contains_key(&0));
+        assert_eq!(compressed.decompressed_bytes(), block_bytes);
+
+        // More frequently read block does
+        read_block(2, PROMOTE_AFTER_READS * 3);
+        assert!(compressed.cache.lock().contains_key(&2));
+        assert_eq!(compressed.decompressed_bytes(), block_bytes);
+    }
+
+    #[test]
+    fn test_compressed_vectors_corrupted_block() {
+        let dir = Builder::new().prefix("compressed").tempdir().unwrap();
+        let path = dir.path().join("matrix.dat");
+
+        let dim = 4;
+        let payload = vec![0; BLOCK_VECTORS * 2 * dim * size_of::<f32>()];
+        CompressedDenseVectors::<VectorElementType>::compress(&path, &payload, dim).unwrap();
+
+        // Clobber the zstd frame of the first block
+        let mut data = fs::read(&path).unwrap();
+        let first_block = HEADER_SIZE + 3 * size_of::<u64>();
+        data[first_block..first_block + 4].fill(0xFF);
+        fs::write(&path, data).unwrap();
+
+        let mmap = mmap::open_read_mmap(&path, AdviceSetting::Global, false).unwrap();
+        let compressed =
+            CompressedDenseVectors::<VectorElementType>::open(&path, Arc::new(mmap), dim).unwrap();
+        assert!(compressed.get(0).is_err());
+        assert!(
+            compressed
+                .get(BLOCK_VECTORS as PointOffsetType)
+                .unwrap()
+                .is_some()
+        );
+    }
+}
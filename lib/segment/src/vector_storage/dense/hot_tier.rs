@@ -0,0 +1,224 @@
+//! Hot tier of tiered mmap vector storages.
+//!
+//! All vectors of a tiered storage stay in the mmap file. Regions of the file which were accessed
+//! recently are locked in RAM with `mlock`, so that they are never evicted from the page cache,
+//! while the rest is left to the page cache like in a plain mmap storage.
+//!
+//! Accesses are counted per region and decay on each rebalance, so that the hot tier follows the
+//! recent access pattern instead of the total one. Rebalancing runs in a background thread, so
+//! that searches never wait for `mlock`.
+
+use std::ops::Deref;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicUsize, Ordering};
+use std::thread;
+
+use common::mmap::advice;
+use parking_lot::Mutex;
+
+/// Size of regions which are promoted to, and evicted from the hot tier as a whole
+///
+/// Multiple of the page size, so that regions never share pages.
+const REGION_BYTES: usize = 256 * 1024;
+/// Number of accesses between rebalances of the hot tier
+const REBALANCE_INTERVAL: usize = 64 * 1024;
+
+#[derive(Debug)]
+pub struct HotTier {
+    /// Maximum number of regions in the hot tier
+    capacity: usize,
+    /// Recent accesses of each region
+    accesses: Vec<AtomicU32>,
+    /// Regions currently locked in RAM
+    hot: Mutex<Vec<bool>>,
+    /// Accesses since the last rebalance
+    since_rebalance: AtomicUsize,
+    /// Set while a rebalance is running in the background
+    rebalancing: AtomicBool,
+    /// Set if locking in RAM failed, regions are not promoted anymore
+    lock_failed: AtomicBool,
+}
+
+impl HotTier {
+    /// Hot tier for a mapping of `data_len` bytes, keeping up to `hot_ratio` of it in RAM.
+    pub fn new(data_len: usize, hot_ratio: f64) -> Self {
+        let num_regions = data_len.div_ceil(REGION_BYTES);
+        let capacity = (num_regions as f64 * hot_ratio.clamp(0.0, 1.0)).ceil() as usize;
+        Self {
+            capacity,
+            accesses: (0..num_regions).map(|_| AtomicU32::new(0)).collect(),
+            hot: Mutex::new(vec![false; num_regions]),
+            since_rebalance: AtomicUsize::new(0),
+            rebalancing: AtomicBool::new(false),
+            lock_failed: AtomicBool::new(false),
+        }
+    }
+
+    /// Record an access at byte `offset` of the mapping `data`, rebalance the hot tier in the
+    /// background every [`REBALANCE_INTERVAL`] accesses.
+    #[inline]
+    pub fn record_access<D>(self: &Arc<Self>, offset: usize, data: &Arc<D>)
+    where
+        D: Deref<Target = [u8]> + Send + Sync + 'static,
+    {
+        if let Some(accesses) = self.accesses.get(offset / REGION_BYTES) {
+            accesses.fetch_add(1, Ordering::Relaxed);
+        }
+
+        let since_rebalance = self.since_rebalance.fetch_add(1, Ordering::Relaxed) + 1;
+        if since_rebalance >= REBALANCE_INTERVAL {
+            self.since_rebalance.store(0, Ordering::Relaxed);
+            self.rebalance_in_background(data);
+        }
+    }
+
+    /// Start [`Self::rebalance`] in a background thread.
+    ///
+    /// Skipped if the previous rebalance is still running.
+    fn rebalance_in_background<D>(self: &Arc<Self>, data: &Arc<D>)
+    where
+        D: Deref<Target = [u8]> + Send + Sync + 'static,
+    {
+        if self.rebalancing.swap(true, Ordering::AcqRel) {
+            return;
+        }
+
+        let tier = Arc::clone(self);
+        let data = Arc::clone(data);
+        let spawned = thread::Builder::new()
+            .name("hot-tier-rebalance".to_string())
+            .spawn(move || {
+                tier.rebalance(&data);
+                tier.rebalancing.store(false, Ordering::Release);
+            });
+        if let Err(err) = spawned {
+            log::warn!("Failed to start rebalancing of the vectors hot tier: {err}");
+            self.rebalancing.store(false, Ordering::Release);
+        }
+    }
+
+    /// Promote the most accessed regions of `data` to the hot tier, and evict the others.
+    ///
+    /// Skipped if another thread is rebalancing already.
+    pub fn rebalance(&self, data: &[u8]) {
+        let Some(mut hot) = self.hot.try_lock() else {
+            return;
+        };
+        let selected = self.select_hot_regions();
+
+        for (region, (is_hot, is_selected)) in hot.iter_mut().zip(&selected).enumerate() {
+            if *is_hot && !is_selected {
+                if let Err(err) = advice::unlock_in_ram(region_slice(data, region)) {
+                    log::warn!("Failed to evict region {region} of vectors from RAM: {err}");
+                }
+                *is_hot = false;
+            }
+        }
+
+        for (region, (is_hot, is_selected)) in hot.iter_mut().zip(&selected).enumerate() {
+            if *is_hot || !is_selected || self.lock_failed.load(Ordering::Relaxed) {
+                continue;
+            }
+            match advice::lock_in_ram(region_slice(data, region)) {
+                Ok(()) => *is_hot = true,
+                Err(err) => {
+                    log::warn!("Failed to promote vectors to RAM, hot tier is disabled: {err}");
+                    self.lock_failed.store(true, Ordering::Relaxed);
+                }
+            }
+        }
+
+        // Decay, so that old accesses matter less than recent ones
+        for accesses in &self.accesses {
+            accesses.store(accesses.load(Ordering::Relaxed) / 2, Ordering::Relaxed);
+        }
+    }
+
+    /// Number of bytes locked in RAM.
+    pub fn hot_bytes(&self) -> usize {
+        self.hot.lock().iter().filter(|is_hot| **is_hot).count() * REGION_BYTES
+    }
+
+    /// Select up to `capacity` of the most accessed regions, ignoring regions without accesses.
+    fn select_hot_regions(&self) -> Vec<bool> {
+        let mut candidates: Vec<(usize, u32)> = self
+            .accesses
+            .iter()
+            .map(|accesses| accesses.load(Ordering::Relaxed))
+            .enumerate()
+            .filter(|(_, accesses)| *accesses > 0)
+            .collect();
+        candidates
+            .sort_unstable_by(|(a_region, a), (b_region, b)| b.cmp(a).then(a_region.cmp(b_region)));
+
+        let mut selected = vec![false; self.accesses.len()];
+        for (region, _) in candidates.into_iter().take(self.capacity) {
+            selected[region] = true;
+        }
+        selected
+    }
+}
+
+fn region_slice(data: &[u8], region: usize) -> &[u8] {
+    let start = (region * REGION_BYTES).min(data.len());
+    let end = (start + REGION_BYTES).min(data.len());
+    &data[start..end]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hot_tier_follows_recent_accesses() {
+        let tier = HotTier::new(REGION_BYTES * 8, 0.25);
+        assert_eq!(tier.capacity, 2);
+
+        let access = |region: usize, count: u32| {
+            tier.accesses[region].fetch_add(count, Ordering::Relaxed);
+        };
+        let selected = |tier: &HotTier| {
+            let selected = tier.select_hot_regions();
+            (0..selected.len())
+                .filter(|region| selected[*region])
+                .collect::<Vec<_>>()
+        };
+
+        assert!(selected(&tier).is_empty());
+
+        access(1, 10);
+        access(3, 100);
+        access(6, 50);
+        assert_eq!(selected(&tier), vec![3, 6]);
+
+        // Decay lets recently accessed regions take over
+        for accesses in &tier.accesses {
+            accesses.store(accesses.load(Ordering::Relaxed) / 4, Ordering::Relaxed);
+        }
+        access(1, 40);
+        assert_eq!(selected(&tier), vec![1, 3]);
+    }
+
+    #[test]
+    fn test_hot_tier_rebalance() {
+        let data = Arc::new(vec![1u8; REGION_BYTES * 4]);
+        let tier = Arc::new(HotTier::new(data.len(), 0.5));
+
+        for _ in 0..REBALANCE_INTERVAL {
+            tier.record_access(REGION_BYTES * 2 + 10, &data);
+        }
+        while tier.rebalancing.load(Ordering::Acquire) {
+            thread::sleep(std::time::Duration::from_millis(1));
+        }
+        // Accesses decayed by rebalance
+        assert_eq!(
+            tier.accesses[2].load(Ordering::Relaxed),
+            REBALANCE_INTERVAL as u32 / 2,
+        );
+
+        // Locking may not be permitted in the test environment
+        if !tier.lock_failed.load(Ordering::Relaxed) {
+            assert_eq!(tier.hot_bytes(), REGION_BYTES);
+        }
+    }
+}
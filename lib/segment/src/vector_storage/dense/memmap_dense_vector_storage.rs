@@ -1,5 +1,4 @@
 use std::borrow::Cow;
-use std::io::{self, BufWriter, Write};
 use std::ops::Range;
 use std::path::{Path, PathBuf};
 use std::sync::atomic::AtomicBool;
@@ -7,11 +6,9 @@ use std::sync::atomic::AtomicBool;
 use bitvec::prelude::BitSlice;
 use common::counter::hardware_counter::HardwareCounterCell;
 use common::fs::clear_disk_cache;
-use common::mmap;
 use common::mmap::AdviceSetting;
 use common::types::PointOffsetType;
 use fs_err as fs;
-use fs_err::{File, OpenOptions};
 
 use crate::common::Flusher;
 use crate::common::operation_error::{OperationError, OperationResult, check_process_stopped};
@@ -20,7 +17,10 @@ use crate::data_types::primitive::PrimitiveVectorElement;
 use crate::data_types::vectors::{VectorElementType, VectorRef};
 use crate::types::{Distance, VectorStorageDatatype};
 use crate::vector_storage::common::get_async_scorer;
-use crate::vector_storage::dense::mmap_dense_vectors::MmapDenseVectors;
+use crate::vector_storage::dense::mmap_dense_vectors::{
+    MmapDenseVectors, MmapDenseVectorsWriter, compress_vectors_file, is_valid_deleted_header,
+    is_valid_vectors_header, migrated_deleted_path, validate_deleted_file, validate_vectors_file,
+};
 use crate::vector_storage::mmap_endian::MmapEndianConvertible;
 use crate::vector_storage::{AccessPattern, DenseVectorStorage, VectorStorage, VectorStorageEnum};
 
@@ -50,18 +50,87 @@ impl<T: PrimitiveVectorElement + MmapEndianConvertible> MemmapDenseVectorStorage
         }
     }
 
+    /// See [`MmapDenseVectors::request_hugepages`].
+    pub fn request_hugepages(&self) -> OperationResult<()> {
+        if let Some(mmap_store) = &self.mmap_store {
+            mmap_store.request_hugepages()?;
+        }
+        Ok(())
+    }
+
+    /// See [`MmapDenseVectors::enable_hot_tier`].
+    pub fn enable_hot_tier(&mut self, hot_ratio: f64) {
+        if let Some(mmap_store) = &mut self.mmap_store {
+            mmap_store.enable_hot_tier(hot_ratio);
+        }
+    }
+
     /// Drop disk cache.
     pub fn clear_cache(&self) -> OperationResult<()> {
         clear_disk_cache(&self.vectors_path)?;
         clear_disk_cache(&self.deleted_path)?;
+        let migrated_path = migrated_deleted_path(&self.deleted_path);
+        if migrated_path.exists() {
+            clear_disk_cache(&migrated_path)?;
+        }
         Ok(())
     }
+
+    /// Compress the vectors file into the zstd-compressed cold tier.
+    ///
+    /// Vectors in this instance are still read from the uncompressed file, the compressed ones are
+    /// used once the storage is opened again.
+    pub fn compress_files(&self) -> OperationResult<()> {
+        compress_vectors_file::<T>(&self.vectors_path, self.vector_dim())
+    }
+}
+
+/// Whether `header` is valid for the plaintext storage file named `file_name`.
+///
+/// Returns `None` for files which don't belong to this storage. Used to scrub storage files.
+pub fn is_valid_file_header(file_name: &str, header: &[u8]) -> Option<bool> {
+    match file_name {
+        VECTORS_PATH => Some(is_valid_vectors_header(header)),
+        DELETED_PATH => Some(is_valid_deleted_header(header)),
+        _ if Path::new(file_name) == migrated_deleted_path(Path::new(DELETED_PATH)) => {
+            Some(is_valid_deleted_header(header))
+        }
+        _ => None,
+    }
+}
+
+/// Validate files of the storage at `path` without opening it.
+///
+/// Returns all problems found, with the path of the invalid file.
+pub fn validate_memmap_vector_storage<T: PrimitiveVectorElement + MmapEndianConvertible>(
+    path: &Path,
+    dim: usize,
+) -> Vec<(PathBuf, OperationError)> {
+    let vectors_path = path.join(VECTORS_PATH);
+    let deleted_path = path.join(DELETED_PATH);
+    let vectors_result = validate_vectors_file::<T>(&vectors_path, dim);
+    let deleted_result = validate_deleted_file(&deleted_path);
+    let migrated_path = migrated_deleted_path(&deleted_path);
+    let migrated_result = if migrated_path.exists() {
+        validate_deleted_file(&migrated_path)
+    } else {
+        Ok(())
+    };
+    [
+        (vectors_path, vectors_result),
+        (deleted_path, deleted_result),
+        (migrated_path, migrated_result),
+    ]
+    .into_iter()
+    .filter_map(|(path, result)| Some((path, result.err()?)))
+    .collect()
 }
 
 pub fn open_memmap_vector_storage(
     path: &Path,
     dim: usize,
     distance: Distance,
+    direct_io: bool,
     madvise: AdviceSetting,
     populate: bool,
 ) -> OperationResult<VectorStorageEnum> {
@@ -70,6 +139,7 @@ pub fn open_memmap_vector_storage(
         dim,
         distance,
         get_async_scorer(),
+        direct_io,
         madvise,
         populate,
     )?;
@@ -80,6 +150,7 @@ pub fn open_memmap_vector_storage_byte(
     path: &Path,
     dim: usize,
     distance: Distance,
+    direct_io: bool,
     madvise: AdviceSetting,
     populate: bool,
 ) -> OperationResult<VectorStorageEnum> {
@@ -88,6 +159,7 @@ pub fn open_memmap_vector_storage_byte(
         dim,
         distance,
         get_async_scorer(),
+        direct_io,
         madvise,
         populate,
     )?;
@@ -98,6 +170,7 @@ pub fn open_memmap_vector_storage_half(
     path: &Path,
     dim: usize,
     distance: Distance,
+    direct_io: bool,
     madvise: AdviceSetting,
     populate: bool,
 ) -> OperationResult<VectorStorageEnum> {
@@ -106,12 +179,33 @@ pub fn open_memmap_vector_storage_half(
         dim,
         distance,
         get_async_scorer(),
+        direct_io,
         madvise,
         populate,
     )?;
     Ok(VectorStorageEnum::DenseMemmapHalf(storage))
 }
 
+pub fn open_memmap_vector_storage_bf16(
+    path: &Path,
+    dim: usize,
+    distance: Distance,
+    direct_io: bool,
+    madvise: AdviceSetting,
+    populate: bool,
+) -> OperationResult<VectorStorageEnum> {
+    let storage = open_memmap_vector_storage_with_async_io_impl(
+        path,
+        dim,
+        distance,
+        get_async_scorer(),
+        direct_io,
+        madvise,
+        populate,
+    )?;
+    Ok(VectorStorageEnum::DenseMemmapBf16(storage))
+}
+
 pub fn open_memmap_vector_storage_with_async_io(
     path: &Path,
     dim: usize,
@@ -125,6 +219,7 @@ pub fn open_memmap_vector_storage_with_async_io(
         dim,
         distance,
         with_async_io,
+        false,
         madvise,
         populate,
     )?;
@@ -138,6 +233,7 @@ fn open_memmap_vector_storage_with_async_io_impl<
     dim: usize,
     distance: Distance,
     with_async_io: bool,
+    with_direct_io: bool,
     madvise: AdviceSetting,
     populate: bool,
 ) -> OperationResult<Box<MemmapDenseVectorStorage<T>>> {
@@ -150,6 +246,7 @@ fn open_memmap_vector_storage_with_async_io_impl<
         &deleted_path,
         dim,
         with_async_io,
+        with_direct_io,
         madvise,
         populate,
     )?;
@@ -182,17 +279,31 @@ impl<T: PrimitiveVectorElement + MmapEndianConvertible> DenseVectorStorage<T>
         self.mmap_store.as_ref().unwrap().dim
     }
 
-    fn get_dense<P: AccessPattern>(&self, key: PointOffsetType) -> &[T] {
+    fn get_dense<P: AccessPattern>(&self, key: PointOffsetType) -> Cow<'_, [T]> {
         self.mmap_store
             .as_ref()
             .unwrap()
-            .get_vector_opt::<P>(key)
-            .unwrap_or_else(|| panic!("vector not found: {key}"))
+            .get_vector_cow::<P>(key)
+            .unwrap_or_else(|err| panic!("Failed to read vector {key}: {err}"))
     }
 
     fn for_each_in_dense_batch<F: FnMut(usize, &[T])>(&self, keys: &[PointOffsetType], f: F) {
         let mmap_store = self.mmap_store.as_ref().unwrap();
-        mmap_store.for_each_in_batch(keys, f);
+        mmap_store
+            .for_each_in_batch(keys, f)
+            .unwrap_or_else(|err| panic!("Failed to read vectors: {err}"));
+    }
+
+    fn prefetch_dense_batch(&self, keys: &[PointOffsetType]) {
+        self.mmap_store.as_ref().unwrap().prefetch(keys);
+    }
+
+    fn get_vectors_batch_into(&self, keys: &[PointOffsetType], buffer: &mut Vec<T>) {
+        self.mmap_store
+            .as_ref()
+            .unwrap()
+            .read_batch_into(keys, buffer)
+            .unwrap_or_else(|err| panic!("Failed to read vectors: {err}"));
     }
 }
 
@@ -216,12 +327,8 @@ impl<T: PrimitiveVectorElement + MmapEndianConvertible> VectorStorage
     }
 
     fn get_vector<P: AccessPattern>(&self, key: PointOffsetType) -> CowVector<'_> {
-        self.mmap_store
-            .as_ref()
-            .unwrap()
-            .get_vector_opt::<P>(key)
-            .map(|vector| T::slice_to_float_cow(vector.into()).into())
-            .expect("Vector not found")
+        let vector = self.get_dense::<P>(key);
+        T::slice_to_float_cow(vector).into()
     }
 
     fn read_vectors<P: AccessPattern>(
@@ -244,8 +351,9 @@ impl<T: PrimitiveVectorElement + MmapEndianConvertible> VectorStorage
         self.mmap_store
             .as_ref()
             .unwrap()
-            .get_vector_opt::<P>(key)
-            .map(|vector| T::slice_to_float_cow(vector.into()).into())
+            .get_vector_cow_opt::<P>(key)
+            .unwrap_or_else(|err| panic!("Failed to read vector {key}: {err}"))
+            .map(|vector| T::slice_to_float_cow(vector).into())
     }
 
     fn insert_vector(
@@ -266,19 +374,19 @@ impl<T: PrimitiveVectorElement + MmapEndianConvertible> VectorStorage
         let start_index = self.mmap_store.as_ref().unwrap().num_vectors as PointOffsetType;
         let mut end_index = start_index;
 
-        let with_async_io = self
+        let (with_async_io, with_direct_io) = self
             .mmap_store
             .take()
-            .map(|x| x.has_async_reader())
-            .unwrap_or(get_async_scorer());
+            .map(|x| (x.has_uring_reader(), x.has_direct_reader()))
+            .unwrap_or((get_async_scorer(), false));
 
-        // Extend vectors file, write other vectors into it
-        let mut vectors_file = BufWriter::new(open_append(&self.vectors_path)?);
+        // Extend vectors file, stream other vectors into it
+        let mut writer = MmapDenseVectorsWriter::<T>::open(&self.vectors_path, dim)?;
         let mut deleted_ids = vec![];
         for (offset, (other_vector, other_deleted)) in other_vectors.enumerate() {
             check_process_stopped(stopped)?;
             let vector = T::slice_from_float_cow(Cow::try_from(other_vector)?);
-            write_vector_le(&mut vectors_file, vector.as_ref())?;
+            writer.push(vector.as_ref())?;
             end_index += 1;
 
             // Remember deleted IDs so we can propagate deletions later
@@ -287,12 +395,7 @@ impl<T: PrimitiveVectorElement + MmapEndianConvertible> VectorStorage
             }
         }
 
-        // Explicitly fsync file contents to ensure durability
-        vectors_file.flush()?;
-        vectors_file
-            .into_inner()
-            .map_err(io::IntoInnerError::into_error)?
-            .sync_data()?;
+        writer.finish()?;
 
         // Load store with updated files
         self.mmap_store.replace(MmapDenseVectors::open(
@@ -300,6 +403,7 @@ impl<T: PrimitiveVectorElement + MmapEndianConvertible> VectorStorage
             &self.deleted_path,
             dim,
             with_async_io,
+            with_direct_io,
             AdviceSetting::Global,
             false, // No need to populate
         )?);
@@ -311,7 +415,7 @@ impl<T: PrimitiveVectorElement + MmapEndianConvertible> VectorStorage
         let store = self.mmap_store.as_mut().unwrap();
         for id in deleted_ids {
             check_process_stopped(stopped)?;
-            store.delete(id);
+            store.delete(id)?;
         }
         store.flusher()()?;
 
@@ -329,7 +433,13 @@ impl<T: PrimitiveVectorElement + MmapEndianConvertible> VectorStorage
     }
 
     fn files(&self) -> Vec<PathBuf> {
-        vec![self.vectors_path.clone(), self.deleted_path.clone()]
+        let mut files = vec![self.vectors_path.clone(), self.deleted_path.clone()];
+        // Deleted flags with the legacy header are used through their migrated copy
+        let migrated_path = migrated_deleted_path(&self.deleted_path);
+        if migrated_path.exists() {
+            files.push(migrated_path);
+        }
+        files
     }
 
     fn immutable_files(&self) -> Vec<PathBuf> {
@@ -339,7 +449,7 @@ impl<T: PrimitiveVectorElement + MmapEndianConvertible> VectorStorage
     }
 
     fn delete_vector(&mut self, key: PointOffsetType) -> OperationResult<bool> {
-        Ok(self.mmap_store.as_mut().unwrap().delete(key))
+        self.mmap_store.as_mut().unwrap().delete(key)
     }
 
     fn is_deleted_vector(&self, key: PointOffsetType) -> bool {
@@ -356,30 +466,6 @@ impl<T: PrimitiveVectorElement + MmapEndianConvertible> VectorStorage
 }
 
 /// Open a file shortly for appending
-fn open_append<P: AsRef<Path>>(path: P) -> io::Result<File> {
-    let path = path.as_ref().to_path_buf();
-    OpenOptions::new().append(true).open(path)
-}
-
-fn write_vector_le<T: PrimitiveVectorElement + MmapEndianConvertible>(
-    writer: &mut impl Write,
-    vector: &[T],
-) -> io::Result<()> {
-    if cfg!(target_endian = "little") {
-        // Safety: `T` implements zerocopy::IntoBytes.
-        #[expect(deprecated, reason = "legacy code")]
-        let raw_bytes = unsafe { mmap::transmute_to_u8_slice(vector) };
-        writer.write_all(raw_bytes)
-    } else {
-        let mut encoded = Vec::with_capacity(vector.len());
-        encoded.extend(vector.iter().map(|value| value.to_le_storage()));
-        // Safety: `T` implements zerocopy::IntoBytes.
-        #[expect(deprecated, reason = "legacy code")]
-        let raw_bytes = unsafe { mmap::transmute_to_u8_slice(encoded.as_slice()) };
-        writer.write_all(raw_bytes)
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use std::mem::transmute;
@@ -398,6 +484,7 @@ mod tests {
     use crate::id_tracker::id_tracker_base::IdTracker;
     use crate::index::hnsw_index::point_scorer::{BatchFilteredSearcher, FilteredScorer};
     use crate::types::{PointIdType, QuantizationConfig, ScalarQuantizationConfig};
+    use crate::vector_storage::dense::mmap_dense_vectors::write_vector_le;
     use crate::vector_storage::dense::volatile_dense_vector_storage::new_volatile_dense_vector_storage;
     use crate::vector_storage::quantized::quantized_vectors::{
         QuantizedVectors, QuantizedVectorsStorageType,
@@ -424,9 +511,15 @@ mod tests {
             vec![1.0, 0.0, 0.0, 0.0],
         ];
         let id_tracker = Arc::new(AtomicRefCell::new(FixtureIdTracker::new(points.len())));
-        let mut storage =
-            open_memmap_vector_storage(dir.path(), 4, Distance::Dot, AdviceSetting::Global, false)
-                .unwrap();
+        let mut storage = open_memmap_vector_storage(
+            dir.path(),
+            4,
+            Distance::Dot,
+            false,
+            AdviceSetting::Global,
+            false,
+        )
+        .unwrap();
         let mut borrowed_id_tracker = id_tracker.borrow_mut();
 
         // Assert this storage lists both the vector and deleted file
@@ -543,9 +636,15 @@ mod tests {
         ];
         let delete_mask = [false, false, true, true, false];
         let id_tracker = Arc::new(AtomicRefCell::new(FixtureIdTracker::new(points.len())));
-        let mut storage =
-            open_memmap_vector_storage(dir.path(), 4, Distance::Dot, AdviceSetting::Global, false)
-                .unwrap();
+        let mut storage = open_memmap_vector_storage(
+            dir.path(),
+            4,
+            Distance::Dot,
+            false,
+            AdviceSetting::Global,
+            false,
+        )
+        .unwrap();
         let borrowed_id_tracker = id_tracker.borrow_mut();
 
         let hw_counter = HardwareCounterCell::new();
@@ -673,9 +772,15 @@ mod tests {
         ];
         let delete_mask = [false, false, true, true, false];
         let id_tracker = Arc::new(AtomicRefCell::new(FixtureIdTracker::new(points.len())));
-        let mut storage =
-            open_memmap_vector_storage(dir.path(), 4, Distance::Dot, AdviceSetting::Global, false)
-                .unwrap();
+        let mut storage = open_memmap_vector_storage(
+            dir.path(),
+            4,
+            Distance::Dot,
+            false,
+            AdviceSetting::Global,
+            false,
+        )
+        .unwrap();
         let borrowed_id_tracker = id_tracker.borrow_mut();
 
         let hw_counter = HardwareCounterCell::new();
@@ -750,9 +855,15 @@ mod tests {
             vec![1.0, 0.0, 0.0, 0.0],
         ];
         let id_tracker = Arc::new(AtomicRefCell::new(FixtureIdTracker::new(points.len())));
-        let mut storage =
-            open_memmap_vector_storage(dir.path(), 4, Distance::Dot, AdviceSetting::Global, false)
-                .unwrap();
+        let mut storage = open_memmap_vector_storage(
+            dir.path(),
+            4,
+            Distance::Dot,
+            false,
+            AdviceSetting::Global,
+            false,
+        )
+        .unwrap();
         let borrowed_id_tracker = id_tracker.borrow_mut();
 
         let hw_counter = HardwareCounterCell::new();
@@ -797,6 +908,60 @@ mod tests {
         assert_eq!(res[2].score, -1.0);
     }
 
+    #[test]
+    fn test_get_vectors_batch_into() {
+        let dir = Builder::new().prefix("storage_dir").tempdir().unwrap();
+
+        let points = [
+            vec![1.0, 0.0, 1.0, 1.0],
+            vec![1.0, 0.0, 1.0, 0.0],
+            vec![1.0, 1.0, 1.0, 1.0],
+            vec![1.0, 1.0, 0.0, 1.0],
+            vec![1.0, 0.0, 0.0, 0.0],
+        ];
+        let mut storage = open_memmap_vector_storage(
+            dir.path(),
+            4,
+            Distance::Dot,
+            false,
+            AdviceSetting::Global,
+            false,
+        )
+        .unwrap();
+
+        let hw_counter = HardwareCounterCell::new();
+
+        {
+            let mut storage2 = new_volatile_dense_vector_storage(4, Distance::Dot);
+            for (i, vec) in points.iter().enumerate() {
+                storage2
+                    .insert_vector(i as PointOffsetType, vec.as_slice().into(), &hw_counter)
+                    .unwrap();
+            }
+            let mut iter = (0..points.len()).map(|i| {
+                let i = i as PointOffsetType;
+                let vector = storage2.get_vector::<Random>(i);
+                let deleted = storage2.is_deleted_vector(i);
+                (vector, deleted)
+            });
+            storage.update_from(&mut iter, &Default::default()).unwrap();
+        }
+
+        let VectorStorageEnum::DenseMemmap(storage) = &storage else {
+            panic!("Expected memmap dense vector storage");
+        };
+
+        let mut buffer = vec![42.0; 3];
+        for keys in [vec![1, 2, 3], vec![4, 0, 2], vec![]] {
+            storage.get_vectors_batch_into(&keys, &mut buffer);
+            let expected = keys
+                .iter()
+                .flat_map(|&key| points[key as usize].iter().copied())
+                .collect_vec();
+            assert_eq!(buffer, expected);
+        }
+    }
+
     #[test]
     fn test_casts() {
         let data: DenseVector = vec![0.42, 0.069, 333.1, 100500.];
@@ -828,9 +993,15 @@ mod tests {
             vec![1.0, 1.0, 0.0, 1.0],
             vec![1.0, 0.0, 0.0, 0.0],
         ];
-        let mut storage =
-            open_memmap_vector_storage(dir.path(), 4, Distance::Dot, AdviceSetting::Global, false)
-                .unwrap();
+        let mut storage = open_memmap_vector_storage(
+            dir.path(),
+            4,
+            Distance::Dot,
+            false,
+            AdviceSetting::Global,
+            false,
+        )
+        .unwrap();
 
         let hw_counter = HardwareCounterCell::new();
 
@@ -867,6 +1038,7 @@ mod tests {
             QuantizedVectorsStorageType::Immutable,
             dir.path(),
             1,
+            None,
             &stopped,
         )
         .unwrap();
@@ -1,23 +1,32 @@
-use std::io::Write;
+use std::borrow::Cow;
+use std::io::{self, BufWriter, Read, Seek, SeekFrom, Write};
+use std::marker::PhantomData;
 use std::mem::{MaybeUninit, size_of};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
 
 use bitvec::prelude::BitSlice;
 use common::ext::BitSliceExt as _;
+use common::fs::sync_parent_dir;
 use common::maybe_uninit::maybe_uninit_fill_from;
 use common::mmap;
+use common::mmap::encryption::EncryptingWriter;
 use common::mmap::{
-    Advice, AdviceSetting, MULTI_MMAP_IS_SUPPORTED, Madviseable, MmapBitSlice, MmapFlusher,
+    Advice, AdviceSetting, MULTI_MMAP_IS_SUPPORTED, Madviseable, MmapBitSlice, MmapFlusher, advice,
+    encryption,
 };
 use common::types::PointOffsetType;
+use fs_err as fs;
 use fs_err::{File, OpenOptions};
 use memmap2::Mmap;
 use parking_lot::Mutex;
+use zerocopy::IntoBytes as _;
 
 use crate::common::error_logging::LogError;
 use crate::common::operation_error::{OperationError, OperationResult};
 use crate::data_types::primitive::PrimitiveVectorElement;
+use crate::types::VectorStorageDatatype;
 #[cfg(all(
     target_os = "linux",
     any(target_arch = "x86_64", target_arch = "aarch64")
@@ -27,16 +36,131 @@ use crate::vector_storage::async_io::UringReader;
     target_os = "linux",
     any(target_arch = "x86_64", target_arch = "aarch64")
 )))]
-use crate::vector_storage::async_io_mock::UringReader;
-use crate::vector_storage::common::VECTOR_READ_BATCH_SIZE;
+use crate::vector_storage::async_io_batched::UringReader;
+use crate::vector_storage::common::{PAGE_SIZE_BYTES, VECTOR_READ_BATCH_SIZE};
+use crate::vector_storage::dense::compressed_dense_vectors::CompressedDenseVectors;
+use crate::vector_storage::dense::hot_tier::HotTier;
+use crate::vector_storage::direct_io::DirectReader;
 use crate::vector_storage::mmap_endian::MmapEndianConvertible;
 use crate::vector_storage::query_scorer::is_read_with_prefetch_efficient;
 use crate::vector_storage::{AccessPattern, Random, Sequential};
 
 const HEADER_SIZE: usize = 4;
+/// Untyped header of vectors files, see [`vectors_header`]
 const VECTORS_HEADER: &[u8; HEADER_SIZE] = b"data";
-const DELETED_HEADER: &[u8; HEADER_SIZE] = b"drop";
+/// Versioned header of `u8` vectors files: magic, then format version
+const BYTE_VECTORS_HEADER: &[u8; HEADER_SIZE] = b"du8\x01";
+const DELETED_HEADER: &[u8; HEADER_SIZE] = b"drp2";
+/// Version of `u8` vectors files, as in [`BYTE_VECTORS_HEADER`]
+pub(crate) const BYTE_VECTORS_FORMAT_VERSION: u32 = BYTE_VECTORS_HEADER[3] as u32;
+/// Version of deleted mmap files, as in [`DELETED_HEADER`]
+pub(crate) const DELETED_FORMAT_VERSION: u32 = (DELETED_HEADER[3] - b'0') as u32;
+/// Header of deleted mmap files without persisted metadata, migrated into a copy on open
+const LEGACY_DELETED_HEADER: &[u8; HEADER_SIZE] = b"drop";
+/// Extension of the migrated copy of a deleted mmap file with the legacy header
+const MIGRATED_DELETED_EXTENSION: &str = "migrated";
+/// Deleted mmap header: magic, padding, deleted count, generation and legacy checksum
+const DELETED_HEADER_SIZE: usize = HEADER_SIZE + 4 + 3 * size_of::<u64>();
 const DELETED_LAYOUT_BLOCK_BYTES: usize = size_of::<u64>();
+/// Number of batches to read ahead of sequential scoring
+const READ_AHEAD_BATCHES: usize = 8;
+
+/// Metadata persisted in the header of the deleted mmap
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct DeletedMeta {
+    deleted_count: u64,
+    /// Incremented before flags are changed after a flush, and when they are flushed again.
+    ///
+    /// Even if `deleted_count` matches the flushed flags and can be trusted. Odd if flags were
+    /// changed since, for example if the node crashed or the file was copied while in use.
+    generation: u64,
+    /// Checksum of the legacy flags this file was migrated from, zero if it was not migrated.
+    ///
+    /// A mismatch means a downgraded node changed the legacy file since, see
+    /// [`migrate_legacy_deleted_file`].
+    legacy_checksum: u64,
+}
+
+impl DeletedMeta {
+    fn is_clean(&self) -> bool {
+        self.generation.is_multiple_of(2)
+    }
+
+    fn from_bytes(header: &[u8]) -> Self {
+        let read_u64 = |offset: usize| {
+            u64::from_le_bytes(
+                header[offset..offset + size_of::<u64>()]
+                    .try_into()
+                    .unwrap(),
+            )
+        };
+        Self {
+            deleted_count: read_u64(HEADER_SIZE + 4),
+            generation: read_u64(HEADER_SIZE + 4 + size_of::<u64>()),
+            legacy_checksum: read_u64(HEADER_SIZE + 4 + 2 * size_of::<u64>()),
+        }
+    }
+
+    fn to_bytes(self) -> [u8; DELETED_HEADER_SIZE] {
+        let mut header = [0; DELETED_HEADER_SIZE];
+        header[..HEADER_SIZE].copy_from_slice(DELETED_HEADER);
+        header[HEADER_SIZE + 4..HEADER_SIZE + 12]
+            .copy_from_slice(&self.deleted_count.to_le_bytes());
+        header[HEADER_SIZE + 12..HEADER_SIZE + 20].copy_from_slice(&self.generation.to_le_bytes());
+        header[HEADER_SIZE + 20..].copy_from_slice(&self.legacy_checksum.to_le_bytes());
+        header
+    }
+}
+
+/// Persisted header of the deleted mmap, shared with flushers
+#[derive(Debug)]
+struct DeletedHeader {
+    file: File,
+    meta: DeletedMeta,
+}
+
+impl DeletedHeader {
+    fn open(path: &Path) -> OperationResult<Self> {
+        let mut file = OpenOptions::new().read(true).write(true).open(path)?;
+        let mut header = [0; DELETED_HEADER_SIZE];
+        file.read_exact(&mut header)?;
+        if &header[..HEADER_SIZE] != DELETED_HEADER {
+            return Err(OperationError::service_error(format!(
+                "Invalid mmap deleted file {} header, expected {:?}",
+                path.display(),
+                DELETED_HEADER,
+            )));
+        }
+        Ok(Self {
+            file,
+            meta: DeletedMeta::from_bytes(&header),
+        })
+    }
+
+    /// Mark the persisted count as stale, must be called before flags are changed.
+    fn mark_changed(&mut self) -> io::Result<()> {
+        if !self.meta.is_clean() {
+            return Ok(());
+        }
+        self.meta.generation += 1;
+        self.write()
+    }
+
+    /// Persist the count, if flags did not change since `flushed` was taken and are flushed.
+    fn mark_flushed(&mut self, flushed: DeletedMeta) -> io::Result<()> {
+        if self.meta != flushed || self.meta.is_clean() {
+            return Ok(());
+        }
+        self.meta.generation += 1;
+        self.write()
+    }
+
+    fn write(&mut self) -> io::Result<()> {
+        self.file.seek(SeekFrom::Start(0))?;
+        self.file.write_all(&self.meta.to_bytes())?;
+        self.file.sync_data()
+    }
+}
 
 /// Mem-mapped file for dense vectors
 #[derive(Debug)]
@@ -53,21 +177,24 @@ pub struct MmapDenseVectors<T: PrimitiveVectorElement + MmapEndianConvertible> {
     /// `None` on platforms that do not support multiple memory maps to the same file.
     /// Use [`mmap_seq`] utility function to access this mmap if available.
     _mmap_seq: Option<Arc<Mmap>>,
-    /// Context for io_uring-base async IO
-    #[cfg_attr(
-        not(all(
-            target_os = "linux",
-            any(target_arch = "x86_64", target_arch = "aarch64")
-        )),
-        allow(dead_code)
-    )]
+    /// Context for async IO: io_uring on Linux, batched reads on other platforms
     uring_reader: Option<Mutex<UringReader<T>>>,
+    /// Reader bypassing the page cache, takes precedence over `uring_reader`
+    direct_reader: Option<Mutex<DirectReader<T>>>,
     /// Memory mapped deletion flags
     deleted: MmapBitSlice,
+    /// Header of the deleted mmap, shared with flushers
+    deleted_header: Arc<Mutex<DeletedHeader>>,
     /// Current number of deleted vectors.
     pub deleted_count: usize,
     /// Cached decoded vectors for BE hosts.
     decoded_vectors: Option<Vec<T>>,
+    /// Vectors of the zstd-compressed cold tier, if the vectors file is compressed
+    compressed: Option<CompressedDenseVectors<T>>,
+    /// Recently accessed parts of the vectors mmap, locked in RAM
+    hot_tier: Option<Arc<HotTier>>,
+    /// End of the range of the vectors mmap last requested to be read ahead
+    read_ahead_until: AtomicUsize,
 }
 
 impl<T: PrimitiveVectorElement + MmapEndianConvertible> MmapDenseVectors<T> {
@@ -86,11 +213,12 @@ impl<T: PrimitiveVectorElement + MmapEndianConvertible> MmapDenseVectors<T> {
         deleted_path: &Path,
         dim: usize,
         with_async_io: bool,
+        with_direct_io: bool,
         madvise: AdviceSetting,
         populate: bool,
     ) -> OperationResult<Self> {
         // Allocate/open vectors mmap
-        ensure_mmap_file_size(vectors_path, VECTORS_HEADER, None)
+        ensure_mmap_file_size(vectors_path, vectors_header::<T>(), None)
             .describe("Create mmap data file")?;
 
         // Validate file length before mmap: empty files can't be mmapped on some platforms, and
@@ -103,6 +231,8 @@ impl<T: PrimitiveVectorElement + MmapEndianConvertible> MmapDenseVectors<T> {
             )));
         }
 
+        // Encrypted vectors are decrypted into RAM, see `common::mmap::encryption`
+        let encrypted = encryption::is_encrypted_file(vectors_path)?;
         let mmap = mmap::open_read_mmap(vectors_path, madvise, populate)
             .describe("Open mmap for reading")?;
 
@@ -115,11 +245,21 @@ impl<T: PrimitiveVectorElement + MmapEndianConvertible> MmapDenseVectors<T> {
                 mmap.len(),
             )));
         }
-        if &mmap[..HEADER_SIZE] != VECTORS_HEADER {
+        let mmap = Arc::new(mmap);
+        let compressed = if CompressedDenseVectors::<T>::is_compressed(&mmap) {
+            Some(CompressedDenseVectors::open(
+                vectors_path,
+                mmap.clone(),
+                dim,
+            )?)
+        } else {
+            None
+        };
+        if compressed.is_none() && !is_vectors_header::<T>(&mmap[..HEADER_SIZE]) {
             return Err(OperationError::service_error(format!(
                 "Invalid mmap vectors file {} header, expected {:?}",
                 vectors_path.display(),
-                VECTORS_HEADER,
+                vectors_header::<T>(),
             )));
         }
 
@@ -136,7 +276,7 @@ impl<T: PrimitiveVectorElement + MmapEndianConvertible> MmapDenseVectors<T> {
             .len()
             .checked_sub(HEADER_SIZE)
             .ok_or_else(|| OperationError::service_error("Vectors mmap size underflow".to_string()))?;
-        if payload_len % vector_bytes != 0 {
+        if compressed.is_none() && payload_len % vector_bytes != 0 {
             return Err(OperationError::service_error(format!(
                 "Invalid mmap vectors file {} size {}, expected header + N * {vector_bytes}",
                 vectors_path.display(),
@@ -144,8 +284,9 @@ impl<T: PrimitiveVectorElement + MmapEndianConvertible> MmapDenseVectors<T> {
             )));
         }
 
-        // Only open second mmap for sequential reads if supported
-        let mmap_seq = if *MULTI_MMAP_IS_SUPPORTED {
+        // Only open second mmap for sequential reads if supported, and if it would not be another
+        // decrypted copy. Compressed vectors are never read from the mmap directly.
+        let mmap_seq = if *MULTI_MMAP_IS_SUPPORTED && !encrypted && compressed.is_none() {
             let mmap_seq = mmap::open_read_mmap(
                 vectors_path,
                 AdviceSetting::Advice(Advice::Sequential),
@@ -157,26 +298,44 @@ impl<T: PrimitiveVectorElement + MmapEndianConvertible> MmapDenseVectors<T> {
             None
         };
 
-        let num_vectors = payload_len / vector_bytes;
-        let decoded_vectors = if cfg!(target_endian = "big") {
-            Some(Self::decode_vectors(&mmap, dim, num_vectors)?)
+        let num_vectors = match &compressed {
+            Some(compressed) => compressed.num_vectors(),
+            None => payload_len / vector_bytes,
+        };
+        // Single byte values have no byte order, they are read from the mmap as is
+        let decoded_vectors =
+            if cfg!(target_endian = "big") && size_of::<T>() > 1 && compressed.is_none() {
+                Some(Self::decode_vectors(&mmap, dim, num_vectors)?)
+            } else {
+                None
+            };
+
+        // Allocate/open deleted mmap, files with the legacy header are used through their
+        // migrated copy
+        let deleted_path = if is_legacy_deleted_file(deleted_path)? {
+            migrate_legacy_deleted_file(deleted_path).describe("Migrate mmap deleted file")?
         } else {
-            None
+            deleted_path.to_path_buf()
         };
-
-        // Allocate/open deleted mmap
+        let deleted_path = deleted_path.as_path();
+        let deleted_data_start = deleted_mmap_data_start();
         let deleted_mmap_size = deleted_mmap_size(num_vectors);
+        // Flags may be cut off when shrinking, the persisted count is only valid when growing
+        let deleted_truncated = deleted_path.exists()
+            && fs_err::metadata(deleted_path)?.len() > deleted_mmap_size as u64;
+        if deleted_truncated {
+            DeletedHeader::open(deleted_path)?.mark_changed()?;
+        }
         ensure_mmap_file_size(deleted_path, DELETED_HEADER, Some(deleted_mmap_size as u64))
             .describe("Create mmap deleted file")?;
         let deleted_mmap = mmap::open_write_mmap(deleted_path, AdviceSetting::Global, false)
             .describe("Open mmap deleted for writing")?;
 
-        if deleted_mmap.len() < deleted_mmap_data_start() {
+        if deleted_mmap.len() < deleted_data_start {
             return Err(OperationError::service_error(format!(
-                "Invalid mmap deleted file {} size {}, expected at least {}",
+                "Invalid mmap deleted file {} size {}, expected at least {deleted_data_start}",
                 deleted_path.display(),
                 deleted_mmap.len(),
-                deleted_mmap_data_start(),
             )));
         }
         if &deleted_mmap[..HEADER_SIZE] != DELETED_HEADER {
@@ -194,10 +353,22 @@ impl<T: PrimitiveVectorElement + MmapEndianConvertible> MmapDenseVectors<T> {
         }
 
         // Transform into mmap BitSlice
-        let deleted = MmapBitSlice::try_from(deleted_mmap, deleted_mmap_data_start())?;
-        let deleted_count = deleted.count_ones();
+        let deleted = MmapBitSlice::try_from(deleted_mmap, deleted_data_start)?;
+        let mut deleted_header = DeletedHeader::open(deleted_path)?;
+        if !deleted_header.meta.is_clean() {
+            log::warn!(
+                "Persisted deleted count in mmap deleted file {} is stale, recounting",
+                deleted_path.display(),
+            );
+            // Stays stale on disk until the recounted flags are flushed
+            deleted_header.meta.deleted_count = deleted.count_ones() as u64;
+        }
+        let deleted_count = deleted_header.meta.deleted_count as usize;
 
-        let uring_reader = if with_async_io {
+        // Vectors decoded on big-endian hosts or decrypted are read from RAM anyway, and the file
+        // itself can't be read directly if encrypted or compressed
+        let read_from_file = decoded_vectors.is_none() && !encrypted && compressed.is_none();
+        let uring_reader = if with_async_io && read_from_file {
             // Keep file handle open for async IO
             let vectors_file = File::open(vectors_path)?;
             let raw_size = dim * size_of::<T>();
@@ -206,15 +377,35 @@ impl<T: PrimitiveVectorElement + MmapEndianConvertible> MmapDenseVectors<T> {
             None
         };
 
+        let direct_reader = if with_direct_io && read_from_file {
+            match DirectReader::open(vectors_path, dim * size_of::<T>(), HEADER_SIZE) {
+                Ok(reader) => Some(reader),
+                Err(err) => {
+                    log::warn!(
+                        "Direct IO is not available for {}, reading vectors with mmap: {err}",
+                        vectors_path.display(),
+                    );
+                    None
+                }
+            }
+        } else {
+            None
+        };
+
         Ok(MmapDenseVectors {
             dim,
             num_vectors,
-            mmap: mmap.into(),
+            mmap,
             _mmap_seq: mmap_seq,
             uring_reader: uring_reader.map(Mutex::new),
+            direct_reader: direct_reader.map(Mutex::new),
             deleted,
+            deleted_header: Arc::new(Mutex::new(deleted_header)),
             deleted_count,
             decoded_vectors,
+            compressed,
+            hot_tier: None,
+            read_ahead_until: AtomicUsize::new(0),
         })
     }
 
@@ -235,11 +426,27 @@ impl<T: PrimitiveVectorElement + MmapEndianConvertible> MmapDenseVectors<T> {
     }
 
     pub fn has_async_reader(&self) -> bool {
+        self.uring_reader.is_some() || self.direct_reader.is_some()
+    }
+
+    pub fn has_uring_reader(&self) -> bool {
         self.uring_reader.is_some()
     }
 
+    pub fn has_direct_reader(&self) -> bool {
+        self.direct_reader.is_some()
+    }
+
     pub fn flusher(&self) -> MmapFlusher {
-        self.deleted.flusher()
+        let flags_flusher = self.deleted.flusher();
+        let header = self.deleted_header.clone();
+        let flushed = header.lock().meta;
+        Box::new(move || {
+            // Flags must be persisted first, the header may never claim more than what is on disk
+            flags_flusher()?;
+            header.lock().mark_flushed(flushed)?;
+            Ok(())
+        })
     }
 
     pub fn data_offset(&self, key: PointOffsetType) -> Option<usize> {
@@ -255,7 +462,10 @@ impl<T: PrimitiveVectorElement + MmapEndianConvertible> MmapDenseVectors<T> {
         self.dim * size_of::<T>()
     }
 
+    /// Vector at `offset` of the uncompressed vectors file
     fn raw_vector_offset<P: AccessPattern>(&self, offset: usize) -> &[T] {
+        debug_assert!(self.compressed.is_none());
+
         if let Some(decoded_vectors) = &self.decoded_vectors {
             let vector_start = (offset - HEADER_SIZE) / size_of::<T>();
             let vector_end = vector_start + self.dim;
@@ -277,20 +487,59 @@ impl<T: PrimitiveVectorElement + MmapEndianConvertible> MmapDenseVectors<T> {
         self.get_vector_opt::<P>(key).expect("vector not found")
     }
 
-    /// Returns an optional reference to vector data by key
-    pub fn get_vector_opt<P: AccessPattern>(&self, key: PointOffsetType) -> Option<&[T]> {
-        self.data_offset(key)
-            .map(|offset| self.raw_vector_offset::<P>(offset))
+    /// Returns an optional reference to vector data by key, if vectors are not compressed
+    fn get_vector_opt<P: AccessPattern>(&self, key: PointOffsetType) -> Option<&[T]> {
+        self.data_offset(key).map(|offset| {
+            if let Some(hot_tier) = &self.hot_tier {
+                hot_tier.record_access(offset, &self.mmap);
+            }
+            self.raw_vector_offset::<P>(offset)
+        })
     }
 
-    pub fn for_each_in_batch<F: FnMut(usize, &[T])>(&self, keys: &[PointOffsetType], mut f: F) {
+    /// Returns an optional vector by key
+    ///
+    /// Compressed vectors are copied out of their decompressed block, which fails if the block
+    /// is corrupted.
+    pub fn get_vector_cow_opt<P: AccessPattern>(
+        &self,
+        key: PointOffsetType,
+    ) -> OperationResult<Option<Cow<'_, [T]>>> {
+        match &self.compressed {
+            Some(compressed) => Ok(compressed.get(key)?.map(Cow::Owned)),
+            None => Ok(self.get_vector_opt::<P>(key).map(Cow::Borrowed)),
+        }
+    }
+
+    /// Returns vector by key, failing if there is no such vector
+    pub fn get_vector_cow<P: AccessPattern>(
+        &self,
+        key: PointOffsetType,
+    ) -> OperationResult<Cow<'_, [T]>> {
+        self.get_vector_cow_opt::<P>(key)?
+            .ok_or_else(|| OperationError::service_error(format!("Vector {key} not found")))
+    }
+
+    pub fn for_each_in_batch<F: FnMut(usize, &[T])>(
+        &self,
+        keys: &[PointOffsetType],
+        mut f: F,
+    ) -> OperationResult<()> {
         debug_assert!(keys.len() <= VECTOR_READ_BATCH_SIZE);
 
+        if self.compressed.is_some() {
+            for (i, &key) in keys.iter().enumerate() {
+                f(i, &self.get_vector_cow::<Random>(key)?);
+            }
+            return Ok(());
+        }
+
         // The `f` is most likely a scorer function.
         // Fetching all vectors first then scoring them is more cache friendly
         // then fetching and scoring in a single loop.
         let mut vectors_buffer = [MaybeUninit::uninit(); VECTOR_READ_BATCH_SIZE];
         let vectors = if is_read_with_prefetch_efficient(keys) {
+            self.read_ahead(keys);
             let iter = keys.iter().map(|key| self.get_vector::<Sequential>(*key));
             maybe_uninit_fill_from(&mut vectors_buffer, iter).0
         } else {
@@ -301,17 +550,117 @@ impl<T: PrimitiveVectorElement + MmapEndianConvertible> MmapDenseVectors<T> {
         for (i, vec) in vectors.iter().enumerate() {
             f(i, vec);
         }
+        Ok(())
+    }
+
+    /// Ask the kernel to read vectors a few batches ahead of a sequential batch of `keys`, so that
+    /// disk reads overlap with scoring of the current batch.
+    ///
+    /// The read-ahead is asynchronous, and is only requested again once scoring gets close to the
+    /// end of the range requested last.
+    fn read_ahead(&self, keys: &[PointOffsetType]) {
+        if self.decoded_vectors.is_some() || self.compressed.is_some() {
+            return;
+        }
+        let Some(last_key) = keys.iter().max() else {
+            return;
+        };
+        let Some(batch_end) = self
+            .data_offset(*last_key)
+            .map(|offset| offset + self.raw_size())
+        else {
+            return;
+        };
+
+        #[expect(clippy::used_underscore_binding)]
+        let mmap: &Mmap = self._mmap_seq.as_deref().unwrap_or(self.mmap.as_ref());
+        let window = READ_AHEAD_BATCHES * keys.len() * self.raw_size();
+        let read_ahead_end = (batch_end + window).min(mmap.len());
+
+        // Still far enough ahead of the current batch. Ranges of other concurrent scans are
+        // requested again, as they are not ahead of this one.
+        let read_ahead_until = self.read_ahead_until.load(Ordering::Relaxed);
+        let is_ahead = read_ahead_until > batch_end && read_ahead_until <= read_ahead_end;
+        if is_ahead && read_ahead_until - batch_end >= window / 2 {
+            return;
+        }
+
+        let start = if is_ahead {
+            read_ahead_until
+        } else {
+            batch_end
+        };
+        if start >= read_ahead_end {
+            return;
+        }
+        self.read_ahead_until
+            .store(read_ahead_end, Ordering::Relaxed);
+
+        #[cfg(unix)]
+        if let Err(err) =
+            mmap.advise_range(memmap2::Advice::WillNeed, start, read_ahead_end - start)
+        {
+            log::trace!("Failed to advise MADV_WILLNEED for vectors read-ahead: {err}");
+        }
+    }
+
+    /// Ask the kernel to read vectors of `keys` in the background, in any order.
+    ///
+    /// Vectors are requested in file order, and vectors within a page of each other are requested
+    /// as a single range. A batch of random keys is then read with a few large requests, instead
+    /// of a page fault per vector once they are accessed.
+    pub fn prefetch(&self, keys: &[PointOffsetType]) {
+        if self.decoded_vectors.is_some() || self.compressed.is_some() {
+            return;
+        }
+        let mut offsets: Vec<_> = keys
+            .iter()
+            .filter_map(|&key| self.data_offset(key))
+            .collect();
+        offsets.sort_unstable();
+
+        #[cfg(unix)]
+        for (start, end) in prefetch_ranges(&offsets, self.raw_size()) {
+            if let Err(err) = self
+                .mmap
+                .advise_range(memmap2::Advice::WillNeed, start, end - start)
+            {
+                log::trace!("Failed to advise MADV_WILLNEED for vectors prefetch: {err}");
+            }
+        }
+    }
+
+    /// Copy vectors of `keys` into one contiguous `buffer`, in native byte order.
+    ///
+    /// Vectors are prefetched first, see [`Self::prefetch`].
+    pub fn read_batch_into(
+        &self,
+        keys: &[PointOffsetType],
+        buffer: &mut Vec<T>,
+    ) -> OperationResult<()> {
+        buffer.clear();
+        buffer.reserve(keys.len() * self.dim);
+        self.prefetch(keys);
+        for &key in keys {
+            buffer.extend_from_slice(&self.get_vector_cow::<Random>(key)?);
+        }
+        Ok(())
     }
 
     /// Marks the key as deleted.
     ///
     /// Returns true if the key was not deleted before, and it is now deleted.
-    pub fn delete(&mut self, key: PointOffsetType) -> bool {
-        let is_deleted = !self.deleted.replace(key as usize, true);
-        if is_deleted {
-            self.deleted_count += 1;
+    pub fn delete(&mut self, key: PointOffsetType) -> OperationResult<bool> {
+        if self.is_deleted_vector(key) {
+            return Ok(false);
         }
-        is_deleted
+        // Hold the header while changing flags, so flushers see the count and flags together
+        let mut header = self.deleted_header.lock();
+        header.mark_changed()?;
+        self.deleted.replace(key as usize, true);
+        self.deleted_count += 1;
+        header.meta.deleted_count = self.deleted_count as u64;
+        Ok(true)
     }
 
     pub fn is_deleted_vector(&self, key: PointOffsetType) -> bool {
@@ -330,42 +679,36 @@ impl<T: PrimitiveVectorElement + MmapEndianConvertible> MmapDenseVectors<T> {
         &self,
         points: impl Iterator<Item = PointOffsetType>,
         mut callback: impl FnMut(usize, PointOffsetType, &[T]),
-    ) {
+    ) -> OperationResult<()> {
         for (idx, point) in points.enumerate() {
-            let vector = self.get_vector::<Random>(point);
-            callback(idx, point, vector);
+            let vector = self.get_vector_cow::<Random>(point)?;
+            callback(idx, point, &vector);
         }
+        Ok(())
     }
 
     /// Reads vectors for the given ids and calls the callback for each vector.
     /// Tries to utilize asynchronous IO if possible.
-    /// In particular, uses io_uring on Linux and simple synchronous IO otherwise.
+    /// In particular, uses io_uring on Linux and batched reads with prefetch hints otherwise.
+    ///
+    /// If direct IO is enabled, vectors are read with `O_DIRECT` instead, bypassing the page cache.
     pub fn read_vectors_async(
         &self,
         points: impl Iterator<Item = PointOffsetType>,
         callback: impl FnMut(usize, PointOffsetType, &[T]),
     ) -> OperationResult<()> {
+        if let Some(direct_reader) = &self.direct_reader {
+            return direct_reader.lock().read_stream(points, callback);
+        }
+
         match &self.uring_reader {
-            None => self.process_points_simple(points, callback),
+            None => self.process_points_simple(points, callback)?,
 
-            #[cfg(all(
-                target_os = "linux",
-                any(target_arch = "x86_64", target_arch = "aarch64")
-            ))]
             Some(uring_reader) => {
-                // Use `UringReader` on Linux
+                // Uses io_uring on Linux, and batched reads on other platforms
                 let mut uring_guard = uring_reader.lock();
                 uring_guard.read_stream(points, callback)?;
             }
-
-            #[cfg(not(all(
-                target_os = "linux",
-                any(target_arch = "x86_64", target_arch = "aarch64")
-            )))]
-            Some(_) => {
-                // Fallback to synchronous processing on non-Linux platforms
-                self.process_points_simple(points, callback);
-            }
         }
         Ok(())
     }
@@ -376,6 +719,412 @@ impl<T: PrimitiveVectorElement + MmapEndianConvertible> MmapDenseVectors<T> {
             mmap_seq.populate();
         }
     }
+
+    /// Keep up to `hot_ratio` of the vectors file locked in RAM, following recent accesses.
+    ///
+    /// Does nothing if vectors are read from RAM anyway, on big-endian hosts, or if they are
+    /// compressed. See [`HotTier`].
+    pub fn enable_hot_tier(&mut self, hot_ratio: f64) {
+        if self.decoded_vectors.is_some() || self.compressed.is_some() {
+            return;
+        }
+        self.hot_tier = Some(Arc::new(HotTier::new(self.mmap.len(), hot_ratio)));
+    }
+
+    /// Number of bytes of the vectors file locked in RAM by the hot tier.
+    pub fn hot_tier_bytes(&self) -> usize {
+        self.hot_tier.as_deref().map_or(0, HotTier::hot_bytes)
+    }
+
+    /// Request transparent huge pages for the vectors file.
+    ///
+    /// Does nothing on big-endian hosts, where vectors are decoded into RAM, and for compressed
+    /// vectors.
+    pub fn request_hugepages(&self) -> OperationResult<()> {
+        if self.decoded_vectors.is_some() || self.compressed.is_some() {
+            return Ok(());
+        }
+        advice::request_hugepages(&self.mmap[..])?;
+        #[expect(clippy::used_underscore_binding)]
+        if let Some(mmap_seq) = &self._mmap_seq {
+            advice::request_hugepages(&mmap_seq[..])?;
+        }
+        Ok(())
+    }
+}
+
+impl<T: PrimitiveVectorElement + MmapEndianConvertible> Drop for MmapDenseVectors<T> {
+    fn drop(&mut self) {
+        if let Err(err) = self.flusher()() {
+            log::error!("Failed to flush mmap deleted file: {err}");
+        }
+    }
+}
+
+/// Header of new vectors files with elements of type `T`.
+///
+/// Files of `u8` vectors carry a versioned header, so that they are never read as vectors of
+/// another type, which could have a matching size. Other types keep the untyped header.
+fn vectors_header<T: PrimitiveVectorElement>() -> &'static [u8; HEADER_SIZE] {
+    match T::datatype() {
+        VectorStorageDatatype::Uint8 => BYTE_VECTORS_HEADER,
+        VectorStorageDatatype::Float32
+        | VectorStorageDatatype::Float16
+        | VectorStorageDatatype::Bfloat16 => VECTORS_HEADER,
+    }
+}
+
+/// Whether `header` is valid for uncompressed vectors with elements of type `T`.
+///
+/// The untyped header is accepted for all types, files written before typed headers existed use
+/// it.
+fn is_vectors_header<T: PrimitiveVectorElement>(header: &[u8]) -> bool {
+    header == VECTORS_HEADER || header == vectors_header::<T>()
+}
+
+/// Whether `header` is the start of a valid plaintext vectors file, uncompressed or compressed.
+pub fn is_valid_vectors_header(header: &[u8]) -> bool {
+    header.starts_with(VECTORS_HEADER)
+        || header.starts_with(BYTE_VECTORS_HEADER)
+        || CompressedDenseVectors::<u8>::is_compressed(header)
+}
+
+/// Whether `header` is the start of a valid deleted flags file, current or legacy.
+pub fn is_valid_deleted_header(header: &[u8]) -> bool {
+    header.starts_with(DELETED_HEADER) || header.starts_with(LEGACY_DELETED_HEADER)
+}
+
+/// Validate the vectors file at `vectors_path` like [`MmapDenseVectors::open`], without opening
+/// the storage or modifying the file.
+pub fn validate_vectors_file<T: PrimitiveVectorElement + MmapEndianConvertible>(
+    vectors_path: &Path,
+    dim: usize,
+) -> OperationResult<()> {
+    let mmap = mmap::open_read_mmap(
+        vectors_path,
+        AdviceSetting::Advice(Advice::Sequential),
+        false,
+    )?;
+    if CompressedDenseVectors::<T>::is_compressed(&mmap) {
+        return CompressedDenseVectors::<T>::open(vectors_path, Arc::new(mmap), dim).map(|_| ());
+    }
+    if mmap.len() < HEADER_SIZE || !is_vectors_header::<T>(&mmap[..HEADER_SIZE]) {
+        return Err(OperationError::service_error(format!(
+            "Invalid mmap vectors file {} header, expected {:?}",
+            vectors_path.display(),
+            vectors_header::<T>(),
+        )));
+    }
+    let vector_bytes = dim * size_of::<T>();
+    if vector_bytes == 0 || !(mmap.len() - HEADER_SIZE).is_multiple_of(vector_bytes) {
+        return Err(OperationError::service_error(format!(
+            "Invalid mmap vectors file {} size {}, expected header + N * {vector_bytes}",
+            vectors_path.display(),
+            mmap.len(),
+        )));
+    }
+    Ok(())
+}
+
+/// Validate the deleted flags file at `deleted_path` like [`MmapDenseVectors::open`], without
+/// opening the storage or modifying the file.
+///
+/// Files with the legacy header are valid, they are migrated into a copy on open.
+pub fn validate_deleted_file(deleted_path: &Path) -> OperationResult<()> {
+    let mmap = mmap::open_read_mmap(deleted_path, AdviceSetting::Global, false)?;
+    if mmap.starts_with(LEGACY_DELETED_HEADER) {
+        return Ok(());
+    }
+    if mmap.len() < deleted_mmap_data_start() {
+        return Err(OperationError::service_error(format!(
+            "Invalid mmap deleted file {} size {}, expected at least {}",
+            deleted_path.display(),
+            mmap.len(),
+            deleted_mmap_data_start(),
+        )));
+    }
+    if &mmap[..HEADER_SIZE] != DELETED_HEADER {
+        return Err(OperationError::service_error(format!(
+            "Invalid mmap deleted file {} header, expected {:?}",
+            deleted_path.display(),
+            DELETED_HEADER,
+        )));
+    }
+    Ok(())
+}
+
+/// Compress the vectors file of [`MmapDenseVectors`] into the zstd-compressed cold tier.
+///
+/// Does nothing if the file is compressed already.
+pub fn compress_vectors_file<T: PrimitiveVectorElement + MmapEndianConvertible>(
+    vectors_path: &Path,
+    dim: usize,
+) -> OperationResult<()> {
+    let mmap = mmap::open_read_mmap(
+        vectors_path,
+        AdviceSetting::Advice(Advice::Sequential),
+        false,
+    )?;
+    if CompressedDenseVectors::<T>::is_compressed(&mmap) {
+        return Ok(());
+    }
+    if mmap.len() < HEADER_SIZE || !is_vectors_header::<T>(&mmap[..HEADER_SIZE]) {
+        return Err(OperationError::service_error(format!(
+            "Invalid mmap vectors file {} header, expected {:?}",
+            vectors_path.display(),
+            vectors_header::<T>(),
+        )));
+    }
+    CompressedDenseVectors::<T>::compress(vectors_path, &mmap[HEADER_SIZE..], dim)
+}
+
+/// Streaming writer, appending dense vectors to the data file of [`MmapDenseVectors`].
+///
+/// Vectors are encoded as little-endian and go through a small buffer straight into the file,
+/// instead of being collected in memory first. Plaintext data files are appended to in place. If
+/// the writer is dropped without finishing, for example when the optimization is cancelled, the
+/// file is truncated back to its original length.
+///
+/// Encrypted files can't be appended to, vectors are written into an encrypted copy of the data
+/// file instead, which replaces the data file on finish, or is removed if unfinished.
+pub struct MmapDenseVectorsWriter<T: PrimitiveVectorElement + MmapEndianConvertible> {
+    /// Writer of the data file or its copy, `None` once finished
+    writer: Option<EncryptingWriter<BufWriter<File>>>,
+    vectors_path: PathBuf,
+    target: WriteTarget,
+    dim: usize,
+    num_vectors: usize,
+    _phantom: PhantomData<T>,
+}
+
+/// Where [`MmapDenseVectorsWriter`] writes vectors to.
+enum WriteTarget {
+    /// Appended to the data file, which had the given length before
+    Append { original_len: u64 },
+    /// Written into a copy of the data file at the given path
+    Copy { tmp_path: PathBuf },
+}
+
+impl<T: PrimitiveVectorElement + MmapEndianConvertible> MmapDenseVectorsWriter<T> {
+    /// Open the data file for appending, create it if it does not exist.
+    pub fn open(vectors_path: &Path, dim: usize) -> OperationResult<Self> {
+        let vector_bytes = dim * size_of::<T>();
+        if vector_bytes == 0 {
+            return Err(OperationError::service_error(
+                "Vector byte size is zero when writing mmap".to_string(),
+            ));
+        }
+
+        let is_encrypted_file =
+            vectors_path.exists() && encryption::is_encrypted_file(vectors_path)?;
+        if encryption::is_enabled() || is_encrypted_file {
+            Self::open_copy(vectors_path, dim)
+        } else {
+            Self::open_append(vectors_path, dim)
+        }
+    }
+
+    fn open_append(vectors_path: &Path, dim: usize) -> OperationResult<Self> {
+        ensure_mmap_file_size(vectors_path, vectors_header::<T>(), None)
+            .describe("Create mmap data file")?;
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(vectors_path)?;
+
+        let mut header = [0; HEADER_SIZE];
+        file.read_exact(&mut header)?;
+        let original_len = file.seek(SeekFrom::End(0))?;
+        let num_vectors = Self::check_vectors_file(vectors_path, &header, original_len, dim)?;
+
+        Ok(Self {
+            writer: Some(EncryptingWriter::new(BufWriter::new(file))?),
+            vectors_path: vectors_path.to_owned(),
+            target: WriteTarget::Append { original_len },
+            dim,
+            num_vectors,
+            _phantom: PhantomData,
+        })
+    }
+
+    fn open_copy(vectors_path: &Path, dim: usize) -> OperationResult<Self> {
+        let tmp_path = vectors_path.with_extension("writing");
+        let file = File::create(&tmp_path)?;
+        // Copy is removed on drop, also if it turns out to be invalid
+        let mut writer = Self {
+            writer: None,
+            vectors_path: vectors_path.to_owned(),
+            target: WriteTarget::Copy { tmp_path },
+            dim,
+            num_vectors: 0,
+            _phantom: PhantomData,
+        };
+        let mut copy = EncryptingWriter::new(BufWriter::new(file))?;
+
+        if vectors_path.exists() {
+            // Existing vectors are copied as plaintext, decrypted if the file is encrypted
+            let existing = mmap::open_read_mmap(
+                vectors_path,
+                AdviceSetting::Advice(Advice::Sequential),
+                false,
+            )?;
+            let header = existing.get(..HEADER_SIZE).unwrap_or_default();
+            writer.num_vectors =
+                Self::check_vectors_file(vectors_path, header, existing.len() as u64, dim)?;
+            copy.write_all(&existing)?;
+        } else {
+            copy.write_all(vectors_header::<T>())?;
+        }
+
+        writer.writer = Some(copy);
+        Ok(writer)
+    }
+
+    /// Check header and length of an existing data file, returns the number of vectors in it.
+    fn check_vectors_file(
+        vectors_path: &Path,
+        header: &[u8],
+        file_len: u64,
+        dim: usize,
+    ) -> OperationResult<usize> {
+        if !is_vectors_header::<T>(header) {
+            return Err(OperationError::service_error(format!(
+                "Invalid mmap vectors file {} header, expected {:?}",
+                vectors_path.display(),
+                vectors_header::<T>(),
+            )));
+        }
+
+        let vector_bytes = dim * size_of::<T>();
+        let payload_len = file_len as usize - HEADER_SIZE;
+        if !payload_len.is_multiple_of(vector_bytes) {
+            return Err(OperationError::service_error(format!(
+                "Invalid mmap vectors file {} size {file_len}, expected header + N * {vector_bytes}",
+                vectors_path.display(),
+            )));
+        }
+        Ok(payload_len / vector_bytes)
+    }
+
+    /// Number of vectors in the file, including the ones written so far.
+    pub fn num_vectors(&self) -> usize {
+        self.num_vectors
+    }
+
+    pub fn push(&mut self, vector: &[T]) -> OperationResult<()> {
+        if vector.len() != self.dim {
+            return Err(OperationError::WrongVectorDimension {
+                expected_dim: self.dim,
+                received_dim: vector.len(),
+            });
+        }
+        let writer = self
+            .writer
+            .as_mut()
+            .expect("writer is only taken on finish");
+        write_vector_le(writer, vector)?;
+        self.num_vectors += 1;
+        Ok(())
+    }
+
+    /// Persist the written vectors, replace the data file with the copy if there is one.
+    ///
+    /// Returns the number of vectors in the file.
+    pub fn finish(mut self) -> OperationResult<usize> {
+        let writer = self.writer.take().expect("writer is only taken on finish");
+        let file = writer
+            .finish()?
+            .into_inner()
+            .map_err(io::IntoInnerError::into_error)?;
+
+        // Explicitly fsync file contents to ensure durability
+        file.sync_all()?;
+        let file_len = file.metadata()?.len();
+        drop(file);
+
+        match &mut self.target {
+            // Written vectors are kept on drop
+            WriteTarget::Append { original_len } => *original_len = file_len,
+            WriteTarget::Copy { tmp_path } => fs::rename(tmp_path, &self.vectors_path)?,
+        }
+        sync_parent_dir(&self.vectors_path)?;
+        Ok(self.num_vectors)
+    }
+}
+
+impl<T: PrimitiveVectorElement + MmapEndianConvertible> Drop for MmapDenseVectorsWriter<T> {
+    fn drop(&mut self) {
+        // Close the file before undoing writes
+        self.writer.take();
+        match &self.target {
+            WriteTarget::Append { original_len } => {
+                let truncated = fs::metadata(&self.vectors_path).and_then(|metadata| {
+                    if metadata.len() <= *original_len {
+                        return Ok(());
+                    }
+                    let file = OpenOptions::new().write(true).open(&self.vectors_path)?;
+                    file.set_len(*original_len)?;
+                    file.sync_all()
+                });
+                if let Err(err) = truncated {
+                    log::warn!(
+                        "Failed to truncate unfinished vectors file {}: {err}",
+                        self.vectors_path.display(),
+                    );
+                }
+            }
+            WriteTarget::Copy { tmp_path } => {
+                if !tmp_path.exists() {
+                    return;
+                }
+                if let Err(err) = fs::remove_file(tmp_path) {
+                    log::warn!(
+                        "Failed to remove unfinished vectors file {}: {err}",
+                        tmp_path.display(),
+                    );
+                }
+            }
+        }
+    }
+}
+
+/// Write vector in the canonical little-endian encoding of the data file.
+pub(super) fn write_vector_le<T: PrimitiveVectorElement + MmapEndianConvertible>(
+    writer: &mut impl Write,
+    vector: &[T],
+) -> io::Result<()> {
+    if cfg!(target_endian = "little") {
+        return writer.write_all(vector.as_bytes());
+    }
+
+    // Encode through a buffer on stack, to not allocate for each vector
+    let mut encoded = [T::default(); 64];
+    for chunk in vector.chunks(encoded.len()) {
+        let encoded = &mut encoded[..chunk.len()];
+        for (encoded, value) in encoded.iter_mut().zip(chunk) {
+            *encoded = value.to_le_storage();
+        }
+        writer.write_all(encoded.as_bytes())?;
+    }
+    Ok(())
+}
+
+/// Byte ranges covering vectors at sorted `offsets`, each `vector_size` bytes long.
+///
+/// Vectors less than a page apart are merged into one range.
+#[cfg(unix)]
+fn prefetch_ranges(offsets: &[usize], vector_size: usize) -> Vec<(usize, usize)> {
+    let mut ranges: Vec<(usize, usize)> = Vec::new();
+    for &offset in offsets {
+        let end = offset + vector_size;
+        match ranges.last_mut() {
+            Some((_, last_end)) if offset <= *last_end + PAGE_SIZE_BYTES => {
+                *last_end = (*last_end).max(end);
+            }
+            _ => ranges.push((offset, end)),
+        }
+    }
+    ranges
 }
 
 /// Ensure the given mmap file exists and is the given size
@@ -408,35 +1157,313 @@ fn ensure_mmap_file_size(path: &Path, header: &[u8], size: Option<u64>) -> Opera
 /// Get start position of flags `BitSlice` in deleted mmap.
 #[inline]
 const fn deleted_mmap_data_start() -> usize {
+    DELETED_HEADER_SIZE.div_ceil(DELETED_LAYOUT_BLOCK_BYTES) * DELETED_LAYOUT_BLOCK_BYTES
+}
+
+/// Get start position of flags `BitSlice` in deleted mmap with a legacy header.
+#[inline]
+const fn legacy_deleted_mmap_data_start() -> usize {
     HEADER_SIZE.div_ceil(DELETED_LAYOUT_BLOCK_BYTES) * DELETED_LAYOUT_BLOCK_BYTES
 }
 
+/// Path of the migrated copy of the deleted mmap file at `deleted_path`, if it has the legacy
+/// header.
+pub fn migrated_deleted_path(deleted_path: &Path) -> PathBuf {
+    deleted_path.with_extension(MIGRATED_DELETED_EXTENSION)
+}
+
+/// Migrate the deleted mmap file with a legacy header at `path` into a copy in the current layout.
+///
+/// The legacy file is kept as is, so that a downgraded node can still open it. The copy records a
+/// checksum of the legacy flags. If they no longer match, a downgraded node changed the legacy file
+/// since, and the copy is stale. It is migrated again then.
+///
+/// Returns the path of the migrated copy.
+fn migrate_legacy_deleted_file(path: &Path) -> OperationResult<PathBuf> {
+    let data = fs_err::read(path)?;
+    let flags = data
+        .get(legacy_deleted_mmap_data_start()..)
+        .unwrap_or_default();
+    let legacy_checksum = seahash::hash(flags);
+
+    let migrated_path = migrated_deleted_path(path);
+    if migrated_path.exists() {
+        let header = DeletedHeader::open(&migrated_path)?;
+        if header.meta.legacy_checksum == legacy_checksum {
+            return Ok(migrated_path);
+        }
+        log::warn!(
+            "Migrated mmap deleted file {} is stale, {} was changed since, migrating again",
+            migrated_path.display(),
+            path.display(),
+        );
+    }
+
+    // The legacy header doesn't persist the deleted count, so it is counted once here
+    let meta = DeletedMeta {
+        deleted_count: flags.iter().map(|byte| u64::from(byte.count_ones())).sum(),
+        generation: 0,
+        legacy_checksum,
+    };
+
+    // Write into temporary file first, and atomically replace a stale copy
+    let tmp_path = migrated_path.with_extension("migrating");
+    let mut file = File::create(&tmp_path)?;
+    file.write_all(&meta.to_bytes())?;
+    file.write_all(&[0; deleted_mmap_data_start() - DELETED_HEADER_SIZE])?;
+    file.write_all(flags)?;
+    file.sync_all()?;
+    fs_err::rename(&tmp_path, &migrated_path)?;
+    sync_parent_dir(&migrated_path)?;
+
+    log::debug!(
+        "Migrated mmap deleted file {} into {}",
+        path.display(),
+        migrated_path.display(),
+    );
+    Ok(migrated_path)
+}
+
+/// Whether the deleted mmap file at `path` exists and has the legacy header.
+fn is_legacy_deleted_file(path: &Path) -> OperationResult<bool> {
+    if !path.exists() {
+        return Ok(false);
+    }
+
+    let mut header = [0; HEADER_SIZE];
+    match File::open(path)?.read_exact(&mut header) {
+        Ok(()) => Ok(&header == LEGACY_DELETED_HEADER),
+        Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => Ok(false),
+        Err(err) => Err(err.into()),
+    }
+}
+
 /// Calculate size for deleted mmap to hold the given number of vectors.
 ///
 /// The mmap will hold a file header and an aligned `BitSlice`.
 fn deleted_mmap_size(num: usize) -> usize {
-    let num_bytes = num.div_ceil(8);
-    let data_size = num_bytes.next_multiple_of(DELETED_LAYOUT_BLOCK_BYTES);
-    deleted_mmap_data_start() + data_size
+    deleted_mmap_data_start() + deleted_flags_size(num)
+}
+
+#[inline]
+fn deleted_flags_size(num: usize) -> usize {
+    num.div_ceil(8).next_multiple_of(DELETED_LAYOUT_BLOCK_BYTES)
 }
 
 #[cfg(test)]
 mod tests {
     use std::io::Write as _;
 
-    use fs_err as fs;
     use tempfile::Builder;
 
     use super::*;
-    use crate::data_types::vectors::VectorElementType;
+    use crate::data_types::vectors::{VectorElementType, VectorElementTypeByte};
 
     #[test]
     fn test_deleted_mmap_layout_is_fixed_width() {
-        assert_eq!(deleted_mmap_data_start(), 8);
-        assert_eq!(deleted_mmap_size(0), 8);
-        assert_eq!(deleted_mmap_size(1), 16);
-        assert_eq!(deleted_mmap_size(64), 16);
-        assert_eq!(deleted_mmap_size(65), 24);
+        assert_eq!(legacy_deleted_mmap_data_start(), 8);
+        assert_eq!(deleted_mmap_data_start(), 32);
+        assert_eq!(deleted_mmap_size(0), 32);
+        assert_eq!(deleted_mmap_size(1), 40);
+        assert_eq!(deleted_mmap_size(64), 40);
+        assert_eq!(deleted_mmap_size(65), 48);
+    }
+
+    fn open_vectors(
+        vectors_path: &Path,
+        deleted_path: &Path,
+    ) -> MmapDenseVectors<VectorElementType> {
+        MmapDenseVectors::open(
+            vectors_path,
+            deleted_path,
+            1,
+            false,
+            false,
+            AdviceSetting::Global,
+            false,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_deleted_count_persisted_in_header() {
+        let dir = Builder::new().prefix("storage_dir").tempdir().unwrap();
+        let vectors_path = dir.path().join("data.mmap");
+        let deleted_path = dir.path().join("drop.mmap");
+
+        let mut data = VECTORS_HEADER.to_vec();
+        data.extend((0..100).flat_map(|i| (i as f32).to_le_bytes()));
+        fs::write(&vectors_path, data).unwrap();
+
+        let mut vectors = open_vectors(&vectors_path, &deleted_path);
+        let meta = DeletedMeta::from_bytes(&fs::read(&deleted_path).unwrap());
+        assert!(meta.is_clean());
+        for key in [1, 42, 99] {
+            assert!(vectors.delete(key).unwrap());
+        }
+        assert!(!vectors.delete(42).unwrap());
+        let meta = DeletedMeta::from_bytes(&fs::read(&deleted_path).unwrap());
+        assert!(!meta.is_clean());
+        drop(vectors);
+
+        let meta = DeletedMeta::from_bytes(&fs::read(&deleted_path).unwrap());
+        assert_eq!(
+            meta,
+            DeletedMeta {
+                deleted_count: 3,
+                generation: 2,
+                legacy_checksum: 0,
+            },
+        );
+
+        let vectors = open_vectors(&vectors_path, &deleted_path);
+        assert_eq!(vectors.deleted_count, 3);
+        assert!(vectors.is_deleted_vector(42));
+    }
+
+    #[test]
+    fn test_open_recounts_unclean_deleted_header() {
+        let dir = Builder::new().prefix("storage_dir").tempdir().unwrap();
+        let vectors_path = dir.path().join("data.mmap");
+        let deleted_path = dir.path().join("drop.mmap");
+
+        let mut data = VECTORS_HEADER.to_vec();
+        data.extend((0..8).flat_map(|i| (i as f32).to_le_bytes()));
+        fs::write(&vectors_path, data).unwrap();
+
+        // Copied while in use, persisted count is stale
+        let meta = DeletedMeta {
+            deleted_count: 5,
+            generation: 7,
+            legacy_checksum: 0,
+        };
+        let mut data = meta.to_bytes().to_vec();
+        data.extend([0b101, 0, 0, 0, 0, 0, 0, 0]);
+        fs::write(&deleted_path, data).unwrap();
+
+        let vectors = open_vectors(&vectors_path, &deleted_path);
+        assert_eq!(vectors.deleted_count, 2);
+        drop(vectors);
+
+        let meta = DeletedMeta::from_bytes(&fs::read(&deleted_path).unwrap());
+        assert_eq!(
+            meta,
+            DeletedMeta {
+                deleted_count: 2,
+                generation: 8,
+                legacy_checksum: 0,
+            },
+        );
+    }
+
+    #[test]
+    fn test_flusher_keeps_header_stale_after_later_changes() {
+        let dir = Builder::new().prefix("storage_dir").tempdir().unwrap();
+        let vectors_path = dir.path().join("data.mmap");
+        let deleted_path = dir.path().join("drop.mmap");
+
+        let mut data = VECTORS_HEADER.to_vec();
+        data.extend((0..8).flat_map(|i| (i as f32).to_le_bytes()));
+        fs::write(&vectors_path, data).unwrap();
+
+        let mut vectors = open_vectors(&vectors_path, &deleted_path);
+        assert!(vectors.delete(1).unwrap());
+        let flusher = vectors.flusher();
+        assert!(vectors.delete(2).unwrap());
+        flusher().unwrap();
+
+        let meta = DeletedMeta::from_bytes(&fs::read(&deleted_path).unwrap());
+        assert!(!meta.is_clean());
+
+        vectors.flusher()().unwrap();
+        let meta = DeletedMeta::from_bytes(&fs::read(&deleted_path).unwrap());
+        assert_eq!(
+            meta,
+            DeletedMeta {
+                deleted_count: 2,
+                generation: 2,
+                legacy_checksum: 0,
+            },
+        );
+    }
+
+    /// Write vectors and deleted flags with a legacy header, as kept by existing segments.
+    fn write_legacy_deleted_files(vectors_path: &Path, deleted_path: &Path) -> Vec<u8> {
+        let mut data = VECTORS_HEADER.to_vec();
+        data.extend((0..10).flat_map(|i| (i as f32).to_le_bytes()));
+        fs::write(vectors_path, data).unwrap();
+
+        let mut data = LEGACY_DELETED_HEADER.to_vec();
+        data.extend([0; 4]);
+        data.extend([0b1000_0010, 0b10, 0, 0, 0, 0, 0, 0]);
+        fs::write(deleted_path, &data).unwrap();
+        data
+    }
+
+    #[test]
+    fn test_open_migrates_legacy_deleted_header_into_copy() {
+        let dir = Builder::new().prefix("storage_dir").tempdir().unwrap();
+        let vectors_path = dir.path().join("data.mmap");
+        let deleted_path = dir.path().join("drop.mmap");
+        let legacy_data = write_legacy_deleted_files(&vectors_path, &deleted_path);
+
+        let mut vectors = open_vectors(&vectors_path, &deleted_path);
+        assert_eq!(vectors.deleted_count, 3);
+        assert!(vectors.is_deleted_vector(1));
+        assert!(vectors.is_deleted_vector(7));
+        assert!(vectors.is_deleted_vector(9));
+        assert!(!vectors.is_deleted_vector(2));
+        assert!(vectors.delete(2).unwrap());
+        drop(vectors);
+
+        // Legacy file is kept for downgrades
+        assert_eq!(fs::read(&deleted_path).unwrap(), legacy_data);
+
+        let data = fs::read(migrated_deleted_path(&deleted_path)).unwrap();
+        assert_eq!(data.len(), deleted_mmap_size(10));
+        assert_eq!(
+            DeletedMeta::from_bytes(&data),
+            DeletedMeta {
+                deleted_count: 4,
+                generation: 2,
+                legacy_checksum: seahash::hash(&legacy_data[legacy_deleted_mmap_data_start()..]),
+            },
+        );
+
+        // Unchanged legacy file, the migrated copy is used as is
+        let vectors = open_vectors(&vectors_path, &deleted_path);
+        assert_eq!(vectors.deleted_count, 4);
+        assert!(vectors.is_deleted_vector(2));
+    }
+
+    #[test]
+    fn test_open_migrates_legacy_deleted_header_again_after_downgrade() {
+        let dir = Builder::new().prefix("storage_dir").tempdir().unwrap();
+        let vectors_path = dir.path().join("data.mmap");
+        let deleted_path = dir.path().join("drop.mmap");
+        let mut legacy_data = write_legacy_deleted_files(&vectors_path, &deleted_path);
+
+        let mut vectors = open_vectors(&vectors_path, &deleted_path);
+        assert!(vectors.delete(2).unwrap());
+        drop(vectors);
+
+        // Downgraded node deletes another vector in the legacy file
+        legacy_data[legacy_deleted_mmap_data_start()] |= 0b1000;
+        fs::write(&deleted_path, &legacy_data).unwrap();
+
+        // Migrated copy is stale, flags are migrated from the legacy file again
+        let vectors = open_vectors(&vectors_path, &deleted_path);
+        assert_eq!(vectors.deleted_count, 4);
+        assert!(vectors.is_deleted_vector(3));
+        assert!(!vectors.is_deleted_vector(2));
+        drop(vectors);
+
+        let meta =
+            DeletedMeta::from_bytes(&fs::read(migrated_deleted_path(&deleted_path)).unwrap());
+        assert_eq!(
+            meta.legacy_checksum,
+            seahash::hash(&legacy_data[legacy_deleted_mmap_data_start()..]),
+        );
     }
 
     #[test]
@@ -452,6 +1479,7 @@ mod tests {
             &deleted_path,
             2,
             false,
+            false,
             AdviceSetting::Global,
             false,
         )
@@ -472,6 +1500,7 @@ mod tests {
             &deleted_path,
             2,
             false,
+            false,
             AdviceSetting::Global,
             false,
         )
@@ -496,6 +1525,7 @@ mod tests {
             &deleted_path,
             2,
             false,
+            false,
             AdviceSetting::Global,
             false,
         )
@@ -516,6 +1546,7 @@ mod tests {
             &deleted_path,
             2,
             false,
+            false,
             AdviceSetting::Global,
             false,
         )
@@ -538,6 +1569,7 @@ mod tests {
             &deleted_path,
             2,
             false,
+            false,
             AdviceSetting::Global,
             false,
         )
@@ -565,6 +1597,7 @@ mod tests {
             &deleted_path,
             dim,
             false,
+            false,
             AdviceSetting::Global,
             false,
         )
@@ -572,4 +1605,287 @@ mod tests {
 
         assert_eq!(opened.num_vectors, num_vectors);
     }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_prefetch_ranges_merge_nearby_vectors() {
+        assert_eq!(prefetch_ranges(&[], 16), vec![]);
+        assert_eq!(
+            prefetch_ranges(&[4, 20, 36 + PAGE_SIZE_BYTES, 100_000], 16),
+            vec![(4, 52 + PAGE_SIZE_BYTES), (100_000, 100_016)],
+        );
+    }
+
+    #[test]
+    fn test_streaming_writer_appends_vectors() {
+        let dir = Builder::new().prefix("storage_dir").tempdir().unwrap();
+        let vectors_path = dir.path().join("data.mmap");
+        let deleted_path = dir.path().join("drop.mmap");
+
+        let mut writer =
+            MmapDenseVectorsWriter::<VectorElementType>::open(&vectors_path, 2).unwrap();
+        writer.push(&[1.0, 2.0]).unwrap();
+        writer.push(&[3.0, 4.0]).unwrap();
+        assert!(writer.push(&[5.0]).is_err());
+        assert_eq!(writer.finish().unwrap(), 2);
+
+        // Unfinished writer truncates the file back, vectors are appended in place
+        let finished = fs::read(&vectors_path).unwrap();
+        let mut writer =
+            MmapDenseVectorsWriter::<VectorElementType>::open(&vectors_path, 2).unwrap();
+        for _ in 0..10_000 {
+            writer.push(&[7.0, 8.0]).unwrap();
+        }
+        assert!(fs::metadata(&vectors_path).unwrap().len() > finished.len() as u64);
+        assert!(!vectors_path.with_extension("writing").exists());
+        drop(writer);
+        assert_eq!(fs::read(&vectors_path).unwrap(), finished);
+
+        // Continue writing into existing file
+        let mut writer =
+            MmapDenseVectorsWriter::<VectorElementType>::open(&vectors_path, 2).unwrap();
+        assert_eq!(writer.num_vectors(), 2);
+        writer.push(&[5.0, -6.5]).unwrap();
+        assert_eq!(writer.finish().unwrap(), 3);
+
+        let bytes = fs::read(&vectors_path).unwrap();
+        assert_eq!(
+            bytes.len(),
+            HEADER_SIZE + 3 * 2 * size_of::<VectorElementType>()
+        );
+        assert_eq!(&bytes[HEADER_SIZE + 20..], &(-6.5f32).to_le_bytes());
+
+        let opened = MmapDenseVectors::<VectorElementType>::open(
+            &vectors_path,
+            &deleted_path,
+            2,
+            false,
+            false,
+            AdviceSetting::Global,
+            false,
+        )
+        .unwrap();
+        assert_eq!(opened.num_vectors, 3);
+        assert_eq!(opened.get_vector::<Random>(0), &[1.0, 2.0]);
+        assert_eq!(opened.get_vector::<Random>(2), &[5.0, -6.5]);
+    }
+
+    #[test]
+    fn test_read_ahead_of_sequential_batches() {
+        let dir = Builder::new().prefix("storage_dir").tempdir().unwrap();
+        let vectors_path = dir.path().join("data.mmap");
+        let deleted_path = dir.path().join("drop.mmap");
+
+        let dim = 4;
+        let mut writer =
+            MmapDenseVectorsWriter::<VectorElementType>::open(&vectors_path, dim).unwrap();
+        for i in 0..10_000 {
+            writer.push(&[i as f32; 4]).unwrap();
+        }
+        writer.finish().unwrap();
+
+        let opened = MmapDenseVectors::<VectorElementType>::open(
+            &vectors_path,
+            &deleted_path,
+            dim,
+            false,
+            false,
+            AdviceSetting::Global,
+            false,
+        )
+        .unwrap();
+        let raw_size = opened.raw_size();
+        let window = READ_AHEAD_BATCHES * VECTOR_READ_BATCH_SIZE * raw_size;
+        let batch = |start: usize| {
+            let keys: Vec<_> = (start..start + VECTOR_READ_BATCH_SIZE)
+                .map(|key| key as PointOffsetType)
+                .collect();
+            let mut scored = 0;
+            opened
+                .for_each_in_batch(&keys, |i, vector| {
+                    assert_eq!(vector[0], (start + i) as f32);
+                    scored += 1;
+                })
+                .unwrap();
+            assert_eq!(scored, VECTOR_READ_BATCH_SIZE);
+            opened.read_ahead_until.load(Ordering::Relaxed)
+        };
+
+        let batch_end = |start: usize| HEADER_SIZE + (start + VECTOR_READ_BATCH_SIZE) * raw_size;
+        assert_eq!(batch(0), batch_end(0) + window);
+
+        // Not requested again while far enough ahead
+        let step = VECTOR_READ_BATCH_SIZE;
+        assert_eq!(batch(step), batch_end(0) + window);
+
+        // Extended once scoring gets close to the end of the range
+        let close = step * (READ_AHEAD_BATCHES / 2 + 1);
+        assert_eq!(batch(close), batch_end(close) + window);
+
+        // Capped by the end of the file
+        assert_eq!(batch(10_000 - 2 * step), HEADER_SIZE + 10_000 * raw_size);
+
+        // Random batches don't read ahead
+        opened.for_each_in_batch(&[1, 5_000], |_, _| {}).unwrap();
+        assert_eq!(
+            opened.read_ahead_until.load(Ordering::Relaxed),
+            HEADER_SIZE + 10_000 * raw_size,
+        );
+    }
+
+    #[test]
+    fn test_open_compressed_vectors() {
+        let dir = Builder::new().prefix("storage_dir").tempdir().unwrap();
+        let vectors_path = dir.path().join("data.mmap");
+        let deleted_path = dir.path().join("drop.mmap");
+
+        let mut writer =
+            MmapDenseVectorsWriter::<VectorElementType>::open(&vectors_path, 2).unwrap();
+        for i in 0..1000 {
+            writer.push(&[i as f32, -(i as f32)]).unwrap();
+        }
+        writer.finish().unwrap();
+
+        compress_vectors_file::<VectorElementType>(&vectors_path, 2).unwrap();
+        // Compressing again is a no-op
+        compress_vectors_file::<VectorElementType>(&vectors_path, 2).unwrap();
+
+        let opened = MmapDenseVectors::<VectorElementType>::open(
+            &vectors_path,
+            &deleted_path,
+            2,
+            true,
+            false,
+            AdviceSetting::Global,
+            false,
+        )
+        .unwrap();
+        assert_eq!(opened.num_vectors, 1000);
+        assert!(!opened.has_async_reader());
+        assert_eq!(
+            opened.get_vector_cow::<Random>(999).unwrap().as_ref(),
+            &[999.0, -999.0],
+        );
+        assert!(opened.get_vector_cow_opt::<Random>(1000).unwrap().is_none());
+        assert!(opened.get_vector_cow::<Random>(1000).is_err());
+
+        let mut batch = vec![];
+        opened
+            .for_each_in_batch(&[1, 2], |_, vector| batch.push(vector.to_vec()))
+            .unwrap();
+        assert_eq!(batch, vec![vec![1.0, -1.0], vec![2.0, -2.0]]);
+
+        let mut read = vec![];
+        opened
+            .read_vectors_async([3, 500].into_iter(), |_, key, vector| {
+                read.push((key, vector.to_vec()));
+            })
+            .unwrap();
+        assert_eq!(read, vec![(3, vec![3.0, -3.0]), (500, vec![500.0, -500.0])]);
+    }
+
+    #[test]
+    fn test_read_vectors_direct_io() {
+        let dir = Builder::new().prefix("storage_dir").tempdir().unwrap();
+        let vectors_path = dir.path().join("data.mmap");
+        let deleted_path = dir.path().join("drop.mmap");
+
+        // Vectors of 12 bytes are not aligned to blocks, some of them span over two blocks
+        let dim = 3;
+        let num_vectors = 100_000u32;
+        let mut raw = Vec::new();
+        raw.extend_from_slice(VECTORS_HEADER);
+        for i in 0..num_vectors * dim as u32 {
+            raw.extend_from_slice(&(i as f32).to_le_bytes());
+        }
+        fs::write(&vectors_path, raw).unwrap();
+
+        // Falls back to mmap if the filesystem refuses O_DIRECT, results must be the same
+        let opened = MmapDenseVectors::<VectorElementType>::open(
+            &vectors_path,
+            &deleted_path,
+            dim,
+            false,
+            true,
+            AdviceSetting::Global,
+            false,
+        )
+        .unwrap();
+
+        let sequential = 0..num_vectors;
+        let backward = (0..num_vectors).rev().step_by(7);
+        let sparse = (0..num_vectors).step_by(1000);
+        let points: Vec<_> = sequential.chain(backward).chain(sparse).collect();
+
+        let mut read_count = 0;
+        opened
+            .read_vectors_async(points.iter().copied(), |idx, point, vector| {
+                assert_eq!(points[idx], point);
+                let first = (point * dim as u32) as f32;
+                assert_eq!(vector, &[first, first + 1.0, first + 2.0]);
+                read_count += 1;
+            })
+            .unwrap();
+        assert_eq!(read_count, points.len());
+    }
+
+    #[test]
+    fn test_byte_vectors_header() {
+        let dir = Builder::new().prefix("storage_dir").tempdir().unwrap();
+        let vectors_path = dir.path().join("data.mmap");
+        let deleted_path = dir.path().join("drop.mmap");
+        let open = |dim| {
+            MmapDenseVectors::<VectorElementTypeByte>::open(
+                &vectors_path,
+                &deleted_path,
+                dim,
+                false,
+                false,
+                AdviceSetting::Global,
+                false,
+            )
+        };
+
+        let mut writer =
+            MmapDenseVectorsWriter::<VectorElementTypeByte>::open(&vectors_path, 4).unwrap();
+        writer.push(&[1, 2, 3, 4]).unwrap();
+        writer.push(&[5, 6, 7, 255]).unwrap();
+        assert_eq!(writer.finish().unwrap(), 2);
+        assert_eq!(
+            &fs::read(&vectors_path).unwrap()[..HEADER_SIZE],
+            BYTE_VECTORS_HEADER
+        );
+
+        let opened = open(4).unwrap();
+        assert_eq!(opened.num_vectors, 2);
+        assert_eq!(opened.get_vector::<Random>(1), &[5, 6, 7, 255]);
+        drop(opened);
+
+        // Payload length is validated like for other types
+        assert!(
+            open(3)
+                .unwrap_err()
+                .to_string()
+                .contains("expected header + N")
+        );
+
+        // Byte vectors are never read as vectors of another type with a matching size
+        let err = MmapDenseVectors::<VectorElementType>::open(
+            &vectors_path,
+            &deleted_path,
+            1,
+            false,
+            false,
+            AdviceSetting::Global,
+            false,
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("Invalid mmap vectors file"));
+
+        // Files with the untyped header are still accepted
+        let mut data = VECTORS_HEADER.to_vec();
+        data.extend([9, 8, 7, 6]);
+        fs::write(&vectors_path, data).unwrap();
+        assert_eq!(open(4).unwrap().get_vector::<Random>(0), &[9, 8, 7, 6]);
+    }
 }
@@ -1,4 +1,6 @@
 pub mod appendable_dense_vector_storage;
+pub mod compressed_dense_vectors;
+pub mod hot_tier;
 pub mod memmap_dense_vector_storage;
 pub mod mmap_dense_vectors;
 #[cfg(feature = "rocksdb")]
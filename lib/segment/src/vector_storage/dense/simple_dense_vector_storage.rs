@@ -120,6 +120,9 @@ pub fn open_simple_dense_vector_storage(
             distance,
             stopped,
         ),
+        VectorStorageDatatype::Bfloat16 => Err(OperationError::ValidationError {
+            description: "Bfloat16 datatype is not supported by RocksDB vector storage".to_string(),
+        }),
     }
 }
 
@@ -234,8 +237,8 @@ impl<T: PrimitiveVectorElement> DenseVectorStorage<T> for SimpleDenseVectorStora
         self.dim
     }
 
-    fn get_dense<P: AccessPattern>(&self, key: PointOffsetType) -> &[T] {
-        self.vectors.get(key as VectorOffsetType)
+    fn get_dense<P: AccessPattern>(&self, key: PointOffsetType) -> Cow<'_, [T]> {
+        Cow::Borrowed(self.vectors.get(key as VectorOffsetType))
     }
 }
 
@@ -0,0 +1,127 @@
+use std::fmt;
+use std::marker::PhantomData;
+use std::ops::Range;
+use std::path::Path;
+
+use common::fs::{AlignedBuffer, DIRECT_IO_ALIGNMENT, open_direct, read_direct};
+use common::types::PointOffsetType;
+use fs_err::File;
+use zerocopy::FromBytes as _;
+
+use crate::common::operation_error::{OperationError, OperationResult};
+use crate::data_types::primitive::PrimitiveVectorElement;
+
+/// Size of the read-ahead buffer of [`DirectReader`].
+const DIRECT_READ_BUFFER_SIZE: usize = 1024 * 1024;
+
+/// Reads vectors from a file with `O_DIRECT`, instead of a memory map.
+///
+/// Reads bypass the page cache, so scanning a storage much larger than RAM doesn't evict pages
+/// of other storages and indexes. Points read in ascending order, like in full scans, are read
+/// ahead in large aligned chunks. Other points are read with the smallest aligned read.
+pub struct DirectReader<T: PrimitiveVectorElement> {
+    file: File,
+    file_len: usize,
+    buffer: AlignedBuffer,
+    /// Range of the file, which is currently in `buffer`
+    loaded: Range<usize>,
+    raw_size: usize,
+    header_size: usize,
+    _phantom: PhantomData<T>,
+}
+
+impl<T: PrimitiveVectorElement> fmt::Debug for DirectReader<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DirectReader")
+            .field("file", &self.file)
+            .field("file_len", &self.file_len)
+            .field("loaded", &self.loaded)
+            .field("raw_size", &self.raw_size)
+            .field("header_size", &self.header_size)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T: PrimitiveVectorElement> DirectReader<T> {
+    /// Fails if the filesystem doesn't support `O_DIRECT`.
+    pub fn open(path: &Path, raw_size: usize, header_size: usize) -> OperationResult<Self> {
+        let file = open_direct(path)?;
+        let file_len = file.metadata()?.len() as usize;
+        // Unaligned vector may span over an extra block
+        let buffer_size = DIRECT_READ_BUFFER_SIZE.max(raw_size + 2 * DIRECT_IO_ALIGNMENT);
+
+        let mut reader = Self {
+            file,
+            file_len,
+            buffer: AlignedBuffer::new(buffer_size),
+            loaded: 0..0,
+            raw_size,
+            header_size,
+            _phantom: PhantomData,
+        };
+
+        // Some filesystems accept `O_DIRECT` when opening a file, but refuse reads
+        reader.load(0..header_size.min(file_len), false)?;
+
+        Ok(reader)
+    }
+
+    /// Takes in iterator of point offsets, reads it, and yields a callback with the read data.
+    pub fn read_stream(
+        &mut self,
+        points: impl IntoIterator<Item = PointOffsetType>,
+        mut callback: impl FnMut(usize, PointOffsetType, &[T]),
+    ) -> OperationResult<()> {
+        for (idx, point) in points.into_iter().enumerate() {
+            let start = self.header_size + self.raw_size * point as usize;
+            let range = start..start + self.raw_size;
+            if range.end > self.file_len {
+                return Err(OperationError::service_error(format!(
+                    "Vector {point} is out of bounds of {:?}",
+                    self.file,
+                )));
+            }
+
+            if range.start < self.loaded.start || range.end > self.loaded.end {
+                let is_forward = range.start >= self.loaded.start
+                    && range.start < self.loaded.end + self.buffer.len();
+                self.load(range.clone(), is_forward)?;
+            }
+
+            let bytes = &self.buffer.as_slice()
+                [range.start - self.loaded.start..range.end - self.loaded.start];
+            let vector = <[T]>::ref_from_bytes(bytes).map_err(|err| {
+                OperationError::service_error(format!("Failed to read vector {point}: {err}"))
+            })?;
+            callback(idx, point, vector);
+        }
+        Ok(())
+    }
+
+    /// Read an aligned part of the file, which contains `range`, into the buffer.
+    ///
+    /// If `read_ahead` is set, the whole buffer is filled, otherwise only the aligned blocks
+    /// covering `range` are read.
+    fn load(&mut self, range: Range<usize>, read_ahead: bool) -> OperationResult<()> {
+        let start = range.start - range.start % DIRECT_IO_ALIGNMENT;
+        let end = if read_ahead {
+            start + self.buffer.len()
+        } else {
+            range.end.next_multiple_of(DIRECT_IO_ALIGNMENT)
+        }
+        .min(self.file_len);
+
+        self.loaded = 0..0;
+        let len = (end - start).next_multiple_of(DIRECT_IO_ALIGNMENT);
+        let read = read_direct(&self.file, &mut self.buffer, start as u64, len)?;
+        if start + read < end {
+            return Err(OperationError::service_error(format!(
+                "Direct read of {:?} at {start} returned {read} bytes, expected {}",
+                self.file,
+                end - start,
+            )));
+        }
+        self.loaded = start..start + read;
+        Ok(())
+    }
+}
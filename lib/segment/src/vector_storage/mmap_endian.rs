@@ -1,4 +1,4 @@
-use half::f16;
+use half::{bf16, f16};
 
 pub trait MmapEndianConvertible: Copy + Sized {
     fn to_le_storage(self) -> Self;
@@ -37,6 +37,8 @@ macro_rules! impl_int_mmap_endian {
     };
 }
 
+// Single bytes have no byte order, canonicalization of `u8` vectors is a no-op on all targets.
+// `u8` vectors are never copied for decoding on big-endian targets for that reason.
 impl_identity_mmap_endian!(u8);
 impl_int_mmap_endian!(u16);
 impl_int_mmap_endian!(u32);
@@ -66,6 +68,18 @@ impl MmapEndianConvertible for f16 {
     }
 }
 
+impl MmapEndianConvertible for bf16 {
+    #[inline]
+    fn to_le_storage(self) -> Self {
+        bf16::from_bits(self.to_bits().to_le())
+    }
+
+    #[inline]
+    fn from_le_storage(stored: Self) -> Self {
+        bf16::from_bits(u16::from_le(stored.to_bits()))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::MmapEndianConvertible;
@@ -86,4 +100,16 @@ mod tests {
         let decoded = f32::from_le_storage(stored);
         assert_eq!(decoded.to_bits(), value.to_bits());
     }
+
+    #[test]
+    fn test_bf16_roundtrip() {
+        let value = half::bf16::from_f32(-1.5e30);
+        let stored = value.to_le_storage();
+        assert_eq!(
+            stored.to_bits().to_ne_bytes(),
+            value.to_bits().to_le_bytes()
+        );
+        let decoded = half::bf16::from_le_storage(stored);
+        assert_eq!(decoded.to_bits(), value.to_bits());
+    }
 }
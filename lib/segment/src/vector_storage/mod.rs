@@ -7,14 +7,14 @@ mod async_io;
     target_os = "linux",
     any(target_arch = "x86_64", target_arch = "aarch64")
 )))]
-mod async_io_mock;
-#[cfg(target_os = "linux")]
+mod async_io_batched;
 pub mod async_raw_scorer;
 mod bitvec;
-mod chunked_mmap_vectors;
+pub(crate) mod chunked_mmap_vectors;
 pub mod chunked_vectors;
 pub mod common;
 pub mod dense;
+mod direct_io;
 mod mmap_endian;
 pub mod multi_dense;
 pub mod quantized;
@@ -27,6 +27,6 @@ mod vector_storage_base;
 #[cfg(test)]
 mod tests;
 
-pub use chunked_mmap_vectors::ChunkedMmapVectors;
+pub use chunked_mmap_vectors::{ChunkedMmapVectors, verify_chunk_file};
 pub use raw_scorer::*;
 pub use vector_storage_base::*;
@@ -1,9 +1,12 @@
+use std::io::Write as _;
 use std::ops::Range;
 use std::path::{Path, PathBuf};
 use std::sync::atomic::AtomicBool;
 
 use bitvec::prelude::BitSlice;
 use common::counter::hardware_counter::HardwareCounterCell;
+use common::fs::atomic_save;
+use common::io_throttle;
 use common::mmap::AdviceSetting;
 use common::types::PointOffsetType;
 use fs_err as fs;
@@ -18,8 +21,8 @@ use crate::data_types::vectors::{TypedMultiDenseVectorRef, VectorElementType, Ve
 use crate::types::{Distance, MultiVectorConfig, VectorStorageDatatype};
 use crate::vector_storage::chunked_mmap_vectors::ChunkedMmapVectors;
 use crate::vector_storage::dense::appendable_dense_vector_storage::{
-    open_appendable_memmap_vector_storage_byte, open_appendable_memmap_vector_storage_full,
-    open_appendable_memmap_vector_storage_half,
+    open_appendable_memmap_vector_storage_bf16, open_appendable_memmap_vector_storage_byte,
+    open_appendable_memmap_vector_storage_full, open_appendable_memmap_vector_storage_half,
 };
 use crate::vector_storage::mmap_endian::MmapEndianConvertible;
 use crate::vector_storage::{
@@ -31,6 +34,14 @@ const VECTORS_DIR_PATH: &str = "vectors";
 const OFFSETS_DIR_PATH: &str = "offsets";
 const DELETED_DIR_PATH: &str = "deleted";
 
+/// Header marking offsets as canonical little-endian, stored next to offset chunks
+///
+/// Storages without it were written before offsets were canonicalized, and may be native-endian.
+const OFFSETS_HEADER_FILE: &str = "offsets_header.dat";
+const OFFSETS_HEADER_MAGIC: &[u8; 4] = b"mvof";
+const OFFSETS_HEADER_VERSION: u32 = 1;
+const OFFSETS_HEADER_SIZE: usize = 16;
+
 #[derive(Clone, Copy, Debug, Default, PartialEq)]
 #[repr(C)]
 pub struct MultivectorMmapOffset {
@@ -59,6 +70,131 @@ impl MmapEndianConvertible for MultivectorMmapOffset {
     }
 }
 
+impl MultivectorMmapOffset {
+    fn swap_bytes(self) -> Self {
+        Self {
+            offset: self.offset.swap_bytes(),
+            count: self.count.swap_bytes(),
+            capacity: self.capacity.swap_bytes(),
+        }
+    }
+
+    /// Whether the offset only refers to stored inner vectors
+    fn is_within(&self, vectors_count: usize) -> bool {
+        self.count <= self.capacity
+            && self.offset as usize + self.capacity as usize <= vectors_count
+    }
+}
+
+fn offsets_header_bytes() -> [u8; OFFSETS_HEADER_SIZE] {
+    let mut header = [0u8; OFFSETS_HEADER_SIZE];
+    header[0..4].copy_from_slice(OFFSETS_HEADER_MAGIC);
+    header[4..8].copy_from_slice(&OFFSETS_HEADER_VERSION.to_le_bytes());
+    let element_size = size_of::<MultivectorMmapOffset>() as u32;
+    header[8..12].copy_from_slice(&element_size.to_le_bytes());
+    header
+}
+
+fn validate_offsets_header(bytes: &[u8]) -> OperationResult<()> {
+    if bytes.len() != OFFSETS_HEADER_SIZE {
+        return Err(OperationError::service_error(format!(
+            "Corrupted {OFFSETS_HEADER_FILE}: expected {OFFSETS_HEADER_SIZE} bytes, got {}",
+            bytes.len(),
+        )));
+    }
+    if &bytes[0..4] != OFFSETS_HEADER_MAGIC {
+        return Err(OperationError::service_error(format!(
+            "Corrupted {OFFSETS_HEADER_FILE}: bad magic {:?}",
+            &bytes[0..4],
+        )));
+    }
+
+    let version = u32::from_le_bytes(bytes[4..8].try_into().expect("slice size checked"));
+    if version != OFFSETS_HEADER_VERSION {
+        return Err(OperationError::service_error(format!(
+            "Unsupported {OFFSETS_HEADER_FILE} version: {version}",
+        )));
+    }
+
+    let element_size = u32::from_le_bytes(bytes[8..12].try_into().expect("slice size checked"));
+    if element_size as usize != size_of::<MultivectorMmapOffset>() {
+        return Err(OperationError::service_error(format!(
+            "Unsupported {OFFSETS_HEADER_FILE} element size: {element_size}",
+        )));
+    }
+    Ok(())
+}
+
+/// Validate that all offsets refer to stored inner vectors
+///
+/// Offsets of storages without a header are assumed to be native-endian, as written by older
+/// versions. If they're only valid with swapped bytes, they're rewritten as little-endian. A
+/// header is stored afterwards.
+fn validate_or_migrate_offsets(
+    offsets_path: &Path,
+    offsets: &mut ChunkedMmapVectors<MultivectorMmapOffset>,
+    vectors_count: usize,
+) -> OperationResult<()> {
+    let header_path = offsets_path.join(OFFSETS_HEADER_FILE);
+    let has_header = header_path.exists();
+    if has_header {
+        validate_offsets_header(&fs::read(&header_path)?)?;
+    }
+
+    let stored_offsets = (0..offsets.len())
+        .map(|key| {
+            offsets
+                .get::<Sequential>(key as VectorOffsetType)
+                .and_then(|offset| offset.first().copied())
+                .ok_or_else(|| {
+                    OperationError::service_error(format!(
+                        "Corrupted multivector offsets: missing offset {key}",
+                    ))
+                })
+        })
+        .collect::<OperationResult<Vec<_>>>()?;
+    let invalid_key = stored_offsets
+        .iter()
+        .position(|offset| !offset.is_within(vectors_count));
+
+    if let Some(key) = invalid_key {
+        // Legacy offsets written on a big-endian host, read back as little-endian
+        let is_native_endian = !has_header
+            && cfg!(target_endian = "big")
+            && stored_offsets
+                .iter()
+                .all(|offset| offset.swap_bytes().is_within(vectors_count));
+        if !is_native_endian {
+            let offset = stored_offsets[key];
+            return Err(OperationError::service_error(format!(
+                "Corrupted multivector offsets: offset {key} ({}..{}, count {}) is out of bounds of {vectors_count} vectors",
+                offset.offset,
+                u64::from(offset.offset) + u64::from(offset.capacity),
+                offset.count,
+            )));
+        }
+
+        log::info!(
+            "Migrating native-endian multivector offsets in {}",
+            offsets_path.display(),
+        );
+        let hw_counter = HardwareCounterCell::disposable(); // Internal operation
+        io_throttle::consume(size_of_val(stored_offsets.as_slice()));
+        for (key, offset) in stored_offsets.into_iter().enumerate() {
+            offsets.insert(key as VectorOffsetType, &[offset.swap_bytes()], &hw_counter)?;
+        }
+        offsets.flusher()()?;
+    }
+
+    if !has_header {
+        atomic_save(&header_path, |writer| {
+            writer.write_all(&offsets_header_bytes())?;
+            Ok::<_, OperationError>(())
+        })?;
+    }
+    Ok(())
+}
+
 #[derive(Debug)]
 pub struct AppendableMmapMultiDenseVectorStorage<T: PrimitiveVectorElement + MmapEndianConvertible>
 {
@@ -96,6 +232,10 @@ impl<T: PrimitiveVectorElement + MmapEndianConvertible> AppendableMmapMultiDense
         previous
     }
 
+    fn offsets_header_path(&self) -> PathBuf {
+        self.offsets.directory().join(OFFSETS_HEADER_FILE)
+    }
+
     /// Populate all pages in the mmap.
     /// Block until all pages are populated.
     pub fn populate(&self) -> OperationResult<()> {
@@ -299,6 +439,7 @@ impl<T: PrimitiveVectorElement + MmapEndianConvertible> VectorStorage
     fn files(&self) -> Vec<PathBuf> {
         let mut files = self.vectors.files();
         files.extend(self.offsets.files());
+        files.push(self.offsets_header_path());
         files.extend(self.deleted.files());
         files
     }
@@ -306,6 +447,7 @@ impl<T: PrimitiveVectorElement + MmapEndianConvertible> VectorStorage
     fn immutable_files(&self) -> Vec<PathBuf> {
         let mut files = self.vectors.immutable_files();
         files.extend(self.offsets.immutable_files());
+        files.push(self.offsets_header_path());
         files
     }
 
@@ -356,6 +498,13 @@ pub fn open_appendable_memmap_vector_storage(
             madvise,
             populate,
         ),
+        VectorStorageDatatype::Bfloat16 => open_appendable_memmap_vector_storage_bf16(
+            vector_storage_path,
+            size,
+            distance,
+            madvise,
+            populate,
+        ),
     }
 }
 
@@ -393,6 +542,14 @@ pub fn open_appendable_memmap_multi_vector_storage(
             madvise,
             populate,
         ),
+        VectorStorageDatatype::Bfloat16 => open_appendable_memmap_multi_vector_storage_bf16(
+            path,
+            dim,
+            distance,
+            multi_vector_config,
+            madvise,
+            populate,
+        ),
     }
 }
 
@@ -462,6 +619,28 @@ pub fn open_appendable_memmap_multi_vector_storage_half(
     )))
 }
 
+pub fn open_appendable_memmap_multi_vector_storage_bf16(
+    path: &Path,
+    dim: usize,
+    distance: Distance,
+    multi_vector_config: MultiVectorConfig,
+    madvise: AdviceSetting,
+    populate: bool,
+) -> OperationResult<VectorStorageEnum> {
+    let storage = open_appendable_memmap_multi_vector_storage_impl(
+        path,
+        dim,
+        distance,
+        multi_vector_config,
+        madvise,
+        populate,
+    )?;
+
+    Ok(VectorStorageEnum::MultiDenseAppendableMemmapBf16(Box::new(
+        storage,
+    )))
+}
+
 pub fn open_appendable_memmap_multi_vector_storage_impl<
     T: PrimitiveVectorElement + MmapEndianConvertible,
 >(
@@ -479,7 +658,8 @@ pub fn open_appendable_memmap_multi_vector_storage_impl<
     let deleted_path = path.join(DELETED_DIR_PATH);
 
     let vectors = ChunkedMmapVectors::open(&vectors_path, dim, madvise, Some(populate))?;
-    let offsets = ChunkedMmapVectors::open(&offsets_path, 1, madvise, Some(populate))?;
+    let mut offsets = ChunkedMmapVectors::open(&offsets_path, 1, madvise, Some(populate))?;
+    validate_or_migrate_offsets(&offsets_path, &mut offsets, vectors.len())?;
 
     let deleted = BitvecFlags::new(DynamicMmapFlags::open(&deleted_path, populate)?);
     let deleted_count = deleted.count_trues();
@@ -578,4 +758,124 @@ mod tests {
             "find_storage_files must find same files that storage reports",
         );
     }
+
+    const OFFSETS_TEST_DIM: usize = 4;
+
+    fn open_offsets_test_storage(path: &Path) -> OperationResult<VectorStorageEnum> {
+        open_appendable_memmap_multi_vector_storage_full(
+            path,
+            OFFSETS_TEST_DIM,
+            Distance::Dot,
+            MultiVectorConfig::default(),
+            AdviceSetting::Global,
+            false,
+        )
+    }
+
+    /// Create storage with multivectors of growing size, returns them
+    fn create_offsets_test_storage(path: &Path) -> Vec<MultiDenseVectorInternal> {
+        let mut storage = open_offsets_test_storage(path).unwrap();
+        let hw_counter = HardwareCounterCell::disposable();
+        let multivecs: Vec<_> = (1..=8)
+            .map(|size| {
+                let vectors = (0..size)
+                    .map(|i| vec![(size * 10 + i) as f32; OFFSETS_TEST_DIM])
+                    .collect::<Vec<Vec<_>>>();
+                MultiDenseVectorInternal::try_from(vectors).unwrap()
+            })
+            .collect();
+        for (internal_id, multivec) in multivecs.iter().enumerate() {
+            storage
+                .insert_vector(
+                    internal_id as PointOffsetType,
+                    VectorRef::from(multivec),
+                    &hw_counter,
+                )
+                .unwrap();
+        }
+        storage.flusher()().unwrap();
+        multivecs
+    }
+
+    fn assert_offsets_test_storage(path: &Path, multivecs: &[MultiDenseVectorInternal]) {
+        let storage = open_offsets_test_storage(path).unwrap();
+        for (internal_id, multivec) in multivecs.iter().enumerate() {
+            let stored = storage.get_vector::<Random>(internal_id as PointOffsetType);
+            assert_eq!(stored, CowVector::from(VectorRef::from(multivec)));
+        }
+
+        let header = fs::read(path.join(OFFSETS_DIR_PATH).join(OFFSETS_HEADER_FILE)).unwrap();
+        assert_eq!(header, offsets_header_bytes());
+    }
+
+    fn offsets_chunk_path(path: &Path) -> PathBuf {
+        common::mmap::chunked::chunk_name(&path.join(OFFSETS_DIR_PATH), 0)
+    }
+
+    #[test]
+    fn test_offsets_header() {
+        let dir = Builder::new().prefix("storage_dir").tempdir().unwrap();
+        let multivecs = create_offsets_test_storage(dir.path());
+        assert_offsets_test_storage(dir.path(), &multivecs);
+
+        // Storages without header are accepted if offsets are valid
+        fs::remove_file(dir.path().join(OFFSETS_DIR_PATH).join(OFFSETS_HEADER_FILE)).unwrap();
+        assert_offsets_test_storage(dir.path(), &multivecs);
+
+        // Unsupported header version
+        let mut header = offsets_header_bytes();
+        header[4..8].copy_from_slice(&(OFFSETS_HEADER_VERSION + 1).to_le_bytes());
+        fs::write(
+            dir.path().join(OFFSETS_DIR_PATH).join(OFFSETS_HEADER_FILE),
+            header,
+        )
+        .unwrap();
+        let err = open_offsets_test_storage(dir.path()).unwrap_err();
+        assert!(err.to_string().contains("Unsupported"), "{err}");
+    }
+
+    #[test]
+    fn test_offsets_out_of_bounds() {
+        let dir = Builder::new().prefix("storage_dir").tempdir().unwrap();
+        create_offsets_test_storage(dir.path());
+
+        // Point the last multivector beyond stored inner vectors
+        let chunk_path = offsets_chunk_path(dir.path());
+        let mut bytes = fs::read(&chunk_path).unwrap();
+        let last = 7 * size_of::<MultivectorMmapOffset>();
+        bytes[last..last + 4].copy_from_slice(&1000u32.to_le_bytes());
+        fs::write(&chunk_path, bytes).unwrap();
+
+        let err = open_offsets_test_storage(dir.path()).unwrap_err();
+        assert!(err.to_string().contains("offset 7"), "{err}");
+        assert!(err.to_string().contains("out of bounds"), "{err}");
+    }
+
+    /// Offsets written natively by older versions on big-endian hosts are migrated
+    #[test]
+    fn test_offsets_legacy_native_endian() {
+        let dir = Builder::new().prefix("storage_dir").tempdir().unwrap();
+        let multivecs = create_offsets_test_storage(dir.path());
+
+        fs::remove_file(dir.path().join(OFFSETS_DIR_PATH).join(OFFSETS_HEADER_FILE)).unwrap();
+        let chunk_path = offsets_chunk_path(dir.path());
+        let le_bytes = fs::read(&chunk_path).unwrap();
+        let mut native_bytes = le_bytes.clone();
+        let offsets_size = multivecs.len() * size_of::<MultivectorMmapOffset>();
+        for value in native_bytes[..offsets_size].chunks_exact_mut(size_of::<u32>()) {
+            let le = u32::from_le_bytes(value.try_into().unwrap());
+            value.copy_from_slice(&le.to_be_bytes());
+        }
+        fs::write(&chunk_path, native_bytes).unwrap();
+
+        if cfg!(target_endian = "big") {
+            assert_offsets_test_storage(dir.path(), &multivecs);
+            let migrated_bytes = fs::read(&chunk_path).unwrap();
+            assert_eq!(&migrated_bytes[..offsets_size], &le_bytes[..offsets_size]);
+        } else {
+            // Native offsets on little-endian hosts are little-endian, so these are corrupted
+            let err = open_offsets_test_storage(dir.path()).unwrap_err();
+            assert!(err.to_string().contains("out of bounds"), "{err}");
+        }
+    }
 }
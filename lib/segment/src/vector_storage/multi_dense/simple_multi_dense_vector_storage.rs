@@ -100,6 +100,9 @@ pub fn open_simple_multi_dense_vector_storage(
             multi_vector_config,
             stopped,
         ),
+        VectorStorageDatatype::Bfloat16 => Err(OperationError::ValidationError {
+            description: "Bfloat16 datatype is not supported by RocksDB vector storage".to_string(),
+        }),
     }
 }
 
@@ -7,6 +7,7 @@ use common::mmap::MmapFlusher;
 use common::types::PointOffsetType;
 use fs_err as fs;
 use fs_err::File;
+use quantization::encoded_storage_mmap::data_offset;
 
 use crate::common::operation_error::OperationResult;
 use crate::common::vector_utils::TrySetCapacityExact;
@@ -22,8 +23,11 @@ pub struct QuantizedRamStorage {
 impl QuantizedRamStorage {
     pub fn from_file(path: &Path, quantized_vector_size: usize) -> std::io::Result<Self> {
         let mut vectors = ChunkedVectors::<u8>::new(quantized_vector_size);
+        // Files written by the mmap storage start with a header
+        let data_offset = data_offset(path, quantized_vector_size)?;
         let file = OneshotFile::open(path)?;
         let mut reader = BufReader::new(file);
+        reader.seek_relative(data_offset as i64)?;
         let mut buffer = vec![0u8; quantized_vector_size];
         while reader.read_exact(&mut buffer).is_ok() {
             vectors.push(&buffer).map_err(|err| {
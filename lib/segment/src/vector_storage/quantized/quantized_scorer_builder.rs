@@ -7,8 +7,8 @@ use super::quantized_vectors::QuantizedVectorStorage;
 use crate::common::operation_error::OperationResult;
 use crate::data_types::primitive::PrimitiveVectorElement;
 use crate::data_types::vectors::{
-    DenseVector, MultiDenseVectorInternal, QueryVector, VectorElementType, VectorElementTypeByte,
-    VectorElementTypeHalf,
+    DenseVector, MultiDenseVectorInternal, QueryVector, VectorElementType, VectorElementTypeBf16,
+    VectorElementTypeByte, VectorElementTypeHalf,
 };
 use crate::spaces::metric::Metric;
 use crate::spaces::simple::{CosineMetric, DotProductMetric, EuclidMetric, ManhattanMetric};
@@ -83,6 +83,16 @@ impl<'a> QuantizedScorerBuilder<'a> {
                     self.build_with_metric::<VectorElementTypeHalf, ManhattanMetric>()
                 }
             },
+            VectorStorageDatatype::Bfloat16 => match self.distance {
+                Distance::Cosine => self.build_with_metric::<VectorElementTypeBf16, CosineMetric>(),
+                Distance::Euclid => self.build_with_metric::<VectorElementTypeBf16, EuclidMetric>(),
+                Distance::Dot => {
+                    self.build_with_metric::<VectorElementTypeBf16, DotProductMetric>()
+                }
+                Distance::Manhattan => {
+                    self.build_with_metric::<VectorElementTypeBf16, ManhattanMetric>()
+                }
+            },
         }
     }
 
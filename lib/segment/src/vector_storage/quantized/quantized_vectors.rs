@@ -1,14 +1,18 @@
 use std::alloc::Layout;
+use std::borrow::Cow;
 use std::fmt;
 use std::path::{Path, PathBuf};
-use std::sync::atomic::AtomicBool;
+use std::sync::atomic::{AtomicBool, AtomicU64};
 
 use common::counter::hardware_counter::HardwareCounterCell;
-use common::fs::{atomic_save_json, clear_disk_cache, read_json};
+use common::fs::{atomic_save_json, bulk_sync_dir, clear_disk_cache, read_json, sync_parent_dir};
 use common::types::PointOffsetType;
-use quantization::encoded_vectors_binary::EncodedVectorsBin;
+use fs_err as fs;
+use ordered_float::OrderedFloat;
+use quantization::encoded_vectors_binary::{BitsStoreType, EncodedVectorsBin};
 use quantization::encoded_vectors_u8::ScalarQuantizationMethod;
-use quantization::{EncodedVectors, EncodedVectorsPQ, EncodedVectorsU8};
+use quantization::rescore::{RescoreDefaults, Rescorer};
+use quantization::{EncodeElement, EncodedVectors, EncodedVectorsPQ, EncodedVectorsU8};
 use serde::{Deserialize, Serialize};
 
 use super::quantized_multivector_storage::{
@@ -23,7 +27,7 @@ use crate::types::{
     BinaryQuantization, BinaryQuantizationConfig, BinaryQuantizationEncoding,
     BinaryQuantizationQueryEncoding, CompressionRatio, Distance, MultiVectorConfig,
     ProductQuantization, ProductQuantizationConfig, QuantizationConfig, ScalarQuantization,
-    ScalarQuantizationConfig, ScalarType, VectorStorageDatatype,
+    ScalarQuantizationConfig, ScalarType, SearchParams, VectorStorageDatatype,
 };
 use crate::vector_storage::quantized::quantized_chunked_mmap_storage::{
     QuantizedChunkedMmapStorage, QuantizedChunkedMmapStorageBuilder,
@@ -51,6 +55,8 @@ pub const QUANTIZED_APPENDABLE_DATA_PATH: &str = "quantized_data";
 pub const QUANTIZED_META_PATH: &str = "quantized.meta.json";
 pub const QUANTIZED_OFFSETS_PATH: &str = "quantized.offsets.data";
 pub const QUANTIZED_APPENDABLE_OFFSETS_PATH: &str = "quantized_offsets_data";
+/// Directory in which quantized vectors are created, before moving them to the final location
+pub const QUANTIZED_STAGING_PATH: &str = "quantized.tmp";
 
 #[derive(Deserialize, Serialize, Clone)]
 pub struct QuantizedVectorsConfig {
@@ -59,6 +65,10 @@ pub struct QuantizedVectorsConfig {
     #[serde(default)]
     #[serde(skip_serializing_if = "QuantizedVectorsStorageType::is_immutable")]
     pub storage_type: QuantizedVectorsStorageType,
+    /// Defaults of searches over these quantized vectors, set when they are built
+    #[serde(default)]
+    #[serde(skip_serializing_if = "RescoreDefaults::is_empty")]
+    pub rescore_defaults: RescoreDefaults,
 }
 
 impl fmt::Debug for QuantizedVectorsConfig {
@@ -213,6 +223,35 @@ impl QuantizedVectors {
         }
     }
 
+    /// Oversampling of searches which don't request one.
+    pub fn default_oversampling(&self) -> Option<f64> {
+        self.config.rescore_defaults.oversampling
+    }
+
+    /// Two-phase search parameters of a search with `params`, falling back to the defaults of
+    /// these quantized vectors.
+    pub fn rescorer(&self, params: Option<&SearchParams>) -> Rescorer {
+        let quantization_params = params.and_then(|p| p.quantization);
+        let oversampling = quantization_params
+            .and_then(|q| q.oversampling)
+            .or(self.default_oversampling());
+        let rescore = quantization_params
+            .and_then(|q| q.rescore)
+            .unwrap_or(self.default_rescoring());
+        Rescorer::new(oversampling, rescore)
+    }
+
+    /// Defaults of searches over vectors quantized with `quantization_config`.
+    fn rescore_defaults(quantization_config: &QuantizationConfig) -> RescoreDefaults {
+        let oversampling = match quantization_config {
+            QuantizationConfig::Scalar(_) | QuantizationConfig::Product(_) => None,
+            QuantizationConfig::Binary(BinaryQuantization { binary }) => binary
+                .oversampling
+                .map(|OrderedFloat(oversampling)| oversampling),
+        };
+        RescoreDefaults { oversampling }
+    }
+
     pub fn is_multivector(&self) -> bool {
         match self.storage_impl {
             QuantizedVectorStorage::ScalarRam(_) => false,
@@ -393,6 +432,10 @@ impl QuantizedVectors {
         path.join(QUANTIZED_META_PATH)
     }
 
+    fn get_staging_path(path: &Path) -> PathBuf {
+        path.join(QUANTIZED_STAGING_PATH)
+    }
+
     fn get_offsets_path(path: &Path, storage_type: QuantizedVectorsStorageType) -> PathBuf {
         match storage_type {
             QuantizedVectorsStorageType::Immutable => path.join(QUANTIZED_OFFSETS_PATH),
@@ -450,12 +493,96 @@ impl QuantizedVectors {
         files
     }
 
+    /// Quantize vectors of `vector_storage` into `path`.
+    ///
+    /// All files are written into a staging directory first, synced, and moved into `path` once
+    /// complete. The config is moved last, so a crash during creation never leaves quantized
+    /// vectors which would be loaded from incomplete files.
     pub fn create(
         vector_storage: &VectorStorageEnum,
         quantization_config: &QuantizationConfig,
         storage_type: QuantizedVectorsStorageType,
         path: &Path,
         max_threads: usize,
+        progress: Option<&AtomicU64>,
+        stopped: &AtomicBool,
+    ) -> OperationResult<Self> {
+        let staging_path = Self::get_staging_path(path);
+        Self::remove_orphan_staging(&staging_path)?;
+        fs::create_dir_all(&staging_path)?;
+
+        let staged = Self::create_in(
+            vector_storage,
+            quantization_config,
+            storage_type,
+            &staging_path,
+            max_threads,
+            progress,
+            stopped,
+        );
+        let config = match staged {
+            Ok(staged) => staged.config.clone(),
+            Err(err) => {
+                if let Err(remove_err) = fs::remove_dir_all(&staging_path) {
+                    log::warn!("Failed to remove quantization staging directory: {remove_err}");
+                }
+                return Err(err);
+            }
+        };
+
+        Self::commit_staging(&staging_path, path)?;
+        Self::load_impl(config, vector_storage, path)
+    }
+
+    /// Move all files of created quantized vectors from `staging_path` into `path`.
+    fn commit_staging(staging_path: &Path, path: &Path) -> OperationResult<()> {
+        bulk_sync_dir(staging_path)?;
+
+        // Previous quantized vectors are not valid anymore as soon as any file is replaced
+        let config_path = Self::get_config_path(path);
+        if config_path.exists() {
+            fs::remove_file(&config_path)?;
+        }
+
+        for entry in fs::read_dir(staging_path)? {
+            let entry = entry?;
+            if entry.file_name() == QUANTIZED_CONFIG_PATH {
+                continue;
+            }
+            let target_path = path.join(entry.file_name());
+            if target_path.is_dir() {
+                fs::remove_dir_all(&target_path)?;
+            }
+            fs::rename(entry.path(), target_path)?;
+        }
+        sync_parent_dir(&config_path)?;
+
+        fs::rename(Self::get_config_path(staging_path), &config_path)?;
+        sync_parent_dir(&config_path)?;
+
+        fs::remove_dir(staging_path)?;
+        Ok(())
+    }
+
+    /// Remove files of quantized vectors, which creation was interrupted.
+    fn remove_orphan_staging(staging_path: &Path) -> OperationResult<()> {
+        if staging_path.exists() {
+            log::warn!(
+                "Removing incomplete quantized vectors at {}",
+                staging_path.display(),
+            );
+            fs::remove_dir_all(staging_path)?;
+        }
+        Ok(())
+    }
+
+    fn create_in(
+        vector_storage: &VectorStorageEnum,
+        quantization_config: &QuantizationConfig,
+        storage_type: QuantizedVectorsStorageType,
+        path: &Path,
+        max_threads: usize,
+        progress: Option<&AtomicU64>,
         stopped: &AtomicBool,
     ) -> OperationResult<Self> {
         match vector_storage {
@@ -466,6 +593,7 @@ impl QuantizedVectors {
                 storage_type,
                 path,
                 max_threads,
+                progress,
                 stopped,
             ),
             #[cfg(feature = "rocksdb")]
@@ -475,6 +603,7 @@ impl QuantizedVectors {
                 storage_type,
                 path,
                 max_threads,
+                progress,
                 stopped,
             ),
             #[cfg(feature = "rocksdb")]
@@ -484,6 +613,7 @@ impl QuantizedVectors {
                 storage_type,
                 path,
                 max_threads,
+                progress,
                 stopped,
             ),
             VectorStorageEnum::DenseVolatile(v) => Self::create_impl(
@@ -492,6 +622,7 @@ impl QuantizedVectors {
                 storage_type,
                 path,
                 max_threads,
+                progress,
                 stopped,
             ),
             #[cfg(test)]
@@ -501,6 +632,7 @@ impl QuantizedVectors {
                 storage_type,
                 path,
                 max_threads,
+                progress,
                 stopped,
             ),
             #[cfg(test)]
@@ -510,6 +642,7 @@ impl QuantizedVectors {
                 storage_type,
                 path,
                 max_threads,
+                progress,
                 stopped,
             ),
             VectorStorageEnum::DenseMemmap(v) => Self::create_impl(
@@ -518,6 +651,7 @@ impl QuantizedVectors {
                 storage_type,
                 path,
                 max_threads,
+                progress,
                 stopped,
             ),
             VectorStorageEnum::DenseMemmapByte(v) => Self::create_impl(
@@ -526,6 +660,7 @@ impl QuantizedVectors {
                 storage_type,
                 path,
                 max_threads,
+                progress,
                 stopped,
             ),
             VectorStorageEnum::DenseMemmapHalf(v) => Self::create_impl(
@@ -534,6 +669,16 @@ impl QuantizedVectors {
                 storage_type,
                 path,
                 max_threads,
+                progress,
+                stopped,
+            ),
+            VectorStorageEnum::DenseMemmapBf16(v) => Self::create_impl(
+                v.as_ref(),
+                quantization_config,
+                storage_type,
+                path,
+                max_threads,
+                progress,
                 stopped,
             ),
             VectorStorageEnum::DenseAppendableMemmap(v) => Self::create_impl(
@@ -542,6 +687,7 @@ impl QuantizedVectors {
                 storage_type,
                 path,
                 max_threads,
+                progress,
                 stopped,
             ),
             VectorStorageEnum::DenseAppendableMemmapByte(v) => Self::create_impl(
@@ -550,6 +696,7 @@ impl QuantizedVectors {
                 storage_type,
                 path,
                 max_threads,
+                progress,
                 stopped,
             ),
             VectorStorageEnum::DenseAppendableMemmapHalf(v) => Self::create_impl(
@@ -558,6 +705,16 @@ impl QuantizedVectors {
                 storage_type,
                 path,
                 max_threads,
+                progress,
+                stopped,
+            ),
+            VectorStorageEnum::DenseAppendableMemmapBf16(v) => Self::create_impl(
+                v.as_ref(),
+                quantization_config,
+                storage_type,
+                path,
+                max_threads,
+                progress,
                 stopped,
             ),
             #[cfg(feature = "rocksdb")]
@@ -571,6 +728,7 @@ impl QuantizedVectors {
                 storage_type,
                 path,
                 max_threads,
+                progress,
                 stopped,
             ),
             #[cfg(feature = "rocksdb")]
@@ -580,6 +738,7 @@ impl QuantizedVectors {
                 storage_type,
                 path,
                 max_threads,
+                progress,
                 stopped,
             ),
             #[cfg(feature = "rocksdb")]
@@ -589,6 +748,7 @@ impl QuantizedVectors {
                 storage_type,
                 path,
                 max_threads,
+                progress,
                 stopped,
             ),
             VectorStorageEnum::MultiDenseVolatile(v) => Self::create_multi_impl(
@@ -597,6 +757,7 @@ impl QuantizedVectors {
                 storage_type,
                 path,
                 max_threads,
+                progress,
                 stopped,
             ),
             #[cfg(test)]
@@ -606,6 +767,7 @@ impl QuantizedVectors {
                 storage_type,
                 path,
                 max_threads,
+                progress,
                 stopped,
             ),
             #[cfg(test)]
@@ -615,6 +777,7 @@ impl QuantizedVectors {
                 storage_type,
                 path,
                 max_threads,
+                progress,
                 stopped,
             ),
             VectorStorageEnum::MultiDenseAppendableMemmap(v) => Self::create_multi_impl(
@@ -623,6 +786,7 @@ impl QuantizedVectors {
                 storage_type,
                 path,
                 max_threads,
+                progress,
                 stopped,
             ),
             VectorStorageEnum::MultiDenseAppendableMemmapByte(v) => Self::create_multi_impl(
@@ -631,6 +795,7 @@ impl QuantizedVectors {
                 storage_type,
                 path,
                 max_threads,
+                progress,
                 stopped,
             ),
             VectorStorageEnum::MultiDenseAppendableMemmapHalf(v) => Self::create_multi_impl(
@@ -639,6 +804,16 @@ impl QuantizedVectors {
                 storage_type,
                 path,
                 max_threads,
+                progress,
+                stopped,
+            ),
+            VectorStorageEnum::MultiDenseAppendableMemmapBf16(v) => Self::create_multi_impl(
+                v.as_ref(),
+                quantization_config,
+                storage_type,
+                path,
+                max_threads,
+                progress,
                 stopped,
             ),
         }
@@ -653,6 +828,7 @@ impl QuantizedVectors {
         storage_type: QuantizedVectorsStorageType,
         path: &Path,
         max_threads: usize,
+        progress: Option<&AtomicU64>,
         stopped: &AtomicBool,
     ) -> OperationResult<Self> {
         let dim = vector_storage.vector_dim();
@@ -660,11 +836,21 @@ impl QuantizedVectors {
         let distance = vector_storage.distance();
         let datatype = vector_storage.datatype();
         let vectors = (0..count as PointOffsetType).map(|i| {
-            PrimitiveVectorElement::quantization_preprocess(
-                quantization_config,
-                distance,
-                vector_storage.get_dense::<Sequential>(i),
-            )
+            match vector_storage.get_dense::<Sequential>(i) {
+                Cow::Borrowed(vector) => PrimitiveVectorElement::quantization_preprocess(
+                    quantization_config,
+                    distance,
+                    vector,
+                ),
+                Cow::Owned(vector) => Cow::Owned(
+                    PrimitiveVectorElement::quantization_preprocess(
+                        quantization_config,
+                        distance,
+                        &vector,
+                    )
+                    .into_owned(),
+                ),
+            }
         });
         let on_disk_vector_storage = vector_storage.is_on_disk();
 
@@ -672,6 +858,32 @@ impl QuantizedVectors {
             Self::construct_vector_parameters(distance, dim, count, storage_type);
 
         let quantized_storage = match quantization_config {
+            // `f16` vectors need no preprocessing, encode them without expanding into `f32`
+            QuantizationConfig::Scalar(ScalarQuantization {
+                scalar: scalar_config,
+            }) if TElement::datatype() == VectorStorageDatatype::Float16 => Self::create_scalar(
+                (0..count as PointOffsetType).map(|i| {
+                    match vector_storage.get_dense::<Sequential>(i) {
+                        Cow::Borrowed(vector) => {
+                            Cow::Borrowed(TElement::quantization_half(vector).unwrap_or_default())
+                        }
+                        Cow::Owned(vector) => Cow::Owned(
+                            TElement::quantization_half(&vector)
+                                .unwrap_or_default()
+                                .to_vec(),
+                        ),
+                    }
+                }),
+                &vector_parameters,
+                count,
+                scalar_config,
+                storage_type,
+                path,
+                on_disk_vector_storage,
+                max_threads,
+                progress,
+                stopped,
+            )?,
             QuantizationConfig::Scalar(ScalarQuantization {
                 scalar: scalar_config,
             }) => Self::create_scalar(
@@ -682,6 +894,8 @@ impl QuantizedVectors {
                 storage_type,
                 path,
                 on_disk_vector_storage,
+                max_threads,
+                progress,
                 stopped,
             )?,
             QuantizationConfig::Product(ProductQuantization { product: pq_config }) => {
@@ -715,6 +929,7 @@ impl QuantizedVectors {
             quantization_config: quantization_config.clone(),
             vector_parameters,
             storage_type,
+            rescore_defaults: Self::rescore_defaults(quantization_config),
         };
 
         let quantized_vectors = QuantizedVectors {
@@ -738,6 +953,7 @@ impl QuantizedVectors {
         storage_type: QuantizedVectorsStorageType,
         path: &Path,
         max_threads: usize,
+        progress: Option<&AtomicU64>,
         stopped: &AtomicBool,
     ) -> OperationResult<Self> {
         let dim = vector_storage.vector_dim();
@@ -779,6 +995,8 @@ impl QuantizedVectors {
                 multi_vector_config,
                 path,
                 on_disk_vector_storage,
+                max_threads,
+                progress,
                 stopped,
             )?,
             QuantizationConfig::Product(ProductQuantization { product: pq_config }) => {
@@ -818,6 +1036,7 @@ impl QuantizedVectors {
             quantization_config: quantization_config.clone(),
             vector_parameters,
             storage_type,
+            rescore_defaults: Self::rescore_defaults(quantization_config),
         };
 
         let quantized_vectors = QuantizedVectors {
@@ -838,6 +1057,8 @@ impl QuantizedVectors {
         path: &Path,
         stopped: &AtomicBool,
     ) -> OperationResult<Option<Self>> {
+        Self::remove_orphan_staging(&Self::get_staging_path(path))?;
+
         let config_path = Self::get_config_path(path);
         if config_path.exists() {
             let config: QuantizedVectorsConfig = read_json(&config_path)?;
@@ -855,6 +1076,7 @@ impl QuantizedVectors {
             QuantizedVectorsStorageType::Mutable,
             path,
             1,
+            None,
             stopped,
         )?;
         Ok(Some(quantized_vectors))
@@ -916,6 +1138,68 @@ impl QuantizedVectors {
         })
     }
 
+    /// Check files of immutable quantized vectors at `path` against their checksums, without
+    /// loading the vector storage.
+    ///
+    /// Does nothing if there are no quantized vectors at `path`. Mutable storages change after
+    /// creation, so they are not checked.
+    pub fn verify_files(path: &Path, is_multivector: bool) -> OperationResult<()> {
+        let config_path = Self::get_config_path(path);
+        if !config_path.exists() {
+            return Ok(());
+        }
+        let config: QuantizedVectorsConfig = read_json(&config_path)?;
+        if !config.storage_type.is_immutable() {
+            return Ok(());
+        }
+
+        let data_path = Self::get_data_path(path, config.storage_type);
+        let meta_path = Self::get_meta_path(path);
+        match &config.quantization_config {
+            QuantizationConfig::Scalar(_) => {
+                let quantized_vector_size =
+                    EncodedVectorsU8::<QuantizedMmapStorage>::get_quantized_vector_size(
+                        &config.vector_parameters,
+                    );
+                let storage = QuantizedMmapStorage::from_file(&data_path, quantized_vector_size)?;
+                EncodedVectorsU8::load(storage, &meta_path)?.verify()?;
+            }
+            QuantizationConfig::Product(ProductQuantization { product }) => {
+                let quantized_vector_size =
+                    EncodedVectorsPQ::<QuantizedMmapStorage>::get_quantized_vector_size(
+                        &config.vector_parameters,
+                        Self::get_bucket_size(product.compression),
+                    );
+                let storage = QuantizedMmapStorage::from_file(&data_path, quantized_vector_size)?;
+                EncodedVectorsPQ::load(storage, &meta_path)?.verify()?;
+            }
+            QuantizationConfig::Binary(BinaryQuantization { binary }) => {
+                // Same word types as in `load_binary` and `load_binary_multi`
+                if is_multivector {
+                    Self::verify_binary_files::<u8>(&data_path, &meta_path, &config, binary)?;
+                } else {
+                    Self::verify_binary_files::<u128>(&data_path, &meta_path, &config, binary)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn verify_binary_files<TBitsStoreType: BitsStoreType>(
+        data_path: &Path,
+        meta_path: &Path,
+        config: &QuantizedVectorsConfig,
+        binary_config: &BinaryQuantizationConfig,
+    ) -> OperationResult<()> {
+        let quantized_vector_size = EncodedVectorsBin::<TBitsStoreType, QuantizedMmapStorage>::get_quantized_vector_size_from_params(
+            config.vector_parameters.dim,
+            Self::convert_binary_encoding(binary_config.encoding),
+        );
+        let storage = QuantizedMmapStorage::from_file(data_path, quantized_vector_size)?;
+        EncodedVectorsBin::<TBitsStoreType, _>::load(storage, meta_path)?.verify()?;
+        Ok(())
+    }
+
     fn load_scalar(
         vector_storage: &VectorStorageEnum,
         path: &Path,
@@ -1251,14 +1535,16 @@ impl QuantizedVectors {
     }
 
     #[allow(clippy::too_many_arguments)]
-    fn create_scalar<'a>(
-        vectors: impl Iterator<Item = impl AsRef<[VectorElementType]> + Send + Sync + 'a> + Clone,
+    fn create_scalar<'a, T: EncodeElement>(
+        vectors: impl Iterator<Item = impl AsRef<[T]> + Send + Sync + 'a> + Clone,
         vector_parameters: &quantization::VectorParameters,
         vectors_count: usize,
         scalar_config: &ScalarQuantizationConfig,
         storage_type: QuantizedVectorsStorageType,
         path: &Path,
         on_disk_vector_storage: bool,
+        max_threads: usize,
+        progress: Option<&AtomicU64>,
         stopped: &AtomicBool,
     ) -> OperationResult<QuantizedVectorStorage> {
         if !storage_type.is_immutable() {
@@ -1279,16 +1565,20 @@ impl QuantizedVectors {
                 vectors_count,
                 quantized_vector_size,
             )?;
-            Ok(QuantizedVectorStorage::ScalarRam(EncodedVectorsU8::encode(
-                vectors,
-                storage_builder,
-                vector_parameters,
-                vectors_count,
-                scalar_config.quantile,
-                encoding,
-                Some(meta_path.as_path()),
-                stopped,
-            )?))
+            Ok(QuantizedVectorStorage::ScalarRam(
+                EncodedVectorsU8::encode_parallel(
+                    vectors,
+                    storage_builder,
+                    vector_parameters,
+                    vectors_count,
+                    scalar_config.quantile,
+                    encoding,
+                    Some(meta_path.as_path()),
+                    max_threads,
+                    progress,
+                    stopped,
+                )?,
+            ))
         } else {
             let storage_builder = QuantizedMmapStorageBuilder::new(
                 data_path.as_path(),
@@ -1296,7 +1586,7 @@ impl QuantizedVectors {
                 quantized_vector_size,
             )?;
             Ok(QuantizedVectorStorage::ScalarMmap(
-                EncodedVectorsU8::encode(
+                EncodedVectorsU8::encode_parallel(
                     vectors,
                     storage_builder,
                     vector_parameters,
@@ -1304,6 +1594,8 @@ impl QuantizedVectors {
                     scalar_config.quantile,
                     encoding,
                     Some(meta_path.as_path()),
+                    max_threads,
+                    progress,
                     stopped,
                 )?,
             ))
@@ -1322,6 +1614,8 @@ impl QuantizedVectors {
         multi_vector_config: MultiVectorConfig,
         path: &Path,
         on_disk_vector_storage: bool,
+        max_threads: usize,
+        progress: Option<&AtomicU64>,
         stopped: &AtomicBool,
     ) -> OperationResult<QuantizedVectorStorage> {
         if !storage_type.is_immutable() {
@@ -1343,7 +1637,7 @@ impl QuantizedVectors {
                 inner_vectors_count,
                 quantized_vector_size,
             )?;
-            let quantized_storage = EncodedVectorsU8::encode(
+            let quantized_storage = EncodedVectorsU8::encode_parallel(
                 vectors,
                 storage_builder,
                 vector_parameters,
@@ -1351,6 +1645,8 @@ impl QuantizedVectors {
                 scalar_config.quantile,
                 encoding,
                 Some(meta_path.as_path()),
+                max_threads,
+                progress,
                 stopped,
             )?;
             let offsets = MultivectorOffsetsStorageRam::create(&offsets_path, offsets)?;
@@ -1368,7 +1664,7 @@ impl QuantizedVectors {
                 inner_vectors_count,
                 quantized_vector_size,
             )?;
-            let quantized_storage = EncodedVectorsU8::encode(
+            let quantized_storage = EncodedVectorsU8::encode_parallel(
                 vectors,
                 storage_builder,
                 vector_parameters,
@@ -1376,6 +1672,8 @@ impl QuantizedVectors {
                 scalar_config.quantile,
                 encoding,
                 Some(meta_path.as_path()),
+                max_threads,
+                progress,
                 stopped,
             )?;
             let offsets =
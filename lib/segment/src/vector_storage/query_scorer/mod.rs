@@ -1,9 +1,13 @@
+use std::cell::RefCell;
+
 use bytemuck::TransparentWrapper;
 use common::typelevel::{TBool, TOption};
 use common::types::{PointOffsetType, ScoreType};
+use half::f16;
+use zerocopy::IntoBytes as _;
 
 use crate::data_types::primitive::PrimitiveVectorElement;
-use crate::data_types::vectors::TypedMultiDenseVectorRef;
+use crate::data_types::vectors::{TypedMultiDenseVectorRef, VectorElementType};
 use crate::spaces::metric::Metric;
 use crate::types::{MultiVectorComparator, MultiVectorConfig};
 use crate::vector_storage::VectorOffset;
@@ -64,6 +68,34 @@ impl<TQueryScorer: QueryScorer> QueryScorerBytes for QueryScorerBytesImpl<TQuery
     }
 }
 
+/// Scores `f16` vectors with a [`QueryScorerBytes`] of `f32` vectors.
+pub struct Float16QueryScorerBytes<'a> {
+    scorer: &'a dyn QueryScorerBytes,
+    buffer: RefCell<Vec<VectorElementType>>,
+}
+
+impl<'a> Float16QueryScorerBytes<'a> {
+    pub fn new(scorer: &'a dyn QueryScorerBytes) -> Self {
+        Self {
+            scorer,
+            buffer: RefCell::new(Vec::new()),
+        }
+    }
+}
+
+impl QueryScorerBytes for Float16QueryScorerBytes<'_> {
+    fn score_bytes(&self, bytes: &[u8]) -> ScoreType {
+        let mut buffer = self.buffer.borrow_mut();
+        buffer.clear();
+        buffer.extend(
+            bytes
+                .chunks_exact(size_of::<f16>())
+                .map(|chunk| f16::from_le_bytes([chunk[0], chunk[1]]).to_f32()),
+        );
+        self.scorer.score_bytes(buffer.as_bytes())
+    }
+}
+
 /// Colbert MaxSim metric, metric for multi-dense vectors
 /// <https://arxiv.org/pdf/2112.01488.pdf>, figure 1
 /// This metric is also implemented in `QuantizedMultivectorStorage` structure for quantized data.
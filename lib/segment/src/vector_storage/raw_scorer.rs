@@ -23,6 +23,8 @@ use crate::spaces::metric::Metric;
 use crate::spaces::simple::{CosineMetric, DotProductMetric, EuclidMetric, ManhattanMetric};
 use crate::types::Distance;
 use crate::vector_storage::common::VECTOR_READ_BATCH_SIZE;
+use crate::vector_storage::dense::memmap_dense_vector_storage::MemmapDenseVectorStorage;
+use crate::vector_storage::mmap_endian::MmapEndianConvertible;
 use crate::vector_storage::query::NaiveFeedbackQuery;
 use crate::vector_storage::query_scorer::QueryScorer;
 use crate::vector_storage::query_scorer::metric_query_scorer::MetricQueryScorer;
@@ -69,31 +71,15 @@ pub fn new_raw_scorer<'a>(
         #[cfg(test)]
         VectorStorageEnum::DenseVolatileHalf(vs) => raw_scorer_impl(query, vs, hc),
 
-        VectorStorageEnum::DenseMemmap(vs) => {
-            if vs.has_async_reader() {
-                #[cfg(target_os = "linux")]
-                {
-                    let scorer_result = super::async_raw_scorer::new(query.clone(), vs, hc.fork());
-                    match scorer_result {
-                        Ok(raw_scorer) => return Ok(raw_scorer),
-                        Err(err) => log::error!("failed to initialize async raw scorer: {err}"),
-                    };
-                }
-
-                #[cfg(not(target_os = "linux"))]
-                log::warn!("async raw scorer is only supported on Linux");
-            }
-
-            raw_scorer_impl(query, vs.as_ref(), hc)
-        }
-
-        // TODO(byte_storage): Implement async raw scorer for DenseMemmapByte and DenseMemmapHalf
-        VectorStorageEnum::DenseMemmapByte(vs) => raw_scorer_impl(query, vs.as_ref(), hc),
-        VectorStorageEnum::DenseMemmapHalf(vs) => raw_scorer_impl(query, vs.as_ref(), hc),
+        VectorStorageEnum::DenseMemmap(vs) => memmap_raw_scorer_impl(query, vs, hc),
+        VectorStorageEnum::DenseMemmapByte(vs) => memmap_raw_scorer_impl(query, vs, hc),
+        VectorStorageEnum::DenseMemmapHalf(vs) => memmap_raw_scorer_impl(query, vs, hc),
+        VectorStorageEnum::DenseMemmapBf16(vs) => memmap_raw_scorer_impl(query, vs, hc),
 
         VectorStorageEnum::DenseAppendableMemmap(vs) => raw_scorer_impl(query, vs.as_ref(), hc),
         VectorStorageEnum::DenseAppendableMemmapByte(vs) => raw_scorer_impl(query, vs.as_ref(), hc),
         VectorStorageEnum::DenseAppendableMemmapHalf(vs) => raw_scorer_impl(query, vs.as_ref(), hc),
+        VectorStorageEnum::DenseAppendableMemmapBf16(vs) => raw_scorer_impl(query, vs.as_ref(), hc),
         #[cfg(feature = "rocksdb")]
         VectorStorageEnum::SparseSimple(vs) => raw_sparse_scorer_impl(query, vs, hc),
         VectorStorageEnum::SparseVolatile(vs) => raw_sparse_scorer_volatile(query, vs, hc),
@@ -118,6 +104,9 @@ pub fn new_raw_scorer<'a>(
         VectorStorageEnum::MultiDenseAppendableMemmapHalf(vs) => {
             raw_multi_scorer_impl(query, vs.as_ref(), hc)
         }
+        VectorStorageEnum::MultiDenseAppendableMemmapBf16(vs) => {
+            raw_multi_scorer_impl(query, vs.as_ref(), hc)
+        }
     }
 }
 
@@ -209,6 +198,31 @@ pub fn new_raw_scorer_for_test<'a>(
     new_raw_scorer(vector, vector_storage, HardwareCounterCell::new())
 }
 
+/// Raw scorer for mmap dense vectors, reading with async IO if the storage has an async reader.
+fn memmap_raw_scorer_impl<'a, TElement: PrimitiveVectorElement + MmapEndianConvertible>(
+    query: QueryVector,
+    vector_storage: &'a MemmapDenseVectorStorage<TElement>,
+    hardware_counter: HardwareCounterCell,
+) -> OperationResult<Box<dyn RawScorer + 'a>>
+where
+    CosineMetric: Metric<TElement>,
+    EuclidMetric: Metric<TElement>,
+    DotProductMetric: Metric<TElement>,
+    ManhattanMetric: Metric<TElement>,
+{
+    // Async reader is io_uring on Linux, and batched reads on other platforms
+    if vector_storage.has_async_reader() {
+        let scorer_result =
+            super::async_raw_scorer::new(query.clone(), vector_storage, hardware_counter.fork());
+        match scorer_result {
+            Ok(raw_scorer) => return Ok(raw_scorer),
+            Err(err) => log::error!("failed to initialize async raw scorer: {err}"),
+        };
+    }
+
+    raw_scorer_impl(query, vector_storage, hardware_counter)
+}
+
 pub fn raw_scorer_impl<
     'a,
     TElement: PrimitiveVectorElement,
@@ -107,6 +107,7 @@ fn binary() -> WithQuantization {
         always_ram: Some(true),
         encoding: None,
         query_encoding: None,
+        oversampling: None,
     }
     .into();
 
@@ -169,6 +170,7 @@ fn scoring_equivalency(
             QuantizedVectorsStorageType::Immutable,
             quant_dir.path(),
             4,
+            None,
             &AtomicBool::new(false),
         )?)
     } else {
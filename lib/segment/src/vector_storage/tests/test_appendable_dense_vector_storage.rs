@@ -20,7 +20,7 @@ use crate::vector_storage::dense::appendable_dense_vector_storage::open_appendab
 use crate::vector_storage::dense::simple_dense_vector_storage::open_simple_dense_full_vector_storage;
 use crate::vector_storage::dense::volatile_dense_vector_storage::new_volatile_dense_vector_storage;
 use crate::vector_storage::quantized::quantized_vectors::{
-    QuantizedVectors, QuantizedVectorsStorageType,
+    QUANTIZED_STAGING_PATH, QuantizedVectors, QuantizedVectorsStorageType,
 };
 use crate::vector_storage::{
     DEFAULT_STOPPED, Random, VectorStorage, VectorStorageEnum, new_raw_scorer,
@@ -345,9 +345,12 @@ fn test_score_quantized_points(storage: &mut VectorStorageEnum) {
         QuantizedVectorsStorageType::Immutable,
         dir.path(),
         1,
+        None,
         &stopped,
     )
     .unwrap();
+    let staging_path = dir.path().join(QUANTIZED_STAGING_PATH);
+    assert!(!staging_path.exists());
 
     let query: QueryVector = vec![0.5, 0.5, 0.5, 0.5].into();
     let scorer_quant = quantized_vectors
@@ -367,10 +370,15 @@ fn test_score_quantized_points(storage: &mut VectorStorageEnum) {
     let files = storage.files();
     let quantization_files = quantized_vectors.files();
 
+    // Leftovers of interrupted creation are removed on load
+    fs_err::create_dir_all(&staging_path).unwrap();
+    fs_err::write(staging_path.join("quantized.data"), [0u8; 3]).unwrap();
+
     // test save-load
     let quantized_vectors = QuantizedVectors::load(&config, storage, dir.path(), &stopped)
         .unwrap()
         .unwrap();
+    assert!(!staging_path.exists());
     assert_eq!(files, storage.files());
     assert_eq!(quantization_files, quantized_vectors.files());
 
@@ -86,10 +86,12 @@ fn do_test_delete_points(vector_dim: usize, vec_count: usize, storage: &mut Vect
             | VectorStorageEnum::DenseVolatileHalf(_) => unreachable!(),
             VectorStorageEnum::DenseMemmap(_)
             | VectorStorageEnum::DenseMemmapByte(_)
-            | VectorStorageEnum::DenseMemmapHalf(_) => unreachable!(),
+            | VectorStorageEnum::DenseMemmapHalf(_)
+            | VectorStorageEnum::DenseMemmapBf16(_) => unreachable!(),
             VectorStorageEnum::DenseAppendableMemmap(_)
             | VectorStorageEnum::DenseAppendableMemmapByte(_)
-            | VectorStorageEnum::DenseAppendableMemmapHalf(_) => unreachable!(),
+            | VectorStorageEnum::DenseAppendableMemmapHalf(_)
+            | VectorStorageEnum::DenseAppendableMemmapBf16(_) => unreachable!(),
             #[cfg(feature = "rocksdb")]
             VectorStorageEnum::SparseSimple(_) => unreachable!(),
             VectorStorageEnum::SparseMmap(_) => unreachable!(),
@@ -117,7 +119,8 @@ fn do_test_delete_points(vector_dim: usize, vec_count: usize, storage: &mut Vect
                 }
             }
             VectorStorageEnum::MultiDenseAppendableMemmapByte(_)
-            | VectorStorageEnum::MultiDenseAppendableMemmapHalf(_) => unreachable!(),
+            | VectorStorageEnum::MultiDenseAppendableMemmapHalf(_)
+            | VectorStorageEnum::MultiDenseAppendableMemmapBf16(_) => unreachable!(),
         };
     }
 
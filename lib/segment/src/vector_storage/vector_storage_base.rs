@@ -1,4 +1,5 @@
 use std::alloc::Layout;
+use std::borrow::Cow;
 use std::fmt;
 use std::mem::MaybeUninit;
 use std::ops::Range;
@@ -27,8 +28,8 @@ use crate::common::operation_error::{OperationError, OperationResult};
 use crate::data_types::named_vectors::CowVector;
 use crate::data_types::primitive::PrimitiveVectorElement;
 use crate::data_types::vectors::{
-    MultiDenseVectorInternal, TypedMultiDenseVectorRef, VectorElementType, VectorElementTypeByte,
-    VectorElementTypeHalf, VectorInternal, VectorRef,
+    MultiDenseVectorInternal, TypedMultiDenseVectorRef, VectorElementType, VectorElementTypeBf16,
+    VectorElementTypeByte, VectorElementTypeHalf, VectorInternal, VectorRef,
 };
 use crate::types::{Distance, MultiVectorConfig, VectorStorageDatatype};
 use crate::vector_storage::common::VECTOR_READ_BATCH_SIZE;
@@ -182,11 +183,15 @@ pub trait VectorStorage {
 pub trait DenseVectorStorage<T: PrimitiveVectorElement>: VectorStorage {
     fn vector_dim(&self) -> usize;
 
-    fn get_dense<P: AccessPattern>(&self, key: PointOffsetType) -> &[T];
+    /// Get vector by key, borrowed unless the storage has to decode it, e.g. when compressed
+    fn get_dense<P: AccessPattern>(&self, key: PointOffsetType) -> Cow<'_, [T]>;
 
     /// Get the raw bytes of the vector by the given key if it exists
-    fn get_dense_bytes_opt<P: AccessPattern>(&self, key: PointOffsetType) -> Option<&[u8]> {
-        ((key as usize) < self.total_vector_count()).then(|| self.get_dense::<P>(key).as_bytes())
+    fn get_dense_bytes_opt<P: AccessPattern>(&self, key: PointOffsetType) -> Option<Cow<'_, [u8]>> {
+        ((key as usize) < self.total_vector_count()).then(|| match self.get_dense::<P>(key) {
+            Cow::Borrowed(vector) => Cow::Borrowed(vector.as_bytes()),
+            Cow::Owned(vector) => Cow::Owned(vector.as_bytes().to_vec()),
+        })
     }
 
     /// Get layout for a single vector
@@ -200,10 +205,26 @@ pub trait DenseVectorStorage<T: PrimitiveVectorElement>: VectorStorage {
     /// Implementation can assume that the keys are consecutive
     fn for_each_in_dense_batch<F: FnMut(usize, &[T])>(&self, keys: &[PointOffsetType], mut f: F) {
         for (idx, &key) in keys.iter().enumerate() {
-            f(idx, self.get_dense::<Random>(key));
+            f(idx, &self.get_dense::<Random>(key));
         }
     }
 
+    /// Hint that vectors of `keys` are going to be read soon, in any order.
+    ///
+    /// Disk-backed storages start reading them in the background. No-op for in-memory storages.
+    fn prefetch_dense_batch(&self, _keys: &[PointOffsetType]) {}
+
+    /// Copy vectors of the dense batch into one contiguous `buffer`, `vector_dim` elements per key.
+    ///
+    /// Vectors are copied in native byte order, storages decode little-endian data on big-endian
+    /// hosts. Allows batched scorers to process the whole batch without chasing a pointer per vector.
+    /// Disk-backed storages prefetch the whole batch before copying it.
+    fn get_vectors_batch_into(&self, keys: &[PointOffsetType], buffer: &mut Vec<T>) {
+        buffer.clear();
+        buffer.reserve(keys.len() * self.vector_dim());
+        self.for_each_in_dense_batch(keys, |_, vector| buffer.extend_from_slice(vector));
+    }
+
     fn size_of_available_vectors_in_bytes(&self) -> usize {
         self.available_vector_count() * self.vector_dim() * std::mem::size_of::<T>()
     }
@@ -256,9 +277,11 @@ pub enum VectorStorageEnum {
     DenseMemmap(Box<MemmapDenseVectorStorage<VectorElementType>>),
     DenseMemmapByte(Box<MemmapDenseVectorStorage<VectorElementTypeByte>>),
     DenseMemmapHalf(Box<MemmapDenseVectorStorage<VectorElementTypeHalf>>),
+    DenseMemmapBf16(Box<MemmapDenseVectorStorage<VectorElementTypeBf16>>),
     DenseAppendableMemmap(Box<AppendableMmapDenseVectorStorage<VectorElementType>>),
     DenseAppendableMemmapByte(Box<AppendableMmapDenseVectorStorage<VectorElementTypeByte>>),
     DenseAppendableMemmapHalf(Box<AppendableMmapDenseVectorStorage<VectorElementTypeHalf>>),
+    DenseAppendableMemmapBf16(Box<AppendableMmapDenseVectorStorage<VectorElementTypeBf16>>),
     #[cfg(feature = "rocksdb")]
     SparseSimple(SimpleSparseVectorStorage),
     SparseVolatile(VolatileSparseVectorStorage),
@@ -281,6 +304,9 @@ pub enum VectorStorageEnum {
     MultiDenseAppendableMemmapHalf(
         Box<AppendableMmapMultiDenseVectorStorage<VectorElementTypeHalf>>,
     ),
+    MultiDenseAppendableMemmapBf16(
+        Box<AppendableMmapMultiDenseVectorStorage<VectorElementTypeBf16>>,
+    ),
 }
 
 impl VectorStorageEnum {
@@ -300,9 +326,11 @@ impl VectorStorageEnum {
             VectorStorageEnum::DenseMemmap(_) => None,
             VectorStorageEnum::DenseMemmapByte(_) => None,
             VectorStorageEnum::DenseMemmapHalf(_) => None,
+            VectorStorageEnum::DenseMemmapBf16(_) => None,
             VectorStorageEnum::DenseAppendableMemmap(_) => None,
             VectorStorageEnum::DenseAppendableMemmapByte(_) => None,
             VectorStorageEnum::DenseAppendableMemmapHalf(_) => None,
+            VectorStorageEnum::DenseAppendableMemmapBf16(_) => None,
             #[cfg(feature = "rocksdb")]
             VectorStorageEnum::SparseSimple(_) => None,
             VectorStorageEnum::SparseVolatile(_) => None,
@@ -321,6 +349,7 @@ impl VectorStorageEnum {
             VectorStorageEnum::MultiDenseAppendableMemmap(s) => Some(s.multi_vector_config()),
             VectorStorageEnum::MultiDenseAppendableMemmapByte(s) => Some(s.multi_vector_config()),
             VectorStorageEnum::MultiDenseAppendableMemmapHalf(s) => Some(s.multi_vector_config()),
+            VectorStorageEnum::MultiDenseAppendableMemmapBf16(s) => Some(s.multi_vector_config()),
         }
     }
 
@@ -352,6 +381,9 @@ impl VectorStorageEnum {
             VectorStorageEnum::DenseMemmapHalf(v) => {
                 VectorInternal::from(vec![1.0; v.vector_dim()])
             }
+            VectorStorageEnum::DenseMemmapBf16(v) => {
+                VectorInternal::from(vec![1.0; v.vector_dim()])
+            }
             VectorStorageEnum::DenseAppendableMemmap(v) => {
                 VectorInternal::from(vec![1.0; v.vector_dim()])
             }
@@ -361,6 +393,9 @@ impl VectorStorageEnum {
             VectorStorageEnum::DenseAppendableMemmapHalf(v) => {
                 VectorInternal::from(vec![1.0; v.vector_dim()])
             }
+            VectorStorageEnum::DenseAppendableMemmapBf16(v) => {
+                VectorInternal::from(vec![1.0; v.vector_dim()])
+            }
             #[cfg(feature = "rocksdb")]
             VectorStorageEnum::SparseSimple(_) => VectorInternal::from(SparseVector::default()),
             VectorStorageEnum::SparseVolatile(_) => VectorInternal::from(SparseVector::default()),
@@ -397,6 +432,9 @@ impl VectorStorageEnum {
             VectorStorageEnum::MultiDenseAppendableMemmapHalf(v) => {
                 VectorInternal::from(MultiDenseVectorInternal::placeholder(v.vector_dim()))
             }
+            VectorStorageEnum::MultiDenseAppendableMemmapBf16(v) => {
+                VectorInternal::from(MultiDenseVectorInternal::placeholder(v.vector_dim()))
+            }
         }
     }
 
@@ -416,6 +454,7 @@ impl VectorStorageEnum {
             VectorStorageEnum::DenseMemmap(v) => v.size_of_available_vectors_in_bytes(),
             VectorStorageEnum::DenseMemmapByte(v) => v.size_of_available_vectors_in_bytes(),
             VectorStorageEnum::DenseMemmapHalf(v) => v.size_of_available_vectors_in_bytes(),
+            VectorStorageEnum::DenseMemmapBf16(v) => v.size_of_available_vectors_in_bytes(),
             VectorStorageEnum::DenseAppendableMemmap(v) => v.size_of_available_vectors_in_bytes(),
             VectorStorageEnum::DenseAppendableMemmapByte(v) => {
                 v.size_of_available_vectors_in_bytes()
@@ -423,6 +462,9 @@ impl VectorStorageEnum {
             VectorStorageEnum::DenseAppendableMemmapHalf(v) => {
                 v.size_of_available_vectors_in_bytes()
             }
+            VectorStorageEnum::DenseAppendableMemmapBf16(v) => {
+                v.size_of_available_vectors_in_bytes()
+            }
             #[cfg(feature = "rocksdb")]
             VectorStorageEnum::SparseSimple(v) => v.size_of_available_vectors_in_bytes(),
             VectorStorageEnum::SparseVolatile(v) => v.size_of_available_vectors_in_bytes(),
@@ -451,6 +493,9 @@ impl VectorStorageEnum {
             VectorStorageEnum::MultiDenseAppendableMemmapHalf(v) => {
                 v.size_of_available_vectors_in_bytes()
             }
+            VectorStorageEnum::MultiDenseAppendableMemmapBf16(v) => {
+                v.size_of_available_vectors_in_bytes()
+            }
         }
     }
 
@@ -470,9 +515,11 @@ impl VectorStorageEnum {
             VectorStorageEnum::DenseMemmap(vs) => vs.populate(),
             VectorStorageEnum::DenseMemmapByte(vs) => vs.populate(),
             VectorStorageEnum::DenseMemmapHalf(vs) => vs.populate(),
+            VectorStorageEnum::DenseMemmapBf16(vs) => vs.populate(),
             VectorStorageEnum::DenseAppendableMemmap(vs) => vs.populate()?,
             VectorStorageEnum::DenseAppendableMemmapByte(vs) => vs.populate()?,
             VectorStorageEnum::DenseAppendableMemmapHalf(vs) => vs.populate()?,
+            VectorStorageEnum::DenseAppendableMemmapBf16(vs) => vs.populate()?,
             #[cfg(feature = "rocksdb")]
             VectorStorageEnum::SparseSimple(_) => {} // Can't populate as it is not mmap
             VectorStorageEnum::SparseVolatile(_) => {} // Can't populate as it is not mmap
@@ -491,6 +538,193 @@ impl VectorStorageEnum {
             VectorStorageEnum::MultiDenseAppendableMemmap(vs) => vs.populate()?,
             VectorStorageEnum::MultiDenseAppendableMemmapByte(vs) => vs.populate()?,
             VectorStorageEnum::MultiDenseAppendableMemmapHalf(vs) => vs.populate()?,
+            VectorStorageEnum::MultiDenseAppendableMemmapBf16(vs) => vs.populate()?,
+        }
+        Ok(())
+    }
+
+    /// Request transparent huge pages for memory mapped vectors of immutable storages.
+    ///
+    /// Appendable storages are left as is, they are written to and usually small.
+    pub fn request_hugepages(&self) -> OperationResult<()> {
+        match self {
+            #[cfg(feature = "rocksdb")]
+            VectorStorageEnum::DenseSimple(_) => {} // Not mmap
+            #[cfg(feature = "rocksdb")]
+            VectorStorageEnum::DenseSimpleByte(_) => {} // Not mmap
+            #[cfg(feature = "rocksdb")]
+            VectorStorageEnum::DenseSimpleHalf(_) => {} // Not mmap
+            VectorStorageEnum::DenseVolatile(_) => {} // Not mmap
+            #[cfg(test)]
+            VectorStorageEnum::DenseVolatileByte(_) => {} // Not mmap
+            #[cfg(test)]
+            VectorStorageEnum::DenseVolatileHalf(_) => {} // Not mmap
+            VectorStorageEnum::DenseMemmap(vs) => vs.request_hugepages()?,
+            VectorStorageEnum::DenseMemmapByte(vs) => vs.request_hugepages()?,
+            VectorStorageEnum::DenseMemmapHalf(vs) => vs.request_hugepages()?,
+            VectorStorageEnum::DenseMemmapBf16(vs) => vs.request_hugepages()?,
+            VectorStorageEnum::DenseAppendableMemmap(_) => {} // Appendable
+            VectorStorageEnum::DenseAppendableMemmapByte(_) => {} // Appendable
+            VectorStorageEnum::DenseAppendableMemmapHalf(_) => {} // Appendable
+            VectorStorageEnum::DenseAppendableMemmapBf16(_) => {} // Appendable
+            #[cfg(feature = "rocksdb")]
+            VectorStorageEnum::SparseSimple(_) => {} // Not mmap
+            VectorStorageEnum::SparseVolatile(_) => {}        // Not mmap
+            VectorStorageEnum::SparseMmap(_) => {}            // Appendable
+            #[cfg(feature = "rocksdb")]
+            VectorStorageEnum::MultiDenseSimple(_) => {} // Not mmap
+            #[cfg(feature = "rocksdb")]
+            VectorStorageEnum::MultiDenseSimpleByte(_) => {} // Not mmap
+            #[cfg(feature = "rocksdb")]
+            VectorStorageEnum::MultiDenseSimpleHalf(_) => {} // Not mmap
+            VectorStorageEnum::MultiDenseVolatile(_) => {}    // Not mmap
+            #[cfg(test)]
+            VectorStorageEnum::MultiDenseVolatileByte(_) => {} // Not mmap
+            #[cfg(test)]
+            VectorStorageEnum::MultiDenseVolatileHalf(_) => {} // Not mmap
+            VectorStorageEnum::MultiDenseAppendableMemmap(_) => {} // Appendable
+            VectorStorageEnum::MultiDenseAppendableMemmapByte(_) => {} // Appendable
+            VectorStorageEnum::MultiDenseAppendableMemmapHalf(_) => {} // Appendable
+            VectorStorageEnum::MultiDenseAppendableMemmapBf16(_) => {} // Appendable
+        }
+        Ok(())
+    }
+
+    /// Keep recently accessed vectors of immutable dense storages locked in RAM.
+    ///
+    /// See [`crate::vector_storage::dense::hot_tier`]. Other storages are left as is.
+    pub fn enable_hot_tier(&mut self, hot_ratio: f64) {
+        match self {
+            #[cfg(feature = "rocksdb")]
+            VectorStorageEnum::DenseSimple(_) => {} // Not mmap
+            #[cfg(feature = "rocksdb")]
+            VectorStorageEnum::DenseSimpleByte(_) => {} // Not mmap
+            #[cfg(feature = "rocksdb")]
+            VectorStorageEnum::DenseSimpleHalf(_) => {} // Not mmap
+            VectorStorageEnum::DenseVolatile(_) => {} // Not mmap
+            #[cfg(test)]
+            VectorStorageEnum::DenseVolatileByte(_) => {} // Not mmap
+            #[cfg(test)]
+            VectorStorageEnum::DenseVolatileHalf(_) => {} // Not mmap
+            VectorStorageEnum::DenseMemmap(vs) => vs.enable_hot_tier(hot_ratio),
+            VectorStorageEnum::DenseMemmapByte(vs) => vs.enable_hot_tier(hot_ratio),
+            VectorStorageEnum::DenseMemmapHalf(vs) => vs.enable_hot_tier(hot_ratio),
+            VectorStorageEnum::DenseMemmapBf16(vs) => vs.enable_hot_tier(hot_ratio),
+            VectorStorageEnum::DenseAppendableMemmap(_) => {} // Appendable
+            VectorStorageEnum::DenseAppendableMemmapByte(_) => {} // Appendable
+            VectorStorageEnum::DenseAppendableMemmapHalf(_) => {} // Appendable
+            VectorStorageEnum::DenseAppendableMemmapBf16(_) => {} // Appendable
+            #[cfg(feature = "rocksdb")]
+            VectorStorageEnum::SparseSimple(_) => {} // Not mmap
+            VectorStorageEnum::SparseVolatile(_) => {}        // Not mmap
+            VectorStorageEnum::SparseMmap(_) => {}            // Appendable
+            #[cfg(feature = "rocksdb")]
+            VectorStorageEnum::MultiDenseSimple(_) => {} // Not mmap
+            #[cfg(feature = "rocksdb")]
+            VectorStorageEnum::MultiDenseSimpleByte(_) => {} // Not mmap
+            #[cfg(feature = "rocksdb")]
+            VectorStorageEnum::MultiDenseSimpleHalf(_) => {} // Not mmap
+            VectorStorageEnum::MultiDenseVolatile(_) => {}    // Not mmap
+            #[cfg(test)]
+            VectorStorageEnum::MultiDenseVolatileByte(_) => {} // Not mmap
+            #[cfg(test)]
+            VectorStorageEnum::MultiDenseVolatileHalf(_) => {} // Not mmap
+            VectorStorageEnum::MultiDenseAppendableMemmap(_) => {} // Appendable
+            VectorStorageEnum::MultiDenseAppendableMemmapByte(_) => {} // Appendable
+            VectorStorageEnum::MultiDenseAppendableMemmapHalf(_) => {} // Appendable
+            VectorStorageEnum::MultiDenseAppendableMemmapBf16(_) => {} // Appendable
+        }
+    }
+
+    /// Start reading vectors of `keys` of immutable dense storages in the background.
+    ///
+    /// See [`DenseVectorStorage::prefetch_dense_batch`]. Other storages are left as is.
+    pub fn prefetch_batch(&self, keys: &[PointOffsetType]) {
+        match self {
+            #[cfg(feature = "rocksdb")]
+            VectorStorageEnum::DenseSimple(_) => {} // Not mmap
+            #[cfg(feature = "rocksdb")]
+            VectorStorageEnum::DenseSimpleByte(_) => {} // Not mmap
+            #[cfg(feature = "rocksdb")]
+            VectorStorageEnum::DenseSimpleHalf(_) => {} // Not mmap
+            VectorStorageEnum::DenseVolatile(_) => {} // Not mmap
+            #[cfg(test)]
+            VectorStorageEnum::DenseVolatileByte(_) => {} // Not mmap
+            #[cfg(test)]
+            VectorStorageEnum::DenseVolatileHalf(_) => {} // Not mmap
+            VectorStorageEnum::DenseMemmap(vs) => vs.prefetch_dense_batch(keys),
+            VectorStorageEnum::DenseMemmapByte(vs) => vs.prefetch_dense_batch(keys),
+            VectorStorageEnum::DenseMemmapHalf(vs) => vs.prefetch_dense_batch(keys),
+            VectorStorageEnum::DenseMemmapBf16(vs) => vs.prefetch_dense_batch(keys),
+            VectorStorageEnum::DenseAppendableMemmap(_) => {} // Appendable
+            VectorStorageEnum::DenseAppendableMemmapByte(_) => {} // Appendable
+            VectorStorageEnum::DenseAppendableMemmapHalf(_) => {} // Appendable
+            VectorStorageEnum::DenseAppendableMemmapBf16(_) => {} // Appendable
+            #[cfg(feature = "rocksdb")]
+            VectorStorageEnum::SparseSimple(_) => {} // Not mmap
+            VectorStorageEnum::SparseVolatile(_) => {}        // Not mmap
+            VectorStorageEnum::SparseMmap(_) => {}            // Appendable
+            #[cfg(feature = "rocksdb")]
+            VectorStorageEnum::MultiDenseSimple(_) => {} // Not mmap
+            #[cfg(feature = "rocksdb")]
+            VectorStorageEnum::MultiDenseSimpleByte(_) => {} // Not mmap
+            #[cfg(feature = "rocksdb")]
+            VectorStorageEnum::MultiDenseSimpleHalf(_) => {} // Not mmap
+            VectorStorageEnum::MultiDenseVolatile(_) => {}    // Not mmap
+            #[cfg(test)]
+            VectorStorageEnum::MultiDenseVolatileByte(_) => {} // Not mmap
+            #[cfg(test)]
+            VectorStorageEnum::MultiDenseVolatileHalf(_) => {} // Not mmap
+            VectorStorageEnum::MultiDenseAppendableMemmap(_) => {} // Appendable
+            VectorStorageEnum::MultiDenseAppendableMemmapByte(_) => {} // Appendable
+            VectorStorageEnum::MultiDenseAppendableMemmapHalf(_) => {} // Appendable
+            VectorStorageEnum::MultiDenseAppendableMemmapBf16(_) => {} // Appendable
+        }
+    }
+
+    /// Compress vectors of immutable dense storages into the zstd-compressed cold tier.
+    ///
+    /// Other storages are left as is.
+    pub fn compress_files(&self) -> OperationResult<()> {
+        match self {
+            #[cfg(feature = "rocksdb")]
+            VectorStorageEnum::DenseSimple(_) => {} // Not mmap
+            #[cfg(feature = "rocksdb")]
+            VectorStorageEnum::DenseSimpleByte(_) => {} // Not mmap
+            #[cfg(feature = "rocksdb")]
+            VectorStorageEnum::DenseSimpleHalf(_) => {} // Not mmap
+            VectorStorageEnum::DenseVolatile(_) => {} // Not mmap
+            #[cfg(test)]
+            VectorStorageEnum::DenseVolatileByte(_) => {} // Not mmap
+            #[cfg(test)]
+            VectorStorageEnum::DenseVolatileHalf(_) => {} // Not mmap
+            VectorStorageEnum::DenseMemmap(vs) => vs.compress_files()?,
+            VectorStorageEnum::DenseMemmapByte(vs) => vs.compress_files()?,
+            VectorStorageEnum::DenseMemmapHalf(vs) => vs.compress_files()?,
+            VectorStorageEnum::DenseMemmapBf16(vs) => vs.compress_files()?,
+            VectorStorageEnum::DenseAppendableMemmap(_) => {} // Appendable
+            VectorStorageEnum::DenseAppendableMemmapByte(_) => {} // Appendable
+            VectorStorageEnum::DenseAppendableMemmapHalf(_) => {} // Appendable
+            VectorStorageEnum::DenseAppendableMemmapBf16(_) => {} // Appendable
+            #[cfg(feature = "rocksdb")]
+            VectorStorageEnum::SparseSimple(_) => {} // Not mmap
+            VectorStorageEnum::SparseVolatile(_) => {}        // Not mmap
+            VectorStorageEnum::SparseMmap(_) => {}            // Appendable
+            #[cfg(feature = "rocksdb")]
+            VectorStorageEnum::MultiDenseSimple(_) => {} // Not mmap
+            #[cfg(feature = "rocksdb")]
+            VectorStorageEnum::MultiDenseSimpleByte(_) => {} // Not mmap
+            #[cfg(feature = "rocksdb")]
+            VectorStorageEnum::MultiDenseSimpleHalf(_) => {} // Not mmap
+            VectorStorageEnum::MultiDenseVolatile(_) => {}    // Not mmap
+            #[cfg(test)]
+            VectorStorageEnum::MultiDenseVolatileByte(_) => {} // Not mmap
+            #[cfg(test)]
+            VectorStorageEnum::MultiDenseVolatileHalf(_) => {} // Not mmap
+            VectorStorageEnum::MultiDenseAppendableMemmap(_) => {} // Appendable
+            VectorStorageEnum::MultiDenseAppendableMemmapByte(_) => {} // Appendable
+            VectorStorageEnum::MultiDenseAppendableMemmapHalf(_) => {} // Appendable
+            VectorStorageEnum::MultiDenseAppendableMemmapBf16(_) => {} // Appendable
         }
         Ok(())
     }
@@ -511,9 +745,11 @@ impl VectorStorageEnum {
             VectorStorageEnum::DenseMemmap(vs) => vs.clear_cache()?,
             VectorStorageEnum::DenseMemmapByte(vs) => vs.clear_cache()?,
             VectorStorageEnum::DenseMemmapHalf(vs) => vs.clear_cache()?,
+            VectorStorageEnum::DenseMemmapBf16(vs) => vs.clear_cache()?,
             VectorStorageEnum::DenseAppendableMemmap(vs) => vs.clear_cache()?,
             VectorStorageEnum::DenseAppendableMemmapByte(vs) => vs.clear_cache()?,
             VectorStorageEnum::DenseAppendableMemmapHalf(vs) => vs.clear_cache()?,
+            VectorStorageEnum::DenseAppendableMemmapBf16(vs) => vs.clear_cache()?,
             #[cfg(feature = "rocksdb")]
             VectorStorageEnum::SparseSimple(_) => {} // Can't populate as it is not mmap
             VectorStorageEnum::SparseVolatile(_) => {} // Can't populate as it is not mmap
@@ -532,12 +768,16 @@ impl VectorStorageEnum {
             VectorStorageEnum::MultiDenseAppendableMemmap(vs) => vs.clear_cache()?,
             VectorStorageEnum::MultiDenseAppendableMemmapByte(vs) => vs.clear_cache()?,
             VectorStorageEnum::MultiDenseAppendableMemmapHalf(vs) => vs.clear_cache()?,
+            VectorStorageEnum::MultiDenseAppendableMemmapBf16(vs) => vs.clear_cache()?,
         }
         Ok(())
     }
 
     /// Get the raw bytes of the vector by the given key if it exists
-    pub fn get_vector_bytes_opt<P: AccessPattern>(&self, key: PointOffsetType) -> Option<&[u8]> {
+    pub fn get_vector_bytes_opt<P: AccessPattern>(
+        &self,
+        key: PointOffsetType,
+    ) -> Option<Cow<'_, [u8]>> {
         match self {
             #[cfg(feature = "rocksdb")]
             VectorStorageEnum::DenseSimple(v) => v.get_dense_bytes_opt::<P>(key),
@@ -553,9 +793,11 @@ impl VectorStorageEnum {
             VectorStorageEnum::DenseMemmap(v) => v.get_dense_bytes_opt::<P>(key),
             VectorStorageEnum::DenseMemmapByte(v) => v.get_dense_bytes_opt::<P>(key),
             VectorStorageEnum::DenseMemmapHalf(v) => v.get_dense_bytes_opt::<P>(key),
+            VectorStorageEnum::DenseMemmapBf16(v) => v.get_dense_bytes_opt::<P>(key),
             VectorStorageEnum::DenseAppendableMemmap(v) => v.get_dense_bytes_opt::<P>(key),
             VectorStorageEnum::DenseAppendableMemmapByte(v) => v.get_dense_bytes_opt::<P>(key),
             VectorStorageEnum::DenseAppendableMemmapHalf(v) => v.get_dense_bytes_opt::<P>(key),
+            VectorStorageEnum::DenseAppendableMemmapBf16(v) => v.get_dense_bytes_opt::<P>(key),
             #[cfg(feature = "rocksdb")]
             VectorStorageEnum::SparseSimple(_) => None,
             VectorStorageEnum::SparseVolatile(_) => None,
@@ -574,6 +816,7 @@ impl VectorStorageEnum {
             VectorStorageEnum::MultiDenseAppendableMemmap(_) => None,
             VectorStorageEnum::MultiDenseAppendableMemmapByte(_) => None,
             VectorStorageEnum::MultiDenseAppendableMemmapHalf(_) => None,
+            VectorStorageEnum::MultiDenseAppendableMemmapBf16(_) => None,
         }
     }
 
@@ -594,9 +837,11 @@ impl VectorStorageEnum {
             VectorStorageEnum::DenseMemmap(v) => return v.get_dense_vector_layout(),
             VectorStorageEnum::DenseMemmapByte(v) => return v.get_dense_vector_layout(),
             VectorStorageEnum::DenseMemmapHalf(v) => return v.get_dense_vector_layout(),
+            VectorStorageEnum::DenseMemmapBf16(v) => return v.get_dense_vector_layout(),
             VectorStorageEnum::DenseAppendableMemmap(v) => return v.get_dense_vector_layout(),
             VectorStorageEnum::DenseAppendableMemmapByte(v) => return v.get_dense_vector_layout(),
             VectorStorageEnum::DenseAppendableMemmapHalf(v) => return v.get_dense_vector_layout(),
+            VectorStorageEnum::DenseAppendableMemmapBf16(v) => return v.get_dense_vector_layout(),
             #[cfg(feature = "rocksdb")]
             VectorStorageEnum::SparseSimple(_) => {}
             VectorStorageEnum::SparseVolatile(_) => {}
@@ -615,11 +860,121 @@ impl VectorStorageEnum {
             VectorStorageEnum::MultiDenseAppendableMemmap(_) => {}
             VectorStorageEnum::MultiDenseAppendableMemmapByte(_) => {}
             VectorStorageEnum::MultiDenseAppendableMemmapHalf(_) => {}
+            VectorStorageEnum::MultiDenseAppendableMemmapBf16(_) => {}
         }
         Err(OperationError::service_error(
             "Vector layout is not implemented for this storage",
         ))
     }
+
+    /// Whether vectors can be moved and truncated in place, see [`VectorStorageEnum::move_vectors`]
+    pub fn is_defragmentable(&self) -> bool {
+        matches!(
+            self,
+            VectorStorageEnum::DenseAppendableMemmap(_)
+                | VectorStorageEnum::DenseAppendableMemmapByte(_)
+                | VectorStorageEnum::DenseAppendableMemmapHalf(_)
+                | VectorStorageEnum::DenseAppendableMemmapBf16(_)
+        )
+    }
+
+    /// Copy vectors along with their deleted flags, from the first to the second offset of each
+    /// move. Only supported by chunked mmap dense storages.
+    pub fn move_vectors(
+        &mut self,
+        moves: &[(PointOffsetType, PointOffsetType)],
+    ) -> OperationResult<()> {
+        match self {
+            #[cfg(feature = "rocksdb")]
+            VectorStorageEnum::DenseSimple(_) => {}
+            #[cfg(feature = "rocksdb")]
+            VectorStorageEnum::DenseSimpleByte(_) => {}
+            #[cfg(feature = "rocksdb")]
+            VectorStorageEnum::DenseSimpleHalf(_) => {}
+            VectorStorageEnum::DenseVolatile(_) => {}
+            #[cfg(test)]
+            VectorStorageEnum::DenseVolatileByte(_) => {}
+            #[cfg(test)]
+            VectorStorageEnum::DenseVolatileHalf(_) => {}
+            VectorStorageEnum::DenseMemmap(_) => {}
+            VectorStorageEnum::DenseMemmapByte(_) => {}
+            VectorStorageEnum::DenseMemmapHalf(_) => {}
+            VectorStorageEnum::DenseMemmapBf16(_) => {}
+            VectorStorageEnum::DenseAppendableMemmap(v) => return v.move_vectors(moves),
+            VectorStorageEnum::DenseAppendableMemmapByte(v) => return v.move_vectors(moves),
+            VectorStorageEnum::DenseAppendableMemmapHalf(v) => return v.move_vectors(moves),
+            VectorStorageEnum::DenseAppendableMemmapBf16(v) => return v.move_vectors(moves),
+            #[cfg(feature = "rocksdb")]
+            VectorStorageEnum::SparseSimple(_) => {}
+            VectorStorageEnum::SparseVolatile(_) => {}
+            VectorStorageEnum::SparseMmap(_) => {}
+            #[cfg(feature = "rocksdb")]
+            VectorStorageEnum::MultiDenseSimple(_) => {}
+            #[cfg(feature = "rocksdb")]
+            VectorStorageEnum::MultiDenseSimpleByte(_) => {}
+            #[cfg(feature = "rocksdb")]
+            VectorStorageEnum::MultiDenseSimpleHalf(_) => {}
+            VectorStorageEnum::MultiDenseVolatile(_) => {}
+            #[cfg(test)]
+            VectorStorageEnum::MultiDenseVolatileByte(_) => {}
+            #[cfg(test)]
+            VectorStorageEnum::MultiDenseVolatileHalf(_) => {}
+            VectorStorageEnum::MultiDenseAppendableMemmap(_) => {}
+            VectorStorageEnum::MultiDenseAppendableMemmapByte(_) => {}
+            VectorStorageEnum::MultiDenseAppendableMemmapHalf(_) => {}
+            VectorStorageEnum::MultiDenseAppendableMemmapBf16(_) => {}
+        }
+        Err(OperationError::service_error(
+            "Moving vectors is not implemented for this storage",
+        ))
+    }
+
+    /// Drop all vectors from `len` onwards. Only supported by chunked mmap dense storages.
+    pub fn truncate(&mut self, len: usize) -> OperationResult<()> {
+        match self {
+            #[cfg(feature = "rocksdb")]
+            VectorStorageEnum::DenseSimple(_) => {}
+            #[cfg(feature = "rocksdb")]
+            VectorStorageEnum::DenseSimpleByte(_) => {}
+            #[cfg(feature = "rocksdb")]
+            VectorStorageEnum::DenseSimpleHalf(_) => {}
+            VectorStorageEnum::DenseVolatile(_) => {}
+            #[cfg(test)]
+            VectorStorageEnum::DenseVolatileByte(_) => {}
+            #[cfg(test)]
+            VectorStorageEnum::DenseVolatileHalf(_) => {}
+            VectorStorageEnum::DenseMemmap(_) => {}
+            VectorStorageEnum::DenseMemmapByte(_) => {}
+            VectorStorageEnum::DenseMemmapHalf(_) => {}
+            VectorStorageEnum::DenseMemmapBf16(_) => {}
+            VectorStorageEnum::DenseAppendableMemmap(v) => return v.truncate(len),
+            VectorStorageEnum::DenseAppendableMemmapByte(v) => return v.truncate(len),
+            VectorStorageEnum::DenseAppendableMemmapHalf(v) => return v.truncate(len),
+            VectorStorageEnum::DenseAppendableMemmapBf16(v) => return v.truncate(len),
+            #[cfg(feature = "rocksdb")]
+            VectorStorageEnum::SparseSimple(_) => {}
+            VectorStorageEnum::SparseVolatile(_) => {}
+            VectorStorageEnum::SparseMmap(_) => {}
+            #[cfg(feature = "rocksdb")]
+            VectorStorageEnum::MultiDenseSimple(_) => {}
+            #[cfg(feature = "rocksdb")]
+            VectorStorageEnum::MultiDenseSimpleByte(_) => {}
+            #[cfg(feature = "rocksdb")]
+            VectorStorageEnum::MultiDenseSimpleHalf(_) => {}
+            VectorStorageEnum::MultiDenseVolatile(_) => {}
+            #[cfg(test)]
+            VectorStorageEnum::MultiDenseVolatileByte(_) => {}
+            #[cfg(test)]
+            VectorStorageEnum::MultiDenseVolatileHalf(_) => {}
+            VectorStorageEnum::MultiDenseAppendableMemmap(_) => {}
+            VectorStorageEnum::MultiDenseAppendableMemmapByte(_) => {}
+            VectorStorageEnum::MultiDenseAppendableMemmapHalf(_) => {}
+            VectorStorageEnum::MultiDenseAppendableMemmapBf16(_) => {}
+        }
+        Err(OperationError::service_error(
+            "Truncation is not implemented for this storage",
+        ))
+    }
 }
 
 impl VectorStorage for VectorStorageEnum {
@@ -639,9 +994,11 @@ impl VectorStorage for VectorStorageEnum {
             VectorStorageEnum::DenseMemmap(v) => v.distance(),
             VectorStorageEnum::DenseMemmapByte(v) => v.distance(),
             VectorStorageEnum::DenseMemmapHalf(v) => v.distance(),
+            VectorStorageEnum::DenseMemmapBf16(v) => v.distance(),
             VectorStorageEnum::DenseAppendableMemmap(v) => v.distance(),
             VectorStorageEnum::DenseAppendableMemmapByte(v) => v.distance(),
             VectorStorageEnum::DenseAppendableMemmapHalf(v) => v.distance(),
+            VectorStorageEnum::DenseAppendableMemmapBf16(v) => v.distance(),
             #[cfg(feature = "rocksdb")]
             VectorStorageEnum::SparseSimple(v) => v.distance(),
             VectorStorageEnum::SparseVolatile(v) => v.distance(),
@@ -660,6 +1017,7 @@ impl VectorStorage for VectorStorageEnum {
             VectorStorageEnum::MultiDenseAppendableMemmap(v) => v.distance(),
             VectorStorageEnum::MultiDenseAppendableMemmapByte(v) => v.distance(),
             VectorStorageEnum::MultiDenseAppendableMemmapHalf(v) => v.distance(),
+            VectorStorageEnum::MultiDenseAppendableMemmapBf16(v) => v.distance(),
         }
     }
 
@@ -679,9 +1037,11 @@ impl VectorStorage for VectorStorageEnum {
             VectorStorageEnum::DenseMemmap(v) => v.datatype(),
             VectorStorageEnum::DenseMemmapByte(v) => v.datatype(),
             VectorStorageEnum::DenseMemmapHalf(v) => v.datatype(),
+            VectorStorageEnum::DenseMemmapBf16(v) => v.datatype(),
             VectorStorageEnum::DenseAppendableMemmap(v) => v.datatype(),
             VectorStorageEnum::DenseAppendableMemmapByte(v) => v.datatype(),
             VectorStorageEnum::DenseAppendableMemmapHalf(v) => v.datatype(),
+            VectorStorageEnum::DenseAppendableMemmapBf16(v) => v.datatype(),
             #[cfg(feature = "rocksdb")]
             VectorStorageEnum::SparseSimple(v) => v.datatype(),
             VectorStorageEnum::SparseVolatile(v) => v.datatype(),
@@ -700,6 +1060,7 @@ impl VectorStorage for VectorStorageEnum {
             VectorStorageEnum::MultiDenseAppendableMemmap(v) => v.datatype(),
             VectorStorageEnum::MultiDenseAppendableMemmapByte(v) => v.datatype(),
             VectorStorageEnum::MultiDenseAppendableMemmapHalf(v) => v.datatype(),
+            VectorStorageEnum::MultiDenseAppendableMemmapBf16(v) => v.datatype(),
         }
     }
 
@@ -721,9 +1082,11 @@ impl VectorStorage for VectorStorageEnum {
             VectorStorageEnum::DenseMemmap(v) => v.is_on_disk(),
             VectorStorageEnum::DenseMemmapByte(v) => v.is_on_disk(),
             VectorStorageEnum::DenseMemmapHalf(v) => v.is_on_disk(),
+            VectorStorageEnum::DenseMemmapBf16(v) => v.is_on_disk(),
             VectorStorageEnum::DenseAppendableMemmap(v) => v.is_on_disk(),
             VectorStorageEnum::DenseAppendableMemmapByte(v) => v.is_on_disk(),
             VectorStorageEnum::DenseAppendableMemmapHalf(v) => v.is_on_disk(),
+            VectorStorageEnum::DenseAppendableMemmapBf16(v) => v.is_on_disk(),
             #[cfg(feature = "rocksdb")]
             VectorStorageEnum::SparseSimple(v) => v.is_on_disk(),
             VectorStorageEnum::SparseVolatile(v) => v.is_on_disk(),
@@ -742,6 +1105,7 @@ impl VectorStorage for VectorStorageEnum {
             VectorStorageEnum::MultiDenseAppendableMemmap(v) => v.is_on_disk(),
             VectorStorageEnum::MultiDenseAppendableMemmapByte(v) => v.is_on_disk(),
             VectorStorageEnum::MultiDenseAppendableMemmapHalf(v) => v.is_on_disk(),
+            VectorStorageEnum::MultiDenseAppendableMemmapBf16(v) => v.is_on_disk(),
         }
     }
 
@@ -761,9 +1125,11 @@ impl VectorStorage for VectorStorageEnum {
             VectorStorageEnum::DenseMemmap(v) => v.total_vector_count(),
             VectorStorageEnum::DenseMemmapByte(v) => v.total_vector_count(),
             VectorStorageEnum::DenseMemmapHalf(v) => v.total_vector_count(),
+            VectorStorageEnum::DenseMemmapBf16(v) => v.total_vector_count(),
             VectorStorageEnum::DenseAppendableMemmap(v) => v.total_vector_count(),
             VectorStorageEnum::DenseAppendableMemmapByte(v) => v.total_vector_count(),
             VectorStorageEnum::DenseAppendableMemmapHalf(v) => v.total_vector_count(),
+            VectorStorageEnum::DenseAppendableMemmapBf16(v) => v.total_vector_count(),
             #[cfg(feature = "rocksdb")]
             VectorStorageEnum::SparseSimple(v) => v.total_vector_count(),
             VectorStorageEnum::SparseVolatile(v) => v.total_vector_count(),
@@ -782,6 +1148,7 @@ impl VectorStorage for VectorStorageEnum {
             VectorStorageEnum::MultiDenseAppendableMemmap(v) => v.total_vector_count(),
             VectorStorageEnum::MultiDenseAppendableMemmapByte(v) => v.total_vector_count(),
             VectorStorageEnum::MultiDenseAppendableMemmapHalf(v) => v.total_vector_count(),
+            VectorStorageEnum::MultiDenseAppendableMemmapBf16(v) => v.total_vector_count(),
         }
     }
 
@@ -801,9 +1168,11 @@ impl VectorStorage for VectorStorageEnum {
             VectorStorageEnum::DenseMemmap(v) => v.get_vector::<P>(key),
             VectorStorageEnum::DenseMemmapByte(v) => v.get_vector::<P>(key),
             VectorStorageEnum::DenseMemmapHalf(v) => v.get_vector::<P>(key),
+            VectorStorageEnum::DenseMemmapBf16(v) => v.get_vector::<P>(key),
             VectorStorageEnum::DenseAppendableMemmap(v) => v.get_vector::<P>(key),
             VectorStorageEnum::DenseAppendableMemmapByte(v) => v.get_vector::<P>(key),
             VectorStorageEnum::DenseAppendableMemmapHalf(v) => v.get_vector::<P>(key),
+            VectorStorageEnum::DenseAppendableMemmapBf16(v) => v.get_vector::<P>(key),
             #[cfg(feature = "rocksdb")]
             VectorStorageEnum::SparseSimple(v) => v.get_vector::<P>(key),
             VectorStorageEnum::SparseVolatile(v) => v.get_vector::<P>(key),
@@ -822,6 +1191,7 @@ impl VectorStorage for VectorStorageEnum {
             VectorStorageEnum::MultiDenseAppendableMemmap(v) => v.get_vector::<P>(key),
             VectorStorageEnum::MultiDenseAppendableMemmapByte(v) => v.get_vector::<P>(key),
             VectorStorageEnum::MultiDenseAppendableMemmapHalf(v) => v.get_vector::<P>(key),
+            VectorStorageEnum::MultiDenseAppendableMemmapBf16(v) => v.get_vector::<P>(key),
         }
     }
 
@@ -845,9 +1215,11 @@ impl VectorStorage for VectorStorageEnum {
             VectorStorageEnum::DenseMemmap(v) => v.read_vectors::<P>(keys, callback),
             VectorStorageEnum::DenseMemmapByte(v) => v.read_vectors::<P>(keys, callback),
             VectorStorageEnum::DenseMemmapHalf(v) => v.read_vectors::<P>(keys, callback),
+            VectorStorageEnum::DenseMemmapBf16(v) => v.read_vectors::<P>(keys, callback),
             VectorStorageEnum::DenseAppendableMemmap(v) => v.read_vectors::<P>(keys, callback),
             VectorStorageEnum::DenseAppendableMemmapByte(v) => v.read_vectors::<P>(keys, callback),
             VectorStorageEnum::DenseAppendableMemmapHalf(v) => v.read_vectors::<P>(keys, callback),
+            VectorStorageEnum::DenseAppendableMemmapBf16(v) => v.read_vectors::<P>(keys, callback),
             #[cfg(feature = "rocksdb")]
             VectorStorageEnum::SparseSimple(v) => v.read_vectors::<P>(keys, callback),
             VectorStorageEnum::SparseVolatile(v) => v.read_vectors::<P>(keys, callback),
@@ -870,6 +1242,9 @@ impl VectorStorage for VectorStorageEnum {
             VectorStorageEnum::MultiDenseAppendableMemmapHalf(v) => {
                 v.read_vectors::<P>(keys, callback)
             }
+            VectorStorageEnum::MultiDenseAppendableMemmapBf16(v) => {
+                v.read_vectors::<P>(keys, callback)
+            }
         }
     }
 
@@ -889,9 +1264,11 @@ impl VectorStorage for VectorStorageEnum {
             VectorStorageEnum::DenseMemmap(v) => v.get_vector_opt::<P>(key),
             VectorStorageEnum::DenseMemmapByte(v) => v.get_vector_opt::<P>(key),
             VectorStorageEnum::DenseMemmapHalf(v) => v.get_vector_opt::<P>(key),
+            VectorStorageEnum::DenseMemmapBf16(v) => v.get_vector_opt::<P>(key),
             VectorStorageEnum::DenseAppendableMemmap(v) => v.get_vector_opt::<P>(key),
             VectorStorageEnum::DenseAppendableMemmapByte(v) => v.get_vector_opt::<P>(key),
             VectorStorageEnum::DenseAppendableMemmapHalf(v) => v.get_vector_opt::<P>(key),
+            VectorStorageEnum::DenseAppendableMemmapBf16(v) => v.get_vector_opt::<P>(key),
             #[cfg(feature = "rocksdb")]
             VectorStorageEnum::SparseSimple(v) => v.get_vector_opt::<P>(key),
             VectorStorageEnum::SparseVolatile(v) => v.get_vector_opt::<P>(key),
@@ -910,6 +1287,7 @@ impl VectorStorage for VectorStorageEnum {
             VectorStorageEnum::MultiDenseAppendableMemmap(v) => v.get_vector_opt::<P>(key),
             VectorStorageEnum::MultiDenseAppendableMemmapByte(v) => v.get_vector_opt::<P>(key),
             VectorStorageEnum::MultiDenseAppendableMemmapHalf(v) => v.get_vector_opt::<P>(key),
+            VectorStorageEnum::MultiDenseAppendableMemmapBf16(v) => v.get_vector_opt::<P>(key),
         }
     }
 
@@ -934,6 +1312,7 @@ impl VectorStorage for VectorStorageEnum {
             VectorStorageEnum::DenseMemmap(v) => v.insert_vector(key, vector, hw_counter),
             VectorStorageEnum::DenseMemmapByte(v) => v.insert_vector(key, vector, hw_counter),
             VectorStorageEnum::DenseMemmapHalf(v) => v.insert_vector(key, vector, hw_counter),
+            VectorStorageEnum::DenseMemmapBf16(v) => v.insert_vector(key, vector, hw_counter),
             VectorStorageEnum::DenseAppendableMemmap(v) => v.insert_vector(key, vector, hw_counter),
             VectorStorageEnum::DenseAppendableMemmapByte(v) => {
                 v.insert_vector(key, vector, hw_counter)
@@ -941,6 +1320,9 @@ impl VectorStorage for VectorStorageEnum {
             VectorStorageEnum::DenseAppendableMemmapHalf(v) => {
                 v.insert_vector(key, vector, hw_counter)
             }
+            VectorStorageEnum::DenseAppendableMemmapBf16(v) => {
+                v.insert_vector(key, vector, hw_counter)
+            }
             #[cfg(feature = "rocksdb")]
             VectorStorageEnum::SparseSimple(v) => v.insert_vector(key, vector, hw_counter),
             VectorStorageEnum::SparseVolatile(v) => v.insert_vector(key, vector, hw_counter),
@@ -969,6 +1351,9 @@ impl VectorStorage for VectorStorageEnum {
             VectorStorageEnum::MultiDenseAppendableMemmapHalf(v) => {
                 v.insert_vector(key, vector, hw_counter)
             }
+            VectorStorageEnum::MultiDenseAppendableMemmapBf16(v) => {
+                v.insert_vector(key, vector, hw_counter)
+            }
         }
     }
 
@@ -992,6 +1377,7 @@ impl VectorStorage for VectorStorageEnum {
             VectorStorageEnum::DenseMemmap(v) => v.update_from(other_vectors, stopped),
             VectorStorageEnum::DenseMemmapByte(v) => v.update_from(other_vectors, stopped),
             VectorStorageEnum::DenseMemmapHalf(v) => v.update_from(other_vectors, stopped),
+            VectorStorageEnum::DenseMemmapBf16(v) => v.update_from(other_vectors, stopped),
             VectorStorageEnum::DenseAppendableMemmap(v) => v.update_from(other_vectors, stopped),
             VectorStorageEnum::DenseAppendableMemmapByte(v) => {
                 v.update_from(other_vectors, stopped)
@@ -999,6 +1385,9 @@ impl VectorStorage for VectorStorageEnum {
             VectorStorageEnum::DenseAppendableMemmapHalf(v) => {
                 v.update_from(other_vectors, stopped)
             }
+            VectorStorageEnum::DenseAppendableMemmapBf16(v) => {
+                v.update_from(other_vectors, stopped)
+            }
             #[cfg(feature = "rocksdb")]
             VectorStorageEnum::SparseSimple(v) => v.update_from(other_vectors, stopped),
             VectorStorageEnum::SparseVolatile(v) => v.update_from(other_vectors, stopped),
@@ -1023,6 +1412,9 @@ impl VectorStorage for VectorStorageEnum {
             VectorStorageEnum::MultiDenseAppendableMemmapHalf(v) => {
                 v.update_from(other_vectors, stopped)
             }
+            VectorStorageEnum::MultiDenseAppendableMemmapBf16(v) => {
+                v.update_from(other_vectors, stopped)
+            }
         }
     }
 
@@ -1042,9 +1434,11 @@ impl VectorStorage for VectorStorageEnum {
             VectorStorageEnum::DenseMemmap(v) => v.flusher(),
             VectorStorageEnum::DenseMemmapByte(v) => v.flusher(),
             VectorStorageEnum::DenseMemmapHalf(v) => v.flusher(),
+            VectorStorageEnum::DenseMemmapBf16(v) => v.flusher(),
             VectorStorageEnum::DenseAppendableMemmap(v) => v.flusher(),
             VectorStorageEnum::DenseAppendableMemmapByte(v) => v.flusher(),
             VectorStorageEnum::DenseAppendableMemmapHalf(v) => v.flusher(),
+            VectorStorageEnum::DenseAppendableMemmapBf16(v) => v.flusher(),
             #[cfg(feature = "rocksdb")]
             VectorStorageEnum::SparseSimple(v) => v.flusher(),
             VectorStorageEnum::SparseVolatile(v) => v.flusher(),
@@ -1063,6 +1457,7 @@ impl VectorStorage for VectorStorageEnum {
             VectorStorageEnum::MultiDenseAppendableMemmap(v) => v.flusher(),
             VectorStorageEnum::MultiDenseAppendableMemmapByte(v) => v.flusher(),
             VectorStorageEnum::MultiDenseAppendableMemmapHalf(v) => v.flusher(),
+            VectorStorageEnum::MultiDenseAppendableMemmapBf16(v) => v.flusher(),
         }
     }
 
@@ -1082,9 +1477,11 @@ impl VectorStorage for VectorStorageEnum {
             VectorStorageEnum::DenseMemmap(v) => v.files(),
             VectorStorageEnum::DenseMemmapByte(v) => v.files(),
             VectorStorageEnum::DenseMemmapHalf(v) => v.files(),
+            VectorStorageEnum::DenseMemmapBf16(v) => v.files(),
             VectorStorageEnum::DenseAppendableMemmap(v) => v.files(),
             VectorStorageEnum::DenseAppendableMemmapByte(v) => v.files(),
             VectorStorageEnum::DenseAppendableMemmapHalf(v) => v.files(),
+            VectorStorageEnum::DenseAppendableMemmapBf16(v) => v.files(),
             #[cfg(feature = "rocksdb")]
             VectorStorageEnum::SparseSimple(v) => v.files(),
             VectorStorageEnum::SparseVolatile(v) => v.files(),
@@ -1103,6 +1500,7 @@ impl VectorStorage for VectorStorageEnum {
             VectorStorageEnum::MultiDenseAppendableMemmap(v) => v.files(),
             VectorStorageEnum::MultiDenseAppendableMemmapByte(v) => v.files(),
             VectorStorageEnum::MultiDenseAppendableMemmapHalf(v) => v.files(),
+            VectorStorageEnum::MultiDenseAppendableMemmapBf16(v) => v.files(),
         }
     }
 
@@ -1122,9 +1520,11 @@ impl VectorStorage for VectorStorageEnum {
             VectorStorageEnum::DenseMemmap(v) => v.immutable_files(),
             VectorStorageEnum::DenseMemmapByte(v) => v.immutable_files(),
             VectorStorageEnum::DenseMemmapHalf(v) => v.immutable_files(),
+            VectorStorageEnum::DenseMemmapBf16(v) => v.immutable_files(),
             VectorStorageEnum::DenseAppendableMemmap(v) => v.immutable_files(),
             VectorStorageEnum::DenseAppendableMemmapByte(v) => v.immutable_files(),
             VectorStorageEnum::DenseAppendableMemmapHalf(v) => v.immutable_files(),
+            VectorStorageEnum::DenseAppendableMemmapBf16(v) => v.immutable_files(),
             #[cfg(feature = "rocksdb")]
             VectorStorageEnum::SparseSimple(v) => v.immutable_files(),
             VectorStorageEnum::SparseVolatile(v) => v.immutable_files(),
@@ -1143,6 +1543,7 @@ impl VectorStorage for VectorStorageEnum {
             VectorStorageEnum::MultiDenseAppendableMemmap(v) => v.immutable_files(),
             VectorStorageEnum::MultiDenseAppendableMemmapByte(v) => v.immutable_files(),
             VectorStorageEnum::MultiDenseAppendableMemmapHalf(v) => v.immutable_files(),
+            VectorStorageEnum::MultiDenseAppendableMemmapBf16(v) => v.immutable_files(),
         }
     }
 
@@ -1162,9 +1563,11 @@ impl VectorStorage for VectorStorageEnum {
             VectorStorageEnum::DenseMemmap(v) => v.delete_vector(key),
             VectorStorageEnum::DenseMemmapByte(v) => v.delete_vector(key),
             VectorStorageEnum::DenseMemmapHalf(v) => v.delete_vector(key),
+            VectorStorageEnum::DenseMemmapBf16(v) => v.delete_vector(key),
             VectorStorageEnum::DenseAppendableMemmap(v) => v.delete_vector(key),
             VectorStorageEnum::DenseAppendableMemmapByte(v) => v.delete_vector(key),
             VectorStorageEnum::DenseAppendableMemmapHalf(v) => v.delete_vector(key),
+            VectorStorageEnum::DenseAppendableMemmapBf16(v) => v.delete_vector(key),
             #[cfg(feature = "rocksdb")]
             VectorStorageEnum::SparseSimple(v) => v.delete_vector(key),
             VectorStorageEnum::SparseVolatile(v) => v.delete_vector(key),
@@ -1183,6 +1586,7 @@ impl VectorStorage for VectorStorageEnum {
             VectorStorageEnum::MultiDenseAppendableMemmap(v) => v.delete_vector(key),
             VectorStorageEnum::MultiDenseAppendableMemmapByte(v) => v.delete_vector(key),
             VectorStorageEnum::MultiDenseAppendableMemmapHalf(v) => v.delete_vector(key),
+            VectorStorageEnum::MultiDenseAppendableMemmapBf16(v) => v.delete_vector(key),
         }
     }
 
@@ -1202,9 +1606,11 @@ impl VectorStorage for VectorStorageEnum {
             VectorStorageEnum::DenseMemmap(v) => v.is_deleted_vector(key),
             VectorStorageEnum::DenseMemmapByte(v) => v.is_deleted_vector(key),
             VectorStorageEnum::DenseMemmapHalf(v) => v.is_deleted_vector(key),
+            VectorStorageEnum::DenseMemmapBf16(v) => v.is_deleted_vector(key),
             VectorStorageEnum::DenseAppendableMemmap(v) => v.is_deleted_vector(key),
             VectorStorageEnum::DenseAppendableMemmapByte(v) => v.is_deleted_vector(key),
             VectorStorageEnum::DenseAppendableMemmapHalf(v) => v.is_deleted_vector(key),
+            VectorStorageEnum::DenseAppendableMemmapBf16(v) => v.is_deleted_vector(key),
             #[cfg(feature = "rocksdb")]
             VectorStorageEnum::SparseSimple(v) => v.is_deleted_vector(key),
             VectorStorageEnum::SparseVolatile(v) => v.is_deleted_vector(key),
@@ -1223,6 +1629,7 @@ impl VectorStorage for VectorStorageEnum {
             VectorStorageEnum::MultiDenseAppendableMemmap(v) => v.is_deleted_vector(key),
             VectorStorageEnum::MultiDenseAppendableMemmapByte(v) => v.is_deleted_vector(key),
             VectorStorageEnum::MultiDenseAppendableMemmapHalf(v) => v.is_deleted_vector(key),
+            VectorStorageEnum::MultiDenseAppendableMemmapBf16(v) => v.is_deleted_vector(key),
         }
     }
 
@@ -1242,9 +1649,11 @@ impl VectorStorage for VectorStorageEnum {
             VectorStorageEnum::DenseMemmap(v) => v.deleted_vector_count(),
             VectorStorageEnum::DenseMemmapByte(v) => v.deleted_vector_count(),
             VectorStorageEnum::DenseMemmapHalf(v) => v.deleted_vector_count(),
+            VectorStorageEnum::DenseMemmapBf16(v) => v.deleted_vector_count(),
             VectorStorageEnum::DenseAppendableMemmap(v) => v.deleted_vector_count(),
             VectorStorageEnum::DenseAppendableMemmapByte(v) => v.deleted_vector_count(),
             VectorStorageEnum::DenseAppendableMemmapHalf(v) => v.deleted_vector_count(),
+            VectorStorageEnum::DenseAppendableMemmapBf16(v) => v.deleted_vector_count(),
             #[cfg(feature = "rocksdb")]
             VectorStorageEnum::SparseSimple(v) => v.deleted_vector_count(),
             VectorStorageEnum::SparseVolatile(v) => v.deleted_vector_count(),
@@ -1263,6 +1672,7 @@ impl VectorStorage for VectorStorageEnum {
             VectorStorageEnum::MultiDenseAppendableMemmap(v) => v.deleted_vector_count(),
             VectorStorageEnum::MultiDenseAppendableMemmapByte(v) => v.deleted_vector_count(),
             VectorStorageEnum::MultiDenseAppendableMemmapHalf(v) => v.deleted_vector_count(),
+            VectorStorageEnum::MultiDenseAppendableMemmapBf16(v) => v.deleted_vector_count(),
         }
     }
 
@@ -1282,9 +1692,11 @@ impl VectorStorage for VectorStorageEnum {
             VectorStorageEnum::DenseMemmap(v) => v.deleted_vector_bitslice(),
             VectorStorageEnum::DenseMemmapByte(v) => v.deleted_vector_bitslice(),
             VectorStorageEnum::DenseMemmapHalf(v) => v.deleted_vector_bitslice(),
+            VectorStorageEnum::DenseMemmapBf16(v) => v.deleted_vector_bitslice(),
             VectorStorageEnum::DenseAppendableMemmap(v) => v.deleted_vector_bitslice(),
             VectorStorageEnum::DenseAppendableMemmapByte(v) => v.deleted_vector_bitslice(),
             VectorStorageEnum::DenseAppendableMemmapHalf(v) => v.deleted_vector_bitslice(),
+            VectorStorageEnum::DenseAppendableMemmapBf16(v) => v.deleted_vector_bitslice(),
             #[cfg(feature = "rocksdb")]
             VectorStorageEnum::SparseSimple(v) => v.deleted_vector_bitslice(),
             VectorStorageEnum::SparseVolatile(v) => v.deleted_vector_bitslice(),
@@ -1303,6 +1715,7 @@ impl VectorStorage for VectorStorageEnum {
             VectorStorageEnum::MultiDenseAppendableMemmap(v) => v.deleted_vector_bitslice(),
             VectorStorageEnum::MultiDenseAppendableMemmapByte(v) => v.deleted_vector_bitslice(),
             VectorStorageEnum::MultiDenseAppendableMemmapHalf(v) => v.deleted_vector_bitslice(),
+            VectorStorageEnum::MultiDenseAppendableMemmapBf16(v) => v.deleted_vector_bitslice(),
         }
     }
 }
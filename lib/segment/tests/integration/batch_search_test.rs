@@ -144,6 +144,9 @@ fn test_batch_and_single_request_equivalency() {
         on_disk: Some(false),
         payload_m: None,
         inline_storage: None,
+        target_recall: None,
+        m0: None,
+        lock_in_ram: None,
     };
 
     let permit_cpu_count = num_rayon_threads(hnsw_config.max_indexing_threads);
@@ -169,6 +172,7 @@ fn test_batch_and_single_request_equivalency() {
             hnsw_global_config: &HnswGlobalConfig::default(),
             feature_flags: FeatureFlags::default(),
             progress: ProgressTracker::new_for_test(),
+            build_checkpoint_dir: None,
         },
     )
     .unwrap();
@@ -45,6 +45,7 @@ fn compare_search_result(result_a: &[Vec<ScoredPointOffset>], result_b: &[Vec<Sc
 #[rstest]
 #[case::nearest(QueryVariant::Nearest, VectorStorageDatatype::Uint8, 32, 10)]
 #[case::nearest(QueryVariant::Nearest, VectorStorageDatatype::Float16, 32, 10)]
+#[case::nearest(QueryVariant::Nearest, VectorStorageDatatype::Bfloat16, 32, 10)]
 #[case::discovery(QueryVariant::Discovery, VectorStorageDatatype::Uint8, 128, 20)]
 #[case::reco_best_score(QueryVariant::RecoBestScore, VectorStorageDatatype::Float16, 64, 20)]
 #[case::reco_sum_scores(QueryVariant::RecoSumScores, VectorStorageDatatype::Float16, 64, 20)]
@@ -109,7 +110,8 @@ fn test_byte_storage_hnsw(
         assert!(matches!(
             raw_storage,
             &VectorStorageEnum::DenseAppendableMemmapByte(_)
-                | &VectorStorageEnum::DenseAppendableMemmapHalf(_),
+                | &VectorStorageEnum::DenseAppendableMemmapHalf(_)
+                | &VectorStorageEnum::DenseAppendableMemmapBf16(_),
         ));
     }
 
@@ -175,6 +177,9 @@ fn test_byte_storage_hnsw(
         on_disk: Some(false),
         payload_m: None,
         inline_storage: None,
+        target_recall: None,
+        m0: None,
+        lock_in_ram: None,
     };
 
     let permit_cpu_count = 1; // single-threaded for deterministic build
@@ -201,6 +206,7 @@ fn test_byte_storage_hnsw(
             hnsw_global_config: &HnswGlobalConfig::default(),
             feature_flags: FeatureFlags::default(),
             progress: ProgressTracker::new_for_test(),
+            build_checkpoint_dir: None,
         },
     )
     .unwrap();
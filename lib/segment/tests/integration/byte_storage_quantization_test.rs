@@ -44,7 +44,7 @@ where
 {
     match data_type {
         VectorStorageDatatype::Float32 => unreachable!(),
-        VectorStorageDatatype::Float16 => {
+        VectorStorageDatatype::Float16 | VectorStorageDatatype::Bfloat16 => {
             let mut vector = segment::fixtures::payload_fixtures::random_vector(rnd_gen, dim);
             vector.iter_mut().for_each(|x| *x -= 0.5);
             vector
@@ -163,6 +163,15 @@ fn sames_count(a: &[Vec<ScoredPointOffset>], b: &[Vec<ScoredPointOffset>]) -> us
     32, // ef
     80., // min_acc out of 100
 )]
+#[case::nearest_scalar_dot(
+    QueryVariant::Nearest,
+    VectorStorageDatatype::Bfloat16,
+    QuantizationVariant::Scalar,
+    Distance::Dot,
+    32, // dim
+    32, // ef
+    80., // min_acc out of 100
+)]
 #[case::nearest_scalar_dot(
     QueryVariant::Nearest,
     VectorStorageDatatype::Uint8,
@@ -247,7 +256,8 @@ fn test_byte_storage_binary_quantization_hnsw(
         assert!(matches!(
             raw_storage,
             &VectorStorageEnum::DenseAppendableMemmapByte(_)
-                | &VectorStorageEnum::DenseAppendableMemmapHalf(_),
+                | &VectorStorageEnum::DenseAppendableMemmapHalf(_)
+                | &VectorStorageEnum::DenseAppendableMemmapBf16(_),
         ));
     }
 
@@ -299,6 +309,7 @@ fn test_byte_storage_binary_quantization_hnsw(
             always_ram: None,
             encoding: None,
             query_encoding: None,
+            oversampling: None,
         }
         .into(),
     };
@@ -313,6 +324,7 @@ fn test_byte_storage_binary_quantization_hnsw(
                 QuantizedVectorsStorageType::Immutable,
                 quantized_data_path,
                 4,
+                None,
                 &stopped,
             )
             .unwrap();
@@ -328,6 +340,9 @@ fn test_byte_storage_binary_quantization_hnsw(
         on_disk: Some(false),
         payload_m: None,
         inline_storage: None,
+        target_recall: None,
+        m0: None,
+        lock_in_ram: None,
     };
 
     let permit_cpu_count = 1; // single-threaded for deterministic build
@@ -354,6 +369,7 @@ fn test_byte_storage_binary_quantization_hnsw(
             hnsw_global_config: &HnswGlobalConfig::default(),
             feature_flags: FeatureFlags::default(),
             progress: ProgressTracker::new_for_test(),
+            build_checkpoint_dir: None,
         },
     )
     .unwrap();
@@ -23,8 +23,8 @@ use segment::payload_json;
 use segment::segment_constructor::VectorIndexBuildArgs;
 use segment::segment_constructor::simple_segment_constructor::build_simple_segment;
 use segment::types::{
-    Condition, Distance, FieldCondition, Filter, HnswConfig, HnswGlobalConfig, PayloadSchemaType,
-    Range, SearchParams, SeqNumberType,
+    AcornSearchParams, Condition, Distance, FieldCondition, Filter, HnswConfig, HnswGlobalConfig,
+    PayloadSchemaType, Range, SearchParams, SeqNumberType,
 };
 use tempfile::Builder;
 
@@ -102,6 +102,9 @@ fn _test_filterable_hnsw(
         on_disk: Some(false),
         payload_m: None,
         inline_storage: None,
+        target_recall: None,
+        m0: None,
+        lock_in_ram: None,
     };
 
     let vector_storage = &segment.vector_data[DEFAULT_VECTOR_NAME].vector_storage;
@@ -169,6 +172,7 @@ fn _test_filterable_hnsw(
             hnsw_global_config: &HnswGlobalConfig::default(),
             feature_flags: FeatureFlags::default(),
             progress: ProgressTracker::new_for_test(),
+            build_checkpoint_dir: None,
         },
     )
     .unwrap();
@@ -286,6 +290,9 @@ fn test_hnsw_search_top_zero(#[case] num_vectors: u64, #[case] full_scan_thresho
         on_disk: Some(false),
         payload_m: None,
         inline_storage: None,
+        target_recall: None,
+        m0: None,
+        lock_in_ram: None,
     };
 
     let vector_storage = &segment.vector_data[DEFAULT_VECTOR_NAME].vector_storage;
@@ -330,6 +337,7 @@ fn test_hnsw_search_top_zero(#[case] num_vectors: u64, #[case] full_scan_thresho
             hnsw_global_config: &HnswGlobalConfig::default(),
             feature_flags: FeatureFlags::default(),
             progress: ProgressTracker::new_for_test(),
+            build_checkpoint_dir: None,
         },
     )
     .unwrap();
@@ -347,3 +355,150 @@ fn test_hnsw_search_top_zero(#[case] num_vectors: u64, #[case] full_scan_thresho
         )
         .unwrap();
 }
+
+#[rstest]
+#[case::acorn(true)]
+#[case::plain(false)]
+fn test_filterable_hnsw_moderate_cardinality(#[case] acorn: bool) {
+    let stopped = AtomicBool::new(false);
+
+    let dim = 8;
+    let m = 16;
+    let num_vectors: u64 = 5_000;
+    let ef_construct = 64;
+    let distance = Distance::Cosine;
+    let full_scan_threshold = 16; // KB, 512 vectors of 8 dimensions
+    let num_groups = 20; // 250 points per group, moderate cardinality
+
+    let mut rng = StdRng::seed_from_u64(42);
+
+    let dir = Builder::new().prefix("segment_dir").tempdir().unwrap();
+    let hnsw_dir = Builder::new().prefix("hnsw_dir").tempdir().unwrap();
+
+    let group_key = "group";
+
+    let hw_counter = HardwareCounterCell::new();
+    let mut segment = build_simple_segment(dir.path(), dim, distance).unwrap();
+    for n in 0..num_vectors {
+        let idx = n.into();
+        let vector = random_vector(&mut rng, dim);
+        let payload = payload_json! {group_key: n % num_groups};
+
+        segment
+            .upsert_point(
+                n as SeqNumberType,
+                idx,
+                only_default_vector(&vector),
+                &hw_counter,
+            )
+            .unwrap();
+        segment
+            .set_full_payload(n as SeqNumberType, idx, &payload, &hw_counter)
+            .unwrap();
+    }
+
+    let payload_index_ptr = segment.payload_index.clone();
+    payload_index_ptr
+        .borrow_mut()
+        .set_indexed(
+            &JsonPath::new(group_key),
+            PayloadSchemaType::Integer,
+            &hw_counter,
+        )
+        .unwrap();
+
+    let hnsw_config = HnswConfig {
+        m,
+        ef_construct,
+        full_scan_threshold,
+        max_indexing_threads: 2,
+        on_disk: Some(false),
+        payload_m: None,
+        inline_storage: None,
+        target_recall: None,
+        m0: None,
+        lock_in_ram: None,
+    };
+
+    let vector_storage = &segment.vector_data[DEFAULT_VECTOR_NAME].vector_storage;
+    let quantized_vectors = &segment.vector_data[DEFAULT_VECTOR_NAME].quantized_vectors;
+
+    let permit_cpu_count = 1; // single-threaded for deterministic build
+    let permit = Arc::new(ResourcePermit::dummy(permit_cpu_count as u32));
+    let hnsw_index = HNSWIndex::build(
+        HnswIndexOpenArgs {
+            path: hnsw_dir.path(),
+            id_tracker: segment.id_tracker.clone(),
+            vector_storage: vector_storage.clone(),
+            quantized_vectors: quantized_vectors.clone(),
+            payload_index: payload_index_ptr.clone(),
+            hnsw_config,
+        },
+        VectorIndexBuildArgs {
+            permit,
+            old_indices: &[],
+            gpu_device: None,
+            rng: &mut rng,
+            stopped: &stopped,
+            hnsw_global_config: &HnswGlobalConfig::default(),
+            feature_flags: FeatureFlags::default(),
+            progress: ProgressTracker::new_for_test(),
+            build_checkpoint_dir: None,
+        },
+    )
+    .unwrap();
+
+    let params = SearchParams {
+        hnsw_ef: Some(128),
+        acorn: Some(AcornSearchParams {
+            enable: acorn,
+            max_selectivity: None,
+        }),
+        ..Default::default()
+    };
+
+    let top = 10;
+    let mut hits = 0;
+    let attempts = 100;
+    for _ in 0..attempts {
+        let query = random_query(&QueryVariant::Nearest, &mut rng, dim);
+        let group = rng.random_range(0..num_groups);
+        let filter = Filter::new_must(Condition::Field(FieldCondition::new_match(
+            JsonPath::new(group_key),
+            (group as i64).into(),
+        )));
+
+        let index_result = hnsw_index
+            .search(
+                &[&query],
+                Some(&filter),
+                top,
+                Some(&params),
+                &Default::default(),
+            )
+            .unwrap();
+
+        let plain_result = segment.vector_data[DEFAULT_VECTOR_NAME]
+            .vector_index
+            .borrow()
+            .search(&[&query], Some(&filter), top, None, &Default::default())
+            .unwrap();
+
+        if plain_result == index_result {
+            hits += 1;
+        }
+    }
+
+    // check that moderate cardinality is searched with ACORN only if it is enabled
+    let telemetry = hnsw_index.get_telemetry_data(TelemetryDetail::default());
+    let (acorn_count, plain_count) = if acorn { (attempts, 0) } else { (0, attempts) };
+    assert_eq!(telemetry.filtered_acorn.count, acorn_count);
+    assert_eq!(telemetry.filtered_small_cardinality.count, plain_count);
+    assert_eq!(telemetry.filtered_large_cardinality.count, 0);
+
+    let max_failures = if acorn { 20 } else { 0 };
+    assert!(
+        attempts - hits <= max_failures,
+        "hits: {hits} of {attempts}"
+    );
+}
@@ -14,11 +14,12 @@ use rand::{Rng, SeedableRng as _};
 use segment::data_types::vectors::{
     DEFAULT_VECTOR_NAME, QueryVector, VectorElementType, only_default_vector,
 };
+use segment::entry::NonAppendableSegmentEntry as _;
 use segment::entry::SegmentEntry as _;
 use segment::fixtures::index_fixtures::random_vector;
-use segment::index::VectorIndexEnum;
 use segment::index::hnsw_index::hnsw::{HNSWIndex, HnswIndexOpenArgs};
 use segment::index::hnsw_index::num_rayon_threads;
+use segment::index::{VectorIndex as _, VectorIndexEnum};
 use segment::segment::Segment;
 use segment::segment_constructor::VectorIndexBuildArgs;
 use segment::segment_constructor::simple_segment_constructor::build_simple_segment;
@@ -95,6 +96,59 @@ fn hnsw_incremental_build() {
     }
 }
 
+#[test]
+fn hnsw_heal_deleted_points() {
+    let mut rng = StdRng::seed_from_u64(42);
+
+    let dir = Builder::new()
+        .prefix("hnsw_heal_deleted_points")
+        .tempdir()
+        .unwrap();
+
+    let num_points = NUM_POINTS / 5;
+    let ids = std::iter::repeat_with(|| ExtendedPointId::NumId(rng.random()))
+        .unique()
+        .take(num_points)
+        .collect_vec();
+    let vectors = std::iter::repeat_with(|| random_vector(&mut rng, DIM))
+        .take(num_points)
+        .collect_vec();
+    let vector_refs = vectors.iter().map(|v| v.as_slice()).collect_vec();
+
+    let mut segment = make_segment(&mut rng, &dir.path().join("segment"), &ids, &vector_refs);
+    let index_path = dir.path().join("hnsw");
+    let mut index = build_hnsw_index(&mut rng, &index_path, &segment, &[]);
+
+    // Delete a third of the points
+    let hw_counter = HardwareCounterCell::new();
+    for (op_num, id) in (num_points..).zip(ids.iter().step_by(3)) {
+        segment
+            .delete_point(op_num as SeqNumberType, *id, &hw_counter)
+            .unwrap();
+    }
+
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(2)
+        .build()
+        .unwrap();
+    assert!(index.unhealed_deleted_ratio() > 0.3);
+    let patched = index.heal_deleted_points(&pool).unwrap();
+    assert!(patched > 0);
+    assert_eq!(index.unhealed_deleted_ratio(), 0.0);
+    assert!(index_path.join("links_delta.bin").exists());
+    assert!(index.files().contains(&index_path.join("links_delta.bin")));
+    assert!(
+        !index
+            .immutable_files()
+            .contains(&index_path.join("links_delta.bin"))
+    );
+
+    let query_vectors: Vec<QueryVector> = (0..10)
+        .map(|_| random_vector(&mut rng, DIM).into())
+        .collect();
+    check_matches(&query_vectors, &segment, &index, None, 64, 10);
+}
+
 fn make_segment(
     rng: &mut StdRng,
     path: &Path,
@@ -133,6 +187,9 @@ fn build_hnsw_index<R: Rng + ?Sized>(
         on_disk: Some(false),
         payload_m: None,
         inline_storage: None,
+        target_recall: None,
+        m0: None,
+        lock_in_ram: None,
     };
 
     let permit_cpu_count = num_rayon_threads(hnsw_config.max_indexing_threads);
@@ -160,6 +217,7 @@ fn build_hnsw_index<R: Rng + ?Sized>(
                 flags.incremental_hnsw_building = true;
             }),
             progress: ProgressTracker::new_for_test(),
+            build_checkpoint_dir: None,
         },
     )
     .unwrap()
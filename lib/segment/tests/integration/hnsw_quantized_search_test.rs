@@ -103,6 +103,7 @@ fn hnsw_quantized_search_test(
             QuantizedVectorsStorageType::Immutable,
             quantized_data_path,
             4,
+            None,
             &stopped,
         )
         .unwrap();
@@ -117,6 +118,9 @@ fn hnsw_quantized_search_test(
         on_disk: Some(false),
         payload_m: None,
         inline_storage: None,
+        target_recall: None,
+        m0: None,
+        lock_in_ram: None,
     };
 
     let permit_cpu_count = 1; // single-threaded for deterministic build
@@ -144,6 +148,7 @@ fn hnsw_quantized_search_test(
             hnsw_global_config: &HnswGlobalConfig::default(),
             feature_flags: FeatureFlags::default(),
             progress: ProgressTracker::new_for_test(),
+            build_checkpoint_dir: None,
         },
     )
     .unwrap();
@@ -424,6 +429,9 @@ fn test_build_hnsw_using_quantization() {
         on_disk: Some(false),
         payload_m: None,
         inline_storage: None,
+        target_recall: None,
+        m0: None,
+        lock_in_ram: None,
     });
 
     let mut builder =
@@ -127,6 +127,9 @@ fn test_multi_filterable_hnsw(
         on_disk: Some(false),
         payload_m: None,
         inline_storage: None,
+        target_recall: None,
+        m0: None,
+        lock_in_ram: None,
     };
 
     let permit_cpu_count = 1; // single-threaded for deterministic build
@@ -152,6 +155,7 @@ fn test_multi_filterable_hnsw(
             hnsw_global_config: &HnswGlobalConfig::default(),
             feature_flags: FeatureFlags::default(),
             progress: ProgressTracker::new_for_test(),
+            build_checkpoint_dir: None,
         },
     )
     .unwrap();
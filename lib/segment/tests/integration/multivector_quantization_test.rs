@@ -280,6 +280,7 @@ fn test_multivector_quantization_hnsw(
             always_ram: Some(false),
             encoding: None,
             query_encoding: None,
+            oversampling: None,
         }
         .into(),
     };
@@ -293,6 +294,7 @@ fn test_multivector_quantization_hnsw(
                 QuantizedVectorsStorageType::Immutable,
                 quantized_data_path,
                 4,
+                None,
                 &stopped,
             )
             .unwrap();
@@ -317,6 +319,9 @@ fn test_multivector_quantization_hnsw(
         on_disk: Some(false),
         payload_m: None,
         inline_storage: None,
+        target_recall: None,
+        m0: None,
+        lock_in_ram: None,
     };
 
     let permit_cpu_count = 1; // single-threaded for deterministic build
@@ -343,6 +348,7 @@ fn test_multivector_quantization_hnsw(
             hnsw_global_config: &HnswGlobalConfig::default(),
             feature_flags: FeatureFlags::default(),
             progress: ProgressTracker::new_for_test(),
+            build_checkpoint_dir: None,
         },
     )
     .unwrap();
@@ -154,6 +154,8 @@ impl TestSegments {
                         is_principal: None,
                         on_disk: None,
                         enable_hnsw: None,
+                        null_index: None,
+                        composite_with: None,
                     },
                 ))),
                 &hw_counter,
@@ -171,6 +173,8 @@ impl TestSegments {
                         is_principal: None,
                         on_disk: None,
                         enable_hnsw: None,
+                        null_index: None,
+                        composite_with: None,
                     },
                 ))),
                 &hw_counter,
@@ -305,6 +309,9 @@ impl TestSegments {
                         is_tenant: None,
                         on_disk: Some(true),
                         enable_hnsw: None,
+                        null_index: None,
+                        trigram_index: None,
+                        composite_with: None,
                     },
                 ))),
                 &hw_counter,
@@ -322,6 +329,8 @@ impl TestSegments {
                         is_principal: None,
                         on_disk: Some(true),
                         enable_hnsw: None,
+                        null_index: None,
+                        composite_with: None,
                     },
                 ))),
                 &hw_counter,
@@ -339,6 +348,8 @@ impl TestSegments {
                         is_principal: None,
                         on_disk: Some(true),
                         enable_hnsw: None,
+                        null_index: None,
+                        composite_with: None,
                     },
                 ))),
                 &hw_counter,
@@ -356,6 +367,8 @@ impl TestSegments {
                         is_principal: None,
                         on_disk: Some(true),
                         enable_hnsw: None,
+                        null_index: None,
+                        composite_with: None,
                     },
                 ))),
                 &hw_counter,
@@ -370,6 +383,7 @@ impl TestSegments {
                     is_principal: None,
                     on_disk: Some(true),
                     enable_hnsw: None,
+                    null_index: None,
                 }))),
                 &hw_counter,
             )
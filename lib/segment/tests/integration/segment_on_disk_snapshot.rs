@@ -82,6 +82,9 @@ fn test_on_disk_segment_snapshot(#[case] format: SnapshotFormat) {
                     is_tenant: None,
                     on_disk: Some(true),
                     enable_hnsw: None,
+                    null_index: None,
+                    trigram_index: None,
+                    composite_with: None,
                 }),
             )),
             &hw_counter,
@@ -99,6 +102,8 @@ fn test_on_disk_segment_snapshot(#[case] format: SnapshotFormat) {
                     is_principal: None,
                     on_disk: Some(true),
                     enable_hnsw: None,
+                    null_index: None,
+                    composite_with: None,
                 }),
             )),
             &hw_counter,
@@ -120,6 +125,9 @@ fn test_on_disk_segment_snapshot(#[case] format: SnapshotFormat) {
                     on_disk: Some(true), // mmap index
                     payload_m: None,
                     inline_storage: None,
+                    target_recall: None,
+                    m0: None,
+                    lock_in_ram: None,
                 }),
                 quantization_config: None,
                 multivector_config: None,
@@ -128,6 +128,8 @@ fn sparse_index_discover_test() {
                     full_scan_threshold: Some(DEFAULT_SPARSE_FULL_SCAN_THRESHOLD),
                     index_type: SparseIndexType::MutableRam,
                     datatype: Some(VectorStorageDatatype::Float32),
+                    max_posting_length: None,
+                    mmap_advice: None,
                 },
                 storage_type: SparseVectorStorageType::default(),
                 modifier: None,
@@ -177,7 +179,10 @@ fn sparse_index_discover_test() {
             full_scan_threshold: Some(DEFAULT_SPARSE_FULL_SCAN_THRESHOLD),
             index_type: SparseIndexType::ImmutableRam,
             datatype: Some(VectorStorageDatatype::Float32),
+            max_posting_length: None,
+            mmap_advice: None,
         },
+        modifier: None,
         id_tracker: sparse_segment.id_tracker.clone(),
         vector_storage: vector_storage.clone(),
         payload_index: payload_index_ptr,
@@ -269,6 +274,8 @@ fn sparse_index_hardware_measurement_test() {
                     full_scan_threshold: Some(DEFAULT_SPARSE_FULL_SCAN_THRESHOLD),
                     index_type: SparseIndexType::MutableRam,
                     datatype: Some(VectorStorageDatatype::Float32),
+                    max_posting_length: None,
+                    mmap_advice: None,
                 },
                 storage_type: SparseVectorStorageType::default(),
                 modifier: None,
@@ -297,7 +304,10 @@ fn sparse_index_hardware_measurement_test() {
             full_scan_threshold: Some(DEFAULT_SPARSE_FULL_SCAN_THRESHOLD),
             index_type: SparseIndexType::ImmutableRam,
             datatype: Some(VectorStorageDatatype::Float32),
+            max_posting_length: None,
+            mmap_advice: None,
         },
+        modifier: None,
         id_tracker: sparse_segment.id_tracker.clone(),
         vector_storage: vector_storage.clone(),
         payload_index: payload_index_ptr,
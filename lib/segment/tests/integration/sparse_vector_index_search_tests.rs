@@ -10,11 +10,13 @@ use itertools::Itertools;
 use rand::SeedableRng;
 use rand::rngs::StdRng;
 use segment::common::operation_error::OperationResult;
+use segment::data_types::modifier::Modifier;
 use segment::data_types::named_vectors::NamedVectors;
 use segment::data_types::vectors::{QueryVector, VectorInternal};
 use segment::entry::entry_point::{NonAppendableSegmentEntry, SegmentEntry};
 use segment::fixtures::payload_fixtures::STR_KEY;
 use segment::fixtures::sparse_fixtures::{fixture_sparse_index, fixture_sparse_index_from_iter};
+use segment::index::sparse_index::document_frequencies::DocumentFrequencies;
 use segment::index::sparse_index::sparse_index_config::{SparseIndexConfig, SparseIndexType};
 use segment::index::sparse_index::sparse_vector_index::{
     SparseVectorIndex, SparseVectorIndexOpenArgs,
@@ -219,6 +221,7 @@ fn sparse_vector_index_consistent_with_storage() {
     let sparse_vector_mmap_index: SparseVectorIndex<InvertedIndexCompressedMmap<f32>> =
         SparseVectorIndex::open(SparseVectorIndexOpenArgs {
             config: sparse_index_config,
+            modifier: None,
             id_tracker: sparse_vector_ram_index.id_tracker().clone(),
             vector_storage: sparse_vector_ram_index.vector_storage().clone(),
             payload_index: sparse_vector_ram_index.payload_index().clone(),
@@ -245,6 +248,7 @@ fn sparse_vector_index_consistent_with_storage() {
     let sparse_vector_mmap_index: SparseVectorIndex<InvertedIndexCompressedMmap<f32>> =
         SparseVectorIndex::open(SparseVectorIndexOpenArgs {
             config: sparse_index_config,
+            modifier: None,
             id_tracker: sparse_vector_ram_index.id_tracker().clone(),
             vector_storage: sparse_vector_ram_index.vector_storage().clone(),
             payload_index: sparse_vector_ram_index.payload_index().clone(),
@@ -263,6 +267,105 @@ fn sparse_vector_index_consistent_with_storage() {
     check_index_storage_consistency(&sparse_vector_mmap_index);
 }
 
+#[test]
+fn sparse_vector_index_materialized_document_frequencies() {
+    let stopped = AtomicBool::new(false);
+    let mut rnd = StdRng::seed_from_u64(42);
+
+    let data_dir = Builder::new().prefix("data_dir").tempdir().unwrap();
+    let sparse_vector_ram_index = fixture_sparse_index::<InvertedIndexCompressedImmutableRam<f32>, _>(
+        &mut rnd,
+        NUM_VECTORS,
+        MAX_SPARSE_DIM,
+        LOW_FULL_SCAN_THRESHOLD,
+        data_dir.path(),
+    );
+
+    let mmap_index_dir = Builder::new().prefix("mmap_index_dir").tempdir().unwrap();
+    let open_index = || -> SparseVectorIndex<InvertedIndexCompressedMmap<f32>> {
+        let mut sparse_index_config = sparse_vector_ram_index.config();
+        sparse_index_config.index_type = SparseIndexType::Mmap;
+        SparseVectorIndex::open(SparseVectorIndexOpenArgs {
+            config: sparse_index_config,
+            modifier: Some(Modifier::Idf),
+            id_tracker: sparse_vector_ram_index.id_tracker().clone(),
+            vector_storage: sparse_vector_ram_index.vector_storage().clone(),
+            payload_index: sparse_vector_ram_index.payload_index().clone(),
+            path: mmap_index_dir.path(),
+            stopped: &stopped,
+            tick_progress: || (),
+        })
+        .unwrap()
+    };
+
+    let sparse_vector_mmap_index = open_index();
+    let document_frequencies_file = DocumentFrequencies::file_path(mmap_index_dir.path());
+    assert!(document_frequencies_file.exists());
+    assert!(
+        sparse_vector_mmap_index
+            .files()
+            .contains(&document_frequencies_file)
+    );
+
+    // statistics must match the ones read from posting lists of the index without modifier
+    let hw_counter = HardwareCounterCell::new();
+    let mut expected: HashMap<DimId, usize> = (0..MAX_SPARSE_DIM as DimId + 10)
+        .map(|dim_id| (dim_id, 0))
+        .collect();
+    let mut materialized = expected.clone();
+    sparse_vector_ram_index.fill_idf_statistics(&mut expected, &hw_counter);
+    sparse_vector_mmap_index.fill_idf_statistics(&mut materialized, &hw_counter);
+    assert!(expected.values().any(|&count| count > 0));
+    assert_eq!(expected, materialized);
+
+    // reload with persisted statistics
+    drop(sparse_vector_mmap_index);
+    let sparse_vector_mmap_index = open_index();
+    let mut reloaded: HashMap<DimId, usize> = expected.keys().map(|&dim_id| (dim_id, 0)).collect();
+    sparse_vector_mmap_index.fill_idf_statistics(&mut reloaded, &hw_counter);
+    assert_eq!(expected, reloaded);
+}
+
+#[test]
+fn sparse_vector_index_postings_telemetry() {
+    let mut rnd = StdRng::seed_from_u64(42);
+
+    let data_dir = Builder::new().prefix("data_dir").tempdir().unwrap();
+    let sparse_vector_index = fixture_sparse_index::<InvertedIndexCompressedImmutableRam<f32>, _>(
+        &mut rnd,
+        NUM_VECTORS,
+        MAX_SPARSE_DIM,
+        LOW_FULL_SCAN_THRESHOLD,
+        data_dir.path(),
+    );
+
+    let hw_counter = HardwareCounterCell::new();
+    let mut posting_lengths: HashMap<DimId, usize> = (0..MAX_SPARSE_DIM as DimId + 10)
+        .map(|dim_id| (dim_id, 0))
+        .collect();
+    sparse_vector_index.fill_idf_statistics(&mut posting_lengths, &hw_counter);
+    posting_lengths.retain(|_, length| *length > 0);
+
+    let telemetry = sparse_vector_index
+        .get_telemetry_data(TelemetryDetail::default())
+        .sparse_postings
+        .unwrap();
+
+    assert_eq!(telemetry.postings_count, posting_lengths.len());
+    assert_eq!(
+        telemetry.max_posting_length,
+        posting_lengths.values().copied().max().unwrap(),
+    );
+    assert_eq!(telemetry.max_dim_id, posting_lengths.keys().copied().max());
+    for (bucket, &count) in telemetry.length_histogram.iter().enumerate() {
+        let expected = posting_lengths
+            .values()
+            .filter(|&&length| length.ilog2() as usize == bucket)
+            .count();
+        assert_eq!(count, expected, "bucket {bucket}");
+    }
+}
+
 #[test]
 fn sparse_vector_index_load_missing_mmap() {
     let data_dir = Builder::new().prefix("data_dir").tempdir().unwrap();
@@ -356,6 +459,181 @@ fn sparse_vector_index_ram_deleted_points_search() {
     );
 }
 
+#[test]
+fn sparse_vector_index_deleted_ratio() {
+    let mut rnd = StdRng::seed_from_u64(42);
+
+    let data_dir = Builder::new().prefix("data_dir").tempdir().unwrap();
+    let sparse_vector_index = fixture_sparse_index_from_iter::<InvertedIndexCompressedMmap<f32>>(
+        data_dir.path(),
+        (0..NUM_VECTORS).map(|_| random_sparse_vector(&mut rnd, MAX_SPARSE_DIM)),
+        LOW_FULL_SCAN_THRESHOLD,
+        SparseIndexType::Mmap,
+    )
+    .unwrap();
+    assert_eq!(sparse_vector_index.deleted_ratio(), 0.0);
+
+    // delete every 4th point
+    for idx in (0..NUM_VECTORS as PointOffsetType).step_by(4) {
+        let mut id_tracker = sparse_vector_index.id_tracker().borrow_mut();
+        let external_id = id_tracker.external_id(idx).unwrap();
+        id_tracker.drop(external_id).unwrap();
+    }
+    let deleted_count = NUM_VECTORS.div_ceil(4);
+    assert_eq!(
+        sparse_vector_index.deleted_ratio(),
+        deleted_count as f64 / NUM_VECTORS as f64,
+    );
+}
+
+#[test]
+fn sparse_vector_index_compaction() {
+    let mut rnd = StdRng::seed_from_u64(42);
+    let top = 10;
+
+    let data_dir = Builder::new().prefix("data_dir").tempdir().unwrap();
+    let mut sparse_vector_index =
+        fixture_sparse_index_from_iter::<InvertedIndexCompressedMmap<f32>>(
+            data_dir.path(),
+            (0..NUM_VECTORS).map(|_| random_sparse_vector(&mut rnd, MAX_SPARSE_DIM)),
+            LOW_FULL_SCAN_THRESHOLD,
+            SparseIndexType::Mmap,
+        )
+        .unwrap();
+
+    // delete every 4th point
+    for idx in (0..NUM_VECTORS as PointOffsetType).step_by(4) {
+        let mut id_tracker = sparse_vector_index.id_tracker().borrow_mut();
+        let external_id = id_tracker.external_id(idx).unwrap();
+        id_tracker.drop(external_id).unwrap();
+    }
+    let deleted_count = NUM_VECTORS.div_ceil(4);
+
+    let query_vector: QueryVector = random_sparse_vector(&mut rnd, MAX_SPARSE_DIM).into();
+    let before_compaction_results = sparse_vector_index
+        .search(&[&query_vector], None, top, None, &Default::default())
+        .unwrap();
+    let total_sparse_size = sparse_vector_index.size_of_searchable_vectors_in_bytes();
+
+    // not littered enough
+    assert!(!sparse_vector_index.compact_if_needed(0.5).unwrap());
+    assert_eq!(sparse_vector_index.indexed_vector_count(), NUM_VECTORS);
+
+    assert!(sparse_vector_index.compact_if_needed(0.2).unwrap());
+    assert_eq!(
+        sparse_vector_index.indexed_vector_count(),
+        NUM_VECTORS - deleted_count,
+    );
+    assert_eq!(sparse_vector_index.deleted_ratio(), 0.0);
+    assert!(sparse_vector_index.size_of_searchable_vectors_in_bytes() < total_sparse_size);
+
+    let after_compaction_results = sparse_vector_index
+        .search(&[&query_vector], None, top, None, &Default::default())
+        .unwrap();
+    assert_eq!(before_compaction_results, after_compaction_results);
+
+    // nothing left to compact
+    assert!(!sparse_vector_index.compact_if_needed(0.0).unwrap());
+
+    // no staging leftovers next to the index directory
+    let mut data_dir_entries = fs::read_dir(data_dir.path())
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+        .collect_vec();
+    data_dir_entries.sort();
+    assert_eq!(data_dir_entries, ["index", "payload", "storage"]);
+}
+
+#[test]
+fn sparse_vector_index_mmap_updates() {
+    let mut rnd = StdRng::seed_from_u64(42);
+    let top = 10;
+
+    let mut vectors = (0..NUM_VECTORS)
+        .map(|_| random_sparse_vector(&mut rnd, MAX_SPARSE_DIM))
+        .collect_vec();
+
+    let data_dir = Builder::new().prefix("data_dir").tempdir().unwrap();
+    let mut sparse_vector_index =
+        fixture_sparse_index_from_iter::<InvertedIndexCompressedMmap<f32>>(
+            data_dir.path(),
+            vectors.clone().into_iter(),
+            LOW_FULL_SCAN_THRESHOLD,
+            SparseIndexType::Mmap,
+        )
+        .unwrap();
+    assert!(sparse_vector_index.flusher().is_none());
+
+    // update every 8th vector, with dimensions missing in the index, and remove every 16th one
+    let hw_counter = HardwareCounterCell::new();
+    for idx in (0..NUM_VECTORS).step_by(8) {
+        let vector = if idx % 16 == 0 {
+            SparseVector::default()
+        } else {
+            random_sparse_vector(&mut rnd, MAX_SPARSE_DIM * 2)
+        };
+        let vector_ref = (!vector.is_empty()).then(|| (&vector).into());
+        sparse_vector_index
+            .update_vector(idx as PointOffsetType, vector_ref, &hw_counter)
+            .unwrap();
+        vectors[idx] = vector;
+    }
+    let updated_count = NUM_VECTORS.div_ceil(8);
+    assert!(sparse_vector_index.deleted_ratio() > 0.0);
+
+    let expected_dir = Builder::new().prefix("expected_dir").tempdir().unwrap();
+    let expected_index = fixture_sparse_index_from_iter::<InvertedIndexCompressedMmap<f32>>(
+        expected_dir.path(),
+        vectors.into_iter(),
+        LOW_FULL_SCAN_THRESHOLD,
+        SparseIndexType::Mmap,
+    )
+    .unwrap();
+    assert_eq!(
+        sparse_vector_index.indexed_vector_count(),
+        expected_index.indexed_vector_count(),
+    );
+
+    let query_vectors: Vec<QueryVector> = (0..10)
+        .map(|_| random_sparse_vector(&mut rnd, MAX_SPARSE_DIM * 2).into())
+        .collect();
+    let query_vectors = query_vectors.iter().collect_vec();
+    // scores may slightly differ, as elements are summed up in different order
+    let search_ids = |sparse_vector_index: &SparseVectorIndex<_>| {
+        sparse_vector_index
+            .search(&query_vectors, None, top, None, &Default::default())
+            .unwrap()
+            .into_iter()
+            .map(|results| results.into_iter().map(|point| point.idx).collect_vec())
+            .collect_vec()
+    };
+    let expected_results = search_ids(&expected_index);
+    assert_eq!(search_ids(&sparse_vector_index), expected_results);
+
+    // updates are persisted next to the index
+    let flusher = sparse_vector_index.flusher().unwrap();
+    flusher().unwrap();
+    assert!(sparse_vector_index.flusher().is_none());
+    let reloaded =
+        InvertedIndexCompressedMmap::<f32>::open(&data_dir.path().join("index")).unwrap();
+    assert!(reloaded.has_updates());
+    assert_eq!(reloaded.outdated_vector_count(), updated_count);
+    assert_eq!(
+        reloaded.vector_count(),
+        expected_index.indexed_vector_count(),
+    );
+
+    // and merged into the index files by compaction
+    assert!(sparse_vector_index.compact_if_needed(0.0).unwrap());
+    assert!(!sparse_vector_index.inverted_index().has_updates());
+    assert_eq!(sparse_vector_index.deleted_ratio(), 0.0);
+    assert_eq!(
+        sparse_vector_index.indexed_vector_count(),
+        expected_index.indexed_vector_count(),
+    );
+    assert_eq!(search_ids(&sparse_vector_index), expected_results);
+}
+
 #[test]
 fn sparse_vector_index_ram_filtered_search() {
     let mut rnd = StdRng::seed_from_u64(42);
@@ -591,6 +869,8 @@ fn sparse_vector_index_persistence_test() {
                     full_scan_threshold: Some(DEFAULT_SPARSE_FULL_SCAN_THRESHOLD),
                     index_type: SparseIndexType::MutableRam,
                     datatype: Some(VectorStorageDatatype::Float32),
+                    max_posting_length: None,
+                    mmap_advice: None,
                 },
                 storage_type: SparseVectorStorageType::default(),
                 modifier: None,
@@ -678,7 +958,10 @@ fn check_persistence<TInvertedIndex: InvertedIndex>(
                 full_scan_threshold: Some(DEFAULT_SPARSE_FULL_SCAN_THRESHOLD),
                 index_type: SparseIndexType::Mmap,
                 datatype: Some(VectorStorageDatatype::Float32),
+                max_posting_length: None,
+                mmap_advice: None,
             },
+            modifier: None,
             id_tracker: segment.id_tracker.clone(),
             vector_storage: segment.vector_data[SPARSE_VECTOR_NAME]
                 .vector_storage
@@ -763,6 +1046,8 @@ fn sparse_vector_test_large_index() {
                     full_scan_threshold: Some(DEFAULT_SPARSE_FULL_SCAN_THRESHOLD),
                     index_type: SparseIndexType::MutableRam,
                     datatype: Some(VectorStorageDatatype::Float32),
+                    max_posting_length: None,
+                    mmap_advice: None,
                 },
                 storage_type: SparseVectorStorageType::Mmap,
                 modifier: None,
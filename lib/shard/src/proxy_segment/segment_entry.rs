@@ -209,6 +209,22 @@ impl NonAppendableSegmentEntry for ProxySegment {
         }
     }
 
+    fn text_match_positions(
+        &self,
+        point_id: PointIdType,
+        condition: &FieldCondition,
+        hw_counter: &HardwareCounterCell,
+    ) -> OperationResult<Option<Vec<u32>>> {
+        if self.deleted_points.contains_key(&point_id) {
+            Ok(None)
+        } else {
+            self.wrapped_segment
+                .get()
+                .read()
+                .text_match_positions(point_id, condition, hw_counter)
+        }
+    }
+
     fn retrieve(
         &self,
         point_ids: &[PointIdType],
@@ -349,6 +365,29 @@ impl NonAppendableSegmentEntry for ProxySegment {
         Ok(values)
     }
 
+    fn facet_point_ids(
+        &self,
+        key: &JsonPath,
+        filter: Option<&Filter>,
+        is_stopped: &AtomicBool,
+        hw_counter: &HardwareCounterCell,
+    ) -> OperationResult<HashMap<FacetValue, Vec<PointIdType>>> {
+        let mut point_ids = self
+            .wrapped_segment
+            .get()
+            .read()
+            .facet_point_ids(key, filter, is_stopped, hw_counter)?;
+
+        if !self.deleted_points.is_empty() {
+            point_ids.retain(|_, ids| {
+                ids.retain(|point_id| !self.deleted_points.contains_key(point_id));
+                !ids.is_empty()
+            });
+        }
+
+        Ok(point_ids)
+    }
+
     fn facet(
         &self,
         request: &FacetParams,
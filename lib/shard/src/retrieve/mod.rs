@@ -1,2 +1,3 @@
 pub mod record_internal;
 pub mod retrieve_blocking;
+pub mod text_match_positions_blocking;
@@ -0,0 +1,58 @@
+use std::collections::hash_map::Entry;
+use std::sync::atomic::AtomicBool;
+use std::time::Duration;
+
+use ahash::AHashMap;
+use common::counter::hardware_accumulator::HwMeasurementAcc;
+use segment::common::operation_error::{OperationError, OperationResult};
+use segment::types::{FieldCondition, PointIdType, SeqNumberType};
+
+use crate::segment_holder::SegmentHolder;
+use crate::segment_holder::locked::LockedSegmentHolder;
+
+/// Positions of tokens matched by a full-text `condition` in the documents of the given points.
+///
+/// Only the latest version of each point is considered. Points which don't match the condition,
+/// or which have no full text index storing positions, are not included.
+pub fn text_match_positions_blocking(
+    segments: LockedSegmentHolder,
+    points: &[PointIdType],
+    condition: &FieldCondition,
+    timeout: Duration,
+    is_stopped: &AtomicBool,
+    hw_measurement_acc: HwMeasurementAcc,
+) -> OperationResult<AHashMap<PointIdType, Vec<u32>>> {
+    let mut point_version: AHashMap<PointIdType, SeqNumberType> = Default::default();
+    let mut point_positions: AHashMap<PointIdType, Vec<u32>> = Default::default();
+
+    let hw_counter = hw_measurement_acc.get_counter_cell();
+
+    SegmentHolder::read_points_locked(&segments, points, is_stopped, timeout, |ids, segment| {
+        let mut applied = 0;
+
+        for &id in ids {
+            let version = segment.point_version(id).ok_or_else(|| {
+                OperationError::service_error(format!("No version for point {id}"))
+            })?;
+
+            // If we already have the latest point version, keep that and continue
+            let version_entry = point_version.entry(id);
+            if matches!(&version_entry, Entry::Occupied(entry) if *entry.get() >= version) {
+                applied += 1;
+                continue;
+            }
+            *version_entry.or_default() = version;
+
+            // Newer version may not match anymore
+            match segment.text_match_positions(id, condition, &hw_counter)? {
+                Some(positions) => point_positions.insert(id, positions),
+                None => point_positions.remove(&id),
+            };
+            applied += 1;
+        }
+
+        Ok(applied)
+    })?;
+
+    Ok(point_positions)
+}
@@ -1,4 +1,5 @@
 use std::cmp::{max, min};
+use std::sync::Arc;
 use std::sync::atomic::Ordering;
 use std::thread::JoinHandle;
 
@@ -92,6 +93,15 @@ impl SegmentHolder {
                 .retain(|_, _, version| *version > max_applied_version);
         } else {
             let flush_dependency = self.flush_dependency.clone();
+            // Tuning indexes may run exact searches, so it is done after flushing, locking one
+            // segment at a time. Segments are referenced weakly, not to delay dropping them.
+            let tuned_segments: Vec<_> = lock_order
+                .iter()
+                .filter_map(|&segment_id| match self.get(segment_id)? {
+                    LockedSegment::Original(segment) => Some(Arc::downgrade(segment)),
+                    LockedSegment::Proxy(_) => None,
+                })
+                .collect();
             *background_flush_lock = Some(
                 std::thread::Builder::new()
                     .name("background_flush".to_string())
@@ -102,6 +112,11 @@ impl SegmentHolder {
                         flush_dependency
                             .lock()
                             .retain(|_, _, version| *version > max_applied_version);
+                        for segment in tuned_segments {
+                            if let Some(segment) = segment.upgrade() {
+                                segment.read().tune_indexes();
+                            }
+                        }
                         Ok(())
                     })
                     .unwrap(),
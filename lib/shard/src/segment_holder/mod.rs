@@ -648,6 +648,59 @@ impl SegmentHolder {
         &self,
         op_num: SeqNumberType,
         ids: &[PointIdType],
+        point_operation: F,
+        point_cow_operation: G,
+        hw_counter: &HardwareCounterCell,
+    ) -> OperationResult<AHashSet<PointIdType>>
+    where
+        F: FnMut(PointIdType, &mut RwLockWriteGuard<dyn SegmentEntry>) -> OperationResult<bool>,
+        for<'n, 'o, 'p> G: FnMut(PointIdType, &'n mut NamedVectors<'o>, &'p mut Payload),
+    {
+        self.apply_points_with_conditional_move_impl(
+            op_num,
+            ids,
+            |segment| segment.is_appendable(),
+            point_operation,
+            point_cow_operation,
+            hw_counter,
+        )
+    }
+
+    /// Apply a payload operation `point_operation` to a set of points `ids`, and, if necessary,
+    /// move the points into appendable segments.
+    ///
+    /// Same as [`Self::apply_points_with_conditional_move`], but points are also updated in-place
+    /// in non-appendable segments, which accept payload updates. See
+    /// [`SegmentEntry::accepts_payload_updates`].
+    ///
+    /// Must only be used for operations, which don't change vectors.
+    pub fn apply_payload_with_conditional_move<F, G>(
+        &self,
+        op_num: SeqNumberType,
+        ids: &[PointIdType],
+        point_operation: F,
+        point_cow_operation: G,
+        hw_counter: &HardwareCounterCell,
+    ) -> OperationResult<AHashSet<PointIdType>>
+    where
+        F: FnMut(PointIdType, &mut RwLockWriteGuard<dyn SegmentEntry>) -> OperationResult<bool>,
+        for<'n, 'o, 'p> G: FnMut(PointIdType, &'n mut NamedVectors<'o>, &'p mut Payload),
+    {
+        self.apply_points_with_conditional_move_impl(
+            op_num,
+            ids,
+            |segment| segment.accepts_payload_updates(),
+            point_operation,
+            point_cow_operation,
+            hw_counter,
+        )
+    }
+
+    fn apply_points_with_conditional_move_impl<F, G>(
+        &self,
+        op_num: SeqNumberType,
+        ids: &[PointIdType],
+        accepts_in_place: impl Fn(&dyn SegmentEntry) -> bool,
         mut point_operation: F,
         mut point_cow_operation: G,
         hw_counter: &HardwareCounterCell,
@@ -669,7 +722,8 @@ impl SegmentHolder {
                 return Ok(false);
             }
 
-            let can_apply_operation = !write_segment.is_proxy() && write_segment.is_appendable();
+            let can_apply_operation =
+                !write_segment.is_proxy() && accepts_in_place(&**write_segment);
 
             let is_applied = if can_apply_operation {
                 point_operation(point_id, write_segment)?
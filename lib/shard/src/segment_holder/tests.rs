@@ -89,6 +89,57 @@ fn test_apply_to_appendable() {
     assert!(!immutable_segment.has_point(12.into()));
 }
 
+#[test]
+fn test_apply_payload_in_place_to_non_appendable() {
+    let dir = Builder::new().prefix("segment_dir").tempdir().unwrap();
+
+    let appendable_segment = build_segment_1(dir.path());
+
+    // Payload indexes of the segment accept updates
+    let mut immutable_segment = build_segment_2(dir.path());
+    immutable_segment.appendable_flag = false;
+    assert!(immutable_segment.accepts_payload_updates());
+
+    let mut holder = SegmentHolder::default();
+    let appendable_id = holder.add_new(appendable_segment);
+    let immutable_id = holder.add_new(immutable_segment);
+
+    let point_ids = [1.into(), 2.into(), 11.into(), 12.into()];
+    let mut updated_in_place = Vec::new();
+    let mut moved_to_appendable = Vec::new();
+
+    holder
+        .apply_payload_with_conditional_move(
+            100,
+            &point_ids,
+            |point_id, segment| {
+                updated_in_place.push(point_id);
+                assert!(segment.has_point(point_id));
+                Ok(true)
+            },
+            |point_id, _, _| {
+                moved_to_appendable.push(point_id);
+            },
+            &HardwareCounterCell::new(),
+        )
+        .unwrap();
+
+    // All points were updated in place, none were moved
+    assert_eq!(updated_in_place.len(), point_ids.len());
+    assert!(moved_to_appendable.is_empty());
+
+    let appendable_segment = holder.get(appendable_id).unwrap().get();
+    let appendable_segment = appendable_segment.read();
+
+    let immutable_segment = holder.get(immutable_id).unwrap().get();
+    let immutable_segment = immutable_segment.read();
+
+    assert!(!appendable_segment.has_point(11.into()));
+    assert!(!appendable_segment.has_point(12.into()));
+    assert!(immutable_segment.has_point(11.into()));
+    assert!(immutable_segment.has_point(12.into()));
+}
+
 /// Test applying points and conditionally moving them if operation versions are off
 ///
 /// More specifically, this tests the move is still applied correctly even if segments already
@@ -54,6 +54,29 @@ impl SnapshotUtils {
         Ok(())
     }
 
+    /// Materialize a partial snapshot on top of a base shard, e.g. a restored full snapshot.
+    ///
+    /// `shard_path` - path to the base shard, which is updated in place.
+    /// `shard_manifest` - manifest of the base shard, which the partial snapshot was created against.
+    /// `snapshot_path` - path to the directory, where the partial snapshot was unpacked to.
+    /// `snapshot_manifest` - manifest of the partial snapshot.
+    ///
+    /// This function performs blocking IO.
+    pub fn merge_partial_snapshot(
+        shard_path: &Path,
+        shard_manifest: &SnapshotManifest,
+        snapshot_path: &Path,
+        snapshot_manifest: &SnapshotManifest,
+    ) -> OperationResult<()> {
+        Self::partial_snapshot_merge_plan(
+            shard_path,
+            shard_manifest,
+            snapshot_path,
+            snapshot_manifest,
+        )
+        .execute()
+    }
+
     /// Create a plan to merge an existing shard with a partial snapshot.
     /// This function doesn't actually perform any file operations; it just prepares the plan.
     ///
@@ -143,3 +166,45 @@ pub struct SnapshotMergePlan {
     pub delete_files: Vec<PathBuf>,
     pub delete_directories: Vec<PathBuf>,
 }
+
+impl SnapshotMergePlan {
+    /// Execute the plan with blocking file operations.
+    pub fn execute(self) -> OperationResult<()> {
+        let Self {
+            move_files,
+            replace_directories,
+            merge_directories,
+            delete_files,
+            delete_directories,
+        } = self;
+
+        for path in delete_files {
+            if path.exists() {
+                fs::remove_file(&path)?;
+            }
+        }
+
+        for path in delete_directories {
+            if path.exists() {
+                fs::remove_dir_all(&path)?;
+            }
+        }
+
+        for (from, to) in move_files {
+            common::fs::move_file(&from, &to)?;
+        }
+
+        for (from, to) in replace_directories {
+            if to.exists() {
+                fs::remove_dir_all(&to)?;
+            }
+            common::fs::move_dir(&from, &to)?;
+        }
+
+        for (from, to) in merge_directories {
+            common::fs::move_dir(&from, &to)?;
+        }
+
+        Ok(())
+    }
+}
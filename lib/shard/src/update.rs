@@ -633,7 +633,7 @@ pub fn set_payload(
     let mut total_updated_points = 0;
 
     for chunk in points.chunks(PAYLOAD_OP_BATCH_SIZE) {
-        let updated_points = segments.apply_points_with_conditional_move(
+        let updated_points = segments.apply_payload_with_conditional_move(
             op_num,
             chunk,
             |id, write_segment| write_segment.set_payload(op_num, id, payload, key, hw_counter),
@@ -681,7 +681,7 @@ pub fn delete_payload(
     let mut total_deleted_points = 0;
 
     for batch in points.chunks(PAYLOAD_OP_BATCH_SIZE) {
-        let updated_points = segments.apply_points_with_conditional_move(
+        let updated_points = segments.apply_payload_with_conditional_move(
             op_num,
             batch,
             |id, write_segment| {
@@ -734,7 +734,7 @@ pub fn clear_payload(
     let mut total_updated_points = 0;
 
     for batch in points.chunks(PAYLOAD_OP_BATCH_SIZE) {
-        let updated_points = segments.apply_points_with_conditional_move(
+        let updated_points = segments.apply_payload_with_conditional_move(
             op_num,
             batch,
             |id, write_segment| write_segment.clear_payload(op_num, id, hw_counter),
@@ -777,7 +777,7 @@ pub fn overwrite_payload(
     let mut total_updated_points = 0;
 
     for batch in points.chunks(PAYLOAD_OP_BATCH_SIZE) {
-        let updated_points = segments.apply_points_with_conditional_move(
+        let updated_points = segments.apply_payload_with_conditional_move(
             op_num,
             batch,
             |id, write_segment| write_segment.set_full_payload(op_num, id, payload, hw_counter),
@@ -828,6 +828,13 @@ pub fn create_field_index(
     };
 
     segments.apply_segments(|write_segment| {
+        // Index with a different schema is rebuilt next to the current one, which keeps serving
+        // reads until the new one is swapped in
+        if write_segment.stage_field_index_rebuild(op_num, field_name, field_schema, hw_counter)? {
+            return write_segment
+                .with_upgraded(|segment| segment.apply_field_index_rebuild(op_num, field_name));
+        }
+
         write_segment.with_upgraded(|segment| {
             segment.delete_field_index_if_incompatible(op_num, field_name, field_schema)
         })?;
@@ -15,7 +15,7 @@ use rand::rngs::StdRng;
 use sparse::common::scores_memory_pool::ScoresMemoryPool;
 use sparse::common::sparse_vector::{RemappedSparseVector, SparseVector};
 use sparse::common::sparse_vector_fixture::{random_positive_sparse_vector, random_sparse_vector};
-use sparse::common::types::QuantizedU8;
+use sparse::common::types::{QuantizedU4, QuantizedU8};
 use sparse::index::inverted_index::InvertedIndex;
 use sparse::index::inverted_index::inverted_index_compressed_immutable_ram::InvertedIndexCompressedImmutableRam;
 use sparse::index::inverted_index::inverted_index_compressed_mmap::InvertedIndexCompressedMmap;
@@ -186,6 +186,7 @@ pub fn run_bench(
     run_bench2!("c16", half::f16);
     // run_bench2!("c8", u8);
     run_bench2!("q8", QuantizedU8);
+    run_bench2!("q4", QuantizedU4);
 }
 
 fn run_bench2(
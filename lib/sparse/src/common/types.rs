@@ -2,14 +2,34 @@ use std::fmt::Debug;
 
 use half::slice::HalfFloatSliceExt;
 use itertools::{Itertools, MinMaxResult};
+use serde::{Deserialize, Serialize};
+
+use crate::index::compressed_posting_list::CHUNK_SIZE;
 
 pub type DimOffset = u32;
 pub type DimId = u32;
 pub type DimId64 = u64;
 pub type DimWeight = f32;
 
-pub trait Weight: PartialEq + Copy + Debug + 'static {
-    type QuantizationParams: Copy + PartialEq + Debug;
+/// Type of weights stored in a sparse index, see [`Weight::TYPE`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WeightType {
+    Float32,
+    Float16,
+    Uint8,
+    QuantizedUint8,
+    QuantizedUint4,
+}
+
+pub trait Weight: PartialEq + Copy + Debug + Send + Sync + 'static {
+    type QuantizationParams: Copy + PartialEq + Debug + Send + Sync;
+
+    /// Storage of weights of a full chunk of a compressed posting list
+    type ChunkWeights: ChunkWeightsStorage<Self>;
+
+    /// Persisted in index files to detect indexes built with a different weight type
+    const TYPE: WeightType;
 
     fn quantization_params_for(
         values: impl ExactSizeIterator<Item = DimWeight> + Clone,
@@ -26,9 +46,51 @@ pub trait Weight: PartialEq + Copy + Debug + 'static {
     ) -> &'a [f32];
 }
 
+/// Weights of a full chunk of a compressed posting list, [`CHUNK_SIZE`] values
+pub trait ChunkWeightsStorage<W: Weight>: Clone + PartialEq + Debug + Send + Sync {
+    /// Panics if the number of weights is not [`CHUNK_SIZE`]
+    fn from_weights(weights: &[W]) -> Self;
+
+    fn get(&self, index: usize) -> W;
+
+    /// Convert weights starting from `start`, as many as fit into `buffer`
+    fn range_into_f32<'a>(
+        &'a self,
+        params: W::QuantizationParams,
+        start: usize,
+        buffer: &'a mut [f32],
+    ) -> &'a [f32];
+}
+
+impl<W: Weight> ChunkWeightsStorage<W> for [W; CHUNK_SIZE] {
+    #[inline]
+    fn from_weights(weights: &[W]) -> Self {
+        weights.try_into().expect("Invalid chunk size")
+    }
+
+    #[inline]
+    fn get(&self, index: usize) -> W {
+        self[index]
+    }
+
+    #[inline]
+    fn range_into_f32<'a>(
+        &'a self,
+        params: W::QuantizationParams,
+        start: usize,
+        buffer: &'a mut [f32],
+    ) -> &'a [f32] {
+        W::into_f32_slice(params, &self[start..start + buffer.len()], buffer)
+    }
+}
+
 impl Weight for f32 {
     type QuantizationParams = ();
 
+    type ChunkWeights = [Self; CHUNK_SIZE];
+
+    const TYPE: WeightType = WeightType::Float32;
+
     #[inline]
     fn quantization_params_for(_values: impl ExactSizeIterator<Item = DimWeight> + Clone) {}
 
@@ -52,6 +114,10 @@ impl Weight for f32 {
 impl Weight for half::f16 {
     type QuantizationParams = ();
 
+    type ChunkWeights = [Self; CHUNK_SIZE];
+
+    const TYPE: WeightType = WeightType::Float16;
+
     #[inline]
     fn quantization_params_for(_values: impl ExactSizeIterator<Item = DimWeight> + Clone) {}
 
@@ -75,6 +141,10 @@ impl Weight for half::f16 {
 impl Weight for u8 {
     type QuantizationParams = ();
 
+    type ChunkWeights = [Self; CHUNK_SIZE];
+
+    const TYPE: WeightType = WeightType::Uint8;
+
     #[inline]
     fn quantization_params_for(_values: impl ExactSizeIterator<Item = DimWeight> + Clone) {}
 
@@ -141,6 +211,10 @@ impl QuantizedU8Params {
 impl Weight for QuantizedU8 {
     type QuantizationParams = QuantizedU8Params;
 
+    type ChunkWeights = [Self; CHUNK_SIZE];
+
+    const TYPE: WeightType = WeightType::QuantizedUint8;
+
     #[inline]
     fn quantization_params_for(
         values: impl Iterator<Item = DimWeight>,
@@ -183,3 +257,131 @@ impl Weight for QuantizedU8 {
         buffer
     }
 }
+
+/// Weight quantized into 4 bits, `0..=15`.
+///
+/// Weights of full chunks are packed two per byte, see [`PackedU4Weights`].
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub struct QuantizedU4(u8);
+
+impl QuantizedU4 {
+    const MAX: u8 = 0x0F;
+
+    #[inline]
+    pub(crate) fn from_raw(raw: u8) -> Self {
+        debug_assert!(raw <= Self::MAX);
+        Self(raw & Self::MAX)
+    }
+
+    #[inline]
+    pub(crate) fn raw(self) -> u8 {
+        self.0
+    }
+}
+
+#[derive(PartialEq, Default, Copy, Clone, Debug)]
+pub struct QuantizedU4Params {
+    /// Minimum value in the range
+    min: f32,
+    /// Difference divided by 16, aka `(max - min) / 15`
+    diff16: f32,
+}
+
+impl QuantizedU4Params {
+    #[inline]
+    pub(crate) fn from_parts(min: f32, diff16: f32) -> Self {
+        Self { min, diff16 }
+    }
+
+    #[inline]
+    pub(crate) fn parts(self) -> (f32, f32) {
+        (self.min, self.diff16)
+    }
+}
+
+impl Weight for QuantizedU4 {
+    type QuantizationParams = QuantizedU4Params;
+
+    type ChunkWeights = PackedU4Weights;
+
+    const TYPE: WeightType = WeightType::QuantizedUint4;
+
+    #[inline]
+    fn quantization_params_for(
+        values: impl Iterator<Item = DimWeight>,
+    ) -> Self::QuantizationParams {
+        let (min, max) = match values.minmax() {
+            MinMaxResult::NoElements => return QuantizedU4Params::default(),
+            MinMaxResult::OneElement(e) => (e, e),
+            MinMaxResult::MinMax(min, max) => (min, max),
+        };
+        QuantizedU4Params {
+            min,
+            diff16: (max - min) / f32::from(Self::MAX),
+        }
+    }
+
+    #[inline]
+    fn from_f32(params: QuantizedU4Params, value: f32) -> Self {
+        QuantizedU4(
+            ((value - params.min) / params.diff16)
+                .round()
+                .clamp(0.0, f32::from(Self::MAX)) as u8,
+        )
+    }
+
+    #[inline]
+    fn to_f32(self, params: QuantizedU4Params) -> f32 {
+        params.min + f32::from(self.0) * params.diff16
+    }
+
+    #[inline]
+    fn into_f32_slice<'a>(
+        params: QuantizedU4Params,
+        weights: &'a [Self],
+        buffer: &'a mut [f32],
+    ) -> &'a [f32] {
+        assert_eq!(weights.len(), buffer.len());
+        for (i, &weight) in weights.iter().enumerate() {
+            buffer[i] = weight.to_f32(params);
+        }
+        buffer
+    }
+}
+
+/// Weights of a full chunk quantized into 4 bits, two per byte.
+///
+/// Even weights are stored in the lower bits of a byte, odd ones in the higher bits.
+#[derive(PartialEq, Clone, Debug)]
+#[repr(transparent)]
+pub struct PackedU4Weights([u8; CHUNK_SIZE / 2]);
+
+impl ChunkWeightsStorage<QuantizedU4> for PackedU4Weights {
+    #[inline]
+    fn from_weights(weights: &[QuantizedU4]) -> Self {
+        assert_eq!(weights.len(), CHUNK_SIZE, "Invalid chunk size");
+        let mut bytes = [0u8; CHUNK_SIZE / 2];
+        for (byte, pair) in bytes.iter_mut().zip(weights.chunks_exact(2)) {
+            *byte = pair[0].0 | (pair[1].0 << 4);
+        }
+        Self(bytes)
+    }
+
+    #[inline]
+    fn get(&self, index: usize) -> QuantizedU4 {
+        QuantizedU4((self.0[index / 2] >> (index % 2 * 4)) & QuantizedU4::MAX)
+    }
+
+    #[inline]
+    fn range_into_f32<'a>(
+        &'a self,
+        params: QuantizedU4Params,
+        start: usize,
+        buffer: &'a mut [f32],
+    ) -> &'a [f32] {
+        for (i, value) in buffer.iter_mut().enumerate() {
+            *value = self.get(start + i).to_f32(params);
+        }
+        buffer
+    }
+}
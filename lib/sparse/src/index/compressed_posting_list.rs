@@ -1,6 +1,7 @@
 use std::cmp::Ordering;
 use std::fmt::Debug;
 use std::mem::size_of;
+use std::sync::Arc;
 
 use bitpacking::BitPacker as _;
 use common::counter::hardware_counter::HardwareCounterCell;
@@ -12,11 +13,11 @@ use itertools::Itertools as _;
 use super::posting_list_common::{
     GenericPostingElement, PostingElement, PostingElementEx, PostingListIter,
 };
-use crate::common::types::{DimWeight, Weight};
+use crate::common::types::{ChunkWeightsStorage, DimWeight, Weight};
 type BitPackerImpl = bitpacking::BitPacker4x;
 
 /// How many elements are packed in a single chunk.
-const CHUNK_SIZE: usize = BitPackerImpl::BLOCK_LEN;
+pub(crate) const CHUNK_SIZE: usize = BitPackerImpl::BLOCK_LEN;
 
 #[derive(Default, Debug, Clone, PartialEq)]
 pub struct CompressedPostingList<W: Weight> {
@@ -45,11 +46,13 @@ pub struct CompressedPostingListView<'a, W: Weight> {
     last_id: Option<PointOffsetType>,
     multiplier: W::QuantizationParams,
     hw_counter: &'a HardwareCounterCell,
+    /// Posting list the slices above point into, if it is owned by the view
+    _decoded: Option<Arc<CompressedPostingList<W>>>,
 }
 
 #[derive(Debug, Clone, PartialEq)]
 #[repr(C)]
-pub struct CompressedPostingChunk<W> {
+pub struct CompressedPostingChunk<W: Weight> {
     /// Initial data point id. Used for decompression.
     initial: PointOffsetType,
 
@@ -57,19 +60,24 @@ pub struct CompressedPostingChunk<W> {
     offset: u32,
 
     /// Weight values for the chunk.
-    weights: [W; CHUNK_SIZE],
+    weights: W::ChunkWeights,
+
+    /// Max of `weights`, upper bound of the chunk contribution used for block-max pruning.
+    max_weight: W,
 }
 
-impl<W> CompressedPostingChunk<W> {
+impl<W: Weight> CompressedPostingChunk<W> {
     pub(crate) fn from_parts(
         initial: PointOffsetType,
         offset: u32,
-        weights: [W; CHUNK_SIZE],
+        weights: W::ChunkWeights,
+        max_weight: W,
     ) -> Self {
         Self {
             initial,
             offset,
             weights,
+            max_weight,
         }
     }
 
@@ -81,9 +89,13 @@ impl<W> CompressedPostingChunk<W> {
         self.offset
     }
 
-    pub(crate) fn weights(&self) -> &[W; CHUNK_SIZE] {
+    pub(crate) fn weights(&self) -> &W::ChunkWeights {
         &self.weights
     }
+
+    pub(crate) fn max_weight(&self) -> &W {
+        &self.max_weight
+    }
 }
 
 impl<W: Weight> CompressedPostingList<W> {
@@ -114,6 +126,7 @@ impl<W: Weight> CompressedPostingList<W> {
             last_id: self.last_id,
             multiplier: self.quantization_params,
             hw_counter,
+            _decoded: None,
         }
     }
 
@@ -179,15 +192,42 @@ impl<'a, W: Weight> CompressedPostingListView<'a, W> {
             last_id,
             multiplier,
             hw_counter,
+            _decoded: None,
+        }
+    }
+
+    /// View, which owns the posting list, e.g. decoded on demand from a file
+    pub(super) fn from_decoded(
+        decoded: Arc<CompressedPostingList<W>>,
+        hw_counter: &'a HardwareCounterCell,
+    ) -> Self {
+        // Safety: buffers of the posting list are neither moved nor modified while the `Arc` is
+        // alive, and the view holds it. The slices never outlive the view, see `parts`.
+        let (id_data, chunks, remainders) = unsafe {
+            (
+                std::slice::from_raw_parts(decoded.id_data.as_ptr(), decoded.id_data.len()),
+                std::slice::from_raw_parts(decoded.chunks.as_ptr(), decoded.chunks.len()),
+                std::slice::from_raw_parts(decoded.remainders.as_ptr(), decoded.remainders.len()),
+            )
+        };
+        CompressedPostingListView {
+            id_data,
+            chunks,
+            remainders,
+            last_id: decoded.last_id,
+            multiplier: decoded.quantization_params,
+            hw_counter,
+            _decoded: Some(decoded),
         }
     }
 
+    /// Slices are bound to the view, as it may own the data
     pub(super) fn parts(
         &self,
     ) -> (
-        &'a [u8],
-        &'a [CompressedPostingChunk<W>],
-        &'a [GenericPostingElement<W>],
+        &[u8],
+        &[CompressedPostingChunk<W>],
+        &[GenericPostingElement<W>],
     ) {
         (self.id_data, self.chunks, self.remainders)
     }
@@ -353,19 +393,48 @@ impl<'a, W: Weight> CompressedPostingListView<'a, W> {
     fn get_weight(&self, pos: usize) -> W {
         self.hw_counter.vector_io_read().incr_delta(size_of::<W>());
         let chunk = &self.chunks[pos / CHUNK_SIZE];
-        chunk.weights[pos % CHUNK_SIZE]
+        chunk.weights.get(pos % CHUNK_SIZE)
     }
 
+    /// Convert weights of chunk elements starting from `pos`, as many as fit into `buffer`
     #[inline]
-    fn weights_range(&self, pos: usize, count: usize) -> &[W] {
-        debug_assert!(count <= CHUNK_SIZE);
+    fn weights_into_f32<'b>(&'b self, pos: usize, buffer: &'b mut [f32]) -> &'b [f32] {
+        debug_assert!(buffer.len() <= CHUNK_SIZE);
         self.hw_counter
             .vector_io_read()
-            .incr_delta(size_of::<W>() * count);
+            .incr_delta(size_of::<W>() * buffer.len());
 
         let chunk = &self.chunks[pos / CHUNK_SIZE];
-        let start = pos % CHUNK_SIZE;
-        chunk.weights[start..start + count].as_ref()
+        chunk
+            .weights
+            .range_into_f32(self.multiplier, pos % CHUNK_SIZE, buffer)
+    }
+
+    /// Upper bound of weights of the elements starting from `pos`, with ids up to `id` (inclusive).
+    ///
+    /// Uses max weights of the chunks, so only the chunk headers are read. Remainders are small,
+    /// and scanned directly.
+    fn max_weight_till_id(&self, pos: usize, id: PointOffsetType) -> Option<DimWeight> {
+        let mut max_weight: Option<DimWeight> = None;
+
+        for chunk in self.chunks.iter().skip(pos / CHUNK_SIZE) {
+            if chunk.initial > id {
+                return max_weight;
+            }
+            self.hw_counter.vector_io_read().incr_delta(size_of::<W>());
+            let weight = chunk.max_weight.to_f32(self.multiplier);
+            max_weight = Some(max_weight.map_or(weight, |max_weight| max_weight.max(weight)));
+        }
+
+        let remainder_pos = pos.saturating_sub(self.chunks.len() * CHUNK_SIZE);
+        for e in self.iter_remainder_from(remainder_pos) {
+            if e.record_id > id {
+                break;
+            }
+            let weight = e.weight.to_f32(self.multiplier);
+            max_weight = Some(max_weight.map_or(weight, |max_weight| max_weight.max(weight)));
+        }
+        max_weight
     }
 
     pub fn iter(&self) -> CompressedPostingListIterator<'a, W> {
@@ -417,15 +486,21 @@ impl CompressedPostingBuilder {
                 let chunk_bits =
                     bitpacker.num_bits_strictly_sorted(initial.checked_sub(1), &this_chunk);
                 let chunk_size = BitPackerImpl::compressed_block_size(chunk_bits);
+                // Quantization is monotonic, so the max element stays the max after it
+                let max_weight = chunk
+                    .iter()
+                    .map(|e| e.weight)
+                    .fold(DimWeight::NEG_INFINITY, DimWeight::max);
                 chunks.push(CompressedPostingChunk {
                     initial,
                     offset: data_size as u32,
-                    weights: chunk
-                        .iter()
-                        .map(|e| Weight::from_f32(quantization_params, e.weight))
-                        .collect::<Vec<_>>()
-                        .try_into()
-                        .expect("Invalid chunk size"),
+                    weights: W::ChunkWeights::from_weights(
+                        &chunk
+                            .iter()
+                            .map(|e| Weight::from_f32(quantization_params, e.weight))
+                            .collect::<Vec<_>>(),
+                    ),
+                    max_weight: Weight::from_f32(quantization_params, max_weight),
                 });
                 data_size += chunk_size;
             } else {
@@ -548,6 +623,10 @@ impl<W: Weight> PostingListIter for CompressedPostingListIterator<'_, W> {
         size_of::<W>()
     }
 
+    fn max_weight_till_id(&mut self, id: PointOffsetType) -> Option<DimWeight> {
+        self.list.max_weight_till_id(self.pos.0, id)
+    }
+
     fn skip_to(&mut self, record_id: PointOffsetType) -> Option<PostingElementEx> {
         // 1. Define which chunk we need to unpack (maybe it is current)
         // 2. If current, change the position to the element and do peek
@@ -656,9 +735,7 @@ impl<W: Weight> PostingListIter for CompressedPostingListIterator<'_, W> {
             let start = pos % CHUNK_SIZE;
             let count = count_le_sorted(id, &self.decompressed_chunk[start..]);
 
-            let weights = self.list.weights_range(pos, count);
-            let weights =
-                W::into_f32_slice(self.list.multiplier, weights, &mut weights_buf[..count]);
+            let weights = self.list.weights_into_f32(pos, &mut weights_buf[..count]);
 
             for (idx, weight) in
                 std::iter::zip(&self.decompressed_chunk[start..start + count], weights)
@@ -803,6 +880,62 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_max_weight_till_id() {
+        // Two chunks and a remainder, weights grow within each of them
+        let case = (0..320)
+            .map(|i| (i as PointOffsetType, (i % CHUNK_SIZE) as DimWeight))
+            .collect::<Vec<_>>();
+        let list = CompressedPostingList::<f32>::from(case);
+        let hw_counter = HardwareCounterCell::new();
+
+        let mut iter = list.iter(&hw_counter);
+        // Chunk max is used for the whole chunk
+        assert_eq!(iter.max_weight_till_id(0), Some(127.0));
+        assert_eq!(iter.max_weight_till_id(200), Some(127.0));
+        // Remainders are checked element by element
+        assert_eq!(iter.max_weight_till_id(260), Some(127.0));
+
+        iter.skip_to(256);
+        assert_eq!(iter.max_weight_till_id(260), Some(4.0));
+        assert_eq!(iter.max_weight_till_id(PointOffsetType::MAX), Some(63.0));
+
+        iter.skip_to_end();
+        assert_eq!(iter.max_weight_till_id(PointOffsetType::MAX), None);
+    }
+
+    #[test]
+    fn test_quantized_u4() {
+        use crate::common::types::{QuantizedU4, QuantizedU8};
+
+        // Weights `0..=15` are exactly representable
+        let case = (0..320)
+            .map(|i| (i as PointOffsetType, (i % 16) as DimWeight))
+            .collect::<Vec<_>>();
+        let list = CompressedPostingList::<QuantizedU4>::from(case.clone());
+        let hw_counter = HardwareCounterCell::new();
+
+        // Two weights per byte in chunks
+        assert!(
+            size_of::<CompressedPostingChunk<QuantizedU4>>()
+                < size_of::<CompressedPostingChunk<QuantizedU8>>()
+        );
+
+        let mut iter = list.iter(&hw_counter);
+        let mut data = Vec::new();
+        while let Some(e) = iter.next() {
+            data.push((e.record_id, e.weight));
+        }
+        assert_eq!(data, case);
+
+        let mut iter = list.iter(&hw_counter);
+        let mut data = Vec::new();
+        iter.for_each_till_id(PointOffsetType::MAX, &mut (), |_, id, weight| {
+            data.push((id, weight));
+        });
+        assert_eq!(data, case);
+    }
+
     #[test]
     fn test_count_le_sorted() {
         let data = [1, 2, 4, 5];
@@ -0,0 +1,378 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use common::fs::{atomic_save_bin, read_bin};
+use common::types::PointOffsetType;
+use itertools::Itertools as _;
+use serde::{Deserialize, Serialize};
+
+use super::InvertedIndex as _;
+use super::inverted_index_ram::InvertedIndexRam;
+use crate::common::sparse_vector::RemappedSparseVector;
+use crate::common::types::{DimId, DimWeight};
+use crate::index::posting_list::{PostingList, PostingListIterator};
+use crate::index::posting_list_common::{PostingElement, PostingElementEx, PostingListIter};
+
+const DELTA_FILE_NAME: &str = "inverted_index_delta.bin";
+
+/// Saves updates of a [`DeltaOverlay`], see [`super::InvertedIndex::flusher`]
+pub type DeltaFlusher = Box<dyn FnOnce() -> std::io::Result<()> + Send>;
+
+/// Record updated on top of the base index
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct DeltaRecord {
+    /// Current vector, `None` if the record is removed
+    vector: Option<RemappedSparseVector>,
+    /// Number of elements of the record in the base index, `None` if it is not indexed there
+    base_len: Option<usize>,
+}
+
+/// Persisted updates, bound to the base index they were applied to
+#[derive(Serialize, Deserialize)]
+struct DeltaFile {
+    /// Checksum of the base index file, see [`super::inverted_index_compressed_mmap::InvertedIndexFileHeader`]
+    base_checksum: Option<String>,
+    records: HashMap<PointOffsetType, DeltaRecord>,
+}
+
+/// Updates of records applied on top of an immutable inverted index.
+///
+/// Current vectors of updated records are kept in a RAM index, while their elements in the base
+/// index are masked out at query time. Updates are persisted into a delta file next to the base
+/// index, and are merged into the base index when it is compacted.
+#[derive(Debug)]
+pub struct DeltaOverlay {
+    records: HashMap<PointOffsetType, DeltaRecord>,
+    /// Current vectors of updated records
+    ram: InvertedIndexRam,
+    /// Checksum of the base index file, the delta file is discarded if it doesn't match
+    base_checksum: Option<String>,
+    /// Incremented on each update
+    version: u64,
+    /// Version written into the delta file
+    persisted_version: Arc<AtomicU64>,
+}
+
+impl DeltaOverlay {
+    pub fn file_path(path: &Path) -> PathBuf {
+        path.join(DELTA_FILE_NAME)
+    }
+
+    /// Empty overlay of a newly written base index
+    pub fn new(base_checksum: Option<String>) -> Self {
+        Self {
+            records: HashMap::new(),
+            ram: InvertedIndexRam::empty(),
+            base_checksum,
+            version: 0,
+            persisted_version: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    /// Load updates persisted next to the base index at `path`, if any
+    pub fn load(path: &Path, base_checksum: Option<String>) -> std::io::Result<Self> {
+        let mut overlay = Self::new(base_checksum);
+
+        let file_path = Self::file_path(path);
+        if !file_path.exists() {
+            return Ok(overlay);
+        }
+        let delta_file: DeltaFile = read_bin(&file_path)?;
+        if delta_file.base_checksum != overlay.base_checksum {
+            // Written before the base index was replaced by compaction, which merged the updates
+            log::debug!(
+                "Ignoring outdated sparse index updates at {}",
+                file_path.display()
+            );
+            return Ok(overlay);
+        }
+
+        // Records are inserted in order of ids, so that posting lists are appended to
+        for (&id, record) in delta_file.records.iter().sorted_by_key(|(id, _)| **id) {
+            if let Some(vector) = &record.vector {
+                overlay.ram.upsert(id, vector.clone(), None);
+            }
+        }
+        overlay.records = delta_file.records;
+        Ok(overlay)
+    }
+
+    /// Flusher writing the delta file, `None` if there are no unsaved updates
+    pub fn flusher(&self, path: &Path) -> Option<DeltaFlusher> {
+        let version = self.version;
+        if self.persisted_version.load(Ordering::Relaxed) == version {
+            return None;
+        }
+
+        let file_path = Self::file_path(path);
+        let delta_file = DeltaFile {
+            base_checksum: self.base_checksum.clone(),
+            records: self.records.clone(),
+        };
+        let persisted_version = self.persisted_version.clone();
+        Some(Box::new(move || {
+            atomic_save_bin(&file_path, &delta_file)?;
+            persisted_version.fetch_max(version, Ordering::Relaxed);
+            Ok(())
+        }))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+
+    /// Get the record, registering its base elements on the first update
+    fn record_mut(
+        &mut self,
+        id: PointOffsetType,
+        old_vector: Option<&RemappedSparseVector>,
+    ) -> &mut DeltaRecord {
+        self.version += 1;
+        self.records.entry(id).or_insert_with(|| DeltaRecord {
+            vector: None,
+            // Empty vectors are not indexed
+            base_len: old_vector
+                .filter(|old_vector| !old_vector.is_empty())
+                .map(RemappedSparseVector::len),
+        })
+    }
+
+    pub fn upsert(
+        &mut self,
+        id: PointOffsetType,
+        vector: RemappedSparseVector,
+        old_vector: Option<RemappedSparseVector>,
+    ) {
+        let record = self.record_mut(id, old_vector.as_ref());
+        let previous = record.vector.replace(vector.clone());
+        self.ram.upsert(id, vector, previous);
+    }
+
+    pub fn remove(&mut self, id: PointOffsetType, old_vector: RemappedSparseVector) {
+        let record = self.record_mut(id, Some(&old_vector));
+        if let Some(previous) = record.vector.take() {
+            self.ram.remove(id, previous);
+        }
+    }
+
+    /// Number of records of the base index, whose elements are outdated by updates
+    pub fn outdated_count(&self) -> usize {
+        self.records
+            .values()
+            .filter(|record| record.base_len.is_some())
+            .count()
+    }
+
+    pub fn vector_count(&self, base_vector_count: usize) -> usize {
+        let present = self
+            .records
+            .values()
+            .filter(|record| record.vector.is_some())
+            .count();
+        base_vector_count.saturating_sub(self.outdated_count()) + present
+    }
+
+    pub fn total_sparse_vectors_size(&self, base_size: usize) -> usize {
+        let element_size = size_of::<PostingElementEx>();
+        let (outdated_len, present_len) =
+            self.records
+                .values()
+                .fold((0, 0), |(outdated_len, present_len), record| {
+                    (
+                        outdated_len + record.base_len.unwrap_or(0),
+                        present_len + record.vector.as_ref().map_or(0, RemappedSparseVector::len),
+                    )
+                });
+        base_size.saturating_sub(outdated_len * element_size) + present_len * element_size
+    }
+
+    /// Number of posting lists, including the ones of dimensions missing in the base index
+    pub fn postings_len(&self, base_len: usize) -> usize {
+        base_len.max(self.ram.postings.len())
+    }
+
+    pub fn posting(&self, dim_id: DimId) -> Option<&PostingList> {
+        self.ram.get(&dim_id)
+    }
+
+    /// Iterator over the base posting list combined with the updates
+    pub fn iter<'a, I: PostingListIter>(
+        &'a self,
+        base: Option<I>,
+        dim_id: DimId,
+    ) -> Option<DeltaPostingListIterator<'a, I>> {
+        let delta = self.posting(dim_id).map(PostingList::iter);
+        if base.is_none() && delta.is_none() {
+            return None;
+        }
+        Some(DeltaPostingListIterator {
+            base,
+            masked: (!self.records.is_empty()).then_some(&self.records),
+            delta,
+        })
+    }
+}
+
+/// Posting list of a base index with masked outdated records, followed by [`DeltaOverlay`]
+/// elements of the same dimension.
+///
+/// Elements of the two lists are interleaved by record id, except for
+/// [`PostingListIter::for_each_till_id`], which visits them list by list.
+#[derive(Clone)]
+pub struct DeltaPostingListIterator<'a, I> {
+    base: Option<I>,
+    /// `None` if there are no updates, to skip lookups
+    masked: Option<&'a HashMap<PointOffsetType, DeltaRecord>>,
+    delta: Option<PostingListIterator<'a>>,
+}
+
+impl<I: PostingListIter> DeltaPostingListIterator<'_, I> {
+    fn is_masked(&self, id: PointOffsetType) -> bool {
+        self.masked.is_some_and(|masked| masked.contains_key(&id))
+    }
+
+    /// Advance the base iterator past masked records
+    fn skip_masked(&mut self) {
+        let Some(masked) = self.masked else {
+            return;
+        };
+        let Some(base) = &mut self.base else {
+            return;
+        };
+        while let Some(element) = base.peek() {
+            if !masked.contains_key(&element.record_id) {
+                break;
+            }
+            let Some(next_id) = element.record_id.checked_add(1) else {
+                base.skip_to_end();
+                break;
+            };
+            base.skip_to(next_id);
+        }
+    }
+}
+
+impl<I: PostingListIter> PostingListIter for DeltaPostingListIterator<'_, I> {
+    fn peek(&mut self) -> Option<PostingElementEx> {
+        self.skip_masked();
+        let base = self.base.as_mut().and_then(PostingListIter::peek);
+        let delta = self.delta.as_mut().and_then(PostingListIter::peek);
+        match (base, delta) {
+            (Some(base), Some(delta)) => Some(if base.record_id < delta.record_id {
+                base
+            } else {
+                delta
+            }),
+            (base, delta) => base.or(delta),
+        }
+    }
+
+    fn last_id(&self) -> Option<PointOffsetType> {
+        let base = self.base.as_ref().and_then(PostingListIter::last_id);
+        let delta = self.delta.as_ref().and_then(PostingListIter::last_id);
+        base.max(delta)
+    }
+
+    fn element_size(&self) -> usize {
+        self.base
+            .as_ref()
+            .map_or(size_of::<DimWeight>(), PostingListIter::element_size)
+    }
+
+    fn max_weight_till_id(&mut self, id: PointOffsetType) -> Option<DimWeight> {
+        // Masked records may only overestimate the bound
+        let base = self
+            .base
+            .as_mut()
+            .and_then(|base| base.max_weight_till_id(id));
+        let delta = self
+            .delta
+            .as_mut()
+            .and_then(|delta| PostingListIter::max_weight_till_id(delta, id));
+        match (base, delta) {
+            (Some(base), Some(delta)) => Some(base.max(delta)),
+            (base, delta) => base.or(delta),
+        }
+    }
+
+    fn skip_to(&mut self, record_id: PointOffsetType) -> Option<PostingElementEx> {
+        let base = self
+            .base
+            .as_mut()
+            .and_then(|base| base.skip_to(record_id))
+            .filter(|element| !self.is_masked(element.record_id));
+        let delta = self
+            .delta
+            .as_mut()
+            .and_then(|delta| PostingListIter::skip_to(delta, record_id));
+        base.or(delta)
+    }
+
+    fn skip_to_end(&mut self) {
+        if let Some(base) = &mut self.base {
+            base.skip_to_end();
+        }
+        if let Some(delta) = &mut self.delta {
+            PostingListIter::skip_to_end(delta);
+        }
+    }
+
+    fn len_to_end(&self) -> usize {
+        // Includes masked records, which are not skipped yet
+        self.base.as_ref().map_or(0, PostingListIter::len_to_end)
+            + self.delta.as_ref().map_or(0, PostingListIter::len_to_end)
+    }
+
+    fn current_index(&self) -> usize {
+        self.base.as_ref().map_or(0, PostingListIter::current_index)
+            + self
+                .delta
+                .as_ref()
+                .map_or(0, PostingListIter::current_index)
+    }
+
+    fn for_each_till_id<Ctx: ?Sized>(
+        &mut self,
+        id: PointOffsetType,
+        ctx: &mut Ctx,
+        mut f: impl FnMut(&mut Ctx, PointOffsetType, DimWeight),
+    ) {
+        let masked = self.masked;
+        if let Some(base) = &mut self.base {
+            match masked {
+                Some(masked) => base.for_each_till_id(id, ctx, |ctx, record_id, weight| {
+                    if !masked.contains_key(&record_id) {
+                        f(ctx, record_id, weight);
+                    }
+                }),
+                None => base.for_each_till_id(id, ctx, &mut f),
+            }
+        }
+        if let Some(delta) = &mut self.delta {
+            delta.for_each_till_id(id, ctx, f);
+        }
+    }
+
+    fn reliable_max_next_weight() -> bool {
+        // Elements of the base list don't account for the updates
+        false
+    }
+
+    fn into_std_iter(self) -> impl Iterator<Item = PostingElement> {
+        let masked = self.masked;
+        let base = self
+            .base
+            .into_iter()
+            .flat_map(PostingListIter::into_std_iter)
+            .filter(move |element| {
+                masked.is_none_or(|masked| !masked.contains_key(&element.record_id))
+            });
+        let delta = self
+            .delta
+            .into_iter()
+            .flat_map(PostingListIter::into_std_iter);
+        base.merge_by(delta, |a, b| a.record_id < b.record_id)
+    }
+}
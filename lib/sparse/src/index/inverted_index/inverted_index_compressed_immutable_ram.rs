@@ -1,8 +1,10 @@
 use std::borrow::Cow;
+use std::collections::HashSet;
 use std::path::Path;
 
 use common::counter::hardware_counter::HardwareCounterCell;
 use common::types::PointOffsetType;
+use rayon::prelude::*;
 
 use super::InvertedIndex;
 use super::inverted_index_compressed_mmap::InvertedIndexCompressedMmap;
@@ -12,6 +14,7 @@ use crate::common::types::{DimId, DimOffset, Weight};
 use crate::index::compressed_posting_list::{
     CompressedPostingBuilder, CompressedPostingList, CompressedPostingListIterator,
 };
+use crate::index::posting_list::PostingList;
 use crate::index::posting_list_common::PostingListIter as _;
 
 #[derive(Debug, Clone, PartialEq)]
@@ -19,6 +22,7 @@ pub struct InvertedIndexCompressedImmutableRam<W: Weight> {
     pub(super) postings: Vec<CompressedPostingList<W>>,
     pub(super) vector_count: usize,
     pub(super) total_sparse_size: usize,
+    pub(super) max_posting_length: Option<usize>,
 }
 
 impl<W: Weight> InvertedIndexCompressedImmutableRam<W> {
@@ -26,23 +30,27 @@ impl<W: Weight> InvertedIndexCompressedImmutableRam<W> {
     pub(super) fn into_postings(self) -> Vec<CompressedPostingList<W>> {
         self.postings
     }
-}
-
-impl<W: Weight> InvertedIndex for InvertedIndexCompressedImmutableRam<W> {
-    type Iter<'a> = CompressedPostingListIterator<'a, W>;
 
-    type Version = <InvertedIndexCompressedMmap<W> as InvertedIndex>::Version;
-
-    fn is_on_disk(&self) -> bool {
-        false
+    pub(super) fn compress_posting_list(posting_list: &PostingList) -> CompressedPostingList<W> {
+        let mut new_posting_list = CompressedPostingBuilder::new();
+        for elem in &posting_list.elements {
+            new_posting_list.add(elem.record_id, elem.weight);
+        }
+        new_posting_list.build()
     }
 
-    fn open(path: &Path) -> std::io::Result<Self> {
-        let mmap_inverted_index = InvertedIndexCompressedMmap::load(path)?;
+    pub(super) fn from_mmap(
+        mmap_inverted_index: &InvertedIndexCompressedMmap<W>,
+    ) -> std::io::Result<Self> {
         let mut inverted_index = InvertedIndexCompressedImmutableRam {
             postings: Vec::with_capacity(mmap_inverted_index.file_header.posting_count),
             vector_count: mmap_inverted_index.file_header.vector_count,
-            total_sparse_size: mmap_inverted_index.total_sparse_vectors_size(),
+            // Calculated after reading mmap, if not stored in the file header
+            total_sparse_size: mmap_inverted_index
+                .file_header
+                .total_sparse_size
+                .unwrap_or_default(),
+            max_posting_length: mmap_inverted_index.file_header.max_posting_length,
         };
 
         let hw_counter = HardwareCounterCell::disposable();
@@ -57,9 +65,92 @@ impl<W: Weight> InvertedIndex for InvertedIndexCompressedImmutableRam<W> {
             inverted_index.postings.push(posting_list.to_owned());
         }
 
+        if mmap_inverted_index.file_header.total_sparse_size.is_none() {
+            inverted_index.total_sparse_size = inverted_index
+                .postings
+                .iter()
+                .map(|p| p.view(&hw_counter).store_size().total)
+                .sum();
+        }
+
         Ok(inverted_index)
     }
 
+    /// Posting list without elements of deleted records, or `None` if it has none of them.
+    ///
+    /// Remaining elements are re-encoded, so their quantized weights may slightly change.
+    pub(super) fn posting_without_deleted(
+        posting_iter: CompressedPostingListIterator<W>,
+        is_deleted: &impl Fn(PointOffsetType) -> bool,
+    ) -> Option<CompressedPostingList<W>> {
+        if !posting_iter
+            .clone()
+            .into_std_iter()
+            .any(|elem| is_deleted(elem.record_id))
+        {
+            return None;
+        }
+
+        let mut new_posting_list = CompressedPostingBuilder::new();
+        for elem in posting_iter.into_std_iter() {
+            if !is_deleted(elem.record_id) {
+                new_posting_list.add(elem.record_id, elem.weight);
+            }
+        }
+        Some(new_posting_list.build())
+    }
+
+    /// Copy of the index without elements of deleted records
+    fn without_deleted(&self, is_deleted: impl Fn(PointOffsetType) -> bool) -> Self {
+        let hw_counter = HardwareCounterCell::disposable();
+        let mut removed_records = HashSet::new();
+
+        let postings: Vec<_> = self
+            .postings
+            .iter()
+            .map(|posting_list| {
+                let posting_iter = posting_list.iter(&hw_counter);
+                removed_records.extend(
+                    posting_iter
+                        .clone()
+                        .into_std_iter()
+                        .map(|elem| elem.record_id)
+                        .filter(|&record_id| is_deleted(record_id)),
+                );
+                Self::posting_without_deleted(posting_iter, &is_deleted)
+                    .unwrap_or_else(|| posting_list.clone())
+            })
+            .collect();
+
+        let total_sparse_size = postings
+            .iter()
+            .map(|p| p.view(&hw_counter).store_size().total)
+            .sum();
+
+        InvertedIndexCompressedImmutableRam {
+            postings,
+            // Empty vectors are not indexed, so each record has elements in some posting list
+            vector_count: self.vector_count.saturating_sub(removed_records.len()),
+            total_sparse_size,
+            max_posting_length: self.max_posting_length,
+        }
+    }
+}
+
+impl<W: Weight> InvertedIndex for InvertedIndexCompressedImmutableRam<W> {
+    type Iter<'a> = CompressedPostingListIterator<'a, W>;
+
+    type Version = <InvertedIndexCompressedMmap<W> as InvertedIndex>::Version;
+
+    fn is_on_disk(&self) -> bool {
+        false
+    }
+
+    fn open(path: &Path) -> std::io::Result<Self> {
+        let mmap_inverted_index = InvertedIndexCompressedMmap::load(path)?;
+        Self::from_mmap(&mmap_inverted_index)
+    }
+
     fn save(&self, path: &Path) -> std::io::Result<()> {
         InvertedIndexCompressedMmap::convert_and_save(self, path)?;
         Ok(())
@@ -110,14 +201,12 @@ impl<W: Weight> InvertedIndex for InvertedIndexCompressedImmutableRam<W> {
         ram_index: Cow<InvertedIndexRam>,
         _path: P,
     ) -> std::io::Result<Self> {
-        let mut postings = Vec::with_capacity(ram_index.postings.len());
-        for old_posting_list in &ram_index.postings {
-            let mut new_posting_list = CompressedPostingBuilder::new();
-            for elem in &old_posting_list.elements {
-                new_posting_list.add(elem.record_id, elem.weight);
-            }
-            postings.push(new_posting_list.build());
-        }
+        // Order of posting lists is preserved by the parallel collect
+        let postings: Vec<_> = ram_index
+            .postings
+            .par_iter()
+            .map(Self::compress_posting_list)
+            .collect();
 
         let hw_counter = HardwareCounterCell::disposable();
 
@@ -130,6 +219,7 @@ impl<W: Weight> InvertedIndex for InvertedIndexCompressedImmutableRam<W> {
             postings,
             vector_count: ram_index.vector_count,
             total_sparse_size,
+            max_posting_length: ram_index.max_posting_length,
         })
     }
 
@@ -147,6 +237,16 @@ impl<W: Weight> InvertedIndex for InvertedIndexCompressedImmutableRam<W> {
             .checked_sub(1)
             .map(|len| len as DimOffset)
     }
+
+    fn compact(
+        &self,
+        path: &Path,
+        is_deleted: impl Fn(PointOffsetType) -> bool,
+    ) -> std::io::Result<Option<Self>> {
+        let inverted_index = self.without_deleted(is_deleted);
+        inverted_index.save(path)?;
+        Ok(Some(inverted_index))
+    }
 }
 
 #[cfg(test)]
@@ -155,7 +255,7 @@ mod tests {
 
     use super::*;
     use crate::common::sparse_vector_fixture::random_sparse_vector;
-    use crate::common::types::QuantizedU8;
+    use crate::common::types::{QuantizedU4, QuantizedU8};
     use crate::index::inverted_index::inverted_index_ram_builder::InvertedIndexBuilder;
 
     #[test]
@@ -170,6 +270,7 @@ mod tests {
         check_save_load::<half::f16>(&inverted_index_ram);
         check_save_load::<u8>(&inverted_index_ram);
         check_save_load::<QuantizedU8>(&inverted_index_ram);
+        check_save_load::<QuantizedU4>(&inverted_index_ram);
     }
 
     #[test]
@@ -186,6 +287,7 @@ mod tests {
         check_save_load::<half::f16>(&inverted_index_ram);
         check_save_load::<u8>(&inverted_index_ram);
         check_save_load::<QuantizedU8>(&inverted_index_ram);
+        check_save_load::<QuantizedU4>(&inverted_index_ram);
     }
 
     fn check_save_load<W: Weight>(inverted_index_ram: &InvertedIndexRam) {
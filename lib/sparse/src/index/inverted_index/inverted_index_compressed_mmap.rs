@@ -1,44 +1,64 @@
 use std::any::TypeId;
-use std::borrow::Cow;
-use std::io::{BufWriter, Write};
+use std::borrow::{Borrow, Cow};
+use std::collections::HashSet;
+use std::io::{BufWriter, Read as _, Write};
 use std::marker::PhantomData;
 use std::mem::{offset_of, size_of};
+use std::ops::Range;
 use std::path::{Path, PathBuf};
-use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, OnceLock};
 
 use bitpacking::BitPacker as _;
 use common::counter::hardware_counter::HardwareCounterCell;
 use common::fs::{atomic_save_json, clear_disk_cache, read_json};
 use common::mmap::{Advice, AdviceSetting, Madviseable};
 #[expect(deprecated, reason = "legacy code")]
-use common::mmap::{
-    create_and_ensure_length, open_read_mmap, transmute_from_u8_to_slice, transmute_to_u8,
-    transmute_to_u8_slice,
-};
+use common::mmap::{create_and_ensure_length, open_read_mmap, transmute_from_u8_to_slice};
 use common::storage_version::StorageVersion;
 use common::types::PointOffsetType;
 use memmap2::Mmap;
+use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
+use sha2::{Digest as _, Sha256};
 
 use super::INDEX_FILE_NAME;
+use super::delta_overlay::{DeltaFlusher, DeltaOverlay, DeltaPostingListIterator};
 use super::inverted_index_compressed_immutable_ram::InvertedIndexCompressedImmutableRam;
 use crate::common::sparse_vector::RemappedSparseVector;
-use crate::common::types::{DimId, DimOffset, QuantizedU8, QuantizedU8Params, Weight};
+use crate::common::types::{
+    ChunkWeightsStorage, DimId, DimOffset, QuantizedU4, QuantizedU4Params, QuantizedU8,
+    QuantizedU8Params, Weight, WeightType,
+};
 use crate::index::compressed_posting_list::{
-    CompressedPostingChunk, CompressedPostingList, CompressedPostingListIterator,
-    CompressedPostingListView,
+    CompressedPostingBuilder, CompressedPostingChunk, CompressedPostingList,
+    CompressedPostingListIterator, CompressedPostingListView,
 };
 use crate::index::inverted_index::InvertedIndex;
 use crate::index::inverted_index::inverted_index_ram::InvertedIndexRam;
-use crate::index::posting_list_common::GenericPostingElement;
+use crate::index::posting_list_common::{GenericPostingElement, PostingListIter as _};
 
 const INDEX_CONFIG_FILE_NAME: &str = "inverted_index_config.json";
 
+/// Size of reads of [`verify_index_file`]
+const VERIFY_READ_SIZE: usize = 1024 * 1024;
+
+/// Number of posting lists compressed in parallel when the index is built from RAM index.
+/// Limits memory held by compressed posting lists, which are not written yet.
+const COMPRESSION_BATCH_SIZE: usize = 1024;
+
+/// Max number of decoded posting lists pinned in memory on big-endian targets
+const HOT_POSTINGS_CAPACITY: usize = 256;
+
+/// Number of lookups, after which a posting list is considered hot and gets pinned
+const HOT_POSTING_MIN_LOOKUPS: usize = 8;
+
 pub struct Version;
 
 impl StorageVersion for Version {
     fn current_raw() -> &'static str {
-        "0.2.0"
+        // 0.3.0: chunks store max weight for block-max pruning
+        "0.3.0"
     }
 }
 
@@ -50,9 +70,20 @@ pub struct InvertedIndexFileHeader {
     pub vector_count: usize,
     /// Total size of all searchable sparse vectors in bytes
     // This is an option because earlier versions of the index did not store this information.
-    // In case it is not present, it will be calculated on load.
+    // In case it is not present, it will be calculated on first use and persisted on save.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub total_sparse_size: Option<usize>,
+    /// Type of the stored weights, the index can only be opened with the same type
+    // Not present in indexes created by earlier versions, which are not checked.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub weight_type: Option<WeightType>,
+    /// SHA-256 of the index file, checked by [`InvertedIndexCompressedMmap::verify`]
+    // Not present in indexes created by earlier versions.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub checksum: Option<String>,
+    /// Length cap posting lists were pruned to on build, search over such index is approximate
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_posting_length: Option<usize>,
 }
 
 /// Inverted flatten index from dimension id to posting list
@@ -60,7 +91,11 @@ pub struct InvertedIndexFileHeader {
 pub struct InvertedIndexCompressedMmap<W: Weight> {
     path: PathBuf,
     mmap: Arc<Mmap>,
-    decoded_postings: Option<Vec<CompressedPostingList<W>>>,
+    decoded_postings: Option<DecodedPostingsCache<W>>,
+    /// Size of sparse vectors, calculated from posting lists if the file header lacks it
+    calculated_total_sparse_size: OnceLock<usize>,
+    /// Updates applied after the index was written, merged into the files on compaction
+    delta: DeltaOverlay,
     pub file_header: InvertedIndexFileHeader,
     _phantom: PhantomData<W>,
 }
@@ -78,6 +113,13 @@ struct PostingListFileHeader<W: Weight> {
     pub quantization_params: W::QuantizationParams,
 }
 
+/// Byte ranges of posting list parts within the index file
+struct PostingListRanges {
+    ids: Range<usize>,
+    chunks: Range<usize>,
+    remainders: Range<usize>,
+}
+
 #[derive(Debug, Clone, Copy)]
 struct PostingListFileHeaderDecoded<W: Weight> {
     ids_start: u64,
@@ -87,8 +129,54 @@ struct PostingListFileHeaderDecoded<W: Weight> {
     quantization_params: W::QuantizationParams,
 }
 
+/// Posting lists decoded from the little-endian file on big-endian targets.
+///
+/// Posting lists of frequently queried dimensions are pinned in memory once they reach
+/// [`HOT_POSTING_MIN_LOOKUPS`], until [`HOT_POSTINGS_CAPACITY`] is reached. Other posting lists
+/// are decoded on each lookup and dropped together with their iterators.
+#[derive(Debug)]
+struct DecodedPostingsCache<W: Weight> {
+    /// Number of lookups of each posting list
+    lookups: Vec<AtomicUsize>,
+    pinned: Vec<OnceLock<CompressedPostingList<W>>>,
+    pinned_count: AtomicUsize,
+    capacity: usize,
+}
+
+impl<W: Weight> DecodedPostingsCache<W> {
+    fn new(posting_count: usize, capacity: usize) -> Self {
+        Self {
+            lookups: (0..posting_count).map(|_| AtomicUsize::new(0)).collect(),
+            pinned: (0..posting_count).map(|_| OnceLock::new()).collect(),
+            pinned_count: AtomicUsize::new(0),
+            capacity,
+        }
+    }
+
+    /// Register a lookup of the posting list, returns `true` if it should be pinned
+    fn register_lookup(&self, id: usize) -> bool {
+        let lookups = self.lookups[id].fetch_add(1, Ordering::Relaxed) + 1;
+        lookups >= HOT_POSTING_MIN_LOOKUPS
+            && self
+                .pinned_count
+                .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |count| {
+                    (count < self.capacity).then_some(count + 1)
+                })
+                .is_ok()
+    }
+
+    /// Pin the posting list into a slot reserved by [`Self::register_lookup`]
+    fn pin(&self, id: usize, posting: CompressedPostingList<W>) -> &CompressedPostingList<W> {
+        if self.pinned[id].set(posting).is_err() {
+            // Pinned concurrently, release the reserved slot
+            self.pinned_count.fetch_sub(1, Ordering::Relaxed);
+        }
+        self.pinned[id].get().expect("posting list is pinned")
+    }
+}
+
 impl<W: Weight> InvertedIndex for InvertedIndexCompressedMmap<W> {
-    type Iter<'a> = CompressedPostingListIterator<'a, W>;
+    type Iter<'a> = DeltaPostingListIterator<'a, CompressedPostingListIterator<'a, W>>;
 
     type Version = Version;
 
@@ -100,6 +188,10 @@ impl<W: Weight> InvertedIndex for InvertedIndexCompressedMmap<W> {
         Self::load(path)
     }
 
+    fn advise(&self, advice: Advice) -> std::io::Result<()> {
+        self.mmap.madvise(advice)
+    }
+
     fn save(&self, path: &Path) -> std::io::Result<()> {
         debug_assert_eq!(path, self.path);
 
@@ -110,6 +202,17 @@ impl<W: Weight> InvertedIndex for InvertedIndexCompressedMmap<W> {
             debug_assert!(file.exists());
         }
 
+        // Persist the size calculated for indexes created by earlier versions
+        if self.file_header.total_sparse_size.is_none()
+            && let Some(&total_sparse_size) = self.calculated_total_sparse_size.get()
+        {
+            let file_header = InvertedIndexFileHeader {
+                total_sparse_size: Some(total_sparse_size),
+                ..self.file_header.clone()
+            };
+            atomic_save_json(&Self::index_config_file_path(path), &file_header)?;
+        }
+
         Ok(())
     }
 
@@ -117,71 +220,167 @@ impl<W: Weight> InvertedIndex for InvertedIndexCompressedMmap<W> {
         &'a self,
         id: DimOffset,
         hw_counter: &'a HardwareCounterCell,
-    ) -> Option<CompressedPostingListIterator<'a, W>> {
-        self.get(id, hw_counter)
-            .map(|posting_list| posting_list.iter())
+    ) -> Option<Self::Iter<'a>> {
+        let base = self
+            .get(id, hw_counter)
+            .map(|posting_list| posting_list.iter());
+        self.delta.iter(base, id)
     }
 
     fn len(&self) -> usize {
-        self.file_header.posting_count
+        self.delta.postings_len(self.file_header.posting_count)
     }
 
     fn posting_list_len(&self, id: &DimOffset, hw_counter: &HardwareCounterCell) -> Option<usize> {
+        if self.has_updates() {
+            return InvertedIndex::get(self, *id, hw_counter)
+                .map(|posting_list| posting_list.into_std_iter().count());
+        }
         self.get(*id, hw_counter)
             .map(|posting_list| posting_list.len())
     }
 
     fn files(path: &Path) -> Vec<PathBuf> {
+        let mut files = Self::immutable_files(path);
+        let delta_file_path = DeltaOverlay::file_path(path);
+        if delta_file_path.exists() {
+            files.push(delta_file_path);
+        }
+        files
+    }
+
+    fn immutable_files(path: &Path) -> Vec<PathBuf> {
+        // Updates are written into the delta file, index files are never changed
         vec![
             Self::index_file_path(path),
             Self::index_config_file_path(path),
         ]
     }
 
-    fn immutable_files(path: &Path) -> Vec<PathBuf> {
-        // `InvertedIndexCompressedMmap` is always immutable
-        Self::files(path)
-    }
-
-    fn remove(&mut self, _id: PointOffsetType, _old_vector: RemappedSparseVector) {
-        panic!("Cannot remove from a read-only Mmap inverted index")
+    fn remove(&mut self, id: PointOffsetType, old_vector: RemappedSparseVector) {
+        self.delta.remove(id, old_vector);
     }
 
     fn upsert(
         &mut self,
-        _id: PointOffsetType,
-        _vector: RemappedSparseVector,
-        _old_vector: Option<RemappedSparseVector>,
+        id: PointOffsetType,
+        vector: RemappedSparseVector,
+        old_vector: Option<RemappedSparseVector>,
     ) {
-        panic!("Cannot upsert into a read-only Mmap inverted index")
+        self.delta.upsert(id, vector, old_vector);
     }
 
     fn from_ram_index<P: AsRef<Path>>(
         ram_index: Cow<InvertedIndexRam>,
         path: P,
     ) -> std::io::Result<Self> {
-        let index = InvertedIndexCompressedImmutableRam::from_ram_index(ram_index, &path)?;
-        Self::convert_and_save(&index, path)
+        // Posting lists are compressed in both passes, so that the whole compressed index is never
+        // held in memory. Batches of posting lists are compressed in parallel, one at a time.
+        let postings = || {
+            ram_index
+                .postings
+                .chunks(COMPRESSION_BATCH_SIZE)
+                .flat_map(|batch| {
+                    batch
+                        .par_iter()
+                        .map(InvertedIndexCompressedImmutableRam::<W>::compress_posting_list)
+                        .collect::<Vec<_>>()
+                })
+        };
+        Self::convert_and_save_streaming(
+            postings,
+            ram_index.vector_count,
+            ram_index.max_posting_length,
+            path,
+        )
     }
 
     fn vector_count(&self) -> usize {
-        self.file_header.vector_count
+        self.delta.vector_count(self.file_header.vector_count)
     }
 
     fn total_sparse_vectors_size(&self) -> usize {
-        debug_assert!(
-            self.file_header.total_sparse_size.is_some(),
-            "The field should be populated from the file, or on load"
-        );
-        self.file_header.total_sparse_size.unwrap_or(0)
+        let base_size = match self.file_header.total_sparse_size {
+            Some(total_sparse_size) => total_sparse_size,
+            None => *self.calculated_total_sparse_size.get_or_init(|| {
+                self.calculate_total_sparse_size(&HardwareCounterCell::disposable())
+            }),
+        };
+        self.delta.total_sparse_vectors_size(base_size)
     }
 
     fn max_index(&self) -> Option<DimId> {
-        match self.file_header.posting_count {
+        match InvertedIndex::len(self) {
             0 => None,
             len => Some(len as DimId - 1),
         }
     }
+
+    fn accepts_updates(&self) -> bool {
+        true
+    }
+
+    fn outdated_vector_count(&self) -> usize {
+        self.delta.outdated_count()
+    }
+
+    fn flusher(&self) -> Option<DeltaFlusher> {
+        self.delta.flusher(&self.path)
+    }
+
+    /// Posting lists are re-encoded one at a time, and merged with the updates, if any.
+    /// The whole index is never held in memory.
+    fn compact(
+        &self,
+        path: &Path,
+        is_deleted: impl Fn(PointOffsetType) -> bool,
+    ) -> std::io::Result<Option<Self>> {
+        if self.has_updates() {
+            return self.compact_with_updates(path, is_deleted).map(Some);
+        }
+
+        let hw_counter = HardwareCounterCell::disposable();
+        let posting_count = self.file_header.posting_count as DimId;
+
+        let mut removed_records = HashSet::new();
+        for id in 0..posting_count {
+            let posting = self
+                .get(id, &hw_counter)
+                .ok_or_else(|| Self::invalid_data(format!("Posting list {id} not found")))?;
+            removed_records.extend(
+                posting
+                    .iter()
+                    .into_std_iter()
+                    .map(|elem| elem.record_id)
+                    .filter(|&record_id| is_deleted(record_id)),
+            );
+        }
+
+        let postings = || {
+            (0..posting_count).map(|id| {
+                // All posting lists are checked to exist above
+                let Some(posting) = self.get(id, &hw_counter) else {
+                    return CompressedPostingBuilder::new().build();
+                };
+                InvertedIndexCompressedImmutableRam::posting_without_deleted(
+                    posting.iter(),
+                    &is_deleted,
+                )
+                .unwrap_or_else(|| posting.to_owned())
+            })
+        };
+
+        Self::convert_and_save_streaming(
+            postings,
+            // Empty vectors are not indexed, so each record has elements in some posting list
+            self.file_header
+                .vector_count
+                .saturating_sub(removed_records.len()),
+            self.file_header.max_posting_length,
+            path,
+        )
+        .map(Some)
+    }
 }
 
 impl<W: Weight> InvertedIndexCompressedMmap<W> {
@@ -195,6 +394,55 @@ impl<W: Weight> InvertedIndexCompressedMmap<W> {
         path.join(INDEX_CONFIG_FILE_NAME)
     }
 
+    /// Whether records were updated since the index was written
+    pub fn has_updates(&self) -> bool {
+        !self.delta.is_empty()
+    }
+
+    /// Write posting lists merged with the updates into `path`, without elements of deleted
+    /// records
+    fn compact_with_updates(
+        &self,
+        path: &Path,
+        is_deleted: impl Fn(PointOffsetType) -> bool,
+    ) -> std::io::Result<Self> {
+        let hw_counter = HardwareCounterCell::disposable();
+        let posting_count = InvertedIndex::len(self) as DimId;
+
+        let mut removed_records = HashSet::new();
+        for id in 0..posting_count {
+            if let Some(posting) = InvertedIndex::get(self, id, &hw_counter) {
+                removed_records.extend(
+                    posting
+                        .into_std_iter()
+                        .map(|elem| elem.record_id)
+                        .filter(|&record_id| is_deleted(record_id)),
+                );
+            }
+        }
+
+        let postings = || {
+            (0..posting_count).map(|id| {
+                let mut new_posting_list = CompressedPostingBuilder::new();
+                if let Some(posting) = InvertedIndex::get(self, id, &hw_counter) {
+                    for elem in posting.into_std_iter() {
+                        if !is_deleted(elem.record_id) {
+                            new_posting_list.add(elem.record_id, elem.weight);
+                        }
+                    }
+                }
+                new_posting_list.build()
+            })
+        };
+
+        Self::convert_and_save_streaming(
+            postings,
+            InvertedIndex::vector_count(self).saturating_sub(removed_records.len()),
+            self.file_header.max_posting_length,
+            path,
+        )
+    }
+
     pub fn get<'a>(
         &'a self,
         id: DimId,
@@ -207,9 +455,7 @@ impl<W: Weight> InvertedIndexCompressedMmap<W> {
 
         if let Some(decoded_postings) = &self.decoded_postings {
             hw_counter.vector_io_read().incr_delta(Self::HEADER_SIZE);
-            return decoded_postings
-                .get(id as usize)
-                .map(|posting| posting.view(hw_counter));
+            return self.get_decoded(decoded_postings, id as usize, hw_counter);
         }
 
         // TODO Safety.
@@ -271,6 +517,30 @@ impl<W: Weight> InvertedIndexCompressedMmap<W> {
     }
 
     // TODO Safety
+    fn get_decoded<'a>(
+        &'a self,
+        decoded_postings: &'a DecodedPostingsCache<W>,
+        id: usize,
+        hw_counter: &'a HardwareCounterCell,
+    ) -> Option<CompressedPostingListView<'a, W>> {
+        if let Some(posting) = decoded_postings.pinned[id].get() {
+            return Some(posting.view(hw_counter));
+        }
+
+        let posting =
+            Self::decode_posting_at_le(self.mmap.as_ref(), self.file_header.posting_count, id)
+                .inspect_err(|err| log::error!("Failed to decode sparse posting list {id}: {err}"))
+                .ok()?;
+
+        if decoded_postings.register_lookup(id) {
+            return Some(decoded_postings.pin(id, posting).view(hw_counter));
+        }
+        Some(CompressedPostingListView::from_decoded(
+            Arc::new(posting),
+            hw_counter,
+        ))
+    }
+
     unsafe fn slice_part<T>(&self, start: impl Into<u64>, count: impl Into<u64>) -> &[T] {
         let start = start.into() as usize;
         let end = start + count.into() as usize * size_of::<T>();
@@ -292,13 +562,25 @@ impl<W: Weight> InvertedIndexCompressedMmap<W> {
             Some(size_of::<u16>())
         } else if TypeId::of::<W>() == TypeId::of::<u8>() {
             Some(size_of::<u8>())
-        } else if TypeId::of::<W>() == TypeId::of::<QuantizedU8>() {
+        } else if TypeId::of::<W>() == TypeId::of::<QuantizedU8>()
+            || TypeId::of::<W>() == TypeId::of::<QuantizedU4>()
+        {
             Some(size_of::<u8>())
         } else {
             None
         }
     }
 
+    /// Size of weights of a full chunk, 4-bit weights are packed two per byte
+    fn chunk_weights_size() -> Option<usize> {
+        let weights_per_chunk = bitpacking::BitPacker4x::BLOCK_LEN;
+        if TypeId::of::<W>() == TypeId::of::<QuantizedU4>() {
+            Some(weights_per_chunk / 2)
+        } else {
+            Self::weight_size()?.checked_mul(weights_per_chunk)
+        }
+    }
+
     fn quantization_params_size() -> Option<usize> {
         if TypeId::of::<W>() == TypeId::of::<f32>()
             || TypeId::of::<W>() == TypeId::of::<half::f16>()
@@ -307,6 +589,8 @@ impl<W: Weight> InvertedIndexCompressedMmap<W> {
             Some(0)
         } else if TypeId::of::<W>() == TypeId::of::<QuantizedU8>() {
             Some(size_of::<QuantizedU8Params>())
+        } else if TypeId::of::<W>() == TypeId::of::<QuantizedU4>() {
+            Some(size_of::<QuantizedU4Params>())
         } else {
             None
         }
@@ -342,6 +626,18 @@ impl<W: Weight> InvertedIndexCompressedMmap<W> {
             let value = QuantizedU8::from_raw(bytes[0]);
             return Ok(unsafe { std::mem::transmute_copy::<QuantizedU8, W>(&value) });
         }
+        if TypeId::of::<W>() == TypeId::of::<QuantizedU4>() {
+            if bytes.len() != size_of::<u8>() {
+                return Err(Self::invalid_data("invalid QuantizedU4 sparse weight size"));
+            }
+            if bytes[0] > 0x0F {
+                return Err(Self::invalid_data(
+                    "invalid QuantizedU4 sparse weight value",
+                ));
+            }
+            let value = QuantizedU4::from_raw(bytes[0]);
+            return Ok(unsafe { std::mem::transmute_copy::<QuantizedU4, W>(&value) });
+        }
 
         Err(Self::invalid_data(format!(
             "unsupported sparse weight type {} for mmap endianness conversion",
@@ -384,6 +680,16 @@ impl<W: Weight> InvertedIndexCompressedMmap<W> {
             out[0] = value.raw();
             return Ok(());
         }
+        if TypeId::of::<W>() == TypeId::of::<QuantizedU4>() {
+            if out.len() != size_of::<u8>() {
+                return Err(Self::invalid_data(
+                    "invalid QuantizedU4 sparse weight output size",
+                ));
+            }
+            let value = unsafe { std::mem::transmute_copy::<W, QuantizedU4>(&value) };
+            out[0] = value.raw();
+            return Ok(());
+        }
 
         Err(Self::invalid_data(format!(
             "unsupported sparse weight type {} for mmap endianness conversion",
@@ -417,6 +723,18 @@ impl<W: Weight> InvertedIndexCompressedMmap<W> {
             });
         }
 
+        if TypeId::of::<W>() == TypeId::of::<QuantizedU4>() {
+            if bytes.len() != size_of::<QuantizedU4Params>() {
+                return Err(Self::invalid_data("invalid QuantizedU4 params size"));
+            }
+            let min = f32::from_le_bytes(bytes[0..4].try_into().expect("slice size checked"));
+            let diff16 = f32::from_le_bytes(bytes[4..8].try_into().expect("slice size checked"));
+            let params = QuantizedU4Params::from_parts(min, diff16);
+            return Ok(unsafe {
+                std::mem::transmute_copy::<QuantizedU4Params, W::QuantizationParams>(&params)
+            });
+        }
+
         Err(Self::invalid_data(format!(
             "unsupported sparse quantization params type for {}",
             std::any::type_name::<W>()
@@ -452,6 +770,19 @@ impl<W: Weight> InvertedIndexCompressedMmap<W> {
             return Ok(());
         }
 
+        if TypeId::of::<W>() == TypeId::of::<QuantizedU4>() {
+            if out.len() != size_of::<QuantizedU4Params>() {
+                return Err(Self::invalid_data("invalid QuantizedU4 params output size"));
+            }
+            let params = unsafe {
+                std::mem::transmute_copy::<W::QuantizationParams, QuantizedU4Params>(&params)
+            };
+            let (min, diff16) = params.parts();
+            out[0..4].copy_from_slice(&min.to_le_bytes());
+            out[4..8].copy_from_slice(&diff16.to_le_bytes());
+            return Ok(());
+        }
+
         Err(Self::invalid_data(format!(
             "unsupported sparse quantization params type for {}",
             std::any::type_name::<W>()
@@ -577,12 +908,12 @@ impl<W: Weight> InvertedIndexCompressedMmap<W> {
             ))
         })?;
         const WEIGHTS_OFFSET: usize = size_of::<u32>() * 2;
-        let weights_per_chunk = bitpacking::BitPacker4x::BLOCK_LEN;
-        let expected_weight_bytes = weights_per_chunk
-            .checked_mul(weight_size)
+        let expected_weight_bytes = Self::chunk_weights_size()
             .ok_or_else(|| Self::invalid_data("sparse chunk weight size overflow"))?;
+        // `max_weight` directly follows `weights`
+        let max_weight_offset = WEIGHTS_OFFSET + expected_weight_bytes;
 
-        if WEIGHTS_OFFSET + expected_weight_bytes > chunk_size {
+        if max_weight_offset + weight_size > chunk_size {
             return Err(Self::invalid_data("invalid sparse chunk layout"));
         }
 
@@ -592,16 +923,20 @@ impl<W: Weight> InvertedIndexCompressedMmap<W> {
                 u32::from_le_bytes(chunk_bytes[0..4].try_into().expect("slice size checked"));
             let offset =
                 u32::from_le_bytes(chunk_bytes[4..8].try_into().expect("slice size checked"));
-            let mut weights = Vec::with_capacity(weights_per_chunk);
-            for i in 0..weights_per_chunk {
-                let start = WEIGHTS_OFFSET + i * weight_size;
-                let end = start + weight_size;
-                weights.push(Self::decode_weight_le(&chunk_bytes[start..end])?);
+            let weights = Self::decode_chunk_weights_le(
+                &chunk_bytes[WEIGHTS_OFFSET..max_weight_offset],
+                weight_size,
+            )?;
+            if weights.len() != bitpacking::BitPacker4x::BLOCK_LEN {
+                return Err(Self::invalid_data("invalid sparse chunk weight count"));
             }
-            let weights: [W; bitpacking::BitPacker4x::BLOCK_LEN] = weights
-                .try_into()
-                .map_err(|_| Self::invalid_data("invalid sparse chunk weight count"))?;
-            chunks.push(CompressedPostingChunk::from_parts(initial, offset, weights));
+            let weights = W::ChunkWeights::from_weights(&weights);
+            let max_weight = Self::decode_weight_le(
+                &chunk_bytes[max_weight_offset..max_weight_offset + weight_size],
+            )?;
+            chunks.push(CompressedPostingChunk::from_parts(
+                initial, offset, weights, max_weight,
+            ));
         }
         Ok(chunks)
     }
@@ -616,23 +951,68 @@ impl<W: Weight> InvertedIndexCompressedMmap<W> {
                 std::any::type_name::<W>()
             ))
         })?;
+        let chunk_weights_size = Self::chunk_weights_size()
+            .ok_or_else(|| Self::invalid_data("sparse chunk weight size overflow"))?;
         let chunk_size = size_of::<CompressedPostingChunk<W>>();
         const WEIGHTS_OFFSET: usize = size_of::<u32>() * 2;
+        // `max_weight` directly follows `weights`
+        let max_weight_offset = WEIGHTS_OFFSET + chunk_weights_size;
 
         for chunk in chunks {
             let mut bytes = vec![0u8; chunk_size];
             bytes[0..4].copy_from_slice(&chunk.initial().to_le_bytes());
             bytes[4..8].copy_from_slice(&chunk.offset().to_le_bytes());
-            for (i, &weight) in chunk.weights().iter().enumerate() {
-                let start = WEIGHTS_OFFSET + i * weight_size;
-                let end = start + weight_size;
-                Self::encode_weight_le(weight, &mut bytes[start..end])?;
-            }
+            Self::encode_chunk_weights_le(
+                chunk.weights(),
+                &mut bytes[WEIGHTS_OFFSET..max_weight_offset],
+                weight_size,
+            )?;
+            Self::encode_weight_le(
+                *chunk.max_weight(),
+                &mut bytes[max_weight_offset..max_weight_offset + weight_size],
+            )?;
             writer.write_all(&bytes)?;
         }
         Ok(())
     }
 
+    /// Decode weights of a full chunk, packed 4-bit weights are stored lower bits first
+    fn decode_chunk_weights_le(bytes: &[u8], weight_size: usize) -> std::io::Result<Vec<W>> {
+        if TypeId::of::<W>() == TypeId::of::<QuantizedU4>() {
+            return bytes
+                .iter()
+                .flat_map(|byte| [byte & 0x0F, byte >> 4])
+                .map(|raw| Self::decode_weight_le(&[raw]))
+                .collect();
+        }
+
+        bytes
+            .chunks_exact(weight_size)
+            .map(Self::decode_weight_le)
+            .collect()
+    }
+
+    fn encode_chunk_weights_le(
+        weights: &W::ChunkWeights,
+        out: &mut [u8],
+        weight_size: usize,
+    ) -> std::io::Result<()> {
+        if TypeId::of::<W>() == TypeId::of::<QuantizedU4>() {
+            for (i, byte) in out.iter_mut().enumerate() {
+                let mut pair = [0u8; 2];
+                Self::encode_weight_le(weights.get(i * 2), &mut pair[0..1])?;
+                Self::encode_weight_le(weights.get(i * 2 + 1), &mut pair[1..2])?;
+                *byte = pair[0] | (pair[1] << 4);
+            }
+            return Ok(());
+        }
+
+        for (i, weight_bytes) in out.chunks_exact_mut(weight_size).enumerate() {
+            Self::encode_weight_le(weights.get(i), weight_bytes)?;
+        }
+        Ok(())
+    }
+
     fn decode_remainders_le(bytes: &[u8]) -> std::io::Result<Vec<GenericPostingElement<W>>> {
         let remainder_size = size_of::<GenericPostingElement<W>>();
         if remainder_size == 0 || !bytes.len().is_multiple_of(remainder_size) {
@@ -689,10 +1069,11 @@ impl<W: Weight> InvertedIndexCompressedMmap<W> {
         Ok(())
     }
 
-    fn decode_postings_le(
+    /// Decode posting headers and validate boundaries of posting lists within the file
+    fn decode_posting_ranges_le(
         data: &[u8],
         posting_count: usize,
-    ) -> std::io::Result<Vec<CompressedPostingList<W>>> {
+    ) -> std::io::Result<Vec<(PostingListFileHeaderDecoded<W>, PostingListRanges)>> {
         let header_bytes = posting_count
             .checked_mul(Self::HEADER_SIZE)
             .ok_or_else(|| Self::invalid_data("sparse header size overflow"))?;
@@ -709,139 +1090,250 @@ impl<W: Weight> InvertedIndexCompressedMmap<W> {
             headers.push(Self::decode_posting_header_le(&data[start..end])?);
         }
 
-        let chunk_size = size_of::<CompressedPostingChunk<W>>();
-        let mut postings = Vec::with_capacity(posting_count);
+        let mut posting_ranges = Vec::with_capacity(posting_count);
         for (i, header) in headers.iter().enumerate() {
-            let ids_start = usize::try_from(header.ids_start).map_err(|_| {
-                Self::invalid_data("ids_start does not fit target architecture address space")
-            })?;
-            let ids_len = header.ids_len as usize;
-            let chunks_count = header.chunks_count as usize;
-            let ids_end = ids_start
-                .checked_add(ids_len)
-                .ok_or_else(|| Self::invalid_data("sparse id_data size overflow"))?;
-            let chunks_end = ids_end
-                .checked_add(
-                    chunk_size
-                        .checked_mul(chunks_count)
-                        .ok_or_else(|| Self::invalid_data("sparse chunks size overflow"))?,
-                )
-                .ok_or_else(|| Self::invalid_data("sparse chunks end overflow"))?;
-            let remainders_end = if i + 1 < headers.len() {
-                usize::try_from(headers[i + 1].ids_start).map_err(|_| {
-                    Self::invalid_data(
-                        "next ids_start does not fit target architecture address space",
-                    )
-                })?
-            } else {
-                data.len()
-            };
+            let next_ids_start = headers.get(i + 1).map(|next| next.ids_start);
+            let ranges = Self::posting_ranges_le(header, next_ids_start, data.len())?;
+            posting_ranges.push((*header, ranges));
+        }
 
-            if !(ids_start <= ids_end
-                && ids_end <= chunks_end
-                && chunks_end <= remainders_end
-                && remainders_end <= data.len())
-            {
-                return Err(Self::invalid_data(
-                    "invalid sparse posting boundaries in mmap file",
-                ));
-            }
+        Ok(posting_ranges)
+    }
 
-            let id_data = data[ids_start..ids_end].to_vec();
-            let chunks = Self::decode_chunks_le(&data[ids_end..chunks_end], chunks_count)?;
-            let remainders = Self::decode_remainders_le(&data[chunks_end..remainders_end])?;
+    /// Validate boundaries of a posting list, which ends where the next one starts
+    fn posting_ranges_le(
+        header: &PostingListFileHeaderDecoded<W>,
+        next_ids_start: Option<u64>,
+        data_len: usize,
+    ) -> std::io::Result<PostingListRanges> {
+        let chunk_size = size_of::<CompressedPostingChunk<W>>();
+        let ids_start = usize::try_from(header.ids_start).map_err(|_| {
+            Self::invalid_data("ids_start does not fit target architecture address space")
+        })?;
+        let ids_len = header.ids_len as usize;
+        let chunks_count = header.chunks_count as usize;
+        let ids_end = ids_start
+            .checked_add(ids_len)
+            .ok_or_else(|| Self::invalid_data("sparse id_data size overflow"))?;
+        let chunks_end = ids_end
+            .checked_add(
+                chunk_size
+                    .checked_mul(chunks_count)
+                    .ok_or_else(|| Self::invalid_data("sparse chunks size overflow"))?,
+            )
+            .ok_or_else(|| Self::invalid_data("sparse chunks end overflow"))?;
+        let remainders_end = match next_ids_start {
+            Some(next_ids_start) => usize::try_from(next_ids_start).map_err(|_| {
+                Self::invalid_data("next ids_start does not fit target architecture address space")
+            })?,
+            None => data_len,
+        };
 
-            postings.push(CompressedPostingList::from_parts(
-                id_data,
-                chunks,
-                remainders,
-                header.last_id.checked_sub(1),
-                header.quantization_params,
+        if !(ids_start <= ids_end
+            && ids_end <= chunks_end
+            && chunks_end <= remainders_end
+            && remainders_end <= data_len)
+        {
+            return Err(Self::invalid_data(
+                "invalid sparse posting boundaries in mmap file",
             ));
         }
 
-        Ok(postings)
+        Ok(PostingListRanges {
+            ids: ids_start..ids_end,
+            chunks: ids_end..chunks_end,
+            remainders: chunks_end..remainders_end,
+        })
+    }
+
+    fn decode_posting_le(
+        data: &[u8],
+        header: &PostingListFileHeaderDecoded<W>,
+        ranges: PostingListRanges,
+    ) -> std::io::Result<CompressedPostingList<W>> {
+        let id_data = data[ranges.ids].to_vec();
+        let chunks = Self::decode_chunks_le(&data[ranges.chunks], header.chunks_count as usize)?;
+        let remainders = Self::decode_remainders_le(&data[ranges.remainders])?;
+
+        Ok(CompressedPostingList::from_parts(
+            id_data,
+            chunks,
+            remainders,
+            header.last_id.checked_sub(1),
+            header.quantization_params,
+        ))
+    }
+
+    /// Decode a single posting list, reading only its own and the next header
+    fn decode_posting_at_le(
+        data: &[u8],
+        posting_count: usize,
+        id: usize,
+    ) -> std::io::Result<CompressedPostingList<W>> {
+        let header_at = |id: usize| {
+            let start = id * Self::HEADER_SIZE;
+            let bytes = data
+                .get(start..start + Self::HEADER_SIZE)
+                .ok_or_else(|| Self::invalid_data("sparse header region exceeds file length"))?;
+            Self::decode_posting_header_le(bytes)
+        };
+
+        let header = header_at(id)?;
+        let next_ids_start = if id + 1 < posting_count {
+            Some(header_at(id + 1)?.ids_start)
+        } else {
+            None
+        };
+        let ranges = Self::posting_ranges_le(&header, next_ids_start, data.len())?;
+        Self::decode_posting_le(data, &header, ranges)
+    }
+
+    #[cfg(test)]
+    fn decode_postings_le(
+        data: &[u8],
+        posting_count: usize,
+    ) -> std::io::Result<Vec<CompressedPostingList<W>>> {
+        Self::decode_posting_ranges_le(data, posting_count)?
+            .into_iter()
+            .map(|(header, ranges)| Self::decode_posting_le(data, &header, ranges))
+            .collect()
+    }
+
+    /// SHA-256 of the index file contents
+    fn checksum(data: &[u8]) -> String {
+        format!("{:x}", Sha256::digest(data))
+    }
+
+    /// Check integrity of the index file without decoding weights.
+    ///
+    /// Validates boundaries of all posting lists, chunk offsets and sizes of remainders, and
+    /// compares the checksum of the file, if it was recorded on save.
+    pub fn verify(&self) -> std::io::Result<()> {
+        let data: &[u8] = self.mmap.as_ref();
+        let posting_ranges = Self::decode_posting_ranges_le(data, self.file_header.posting_count)?;
+
+        let chunk_size = size_of::<CompressedPostingChunk<W>>();
+        let remainder_size = size_of::<GenericPostingElement<W>>();
+        for (id, (header, ranges)) in posting_ranges.into_iter().enumerate() {
+            if !ranges.remainders.len().is_multiple_of(remainder_size) {
+                return Err(Self::invalid_data(format!(
+                    "invalid size of sparse remainders in posting list {id}",
+                )));
+            }
+
+            // Chunks point into id data in increasing order, chunks of consecutive ids are empty
+            let mut prev_offset = 0;
+            for chunk_bytes in data[ranges.chunks].chunks_exact(chunk_size) {
+                let offset =
+                    u32::from_le_bytes(chunk_bytes[4..8].try_into().expect("slice size checked"));
+                if offset < prev_offset || offset > header.ids_len {
+                    return Err(Self::invalid_data(format!(
+                        "invalid sparse chunk offset {offset} in posting list {id}",
+                    )));
+                }
+                prev_offset = offset;
+            }
+        }
+
+        if let Some(expected_checksum) = &self.file_header.checksum {
+            let checksum = Self::checksum(data);
+            if &checksum != expected_checksum {
+                return Err(Self::invalid_data(format!(
+                    "sparse index checksum mismatch, expected {expected_checksum}, found {checksum}",
+                )));
+            }
+        }
+
+        Ok(())
     }
 
     pub fn convert_and_save<P: AsRef<Path>>(
         index: &InvertedIndexCompressedImmutableRam<W>,
         path: P,
     ) -> std::io::Result<Self> {
-        let total_posting_headers_size =
-            index.postings.as_slice().len() * size_of::<PostingListFileHeader<W>>();
+        Self::convert_and_save_streaming(
+            || index.postings.iter(),
+            index.vector_count,
+            index.max_posting_length,
+            path,
+        )
+    }
 
+    /// Save posting lists into a new index without holding all of them in memory.
+    ///
+    /// `postings` is called twice: posting headers are written in the first pass, and contents
+    /// of posting lists in the second one. Both passes must yield the same posting lists.
+    pub fn convert_and_save_streaming<P, I>(
+        postings: impl Fn() -> I,
+        vector_count: usize,
+        max_posting_length: Option<usize>,
+        path: P,
+    ) -> std::io::Result<Self>
+    where
+        P: AsRef<Path>,
+        I: IntoIterator,
+        I::Item: Borrow<CompressedPostingList<W>>,
+    {
         // Ignore HW on load
         let hw_counter = HardwareCounterCell::disposable();
 
-        let file_length = total_posting_headers_size
-            + index
-                .postings
-                .as_slice()
-                .iter()
-                .map(|p| p.view(&hw_counter).store_size().total)
-                .sum::<usize>();
+        // First pass: collect posting headers, offsets are relative to the end of headers
+        let mut posting_headers = Vec::new();
+        let mut postings_size: usize = 0;
+        for posting in postings() {
+            let posting_view = posting.borrow().view(&hw_counter);
+            let store_size = posting_view.store_size();
+            posting_headers.push(PostingListFileHeaderDecoded::<W> {
+                ids_start: postings_size as u64,
+                ids_len: store_size.id_data_bytes as u32,
+                chunks_count: store_size.chunks_count as u32,
+                last_id: posting_view.last_id().map_or(0, |id| id + 1),
+                quantization_params: posting_view.multiplier(),
+            });
+            postings_size += store_size.total;
+        }
+
+        let posting_count = posting_headers.len();
+        let total_posting_headers_size = posting_count * Self::HEADER_SIZE;
         let file_path = Self::index_file_path(path.as_ref());
-        let file = create_and_ensure_length(file_path.as_ref(), file_length)?;
+        let file = create_and_ensure_length(
+            file_path.as_ref(),
+            total_posting_headers_size + postings_size,
+        )?;
 
         let mut buf = BufWriter::new(file);
 
-        if cfg!(target_endian = "big") {
-            // Save posting headers in little-endian while preserving existing repr(C) layout size.
-            let mut offset: usize = total_posting_headers_size;
-            for posting in index.postings.as_slice() {
-                let posting_view = posting.view(&hw_counter);
-                let store_size = posting_view.store_size();
-                let posting_header = PostingListFileHeaderDecoded::<W> {
-                    ids_start: offset as u64,
-                    ids_len: store_size.id_data_bytes as u32,
-                    chunks_count: store_size.chunks_count as u32,
-                    last_id: posting_view.last_id().map_or(0, |id| id + 1),
-                    quantization_params: posting_view.multiplier(),
-                };
-                let mut posting_header_bytes = vec![0u8; Self::HEADER_SIZE];
-                Self::encode_posting_header_le(&posting_header, &mut posting_header_bytes)?;
-                buf.write_all(&posting_header_bytes)?;
-                offset += store_size.total;
-            }
+        // Save posting headers
+        let mut posting_header_bytes = vec![0u8; Self::HEADER_SIZE];
+        for posting_header in &posting_headers {
+            let ids_start = total_posting_headers_size as u64 + posting_header.ids_start;
+            // Little-endian with zeroed padding, in the existing repr(C) layout
+            let posting_header = PostingListFileHeaderDecoded {
+                ids_start,
+                ..*posting_header
+            };
+            Self::encode_posting_header_le(&posting_header, &mut posting_header_bytes)?;
+            buf.write_all(&posting_header_bytes)?;
+        }
 
-            // Save posting payloads in little-endian while preserving existing struct layout.
-            for posting in index.postings.as_slice() {
-                let posting_view = posting.view(&hw_counter);
-                let (id_data, chunks, remainders) = posting_view.parts();
-                buf.write_all(id_data)?;
-                Self::write_chunks_le(&mut buf, chunks)?;
-                Self::write_remainders_le(&mut buf, remainders)?;
-            }
-        } else {
-            // Save posting headers
-            let mut offset: usize = total_posting_headers_size;
-            for posting in index.postings.as_slice() {
-                let store_size = posting.view(&hw_counter).store_size();
-                let posting_header = PostingListFileHeader::<W> {
-                    ids_start: offset as u64,
-                    ids_len: store_size.id_data_bytes as u32,
-                    chunks_count: store_size.chunks_count as u32,
-                    last_id: posting.view(&hw_counter).last_id().map_or(0, |id| id + 1),
-                    quantization_params: posting.view(&hw_counter).multiplier(),
-                };
-                // TODO Safety
-                #[expect(deprecated, reason = "legacy code")]
-                buf.write_all(unsafe { transmute_to_u8(&posting_header) })?;
-                offset += store_size.total;
-            }
+        // Second pass: save posting elements
+        let mut written_posting_count = 0;
+        let mut written_postings_size = 0;
+        for posting in postings() {
+            let posting_view = posting.borrow().view(&hw_counter);
+            let (id_data, chunks, remainders) = posting_view.parts();
+            buf.write_all(id_data)?;
+            // Little-endian with zeroed padding, in the existing struct layout
+            Self::write_chunks_le(&mut buf, chunks)?;
+            Self::write_remainders_le(&mut buf, remainders)?;
+            written_posting_count += 1;
+            written_postings_size += posting_view.store_size().total;
+        }
 
-            // Save posting elements
-            for posting in index.postings.as_slice() {
-                let posting_view = posting.view(&hw_counter);
-                let (id_data, chunks, remainders) = posting_view.parts();
-                buf.write_all(id_data)?;
-                // TODO Safety
-                #[expect(deprecated, reason = "legacy code")]
-                buf.write_all(unsafe { transmute_to_u8_slice(chunks) })?;
-                // TODO Safety
-                #[expect(deprecated, reason = "legacy code")]
-                buf.write_all(unsafe { transmute_to_u8_slice(remainders) })?;
-            }
+        if written_posting_count != posting_count || written_postings_size != postings_size {
+            return Err(Self::invalid_data(
+                "sparse posting lists differ between passes",
+            ));
         }
 
         // Explicitly fsync file contents to ensure durability
@@ -849,27 +1341,35 @@ impl<W: Weight> InvertedIndexCompressedMmap<W> {
         let file = buf.into_inner().unwrap();
         file.sync_all()?;
 
+        let mmap = open_read_mmap(file_path.as_ref(), AdviceSetting::Global, false)?;
+
         // save header properties
         let file_header = InvertedIndexFileHeader {
-            posting_count: index.postings.as_slice().len(),
-            vector_count: index.vector_count,
-            total_sparse_size: Some(index.total_sparse_size),
+            posting_count,
+            vector_count,
+            total_sparse_size: Some(postings_size),
+            weight_type: Some(W::TYPE),
+            checksum: Some(Self::checksum(mmap.as_ref())),
+            max_posting_length,
         };
 
         atomic_save_json(&Self::index_config_file_path(path.as_ref()), &file_header)?;
+        let decoded_postings = if cfg!(target_endian = "big") {
+            Self::decode_posting_ranges_le(mmap.as_ref(), posting_count)?;
+            Some(DecodedPostingsCache::new(
+                posting_count,
+                HOT_POSTINGS_CAPACITY,
+            ))
+        } else {
+            None
+        };
 
         Ok(Self {
             path: path.as_ref().to_owned(),
-            mmap: Arc::new(open_read_mmap(
-                file_path.as_ref(),
-                AdviceSetting::Global,
-                false,
-            )?),
-            decoded_postings: if cfg!(target_endian = "big") {
-                Some(index.postings.as_slice().to_vec())
-            } else {
-                None
-            },
+            mmap: Arc::new(mmap),
+            decoded_postings,
+            calculated_total_sparse_size: OnceLock::new(),
+            delta: DeltaOverlay::new(file_header.checksum.clone()),
             file_header,
             _phantom: PhantomData,
         })
@@ -880,6 +1380,14 @@ impl<W: Weight> InvertedIndexCompressedMmap<W> {
         let config_file_path = Self::index_config_file_path(path.as_ref());
         // if the file header does not exist, the index is malformed
         let file_header: InvertedIndexFileHeader = read_json(&config_file_path)?;
+        if let Some(weight_type) = file_header.weight_type
+            && weight_type != W::TYPE
+        {
+            return Err(Self::invalid_data(format!(
+                "sparse index weight type mismatch, expected {:?}, found {weight_type:?}",
+                W::TYPE,
+            )));
+        }
         // read index data into mmap
         let file_path = Self::index_file_path(path.as_ref());
         let mmap = open_read_mmap(
@@ -888,32 +1396,28 @@ impl<W: Weight> InvertedIndexCompressedMmap<W> {
             false,
         )?;
 
+        // Posting lists are decoded on demand, only their boundaries are validated on load
         let decoded_postings = if cfg!(target_endian = "big") {
-            Some(Self::decode_postings_le(
-                mmap.as_ref(),
+            Self::decode_posting_ranges_le(mmap.as_ref(), file_header.posting_count)?;
+            Some(DecodedPostingsCache::new(
                 file_header.posting_count,
-            )?)
+                HOT_POSTINGS_CAPACITY,
+            ))
         } else {
             None
         };
 
-        let mut index = Self {
+        let delta = DeltaOverlay::load(path.as_ref(), file_header.checksum.clone())?;
+
+        Ok(Self {
             path: path.as_ref().to_owned(),
             mmap: Arc::new(mmap),
             decoded_postings,
+            calculated_total_sparse_size: OnceLock::new(),
+            delta,
             file_header,
             _phantom: PhantomData,
-        };
-
-        let hw_counter = HardwareCounterCell::disposable();
-
-        if index.file_header.total_sparse_size.is_none() {
-            index.file_header.total_sparse_size =
-                Some(index.calculate_total_sparse_size(&hw_counter));
-            atomic_save_json(&config_file_path, &index.file_header)?;
-        }
-
-        Ok(index)
+        })
     }
 
     fn calculate_total_sparse_size(&self, hw_counter: &HardwareCounterCell) -> usize {
@@ -938,13 +1442,57 @@ impl<W: Weight> InvertedIndexCompressedMmap<W> {
     }
 }
 
+/// Verify the index file at `path` against the checksum recorded in its file header, without
+/// loading the index.
+///
+/// `on_read` is called with the number of bytes of each read from the index file. Returns `None`
+/// for files which are not sparse index files, and for indexes without a recorded checksum. Used
+/// to scrub storage files.
+pub fn verify_index_file(
+    path: &Path,
+    mut on_read: impl FnMut(usize),
+) -> std::io::Result<Option<bool>> {
+    let (Some(directory), Some(INDEX_FILE_NAME)) = (
+        path.parent(),
+        path.file_name().and_then(|name| name.to_str()),
+    ) else {
+        return Ok(None);
+    };
+    let config_file_path = directory.join(INDEX_CONFIG_FILE_NAME);
+    if !config_file_path.exists() {
+        return Ok(None);
+    }
+    let file_header: InvertedIndexFileHeader = read_json(&config_file_path)?;
+    let Some(expected_checksum) = file_header.checksum else {
+        return Ok(None);
+    };
+
+    let mut file = fs_err::File::open(path)?;
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0; VERIFY_READ_SIZE];
+    loop {
+        let read = file.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+        on_read(read);
+    }
+    Ok(Some(
+        format!("{:x}", hasher.finalize()) == expected_checksum,
+    ))
+}
+
 #[cfg(test)]
 mod tests {
+    use std::collections::BTreeMap;
+
     use fs_err as fs;
     use tempfile::Builder;
 
     use super::*;
-    use crate::common::types::QuantizedU8;
+    use crate::common::sparse_vector_fixture::random_sparse_vector;
+    use crate::common::types::{QuantizedU4, QuantizedU8};
     use crate::index::inverted_index::inverted_index_ram_builder::InvertedIndexBuilder;
 
     fn compare_indexes<W: Weight>(
@@ -997,6 +1545,7 @@ mod tests {
         check_inverted_index_mmap::<half::f16>();
         check_inverted_index_mmap::<u8>();
         check_inverted_index_mmap::<QuantizedU8>();
+        check_inverted_index_mmap::<QuantizedU4>();
     }
 
     fn check_inverted_index_mmap<W: Weight>() {
@@ -1056,6 +1605,333 @@ mod tests {
         assert!(inverted_index_mmap.get(100, &hw_counter).is_none());
     }
 
+    #[test]
+    fn test_convert_and_save_streaming() {
+        let mut rnd_gen = rand::rng();
+        let mut builder = InvertedIndexBuilder::new();
+        // Enough elements to put some of them into chunks
+        for i in 0..1024 {
+            builder.add(i, random_sparse_vector(&mut rnd_gen, 32).into_remapped());
+        }
+        let inverted_index_ram = builder.build();
+
+        check_convert_and_save_streaming::<f32>(&inverted_index_ram);
+        check_convert_and_save_streaming::<half::f16>(&inverted_index_ram);
+        check_convert_and_save_streaming::<u8>(&inverted_index_ram);
+        check_convert_and_save_streaming::<QuantizedU8>(&inverted_index_ram);
+        check_convert_and_save_streaming::<QuantizedU4>(&inverted_index_ram);
+    }
+
+    fn check_convert_and_save_streaming<W: Weight>(inverted_index_ram: &InvertedIndexRam) {
+        let converted_dir = Builder::new().prefix("test_index_dir1").tempdir().unwrap();
+        let inverted_index_immutable_ram =
+            InvertedIndexCompressedImmutableRam::<W>::from_ram_index(
+                Cow::Borrowed(inverted_index_ram),
+                &converted_dir,
+            )
+            .unwrap();
+        let converted = InvertedIndexCompressedMmap::<W>::convert_and_save(
+            &inverted_index_immutable_ram,
+            &converted_dir,
+        )
+        .unwrap();
+
+        // Compresses posting lists on the fly
+        let streamed_dir = Builder::new().prefix("test_index_dir2").tempdir().unwrap();
+        let streamed = InvertedIndexCompressedMmap::<W>::from_ram_index(
+            Cow::Borrowed(inverted_index_ram),
+            &streamed_dir,
+        )
+        .unwrap();
+
+        assert_eq!(
+            fs::read(InvertedIndexCompressedMmap::<W>::index_file_path(
+                converted_dir.path()
+            ))
+            .unwrap(),
+            fs::read(InvertedIndexCompressedMmap::<W>::index_file_path(
+                streamed_dir.path()
+            ))
+            .unwrap(),
+        );
+        assert_eq!(
+            converted.file_header.posting_count,
+            streamed.file_header.posting_count,
+        );
+        assert_eq!(converted.vector_count(), streamed.vector_count());
+        assert_eq!(
+            converted.total_sparse_vectors_size(),
+            streamed.total_sparse_vectors_size(),
+        );
+        compare_indexes(&inverted_index_immutable_ram, &streamed);
+    }
+
+    #[test]
+    fn test_convert_and_save_streaming_rejects_changed_postings() {
+        let postings: Vec<CompressedPostingList<f32>> = vec![
+            CompressedPostingList::from(vec![(1, 10.0), (2, 20.0)]),
+            CompressedPostingList::from(vec![(1, 10.0)]),
+        ];
+        let tmp_dir_path = Builder::new()
+            .prefix("test_index_dir_changed")
+            .tempdir()
+            .unwrap();
+
+        let pass = std::cell::Cell::new(0);
+        let result = InvertedIndexCompressedMmap::<f32>::convert_and_save_streaming(
+            || {
+                pass.set(pass.get() + 1);
+                // second pass skips the last posting list
+                postings.iter().take(3 - pass.get())
+            },
+            2,
+            None,
+            &tmp_dir_path,
+        );
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_from_ram_index_keeps_posting_order() {
+        let hw_counter = HardwareCounterCell::new();
+
+        // More dimensions than in a single compression batch
+        let inverted_index_ram = InvertedIndexBuilder::build_from_iterator((0..6000).map(|id| {
+            let vector = [(id % 7, 1.0), (7 + id % 3000, id as f32)].into();
+            (id, vector)
+        }));
+
+        let tmp_dir_path = Builder::new().prefix("test_index_dir").tempdir().unwrap();
+        let inverted_index_mmap = InvertedIndexCompressedMmap::<f32>::from_ram_index(
+            Cow::Borrowed(&inverted_index_ram),
+            &tmp_dir_path,
+        )
+        .unwrap();
+
+        assert_eq!(
+            inverted_index_mmap.file_header.posting_count,
+            inverted_index_ram.postings.len(),
+        );
+        for (id, posting_list) in inverted_index_ram.postings.iter().enumerate() {
+            let expected =
+                InvertedIndexCompressedImmutableRam::<f32>::compress_posting_list(posting_list);
+            assert_eq!(
+                inverted_index_mmap
+                    .get(id as DimId, &hw_counter)
+                    .unwrap()
+                    .parts(),
+                expected.view(&hw_counter).parts(),
+            );
+        }
+    }
+
+    #[test]
+    fn test_decoded_postings_cache() {
+        let inverted_index_ram = InvertedIndexBuilder::build_from_iterator((0..1000).map(|id| {
+            let vector = [(id % 5, 1.0 + id as f32), (5 + id % 200, 2.0)].into();
+            (id, vector)
+        }));
+
+        let tmp_dir_path = Builder::new().prefix("test_index_dir").tempdir().unwrap();
+        let mut inverted_index_mmap = InvertedIndexCompressedMmap::<f32>::from_ram_index(
+            Cow::Borrowed(&inverted_index_ram),
+            &tmp_dir_path,
+        )
+        .unwrap();
+        let inverted_index_immutable_ram =
+            InvertedIndexCompressedImmutableRam::<f32>::from_ram_index(
+                Cow::Borrowed(&inverted_index_ram),
+                &tmp_dir_path,
+            )
+            .unwrap();
+
+        // Decode posting lists on lookup, as on big-endian targets
+        inverted_index_mmap.decoded_postings = Some(DecodedPostingsCache::new(
+            inverted_index_mmap.file_header.posting_count,
+            2,
+        ));
+
+        for _ in 0..HOT_POSTING_MIN_LOOKUPS {
+            compare_indexes(&inverted_index_immutable_ram, &inverted_index_mmap);
+        }
+
+        // Only the first hot posting lists are pinned
+        let decoded_postings = inverted_index_mmap.decoded_postings.as_ref().unwrap();
+        assert_eq!(decoded_postings.pinned_count.load(Ordering::Relaxed), 2);
+        assert!(decoded_postings.pinned[0].get().is_some());
+        assert!(decoded_postings.pinned[1].get().is_some());
+        assert!(decoded_postings.pinned[2].get().is_none());
+
+        compare_indexes(&inverted_index_immutable_ram, &inverted_index_mmap);
+    }
+
+    #[test]
+    fn test_max_posting_length() {
+        let hw_counter = HardwareCounterCell::new();
+
+        let mut builder = InvertedIndexBuilder::new().with_max_posting_length(Some(3));
+        builder.add(1, [(1, 10.0), (2, 10.0)].into());
+        builder.add(2, [(1, 20.0), (2, 20.0)].into());
+        builder.add(3, [(1, 30.0)].into());
+        builder.add(4, [(1, 1.0)].into());
+        builder.add(5, [(1, 40.0)].into());
+        let inverted_index_ram = builder.build();
+
+        let tmp_dir_path = Builder::new().prefix("test_index_dir").tempdir().unwrap();
+        InvertedIndexCompressedMmap::<f32>::from_ram_index(
+            Cow::Borrowed(&inverted_index_ram),
+            &tmp_dir_path,
+        )
+        .unwrap();
+
+        let inverted_index_mmap = InvertedIndexCompressedMmap::<f32>::load(&tmp_dir_path).unwrap();
+        assert_eq!(inverted_index_mmap.file_header.max_posting_length, Some(3));
+        assert_eq!(inverted_index_mmap.file_header.vector_count, 5);
+
+        // only the lowest weights are dropped, the order of ids is kept
+        let ids: Vec<_> = inverted_index_mmap
+            .get(1, &hw_counter)
+            .unwrap()
+            .iter()
+            .into_std_iter()
+            .map(|elem| elem.record_id)
+            .collect();
+        assert_eq!(ids, vec![2, 3, 5]);
+        assert_eq!(inverted_index_mmap.get(2, &hw_counter).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_load_rejects_other_weight_type() {
+        let mut builder = InvertedIndexBuilder::new();
+        builder.add(1, [(1, 10.0), (2, 20.0)].into());
+        let inverted_index_ram = builder.build();
+        let tmp_dir_path = Builder::new()
+            .prefix("test_index_dir_type")
+            .tempdir()
+            .unwrap();
+
+        let inverted_index_mmap = InvertedIndexCompressedMmap::<half::f16>::from_ram_index(
+            Cow::Borrowed(&inverted_index_ram),
+            &tmp_dir_path,
+        )
+        .unwrap();
+        assert_eq!(
+            inverted_index_mmap.file_header.weight_type,
+            Some(WeightType::Float16),
+        );
+        drop(inverted_index_mmap);
+
+        assert!(InvertedIndexCompressedMmap::<half::f16>::load(&tmp_dir_path).is_ok());
+        assert!(InvertedIndexCompressedMmap::<f32>::load(&tmp_dir_path).is_err());
+        assert!(InvertedIndexCompressedImmutableRam::<f32>::open(tmp_dir_path.path()).is_err());
+    }
+
+    #[test]
+    fn test_verify() {
+        let mut rnd_gen = rand::rng();
+        let mut builder = InvertedIndexBuilder::new();
+        // Enough elements to put some of them into chunks
+        for i in 0..1024 {
+            builder.add(i, random_sparse_vector(&mut rnd_gen, 8).into_remapped());
+        }
+        let inverted_index_ram = builder.build();
+        let tmp_dir_path = Builder::new()
+            .prefix("test_index_dir_verify")
+            .tempdir()
+            .unwrap();
+        let index_file_path =
+            InvertedIndexCompressedMmap::<f32>::index_file_path(tmp_dir_path.path());
+
+        let inverted_index_mmap = InvertedIndexCompressedMmap::<f32>::from_ram_index(
+            Cow::Borrowed(&inverted_index_ram),
+            &tmp_dir_path,
+        )
+        .unwrap();
+        assert!(inverted_index_mmap.file_header.checksum.is_some());
+        inverted_index_mmap.verify().unwrap();
+        drop(inverted_index_mmap);
+        InvertedIndexCompressedMmap::<f32>::load(&tmp_dir_path)
+            .unwrap()
+            .verify()
+            .unwrap();
+        let verify_file = || verify_index_file(&index_file_path, |_| {}).unwrap();
+        assert_eq!(verify_file(), Some(true));
+
+        // flip a byte of the last remainder
+        let original_bytes = fs::read(&index_file_path).unwrap();
+        let mut bytes = original_bytes.clone();
+        *bytes.last_mut().unwrap() ^= 0xFF;
+        fs::write(&index_file_path, &bytes).unwrap();
+        let err = InvertedIndexCompressedMmap::<f32>::load(&tmp_dir_path)
+            .unwrap()
+            .verify()
+            .unwrap_err();
+        assert!(err.to_string().contains("checksum"), "{err}");
+        assert_eq!(verify_file(), Some(false));
+
+        // break boundaries of the first posting list
+        let mut bytes = original_bytes.clone();
+        let len = bytes.len() as u64;
+        bytes[0..8].copy_from_slice(&(len + 1024).to_le_bytes());
+        fs::write(&index_file_path, &bytes).unwrap();
+        // big-endian targets decode postings on load, so the same check fails there already
+        let err = InvertedIndexCompressedMmap::<f32>::load(&tmp_dir_path)
+            .and_then(|index| index.verify())
+            .unwrap_err();
+        assert!(err.to_string().contains("boundaries"), "{err}");
+
+        // indexes created by earlier versions don't have the checksum
+        fs::write(&index_file_path, &original_bytes).unwrap();
+        let config_path =
+            InvertedIndexCompressedMmap::<f32>::index_config_file_path(tmp_dir_path.path());
+        let mut file_header: InvertedIndexFileHeader = read_json(&config_path).unwrap();
+        file_header.checksum = None;
+        atomic_save_json(&config_path, &file_header).unwrap();
+        InvertedIndexCompressedMmap::<f32>::load(&tmp_dir_path)
+            .unwrap()
+            .verify()
+            .unwrap();
+        assert_eq!(verify_file(), None);
+    }
+
+    #[test]
+    fn test_total_sparse_size_missing_in_header() {
+        let mut rnd_gen = rand::rng();
+        let inverted_index_ram = InvertedIndexBuilder::build_from_iterator(
+            (0..256).map(|id| (id, random_sparse_vector(&mut rnd_gen, 16).into_remapped())),
+        );
+        let tmp_dir_path = Builder::new()
+            .prefix("test_index_dir_size")
+            .tempdir()
+            .unwrap();
+        let expected_size = InvertedIndexCompressedMmap::<f32>::from_ram_index(
+            Cow::Borrowed(&inverted_index_ram),
+            &tmp_dir_path,
+        )
+        .unwrap()
+        .total_sparse_vectors_size();
+
+        // indexes created by earlier versions don't store the size
+        let config_path =
+            InvertedIndexCompressedMmap::<f32>::index_config_file_path(tmp_dir_path.path());
+        let mut file_header: InvertedIndexFileHeader = read_json(&config_path).unwrap();
+        file_header.total_sparse_size = None;
+        atomic_save_json(&config_path, &file_header).unwrap();
+
+        let immutable_ram =
+            InvertedIndexCompressedImmutableRam::<f32>::open(tmp_dir_path.path()).unwrap();
+        assert_eq!(immutable_ram.total_sparse_vectors_size(), expected_size);
+
+        // calculated on first use, not on load
+        let index = InvertedIndexCompressedMmap::<f32>::load(&tmp_dir_path).unwrap();
+        assert!(index.calculated_total_sparse_size.get().is_none());
+        assert_eq!(index.total_sparse_vectors_size(), expected_size);
+
+        index.save(tmp_dir_path.path()).unwrap();
+        let file_header: InvertedIndexFileHeader = read_json(&config_path).unwrap();
+        assert_eq!(file_header.total_sparse_size, Some(expected_size));
+    }
+
     #[test]
     fn test_decode_postings_le_rejects_truncated_header() {
         let mut builder = InvertedIndexBuilder::new();
@@ -1120,4 +1996,185 @@ mod tests {
             InvertedIndexCompressedMmap::<f32>::decode_postings_le(&bytes, posting_count).is_err()
         );
     }
+
+    #[test]
+    fn test_compact_drops_deleted_records() {
+        let mut rnd_gen = rand::rng();
+        let vectors = (0..1024)
+            .map(|id| (id, random_sparse_vector(&mut rnd_gen, 16).into_remapped()))
+            .collect::<Vec<_>>();
+        let is_deleted = |id: PointOffsetType| id.is_multiple_of(3);
+
+        let full_index_dir = Builder::new().prefix("test_index_full").tempdir().unwrap();
+        let inverted_index_mmap = InvertedIndexCompressedMmap::<f32>::from_ram_index(
+            Cow::Owned(InvertedIndexBuilder::build_from_iterator(
+                vectors.iter().cloned(),
+            )),
+            &full_index_dir,
+        )
+        .unwrap();
+
+        let compacted_dir = Builder::new()
+            .prefix("test_index_compacted")
+            .tempdir()
+            .unwrap();
+        let compacted = inverted_index_mmap
+            .compact(compacted_dir.path(), is_deleted)
+            .unwrap()
+            .unwrap();
+        drop(compacted);
+        let compacted = InvertedIndexCompressedMmap::<f32>::load(&compacted_dir).unwrap();
+
+        let expected_dir = Builder::new()
+            .prefix("test_index_expected")
+            .tempdir()
+            .unwrap();
+        let expected = InvertedIndexCompressedImmutableRam::<f32>::from_ram_index(
+            Cow::Owned(InvertedIndexBuilder::build_from_iterator(
+                vectors.into_iter().filter(|(id, _)| !is_deleted(*id)),
+            )),
+            &expected_dir,
+        )
+        .unwrap();
+
+        assert_eq!(compacted.vector_count(), expected.vector_count());
+        assert_eq!(
+            compacted.total_sparse_vectors_size(),
+            expected.total_sparse_vectors_size(),
+        );
+        assert!(
+            compacted.total_sparse_vectors_size() < inverted_index_mmap.total_sparse_vectors_size()
+        );
+        compare_indexes(&expected, &compacted);
+    }
+
+    fn collect_postings(
+        inverted_index: &impl InvertedIndex,
+        posting_count: usize,
+    ) -> Vec<Vec<(PointOffsetType, f32)>> {
+        let hw_counter = HardwareCounterCell::new();
+        (0..posting_count as DimId)
+            .map(|dim_id| {
+                InvertedIndex::get(inverted_index, dim_id, &hw_counter)
+                    .into_iter()
+                    .flat_map(|posting_list| posting_list.into_std_iter())
+                    .map(|elem| (elem.record_id, elem.weight))
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_updates_through_delta_overlay() {
+        let mut rnd_gen = rand::rng();
+        let mut vectors = (0..256)
+            .map(|id| (id, random_sparse_vector(&mut rnd_gen, 16).into_remapped()))
+            .collect::<BTreeMap<PointOffsetType, _>>();
+
+        let base_dir = Builder::new().prefix("test_index_base").tempdir().unwrap();
+        let mut inverted_index_ram =
+            InvertedIndexBuilder::build_from_iterator(vectors.clone().into_iter());
+        let mut inverted_index_mmap = InvertedIndexCompressedMmap::<f32>::from_ram_index(
+            Cow::Borrowed(&inverted_index_ram),
+            &base_dir,
+        )
+        .unwrap();
+        assert!(!inverted_index_mmap.has_updates());
+        assert!(InvertedIndex::flusher(&inverted_index_mmap).is_none());
+
+        // Update existing records, remove some of them, and add new ones with more dimensions
+        for id in 0..320 {
+            let old_vector = vectors.get(&id).cloned();
+            if (64..96).contains(&id) {
+                let old_vector = old_vector.unwrap();
+                inverted_index_ram.remove(id, old_vector.clone());
+                inverted_index_mmap.remove(id, old_vector);
+                vectors.remove(&id);
+            } else if !(64..256).contains(&id) {
+                let vector = random_sparse_vector(&mut rnd_gen, 24).into_remapped();
+                inverted_index_ram.upsert(id, vector.clone(), old_vector.clone());
+                inverted_index_mmap.upsert(id, vector.clone(), old_vector);
+                vectors.insert(id, vector);
+            }
+        }
+        assert!(inverted_index_mmap.has_updates());
+        assert_eq!(inverted_index_mmap.outdated_vector_count(), 96);
+
+        let posting_count = inverted_index_ram.postings.len();
+        let expected_postings = collect_postings(&inverted_index_ram, posting_count);
+        assert_eq!(InvertedIndex::len(&inverted_index_mmap), posting_count);
+        assert_eq!(
+            inverted_index_mmap.vector_count(),
+            inverted_index_ram.vector_count(),
+        );
+        assert_eq!(
+            collect_postings(&inverted_index_mmap, posting_count),
+            expected_postings,
+        );
+        let hw_counter = HardwareCounterCell::new();
+        for (dim_id, expected) in expected_postings.iter().enumerate() {
+            assert_eq!(
+                inverted_index_mmap
+                    .posting_list_len(&(dim_id as DimId), &hw_counter)
+                    .unwrap_or(0),
+                expected.len(),
+            );
+        }
+
+        // Updates are persisted into the delta file and loaded with the index
+        let flusher = InvertedIndex::flusher(&inverted_index_mmap).unwrap();
+        flusher().unwrap();
+        assert!(InvertedIndex::flusher(&inverted_index_mmap).is_none());
+        assert!(
+            InvertedIndexCompressedMmap::<f32>::files(base_dir.path())
+                .contains(&DeltaOverlay::file_path(base_dir.path()))
+        );
+        let reloaded = InvertedIndexCompressedMmap::<f32>::load(&base_dir).unwrap();
+        assert_eq!(reloaded.vector_count(), inverted_index_mmap.vector_count());
+        assert_eq!(reloaded.outdated_vector_count(), 96);
+        assert_eq!(
+            collect_postings(&reloaded, posting_count),
+            expected_postings
+        );
+
+        // Compaction writes the updates into the files
+        let compacted_dir = Builder::new()
+            .prefix("test_index_compacted")
+            .tempdir()
+            .unwrap();
+        inverted_index_mmap
+            .compact(compacted_dir.path(), |_| false)
+            .unwrap()
+            .unwrap();
+        let compacted = InvertedIndexCompressedMmap::<f32>::load(&compacted_dir).unwrap();
+        assert!(!compacted.has_updates());
+        assert_eq!(
+            collect_postings(&compacted, posting_count),
+            expected_postings,
+        );
+
+        let expected_dir = Builder::new()
+            .prefix("test_index_expected")
+            .tempdir()
+            .unwrap();
+        let expected = InvertedIndexCompressedImmutableRam::<f32>::from_ram_index(
+            Cow::Owned(InvertedIndexBuilder::build_from_iterator(
+                vectors.into_iter(),
+            )),
+            &expected_dir,
+        )
+        .unwrap();
+        assert_eq!(compacted.vector_count(), expected.vector_count());
+        compare_indexes(&expected, &compacted);
+
+        // Delta file of the replaced base index is ignored
+        fs::copy(
+            DeltaOverlay::file_path(base_dir.path()),
+            DeltaOverlay::file_path(compacted_dir.path()),
+        )
+        .unwrap();
+        let compacted = InvertedIndexCompressedMmap::<f32>::load(&compacted_dir).unwrap();
+        assert!(!compacted.has_updates());
+        assert_eq!(compacted.vector_count(), expected.vector_count());
+    }
 }
@@ -35,6 +35,7 @@ impl InvertedIndex for InvertedIndexImmutableRam {
             vector_count: mmap_inverted_index.file_header.vector_count,
             // Calculated after reading mmap
             total_sparse_size: 0,
+            max_posting_length: None,
         };
 
         for i in 0..mmap_inverted_index.file_header.posting_count as DimId {
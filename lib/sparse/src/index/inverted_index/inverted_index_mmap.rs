@@ -72,6 +72,10 @@ impl InvertedIndex for InvertedIndexMmap {
         Self::load(path)
     }
 
+    fn advise(&self, advice: Advice) -> std::io::Result<()> {
+        self.mmap.madvise(advice)
+    }
+
     fn save(&self, path: &Path) -> std::io::Result<()> {
         debug_assert_eq!(path, self.path);
 
@@ -30,6 +30,8 @@ pub struct InvertedIndexRam {
     pub vector_count: usize,
     /// Total size of all searchable sparse vectors in bytes
     pub total_sparse_size: usize,
+    /// Length cap posting lists were pruned to on build, if any
+    pub max_posting_length: Option<usize>,
 }
 
 impl InvertedIndex for InvertedIndexRam {
@@ -127,6 +129,7 @@ impl InvertedIndexRam {
             postings: Vec::new(),
             vector_count: 0,
             total_sparse_size: 0,
+            max_posting_length: None,
         }
     }
 
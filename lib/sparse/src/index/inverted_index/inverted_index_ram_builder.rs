@@ -13,6 +13,8 @@ pub struct InvertedIndexBuilder {
     pub posting_builders: Vec<PostingBuilder>,
     pub vector_count: usize,
     pub total_sparse_size: usize,
+    /// Keep only this many elements with the largest weights in each posting list
+    pub max_posting_length: Option<usize>,
 }
 
 impl Default for InvertedIndexBuilder {
@@ -27,9 +29,18 @@ impl InvertedIndexBuilder {
             posting_builders: Vec::new(),
             vector_count: 0,
             total_sparse_size: 0,
+            max_posting_length: None,
         }
     }
 
+    /// Cap the length of built posting lists, lower weights are dropped.
+    ///
+    /// Search over the pruned index is approximate, but its size and latency are bounded.
+    pub fn with_max_posting_length(mut self, max_posting_length: Option<usize>) -> Self {
+        self.max_posting_length = max_posting_length;
+        self
+    }
+
     /// Add a vector to the inverted index builder
     pub fn add(&mut self, id: PointOffsetType, vector: RemappedSparseVector) {
         let sparse_size = vector.len() * size_of::<PostingElementEx>();
@@ -52,6 +63,7 @@ impl InvertedIndexBuilder {
                 postings: vec![],
                 total_sparse_size: self.total_sparse_size,
                 vector_count: self.vector_count,
+                max_posting_length: self.max_posting_length,
             };
         }
 
@@ -62,16 +74,23 @@ impl InvertedIndexBuilder {
         );
 
         let mut postings = Vec::with_capacity(self.posting_builders.len());
-        for posting_builder in self.posting_builders {
+        let mut pruned_count = 0;
+        for mut posting_builder in self.posting_builders {
+            if let Some(max_posting_length) = self.max_posting_length {
+                pruned_count += posting_builder.prune(max_posting_length);
+            }
             postings.push(posting_builder.build());
         }
 
         let vector_count = self.vector_count;
-        let total_sparse_size = self.total_sparse_size;
+        let total_sparse_size = self
+            .total_sparse_size
+            .saturating_sub(pruned_count * size_of::<PostingElementEx>());
         InvertedIndexRam {
             postings,
             vector_count,
             total_sparse_size,
+            max_posting_length: self.max_posting_length,
         }
     }
 
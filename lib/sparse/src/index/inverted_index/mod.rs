@@ -3,14 +3,17 @@ use std::fmt::Debug;
 use std::path::{Path, PathBuf};
 
 use common::counter::hardware_counter::HardwareCounterCell;
+use common::mmap::Advice;
 use common::storage_version::StorageVersion;
 use common::types::PointOffsetType;
 
 use super::posting_list_common::PostingListIter;
 use crate::common::sparse_vector::RemappedSparseVector;
 use crate::common::types::DimOffset;
+use crate::index::inverted_index::delta_overlay::DeltaFlusher;
 use crate::index::inverted_index::inverted_index_ram::InvertedIndexRam;
 
+pub mod delta_overlay;
 pub mod inverted_index_compressed_immutable_ram;
 pub mod inverted_index_compressed_mmap;
 pub mod inverted_index_immutable_ram;
@@ -36,6 +39,13 @@ pub trait InvertedIndex: Sized + Debug + 'static {
     /// Save index
     fn save(&self, path: &Path) -> std::io::Result<()>;
 
+    /// Advise OS how memory mapped index files will be accessed.
+    ///
+    /// No-op for indexes, which are not memory mapped.
+    fn advise(&self, _advice: Advice) -> std::io::Result<()> {
+        Ok(())
+    }
+
     /// Get posting list for dimension id
     fn get<'a>(
         &'a self,
@@ -83,4 +93,31 @@ pub trait InvertedIndex: Sized + Debug + 'static {
 
     /// Get max existed index
     fn max_index(&self) -> Option<DimOffset>;
+
+    /// Whether `upsert` and `remove` are supported by an index loaded from disk
+    fn accepts_updates(&self) -> bool {
+        false
+    }
+
+    /// Number of indexed vectors, which are updated, but whose outdated elements remain in
+    /// posting lists until compaction
+    fn outdated_vector_count(&self) -> usize {
+        0
+    }
+
+    /// Flusher persisting updates, `None` if there are no unsaved updates
+    fn flusher(&self) -> Option<DeltaFlusher> {
+        None
+    }
+
+    /// Write a copy of the index without elements of deleted records into `path`.
+    ///
+    /// Returns `None` if the index type doesn't support compaction.
+    fn compact(
+        &self,
+        _path: &Path,
+        _is_deleted: impl Fn(PointOffsetType) -> bool,
+    ) -> std::io::Result<Option<Self>> {
+        Ok(None)
+    }
 }
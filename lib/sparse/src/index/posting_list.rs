@@ -136,6 +136,26 @@ impl PostingBuilder {
         self.elements.push(PostingElementEx::new(record_id, weight));
     }
 
+    /// Keep only `max_len` elements with the largest weights.
+    ///
+    /// Ties are broken by record id, so the result doesn't depend on insertion order.
+    /// Returns the number of dropped elements.
+    pub fn prune(&mut self, max_len: usize) -> usize {
+        if self.elements.len() <= max_len {
+            return 0;
+        }
+        let dropped = self.elements.len() - max_len;
+        if max_len > 0 {
+            self.elements.select_nth_unstable_by(max_len - 1, |a, b| {
+                b.weight
+                    .total_cmp(&a.weight)
+                    .then(a.record_id.cmp(&b.record_id))
+            });
+        }
+        self.elements.truncate(max_len);
+        dropped
+    }
+
     /// Consume the builder and return the posting list.
     pub fn build(mut self) -> PostingList {
         // Sort by id
@@ -188,6 +208,14 @@ impl PostingListIter for PostingListIterator<'_> {
         size_of::<DimWeight>()
     }
 
+    #[inline]
+    fn max_weight_till_id(&mut self, id: PointOffsetType) -> Option<DimWeight> {
+        self.elements
+            .get(self.current_index)
+            .filter(|element| element.record_id <= id)
+            .map(|element| element.weight.max(element.max_next_weight))
+    }
+
     #[inline]
     fn skip_to(&mut self, record_id: PointOffsetType) -> Option<PostingElementEx> {
         self.skip_to(record_id)
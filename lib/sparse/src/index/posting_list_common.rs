@@ -62,6 +62,12 @@ pub trait PostingListIter {
     /// Size of the weight element
     fn element_size(&self) -> usize;
 
+    /// Upper bound of weights of the remaining elements with ids up to `id` (inclusive).
+    ///
+    /// The bound is not necessarily tight, but it is cheap to compute and never underestimates.
+    /// Returns `None` if it is known that there are no such elements.
+    fn max_weight_till_id(&mut self, id: PointOffsetType) -> Option<DimWeight>;
+
     /// Tries to find the element with ID == id and returns it.
     /// If the element is not found, the iterator is advanced to the next element with ID > id
     /// and None is returned.
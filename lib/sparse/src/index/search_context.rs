@@ -33,6 +33,7 @@ pub struct SearchContext<'a, 'b, T: PostingListIter = PostingListIterator<'a>> {
     max_record_id: PointOffsetType,         // max_record_id ids across all posting lists
     pooled: PooledScoresHandle<'b>,         // handle to pooled scores
     use_pruning: bool,
+    use_block_max_pruning: bool,
     hardware_counter: &'a HardwareCounterCell,
 }
 
@@ -78,6 +79,9 @@ impl<'a, 'b, T: PostingListIter> SearchContext<'a, 'b, T> {
         // The max contribution per posting list that we calculate is not made to compute the max value of two negative numbers.
         // This is a limitation of the current pruning implementation.
         let use_pruning = T::reliable_max_next_weight() && query.values.iter().all(|v| *v >= 0.0);
+        // Block max weights bound the contribution of each posting list within a batch, which
+        // also holds only for non-negative query values.
+        let use_block_max_pruning = query.values.iter().all(|v| *v >= 0.0);
         let min_record_id = Some(min_record_id);
         SearchContext {
             postings_iterators,
@@ -89,6 +93,7 @@ impl<'a, 'b, T: PostingListIter> SearchContext<'a, 'b, T> {
             max_record_id,
             pooled,
             use_pruning,
+            use_block_max_pruning,
             hardware_counter,
         }
     }
@@ -193,6 +198,34 @@ impl<'a, 'b, T: PostingListIter> SearchContext<'a, 'b, T> {
         }
     }
 
+    /// Upper bound of the score of any point in the batch, which ends at `batch_last_id`.
+    ///
+    /// Based on the max weights of the posting list blocks overlapping the batch.
+    fn batch_max_score(&mut self, batch_last_id: PointOffsetType) -> f32 {
+        self.postings_iterators
+            .iter_mut()
+            .filter_map(|posting| {
+                let max_weight = posting
+                    .posting_list_iterator
+                    .max_weight_till_id(batch_last_id)?;
+                // points missing in the posting list get no contribution from it
+                Some((max_weight * posting.query_weight).max(0.0))
+            })
+            .sum()
+    }
+
+    /// Advance posting lists iterators past the batch without scoring it.
+    fn skip_batch(&mut self, batch_last_id: PointOffsetType) {
+        for posting in self.postings_iterators.iter_mut() {
+            match batch_last_id.checked_add(1) {
+                Some(next_id) => {
+                    posting.posting_list_iterator.skip_to(next_id);
+                }
+                None => posting.posting_list_iterator.skip_to_end(),
+            }
+        }
+    }
+
     /// Compute scores for the last posting list quickly
     fn process_last_posting_list<F: Fn(PointOffsetType) -> bool>(&mut self, filter_condition: &F) {
         debug_assert_eq!(self.postings_iterators.len(), 1);
@@ -306,8 +339,16 @@ impl<'a, 'b, T: PostingListIter> SearchContext<'a, 'b, T> {
                 self.max_record_id,
             );
 
-            // advance and score posting lists iterators
-            self.advance_batch(start_batch_id, last_batch_id, filter_condition);
+            // skip batches, in which no point can beat the current min score
+            if self.use_block_max_pruning
+                && self.top_results.len() >= self.top
+                && self.batch_max_score(last_batch_id) <= self.top_results.threshold()
+            {
+                self.skip_batch(last_batch_id);
+            } else {
+                // advance and score posting lists iterators
+                self.advance_batch(start_batch_id, last_batch_id, filter_condition);
+            }
 
             // remove empty posting lists if necessary
             self.postings_iterators.retain(|posting_iterator| {
@@ -9,7 +9,7 @@ mod tests {
     use common::counter::hardware_accumulator::HwMeasurementAcc;
     use common::counter::hardware_counter::HardwareCounterCell;
     use common::types::{PointOffsetType, ScoredPointOffset};
-    use rand::Rng;
+    use rand::{Rng, SeedableRng};
     use tempfile::TempDir;
 
     use crate::common::scores_memory_pool::{PooledScoresHandle, ScoresMemoryPool};
@@ -486,6 +486,61 @@ mod tests {
         );
     }
 
+    #[test]
+    fn block_max_pruning_test<I: InvertedIndex>() {
+        let mut rnd = rand::rngs::StdRng::seed_from_u64(42);
+
+        // Spread ids, so that the search goes through multiple batches
+        let ids = (1..=2_000)
+            .map(|i| i * 37)
+            .collect::<Vec<PointOffsetType>>();
+        let index = TestIndex::<I>::from_ram({
+            let mut builder = InvertedIndexBuilder::new();
+            for &id in &ids {
+                let SparseVector { indices, values } = random_sparse_vector(&mut rnd, 20);
+                builder.add(id, RemappedSparseVector::new(indices, values).unwrap());
+            }
+            builder.build()
+        });
+
+        // Pruning is only applied to non-negative queries
+        let query = RemappedSparseVector {
+            indices: (1..20).collect(),
+            values: (1..20).map(|_| rnd.random_range(0.0..1.0)).collect(),
+        };
+
+        let is_stopped = AtomicBool::new(false);
+        let hardware_counter = HardwareCounterCell::new();
+
+        let mut search_context = SearchContext::new(
+            query.clone(),
+            10,
+            &index.index,
+            get_pooled_scores(),
+            &is_stopped,
+            &hardware_counter,
+        );
+        let plain_scores = search_context.plain_search(&ids);
+
+        let mut search_context = SearchContext::new(
+            query,
+            10,
+            &index.index,
+            get_pooled_scores(),
+            &is_stopped,
+            &hardware_counter,
+        );
+        let pruned_scores = search_context.search(&match_all);
+
+        let scores = |results: Vec<ScoredPointOffset>| {
+            round_scores::<I>(results)
+                .into_iter()
+                .map(|scored| scored.score)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(scores(pruned_scores), scores(plain_scores));
+    }
+
     /// Generates a random inverted index with `num_vectors` vectors
     #[allow(dead_code)]
     fn random_inverted_index<R: Rng + ?Sized>(
@@ -446,6 +446,7 @@ impl From<CollectionConfigInternal> for CreateCollection {
             read_fan_out_delay_ms: _,
             on_disk_payload,
             sparse_vectors,
+            populate_policy: _,
         } = params;
 
         Self {
@@ -0,0 +1,172 @@
+//! Snapshot of several collections at a consistent point.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::pin::pin;
+use std::time::Instant;
+
+use chrono::{DateTime, Utc};
+use collection::common::snapshot_point::SnapshotPoint;
+use collection::operations::snapshot_ops::SnapshotDescription;
+use collection::operations::verification::new_unchecked_verification_pass;
+use futures::future;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use super::archive_storage_snapshot;
+use crate::content_manager::toc::SNAPSHOT_BUNDLE_FILE_NAME;
+use crate::dispatcher::Dispatcher;
+use crate::rbac::{AccessRequirements, Auth, CollectionMultipass};
+use crate::{StorageError, TableOfContent};
+
+/// File in the root of a snapshot bundle, describing the point at which it was taken
+pub const BUNDLE_MANIFEST_FILE: &str = "bundle_manifest.json";
+
+/// Bundles are created one at a time, so that their barriers never wait on each other.
+///
+/// The lock is local to this peer. Bundles created on several peers at once may still wait on each
+/// other, if their collections forward updates between these peers.
+static BUNDLE_LOCK: tokio::sync::Mutex<()> = tokio::sync::Mutex::const_new(());
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Validate, Clone)]
+pub struct CreateSnapshotBundle {
+    /// Collections to snapshot at a consistent point, by name or alias
+    #[validate(length(min = 1))]
+    pub collections: Vec<String>,
+}
+
+/// Manifest of a snapshot bundle.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct SnapshotBundleManifest {
+    /// Time when updates of all collections were quiesced
+    pub consistent_at: DateTime<Utc>,
+    /// Snapshot of each collection, by collection name
+    pub collections: BTreeMap<String, BundledSnapshot>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct BundledSnapshot {
+    pub snapshot: String,
+    pub size: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub checksum: Option<String>,
+}
+
+/// Snapshot the requested collections at a consistent point.
+///
+/// Updates of all requested collections are blocked, and accepted updates are applied, before
+/// any of the snapshots is taken. Updates stay blocked only until all local shards have taken
+/// their snapshot point, archiving and storing the snapshots happens after updates are resumed.
+///
+/// Consistency is local to this peer: only local shards are snapshotted, and replicas on other
+/// peers are not quiesced. Creating bundles of collections which forward updates between peers on
+/// several peers at once may deadlock, as each peer blocks updates the other one waits for.
+///
+/// The bundle is stored along with storage snapshots, and is recovered the same way.
+pub async fn do_create_snapshot_bundle(
+    dispatcher: &Dispatcher,
+    auth: Auth,
+    request: CreateSnapshotBundle,
+) -> Result<SnapshotDescription, StorageError> {
+    let multipass =
+        auth.check_global_access(AccessRequirements::new().manage(), "create_snapshot_bundle")?;
+
+    // All checks should've been done at this point.
+    let pass = new_unchecked_verification_pass();
+    let toc = dispatcher.toc(&auth, &pass).clone();
+
+    let res = tokio::spawn(async move {
+        _do_create_snapshot_bundle(&toc, multipass, request.collections).await
+    })
+    .await??;
+    Ok(res)
+}
+
+async fn _do_create_snapshot_bundle(
+    toc: &TableOfContent,
+    multipass: CollectionMultipass,
+    requested_collections: Vec<String>,
+) -> Result<SnapshotDescription, StorageError> {
+    let _bundle_guard = BUNDLE_LOCK.lock().await;
+
+    // Resolve aliases, collections are quiesced in a stable order
+    let mut collection_names = BTreeSet::new();
+    for name in &requested_collections {
+        let collection = toc.get_collection(&multipass.issue_pass(name)).await?;
+        collection_names.insert(collection.name().to_string());
+    }
+    let collection_passes: Vec<_> = collection_names
+        .iter()
+        .map(|name| multipass.issue_pass(name))
+        .collect();
+
+    let quiesce_start = Instant::now();
+    let mut barriers = Vec::with_capacity(collection_passes.len());
+    for collection_pass in &collection_passes {
+        let collection = toc.get_collection(collection_pass).await?;
+        barriers.push(collection.quiesce_updates().await?);
+    }
+    let consistent_at = Utc::now();
+    log::info!(
+        "Updates of {} collections quiesced for snapshot bundle in {:?}",
+        barriers.len(),
+        quiesce_start.elapsed(),
+    );
+
+    let (snapshot_point, snapshot_point_guard) = SnapshotPoint::new();
+    let snapshots = future::try_join_all(collection_passes.iter().map(|collection_pass| {
+        toc.create_snapshot_at_point(collection_pass, Some(snapshot_point_guard.clone()))
+    }));
+    drop(snapshot_point_guard);
+    let mut snapshots = pin!(snapshots);
+
+    // Drive the snapshots until all of them have taken their snapshot point
+    let finished_snapshots = tokio::select! {
+        () = snapshot_point.wait() => None,
+        snapshot_details = &mut snapshots => Some(snapshot_details),
+    };
+
+    drop(barriers);
+    log::info!(
+        "Updates of snapshot bundle collections resumed after {:?}",
+        quiesce_start.elapsed(),
+    );
+
+    let snapshot_details = match finished_snapshots {
+        Some(snapshot_details) => snapshot_details?,
+        None => snapshots.await?,
+    };
+
+    let created_snapshots: Vec<_> = collection_passes
+        .iter()
+        .map(|collection_pass| collection_pass.name().to_string())
+        .zip(snapshot_details)
+        .collect();
+
+    let manifest = SnapshotBundleManifest {
+        consistent_at,
+        collections: created_snapshots
+            .iter()
+            .map(|(collection_name, snapshot_details)| {
+                let bundled = BundledSnapshot {
+                    snapshot: snapshot_details.name.clone(),
+                    size: snapshot_details.size,
+                    checksum: snapshot_details.checksum.clone(),
+                };
+                (collection_name.clone(), bundled)
+            })
+            .collect(),
+    };
+    let manifest = serde_json::to_vec_pretty(&manifest).map_err(|err| {
+        StorageError::service_error(format!("Failed to serialize bundle manifest: {err}"))
+    })?;
+
+    archive_storage_snapshot(
+        toc,
+        &multipass,
+        SNAPSHOT_BUNDLE_FILE_NAME,
+        &created_snapshots,
+        vec![(BUNDLE_MANIFEST_FILE, manifest)],
+    )
+    .await
+}
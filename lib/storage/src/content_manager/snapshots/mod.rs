@@ -1,3 +1,4 @@
+pub mod bundle;
 pub mod download;
 pub mod download_result;
 pub mod download_tar;
@@ -117,32 +118,51 @@ async fn _do_create_full_snapshot(
     toc: &TableOfContent,
     multipass: CollectionMultipass,
 ) -> Result<SnapshotDescription, StorageError> {
-    let snapshot_dir = toc.snapshots_path();
-
     let all_collections = toc.multipass_into_collections(&multipass).await;
-    let mut created_snapshots: Vec<(&str, SnapshotDescription)> = vec![];
+    let mut created_snapshots: Vec<(String, SnapshotDescription)> = vec![];
     for collection_pass in &all_collections {
         let snapshot_details = toc.create_snapshot(collection_pass).await?;
-        created_snapshots.push((collection_pass.name(), snapshot_details));
+        created_snapshots.push((collection_pass.name().to_string(), snapshot_details));
     }
+
+    archive_storage_snapshot(
+        toc,
+        &multipass,
+        FULL_SNAPSHOT_FILE_NAME,
+        &created_snapshots,
+        vec![],
+    )
+    .await
+}
+
+/// Archive collection snapshots into a storage snapshot, along with the collection mapping and
+/// aliases, so that it can be recovered with `--storage-snapshot`.
+///
+/// `extra_files` are added to the root of the archive, as `(file name, content)`.
+async fn archive_storage_snapshot(
+    toc: &TableOfContent,
+    multipass: &CollectionMultipass,
+    snapshot_name_prefix: &str,
+    created_snapshots: &[(String, SnapshotDescription)],
+    extra_files: Vec<(&'static str, Vec<u8>)>,
+) -> Result<SnapshotDescription, StorageError> {
+    let snapshot_dir = toc.snapshots_path();
+
     let current_time = chrono::Utc::now().format("%Y-%m-%d-%H-%M-%S").to_string();
 
-    let snapshot_name = format!("{FULL_SNAPSHOT_FILE_NAME}-{current_time}.snapshot");
+    let snapshot_name = format!("{snapshot_name_prefix}-{current_time}.snapshot");
 
     let collection_name_to_snapshot_path: HashMap<_, _> = created_snapshots
         .iter()
-        .map(|&(collection_name, ref snapshot_details)| {
-            (collection_name.to_string(), snapshot_details.name.clone())
+        .map(|(collection_name, snapshot_details)| {
+            (collection_name.clone(), snapshot_details.name.clone())
         })
         .collect();
 
     let mut alias_mapping: HashMap<String, String> = Default::default();
-    for collection_pass in &all_collections {
-        for alias in toc
-            .all_collection_aliases(collection_pass.name(), &multipass)
-            .await
-        {
-            alias_mapping.insert(alias, collection_pass.name().to_string());
+    for (collection_name, _) in created_snapshots {
+        for alias in toc.all_collection_aliases(collection_name, multipass).await {
+            alias_mapping.insert(alias, collection_name.clone());
         }
     }
 
@@ -180,7 +200,7 @@ async fn _do_create_full_snapshot(
     let temp_storage_path = toc.optional_temp_or_storage_temp_path()?;
     let snapshot_manager = toc.get_snapshots_storage_manager()?;
 
-    for (collection_name, snapshot_details) in &created_snapshots {
+    for (collection_name, snapshot_details) in created_snapshots {
         let snapshot_path = snapshot_dir
             .join(collection_name)
             .join(&snapshot_details.name);
@@ -209,6 +229,13 @@ async fn _do_create_full_snapshot(
             builder.append_path_with_name(&temp_file, &snapshot_name)?;
         }
         builder.append_path_with_name(&config_path_clone, "config.json")?;
+        for (file_name, content) in extra_files {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(content.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder.append_data(&mut header, file_name, content.as_slice())?;
+        }
         builder.finish()?;
 
         // Explicitly flush write buffer so we can catch IO errors
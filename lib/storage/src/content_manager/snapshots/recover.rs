@@ -1,5 +1,8 @@
+use std::collections::HashSet;
+
 use collection::collection::Collection;
 use collection::collection::payload_index_schema::{PAYLOAD_INDEX_CONFIG_FILE, PayloadIndexSchema};
+use collection::collection_state::State;
 use collection::common::sha_256::hashes_equal;
 use collection::config::CollectionConfigInternal;
 use collection::operations::snapshot_ops::{SnapshotPriority, SnapshotRecover};
@@ -99,8 +102,12 @@ async fn _do_recover_from_snapshot(
         priority,
         checksum,
         api_key: _,
+        shards,
     } = source;
 
+    // Selected shards are restored into the existing collection
+    let is_partial_restore = shards.is_some();
+
     // All checks should've been done at this point.
     let pass = new_unchecked_verification_pass();
 
@@ -113,6 +120,12 @@ async fn _do_recover_from_snapshot(
         .running_snapshot_recovery
         .measure_scope();
 
+    if is_partial_restore && toc.get_collection(&collection_pass).await.is_err() {
+        return Err(StorageError::bad_input(format!(
+            "Collection {collection_pass} must exist to restore selected shards",
+        )));
+    }
+
     let this_peer_id = toc.this_peer_id;
 
     let is_distributed = toc.is_distributed();
@@ -149,6 +162,11 @@ async fn _do_recover_from_snapshot(
         .tempdir_in(temp_storage_path)?;
 
     let tmp_collection_dir_clone = tmp_collection_dir.path().to_path_buf();
+    let compatibility_policy = toc.storage_config.snapshots_config.compatibility_policy;
+    // Only unpack selected shards, other shards of the snapshot are not used
+    let selected_shards: Option<HashSet<ShardId>> = shards
+        .as_ref()
+        .map(|shards| shards.iter().copied().collect());
 
     let restoring = tokio::task::spawn_blocking(move || {
         Collection::restore_snapshot(
@@ -156,6 +174,8 @@ async fn _do_recover_from_snapshot(
             &tmp_collection_dir_clone,
             this_peer_id,
             is_distributed,
+            compatibility_policy,
+            selected_shards.as_ref(),
         )?;
         common::fs::bulk_sync_dir(&tmp_collection_dir_clone)?;
         Ok::<(), StorageError>(())
@@ -227,6 +247,43 @@ async fn _do_recover_from_snapshot(
         )));
     }
 
+    let shards_to_recover = select_shards_to_recover(&state, shards.as_deref())?;
+
+    if is_partial_restore {
+        // Restored shards are used along with existing ones, so they have to be fully compatible
+        if snapshot_config.params.sparse_vectors != state.config.params.sparse_vectors {
+            return Err(StorageError::bad_input(format!(
+                "Snapshot is not compatible with existing collection: Collection sparse vectors: {:?} Snapshot sparse vectors: {:?}",
+                state.config.params.sparse_vectors, snapshot_config.params.sparse_vectors
+            )));
+        }
+        if snapshot_config.params.sharding_method != state.config.params.sharding_method {
+            return Err(StorageError::bad_input(format!(
+                "Snapshot is not compatible with existing collection: Collection sharding method: {:?} Snapshot sharding method: {:?}",
+                state.config.params.sharding_method, snapshot_config.params.sharding_method
+            )));
+        }
+        // Restored points must end up under the same shard key
+        let snapshot_key_mapping =
+            Collection::load_snapshot_shard_key_mapping(tmp_collection_dir.path())?;
+        for &shard_id in &shards_to_recover {
+            let collection_key = state.shards_key_mapping.shard_key(shard_id);
+            let snapshot_key = snapshot_key_mapping.shard_key(shard_id);
+            if snapshot_key != collection_key {
+                return Err(StorageError::bad_input(format!(
+                    "Snapshot is not compatible with existing collection: Shard {shard_id} has shard key {collection_key:?} in collection and {snapshot_key:?} in snapshot",
+                )));
+            }
+        }
+        // Restored segments must have the same payload indexes as the rest of the collection
+        if schema != state.payload_index_schema.schema {
+            return Err(StorageError::bad_input(format!(
+                "Snapshot is not compatible with existing collection: Collection payload indexes: {:?} Snapshot payload indexes: {:?}",
+                state.payload_index_schema.schema, schema
+            )));
+        }
+    }
+
     let is_manual_recovery_state_supported = toc
         .get_channel_service()
         .all_peers_at_version(&MANUAL_RECOVERY_SHARD_STATE_VERSION);
@@ -239,6 +296,9 @@ async fn _do_recover_from_snapshot(
 
     // Deactivate collection local shards during recovery
     for (shard_id, shard_info) in &state.shards {
+        if !shards_to_recover.contains(shard_id) {
+            continue;
+        }
         let local_shard_state = shard_info.replicas.get(&this_peer_id);
         match local_shard_state {
             None => {} // Shard is not on this node, skip
@@ -260,6 +320,10 @@ async fn _do_recover_from_snapshot(
 
     // Recover shards from the snapshot
     for (shard_id, shard_info) in &state.shards {
+        if !shards_to_recover.contains(shard_id) {
+            continue;
+        }
+
         let snapshot_shard_path = check_shard_path(tmp_collection_dir.path(), *shard_id).await?;
         log::debug!(
             "Recovering shard {} from {}",
@@ -398,3 +462,27 @@ async fn _do_recover_from_snapshot(
 
     Ok(true)
 }
+
+/// Shards to recover from collection snapshot.
+///
+/// Shards are always recovered as a whole, so that restored data never mixes with segments and
+/// WAL of the live shard.
+fn select_shards_to_recover(
+    state: &State,
+    shards: Option<&[ShardId]>,
+) -> Result<HashSet<ShardId>, StorageError> {
+    let Some(shards) = shards else {
+        return Ok(state.shards.keys().copied().collect());
+    };
+
+    if let Some(shard_id) = shards
+        .iter()
+        .find(|&&shard_id| !state.shards.contains_key(&shard_id))
+    {
+        return Err(StorageError::bad_input(format!(
+            "Shard {shard_id} does not exist in collection",
+        )));
+    }
+
+    Ok(shards.iter().copied().collect())
+}
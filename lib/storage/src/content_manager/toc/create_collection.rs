@@ -148,6 +148,7 @@ impl TableOfContent {
             )?,
             read_fan_out_factor: None,
             read_fan_out_delay_ms: None,
+            populate_policy: None,
         };
         let wal_config = self.storage_config.wal.update_opt(wal_config_diff.as_ref());
 
@@ -55,6 +55,7 @@ use crate::types::StorageConfig;
 pub const ALIASES_PATH: &str = "aliases";
 pub const COLLECTIONS_DIR: &str = "collections";
 pub const FULL_SNAPSHOT_FILE_NAME: &str = "full-snapshot";
+pub const SNAPSHOT_BUNDLE_FILE_NAME: &str = "snapshot-bundle";
 
 /// How long to wait till deleted collection is released from previous operations
 pub const COLLECTION_DELETE_WAIT_TIMEOUT: Duration = Duration::from_secs(60 * 10); // 10 mins
@@ -1,5 +1,7 @@
 use std::path::{Path, PathBuf};
 
+use collection::common::snapshot_point::SnapshotPointGuard;
+use collection::common::snapshot_stream::SnapshotStream;
 use collection::common::snapshots_manager::SnapshotStorageManager;
 use collection::operations::snapshot_ops::SnapshotDescription;
 use collection::shards::replica_set::replica_set_state::ReplicaState;
@@ -53,6 +55,18 @@ impl TableOfContent {
     pub async fn create_snapshot(
         &self,
         collection_pass: &CollectionPass<'_>,
+    ) -> Result<SnapshotDescription, StorageError> {
+        self.create_snapshot_at_point(collection_pass, None).await
+    }
+
+    /// Create a snapshot of the collection, dropping `snapshot_point` once all its local shards
+    /// have taken their snapshot point, see [`SnapshotPoint`].
+    ///
+    /// [`SnapshotPoint`]: collection::common::snapshot_point::SnapshotPoint
+    pub async fn create_snapshot_at_point(
+        &self,
+        collection_pass: &CollectionPass<'_>,
+        snapshot_point: Option<SnapshotPointGuard>,
     ) -> Result<SnapshotDescription, StorageError> {
         // Increment snapshot telemetry/mertic counter and account for the whole scope.
         // (This must be a named variable so it doesn't get dropped prematurely!)
@@ -67,10 +81,27 @@ impl TableOfContent {
         // snapshot directory is mounted as network share and multiple writes to it could be slow
         let temp_dir = self.optional_temp_or_storage_temp_path()?;
         Ok(collection
-            .create_snapshot(&temp_dir, self.this_peer_id)
+            .create_snapshot_at_point(&temp_dir, self.this_peer_id, snapshot_point)
             .await?)
     }
 
+    /// Stream a snapshot of the collection, without storing it in the snapshots directory.
+    ///
+    /// The snapshot is counted as running until the returned stream is dropped.
+    pub async fn stream_snapshot(
+        &self,
+        collection_pass: &CollectionPass<'_>,
+    ) -> Result<SnapshotStream, StorageError> {
+        let running_snapshots_guard = self.count_snapshot_creation(collection_pass.name());
+
+        let collection = self.get_collection(collection_pass).await?;
+        let temp_dir = self.optional_temp_or_storage_temp_path()?;
+        let stream = collection
+            .stream_snapshot(&temp_dir, self.this_peer_id)
+            .await?;
+        Ok(stream.with_guard(running_snapshots_guard))
+    }
+
     pub fn send_set_replica_state_proposal(
         &self,
         collection_name: String,
@@ -54,6 +54,11 @@ pub struct PerformanceConfig {
     pub outgoing_shard_transfers_limit: Option<usize>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub async_scorer: Option<bool>,
+    /// Fraction of vectors of tiered vector storages to keep in RAM, from 0 to 1.
+    /// Default: 0.1
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[validate(range(min = 0.0, max = 1.0))]
+    pub hot_tier_ratio: Option<f64>,
     #[serde(default, flatten)]
     pub load_concurrency: LoadConcurrencyConfig,
 }
@@ -115,6 +120,68 @@ pub struct StorageConfig {
     /// Maximum number of collections to allow in the cluster.
     #[serde(default)]
     pub max_collections: Option<usize>,
+    /// Encryption at rest of files written once by segment optimization, see
+    /// [`common::mmap::encryption`] for the files covered. If not set, new files are not encrypted.
+    #[serde(default)]
+    pub encryption: Option<EncryptionConfig>,
+    /// Background scrubber of storage files. If not set, files are not scrubbed.
+    #[validate(nested)]
+    #[serde(default)]
+    pub scrubber: Option<ScrubberConfig>,
+    /// Global limit of background IO, like snapshots and migrations. If not set, it is unlimited.
+    #[validate(nested)]
+    #[serde(default)]
+    pub io_throttle: Option<IoThrottleConfig>,
+}
+
+/// Configuration of encryption at rest, see [`common::mmap::encryption`]
+#[derive(Clone, Deserialize)]
+pub struct EncryptionConfig {
+    /// AES-256 key as 64 hexadecimal characters
+    pub key: String,
+}
+
+impl std::fmt::Debug for EncryptionConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EncryptionConfig")
+            .field("key", &"<redacted>")
+            .finish()
+    }
+}
+
+/// Configuration of the background storage scrubber
+///
+/// The scrubber slowly reads all storage files, to detect latent disk errors and corrupted files
+/// before the data is needed.
+#[derive(Debug, Clone, Deserialize, Validate)]
+pub struct ScrubberConfig {
+    /// Maximum read rate of the scrubber, in bytes per second. Reads are also accounted with the
+    /// global IO throttle, if it is configured.
+    #[serde(default = "default_scrubber_bytes_per_sec")]
+    #[validate(range(min = 1))]
+    pub bytes_per_sec: u64,
+    /// Pause between passes over all storage files, in seconds
+    #[serde(default = "default_scrubber_pause_sec")]
+    pub pause_sec: u64,
+}
+
+const fn default_scrubber_bytes_per_sec() -> u64 {
+    16 * 1024 * 1024
+}
+
+const fn default_scrubber_pause_sec() -> u64 {
+    24 * 60 * 60
+}
+
+/// Configuration of the global background IO throttle, see [`common::io_throttle`]
+///
+/// Shared by snapshot archives, background rewrites of storage files and the storage scrubber, so
+/// that they don't starve IO of searches.
+#[derive(Debug, Clone, Deserialize, Validate)]
+pub struct IoThrottleConfig {
+    /// Maximum rate of background IO, in bytes per second
+    #[validate(range(min = 1))]
+    pub bytes_per_sec: u64,
 }
 
 impl StorageConfig {
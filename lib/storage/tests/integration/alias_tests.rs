@@ -44,6 +44,7 @@ fn test_alias_operation() {
             flush_interval_sec: 2,
             max_optimization_threads: Some(2),
             prevent_unoptimized: None,
+            mmap_advice: None,
         },
         optimizers_overwrite: None,
         wal: Default::default(),
@@ -57,6 +58,7 @@ fn test_alias_operation() {
             incoming_shard_transfers_limit: Some(1),
             outgoing_shard_transfers_limit: Some(1),
             async_scorer: None,
+            hot_tier_ratio: None,
             load_concurrency: LoadConcurrencyConfig::default(),
         },
         hnsw_index: Default::default(),
@@ -71,6 +73,9 @@ fn test_alias_operation() {
         shard_transfer_method: None,
         collection: None,
         max_collections: None,
+        encryption: None,
+        scrubber: None,
+        io_throttle: None,
     };
 
     let search_runtime = Runtime::new().unwrap();
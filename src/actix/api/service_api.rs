@@ -252,6 +252,36 @@ async fn truncate_unapplied_wal(
     helpers::time_or_accept(future, params.wait.unwrap_or(true)).await
 }
 
+#[derive(Deserialize, Serialize, JsonSchema, Validate)]
+pub struct DefragmentParams {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wait: Option<bool>,
+}
+
+#[post("/collections/{name}/defragment")]
+async fn defragment(
+    dispatcher: web::Data<Dispatcher>,
+    collection: Path<CollectionPath>,
+    params: Query<DefragmentParams>,
+    ActixAuth(auth): ActixAuth,
+) -> impl Responder {
+    let future = async move {
+        let collection_pass = auth
+            .check_global_access(AccessRequirements::new().manage(), "defragment")?
+            .issue_pass(&collection.name)
+            .into_static();
+
+        let pass = new_unchecked_verification_pass();
+        let collection = dispatcher
+            .toc(&auth, &pass)
+            .get_collection(&collection_pass)
+            .await?;
+
+        collection.defragment().await.map_err(StorageError::from)
+    };
+    helpers::time_or_accept(future, params.wait.unwrap_or(true)).await
+}
+
 // Configure services
 pub fn config_service_api(cfg: &mut web::ServiceConfig) {
     cfg.service(telemetry)
@@ -262,7 +292,8 @@ pub fn config_service_api(cfg: &mut web::ServiceConfig) {
         .service(readyz)
         .service(get_logger_config)
         .service(update_logger_config)
-        .service(truncate_unapplied_wal);
+        .service(truncate_unapplied_wal)
+        .service(defragment);
 }
 
 // Dedicated service for metrics
@@ -24,6 +24,9 @@ use serde::{Deserialize, Serialize};
 use shard::snapshots::snapshot_data::SnapshotData;
 use shard::snapshots::snapshot_manifest::{RecoveryType, SnapshotManifest};
 use storage::content_manager::errors::{StorageError, StorageResult};
+use storage::content_manager::snapshots::bundle::{
+    CreateSnapshotBundle, do_create_snapshot_bundle,
+};
 use storage::content_manager::snapshots::recover::do_recover_from_snapshot;
 use storage::content_manager::snapshots::{
     do_create_full_snapshot, do_delete_collection_snapshot, do_delete_full_snapshot,
@@ -186,6 +189,19 @@ async fn create_snapshot(
     helpers::time_or_accept(future, params.wait.unwrap_or(true)).await
 }
 
+#[get("/collections/{name}/snapshot")]
+async fn stream_snapshot(
+    dispatcher: web::Data<Dispatcher>,
+    path: web::Path<String>,
+    ActixAuth(auth): ActixAuth,
+) -> Result<SnapshotStream, HttpError> {
+    // Nothing to verify.
+    let pass = new_unchecked_verification_pass();
+
+    let collection_name = path.into_inner();
+    Ok(do_stream_snapshot(dispatcher.toc(&auth, &pass), &auth, &collection_name).await?)
+}
+
 #[post("/collections/{name}/snapshots/upload")]
 async fn upload_snapshot(
     dispatcher: web::Data<Dispatcher>,
@@ -224,6 +240,7 @@ async fn upload_snapshot(
             priority: params.priority,
             checksum: None,
             api_key: None,
+            shards: None,
         };
 
         do_recover_from_snapshot(
@@ -305,6 +322,20 @@ async fn create_full_snapshot(
     helpers::time_or_accept(future, params.wait.unwrap_or(true)).await
 }
 
+#[post("/snapshots/bundle")]
+async fn create_snapshot_bundle(
+    dispatcher: web::Data<Dispatcher>,
+    request: valid::Json<CreateSnapshotBundle>,
+    params: valid::Query<SnapshottingParam>,
+    ActixAuth(auth): ActixAuth,
+) -> impl Responder {
+    let future = async move {
+        let request = request.into_inner();
+        do_create_snapshot_bundle(dispatcher.get_ref(), auth, request).await
+    };
+    helpers::time_or_accept(future, params.wait.unwrap_or(true)).await
+}
+
 #[get("/snapshots/{snapshot_name}")]
 async fn get_full_snapshot(
     dispatcher: web::Data<Dispatcher>,
@@ -885,11 +916,13 @@ async fn get_partial_snapshot_manifest(
 pub fn config_snapshots_api(cfg: &mut web::ServiceConfig) {
     cfg.service(list_snapshots)
         .service(create_snapshot)
+        .service(stream_snapshot)
         .service(upload_snapshot)
         .service(recover_from_snapshot)
         .service(get_snapshot)
         .service(list_full_snapshots)
         .service(create_full_snapshot)
+        .service(create_snapshot_bundle)
         .service(get_full_snapshot)
         .service(delete_full_snapshot)
         .service(delete_collection_snapshot)
@@ -6,6 +6,7 @@ use api::grpc::qdrant::CollectionExists;
 use api::rest::models::{
     CollectionDescription, CollectionsResponse, ShardKeyDescription, ShardKeysResponse,
 };
+use collection::common::snapshot_stream::SnapshotStream;
 use collection::config::ShardingMethod;
 #[cfg(feature = "staging")]
 use collection::operations::cluster_ops::TestSlowDownOperation;
@@ -229,6 +230,20 @@ pub async fn do_create_snapshot(
     Ok(result)
 }
 
+pub async fn do_stream_snapshot(
+    toc: &TableOfContent,
+    auth: &Auth,
+    collection_name: &str,
+) -> Result<SnapshotStream, StorageError> {
+    let collection_pass = auth.check_collection_access(
+        collection_name,
+        AccessRequirements::new().write().extras(),
+        "stream_snapshot",
+    )?;
+
+    toc.stream_snapshot(&collection_pass).await
+}
+
 pub async fn do_get_collection_cluster(
     toc: &TableOfContent,
     auth: &Auth,
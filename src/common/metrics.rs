@@ -16,10 +16,12 @@ use crate::common::telemetry_ops::cluster_telemetry::{ClusterStatusTelemetry, Cl
 use crate::common::telemetry_ops::collections_telemetry::{
     CollectionTelemetryEnum, CollectionsTelemetry,
 };
+use crate::common::telemetry_ops::io_throttle_telemetry::IoThrottleTelemetry;
 use crate::common::telemetry_ops::memory_telemetry::MemoryTelemetry;
 use crate::common::telemetry_ops::requests_telemetry::{
     GrpcTelemetry, RequestsTelemetry, WebApiTelemetry,
 };
+use crate::common::telemetry_ops::scrubber_telemetry::ScrubberTelemetry;
 
 /// Whitelist for REST endpoints in metrics output.
 ///
@@ -147,6 +149,21 @@ impl MetricsProvider for TelemetryData {
         if let Some(mem) = &self.memory {
             mem.add_metrics(metrics, prefix);
         }
+        if let Some(scrubber) = &self.scrubber {
+            scrubber.add_metrics(metrics, prefix);
+        }
+        if let Some(io_throttle) = &self.io_throttle {
+            io_throttle.add_metrics(metrics, prefix);
+        }
+        if let Some(failed_segments) = &self.failed_segments {
+            metrics.push_metric(metric_family(
+                "segments_failed_to_open",
+                "Number of segments which failed to open, because of invalid files",
+                MetricType::GAUGE,
+                vec![gauge(failed_segments.len() as f64, &[])],
+                prefix,
+            ));
+        }
 
         #[cfg(target_os = "linux")]
         match procfs_metrics::ProcFsMetrics::collect() {
@@ -735,6 +752,65 @@ impl MetricsProvider for MemoryTelemetry {
     }
 }
 
+impl MetricsProvider for ScrubberTelemetry {
+    fn add_metrics(&self, metrics: &mut MetricsData, prefix: Option<&str>) {
+        metrics.push_metric(metric_family(
+            "scrubber_passes_total",
+            "Total number of completed storage scrubber passes",
+            MetricType::COUNTER,
+            vec![counter(self.passes as f64, &[])],
+            prefix,
+        ));
+        metrics.push_metric(metric_family(
+            "scrubber_checked_bytes_total",
+            "Total number of bytes checked by the storage scrubber",
+            MetricType::COUNTER,
+            vec![counter(self.bytes_checked as f64, &[])],
+            prefix,
+        ));
+        metrics.push_metric(metric_family(
+            "scrubber_errors_total",
+            "Total number of problems found by the storage scrubber",
+            MetricType::COUNTER,
+            vec![counter(self.errors as f64, &[])],
+            prefix,
+        ));
+    }
+}
+
+impl MetricsProvider for IoThrottleTelemetry {
+    fn add_metrics(&self, metrics: &mut MetricsData, prefix: Option<&str>) {
+        metrics.push_metric(metric_family(
+            "io_throttle_limit_bytes_per_second",
+            "Configured limit of background IO, in bytes per second",
+            MetricType::GAUGE,
+            vec![gauge(self.bytes_per_sec as f64, &[])],
+            prefix,
+        ));
+        metrics.push_metric(metric_family(
+            "io_throttle_current_bytes_per_second",
+            "Throttled background IO rate over the last second",
+            MetricType::GAUGE,
+            vec![gauge(self.current_bytes_per_sec as f64, &[])],
+            prefix,
+        ));
+        metrics.push_metric(metric_family(
+            "io_throttle_bytes_total",
+            "Total number of bytes of throttled background IO",
+            MetricType::COUNTER,
+            vec![counter(self.bytes_total as f64, &[])],
+            prefix,
+        ));
+        metrics.push_metric(metric_family(
+            "io_throttle_waited_seconds_total",
+            "Total time background IO waited for the throttle",
+            MetricType::COUNTER,
+            vec![counter(self.waited_ms_total as f64 / 1000.0, &[])],
+            prefix,
+        ));
+    }
+}
+
 impl HardwareTelemetry {
     // Helper function to create counter metrics of a single Hw type, like cpu.
     fn make_metric_counters<F: Fn(&HardwareUsage) -> usize>(&self, f: F) -> Vec<Metric> {
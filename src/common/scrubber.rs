@@ -0,0 +1,246 @@
+//! Background scrubber of storage files.
+//!
+//! Slowly reads all files in the storage directory, to detect latent disk errors and corrupted
+//! files before the data is needed by a search. Files are read with regular reads instead of
+//! mmap, so that an unreadable page is reported as an error instead of crashing the process.
+//! Files with known checksums are verified against them.
+//!
+//! Reads are limited by the scrubber's own rate and accounted with the global IO throttle, see
+//! [`io_throttle`]. Findings are logged and reported in telemetry.
+
+use std::cell::Cell;
+use std::io::{self, Read as _};
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, Instant};
+
+#[cfg(target_os = "linux")]
+use common::cpu::linux_low_thread_priority;
+use common::io_throttle::{self, Throttle};
+use common::mmap::encryption;
+use fs_err as fs;
+use fs_err::File;
+use parking_lot::Mutex;
+use segment::index::sparse_index::verify_index_file;
+use segment::vector_storage::dense::memmap_dense_vector_storage::is_valid_file_header;
+use segment::vector_storage::verify_chunk_file;
+use storage::types::ScrubberConfig;
+
+use crate::common::telemetry_ops::scrubber_telemetry::ScrubberTelemetry;
+
+const READ_BUFFER_SIZE: usize = 1024 * 1024;
+/// Number of most recent errors kept in telemetry
+const RECENT_ERRORS: usize = 16;
+
+/// Telemetry of the scrubber, `None` if it is not running
+static TELEMETRY: Mutex<Option<ScrubberTelemetry>> = Mutex::new(None);
+
+pub fn telemetry() -> Option<ScrubberTelemetry> {
+    TELEMETRY.lock().clone()
+}
+
+pub struct Scrubber {
+    storage_path: PathBuf,
+    config: ScrubberConfig,
+}
+
+impl Scrubber {
+    /// Spawn the scrubber on a low priority background thread, scrubbing `storage_path` forever.
+    pub fn spawn(storage_path: PathBuf, config: ScrubberConfig) -> io::Result<()> {
+        *TELEMETRY.lock() = Some(ScrubberTelemetry::default());
+
+        let scrubber = Self {
+            storage_path,
+            config,
+        };
+        thread::Builder::new()
+            .name("scrubber".to_string())
+            .spawn(move || {
+                #[cfg(target_os = "linux")]
+                if let Err(err) = linux_low_thread_priority() {
+                    log::debug!("Failed to set low thread priority for scrubber: {err}");
+                }
+                scrubber.run();
+            })?;
+        Ok(())
+    }
+
+    fn run(&self) {
+        loop {
+            let started = Instant::now();
+            self.scrub_pass();
+            log::debug!(
+                "Storage scrubber pass finished in {:.1?}",
+                started.elapsed(),
+            );
+
+            if let Some(telemetry) = TELEMETRY.lock().as_mut() {
+                telemetry.passes += 1;
+                telemetry.last_pass_finished_at = Some(chrono::Utc::now());
+            }
+            thread::sleep(Duration::from_secs(self.config.pause_sec));
+        }
+    }
+
+    fn scrub_pass(&self) {
+        let mut throttle = Throttle::with_rate(self.config.bytes_per_sec);
+        let mut dirs = vec![self.storage_path.clone()];
+
+        while let Some(dir) = dirs.pop() {
+            let entries = match fs::read_dir(&dir) {
+                Ok(entries) => entries,
+                // Directories disappear during optimizations and collection removals
+                Err(err) if err.kind() == io::ErrorKind::NotFound => continue,
+                Err(err) => {
+                    report_error(&dir, &err);
+                    continue;
+                }
+            };
+
+            for entry in entries {
+                let path = match entry.and_then(|entry| entry.file_type().map(|t| (entry, t))) {
+                    Ok((entry, file_type)) if file_type.is_dir() => {
+                        dirs.push(entry.path());
+                        continue;
+                    }
+                    Ok((entry, file_type)) if file_type.is_file() => entry.path(),
+                    Ok(_) => continue,
+                    Err(err) => {
+                        report_error(&dir, &err);
+                        continue;
+                    }
+                };
+
+                match scrub_file(&path, &mut throttle) {
+                    Ok(bytes) => {
+                        if let Some(telemetry) = TELEMETRY.lock().as_mut() {
+                            telemetry.files_checked += 1;
+                            telemetry.bytes_checked += bytes;
+                        }
+                    }
+                    Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+                    Err(err) => report_error(&path, &err),
+                }
+            }
+        }
+    }
+}
+
+/// Read the whole file at `path`, and verify its header, checksum or authentication tags if known.
+///
+/// Returns the number of bytes checked.
+fn scrub_file(path: &Path, throttle: &mut Throttle) -> io::Result<u64> {
+    let bytes = Cell::new(0);
+    let mut on_read = |len: usize| {
+        bytes.set(bytes.get() + len as u64);
+        consume(throttle, len);
+    };
+
+    if encryption::is_encrypted_file(path)? {
+        encryption::verify_file(path, &mut on_read)?;
+        return Ok(bytes.get());
+    }
+
+    match verify_chunk_file(path, &mut on_read)? {
+        Some(true) => return Ok(bytes.get()),
+        Some(false) => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "chunk checksum mismatch",
+            ));
+        }
+        // Chunk modified while reading, it was read anyway
+        None if bytes.get() > 0 => return Ok(bytes.get()),
+        None => {}
+    }
+
+    match verify_index_file(path, &mut on_read)? {
+        Some(true) => return Ok(bytes.get()),
+        Some(false) => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "sparse index checksum mismatch",
+            ));
+        }
+        None => {}
+    }
+
+    let mut bytes = bytes.get();
+    let mut file = File::open(path)?;
+    let mut buffer = vec![0; READ_BUFFER_SIZE];
+    loop {
+        let read = file.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        if bytes == 0 {
+            let file_name = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .unwrap_or("");
+            if is_valid_file_header(file_name, &buffer[..read]) == Some(false) {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "unexpected file header",
+                ));
+            }
+        }
+        bytes += read as u64;
+        consume(throttle, read);
+    }
+    Ok(bytes)
+}
+
+/// Account `bytes` read by the scrubber, both with its own rate limit and the global IO throttle.
+fn consume(throttle: &mut Throttle, bytes: usize) {
+    throttle.consume(bytes);
+    io_throttle::consume(bytes);
+}
+
+fn report_error(path: &Path, err: &io::Error) {
+    log::error!(
+        "Storage scrubber found a problem in {}: {err}",
+        path.display()
+    );
+
+    if let Some(telemetry) = TELEMETRY.lock().as_mut() {
+        telemetry.errors += 1;
+        if telemetry.recent_errors.len() >= RECENT_ERRORS {
+            telemetry.recent_errors.remove(0);
+        }
+        telemetry
+            .recent_errors
+            .push(format!("{}: {err}", path.display()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write as _;
+
+    use super::*;
+
+    #[test]
+    fn test_scrub_file_detects_bad_header() {
+        let dir = tempfile::Builder::new()
+            .prefix("scrubber")
+            .tempdir()
+            .unwrap();
+        let mut throttle = Throttle::with_rate(u64::MAX);
+
+        let other = dir.path().join("other.dat");
+        fs::write(&other, vec![7u8; READ_BUFFER_SIZE + 10]).unwrap();
+        assert_eq!(
+            scrub_file(&other, &mut throttle).unwrap(),
+            READ_BUFFER_SIZE as u64 + 10,
+        );
+
+        let deleted = dir.path().join("deleted.dat");
+        let mut file = File::create(&deleted).unwrap();
+        file.write_all(b"xxxx").unwrap();
+        file.write_all(&[0; 64]).unwrap();
+        drop(file);
+        let err = scrub_file(&deleted, &mut throttle).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+}
@@ -10,11 +10,12 @@ use itertools::Itertools;
 use parking_lot::Mutex;
 use schemars::JsonSchema;
 use segment::common::anonymize::Anonymize;
+use segment::segment_constructor::validation_report::{ValidationReport, failed_segment_reports};
 use serde::Serialize;
 use shard::common::stopping_guard::StoppingGuard;
 use storage::content_manager::errors::{StorageError, StorageResult};
 use storage::dispatcher::Dispatcher;
-use storage::rbac::Auth;
+use storage::rbac::{AccessRequirements, Auth};
 use tokio::time::error::Elapsed;
 use tokio_util::task::AbortOnDropHandle;
 use tonic::Status;
@@ -26,10 +27,12 @@ use crate::common::telemetry_ops::collections_telemetry::{
     CollectionTelemetryEnum, CollectionsTelemetry,
 };
 use crate::common::telemetry_ops::hardware::HardwareTelemetry;
+use crate::common::telemetry_ops::io_throttle_telemetry::IoThrottleTelemetry;
 use crate::common::telemetry_ops::memory_telemetry::MemoryTelemetry;
 use crate::common::telemetry_ops::requests_telemetry::{
     ActixTelemetryCollector, RequestsTelemetry, TonicTelemetryCollector,
 };
+use crate::common::telemetry_ops::scrubber_telemetry::ScrubberTelemetry;
 use crate::settings::Settings;
 
 // Keep in sync with openapi/openapi-service.ytt.yaml
@@ -60,6 +63,13 @@ pub struct TelemetryData {
     pub(crate) memory: Option<MemoryTelemetry>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub(crate) hardware: Option<HardwareTelemetry>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) scrubber: Option<ScrubberTelemetry>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) io_throttle: Option<IoThrottleTelemetry>,
+    /// Validation reports of segments which failed to open
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) failed_segments: Option<Vec<ValidationReport>>,
 }
 
 impl TelemetryCollector {
@@ -145,6 +155,18 @@ impl TelemetryCollector {
                 .flatten(),
             hardware: (detail.level > DetailsLevel::Level0)
                 .then(|| HardwareTelemetry::new(&self.dispatcher, access)),
+            scrubber: (detail.level > DetailsLevel::Level0)
+                .then(|| ScrubberTelemetry::collect(auth))
+                .flatten(),
+            io_throttle: (detail.level > DetailsLevel::Level0)
+                .then(|| IoThrottleTelemetry::collect(auth))
+                .flatten(),
+            failed_segments: (detail.level > DetailsLevel::Level0
+                && auth
+                    .check_global_access(AccessRequirements::new(), "telemetry_failed_segments")
+                    .is_ok())
+            .then(failed_segment_reports)
+            .filter(|reports| !reports.is_empty()),
         })
     }
 }
@@ -185,6 +207,9 @@ impl TryFrom<grpc::PeerTelemetry> for TelemetryData {
             requests: None,
             memory: None,
             hardware: None,
+            scrubber: None,
+            io_throttle: None,
+            failed_segments: None,
         })
     }
 }
@@ -201,6 +226,9 @@ impl TryFrom<TelemetryData> for grpc::PeerTelemetry {
             requests: _,
             memory: _,
             hardware: _,
+            scrubber: _,
+            io_throttle: _,
+            failed_segments: _,
         } = telemetry_data;
 
         let app = app.map(grpc::AppTelemetry::from);
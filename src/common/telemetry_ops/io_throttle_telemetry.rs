@@ -0,0 +1,33 @@
+use schemars::JsonSchema;
+use segment::common::anonymize::Anonymize;
+use serde::Serialize;
+use storage::rbac::{AccessRequirements, Auth};
+
+/// Usage of the global throttle of background IO, like snapshots and migrations, since startup.
+#[derive(Debug, Clone, Default, JsonSchema, Serialize, Anonymize)]
+#[anonymize(false)]
+pub struct IoThrottleTelemetry {
+    /// Configured limit, in bytes per second
+    pub bytes_per_sec: u64,
+    /// Throttled IO rate over the last second, in bytes per second
+    pub current_bytes_per_sec: u64,
+    /// Total number of throttled bytes
+    pub bytes_total: u64,
+    /// Total time throttled operations waited for the budget, in milliseconds
+    pub waited_ms_total: u64,
+}
+
+impl IoThrottleTelemetry {
+    /// Telemetry of the throttle, `None` if it is disabled.
+    pub fn collect(auth: &Auth) -> Option<IoThrottleTelemetry> {
+        auth.check_global_access(AccessRequirements::new(), "telemetry_io_throttle")
+            .ok()?;
+        let stats = common::io_throttle::stats()?;
+        Some(IoThrottleTelemetry {
+            bytes_per_sec: stats.bytes_per_sec,
+            current_bytes_per_sec: stats.current_bytes_per_sec,
+            bytes_total: stats.bytes_total,
+            waited_ms_total: stats.waited_total.as_millis() as u64,
+        })
+    }
+}
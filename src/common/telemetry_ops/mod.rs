@@ -4,5 +4,7 @@ pub mod collections_telemetry;
 mod conversions;
 pub mod distributed_telemetry;
 pub mod hardware;
+pub mod io_throttle_telemetry;
 pub mod memory_telemetry;
 pub mod requests_telemetry;
+pub mod scrubber_telemetry;
@@ -0,0 +1,36 @@
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
+use segment::common::anonymize::Anonymize;
+use serde::Serialize;
+use storage::rbac::{AccessRequirements, Auth};
+
+use crate::common::scrubber;
+
+/// Findings of the background storage scrubber, since startup.
+#[derive(Debug, Clone, Default, JsonSchema, Serialize, Anonymize)]
+#[anonymize(false)]
+pub struct ScrubberTelemetry {
+    /// Number of completed passes over all storage files
+    pub passes: usize,
+    /// Number of files checked
+    pub files_checked: usize,
+    /// Number of bytes checked
+    pub bytes_checked: u64,
+    /// Number of problems found, like read errors or corrupted files
+    pub errors: usize,
+    /// Most recent problems found
+    #[anonymize(value = Vec::new())]
+    pub recent_errors: Vec<String>,
+    /// Time when the last complete pass finished
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_pass_finished_at: Option<DateTime<Utc>>,
+}
+
+impl ScrubberTelemetry {
+    /// Telemetry of the scrubber, `None` if it is not running.
+    pub fn collect(auth: &Auth) -> Option<ScrubberTelemetry> {
+        auth.check_global_access(AccessRequirements::new(), "telemetry_scrubber")
+            .ok()?;
+        scrubber::telemetry()
+    }
+}
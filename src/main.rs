@@ -22,6 +22,7 @@ use ::common::flags::{feature_flags, init_feature_flags};
 use ::common::fs::{FsCheckResult, check_fs_info, check_mmap_functionality};
 use ::common::mmap::MULTI_MMAP_SUPPORT_CHECK_RESULT;
 use ::common::mmap::advice::set_global;
+use ::common::mmap::encryption::{self, EncryptionKey, StaticKeyProvider};
 use ::tonic::transport::Uri;
 use api::grpc::transport_channel_pool::TransportChannelPool;
 use clap::Parser;
@@ -171,6 +172,11 @@ fn main() -> anyhow::Result<()> {
     setup_panic_hook(reporting_enabled, reporting_id.to_string());
 
     set_global(settings.storage.mmap_advice);
+    if let Some(encryption_config) = &settings.storage.encryption {
+        let key = EncryptionKey::from_hex(&encryption_config.key)
+            .map_err(|err| anyhow::anyhow!("Invalid storage encryption key: {err}"))?;
+        encryption::set_key_provider(Some(Arc::new(StaticKeyProvider::new(key))));
+    }
     segment::vector_storage::common::set_async_scorer(
         settings
             .storage
@@ -178,6 +184,9 @@ fn main() -> anyhow::Result<()> {
             .async_scorer
             .unwrap_or_default(),
     );
+    if let Some(hot_tier_ratio) = settings.storage.performance.hot_tier_ratio {
+        segment::vector_storage::common::set_hot_tier_ratio(hot_tier_ratio);
+    }
     welcome(&settings);
 
     // If audit logging is enabled, but failed to initialize,
@@ -294,6 +303,7 @@ fn main() -> anyhow::Result<()> {
     let is_distributed_deployment = settings.cluster.enabled;
 
     let temp_path = settings.storage.temp_path.as_deref();
+    let compatibility_policy = settings.storage.snapshots_config.compatibility_policy;
 
     let restored_collections = if let Some(full_snapshot) = args.storage_snapshot {
         recover_full_snapshot(
@@ -303,6 +313,7 @@ fn main() -> anyhow::Result<()> {
             args.force_snapshot,
             persistent_consensus_state.this_peer_id(),
             is_distributed_deployment,
+            compatibility_policy,
         )
     } else if let Some(snapshots) = args.snapshot {
         // recover from snapshots
@@ -313,6 +324,7 @@ fn main() -> anyhow::Result<()> {
             &settings.storage.storage_path,
             persistent_consensus_state.this_peer_id(),
             is_distributed_deployment,
+            compatibility_policy,
         )
     } else {
         vec![]
@@ -541,6 +553,22 @@ fn main() -> anyhow::Result<()> {
         log::info!("Hardware reporting enabled");
     }
 
+    if let Some(scrubber_config) = settings.storage.scrubber.clone() {
+        log::info!(
+            "Storage scrubber enabled, rate: {} bytes/sec",
+            scrubber_config.bytes_per_sec,
+        );
+        common::scrubber::Scrubber::spawn(settings.storage.storage_path.clone(), scrubber_config)?;
+    }
+
+    if let Some(io_throttle_config) = &settings.storage.io_throttle {
+        log::info!(
+            "Background IO throttle enabled, rate: {} bytes/sec",
+            io_throttle_config.bytes_per_sec,
+        );
+        ::common::io_throttle::configure(Some(io_throttle_config.bytes_per_sec));
+    }
+
     // Setup subscribers to listen for issue-able events
     issues_setup::setup_subscribers(&settings);
     init_requests_profile_collector(runtime_handle.clone());
@@ -28,6 +28,7 @@ use serde::Serialize;
 use storage::content_manager::collection_meta_ops::{
     ChangeAliasesOperation, CreateCollection, UpdateCollection,
 };
+use storage::content_manager::snapshots::bundle::CreateSnapshotBundle;
 use storage::types::ClusterStatus;
 
 use crate::common::telemetry::TelemetryData;
@@ -100,6 +101,7 @@ struct AllDefinitions {
     bo: ShardKeysResponse,
     bp: OptimizationsResponse,
     bq: DistributedTelemetryData,
+    br: CreateSnapshotBundle,
 }
 
 fn save_schema<T: JsonSchema>() {
@@ -1,9 +1,12 @@
+use std::io;
 use std::path::Path;
 use std::sync::atomic::AtomicBool;
 
 use clap::Parser;
 use common::counter::hardware_counter::HardwareCounterCell;
 use segment::entry::entry_point::NonAppendableSegmentEntry;
+use segment::index::VectorIndexEnum;
+use segment::index::hnsw_index::graph_links::GraphExportFormat;
 use segment::segment_constructor::load_segment;
 use segment::types::PointIdType;
 use uuid::Uuid;
@@ -26,6 +29,14 @@ struct Args {
     /// Point ID to inspect (UUID)
     #[clap(long)]
     point_id_uuid: Option<String>,
+
+    /// Name of the vector, HNSW graph of which is exported to stdout. Empty for the default vector
+    #[clap(long)]
+    export_graph: Option<String>,
+
+    /// Format of the exported graph: `jsonl` or `graphml`
+    #[clap(long, default_value = "jsonl")]
+    export_format: GraphExportFormat,
 }
 
 fn main() {
@@ -78,5 +89,20 @@ fn main() {
                 // println!("Vectors: {vectors:?}");
             }
         }
+
+        if let Some(vector_name) = &args.export_graph {
+            let Some(vector_data) = segment.vector_data.get(vector_name.as_str()) else {
+                eprintln!("Vector does not exist: {vector_name:?}");
+                continue;
+            };
+            let vector_index = vector_data.vector_index.borrow();
+            let VectorIndexEnum::Hnsw(index) = &*vector_index else {
+                eprintln!("Vector {vector_name:?} has no HNSW index");
+                continue;
+            };
+            index
+                .export_graph(args.export_format, io::BufWriter::new(io::stdout().lock()))
+                .unwrap();
+        }
     }
 }
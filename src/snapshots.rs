@@ -2,6 +2,7 @@ use std::io::BufReader;
 use std::path::{Path, PathBuf};
 
 use collection::collection::Collection;
+use collection::common::snapshot_metadata::SnapshotCompatibilityPolicy;
 use collection::shards::shard::PeerId;
 use common::fs::safe_delete_in_tmp;
 use common::tar_unpack::tar_unpack_file;
@@ -11,6 +12,7 @@ use log::info;
 use shard::snapshots::snapshot_data::SnapshotData;
 use storage::content_manager::alias_mapping::AliasPersistence;
 use storage::content_manager::snapshots::SnapshotConfig;
+use storage::content_manager::snapshots::bundle::{BUNDLE_MANIFEST_FILE, SnapshotBundleManifest};
 use storage::content_manager::toc::{ALIASES_PATH, COLLECTIONS_DIR};
 
 /// Recover snapshots from the given arguments
@@ -19,6 +21,7 @@ use storage::content_manager::toc::{ALIASES_PATH, COLLECTIONS_DIR};
 ///
 /// * `mapping` - `[ <path>:<collection_name> ]`
 /// * `force` - if true, allow to overwrite collections from snapshots
+/// * `compatibility_policy` - how to handle snapshots made on a different platform or version
 ///
 /// # Returns
 ///
@@ -30,6 +33,7 @@ pub fn recover_snapshots(
     storage_dir: &Path,
     this_peer_id: PeerId,
     is_distributed: bool,
+    compatibility_policy: SnapshotCompatibilityPolicy,
 ) -> Vec<String> {
     let collection_dir_path = storage_dir.join(COLLECTIONS_DIR);
     let mut recovered_collections: Vec<String> = vec![];
@@ -71,6 +75,8 @@ pub fn recover_snapshots(
             &collection_temp_path,
             this_peer_id,
             is_distributed,
+            compatibility_policy,
+            None,
         ) {
             panic!("Failed to recover snapshot {collection_name}: {err}");
         }
@@ -93,6 +99,7 @@ pub fn recover_full_snapshot(
     force: bool,
     this_peer_id: PeerId,
     is_distributed: bool,
+    compatibility_policy: SnapshotCompatibilityPolicy,
 ) -> Vec<String> {
     let snapshot_temp_path = temp_dir
         .map(PathBuf::from)
@@ -107,6 +114,18 @@ pub fn recover_full_snapshot(
     let config_file = BufReader::new(File::open(config_path).unwrap());
     let config_json: SnapshotConfig = serde_json::from_reader(config_file).unwrap();
 
+    // Snapshot bundles also describe the point at which all collections were taken
+    let bundle_manifest_path = snapshot_temp_path.join(BUNDLE_MANIFEST_FILE);
+    if bundle_manifest_path.exists() {
+        let manifest_file = BufReader::new(File::open(bundle_manifest_path).unwrap());
+        let manifest: SnapshotBundleManifest = serde_json::from_reader(manifest_file).unwrap();
+        info!(
+            "Recovering snapshot bundle of {} collections, consistent at {}",
+            manifest.collections.len(),
+            manifest.consistent_at,
+        );
+    }
+
     // Create mapping from the configuration file
     let mapping: Vec<String> = config_json
         .collections_mapping
@@ -127,6 +146,7 @@ pub fn recover_full_snapshot(
         storage_dir,
         this_peer_id,
         is_distributed,
+        compatibility_policy,
     );
 
     let alias_path = storage_dir.join(ALIASES_PATH);
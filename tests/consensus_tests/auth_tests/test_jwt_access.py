@@ -311,6 +311,13 @@ ACTION_ACCESS = {
         "GET /collections/{collection_name}/snapshots/{snapshot_name}",
         coll_prw=False,
     ),
+    "stream_collection_snapshot": EndpointAccess(
+        False,
+        True,
+        True,
+        "GET /collections/{collection_name}/snapshot",
+        coll_prw=False,
+    ),
     "upload_collection_snapshot": EndpointAccess(
         False, False, True, "POST /collections/{collection_name}/snapshots/upload"
     ),
@@ -1289,6 +1296,13 @@ def test_download_collection_snapshot():
     )
 
 
+def test_stream_collection_snapshot():
+    check_access(
+        "stream_collection_snapshot",
+        path_params={"collection_name": COLL_NAME},
+    )
+
+
 @pytest.fixture(scope="module")
 def collection_snapshot():
     res = requests.post(
@@ -409,3 +409,107 @@ def test_payload_schemas(collection_name):
                     "field_name": f"field_{field_no:02d}",
                 },
             )
+
+
+def test_composite_index(collection_name):
+    for point_id in range(1, 11):
+        tenant = "a" if point_id % 2 == 0 else "b"
+        set_payload(collection_name, {"tenant": tenant, "ts": point_id}, [point_id])
+
+    response = request_with_validation(
+        api="/collections/{collection_name}/index",
+        method="PUT",
+        path_params={"collection_name": collection_name},
+        query_params={"wait": "true"},
+        body={
+            "field_name": "tenant",
+            "field_schema": {"type": "keyword", "composite_with": "ts"},
+        },
+    )
+    assert response.ok
+
+    response = request_with_validation(
+        api="/collections/{collection_name}",
+        method="GET",
+        path_params={"collection_name": collection_name},
+    )
+    assert response.ok
+    assert response.json()["result"]["payload_schema"]["tenant"]["params"] == {
+        "type": "keyword",
+        "composite_with": "ts",
+    }
+
+    def scroll_ids():
+        response = request_with_validation(
+            api="/collections/{collection_name}/points/scroll",
+            method="POST",
+            path_params={"collection_name": collection_name},
+            body={
+                "with_vector": False,
+                "filter": {
+                    "must": [
+                        {"key": "tenant", "match": {"value": "a"}},
+                        {"key": "ts", "range": {"gte": 3, "lt": 9}},
+                    ]
+                },
+            },
+        )
+        assert response.ok, response.json()
+        return sorted(p["id"] for p in response.json()["result"]["points"])
+
+    assert scroll_ids() == [4, 6, 8]
+
+    # Points with changed payload are found by their new values
+    set_payload(collection_name, {"tenant": "a"}, [5])
+    set_payload(collection_name, {"ts": 10}, [4])
+    assert scroll_ids() == [5, 6, 8]
+
+    response = request_with_validation(
+        api="/collections/{collection_name}/index/{field_name}",
+        method="DELETE",
+        path_params={"collection_name": collection_name, "field_name": "tenant"},
+        query_params={"wait": "true"},
+    )
+    assert response.ok
+    assert scroll_ids() == [5, 6, 8]
+
+
+def test_change_index_schema(collection_name):
+    set_payload(collection_name, {"name": "yellow"}, [1, 2])
+    set_payload(collection_name, {"name": "hello"}, [3])
+
+    def create_index(field_schema):
+        response = request_with_validation(
+            api="/collections/{collection_name}/index",
+            method="PUT",
+            path_params={"collection_name": collection_name},
+            query_params={"wait": "true"},
+            body={"field_name": "name", "field_schema": field_schema},
+        )
+        assert response.ok
+
+    create_index("keyword")
+    # Index with a different schema is rebuilt in place
+    create_index({"type": "keyword", "trigram_index": True})
+
+    response = request_with_validation(
+        api="/collections/{collection_name}",
+        method="GET",
+        path_params={"collection_name": collection_name},
+    )
+    assert response.ok
+    schema = response.json()["result"]["payload_schema"]["name"]
+    assert schema["params"] == {"type": "keyword", "trigram_index": True}
+    assert schema["points"] == 3
+
+    response = request_with_validation(
+        api="/collections/{collection_name}/points/scroll",
+        method="POST",
+        path_params={"collection_name": collection_name},
+        body={
+            "with_vector": False,
+            "filter": {"must": [{"key": "name", "match": {"value": "yellow"}}]},
+        },
+    )
+    assert response.ok
+    assert sorted(p["id"] for p in response.json()["result"]["points"]) == [1, 2]
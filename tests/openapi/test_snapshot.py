@@ -1,7 +1,10 @@
 import time
 from time import sleep
 import hashlib
+import io
+import json
 import os
+import tarfile
 import pytest
 import requests
 
@@ -164,6 +167,155 @@ def test_collection_snapshot_operations(http_server, collection_name):
     assert len(response.json()['result']['points']) == 10
 
 
+def test_collection_snapshot_stream(http_server, collection_name):
+    (srv_dir, srv_url) = http_server
+
+    # stream snapshot of the collection
+    response = request_with_validation(
+        api='/collections/{collection_name}/snapshot',
+        method="GET",
+        path_params={'collection_name': collection_name},
+    )
+    assert response.ok
+    with open(srv_dir / "snapshot.tar", 'wb') as f:
+        f.write(response.content)
+
+    # streamed snapshot is not stored
+    response = request_with_validation(
+        api='/collections/{collection_name}/snapshots',
+        method="GET",
+        path_params={'collection_name': collection_name},
+    )
+    assert response.ok
+    assert len(response.json()['result']) == 0
+
+    # delete collection
+    response = request_with_validation(
+        api='/collections/{collection_name}',
+        method="DELETE",
+        path_params={'collection_name': collection_name},
+    )
+    assert response.ok
+
+    # recover collection from streamed snapshot
+    response = request_with_validation(
+        api='/collections/{collection_name}/snapshots/recover',
+        method="PUT",
+        path_params={'collection_name': collection_name},
+        body={
+            "location": f"{srv_url}/snapshot.tar",
+            "wait": "true",
+        },
+    )
+    assert response.ok
+
+    # validate that the collection is recovered
+    response = request_with_validation(
+        api='/collections/{collection_name}/points/scroll',
+        method="POST",
+        path_params={'collection_name': collection_name},
+        body={},
+    )
+    assert response.ok
+    assert len(response.json()['result']['points']) == 10
+
+
+def test_collection_snapshot_partial_restore(http_server, collection_name):
+    (srv_dir, srv_url) = http_server
+
+    response = request_with_validation(
+        api='/collections/{collection_name}/snapshot',
+        method="GET",
+        path_params={'collection_name': collection_name},
+    )
+    assert response.ok
+    with open(srv_dir / "snapshot.tar", 'wb') as f:
+        f.write(response.content)
+
+    response = request_with_validation(
+        api='/collections/{collection_name}/points/delete',
+        method="POST",
+        path_params={'collection_name': collection_name},
+        query_params={'wait': 'true'},
+        body={"points": [1, 2]},
+    )
+    assert response.ok
+
+    # unknown shard
+    response = request_with_validation(
+        api='/collections/{collection_name}/snapshots/recover',
+        method="PUT",
+        path_params={'collection_name': collection_name},
+        body={
+            "location": f"{srv_url}/snapshot.tar",
+            "shards": [42],
+        },
+    )
+    assert response.status_code == 400
+
+    # selected shards are only restored into an existing collection
+    response = request_with_validation(
+        api='/collections/{collection_name}/snapshots/recover',
+        method="PUT",
+        path_params={'collection_name': "somethingthatdoesnotexist"},
+        body={
+            "location": f"{srv_url}/snapshot.tar",
+            "shards": [0],
+        },
+    )
+    assert response.status_code == 400
+
+    # payload indexes differ between snapshot and collection
+    response = request_with_validation(
+        api='/collections/{collection_name}/index',
+        method="PUT",
+        path_params={'collection_name': collection_name},
+        query_params={'wait': 'true'},
+        body={"field_name": "city", "field_schema": "keyword"},
+    )
+    assert response.ok
+
+    response = request_with_validation(
+        api='/collections/{collection_name}/snapshots/recover',
+        method="PUT",
+        path_params={'collection_name': collection_name},
+        body={
+            "location": f"{srv_url}/snapshot.tar",
+            "shards": [0],
+        },
+    )
+    assert response.status_code == 400
+
+    response = request_with_validation(
+        api='/collections/{collection_name}/index/{field_name}',
+        method="DELETE",
+        path_params={'collection_name': collection_name, 'field_name': 'city'},
+        query_params={'wait': 'true'},
+    )
+    assert response.ok
+
+    # restore the only shard
+    response = request_with_validation(
+        api='/collections/{collection_name}/snapshots/recover',
+        method="PUT",
+        path_params={'collection_name': collection_name},
+        body={
+            "location": f"{srv_url}/snapshot.tar",
+            "shards": [0],
+        },
+    )
+    assert response.ok
+
+    response = request_with_validation(
+        api='/collections/{collection_name}/points/scroll',
+        method="POST",
+        path_params={'collection_name': collection_name},
+        body={},
+    )
+    assert response.ok
+    assert len(response.json()['result']['points']) == 10
+
+
 def test_full_snapshot_operations():
     # no full snapshot
     response = request_with_validation(
@@ -207,6 +359,70 @@ def test_full_snapshot_operations():
     assert len(response.json()['result']) == 0
 
 
+def test_snapshot_bundle_operations(collection_name):
+    # collections are required
+    response = request_with_validation(
+        api='/snapshots/bundle',
+        method="POST",
+        body={"collections": []},
+    )
+    assert response.status_code == 422
+
+    response = request_with_validation(
+        api='/snapshots/bundle',
+        method="POST",
+        body={"collections": [collection_name, "missing_collection"]},
+    )
+    assert response.status_code == 404
+
+    # create snapshot bundle
+    response = request_with_validation(
+        api='/snapshots/bundle',
+        method="POST",
+        body={"collections": [collection_name]},
+    )
+    assert response.ok
+    snapshot_name = response.json()['result']['name']
+    assert snapshot_name.startswith("snapshot-bundle-")
+
+    # it is listed along with storage snapshots
+    response = request_with_validation(
+        api='/snapshots',
+        method="GET",
+    )
+    assert response.ok
+    assert [snapshot['name'] for snapshot in response.json()['result']] == [snapshot_name]
+
+    # it contains the collection snapshot, the recovery config and the bundle manifest
+    response = requests.get(f"http://{QDRANT_HOST}/snapshots/{snapshot_name}")
+    assert response.ok
+    with tarfile.open(fileobj=io.BytesIO(response.content)) as archive:
+        config = json.load(archive.extractfile("config.json"))
+        manifest = json.load(archive.extractfile("bundle_manifest.json"))
+        collection_snapshot = manifest['collections'][collection_name]['snapshot']
+        assert config['collections_mapping'] == {collection_name: collection_snapshot}
+        assert collection_snapshot in archive.getnames()
+    assert 'consistent_at' in manifest
+
+    # updates are resumed once the bundle is created
+    response = request_with_validation(
+        api='/collections/{collection_name}/points',
+        method="PUT",
+        path_params={'collection_name': collection_name},
+        query_params={'wait': 'true'},
+        body={"points": [{"id": 100, "vector": [0.1, 0.2, 0.3, 0.4]}]},
+    )
+    assert response.ok
+
+    response = request_with_validation(
+        api='/snapshots/{snapshot_name}',
+        path_params={'snapshot_name': snapshot_name},
+        method="DELETE",
+        query_params={'wait': 'true'},
+    )
+    assert response.ok
+
+
 @pytest.mark.timeout(30)
 def test_snapshot_operations_non_wait(collection_name):
     # there no snapshot on collection
@@ -12,6 +12,9 @@
 //! Note: Snapshot fixtures are stored gzipped (`*.snapshot.gz`) to avoid committing or transferring
 //! large preallocated WAL/mmap files. The consumer inflates each fixture into a temp directory
 //! before calling the Qdrant snapshot recovery API.
+//!
+//! The producing architecture and endianness are recorded by Qdrant itself in the snapshot
+//! metadata, the consumer checks that recovery reports them in its compatibility summary.
 
 use flate2::Compression;
 use flate2::read::GzDecoder;
@@ -35,8 +38,6 @@ const MANIFEST_FILE: &str = "manifest.json";
 struct SnapshotFixtureManifest {
     format_version: u32,
     created_unix_utc: u64,
-    arch: String,
-    endian: String,
     fixtures: Vec<SnapshotFixtureEntry>,
 }
 
@@ -136,20 +137,30 @@ fn s390x_snapshot_fixture_produce() {
         snapshot_file: bq_snapshot_name.to_string(),
     });
 
+    // Fixture 4: ColBERT multivectors (covers multivector offsets persistence).
+    let colbert = "s390x_fixture_colbert";
+    http_delete_collection_if_exists(&client, &base_url, colbert, &log_path);
+    http_create_colbert_collection(&client, &base_url, colbert, &log_path);
+    http_upsert_colbert_points(&client, &base_url, colbert, &log_path);
+    http_query_colbert_and_assert(&client, &base_url, colbert, &log_path);
+    let colbert_snapshot =
+        http_create_collection_snapshot(&client, &base_url, colbert, &snapshots_path, &log_path);
+    let colbert_snapshot_name = "colbert.snapshot.gz";
+    gzip_fixture(&colbert_snapshot, &out_dir, colbert_snapshot_name);
+    fixtures.push(SnapshotFixtureEntry {
+        id: "colbert".to_string(),
+        collection: colbert.to_string(),
+        snapshot_file: colbert_snapshot_name.to_string(),
+    });
+
     qdrant.shutdown();
 
     let manifest = SnapshotFixtureManifest {
-        format_version: 1,
+        format_version: 2,
         created_unix_utc: SystemTime::now()
             .duration_since(UNIX_EPOCH)
             .expect("system time")
             .as_secs(),
-        arch: env::consts::ARCH.to_string(),
-        endian: if cfg!(target_endian = "big") {
-            "big".to_string()
-        } else {
-            "little".to_string()
-        },
         fixtures,
     };
 
@@ -216,6 +227,7 @@ fn s390x_snapshot_fixture_consume() {
             &snapshot_path,
             &log_path,
         );
+        assert_log_contains(&log_path, "Snapshot produced by Qdrant");
 
         match entry.id.as_str() {
             "multivec" => {
@@ -258,6 +270,16 @@ fn s390x_snapshot_fixture_consume() {
                     &log_path,
                 );
             }
+            "colbert" => {
+                http_collection_points_and_assert_at_least(
+                    &client,
+                    &base_url,
+                    &entry.collection,
+                    8,
+                    &log_path,
+                );
+                http_query_colbert_and_assert(&client, &base_url, &entry.collection, &log_path);
+            }
             other => panic!("unknown fixture id: {other}"),
         }
 
@@ -762,6 +784,163 @@ fn http_search_binary_quant_and_assert(
     );
 }
 
+fn colbert_fixture_multivector(id: u64) -> Vec<Vec<f32>> {
+    // Point `id` has `id` inner vectors, growing with the id. With a query of ones the highest id
+    // ranks first under max-sim, and a wrong offset or count changes the vectors we get back.
+    (0..id)
+        .map(|i| vec![id as f32 * 0.1 + i as f32 * 0.01; 4])
+        .collect()
+}
+
+fn http_create_colbert_collection(
+    client: &Client,
+    base_url: &str,
+    collection: &str,
+    log_path: &Path,
+) {
+    // Few on-disk multivectors stay in an appendable segment -> chunked mmap multivector storage
+    // with a separate offsets file
+    let body = json!({
+        "vectors": {
+            "size": 4,
+            "distance": "Dot",
+            "multivector_config": { "comparator": "max_sim" },
+            "on_disk": true
+        },
+        "optimizers_config": { "default_segment_number": 1 },
+        "replication_factor": 1
+    });
+
+    let resp = client
+        .put(format!("{base_url}/collections/{collection}"))
+        .json(&body)
+        .send()
+        .unwrap_or_else(|e| {
+            panic!(
+                "create colbert collection request failed: {e}\n{}",
+                tail_log(log_path)
+            )
+        });
+
+    if !resp.status().is_success() {
+        let status = resp.status();
+        let body = resp.text().unwrap_or_default();
+        panic!(
+            "create colbert collection failed: {status} {body}\n{}",
+            tail_log(log_path)
+        );
+    }
+}
+
+fn http_upsert_colbert_points(client: &Client, base_url: &str, collection: &str, log_path: &Path) {
+    // Keep this deterministic (no rng) so fixtures are reproducible.
+    let points: Vec<_> = (1..=8)
+        .map(|id| {
+            json!({
+                "id": id,
+                "vector": colbert_fixture_multivector(id),
+                "payload": { "id": id }
+            })
+        })
+        .collect();
+    let body = json!({ "points": points });
+
+    let resp = client
+        .put(format!(
+            "{base_url}/collections/{collection}/points?wait=true"
+        ))
+        .json(&body)
+        .send()
+        .unwrap_or_else(|e| {
+            panic!(
+                "upsert colbert points request failed: {e}\n{}",
+                tail_log(log_path)
+            )
+        });
+
+    if !resp.status().is_success() {
+        let status = resp.status();
+        let body = resp.text().unwrap_or_default();
+        panic!(
+            "upsert colbert points failed: {status} {body}\n{}",
+            tail_log(log_path)
+        );
+    }
+}
+
+fn http_query_colbert_and_assert(
+    client: &Client,
+    base_url: &str,
+    collection: &str,
+    log_path: &Path,
+) {
+    let body = json!({
+        "query": [[1.0, 1.0, 1.0, 1.0], [1.0, 1.0, 1.0, 1.0]],
+        "with_vector": true,
+        "limit": 8
+    });
+
+    let resp = client
+        .post(format!("{base_url}/collections/{collection}/points/query"))
+        .json(&body)
+        .send()
+        .unwrap_or_else(|e| panic!("colbert query request failed: {e}\n{}", tail_log(log_path)));
+
+    if !resp.status().is_success() {
+        let status = resp.status();
+        let body = resp.text().unwrap_or_default();
+        panic!(
+            "colbert query failed: {status} {body}\n{}",
+            tail_log(log_path)
+        );
+    }
+
+    let v: serde_json::Value = resp.json().unwrap_or_else(|e| {
+        panic!(
+            "parse colbert query response failed: {e}\n{}",
+            tail_log(log_path)
+        )
+    });
+    let hits = v
+        .get("result")
+        .and_then(|r| r.get("points"))
+        .and_then(|r| r.as_array())
+        .unwrap_or_else(|| {
+            panic!(
+                "query response missing result points: {v}\n{}",
+                tail_log(log_path)
+            )
+        });
+
+    // Deterministic dataset: hits are ordered by descending id.
+    let ids: Vec<_> = hits.iter().filter_map(hit_id_u64).collect();
+    assert_eq!(
+        ids,
+        (1..=8).rev().collect::<Vec<u64>>(),
+        "unexpected hit order; response={v}\n{}",
+        tail_log(log_path)
+    );
+
+    for hit in hits {
+        let id = hit_id_u64(hit).expect("hit id checked above");
+        let vector: Vec<Vec<f32>> = hit
+            .get("vector")
+            .and_then(|vector| serde_json::from_value(vector.clone()).ok())
+            .unwrap_or_else(|| {
+                panic!(
+                    "query hit {id} missing multivector: {hit}\n{}",
+                    tail_log(log_path)
+                )
+            });
+        assert_eq!(
+            vector,
+            colbert_fixture_multivector(id),
+            "unexpected multivector of point {id}\n{}",
+            tail_log(log_path)
+        );
+    }
+}
+
 fn http_create_sparse_collection(
     client: &Client,
     base_url: &str,
@@ -1116,6 +1295,15 @@ impl Drop for QdrantProc {
     }
 }
 
+fn assert_log_contains(path: &Path, needle: &str) {
+    let log = fs::read_to_string(path).unwrap_or_default();
+    assert!(
+        log.contains(needle),
+        "expected qdrant log to contain {needle:?}\n{}",
+        tail_log(path)
+    );
+}
+
 fn tail_log(path: &Path) -> String {
     // Best-effort tail; avoid panicking while building an error message.
     const MAX_BYTES: u64 = 16 * 1024;